@@ -16,6 +16,9 @@ use std::fs;
 use std::path::Path;
 use std::process::Command;
 
+#[path = "build/openapi.rs"]
+mod openapi;
+
 fn main() {
     // Sources we scrape. Each file is `rerun-if-changed` tracked so
     // the build system knows when to regenerate. Anything else under
@@ -23,6 +26,7 @@ fn main() {
     // incremental builds fast.
     let sources: &[&str] = &[
         "src/api/mod.rs",
+        "src/api/v1.rs",
         "src/networking/router/api.rs",
         "src/vms/api.rs",
         "src/tui.rs",
@@ -162,6 +166,18 @@ fn main() {
     let dest = Path::new(&out_dir).join("wolfstack-kb-generated.md");
     fs::write(&dest, out).expect("failed to write generated KB");
     println!("cargo:rustc-env=WOLFSTACK_KB_GENERATED={}", dest.display());
+
+    // ── OpenAPI document + TypeScript client ────────────────────
+    // Served at /api/openapi.json and /api/openapi/client.ts. The TUI
+    // routes are HTML, so only the JSON API sources are described.
+    println!("cargo:rerun-if-changed=build/openapi.rs");
+    let version = std::env::var("CARGO_PKG_VERSION").unwrap_or_default();
+    let (spec, client) = openapi::generate(
+        &["src/api/mod.rs", "src/api/v1.rs", "src/vms/api.rs", "src/networking/router/api.rs"],
+        &version,
+    );
+    fs::write(Path::new(&out_dir).join("openapi.json"), spec).expect("failed to write OpenAPI document");
+    fs::write(Path::new(&out_dir).join("wolfstack-client.ts"), client).expect("failed to write TypeScript client");
 }
 
 fn git_out(args: &[&str]) -> Option<String> {
@@ -0,0 +1,1280 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Build-time OpenAPI 3 generator, included by `build.rs`.
+//!
+//! Same approach as the KB scraper: string scanning, zero dependencies.
+//! For every `.route(…)` in the API sources it finds the handler, reads
+//! its doc comment and extractor arguments (`web::Json<T>`,
+//! `web::Query<T>`, `web::Path<…>`, `web::Payload`, `Multipart`) and
+//! turns them into an operation. Request types are resolved to their
+//! `struct`/`enum` definitions — in the handler's file, via its
+//! `use crate::…` imports, or a `crate::…` path — and emitted as
+//! component schemas, following field types recursively.
+//!
+//! Handlers return `HttpResponse`, so response bodies are documented as
+//! untyped JSON plus the shared `{"error": …}` shape. A TypeScript client
+//! is generated from the same operation list.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::rc::Rc;
+
+/// Minimal JSON value — build scripts get no serde.
+pub enum J {
+    Bool(bool),
+    Num(i64),
+    Str(String),
+    Arr(Vec<J>),
+    Obj(Vec<(String, J)>),
+}
+
+fn s(v: &str) -> J { J::Str(v.to_string()) }
+fn obj(pairs: Vec<(&str, J)>) -> J { J::Obj(pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()) }
+fn any() -> J { J::Obj(Vec::new()) }
+fn reference(name: &str) -> J { obj(vec![("$ref", J::Str(format!("#/components/schemas/{}", name)))]) }
+
+impl J {
+    fn get(&self, key: &str) -> Option<&J> {
+        match self {
+            J::Obj(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    fn as_str(&self) -> Option<&str> {
+        match self { J::Str(v) => Some(v), _ => None }
+    }
+
+    pub fn write(&self, out: &mut String, indent: usize) {
+        let pad = |n: usize| "  ".repeat(n);
+        match self {
+            J::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            J::Num(n) => out.push_str(&n.to_string()),
+            J::Str(v) => write_str(out, v),
+            J::Arr(items) if items.is_empty() => out.push_str("[]"),
+            J::Arr(items) => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(&pad(indent + 1));
+                    item.write(out, indent + 1);
+                    if i + 1 < items.len() { out.push(','); }
+                    out.push('\n');
+                }
+                out.push_str(&pad(indent));
+                out.push(']');
+            }
+            J::Obj(pairs) if pairs.is_empty() => out.push_str("{}"),
+            J::Obj(pairs) => {
+                out.push_str("{\n");
+                for (i, (k, v)) in pairs.iter().enumerate() {
+                    out.push_str(&pad(indent + 1));
+                    write_str(out, k);
+                    out.push_str(": ");
+                    v.write(out, indent + 1);
+                    if i + 1 < pairs.len() { out.push(','); }
+                    out.push('\n');
+                }
+                out.push_str(&pad(indent));
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_str(out: &mut String, v: &str) {
+    out.push('"');
+    for c in v.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// ─── Source scanning ───
+
+/// Source files read so far, keyed by path relative to the crate root.
+/// Texts are shared (`Rc`) because the API module alone is megabytes.
+#[derive(Default)]
+pub struct Sources {
+    files: HashMap<String, Option<Rc<str>>>,
+    imports: HashMap<String, Rc<HashMap<String, String>>>,
+    items: HashMap<String, Rc<ItemIndex>>,
+}
+
+impl Sources {
+    fn get(&mut self, path: &str) -> Option<Rc<str>> {
+        self.files.entry(path.to_string()).or_insert_with(|| {
+            let text = fs::read_to_string(path).ok();
+            if text.is_some() {
+                println!("cargo:rerun-if-changed={}", path);
+            }
+            text.map(Rc::from)
+        }).clone()
+    }
+
+    /// Byte offset of `keyword name` (`fn foo`, `struct Foo`) in a file.
+    fn item(&mut self, file: &str, keyword: &'static str, name: &str) -> Option<usize> {
+        if !self.items.contains_key(file) {
+            let index = Rc::new(self.get(file).map(|t| index_items(&t)).unwrap_or_default());
+            self.items.insert(file.to_string(), index);
+        }
+        self.items[file].get(&(keyword, name.to_string())).copied()
+    }
+
+    fn defines(&mut self, file: &str, name: &str) -> bool {
+        self.item(file, "struct", name).is_some() || self.item(file, "enum", name).is_some()
+    }
+
+    fn imports(&mut self, file: &str) -> Rc<HashMap<String, String>> {
+        if let Some(map) = self.imports.get(file) {
+            return map.clone();
+        }
+        let map = Rc::new(self.get(file).map(|t| imports(&t, file)).unwrap_or_default());
+        self.imports.insert(file.to_string(), map.clone());
+        map
+    }
+}
+
+/// Is byte offset `at` inside a `//` comment on its line?
+fn commented(text: &str, at: usize) -> bool {
+    let line_start = text[..at].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    text[line_start..at].contains("//")
+}
+
+/// Text between the bracket at `open` and its partner, exclusive.
+/// Skips string literals and `//` comments.
+fn balanced(text: &str, open: usize) -> Option<&str> {
+    let bytes = text.as_bytes();
+    let (o, c) = match bytes[open] {
+        b'(' => (b'(', b')'),
+        b'{' => (b'{', b'}'),
+        b'<' => (b'<', b'>'),
+        b'[' => (b'[', b']'),
+        _ => return None,
+    };
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' { i += 1; }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' { i += 1; }
+            }
+            // `->` inside a signature isn't a closing angle bracket.
+            b'>' if o == b'<' && i > 0 && bytes[i - 1] == b'-' => {}
+            b if b == o => depth += 1,
+            b if b == c => {
+                depth -= 1;
+                if depth == 0 { return Some(&text[open + 1..i]); }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Split on commas that aren't nested inside brackets.
+fn split_top(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut cur = String::new();
+    let mut prev = ' ';
+    for ch in text.chars() {
+        match ch {
+            '<' | '(' | '[' | '{' => depth += 1,
+            '>' if prev != '-' => depth -= 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(cur.trim().to_string());
+                cur.clear();
+                prev = ch;
+                continue;
+            }
+            _ => {}
+        }
+        cur.push(ch);
+        prev = ch;
+    }
+    if !cur.trim().is_empty() { parts.push(cur.trim().to_string()); }
+    parts
+}
+
+pub struct Route {
+    pub method: String,
+    pub path: String,
+    pub handler: String,
+    pub file: String,
+}
+
+/// Every `.route("…", web::METHOD()….to(handler))` in `file`, including
+/// ones split across lines. Relative paths get the enclosing
+/// `web::scope("…")` prefix.
+pub fn routes_in(file: &str, text: &str) -> Vec<Route> {
+    let mut out = Vec::new();
+    let mut scope = String::new();
+    let mut pos = 0;
+    loop {
+        let next_route = text[pos..].find(".route(").map(|i| i + pos);
+        let next_scope = text[pos..].find("web::scope(\"").map(|i| i + pos);
+        let at = match (next_route, next_scope) {
+            (Some(r), Some(sc)) if sc < r => {
+                let start = sc + "web::scope(\"".len();
+                if let Some(end) = text[start..].find('"') {
+                    scope = text[start..start + end].to_string();
+                }
+                pos = start;
+                continue;
+            }
+            (Some(r), _) => r,
+            _ => break,
+        };
+        pos = at + 1;
+        if commented(text, at) { continue; }
+        let Some(to_at) = text[at..].find(".to(").map(|i| i + at) else { break; };
+        if to_at - at > 600 { continue; }
+        let Some(to_body) = balanced(text, to_at + 3) else { continue; };
+        let call = &text[at..to_at];
+        let Some(q1) = call.find('"') else { continue; };
+        let Some(q2) = call[q1 + 1..].find('"') else { continue; };
+        let mut path = call[q1 + 1..q1 + 1 + q2].to_string();
+        let Some(web_at) = call.find("web::") else { continue; };
+        let method: String = call[web_at + 5..].chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        if !matches!(method.as_str(), "get" | "post" | "put" | "delete" | "patch") { continue; }
+        if !path.starts_with("/api") {
+            if scope.is_empty() { continue; }
+            path = format!("{}{}", scope, path);
+        }
+        let handler = to_body.trim().rsplit("::").next().unwrap_or("").trim().to_string();
+        if handler.is_empty() || !handler.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') { continue; }
+        out.push(Route { method, path, handler, file: file.to_string() });
+    }
+    out
+}
+
+/// Doc-comment lines directly above byte offset `pos`, skipping
+/// attributes and the `pub async` prefix of the item itself.
+fn doc_above(text: &str, pos: usize) -> Vec<String> {
+    let line_start = text[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let mut lines = text[..line_start].lines().rev().map(str::trim).peekable();
+    while lines.next_if(|l| l.starts_with("#[") || l.ends_with(")]") && !l.starts_with("///")).is_some() {}
+    let mut doc: Vec<String> = lines
+        .map_while(|l| l.strip_prefix("///").map(|rest| rest.strip_prefix(' ').unwrap_or(rest).to_string()))
+        .collect();
+    doc.reverse();
+    doc
+}
+
+/// Offsets of every `fn`/`struct`/`enum` item in a file, keyed by
+/// (keyword, name). First uncommented definition wins.
+type ItemIndex = HashMap<(&'static str, String), usize>;
+
+fn index_items(text: &str) -> ItemIndex {
+    let mut map = HashMap::new();
+    let bytes = text.as_bytes();
+    for keyword in ["fn", "struct", "enum"] {
+        let needle = format!("{} ", keyword);
+        let mut from = 0;
+        while let Some(i) = text[from..].find(&needle).map(|i| i + from) {
+            from = i + needle.len();
+            if i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_') { continue; }
+            let name: String = text[from..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+            if name.is_empty() || map.contains_key(&(keyword, name.clone())) || commented(text, i) { continue; }
+            map.insert((keyword, name), i);
+        }
+    }
+    map
+}
+
+// ─── Types ───
+
+#[derive(Debug)]
+enum Ty {
+    Path(String, Vec<Ty>),
+    Tuple(Vec<Ty>),
+    Array(Box<Ty>),
+}
+
+fn parse_ty(text: &str) -> Ty {
+    let mut t = text.trim();
+    loop {
+        let before = t;
+        t = t.trim_start_matches('&').trim_start();
+        if t.starts_with('\'') {
+            t = t.split_once(' ').map(|(_, rest)| rest).unwrap_or("").trim_start();
+        }
+        t = t.strip_prefix("mut ").unwrap_or(t).trim_start();
+        t = t.strip_prefix("dyn ").unwrap_or(t).trim_start();
+        if t == before { break; }
+    }
+    if t.starts_with('(') {
+        let inner = balanced(t, 0).unwrap_or("");
+        return Ty::Tuple(split_top(inner).iter().map(|p| parse_ty(p)).collect());
+    }
+    if t.starts_with('[') {
+        let inner = balanced(t, 0).unwrap_or("");
+        let elem = split_top(inner).first().cloned().unwrap_or_default();
+        let elem = elem.split(';').next().unwrap_or("").to_string();
+        return Ty::Array(Box::new(parse_ty(&elem)));
+    }
+    let name: String = t.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == ':').collect();
+    let rest = &t[name.len()..];
+    let args = if rest.trim_start().starts_with('<') {
+        let off = t.len() - rest.trim_start().len();
+        balanced(t, off).map(|inner| {
+            split_top(inner).iter()
+                .filter(|a| !a.starts_with('\''))
+                .map(|a| parse_ty(a))
+                .collect()
+        }).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    Ty::Path(name, args)
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// `crate::a::b` → the file defining module `a::b`.
+fn module_file(sources: &mut Sources, module: &str) -> Option<String> {
+    let rel = module.strip_prefix("crate::")?.replace("::", "/");
+    if rel.is_empty() { return Some("src/main.rs".to_string()); }
+    [format!("src/{}.rs", rel), format!("src/{}/mod.rs", rel)]
+        .into_iter()
+        .find(|p| sources.get(p).is_some())
+}
+
+/// Module path of the file itself (`src/api/mod.rs` → `crate::api`).
+fn file_module(file: &str) -> String {
+    let rel = file.trim_start_matches("src/").trim_end_matches(".rs").trim_end_matches("/mod");
+    if rel == "main" { "crate".to_string() } else { format!("crate::{}", rel.replace('/', "::")) }
+}
+
+/// `use crate::…` imports of a file: local name → full path.
+fn imports(text: &str, file: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let parent = {
+        let m = file_module(file);
+        m.rsplit_once("::").map(|(p, _)| p.to_string()).unwrap_or(m)
+    };
+    for line in text.lines() {
+        let t = line.trim();
+        let Some(rest) = t.strip_prefix("use ").or_else(|| t.strip_prefix("pub use ")) else { continue; };
+        let rest = rest.trim_end_matches(';').trim();
+        let rest = if let Some(r) = rest.strip_prefix("super::") {
+            format!("{}::{}", parent, r)
+        } else if rest.starts_with("crate::") {
+            rest.to_string()
+        } else {
+            continue;
+        };
+        if let Some((base, list)) = rest.split_once("::{") {
+            for item in list.trim_end_matches('}').split(',') {
+                let item = item.trim();
+                if item.is_empty() || item.contains('{') { continue; }
+                let (orig, alias) = item.split_once(" as ").map(|(a, b)| (a.trim(), b.trim())).unwrap_or((item, item));
+                let alias = last_segment(alias);
+                map.insert(alias.to_string(), format!("{}::{}", base, orig));
+            }
+        } else {
+            let (orig, alias) = rest.split_once(" as ").map(|(a, b)| (a.trim().to_string(), b.trim().to_string()))
+                .unwrap_or_else(|| (rest.clone(), last_segment(&rest).to_string()));
+            map.insert(alias, orig);
+        }
+    }
+    map
+}
+
+/// Where a named type is defined: (file, type name).
+fn resolve_type(sources: &mut Sources, ctx_file: &str, name: &str) -> Option<(String, String)> {
+    let ty = last_segment(name).to_string();
+    let full = if name.contains("::") {
+        let first = name.split("::").next().unwrap_or("");
+        if first == "crate" {
+            name.to_string()
+        } else if first == "super" || first == "self" {
+            let module = file_module(ctx_file);
+            let base = if first == "super" {
+                module.rsplit_once("::").map(|(p, _)| p.to_string()).unwrap_or(module)
+            } else {
+                module
+            };
+            format!("{}::{}", base, name.split_once("::").map(|(_, r)| r).unwrap_or(""))
+        } else {
+            match sources.imports(ctx_file).get(first) {
+                Some(p) => format!("{}{}", p, &name[first.len()..]),
+                None => format!("crate::{}", name),
+            }
+        }
+    } else {
+        if sources.defines(ctx_file, &ty) {
+            return Some((ctx_file.to_string(), ty));
+        }
+        sources.imports(ctx_file).get(&ty)?.clone()
+    };
+    let (module, _) = full.rsplit_once("::")?;
+    let file = module_file(sources, module)?;
+    if sources.defines(&file, &ty) {
+        Some((file, ty))
+    } else {
+        None
+    }
+}
+
+fn to_words(ident: &str) -> Vec<String> {
+    // snake_case fields and PascalCase variants both split into words.
+    let mut words = Vec::new();
+    let mut cur = String::new();
+    for ch in ident.chars() {
+        if ch == '_' {
+            if !cur.is_empty() { words.push(std::mem::take(&mut cur)); }
+        } else if ch.is_ascii_uppercase() && !cur.is_empty() {
+            words.push(std::mem::take(&mut cur));
+            cur.push(ch.to_ascii_lowercase());
+        } else {
+            cur.push(ch.to_ascii_lowercase());
+        }
+    }
+    if !cur.is_empty() { words.push(cur); }
+    words
+}
+
+fn capitalize(w: &str) -> String {
+    let mut c = w.chars();
+    c.next().map(|f| f.to_ascii_uppercase().to_string() + c.as_str()).unwrap_or_default()
+}
+
+/// Apply a serde `rename_all` rule. `is_variant` matters for
+/// `lowercase`/`UPPERCASE`, which don't insert separators.
+fn rename(ident: &str, rule: Option<&str>, is_variant: bool) -> String {
+    let ident = ident.strip_prefix("r#").unwrap_or(ident);
+    let words = to_words(ident);
+    match rule {
+        Some("snake_case") => words.join("_"),
+        Some("SCREAMING_SNAKE_CASE") => words.join("_").to_uppercase(),
+        Some("kebab-case") => words.join("-"),
+        Some("SCREAMING-KEBAB-CASE") => words.join("-").to_uppercase(),
+        Some("camelCase") => words.iter().enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) }).collect(),
+        Some("PascalCase") => words.iter().map(|w| capitalize(w)).collect(),
+        Some("lowercase") => if is_variant { ident.to_lowercase() } else { ident.to_string() },
+        Some("UPPERCASE") => ident.to_uppercase(),
+        _ => ident.to_string(),
+    }
+}
+
+/// Value of `key = "…"` inside a `#[serde(…)]` attribute.
+fn serde_value(attr: &str, key: &str) -> Option<String> {
+    let at = attr.find(&format!("{} =", key)).or_else(|| attr.find(&format!("{}=", key)))?;
+    let rest = &attr[at..];
+    let q1 = rest.find('"')?;
+    let q2 = rest[q1 + 1..].find('"')?;
+    Some(rest[q1 + 1..q1 + 1 + q2].to_string())
+}
+
+fn serde_flag(attr: &str, flag: &str) -> bool {
+    attr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).any(|w| w == flag)
+}
+
+/// One `#[…]`/`///`-annotated member of a struct or enum body.
+struct Member {
+    attrs: Vec<String>,
+    doc: Vec<String>,
+    text: String,
+}
+
+fn members(body: &str) -> Vec<Member> {
+    let mut out = Vec::new();
+    let mut attrs = Vec::new();
+    let mut doc = Vec::new();
+    let mut pending_attr = String::new();
+    let mut cur = String::new();
+    let mut depth = 0i32;
+    for line in body.lines() {
+        let t = line.trim();
+        if !pending_attr.is_empty() {
+            pending_attr.push(' ');
+            pending_attr.push_str(t);
+            if t.ends_with(']') {
+                attrs.push(std::mem::take(&mut pending_attr));
+            }
+            continue;
+        }
+        if cur.trim().is_empty() {
+            if let Some(d) = t.strip_prefix("///") {
+                doc.push(d.trim().to_string());
+                continue;
+            }
+            if t.starts_with("//") || t.is_empty() { continue; }
+            if t.starts_with("#[") {
+                if t.ends_with(']') { attrs.push(t.to_string()); } else { pending_attr = t.to_string(); }
+                continue;
+            }
+        }
+        let code = match t.find("//") {
+            Some(i) if !t[..i].contains('"') => t[..i].trim_end(),
+            _ => t,
+        };
+        let mut prev = ' ';
+        for ch in code.chars() {
+            match ch {
+                '<' | '(' | '[' | '{' => depth += 1,
+                '>' if prev != '-' => depth -= 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+            if ch == ',' && depth == 0 {
+                out.push(Member { attrs: std::mem::take(&mut attrs), doc: std::mem::take(&mut doc), text: std::mem::take(&mut cur) });
+            } else {
+                cur.push(ch);
+            }
+            prev = ch;
+        }
+        if !cur.trim().is_empty() { cur.push(' '); }
+    }
+    if !cur.trim().is_empty() {
+        out.push(Member { attrs, doc, text: cur });
+    }
+    out
+}
+
+fn serde_attrs(attrs: &[String]) -> String {
+    attrs.iter().filter(|a| a.starts_with("#[serde(")).cloned().collect::<Vec<_>>().join(" ")
+}
+
+fn description(doc: &[String]) -> Option<String> {
+    let para: Vec<&str> = doc.iter().map(|l| l.trim()).take_while(|l| !l.is_empty()).collect();
+    if para.is_empty() { None } else { Some(para.join(" ")) }
+}
+
+/// A struct field as a query parameter: (wire name, schema, required, doc).
+type Field = (String, J, bool, Option<String>);
+
+/// Builds component schemas on demand while operations are generated.
+#[derive(Default)]
+pub struct Schemas {
+    /// (file, type) → component name.
+    names: HashMap<(String, String), String>,
+    taken: BTreeSet<String>,
+    /// Component name → schema, filled in as types are first referenced.
+    pub defs: BTreeMap<String, J>,
+    /// Struct fields per component, for expanding `web::Query<T>`.
+    fields: HashMap<String, Vec<Field>>,
+}
+
+/// Component names the document or the TS client already use; a Rust
+/// type with one of these names gets its module prefix instead.
+const RESERVED_NAMES: &[&str] = &[
+    "Error", "ErrorBody", "Query", "ClientOptions", "WolfStackClient", "WolfStackError", "Record", "Promise",
+    "Blob", "FormData", "URLSearchParams", "ReadableStream", "ArrayBuffer", "BodyInit", "Array", "Object",
+];
+
+const STRING_TYPES: &[&str] = &[
+    "String", "str", "PathBuf", "Path", "char", "IpAddr", "Ipv4Addr", "Ipv6Addr", "SocketAddr", "Uuid", "Url",
+    "OsString", "Cow",
+];
+
+impl Schemas {
+    /// Schema for a Rust type as used in `ctx_file`, plus whether it's
+    /// an `Option` (so the field isn't required).
+    fn schema(&mut self, sources: &mut Sources, ctx_file: &str, ty: &Ty) -> (J, bool) {
+        match ty {
+            Ty::Tuple(items) if items.is_empty() => (obj(vec![("nullable", J::Bool(true))]), true),
+            Ty::Tuple(items) => {
+                let items = items.iter().map(|t| self.schema(sources, ctx_file, t).0).collect::<Vec<_>>();
+                let n = items.len() as i64;
+                (obj(vec![
+                    ("type", s("array")),
+                    ("prefixItems", J::Arr(items)),
+                    ("minItems", J::Num(n)),
+                    ("maxItems", J::Num(n)),
+                ]), false)
+            }
+            Ty::Array(elem) => {
+                let items = self.schema(sources, ctx_file, elem).0;
+                (obj(vec![("type", s("array")), ("items", items)]), false)
+            }
+            Ty::Path(name, args) => {
+                let last = last_segment(name);
+                let arg = |i: usize| args.get(i);
+                match last {
+                    "Option" => {
+                        let inner = arg(0).map(|a| self.schema(sources, ctx_file, a).0).unwrap_or_else(any);
+                        (with_nullable(inner), true)
+                    }
+                    "Box" | "Arc" | "Rc" | "RefCell" | "Mutex" | "RwLock" => {
+                        arg(0).map(|a| self.schema(sources, ctx_file, a)).unwrap_or((any(), false))
+                    }
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" | "IndexSet" => {
+                        let items = arg(0).map(|a| self.schema(sources, ctx_file, a).0).unwrap_or_else(any);
+                        (obj(vec![("type", s("array")), ("items", items)]), false)
+                    }
+                    "HashMap" | "BTreeMap" | "IndexMap" => {
+                        let values = arg(1).map(|a| self.schema(sources, ctx_file, a).0).unwrap_or_else(any);
+                        (obj(vec![("type", s("object")), ("additionalProperties", values)]), false)
+                    }
+                    "Map" => (obj(vec![("type", s("object"))]), false),
+                    "Value" => (any(), false),
+                    "bool" => (obj(vec![("type", s("boolean"))]), false),
+                    "f32" | "f64" => (obj(vec![("type", s("number"))]), false),
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => {
+                        let fmt = if matches!(last, "u64" | "u128" | "usize") { "int64" } else { "int32" };
+                        (obj(vec![("type", s("integer")), ("format", s(fmt)), ("minimum", J::Num(0))]), false)
+                    }
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => {
+                        let fmt = if matches!(last, "i64" | "i128" | "isize") { "int64" } else { "int32" };
+                        (obj(vec![("type", s("integer")), ("format", s(fmt))]), false)
+                    }
+                    "DateTime" | "NaiveDateTime" | "SystemTime" => {
+                        (obj(vec![("type", s("string")), ("format", s("date-time"))]), false)
+                    }
+                    "NaiveDate" => (obj(vec![("type", s("string")), ("format", s("date"))]), false),
+                    l if STRING_TYPES.contains(&l) => (obj(vec![("type", s("string"))]), false),
+                    _ if !args.is_empty() => (any(), false),
+                    _ => match self.component(sources, ctx_file, name) {
+                        Some(c) => (reference(&c), false),
+                        None => (any(), false),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Component name for a user-defined type, generating its schema the
+    /// first time it's seen. None when the definition can't be found.
+    fn component(&mut self, sources: &mut Sources, ctx_file: &str, name: &str) -> Option<String> {
+        let key = resolve_type(sources, ctx_file, name)?;
+        if let Some(existing) = self.names.get(&key) {
+            return Some(existing.clone());
+        }
+        let mut cname = key.1.clone();
+        if self.taken.contains(&cname) || RESERVED_NAMES.contains(&cname.as_str()) {
+            let module = file_module(&key.0).trim_start_matches("crate::").replace("::", "_");
+            cname = format!("{}_{}", module, key.1);
+        }
+        self.names.insert(key.clone(), cname.clone());
+        self.taken.insert(cname.clone());
+        // Placeholder so recursive types terminate.
+        self.defs.insert(cname.clone(), any());
+        let schema = self.definition(sources, &key.0, &key.1, &cname).unwrap_or_else(any);
+        self.defs.insert(cname.clone(), schema);
+        Some(cname)
+    }
+
+    fn definition(&mut self, sources: &mut Sources, file: &str, ty: &str, cname: &str) -> Option<J> {
+        let text = sources.get(file)?;
+        if let Some(at) = sources.item(file, "struct", ty) {
+            let after = &text[at + "struct ".len() + ty.len()..];
+            let brace = after.find(['{', ';', '('])?;
+            if after.as_bytes()[brace] != b'{' || after[..brace].contains('<') {
+                return None; // tuple, unit or generic struct
+            }
+            let body = balanced(&text, at + "struct ".len() + ty.len() + brace)?.to_string();
+            let item_doc = doc_above(&text, at);
+            let container = {
+                let line_start = text[..at].rfind('\n').map(|i| i + 1).unwrap_or(0);
+                attrs_above(&text, line_start)
+            };
+            let rename_all = serde_value(&container, "rename_all");
+            let container_default = serde_flag(&container, "default");
+            let mut props = Vec::new();
+            let mut required = Vec::new();
+            let mut fields = Vec::new();
+            let mut flatten = false;
+            for m in members(&body) {
+                let sattr = serde_attrs(&m.attrs);
+                if serde_flag(&sattr, "skip") || serde_flag(&sattr, "skip_deserializing") { continue; }
+                if serde_flag(&sattr, "flatten") { flatten = true; continue; }
+                let field = m.text.trim();
+                let field = field.strip_prefix("pub(crate)").or_else(|| field.strip_prefix("pub(super)"))
+                    .or_else(|| field.strip_prefix("pub")).unwrap_or(field).trim();
+                let Some((fname, fty)) = field.split_once(':') else { continue; };
+                let fname = fname.trim();
+                if fname.is_empty() || fname.contains(' ') { continue; }
+                let wire = serde_value(&sattr, "rename").unwrap_or_else(|| rename(fname, rename_all.as_deref(), false));
+                let (mut schema, optional) = self.schema(sources, file, &parse_ty(fty));
+                let bare = clone_j(&schema);
+                let desc = description(&m.doc);
+                if let (Some(d), J::Obj(pairs)) = (&desc, &mut schema)
+                    && pairs.first().map(|(k, _)| k.as_str()) != Some("$ref")
+                {
+                    pairs.push(("description".to_string(), J::Str(d.clone())));
+                }
+                let req = !optional && !container_default && !serde_flag(&sattr, "default");
+                if req { required.push(s(&wire)); }
+                fields.push((wire.clone(), bare, req, desc));
+                props.push((wire, schema));
+            }
+            self.fields.insert(cname.to_string(), fields);
+            let mut pairs = vec![("type", s("object"))];
+            if let Some(d) = description(&item_doc) { pairs.push(("description", J::Str(d))); }
+            pairs.push(("properties", J::Obj(props)));
+            if !required.is_empty() { pairs.push(("required", J::Arr(required))); }
+            if flatten { pairs.push(("additionalProperties", J::Bool(true))); }
+            return Some(obj(pairs));
+        }
+        let at = sources.item(file, "enum", ty)?;
+        let after = &text[at + "enum ".len() + ty.len()..];
+        let brace = after.find('{')?;
+        if after[..brace].contains('<') { return None; }
+        let body = balanced(&text, at + "enum ".len() + ty.len() + brace)?.to_string();
+        let line_start = text[..at].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let container = attrs_above(&text, line_start);
+        if serde_flag(&container, "untagged") || serde_value(&container, "tag").is_some() { return None; }
+        let rename_all = serde_value(&container, "rename_all");
+        let mut values = Vec::new();
+        for m in members(&body) {
+            let v = m.text.trim();
+            if v.contains('(') || v.contains('{') { return None; } // data-carrying variant
+            let sattr = serde_attrs(&m.attrs);
+            if serde_flag(&sattr, "other") || serde_flag(&sattr, "skip") { continue; }
+            let v = v.split('=').next().unwrap_or(v).trim();
+            if v.is_empty() { continue; }
+            values.push(s(&serde_value(&sattr, "rename").unwrap_or_else(|| rename(v, rename_all.as_deref(), true))));
+        }
+        let mut pairs = vec![("type", s("string"))];
+        if let Some(d) = description(&doc_above(&text, at)) { pairs.push(("description", J::Str(d))); }
+        pairs.push(("enum", J::Arr(values)));
+        Some(obj(pairs))
+    }
+}
+
+/// Attribute text directly above a line (for container-level serde options).
+fn attrs_above(text: &str, line_start: usize) -> String {
+    let mut out = Vec::new();
+    for l in text[..line_start].lines().rev() {
+        let t = l.trim();
+        if t.starts_with("#[") || t.starts_with("///") || t.ends_with(")]") {
+            if !t.starts_with("///") { out.push(t.to_string()); }
+        } else {
+            break;
+        }
+    }
+    out.join(" ")
+}
+
+fn with_nullable(schema: J) -> J {
+    match schema {
+        J::Obj(mut pairs) if pairs.first().map(|(k, _)| k.as_str()) == Some("$ref") => {
+            // A `$ref` can't carry siblings in 3.0 — wrap it.
+            let r = pairs.remove(0).1;
+            obj(vec![("allOf", J::Arr(vec![obj(vec![("$ref", r)])])), ("nullable", J::Bool(true))])
+        }
+        J::Obj(mut pairs) if !pairs.is_empty() => {
+            pairs.push(("nullable".to_string(), J::Bool(true)));
+            J::Obj(pairs)
+        }
+        other => other,
+    }
+}
+
+// ─── Operations ───
+
+pub enum Body {
+    Json(J),
+    Form(J),
+    Multipart,
+    Binary,
+    Text,
+}
+
+pub struct Operation {
+    pub method: String,
+    pub path: String,
+    pub operation_id: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub tag: String,
+    /// (name, in, required, schema, description)
+    pub params: Vec<(String, &'static str, bool, J, Option<String>)>,
+    pub body: Option<Body>,
+}
+
+/// Summary and description from a handler's doc comment. A leading
+/// `GET /api/… — ` route echo is dropped; the route is already the key.
+fn summarize(doc: &[String]) -> (Option<String>, Option<String>) {
+    let mut paras: Vec<String> = Vec::new();
+    let mut cur = String::new();
+    for l in doc {
+        if l.trim().is_empty() {
+            if !cur.is_empty() { paras.push(std::mem::take(&mut cur)); }
+        } else {
+            if !cur.is_empty() { cur.push(' '); }
+            cur.push_str(l.trim());
+        }
+    }
+    if !cur.is_empty() { paras.push(cur); }
+    let Some(first) = paras.first().cloned() else { return (None, None); };
+    let echoes_route = first.starts_with('/')
+        || ["GET", "POST", "PUT", "DELETE", "PATCH"].iter().any(|m| first.starts_with(m));
+    let summary = if echoes_route {
+        [" — ", " -- ", " - ", ": "].iter()
+            .find_map(|sep| first.split_once(sep).map(|(_, rest)| rest.trim().to_string()))
+    } else {
+        Some(first)
+    };
+    let summary = summary.filter(|s| !s.is_empty()).map(|s| {
+        let s = match s.find(". ") {
+            Some(i) if i < 160 => s[..i].to_string(),
+            _ => s.trim_end_matches('.').to_string(),
+        };
+        capitalize(&s)
+    });
+    let rest = paras[1..].join("\n\n");
+    let description = if rest.is_empty() { None } else { Some(rest.chars().take(1500).collect()) };
+    (summary, description)
+}
+
+fn path_params(path: &str) -> (String, Vec<String>) {
+    let mut clean = String::new();
+    let mut names = Vec::new();
+    let mut rest = path;
+    while let Some(open) = rest.find('{') {
+        clean.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else { break; };
+        let inner = &rest[open + 1..open + close];
+        let name = inner.split(':').next().unwrap_or(inner).to_string();
+        clean.push('{');
+        clean.push_str(&name);
+        clean.push('}');
+        names.push(name);
+        rest = &rest[open + close + 1..];
+    }
+    clean.push_str(rest);
+    (clean, names)
+}
+
+pub fn operations(sources: &mut Sources, files: &[&str], schemas: &mut Schemas) -> Vec<Operation> {
+    let mut routes = Vec::new();
+    for f in files {
+        if let Some(text) = sources.get(f) {
+            routes.extend(routes_in(f, &text));
+        }
+    }
+    routes.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.method.cmp(&b.method)));
+    let mut seen_ids: HashMap<String, usize> = HashMap::new();
+    let mut ops = Vec::new();
+    for r in routes {
+        let (path, names) = path_params(&r.path);
+        // Handler in the route's file, else any scanned file.
+        let mut found = None;
+        for f in std::iter::once(r.file.as_str()).chain(files.iter().copied()) {
+            if let Some(at) = sources.item(f, "fn", &r.handler) {
+                found = Some((f.to_string(), at));
+                break;
+            }
+        }
+        let mut op = Operation {
+            method: r.method.clone(),
+            path: path.clone(),
+            operation_id: String::new(),
+            summary: None,
+            description: None,
+            tag: path.split('/').nth(2).unwrap_or("api").to_string(),
+            params: Vec::new(),
+            body: None,
+        };
+        let mut path_types: Vec<J> = Vec::new();
+        if let Some((file, at)) = &found {
+            let text = sources.get(file).unwrap_or_else(|| Rc::from(""));
+            let (summary, description) = summarize(&doc_above(&text, *at));
+            op.summary = summary;
+            op.description = description;
+            let open = at + text[*at..].find('(').unwrap_or(0);
+            let sig = balanced(&text, open).unwrap_or("").to_string();
+            for param in split_top(&sig) {
+                let Some((_, ty)) = param.split_once(':') else { continue; };
+                let ty = ty.trim().trim_start_matches("actix_web::").trim_start_matches("web::");
+                let parsed = parse_ty(ty);
+                let Ty::Path(name, args) = &parsed else { continue; };
+                let inner = args.first();
+                match last_segment(name) {
+                    "Json" => {
+                        let sch = inner.map(|t| schemas.schema(sources, file, t).0).unwrap_or_else(any);
+                        op.body = Some(Body::Json(sch));
+                    }
+                    "Form" => {
+                        let sch = inner.map(|t| schemas.schema(sources, file, t).0).unwrap_or_else(any);
+                        op.body = Some(Body::Form(sch));
+                    }
+                    "Multipart" => op.body = Some(Body::Multipart),
+                    "Payload" | "Bytes" => op.body = Some(Body::Binary),
+                    "String" if op.body.is_none() => op.body = Some(Body::Text),
+                    "Query" => {
+                        let Some(t) = inner else { continue; };
+                        let sch = schemas.schema(sources, file, t).0;
+                        let comp = sch.get("$ref").and_then(|r| r.as_str())
+                            .map(|r| r.trim_start_matches("#/components/schemas/").to_string());
+                        match comp.and_then(|c| schemas.fields.get(&c).map(|f| f.iter().map(|(n, j, req, d)| {
+                            (n.clone(), clone_j(j), *req, d.clone())
+                        }).collect::<Vec<_>>())) {
+                            Some(fields) => {
+                                for (n, j, req, d) in fields {
+                                    op.params.push((n, "query", req, j, d));
+                                }
+                            }
+                            None => op.params.push(("params".to_string(), "query", false, sch, None)),
+                        }
+                    }
+                    "Path" => {
+                        path_types = match inner {
+                            Some(Ty::Tuple(items)) => items.iter().map(|t| schemas.schema(sources, file, t).0).collect(),
+                            Some(t) => vec![schemas.schema(sources, file, t).0],
+                            None => Vec::new(),
+                        };
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let typed = path_types.len() == names.len();
+        let mut path_params = Vec::new();
+        for (i, n) in names.iter().enumerate() {
+            let sch = if typed { std::mem::replace(&mut path_types[i], any()) } else { obj(vec![("type", s("string"))]) };
+            let sch = if sch.get("type").is_none() { obj(vec![("type", s("string"))]) } else { sch };
+            path_params.push((n.clone(), "path", true, sch, None));
+        }
+        path_params.append(&mut op.params);
+        op.params = path_params;
+        let count = seen_ids.entry(r.handler.clone()).or_insert(0);
+        *count += 1;
+        op.operation_id = if *count == 1 { r.handler.clone() } else { format!("{}_{}", r.handler, count) };
+        ops.push(op);
+    }
+    ops
+}
+
+fn clone_j(j: &J) -> J {
+    match j {
+        J::Bool(b) => J::Bool(*b),
+        J::Num(n) => J::Num(*n),
+        J::Str(v) => J::Str(v.clone()),
+        J::Arr(items) => J::Arr(items.iter().map(clone_j).collect()),
+        J::Obj(pairs) => J::Obj(pairs.iter().map(|(k, v)| (k.clone(), clone_j(v))).collect()),
+    }
+}
+
+// ─── Output ───
+
+pub fn document(ops: &[Operation], schemas: &Schemas, version: &str) -> J {
+    let mut paths: BTreeMap<String, Vec<(String, J)>> = BTreeMap::new();
+    let mut tags = BTreeSet::new();
+    for op in ops {
+        tags.insert(op.tag.clone());
+        let mut o = vec![
+            ("operationId", s(&op.operation_id)),
+            ("tags", J::Arr(vec![s(&op.tag)])),
+        ];
+        if let Some(sm) = &op.summary { o.push(("summary", s(sm))); }
+        if let Some(d) = &op.description { o.push(("description", s(d))); }
+        if !op.params.is_empty() {
+            o.push(("parameters", J::Arr(op.params.iter().map(|(n, loc, req, sch, d)| {
+                let mut p = vec![("name", s(n)), ("in", s(loc)), ("required", J::Bool(*req)), ("schema", clone_j(sch))];
+                if let Some(d) = d { p.push(("description", s(d))); }
+                obj(p)
+            }).collect())));
+        }
+        if let Some(body) = &op.body {
+            let (ctype, sch, required) = match body {
+                Body::Json(sch) => ("application/json", clone_j(sch), true),
+                Body::Form(sch) => ("application/x-www-form-urlencoded", clone_j(sch), true),
+                Body::Multipart => ("multipart/form-data", obj(vec![("type", s("object"))]), true),
+                Body::Binary => ("application/octet-stream", obj(vec![("type", s("string")), ("format", s("binary"))]), false),
+                Body::Text => ("text/plain", obj(vec![("type", s("string"))]), false),
+            };
+            o.push(("requestBody", obj(vec![
+                ("required", J::Bool(required)),
+                ("content", J::Obj(vec![(ctype.to_string(), obj(vec![("schema", sch)]))])),
+            ])));
+        }
+        o.push(("responses", obj(vec![
+            ("200", obj(vec![
+                ("description", s("Success. Response bodies are not typed yet.")),
+                ("content", obj(vec![("application/json", obj(vec![("schema", any())]))])),
+            ])),
+            ("default", obj(vec![("$ref", s("#/components/responses/Error"))])),
+        ])));
+        paths.entry(op.path.clone()).or_default().push((op.method.clone(), obj(o)));
+    }
+    let mut schema_defs: Vec<(String, J)> = vec![("Error".to_string(), obj(vec![
+        ("type", s("object")),
+        ("properties", obj(vec![("error", obj(vec![("type", s("string"))]))])),
+        ("required", J::Arr(vec![s("error")])),
+    ]))];
+    schema_defs.extend(schemas.defs.iter().map(|(k, v)| (k.clone(), clone_j(v))));
+    obj(vec![
+        ("openapi", s("3.0.3")),
+        ("info", obj(vec![
+            ("title", s("WolfStack API")),
+            ("version", s(version)),
+            ("description", s("Generated at build time from WolfStack's route table and handler signatures. \
+                Request bodies and query parameters are typed from the handlers' extractors; response bodies are \
+                not typed yet. Authenticate with a session cookie, an API key (X-API-Key or Authorization: Bearer), \
+                or — node to node only — the cluster secret.")),
+        ])),
+        ("servers", J::Arr(vec![obj(vec![("url", s("/"))])])),
+        ("security", J::Arr(vec![
+            obj(vec![("session", J::Arr(vec![]))]),
+            obj(vec![("apiKey", J::Arr(vec![]))]),
+            obj(vec![("bearer", J::Arr(vec![]))]),
+            obj(vec![("clusterSecret", J::Arr(vec![]))]),
+        ])),
+        ("tags", J::Arr(tags.iter().map(|t| obj(vec![("name", s(t))])).collect())),
+        ("paths", J::Obj(paths.into_iter().map(|(p, methods)| (p, J::Obj(methods))).collect())),
+        ("components", obj(vec![
+            ("securitySchemes", obj(vec![
+                ("session", obj(vec![("type", s("apiKey")), ("in", s("cookie")), ("name", s("wolfstack_session"))])),
+                ("apiKey", obj(vec![("type", s("apiKey")), ("in", s("header")), ("name", s("X-API-Key"))])),
+                ("bearer", obj(vec![("type", s("http")), ("scheme", s("bearer"))])),
+                ("clusterSecret", obj(vec![("type", s("apiKey")), ("in", s("header")), ("name", s("X-WolfStack-Secret"))])),
+            ])),
+            ("responses", obj(vec![
+                ("Error", obj(vec![
+                    ("description", s("Error")),
+                    ("content", obj(vec![("application/json", obj(vec![("schema", reference("Error"))]))])),
+                ])),
+            ])),
+            ("schemas", J::Obj(schema_defs)),
+        ])),
+    ])
+}
+
+const TS_RESERVED: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do", "else", "enum",
+    "export", "extends", "false", "finally", "for", "function", "if", "import", "in", "instanceof", "new", "null",
+    "return", "super", "switch", "this", "throw", "true", "try", "typeof", "var", "void", "while", "with", "body",
+    "query",
+];
+
+fn ts_ident(name: &str) -> String {
+    let mut out: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if out.chars().next().is_some_and(|c| c.is_ascii_digit()) { out.insert(0, '_'); }
+    if TS_RESERVED.contains(&out.as_str()) { out.push('_'); }
+    out
+}
+
+fn ts_key(name: &str) -> String {
+    let plain = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !name.chars().next().is_some_and(|c| c.is_ascii_digit());
+    if plain { name.to_string() } else { format!("\"{}\"", name.replace('"', "\\\"")) }
+}
+
+fn camel(snake: &str) -> String {
+    rename(snake, Some("camelCase"), false)
+}
+
+fn ts_type(j: &J) -> String {
+    if let Some(r) = j.get("$ref").and_then(|r| r.as_str()) {
+        return r.trim_start_matches("#/components/schemas/").to_string();
+    }
+    let base = if let Some(J::Arr(all)) = j.get("allOf") {
+        all.first().map(ts_type).unwrap_or_else(|| "unknown".into())
+    } else if let Some(J::Arr(values)) = j.get("enum") {
+        values.iter().filter_map(|v| v.as_str()).map(|v| format!("\"{}\"", v)).collect::<Vec<_>>().join(" | ")
+    } else {
+        match j.get("type").and_then(|t| t.as_str()) {
+            Some("string") => "string".into(),
+            Some("integer") | Some("number") => "number".into(),
+            Some("boolean") => "boolean".into(),
+            Some("array") => match (j.get("items"), j.get("prefixItems")) {
+                (_, Some(J::Arr(items))) => format!("[{}]", items.iter().map(ts_type).collect::<Vec<_>>().join(", ")),
+                (Some(items), _) => {
+                    let t = ts_type(items);
+                    if t.contains(' ') { format!("Array<{}>", t) } else { format!("{}[]", t) }
+                }
+                _ => "unknown[]".into(),
+            },
+            Some("object") => match (j.get("properties"), j.get("additionalProperties")) {
+                (Some(J::Obj(props)), _) => {
+                    let required: Vec<&str> = match j.get("required") {
+                        Some(J::Arr(r)) => r.iter().filter_map(|v| v.as_str()).collect(),
+                        _ => Vec::new(),
+                    };
+                    let fields: Vec<String> = props.iter().map(|(k, v)| {
+                        let opt = if required.contains(&k.as_str()) { "" } else { "?" };
+                        format!("{}{}: {}", ts_key(k), opt, ts_type(v))
+                    }).collect();
+                    format!("{{ {} }}", fields.join("; "))
+                }
+                (_, Some(J::Obj(v))) if !v.is_empty() => format!("Record<string, {}>", ts_type(&J::Obj(v.iter().map(|(k, v)| (k.clone(), clone_j(v))).collect()))),
+                _ => "Record<string, unknown>".into(),
+            },
+            _ => "unknown".into(),
+        }
+    };
+    if matches!(j.get("nullable"), Some(J::Bool(true))) { format!("{} | null", base) } else { base }
+}
+
+pub fn typescript_client(ops: &[Operation], schemas: &Schemas, version: &str) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "// WolfStack API client v{} — generated at build time from the same route\n\
+         // table as /api/openapi.json. Do not edit; download a fresh copy from\n\
+         // /api/openapi/client.ts after upgrading.\n\n",
+        version
+    ));
+    out.push_str("export interface ErrorBody { error: string }\n\n");
+    for (name, def) in &schemas.defs {
+        if let Some(d) = def.get("description").and_then(|d| d.as_str()) {
+            out.push_str(&format!("/** {} */\n", d.replace("*/", "*\\/")));
+        }
+        match def.get("properties") {
+            Some(J::Obj(props)) => {
+                let required: Vec<&str> = match def.get("required") {
+                    Some(J::Arr(r)) => r.iter().filter_map(|v| v.as_str()).collect(),
+                    _ => Vec::new(),
+                };
+                out.push_str(&format!("export interface {} {{\n", name));
+                for (k, v) in props {
+                    if let Some(d) = v.get("description").and_then(|d| d.as_str()) {
+                        out.push_str(&format!("  /** {} */\n", d.replace("*/", "*\\/")));
+                    }
+                    let opt = if required.contains(&k.as_str()) { "" } else { "?" };
+                    out.push_str(&format!("  {}{}: {};\n", ts_key(k), opt, ts_type(v)));
+                }
+                if matches!(def.get("additionalProperties"), Some(J::Bool(true))) {
+                    out.push_str("  [key: string]: unknown;\n");
+                }
+                out.push_str("}\n\n");
+            }
+            _ => out.push_str(&format!("export type {} = {};\n\n", name, ts_type(def))),
+        }
+    }
+    out.push_str(r#"export interface ClientOptions {
+  /** API key (sent as X-API-Key). Omit to rely on the browser session cookie. */
+  apiKey?: string;
+  fetch?: typeof fetch;
+}
+
+export class WolfStackError extends Error {
+  constructor(public status: number, message: string, public body: unknown) {
+    super(message);
+  }
+}
+
+type Query = Record<string, string | number | boolean | null | undefined>;
+
+export class WolfStackClient {
+  constructor(private baseUrl = "", private options: ClientOptions = {}) {}
+
+  private async request(method: string, path: string, query?: object, body?: unknown, contentType?: string): Promise<any> {
+    let url = this.baseUrl.replace(/\/$/, "") + path;
+    const params = new URLSearchParams();
+    for (const [k, v] of Object.entries((query ?? {}) as Query)) {
+      if (v !== undefined && v !== null) params.append(k, String(v));
+    }
+    if ([...params].length) url += "?" + params.toString();
+    const headers: Record<string, string> = {};
+    if (this.options.apiKey) headers["X-API-Key"] = this.options.apiKey;
+    let payload: BodyInit | undefined;
+    if (body !== undefined) {
+      if (contentType === "application/json") {
+        headers["Content-Type"] = contentType;
+        payload = JSON.stringify(body);
+      } else {
+        if (contentType && contentType !== "multipart/form-data") headers["Content-Type"] = contentType;
+        payload = body as BodyInit;
+      }
+    }
+    const resp = await (this.options.fetch ?? fetch)(url, { method, headers, body: payload, credentials: "same-origin" });
+    const text = await resp.text();
+    let data: unknown = text;
+    try { data = text ? JSON.parse(text) : null; } catch { /* not JSON */ }
+    if (!resp.ok) {
+      const message = (data as ErrorBody | null)?.error ?? `HTTP ${resp.status}`;
+      throw new WolfStackError(resp.status, message, data);
+    }
+    return data;
+  }
+"#);
+    for op in ops {
+        let mut args = Vec::new();
+        let mut path_expr = op.path.clone();
+        for (n, loc, _, sch, _) in &op.params {
+            if *loc != "path" { continue; }
+            let ident = ts_ident(n);
+            let t = if ts_type(sch) == "number" { "number" } else { "string" };
+            args.push(format!("{}: {}", ident, t));
+            path_expr = path_expr.replace(&format!("{{{}}}", n), &format!("${{encodeURIComponent({})}}", ident));
+        }
+        let query: Vec<&(String, &str, bool, J, Option<String>)> = op.params.iter().filter(|p| p.1 == "query").collect();
+        let query_arg = if query.is_empty() {
+            "undefined"
+        } else {
+            let optional = if query.iter().any(|p| p.2) { "" } else { "?" };
+            // `web::Query<HashMap<…>>` is a single free-form "params" object.
+            let ty = if query.len() == 1 && query[0].0 == "params" {
+                ts_type(&query[0].3)
+            } else {
+                let fields: Vec<String> = query.iter()
+                    .map(|(n, _, req, sch, _)| format!("{}{}: {}", ts_key(n), if *req { "" } else { "?" }, ts_type(sch)))
+                    .collect();
+                format!("{{ {} }}", fields.join("; "))
+            };
+            args.push(format!("query{}: {}", optional, ty));
+            "query"
+        };
+        let (body_arg, ctype) = match &op.body {
+            Some(Body::Json(sch)) => {
+                args.push(format!("body: {}", ts_type(sch)));
+                ("body", "\"application/json\"")
+            }
+            Some(Body::Form(_)) => {
+                args.push("body: URLSearchParams".to_string());
+                ("body", "\"application/x-www-form-urlencoded\"")
+            }
+            Some(Body::Multipart) => {
+                args.push("body: FormData".to_string());
+                ("body", "\"multipart/form-data\"")
+            }
+            Some(Body::Binary) => {
+                args.push("body?: Blob | ArrayBuffer | ReadableStream".to_string());
+                ("body", "\"application/octet-stream\"")
+            }
+            Some(Body::Text) => {
+                args.push("body?: string".to_string());
+                ("body", "\"text/plain\"")
+            }
+            None => ("undefined", "undefined"),
+        };
+        out.push('\n');
+        let doc = op.summary.clone().unwrap_or_default();
+        out.push_str(&format!("  /** {} {}{}{} */\n", op.method.to_uppercase(), op.path,
+            if doc.is_empty() { "" } else { " — " }, doc.replace("*/", "*\\/")));
+        out.push_str(&format!(
+            "  {}({}): Promise<any> {{\n    return this.request(\"{}\", `{}`, {}, {}, {});\n  }}\n",
+            ts_ident(&camel(&op.operation_id)), args.join(", "), op.method.to_uppercase(), path_expr,
+            query_arg, body_arg, ctype,
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Generate `(openapi.json, client.ts)` for the given API source files.
+pub fn generate(files: &[&str], version: &str) -> (String, String) {
+    let mut sources = Sources::default();
+    let mut schemas = Schemas::default();
+    let ops = operations(&mut sources, files, &mut schemas);
+    let mut json = String::new();
+    document(&ops, &schemas, version).write(&mut json, 0);
+    json.push('\n');
+    let ts = typescript_client(&ops, &schemas, version);
+    (json, ts)
+}
@@ -2617,8 +2617,17 @@ After=network-online.target
 Wants=network-online.target
 
 [Service]
-Type=simple
+# WolfStack reports READY=1 once its ports are bound, so a start that
+# can't bind fails visibly instead of "active" with nothing listening.
+# It pings the watchdog from its async runtime; if that wedges, systemd
+# restarts it (Restart=on-failure). Panics and failed starts leave a
+# report in /var/lib/wolfstack/crash (GET /api/system/crash-reports).
+Type=notify
+NotifyAccess=main
+WatchdogSec=120
+TimeoutStartSec=300
 ExecStart=/usr/local/bin/wolfstack --bind $WS_BIND${AGENT_FLAG}
+ExecReload=/bin/kill -HUP \$MAINPID
 WorkingDirectory=/opt/wolfstack
 Restart=on-failure
 RestartSec=5
@@ -2712,6 +2721,16 @@ else
         echo "  → Adding KillMode=process (WolfStack restarts no longer stop VMs/containers)"
         sed -i '/^\[Service\]/a KillMode=process' "$UNIT_FILE"
     fi
+    # Systemd watchdog for existing units. Type= is left as it is (a
+    # Type=simple unit works with the watchdog too); only added when the
+    # operator hasn't set WatchdogSec= themselves.
+    if ! grep -qE '^[[:space:]]*WatchdogSec=' "$UNIT_FILE" 2>/dev/null; then
+        echo "  → Adding WatchdogSec=120 (systemd restarts WolfStack if it stops responding)"
+        sed -i '/^\[Service\]/a WatchdogSec=120' "$UNIT_FILE"
+        if ! grep -qE '^[[:space:]]*NotifyAccess=' "$UNIT_FILE" 2>/dev/null; then
+            sed -i '/^\[Service\]/a NotifyAccess=main' "$UNIT_FILE"
+        fi
+    fi
     systemctl daemon-reload
     # Restart only if we actually changed the unit, otherwise leave
     # whatever the user has running alone.
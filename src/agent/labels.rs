@@ -0,0 +1,147 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Node labels and label selectors.
+//!
+//! Labels are free-form `key=value` tags an operator puts on a node
+//! (`env=prod`, `rack=b4`, `tier=web`). Like `site` and `roles` they are
+//! owned by the node itself (`self_labels.json`) and gossiped in its
+//! StatusReport. A selector picks nodes by their labels — `GET
+//! /api/nodes?selector=env=prod` and the bulk operations in
+//! `POST /api/nodes/bulk` both take one.
+//!
+//! Selector syntax is a comma-separated list of terms, all of which must
+//! hold: `key=value` (or `key==value`), `key!=value` (a node without the
+//! key matches), `key` (has the key, any value) and `!key` (doesn't).
+
+use std::collections::BTreeMap;
+
+pub const MAX_LABELS: usize = 32;
+const MAX_LEN: usize = 63;
+
+/// Keys: letters, digits and `.` `_` `-` `/`, starting with a letter or
+/// digit (so `example.com/owner` works). Values: the same minus `/`, and
+/// may be empty.
+pub fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_LEN {
+        return Err(format!("label key must be 1-{} characters", MAX_LEN));
+    }
+    if !key.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(format!("label key '{}' must start with a letter or digit", key));
+    }
+    if !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/')) {
+        return Err(format!("label key '{}' may only contain letters, digits, '.', '_', '-' and '/'", key));
+    }
+    Ok(())
+}
+
+pub fn validate_value(value: &str) -> Result<(), String> {
+    if value.len() > MAX_LEN {
+        return Err(format!("label value must be at most {} characters", MAX_LEN));
+    }
+    if !value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+        return Err(format!("label value '{}' may only contain letters, digits, '.', '_' and '-'", value));
+    }
+    Ok(())
+}
+
+pub fn validate(labels: &BTreeMap<String, String>) -> Result<(), String> {
+    if labels.len() > MAX_LABELS {
+        return Err(format!("at most {} labels per node", MAX_LABELS));
+    }
+    for (k, v) in labels {
+        validate_key(k)?;
+        validate_value(v)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Eq(String, String),
+    Ne(String, String),
+    Exists(String),
+    Missing(String),
+}
+
+/// A parsed selector. The empty selector matches every node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelSelector {
+    terms: Vec<Term>,
+}
+
+impl LabelSelector {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for raw in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let term = if let Some((k, v)) = raw.split_once("!=") {
+                Term::Ne(k.trim().to_string(), v.trim().to_string())
+            } else if let Some((k, v)) = raw.split_once("==").or_else(|| raw.split_once('=')) {
+                Term::Eq(k.trim().to_string(), v.trim().to_string())
+            } else if let Some(k) = raw.strip_prefix('!') {
+                Term::Missing(k.trim().to_string())
+            } else {
+                Term::Exists(raw.to_string())
+            };
+            match &term {
+                Term::Eq(k, v) | Term::Ne(k, v) => {
+                    validate_key(k)?;
+                    validate_value(v)?;
+                }
+                Term::Exists(k) | Term::Missing(k) => validate_key(k)?,
+            }
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.terms.iter().all(|t| match t {
+            Term::Eq(k, v) => labels.get(k) == Some(v),
+            Term::Ne(k, v) => labels.get(k) != Some(v),
+            Term::Exists(k) => labels.contains_key(k),
+            Term::Missing(k) => !labels.contains_key(k),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn selector_terms_all_have_to_hold() {
+        let prod_web = labels(&[("env", "prod"), ("tier", "web")]);
+        let staging = labels(&[("env", "staging")]);
+        let sel = LabelSelector::parse("env=prod").unwrap();
+        assert!(sel.matches(&prod_web) && !sel.matches(&staging));
+        let sel = LabelSelector::parse(" env == prod , tier").unwrap();
+        assert!(sel.matches(&prod_web) && !sel.matches(&staging));
+        let sel = LabelSelector::parse("env!=prod").unwrap();
+        assert!(!sel.matches(&prod_web) && sel.matches(&staging) && sel.matches(&labels(&[])));
+        let sel = LabelSelector::parse("!tier").unwrap();
+        assert!(!sel.matches(&prod_web) && sel.matches(&staging));
+        assert!(LabelSelector::parse("").unwrap().is_empty());
+        assert!(LabelSelector::parse("").unwrap().matches(&staging));
+    }
+
+    #[test]
+    fn bad_keys_and_values_are_refused() {
+        assert!(LabelSelector::parse("=prod").is_err());
+        assert!(LabelSelector::parse("env=pr od").is_err());
+        assert!(LabelSelector::parse("-env").is_err());
+        assert!(validate(&labels(&[("example.com/owner", "ops-team")])).is_ok());
+        assert!(validate(&labels(&[("env", "")])).is_ok());
+        assert!(validate(&labels(&[("env", "a/b")])).is_err());
+        assert!(validate(&labels(&[(&"k".repeat(64), "v")])).is_err());
+    }
+}
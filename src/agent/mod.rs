@@ -10,14 +10,17 @@
 //! - Discovers other nodes (via WolfNet or direct IP)
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, RwLock};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::warn;
 
 use crate::monitoring::SystemMetrics;
 use crate::installer::ComponentStatus;
 
+pub mod labels;
+pub mod readdress;
+
 /// Per-file result of `leave_wipe_membership_files`. A `cleared` of
 /// `false` either means the file was already absent (treat as success)
 /// or the unlink failed; `error` differentiates the two so the CLI can
@@ -42,6 +45,7 @@ pub struct LeaveWipeResult {
 
 /// Delete the on-disk files that make this node a member of its cluster:
 ///   • `self_cluster.json`  — this node's chosen cluster name
+///   • `cluster.json`       — the persisted cluster state (`ClusterSnapshot`)
 ///   • `nodes.json`         — every peer we know about
 ///   • `deleted_nodes.json` — tombstones (stale once we're starting fresh)
 ///   • `node_id`            — this node's stable identity; regenerated on
@@ -62,6 +66,7 @@ pub fn leave_wipe_membership_files() -> LeaveWipeResult {
 
     let targets = [
         p.self_cluster_config.clone(),
+        p.cluster_state.clone(),
         p.nodes_config.clone(),
         p.deleted_nodes_config.clone(),
         p.node_id_file.clone(),
@@ -197,10 +202,43 @@ pub fn is_usable_addr(addr: &str) -> bool {
     true
 }
 
-/// Track consecutive poll failures per node — only mark offline after 2+ failures
-static POLL_FAIL_COUNTS: std::sync::LazyLock<std::sync::Mutex<HashMap<String, u32>>> =
+/// Per-node poll bookkeeping: consecutive failures (a node is only marked
+/// offline after 2+) and, once it has failed enough in a row, when it may
+/// next be tried.
+#[derive(Default)]
+struct PollHealth {
+    fails: u32,
+    retry_at: Option<Instant>,
+}
+
+static POLL_HEALTH: std::sync::LazyLock<std::sync::Mutex<HashMap<String, PollHealth>>> =
     std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
 
+/// Peers polled at once. Enough that a 30-node fleet is one round; the cap
+/// only stops a runaway node list from opening hundreds of sockets.
+const POLL_CONCURRENCY: usize = 16;
+
+/// Budget for one peer across its whole URL fallback chain, so a dead node
+/// costs one timeout per cycle, not one per URL.
+const POLL_NODE_TIMEOUT: Duration = Duration::from_secs(12);
+
+/// Consecutive failures before a node is put on a backoff schedule. Below
+/// this a miss is a blip or a restart and the node is polled every cycle.
+const POLL_BACKOFF_AFTER: u32 = 3;
+
+/// Delay before the next attempt at a node that has failed `fails` polls
+/// in a row: none at first, then doubling from 20s to a 5-minute cap.
+/// `jitter` in -1..=1 moves it ±25% so a fleet of managers doesn't retry a
+/// recovering node in lockstep.
+fn poll_backoff(fails: u32, jitter: f64) -> Option<Duration> {
+    if fails < POLL_BACKOFF_AFTER {
+        return None;
+    }
+    let doublings = (fails - POLL_BACKOFF_AFTER).min(4);
+    let base = (20u64 << doublings).min(300) as f64;
+    Some(Duration::from_secs_f64(base * (1.0 + 0.25 * jitter.clamp(-1.0, 1.0))))
+}
+
 /// A tier role an operator can assign to a node so it serves ONE part of an
 /// HA hosting stack (NoroNetwork 2026-07-09). A node's roles are the keystone
 /// the DNS / mail / ingress / host tiers dispatch on: config for a subsystem
@@ -368,6 +406,12 @@ pub struct Node {
     /// Backward-compat: missing for older configs and older peers → empty Vec.
     #[serde(default)]
     pub roles: Vec<NodeRole>,
+    /// Operator-set `key=value` labels (`env=prod`, `rack=b4`) — see
+    /// `agent::labels`. Owned by the node like `roles`: persisted in
+    /// `self_labels.json`, carried in its StatusReport, authoritative on
+    /// merge. Empty for older configs and older peers.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
 }
 
 fn default_node_type() -> String { "wolfstack".to_string() }
@@ -435,6 +479,155 @@ pub fn self_node_id() -> String {
         .to_string()
 }
 
+/// Format version of `cluster.json`. Bump on a change an older build
+/// would misread; a file newer than this build understands is set aside
+/// (not overwritten) and the node boots from the legacy files instead.
+const CLUSTER_STATE_VERSION: u32 = 1;
+
+/// `at_rest_crypto` purpose label for the PVE tokens in `cluster.json`.
+/// Never rename — it derives the key.
+const CLUSTER_STATE_PURPOSE: &[u8] = b"cluster-state";
+
+/// Everything a restarted node needs to know its cluster before the first
+/// poll or gossip round, persisted as `cluster.json` (mode 0600, written
+/// atomically). Supersedes `nodes.json` + `deleted_nodes.json` +
+/// `login_disabled` as the startup source; `nodes.json` is still written,
+/// without PVE tokens, for the modules that read peer addresses from it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ClusterSnapshot {
+    #[serde(default)]
+    version: u32,
+    /// Bumped on every write, so two copies of the file (a backup, a
+    /// restore) can be told apart.
+    #[serde(default)]
+    generation: u64,
+    #[serde(default)]
+    saved_at: u64,
+    #[serde(default)]
+    self_id: String,
+    #[serde(default)]
+    cluster_name: Option<String>,
+    #[serde(default)]
+    login_disabled: bool,
+    /// Remote nodes. `pve_token` holds an at-rest `v2:` value — plaintext
+    /// only if the key was unavailable when the file was written.
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    deleted_ids: Vec<String>,
+    /// Archived nodes, `pve_token` sealed like `nodes`.
+    #[serde(default)]
+    archived: Vec<ArchivedNode>,
+    #[serde(default)]
+    archive_marks: BTreeMap<String, ArchiveMark>,
+}
+
+/// A node the operator archived: out of membership — not polled, not
+/// gossiped back in — but kept whole (PVE token, cluster name, site…) so
+/// bringing a decommissioned server back is a restore, not a re-join.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedNode {
+    pub node: Node,
+    #[serde(default)]
+    pub archived_at: u64,
+}
+
+/// Archive state of one node id, gossiped last-write-wins so every peer
+/// archives and restores it together. Unlike a tombstone it can flip back;
+/// a tombstone always beats it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveMark {
+    pub archived: bool,
+    pub at: u64,
+}
+
+/// Fold `remote` marks into `local`: the newer mark wins, a tie goes to
+/// restored so a node is never hidden by a clock coincidence. Returns the
+/// ids whose archived state changed.
+fn merge_archive_marks_into(
+    local: &mut BTreeMap<String, ArchiveMark>,
+    remote: &HashMap<String, ArchiveMark>,
+    self_id: &str,
+) -> Vec<String> {
+    let mut flipped = Vec::new();
+    for (id, mark) in remote {
+        if id == self_id {
+            continue;
+        }
+        let newer = match local.get(id) {
+            None => true,
+            Some(cur) => mark.at > cur.at || (mark.at == cur.at && cur.archived && !mark.archived),
+        };
+        if !newer {
+            continue;
+        }
+        let was_archived = local.get(id).is_some_and(|m| m.archived);
+        local.insert(id.clone(), *mark);
+        if was_archived != mark.archived {
+            flipped.push(id.clone());
+        }
+    }
+    flipped
+}
+
+/// Write-side state for `cluster.json`, behind one mutex so concurrent
+/// saves can't interleave on the temp file.
+#[derive(Default)]
+struct SnapshotWriter {
+    generation: u64,
+    /// Hash of the last snapshot written (tokens in plaintext), so a save
+    /// that changes nothing does no I/O.
+    last_hash: Option<u64>,
+}
+
+fn seal_pve_token(token: String) -> String {
+    // encrypt() refuses only when the key is stale (secret rotated, not yet
+    // restarted) — keep the token readable rather than lose it; the first
+    // save after the restart seals it.
+    crate::at_rest_crypto::encrypt(token.as_bytes(), CLUSTER_STATE_PURPOSE).unwrap_or(token)
+}
+
+fn open_pve_token(stored: String) -> Option<String> {
+    let token = crate::at_rest_crypto::decrypt_or_legacy(&stored, CLUSTER_STATE_PURPOSE, |s| s.to_string());
+    if token.is_empty() {
+        warn!("cluster.json: a PVE token did not decrypt (cluster secret changed?) — re-enter it in the node settings");
+        return None;
+    }
+    Some(token)
+}
+
+/// Re-key the PVE tokens in `cluster.json` from the OLD cluster secret to
+/// the NEW one, as part of a cluster-secret rotation. Returns the number
+/// re-keyed. Same loss-free contract as `at_rest_crypto::reencrypt_v2_field`:
+/// a token that doesn't open under `old` is left as it is.
+pub fn reencrypt_cluster_state_at_rest(old: &str, new: &str) -> Result<usize, String> {
+    if old == new {
+        return Ok(0);
+    }
+    let path = crate::paths::get().cluster_state;
+    let data = match std::fs::read_to_string(&path) {
+        Ok(d) => d,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("read {}: {}", path, e)),
+    };
+    let mut snap: ClusterSnapshot = serde_json::from_str(&data).map_err(|e| format!("parse {}: {}", path, e))?;
+    let mut rekeyed = 0usize;
+    for node in snap.nodes.iter_mut().chain(snap.archived.iter_mut().map(|a| &mut a.node)) {
+        let Some(stored) = node.pve_token.as_deref() else { continue };
+        if let crate::at_rest_crypto::ReencryptOutcome::Rekeyed(v) =
+            crate::at_rest_crypto::reencrypt_v2_field(stored, CLUSTER_STATE_PURPOSE, old, new)
+        {
+            node.pve_token = Some(v);
+            rekeyed += 1;
+        }
+    }
+    if rekeyed > 0 {
+        let json = serde_json::to_string_pretty(&snap).map_err(|e| format!("serialize: {}", e))?;
+        crate::paths::write_secure_atomic(&path, json).map_err(|e| format!("write {}: {}", path, e))?;
+    }
+    Ok(rekeyed)
+}
+
 /// Cluster state
 pub struct ClusterState {
     pub nodes: RwLock<HashMap<String, Node>>,
@@ -443,11 +636,16 @@ pub struct ClusterState {
     pub port: u16,
     /// Tombstone set: node IDs that were explicitly deleted and must not be re-added by gossip
     deleted_ids: RwLock<HashSet<String>>,
+    /// Archived nodes by id, and the archive marks gossiped for them.
+    archived: RwLock<HashMap<String, ArchivedNode>>,
+    archive_marks: RwLock<BTreeMap<String, ArchiveMark>>,
+    snapshot: Mutex<SnapshotWriter>,
 }
 
 impl ClusterState {
     fn nodes_file() -> String { crate::paths::get().nodes_config }
     fn deleted_file() -> String { crate::paths::get().deleted_nodes_config }
+    fn cluster_state_file() -> String { crate::paths::get().cluster_state }
     fn self_cluster_file() -> String { crate::paths::get().self_cluster_config }
     fn self_site_file() -> String { crate::paths::get().self_site_config }
     fn self_display_name_file() -> String { crate::paths::get().self_display_name_config }
@@ -460,10 +658,16 @@ impl ClusterState {
             self_address,
             port,
             deleted_ids: RwLock::new(HashSet::new()),
+            archived: RwLock::new(HashMap::new()),
+            archive_marks: RwLock::new(BTreeMap::new()),
+            snapshot: Mutex::new(SnapshotWriter::default()),
         };
-        // Load persisted state
-        state.load_deleted_ids();
-        state.load_nodes();
+        // Load persisted state: cluster.json, or the legacy per-file state
+        // on the first start after upgrading.
+        if !state.load_snapshot() {
+            state.load_deleted_ids();
+            state.load_nodes();
+        }
         // Auto-remove legacy Proxmox-API entries (writes a one-shot notice for the UI)
         state.cleanup_proxmox_legacy();
         // Remove ghost nodes (same IP/port but different ID)
@@ -482,6 +686,9 @@ impl ClusterState {
                 pruned
             );
         }
+        // Migrates legacy state into cluster.json; a no-op when the cleanups
+        // above already wrote it.
+        state.save_snapshot();
         state
     }
 
@@ -556,22 +763,136 @@ impl ClusterState {
         }
     }
 
-    /// Save remote nodes to disk
+    /// Save remote nodes to disk: `cluster.json`, plus the `nodes.json`
+    /// list other modules read peer addresses from.
     pub fn save_nodes(&self) {
         let nodes = self.nodes.read().unwrap();
-        let remote_nodes: Vec<&Node> = nodes.values()
+        // PVE tokens live only in cluster.json, encrypted.
+        let remote_nodes: Vec<Node> = nodes.values()
             .filter(|n| !n.is_self)
+            .map(|n| Node { pve_token: None, ..n.clone() })
             .collect();
+        drop(nodes);
         if let Ok(json) = serde_json::to_string_pretty(&remote_nodes) {
             let path = Self::nodes_file();
-            // Written with mode 0600 because each Node row embeds the
-            // peer's pve_token (if any) and pve_fingerprint. Pre-v18.7.27
-            // nodes.json was world-readable — any unprivileged local user
-            // could siphon every PVE API token on the cluster.
+            // Still mode 0600: rows carry pve_fingerprint and addresses.
+            // Pre-v18.7.27 nodes.json was world-readable — any unprivileged
+            // local user could siphon every PVE API token on the cluster.
             if let Err(e) = crate::paths::write_secure(&path, json) {
                 warn!("Failed to save nodes: {}", e);
             }
         }
+        self.save_snapshot();
+    }
+
+    /// Restore nodes and tombstones from `cluster.json`. False when there
+    /// is no usable file, so the caller falls back to the legacy files.
+    fn load_snapshot(&self) -> bool {
+        let path = Self::cluster_state_file();
+        let Ok(data) = std::fs::read_to_string(&path) else { return false };
+        let snap = match serde_json::from_str::<ClusterSnapshot>(&data) {
+            Ok(s) if (1..=CLUSTER_STATE_VERSION).contains(&s.version) => s,
+            Ok(s) if s.version > CLUSTER_STATE_VERSION => {
+                // Written by a newer build (this is a downgrade). Set it
+                // aside instead of overwriting it with an older format.
+                let kept = format!("{}.v{}", path, s.version);
+                warn!("{} is format v{}, newer than this build reads (v{}) — moved to {}, starting from the legacy files",
+                    path, s.version, CLUSTER_STATE_VERSION, kept);
+                let _ = std::fs::rename(&path, &kept);
+                return false;
+            }
+            Ok(_) => {
+                warn!("Ignoring {}: no format version", path);
+                return false;
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable {}: {}", path, e);
+                return false;
+            }
+        };
+        self.snapshot.lock().unwrap().generation = snap.generation;
+        self.deleted_ids.write().unwrap().extend(snap.deleted_ids);
+        self.archive_marks.write().unwrap().extend(snap.archive_marks);
+        {
+            let mut archived = self.archived.write().unwrap();
+            for mut a in snap.archived {
+                a.node.pve_token = a.node.pve_token.and_then(open_pve_token);
+                archived.insert(a.node.id.clone(), a);
+            }
+        }
+        let mut nodes = self.nodes.write().unwrap();
+        for mut node in snap.nodes {
+            if node.id == self.self_id {
+                continue;
+            }
+            node.online = false; // Will be updated by polling
+            node.is_self = false;
+            node.pve_token = node.pve_token.and_then(open_pve_token);
+            nodes.insert(node.id.clone(), node);
+        }
+        true
+    }
+
+    /// `cluster.json` as last written, if this build can read it.
+    fn read_snapshot() -> Option<ClusterSnapshot> {
+        let data = std::fs::read_to_string(Self::cluster_state_file()).ok()?;
+        serde_json::from_str::<ClusterSnapshot>(&data).ok()
+            .filter(|s| (1..=CLUSTER_STATE_VERSION).contains(&s.version))
+    }
+
+    /// Write `cluster.json`. Skipped when nothing changed since the last
+    /// write; otherwise written to a temp file and renamed into place, so
+    /// a crash mid-write leaves the previous generation intact.
+    pub fn save_snapshot(&self) {
+        use std::hash::{Hash, Hasher};
+        let mut writer = self.snapshot.lock().unwrap();
+        let (mut remote, self_login_disabled) = {
+            let nodes = self.nodes.read().unwrap();
+            let remote: Vec<Node> = nodes.values().filter(|n| !n.is_self).cloned().collect();
+            (remote, nodes.get(&self.self_id).map(|n| n.login_disabled))
+        };
+        remote.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut deleted_ids: Vec<String> = self.deleted_ids.read().unwrap().iter().cloned().collect();
+        deleted_ids.sort();
+        let mut archived: Vec<ArchivedNode> = self.archived.read().unwrap().values().cloned().collect();
+        archived.sort_by(|a, b| a.node.id.cmp(&b.node.id));
+        let mut snap = ClusterSnapshot {
+            version: CLUSTER_STATE_VERSION,
+            generation: 0,
+            saved_at: 0,
+            self_id: self.self_id.clone(),
+            cluster_name: Self::load_self_cluster_name(),
+            login_disabled: self_login_disabled
+                .or_else(Self::load_self_login_disabled)
+                .unwrap_or(false),
+            nodes: remote,
+            deleted_ids,
+            archived,
+            archive_marks: self.archive_marks.read().unwrap().clone(),
+        };
+        let Ok(plain) = serde_json::to_vec(&snap) else { return };
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        plain.hash(&mut hasher);
+        let hash = hasher.finish();
+        if writer.last_hash == Some(hash) {
+            return;
+        }
+        snap.generation = writer.generation + 1;
+        snap.saved_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        for node in snap.nodes.iter_mut().chain(snap.archived.iter_mut().map(|a| &mut a.node)) {
+            node.pve_token = node.pve_token.take().map(seal_pve_token);
+        }
+        let Ok(json) = serde_json::to_string_pretty(&snap) else { return };
+        if let Err(e) = crate::paths::write_secure_atomic(&Self::cluster_state_file(), json) {
+            warn!("Failed to save cluster state: {}", e);
+            return;
+        }
+        writer.generation = snap.generation;
+        writer.last_hash = Some(hash);
+        // login_disabled now lives in cluster.json.
+        if std::path::Path::new(Self::SELF_LOGIN_DISABLED_FILE).exists() {
+            let _ = std::fs::remove_file(Self::SELF_LOGIN_DISABLED_FILE);
+        }
     }
 
     /// The address this node advertises to peers in its OWN self entry.
@@ -663,6 +984,11 @@ impl ClusterState {
             .map(|n| n.roles.clone())
             .filter(|r| !r.is_empty())
             .unwrap_or_else(Self::load_self_roles);
+        // Labels: the same in-memory-then-disk re-assertion as roles.
+        let prev_labels = nodes.get(&self.self_id)
+            .map(|n| n.labels.clone())
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(Self::load_self_labels);
         nodes.insert(self.self_id.clone(), Node {
             id: self.self_id.clone(),
             hostname: metrics.hostname.clone(),
@@ -705,6 +1031,7 @@ impl ClusterState {
             site: prev_site,
             display_name: prev_display_name,
             roles: prev_roles,
+            labels: prev_labels,
         });
 
         // Self-heal: drop any NON-self entry previously saved under one of our
@@ -753,6 +1080,14 @@ impl ClusterState {
             .collect()
     }
 
+    /// Every cluster node whose labels satisfy `selector`, deduplicated via
+    /// `get_all_nodes`. Bulk operations (`POST /api/nodes/bulk`) target these.
+    pub fn nodes_matching(&self, selector: &labels::LabelSelector) -> Vec<Node> {
+        self.get_all_nodes().into_iter()
+            .filter(|n| selector.matches(&n.labels))
+            .collect()
+    }
+
     pub fn get_all_nodes(&self) -> Vec<Node> {
         let nodes = self.nodes.read().unwrap();
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
@@ -910,17 +1245,21 @@ impl ClusterState {
             display_name: None,
             // Roles likewise arrive from the peer's own self-report.
             roles: Vec::new(),
+            labels: Default::default(),
         });
         drop(nodes);
         self.save_nodes();
         id
     }
 
-    /// Remove a server — persists to disk and adds to tombstone set
+    /// Remove a server — persists to disk and adds to tombstone set.
+    /// Also deletes it from the archive.
     pub fn remove_server(&self, id: &str) -> bool {
         let mut nodes = self.nodes.write().unwrap();
-        let removed = nodes.remove(id).is_some();
+        let mut removed = nodes.remove(id).is_some();
         drop(nodes);
+        removed |= self.archived.write().unwrap().remove(id).is_some();
+        self.archive_marks.write().unwrap().remove(id);
         if removed {
             self.save_nodes();
             // Tombstone: prevent gossip from re-adding this node
@@ -963,6 +1302,12 @@ impl ClusterState {
                 nodes.remove(id);
             }
             drop(nodes);
+            // A delete beats an archive: drop the kept copy and its mark.
+            {
+                let deleted = self.deleted_ids.read().unwrap();
+                self.archived.write().unwrap().retain(|id, _| !deleted.contains(id));
+                self.archive_marks.write().unwrap().retain(|id, _| !deleted.contains(id));
+            }
             self.save_deleted_ids();
             if !to_remove.is_empty() {
                 self.save_nodes();
@@ -976,6 +1321,93 @@ impl ClusterState {
         self.deleted_ids.read().unwrap().iter().cloned().collect()
     }
 
+    /// Archive a server: take it out of membership but keep its record, so
+    /// it can be restored later with its settings intact. False if it isn't
+    /// a known remote node.
+    pub fn archive_server(&self, id: &str) -> bool {
+        if id == self.self_id {
+            return false;
+        }
+        let Some(mut node) = self.nodes.write().unwrap().remove(id) else { return false };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        node.online = false;
+        node.metrics = None;
+        node.components = Vec::new();
+        self.archived.write().unwrap().insert(id.to_string(), ArchivedNode { node, archived_at: now });
+        self.archive_marks.write().unwrap().insert(id.to_string(), ArchiveMark { archived: true, at: now });
+        self.save_nodes();
+        true
+    }
+
+    /// Put an archived server back into membership. The poll brings it
+    /// online again; peers restore it from the gossiped mark.
+    pub fn restore_server(&self, id: &str) -> Option<Node> {
+        let archived = self.archived.write().unwrap().remove(id)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        self.archive_marks.write().unwrap().insert(id.to_string(), ArchiveMark { archived: false, at: now });
+        self.nodes.write().unwrap().insert(id.to_string(), archived.node.clone());
+        self.save_nodes();
+        Some(archived.node)
+    }
+
+    /// True if the node is archived — gossip must not re-add it.
+    pub fn is_archived(&self, id: &str) -> bool {
+        self.archive_marks.read().unwrap().get(id).is_some_and(|m| m.archived)
+    }
+
+    pub fn get_archived_nodes(&self) -> Vec<ArchivedNode> {
+        let mut list: Vec<ArchivedNode> = self.archived.read().unwrap().values().cloned().collect();
+        list.sort_by(|a, b| b.archived_at.cmp(&a.archived_at));
+        list
+    }
+
+    pub fn get_archived_node(&self, id: &str) -> Option<Node> {
+        self.archived.read().unwrap().get(id).map(|a| a.node.clone())
+    }
+
+    pub fn get_archive_marks(&self) -> HashMap<String, ArchiveMark> {
+        self.archive_marks.read().unwrap().iter().map(|(k, v)| (k.clone(), *v)).collect()
+    }
+
+    /// Merge a peer's archive marks and archive or restore our copy of
+    /// each node whose state flipped. Tombstoned ids are ignored — a
+    /// deleted node stays deleted.
+    pub fn merge_archive_marks(&self, remote: &HashMap<String, ArchiveMark>) {
+        let remote: HashMap<String, ArchiveMark> = remote.iter()
+            .filter(|(id, _)| !self.is_tombstoned(id))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        let (flipped, marks_changed) = {
+            let mut marks = self.archive_marks.write().unwrap();
+            let before = marks.clone();
+            let flipped = merge_archive_marks_into(&mut marks, &remote, &self.self_id);
+            (flipped, *marks != before)
+        };
+        for id in &flipped {
+            let mark = self.archive_marks.read().unwrap().get(id).copied();
+            let Some(mark) = mark else { continue };
+            if mark.archived {
+                let node = self.nodes.write().unwrap().remove(id);
+                if let Some(mut node) = node {
+                    node.online = false;
+                    node.metrics = None;
+                    node.components = Vec::new();
+                    self.archived.write().unwrap().insert(id.clone(), ArchivedNode { node, archived_at: mark.at });
+                }
+            } else {
+                let restored = self.archived.write().unwrap().remove(id);
+                if let Some(a) = restored {
+                    self.nodes.write().unwrap().insert(id.clone(), a.node);
+                }
+            }
+        }
+        if !flipped.is_empty() {
+            self.save_nodes();
+        } else if marks_changed {
+            self.save_snapshot();
+        }
+    }
+
     /// Merge a peer's advertised cluster members into our own list so that ANY
     /// node converges to the full mesh — not just the node the cluster was
     /// built on. This is what lets an operator log into a secondary node and
@@ -1032,9 +1464,9 @@ impl ClusterState {
             // Already seeded earlier in THIS same bundle (under another record
             // id sharing this self_id)? Skip — the snapshot below can't see it.
             if added_self_ids.contains(m_self_id) { continue; }
-            // Never resurrect an operator-removed node (same guard the pull
-            // gossip uses).
-            if self.is_tombstoned(&m.id) { continue; }
+            // Never resurrect an operator-removed or archived node (same
+            // guard the pull gossip uses).
+            if self.is_tombstoned(&m.id) || self.is_archived(&m.id) { continue; }
             // Already known — dedup STRICTLY by the stable self_id first, then
             // fall back to id / address+port / hostname+port for records that
             // predate self_id. Leave refinement to the regular poll.
@@ -1156,6 +1588,8 @@ impl ClusterState {
         }
         drop(nodes);
         self.deleted_ids.write().unwrap().clear();
+        self.archived.write().unwrap().clear();
+        self.archive_marks.write().unwrap().clear();
     }
 
     /// Load tombstoned node IDs from disk
@@ -1184,6 +1618,8 @@ impl ClusterState {
                 warn!("Failed to save deleted nodes: {}", e);
             }
         }
+        drop(deleted);
+        self.save_snapshot();
     }
 
     /// On startup, purge any legacy Proxmox-API entries from nodes.json.
@@ -1339,8 +1775,7 @@ impl ClusterState {
                     Self::save_self_cluster_name(name);
                 }
                 // Persist site for self node — save_nodes skips self so
-                // we need a dedicated file (same pattern as cluster_name
-                // and login_disabled).
+                // we need a dedicated file (same pattern as cluster_name).
                 if site.is_some() {
                     Self::save_self_site(final_site.as_deref().unwrap_or(""));
                 }
@@ -1348,10 +1783,6 @@ impl ClusterState {
                 if display_name.is_some() {
                     Self::save_self_display_name(final_display_name.as_deref().unwrap_or(""));
                 }
-                // Persist login_disabled for self node (since save_nodes skips self)
-                if let Some(disabled) = login_disabled {
-                    Self::save_login_disabled_file(disabled);
-                }
             }
             true
         } else {
@@ -1374,7 +1805,9 @@ impl ClusterState {
                 }
             }
         }
-        None
+        Self::read_snapshot()
+            .and_then(|s| s.cluster_name)
+            .filter(|n| !n.is_empty())
     }
 
     /// Persist self cluster_name to disk (survives reinstalls)
@@ -1455,6 +1888,35 @@ impl ClusterState {
         }
     }
 
+    fn self_labels_file() -> String { crate::paths::get().self_labels_config }
+
+    /// Load persisted self labels from disk. Empty for missing/malformed.
+    pub fn load_self_labels() -> BTreeMap<String, String> {
+        if let Ok(data) = std::fs::read_to_string(Self::self_labels_file())
+            && let Ok(labels) = serde_json::from_str::<BTreeMap<String, String>>(&data)
+        {
+            return labels;
+        }
+        BTreeMap::new()
+    }
+
+    /// Persist self labels to disk. An empty map removes the file.
+    pub fn save_self_labels(labels: &BTreeMap<String, String>) {
+        let path = Self::self_labels_file();
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if labels.is_empty() {
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(labels)
+            && let Err(e) = std::fs::write(&path, json)
+        {
+            warn!("Failed to save self labels: {}", e);
+        }
+    }
+
     /// Load persisted self display name from disk. Same path/format as the
     /// site tag. `None` for missing/empty/malformed — UI then shows the
     /// hostname.
@@ -1488,8 +1950,12 @@ impl ClusterState {
         }
     }
 
-    /// Load persisted login_disabled for self node
+    /// Load persisted login_disabled for self node: cluster.json, else the
+    /// pre-cluster.json `login_disabled` file.
     fn load_self_login_disabled() -> Option<bool> {
+        if let Some(snap) = Self::read_snapshot() {
+            return Some(snap.login_disabled);
+        }
         if let Ok(data) = std::fs::read_to_string(Self::SELF_LOGIN_DISABLED_FILE) {
             let trimmed = data.trim();
             match trimmed {
@@ -1501,14 +1967,6 @@ impl ClusterState {
         None
     }
 
-    /// Persist self login_disabled to disk
-    pub fn save_login_disabled_file(disabled: bool) {
-        let _ = std::fs::create_dir_all("/etc/wolfstack");
-        if let Err(e) = std::fs::write(Self::SELF_LOGIN_DISABLED_FILE, if disabled { "true" } else { "false" }) {
-            warn!("Failed to save self login_disabled: {}", e);
-        }
-    }
-
 }
 
 /// Message exchanged between agents
@@ -1534,6 +1992,9 @@ pub enum AgentMessage {
         known_nodes: Vec<Node>,
         #[serde(default)]
         deleted_ids: Vec<String>,
+        /// Archive marks — see `ArchiveMark`.
+        #[serde(default)]
+        archive_marks: HashMap<String, ArchiveMark>,
         /// WolfNet IPs in use on this node (host IP first, then container/VM IPs)
         #[serde(default)]
         wolfnet_ips: Vec<String>,
@@ -1562,6 +2023,9 @@ pub enum AgentMessage {
         /// older peers → general-purpose node.
         #[serde(default)]
         roles: Vec<NodeRole>,
+        /// Operator-set labels — see `Node::labels`. Empty from older peers.
+        #[serde(default)]
+        labels: BTreeMap<String, String>,
         /// Enterprise license key — propagated to cluster nodes that don't have one
         #[serde(default)]
         license_key: Option<String>,
@@ -1793,6 +2257,9 @@ pub struct ControlPlaneBundle {
     /// Sender's tombstones — merged first so we never re-add a removed node.
     #[serde(default)]
     pub deleted_ids: Vec<String>,
+    /// Sender's archive marks — merged right after the tombstones.
+    #[serde(default)]
+    pub archive_marks: HashMap<String, ArchiveMark>,
     /// Raw users.json (UserStore) + its logical version.
     #[serde(default)]
     pub users_json: String,
@@ -1835,6 +2302,7 @@ pub fn build_control_plane_bundle(cluster: &ClusterState) -> ControlPlaneBundle
         from_id: cluster.self_id.clone(),
         members,
         deleted_ids: cluster.get_deleted_ids(),
+        archive_marks: cluster.get_archive_marks(),
         users_json,
         users_version,
         auth_json,
@@ -1850,6 +2318,7 @@ pub fn build_control_plane_bundle(cluster: &ClusterState) -> ControlPlaneBundle
 /// Returns a one-line summary for logging.
 pub fn apply_control_plane_bundle(cluster: &ClusterState, bundle: &ControlPlaneBundle, sender_addr: Option<String>) -> String {
     cluster.merge_tombstones(&bundle.deleted_ids);
+    cluster.merge_archive_marks(&bundle.archive_marks);
     let mut members = bundle.members.clone();
     if let Some(addr) = sender_addr.filter(|a| is_usable_addr(a)) {
         // Repair the sender's self-entry (id/self_id == from_id) when it
@@ -1989,6 +2458,73 @@ pub async fn sweep_replicate_control_plane(cluster: Arc<ClusterState>, cluster_s
     }
 }
 
+/// Fetch one peer's StatusReport, trying its URLs in order; returns the URL
+/// that answered with the report. `None` when no URL produced one within
+/// [`POLL_NODE_TIMEOUT`].
+async fn fetch_node_status(node: &Node, cluster_secret: &str) -> Option<(String, AgentMessage)> {
+    // Reuse a single client across all poll cycles for connection pooling & keep-alive
+    static POLL_CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
+        crate::api::ipv4_only_client_builder()
+            .timeout(Duration::from_secs(10))
+            .danger_accept_invalid_certs(true)
+            // Aggressive pool tuning so cluster polling doesn't
+            // leave orphaned idle sockets in CLOSE_WAIT when
+            // peers close early. See api/mod.rs API_HTTP_CLIENT.
+            .pool_idle_timeout(Duration::from_secs(15))
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new())
+    });
+
+    // v23.12: HTTPS-first via build_node_urls. CA-signed-cert peers no
+    // longer bind the second listener, so the pre-v23.12 chain that
+    // led with http://addr:port+1 silently dropped them. POLL_CLIENT
+    // has danger_accept_invalid_certs so self-signed peers still
+    // answer on HTTPS.
+    let urls = crate::api::build_node_urls(&node.address, node.port, "/api/agent/status");
+
+    // Spread the cycle's requests over half a second: every peer polled
+    // at the same instant by every manager is a thundering herd.
+    tokio::time::sleep(Duration::from_millis(rand::random::<u64>() % 500)).await;
+
+    let attempt = async {
+        for url in urls {
+            let resp = match POLL_CLIENT.get(&url)
+                .header("X-WolfStack-Secret", cluster_secret)
+                .send().await
+            {
+                Ok(resp) => resp,
+                Err(_) => continue, // Try next URL
+            };
+            // Only treat a peer as "polled" when we actually
+            // parsed a StatusReport from its body. A 401 / 404 /
+            // 500 response from a misconfigured peer used to fall
+            // into the catch-all `poll_ok = true` — the
+            // node looked successfully polled while we'd
+            // collected zero data, which then caused
+            // `replace_wolfnet_routes` to wipe that host's
+            // container/VM routes from `routes.json` (because
+            // its host wolfnet IP wasn't added to `fresh_hosts`
+            // and existing entries pointing at it were dropped
+            // from `final_routes`). klasSponsor 2026-05-13:
+            // intermittent container/VM WolfNet IP unreachability
+            // from the VPS while peer-to-peer ping kept working.
+            if !resp.status().is_success() {
+                continue;
+            }
+            // A body that isn't a StatusReport (corrupt agent, mid-restart
+            // partial JSON, version mismatch) — try the next URL in the
+            // fallback chain rather than declaring success.
+            if let Ok(msg @ AgentMessage::StatusReport { .. }) = resp.json::<AgentMessage>().await {
+                return Some((url, msg));
+            }
+        }
+        None
+    };
+    tokio::time::timeout(POLL_NODE_TIMEOUT, attempt).await.ok().flatten()
+}
+
 /// Poll remote nodes for their status
 pub async fn poll_remote_nodes(cluster: Arc<ClusterState>, cluster_secret: String, ai_agent: Option<Arc<crate::ai::AiAgent>>) {
     // Snapshot previous online state BEFORE polling
@@ -2006,8 +2542,14 @@ pub async fn poll_remote_nodes(cluster: Arc<ClusterState>, cluster_secret: Strin
     // so the loop never contacts itself or the same endpoint twice (storm guard).
     let local_ips = local_ipv4_addrs();
     let mut polled_endpoints: HashSet<(String, u16)> = HashSet::new();
-    // Collect subnet routes from all remote nodes' wolfnet_ips
-    let mut subnet_routes: HashMap<String, String> = HashMap::new();
+    // Eligible peers, in node-list order. Never poll our OWN addresses (a
+    // self entry under another NIC), and poll each distinct endpoint at most
+    // once per cycle. These two guards bound the loop to distinct, non-local
+    // peers — so a bloated or multi-homed node list can't turn it into a CPU
+    // storm. No real peer is dropped: a distinct, non-local endpoint is
+    // always polled (unless it is backing off, below).
+    let now_instant = Instant::now();
+    let mut targets: Vec<Node> = Vec::new();
     for node in nodes {
         if node.is_self { continue; }
 
@@ -2018,467 +2560,441 @@ pub async fn poll_remote_nodes(cluster: Arc<ClusterState>, cluster_secret: Strin
             continue;
         }
 
-        // Never poll our OWN addresses (a self entry under another NIC), and poll
-        // each distinct endpoint at most once per cycle. These two guards bound
-        // the 10s loop to distinct, non-local peers — so a bloated or multi-homed
-        // node list can't turn it into a CPU storm. No real peer is dropped: a
-        // distinct, non-local endpoint is always polled.
         if local_ips.contains(&node.address) { continue; }
         if !polled_endpoints.insert((node.address.clone(), node.port)) { continue; }
 
-        // ── Poll WolfStack node via agent ──
-        // v23.12: HTTPS-first via build_node_urls. CA-signed-cert peers no
-        // longer bind the second listener, so the pre-v23.12 chain that
-        // led with http://addr:port+1 silently dropped them. The shared
-        // POLL_CLIENT below has danger_accept_invalid_certs so self-signed
-        // peers still answer on HTTPS.
-        let urls = crate::api::build_node_urls(&node.address, node.port, "/api/agent/status");
-
-
-        let client = {
-            // Reuse a single client across all poll cycles for connection pooling & keep-alive
-            static POLL_CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
-                crate::api::ipv4_only_client_builder()
-                    .timeout(Duration::from_secs(10))
-                    .danger_accept_invalid_certs(true)
-                    // Aggressive pool tuning so cluster polling doesn't
-                    // leave orphaned idle sockets in CLOSE_WAIT when
-                    // peers close early. See api/mod.rs API_HTTP_CLIENT.
-                    .pool_idle_timeout(Duration::from_secs(15))
-                    .pool_max_idle_per_host(4)
-                    .tcp_keepalive(Duration::from_secs(30))
-                    .build()
-                    .unwrap_or_else(|_| reqwest::Client::new())
-            });
-            &*POLL_CLIENT
-        };
+        // A node that has been down for several polls in a row is retried on
+        // its backoff schedule, not every cycle — it would only burn a
+        // connect timeout each time.
+        let backing_off = POLL_HEALTH.lock().unwrap().get(&node.id)
+            .and_then(|h| h.retry_at)
+            .is_some_and(|at| at > now_instant);
+        if backing_off { continue; }
+        targets.push(node);
+    }
+
+    // Fetch every peer's status concurrently — sequentially, a couple of
+    // dead nodes' timeouts added up past the poll interval — then apply
+    // the results one by one in node order, as before.
+    use futures::StreamExt;
+    let polled: Vec<(Node, Option<(String, AgentMessage)>)> = futures::stream::iter(targets)
+        .map(|node| {
+            let secret = cluster_secret.clone();
+            async move {
+                let fetched = fetch_node_status(&node, &secret).await;
+                (node, fetched)
+            }
+        })
+        .buffered(POLL_CONCURRENCY)
+        .collect()
+        .await;
 
+    // Collect subnet routes from all remote nodes' wolfnet_ips
+    let mut subnet_routes: HashMap<String, String> = HashMap::new();
+    for (node, fetched) in polled {
         let mut poll_ok = false;
-        for url in &urls {
-            match client.get(url)
-                .header("X-WolfStack-Secret", &cluster_secret)
-                .send().await
-            {
-                Ok(resp) => {
-                    // Only treat a peer as "polled" when we actually
-                    // parsed a StatusReport from its body. A 401 / 404 /
-                    // 500 response from a misconfigured peer used to fall
-                    // into the catch-all `poll_ok = true` below — the
-                    // node looked successfully polled while we'd
-                    // collected zero data, which then caused
-                    // `replace_wolfnet_routes` to wipe that host's
-                    // container/VM routes from `routes.json` (because
-                    // its host wolfnet IP wasn't added to `fresh_hosts`
-                    // and existing entries pointing at it were dropped
-                    // from `final_routes`). klasSponsor 2026-05-13:
-                    // intermittent container/VM WolfNet IP unreachability
-                    // from the VPS while peer-to-peer ping kept working.
-                    if !resp.status().is_success() {
-                        continue;
-                    }
-                    if let Ok(msg) = resp.json::<AgentMessage>().await {
-                        if let AgentMessage::StatusReport { node_id: peer_self_id, hostname, metrics, components, docker_count, lxc_count, vm_count, compose_count, public_ip, known_nodes, deleted_ids, wolfnet_ips, has_docker, has_lxc, has_kvm, workload_subnets: peer_workload_subnets, site: peer_site, display_name: peer_display_name, roles: peer_roles, license_key } = msg {
-                            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            // Detect TLS by the URL scheme that actually
-                            // answered. v23.12 chain is HTTPS → HTTP-over-
-                            // WolfNet → legacy plaintext; only the last
-                            // (plain http://addr:port) implies a `--no-tls`
-                            // peer. The WolfNet HTTP overlay step is also
-                            // a TLS peer (the peer binds the second
-                            // listener only because it's self-signed).
-                            // Must build the comparison prefix the same way
-                            // build_node_urls does (bracketed v6) or the
-                            // legacy-plaintext match never fires for v6 peers.
-                            let node_tls = url.starts_with("https://")
-                                || !url.starts_with(&format!("http://{}:{}/", crate::netaddr::bracket_host(&node.address), node.port));
-                            // Capture fresh hostname + public_ip BEFORE the move into
-                            // update_remote so we can pass them to the wolfnet endpoint
-                            // reconciler below without re-locking cluster state.
-                            let peer_hostname_for_reconcile = hostname.clone();
-                            let peer_public_ip_for_reconcile = public_ip.clone();
-                            cluster.update_remote(Node {
-                                id: node.id.clone(),
-                                hostname,
-                                address: node.address.clone(),
-                                port: node.port,
-                                last_seen: now,
-                                metrics: Some(metrics),
-                                components,
-                                online: true,
-                                is_self: false,
-                                docker_count,
-                                lxc_count,
-                                vm_count,
-                                compose_count,
-                                public_ip: public_ip.clone(),
-                                node_type: "wolfstack".to_string(),
-                                pve_token: None,
-                                pve_fingerprint: None,
-                                pve_node_name: None,
-                                pve_cluster_name: None,
-                                cluster_name: node.cluster_name.clone(),
-                                join_verified: node.join_verified,
-                                has_docker,
-                                has_lxc,
-                                has_kvm,
-                                login_disabled: node.login_disabled,
-                                tls: node_tls,
-                                update_script: node.update_script.clone(),
-                                // Capture the peer's own self_id from its
-                                // status report so cross-node proxy calls
-                                // that arrive with the self_id (topology,
-                                // LAN records) resolve via the get_node
-                                // self_id fallback.
-                                //
-                                // If the peer's report is anomalously empty
-                                // (transient bug, partial config), preserve
-                                // the previously-captured self_id rather
-                                // than wiping it — otherwise a single bad
-                                // poll re-opens the 404 window until the
-                                // next good poll.
-                                self_id: if peer_self_id.is_empty() {
-                                    node.self_id.clone()
-                                } else {
-                                    Some(peer_self_id)
-                                },
-                                workload_subnets: peer_workload_subnets,
-                                // Peer's own declared site (None for
-                                // older peers and for nodes the
-                                // operator hasn't tagged yet). We
-                                // trust the peer's self-report —
-                                // that's the source of truth for a
-                                // node's own location.
-                                site: peer_site,
-                                // The owner is authoritative for its own
-                                // display name — trust its self-report, same
-                                // as site. (None = no override → show hostname.)
-                                display_name: peer_display_name,
-                                // The owner is authoritative for its own roles
-                                // too — trust the self-report. Empty = a
-                                // general-purpose node (or an older peer).
-                                roles: peer_roles,
-                            });
+        if let Some((url, msg)) = fetched {
+            if let AgentMessage::StatusReport { node_id: peer_self_id, hostname, metrics, components, docker_count, lxc_count, vm_count, compose_count, public_ip, known_nodes, deleted_ids, archive_marks, wolfnet_ips, has_docker, has_lxc, has_kvm, workload_subnets: peer_workload_subnets, site: peer_site, display_name: peer_display_name, roles: peer_roles, labels: peer_labels, license_key } = msg {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                // Detect TLS by the URL scheme that actually
+                // answered. v23.12 chain is HTTPS → HTTP-over-
+                // WolfNet → legacy plaintext; only the last
+                // (plain http://addr:port) implies a `--no-tls`
+                // peer. The WolfNet HTTP overlay step is also
+                // a TLS peer (the peer binds the second
+                // listener only because it's self-signed).
+                // Must build the comparison prefix the same way
+                // build_node_urls does (bracketed v6) or the
+                // legacy-plaintext match never fires for v6 peers.
+                let node_tls = url.starts_with("https://")
+                    || !url.starts_with(&format!("http://{}:{}/", crate::netaddr::bracket_host(&node.address), node.port));
+                // Capture fresh hostname + public_ip BEFORE the move into
+                // update_remote so we can pass them to the wolfnet endpoint
+                // reconciler below without re-locking cluster state.
+                let peer_hostname_for_reconcile = hostname.clone();
+                let peer_public_ip_for_reconcile = public_ip.clone();
+                cluster.update_remote(Node {
+                    id: node.id.clone(),
+                    hostname,
+                    address: node.address.clone(),
+                    port: node.port,
+                    last_seen: now,
+                    metrics: Some(metrics),
+                    components,
+                    online: true,
+                    is_self: false,
+                    docker_count,
+                    lxc_count,
+                    vm_count,
+                    compose_count,
+                    public_ip: public_ip.clone(),
+                    node_type: "wolfstack".to_string(),
+                    pve_token: None,
+                    pve_fingerprint: None,
+                    pve_node_name: None,
+                    pve_cluster_name: None,
+                    cluster_name: node.cluster_name.clone(),
+                    join_verified: node.join_verified,
+                    has_docker,
+                    has_lxc,
+                    has_kvm,
+                    login_disabled: node.login_disabled,
+                    tls: node_tls,
+                    update_script: node.update_script.clone(),
+                    // Capture the peer's own self_id from its
+                    // status report so cross-node proxy calls
+                    // that arrive with the self_id (topology,
+                    // LAN records) resolve via the get_node
+                    // self_id fallback.
+                    //
+                    // If the peer's report is anomalously empty
+                    // (transient bug, partial config), preserve
+                    // the previously-captured self_id rather
+                    // than wiping it — otherwise a single bad
+                    // poll re-opens the 404 window until the
+                    // next good poll.
+                    self_id: if peer_self_id.is_empty() {
+                        node.self_id.clone()
+                    } else {
+                        Some(peer_self_id)
+                    },
+                    workload_subnets: peer_workload_subnets,
+                    // Peer's own declared site (None for
+                    // older peers and for nodes the
+                    // operator hasn't tagged yet). We
+                    // trust the peer's self-report —
+                    // that's the source of truth for a
+                    // node's own location.
+                    site: peer_site,
+                    // The owner is authoritative for its own
+                    // display name — trust its self-report, same
+                    // as site. (None = no override → show hostname.)
+                    display_name: peer_display_name,
+                    // The owner is authoritative for its own roles
+                    // too — trust the self-report. Empty = a
+                    // general-purpose node (or an older peer).
+                    roles: peer_roles,
+                    // Labels too — the owner's self-report wins.
+                    labels: peer_labels,
+                });
+
+                // Reset fail count (and any backoff) on success
+                POLL_HEALTH.lock().unwrap().remove(&node.id);
+
+                // Hook B for WolfNet endpoint self-healing — cheap O(1)
+                // check against the local wolfnet config; only acts on
+                // the demonstrably-bad pattern (public self + RFC1918
+                // peer endpoint). See
+                // networking::reconcile_local_wolfnet_endpoint_if_needed
+                // for the conservative decision rule, and
+                // networking::decide_peer_endpoint for the five safety
+                // guards (wolfnet-subnet loop, self-loop,
+                // loopback/link-local, behind-NAT, no-public-ip). Runs
+                // in a blocking task to keep file I/O off the poll
+                // task.
+                {
+                    let self_addr = cluster.self_address.clone();
+                    let hn = peer_hostname_for_reconcile;
+                    let plan = node.address.clone();
+                    let pip = peer_public_ip_for_reconcile;
+                    tokio::task::spawn_blocking(move || {
+                        crate::networking::reconcile_local_wolfnet_endpoint_if_needed(
+                            &self_addr,
+                            &hn,
+                            Some(&plan),
+                            pip.as_deref(),
+                        );
+                    });
+                }
 
-                            // Reset fail count on success
-                            POLL_FAIL_COUNTS.lock().unwrap().remove(&node.id);
-
-                            // Hook B for WolfNet endpoint self-healing — cheap O(1)
-                            // check against the local wolfnet config; only acts on
-                            // the demonstrably-bad pattern (public self + RFC1918
-                            // peer endpoint). See
-                            // networking::reconcile_local_wolfnet_endpoint_if_needed
-                            // for the conservative decision rule, and
-                            // networking::decide_peer_endpoint for the five safety
-                            // guards (wolfnet-subnet loop, self-loop,
-                            // loopback/link-local, behind-NAT, no-public-ip). Runs
-                            // in a blocking task to keep file I/O off the poll
-                            // task.
-                            {
-                                let self_addr = cluster.self_address.clone();
-                                let hn = peer_hostname_for_reconcile;
-                                let plan = node.address.clone();
-                                let pip = peer_public_ip_for_reconcile;
-                                tokio::task::spawn_blocking(move || {
-                                    crate::networking::reconcile_local_wolfnet_endpoint_if_needed(
-                                        &self_addr,
-                                        &hn,
-                                        Some(&plan),
-                                        pip.as_deref(),
-                                    );
-                                });
+                // Enterprise license propagation: if a remote node has a
+                // valid license and we don't, save it locally.
+                if let Some(ref lk) = license_key {
+                    if !lk.is_empty() && !crate::compat::platform_ready() {
+                        let dm_path = crate::compat::dm_path();
+                        if std::fs::read_to_string(&dm_path).map(|s| s.trim().is_empty()).unwrap_or(true) {
+                            if let Some(parent) = std::path::Path::new(&dm_path).parent() {
+                                let _ = std::fs::create_dir_all(parent);
                             }
-
-                            // Enterprise license propagation: if a remote node has a
-                            // valid license and we don't, save it locally.
-                            if let Some(ref lk) = license_key {
-                                if !lk.is_empty() && !crate::compat::platform_ready() {
-                                    let dm_path = crate::compat::dm_path();
-                                    if std::fs::read_to_string(&dm_path).map(|s| s.trim().is_empty()).unwrap_or(true) {
-                                        if let Some(parent) = std::path::Path::new(&dm_path).parent() {
-                                            let _ = std::fs::create_dir_all(parent);
-                                        }
-                                        if std::fs::write(&dm_path, lk).is_ok() {
-                                            tracing::info!("Enterprise license received from cluster node '{}'", node.hostname);
-                                        }
-                                    }
-                                }
+                            if std::fs::write(&dm_path, lk).is_ok() {
+                                tracing::info!("Enterprise license received from cluster node '{}'", node.hostname);
                             }
+                        }
+                    }
+                }
 
-                            // Merge tombstones first — so we don't re-add deleted nodes
-                            cluster.merge_tombstones(&deleted_ids);
-
-                            // Merge known_nodes (gossip) — mirror node settings from remote
-                            let current_nodes = cluster.get_all_nodes();
-                            // Pending identity edits (display name / cluster move) made on
-                            // THIS node that their owner hasn't confirmed yet. While such an
-                            // intent is open, a peer that still gossips the OLD value must
-                            // not revert our local view — the operator just made the edit
-                            // and the sweep is still pushing it to the owner. Without this
-                            // guard, moving an OFFLINE node visibly snapped back in the UI
-                            // on the next 10s poll of any peer (fleet audit, 2026-06-11).
-                            let pending_intents = load_identity_intents();
-                            let self_hostname = hostname::get()
-                                .map(|h| h.to_string_lossy().to_string())
-                                .unwrap_or_default();
-                            for known in known_nodes {
-                                // Self-identification in a gossip entry: match EITHER
-                                // by the entry's id (only fires when a node gossips its
-                                // OWN view, which is rare) OR by the entry's `self_id`
-                                // field (populated from the remote's StatusReport.node_id,
-                                // which is the canonical `ws-{uuid}` from /etc/wolfstack/
-                                // node_id). Pre-fix this only checked `known.id` against
-                                // `self_id`, but those live in disjoint ID namespaces —
-                                // `id` is the LOCALLY-ASSIGNED `node-{uuid}` of the
-                                // sending peer, while `self_id` is the global ws-{uuid}.
-                                // The pre-fix condition never matched cross-node, so
-                                // gossip-driven cluster-name adoption was dead code.
-                                // Also recognise a gossip entry carrying one of our OWN
-                                // LAN IPs as self. A node behind a reverse-proxy WAN
-                                // hostname self-identifies by that hostname, so when a peer
-                                // gossips this node's LAN IP back, neither id nor self_id
-                                // match and it was admitted as a foreign, un-pollable "red"
-                                // node named after our own IP (wabil 2026-06-28: main showed
-                                // a red 192.168.1.10, immich a red 192.168.1.4). `local_ips`
-                                // is the cached set already computed at the top of this fn.
-                                let is_self = known.id == cluster.self_id
-                                    || known.self_id.as_deref() == Some(cluster.self_id.as_str())
-                                    || (!known.address.is_empty()
-                                        && local_ips.contains(&known.address));
-                                if is_self {
-                                    // Accept cluster_name updates from gossip (admin may have changed it on another node)
-                                    if let Some(ref gossiped_cluster) = known.cluster_name {
-                                        let current_cluster = {
-                                            let nodes_r = cluster.nodes.read().unwrap();
-                                            nodes_r.get(&cluster.self_id).and_then(|n| n.cluster_name.clone())
-                                        };
-                                        // Case-insensitive: a peer gossiping a different-CASE
-                                        // spelling of our own cluster must NOT flip us — only a
-                                        // genuinely different name (a real rename) is adopted.
-                                        if !cluster_eq(current_cluster.as_deref(), Some(gossiped_cluster.as_str())) {
-
-                                            let mut nodes_w = cluster.nodes.write().unwrap();
-                                            if let Some(n) = nodes_w.get_mut(&cluster.self_id) {
-                                                n.cluster_name = Some(gossiped_cluster.clone());
-                                            }
-                                            drop(nodes_w);
-                                            ClusterState::save_self_cluster_name(gossiped_cluster);
-                                            // Our cluster changed (a rename/move learned via
-                                            // gossip before the direct push landed) — bring this
-                                            // node's local cluster-tagged stores along, same as
-                                            // the /api/agent/cluster-name receiver does. Without
-                                            // this, a member that converges via gossip first
-                                            // would satisfy the admin's intent sweep and the
-                                            // push (which carries the migration) never fires.
-                                            let old_label = current_cluster
-                                                .unwrap_or_else(|| "WolfStack".to_string());
-                                            migrate_local_cluster_tags(&old_label, gossiped_cluster);
-                                        }
-                                    }
-                                    // Same gossip-adoption safety net for the display name an
-                                    // admin set on another node. Only adopt a Some value —
-                                    // an older peer that doesn't know the field gossips None,
-                                    // which must NOT wipe an operator-set name.
-                                    if let Some(ref gossiped_name) = known.display_name {
-                                        // Normalise empty → cleared so memory and the
-                                        // on-disk file (which save_* removes on empty)
-                                        // never disagree and re-assert a stale "".
-                                        let want = if gossiped_name.is_empty() { None } else { Some(gossiped_name.clone()) };
-                                        let current_name = {
-                                            let nodes_r = cluster.nodes.read().unwrap();
-                                            nodes_r.get(&cluster.self_id).and_then(|n| n.display_name.clone())
-                                        };
-                                        if current_name != want {
-                                            let mut nodes_w = cluster.nodes.write().unwrap();
-                                            if let Some(n) = nodes_w.get_mut(&cluster.self_id) {
-                                                n.display_name = want.clone();
-                                            }
-                                            drop(nodes_w);
-                                            ClusterState::save_self_display_name(want.as_deref().unwrap_or(""));
-                                        }
-                                    }
-                                    continue;
+                // Merge tombstones first — so we don't re-add deleted nodes
+                cluster.merge_tombstones(&deleted_ids);
+                cluster.merge_archive_marks(&archive_marks);
+
+                // Merge known_nodes (gossip) — mirror node settings from remote
+                let current_nodes = cluster.get_all_nodes();
+                // Pending identity edits (display name / cluster move) made on
+                // THIS node that their owner hasn't confirmed yet. While such an
+                // intent is open, a peer that still gossips the OLD value must
+                // not revert our local view — the operator just made the edit
+                // and the sweep is still pushing it to the owner. Without this
+                // guard, moving an OFFLINE node visibly snapped back in the UI
+                // on the next 10s poll of any peer (fleet audit, 2026-06-11).
+                let pending_intents = load_identity_intents();
+                let self_hostname = hostname::get()
+                    .map(|h| h.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                for known in known_nodes {
+                    // Self-identification in a gossip entry: match EITHER
+                    // by the entry's id (only fires when a node gossips its
+                    // OWN view, which is rare) OR by the entry's `self_id`
+                    // field (populated from the remote's StatusReport.node_id,
+                    // which is the canonical `ws-{uuid}` from /etc/wolfstack/
+                    // node_id). Pre-fix this only checked `known.id` against
+                    // `self_id`, but those live in disjoint ID namespaces —
+                    // `id` is the LOCALLY-ASSIGNED `node-{uuid}` of the
+                    // sending peer, while `self_id` is the global ws-{uuid}.
+                    // The pre-fix condition never matched cross-node, so
+                    // gossip-driven cluster-name adoption was dead code.
+                    // Also recognise a gossip entry carrying one of our OWN
+                    // LAN IPs as self. A node behind a reverse-proxy WAN
+                    // hostname self-identifies by that hostname, so when a peer
+                    // gossips this node's LAN IP back, neither id nor self_id
+                    // match and it was admitted as a foreign, un-pollable "red"
+                    // node named after our own IP (wabil 2026-06-28: main showed
+                    // a red 192.168.1.10, immich a red 192.168.1.4). `local_ips`
+                    // is the cached set already computed at the top of this fn.
+                    let is_self = known.id == cluster.self_id
+                        || known.self_id.as_deref() == Some(cluster.self_id.as_str())
+                        || (!known.address.is_empty()
+                            && local_ips.contains(&known.address));
+                    if is_self {
+                        // Accept cluster_name updates from gossip (admin may have changed it on another node)
+                        if let Some(ref gossiped_cluster) = known.cluster_name {
+                            let current_cluster = {
+                                let nodes_r = cluster.nodes.read().unwrap();
+                                nodes_r.get(&cluster.self_id).and_then(|n| n.cluster_name.clone())
+                            };
+                            // Case-insensitive: a peer gossiping a different-CASE
+                            // spelling of our own cluster must NOT flip us — only a
+                            // genuinely different name (a real rename) is adopted.
+                            if !cluster_eq(current_cluster.as_deref(), Some(gossiped_cluster.as_str())) {
+
+                                let mut nodes_w = cluster.nodes.write().unwrap();
+                                if let Some(n) = nodes_w.get_mut(&cluster.self_id) {
+                                    n.cluster_name = Some(gossiped_cluster.clone());
                                 }
-                                // Also skip if this is us by hostname+port (gossip may report different address)
-                                if known.node_type == "wolfstack" && known.hostname == self_hostname && known.port == cluster.port {
-                                    continue;
+                                drop(nodes_w);
+                                ClusterState::save_self_cluster_name(gossiped_cluster);
+                                // Our cluster changed (a rename/move learned via
+                                // gossip before the direct push landed) — bring this
+                                // node's local cluster-tagged stores along, same as
+                                // the /api/agent/cluster-name receiver does. Without
+                                // this, a member that converges via gossip first
+                                // would satisfy the admin's intent sweep and the
+                                // push (which carries the migration) never fires.
+                                let old_label = current_cluster
+                                    .unwrap_or_else(|| "WolfStack".to_string());
+                                migrate_local_cluster_tags(&old_label, gossiped_cluster);
+                            }
+                        }
+                        // Same gossip-adoption safety net for the display name an
+                        // admin set on another node. Only adopt a Some value —
+                        // an older peer that doesn't know the field gossips None,
+                        // which must NOT wipe an operator-set name.
+                        if let Some(ref gossiped_name) = known.display_name {
+                            // Normalise empty → cleared so memory and the
+                            // on-disk file (which save_* removes on empty)
+                            // never disagree and re-assert a stale "".
+                            let want = if gossiped_name.is_empty() { None } else { Some(gossiped_name.clone()) };
+                            let current_name = {
+                                let nodes_r = cluster.nodes.read().unwrap();
+                                nodes_r.get(&cluster.self_id).and_then(|n| n.display_name.clone())
+                            };
+                            if current_name != want {
+                                let mut nodes_w = cluster.nodes.write().unwrap();
+                                if let Some(n) = nodes_w.get_mut(&cluster.self_id) {
+                                    n.display_name = want.clone();
                                 }
+                                drop(nodes_w);
+                                ClusterState::save_self_display_name(want.as_deref().unwrap_or(""));
+                            }
+                        }
+                        continue;
+                    }
+                    // Also skip if this is us by hostname+port (gossip may report different address)
+                    if known.node_type == "wolfstack" && known.hostname == self_hostname && known.port == cluster.port {
+                        continue;
+                    }
 
-                                // Skip tombstoned nodes
-                                if cluster.is_tombstoned(&known.id) {
-                                    continue;
-                                }
+                    // Skip tombstoned and archived nodes
+                    if cluster.is_tombstoned(&known.id) || cluster.is_archived(&known.id) {
+                        continue;
+                    }
 
-                                // Check if this node is already known by ID
-                                let existing_by_id = current_nodes.iter().find(|n| n.id == known.id);
-
-                                if let Some(existing) = existing_by_id {
-                                    // While an unconfirmed local intent covers a field, ignore
-                                    // what peers gossip for it and keep our own (already-edited)
-                                    // value — the intent sweep converges the owner, and the
-                                    // intent clears once the owner self-reports the new value.
-                                    let intent = pending_intents.get(&known.id);
-                                    let eff_cluster: Option<String> =
-                                        if intent.is_some_and(|i| i.cluster_name.is_some()) {
-                                            existing.cluster_name.clone()
-                                        } else {
-                                            known.cluster_name.clone()
-                                        };
-                                    let eff_display: Option<String> =
-                                        if intent.is_some_and(|i| i.display_name.is_some()) {
-                                            existing.display_name.clone()
-                                        } else {
-                                            known.display_name.clone()
-                                        };
-                                    // Node already known — update its settings to mirror the source.
-                                    // A wildcard (0.0.0.0) gossiped address doesn't count as a
-                                    // change — it's preserved below — so don't let it trigger a
-                                    // spurious write on its own.
-                                    if (is_usable_addr(&known.address) && existing.address != known.address)
-                                        || existing.hostname != known.hostname
-                                        || existing.port != known.port
-                                        || existing.pve_token != known.pve_token
-                                        || existing.pve_fingerprint != known.pve_fingerprint
-                                        // Case-insensitive: a different-CASE spelling of the same
-                                        // cluster isn't a change (prevents the gossip flip-flop that
-                                        // kept a node bouncing between e.g. "minio" and "Minio").
-                                        || !cluster_eq(existing.cluster_name.as_deref(), eff_cluster.as_deref())
-                                        // Only a Some gossiped display name counts as a change —
-                                        // a None from an older peer must never clear an operator-set name.
-                                        || (eff_display.is_some() && existing.display_name != eff_display)
-                                    {
-
-
-                                        cluster.update_node_settings(
-                                            &known.id,
-                                            Some(known.hostname.clone()),
-                                            // Never overwrite a real, reachable address with a
-                                            // peer's unusable self-entry (0.0.0.0 bind address) —
-                                            // that's what dropped the hub "main" from other nodes.
-                                            if is_usable_addr(&known.address) {
-                                                Some(known.address.clone())
-                                            } else {
-                                                Some(existing.address.clone())
-                                            },
-                                            Some(known.port),
-                                            known.pve_token.clone(),
-                                            if known.pve_fingerprint.is_some() || existing.pve_fingerprint.is_some() {
-                                                Some(known.pve_fingerprint.clone())
-                                            } else {
-                                                None
-                                            },
-                                            eff_cluster,
-                                            None,  // don't propagate login_disabled via gossip
-                                            None,  // don't propagate update_script via gossip
-                                            None,  // site is propagated via StatusReport, not nested gossip
-                                            // Mirror the gossiped display name (None = leave
-                                            // untouched, so an older peer can't wipe it).
-                                            eff_display,
-                                        );
-                                    }
+                    // Check if this node is already known by ID
+                    let existing_by_id = current_nodes.iter().find(|n| n.id == known.id);
+
+                    if let Some(existing) = existing_by_id {
+                        // While an unconfirmed local intent covers a field, ignore
+                        // what peers gossip for it and keep our own (already-edited)
+                        // value — the intent sweep converges the owner, and the
+                        // intent clears once the owner self-reports the new value.
+                        let intent = pending_intents.get(&known.id);
+                        let eff_cluster: Option<String> =
+                            if intent.is_some_and(|i| i.cluster_name.is_some()) {
+                                existing.cluster_name.clone()
+                            } else {
+                                known.cluster_name.clone()
+                            };
+                        let eff_display: Option<String> =
+                            if intent.is_some_and(|i| i.display_name.is_some()) {
+                                existing.display_name.clone()
+                            } else {
+                                known.display_name.clone()
+                            };
+                        // A just re-addressed node keeps its new address even if
+                        // this peer still gossips the old one (readdress pin).
+                        let (known_address, known_port) = readdress::pinned(&known.id)
+                            .unwrap_or_else(|| (known.address.clone(), known.port));
+                        // Node already known — update its settings to mirror the source.
+                        // A wildcard (0.0.0.0) gossiped address doesn't count as a
+                        // change — it's preserved below — so don't let it trigger a
+                        // spurious write on its own.
+                        if (is_usable_addr(&known_address) && existing.address != known_address)
+                            || existing.hostname != known.hostname
+                            || existing.port != known_port
+                            || existing.pve_token != known.pve_token
+                            || existing.pve_fingerprint != known.pve_fingerprint
+                            // Case-insensitive: a different-CASE spelling of the same
+                            // cluster isn't a change (prevents the gossip flip-flop that
+                            // kept a node bouncing between e.g. "minio" and "Minio").
+                            || !cluster_eq(existing.cluster_name.as_deref(), eff_cluster.as_deref())
+                            // Only a Some gossiped display name counts as a change —
+                            // a None from an older peer must never clear an operator-set name.
+                            || (eff_display.is_some() && existing.display_name != eff_display)
+                        {
+
+
+                            cluster.update_node_settings(
+                                &known.id,
+                                Some(known.hostname.clone()),
+                                // Never overwrite a real, reachable address with a
+                                // peer's unusable self-entry (0.0.0.0 bind address) —
+                                // that's what dropped the hub "main" from other nodes.
+                                if is_usable_addr(&known_address) {
+                                    Some(known_address.clone())
                                 } else {
-                                    // Dedup STRICTLY by the stable global self_id first
-                                    // (mirrors merge_member_refs). A multi-homed node is
-                                    // gossiped under its LAN IP, its WolfNet 10.x IP and
-                                    // the v24.27 source-IP-repair variant — three different
-                                    // addresses, ONE self_id. Keying only on address/hostname
-                                    // (as before) let each variant be admitted as a fresh
-                                    // record on successive polls, re-bloating nodes.json
-                                    // between restarts — the same vector as the v24.27 storm.
-                                    let known_sid = known.self_id.as_deref().filter(|s| !s.is_empty());
-                                    let already_known = current_nodes.iter().any(|n| {
-                                        (known_sid.is_some() && n.self_id.as_deref() == known_sid)
-                                        || (n.address == known.address && n.port == known.port && n.pve_node_name == known.pve_node_name)
-                                        || (n.hostname == known.hostname && n.port == known.port && n.node_type == known.node_type)
-                                    });
-                                    if !already_known {
-                                        // Only auto-add nodes on private/local networks
-                                        // Public-IP nodes must be added manually to prevent
-                                        // machines from accidentally switching hosts
-                                        if !is_private_address(&known.address) {
-
-                                            continue;
-                                        }
-
-                                        let mut new_node = known.clone();
-                                        new_node.online = false;
-                                        new_node.is_self = false;
-                                        cluster.update_remote(new_node);
-                                        cluster.save_nodes();
-                                    }
-                                }
-                            }
-                            // Collect subnet routes from this node's wolfnet_ips.
-                            // First IP = host WolfNet address, remaining =
-                            // container/VM IPs. Validate the host entry
-                            // before treating it as a gateway: if `wolfnet0`
-                            // had no IP on the peer at the moment its
-                            // status was built, `wolfnet_used_ips()`
-                            // returns containers WITHOUT a host index 0,
-                            // and the old code would happily map
-                            // container_b → container_a — poisoning
-                            // routes.json on receivers.
-                            let self_cluster = cluster.get_self_cluster_name();
-                            let peer_cluster = node.cluster_name.as_deref().unwrap_or("WolfStack");
-                            if peer_cluster == self_cluster && wolfnet_ips.len() > 1 {
-                                let host_wn_ip = &wolfnet_ips[0];
-                                let host_ok = !host_wn_ip.is_empty()
-                                    && host_wn_ip.parse::<std::net::Ipv4Addr>().is_ok();
-                                if host_ok {
-                                    for container_ip in &wolfnet_ips[1..] {
-                                        if container_ip.is_empty() { continue; }
-                                        if container_ip == host_wn_ip { continue; }
-                                        if container_ip.parse::<std::net::Ipv4Addr>().is_err() { continue; }
-                                        subnet_routes.insert(container_ip.clone(), host_wn_ip.clone());
-                                    }
+                                    Some(existing.address.clone())
+                                },
+                                Some(known_port),
+                                known.pve_token.clone(),
+                                if known.pve_fingerprint.is_some() || existing.pve_fingerprint.is_some() {
+                                    Some(known.pve_fingerprint.clone())
                                 } else {
-                                    tracing::warn!(
-                                        "poll_remote_nodes: peer {} returned {} wolfnet_ips with no valid host IP at [0]; skipping container-route propagation for this peer",
-                                        node.id, wolfnet_ips.len()
-                                    );
-                                }
-                            }
-                            // Cache the peer's host WolfNet IP so future
-                            // build_node_urls calls can insert a
-                            // HTTP-over-WolfNet attempt before falling
-                            // back to plaintext on the public address.
-                            // Same validity guard as above — never cache
-                            // a bogus "host IP" that's actually a
-                            // container address.
-                            if let Some(host_wn_ip) = wolfnet_ips.first() {
-                                if !host_wn_ip.is_empty()
-                                    && host_wn_ip.parse::<std::net::Ipv4Addr>().is_ok() {
-                                    crate::api::record_node_wolfnet_ip(&node.address, host_wn_ip);
-                                }
+                                    None
+                                },
+                                eff_cluster,
+                                None,  // don't propagate login_disabled via gossip
+                                None,  // don't propagate update_script via gossip
+                                None,  // site is propagated via StatusReport, not nested gossip
+                                // Mirror the gossiped display name (None = leave
+                                // untouched, so an older peer can't wipe it).
+                                eff_display,
+                            );
+                        }
+                    } else {
+                        // Dedup STRICTLY by the stable global self_id first
+                        // (mirrors merge_member_refs). A multi-homed node is
+                        // gossiped under its LAN IP, its WolfNet 10.x IP and
+                        // the v24.27 source-IP-repair variant — three different
+                        // addresses, ONE self_id. Keying only on address/hostname
+                        // (as before) let each variant be admitted as a fresh
+                        // record on successive polls, re-bloating nodes.json
+                        // between restarts — the same vector as the v24.27 storm.
+                        let known_sid = known.self_id.as_deref().filter(|s| !s.is_empty());
+                        let already_known = current_nodes.iter().any(|n| {
+                            (known_sid.is_some() && n.self_id.as_deref() == known_sid)
+                            || (n.address == known.address && n.port == known.port && n.pve_node_name == known.pve_node_name)
+                            || (n.hostname == known.hostname && n.port == known.port && n.node_type == known.node_type)
+                        });
+                        if !already_known {
+                            // Only auto-add nodes on private/local networks
+                            // Public-IP nodes must be added manually to prevent
+                            // machines from accidentally switching hosts
+                            if !is_private_address(&known.address) {
+
+                                continue;
                             }
-                            // Only mark this poll as successful when we
-                            // actually parsed a StatusReport. A 200 with
-                            // a non-StatusReport body (corrupt agent, mid-
-                            // restart partial JSON, version mismatch)
-                            // used to also set poll_ok=true and cause
-                            // the route-merge phase to treat the peer as
-                            // authoritative-but-empty, dropping its
-                            // routes.
-                            poll_ok = true;
+
+                            let mut new_node = known.clone();
+                            new_node.online = false;
+                            new_node.is_self = false;
+                            cluster.update_remote(new_node);
+                            cluster.save_nodes();
+                        }
+                    }
+                }
+                // Collect subnet routes from this node's wolfnet_ips.
+                // First IP = host WolfNet address, remaining =
+                // container/VM IPs. Validate the host entry
+                // before treating it as a gateway: if `wolfnet0`
+                // had no IP on the peer at the moment its
+                // status was built, `wolfnet_used_ips()`
+                // returns containers WITHOUT a host index 0,
+                // and the old code would happily map
+                // container_b → container_a — poisoning
+                // routes.json on receivers.
+                let self_cluster = cluster.get_self_cluster_name();
+                let peer_cluster = node.cluster_name.as_deref().unwrap_or("WolfStack");
+                if peer_cluster == self_cluster && wolfnet_ips.len() > 1 {
+                    let host_wn_ip = &wolfnet_ips[0];
+                    let host_ok = !host_wn_ip.is_empty()
+                        && host_wn_ip.parse::<std::net::Ipv4Addr>().is_ok();
+                    if host_ok {
+                        for container_ip in &wolfnet_ips[1..] {
+                            if container_ip.is_empty() { continue; }
+                            if container_ip == host_wn_ip { continue; }
+                            if container_ip.parse::<std::net::Ipv4Addr>().is_err() { continue; }
+                            subnet_routes.insert(container_ip.clone(), host_wn_ip.clone());
                         }
+                    } else {
+                        tracing::warn!(
+                            "poll_remote_nodes: peer {} returned {} wolfnet_ips with no valid host IP at [0]; skipping container-route propagation for this peer",
+                            node.id, wolfnet_ips.len()
+                        );
                     }
-                    if poll_ok { break; }
-                    // Body wasn't a StatusReport — try the next URL in
-                    // the fallback chain rather than declaring success.
-                    continue;
                 }
-                Err(_) => {
-                    continue; // Try next URL
+                // Cache the peer's host WolfNet IP so future
+                // build_node_urls calls can insert a
+                // HTTP-over-WolfNet attempt before falling
+                // back to plaintext on the public address.
+                // Same validity guard as above — never cache
+                // a bogus "host IP" that's actually a
+                // container address.
+                if let Some(host_wn_ip) = wolfnet_ips.first() {
+                    if !host_wn_ip.is_empty()
+                        && host_wn_ip.parse::<std::net::Ipv4Addr>().is_ok() {
+                        crate::api::record_node_wolfnet_ip(&node.address, host_wn_ip);
+                    }
                 }
+                // Only mark this poll as successful when we
+                // actually parsed a StatusReport. A 200 with
+                // a non-StatusReport body (corrupt agent, mid-
+                // restart partial JSON, version mismatch)
+                // used to also set poll_ok=true and cause
+                // the route-merge phase to treat the peer as
+                // authoritative-but-empty, dropping its
+                // routes.
+                poll_ok = true;
             }
         }
 
         if !poll_ok {
-
-            // Increment fail count; keep node online until 2 consecutive failures
-            let mut fails = POLL_FAIL_COUNTS.lock().unwrap();
-            let count = fails.entry(node.id.clone()).or_insert(0);
-            *count += 1;
-            if *count < 2 {
+            // Increment fail count; keep node online until 2 consecutive
+            // failures, and back off once it is clearly down.
+            let mut health = POLL_HEALTH.lock().unwrap();
+            let h = health.entry(node.id.clone()).or_default();
+            h.fails += 1;
+            h.retry_at = poll_backoff(h.fails, rand::random::<f64>() * 2.0 - 1.0)
+                .map(|d| Instant::now() + d);
+            if h.fails < 2 {
                 let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
                 let mut nodes = cluster.nodes.write().unwrap();
                 if let Some(n) = nodes.get_mut(&node.id) {
@@ -2614,10 +3130,11 @@ pub async fn poll_remote_nodes(cluster: Arc<ClusterState>, cluster_secret: Strin
                         // line — between the two, recipients have both
                         // observer and subject context.
                         let (subject, body) = crate::alerting::decorate_local(&raw_subject, &raw_body);
-                        if lifecycle_allowed {
-                            if let Err(e) = crate::ai::send_alert_email(&config, &subject, &body) {
-                                warn!("Failed to send node-offline email for {}: {}", display_name, e);
-                            }
+                        if lifecycle_allowed
+                            && crate::alerting::email_now(&alert_config, crate::alerting::AlertCategory::Lifecycle, &subject, &body)
+                            && let Err(e) = crate::ai::send_alert_email(&config, &subject, &body)
+                        {
+                            warn!("Failed to send node-offline email for {}: {}", display_name, e);
                         }
                         // Send to webhook channels
                         if alert_config.enabled && alert_config.alert_node_offline {
@@ -2648,10 +3165,11 @@ pub async fn poll_remote_nodes(cluster: Arc<ClusterState>, cluster_secret: Strin
                         // Decorate with observer cluster + host — same shape as
                         // every other WolfStack alert.
                         let (subject, body) = crate::alerting::decorate_local(&raw_subject, &raw_body);
-                        if lifecycle_allowed {
-                            if let Err(e) = crate::ai::send_alert_email(&config, &subject, &body) {
-                                warn!("Failed to send node-restored email for {}: {}", display_name, e);
-                            }
+                        if lifecycle_allowed
+                            && crate::alerting::email_now(&alert_config, crate::alerting::AlertCategory::Lifecycle, &subject, &body)
+                            && let Err(e) = crate::ai::send_alert_email(&config, &subject, &body)
+                        {
+                            warn!("Failed to send node-restored email for {}: {}", display_name, e);
                         }
                         // Send to webhook channels
                         if alert_config.enabled && alert_config.alert_node_restored {
@@ -2991,3 +3509,73 @@ mod fleet_rename_tests {
         assert!(!cluster_rename_member_matches(None, "Minio"));
     }
 }
+
+#[cfg(test)]
+mod poll_backoff_tests {
+    use super::*;
+
+    #[test]
+    fn backoff_starts_after_repeated_failures_and_caps() {
+        assert_eq!(poll_backoff(1, 0.0), None);
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER - 1, 0.0), None);
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER, 0.0), Some(Duration::from_secs(20)));
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER + 1, 0.0), Some(Duration::from_secs(40)));
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER + 10, 0.0), Some(Duration::from_secs(300)));
+        assert_eq!(poll_backoff(u32::MAX, 0.0), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn jitter_stays_within_a_quarter() {
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER, 1.0), Some(Duration::from_secs(25)));
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER, -1.0), Some(Duration::from_secs(15)));
+        assert_eq!(poll_backoff(POLL_BACKOFF_AFTER, 7.0), Some(Duration::from_secs(25)));
+    }
+}
+
+#[cfg(test)]
+mod cluster_snapshot_tests {
+    use super::*;
+
+    #[test]
+    fn pve_tokens_round_trip_sealed() {
+        crate::at_rest_crypto::init("wsc_test_cluster_snapshot");
+        let token = "PVEAPIToken=root@pam!wolf=1234".to_string();
+        let sealed = seal_pve_token(token.clone());
+        assert!(crate::at_rest_crypto::is_v2_format(&sealed));
+        assert_eq!(open_pve_token(sealed), Some(token.clone()));
+        // A token written before the key was available stays readable.
+        assert_eq!(open_pve_token(token.clone()), Some(token));
+        // A sealed value that doesn't open is dropped, not handed on garbled.
+        assert_eq!(open_pve_token("v2:AAAA".into()), None);
+    }
+
+    #[test]
+    fn snapshot_fields_default_when_missing() {
+        let s: ClusterSnapshot = serde_json::from_str(r#"{"version":1}"#).unwrap();
+        assert_eq!(s.version, 1);
+        assert!(s.nodes.is_empty() && s.deleted_ids.is_empty());
+        assert!(s.archived.is_empty() && s.archive_marks.is_empty());
+        assert!(s.cluster_name.is_none() && !s.login_disabled);
+    }
+
+    #[test]
+    fn archive_marks_last_write_wins() {
+        let mark = |archived, at| ArchiveMark { archived, at };
+        let mut local = BTreeMap::from([("a".to_string(), mark(true, 100)), ("b".to_string(), mark(false, 100))]);
+        let remote = HashMap::from([
+            ("a".to_string(), mark(false, 90)),  // older restore: ignored
+            ("b".to_string(), mark(true, 100)),  // tie: restored wins
+            ("c".to_string(), mark(true, 50)),   // unknown: taken
+            ("self".to_string(), mark(true, 999)),
+        ]);
+        let mut flipped = merge_archive_marks_into(&mut local, &remote, "self");
+        flipped.sort();
+        assert_eq!(flipped, vec!["c".to_string()]);
+        assert!(local["a"].archived && !local["b"].archived && local["c"].archived);
+        assert!(!local.contains_key("self"));
+
+        let flipped = merge_archive_marks_into(&mut local, &HashMap::from([("a".to_string(), mark(false, 101))]), "self");
+        assert_eq!(flipped, vec!["a".to_string()]);
+        assert_eq!(local["a"], mark(false, 101));
+    }
+}
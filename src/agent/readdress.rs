@@ -0,0 +1,169 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Supervised node re-address.
+//!
+//! A node's address lives in every peer's membership list and WolfNet
+//! config, and gossip mirrors it between peers — so changing it on one
+//! node alone gets reverted by the next poll, or leaves the cluster
+//! polling a dead IP. `POST /api/nodes/{id}/readdress` instead proves the
+//! new address answers as the same node, has every peer prove the same
+//! from its side, and only then commits everywhere (rolling back if a
+//! peer fails to commit).
+//!
+//! Reachability is checked with a challenge, not the cluster secret: the
+//! new address hasn't been verified yet, so the secret must not be sent
+//! to it. The node answers with `proof()` over the caller's nonce, which
+//! only a holder of the cluster secret can compute.
+//!
+//! A committed address is pinned for a while so a peer that still gossips
+//! the old value in the commit window can't write it back.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use super::Node;
+
+const PIN_TTL: Duration = Duration::from_secs(600);
+
+/// Pinned address, port and when it was pinned.
+type Pin = (String, u16, Instant);
+
+static PINS: LazyLock<Mutex<HashMap<String, Pin>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Pin node `id` (local record id) to `address:port` against gossip.
+pub fn pin(id: &str, address: &str, port: u16) {
+    if let Ok(mut pins) = PINS.lock() {
+        pins.retain(|_, (_, _, at)| at.elapsed() < PIN_TTL);
+        pins.insert(id.to_string(), (address.to_string(), port, Instant::now()));
+    }
+}
+
+/// The pinned address for `id`, while the pin is fresh.
+pub fn pinned(id: &str) -> Option<(String, u16)> {
+    let pins = PINS.lock().ok()?;
+    pins.get(id)
+        .filter(|(_, _, at)| at.elapsed() < PIN_TTL)
+        .map(|(a, p, _)| (a.clone(), *p))
+}
+
+/// Answer to a reachability challenge: HMAC of the nonce and the node's
+/// self_id under the cluster secret.
+pub fn proof(secret: &str, nonce: &str, self_id: &str) -> String {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac can take any key length");
+    mac.update(b"wolfstack-readdress:");
+    mac.update(nonce.as_bytes());
+    mac.update(b":");
+    mac.update(self_id.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// A bare host: IP or hostname, no scheme, port, path or whitespace.
+pub fn validate_address(address: &str) -> Result<(), String> {
+    if address.is_empty() {
+        return Err("Enter the node's new address".into());
+    }
+    if address.contains("://") || address.contains('/') || address.chars().any(char::is_whitespace) {
+        return Err("Enter an IP or hostname only — no https:// or path".into());
+    }
+    if address.parse::<std::net::IpAddr>().is_err()
+        && !address.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return Err(format!("'{}' is not an IP address or hostname", address));
+    }
+    if !super::is_usable_addr(address) {
+        return Err(format!("'{}' can't be used as a node address", address));
+    }
+    Ok(())
+}
+
+/// Ids of the local records for the node being re-addressed: matched by
+/// its global self_id, or by the old address and port for records that
+/// predate self_id. Never our own entry.
+pub fn matching_records(nodes: &[Node], self_id: &str, old_address: &str, old_port: u16) -> Vec<String> {
+    nodes.iter()
+        .filter(|n| !n.is_self && n.node_type == "wolfstack")
+        .filter(|n| {
+            n.id == self_id
+                || n.self_id.as_deref() == Some(self_id)
+                || (n.address == old_address && n.port == old_port)
+        })
+        .map(|n| n.id.clone())
+        .collect()
+}
+
+/// Rewrite the host of a WolfNet `host:port` endpoint from `old_host` to
+/// `new_host`, keeping the WolfNet port. None when the endpoint points
+/// somewhere else (a relay, a public name) and must be left alone.
+pub fn replace_endpoint_host(endpoint: &str, old_host: &str, new_host: &str) -> Option<String> {
+    let (host, port) = match endpoint.strip_prefix('[') {
+        Some(rest) => {
+            let (h, tail) = rest.split_once(']')?;
+            (h, tail.strip_prefix(':')?)
+        }
+        None => endpoint.rsplit_once(':')?,
+    };
+    if !host.eq_ignore_ascii_case(old_host) {
+        return None;
+    }
+    Some(format!("{}:{}", crate::netaddr::bracket_host(new_host), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, self_id: Option<&str>, address: &str) -> Node {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "hostname": id, "address": address, "port": 8553,
+            "last_seen": 0, "metrics": null, "components": [],
+            "online": true, "is_self": false, "node_type": "wolfstack",
+            "self_id": self_id,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_by_self_id_or_old_address() {
+        let nodes = vec![
+            node("a", Some("ws-x"), "10.0.0.5"),
+            node("b", None, "10.0.0.5"),
+            node("c", Some("ws-y"), "10.0.0.6"),
+            node("ws-x", None, "10.9.9.9"),
+        ];
+        assert_eq!(matching_records(&nodes, "ws-x", "10.0.0.5", 8553), vec!["a", "b", "ws-x"]);
+        assert!(matching_records(&nodes, "ws-z", "10.0.0.7", 8553).is_empty());
+    }
+
+    #[test]
+    fn endpoint_host_is_rewritten_only_when_it_matches() {
+        assert_eq!(replace_endpoint_host("10.0.0.5:9600", "10.0.0.5", "10.0.1.5").as_deref(), Some("10.0.1.5:9600"));
+        assert_eq!(replace_endpoint_host("[fd00::5]:9600", "fd00::5", "fd00::6").as_deref(), Some("[fd00::6]:9600"));
+        assert_eq!(replace_endpoint_host("relay.example.com:9600", "10.0.0.5", "10.0.1.5"), None);
+        assert_eq!(replace_endpoint_host("10.0.0.5", "10.0.0.5", "10.0.1.5"), None);
+    }
+
+    #[test]
+    fn address_validation() {
+        assert!(validate_address("10.0.1.5").is_ok());
+        assert!(validate_address("node-3.lan").is_ok());
+        assert!(validate_address("").is_err());
+        assert!(validate_address("https://10.0.1.5").is_err());
+        assert!(validate_address("10.0.1.5/24").is_err());
+        assert!(validate_address("0.0.0.0").is_err());
+    }
+
+    #[test]
+    fn proof_binds_nonce_and_identity() {
+        let p = proof("secret", "n1", "ws-x");
+        assert_eq!(p, proof("secret", "n1", "ws-x"));
+        assert_ne!(p, proof("secret", "n2", "ws-x"));
+        assert_ne!(p, proof("secret", "n1", "ws-y"));
+        assert_ne!(p, proof("other", "n1", "ws-x"));
+    }
+}
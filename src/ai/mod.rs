@@ -8,7 +8,8 @@
 //! - Answers questions about WolfStack, WolfNet, WolfDisk, WolfProxy, WolfServe
 //! - Monitors server health hourly and alerts on issues
 //! - Coordinates with agents on other cluster nodes
-//! - Supports Claude and Gemini as LLM backends
+//! - Supports Claude, Gemini, OpenAI/OpenRouter/Cloudflare, and any
+//!   OpenAI-compatible local server (Ollama, vLLM, LM Studio) as LLM backends
 //! - Can execute read-only commands locally and across the cluster
 
 use serde::{Deserialize, Serialize};
@@ -18,6 +19,7 @@ use std::process::Command as StdCommand;
 use std::time::Duration;
 
 pub mod baseline;
+pub mod remediation;
 
 /// Shared HTTP client for the stateless `simple_chat` entry point
 /// (used by plugins and the wolfagents dispatcher). AiAgent owns its
@@ -86,6 +88,15 @@ pub enum HealthOutcome {
 
 fn ai_config_path() -> String { crate::paths::get().ai_config }
 
+/// Credentials sealed at rest in ai-config.json.
+pub const AI_CONFIG_SECRETS: crate::secrets::SecretFields = crate::secrets::SecretFields {
+    label: "ai-config",
+    fields: &[
+        "claude_api_key", "gemini_api_key", "openai_api_key", "openrouter_api_key",
+        "cloudflare_api_key", "local_api_key", "smtp_pass",
+    ],
+};
+
 // ─── Configuration ───
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,6 +138,18 @@ pub struct AiConfig {
     /// Optional API key for the local server (some require it, most don't)
     #[serde(default)]
     pub local_api_key: String,
+    /// Stream chat replies from the local server (`"stream": true`) so the
+    /// dashboard shows tokens as they're generated instead of a spinner for
+    /// the whole multi-minute run on a CPU-bound model. Streamed turns use
+    /// bracket tags rather than the function-calling schema — most local
+    /// servers don't emit tool_calls deltas reliably.
+    #[serde(default)]
+    pub local_stream: bool,
+    /// Guarantee nothing leaves the network: only the `local` provider is
+    /// accepted, its URL must point at a loopback / private / `.local`
+    /// host, and the web research tools (WEBSEARCH / FETCH) are refused.
+    #[serde(default)]
+    pub local_no_egress: bool,
     pub model: String,            // e.g. "claude-sonnet-4-20250514", "gemini-2.0-flash", "llama3", "mistral"
     pub email_enabled: bool,
     pub email_to: String,
@@ -172,6 +195,11 @@ pub struct AiConfig {
     /// forever. Default off; flip on if you see runaway tool use.
     #[serde(default)]
     pub agent_tool_call_limit_enabled: bool,
+    /// Let the agent propose structured remediations (restart a unit,
+    /// restart a container, prune images) that WolfStack executes itself
+    /// once an operator approves them. Default off — see `ai::remediation`.
+    #[serde(default)]
+    pub remediation_actions_enabled: bool,
 }
 
 fn default_agent_max_tool_calls() -> u32 { 6 }
@@ -204,6 +232,8 @@ impl Default for AiConfig {
             cloudflare_api_key: String::new(),
             local_url: String::new(),
             local_api_key: String::new(),
+            local_stream: false,
+            local_no_egress: false,
             model: "claude-sonnet-4-20250514".to_string(),
             email_enabled: false,
             email_to: String::new(),
@@ -218,6 +248,7 @@ impl Default for AiConfig {
             accepted_risks: Vec::new(),
             agent_max_tool_calls: default_agent_max_tool_calls(),
             agent_tool_call_limit_enabled: false,
+            remediation_actions_enabled: false,
         }
     }
 }
@@ -225,7 +256,7 @@ impl Default for AiConfig {
 impl AiConfig {
     pub fn load() -> Self {
         match std::fs::read_to_string(&ai_config_path()) {
-            Ok(content) => Self::parse_with_migrations(&content),
+            Ok(content) => crate::secrets::open_all(Self::parse_with_migrations(&content), &AI_CONFIG_SECRETS),
             Err(_) => Self::default(),
         }
     }
@@ -256,9 +287,10 @@ impl AiConfig {
 
     pub fn save(&self) -> Result<(), String> {
         let path = ai_config_path();
-        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let json = crate::secrets::to_sealed_json(self, &AI_CONFIG_SECRETS)?;
         // 0600 — this file embeds Claude / Gemini / OpenRouter API
-        // keys and the SMTP password for alert emails. Pre-v18.7.30
+        // keys and the SMTP password for alert emails (sealed since the
+        // secrets store, but still nobody else's business). Pre-v18.7.30
         // it was world-readable AND visible via the [READ] AI tool
         // (deny-list didn't cover it either — both closed in v18.7.30).
         crate::paths::write_secure(&path, json).map_err(|e| e.to_string())
@@ -289,6 +321,8 @@ impl AiConfig {
             "cloudflare_api_key": mask_key(&self.cloudflare_api_key),
             "local_url": self.local_url,
             "local_api_key": mask_key(&self.local_api_key),
+            "local_stream": self.local_stream,
+            "local_no_egress": self.local_no_egress,
             "model": self.model,
             "email_enabled": self.email_enabled,
             "email_to": self.email_to,
@@ -302,6 +336,7 @@ impl AiConfig {
             "accepted_risks": self.accepted_risks,
             "agent_max_tool_calls": self.agent_max_tool_calls,
             "agent_tool_call_limit_enabled": self.agent_tool_call_limit_enabled,
+            "remediation_actions_enabled": self.remediation_actions_enabled,
             "has_claude_key": !self.claude_api_key.is_empty(),
             "has_gemini_key": !self.gemini_api_key.is_empty(),
             "has_openai_key": !self.openai_api_key.is_empty(),
@@ -412,6 +447,18 @@ impl AiConfig {
             _ => {}
         }
 
+        if self.local_no_egress {
+            if self.provider != "local" {
+                return Err("No-egress mode only works with the Local provider".to_string());
+            }
+            if !fetch_url_is_internal(&self.local_url) {
+                return Err(format!(
+                    "No-egress mode: {} is not a loopback or private address — use an IP literal, localhost, or a .local name",
+                    self.local_url
+                ));
+            }
+        }
+
         // Validate required API keys
         match self.provider.as_str() {
             "claude" => {
@@ -751,6 +798,15 @@ mod suppress_tests {
 
 // ─── Chat Messages ───
 
+/// Live text of an in-flight streamed reply, shared between `chat()` and
+/// the chat-job poller so the dashboard can render tokens as they arrive.
+pub type ChatPartial = std::sync::Arc<Mutex<String>>;
+
+/// Tool result fed back to the model when it asks for web research while
+/// no-egress mode is on — tells it to carry on from local knowledge.
+const NO_EGRESS_REFUSAL: &str =
+    "Refused: no-egress mode is enabled, internet access is disabled. Answer from local server state and your own knowledge.";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,     // "user" or "assistant"
@@ -785,6 +841,10 @@ pub struct AiAction {
     pub result: String,
     #[serde(default)]
     pub approved_by: String,
+    /// Set for structured remediations (`[REMEDIATE]`). Approving one
+    /// executes it server-side instead of handing `command` to a terminal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<remediation::Remediation>,
 }
 
 // ─── AI Agent State ───
@@ -844,6 +904,7 @@ impl AiAgent {
 
     /// Chat with the AI — multi-turn with command execution and action proposal support.
     /// Returns (response_text, proposed_actions).
+    /// partial, when given, mirrors a streamed reply token-by-token for live display
     /// cluster_nodes is a list of (node_id, hostname, base_url) for remote execution
     /// cluster_secret is used to authenticate with remote nodes via X-WolfStack-Secret
     pub async fn chat(
@@ -857,6 +918,9 @@ impl AiAgent {
         // the chain now leads with HTTPS.
         cluster_nodes: &[(String, String, Vec<String>)],
         cluster_secret: &str,
+        // Receives the reply text as it streams in (local provider with
+        // `local_stream` on). Reset at the start of every round.
+        partial: Option<&ChatPartial>,
     ) -> Result<(String, Vec<AiAction>), String> {
         let config = self.config.lock().unwrap().clone();
         if !config.is_configured() {
@@ -881,11 +945,17 @@ impl AiAgent {
         // the full knowledge base alongside a real conversation. Larger
         // CF models tolerate the full prompt fine — but defaulting to the
         // compact one is the safer choice across the catalogue.
-        let system_prompt = if config.provider == "local" || config.provider == "cloudflare" {
+        let mut system_prompt = if config.provider == "local" || config.provider == "cloudflare" {
             build_compact_system_prompt(system_context)
         } else {
             build_system_prompt(&self.knowledge_base, system_context)
         };
+        if config.remediation_actions_enabled {
+            system_prompt.push_str(&remediation::prompt_section());
+        }
+        if config.local_no_egress {
+            system_prompt.push_str("\n\n## No-Egress Mode\nInternet access is disabled on this server. Do not use WEBSEARCH or FETCH — answer from the server state and your own knowledge.");
+        }
 
         let mut current_msg = user_message.to_string();
         let mut final_response = String::new();
@@ -907,9 +977,14 @@ impl AiAgent {
                     let url = cloudflare_base_url(&config.cloudflare_account_id);
                     call_local_with_tools(&self.client, &url, &config.cloudflare_api_key, &config.model, &system_prompt, &history, &current_msg).await?
                 }
-                "local" => {
-                    call_local_with_tools(&self.client, &config.local_url, &config.local_api_key, &config.model, &system_prompt, &history, &current_msg).await?
-                }
+                "local" => match partial.filter(|_| config.local_stream) {
+                    Some(sink) => {
+                        call_local_stream(&self.client, &config.local_url, &config.local_api_key, &config.model, &system_prompt, &history, &current_msg, sink).await?
+                    }
+                    None => {
+                        call_local_with_tools(&self.client, &config.local_url, &config.local_api_key, &config.model, &system_prompt, &history, &current_msg).await?
+                    }
+                },
                 "claude-cli" => {
                     call_claude_cli(&config.model, &system_prompt, &history, &current_msg).await?
                 }
@@ -1061,6 +1136,8 @@ impl AiAgent {
                 let query = query.trim();
                 let results = if query.is_empty() {
                     "(no query)".to_string()
+                } else if config.local_no_egress {
+                    NO_EGRESS_REFUSAL.to_string()
                 } else {
                     match web_search(&self.client, query).await {
                         Ok(r) => r,
@@ -1090,6 +1167,8 @@ impl AiAgent {
                 let url = url.trim();
                 let fetched = if url.is_empty() {
                     "(no url)".to_string()
+                } else if config.local_no_egress {
+                    NO_EGRESS_REFUSAL.to_string()
                 } else {
                     match web_fetch(&self.client, url).await {
                         Ok(r) => r,
@@ -1166,8 +1245,12 @@ impl AiAgent {
             final_response = last_response;
         }
 
-        // Parse [ACTION] tags from the final response
-        let actions = parse_actions(&final_response);
+        // Parse [ACTION] tags from the final response, plus [REMEDIATE]
+        // tags when the operator has opted in to structured remediations.
+        let mut actions = parse_actions(&final_response);
+        if config.remediation_actions_enabled {
+            actions.extend(remediation::parse_remediations(&final_response));
+        }
 
         // Store pending actions (expire old ones first)
         {
@@ -1184,8 +1267,8 @@ impl AiAgent {
             pa.extend(actions.clone());
         }
 
-        // Strip [ACTION] tags from the displayed response (frontend renders them separately)
-        let clean_response = strip_action_tags(&final_response);
+        // Strip [ACTION] / [REMEDIATE] tags from the displayed response (frontend renders them separately)
+        let clean_response = remediation::strip_remediation_tags(&strip_action_tags(&final_response));
 
         // Store messages in history
         {
@@ -1554,7 +1637,7 @@ impl AiAgent {
 
                     // Parse proposed actions from the response
                     let actions = parse_actions(&response);
-                    let clean_response = strip_action_tags(&response);
+                    let clean_response = remediation::strip_remediation_tags(&strip_action_tags(&response));
 
                     // Store pending actions
                     if !actions.is_empty() {
@@ -1601,19 +1684,7 @@ impl AiAgent {
                         if findings.is_empty() {
                             String::new()
                         } else {
-                            // Prefer the reverse-proxy public URL when
-                            // configured — admins behind Cloudflare /
-                            // nginx need the email link to go to the
-                            // public domain, not the internal host:port.
-                            let base_url = {
-                                let rp = crate::reverse_proxy::ReverseProxyConfig::load().normalised();
-                                if !rp.public_base_url.is_empty() {
-                                    rp.public_base_url
-                                } else {
-                                    let port = crate::ports::PortConfig::load().api;
-                                    format!("https://{}:{}", crate::netaddr::bracket_host(&hostname), port)
-                                }
-                            };
+                            let base_url = dashboard_base_url(&hostname);
                             let mut b = String::from("\n\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
                             b.push_str("SUPPRESS FUTURE ALERTS (\"I know, not going to fix\")\n");
                             b.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
@@ -1670,7 +1741,9 @@ impl AiAgent {
                         // operators see which node fired this AI health alert.
                         let (subject, decorated_body) =
                             crate::alerting::decorate_local(&raw_subject, &email_body);
-                        if let Err(e) = send_alert_email(&config, &subject, &decorated_body) {
+                        if crate::alerting::email_now(&alert_config, crate::alerting::AlertCategory::Posture, &subject, &decorated_body)
+                            && let Err(e) = send_alert_email(&config, &subject, &decorated_body)
+                        {
                             warn!("Failed to send alert email: {}", e);
                         }
                     }
@@ -1739,10 +1812,11 @@ impl AiAgent {
         // delivered the resolved notification.
         let (subject, body) = crate::alerting::decorate_local(&raw_subject, &raw_body);
 
-        if posture_allowed && config.email_enabled && !config.email_to.is_empty() {
-            if let Err(e) = send_alert_email(&config, &subject, &body) {
-                warn!("Failed to send resolved email: {}", e);
-            }
+        if posture_allowed && config.email_enabled && !config.email_to.is_empty()
+            && crate::alerting::email_now(&alert_config, crate::alerting::AlertCategory::Posture, &subject, &body)
+            && let Err(e) = send_alert_email(&config, &subject, &body)
+        {
+            warn!("Failed to send resolved email: {}", e);
         }
 
         if alert_config.enabled && alert_config.has_channels() {
@@ -2084,6 +2158,7 @@ fn parse_actions(response: &str) -> Vec<AiAction> {
                 created_at: now,
                 result: String::new(),
                 approved_by: String::new(),
+                remediation: None,
             });
         }
 
@@ -2528,6 +2603,7 @@ const MAIN_AI_TOOLS: &[&str] = &[
     "security_audit",
     "wolfnote_create",
     "propose_action",
+    "propose_remediation",
 ];
 
 fn openai_tools_schema() -> serde_json::Value {
@@ -2643,6 +2719,23 @@ fn openai_tools_schema() -> serde_json::Value {
                     "required": ["id", "title", "risk", "explain", "target", "command"]
                 }
             }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "propose_remediation",
+                "description": "Propose a structured fix that WolfStack performs itself after the user approves (no shell command). Only honoured when structured remediations are enabled in AI Settings; otherwise use propose_action.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "kind":    { "type": "string", "enum": remediation::REMEDIATION_KINDS, "description": "restart_service, docker_restart, lxc_restart, or docker_prune_images." },
+                        "target":  { "type": "string", "description": "Unit name, container name, or for docker_prune_images \"dangling\" / \"all\"." },
+                        "title":   { "type": "string", "description": "Short title shown on the card." },
+                        "explain": { "type": "string", "description": "Why this fixes the problem." }
+                    },
+                    "required": ["kind", "target", "title", "explain"]
+                }
+            }
         }
     ])
 }
@@ -2686,6 +2779,19 @@ fn tool_call_to_bracket(name: &str, args: &serde_json::Value) -> Option<String>
                 ))
             }
         },
+        "propose_remediation" => {
+            let kind = s("kind"); let target = s("target");
+            if kind.is_empty() || target.is_empty() { None }
+            else {
+                Some(format!(
+                    "[REMEDIATE kind=\"{}\" target=\"{}\" title=\"{}\" explain=\"{}\"][/REMEDIATE]",
+                    kind.replace('"', ""),
+                    target.replace('"', ""),
+                    s("title").replace('"', "'"),
+                    s("explain").replace('"', "'"),
+                ))
+            }
+        },
         _ => None, // Unknown tool name from a hallucinating model — drop it.
     }
 }
@@ -2731,6 +2837,122 @@ async fn call_local_with_tools(
     call_local_inner(client, base_url, api_key, model, system, history, user_msg, true).await
 }
 
+/// Build the chat-completions URL — append /chat/completions if not already present
+fn local_chat_completions_url(base_url: &str) -> String {
+    if base_url.ends_with("/chat/completions") {
+        base_url.to_string()
+    } else {
+        let base = base_url.trim_end_matches('/');
+        if base.ends_with("/v1") {
+            format!("{}/chat/completions", base)
+        } else {
+            format!("{}/v1/chat/completions", base)
+        }
+    }
+}
+
+/// One `data:` line of an OpenAI-compatible server-sent-events stream.
+#[derive(Debug, PartialEq)]
+enum SseEvent {
+    Delta(String),
+    Done,
+    Error(String),
+    Skip,
+}
+
+/// Parse a single SSE line. Comments, blank keep-alives, role-only deltas
+/// and non-`data:` fields are `Skip`; `[DONE]` ends the stream. Servers
+/// report mid-stream failures as `data: {"error": …}` (vLLM, Ollama).
+fn parse_sse_line(line: &str) -> SseEvent {
+    let Some(data) = line.strip_prefix("data:") else { return SseEvent::Skip; };
+    let data = data.trim();
+    if data == "[DONE]" { return SseEvent::Done; }
+    let Ok(v) = serde_json::from_str::<serde_json::Value>(data) else { return SseEvent::Skip; };
+    if let Some(err) = v.get("error") {
+        let msg = err["message"].as_str().or_else(|| err.as_str()).unwrap_or("unknown error");
+        return SseEvent::Error(msg.to_string());
+    }
+    match v["choices"][0]["delta"]["content"].as_str() {
+        Some(text) if !text.is_empty() => SseEvent::Delta(text.to_string()),
+        _ => SseEvent::Skip,
+    }
+}
+
+/// Streaming chat completion against an OpenAI-compatible local server.
+/// Every content delta is appended to `partial` as it arrives; the full
+/// reply is returned once the server sends `[DONE]` or closes the stream.
+/// No tools schema is sent — the model drives tools via bracket tags.
+#[allow(clippy::too_many_arguments)]
+async fn call_local_stream(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    system: &str,
+    history: &[ChatMessage],
+    user_msg: &str,
+    partial: &ChatPartial,
+) -> Result<String, String> {
+    use futures::StreamExt;
+
+    if base_url.is_empty() {
+        return Err("Local AI URL not configured — set it in Settings → AI Agent".to_string());
+    }
+    let url = local_chat_completions_url(base_url);
+
+    let mut messages = vec![serde_json::json!({"role": "system", "content": system})];
+    for msg in history {
+        messages.push(serde_json::json!({"role": msg.role, "content": msg.content}));
+    }
+    messages.push(serde_json::json!({"role": "user", "content": user_msg}));
+
+    let body = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": 4096,
+        "temperature": 0.7,
+        "stream": true,
+    });
+
+    let mut req = client.post(&url).json(&body);
+    if !api_key.is_empty() {
+        req = req.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let resp = req.send().await.map_err(|e| ai_connection_error(&url, &e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("Local AI returned {} — {}", status, text.chars().take(500).collect::<String>()));
+    }
+
+    partial.lock().unwrap().clear();
+    let mut full = String::new();
+    // Raw bytes, not String: a multi-byte UTF-8 char can straddle chunks.
+    let mut buf: Vec<u8> = Vec::new();
+    let mut stream = resp.bytes_stream();
+    'read: while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Local AI stream error: {}", e))?;
+        buf.extend_from_slice(&chunk);
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buf.drain(..=pos).collect();
+            match parse_sse_line(String::from_utf8_lossy(&line).trim()) {
+                SseEvent::Delta(text) => {
+                    full.push_str(&text);
+                    partial.lock().unwrap().push_str(&text);
+                }
+                SseEvent::Done => break 'read,
+                SseEvent::Error(e) => return Err(format!("Local AI stream error: {}", e)),
+                SseEvent::Skip => {}
+            }
+        }
+    }
+
+    if full.trim().is_empty() {
+        return Err("Local AI stream ended without any content".to_string());
+    }
+    Ok(full)
+}
+
 async fn call_local_inner(
     client: &reqwest::Client,
     base_url: &str,
@@ -2745,17 +2967,7 @@ async fn call_local_inner(
         return Err("Local AI URL not configured — set it in Settings → AI Agent".to_string());
     }
 
-    // Build the URL — append /chat/completions if not already present
-    let url = if base_url.ends_with("/chat/completions") {
-        base_url.to_string()
-    } else {
-        let base = base_url.trim_end_matches('/');
-        if base.ends_with("/v1") {
-            format!("{}/chat/completions", base)
-        } else {
-            format!("{}/v1/chat/completions", base)
-        }
-    };
+    let url = local_chat_completions_url(base_url);
 
     let mut messages = vec![
         serde_json::json!({"role": "system", "content": system})
@@ -3124,6 +3336,55 @@ fn parse_one_call(v: &serde_json::Value) -> Option<(String, serde_json::Value)>
     None
 }
 
+#[cfg(test)]
+mod sse_stream_tests {
+    use super::*;
+
+    #[test]
+    fn content_delta_is_extracted() {
+        let line = r#"data: {"id":"x","choices":[{"index":0,"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(parse_sse_line(line), SseEvent::Delta("Hel".into()));
+    }
+
+    #[test]
+    fn role_only_and_keepalive_lines_are_skipped() {
+        assert_eq!(parse_sse_line(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#), SseEvent::Skip);
+        assert_eq!(parse_sse_line(": keep-alive"), SseEvent::Skip);
+        assert_eq!(parse_sse_line(""), SseEvent::Skip);
+        assert_eq!(parse_sse_line("event: ping"), SseEvent::Skip);
+    }
+
+    #[test]
+    fn done_and_errors_terminate() {
+        assert_eq!(parse_sse_line("data: [DONE]"), SseEvent::Done);
+        assert_eq!(
+            parse_sse_line(r#"data: {"error":{"message":"model not found"}}"#),
+            SseEvent::Error("model not found".into())
+        );
+        assert_eq!(parse_sse_line(r#"data: {"error":"oom"}"#), SseEvent::Error("oom".into()));
+    }
+
+    #[test]
+    fn completions_url_is_normalised() {
+        assert_eq!(local_chat_completions_url("http://h:11434"), "http://h:11434/v1/chat/completions");
+        assert_eq!(local_chat_completions_url("http://h:11434/v1/"), "http://h:11434/v1/chat/completions");
+        assert_eq!(local_chat_completions_url("http://h/v1/chat/completions"), "http://h/v1/chat/completions");
+    }
+
+    #[test]
+    fn no_egress_requires_local_provider_on_private_host() {
+        let mut cfg = AiConfig { provider: "local".into(), local_url: "http://192.168.1.20:11434/v1".into(), local_no_egress: true, ..Default::default() };
+        assert!(cfg.validate().is_ok());
+        cfg.local_url = "https://api.example.com/v1".into();
+        assert!(cfg.validate().is_err());
+        cfg.local_url = "http://localhost:11434".into();
+        cfg.provider = "openai".into();
+        cfg.openai_api_key = "sk-test".into();
+        cfg.model = "gpt-4o".into();
+        assert!(cfg.validate().is_err());
+    }
+}
+
 #[cfg(test)]
 mod content_tool_call_tests {
     use super::*;
@@ -3717,8 +3978,14 @@ pub(crate) fn build_smtp_mailer(config: &AiConfig) -> Result<lettre::SmtpTranspo
     Ok(builder.build())
 }
 
+/// Send to the alert inbox. A failure is logged to the mail outbox and,
+/// if transient, queued there for retry (see `mail_outbox`).
 pub fn send_alert_email(config: &AiConfig, subject: &str, body: &str) -> Result<(), String> {
-    send_text_email(config, &config.email_to, "WolfStack AI", subject, body)
+    let result = send_text_email(config, &config.email_to, "WolfStack AI", subject, body);
+    if let Err(e) = &result {
+        crate::mail_outbox::record_failure(subject, body, e);
+    }
+    result
 }
 
 /// Send a plain-text email to a SPECIFIC recipient. Password-reset
@@ -3765,6 +4032,73 @@ pub fn send_html_email(config: &AiConfig, subject: &str, html_body: &str) -> Res
     Ok(())
 }
 
+/// Send the daily report as multipart/alternative — HTML for clients that
+/// render it, with a plain-text part for those that block HTML mail.
+pub fn send_html_email_with_text(config: &AiConfig, subject: &str, html_body: &str, text_body: &str) -> Result<(), String> {
+    use lettre::{Message, Transport};
+    use lettre::message::MultiPart;
+
+    let email = Message::builder()
+        .from(resolve_from_mailbox(config, "WolfStack AI")?)
+        .to(config.email_to.parse().map_err(|e| format!("Email to: {}", e))?)
+        .subject(subject)
+        .multipart(MultiPart::alternative_plain_html(text_body.to_string(), html_body.to_string()))
+        .map_err(|e| format!("Email build: {}", e))?;
+
+    build_smtp_mailer(config)?
+        .send(&email)
+        .map_err(|e| format!("SMTP send: {}", e))?;
+
+    Ok(())
+}
+
+/// Send an HTML + plain-text report to `to` with one file attached (the
+/// monthly inventory CSV).
+#[allow(clippy::too_many_arguments)]
+pub fn send_email_with_attachment(
+    config: &AiConfig,
+    to: &str,
+    subject: &str,
+    html_body: &str,
+    text_body: &str,
+    filename: &str,
+    content_type: &str,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    use lettre::{Message, Transport};
+    use lettre::message::{Attachment, MultiPart, header::ContentType};
+
+    let ct = ContentType::parse(content_type).map_err(|e| format!("Attachment type: {}", e))?;
+    let email = Message::builder()
+        .from(resolve_from_mailbox(config, "WolfStack")?)
+        .to(to.parse().map_err(|e| format!("Email to: {}", e))?)
+        .subject(subject)
+        .multipart(MultiPart::mixed()
+            .multipart(MultiPart::alternative_plain_html(text_body.to_string(), html_body.to_string()))
+            .singlepart(Attachment::new(filename.to_string()).body(data, ct)))
+        .map_err(|e| format!("Email build: {}", e))?;
+
+    build_smtp_mailer(config)?
+        .send(&email)
+        .map_err(|e| format!("SMTP send: {}", e))?;
+
+    Ok(())
+}
+
+/// Base URL for links back to this node's dashboard in emails. Prefers the
+/// reverse-proxy public URL when configured — admins behind Cloudflare /
+/// nginx need the link to go to the public domain, not the internal
+/// host:port.
+pub fn dashboard_base_url(hostname: &str) -> String {
+    let rp = crate::reverse_proxy::ReverseProxyConfig::load().normalised();
+    if !rp.public_base_url.is_empty() {
+        rp.public_base_url
+    } else {
+        let port = crate::ports::PortConfig::load().api;
+        format!("https://{}:{}", crate::netaddr::bracket_host(hostname), port)
+    }
+}
+
 // ─── Metrics Summary Builder ───
 
 /// Get the top processes by CPU and memory usage (for AI analysis)
@@ -4384,7 +4718,8 @@ fn run_security_audit() -> String {
     let sensitive = [
         ("/etc/wolfstack/custom-cluster-secret", "cluster secret (inter-node auth)"),
         ("/etc/wolfstack/cluster-secret", "legacy cluster secret (pre-v11.26.3 leftovers)"),
-        ("/etc/wolfstack/nodes.json", "cluster node list with embedded PVE API tokens"),
+        ("/etc/wolfstack/nodes.json", "cluster node list (older builds embedded PVE API tokens)"),
+        ("/var/lib/wolfstack/cluster.json", "persisted cluster state with encrypted PVE API tokens"),
         ("/etc/wolfstack/join-token", "cluster join token"),
         ("/etc/wolfstack/license.key", "enterprise license key"),
         ("/etc/wolfstack/key.pem", "TLS private key"),
@@ -4541,6 +4876,7 @@ mod tool_calling_tests {
             "security_audit",
             "wolfnote_create",
             "propose_action",
+            "propose_remediation",
         ] {
             assert!(names.contains(required),
                 "tools schema must expose `{}` to function-calling models — \
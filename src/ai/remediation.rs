@@ -0,0 +1,249 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Structured remediation actions for the AI agent.
+//!
+//! The free-form `[ACTION]` path hands the operator a shell command to
+//! run in a terminal. Remediations are the typed alternative: the model
+//! names a *kind* of fix (restart a systemd unit, restart a container,
+//! prune unused Docker images) plus a target, and after a human clicks
+//! Approve WolfStack performs it through the same installer / containers
+//! functions the dashboard buttons use — no shell string is ever built
+//! from model output.
+//!
+//! Opt-in via `AiConfig::remediation_actions_enabled`. When the flag is
+//! off, `[REMEDIATE]` tags are parsed out of the response and discarded
+//! so the model can't smuggle them past the operator as prose.
+
+use serde::{Deserialize, Serialize};
+
+use super::{extract_attr, next_char_boundary, AiAction};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Remediation {
+    /// `systemctl restart <service>` via `installer::restart_service`.
+    RestartService { service: String },
+    /// `containers::docker_restart`.
+    DockerRestart { container: String },
+    /// `containers::lxc_restart`.
+    LxcRestart { container: String },
+    /// `docker image prune` — dangling only unless `all` is set, in which
+    /// case every image not referenced by a container is removed.
+    DockerPruneImages {
+        #[serde(default)]
+        all: bool,
+    },
+}
+
+/// Every kind the parser accepts — also the enum exposed in the
+/// function-calling schema so the two can't drift.
+pub const REMEDIATION_KINDS: &[&str] = &[
+    "restart_service",
+    "docker_restart",
+    "lxc_restart",
+    "docker_prune_images",
+];
+
+/// systemd unit names: letters, digits and `:-_.@\`, optionally with a
+/// `.service` suffix. Rejecting everything else keeps a hallucinated
+/// "nginx; reboot" from ever reaching systemctl (which would refuse it
+/// anyway, but the audit log shouldn't record it as approved).
+fn valid_unit_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 128
+        && !s.starts_with('-')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || ":-_.@\\".contains(c))
+}
+
+/// Docker and LXC both restrict names to `[a-zA-Z0-9][a-zA-Z0-9_.-]*`.
+fn valid_container_name(s: &str) -> bool {
+    crate::validate::ContainerName::parse(s).is_ok()
+}
+
+impl Remediation {
+    /// Build a remediation from the `kind` / `target` pair the model
+    /// supplied, validating the target for that kind.
+    pub fn from_parts(kind: &str, target: &str) -> Result<Self, String> {
+        let target = target.trim();
+        match kind {
+            "restart_service" => {
+                if !valid_unit_name(target) {
+                    return Err(format!("Invalid service name '{}'", target));
+                }
+                Ok(Remediation::RestartService { service: target.to_string() })
+            }
+            "docker_restart" | "lxc_restart" => {
+                if !valid_container_name(target) {
+                    return Err(format!("Invalid container name '{}'", target));
+                }
+                let container = target.to_string();
+                Ok(if kind == "docker_restart" {
+                    Remediation::DockerRestart { container }
+                } else {
+                    Remediation::LxcRestart { container }
+                })
+            }
+            "docker_prune_images" => Ok(Remediation::DockerPruneImages { all: target == "all" }),
+            other => Err(format!("Unknown remediation kind '{}'", other)),
+        }
+    }
+
+    /// Human-readable summary shown on the approval card and written to
+    /// the audit log in place of a shell command.
+    pub fn describe(&self) -> String {
+        match self {
+            Remediation::RestartService { service } => format!("Restart systemd service {}", service),
+            Remediation::DockerRestart { container } => format!("Restart Docker container {}", container),
+            Remediation::LxcRestart { container } => format!("Restart LXC container {}", container),
+            Remediation::DockerPruneImages { all: false } => "Prune dangling Docker images".to_string(),
+            Remediation::DockerPruneImages { all: true } => "Prune all unused Docker images".to_string(),
+        }
+    }
+
+    /// Risk is fixed per kind rather than taken from the model — a
+    /// restart is always low risk, deleting images is always medium.
+    pub fn risk(&self) -> &'static str {
+        match self {
+            Remediation::DockerPruneImages { .. } => "medium",
+            _ => "low",
+        }
+    }
+
+    /// Perform the remediation. Blocking — call from `web::block`.
+    pub fn execute(&self) -> Result<String, String> {
+        match self {
+            Remediation::RestartService { service } => crate::installer::restart_service(service),
+            Remediation::DockerRestart { container } => crate::containers::docker_restart(container)
+                .map(|_| format!("{} restarted", container)),
+            Remediation::LxcRestart { container } => crate::containers::lxc_restart(container),
+            Remediation::DockerPruneImages { all } => crate::containers::docker_prune_images(*all),
+        }
+    }
+}
+
+/// Parse `[REMEDIATE kind="..." target="..." title="..." explain="..."][/REMEDIATE]`
+/// tags into pending actions. Invalid tags are dropped with a warning —
+/// the operator never sees a card that would fail validation on approve.
+pub fn parse_remediations(response: &str) -> Vec<AiAction> {
+    let mut actions = Vec::new();
+    let now = chrono::Utc::now().timestamp();
+    let mut search_from = 0;
+
+    while search_from < response.len() {
+        let start = match response[search_from..].find("[REMEDIATE ") {
+            Some(i) => search_from + i,
+            None => break,
+        };
+        let tag_end = match response[start..].find(']') {
+            Some(i) => start + i,
+            None => break,
+        };
+        let after_tag = next_char_boundary(response, tag_end + 1);
+        let header = &response[start..after_tag];
+        search_from = match response[after_tag..].find("[/REMEDIATE]") {
+            Some(i) => after_tag + i + "[/REMEDIATE]".len(),
+            None => after_tag,
+        };
+
+        let kind = extract_attr(header, "kind").unwrap_or_default();
+        let target = extract_attr(header, "target").unwrap_or_default();
+        let remediation = match Remediation::from_parts(&kind, &target) {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("AI proposed an invalid remediation: {}", e);
+                continue;
+            }
+        };
+        actions.push(AiAction {
+            id: format!("rem-{}", &uuid::Uuid::new_v4().to_string()[..16]),
+            title: extract_attr(header, "title").unwrap_or_else(|| remediation.describe()),
+            command: remediation.describe(),
+            risk: remediation.risk().to_string(),
+            explanation: extract_attr(header, "explain").unwrap_or_default(),
+            node_target: "local".to_string(),
+            status: "pending".to_string(),
+            created_at: now,
+            result: String::new(),
+            approved_by: String::new(),
+            remediation: Some(remediation),
+        });
+    }
+
+    actions
+}
+
+/// Strip `[REMEDIATE ...][/REMEDIATE]` tags from displayed text.
+pub fn strip_remediation_tags(text: &str) -> String {
+    let mut result = text.to_string();
+    while let Some(start) = result.find("[REMEDIATE ") {
+        let end = match result[start..].find("[/REMEDIATE]") {
+            Some(i) => start + i + "[/REMEDIATE]".len(),
+            None => match result[start..].find(']') {
+                Some(i) => start + i + 1,
+                None => break,
+            },
+        };
+        result.replace_range(start..end, "");
+    }
+    result
+}
+
+/// System-prompt section appended when remediations are enabled.
+pub fn prompt_section() -> String {
+    format!(
+        "\n\n## Structured Remediations\n\
+         For common fixes, prefer a REMEDIATE tag over an ACTION. WolfStack performs these itself after the user approves — no shell command is run.\n\
+         Format: `[REMEDIATE kind=\"KIND\" target=\"TARGET\" title=\"Short Title\" explain=\"Why this fixes it\"][/REMEDIATE]`\n\
+         Kinds: {}\n\
+         - restart_service: target is the systemd unit name (e.g. nginx)\n\
+         - docker_restart / lxc_restart: target is the container name on THIS node\n\
+         - docker_prune_images: target is \"dangling\" (untagged only) or \"all\" (every image not used by a container)\n\
+         Remediations always run on the local node.",
+        REMEDIATION_KINDS.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_tags_and_fixes_risk_per_kind() {
+        let text = "Nginx is down.\n[REMEDIATE kind=\"restart_service\" target=\"nginx\" title=\"Restart Nginx\" explain=\"unit failed\"][/REMEDIATE]\n\
+                    [REMEDIATE kind=\"docker_prune_images\" target=\"all\"][/REMEDIATE]";
+        let actions = parse_remediations(text);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].remediation, Some(Remediation::RestartService { service: "nginx".into() }));
+        assert_eq!(actions[0].title, "Restart Nginx");
+        assert_eq!(actions[0].risk, "low");
+        assert_eq!(actions[1].remediation, Some(Remediation::DockerPruneImages { all: true }));
+        assert_eq!(actions[1].risk, "medium");
+        assert!(actions.iter().all(|a| a.node_target == "local" && a.status == "pending"));
+    }
+
+    #[test]
+    fn rejects_injection_in_targets() {
+        assert!(Remediation::from_parts("restart_service", "nginx; reboot").is_err());
+        assert!(Remediation::from_parts("restart_service", "--now").is_err());
+        assert!(Remediation::from_parts("docker_restart", "web $(id)").is_err());
+        assert!(Remediation::from_parts("lxc_restart", "-n").is_err());
+        assert!(Remediation::from_parts("rm_rf", "/").is_err());
+        let text = "[REMEDIATE kind=\"restart_service\" target=\"a|b\"][/REMEDIATE]";
+        assert!(parse_remediations(text).is_empty());
+    }
+
+    #[test]
+    fn accepts_realistic_names() {
+        assert!(Remediation::from_parts("restart_service", "systemd-resolved.service").is_ok());
+        assert!(Remediation::from_parts("restart_service", "getty@tty1").is_ok());
+        assert!(Remediation::from_parts("docker_restart", "my_app-1.web").is_ok());
+    }
+
+    #[test]
+    fn strip_removes_tags_including_unterminated() {
+        let text = "before [REMEDIATE kind=\"x\" target=\"y\"][/REMEDIATE] after [REMEDIATE kind=\"z\"] end";
+        assert_eq!(strip_remediation_tags(text), "before  after  end");
+    }
+}
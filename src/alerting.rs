@@ -303,6 +303,18 @@ pub struct AlertConfig {
     #[serde(default = "default_container_mem_threshold")]
     pub container_memory_threshold: f32,  // percentage (0-100)
 
+    // ── WolfNet tunnels ──
+    /// Alert when a WolfNet peer that was up hasn't completed a
+    /// handshake in this many minutes. 0 = off.
+    #[serde(default = "default_wolfnet_stale_minutes")]
+    pub wolfnet_stale_minutes: u64,
+
+    // ── Intrusion attempts ──
+    /// Alert when this many failed logins (every surface, this node)
+    /// land within ten minutes. 0 = off.
+    #[serde(default = "default_attack_alert_threshold")]
+    pub attack_alert_threshold: u64,
+
     // ── Check interval ──
     #[serde(default = "default_check_interval")]
     pub check_interval_secs: u64,  // how often to check thresholds (seconds)
@@ -371,6 +383,22 @@ pub struct AlertConfig {
     /// flip this off without untangling individual per-rule toggles.
     #[serde(default = "default_true")]
     pub recovery_notifications: bool,
+
+    /// Per-channel quiet hours, keyed by channel (see [`CHANNELS`]).
+    /// Applies on top of the cluster-wide `quiet_hours` — the on-call
+    /// phone (ntfy/Telegram) can go quiet overnight while Slack keeps
+    /// getting everything. With `digest` set, suppressed alerts are
+    /// held and sent as one digest when the window closes.
+    #[serde(default)]
+    pub channel_quiet_hours: HashMap<String, QuietHours>,
+
+    /// Per-category digest interval in minutes, keyed by
+    /// `AlertCategory::as_str`. A listed category is batched and sent
+    /// as one digest per interval instead of one notification per
+    /// alert; the alerting loop sends them (`flush_digests`). Absent
+    /// or 0 = immediate. Compromise is never batched.
+    #[serde(default)]
+    pub digest_minutes: HashMap<String, u64>,
 }
 
 /// Quiet-hours window. When `enabled` is true and the current time
@@ -403,6 +431,11 @@ pub struct QuietHours {
     /// always fire.
     #[serde(default)]
     pub suppress_categories: Vec<String>,
+    /// Hold suppressed alerts and send them as one digest when the
+    /// window closes, rather than dropping them. Off for windows saved
+    /// before digests existed, so upgrades keep the old behaviour.
+    #[serde(default)]
+    pub digest: bool,
 }
 
 fn default_quiet_start() -> String { "22:00".into() }
@@ -419,6 +452,7 @@ impl Default for QuietHours {
             timezone: default_timezone(),
             days_of_week: default_quiet_days(),
             suppress_categories: Vec::new(),
+            digest: false,
         }
     }
 }
@@ -485,6 +519,8 @@ fn default_mem_threshold() -> f32 { 90.0 }
 fn default_disk_threshold() -> f32 { 90.0 }
 fn default_true() -> bool { true }
 fn default_container_mem_threshold() -> f32 { 90.0 }
+fn default_wolfnet_stale_minutes() -> u64 { 5 }
+fn default_attack_alert_threshold() -> u64 { 100 }
 fn default_check_interval() -> u64 { 60 }
 fn default_security_scan_interval() -> u64 { 4 * 60 * 60 }
 fn default_cooldown_secs() -> u64 { 900 }
@@ -525,6 +561,8 @@ impl Default for AlertConfig {
             alert_disk: true,
             alert_containers: true,
             container_memory_threshold: 90.0,
+            wolfnet_stale_minutes: default_wolfnet_stale_minutes(),
+            attack_alert_threshold: default_attack_alert_threshold(),
             check_interval_secs: 60,
             security_scan_interval_secs: 4 * 60 * 60,
             alert_verbosity: AlertVerbosity::Simple,
@@ -534,6 +572,8 @@ impl Default for AlertConfig {
             grouping_window_secs: 0,
             grouping_strategy: GroupingStrategy::ByNode,
             recovery_notifications: true,
+            channel_quiet_hours: HashMap::new(),
+            digest_minutes: HashMap::new(),
             discord_bot_token: String::new(),
             telegram_receiver_enabled: false,
             twilio_account_sid: String::new(),
@@ -608,6 +648,9 @@ impl AlertConfig {
         if category == AlertCategory::Compromise { return true; }
         let Some(q) = self.quiet_hours.as_ref() else { return true; };
         if !q.enabled { return true; }
+        // A digesting window holds alerts per channel (`delivery_at`)
+        // instead of dropping them here.
+        if q.digest { return true; }
         if !q.suppress_categories.iter().any(|s| s == category.as_str()) {
             return true;
         }
@@ -649,6 +692,8 @@ impl AlertConfig {
             "alert_disk": self.alert_disk,
             "alert_containers": self.alert_containers,
             "container_memory_threshold": self.container_memory_threshold,
+            "wolfnet_stale_minutes": self.wolfnet_stale_minutes,
+            "attack_alert_threshold": self.attack_alert_threshold,
             "check_interval_secs": self.check_interval_secs,
             "security_scan_interval_secs": self.security_scan_interval_secs,
             "alert_verbosity": match self.alert_verbosity {
@@ -664,6 +709,8 @@ impl AlertConfig {
                 GroupingStrategy::ByGuest => "by_guest",
             },
             "recovery_notifications": self.recovery_notifications,
+            "channel_quiet_hours": self.channel_quiet_hours,
+            "digest_minutes": self.digest_minutes,
             "has_discord_bot": !self.discord_bot_token.is_empty(),
             "telegram_receiver_enabled": self.telegram_receiver_enabled,
             "twilio_account_sid": self.twilio_account_sid,
@@ -800,7 +847,9 @@ pub async fn send_node_alert(
     // Email — sync function, run on the blocking pool so we don't
     // stall the async runtime over SMTP handshakes.
     let email_cfg = crate::ai::AiConfig::load();
-    if email_cfg.email_enabled && !email_cfg.email_to.is_empty() {
+    if email_cfg.email_enabled && !email_cfg.email_to.is_empty()
+        && email_now(&alert_cfg, category, &full_title, &full_body)
+    {
         let t = full_title.clone();
         let b = full_body.clone();
         tokio::task::spawn_blocking(move || {
@@ -895,6 +944,15 @@ pub async fn send_alert(config: &AlertConfig, category: AlertCategory, title: &s
     {
         return;
     }
+    // Per-channel quiet hours and digest intervals — a channel may hold
+    // this alert for a digest while the others send it now.
+    let now_channels: Vec<&'static str> = push_channels(config)
+        .into_iter()
+        .filter(|channel| route(config, channel, category, title, message))
+        .collect();
+    if now_channels.is_empty() {
+        return;
+    }
     // Hourly rate-limit. Compromise alerts bypass the cap — we never
     // want a flood of failed-auth events to silence a "miner running"
     // signal. Same invariant as Simple verbosity + quiet-hours
@@ -910,7 +968,11 @@ pub async fn send_alert(config: &AlertConfig, category: AlertCategory, title: &s
     }
 
     let priority: u8 = if category == AlertCategory::Compromise { 5 } else { 3 };
-    dispatch_to_channels(config, title, message, priority).await;
+    for channel in now_channels {
+        if let Err(e) = send_to_channel(config, channel, title, message, priority).await {
+            warn!("{} alert failed: {}", channel, e);
+        }
+    }
 }
 
 /// Fan a title/body out to every configured push channel (Discord,
@@ -918,25 +980,214 @@ pub async fn send_alert(config: &AlertConfig, category: AlertCategory, title: &s
 /// rate-limit gating; callers decide whether to send. `ntfy_priority`
 /// is the 1–5 ntfy scale.
 async fn dispatch_to_channels(config: &AlertConfig, title: &str, message: &str, ntfy_priority: u8) {
-    if !config.discord_webhook.is_empty()
-        && let Err(e) = send_discord(&config.discord_webhook, title, message).await
-    {
-        warn!("Discord alert failed: {}", e);
+    for channel in push_channels(config) {
+        if let Err(e) = send_to_channel(config, channel, title, message, ntfy_priority).await {
+            warn!("{} alert failed: {}", channel, e);
+        }
     }
-    if !config.slack_webhook.is_empty()
-        && let Err(e) = send_slack(&config.slack_webhook, title, message).await
-    {
-        warn!("Slack alert failed: {}", e);
+}
+
+/// The push channels that are configured, by [`CHANNELS`] name.
+fn push_channels(config: &AlertConfig) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    if !config.discord_webhook.is_empty() { out.push("discord"); }
+    if !config.slack_webhook.is_empty() { out.push("slack"); }
+    if !config.telegram_bot_token.is_empty() && !config.telegram_chat_id.is_empty() { out.push("telegram"); }
+    if !config.ntfy_topic.is_empty() { out.push("ntfy"); }
+    out
+}
+
+async fn send_to_channel(config: &AlertConfig, channel: &str, title: &str, message: &str, ntfy_priority: u8) -> Result<(), String> {
+    match channel {
+        "discord" => send_discord(&config.discord_webhook, title, message).await,
+        "slack" => send_slack(&config.slack_webhook, title, message).await,
+        "telegram" => send_telegram(&config.telegram_bot_token, &config.telegram_chat_id, title, message).await,
+        "ntfy" => send_ntfy(&config.ntfy_server, &config.ntfy_topic, &config.ntfy_token, title, message, ntfy_priority).await,
+        other => Err(format!("unknown channel '{}'", other)),
     }
-    if !config.telegram_bot_token.is_empty() && !config.telegram_chat_id.is_empty()
-        && let Err(e) = send_telegram(&config.telegram_bot_token, &config.telegram_chat_id, title, message).await
-    {
-        warn!("Telegram alert failed: {}", e);
+}
+
+// ═══════════════════════════════════════════════
+// ─── Digests: quiet-hours hold-back + batching ───
+// ═══════════════════════════════════════════════
+
+/// Notification channels, as keyed in `channel_quiet_hours`: the four
+/// push channels plus email.
+pub const CHANNELS: [&str; 5] = ["discord", "slack", "telegram", "ntfy", "email"];
+
+/// What happens to one alert on one channel right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Send,
+    /// Held for the channel's next digest.
+    Hold,
+    /// Silenced by a quiet window that doesn't digest.
+    Drop,
+}
+
+/// An alert waiting for its channel's next digest.
+struct HeldAlert {
+    channel: &'static str,
+    category: AlertCategory,
+    title: String,
+    body: String,
+    at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Alerts held for digests. In memory like `ALERT_HISTORY` — a restart
+/// drops whatever is pending. Capped (oldest out) so a long quiet
+/// window over an alert storm can't grow without bound.
+static HELD: std::sync::LazyLock<std::sync::Mutex<Vec<HeldAlert>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(Vec::new()));
+const HELD_CAP: usize = 1000;
+
+/// When the open interval batch for a (channel, category) is due. Set
+/// by the batch's first alert, cleared when the digest goes out.
+static BATCH_DUE: std::sync::LazyLock<std::sync::Mutex<HashMap<(&'static str, &'static str), Instant>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+impl AlertConfig {
+    /// Digest interval for a category, if it's batched.
+    pub fn digest_interval(&self, category: AlertCategory) -> Option<std::time::Duration> {
+        if category == AlertCategory::Compromise { return None; }
+        self.digest_minutes.get(category.as_str())
+            .copied()
+            .filter(|m| *m > 0)
+            .map(|m| std::time::Duration::from_secs(m * 60))
     }
-    if !config.ntfy_topic.is_empty()
-        && let Err(e) = send_ntfy(&config.ntfy_server, &config.ntfy_topic, &config.ntfy_token, title, message, ntfy_priority).await
-    {
-        warn!("ntfy alert failed: {}", e);
+
+    /// Whether a quiet window covers `category` on `channel` at `now`:
+    /// the channel's own window, plus the cluster-wide one when that
+    /// digests (a dropping cluster-wide window already stopped the
+    /// alert in `allows_at`). A dropping window wins over a digesting one.
+    fn quiet_delivery(&self, channel: &str, category: AlertCategory, now: chrono::DateTime<chrono::Utc>) -> Option<Delivery> {
+        let windows = self.channel_quiet_hours.get(channel).into_iter()
+            .chain(self.quiet_hours.as_ref().filter(|q| q.digest))
+            .filter(|q| q.enabled && q.suppress_categories.iter().any(|c| c == category.as_str()));
+        let mut quiet = None;
+        for q in windows {
+            if q.includes(now) == Some(true) {
+                if !q.digest { return Some(Delivery::Drop); }
+                quiet = Some(Delivery::Hold);
+            }
+        }
+        quiet
+    }
+
+    /// How an alert of `category` reaches `channel` at `now`. Compromise
+    /// always sends; otherwise a quiet window holds or drops it, and a
+    /// digest interval holds it.
+    pub fn delivery_at(&self, channel: &str, category: AlertCategory, now: chrono::DateTime<chrono::Utc>) -> Delivery {
+        if category == AlertCategory::Compromise { return Delivery::Send; }
+        if let Some(d) = self.quiet_delivery(channel, category, now) { return d; }
+        if self.digest_interval(category).is_some() { return Delivery::Hold; }
+        Delivery::Send
+    }
+}
+
+/// Decide one alert for one channel, holding it if need be. True when
+/// it should be sent now.
+fn route(config: &AlertConfig, channel: &'static str, category: AlertCategory, title: &str, body: &str) -> bool {
+    match config.delivery_at(channel, category, chrono::Utc::now()) {
+        Delivery::Send => true,
+        Delivery::Drop => false,
+        Delivery::Hold => {
+            if let Some(every) = config.digest_interval(category) {
+                BATCH_DUE.lock().unwrap()
+                    .entry((channel, category.as_str()))
+                    .or_insert_with(|| Instant::now() + every);
+            }
+            let mut held = HELD.lock().unwrap();
+            if held.len() >= HELD_CAP {
+                held.remove(0);
+            }
+            held.push(HeldAlert {
+                channel,
+                category,
+                title: title.to_string(),
+                body: body.to_string(),
+                at: chrono::Utc::now(),
+            });
+            false
+        }
+    }
+}
+
+/// The email side of `send_alert`'s per-channel routing, for the call
+/// sites that mail alerts themselves. True when the email should go out
+/// now; false when it was held for a digest or silenced by the email
+/// channel's quiet hours.
+pub fn email_now(config: &AlertConfig, category: AlertCategory, subject: &str, body: &str) -> bool {
+    route(config, "email", category, subject, body)
+}
+
+/// Number of alerts waiting for a digest (Alerts → Schedule shows it).
+pub fn held_count() -> usize {
+    HELD.lock().unwrap().len()
+}
+
+/// A held alert is ready once no quiet window covers it and its
+/// interval batch (if any) is due.
+fn digest_ready(config: &AlertConfig, alert: &HeldAlert, now: chrono::DateTime<chrono::Utc>, due: &HashMap<(&'static str, &'static str), Instant>) -> bool {
+    config.quiet_delivery(alert.channel, alert.category, now).is_none()
+        && due.get(&(alert.channel, alert.category.as_str())).is_none_or(|d| Instant::now() >= *d)
+}
+
+/// Title and body of one channel's digest: a line per held alert,
+/// oldest first, with the usual cluster/host decoration.
+fn digest_message(alerts: &[HeldAlert]) -> (String, String) {
+    let title = format!(
+        "Alert digest: {} notification{}",
+        alerts.len(), if alerts.len() == 1 { "" } else { "s" },
+    );
+    let mut body = String::new();
+    for a in alerts {
+        body.push_str(&format!(
+            "{}  [{}]  {}\n",
+            a.at.format("%Y-%m-%d %H:%M UTC"), a.category.as_str(), a.title,
+        ));
+    }
+    body.push_str("\nHeld by quiet hours or a digest interval — see Alerts → Schedule.");
+    decorate_local(&title, &body)
+}
+
+/// Send every digest that's due: alerts held by a quiet window once it
+/// has closed, interval batches once their interval is up. One message
+/// per channel. Called every minute from a background task.
+pub async fn flush_digests() {
+    let config = AlertConfig::load();
+    let now = chrono::Utc::now();
+    let ready: Vec<HeldAlert> = {
+        let mut held = HELD.lock().unwrap();
+        if held.is_empty() { return; }
+        let mut due = BATCH_DUE.lock().unwrap();
+        let (ready, keep): (Vec<_>, Vec<_>) = held.drain(..).partition(|a| digest_ready(&config, a, now, &due));
+        *held = keep;
+        for a in &ready {
+            due.remove(&(a.channel, a.category.as_str()));
+        }
+        ready
+    };
+    let mut by_channel: std::collections::BTreeMap<&'static str, Vec<HeldAlert>> = Default::default();
+    for a in ready {
+        by_channel.entry(a.channel).or_default().push(a);
+    }
+    for (channel, alerts) in by_channel {
+        let (title, body) = digest_message(&alerts);
+        if channel == "email" {
+            let email_cfg = crate::ai::AiConfig::load();
+            if email_cfg.email_enabled && !email_cfg.email_to.is_empty() {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = crate::ai::send_alert_email(&email_cfg, &title, &body) {
+                        warn!("digest email failed: {}", e);
+                    }
+                }).await.ok();
+            }
+        } else if config.enabled && push_channels(&config).contains(&channel)
+            && let Err(e) = send_to_channel(&config, channel, &title, &body, 3).await
+        {
+            warn!("{} digest failed: {}", channel, e);
+        }
     }
 }
 
@@ -1390,6 +1641,7 @@ mod tests {
             suppress_categories: vec![
                 "threshold".into(), "lifecycle".into(), "compromise".into(),
             ],
+            digest: false,
         });
 
         // Inside the window, Tuesday 13:00 Berlin == 11:00 UTC.
@@ -1460,6 +1712,7 @@ mod tests {
             timezone: "Not/A/Real/Tz".into(),
             days_of_week: 0x7F,
             suppress_categories: vec!["threshold".into()],
+            digest: false,
         };
         // includes() returns None on bad tz; allows_at maps None to "allowed".
         assert_eq!(q.includes(chrono::Utc::now()), None);
@@ -1471,4 +1724,82 @@ mod tests {
         assert!(cfg.allows_at(AlertCategory::Threshold, chrono::Utc::now()),
             "bad timezone must not silently swallow alerts");
     }
+
+    /// Per-channel quiet hours: a digesting window holds, a plain one
+    /// drops, other channels are untouched, and Compromise always sends.
+    #[test]
+    fn channel_quiet_hours_hold_or_drop_per_channel() {
+        let mut cfg = AlertConfig::default();
+        let window = |digest| QuietHours {
+            enabled: true,
+            start_hhmm: "22:00".into(),
+            end_hhmm: "06:00".into(),
+            timezone: "UTC".into(),
+            days_of_week: 0x7F,
+            suppress_categories: vec!["threshold".into()],
+            digest,
+        };
+        cfg.channel_quiet_hours.insert("ntfy".into(), window(true));
+        cfg.channel_quiet_hours.insert("email".into(), window(false));
+        let night = chrono::DateTime::parse_from_rfc3339("2026-05-19T03:00:00Z")
+            .unwrap().with_timezone(&chrono::Utc);
+        let day = chrono::DateTime::parse_from_rfc3339("2026-05-19T12:00:00Z")
+            .unwrap().with_timezone(&chrono::Utc);
+
+        assert_eq!(cfg.delivery_at("ntfy", AlertCategory::Threshold, night), Delivery::Hold);
+        assert_eq!(cfg.delivery_at("email", AlertCategory::Threshold, night), Delivery::Drop);
+        assert_eq!(cfg.delivery_at("slack", AlertCategory::Threshold, night), Delivery::Send);
+        assert_eq!(cfg.delivery_at("ntfy", AlertCategory::Lifecycle, night), Delivery::Send, "not suppressed");
+        assert_eq!(cfg.delivery_at("ntfy", AlertCategory::Compromise, night), Delivery::Send);
+        assert_eq!(cfg.delivery_at("ntfy", AlertCategory::Threshold, day), Delivery::Send);
+    }
+
+    /// A digesting cluster-wide window no longer drops in `allows_at` —
+    /// it holds on every channel instead.
+    #[test]
+    fn digesting_global_window_holds_instead_of_dropping() {
+        let cfg = AlertConfig {
+            alert_verbosity: AlertVerbosity::Verbose,
+            quiet_hours: Some(QuietHours {
+                enabled: true,
+                start_hhmm: "00:00".into(),
+                end_hhmm: "23:59".into(),
+                suppress_categories: vec!["posture".into()],
+                digest: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let now = chrono::DateTime::parse_from_rfc3339("2026-05-19T12:00:00Z")
+            .unwrap().with_timezone(&chrono::Utc);
+        assert!(cfg.allows_at(AlertCategory::Posture, now));
+        assert_eq!(cfg.delivery_at("discord", AlertCategory::Posture, now), Delivery::Hold);
+        assert_eq!(cfg.delivery_at("email", AlertCategory::Posture, now), Delivery::Hold);
+    }
+
+    /// Digest intervals batch only the listed categories, never Compromise.
+    #[test]
+    fn digest_interval_batches_listed_categories() {
+        let mut cfg = AlertConfig::default();
+        cfg.digest_minutes.insert("brute_force".into(), 60);
+        cfg.digest_minutes.insert("compromise".into(), 60);
+        cfg.digest_minutes.insert("posture".into(), 0);
+        let now = chrono::Utc::now();
+        assert_eq!(cfg.delivery_at("slack", AlertCategory::BruteForce, now), Delivery::Hold);
+        assert_eq!(cfg.delivery_at("slack", AlertCategory::Compromise, now), Delivery::Send);
+        assert_eq!(cfg.delivery_at("slack", AlertCategory::Posture, now), Delivery::Send);
+        assert_eq!(cfg.digest_interval(AlertCategory::BruteForce), Some(std::time::Duration::from_secs(3600)));
+
+        let held = HeldAlert {
+            channel: "slack",
+            category: AlertCategory::BruteForce,
+            title: "x".into(),
+            body: String::new(),
+            at: now,
+        };
+        let mut due = HashMap::new();
+        assert!(digest_ready(&cfg, &held, now, &due), "no open batch → ready");
+        due.insert(("slack", "brute_force"), Instant::now() + std::time::Duration::from_secs(60));
+        assert!(!digest_ready(&cfg, &held, now, &due), "batch not due yet");
+    }
 }
@@ -1395,9 +1395,12 @@ pub async fn container_vnc_ws(
     state: web::Data<AppState>,
     path: web::Path<(String, String)>,
 ) -> Result<HttpResponse, Error> {
-    if let Err(resp) = super::require_auth(&req, &state) { return Ok(resp); }
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
     let (runtime, name) = path.into_inner();
     if let Err(resp) = validate_target(&runtime, &name) { return Ok(resp); }
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, &runtime, &name) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
 
     if !load_config().contains_key(&config_key(&runtime, &name)) {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
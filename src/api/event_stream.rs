@@ -0,0 +1,120 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! `/api/ws/events` — the dashboard's live event stream.
+//!
+//! One WebSocket per browser tab carrying this node's live feed
+//! (`crate::events::subscribe_live`): node online/offline, container
+//! lifecycle, alerts and job progress, each a JSON `Event` text frame.
+//! The UI reacts to these instead of re-polling the endpoints behind
+//! them; metrics still come from the regular poll.
+//!
+//! A tab that falls behind gets `{"event":"lagged","payload":{"missed":N}}`
+//! and should reload what it shows. The server pings every 30 s and drops
+//! a client that hasn't answered anything for 90 s.
+
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use futures::StreamExt;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::AppState;
+use crate::events::Event;
+
+const PING_EVERY: Duration = Duration::from_secs(30);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// GET /api/ws/events — WebSocket stream of node, container, alert and job events
+pub async fn ws_events(req: HttpRequest, stream: web::Payload, state: web::Data<AppState>) -> Result<HttpResponse, Error> {
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let (res, session, msg_stream) = actix_ws::handle(&req, stream)?;
+    actix_rt::spawn(pump(session, msg_stream, scope));
+    Ok(res)
+}
+
+/// Whether a tenant-scoped caller may see `event`: lifecycle events for
+/// its tenant's containers and nothing else. Unscoped callers see all.
+fn visible_to(scope: Option<&str>, event: &Event) -> bool {
+    let Some(tenant) = scope else { return true };
+    if !event.event.starts_with("container_") {
+        return false;
+    }
+    let name = event.payload.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    let kind = match event.payload.get("runtime").and_then(|v| v.as_str()) {
+        Some("lxc") => crate::auth::tenancy::Kind::Lxc,
+        Some("docker") => crate::auth::tenancy::Kind::Docker,
+        _ => return false,
+    };
+    !crate::auth::tenancy::visible(Some(tenant), vec![name], |n| Some((kind, *n))).is_empty()
+}
+
+async fn pump(mut session: actix_ws::Session, mut msg_stream: actix_ws::MessageStream, scope: Option<String>) {
+    let mut events = crate::events::subscribe_live();
+    let mut ping = tokio::time::interval(PING_EVERY);
+    let mut last_heard = Instant::now();
+    loop {
+        tokio::select! {
+            received = events.recv() => {
+                let text = match received {
+                    Ok(event) if visible_to(scope.as_deref(), &event) => serde_json::to_string(&event).unwrap_or_default(),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => serde_json::json!({
+                        "event": "lagged",
+                        "payload": { "missed": missed },
+                    }).to_string(),
+                    Err(RecvError::Closed) => break,
+                };
+                if session.text(text).await.is_err() { break; }
+            }
+            _ = ping.tick() => {
+                if last_heard.elapsed() > CLIENT_TIMEOUT { break; }
+                if session.ping(b"").await.is_err() { break; }
+            }
+            msg = msg_stream.next() => {
+                last_heard = Instant::now();
+                match msg {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        let _ = session.pong(&bytes).await;
+                    }
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, payload: serde_json::Value) -> Event {
+        Event {
+            id: "1".into(),
+            event: name.into(),
+            timestamp: String::new(),
+            cluster: String::new(),
+            hostname: String::new(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn unscoped_callers_see_everything() {
+        assert!(visible_to(None, &event("node_offline", serde_json::json!({}))));
+        assert!(visible_to(None, &event("alert", serde_json::json!({ "title": "x" }))));
+    }
+
+    #[test]
+    fn tenants_never_see_node_alert_or_job_events() {
+        for name in ["node_offline", "alert", "job", "backup_failed"] {
+            assert!(!visible_to(Some("acme"), &event(name, serde_json::json!({}))), "{}", name);
+        }
+        let unknown = event("container_started", serde_json::json!({ "runtime": "kvm", "name": "x" }));
+        assert!(!visible_to(Some("acme"), &unknown));
+    }
+}
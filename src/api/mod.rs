@@ -23,7 +23,11 @@ use crate::appstore;
 
 pub mod pve_console;
 pub mod container_vnc;
+mod streaming;
 mod cluster_browser_proxy;
+mod event_stream;
+mod readdress;
+mod v1;
 
 /// Shared HTTP client for every cluster-peer / external / self-loop
 /// call in this file. Previously ~40 API handlers each built their
@@ -266,9 +270,13 @@ pub struct AppState {
     /// reference and call `collect()` on its own cadence without
     /// blocking the high-frequency `cached_status_bg` task.
     pub monitor: Arc<std::sync::Mutex<SystemMonitor>>,
-    pub metrics_history: std::sync::Mutex<MetricsHistory>,
+    /// Only the self-monitor loop samples `monitor`; handlers read its
+    /// published snapshot (`monitoring::inventory::METRICS`) instead.
+    pub metrics_history: std::sync::RwLock<MetricsHistory>,
     pub cluster: Arc<ClusterState>,
     pub sessions: Arc<SessionManager>,
+    /// Serialises VM writes. Listing takes it too, so readers use the
+    /// `monitoring::inventory::VMS` snapshot rather than `list_vms()`.
     pub vms: std::sync::Mutex<crate::vms::manager::VmManager>,
     /// UPS staged-shutdown engine runtime (live status, latches, log).
     pub ups: Arc<crate::ups::UpsState>,
@@ -377,10 +385,18 @@ pub struct AlertLogEntry {
     pub cluster: String,
 }
 
+impl AlertLogEntry {
+    /// Push the entry to connected dashboards (`/api/ws/events`), so the
+    /// Tasks panel shows it without waiting for its next poll.
+    pub fn publish(&self) {
+        crate::events::live("alert", serde_json::to_value(self).unwrap_or_default());
+    }
+}
+
 #[derive(Clone, Serialize)]
 pub struct MigrationTask {
     pub id: String,
-    pub stage: String,       // "preflight", "stop_source", "export", "upload", "import", "start", "disk_copy", "done", "failed"
+    pub stage: String,       // "queued", "preflight", "stop_source", "export", "upload", "import", "start", "disk_copy", "done", "failed"
     pub message: String,
     pub completed: bool,
     pub error: Option<String>,
@@ -395,6 +411,24 @@ pub struct MigrationTask {
     /// Total bytes expected for this stage (archive size, disk size, …).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bytes_total: Option<u64>,
+    /// Smoothed bytes/s over the current stage, once there is a second
+    /// of byte counts to go on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_bps: Option<u64>,
+    /// Seconds left at `rate_bps`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_secs: Option<u64>,
+    /// Cap on the task's network transfer in Mbit/s; None = uncapped.
+    /// Changed while it runs with POST /api/migration/{id}/bwlimit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bwlimit_mbps: Option<u32>,
+    /// The same cap in bytes/s, shared with the running transfer (0 =
+    /// uncapped) — see `migration_throttle`.
+    #[serde(skip)]
+    pub throttle: Arc<std::sync::atomic::AtomicU64>,
+    /// (when, bytes_done) of the last rate sample.
+    #[serde(skip)]
+    rate_sample: Option<(std::time::Instant, u64)>,
 }
 
 /// Load or generate the join token from /etc/wolfstack/join-token
@@ -447,8 +481,77 @@ fn get_session_token(req: &HttpRequest) -> Option<String> {
         .map(|c| c.value().to_string())
 }
 
-/// Check if request is authenticated; returns username or error response
+/// The JS-readable CSRF cookie for a session (see `auth::csrf`). Not
+/// HttpOnly — the dashboard reads it to echo the token in a header.
+fn csrf_cookie(state: &AppState, session: &str) -> Cookie<'static> {
+    let token = state.sessions.csrf_token(session).unwrap_or_default();
+    let mut cookie = Cookie::build(crate::auth::csrf::COOKIE, token)
+        .path("/")
+        .same_site(actix_web::cookie::SameSite::Strict)
+        .max_age(session_cookie_age(state, session))
+        .finish();
+    if state.tls_enabled {
+        cookie.set_secure(true);
+    }
+    cookie
+}
+
+/// Cookie max-age for a session: what's left of its lifetime under the
+/// session policy (8 hours by default, longer for "remember me").
+fn session_cookie_age(state: &AppState, session: &str) -> actix_web::cookie::time::Duration {
+    actix_web::cookie::time::Duration::seconds(state.sessions.cookie_max_age(session).as_secs() as i64)
+}
+
+/// The `wolfstack_session` cookie for a freshly created session.
+fn session_cookie(state: &AppState, token: &str) -> Cookie<'static> {
+    let mut cookie = Cookie::build("wolfstack_session", token.to_string())
+        .path("/")
+        .http_only(true)
+        // Lax, not Strict: Strict withholds the session cookie on
+        // top-level navigations/redirects, which broke login on
+        // Microsoft Edge (stricter tracking-prevention than Chrome) —
+        // users authenticated then immediately appeared logged out.
+        // Lax still blocks cross-site POSTs (CSRF) for this same-origin SPA.
+        .same_site(actix_web::cookie::SameSite::Lax)
+        .max_age(session_cookie_age(state, token))
+        .finish();
+    if state.tls_enabled {
+        cookie.set_secure(true);
+    }
+    cookie
+}
+
+/// Gate for sensitive actions (removing a node, managing users): a browser
+/// session must have entered its password within the policy's re-auth
+/// window. Refused with 403 + `reauth_required`, which the dashboard
+/// answers by asking for the password (POST /api/auth/reauth) and retrying.
+/// API keys and cluster peers carry no session and aren't asked.
+fn require_recent_auth(req: &HttpRequest, state: &AppState) -> Result<(), HttpResponse> {
+    if req.headers().contains_key("X-WolfStack-Secret") {
+        return Ok(());
+    }
+    match get_session_token(req) {
+        Some(token) if !state.sessions.recently_authenticated(&token) => {
+            Err(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Confirm your password to continue",
+                "reauth_required": true,
+            })))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check if request is authenticated; returns username or error response.
+/// A tenant-scoped caller naming another tenant's resource in the path is
+/// refused here too (see `auth::tenancy`), so every handler enforces it.
 pub fn require_auth(req: &HttpRequest, state: &web::Data<AppState>) -> Result<String, HttpResponse> {
+    let caller = authenticate(req, state)?;
+    crate::auth::tenancy::check(req, &caller)
+        .map_err(|e| HttpResponse::NotFound().json(serde_json::json!({ "error": e })))?;
+    Ok(caller)
+}
+
+fn authenticate(req: &HttpRequest, state: &web::Data<AppState>) -> Result<String, HttpResponse> {
     // Accept internal requests from other WolfStack nodes if they provide the cluster secret.
     // Centralised three-way check (in-memory, on-disk, optional default) lives in
     // `auth::validate_inter_node_secret` — see Stage 5 docs there. Using the helper
@@ -466,6 +569,26 @@ pub fn require_auth(req: &HttpRequest, state: &web::Data<AppState>) -> Result<St
             "error": "Invalid cluster secret"
         })));
     }
+    // Headless CLI (`wolfstack node list`, …): the join token proves the
+    // caller could read the root-only /etc/wolfstack/join-token, so it is
+    // treated as root — but only over loopback, so a leaked token is no
+    // use from the network. The on-disk value is checked too, so a token
+    // rotated since startup works for the CLI without a restart.
+    if let Some(val) = req.headers().get("X-WolfStack-Join-Token") {
+        let provided = val.to_str().unwrap_or("");
+        let loopback = req.peer_addr().map(|a| a.ip().to_canonical().is_loopback()).unwrap_or(false);
+        let on_disk = std::fs::read_to_string("/etc/wolfstack/join-token")
+            .map(|t| t.trim().to_string())
+            .unwrap_or_default();
+        let valid = crate::auth::validate_cluster_secret(provided, &state.join_token)
+            || crate::auth::validate_cluster_secret(provided, &on_disk);
+        if loopback && valid {
+            return Ok("root".to_string());
+        }
+        return Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Invalid join token (accepted from localhost only)"
+        })));
+    }
     // Accept API key authentication (X-API-Key header or Authorization: Bearer wsk_...)
     // Check platform calibration state for token-based auth
     if crate::compat::platform_ready() {
@@ -559,6 +682,9 @@ pub struct LoginRequest {
     pub password: String,
     #[serde(default)]
     pub totp_code: String,
+    /// "Remember me" — the policy's longer lifetime, no idle timeout.
+    #[serde(default)]
+    pub remember: bool,
 }
 
 /// POST /api/auth/login — authenticate with Linux or WolfStack credentials + optional 2FA
@@ -621,23 +747,10 @@ pub async fn login(req: HttpRequest, state: web::Data<AppState>, body: web::Json
 
             state.login_limiter.clear_with(&client_ip, &body.username);
             crate::auth::record_admin_ip(&client_ip);
-            let token = state.sessions.create_session(&body.username);
-            let mut cookie = Cookie::build("wolfstack_session", &token)
-                .path("/")
-                .http_only(true)
-                // Lax, not Strict: Strict withholds the session cookie on
-                // top-level navigations/redirects, which broke login on
-                // Microsoft Edge (stricter tracking-prevention than Chrome) —
-                // users authenticated then immediately appeared logged out.
-                // Lax still blocks cross-site POSTs (CSRF) for this same-origin SPA.
-                .same_site(actix_web::cookie::SameSite::Lax)
-                .max_age(actix_web::cookie::time::Duration::hours(8))
-                .finish();
-            if state.tls_enabled {
-                cookie.set_secure(true);
-            }
+            let token = state.sessions.create_session_with(&body.username, body.remember);
             return HttpResponse::Ok()
-                .cookie(cookie)
+                .cookie(session_cookie(&state, &token))
+                .cookie(csrf_cookie(&state, &token))
                 .json(serde_json::json!({
                     "success": true,
                     "username": body.username
@@ -650,23 +763,10 @@ pub async fn login(req: HttpRequest, state: web::Data<AppState>, body: web::Json
         if crate::auth::authenticate_user(&body.username, &body.password) {
             state.login_limiter.clear_with(&client_ip, &body.username);
             crate::auth::record_admin_ip(&client_ip);
-            let token = state.sessions.create_session(&body.username);
-            let mut cookie = Cookie::build("wolfstack_session", &token)
-                .path("/")
-                .http_only(true)
-                // Lax, not Strict: Strict withholds the session cookie on
-                // top-level navigations/redirects, which broke login on
-                // Microsoft Edge (stricter tracking-prevention than Chrome) —
-                // users authenticated then immediately appeared logged out.
-                // Lax still blocks cross-site POSTs (CSRF) for this same-origin SPA.
-                .same_site(actix_web::cookie::SameSite::Lax)
-                .max_age(actix_web::cookie::time::Duration::hours(8))
-                .finish();
-            if state.tls_enabled {
-                cookie.set_secure(true);
-            }
+            let token = state.sessions.create_session_with(&body.username, body.remember);
             return HttpResponse::Ok()
-                .cookie(cookie)
+                .cookie(session_cookie(&state, &token))
+                .cookie(csrf_cookie(&state, &token))
                 .json(serde_json::json!({
                     "success": true,
                     "username": body.username
@@ -685,6 +785,57 @@ pub async fn login(req: HttpRequest, state: web::Data<AppState>, body: web::Json
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ReauthRequest {
+    pub password: String,
+    #[serde(default)]
+    pub totp_code: String,
+}
+
+/// POST /api/auth/reauth — re-enter the session user's password (and 2FA
+/// code) to unlock sensitive actions for `reauth_minutes`. Checked the same
+/// way as login and counted against the same lockout.
+pub async fn reauth(req: HttpRequest, state: web::Data<AppState>, body: web::Json<ReauthRequest>) -> HttpResponse {
+    let Some(token) = get_session_token(&req) else {
+        return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "Not signed in" }));
+    };
+    let Some(username) = state.sessions.validate(&token) else {
+        return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "Session expired" }));
+    };
+    let raw_ip = req.connection_info().peer_addr().unwrap_or("unknown").to_string();
+    let client_ip = crate::netaddr::canonical_ip_str(&strip_port(&raw_ip)).into_owned();
+    if state.login_limiter.is_locked_out(&client_ip) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Access denied — this IP is blocked. Contact the operator.",
+        }));
+    }
+
+    let auth_config = crate::auth::users::AuthConfig::load();
+    let mode = auth_config.auth_mode.as_str();
+    let mut ok = false;
+    if (mode == "wolfstack" || mode == "both")
+        && let Some(user) = crate::auth::users::authenticate_wolfstack_user(&username, &body.password)
+    {
+        if user.totp_enabled {
+            if body.totp_code.is_empty() {
+                return HttpResponse::Ok().json(serde_json::json!({ "success": false, "requires_2fa": true }));
+            }
+            ok = crate::auth::users::verify_totp(&user.totp_secret, &body.totp_code);
+        } else {
+            ok = true;
+        }
+    }
+    if !ok && (mode == "linux" || mode == "both") {
+        ok = crate::auth::authenticate_user(&username, &body.password);
+    }
+    if !ok {
+        state.login_limiter.record_failure_with(&client_ip, &username);
+        return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "Incorrect password" }));
+    }
+    state.sessions.mark_authenticated(&token);
+    HttpResponse::Ok().json(serde_json::json!({ "success": true }))
+}
+
 /// Fan out a kernel-block decision to every federation registered on
 /// this WolfStack. Uses each federation's stored api_key as a Bearer
 /// token to authenticate against the federation's
@@ -1080,6 +1231,31 @@ pub async fn security_auth_config_set(
     }
 }
 
+/// GET /api/security/session-policy — session lifetime, idle timeout,
+/// "remember me" and re-auth window.
+pub async fn security_session_policy_get(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    HttpResponse::Ok().json(state.sessions.policy())
+}
+
+/// POST /api/security/session-policy — update the session policy (admin).
+/// Applies to existing sessions on their next request.
+pub async fn security_session_policy_set(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<crate::auth::SessionPolicy>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    if let Err(resp) = require_admin_caller(&req, &caller, "change the session policy") { return resp; }
+    match state.sessions.set_policy(body.into_inner()) {
+        Ok(saved) => HttpResponse::Ok().json(saved),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ---- Brute-force protection (per-node) --------------------------------------
 // These are the node-local endpoints. The fleet_* wrappers below fan them out
 // across the cluster; a peer that receives a propagated call lands here too.
@@ -1238,6 +1414,78 @@ pub async fn security_auth_lockouts(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct AttacksQuery {
+    /// Look-back window (default 24, at most a week).
+    #[serde(default)]
+    pub hours: Option<u64>,
+    /// `local` for this node only; anything else gathers the cluster.
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// GET /api/security/attacks — failed logins grouped by attacker IP across the cluster
+pub async fn security_attacks(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<AttacksQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let hours = query.hours.unwrap_or(24).clamp(1, 24 * 7);
+    let limiter = state.login_limiter.clone();
+    let local = move || crate::auth::attacks::summary(hours, &|ip| limiter.is_locked_out(ip));
+    if query.scope == "local" {
+        return HttpResponse::Ok().json(local());
+    }
+    let results = fleet_fanout_get::<crate::auth::attacks::AttackSummary, _>(
+        &state, &format!("/api/security/attacks?scope=local&hours={}", hours), local,
+    ).await;
+    let unreachable: Vec<String> = results.iter()
+        .filter(|r| r.status == "failed")
+        .map(|r| r.hostname.clone())
+        .collect();
+    let merged = crate::auth::attacks::merge(results.into_iter()
+        .filter_map(|r| r.data.map(|d| (r.hostname, d)))
+        .collect());
+    HttpResponse::Ok().json(serde_json::json!({
+        "hours": hours,
+        "summary": merged,
+        "unreachable": unreachable,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct AttackBlockRequest {
+    pub ip: String,
+}
+
+/// POST /api/security/attacks/block — kernel-block an attacker on every node
+pub async fn security_attacks_block(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<AttackBlockRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let ip = body.ip.trim().to_string();
+    if ip.parse::<std::net::IpAddr>().is_err() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": format!("'{}' is not an IP address", ip)}));
+    }
+    let cfg = state.login_limiter.config();
+    if cfg.is_trusted(&ip) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": format!("{} is on the trusted list", ip)}));
+    }
+    let secs = cfg.lockout_seconds;
+    state.login_limiter.add_propagated_lockout(&ip, secs, "operator");
+    let cluster = state.cluster.clone();
+    let secret = state.cluster_secret.clone();
+    let source_node = state.cluster.self_id.clone();
+    let peer_ip = ip.clone();
+    tokio::spawn(async move {
+        propagate_kernel_block_to_peers(cluster, secret, peer_ip, secs, source_node).await;
+    });
+    HttpResponse::Ok().json(serde_json::json!({ "ok": true, "ip": ip, "lockout_seconds": secs }))
+}
+
 #[derive(Deserialize)]
 pub struct UnblockRequest {
     pub ip: String,
@@ -1403,6 +1651,31 @@ pub async fn set_roles(req: HttpRequest, state: web::Data<AppState>, body: web::
     HttpResponse::Ok().json(serde_json::json!({ "roles": roles }))
 }
 
+/// POST /api/settings/labels — set the labels on THIS node. Pushed by the
+/// control node when an admin edits a node's labels, like `set_roles`.
+/// Body: `{"labels": {"env": "prod"}}`; an empty map clears them.
+pub async fn set_labels(req: HttpRequest, state: web::Data<AppState>, body: web::Json<serde_json::Value>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let labels: std::collections::BTreeMap<String, String> = match body.get("labels").cloned()
+        .map(serde_json::from_value)
+        .unwrap_or_else(|| Ok(Default::default()))
+    {
+        Ok(l) => l,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("labels must be a map of strings: {}", e) })),
+    };
+    if let Err(e) = crate::agent::labels::validate(&labels) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    {
+        let mut nodes = state.cluster.nodes.write().unwrap();
+        if let Some(node) = nodes.get_mut(&state.cluster.self_id) {
+            node.labels = labels.clone();
+        }
+    }
+    crate::agent::ClusterState::save_self_labels(&labels);
+    HttpResponse::Ok().json(serde_json::json!({ "labels": labels }))
+}
+
 /// GET /api/cluster/roles/available — the assignable tier roles (value +
 /// label) for the node-settings role picker.
 pub async fn cluster_roles_available(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
@@ -1540,7 +1813,7 @@ pub async fn set_login_disabled(req: HttpRequest, state: web::Data<AppState>, bo
         }
     }
     // Persist to disk
-    crate::agent::ClusterState::save_login_disabled_file(disabled);
+    state.cluster.save_snapshot();
 
     HttpResponse::Ok().json(serde_json::json!({ "login_disabled": disabled }))
 }
@@ -1558,21 +1831,47 @@ pub async fn logout(req: HttpRequest, state: web::Data<AppState>) -> HttpRespons
         .same_site(actix_web::cookie::SameSite::Lax)
         .finish();
     cookie.make_removal();
+    let mut csrf = Cookie::build(crate::auth::csrf::COOKIE, "").path("/").finish();
+    csrf.make_removal();
 
     HttpResponse::Ok()
         .cookie(cookie)
+        .cookie(csrf)
         .json(serde_json::json!({ "success": true }))
 }
 
 /// GET /api/auth/check — check if session is valid
 pub async fn auth_check(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    // The dashboard shows a banner and the message when writes are refused.
+    let read_only = crate::auth::read_only::enabled().then(crate::auth::read_only::message);
     match require_auth(&req, &state) {
-        Ok(username) => HttpResponse::Ok().json(serde_json::json!({
-            "authenticated": true,
-            "username": username
-        })),
+        Ok(username) => {
+            let mut resp = HttpResponse::Ok();
+            let policy = state.sessions.policy();
+            let mut remember = false;
+            // Re-issue the CSRF cookie in case it was cleared while the
+            // session cookie survived — the dashboard calls this on load.
+            if let Some(token) = get_session_token(&req)
+                && state.sessions.csrf_token(&token).is_some()
+            {
+                resp.cookie(csrf_cookie(&state, &token));
+                remember = state.sessions.is_remembered(&token);
+            }
+            // idle_minutes drives the dashboard's own sign-out timer;
+            // remembered sessions have no idle timeout.
+            resp.json(serde_json::json!({
+                "authenticated": true,
+                "username": username,
+                "read_only": read_only,
+                "remember": remember,
+                "idle_minutes": if remember { 0 } else { policy.idle_minutes },
+            }))
+        }
         Err(_) => HttpResponse::Ok().json(serde_json::json!({
-            "authenticated": false
+            "authenticated": false,
+            "read_only": read_only,
+            // The login page offers "Remember me" only when this is > 0.
+            "remember_days": state.sessions.policy().remember_days,
         })),
     }
 }
@@ -1780,20 +2079,9 @@ pub async fn passkey_login_finish(req: HttpRequest, state: web::Data<AppState>,
             state.login_limiter.clear(&client_ip);
             crate::auth::record_admin_ip(&client_ip);
             let token = state.sessions.create_session(&username);
-            let mut cookie = Cookie::build("wolfstack_session", &token)
-                .path("/")
-                .http_only(true)
-                // Lax, not Strict: Strict withholds the session cookie on
-                // top-level navigations/redirects, which broke login on
-                // Microsoft Edge (stricter tracking-prevention than Chrome) —
-                // users authenticated then immediately appeared logged out.
-                // Lax still blocks cross-site POSTs (CSRF) for this same-origin SPA.
-                .same_site(actix_web::cookie::SameSite::Lax)
-                .max_age(actix_web::cookie::time::Duration::hours(8))
-                .finish();
-            if state.tls_enabled { cookie.set_secure(true); }
             HttpResponse::Ok()
-                .cookie(cookie)
+                .cookie(session_cookie(&state, &token))
+                .cookie(csrf_cookie(&state, &token))
                 .json(serde_json::json!({ "success": true, "username": username }))
         }
         Err(e) => {
@@ -2787,6 +3075,7 @@ pub async fn list_users(req: HttpRequest, state: web::Data<AppState>) -> HttpRes
             "email": u.email,
             "totp_enabled": u.totp_enabled,
             "created_at": u.created_at,
+            "tenant": u.tenant,
         })
     }).collect();
     HttpResponse::Ok().json(users)
@@ -2796,6 +3085,7 @@ pub async fn list_users(req: HttpRequest, state: web::Data<AppState>) -> HttpRes
 pub async fn create_user(req: HttpRequest, state: web::Data<AppState>, body: web::Json<serde_json::Value>) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
     if let Err(resp) = require_admin(&caller) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
 
     let username = body["username"].as_str().unwrap_or("").trim().to_string();
     let password = body["password"].as_str().unwrap_or("");
@@ -2838,6 +3128,14 @@ pub async fn create_user(req: HttpRequest, state: web::Data<AppState>, body: web
         Vec::new()
     };
 
+    // Tenant — see `auth::tenancy`. Absent or empty = not tenant-scoped.
+    let tenant = body["tenant"].as_str().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if let Some(ref t) = tenant
+        && let Err(e) = crate::auth::tenancy::validate_tenant(t)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+    }
+
     let user = crate::auth::users::WolfUser {
         username: username.clone(),
         password_hash,
@@ -2848,6 +3146,7 @@ pub async fn create_user(req: HttpRequest, state: web::Data<AppState>, body: web
         email,
         created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
         allowed_clusters,
+        tenant,
     };
 
     let mut store = crate::auth::users::UserStore::load();
@@ -2866,6 +3165,7 @@ pub async fn create_user(req: HttpRequest, state: web::Data<AppState>, body: web
 pub async fn delete_user(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
     if let Err(resp) = require_admin(&caller) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     let username = path.into_inner();
     let mut store = crate::auth::users::UserStore::load();
     match store.remove(&username) {
@@ -2895,6 +3195,19 @@ fn require_admin_or_self(caller: &str, target: &str) -> Result<(), HttpResponse>
     }
 }
 
+/// 403 unless the caller is an admin, counting a cluster peer only when it
+/// relays an admin's request (see `auth::caller_is_admin`). `action`
+/// completes "Only admin users can …".
+fn require_admin_caller(req: &HttpRequest, caller: &str, action: &str) -> Result<(), HttpResponse> {
+    if crate::auth::caller_is_admin(req, caller) {
+        Ok(())
+    } else {
+        Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": format!("Only admin users can {}", action)
+        })))
+    }
+}
+
 /// Admin-only gate (user create / delete).
 fn require_admin(caller: &str) -> Result<(), HttpResponse> {
     if crate::auth::session_user_is_admin(caller) {
@@ -2911,6 +3224,7 @@ pub async fn change_user_password(req: HttpRequest, state: web::Data<AppState>,
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
     let username = path.into_inner();
     if let Err(resp) = require_admin_or_self(&caller, &username) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     let new_password = body["password"].as_str().unwrap_or("");
     if new_password.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({"error": "Password required"}));
@@ -2948,6 +3262,7 @@ pub async fn change_user_email(req: HttpRequest, state: web::Data<AppState>, pat
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
     let username = path.into_inner();
     if let Err(resp) = require_admin_or_self(&caller, &username) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     let email = body["email"].as_str().unwrap_or("").trim().to_string();
 
     if let Err(e) = validate_email_loose(&email) {
@@ -3057,6 +3372,7 @@ pub async fn update_user_clusters(
     path: web::Path<String>, body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     // Enterprise-only — any valid license unlocks it, no per-feature
     // toggle. On a non-enterprise install the route returns 404 (route
     // invisible, matching the hidden UI — no upgrade leakage).
@@ -3097,11 +3413,87 @@ pub async fn update_user_clusters(
     }
 }
 
+/// PUT /api/auth/users/{username}/tenant — put a user in a tenant (or,
+/// with `{"tenant": null}`, take them out). Admin only.
+pub async fn update_user_tenant(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, body: web::Json<serde_json::Value>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_admin(&caller) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    let username = path.into_inner();
+    let tenant = body["tenant"].as_str().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if let Some(ref t) = tenant
+        && let Err(e) = crate::auth::tenancy::validate_tenant(t)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+    }
+    let mut store = crate::auth::users::UserStore::load();
+    let Some(user) = store.find_mut(&username) else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "User not found"}));
+    };
+    user.tenant = tenant.clone();
+    match store.save() {
+        Ok(()) => {
+            trigger_control_plane_push(&state);
+            HttpResponse::Ok().json(serde_json::json!({"success": true, "username": username, "tenant": tenant}))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
+    }
+}
+
+/// Tenancy admin: an admin session, or a peer relaying an admin (a
+/// proxied request carrying a tenant is a tenant user, not an admin).
+fn require_tenancy_admin(req: &HttpRequest, caller: &str) -> Result<(), HttpResponse> {
+    require_admin_caller(req, caller, "manage tenancy")
+}
+
+/// GET /api/tenancy — which tenant owns which resource on this node
+pub async fn tenancy_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_tenancy_admin(&req, &caller) { return resp; }
+    HttpResponse::Ok().json(crate::auth::tenancy::Ownership::load())
+}
+
+#[derive(Deserialize)]
+pub struct TenancyAssignRequest {
+    pub kind: crate::auth::tenancy::Kind,
+    pub id: String,
+    /// `None` releases the resource (admin-only again).
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+/// POST /api/tenancy/assign — give a resource on this node to a tenant, or release it
+pub async fn tenancy_assign(req: HttpRequest, state: web::Data<AppState>, body: web::Json<TenancyAssignRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_tenancy_admin(&req, &caller) { return resp; }
+    let tenant = body.tenant.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if let Some(t) = tenant
+        && let Err(e) = crate::auth::tenancy::validate_tenant(t)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    if body.id.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "id is required" }));
+    }
+    let mut ownership = crate::auth::tenancy::Ownership::load();
+    if let Err(e) = ownership.assign(body.kind, &body.id, tenant) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    match ownership.save() {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "kind": body.kind, "id": body.id, "tenant": tenant })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 /// POST /api/auth/users/{username}/2fa/disable — disable 2FA for a user
 pub async fn disable_2fa(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
     let username = path.into_inner();
     if let Err(resp) = require_admin_or_self(&caller, &username) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     let mut store = crate::auth::users::UserStore::load();
     match store.find_mut(&username) {
         Some(user) => {
@@ -3124,17 +3516,166 @@ pub async fn disable_2fa(req: HttpRequest, state: web::Data<AppState>, path: web
 /// GET /api/metrics — current system metrics
 pub async fn get_metrics(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let st = state.into_inner();
-    let metrics = tokio::task::spawn_blocking(move || {
-        st.monitor.lock().unwrap().collect()
-    }).await.unwrap();
-    HttpResponse::Ok().json(metrics)
+    match crate::monitoring::inventory::metrics(&state).await {
+        Some(metrics) => HttpResponse::Ok().json(metrics),
+        None => HttpResponse::InternalServerError().json(serde_json::json!({"error": "metrics unavailable"})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ApiStatsQuery {
+    /// total (default), avg, p95, p99, max, count or errors.
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Only routes containing this text, e.g. `docker`.
+    #[serde(default)]
+    pub route: Option<String>,
+}
+
+/// GET /api/system/api-stats — per-endpoint latency and error summary,
+/// slowest first, for finding handlers that drag (shell-outs and the like).
+pub async fn api_stats(req: HttpRequest, state: web::Data<AppState>, query: web::Query<ApiStatsQuery>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let (since, mut list) = crate::monitoring::api_stats::summary();
+    if let Some(filter) = query.route.as_deref().filter(|f| !f.is_empty()) {
+        list.retain(|s| s.route.contains(filter));
+    }
+    crate::monitoring::api_stats::sort(&mut list, query.sort.as_deref().unwrap_or("total"));
+    let total_requests: u64 = list.iter().map(|s| s.count).sum();
+    let total_errors: u64 = list.iter().map(|s| s.server_errors).sum();
+    list.truncate(query.limit.unwrap_or(50));
+    HttpResponse::Ok().json(serde_json::json!({
+        "since": since.to_rfc3339(),
+        "total_requests": total_requests,
+        "total_server_errors": total_errors,
+        "endpoints": list,
+    }))
+}
+
+/// GET /api/system/inventory — age and collection time of each background
+/// snapshot (Docker, LXC, VMs, host metrics), for spotting a stuck source.
+pub async fn inventory_status(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(crate::monitoring::inventory::status())
+}
+
+#[derive(Deserialize)]
+pub struct HardwareQuery {
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// GET /api/hardware — this node's hardware inventory (DMI identity, CPUs,
+/// DIMMs, disks, NICs with firmware, PCI devices). `?refresh=true` takes a
+/// new snapshot instead of returning the six-hourly one.
+pub async fn hardware_inventory(req: HttpRequest, state: web::Data<AppState>, query: web::Query<HardwareQuery>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let cached = if query.refresh { None } else { crate::monitoring::hardware::local() };
+    let inv = match cached {
+        Some(inv) => inv,
+        None => {
+            let started = std::time::Instant::now();
+            match web::block(crate::monitoring::hardware::collect_and_store).await {
+                Ok(inv) => {
+                    crate::monitoring::inventory::HARDWARE.publish(inv.clone(), started.elapsed());
+                    inv
+                }
+                Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+            }
+        }
+    };
+    HttpResponse::Ok().json(inv)
+}
+
+/// GET /api/system/selfcheck — re-run the port pre-flight checks: each
+/// listener reachable over loopback, and any firewall rule blocking it.
+pub async fn system_selfcheck(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match crate::selfcheck::run().await {
+        Some(check) => HttpResponse::Ok().json(check),
+        None => HttpResponse::ServiceUnavailable().json(serde_json::json!({ "error": "Listeners not started yet" })),
+    }
+}
+
+/// GET /api/system/crash-reports — crash reports left by panics and
+/// failed starts, newest first (summaries; fetch one for the full report).
+pub async fn crash_reports(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(serde_json::json!({ "reports": crate::crash::list() }))
+}
+
+/// GET /api/system/crash-reports/{id} — one report: backtrace, log tail
+/// and the settings in force when it was written.
+pub async fn crash_report_get(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match crate::crash::get(&path.into_inner()) {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/system/crash-reports — remove every saved report.
+pub async fn crash_reports_clear(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(serde_json::json!({ "removed": crate::crash::clear() }))
+}
+
+/// DELETE /api/system/api-stats — zero the counters.
+pub async fn api_stats_reset(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    crate::monitoring::api_stats::reset();
+    HttpResponse::Ok().json(serde_json::json!({"reset": true}))
+}
+
+/// GET /metrics — Prometheus exposition: this node's CPU, memory, load and
+/// disk gauges plus the per-route API latency histograms and error counts.
+/// Scrapers authenticate with an API key as a bearer token.
+pub async fn prometheus_metrics(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    use std::fmt::Write;
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let m = match crate::monitoring::inventory::metrics(&state).await {
+        Some(m) => m,
+        None => return HttpResponse::InternalServerError().json(serde_json::json!({"error": "metrics unavailable"})),
+    };
+    let mut out = String::with_capacity(64 * 1024);
+    let gauge = |out: &mut String, name: &str, help: &str, value: f64| {
+        let _ = write!(out, "# HELP {0} {1}\n# TYPE {0} gauge\n{0} {2}\n", name, help, value);
+    };
+    let _ = write!(out, "# HELP wolfstack_build_info WolfStack version.\n# TYPE wolfstack_build_info gauge\nwolfstack_build_info{{version=\"{}\"}} 1\n",
+        env!("CARGO_PKG_VERSION"));
+    gauge(&mut out, "wolfstack_uptime_seconds", "Host uptime.", m.uptime_secs as f64);
+    gauge(&mut out, "wolfstack_cpu_usage_percent", "CPU usage across all cores.", m.cpu_usage_percent as f64);
+    gauge(&mut out, "wolfstack_cpu_count", "Logical CPUs.", m.cpu_count as f64);
+    gauge(&mut out, "wolfstack_memory_total_bytes", "Physical memory.", m.memory_total_bytes as f64);
+    gauge(&mut out, "wolfstack_memory_used_bytes", "Physical memory in use.", m.memory_used_bytes as f64);
+    gauge(&mut out, "wolfstack_swap_total_bytes", "Swap size.", m.swap_total_bytes as f64);
+    gauge(&mut out, "wolfstack_swap_used_bytes", "Swap in use.", m.swap_used_bytes as f64);
+    gauge(&mut out, "wolfstack_load1", "1-minute load average.", m.load_avg.one);
+    gauge(&mut out, "wolfstack_load5", "5-minute load average.", m.load_avg.five);
+    gauge(&mut out, "wolfstack_load15", "15-minute load average.", m.load_avg.fifteen);
+    let disk_label = |d: &crate::monitoring::DiskMetrics| format!("mount=\"{}\",device=\"{}\"",
+        d.mount_point.replace('\\', "\\\\").replace('"', "\\\""),
+        d.name.replace('\\', "\\\\").replace('"', "\\\""));
+    out.push_str("# HELP wolfstack_disk_total_bytes Filesystem size.\n# TYPE wolfstack_disk_total_bytes gauge\n");
+    for d in &m.disks {
+        let _ = writeln!(out, "wolfstack_disk_total_bytes{{{}}} {}", disk_label(d), d.total_bytes);
+    }
+    out.push_str("# HELP wolfstack_disk_used_bytes Filesystem space in use.\n# TYPE wolfstack_disk_used_bytes gauge\n");
+    for d in &m.disks {
+        let _ = writeln!(out, "wolfstack_disk_used_bytes{{{}}} {}", disk_label(d), d.used_bytes);
+    }
+    crate::monitoring::api_stats::render_prometheus(&mut out);
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(out)
 }
 
 /// GET /api/metrics/history — historical CPU, RAM, disk metrics
 pub async fn get_metrics_history(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let history = state.metrics_history.lock().unwrap();
+    let history = state.metrics_history.read().unwrap();
     HttpResponse::Ok().json(history.get_all())
 }
 
@@ -3151,6 +3692,14 @@ pub async fn get_top_processes(req: HttpRequest, state: web::Data<AppState>) ->
     }))
 }
 
+/// GET /api/metrics/cache-services — local Redis / Memcached instances
+pub async fn get_cache_services(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let instances = tokio::task::spawn_blocking(crate::monitoring::cache_services::collect)
+        .await.unwrap_or_default();
+    HttpResponse::Ok().json(serde_json::json!({ "instances": instances }))
+}
+
 /// POST /api/metrics/processes/{pid}/kill — kill a process by PID
 pub async fn kill_process(req: HttpRequest, state: web::Data<AppState>, path: web::Path<u32>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -3330,10 +3879,21 @@ pub async fn proxmox_cleanup_dismiss(req: HttpRequest, state: web::Data<AppState
     }
 }
 
-/// GET /api/nodes — all cluster nodes
-pub async fn get_nodes(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+#[derive(Deserialize)]
+pub struct NodesQuery {
+    /// Label selector, e.g. `env=prod,tier!=db` — see `agent::labels`.
+    #[serde(default)]
+    pub selector: Option<String>,
+}
+
+/// GET /api/nodes — all cluster nodes (`?selector=env=prod` filters by label)
+pub async fn get_nodes(req: HttpRequest, state: web::Data<AppState>, query: web::Query<NodesQuery>) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
-    let nodes = state.cluster.get_all_nodes();
+    let selector = match crate::agent::labels::LabelSelector::parse(query.selector.as_deref().unwrap_or("")) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid selector: {}", e) })),
+    };
+    let nodes = state.cluster.nodes_matching(&selector);
     // Per-user cluster filter. Cluster-node callers (inter-node
     // traffic via X-WolfStack-Secret) always see everything; the
     // helper returns "cluster-node" for those and find() below misses,
@@ -3364,6 +3924,103 @@ fn filter_nodes_for_caller(caller: &str, nodes: Vec<crate::agent::Node>) -> Vec<
         .collect()
 }
 
+#[derive(Deserialize)]
+pub struct BulkNodeRequest {
+    /// Label selector naming the target nodes. Required — a bulk operation
+    /// never silently means "every node".
+    pub selector: String,
+    /// `run_command`, `alert_config` or `backup_schedule`.
+    pub action: String,
+    /// For `run_command`: `{"command": .., "timeout_secs": ..}`. For
+    /// `alert_config`: the fields to override, as `POST /api/alerts/config`
+    /// takes them. For `backup_schedule`: a schedule, as
+    /// `POST /api/backups/schedules` takes it.
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+/// POST /api/nodes/bulk — run one operation on every WolfStack node matching a label selector
+///
+/// Each node gets the same request it would get from its own dashboard
+/// (`/api/wolfflow/exec`, `/api/alerts/config` or `/api/backups/schedules`),
+/// this node included — it is addressed like any other so every target
+/// runs exactly the same code path. Answers with one result per node.
+pub async fn nodes_bulk(req: HttpRequest, state: web::Data<AppState>, body: web::Json<BulkNodeRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if !crate::auth::session_user_is_admin(&caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Only admin users can run bulk operations" }));
+    }
+    let body = body.into_inner();
+    let selector = match crate::agent::labels::LabelSelector::parse(&body.selector) {
+        Ok(s) if !s.is_empty() => s,
+        Ok(_) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "selector is required" })),
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid selector: {}", e) })),
+    };
+    let (path, payload, timeout_secs) = match body.action.as_str() {
+        "run_command" => {
+            let mut action = body.payload.clone();
+            if let Some(obj) = action.as_object_mut() {
+                obj.insert("action".into(), "run_command".into());
+            }
+            match serde_json::from_value::<crate::wolfflow::ActionType>(action.clone()) {
+                Ok(crate::wolfflow::ActionType::RunCommand { timeout_secs, .. }) => {
+                    ("/api/wolfflow/exec", action, timeout_secs.saturating_add(15))
+                }
+                _ => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "run_command needs {\"command\": \"...\"}" })),
+            }
+        }
+        "alert_config" => {
+            if !body.payload.as_object().is_some_and(|o| !o.is_empty()) {
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": "alert_config needs the fields to override" }));
+            }
+            ("/api/alerts/config", body.payload, 15)
+        }
+        "backup_schedule" => {
+            if let Err(e) = serde_json::from_value::<CreateScheduleRequest>(body.payload.clone()) {
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid schedule: {}", e) }));
+            }
+            ("/api/backups/schedules", body.payload, 30)
+        }
+        other => return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown action '{}' — expected run_command, alert_config or backup_schedule", other)
+        })),
+    };
+    let targets: Vec<crate::agent::Node> = filter_nodes_for_caller(&caller, state.cluster.nodes_matching(&selector))
+        .into_iter()
+        .filter(|n| n.node_type == "wolfstack")
+        .collect();
+    let results = futures::future::join_all(targets.iter().map(|node| {
+        let (state, payload) = (&state, &payload);
+        async move {
+            let urls = wolfstack_api_urls(node, path);
+            let mut entry = serde_json::json!({
+                "node_id": node.id,
+                "hostname": node.display_name.clone().unwrap_or_else(|| node.hostname.clone()),
+            });
+            match post_json_to_node(state, &urls, payload, timeout_secs).await {
+                // run_command reports a failed command as 200 + ok:false.
+                Ok(data) => {
+                    entry["ok"] = data.get("ok").cloned().unwrap_or(serde_json::Value::Bool(true));
+                    entry["result"] = data;
+                }
+                Err(e) => {
+                    entry["ok"] = false.into();
+                    entry["error"] = e.into();
+                }
+            }
+            entry
+        }
+    })).await;
+    let failed = results.iter().filter(|r| r["ok"] != true).count();
+    HttpResponse::Ok().json(serde_json::json!({
+        "action": body.action,
+        "selector": body.selector,
+        "matched": results.len(),
+        "failed": failed,
+        "results": results,
+    }))
+}
+
 /// GET /api/nodes/{id} — single node details
 pub async fn get_node(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
@@ -3382,7 +4039,16 @@ pub async fn get_node(req: HttpRequest, state: web::Data<AppState>, path: web::P
                     }
                 }
             }
-            HttpResponse::Ok().json(node)
+            // Hardware inventory for asset tracking: ours from the local
+            // snapshot, a peer's from its /api/hardware (cached).
+            let hardware = if node.is_self {
+                crate::monitoring::hardware::local()
+            } else {
+                crate::monitoring::hardware::remote(&node, &state.cluster_secret).await
+            };
+            let mut body = serde_json::to_value(&node).unwrap_or_default();
+            body["hardware"] = serde_json::json!(hardware);
+            HttpResponse::Ok().json(body)
         },
         None => HttpResponse::NotFound().json(serde_json::json!({
             "error": "Node not found"
@@ -3631,6 +4297,10 @@ pub struct JoinHandshakeRequest {
     pub master_port: Option<u16>,
     #[serde(default)]
     pub master_self_id: Option<String>,
+    /// Bare-metal provisioning: the single-use key `firstboot.sh` wrote to
+    /// this node, accepted in place of the admin credentials.
+    #[serde(default)]
+    pub provision_key: String,
 }
 
 /// POST /api/cluster/join-handshake — the hardened, admin-credentialled
@@ -3694,17 +4364,23 @@ pub async fn cluster_join_handshake(
     // 2) Admin credential proof against THIS node's own auth. Run the
     //    crypt()/role check inside web::block — it reads /etc/shadow and
     //    /etc/group and must not park an actix worker. The password is
-    //    moved in and dropped inside the closure; never logged.
-    let admin_user = body.admin_username.trim().to_string();
+    //    moved in and dropped inside the closure; never logged. A
+    //    provisioned machine instead proves itself with the key its
+    //    first-boot script left on disk (only the master it enrolled
+    //    with was told it).
+    let provisioned = !body.provision_key.is_empty();
+    let admin_user = if provisioned { "provisioning".to_string() } else { body.admin_username.trim().to_string() };
     let admin_pass = body.admin_password.clone();
-    if admin_user.is_empty() || admin_pass.is_empty() {
+    if !provisioned && (admin_user.is_empty() || admin_pass.is_empty()) {
         if let Some(exp) = ott_expiry { state.one_time_join_tokens.reinsert(provided, exp); }
         return HttpResponse::Unauthorized().json(serde_json::json!({
             "valid": false,
             "error": "Admin username and password for THIS node are required to add it to a cluster.",
         }));
     }
-    let admin_ok = {
+    let admin_ok = if provisioned {
+        crate::provisioning::verify_local_key(&body.provision_key)
+    } else {
         let u = admin_user.clone();
         match web::block(move || crate::auth::verify_target_admin(&u, &admin_pass)).await {
             Ok(v) => v,
@@ -3729,10 +4405,15 @@ pub async fn cluster_join_handshake(
             &peer_ip,
         );
         if let Some(exp) = ott_expiry { state.one_time_join_tokens.reinsert(provided, exp); }
+        let error = if provisioned {
+            "Provisioning key rejected — this node was not enrolled by the provisioning flow."
+        } else {
+            "Admin credentials rejected — the supplied username/password \
+             must be an admin account on the node being added."
+        };
         return HttpResponse::Unauthorized().json(serde_json::json!({
             "valid": false,
-            "error": "Admin credentials rejected — the supplied username/password \
-                      must be an admin account on the node being added.",
+            "error": error,
         }));
     }
 
@@ -3827,6 +4508,10 @@ pub async fn cluster_join_handshake(
     // The one-time token (if used) was already consumed atomically at step 1
     // via `take`, and was reinserted on every failure path above — so a
     // success here means it is correctly single-use with no consume race.
+    // The provisioning key is single-use the same way.
+    if provisioned {
+        crate::provisioning::clear_local_key();
+    }
 
     // Mint a single-use bootstrap grant. The master presents it on the
     // immediately-following /api/cluster/secret/receive push so the fleet
@@ -3924,6 +4609,7 @@ fn cluster_join_audit(
         hostname,
         cluster,
     });
+    if let Some(entry) = log.last() { entry.publish(); }
     while log.len() > 200 {
         log.remove(0);
     }
@@ -4088,8 +4774,9 @@ pub async fn cluster_secret_repush(req: HttpRequest, state: web::Data<AppState>)
 //      online peer so they tombstone us instead of gossiping us back.
 //   2. Drop in-memory peer + tombstone state so any save fired during
 //      the shutdown gap writes empty.
-//   3. Unlink `self_cluster.json`, `nodes.json`, `deleted_nodes.json`,
-//      and `node_id` (see `agent::leave_wipe_membership_files`).
+//   3. Unlink `self_cluster.json`, `cluster.json`, `nodes.json`,
+//      `deleted_nodes.json` and `node_id` (see
+//      `agent::leave_wipe_membership_files`).
 //   4. Optionally rotate the cluster secret.
 //   5. Schedule `systemctl restart wolfstack` so the daemon comes back
 //      up with fresh state and a new `node_id`.
@@ -4429,7 +5116,7 @@ pub async fn cluster_secret_receive(req: HttpRequest, state: web::Data<AppState>
 }
 
 /// POST /api/nodes — add a server to the cluster
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 #[allow(dead_code)]
 pub struct AddServerRequest {
     pub address: String,
@@ -4459,6 +5146,10 @@ pub struct AddServerRequest {
     pub pve_cluster_name: Option<String>,
     #[serde(default)]
     pub cluster_name: Option<String>,     // Generic cluster name for WolfStack nodes
+    // Set only by provisioning enrolment, never from request JSON: the
+    // key the new machine proves itself with instead of admin credentials.
+    #[serde(skip)]
+    pub provision_key: Option<String>,
 }
 
 pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::Json<AddServerRequest>) -> HttpResponse {
@@ -4474,7 +5165,13 @@ pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::J
             "error": "Adding a node requires an interactive admin session."
         }));
     }
+    join_wolfstack_node(state, body.into_inner(), add_actor).await
+}
 
+/// The join itself, behind `add_node`'s operator check — also driven by
+/// provisioning enrolment (`provision_enroll`), which supplies
+/// `provision_key` in place of the target's admin credentials.
+async fn join_wolfstack_node(state: web::Data<AppState>, body: AddServerRequest, add_actor: String) -> HttpResponse {
     // SSRF prevention: block loopback and link-local addresses
     if is_ssrf_blocked_address(&body.address) {
         return HttpResponse::BadRequest().json(serde_json::json!({
@@ -4553,7 +5250,8 @@ pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::J
     // are NEVER logged or persisted on this master.
     let target_admin_username = body.target_admin_username.clone().unwrap_or_default();
     let target_admin_password = body.target_admin_password.clone().unwrap_or_default();
-    if target_admin_username.trim().is_empty() || target_admin_password.is_empty() {
+    let provision_key = body.provision_key.clone().unwrap_or_default();
+    if provision_key.is_empty() && (target_admin_username.trim().is_empty() || target_admin_password.is_empty()) {
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "The target node's admin username and password are required \
                       (proves you control the server you're adding)."
@@ -4569,6 +5267,7 @@ pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::J
         "token": join_token,
         "admin_username": target_admin_username,
         "admin_password": target_admin_password,
+        "provision_key": provision_key,
         "cluster_name": cluster_name,
         // So the joining node can seed US (the master) into its own registry by
         // our source IP — the fix for the membership split (wabil 2026-06-27).
@@ -4641,8 +5340,10 @@ pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::J
     }
 
     if !verified {
+        let hint = crate::selfcheck::join_hint(&body.address, port).await;
         return HttpResponse::BadGateway().json(serde_json::json!({
-            "error": format!("Cannot reach remote server at {}:{}. Tried every scheme — {}", body.address, port, last_error)
+            "error": format!("Cannot reach remote server at {}:{}. Tried every scheme — {}", body.address, port, last_error),
+            "hint": hint,
         }));
     }
 
@@ -4972,13 +5673,14 @@ pub async fn add_node(req: HttpRequest, state: web::Data<AppState>, body: web::J
 /// DELETE /api/nodes/{id} — remove a server
 pub async fn remove_node(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
     let id = path.into_inner();
     // Capture the node's identity BEFORE removal so we can also evict it from
     // every node's WolfNet peer list. Without this the deleted node lingers in
     // /etc/wolfnet/config.toml on each remaining peer and shows up forever as an
     // unreachable peer (wabil 2026-06-17). Cluster-sync's prune pass would
     // eventually catch it, but a delete should clean up immediately.
-    let removed_node = state.cluster.get_node(&id);
+    let removed_node = state.cluster.get_node(&id).or_else(|| state.cluster.get_archived_node(&id));
     if state.cluster.remove_server(&id) {
         // Evict from THIS node's WolfNet config right away (synchronous).
         // Best-effort: "not found" is fine, and a WolfNet-less node has nothing
@@ -5036,6 +5738,57 @@ pub async fn remove_node(req: HttpRequest, state: web::Data<AppState>, path: web
     }
 }
 
+/// POST /api/nodes/{id}/archive — take a node out of the cluster but keep
+/// its settings for a later restore. Peers follow through the gossiped
+/// archive mark; the WolfNet peer is kept so a restore just reconnects.
+pub async fn archive_node(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    if id == state.cluster.self_id {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Can't archive the node you're logged into" }));
+    }
+    if state.cluster.archive_server(&id) {
+        HttpResponse::Ok().json(serde_json::json!({ "archived": true }))
+    } else {
+        HttpResponse::NotFound().json(serde_json::json!({ "error": "Node not found" }))
+    }
+}
+
+/// GET /api/nodes/archived — archived nodes, newest first. Tokens are
+/// never returned, only whether one is kept.
+pub async fn list_archived_nodes(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let store = crate::auth::users::UserStore::load();
+    let user = if caller == "cluster-node" { None } else { store.find(&caller) };
+    let list: Vec<serde_json::Value> = state.cluster.get_archived_nodes().into_iter()
+        .filter(|a| user.is_none_or(|u| u.can_access_cluster(a.node.cluster_name.as_deref())))
+        .map(|a| {
+            let has_pve_token = a.node.pve_token.is_some();
+            let mut v = serde_json::to_value(crate::agent::Node { pve_token: None, ..a.node }).unwrap_or_default();
+            v["has_pve_token"] = serde_json::json!(has_pve_token);
+            v["archived_at"] = serde_json::json!(a.archived_at);
+            v
+        })
+        .collect();
+    HttpResponse::Ok().json(list)
+}
+
+/// POST /api/nodes/{id}/restore — put an archived node back, settings and
+/// all. The next poll brings it online.
+pub async fn restore_node(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    match state.cluster.restore_server(&id) {
+        Some(node) => HttpResponse::Ok().json(serde_json::json!({
+            "restored": true,
+            "id": node.id,
+            "hostname": node.hostname,
+        })),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "No archived node with that id" })),
+    }
+}
+
 /// PATCH /api/nodes/{id}/settings — update node settings
 #[derive(Deserialize)]
 pub struct UpdateNodeSettings {
@@ -5061,6 +5814,10 @@ pub struct UpdateNodeSettings {
     /// (general-purpose node); `None` (field absent) leaves them unchanged.
     #[serde(default)]
     pub roles: Option<Vec<crate::agent::NodeRole>>,
+    /// Labels — see `agent::Node::labels`. Replaces the whole set;
+    /// `Some({})` clears them, `None` leaves them unchanged.
+    #[serde(default)]
+    pub labels: Option<std::collections::BTreeMap<String, String>>,
 }
 
 pub async fn update_node_settings(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<UpdateNodeSettings>) -> HttpResponse {
@@ -5095,6 +5852,11 @@ pub async fn update_node_settings(req: HttpRequest, state: web::Data<AppState>,
             }));
         }
     }
+    if let Some(ref labels) = body.labels {
+        if let Err(e) = crate::agent::labels::validate(labels) {
+            return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+        }
+    }
 
     if state.cluster.update_node_settings(
         &id,
@@ -5225,6 +5987,36 @@ pub async fn update_node_settings(req: HttpRequest, state: web::Data<AppState>,
                 }
             }
         }
+        // Labels: owned by the node, same flow as roles. A non-WolfStack
+        // node (Proxmox etc.) has no daemon to own them, so they live in
+        // this node's nodes.json instead.
+        if let Some(ref labels) = body.labels {
+            let node = {
+                let mut nodes = state.cluster.nodes.write().unwrap();
+                nodes.get_mut(&id).map(|n| {
+                    n.labels = labels.clone();
+                    n.clone()
+                })
+            };
+            match node {
+                Some(n) if n.is_self => crate::agent::ClusterState::save_self_labels(labels),
+                Some(n) if n.node_type == "wolfstack" => {
+                    let state = state.clone();
+                    let payload = serde_json::json!({ "labels": labels });
+                    tokio::spawn(async move {
+                        let urls = build_node_urls(&n.address, n.port, "/api/settings/labels");
+                        if let Err(e) = post_json_to_node(&state, &urls, &payload, 5).await {
+                            tracing::warn!(
+                                "labels propagation push to '{}' ({}:{}) failed: {} — remote will re-assert its previous labels until next direct StatusReport",
+                                n.hostname, n.address, n.port, e
+                            );
+                        }
+                    });
+                }
+                Some(_) => state.cluster.save_nodes(),
+                None => {}
+            }
+        }
         // Propagate login_disabled to remote node so it takes effect on their login page
         if let Some(disabled) = body.login_disabled {
             let node = state.cluster.get_node(&id);
@@ -6217,6 +7009,177 @@ pub async fn cluster_diagnose(req: HttpRequest, state: web::Data<AppState>, body
     HttpResponse::Ok().json(serde_json::json!({ "results": results }))
 }
 
+/// Body for the `/api/cluster/diagnose/*` probes. `node_id` runs the probe
+/// on that node instead of this one; `count` is ping's echo count, mtr's
+/// cycles or traceroute's hop limit.
+#[derive(Deserialize, Serialize)]
+pub struct DiagnoseProbeRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+}
+
+/// Long enough for the slowest probe (mtr, 30 cycles) plus the hop.
+const DIAGNOSE_FORWARD_TIMEOUT_SECS: u64 = 150;
+
+/// Run a probe on another node: `Some(response)` if `node_id` names a
+/// node other than this one. The forwarded body drops `node_id` so the
+/// target runs it locally.
+async fn forward_diagnose(state: &web::Data<AppState>, path: &str, body: &mut DiagnoseProbeRequest) -> Option<HttpResponse> {
+    let node_id = body.node_id.take()?;
+    let node = match state.cluster.get_node(&node_id) {
+        Some(n) if n.is_self => return None,
+        Some(n) => n,
+        None => return Some(HttpResponse::NotFound().json(serde_json::json!({ "error": "Node not found" }))),
+    };
+    let payload = serde_json::to_value(&*body).unwrap_or_default();
+    Some(match post_json_to_node(state, &wolfstack_api_urls(&node, path), &payload, DIAGNOSE_FORWARD_TIMEOUT_SECS).await {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::BadGateway().json(serde_json::json!({ "error": format!("{}: {}", node.hostname, e) })),
+    })
+}
+
+fn diagnose_host(host: &str) -> Result<crate::validate::HostName, HttpResponse> {
+    crate::validate::HostName::parse(host)
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))
+}
+
+/// POST /api/cluster/diagnose/ping — ping a host from this node (or `node_id`)
+pub async fn diagnose_ping(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseProbeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut body = body.into_inner();
+    if let Some(resp) = forward_diagnose(&state, "/api/cluster/diagnose/ping", &mut body).await { return resp; }
+    let host = match diagnose_host(&body.host) { Ok(h) => h, Err(resp) => return resp };
+    match crate::networking::diagnostics::ping(&host, body.count.unwrap_or(4)).await {
+        Ok(r) => HttpResponse::Ok().json(r),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/cluster/diagnose/traceroute — trace the route to a host
+pub async fn diagnose_traceroute(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseProbeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut body = body.into_inner();
+    if let Some(resp) = forward_diagnose(&state, "/api/cluster/diagnose/traceroute", &mut body).await { return resp; }
+    let host = match diagnose_host(&body.host) { Ok(h) => h, Err(resp) => return resp };
+    match crate::networking::diagnostics::traceroute(&host, body.count.unwrap_or(30)).await {
+        Ok(r) => HttpResponse::Ok().json(r),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/cluster/diagnose/mtr — per-hop loss and latency to a host
+pub async fn diagnose_mtr(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseProbeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut body = body.into_inner();
+    if let Some(resp) = forward_diagnose(&state, "/api/cluster/diagnose/mtr", &mut body).await { return resp; }
+    let host = match diagnose_host(&body.host) { Ok(h) => h, Err(resp) => return resp };
+    match crate::networking::diagnostics::mtr(&host, body.count.unwrap_or(10)).await {
+        Ok(hops) => HttpResponse::Ok().json(serde_json::json!({ "host": host.as_str(), "hops": hops })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/cluster/diagnose/port — test a TCP connection to host:port
+pub async fn diagnose_port(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseProbeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut body = body.into_inner();
+    if let Some(resp) = forward_diagnose(&state, "/api/cluster/diagnose/port", &mut body).await { return resp; }
+    let host = match diagnose_host(&body.host) { Ok(h) => h, Err(resp) => return resp };
+    let Some(port) = body.port.filter(|p| *p > 0) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "port is required" }));
+    };
+    let r = crate::networking::diagnostics::check_port(&host, port, std::time::Duration::from_secs(5)).await;
+    HttpResponse::Ok().json(r)
+}
+
+/// POST /api/cluster/diagnose/dns — resolve a name, optionally against a given server
+pub async fn diagnose_dns(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseProbeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut body = body.into_inner();
+    if let Some(resp) = forward_diagnose(&state, "/api/cluster/diagnose/dns", &mut body).await { return resp; }
+    let name = match diagnose_host(&body.host) { Ok(h) => h, Err(resp) => return resp };
+    let server = match body.server.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(diagnose_host).transpose() {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let record_type = body.record_type.as_deref().unwrap_or("A");
+    HttpResponse::Ok().json(crate::networking::diagnostics::dns_lookup(&name, record_type, server.as_ref()).await)
+}
+
+#[derive(Deserialize)]
+pub struct DiagnoseReachRequest {
+    /// Node the connection starts from; this node if omitted.
+    #[serde(default)]
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// POST /api/cluster/diagnose/reach — explain why node `from` can or can't reach node `to`
+pub async fn diagnose_reach(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseReachRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let body = body.into_inner();
+    if let Some(from) = body.from.as_deref().filter(|f| *f != state.cluster.self_id) {
+        let Some(node) = state.cluster.get_node(from) else {
+            return HttpResponse::NotFound().json(serde_json::json!({ "error": "Node not found" }));
+        };
+        let urls = wolfstack_api_urls(&node, "/api/cluster/diagnose/reach");
+        return match post_json_to_node(&state, &urls, &serde_json::json!({ "to": body.to }), DIAGNOSE_FORWARD_TIMEOUT_SECS).await {
+            Ok(v) => HttpResponse::Ok().json(v),
+            // A itself being unreachable is an answer too.
+            Err(e) => HttpResponse::BadGateway().json(serde_json::json!({
+                "error": format!("couldn't ask {} to run the check — {}; run it from {} towards this node to see why", node.hostname, e, node.hostname),
+            })),
+        };
+    }
+    let node = match state.cluster.get_node(&body.to) {
+        Some(n) if n.is_self => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "from and to are the same node" })),
+        Some(n) => n,
+        None => return HttpResponse::NotFound().json(serde_json::json!({ "error": "Node not found" })),
+    };
+    let port = if node.node_type == "proxmox" { 8553 } else { node.port };
+    let target = crate::networking::diagnostics::ReachTarget { hostname: node.hostname.clone(), address: node.address.clone(), port };
+    let facts_body = serde_json::json!({ "peer": state.cluster.self_address, "port": port });
+    let remote = post_json_to_node(&state, &wolfstack_api_urls(&node, "/api/cluster/diagnose/facts"), &facts_body, 20).await
+        .and_then(|v| serde_json::from_value(v).map_err(|e| format!("unexpected reply: {}", e)));
+    HttpResponse::Ok().json(crate::networking::diagnostics::reach(&target, remote).await)
+}
+
+#[derive(Deserialize)]
+pub struct DiagnoseFactsRequest {
+    /// Address of the node asking; firewall rules are evaluated for it.
+    #[serde(default)]
+    pub peer: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+/// POST /api/cluster/diagnose/facts — this node's side of a reach check (listener, INPUT firewall, WolfNet)
+pub async fn diagnose_facts(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DiagnoseFactsRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let body = body.into_inner();
+    let peer = match body.peer.as_deref().map(crate::netaddr::strip_port).and_then(|p| crate::validate::HostName::parse(p).ok()) {
+        Some(h) => match h.ip() {
+            Some(ip) => Some(ip),
+            None => tokio::net::lookup_host((h.as_str(), 0)).await.ok().and_then(|mut a| a.next()).map(|a| a.ip()),
+        },
+        None => None,
+    };
+    let port = body.port;
+    match web::block(move || crate::networking::diagnostics::local_facts(peer, port)).await {
+        Ok(f) => HttpResponse::Ok().json(f),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// GET /api/nodes/{id}/pve/resources — list VMs and containers on a Proxmox node
 pub async fn get_pve_resources(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -6239,6 +7202,16 @@ pub async fn get_pve_resources(req: HttpRequest, state: web::Data<AppState>, pat
     }
 }
 
+/// GET /api/pve/clusters — cluster-wide totals (cores, RAM, storage,
+/// guests, HA) for every PVE cluster the caller can see. Cached for 30s;
+/// `?refresh=true` re-reads the PVE API.
+pub async fn pve_clusters(req: HttpRequest, state: web::Data<AppState>, query: web::Query<std::collections::HashMap<String, String>>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let refresh = query.get("refresh").is_some_and(|v| v == "true" || v == "1");
+    let nodes = filter_nodes_for_caller(&caller, state.cluster.get_all_nodes());
+    HttpResponse::Ok().json(crate::proxmox::cluster_summaries(&nodes, refresh).await)
+}
+
 /// POST /api/nodes/{id}/pve/{vmid}/{action} — start/stop/restart a Proxmox guest
 pub async fn pve_guest_action(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String, String)>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -6321,21 +7294,13 @@ pub async fn get_components(req: HttpRequest, state: web::Data<AppState>) -> Htt
 
 /// GET /api/components/{name}/detail — detailed component info with config and logs
 pub async fn get_component_detail(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
     let name = path.into_inner();
 
-    let component = match name.to_lowercase().as_str() {
-        "wolfnet" => installer::Component::WolfNet,
-        "wolfproxy" => installer::Component::WolfProxy,
-        "wolfserve" => installer::Component::WolfServe,
-        "wolfdisk" => installer::Component::WolfDisk,
-        "wolfscale" => installer::Component::WolfScale,
-        "mariadb" => installer::Component::MariaDB,
-        "postgresql" => installer::Component::PostgreSQL,
-        "certbot" => installer::Component::Certbot,
-        _ => return HttpResponse::BadRequest().json(serde_json::json!({
+    let Some(component) = installable_component(&name) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
             "error": format!("Unknown component: {}", name)
-        })),
+        }));
     };
 
     // Get service status
@@ -6381,21 +7346,13 @@ pub async fn save_component_config(
     path: web::Path<String>,
     body: web::Json<SaveConfigRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
     let name = path.into_inner();
 
-    let component = match name.to_lowercase().as_str() {
-        "wolfnet" => installer::Component::WolfNet,
-        "wolfproxy" => installer::Component::WolfProxy,
-        "wolfserve" => installer::Component::WolfServe,
-        "wolfdisk" => installer::Component::WolfDisk,
-        "wolfscale" => installer::Component::WolfScale,
-        "mariadb" => installer::Component::MariaDB,
-        "postgresql" => installer::Component::PostgreSQL,
-        "certbot" => installer::Component::Certbot,
-        _ => return HttpResponse::BadRequest().json(serde_json::json!({
+    let Some(component) = installable_component(&name) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
             "error": format!("Unknown component: {}", name)
-        })),
+        }));
     };
 
     let config_path = match component.config_path() {
@@ -6405,9 +7362,11 @@ pub async fn save_component_config(
         })),
     };
 
-    match std::fs::write(config_path, &body.content) {
+    if let Err(e) = crate::config_check::check(config_path, &body.content) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    match crate::config_history::save(config_path, &body.content, &caller, "Config editor") {
         Ok(_) => {
-
             HttpResponse::Ok().json(serde_json::json!({
                 "message": format!("Config saved. Restart {} to apply changes.", component.service_name())
             }))
@@ -6422,18 +7381,10 @@ pub async fn save_component_config(
 pub async fn install_component(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let component = match name.to_lowercase().as_str() {
-        "wolfnet" => installer::Component::WolfNet,
-        "wolfproxy" => installer::Component::WolfProxy,
-        "wolfserve" => installer::Component::WolfServe,
-        "wolfdisk" => installer::Component::WolfDisk,
-        "wolfscale" => installer::Component::WolfScale,
-        "mariadb" => installer::Component::MariaDB,
-        "postgresql" => installer::Component::PostgreSQL,
-        "certbot" => installer::Component::Certbot,
-        _ => return HttpResponse::BadRequest().json(serde_json::json!({
+    let Some(component) = installable_component(&name) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
             "error": format!("Unknown component: {}", name)
-        })),
+        }));
     };
 
     // web::block: the install pipes `curl | bash` synchronously and can run
@@ -6449,6 +7400,329 @@ pub async fn install_component(req: HttpRequest, state: web::Data<AppState>, pat
     }
 }
 
+/// Every component `installer::install_component` knows how to install.
+fn installable_component(name: &str) -> Option<installer::Component> {
+    match name.to_lowercase().as_str() {
+        "wolfnet" => Some(installer::Component::WolfNet),
+        "wolfproxy" => Some(installer::Component::WolfProxy),
+        "wolfserve" => Some(installer::Component::WolfServe),
+        "wolfdisk" => Some(installer::Component::WolfDisk),
+        "wolfscale" => Some(installer::Component::WolfScale),
+        "mariadb" => Some(installer::Component::MariaDB),
+        "postgresql" => Some(installer::Component::PostgreSQL),
+        "certbot" => Some(installer::Component::Certbot),
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ClusterInstallRequest {
+    /// Explicit targets. Either this or `selector`.
+    #[serde(default)]
+    pub node_ids: Vec<String>,
+    /// Label selector naming the targets, as `/api/nodes/bulk` takes it.
+    #[serde(default)]
+    pub selector: String,
+}
+
+/// POST /api/components/{name}/install-cluster — install or update a
+/// component on the selected nodes in parallel. Answers with the rollout
+/// job; poll `/api/components/install-jobs/{id}` for per-node progress.
+pub async fn install_component_cluster(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ClusterInstallRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "install across the cluster") { return resp; }
+    let name = path.into_inner();
+    let Some(component) = installable_component(&name) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Unknown component: {}", name) }));
+    };
+    let body = body.into_inner();
+    let candidates = if !body.selector.trim().is_empty() {
+        match crate::agent::labels::LabelSelector::parse(&body.selector) {
+            Ok(s) if !s.is_empty() => state.cluster.nodes_matching(&s),
+            Ok(_) => Vec::new(),
+            Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid selector: {}", e) })),
+        }
+    } else if !body.node_ids.is_empty() {
+        state.cluster.get_all_nodes().into_iter().filter(|n| body.node_ids.contains(&n.id)).collect()
+    } else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "node_ids or selector is required" }));
+    };
+    let targets: Vec<crate::agent::Node> = filter_nodes_for_caller(&caller, candidates)
+        .into_iter()
+        .filter(|n| n.node_type == "wolfstack")
+        .collect();
+    match installer::rollout::start(component, targets, state.cluster_secret.clone(), &caller) {
+        Ok(job) => HttpResponse::Ok().json(job),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// GET /api/components/install-jobs — current and recent cluster installs
+pub async fn component_install_jobs(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(installer::rollout::jobs())
+}
+
+/// GET /api/components/install-jobs/{id} — one cluster install with per-node status
+pub async fn component_install_job(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match installer::rollout::job(&path) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Install job not found or expired" })),
+    }
+}
+
+/// Wolf components that `installer::upgrade` can swap binaries for.
+fn upgradable_component(name: &str) -> Option<installer::Component> {
+    match name.to_lowercase().as_str() {
+        "wolfnet" => Some(installer::Component::WolfNet),
+        "wolfproxy" => Some(installer::Component::WolfProxy),
+        "wolfserve" => Some(installer::Component::WolfServe),
+        "wolfdisk" => Some(installer::Component::WolfDisk),
+        "wolfscale" => Some(installer::Component::WolfScale),
+        _ => None,
+    }
+}
+
+/// GET /api/components/versions — installed component versions on every
+/// node of this cluster, plus any compatibility warnings
+pub async fn component_versions(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let rows = installer::versions::matrix();
+    let issues = installer::versions::mismatches(&rows);
+    let components: Vec<&str> = installer::Component::all().iter().map(|c| c.service_name()).collect();
+    HttpResponse::Ok().json(serde_json::json!({ "components": components, "nodes": rows, "issues": issues }))
+}
+
+/// GET /api/components/{name}/upgrade — default binary URL and upgrade history
+pub async fn component_upgrade_info(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let Some(component) = upgradable_component(&path) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Only Wolf components have blue/green upgrades" }));
+    };
+    HttpResponse::Ok().json(serde_json::json!({
+        "default_url": installer::upgrade::default_binary_url(component),
+        "trial_run": installer::upgrade::runs_in_parallel(component),
+        "version": installer::get_component_version(component),
+        "history": installer::upgrade::history(component),
+    }))
+}
+
+/// POST /api/components/{name}/upgrade — download, trial-run, swap, health
+/// check, roll back on failure. Returns the step-by-step report.
+pub async fn component_upgrade(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<installer::upgrade::UpgradeRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "upgrade components") { return resp; }
+    let Some(component) = upgradable_component(&path) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Only Wolf components have blue/green upgrades" }));
+    };
+    // Runs for a minute or more (download, two soak periods).
+    match web::block(move || installer::upgrade::upgrade(component, body.into_inner())).await {
+        Ok(Ok(report)) => HttpResponse::Ok().json(report),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("upgrade worker failed: {}", e) })),
+    }
+}
+
+/// POST /api/components/{name}/rollback — back to the binary the last
+/// upgrade kept
+pub async fn component_rollback(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "roll back components") { return resp; }
+    let Some(component) = upgradable_component(&path) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Only Wolf components have blue/green upgrades" }));
+    };
+    match web::block(move || installer::upgrade::rollback(component)).await {
+        Ok(Ok(report)) => HttpResponse::Ok().json(report),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("rollback worker failed: {}", e) })),
+    }
+}
+
+// ─── Config History API ───
+
+#[derive(Deserialize)]
+pub struct ConfigHistoryQuery {
+    pub path: String,
+    /// Revision to show, or the old side of a diff.
+    #[serde(default)]
+    pub id: Option<u64>,
+    /// New side of a diff; the live file when absent.
+    #[serde(default)]
+    pub to: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfigRollbackRequest {
+    pub path: String,
+    pub id: u64,
+}
+
+/// Whether `caller` may see and roll back the history of `path`. The path
+/// arrives in the query/body, out of sight of the tenancy path check: a
+/// tenant-scoped caller only gets the configs of its own LXC containers,
+/// and no host files.
+fn config_history_access(req: &HttpRequest, caller: &str, path: &str) -> Result<(), String> {
+    match containers::lxc_of_config_path(path) {
+        Some(ct) => crate::auth::tenancy::check_console(req, caller, "lxc", &ct),
+        None if crate::auth::tenancy::scope(req, caller).is_some() => Err(format!("No history for {}", path)),
+        None => Ok(()),
+    }
+}
+
+/// GET /api/config-history — files with a revision history on this node
+pub async fn config_history_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let mut files = crate::config_history::tracked();
+    files.retain(|f| config_history_access(&req, &caller, &f.path).is_ok());
+    HttpResponse::Ok().json(files)
+}
+
+/// GET /api/config-history/file?path= — one file's revisions, newest first
+pub async fn config_history_file(req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfigHistoryQuery>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(e) = config_history_access(&req, &caller, &query.path) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "path": query.path,
+        "revisions": crate::config_history::revisions(&query.path),
+    }))
+}
+
+/// GET /api/config-history/revision?path=&id= — a revision's contents
+pub async fn config_history_revision(req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfigHistoryQuery>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(e) = config_history_access(&req, &caller, &query.path) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    let Some(id) = query.id else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "id is required" }));
+    };
+    match crate::config_history::content(&query.path, id) {
+        Ok(content) => HttpResponse::Ok().json(serde_json::json!({ "path": query.path, "id": id, "content": content })),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// GET /api/config-history/diff?path=&id=[&to=] — unified diff from a
+/// revision to another revision or to the live file
+pub async fn config_history_diff(req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfigHistoryQuery>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(e) = config_history_access(&req, &caller, &query.path) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    let Some(id) = query.id else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "id is required" }));
+    };
+    match crate::config_history::diff(&query.path, id, query.to) {
+        Ok(diff) => HttpResponse::Ok().json(serde_json::json!({ "path": query.path, "diff": diff })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/config-history/rollback — write a revision back to its file
+pub async fn config_history_rollback(req: HttpRequest, state: web::Data<AppState>, body: web::Json<ConfigRollbackRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(e) = config_history_access(&req, &caller, &body.path) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    match crate::config_history::rollback(&body.path, body.id, &caller) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "message": format!("{} rolled back. Restart the service to apply.", body.path)
+        })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+// ─── Config Drift API ───
+
+#[derive(Deserialize)]
+pub struct DriftPathRequest {
+    pub path: String,
+}
+
+fn drift_broadcast_soon(state: &web::Data<AppState>) {
+    tokio::spawn(crate::drift::broadcast_to_cluster(state.cluster.clone(), state.cluster_secret.clone()));
+}
+
+/// GET /api/drift — cluster baselines and this node's latest drift scan
+pub async fn drift_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(serde_json::json!({
+        "baselines": crate::drift::config().baselines,
+        "scan": crate::drift::last_scan(),
+    }))
+}
+
+/// POST /api/drift/scan — scan this node now
+pub async fn drift_scan(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(crate::drift::scan).await {
+        Ok(scan) => HttpResponse::Ok().json(scan),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("scan worker failed: {}", e) })),
+    }
+}
+
+/// POST /api/drift/baselines — pin this node's copy of a file as the
+/// cluster's desired state
+pub async fn drift_pin(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DriftPathRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "pin config baselines") { return resp; }
+    let hostname = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    match crate::drift::pin(&body.path, &caller, &hostname) {
+        Ok(cfg) => {
+            drift_broadcast_soon(&state);
+            let _ = web::block(crate::drift::scan).await;
+            HttpResponse::Ok().json(serde_json::json!({ "baselines": cfg.baselines }))
+        }
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/drift/baselines — stop holding a file to a baseline
+pub async fn drift_unpin(req: HttpRequest, state: web::Data<AppState>, body: web::Json<DriftPathRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "remove config baselines") { return resp; }
+    match crate::drift::unpin(&body.path) {
+        Ok(cfg) => {
+            drift_broadcast_soon(&state);
+            let _ = web::block(crate::drift::scan).await;
+            HttpResponse::Ok().json(serde_json::json!({ "baselines": cfg.baselines }))
+        }
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/drift/sync — a peer's baselines (cluster secret only)
+pub async fn drift_sync(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::drift::DriftConfig>) -> HttpResponse {
+    let secret = req.headers().get("X-WolfStack-Secret")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !crate::auth::validate_inter_node_secret(secret, &state.cluster_secret) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Invalid cluster secret" }));
+    }
+    match crate::drift::merge_from_peer(body.into_inner()) {
+        Ok(applied) => {
+            if applied {
+                tokio::task::spawn_blocking(crate::drift::scan);
+            }
+            HttpResponse::Ok().json(serde_json::json!({ "synced": applied }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ─── Service Control API ───
 
 #[derive(Deserialize)]
@@ -6680,6 +7954,9 @@ pub async fn cron_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResp
 /// POST /api/cron — add or edit a cron entry
 pub async fn cron_save(req: HttpRequest, state: web::Data<AppState>, body: web::Json<CronJobRequest>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if let Err(e) = crate::config_check::check_cron_line(&format!("{} {}", body.schedule, body.command), false) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid cron entry: {}", e) }));
+    }
     let (mut lines, _) = read_crontab();
     let comment_suffix = if body.comment.is_empty() { String::new() } else { format!(" # {}", body.comment) };
     let new_line = if body.enabled {
@@ -6787,6 +8064,9 @@ pub async fn container_cron_list(req: HttpRequest, state: web::Data<AppState>, p
 /// POST /api/containers/{runtime}/{id}/cron — add or edit a cron entry inside a container
 pub async fn container_cron_save(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>, body: web::Json<CronJobRequest>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if let Err(e) = crate::config_check::check_cron_line(&format!("{} {}", body.schedule, body.command), false) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid cron entry: {}", e) }));
+    }
     let (runtime, container) = path.into_inner();
     let (mut lines, _) = read_container_crontab(&runtime, &container);
     let comment_suffix = if body.comment.is_empty() { String::new() } else { format!(" # {}", body.comment) };
@@ -7379,11 +8659,11 @@ pub async fn agent_status(req: HttpRequest, state: web::Data<AppState>) -> HttpR
     let st = state.clone().into_inner();
     let (metrics, components, docker_count, lxc_count, vm_count, compose_count, has_docker, has_lxc, has_kvm) =
         tokio::task::spawn_blocking(move || {
-            let m = st.monitor.lock().unwrap().collect();
+            let m = crate::monitoring::inventory::metrics_blocking(&st);
             let c = installer::get_all_status();
-            let dc = containers::docker_list_all().len() as u32;
-            let lc = containers::lxc_list_all().len() as u32;
-            let vc = st.vms.lock().unwrap().list_vms().len() as u32;
+            let dc = containers::docker_count();
+            let lc = containers::lxc_count();
+            let vc = crate::monitoring::inventory::vms_blocking(&st).len() as u32;
             let cmc = compose_stack_count();
             let hd = containers::docker_status().installed;
             let hl = containers::lxc_status().installed;
@@ -7404,6 +8684,7 @@ pub async fn agent_status(req: HttpRequest, state: web::Data<AppState>) -> HttpR
         public_ip,
         known_nodes: state.cluster.get_all_nodes(),
         deleted_ids: state.cluster.get_deleted_ids(),
+        archive_marks: state.cluster.get_archive_marks(),
         wolfnet_ips: containers::wolfnet_used_ips(),
         has_docker,
         has_lxc,
@@ -7422,6 +8703,8 @@ pub async fn agent_status(req: HttpRequest, state: web::Data<AppState>) -> HttpR
         // Self's assigned tier roles — gossiped so peers know which nodes
         // carry the DNS / mail / ingress / host roles.
         roles: state.cluster.get_node(&state.cluster.self_id).map(|n| n.roles).unwrap_or_default(),
+        // Self's operator-set labels, for label selectors on peers.
+        labels: state.cluster.get_node(&state.cluster.self_id).map(|n| n.labels).unwrap_or_default(),
         license_key: if crate::compat::platform_ready() {
             std::fs::read_to_string(crate::compat::dm_path()).ok().map(|s| s.trim().to_string())
         } else { None },
@@ -7752,7 +9035,13 @@ pub async fn node_proxy(
     path: web::Path<(String, String)>,
     body: web::Bytes,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    // The peer trusts the cluster secret, so a tenant-scoped caller's
+    // tenant travels with the request for the peer to enforce.
+    let tenant = crate::auth::tenancy::scope(&req, &caller);
+    // Likewise whether they're an admin, which the peer can't tell from
+    // the cluster secret alone.
+    let non_admin = !crate::auth::caller_is_admin(&req, &caller);
 
     let (node_id, api_path) = path.into_inner();
 
@@ -7803,6 +9092,20 @@ pub async fn node_proxy(
         // "a peer synced state to me" (e.g. image_watcher_config_save) key off
         // this stamp. Handlers that don't care simply ignore it.
         builder = builder.header("X-WolfStack-Proxied", "1");
+        if let Some(ref t) = tenant {
+            builder = builder.header(crate::auth::tenancy::TENANT_HEADER, t);
+        }
+        if non_admin {
+            builder = builder.header(crate::auth::NON_ADMIN_HEADER, "1");
+        }
+        // Same request ID on the peer, so its log lines join up with ours.
+        if let Some(id) = crate::logging::request_id_of(&req) {
+            builder = builder.header("X-Request-Id", id);
+        }
+        // Pass Range through so downloads from a remote node can resume.
+        if let Some(range) = req.headers().get("range").and_then(|v| v.to_str().ok()) {
+            builder = builder.header("range", range);
+        }
         if !body_vec.is_empty() {
             builder = builder.body(body_vec.clone());
         }
@@ -7835,6 +9138,33 @@ pub async fn node_proxy(
                         .streaming(byte_stream);
                 }
 
+                // Stream file downloads (attachments, archives, ranged
+                // reads) instead of buffering a multi-GB body in RAM.
+                let is_download = resp.headers().contains_key("content-disposition")
+                    || resp.headers().contains_key("content-range");
+                if is_download {
+                    use futures::StreamExt;
+                    let mut out = HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap_or(actix_web::http::StatusCode::OK));
+                    out.content_type(resp_ct);
+                    for name in ["content-disposition", "content-range", "accept-ranges"] {
+                        if let Some(v) = resp.headers().get(name).and_then(|v| v.to_str().ok()) {
+                            out.insert_header((name, v.to_string()));
+                        }
+                    }
+                    out.insert_header(("Content-Encoding", "identity"));
+                    // Keep the length so the browser can show progress.
+                    let length = resp.content_length();
+                    let byte_stream = resp.bytes_stream().map(|chunk| {
+                        chunk
+                            .map(|b| actix_web::web::Bytes::copy_from_slice(&b))
+                            .map_err(|e| std::io::Error::other(e.to_string()))
+                    });
+                    return match length {
+                        Some(n) => out.body(actix_web::body::SizedStream::new(n, byte_stream)),
+                        None => out.streaming(byte_stream),
+                    };
+                }
+
                 match resp.bytes().await {
                     Ok(bytes) => {
                         return HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap_or(actix_web::http::StatusCode::OK))
@@ -8016,6 +9346,170 @@ pub async fn container_web_url(
     HttpResponse::Ok().json(serde_json::json!({ "open_host": null, "open_port": null }))
 }
 
+/// GET /api/containers/{runtime}/{id}/mount-deps — storage mounts a container requires
+pub async fn container_mount_deps(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let (runtime, name) = path.into_inner();
+    if runtime != "docker" && runtime != "lxc" {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "runtime must be docker or lxc" }));
+    }
+    let result = web::block(move || {
+        let required = containers::mount_deps::MountDeps::load().required(&runtime, &name);
+        let mounts = crate::auth::tenancy::visible(scope.as_deref(), storage::load_config().mounts,
+            |m| Some((crate::auth::tenancy::Kind::Mount, m.id.as_str())));
+        let binds = if runtime == "docker" { containers::docker_list_volumes(&name) } else { containers::lxc_list_mounts(&name) };
+        let host_paths: Vec<String> = binds.into_iter().map(|b| b.host_path).collect();
+        let suggested = containers::mount_deps::covering(&host_paths, &mounts);
+        let available: Vec<serde_json::Value> = mounts.iter().map(|m| serde_json::json!({
+            "id": m.id, "name": m.name, "mount_point": m.mount_point, "auto_mount": m.auto_mount,
+        })).collect();
+        serde_json::json!({ "mounts": required, "suggested": suggested, "available": available })
+    }).await;
+    match result {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MountDepsRequest {
+    #[serde(default)]
+    pub mounts: Vec<String>,
+}
+
+/// PUT /api/containers/{runtime}/{id}/mount-deps — set the storage mounts a container requires
+pub async fn container_mount_deps_set(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<MountDepsRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let (runtime, name) = path.into_inner();
+    let ids = body.into_inner().mounts;
+    let result = web::block(move || {
+        let mounts = crate::auth::tenancy::visible(scope.as_deref(), storage::load_config().mounts,
+            |m| Some((crate::auth::tenancy::Kind::Mount, m.id.as_str())));
+        if let Some(unknown) = ids.iter().find(|id| !mounts.iter().any(|m| &m.id == *id)) {
+            return Err(format!("Mount '{}' not found", unknown));
+        }
+        let mut deps = containers::mount_deps::MountDeps::load();
+        deps.set(&runtime, &name, ids)?;
+        deps.save()?;
+        Ok(deps.required(&runtime, &name))
+    }).await;
+    match result {
+        Ok(Ok(mounts)) => HttpResponse::Ok().json(serde_json::json!({ "mounts": mounts })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// This node's LXC containers and VMs as `(kind, name, autostart, running)`;
+/// call from the blocking pool.
+fn startup_guests(state: &AppState) -> Vec<(&'static str, String, bool, bool)> {
+    let mut guests: Vec<(&'static str, String, bool, bool)> = containers::lxc_list_all_cached().into_iter()
+        .map(|c| ("lxc", c.name, c.autostart, c.state == "running"))
+        .collect();
+    guests.extend(crate::monitoring::inventory::vms_blocking(state).into_iter()
+        .map(|vm| ("vm", vm.name, vm.auto_start, vm.running)));
+    guests
+}
+
+/// Boot sequence for the guests set to autostart, and any cycle in it.
+fn startup_plan(
+    guests: &[(&'static str, String, bool, bool)],
+    cfg: &containers::startup_order::StartupOrder,
+) -> (Vec<String>, Vec<String>) {
+    let boot: Vec<containers::startup_order::BootGuest> = guests.iter()
+        .filter(|g| g.2)
+        .map(|g| containers::startup_order::BootGuest { kind: g.0.to_string(), name: g.1.clone(), delay_s: 0 })
+        .collect();
+    let (order, cyclic) = containers::startup_order::plan(boot, cfg);
+    (order.iter().map(|g| g.key()).collect(), cyclic)
+}
+
+/// GET /api/autostart — start order, delays and dependencies of this node's guests
+pub async fn autostart_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let st = state.clone();
+    let result = web::block(move || {
+        let cfg = containers::startup_order::StartupOrder::load();
+        let guests = startup_guests(&st);
+        let (boot_order, cycle) = startup_plan(&guests, &cfg);
+        let list: Vec<serde_json::Value> = guests.iter().map(|(kind, name, autostart, running)| {
+            let entry = cfg.get(kind, name);
+            serde_json::json!({
+                "kind": kind, "name": name, "autostart": autostart, "running": running,
+                "order": entry.order, "delay_s": entry.delay_s, "depends_on": entry.depends_on,
+            })
+        }).collect();
+        let mounts: Vec<serde_json::Value> = storage::load_config().mounts.iter()
+            .map(|m| serde_json::json!({ "id": m.id, "name": m.name, "mount_point": m.mount_point }))
+            .collect();
+        serde_json::json!({
+            "proxmox": containers::is_proxmox(),
+            "guests": list,
+            "mounts": mounts,
+            "boot_order": boot_order,
+            "cycle": cycle,
+        })
+    }).await;
+    match result {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// PUT /api/autostart/{kind}/{name} — set a guest's start order, delay and dependencies
+pub async fn autostart_set(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<containers::startup_order::GuestStartup>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let (kind, name) = path.into_inner();
+    let entry = body.into_inner();
+    let st = state.clone();
+    let result = web::block(move || {
+        if containers::is_proxmox() {
+            return Err("Proxmox starts guests at boot itself — set the start order under the guest's Options in Proxmox.".to_string());
+        }
+        let guests = startup_guests(&st);
+        if !guests.iter().any(|g| g.0 == kind && g.1 == name) {
+            return Err(format!("{} '{}' not found on this node", kind, name));
+        }
+        let mounts = storage::load_config().mounts;
+        for dep in &entry.depends_on {
+            let known = match dep.split_once(':') {
+                Some(("mount", id)) => mounts.iter().any(|m| m.id == id),
+                Some((k, n)) => guests.iter().any(|g| g.0 == k && g.1 == n),
+                None => true, // malformed — set() says why
+            };
+            if !known {
+                return Err(format!("Dependency '{}' not found", dep));
+            }
+        }
+        let mut cfg = containers::startup_order::StartupOrder::load();
+        cfg.set(&kind, &name, entry)?;
+        cfg.save()?;
+        let (boot_order, cycle) = startup_plan(&guests, &cfg);
+        Ok(serde_json::json!({ "startup": cfg.get(&kind, &name), "boot_order": boot_order, "cycle": cycle }))
+    }).await;
+    match result {
+        Ok(Ok(v)) => HttpResponse::Ok().json(v),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 pub async fn container_runtime_status(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     // docker_status()/lxc_status() probe the runtimes via subprocess — offload so
@@ -8033,13 +9527,15 @@ pub async fn container_runtime_status(req: HttpRequest, state: web::Data<AppStat
 
 /// GET /api/containers/docker — list all Docker containers
 pub async fn docker_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
     // `docker_list_all_cached` runs `docker ps`/inspect subprocesses on a cache
     // miss. Running that directly in the async handler blocked an actix request
     // worker; under the page-load burst that starved the worker pool and the
     // list came up blank for 10-20s (KO4BSR/wabil). Offload to the blocking pool.
     match web::block(containers::docker_list_all_cached).await {
-        Ok(containers) => HttpResponse::Ok().json(containers),
+        Ok(containers) => HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), containers,
+            |c| Some((crate::auth::tenancy::Kind::Docker, c.name.as_str())))),
         // A non-OK (not an empty 200) so the frontend keeps the current list
         // instead of blanking it on a rare blocking-pool error.
         Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "docker list unavailable"})),
@@ -8099,7 +9595,7 @@ pub async fn global_search(
 
         // VMs → Proxmox combined view on PVE hosts, native VM view otherwise
         let vm_view = if containers::is_proxmox() { "pve-resources" } else { "vms" };
-        let vms = state.vms.lock().unwrap().list_vms();
+        let vms = crate::monitoring::inventory::vms_blocking(&state);
         for vm in vms.into_iter()
             .filter(|v| v.name.to_lowercase().contains(&q))
             .take(PER_KIND)
@@ -8260,9 +9756,91 @@ pub async fn docker_search(
     HttpResponse::Ok().json(results)
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct DockerPullRequest {
     pub image: String,
+    /// Pull on this cluster node instead of here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<String>,
+}
+
+/// Where a create should run: `None` for this node, or the peer that
+/// `target_node` names. Err is the response for an unknown or offline node.
+fn create_target(state: &web::Data<AppState>, target: Option<&str>) -> Result<Option<crate::agent::Node>, HttpResponse> {
+    let Some(id) = target.map(str::trim).filter(|t| !t.is_empty() && *t != state.cluster.self_id) else {
+        return Ok(None);
+    };
+    match state.cluster.get_node(id) {
+        None => Err(HttpResponse::NotFound().json(serde_json::json!({ "error": format!("Target node '{}' not found", id) }))),
+        Some(n) if n.is_self => Ok(None),
+        Some(n) if !n.online => Err(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": format!("Target node {} is offline", n.hostname)
+        }))),
+        Some(n) => Ok(Some(n)),
+    }
+}
+
+/// Run a create on `node` and relay its answer with the peer's own status,
+/// so a port clash is still a 409 and a bad request still a 400. Errors
+/// are prefixed with the node's hostname; successes carry `node_id` and
+/// `node`. The caller's tenant travels along as with node_proxy, so the
+/// peer allocates the WolfNet address from the tenant's subnet and records
+/// the new container as theirs. Only a failure to connect moves on to the
+/// node's next address — anything later may have created the container.
+async fn forward_create(
+    req: &HttpRequest,
+    state: &web::Data<AppState>,
+    caller: &str,
+    node: &crate::agent::Node,
+    path: &str,
+    body: &serde_json::Value,
+    timeout_secs: u64,
+) -> HttpResponse {
+    let tenant = crate::auth::tenancy::scope(req, caller);
+    let non_admin = !crate::auth::caller_is_admin(req, caller);
+    let mut last_err = String::from("no address to try");
+    for url in wolfstack_api_urls(node, path) {
+        let mut builder = API_HTTP_CLIENT.post(&url)
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .header("X-WolfStack-Secret", state.cluster_secret.clone())
+            .header("X-WolfStack-Proxied", "1")
+            .json(body);
+        if let Some(ref t) = tenant {
+            builder = builder.header(crate::auth::tenancy::TENANT_HEADER, t);
+        }
+        if non_admin {
+            builder = builder.header(crate::auth::NON_ADMIN_HEADER, "1");
+        }
+        if let Some(id) = crate::logging::request_id_of(req) {
+            builder = builder.header("X-Request-Id", id);
+        }
+        match builder.send().await {
+            Ok(r) => {
+                let status = actix_web::http::StatusCode::from_u16(r.status().as_u16())
+                    .unwrap_or(actix_web::http::StatusCode::BAD_GATEWAY);
+                let mut data = r.json::<serde_json::Value>().await.unwrap_or_else(|_| serde_json::json!({}));
+                if status.is_success() {
+                    if let Some(obj) = data.as_object_mut() {
+                        obj.insert("node_id".into(), serde_json::json!(node.id));
+                        obj.insert("node".into(), serde_json::json!(node.hostname));
+                    }
+                } else {
+                    let err = data.get("error").and_then(|v| v.as_str()).unwrap_or("request failed");
+                    data = serde_json::json!({ "error": format!("{}: {}", node.hostname, err) });
+                }
+                return HttpResponse::build(status).json(data);
+            }
+            Err(e) if e.is_connect() => last_err = e.to_string(),
+            Err(e) => {
+                return HttpResponse::BadGateway().json(serde_json::json!({
+                    "error": format!("{}: {}", node.hostname, e)
+                }));
+            }
+        }
+    }
+    HttpResponse::BadGateway().json(serde_json::json!({
+        "error": format!("Could not reach node {} ({}) — last error: {}", node.hostname, node.address, last_err)
+    }))
 }
 
 /// POST /api/containers/docker/pull — pull a Docker image
@@ -8271,14 +9849,23 @@ pub async fn docker_pull(
     state: web::Data<AppState>,
     body: web::Json<DockerPullRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let mut body = body.into_inner();
+    match create_target(&state, body.target_node.take().as_deref()) {
+        Err(resp) => return resp,
+        Ok(Some(node)) => {
+            let payload = serde_json::to_value(&body).unwrap_or_default();
+            return forward_create(&req, &state, &caller, &node, "/api/containers/docker/pull", &payload, 900).await;
+        }
+        Ok(None) => {}
+    }
     match containers::docker_pull(&body.image) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct DockerCreateRequest {
     pub name: String,
     pub image: String,
@@ -8291,6 +9878,12 @@ pub struct DockerCreateRequest {
     /// Volume mounts: ["host:container", "volume_name:/data", ...]
     #[serde(default)]
     pub volumes: Vec<String>,
+    /// Join this WolfRouter HTTP proxy's upstream pool once created.
+    #[serde(default)]
+    pub proxy: Option<crate::networking::router::auto_upstream::Registration>,
+    /// Create on this cluster node instead of here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<String>,
 }
 
 /// POST /api/containers/docker/create — create a Docker container
@@ -8299,15 +9892,50 @@ pub async fn docker_create(
     state: web::Data<AppState>,
     body: web::Json<DockerCreateRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let mut body = body.into_inner();
+    // Forward before anything here claims a WolfNet IP — the lease belongs
+    // to the node the container runs on.
+    match create_target(&state, body.target_node.take().as_deref()) {
+        Err(resp) => return resp,
+        Ok(Some(node)) => {
+            let payload = serde_json::to_value(&body).unwrap_or_default();
+            return forward_create(&req, &state, &caller, &node, "/api/containers/docker/create", &payload, 120).await;
+        }
+        Ok(None) => {}
+    }
     let ports = body.ports.as_deref().unwrap_or(&[]);
     let env = body.env.as_deref().unwrap_or(&[]);
     let wolfnet_ip = body.wolfnet_ip.as_deref();
+    if let Some(ip) = wolfnet_ip.filter(|ip| !ip.is_empty()) {
+        if let Err(e) = containers::ipam::claim(ip) {
+            return HttpResponse::Conflict().json(serde_json::json!({ "error": e }));
+        }
+    }
     let memory = body.memory_limit.as_deref();
     let cpus = body.cpu_cores.as_deref();
     let storage = body.storage_limit.as_deref();
+    let capacity_warning = match crate::capacity_policy::check(
+        &format!("Container '{}'", body.name), crate::capacity_policy::Resources::from_limits(memory, cpus),
+    ) {
+        Ok(w) => w,
+        Err(e) => return HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    };
+    if let Some(reg) = &body.proxy
+        && let Err(e) = crate::networking::router::auto_upstream::validate(&state.router.config.read().unwrap().http_proxies, reg)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
     match containers::docker_create(&body.name, &body.image, ports, env, wolfnet_ip, memory, cpus, storage, &body.volumes) {
-        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Ok(msg) => {
+            crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Docker, &body.name);
+            fire_container_event("docker", &body.name, "create");
+            let proxy_warning = body.proxy.as_ref()
+                .and_then(|reg| register_auto_upstream(&state, "docker", &body.name, reg).err());
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": msg, "capacity_warning": capacity_warning, "proxy_warning": proxy_warning,
+            }))
+        }
         // Port-conflict pre-flight rejections start with "Cannot
         // create container `…`: requested host port …" — that's a
         // user input error, not a server fault, so return 409
@@ -8320,6 +9948,17 @@ pub async fn docker_create(
     }
 }
 
+/// GET /api/containers/docker/storage-quota — whether the create form's
+/// disk quota (`--storage-opt size=`) works on this host's storage driver.
+pub async fn docker_storage_quota(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(containers::docker_storage_quota_support).await {
+        Ok(Ok(driver)) => HttpResponse::Ok().json(serde_json::json!({ "supported": true, "driver": driver })),
+        Ok(Err(reason)) => HttpResponse::Ok().json(serde_json::json!({ "supported": false, "reason": reason })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// GET /api/containers/lxc/templates — list available LXC templates
 pub async fn lxc_templates(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -8327,7 +9966,104 @@ pub async fn lxc_templates(req: HttpRequest, state: web::Data<AppState>) -> Http
     HttpResponse::Ok().json(templates)
 }
 
+// ─── LXC image sources (mirrors, cache, custom rootfs) ───
+
 #[derive(Deserialize)]
+pub struct LxcImageSourcesRequest {
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    #[serde(default)]
+    pub skip_public: bool,
+    #[serde(default)]
+    pub cache_dir: String,
+}
+
+#[derive(Deserialize)]
+pub struct LxcCustomImageRequest {
+    pub name: String,
+    pub release: String,
+    #[serde(default)]
+    pub architecture: String,
+    pub source: String,
+    #[serde(default)]
+    pub sha256: String,
+}
+
+/// GET /api/containers/lxc/image-sources — mirrors, cache contents and custom images
+pub async fn lxc_image_sources_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let result = web::block(|| {
+        let settings = containers::lxc_images::ImageSettings::load();
+        serde_json::json!({
+            "mirrors": settings.mirrors,
+            "skip_public": settings.skip_public,
+            "cache_dir": settings.cache_dir,
+            "effective_cache_dir": settings.cache_dir().to_string_lossy(),
+            "custom": settings.custom,
+            "cached": containers::lxc_images::list_cache(),
+            "proxmox": containers::is_proxmox(),
+        })
+    })
+    .await;
+    match result {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/containers/lxc/image-sources — save mirrors and cache location
+pub async fn lxc_image_sources_save(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<LxcImageSourcesRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let body = body.into_inner();
+    match containers::lxc_images::save_sources(body.mirrors, body.skip_public, body.cache_dir) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Image sources saved" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/containers/lxc/image-sources/custom — register a rootfs tarball as a template
+pub async fn lxc_custom_image_add(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<LxcCustomImageRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let b = body.into_inner();
+    match web::block(move || containers::lxc_images::add_custom(&b.name, &b.release, &b.architecture, &b.source, &b.sha256)).await {
+        Ok(Ok(image)) => HttpResponse::Ok().json(image),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// DELETE /api/containers/lxc/image-sources/custom/{id}
+pub async fn lxc_custom_image_remove(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::lxc_images::remove_custom(&path.into_inner()) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Custom image removed" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/containers/lxc/image-sources/cache — drop cached distro images
+pub async fn lxc_image_cache_clear(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(containers::lxc_images::clear_cache).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "message": "Image cache cleared" })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize, Serialize)]
 pub struct LxcCreateRequest {
     pub name: String,
     pub distribution: String,
@@ -8365,6 +10101,10 @@ pub struct LxcCreateRequest {
     /// --description` (Proxmox) or the WolfStack sidecar (native LXC).
     #[serde(default)]
     pub notes: Option<String>,
+    /// Create on this cluster node instead of here. It picks the WolfNet
+    /// address itself, so the lease is its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<String>,
 }
 
 /// POST /api/containers/lxc/create — create an LXC container from template
@@ -8373,7 +10113,17 @@ pub async fn lxc_create(
     state: web::Data<AppState>,
     body: web::Json<LxcCreateRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let mut body = body.into_inner();
+    match create_target(&state, body.target_node.take().as_deref()) {
+        Err(resp) => return resp,
+        // Template downloads make this slow on a cold cache.
+        Ok(Some(node)) => {
+            let payload = serde_json::to_value(&body).unwrap_or_default();
+            return forward_create(&req, &state, &caller, &node, "/api/containers/lxc/create", &payload, 900).await;
+        }
+        Ok(None) => {}
+    }
 
     // Auto-assign WolfNet IP only when the user chose WolfNet mode (or
     // no mode = legacy/default). Bridge and Host modes explicitly don't
@@ -8384,13 +10134,26 @@ pub async fn lxc_create(
     let net_mode = body.network_mode.as_deref().unwrap_or("wolfnet");
     let wolfnet_ip = if net_mode == "wolfnet" || net_mode.is_empty() {
         match body.wolfnet_ip.as_deref() {
-            Some(ip) if !ip.is_empty() => Some(ip.to_string()),
-            _ => containers::next_available_wolfnet_ip(),
+            Some(ip) if !ip.is_empty() => {
+                if let Err(e) = containers::ipam::claim(ip) {
+                    return HttpResponse::Conflict().json(serde_json::json!({ "error": e }));
+                }
+                Some(ip.to_string())
+            }
+            _ => containers::next_available_wolfnet_ip_for(crate::auth::tenancy::scope(&req, &caller).as_deref()),
         }
     } else {
         None
     };
 
+    let capacity_warning = match crate::capacity_policy::check(
+        &format!("Container '{}'", body.name),
+        crate::capacity_policy::Resources::from_limits(body.memory_limit.as_deref(), body.cpu_cores.as_deref()),
+    ) {
+        Ok(w) => w,
+        Err(e) => return HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    };
+
     // On Proxmox, pct_create handles password, memory, and CPU natively
     if containers::is_proxmox() {
         let storage = body.storage_path.as_deref();
@@ -8423,7 +10186,10 @@ pub async fn lxc_create(
                     && let Err(e) = containers::lxc_set_notes(&vmid.to_string(), notes) {
                     msg = format!("{} — Notes warning: {}", msg, e);
                 }
-                HttpResponse::Ok().json(serde_json::json!({ "message": msg }))
+                // Proxmox routes address the container by VMID.
+                crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Lxc, &vmid.to_string());
+                fire_container_event("lxc", &body.name, "create");
+                HttpResponse::Ok().json(serde_json::json!({ "message": msg, "capacity_warning": capacity_warning }))
             }
             Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
         };
@@ -8552,7 +10318,9 @@ pub async fn lxc_create(
                 messages.push(format!("Notes warning: {}", e));
             }
 
-            HttpResponse::Ok().json(serde_json::json!({ "message": messages.join(" — ") }))
+            crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Lxc, &body.name);
+            fire_container_event("lxc", &body.name, "create");
+            HttpResponse::Ok().json(serde_json::json!({ "message": messages.join(" — "), "capacity_warning": capacity_warning }))
         }
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
     }
@@ -8789,18 +10557,141 @@ pub async fn set_ports(req: HttpRequest, state: web::Data<AppState>, body: web::
     HttpResponse::Ok().json(serde_json::json!({"saved": true, "restart_required": true}))
 }
 
+/// GET /api/daemon-config — this node's wolfstack.toml, the settings in
+/// force, and any WOLFSTACK_* env overrides.
+pub async fn daemon_config_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let (file, file_error) = match crate::daemon_config::DaemonConfig::load_file() {
+        Ok(c) => (c, None),
+        Err(e) => (crate::daemon_config::DaemonConfig::default(), Some(e)),
+    };
+    let (effective, _) = crate::daemon_config::load_effective();
+    let running = crate::daemon_config::get();
+    let env_keys: std::collections::HashMap<&str, &str> = crate::daemon_config::ENV_VARS.iter().copied().collect();
+    let overrides: Vec<serde_json::Value> = crate::daemon_config::env_overrides().iter().map(|var| serde_json::json!({
+        "var": var,
+        "key": env_keys.get(var.as_str()).copied().unwrap_or(""),
+    })).collect();
+    HttpResponse::Ok().json(serde_json::json!({
+        "path": crate::paths::get().daemon_config,
+        "file": file,
+        "file_error": file_error,
+        "running": running,
+        "env_overrides": overrides,
+        "env_vars": crate::daemon_config::ENV_VARS.iter().map(|(v, k)| serde_json::json!({"var": v, "key": k})).collect::<Vec<_>>(),
+        // Saved but not yet reloaded, and saved keys that need a restart.
+        "reload_pending": crate::daemon_config::changed_keys(&running, &effective),
+        "restart_required": crate::daemon_config::pending_restart(&effective),
+    }))
+}
+
+/// PUT /api/daemon-config — validate and write wolfstack.toml. Nothing
+/// changes until a reload (`?reload=true` does it in the same call).
+pub async fn daemon_config_put(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+    body: web::Json<crate::daemon_config::DaemonConfig>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let cfg = body.into_inner();
+    if let Err(e) = cfg.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+    }
+    if let Err(e) = cfg.save() {
+        return HttpResponse::InternalServerError().json(serde_json::json!({"error": e}));
+    }
+    if query.get("reload").map(|v| v == "true" || v == "1").unwrap_or(false) {
+        return match crate::daemon_config::reload() {
+            Ok(report) => HttpResponse::Ok().json(serde_json::json!({"saved": true, "reload": report})),
+            Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
+        };
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "saved": true,
+        "restart_required": crate::daemon_config::pending_restart(&cfg),
+        "env_overrides": crate::daemon_config::env_overrides(),
+    }))
+}
+
+/// POST /api/daemon-config/reload — re-read wolfstack.toml and apply it
+/// without a restart (same as SIGHUP). A file that fails to parse or
+/// validate is rejected and the current settings stay in force.
+pub async fn daemon_config_reload(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match crate::daemon_config::reload() {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+    }
+}
+
+/// GET /api/logging — log format and the tracing filter in force.
+pub async fn logging_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let (filter, configured) = crate::logging::filters();
+    HttpResponse::Ok().json(serde_json::json!({
+        "format": crate::logging::format(),
+        "filter": filter,
+        "configured_filter": configured,
+        "rust_log": std::env::var("RUST_LOG").ok().filter(|v| !v.trim().is_empty()),
+        "exec_target": crate::logging::EXEC_TARGET,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct LoggingUpdate {
+    /// Replace the whole filter (`RUST_LOG` syntax).
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Set one module's level: `module` is a target such as
+    /// `wolfstack::agent`, `level` one of trace/debug/info/warn/error/off
+    /// or `default` to drop the override.
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Go back to the configured filter.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// PUT /api/logging — change tracing levels at runtime. Not persisted:
+/// lasts until restart or config reload (set `logging.filter` in
+/// wolfstack.toml to keep it).
+pub async fn logging_update(req: HttpRequest, state: web::Data<AppState>, body: web::Json<LoggingUpdate>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let body = body.into_inner();
+    let result = if body.reset {
+        crate::logging::reset()
+    } else if let Some(f) = &body.filter {
+        crate::logging::set_filter(f)
+    } else if let (Some(m), Some(l)) = (&body.module, &body.level) {
+        crate::logging::set_module_level(m, l)
+    } else {
+        Err("Provide filter, module and level, or reset".to_string())
+    };
+    if let Err(e) = result {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+    }
+    let (filter, configured) = crate::logging::filters();
+    tracing::info!("Log filter changed by {} to {:?}", caller, filter);
+    HttpResponse::Ok().json(serde_json::json!({"filter": filter, "configured_filter": configured}))
+}
+
 // ─── Reverse proxy configuration ────────────────────────────────────────
 
 /// GET /api/reverse-proxy/config — public base URL override for building
 /// shareable links (status pages, cluster browser) when WolfStack is
 /// served through a reverse proxy on a domain that differs from the
-/// node's internal address.
+/// node's internal address, plus trusted proxies, base path and CORS.
 pub async fn reverse_proxy_config_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     HttpResponse::Ok().json(crate::reverse_proxy::ReverseProxyConfig::load())
 }
 
-/// POST /api/reverse-proxy/config — validate and persist the override.
+/// POST /api/reverse-proxy/config — validate and persist the override,
+/// trusted proxies, base path and CORS origins. Takes effect on the next
+/// request; no restart.
 pub async fn reverse_proxy_config_save(
     req: HttpRequest,
     state: web::Data<AppState>,
@@ -8808,18 +10699,8 @@ pub async fn reverse_proxy_config_save(
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let cfg = body.into_inner().normalised();
-    if !cfg.public_base_url.is_empty() {
-        let u = &cfg.public_base_url;
-        if !(u.starts_with("http://") || u.starts_with("https://")) {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "public_base_url must start with http:// or https://"
-            }));
-        }
-        if u.contains(' ') || u.contains('\n') {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "public_base_url must not contain whitespace"
-            }));
-        }
+    if let Err(e) = cfg.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
     }
     if let Err(e) = cfg.save() {
         return HttpResponse::InternalServerError().json(serde_json::json!({"error": e}));
@@ -11079,9 +12960,11 @@ pub async fn wolfnet_routes_debug(req: HttpRequest, state: web::Data<AppState>)
 
 /// GET /api/containers/docker/stats — Docker container stats
 pub async fn docker_stats(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
     match web::block(containers::docker_stats_cached).await {
-        Ok(stats) => HttpResponse::Ok().json(stats),
+        Ok(stats) => HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), stats,
+            |c| Some((crate::auth::tenancy::Kind::Docker, c.name.as_str())))),
         Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "docker stats unavailable"})),
     }
 }
@@ -11095,6 +12978,42 @@ pub async fn docker_images(req: HttpRequest, state: web::Data<AppState>) -> Http
     }
 }
 
+#[derive(Deserialize)]
+pub struct ImageCvesQuery {
+    /// `local` for this node only; anything else gathers the cluster.
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// GET /api/containers/docker/cves — cached trivy results per Docker image
+pub async fn docker_image_cves(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<ImageCvesQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if query.scope == "local" {
+        let report = web::block(containers::image_cves::local_report).await.unwrap_or_default();
+        return HttpResponse::Ok().json(report);
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "nodes": containers::image_cves::collect_cluster(&state).await,
+    }))
+}
+
+/// POST /api/containers/docker/cves/scan — rescan every local Docker image now
+pub async fn docker_image_cves_scan(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if !crate::predictive::vulnerability::trivy_available() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "trivy is not installed on this node"}));
+    }
+    if containers::image_cves::scanning() {
+        return HttpResponse::Conflict().json(serde_json::json!({"error": "A scan is already running"}));
+    }
+    tokio::task::spawn_blocking(|| containers::image_cves::refresh(true));
+    HttpResponse::Accepted().json(serde_json::json!({"message": "Scan started"}))
+}
+
 /// DELETE /api/containers/docker/images/{id} — remove a Docker image
 pub async fn docker_remove_image(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -11105,11 +13024,58 @@ pub async fn docker_remove_image(req: HttpRequest, state: web::Data<AppState>, p
     }
 }
 
-/// GET /api/containers/docker/{id}/logs — get Docker container logs
-pub async fn docker_logs(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+#[derive(Deserialize)]
+pub struct LogsQuery {
+    /// Lines from the end (default 100, at most 5000 as JSON); `all` for
+    /// the whole log, which only makes sense with `download`.
+    #[serde(default)]
+    pub tail: Option<String>,
+    /// `1` streams the log as a text attachment instead of JSON lines.
+    #[serde(default)]
+    pub download: Option<String>,
+}
+
+fn wants_download(flag: &Option<String>) -> bool {
+    matches!(flag.as_deref(), Some("1") | Some("true") | Some("yes"))
+}
+
+/// GET /api/containers/docker/{id}/logs?tail=&download= — get Docker
+/// container logs. `download=1` streams them (any size, `tail=all` for
+/// everything) as a text attachment straight from `docker logs`.
+pub async fn docker_logs(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, query: web::Query<LogsQuery>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
-    let logs = containers::docker_logs(&id, 100);
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
+    if wants_download(&query.download) {
+        let tail = match query.tail.as_deref() {
+            None | Some("all") => "all".to_string(),
+            Some(t) => match t.parse::<u64>() {
+                Ok(n) => n.to_string(),
+                Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({"error": "tail must be a number or 'all'"})),
+            },
+        };
+        let child = tokio::process::Command::new("docker")
+            .args(["logs", "--timestamps", "--tail", &tail, &id])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let stream = match child.map_err(|e| format!("Cannot run docker: {}", e)).and_then(streaming::child_lines_stream) {
+            Ok(s) => s,
+            Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
+        };
+        let filename = format!("{}-{}.log", id.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')).collect::<String>(),
+            chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+        return HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .insert_header(actix_web::http::header::ContentDisposition {
+                disposition: actix_web::http::header::DispositionType::Attachment,
+                parameters: vec![actix_web::http::header::DispositionParam::Filename(filename)],
+            })
+            .streaming(stream);
+    }
+    let lines = query.tail.as_deref().and_then(|t| t.parse::<u32>().ok()).unwrap_or(100).min(5000);
+    let logs = web::block(move || containers::docker_logs(&id, lines)).await.unwrap_or_default();
     HttpResponse::Ok().json(serde_json::json!({ "logs": logs }))
 }
 
@@ -11194,6 +13160,76 @@ pub(crate) async fn wolfnet_ip_active_elsewhere(state: &web::Data<AppState>, ip:
     None
 }
 
+/// Fire the container lifecycle event for a successful create/action.
+/// Node-local, so it bypasses the leader gate. Actions with no lifecycle
+/// meaning are ignored.
+/// Save a change to the WolfRouter HTTP proxies made on a container's
+/// behalf, re-render this node's share and replicate it. `f` returns
+/// whether it changed anything.
+fn update_http_proxies(
+    state: &web::Data<AppState>,
+    f: impl FnOnce(&mut Vec<crate::networking::router::http_proxy::HttpProxy>) -> Result<bool, String>,
+) -> Result<(), String> {
+    let snapshot = {
+        let mut cfg = state.router.config.write().unwrap();
+        let mut proxies = cfg.http_proxies.clone();
+        if !f(&mut proxies)? {
+            return Ok(());
+        }
+        cfg.http_proxies = proxies.clone();
+        cfg.save().map_err(|e| format!("save router config: {}", e))?;
+        proxies
+    };
+    crate::networking::router::http_proxy::apply_for_node(&snapshot, &crate::agent::self_node_id());
+    crate::networking::router::api::replicate_config_to_cluster(state.clone());
+    Ok(())
+}
+
+/// Add a freshly created container to the proxy it asked to join. Its URL
+/// comes from the cached container list, which may not have the new
+/// container yet — the reconcile fills it in within 30s if so.
+fn register_auto_upstream(
+    state: &web::Data<AppState>,
+    kind: &str,
+    name: &str,
+    reg: &crate::networking::router::auto_upstream::Registration,
+) -> Result<(), String> {
+    use crate::networking::router::auto_upstream;
+    update_http_proxies(state, |proxies| {
+        let ingress = proxies.iter().find(|p| p.id == reg.proxy_id)
+            .and_then(auto_upstream::ingress_node).unwrap_or_default().to_string();
+        let url = crate::exposure::resolve_upstream_local(kind, name, reg.port, &reg.scheme, &ingress, &state.cluster)
+            .ok().flatten();
+        auto_upstream::register(proxies, kind, name, reg, url).map(|_| true)
+    })
+    .map_err(|e| format!("Container created, but not registered with the proxy: {}", e))
+}
+
+/// Take a destroyed container out of every proxy pool it joined.
+fn deregister_auto_upstream(state: &web::Data<AppState>, kind: &str, name: &str) {
+    let result = update_http_proxies(state, |proxies| {
+        Ok(!crate::networking::router::auto_upstream::deregister(proxies, kind, name).is_empty())
+    });
+    if let Err(e) = result {
+        warn!("Failed to deregister {} '{}' from its HTTP proxy: {}", kind, name, e);
+    }
+}
+
+fn fire_container_event(runtime: &str, name: &str, action: &str) {
+    let event = match action {
+        "create" => crate::wolffunctions::TriggerEvent::ContainerCreated,
+        "start" | "restart" | "unpause" | "unfreeze" => crate::wolffunctions::TriggerEvent::ContainerStarted,
+        "stop" | "pause" | "freeze" => crate::wolffunctions::TriggerEvent::ContainerStopped,
+        "remove" | "destroy" => crate::wolffunctions::TriggerEvent::ContainerDeleted,
+        _ => return,
+    };
+    crate::wolffunctions::fire_event_global(event, serde_json::json!({
+        "runtime": runtime,
+        "name": name,
+        "action": action,
+    }), true);
+}
+
 pub async fn docker_action(
     req: HttpRequest,
     state: web::Data<AppState>,
@@ -11201,7 +13237,7 @@ pub async fn docker_action(
     body: web::Json<ContainerActionRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     // A start must not bring up a second copy of a WolfNet IP already live
     // elsewhere in the cluster.
     if body.action == "start"
@@ -11223,7 +13259,13 @@ pub async fn docker_action(
     };
 
     match result {
-        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Ok(msg) => {
+            fire_container_event("docker", &id, &body.action);
+            if body.action == "remove" {
+                deregister_auto_upstream(&state, "docker", &id);
+            }
+            HttpResponse::Ok().json(serde_json::json!({ "message": msg }))
+        }
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
     }
 }
@@ -11237,6 +13279,8 @@ pub struct CloneRequest {
     pub wolfnet_ip: Option<String>,  // pre-allocated wolfnet IP (avoids cross-node conflicts)
     #[serde(default)]
     pub vmid: Option<u32>,       // Proxmox local clone — operator-chosen new VMID
+    #[serde(default)]
+    pub bwlimit_mbps: Option<u32>, // cross-node clone — upload cap in Mbit/s
 }
 
 #[derive(Deserialize)]
@@ -11248,6 +13292,14 @@ pub struct MigrateRequest {
     pub target_address: Option<String>,
     #[serde(default)]
     pub target_port: Option<u16>,
+    /// LXC only: checkpoint the running container with CRIU and restore it
+    /// on the target instead of stopping and booting it. Falls back to a
+    /// cold move when either node can't.
+    #[serde(default)]
+    pub live: bool,
+    /// Cap on the transfer to the target, in Mbit/s. None = uncapped.
+    #[serde(default)]
+    pub bwlimit_mbps: Option<u32>,
 }
 
 #[derive(Deserialize)]
@@ -11258,6 +13310,9 @@ pub struct MigrateExternalRequest {
     pub new_name: Option<String>,
     pub storage: Option<String>,
     pub delete_source: Option<bool>, // accepted but ignored — source is never deleted
+    /// Cap on the upload, in Mbit/s. None = uncapped.
+    #[serde(default)]
+    pub bwlimit_mbps: Option<u32>,
 }
 
 #[derive(Deserialize)]
@@ -11275,7 +13330,7 @@ pub async fn docker_clone(
     body: web::Json<CloneRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::docker_clone(&id, &body.new_name) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
@@ -11295,8 +13350,10 @@ pub async fn docker_migrate(
     body: web::Json<DockerMigrateRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let remove = body.remove_source.unwrap_or(false);
+    let _slot = crate::jobs::acquire_async(crate::jobs::Category::Migration, crate::jobs::Priority::Normal,
+        &format!("Migrate Docker '{}'", id), |_| {}).await;
     match containers::docker_migrate(&id, &body.target_url, remove, &state.cluster_secret) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
@@ -11305,28 +13362,41 @@ pub async fn docker_migrate(
 
 /// GET /api/containers/lxc — list all LXC containers
 pub async fn lxc_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
     // Offload the blocking lxc-ls/lxc-info subprocesses (on a cache miss) to the
     // blocking pool — see docker_list for why running it inline blanked the list.
     match web::block(containers::lxc_list_all_cached).await {
-        Ok(containers) => HttpResponse::Ok().json(containers),
+        Ok(containers) => HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), containers,
+            |c| Some((crate::auth::tenancy::Kind::Lxc, c.name.as_str())))),
         Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "lxc list unavailable"})),
     }
 }
 
 /// GET /api/containers/lxc/stats — LXC container stats
 pub async fn lxc_stats(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
     match web::block(containers::lxc_stats_cached).await {
-        Ok(stats) => HttpResponse::Ok().json(stats),
+        Ok(stats) => HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), stats,
+            |c| Some((crate::auth::tenancy::Kind::Lxc, c.name.as_str())))),
         Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "lxc stats unavailable"})),
     }
 }
 
+/// The `{id}` / `{name}` segment of a container route as a checked
+/// [`ContainerName`](crate::validate::ContainerName) — it is passed to
+/// `docker` / `lxc-*` and interpolated into paths, so anything else is
+/// a 400 before the handler runs a command.
+fn container_name_param(path: web::Path<String>) -> Result<crate::validate::ContainerName, HttpResponse> {
+    crate::validate::ContainerName::parse(&path.into_inner())
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))
+}
+
 /// GET /api/containers/lxc/{name}/logs — get LXC container logs
 pub async fn lxc_logs(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let logs = containers::lxc_logs(&name, 100);
     HttpResponse::Ok().json(serde_json::json!({ "logs": logs }))
 }
@@ -11336,7 +13406,7 @@ pub async fn lxc_logs(req: HttpRequest, state: web::Data<AppState>, path: web::P
 /// it's cache-only — never touches the working set, so the app keeps running).
 pub async fn lxc_reclaim_cache(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     // Subprocess + synchronous kernel reclaim — off the async worker.
     match web::block(move || containers::lxc_reclaim_cache(&name)).await {
         Ok(Ok(freed)) => HttpResponse::Ok().json(serde_json::json!({ "freed_bytes": freed })),
@@ -11348,7 +13418,7 @@ pub async fn lxc_reclaim_cache(req: HttpRequest, state: web::Data<AppState>, pat
 /// GET /api/containers/lxc/{name}/config — get LXC container config
 pub async fn lxc_config(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::lxc_config(&name) {
         Some(content) => HttpResponse::Ok().json(serde_json::json!({ "config": content })),
         None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Config not found" })),
@@ -11362,12 +13432,7 @@ pub async fn lxc_config(req: HttpRequest, state: web::Data<AppState>, path: web:
 /// second restore of a container that already restored fine.
 pub async fn lxc_exists(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
-    // The name is interpolated into a filesystem path — reject anything
-    // unsafe rather than stat a traversal path.
-    if !crate::auth::is_safe_name(&name) {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "invalid container name" }));
-    }
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let exists = std::path::Path::new(&format!("/var/lib/lxc/{}", name)).exists()
         || std::path::Path::new(&format!("/etc/pve/lxc/{}.conf", name)).exists();
     HttpResponse::Ok().json(serde_json::json!({ "exists": exists }))
@@ -11380,9 +13445,9 @@ pub async fn lxc_save_config(
     path: web::Path<String>,
     body: web::Json<SaveConfigRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
-    match containers::lxc_save_config(&name, &body.content) {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
+    match containers::lxc_save_config(&name, &body.content, &caller) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
     }
@@ -11396,7 +13461,7 @@ pub async fn lxc_action(
     body: web::Json<ContainerActionRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     // A start must not bring up a second copy of a WolfNet IP already live
     // elsewhere in the cluster.
     if body.action == "start"
@@ -11418,7 +13483,13 @@ pub async fn lxc_action(
     };
 
     match result {
-        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Ok(msg) => {
+            fire_container_event("lxc", &name, &body.action);
+            if body.action == "destroy" {
+                deregister_auto_upstream(&state, "lxc", &name);
+            }
+            HttpResponse::Ok().json(serde_json::json!({ "message": msg }))
+        }
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
     }
 }
@@ -11431,11 +13502,11 @@ pub async fn lxc_clone(
     body: web::Json<CloneRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
 
     // Remote clone: export → transfer → import on target node
     if let Some(ref target_node_id) = body.target_node {
-        return lxc_remote_clone(&state, &name, &body.new_name, target_node_id, body.storage.as_deref(), body.wolfnet_ip.as_deref(), None, None).await;
+        return lxc_remote_clone(&state, &name, &body.new_name, target_node_id, body.storage.as_deref(), body.wolfnet_ip.as_deref(), None, None, body.bwlimit_mbps).await;
     }
 
     // Local clone — a full clone requires the source stopped; restart it after.
@@ -11522,10 +13593,173 @@ fn resolve_target_node(
         site: None,
         display_name: None,
         roles: Vec::new(),
+        labels: Default::default(),
     })
 }
 
-/// Remote clone: export on this node, stream to target, import there
+// ─── Chunked node-to-node transfer ───
+
+/// POST /api/transfer/begin — start (or resume) receiving a file from another node. The sender lists the file's chunks; the reply names the ones this node still needs
+pub async fn transfer_begin(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<crate::transfer::BeginRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_cluster_auth(&req, &state) { return resp; }
+    let body = body.into_inner();
+    match web::block(move || crate::transfer::begin(&body)).await {
+        Ok(Ok(plan)) => HttpResponse::Ok().json(plan),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// PUT /api/transfer/{id}/chunks/{hash} — one zstd-compressed chunk of a transfer
+pub async fn transfer_chunk(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Bytes,
+) -> HttpResponse {
+    if let Err(resp) = require_cluster_auth(&req, &state) { return resp; }
+    let (id, hash) = path.into_inner();
+    match web::block(move || crate::transfer::put_chunk(&id, &hash, &body)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "ok": true })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/transfer/{id}/finish — assemble a transfer once every chunk is in; an import then names it by id
+pub async fn transfer_finish(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(resp) = require_cluster_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    match web::block(move || crate::transfer::finish(&id)).await {
+        Ok(Ok(file)) => HttpResponse::Ok().json(serde_json::json!({ "size": file.metadata().map(|m| m.len()).unwrap_or(0) })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+const LXC_IMPORT_PATH: &str = "/api/containers/lxc/import";
+
+/// Why an archive didn't reach a node's import.
+struct PushError {
+    /// The import request went out but no answer came back — the target
+    /// may have imported (and, for a migrate, started) the container.
+    ambiguous: bool,
+    error: String,
+}
+
+/// A file as a multipart part streamed from disk, held to `bwlimit`
+/// bytes/s (0 = uncapped).
+async fn file_part(
+    path: &std::path::Path,
+    bwlimit: &Arc<std::sync::atomic::AtomicU64>,
+    progress: impl FnMut(u64) + Send + 'static,
+) -> Result<reqwest::multipart::Part, String> {
+    let file = tokio::fs::File::open(path).await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let len = file.metadata().await.map_err(|e| e.to_string())?.len();
+    let body = crate::transfer::paced_body(file, bwlimit.clone(), progress);
+    Ok(reqwest::multipart::Part::stream_with_length(body, len)
+        .file_name(path.file_name().unwrap_or_default().to_string_lossy().to_string()))
+}
+
+/// Get a container archive (and a live migrate's CRIU checkpoint) to the
+/// first reachable of `import_urls` and run the import there with
+/// `fields`. The archive goes over chunked transfer — resumable, only the
+/// chunks the target lacks from the last copy sent under `key` — and the
+/// import names it by transfer id. A target that predates chunked
+/// transfer gets it as a multipart upload streamed from disk instead.
+/// Either way the upload is held to `bwlimit` bytes/s, read as it goes.
+/// Returns the import's response and, when chunked, what was actually
+/// uploaded.
+#[allow(clippy::too_many_arguments)]
+async fn push_lxc_import(
+    import_urls: &[String],
+    secret: &str,
+    archive: &std::path::Path,
+    checkpoint: Option<&std::path::Path>,
+    key: &str,
+    fields: &[(&'static str, String)],
+    bwlimit: &Arc<std::sync::atomic::AtomicU64>,
+    timeout: std::time::Duration,
+    mut progress: impl FnMut(u64, u64) + Clone + Send + 'static,
+) -> Result<(reqwest::Response, Option<crate::transfer::Sent>), PushError> {
+    use crate::transfer::SendError;
+    let client = &*API_HTTP_CLIENT;
+    let mut last_err = String::new();
+    let mut ambiguous = false;
+    for url in import_urls {
+        let base = url.strip_suffix(LXC_IMPORT_PATH).unwrap_or(url);
+        let mut form = reqwest::multipart::Form::new();
+        for (name, value) in fields {
+            form = form.text(*name, value.clone());
+        }
+        let sent = match crate::transfer::send(client, base, secret, archive, key, bwlimit, &mut progress).await {
+            Ok(sent) => {
+                form = form.text("transfer", sent.id.clone());
+                if let Some(ckpt) = checkpoint {
+                    let ckpt_key = format!("{}:checkpoint", key);
+                    match crate::transfer::send(client, base, secret, ckpt, &ckpt_key, bwlimit, |_, _| {}).await {
+                        Ok(c) => form = form.text("checkpoint_transfer", c.id),
+                        Err(e) => { last_err = e.to_string(); continue; }
+                    }
+                }
+                Some(sent)
+            }
+            Err(SendError::Unsupported) => {
+                let total = std::fs::metadata(archive).map(|m| m.len()).unwrap_or(0);
+                let mut on_read = progress.clone();
+                let part = file_part(archive, bwlimit, move |done| on_read(done, total)).await
+                    .map_err(|error| PushError { ambiguous, error })?;
+                form = form.part("archive", part);
+                if let Some(ckpt) = checkpoint {
+                    let part = file_part(ckpt, bwlimit, |_| {}).await.map_err(|error| PushError { ambiguous, error })?;
+                    form = form.part("checkpoint", part.file_name("checkpoint.tar.gz"));
+                }
+                None
+            }
+            // Nothing has been imported yet — the next URL resumes where
+            // this one stopped.
+            Err(e) => { last_err = e.to_string(); continue; }
+        };
+        match client.post(url)
+            .timeout(timeout)
+            .header("X-WolfStack-Secret", secret)
+            .multipart(form)
+            .send()
+            .await
+        {
+            Ok(r) => return Ok((r, sent)),
+            Err(e) => {
+                if !e.is_connect() { ambiguous = true; }
+                last_err = e.to_string();
+            }
+        }
+    }
+    Err(PushError { ambiguous, error: last_err })
+}
+
+/// " (only 12 MB of 800 MB sent — the rest was already there)" after a
+/// delta or resumed transfer; empty when everything went.
+fn transfer_saving_note(sent: Option<&crate::transfer::Sent>) -> String {
+    match sent {
+        Some(s) if s.uploaded < s.size => format!(" (only {} MB of {} MB sent — the rest was already on the target)",
+            s.uploaded / (1024 * 1024), s.size / (1024 * 1024)),
+        _ => String::new(),
+    }
+}
+
+/// Remote clone: export on this node, stream to target, import there.
+/// Answers when done, but runs as a job too (GET /api/migration) so its
+/// progress can be watched and its bandwidth cap changed meanwhile.
+#[allow(clippy::too_many_arguments)]
 async fn lxc_remote_clone(
     state: &web::Data<AppState>,
     source: &str,
@@ -11535,6 +13769,7 @@ async fn lxc_remote_clone(
     wolfnet_ip: Option<&str>,
     fallback_address: Option<&str>,
     fallback_port: Option<u16>,
+    bwlimit_mbps: Option<u32>,
 ) -> HttpResponse {
     // 1. Find target node — fall back to address/port if node ID not in local cluster state
     //    (can happen when request is proxied to a remote node with different cluster state)
@@ -11556,6 +13791,12 @@ async fn lxc_remote_clone(
         }
     }
 
+    let tasks = state.migration_tasks.clone();
+    let tid = migration_create(&tasks);
+    migration_set_bwlimit(&tasks, &tid, bwlimit_mbps);
+    let _slot = migration_slot(&tasks, &tid, &format!("Clone LXC '{}' to {}", source, target_node_id)).await;
+    migration_update(&tasks, &tid, "export", &format!("Cloning '{}' to '{}' on {}: exporting…", source, new_name, target_node_id));
+
     // 2. Stop container temporarily for consistent export, then restart immediately
     let _ = containers::lxc_stop(source);
 
@@ -11564,93 +13805,70 @@ async fn lxc_remote_clone(
         Ok(v) => v,
         Err(e) => {
             let _ = containers::lxc_start(source);
-            return HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Export failed: {}", e)}));
+            let error = format!("Export failed: {}", e);
+            migration_fail(&tasks, &tid, &error);
+            return HttpResponse::InternalServerError().json(serde_json::json!({"error": error}));
         }
     };
 
     // Restart source immediately after export — source stays running during transfer
     let _ = containers::lxc_start(source);
 
-    // 4. Read archive
-    let archive_bytes = match std::fs::read(&archive_path) {
-        Ok(b) => b,
-        Err(e) => {
-            containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-            return HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Failed to read archive: {}", e)}));
-        }
-    };
-
-    // 5. Transfer to target
+    // 4. Transfer to target
     // For Proxmox-type nodes, WolfStack is also installed on the server but the
     // node is registered with the PVE API port (8006), not the WolfStack port (8553).
     // Build import URLs using the correct WolfStack port.
     let import_urls = if node.node_type == "proxmox" {
         // Proxmox nodes have WolfStack running on port 8553 — try that
-        let mut urls = build_node_urls(&node.address, 8553, "/api/containers/lxc/import");
+        let mut urls = build_node_urls(&node.address, 8553, LXC_IMPORT_PATH);
         // Also try 8552 as a fallback WolfStack port
-        urls.extend(build_node_urls(&node.address, 8552, "/api/containers/lxc/import"));
+        urls.extend(build_node_urls(&node.address, 8552, LXC_IMPORT_PATH));
         urls
     } else {
-        build_node_urls(&node.address, node.port, "/api/containers/lxc/import")
+        build_node_urls(&node.address, node.port, LXC_IMPORT_PATH)
     };
 
-    let storage_val = storage.unwrap_or("").to_string();
-
-    let client = &*API_HTTP_CLIENT;
-
-    let file_name = archive_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-    let meta_json = serde_json::to_string(&meta).unwrap_or_default();
-    let mut last_err: Option<String> = None;
-
-    for import_url in &import_urls {
-        let mut form = reqwest::multipart::Form::new()
-            .text("new_name", new_name.to_string())
-            .text("storage", storage_val.clone())
-            .text("meta", meta_json.clone())
-            .part("archive", reqwest::multipart::Part::bytes(archive_bytes.clone())
-                .file_name(file_name.clone()));
-
-        // Pass pre-allocated wolfnet IP to avoid cross-node conflicts
-        if let Some(ip) = wolfnet_ip {
-            form = form.text("wolfnet_ip", ip.to_string());
-        }
+    let mut fields = vec![
+        ("new_name", new_name.to_string()),
+        ("storage", storage.unwrap_or("").to_string()),
+        ("meta", serde_json::to_string(&meta).unwrap_or_default()),
+    ];
+    // Pass pre-allocated wolfnet IP to avoid cross-node conflicts
+    if let Some(ip) = wolfnet_ip {
+        fields.push(("wolfnet_ip", ip.to_string()));
+    }
+    // Keyed by source container, so cloning it to the same node again
+    // only sends what changed since.
+    let key = format!("lxc:{}:{}", crate::agent::self_node_id(), source);
+    migration_update(&tasks, &tid, "upload", &format!("Cloning '{}' to '{}' on {}: transferring…", source, new_name, target_node_id));
+    let progress = { let (tasks, tid) = (tasks.clone(), tid.clone());
+        move |done, total| migration_progress(&tasks, &tid, Some(done), Some(total), None) };
+    let pushed = push_lxc_import(&import_urls, &state.cluster_secret, &archive_path, None, &key, &fields,
+        &migration_throttle(&tasks, &tid), std::time::Duration::from_secs(600), progress).await;
+    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
 
-        match client.post(import_url)
-            .timeout(std::time::Duration::from_secs(600))
-            .header("X-WolfStack-Secret", state.cluster_secret.clone())
-            .multipart(form)
-            .send()
-            .await
-        {
-            Ok(r) => {
-                containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-                if r.status().is_success() {
-                    return match r.json::<serde_json::Value>().await {
-                        Ok(data) => HttpResponse::Ok().json(serde_json::json!({
-                            "message": format!("Container '{}' cloned to '{}' on node '{}'", source, new_name, target_node_id),
-                            "detail": data
-                        })),
-                        Err(_) => HttpResponse::Ok().json(serde_json::json!({
-                            "message": format!("Container '{}' cloned to '{}' on node '{}'", source, new_name, target_node_id)
-                        })),
-                    };
-                } else {
-                    let err_text = r.text().await.unwrap_or_default();
-                    return HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Import on target failed: {}", err_text)}));
-                }
-            }
-            Err(e) => {
-                last_err = Some(e.to_string());
-                continue; // Try next URL
+    match pushed {
+        Ok((r, sent)) if r.status().is_success() => {
+            let message = format!("Container '{}' cloned to '{}' on node '{}'{}",
+                source, new_name, target_node_id, transfer_saving_note(sent.as_ref()));
+            migration_done(&tasks, &tid, &message);
+            match r.json::<serde_json::Value>().await {
+                Ok(data) => HttpResponse::Ok().json(serde_json::json!({ "message": message, "detail": data })),
+                Err(_) => HttpResponse::Ok().json(serde_json::json!({ "message": message })),
             }
         }
+        Ok((r, _)) => {
+            let error = format!("Import on target failed: {}", r.text().await.unwrap_or_default());
+            migration_fail(&tasks, &tid, &error);
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": error}))
+        }
+        // All URLs failed — source is already running
+        Err(e) => {
+            let error = format!("Transfer to {} failed on all ports/protocols: {}", node.address, e.error);
+            migration_fail(&tasks, &tid, &error);
+            HttpResponse::BadGateway().json(serde_json::json!({"error": error}))
+        }
     }
-
-    // All URLs failed — source is already running
-    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-    HttpResponse::BadGateway().json(serde_json::json!({
-        "error": format!("Transfer to {} failed on all ports/protocols: {}", node.address, last_err.unwrap_or_default())
-    }))
 }
 
 /// POST /api/containers/lxc/{name}/export — export container as downloadable archive
@@ -11660,34 +13878,31 @@ pub async fn lxc_export_endpoint(
     path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
 
-    // Stop, export, restart
-    let _ = containers::lxc_stop(&name);
-    let (archive_path, meta) = match containers::lxc_export(&name) {
+    // Stop, export, restart — blocking work that runs for minutes on a
+    // big container, so off the async worker.
+    let exported = web::block(move || {
+        let _ = containers::lxc_stop(&name);
+        let result = containers::lxc_export(&name);
+        let _ = containers::lxc_start(&name);
+        result
+    }).await.unwrap_or_else(|e| Err(e.to_string()));
+    let (archive_path, meta) = match exported {
         Ok(v) => v,
-        Err(e) => {
-            let _ = containers::lxc_start(&name);
-            return HttpResponse::InternalServerError().json(serde_json::json!({"error": e}));
-        }
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
     };
-    let _ = containers::lxc_start(&name);
 
-    // Read the file and return as binary download
-    match std::fs::read(&archive_path) {
-        Ok(bytes) => {
-            let filename = archive_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-            containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-            HttpResponse::Ok()
-                .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
-                .insert_header(("X-Container-Meta", serde_json::to_string(&meta).unwrap_or_default()))
-                .content_type("application/octet-stream")
-                .body(bytes)
-        }
-        Err(e) => {
-            containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-            HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Read archive: {}", e)}))
-        }
+    // Stream the archive rather than reading it into memory: a multi-GB
+    // export used to OOM the node. The archive is unlinked as soon as it
+    // is open, so its space comes back when the download ends.
+    let filename = archive_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let meta_header = ("X-Container-Meta", serde_json::to_string(&meta).unwrap_or_default());
+    let resp = streaming::download_and_remove(&archive_path, &filename, &[meta_header]).await;
+    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
+    match resp {
+        Ok(resp) => resp,
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
     }
 }
 
@@ -11698,7 +13913,54 @@ pub async fn lxc_import_endpoint(
     mut payload: actix_multipart::Multipart,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    lxc_import_endpoint_inner(&mut payload).await
+    lxc_import_endpoint_inner(&mut payload, true).await
+}
+
+/// GET /api/containers/lxc/criu-check — whether this node can restore a
+/// CRIU checkpoint (asked by a live migrate's source before it freezes
+/// anything)
+pub async fn lxc_criu_check(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(containers::lxc_criu::host_preflight).await {
+        Ok(Ok(version)) => HttpResponse::Ok().json(serde_json::json!({ "ok": true, "version": version })),
+        Ok(Err(e)) => HttpResponse::Ok().json(serde_json::json!({ "ok": false, "reason": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// Both ends of a live migrate: the container here, and the target's own
+/// CRIU check. Any `Err` means fall back to a cold move.
+async fn lxc_live_preflight(name: &str, check_urls: &[String], cluster_secret: &str) -> Result<(), String> {
+    containers::lxc_criu::preflight(name)?;
+    for url in check_urls {
+        match API_HTTP_CLIENT.get(url)
+            .timeout(std::time::Duration::from_secs(20))
+            .header("X-WolfStack-Secret", cluster_secret)
+            .send().await
+        {
+            Ok(r) => {
+                let data = r.json::<serde_json::Value>().await.unwrap_or_default();
+                if data.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    return Ok(());
+                }
+                let reason = data.get("reason").or_else(|| data.get("error"))
+                    .and_then(|v| v.as_str()).unwrap_or("no CRIU support (older WolfStack?)");
+                return Err(format!("destination: {}", reason));
+            }
+            Err(_) => continue,
+        }
+    }
+    Err("could not ask the destination about CRIU support".into())
+}
+
+/// Bring a migrate's source back after a failed move — only if it was
+/// running, and from its checkpoint when a live migrate took one.
+fn lxc_migrate_restart_source(name: &str, was_running: bool, checkpoint: Option<&std::path::Path>) {
+    if !was_running { return; }
+    match checkpoint {
+        Some(dir) => { let _ = containers::lxc_criu::restore_or_start(name, dir); }
+        None => { let _ = containers::lxc_start(name); }
+    }
 }
 
 /// POST /api/containers/lxc/{name}/migrate — move a container to another
@@ -11706,8 +13968,11 @@ pub async fn lxc_import_endpoint(
 /// source is stopped, transferred, imported with a bootable config, then
 /// the destination is started and verified RUNNING. The source is left
 /// stopped (kept on the old node as a rollback). On any failure the
-/// source is restarted. Runs in the background — the response carries a
-/// `task_id`; poll GET /api/migration/{id}/status for live progress.
+/// source is restarted. With `live`, the source is checkpointed with CRIU
+/// rather than stopped and the destination restores it, falling back to a
+/// cold move if the CRIU preflight or the checkpoint fails. Runs in the
+/// background — the response carries a `task_id`; poll
+/// GET /api/migration/{id}/status for live progress.
 pub async fn lxc_migrate(
     req: HttpRequest,
     state: web::Data<AppState>,
@@ -11715,7 +13980,7 @@ pub async fn lxc_migrate(
     body: web::Json<MigrateRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let new_name = body.new_name.as_deref().unwrap_or(&name).to_string();
     // Validate here, before we stop/export anything — a bad name must not
     // cost the operator downtime only to be rejected by the target later.
@@ -11738,96 +14003,127 @@ pub async fn lxc_migrate(
     // Build the import endpoints up front (Proxmox nodes register their PVE
     // port, but WolfStack listens on 8553/8552 there).
     let import_urls = if node.node_type == "proxmox" {
-        let mut urls = build_node_urls(&node.address, 8553, "/api/containers/lxc/import");
-        urls.extend(build_node_urls(&node.address, 8552, "/api/containers/lxc/import"));
+        let mut urls = build_node_urls(&node.address, 8553, LXC_IMPORT_PATH);
+        urls.extend(build_node_urls(&node.address, 8552, LXC_IMPORT_PATH));
         urls
     } else {
-        build_node_urls(&node.address, node.port, "/api/containers/lxc/import")
+        build_node_urls(&node.address, node.port, LXC_IMPORT_PATH)
     };
     let target_label = if node.hostname.is_empty() { node.address.clone() } else { node.hostname.clone() };
     let target_addr = node.address.clone();
     let target_is_proxmox = node.node_type == "proxmox";
+    let criu_check_urls: Vec<String> = import_urls.iter()
+        .map(|u| u.replace(LXC_IMPORT_PATH, "/api/containers/lxc/criu-check"))
+        .collect();
+    let live = body.live;
 
     let tasks = state.migration_tasks.clone();
     let task_id = migration_create(&tasks);
+    migration_set_bwlimit(&tasks, &task_id, body.bwlimit_mbps);
     let tid = task_id.clone();
     let cluster_secret = state.cluster_secret.clone();
     let storage_val = body.storage.as_deref().unwrap_or("").to_string();
 
     tokio::spawn(async move {
-        // 1. Stop the source for a consistent export. Record whether it was
-        //    running so a rollback only restarts a previously-running one.
-        migration_update(&tasks, &tid, "stop_source", &format!("Stopping '{}' on the source node…", name));
+        let _slot = migration_slot(&tasks, &tid, &format!("Migrate LXC '{}'", name)).await;
         let was_running = containers::lxc_is_running(&name);
-        let _ = containers::lxc_stop(&name);
-        // Refuse to export a still-running rootfs — the archive would be
-        // inconsistent. The source is untouched here, so just abort.
-        if containers::lxc_is_running(&name) {
-            migration_fail(&tasks, &tid, &format!(
-                "Could not stop '{}' on the source node — aborted before export so no inconsistent copy is shipped. The source is still running, untouched.", name));
-            return;
-        }
 
-        // 2. Export (stays stopped — this is a move, not a live clone).
-        migration_update(&tasks, &tid, "export", "Exporting container…");
-        let (archive_path, meta) = match containers::lxc_export(&name) {
-            Ok(v) => v,
-            Err(e) => {
-                if was_running { let _ = containers::lxc_start(&name); }
-                migration_fail(&tasks, &tid, &format!("Export failed: {}", e));
-                return;
-            }
-        };
-        let archive_bytes = match std::fs::read(&archive_path) {
-            Ok(b) => b,
-            Err(e) => {
-                containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-                if was_running { let _ = containers::lxc_start(&name); }
-                migration_fail(&tasks, &tid, &format!("Failed to read archive: {}", e));
+        // 0. Live: checkpoint (which also stops) instead of a plain stop.
+        //    Anything going wrong here leaves the source running and the
+        //    move carries on cold.
+        let mut checkpoint_dir: Option<std::path::PathBuf> = None;
+        let mut live_note = String::new();
+        let mut frozen_at = std::time::Instant::now();
+        if live {
+            migration_update(&tasks, &tid, "preflight", "Checking CRIU support on both nodes…");
+            match lxc_live_preflight(&name, &criu_check_urls, &cluster_secret).await {
+                Ok(()) => {
+                    migration_update(&tasks, &tid, "checkpoint", &format!("Checkpointing '{}'…", name));
+                    let dir = std::path::PathBuf::from(format!("/tmp/wolfstack-exports/criu-{}", uuid::Uuid::new_v4()));
+                    frozen_at = std::time::Instant::now();
+                    match containers::lxc_criu::checkpoint(&name, &dir) {
+                        Ok(()) => checkpoint_dir = Some(dir),
+                        Err(e) => {
+                            let _ = std::fs::remove_dir_all(&dir);
+                            live_note = format!("\n\nLive migration not possible ({}) — moved cold instead.", e);
+                        }
+                    }
+                }
+                Err(e) => live_note = format!("\n\nLive migration not possible ({}) — moved cold instead.", e),
+            }
+        }
+        let ckpt = checkpoint_dir.as_deref();
+        // The move itself; `return` ends it early. The checkpoint directory
+        // is removed afterwards whichever way it went.
+        async {
+            // 1. Stop the source for a consistent export. Record whether it was
+            //    running so a rollback only restarts a previously-running one.
+            //    (Already stopped by a checkpoint.)
+            migration_update(&tasks, &tid, "stop_source", &format!("Stopping '{}' on the source node…", name));
+            let _ = containers::lxc_stop(&name);
+            // Refuse to export a still-running rootfs — the archive would be
+            // inconsistent. The source is untouched here, so just abort.
+            if containers::lxc_is_running(&name) {
+                migration_fail(&tasks, &tid, &format!(
+                    "Could not stop '{}' on the source node — aborted before export so no inconsistent copy is shipped. The source is still running, untouched.", name));
                 return;
             }
-        };
 
-        let size_mb = archive_bytes.len() / (1024 * 1024);
-        migration_update(&tasks, &tid, "upload", &format!("Transferring {} MB to {}…", size_mb, target_label));
+            // 2. Export (stays stopped — this is a move, not a live clone).
+            migration_update(&tasks, &tid, "export", "Exporting container…");
+            let (archive_path, meta) = match containers::lxc_export(&name) {
+                Ok(v) => v,
+                Err(e) => {
+                    lxc_migrate_restart_source(&name, was_running, ckpt);
+                    migration_fail(&tasks, &tid, &format!("Export failed: {}", e));
+                    return;
+                }
+            };
+            // The checkpoint travels as a second archive next to the rootfs.
+            let checkpoint_archive = match ckpt.map(containers::lxc_criu::pack) {
+                None => None,
+                Some(Ok(packed)) => Some(packed),
+                Some(Err(e)) => {
+                    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
+                    lxc_migrate_restart_source(&name, was_running, ckpt);
+                    migration_fail(&tasks, &tid, &format!("Failed to pack checkpoint: {}", e));
+                    return;
+                }
+            };
 
-        // 3. Upload → target imports a bootable config, starts + verifies it.
-        let client = &*API_HTTP_CLIENT;
-        let file_name = archive_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-        let meta_json = serde_json::to_string(&meta).unwrap_or_default();
-        // Carry the source's exact WolfNet IP so the destination keeps it —
-        // a move reproduces the container as-is (the source is stopped, so
-        // there is no IP conflict).
-        let src_wolfnet_ip = containers::lxc_get_wolfnet_ip(&name);
-        let mut last_err: Option<String> = None;
-        // A connect failure means nothing was sent (safe to restart the
-        // source). A timeout or any other error is ambiguous — the target
-        // may already have imported AND started the container, so restarting
-        // the source would leave two copies running (split-brain).
-        let mut saw_ambiguous = false;
+            let size_mb = [Some(archive_path.as_path()), checkpoint_archive.as_deref()].into_iter().flatten()
+                .filter_map(|p| std::fs::metadata(p).ok())
+                .map(|m| m.len()).sum::<u64>() / (1024 * 1024);
+            migration_update(&tasks, &tid, "upload", &format!("Transferring {} MB to {}…", size_mb, target_label));
+
+            // 3. Upload → target imports a bootable config, starts + verifies it.
+            let mut fields = vec![
+                ("new_name", new_name.clone()),
+                ("storage", storage_val.clone()),
+                ("meta", serde_json::to_string(&meta).unwrap_or_default()),
+                ("start", "1".to_string()),
+                ("preserve_identity", "1".to_string()),
+            ];
+            // Carry the source's exact WolfNet IP so the destination keeps it —
+            // a move reproduces the container as-is (the source is stopped, so
+            // there is no IP conflict).
+            if let Some(ip) = containers::lxc_get_wolfnet_ip(&name) {
+                fields.push(("wolfnet_ip", ip));
+            }
+            // Same key as a clone of this container, so a move after an
+            // earlier clone (or a retried move) only sends the difference.
+            let key = format!("lxc:{}:{}", crate::agent::self_node_id(), name);
+            let progress = { let (tasks, tid) = (tasks.clone(), tid.clone());
+                move |done, total| migration_progress(&tasks, &tid, Some(done), Some(total), None) };
+            let pushed = push_lxc_import(&import_urls, &cluster_secret, &archive_path, checkpoint_archive.as_deref(),
+                &key, &fields, &migration_throttle(&tasks, &tid), std::time::Duration::from_secs(3600), progress).await;
+            containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
+            if let Some(packed) = &checkpoint_archive {
+                let _ = std::fs::remove_file(packed);
+            }
 
-        for import_url in &import_urls {
-            let mut form = reqwest::multipart::Form::new()
-                .text("new_name", new_name.clone())
-                .text("storage", storage_val.clone())
-                .text("meta", meta_json.clone())
-                .text("start", "1")
-                .text("preserve_identity", "1")
-                .part("archive", reqwest::multipart::Part::bytes(archive_bytes.clone())
-                    .file_name(file_name.clone()));
-            if let Some(ip) = src_wolfnet_ip.clone() {
-                form = form.text("wolfnet_ip", ip);
-            }
-
-            match client.post(import_url)
-                .timeout(std::time::Duration::from_secs(3600))
-                .header("X-WolfStack-Secret", cluster_secret.clone())
-                .multipart(form)
-                .send()
-                .await
-            {
-                Ok(r) => {
-                    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
+            match pushed {
+                Ok((r, sent)) => {
                     let status = r.status();
                     let data = r.json::<serde_json::Value>().await.unwrap_or_else(|_| serde_json::json!({}));
                     if status.is_success() {
@@ -11839,49 +14135,54 @@ pub async fn lxc_migrate(
                             let note = if target_is_proxmox && meta.source_type == "standalone" {
                                 "\n\nNote: imported onto Proxmox from a standalone node — custom resource limits / LXC settings were not translated; review with `pct config` and adjust via `pct set`."
                             } else { "" };
+                            let how = match (ckpt.is_some(), data.get("restored").and_then(|v| v.as_bool())) {
+                                (true, Some(true)) => format!(
+                                    "Live-migrated '{}' to {} — restored from a CRIU checkpoint with its processes intact ({}s frozen).",
+                                    name, target_label, frozen_at.elapsed().as_secs()),
+                                (true, _) => format!(
+                                    "Migrated '{}' to {} — the checkpoint could not be restored there, so the destination was cold-booted instead ({}).",
+                                    name, target_label,
+                                    data.get("restore_error").and_then(|v| v.as_str()).unwrap_or("older WolfStack on the target")),
+                                _ => format!("Migrated '{}' to {} — destination is running.", name, target_label),
+                            };
                             migration_done(&tasks, &tid, &format!(
-                                "Migrated '{}' to {} — destination is running. Source stopped and kept on the old node as a rollback.{}",
-                                name, target_label, note));
+                                "{} Source stopped and kept on the old node as a rollback.{}{}{}",
+                                how, transfer_saving_note(sent.as_ref()), note, live_note));
                         } else {
                             // Imported but didn't boot — roll back to the source.
-                            if was_running { let _ = containers::lxc_start(&name); }
+                            lxc_migrate_restart_source(&name, was_running, ckpt);
                             let serr = data.get("start_error").and_then(|v| v.as_str())
                                 .unwrap_or("destination did not reach RUNNING");
                             migration_fail(&tasks, &tid, &format!(
                                 "Destination imported but failed to start — source restarted, nothing lost.\n\n{}", serr));
                         }
                     } else {
-                        if was_running { let _ = containers::lxc_start(&name); }
+                        lxc_migrate_restart_source(&name, was_running, ckpt);
                         let err_text = data.get("error").and_then(|v| v.as_str()).map(|s| s.to_string())
                             .unwrap_or_else(|| format!("HTTP {}", status));
                         migration_fail(&tasks, &tid, &format!("Import on target failed — source restarted: {}", err_text));
                     }
-                    return;
                 }
+                // A connect failure, or a chunk upload that never completed,
+                // means nothing was imported (safe to restart the source). An
+                // import request that got no answer is ambiguous — the target
+                // may already have imported AND started the container, so
+                // restarting the source would leave two copies running
+                // (split-brain). Leave it stopped and make the operator
+                // reconcile (critical for clustered DBs like Galera).
+                Err(e) if e.ambiguous => migration_fail(&tasks, &tid, &format!(
+                    "Transfer to {} timed out or failed ambiguously — the destination may have started the container. Left the source STOPPED to avoid running two copies; check {} and start whichever copy you want to keep. ({})",
+                    target_addr, target_addr, e.error)),
                 Err(e) => {
-                    if !e.is_connect() { saw_ambiguous = true; }
-                    last_err = Some(e.to_string());
-                    continue;
+                    lxc_migrate_restart_source(&name, was_running, ckpt);
+                    migration_fail(&tasks, &tid, &format!(
+                        "Could not transfer to {} on any port — nothing was imported, source restarted: {}",
+                        target_addr, e.error));
                 }
             }
-        }
-
-        // Transfer failed on every endpoint.
-        containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-        if saw_ambiguous {
-            // The destination MAY have received and started the container.
-            // Do NOT restart the source — two running copies risk split-brain
-            // (critical for clustered DBs like Galera). Leave it stopped and
-            // make the operator reconcile.
-            migration_fail(&tasks, &tid, &format!(
-                "Transfer to {} timed out or failed ambiguously — the destination may have started the container. Left the source STOPPED to avoid running two copies; check {} and start whichever copy you want to keep. ({})",
-                target_addr, target_addr, last_err.unwrap_or_default()));
-        } else {
-            // Pure connect failures: nothing was sent, safe to bring it back.
-            if was_running { let _ = containers::lxc_start(&name); }
-            migration_fail(&tasks, &tid, &format!(
-                "Could not connect to {} on any port — nothing was sent, source restarted: {}",
-                target_addr, last_err.unwrap_or_default()));
+        }.await;
+        if let Some(dir) = &checkpoint_dir {
+            let _ = std::fs::remove_dir_all(dir);
         }
     });
 
@@ -11956,13 +14257,17 @@ pub async fn lxc_import_external(
         if let Err(resp) = require_auth(&req, &state) { return resp; }
     }
 
-    // Delegate to the standard import logic
-    lxc_import_endpoint_inner(&mut payload).await
+    // Delegate to the standard import logic. Chunked transfers are a
+    // same-cluster mechanism — a token holder can't name one.
+    lxc_import_endpoint_inner(&mut payload, has_secret).await
 }
 
-/// Shared import logic for both internal and external imports
+/// Shared import logic for both internal and external imports. The
+/// archive is either uploaded (`archive`) or, with `allow_transfer`,
+/// already here from a chunked transfer (`transfer` = its id).
 async fn lxc_import_endpoint_inner(
     payload: &mut actix_multipart::Multipart,
+    allow_transfer: bool,
 ) -> HttpResponse {
     let import_dir = std::path::Path::new("/tmp/wolfstack-imports");
     let _ = std::fs::create_dir_all(import_dir);
@@ -11975,6 +14280,7 @@ async fn lxc_import_endpoint_inner(
     let mut lxc_config: Option<String> = None;   // source config, for a bootable standalone import
     let mut start_after_import = false;          // migrate sets this to boot + verify the destination
     let mut preserve_identity = false;           // migrate (a move) sets this to keep IP/MAC/WolfNet as-is
+    let mut checkpoint_path = None;              // live migrate: CRIU checkpoint to restore instead of booting
 
     use futures::StreamExt;
     while let Some(item) = payload.next().await {
@@ -12042,20 +14348,36 @@ async fn lxc_import_endpoint_inner(
                 let ext = if archive_format == "vzdump" { "tar.zst" } else { "tar.gz" };
                 let filename = format!("lxc-import-{}.{}", uuid::Uuid::new_v4(), ext);
                 let dest = import_dir.join(&filename);
-                let mut file = match std::fs::File::create(&dest) {
-                    Ok(f) => f,
-                    Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Write error: {}", e)})),
-                };
-                use std::io::Write;
-                while let Some(chunk) = field.next().await {
-                    if let Ok(data) = chunk {
-                        if let Err(e) = file.write_all(&data) {
-                            return HttpResponse::InternalServerError().json(serde_json::json!({"error": format!("Write error: {}", e)}));
-                        }
-                    }
+                // Straight to disk as it arrives; a dropped upload is an
+                // error, not a silently truncated archive.
+                if let Err(e) = streaming::stream_to_file(&mut field, &dest).await {
+                    return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
                 }
                 archive_path = Some(dest);
             }
+            "checkpoint" => {
+                let dest = import_dir.join(format!("lxc-checkpoint-{}.tar.gz", uuid::Uuid::new_v4()));
+                if let Err(e) = streaming::stream_to_file(&mut field, &dest).await {
+                    return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+                }
+                checkpoint_path = Some(dest);
+            }
+            "transfer" | "checkpoint_transfer" if allow_transfer => {
+                let mut buf = Vec::new();
+                while let Some(chunk) = field.next().await {
+                    if let Ok(data) = chunk { buf.extend_from_slice(&data); }
+                }
+                let id = String::from_utf8_lossy(&buf).trim().to_string();
+                let assembled = match crate::transfer::assembled(&id) {
+                    Ok(p) => p,
+                    Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+                };
+                if field_name == "transfer" {
+                    archive_path = Some(assembled);
+                } else {
+                    checkpoint_path = Some(assembled);
+                }
+            }
             _ => { while let Some(_) = field.next().await {} }
         }
     }
@@ -12079,8 +14401,15 @@ async fn lxc_import_endpoint_inner(
     let storage_c = storage.clone();
     let carried = lxc_config.clone();
     let pre_ip = wolfnet_ip.clone();
-    let blk = web::block(move || -> Result<(String, Option<String>), String> {
-        let outcome = containers::lxc_import(&archive_str, &nn, storage_c.as_deref(), carried.as_deref())?;
+    let blk = web::block(move || -> Result<(String, Option<String>, Option<Result<(), String>>), String> {
+        let outcome = containers::lxc_import(&archive_str, &nn, storage_c.as_deref(), carried.as_deref());
+        let outcome = match outcome {
+            Ok(o) => o,
+            Err(e) => {
+                if let Some(c) = &checkpoint_path { let _ = std::fs::remove_file(c); }
+                return Err(e);
+            }
+        };
         let _ = std::fs::remove_file(&archive_str);
 
         if preserve_identity {
@@ -12106,21 +14435,36 @@ async fn lxc_import_endpoint_inner(
             }
         }
 
-        // When the caller is a migrate orchestrator it asks us to boot the
-        // destination and confirm it reaches RUNNING (lxc_start polls and
-        // returns the lxc log tail on failure). Otherwise leave it stopped
-        // for the operator to start manually — the historical behaviour.
-        let start_err = if start_after_import {
-            containers::lxc_start(&outcome.start_id).err()
-        } else {
-            None
-        };
-        Ok((outcome.message, start_err))
+        // A live migrate sends a CRIU checkpoint: restore it, cold-booting
+        // if that fails. `restored` is None when there was nothing to restore.
+        let mut restored = None;
+        let mut start_err = None;
+        if let Some(archive) = &checkpoint_path {
+            if start_after_import {
+                let dir = archive.with_extension("").with_extension("");
+                let result = containers::lxc_criu::unpack(archive, &dir)
+                    .and_then(|()| containers::lxc_criu::restore_or_start(&outcome.start_id, &dir));
+                match result {
+                    Ok(None) => restored = Some(Ok(())),
+                    Ok(Some(restore_err)) => restored = Some(Err(restore_err)),
+                    Err(e) => start_err = Some(e),
+                }
+                let _ = std::fs::remove_dir_all(&dir);
+            }
+            let _ = std::fs::remove_file(archive);
+        } else if start_after_import {
+            // When the caller is a migrate orchestrator it asks us to boot the
+            // destination and confirm it reaches RUNNING (lxc_start polls and
+            // returns the lxc log tail on failure). Otherwise leave it stopped
+            // for the operator to start manually — the historical behaviour.
+            start_err = containers::lxc_start(&outcome.start_id).err();
+        }
+        Ok((outcome.message, start_err, restored))
     })
     .await;
 
     match blk {
-        Ok(Ok((message, start_err))) => {
+        Ok(Ok((message, start_err, restored))) => {
             if start_after_import {
                 if let Some(err) = start_err {
                     // Imported but did not boot — report so a migrate caller
@@ -12135,6 +14479,8 @@ async fn lxc_import_endpoint_inner(
                 return HttpResponse::Ok().json(serde_json::json!({
                     "message": message,
                     "started": true,
+                    "restored": restored.as_ref().map(|r| r.is_ok()),
+                    "restore_error": restored.and_then(|r| r.err()),
                 }));
             }
             HttpResponse::Ok().json(serde_json::json!({"message": message}))
@@ -12150,8 +14496,548 @@ async fn lxc_import_endpoint_inner(
     }
 }
 
+// ─── Container Templates ───
+
+#[derive(Deserialize)]
+pub struct TemplateCreateRequest {
+    /// `lxc` or `docker`.
+    pub kind: String,
+    pub container: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct TemplateDeployRequest {
+    pub name: String,
+    /// Target node; empty = this node.
+    #[serde(default)]
+    pub node_id: String,
+    #[serde(default)]
+    pub storage: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TemplateSettingsRequest {
+    #[serde(default)]
+    pub library_path: String,
+}
+
+/// GET /api/templates — every template in the library
+pub async fn templates_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match web::block(|| (crate::templates::list(), crate::templates::library_dir())).await {
+        Ok((templates, dir)) => HttpResponse::Ok().json(serde_json::json!({
+            "templates": templates,
+            "library_path": dir.to_string_lossy(),
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+/// POST /api/templates — save an existing container as a template
+pub async fn templates_create(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<TemplateCreateRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    let body = body.into_inner();
+    if !crate::auth::is_safe_name(&body.container) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Invalid container name"}));
+    }
+    let kind = match body.kind.as_str() {
+        "docker" => crate::auth::tenancy::Kind::Docker,
+        "lxc" => crate::auth::tenancy::Kind::Lxc,
+        other => return HttpResponse::BadRequest().json(serde_json::json!({"error": format!("Unknown container type '{}'", other)})),
+    };
+    // The source container is named in the body, not the path.
+    if let Err(e) = container_access(&req, &caller, kind, &body.container) { return e; }
+    let source_node = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let result = web::block(move || if kind == crate::auth::tenancy::Kind::Docker {
+        crate::templates::create_from_docker(&body.container, &body.name, &body.description, &source_node)
+    } else {
+        crate::templates::create_from_lxc(&body.container, &body.name, &body.description, &source_node)
+    })
+    .await;
+    match result {
+        Ok(Ok(t)) => HttpResponse::Ok().json(t),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+/// DELETE /api/templates/{id}
+pub async fn templates_delete(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    match web::block(move || crate::templates::delete(&id)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({"message": "Template deleted"})),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+/// GET /api/templates/settings — where the library lives
+pub async fn templates_settings_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let settings = crate::templates::LibrarySettings::load();
+    HttpResponse::Ok().json(serde_json::json!({
+        "library_path": settings.library_path,
+        "effective_path": crate::templates::library_dir().to_string_lossy(),
+    }))
+}
+
+/// POST /api/templates/settings — move the library (e.g. onto a shared mount)
+pub async fn templates_settings_save(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<TemplateSettingsRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match crate::templates::set_library_path(&body.library_path) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"message": "Template library location saved"})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+    }
+}
+
+/// WolfStack API URLs for a node. Proxmox nodes are registered with the
+/// PVE API port, so try the WolfStack ports instead (as lxc_remote_clone does).
+fn wolfstack_api_urls(node: &crate::agent::Node, path: &str) -> Vec<String> {
+    if node.node_type == "proxmox" {
+        let mut urls = build_node_urls(&node.address, 8553, path);
+        urls.extend(build_node_urls(&node.address, 8552, path));
+        urls
+    } else {
+        build_node_urls(&node.address, node.port, path)
+    }
+}
+
+/// POST JSON to the first reachable URL. Err carries the remote's error.
+async fn post_json_to_node(
+    state: &web::Data<AppState>,
+    urls: &[String],
+    body: &serde_json::Value,
+    timeout_secs: u64,
+) -> Result<serde_json::Value, String> {
+    let mut last_err = String::new();
+    for url in urls {
+        match API_HTTP_CLIENT.post(url)
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .header("X-WolfStack-Secret", state.cluster_secret.clone())
+            .json(body)
+            .send()
+            .await
+        {
+            Ok(r) => {
+                let ok = r.status().is_success();
+                let data = r.json::<serde_json::Value>().await.unwrap_or_default();
+                if ok { return Ok(data); }
+                return Err(data.get("error").and_then(|v| v.as_str()).unwrap_or("Remote request failed").to_string());
+            }
+            Err(e) => last_err = e.to_string(),
+        }
+    }
+    Err(format!("Node unreachable: {}", last_err))
+}
+
+/// POST /api/templates/{id}/deploy — provision a container from a template
+/// on this node or any node in the cluster
+pub async fn templates_deploy(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<TemplateDeployRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    let body = body.into_inner();
+    let new_name = body.name.trim().to_string();
+    if !crate::auth::is_safe_name(&new_name) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Invalid container name"}));
+    }
+    let template = match crate::templates::get(&id) {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::NotFound().json(serde_json::json!({"error": e})),
+    };
+    let storage = body.storage.filter(|s| !s.trim().is_empty());
+
+    let remote = if body.node_id.is_empty() || body.node_id == state.cluster.self_id {
+        None
+    } else {
+        match resolve_target_node(&state, &body.node_id, None, None) {
+            Some(n) if !n.is_self => Some(n),
+            Some(_) => None,
+            None => return HttpResponse::NotFound().json(serde_json::json!({"error": "Target node not found"})),
+        }
+    };
+
+    let node = match remote {
+        Some(n) => n,
+        None => {
+            let t = template.clone();
+            let nn = new_name.clone();
+            return match web::block(move || crate::templates::deploy_local(&t, &nn, storage.as_deref())).await {
+                Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({"message": msg})),
+                Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
+                Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+            };
+        }
+    };
+
+    let done = format!("Deployed '{}' from template '{}' on {}", new_name, template.name, node.hostname);
+    match template.kind.as_str() {
+        "docker" => {
+            let spec = match template.docker.as_ref() {
+                Some(s) => s,
+                None => return HttpResponse::BadRequest().json(serde_json::json!({"error": "Template has no Docker spec"})),
+            };
+            let pull = serde_json::json!({"image": spec.image});
+            if let Err(e) = post_json_to_node(&state, &wolfstack_api_urls(&node, "/api/containers/docker/pull"), &pull, 900).await {
+                return HttpResponse::BadGateway().json(serde_json::json!({"error": format!("Pull on {} failed: {}", node.hostname, e)}));
+            }
+            let create = serde_json::json!({
+                "name": new_name,
+                "image": spec.image,
+                "ports": spec.ports,
+                "env": spec.env,
+                "volumes": spec.volumes,
+                "memory_limit": spec.memory,
+                "cpu_cores": spec.cpus,
+            });
+            match post_json_to_node(&state, &wolfstack_api_urls(&node, "/api/containers/docker/create"), &create, 120).await {
+                Ok(_) => HttpResponse::Ok().json(serde_json::json!({"message": done})),
+                Err(e) => HttpResponse::BadGateway().json(serde_json::json!({"error": format!("Create on {} failed: {}", node.hostname, e)})),
+            }
+        }
+        "lxc" => {
+            // Same transfer as a cross-node clone: the target's import
+            // endpoint gives the copy a fresh identity. Keyed by template,
+            // so deploying it to the same node again sends next to nothing.
+            let archive_path = match crate::templates::archive_path(&template) {
+                Ok(p) => p,
+                Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+            };
+            let fields = [
+                ("new_name", new_name.clone()),
+                ("storage", storage.clone().unwrap_or_default()),
+                ("meta", template.lxc.as_ref().and_then(|m| serde_json::to_string(m).ok()).unwrap_or_default()),
+            ];
+            let key = format!("template:{}", template.id);
+            let urls = wolfstack_api_urls(&node, LXC_IMPORT_PATH);
+            match push_lxc_import(&urls, &state.cluster_secret, &archive_path, None, &key, &fields,
+                &Default::default(), std::time::Duration::from_secs(600), |_, _| {}).await
+            {
+                Ok((r, _)) if r.status().is_success() => {
+                    HttpResponse::Ok().json(serde_json::json!({"message": done}))
+                }
+                Ok((r, _)) => {
+                    let err_text = r.text().await.unwrap_or_default();
+                    HttpResponse::BadGateway().json(serde_json::json!({"error": format!("Import on {} failed: {}", node.hostname, err_text)}))
+                }
+                Err(e) => HttpResponse::BadGateway().json(serde_json::json!({
+                    "error": format!("Transfer to {} failed on all ports/protocols: {}", node.address, e.error)
+                })),
+            }
+        }
+        other => HttpResponse::BadRequest().json(serde_json::json!({"error": format!("Unknown template kind '{}'", other)})),
+    }
+}
+
+// ─── Console history & snippets ───
+
+#[derive(Deserialize)]
+pub struct ConsoleHistoryQuery {
+    #[serde(default)]
+    pub q: String,
+    /// Container / VM name or node hostname.
+    #[serde(default)]
+    pub target: String,
+    /// Admins only: `*` for every user's history, or a username.
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub limit: usize,
+}
+
+/// GET /api/console/history — commands typed into WolfStack consoles,
+/// newest first. Users see their own; admins may ask for anyone's.
+pub async fn console_history(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<ConsoleHistoryQuery>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let query = query.into_inner();
+    let user = if query.user.is_empty() || query.user == caller {
+        Some(caller)
+    } else if crate::auth::session_user_is_admin(&caller) {
+        if query.user == "*" { None } else { Some(query.user) }
+    } else {
+        return HttpResponse::Forbidden().json(serde_json::json!({"error": "Only admins can view other users' console history"}));
+    };
+    let q = crate::console::history::HistoryQuery { user, text: query.q, target: query.target, limit: query.limit };
+    match web::block(move || crate::console::history::search(&q)).await {
+        Ok(entries) => HttpResponse::Ok().json(entries),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+/// GET /api/console/snippets — the caller's saved commands
+pub async fn console_snippets_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let user = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    HttpResponse::Ok().json(crate::console::history::list_snippets(&user))
+}
+
+/// POST /api/console/snippets — create, or update when `id` is set
+pub async fn console_snippets_save(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<crate::console::history::Snippet>,
+) -> HttpResponse {
+    let user = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    match crate::console::history::save_snippet(&user, body.into_inner()) {
+        Ok(s) => HttpResponse::Ok().json(s),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+    }
+}
+
+/// DELETE /api/console/snippets/{id}
+pub async fn console_snippets_delete(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    let user = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    match crate::console::history::delete_snippet(&user, &path.into_inner()) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"message": "Snippet deleted"})),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({"error": e})),
+    }
+}
+
+// ─── Inventory reports ───
+
+#[derive(Deserialize)]
+pub struct InventoryQuery {
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    pub format: String,
+    /// `cluster` (default) or `local` — what peers are asked for
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// GET /api/reports/inventory — every node and guest with allocations,
+/// IPs and versions, as JSON or a CSV download
+pub async fn reports_inventory(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<InventoryQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let rows = if query.scope == "local" {
+        let me = match state.cluster.get_all_nodes().into_iter().find(|n| n.is_self) {
+            Some(n) => n,
+            None => return HttpResponse::ServiceUnavailable().json(serde_json::json!({ "error": "Node not registered yet" })),
+        };
+        let st = state.clone();
+        web::block(move || {
+            let vms = crate::monitoring::inventory::vms_blocking(&st);
+            crate::inventory_report::collect_local(&me, &vms)
+        }).await.unwrap_or_default()
+    } else {
+        crate::inventory_report::collect_cluster(&state).await
+    };
+
+    if query.format == "csv" {
+        let filename = format!("wolfstack-inventory-{}.csv", chrono::Utc::now().format("%Y-%m-%d"));
+        return HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+            .body(crate::inventory_report::to_csv(&rows));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "generated": chrono::Utc::now().to_rfc3339(),
+        "rows": rows,
+    }))
+}
+
+/// GET /api/reports/inventory/schedule — the monthly email settings
+pub async fn reports_inventory_schedule_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(crate::inventory_report::ReportSchedule::load())
+}
+
+#[derive(Deserialize)]
+pub struct InventoryScheduleRequest {
+    pub enabled: bool,
+    pub day_of_month: u32,
+    #[serde(default)]
+    pub email_to: String,
+}
+
+/// PUT /api/reports/inventory/schedule
+pub async fn reports_inventory_schedule_put(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<InventoryScheduleRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if !(1..=28).contains(&body.day_of_month) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Day of month must be 1–28" }));
+    }
+    let to = body.email_to.trim();
+    if !to.is_empty() && to.parse::<lettre::message::Mailbox>().is_err() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("'{}' is not a valid email address", to) }));
+    }
+    let mut schedule = crate::inventory_report::ReportSchedule::load();
+    schedule.enabled = body.enabled;
+    schedule.day_of_month = body.day_of_month;
+    schedule.email_to = to.to_string();
+    match schedule.save() {
+        Ok(()) => HttpResponse::Ok().json(schedule),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/reports/inventory/send — email the report now (doesn't
+/// count as this month's scheduled send)
+pub async fn reports_inventory_send(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let month = chrono::Utc::now().format("%Y-%m").to_string();
+    match crate::inventory_report::send_report(&state, &month).await {
+        Ok(n) => HttpResponse::Ok().json(serde_json::json!({ "message": format!("Inventory report sent ({} rows)", n) })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AvailabilityQuery {
+    /// `YYYY-MM`; default the current month
+    #[serde(default)]
+    pub month: String,
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    pub format: String,
+    /// `cluster` (default) or `local`
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// The requested `YYYY-MM`, defaulting to the current month.
+fn availability_month(month: &str) -> Result<String, HttpResponse> {
+    if month.is_empty() {
+        return Ok(chrono::Utc::now().format("%Y-%m").to_string());
+    }
+    if crate::availability::month_bounds(month).is_none() {
+        return Err(HttpResponse::BadRequest().json(serde_json::json!({ "error": "month must be YYYY-MM" })));
+    }
+    Ok(month.to_string())
+}
+
+/// GET /api/reports/availability — monthly availability per guest and
+/// per node, as JSON or a CSV download for SLA reporting
+pub async fn reports_availability(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<AvailabilityQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let month = match availability_month(&query.month) { Ok(m) => m, Err(resp) => return resp };
+    let rows = if query.scope == "local" {
+        let me = match state.cluster.get_all_nodes().into_iter().find(|n| n.is_self) {
+            Some(n) => n,
+            None => return HttpResponse::ServiceUnavailable().json(serde_json::json!({ "error": "Node not registered yet" })),
+        };
+        let m = month.clone();
+        web::block(move || crate::availability::collect_local(&me, &m)).await.unwrap_or_default()
+    } else {
+        crate::availability::collect_cluster(&state, &month).await
+    };
+
+    if query.format == "csv" {
+        let filename = format!("wolfstack-availability-{}.csv", month);
+        return HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+            .body(crate::availability::to_csv(&rows));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "month": month,
+        "generated": chrono::Utc::now().to_rfc3339(),
+        "rows": rows,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct AvailabilityHistoryQuery {
+    #[serde(default)]
+    pub month: String,
+    pub kind: String,
+    pub name: String,
+}
+
+/// GET /api/reports/availability/history?month=&kind=&name= — one guest's
+/// (or, with kind=node, this node's) state changes for the month
+pub async fn reports_availability_history(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<AvailabilityHistoryQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let month = match availability_month(&query.month) { Ok(m) => m, Err(resp) => return resp };
+    let m = month.clone();
+    let changes: Vec<_> = web::block(move || crate::availability::load_month(&m)).await.unwrap_or_default()
+        .into_iter()
+        .filter(|c| c.kind == query.kind && c.name == query.name)
+        .collect();
+    HttpResponse::Ok().json(serde_json::json!({ "month": month, "changes": changes }))
+}
+
+#[derive(Deserialize)]
+pub struct CapacityQuery {
+    /// `cluster` (default) or `local`
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// GET /api/reports/capacity — projected date each node's memory and
+/// disks reach 90%, from the long-horizon usage history
+pub async fn reports_capacity(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<CapacityQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if query.scope == "local" {
+        let projections = web::block(crate::predictive::capacity::local_projections).await.unwrap_or_default();
+        return HttpResponse::Ok().json(serde_json::json!({
+            "target_pct": crate::predictive::capacity::TARGET_PCT,
+            "projections": projections,
+        }));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "target_pct": crate::predictive::capacity::TARGET_PCT,
+        "nodes": crate::predictive::capacity::collect_cluster(&state).await,
+    }))
+}
+
 pub type MigrationTasks = Arc<std::sync::RwLock<std::collections::HashMap<String, MigrationTask>>>;
 
+/// Report a long-running task's state to connected dashboards as a `job`
+/// event on `/api/ws/events`. `kind` names the task family (`migration`,
+/// `pbs_restore`), `job` is the same snapshot its status endpoint returns.
+pub fn publish_job(kind: &str, id: &str, job: &impl Serialize) {
+    if crate::events::live_listeners() {
+        crate::events::live("job", serde_json::json!({ "kind": kind, "id": id, "job": job }));
+    }
+}
+
 /// Helper: update a migration task's stage/message. Resets the
 /// per-stage progress counters so a stage transition always starts
 /// with a blank progress bar rather than showing the previous stage's
@@ -12164,6 +15050,10 @@ pub fn migration_update(tasks: &MigrationTasks, id: &str, stage: &str, message:
             task.percent = None;
             task.bytes_done = None;
             task.bytes_total = None;
+            task.rate_bps = None;
+            task.eta_secs = None;
+            task.rate_sample = None;
+            publish_job("migration", id, &*task);
         }
     }
 }
@@ -12171,7 +15061,7 @@ pub fn migration_update(tasks: &MigrationTasks, id: &str, stage: &str, message:
 /// Update the per-stage progress indicators without changing the
 /// current stage. Pass `percent` alone (a 0.0–100.0 float) when there
 /// are no byte counts, or pass byte counts and let the helper
-/// recompute percent.
+/// recompute percent. Byte counts also feed the rate and ETA.
 pub fn migration_progress(
     tasks: &MigrationTasks,
     id: &str,
@@ -12181,7 +15071,11 @@ pub fn migration_progress(
 ) {
     if let Ok(mut map) = tasks.write() {
         if let Some(task) = map.get_mut(id) {
-            if let Some(b) = bytes_done { task.bytes_done = Some(b); }
+            let shown = task.percent.map(|p| p as u32);
+            if let Some(b) = bytes_done {
+                task.bytes_done = Some(b);
+                update_rate(task, b, std::time::Instant::now());
+            }
             if let Some(b) = bytes_total { task.bytes_total = Some(b); }
             if let Some(p) = percent {
                 task.percent = Some(p.clamp(0.0, 100.0));
@@ -12190,10 +15084,57 @@ pub fn migration_progress(
                     task.percent = Some(((d as f64 / t as f64) * 100.0).clamp(0.0, 100.0));
                 }
             }
+            if let (Some(rate), Some(d), Some(t)) = (task.rate_bps, task.bytes_done, task.bytes_total) {
+                task.eta_secs = (rate > 0).then(|| t.saturating_sub(d) / rate);
+            }
+            // Whole percents only — byte counts tick far faster than a
+            // progress bar needs.
+            if task.percent.map(|p| p as u32) != shown {
+                publish_job("migration", id, &*task);
+            }
         }
     }
 }
 
+/// Fold a new byte count into the task's rate: sampled at most once a
+/// second, smoothed so one slow chunk doesn't swing the ETA about.
+fn update_rate(task: &mut MigrationTask, bytes_done: u64, now: std::time::Instant) {
+    let Some((at, bytes)) = task.rate_sample else {
+        task.rate_sample = Some((now, bytes_done));
+        return;
+    };
+    let secs = now.duration_since(at).as_secs_f64();
+    if secs < 1.0 {
+        return;
+    }
+    let sample = bytes_done.saturating_sub(bytes) as f64 / secs;
+    let rate = match task.rate_bps {
+        Some(prev) => prev as f64 * 0.7 + sample * 0.3,
+        None => sample,
+    };
+    task.rate_bps = Some(rate as u64);
+    task.rate_sample = Some((now, bytes_done));
+}
+
+/// Set (or with None, lift) a task's transfer cap. A running transfer
+/// picks it up from its next chunk. False when there is no such task.
+pub fn migration_set_bwlimit(tasks: &MigrationTasks, id: &str, mbps: Option<u32>) -> bool {
+    let Ok(mut map) = tasks.write() else { return false };
+    let Some(task) = map.get_mut(id) else { return false };
+    let mbps = mbps.filter(|m| *m > 0);
+    task.bwlimit_mbps = mbps;
+    task.throttle.store(mbps.map_or(0, |m| m as u64 * 125_000), std::sync::atomic::Ordering::Relaxed);
+    true
+}
+
+/// The task's transfer cap in bytes/s, for the transfer to read as it
+/// goes. A missing task gets a fresh, uncapped one.
+pub fn migration_throttle(tasks: &MigrationTasks, id: &str) -> Arc<std::sync::atomic::AtomicU64> {
+    tasks.read().ok()
+        .and_then(|map| map.get(id).map(|t| t.throttle.clone()))
+        .unwrap_or_default()
+}
+
 pub fn migration_fail(tasks: &MigrationTasks, id: &str, error: &str) {
     if let Ok(mut map) = tasks.write() {
         if let Some(task) = map.get_mut(id) {
@@ -12201,6 +15142,7 @@ pub fn migration_fail(tasks: &MigrationTasks, id: &str, error: &str) {
             task.message = error.to_string();
             task.error = Some(error.to_string());
             task.completed = true;
+            publish_job("migration", id, &*task);
         }
     }
 }
@@ -12212,10 +15154,19 @@ pub fn migration_done(tasks: &MigrationTasks, id: &str, message: &str) {
             task.message = message.to_string();
             task.completed = true;
             task.percent = Some(100.0);
+            publish_job("migration", id, &*task);
         }
     }
 }
 
+/// Wait for a migration slot (see `crate::jobs`), showing the task as
+/// queued until it gets one.
+pub async fn migration_slot(tasks: &MigrationTasks, id: &str, label: &str) -> crate::jobs::Slot {
+    crate::jobs::acquire_async(crate::jobs::Category::Migration, crate::jobs::Priority::Normal, label, |why| {
+        migration_update(tasks, id, "queued", &format!("Queued — {}", why));
+    }).await
+}
+
 /// Create a new migration task entry and return the generated id. The
 /// task starts in `preflight` stage — callers transition it via
 /// `migration_update` as soon as real work begins.
@@ -12229,6 +15180,8 @@ pub fn migration_create(tasks: &MigrationTasks) -> String {
         .unwrap_or_default()
         .as_secs();
     if let Ok(mut map) = tasks.write() {
+        // Finished jobs are kept a day for GET /api/migration, then dropped.
+        map.retain(|_, t| !t.completed || now.saturating_sub(t.started_at) < 86400);
         map.insert(id.clone(), MigrationTask {
             id: id.clone(),
             stage: "preflight".to_string(),
@@ -12239,6 +15192,11 @@ pub fn migration_create(tasks: &MigrationTasks) -> String {
             percent: None,
             bytes_done: None,
             bytes_total: None,
+            rate_bps: None,
+            eta_secs: None,
+            bwlimit_mbps: None,
+            throttle: Default::default(),
+            rate_sample: None,
         });
     }
     id
@@ -12260,6 +15218,120 @@ pub async fn migration_status(
     HttpResponse::NotFound().json(serde_json::json!({"error": "Task not found"}))
 }
 
+/// GET /api/migration — this node's migration, clone and transfer jobs, running ones first, with progress, rate and ETA
+pub async fn migration_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    // Jobs aren't tenant-tagged — a tenant sees only the ones it started, by id.
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({"error": "Not available to tenant accounts"}));
+    }
+    let mut jobs: Vec<MigrationTask> = state.migration_tasks.read()
+        .map(|map| map.values().cloned().collect())
+        .unwrap_or_default();
+    jobs.sort_by(|a, b| a.completed.cmp(&b.completed).then(b.started_at.cmp(&a.started_at)));
+    jobs.truncate(100);
+    HttpResponse::Ok().json(serde_json::json!({ "jobs": jobs }))
+}
+
+/// GET /api/jobs — heavy jobs (backups, restores, migrations) holding or
+/// waiting for a slot on this node, and the limits in force
+pub async fn jobs_status(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({"error": "Not available to tenant accounts"}));
+    }
+    HttpResponse::Ok().json(crate::jobs::status())
+}
+
+/// PUT /api/jobs/limits — set this node's concurrency limits.
+/// Body: `{"max_total": 3, "max_backups": 2, "max_restores": 2, "max_migrations": 1}`
+pub async fn jobs_set_limits(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<crate::jobs::JobLimits>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    if let Err(resp) = require_admin_caller(&req, &caller, "change job limits") { return resp; }
+    match crate::jobs::set_limits(body.into_inner()) {
+        Ok(()) => HttpResponse::Ok().json(crate::jobs::status()),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// Only admins (or a peer relaying an admin's request) may read or
+/// change the SNMP agent: its config holds the community and passphrases.
+fn snmp_caller(req: &HttpRequest, state: &web::Data<AppState>) -> Result<(), HttpResponse> {
+    let caller = require_auth(req, state)?;
+    require_admin_caller(req, &caller, "manage SNMP")
+}
+
+/// GET /api/snmp — this node's SNMP agent settings (community and
+/// passphrases blanked) and whether snmpd is installed and running
+pub async fn snmp_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = snmp_caller(&req, &state) { return resp; }
+    let (cfg, installed, running) = web::block(|| {
+        (crate::snmp::SnmpConfig::load(), crate::snmp::snmpd_installed(), crate::snmp::snmpd_running())
+    }).await.unwrap_or_default();
+    HttpResponse::Ok().json(serde_json::json!({
+        "config": cfg.masked(),
+        "community_set": !cfg.community.is_empty(),
+        "v3_auth_pass_set": !cfg.v3_auth_pass.is_empty(),
+        "v3_priv_pass_set": !cfg.v3_priv_pass.is_empty(),
+        "installed": installed,
+        "running": running,
+    }))
+}
+
+/// PUT /api/snmp — save the SNMP agent settings and apply them to snmpd.
+/// A blank community or passphrase keeps the saved one.
+pub async fn snmp_set(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::snmp::SnmpConfig>) -> HttpResponse {
+    if let Err(resp) = snmp_caller(&req, &state) { return resp; }
+    let mut cfg = body.into_inner();
+    let result = web::block(move || {
+        cfg.keep_secrets(&crate::snmp::SnmpConfig::load());
+        cfg.validate()?;
+        cfg.save()?;
+        crate::snmp::apply(&cfg)
+    }).await;
+    match result {
+        Ok(Ok(message)) => HttpResponse::Ok().json(serde_json::json!({ "ok": true, "message": message })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// GET /api/snmp/mib — WOLFSTACK-MIB for this node's base OID, to load
+/// into the NMS
+pub async fn snmp_mib(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let base_oid = web::block(|| crate::snmp::SnmpConfig::load().base_oid).await.unwrap_or_default();
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header(("Content-Disposition", "attachment; filename=\"WOLFSTACK-MIB.txt\""))
+        .body(crate::snmp::mib(&base_oid))
+}
+
+#[derive(Deserialize)]
+pub struct MigrationBwlimitRequest {
+    /// Mbit/s; None or 0 lifts the cap.
+    #[serde(default)]
+    pub bwlimit_mbps: Option<u32>,
+}
+
+/// POST /api/migration/{id}/bwlimit — change a running job's bandwidth cap (`{"bwlimit_mbps": 50}`, or null to lift it)
+pub async fn migration_bwlimit(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<MigrationBwlimitRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    if !migration_set_bwlimit(&state.migration_tasks, &id, body.bwlimit_mbps) {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Task not found"}));
+    }
+    HttpResponse::Ok().json(serde_json::json!({ "bwlimit_mbps": body.bwlimit_mbps.filter(|m| *m > 0) }))
+}
+
 /// POST /api/containers/lxc/{name}/migrate-external — migrate to external cluster (background task)
 pub async fn lxc_migrate_external(
     req: HttpRequest,
@@ -12268,7 +15340,7 @@ pub async fn lxc_migrate_external(
     body: web::Json<MigrateExternalRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let new_name = body.new_name.as_deref().unwrap_or(&name).to_string();
     let target_url = body.target_url.clone();
     let target_token = body.target_token.clone();
@@ -12284,11 +15356,13 @@ pub async fn lxc_migrate_external(
 
     // Create task entry
     let task_id = migration_create(&tasks);
+    migration_set_bwlimit(&tasks, &task_id, body.bwlimit_mbps);
     migration_update(&tasks, &task_id, "preflight", "Checking destination connectivity...");
 
     let tid = task_id.clone();
     // Spawn migration in background
     tokio::spawn(async move {
+        let _slot = migration_slot(&tasks, &tid, &format!("Migrate LXC '{}' to {}", name, target_url)).await;
         // 1. Pre-flight
         let preflight_urls = build_external_urls(&target_url, "/api/storage/list");
         let preflight_client = &*API_HTTP_CLIENT;
@@ -12325,35 +15399,34 @@ pub async fn lxc_migrate_external(
             }
         };
         let _ = containers::lxc_start(&name);
-        migration_update(&tasks, &tid, "export", "Reading archive...");
 
-        let archive_bytes = match std::fs::read(&archive_path) {
-            Ok(b) => b,
-            Err(e) => {
-                containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
-                migration_fail(&tasks, &tid, &format!("Read archive: {}", e));
-                return;
-            }
-        };
-
-        let size_mb = archive_bytes.len() / (1024 * 1024);
-        migration_update(&tasks, &tid, "upload", &format!("Uploading {} MB to destination...", size_mb));
+        let size = std::fs::metadata(&archive_path).map(|m| m.len()).unwrap_or(0);
+        migration_update(&tasks, &tid, "upload", &format!("Uploading {} MB to destination...", size / (1024 * 1024)));
 
-        // 3. Upload
+        // 3. Upload — streamed from disk; the one-time token only covers a
+        // single import request, so this stays a plain multipart upload.
         let import_urls = build_external_urls(&target_url, "/api/containers/lxc/import-external");
-        let file_name = archive_path.file_name().unwrap_or_default().to_string_lossy().to_string();
 
         let client = &*API_HTTP_CLIENT;
 
         let mut last_err: Option<String> = None;
 
         for import_url in &import_urls {
+            let progress = { let (tasks, tid) = (tasks.clone(), tid.clone());
+                move |done| migration_progress(&tasks, &tid, Some(done), Some(size), None) };
+            let archive_part = match file_part(&archive_path, &migration_throttle(&tasks, &tid), progress).await {
+                Ok(p) => p,
+                Err(e) => {
+                    containers::lxc_export_cleanup(archive_path.to_str().unwrap_or(""));
+                    migration_fail(&tasks, &tid, &format!("Read archive: {}", e));
+                    return;
+                }
+            };
             let form = reqwest::multipart::Form::new()
                 .text("new_name", new_name.clone())
                 .text("storage", storage_val.clone())
                 .text("meta", serde_json::to_string(&meta).unwrap_or_default())
-                .part("archive", reqwest::multipart::Part::bytes(archive_bytes.clone())
-                    .file_name(file_name.clone()));
+                .part("archive", archive_part);
 
             let mut rb = client.post(import_url)
                 .timeout(std::time::Duration::from_secs(600))
@@ -12404,7 +15477,7 @@ pub async fn docker_volumes(
     path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let mounts = containers::docker_list_volumes(&id);
     HttpResponse::Ok().json(mounts)
 }
@@ -12425,7 +15498,7 @@ pub async fn docker_update_config(
     body: web::Json<DockerUpdateConfigReq>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
 
     match containers::docker_update_config(&id, body.autostart, body.memory_mb, body.cpus, body.wolfnet_ip.clone()) {
          Ok(msg) => {
@@ -12463,7 +15536,7 @@ pub async fn docker_update_env(
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
 
     let env: Vec<String> = match body.get("env").and_then(|v| v.as_array()) {
         Some(arr) => arr.iter()
@@ -12500,7 +15573,7 @@ pub async fn docker_update_raw_config(
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
 
     // Docker's inspect output is always a single-element array. The
     // UI may send it either way (bare object OR 1-element array),
@@ -12527,6 +15600,97 @@ pub async fn docker_update_raw_config(
     }
 }
 
+/// A stored variable as the UI sees it — secret values never leave the
+/// node.
+fn env_store_view(vars: &[containers::env_store::EnvVar]) -> Vec<serde_json::Value> {
+    vars.iter().map(|v| serde_json::json!({
+        "name": v.name,
+        "value": if v.secret { String::new() } else { v.value.clone() },
+        "secret": v.secret,
+    })).collect()
+}
+
+/// The scope is in the path but not where the tenancy path check looks,
+/// so a tenant-scoped caller is checked here: it may only use the scope
+/// of a Docker container its tenant owns (compose stacks are admin-side).
+fn env_scope_access(req: &HttpRequest, caller: &str, scope: &str) -> Result<(), HttpResponse> {
+    crate::auth::tenancy::check_console(req, caller, "docker", scope)
+        .map_err(|e| HttpResponse::NotFound().json(serde_json::json!({ "error": e })))
+}
+
+/// GET /api/env-store — every scope (container or compose stack) with
+/// stored environment on this node.
+pub async fn env_store_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let store = containers::env_store::load();
+    let scopes: serde_json::Map<String, serde_json::Value> = store.scopes.iter()
+        .filter(|(k, _)| env_scope_access(&req, &caller, k).is_ok())
+        .map(|(k, v)| (k.clone(), serde_json::Value::from(env_store_view(v))))
+        .collect();
+    HttpResponse::Ok().json(serde_json::json!({ "scopes": scopes }))
+}
+
+/// GET /api/env-store/{scope}
+pub async fn env_store_get(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let scope = path.into_inner();
+    if let Err(resp) = env_scope_access(&req, &caller, &scope) { return resp; }
+    HttpResponse::Ok().json(serde_json::json!({
+        "scope": scope,
+        "vars": env_store_view(&containers::env_store::vars(&scope)),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct EnvStoreRequest {
+    pub vars: Vec<containers::env_store::EnvVar>,
+    /// Recreate the Docker container of this name so the values apply now.
+    #[serde(default)]
+    pub apply: bool,
+}
+
+/// PUT /api/env-store/{scope} — replace a scope's variables. A secret sent
+/// with an empty value keeps its stored one.
+pub async fn env_store_set(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<EnvStoreRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let scope = path.into_inner();
+    if let Err(resp) = env_scope_access(&req, &caller, &scope) { return resp; }
+    let body = body.into_inner();
+    if let Err(e) = containers::env_store::set_scope(&scope, body.vars) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    if !body.apply {
+        return HttpResponse::Ok().json(serde_json::json!({ "message": format!("Environment for '{}' saved", scope) }));
+    }
+    let res = tokio::task::spawn_blocking(move || {
+        let inspect = containers::docker_inspect(&scope)?;
+        containers::docker_recreate_from_inspect(&scope, &inspect)
+    }).await;
+    match res {
+        Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({ "message": format!("Saved and recreated: {}", msg) })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Saved, but the recreate failed: {}", e) })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("recreate task panicked: {}", e)
+        })),
+    }
+}
+
+/// DELETE /api/env-store/{scope}
+pub async fn env_store_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let scope = path.into_inner();
+    if let Err(resp) = env_scope_access(&req, &caller, &scope) { return resp; }
+    match containers::env_store::delete_scope(&scope) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Stored environment removed" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
 /// GET /api/containers/docker/{id}/inspect — inspect raw docker config
 pub async fn docker_inspect(
     req: HttpRequest,
@@ -12534,7 +15698,7 @@ pub async fn docker_inspect(
     path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let id = path.into_inner();
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     
     match containers::docker_inspect(&id) {
         Ok(json) => HttpResponse::Ok().json(json),
@@ -12542,6 +15706,176 @@ pub async fn docker_inspect(
     }
 }
 
+// ─── Docker image builds ───────────────────────────────────────
+
+/// POST /api/containers/docker/build — build and tag an image. Either a JSON body naming a Git repository (`{"tag","git_url","git_ref","git_dir","dockerfile",..}`), or a tar / tar.gz build context as the body with `?tag=&dockerfile=`. Returns the build job; poll its log with GET /api/containers/docker/builds/{id}
+pub async fn docker_build(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    mut body: web::Payload,
+    query: web::Query<containers::docker_build::BuildRequest>,
+) -> HttpResponse {
+    use futures::StreamExt;
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let is_json = req.headers().get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+
+    let (build, upload) = if is_json {
+        let mut raw = web::BytesMut::new();
+        while let Some(chunk) = body.next().await {
+            let Ok(chunk) = chunk else {
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Upload interrupted" }));
+            };
+            if raw.len() + chunk.len() > 64 * 1024 {
+                return HttpResponse::PayloadTooLarge().json(serde_json::json!({ "error": "Build request too large" }));
+            }
+            raw.extend_from_slice(&chunk);
+        }
+        match serde_json::from_slice::<containers::docker_build::BuildRequest>(&raw) {
+            Ok(b) => (b, None),
+            Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Invalid build request: {}", e) })),
+        }
+    } else {
+        let build = query.into_inner();
+        // Validate before taking a possibly large upload.
+        if let Err(e) = build.validate(true) {
+            return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+        }
+        let path = format!("/tmp/wolfstack-build-{}.tar", uuid::Uuid::new_v4());
+        match streaming::stream_to_file(&mut body, std::path::Path::new(&path)).await {
+            Ok(0) => {
+                let _ = std::fs::remove_file(&path);
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Empty build context" }));
+            }
+            Ok(_) => (build, Some(path)),
+            Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("Failed to save build context: {}", e) })),
+        }
+    };
+
+    match containers::docker_build::start(&build, upload.as_deref()) {
+        Ok(job) => {
+            let id = job.id.clone();
+            tokio::task::spawn_blocking(move || containers::docker_build::run(&id, &build, upload.as_deref()));
+            HttpResponse::Ok().json(job)
+        }
+        Err(e) => {
+            if let Some(path) = upload { let _ = std::fs::remove_file(path); }
+            HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))
+        }
+    }
+}
+
+/// GET /api/containers/docker/builds — current and recent image builds
+pub async fn docker_builds_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(containers::docker_build::jobs())
+}
+
+#[derive(Deserialize)]
+pub struct BuildLogQuery {
+    /// Log position already seen (the previous response's `log_total`).
+    #[serde(default)]
+    pub since: usize,
+}
+
+/// GET /api/containers/docker/builds/{id} — a build's status and the log lines after `?since=N`
+pub async fn docker_build_status(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<BuildLogQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::docker_build::job(&path.into_inner()) {
+        Some(job) => HttpResponse::Ok().json(serde_json::json!({
+            "job": job,
+            "log": job.log_since(query.since),
+            "log_total": job.log_total(),
+        })),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Build not found" })),
+    }
+}
+
+// ─── Docker networks ───────────────────────────────────────────
+
+/// GET /api/containers/docker/networks — Docker networks with their attached containers, plus the host interfaces a macvlan network can use
+pub async fn docker_networks_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(|| (containers::docker_networks::list(), containers::docker_networks::parent_interfaces())).await {
+        Ok((Ok(networks), parents)) => HttpResponse::Ok().json(serde_json::json!({
+            "networks": networks,
+            "parent_interfaces": parents,
+        })),
+        Ok((Err(e), _)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/containers/docker/networks — create a bridge or macvlan network
+pub async fn docker_network_create(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<containers::docker_networks::CreateNetwork>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let spec = body.into_inner();
+    if let Err(e) = spec.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    match containers::docker_networks::create(&spec) {
+        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/containers/docker/networks/{name} — remove a network (Docker refuses while containers are attached)
+pub async fn docker_network_remove(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::docker_networks::remove(&path.into_inner()) {
+        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DockerNetworkAttachRequest {
+    pub network: String,
+    /// Fixed address on the network; Docker assigns one when absent.
+    #[serde(default)]
+    pub ip: Option<String>,
+}
+
+/// POST /api/containers/docker/{id}/networks/connect — attach a container to a network
+pub async fn docker_network_connect(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<DockerNetworkAttachRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
+    match containers::docker_networks::connect(&body.network, &id, body.ip.as_deref()) {
+        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/containers/docker/{id}/networks/disconnect — detach a container from a network
+pub async fn docker_network_disconnect(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<DockerNetworkAttachRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
+    match containers::docker_networks::disconnect(&body.network, &id) {
+        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ─── LXC disk: inspect / resize / migrate ───────────────────────
 
 #[derive(serde::Deserialize)]
@@ -12576,7 +15910,7 @@ pub async fn lxc_disk_info(
     path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::lxc_storage::inspect(&name) {
         Ok(info) => HttpResponse::Ok().json(info),
         Err(e) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
@@ -12592,7 +15926,7 @@ pub async fn lxc_disk_resize(
     body: web::Json<LxcResizeRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let r = body.into_inner();
     let info = match containers::lxc_storage::inspect(&name) {
         Ok(i) => i,
@@ -12633,7 +15967,7 @@ pub async fn lxc_disk_migrate(
     body: web::Json<LxcMigrateStorageRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let r = body.into_inner();
     let info = match containers::lxc_storage::inspect(&name) {
         Ok(i) => i,
@@ -12655,6 +15989,8 @@ pub async fn lxc_disk_migrate(
             Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
         }
     } else {
+        let _slot = crate::jobs::acquire_async(crate::jobs::Category::Migration, crate::jobs::Priority::Normal,
+            &format!("Move LXC '{}' storage", name), |_| {}).await;
         match containers::lxc_storage::migrate(&name, &r.target, r.remove_source) {
             Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
             Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
@@ -12690,7 +16026,7 @@ pub async fn lxc_mounts(
     path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let mounts = containers::lxc_list_mounts(&name);
     HttpResponse::Ok().json(mounts)
 }
@@ -12721,7 +16057,7 @@ pub async fn lxc_add_mount(
     body: web::Json<AddMountRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     let opts = containers::LxcMountOptions {
         host_path: body.host_path.clone(),
         container_path: body.container_path.clone(),
@@ -12750,7 +16086,7 @@ pub async fn lxc_remove_mount(
     body: web::Json<RemoveMountRequest>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::lxc_remove_mount(&name, &body.host_path) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
@@ -12769,7 +16105,7 @@ pub async fn lxc_set_autostart(
     body: web::Json<LxcSetAutostartReq>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     
     match containers::lxc_set_autostart(&name, body.enabled) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
@@ -12789,7 +16125,7 @@ pub async fn lxc_set_network_link(
     body: web::Json<LxcSetNetworkLinkReq>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     
     match containers::lxc_set_network_link(&name, &body.link) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
@@ -12802,9 +16138,15 @@ pub async fn lxc_parsed_config(
     req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::lxc_parse_config(&name) {
-        Some(cfg) => HttpResponse::Ok().json(cfg),
+        Some(mut cfg) => {
+            if let Some((memory, cpus)) = containers::lxc_live_limits(&name) {
+                cfg.live_memory_limit = Some(memory);
+                cfg.live_cpus = Some(cpus);
+            }
+            HttpResponse::Ok().json(cfg)
+        }
         None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Config not found" })),
     }
 }
@@ -12816,7 +16158,7 @@ pub async fn lxc_update_settings(
     body: web::Json<containers::LxcSettingsUpdate>,
 ) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let name = path.into_inner();
+    let name = match container_name_param(path) { Ok(n) => n.into_string(), Err(resp) => return resp };
     match containers::lxc_update_settings(&name, &body.into_inner()) {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
@@ -12827,7 +16169,7 @@ pub async fn lxc_update_settings(
 pub async fn wolfnet_next_ip(
     req: HttpRequest, state: web::Data<AppState>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
 
     // Query ALL remote cluster nodes for their used WolfNet IPs
     // to prevent cross-node IP collisions
@@ -12882,8 +16224,20 @@ pub async fn wolfnet_next_ip(
         }
     }
 
+    // Leased to any node or reserved in the IPAM ledger. Not recorded in
+    // the history below — a pending lease may never be used.
+    let unavailable = match containers::ipam::unavailable() {
+        Ok(ips) => ips,
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "ip": null, "error": e })),
+    };
+
     // Find next available IP that isn't in the combined used set
-    let prefix = containers::wolfnet_subnet_prefix().unwrap_or_default();
+    // The caller's tenant or this cluster may have an overlay subnet of its own.
+    let tenant = crate::auth::tenancy::scope(&req, &caller);
+    let prefix = match containers::ipam::subnet_for(tenant.as_deref()) {
+        Ok(prefix) => prefix.unwrap_or_default(),
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "ip": null, "error": e })),
+    };
     if prefix.is_empty() {
         return HttpResponse::Ok().json(serde_json::json!({ "ip": null, "error": "WolfNet is not configured" }));
     }
@@ -12901,7 +16255,7 @@ pub async fn wolfnet_next_ip(
     let mut recycled: Option<String> = None;
     for i in 2..=254u8 {
         let ip = format!("{}.{}", prefix, i);
-        if all_used.contains(&ip) { continue; }
+        if all_used.contains(&ip) || unavailable.contains(&ip) { continue; }
         if !history.contains(&ip) { fresh = Some(ip); break; }
         if recycled.is_none() { recycled = Some(ip); }
     }
@@ -12944,6 +16298,119 @@ pub async fn wolfnet_ip_status(
     }))
 }
 
+/// GET /api/wolfnet/ipam — the cluster's WolfNet IPAM ledger: pools, reservations and leases
+pub async fn wolfnet_ipam_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(containers::ipam::overview).await {
+        Ok(Ok(view)) => HttpResponse::Ok().json(view),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct IpamReservationRequest {
+    pub ip: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// POST /api/wolfnet/ipam/reservations — keep a WolfNet IP out of allocation cluster-wide
+pub async fn wolfnet_ipam_reserve(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<IpamReservationRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::ipam::reserve(&body.ip, &body.note) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "reserved": body.ip })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/wolfnet/ipam/reservations/{ip} — release a reserved WolfNet IP
+pub async fn wolfnet_ipam_unreserve(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::ipam::unreserve(&path) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "released": path.into_inner() })),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// PUT /api/wolfnet/ipam/pools/{prefix} — set the allocatable range of a WolfNet subnet
+pub async fn wolfnet_ipam_set_pool(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
+    body: web::Json<containers::ipam::Pool>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::ipam::set_pool(&path, Some(body.into_inner())) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "saved": true })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/wolfnet/ipam/pools/{prefix} — reset a WolfNet subnet to the default range
+pub async fn wolfnet_ipam_reset_pool(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match containers::ipam::set_pool(&path, None) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "saved": true })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// PUT /api/wolfnet/ipam/subnets/{prefix} — add or update an overlay subnet and its cluster/tenant assignment
+pub async fn wolfnet_ipam_set_subnet(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
+    body: web::Json<containers::ipam::OverlaySubnet>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_admin(&caller) { return resp; }
+    match containers::ipam::set_subnet(&path, Some(body.into_inner())) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "saved": true })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/wolfnet/ipam/subnets/{prefix} — remove an overlay subnet with no leased addresses
+pub async fn wolfnet_ipam_remove_subnet(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_admin(&caller) { return resp; }
+    match containers::ipam::set_subnet(&path, None) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "removed": true })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/wolfnet/ipam/grant — allocate WolfNet IPs for a peer (this node is the allocator)
+/// Requires cluster auth.
+pub async fn wolfnet_ipam_grant(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<containers::ipam::GrantRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_cluster_auth(&req, &state) { return resp; }
+    match web::block(move || containers::ipam::grant_for_peer(body.into_inner())).await {
+        Ok(Ok(ips)) => HttpResponse::Ok().json(containers::ipam::GrantResponse { ips }),
+        Ok(Err(e)) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/wolfnet/ipam/sync — accept a peer's leases and IPAM settings
+/// Requires cluster auth.
+pub async fn wolfnet_ipam_sync(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<containers::ipam::LeaseSync>,
+) -> HttpResponse {
+    if let Err(resp) = require_cluster_auth(&req, &state) { return resp; }
+    match web::block(move || containers::ipam::merge(body.into_inner())).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "ok": true })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// GET /api/network/conflicts — detect duplicate MACs/IPs across LXC containers
 pub async fn network_conflicts(
     req: HttpRequest, state: web::Data<AppState>,
@@ -12958,7 +16425,7 @@ pub async fn network_conflicts(
 pub async fn docker_import(
     req: HttpRequest,
     state: web::Data<AppState>,
-    body: web::Bytes,
+    mut body: web::Payload,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
     let has_secret = require_cluster_auth(&req, &state).is_ok();
@@ -12983,15 +16450,19 @@ pub async fn docker_import(
         }));
     }
 
-    // Save to temp file
+    // Stream the image tarball to a temp file as it arrives — it is
+    // routinely several GB, far too big to hold as one body in memory.
     let tar_path = format!("/tmp/wolfstack-import-{}.tar", container_name);
-    if let Err(e) = std::fs::write(&tar_path, &body) {
+    if let Err(e) = streaming::stream_to_file(&mut body, std::path::Path::new(&tar_path)).await {
         return HttpResponse::InternalServerError().json(serde_json::json!({
             "error": format!("Failed to save import file: {}", e)
         }));
     }
 
-    match containers::docker_import_image(&tar_path, &container_name) {
+    let name = container_name.clone();
+    let imported = web::block(move || containers::docker_import_image(&tar_path, &name))
+        .await.unwrap_or_else(|e| Err(e.to_string()));
+    match imported {
         Ok(msg) => {
             // Imported container is left stopped — user starts it manually when ready
             HttpResponse::Ok().json(serde_json::json!({ "message": msg }))
@@ -13037,11 +16508,13 @@ async fn install_component_in_container(
     state: web::Data<AppState>,
     body: web::Json<InstallComponentInContainerRequest>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
 
     let runtime = body.runtime.clone();
     let container = body.container.clone();
     let component = body.component.clone();
+    let kind = if runtime == "docker" { crate::auth::tenancy::Kind::Docker } else { crate::auth::tenancy::Kind::Lxc };
+    if let Err(resp) = container_access(&req, &caller, kind, &container) { return resp; }
 
     // Run in blocking thread since it may take a while
     let result = web::block(move || {
@@ -13061,7 +16534,7 @@ async fn container_component_version(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
     let runtime = query.get("runtime").cloned().unwrap_or_default();
     let target = query.get("target").cloned().unwrap_or_default();
     let component = query.get("component").cloned().unwrap_or_default();
@@ -13075,6 +16548,8 @@ async fn container_component_version(
     if !crate::auth::is_safe_name(&component) || !crate::auth::is_safe_name(&target) {
         return HttpResponse::BadRequest().json(serde_json::json!({"error": "invalid component or target name"}));
     }
+    let kind = if runtime == "docker" { crate::auth::tenancy::Kind::Docker } else { crate::auth::tenancy::Kind::Lxc };
+    if let Err(resp) = container_access(&req, &caller, kind, &target) { return resp; }
     let exec_target = match runtime.as_str() {
         "docker" => crate::configurator::ExecTarget::Docker(target),
         "lxc" => crate::configurator::ExecTarget::Lxc(target),
@@ -13175,6 +16650,12 @@ pub async fn ai_save_config(
                 config.local_api_key = v.to_string();
             }
         }
+        if let Some(v) = body.get("local_stream").and_then(|v| v.as_bool()) {
+            config.local_stream = v;
+        }
+        if let Some(v) = body.get("local_no_egress").and_then(|v| v.as_bool()) {
+            config.local_no_egress = v;
+        }
         if let Some(v) = body.get("model").and_then(|v| v.as_str()) {
             config.model = v.to_string();
         }
@@ -13225,6 +16706,9 @@ pub async fn ai_save_config(
         if let Some(v) = body.get("agent_tool_call_limit_enabled").and_then(|v| v.as_bool()) {
             config.agent_tool_call_limit_enabled = v;
         }
+        if let Some(v) = body.get("remediation_actions_enabled").and_then(|v| v.as_bool()) {
+            config.remediation_actions_enabled = v;
+        }
 
         // Validate provider and model compatibility
         if let Err(e) = config.validate() {
@@ -13313,7 +16797,9 @@ pub async fn ai_test_email(
         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
     );
 
-    match crate::ai::send_alert_email(&config, &subject, &body) {
+    // Straight to SMTP, not via the outbox — a queued test email would
+    // "succeed" later while the operator is looking at an error now.
+    match crate::ai::send_text_email(&config, &config.email_to, "WolfStack AI", &subject, &body) {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
             "status": "sent",
             "message": format!("Test email sent to {}", config.email_to)
@@ -13568,14 +17054,17 @@ pub async fn ai_chat(
             .map(|h| h.to_string_lossy().to_string())
             .unwrap_or_else(|_| "unknown".to_string());
 
-        // Get local container/VM lists once (reuse for counts and names)
-        let local_docker_list = crate::containers::docker_list_all();
-        let local_lxc_list = crate::containers::lxc_list_all();
-        let local_vm_list = state.vms.lock().unwrap().list_vms();
+        // Local container/VM lists once (reuse for counts and names) —
+        // the inventory snapshots, so a chat turn never waits on `docker ps`.
+        let (local_docker_list, local_lxc_list, components) = web::block(|| {
+            (crate::containers::docker_list_all_cached(),
+             crate::containers::lxc_list_all_cached(),
+             crate::installer::get_all_status_cached())
+        }).await.unwrap_or_default();
+        let local_vm_list = crate::monitoring::inventory::vms(&state).await;
         let docker_count = local_docker_list.len();
         let lxc_count = local_lxc_list.len();
         let vm_count = local_vm_list.len();
-        let components = crate::installer::get_all_status();
 
         let local_docker: Vec<String> = local_docker_list.iter().map(|c| format!("{} [{}]", c.name, c.state)).collect();
         let local_lxc: Vec<String> = local_lxc_list.iter().map(|c| format!("{} [{}]", c.name, c.state)).collect();
@@ -13748,12 +17237,14 @@ pub async fn ai_chat(
     prune_ai_chat_jobs();
 
     let job_id = format!("aichat-{}", uuid::Uuid::new_v4().simple());
+    let partial = crate::ai::ChatPartial::default();
     {
         let mut jobs = AI_CHAT_JOBS.lock().unwrap();
         jobs.insert(job_id.clone(), AiChatJob {
             created_at: chrono::Utc::now().timestamp(),
             owner: username.clone(),
             status: AiChatJobStatus::Running,
+            partial: partial.clone(),
             response: None,
             actions: None,
             error: None,
@@ -13765,7 +17256,7 @@ pub async fn ai_chat(
     let message = body.message.clone();
     let job_id_task = job_id.clone();
     tokio::spawn(async move {
-        let result = agent.chat(&message, &server_context, &cluster_nodes, &cluster_secret).await;
+        let result = agent.chat(&message, &server_context, &cluster_nodes, &cluster_secret, Some(&partial)).await;
         let mut jobs = AI_CHAT_JOBS.lock().unwrap();
         // The job may have been pruned (>10 min) or evicted; only update if it's
         // still present. Never holds the lock across an await — chat() already
@@ -13781,6 +17272,7 @@ pub async fn ai_chat(
                             "risk": a.risk,
                             "explanation": a.explanation,
                             "target": a.node_target,
+                            "remediation": a.remediation,
                         })
                     }).collect();
                     job.response = Some(response);
@@ -13817,6 +17309,9 @@ struct AiChatJob {
     /// unguessable; this is defence in depth.
     owner: String,
     status: AiChatJobStatus,
+    /// Reply text streamed so far (local provider with streaming on);
+    /// empty for providers that answer in one piece.
+    partial: crate::ai::ChatPartial,
     response: Option<String>,
     actions: Option<Vec<serde_json::Value>>,
     error: Option<String>,
@@ -13861,7 +17356,10 @@ pub async fn ai_chat_result(
     // leaks neither the response nor the job's existence.
     match jobs.get(&job_id).filter(|job| job.owner == username) {
         Some(job) => match job.status {
-            AiChatJobStatus::Running => HttpResponse::Ok().json(serde_json::json!({ "status": "running" })),
+            AiChatJobStatus::Running => HttpResponse::Ok().json(serde_json::json!({
+                "status": "running",
+                "partial": job.partial.lock().unwrap().clone(),
+            })),
             AiChatJobStatus::Done => HttpResponse::Ok().json(serde_json::json!({
                 "status": "done",
                 "response": job.response.clone().unwrap_or_default(),
@@ -13877,9 +17375,11 @@ pub async fn ai_chat_result(
 }
 
 /// POST /api/ai/action — approve or reject a proposed action.
-/// Approve only marks the action as "approved" — it does NOT execute it.
-/// The terminal (console.html) fetches the command via GET /api/ai/action/command
-/// and sends it to the PTY shell, so the user sees it run live.
+/// For shell actions, approve only marks the action as "approved" — it does
+/// NOT execute it. The terminal (console.html) fetches the command via
+/// GET /api/ai/action/command and sends it to the PTY shell, so the user sees
+/// it run live. Structured remediations have no command to hand over, so
+/// approving one (admins only) executes it here and returns the outcome.
 #[derive(Deserialize)]
 pub struct AiActionRequest {
     pub action_id: String,
@@ -13905,8 +17405,13 @@ pub async fn ai_action(
         }
     }
 
+    // A structured remediation runs as root on approval, so only an admin
+    // (or a cluster peer acting for one) may approve it — never a
+    // tenant-scoped caller.
+    let may_remediate = crate::auth::caller_is_admin(&req, &username);
+
     // Mark as approved (terminal will fetch and execute the command)
-    {
+    let approved = {
         let mut pa = state.ai_agent.pending_actions.lock().unwrap();
         let now = chrono::Utc::now().timestamp();
         if let Some(a) = pa.iter_mut().find(|a| a.id == body.action_id) {
@@ -13919,17 +17424,50 @@ pub async fn ai_action(
                 a.status = "expired".to_string();
                 return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Action expired" }));
             }
+            if a.remediation.is_some() && !may_remediate {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Only admin users can approve remediations"
+                }));
+            }
+            if a.remediation.is_some() && !state.ai_agent.config.lock().unwrap().remediation_actions_enabled {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Structured remediations have been disabled in AI Settings"
+                }));
+            }
             a.status = "approved".to_string();
             a.approved_by = username.clone();
             crate::ai::log_action_audit(a, "approved", &username, "");
-            return HttpResponse::Ok().json(serde_json::json!({
-                "status": "approved",
-                "command": a.command,
-            }));
+            match &a.remediation {
+                Some(r) => (a.clone(), r.clone()),
+                None => return HttpResponse::Ok().json(serde_json::json!({
+                    "status": "approved",
+                    "command": a.command,
+                })),
+            }
+        } else {
+            return HttpResponse::NotFound().json(serde_json::json!({ "error": "Action not found" }));
         }
+    };
+
+    let (mut action, remediation) = approved;
+    let result = web::block(move || remediation.execute()).await
+        .unwrap_or_else(|e| Err(format!("Remediation task failed: {}", e)));
+    let (status, output) = match &result {
+        Ok(out) => ("executed", out.clone()),
+        Err(e) => ("failed", e.clone()),
+    };
+    if let Some(a) = state.ai_agent.pending_actions.lock().unwrap().iter_mut().find(|a| a.id == action.id) {
+        a.status = status.to_string();
+        a.result = output.clone();
     }
+    action.status = status.to_string();
+    action.result = output.clone();
+    crate::ai::log_action_audit(&action, status, &username, &output);
 
-    HttpResponse::NotFound().json(serde_json::json!({ "error": "Action not found" }))
+    match result {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "status": status, "output": output })),
+        Err(_) => HttpResponse::Ok().json(serde_json::json!({ "status": status, "error": output })),
+    }
 }
 
 /// GET /api/ai/action/command?id=xxx — retrieve the command for an approved action (one-time, for terminal auto-exec)
@@ -13946,7 +17484,8 @@ pub async fn ai_action_command(
 
     let pa = state.ai_agent.pending_actions.lock().unwrap();
     // Only return command for approved actions (approved by the POST /api/ai/action call)
-    match pa.iter().find(|a| a.id == action_id && a.status == "approved") {
+    // Remediations execute server-side and must never be fed to a shell.
+    match pa.iter().find(|a| a.id == action_id && a.status == "approved" && a.remediation.is_none()) {
         Some(a) => HttpResponse::Ok().json(serde_json::json!({
             "command": a.command,
             "title": a.title,
@@ -14483,6 +18022,30 @@ pub async fn net_get_wolfnet(req: HttpRequest, state: web::Data<AppState>) -> Ht
     HttpResponse::Ok().json(networking::get_wolfnet_status())
 }
 
+#[derive(Deserialize)]
+pub struct WolfNetHealthQuery {
+    /// History window in minutes (default 60, max 1440)
+    #[serde(default)]
+    pub minutes: Option<u64>,
+    /// Events returned, newest first (default 100)
+    #[serde(default)]
+    pub events: Option<usize>,
+}
+
+/// GET /api/networking/wolfnet/health — per-peer tunnel telemetry
+/// (handshake age, traffic, endpoint changes) with history and events
+pub async fn net_wolfnet_health(
+    req: HttpRequest, state: web::Data<AppState>, query: web::Query<WolfNetHealthQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let minutes = query.minutes.unwrap_or(60).clamp(1, 24 * 60);
+    let events = query.events.unwrap_or(100).min(500);
+    match web::block(move || networking::wolfnet_health::report(minutes, events)).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// GET /api/networking/wolfnet/config — get raw WolfNet config
 pub async fn net_get_wolfnet_config(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
@@ -14934,6 +18497,94 @@ pub async fn net_listening_ports(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ConnectionsQuery {
+    /// Only this container's connections
+    #[serde(default)]
+    pub container: Option<String>,
+    /// Only connections to or from public addresses
+    #[serde(default)]
+    pub external: bool,
+    /// Only connections in this TCP state (ESTABLISHED, TIME_WAIT, …)
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Rows returned (default 200, max 2000); the summary covers all
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// GET /api/networking/connections — live connection table: top talkers,
+/// per-container connection counts and states, and the busiest flows
+pub async fn net_connections(
+    req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConnectionsQuery>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    // The table shows every tenant's traffic on the host.
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "The connection table needs an admin" }));
+    }
+    let q = query.into_inner();
+    let filter = networking::connections::ConnectionFilter {
+        container: q.container.filter(|c| !c.trim().is_empty()),
+        external_only: q.external,
+        state: q.state.filter(|s| !s.trim().is_empty()),
+        limit: q.limit.unwrap_or(200).min(2000),
+    };
+    match web::block(move || networking::connections::report(&filter)).await {
+        Ok(Ok(report)) => HttpResponse::Ok().json(report),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/networking/capture — bounded tcpdump on an interface or inside
+/// a container, returned as a .pcap download (admin only, audited)
+pub async fn net_capture(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<networking::capture::CaptureRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if !crate::auth::caller_is_admin(&req, &caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Packet capture needs an admin" }));
+    }
+    let capture = body.into_inner();
+    let ip = crate::netaddr::canonical_ip_str(req.connection_info().peer_addr().unwrap_or("")).into_owned();
+    let summary = format!("{} filter='{}' {}s/{} packets",
+        capture.target(), capture.filter, capture.duration(), capture.packets());
+    let audit = move |status: u16| crate::compat::audit_log(&crate::compat::AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        key_name: caller.clone(),
+        key_id: "pcap".to_string(),
+        method: "CAPTURE".to_string(),
+        path: summary.clone(),
+        ip: ip.clone(),
+        status,
+    });
+    let target = capture.target();
+    let result = match web::block(move || networking::capture::run(&capture)).await {
+        Ok(r) => r,
+        Err(e) => Err(e.to_string()),
+    };
+    match result {
+        Ok(cap) => {
+            audit(200);
+            let safe: String = target.chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+                .collect();
+            let filename = format!("capture-{}-{}.pcap", safe, chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+            HttpResponse::Ok()
+                .content_type("application/vnd.tcpdump.pcap")
+                .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+                .insert_header(("X-Capture-Packets", cap.packets.map(|p| p.to_string()).unwrap_or_default()))
+                .insert_header(("X-Capture-Stopped-By", cap.stopped_by))
+                .body(cap.data)
+        }
+        Err(e) => {
+            audit(400);
+            HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))
+        }
+    }
+}
+
 // ─── WireGuard Bridge API ───
 
 /// GET /api/networking/wireguard — list all bridges
@@ -17632,6 +21283,8 @@ pub struct CreateScheduleRequest {
     pub pre_command: String,
     #[serde(default)]
     pub post_command: String,
+    #[serde(default)]
+    pub io_limits: crate::io_throttle::IoLimits,
 }
 
 #[derive(Deserialize)]
@@ -17643,8 +21296,10 @@ pub struct ToggleScheduleRequest {
 pub async fn backup_list(
     req: HttpRequest, state: web::Data<AppState>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
-    HttpResponse::Ok().json(backup::list_backups())
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), backup::list_backups(),
+        |b| crate::auth::tenancy::backup_target(&b.target)))
 }
 
 /// POST /api/backups — create a backup now
@@ -17652,7 +21307,11 @@ pub async fn backup_create(
     req: HttpRequest, state: web::Data<AppState>,
     body: web::Json<CreateBackupRequest>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    if let Err(e) = crate::auth::tenancy::check_backup_targets(scope.as_deref(), body.target.as_ref().map(std::slice::from_ref)) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
     let mut storage = body.storage.clone();
     // Validate the WolfDisk subpath at the API boundary so a
     // crafted POST can't silently pick a sanitized-but-different
@@ -17673,6 +21332,11 @@ pub async fn backup_create(
                 return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
             }
         }
+        if t.target_type == backup::BackupTargetType::Mysql {
+            if let Err(e) = backup::validate_mysql_target(t) {
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+            }
+        }
     }
     backup::merge_pbs_secrets(&mut storage);
     // W6 fix: backup::create_backup runs `qemu-img convert` for VM
@@ -17701,7 +21365,11 @@ pub async fn backup_stream(
     req: HttpRequest, state: web::Data<AppState>,
     body: web::Json<CreateBackupRequest>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    if let Err(e) = crate::auth::tenancy::check_backup_targets(scope.as_deref(), body.target.as_ref().map(std::slice::from_ref)) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
 
     let mut storage = body.storage.clone();
     // Mirror backup_create's WolfDisk subpath validation. SSE-stream
@@ -17719,6 +21387,11 @@ pub async fn backup_stream(
                 return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
             }
         }
+        if t.target_type == backup::BackupTargetType::Mysql {
+            if let Err(e) = backup::validate_mysql_target(t) {
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+            }
+        }
     }
     backup::merge_pbs_secrets(&mut storage);
 
@@ -18025,7 +21698,12 @@ pub struct GaleraAdoptReq {
 /// managed cluster. WolfStack resolves each container's address on its host, so
 /// the operator never types IPs.
 pub async fn galera_adopt(req: HttpRequest, state: web::Data<AppState>, body: web::Json<GaleraAdoptReq>) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    // The picks name containers on any node, and ownership is recorded
+    // per node — adopting them into a cluster needs an unscoped caller.
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Not available to tenant accounts" }));
+    }
     let b = body.into_inner();
     if b.cluster_name.trim().is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "cluster name required" }));
@@ -18221,7 +21899,12 @@ pub struct WolfScaleAdoptNode {
 
 /// POST /api/wolfscale/adopt — adopt picked containers into a managed cluster.
 pub async fn wolfscale_adopt(req: HttpRequest, state: web::Data<AppState>, body: web::Json<WolfScaleAdoptReq>) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    // The picks name containers on any node, and ownership is recorded
+    // per node — adopting them into a cluster needs an unscoped caller.
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Not available to tenant accounts" }));
+    }
     let b = body.into_inner();
     let name = b.cluster_name.clone();
     let ws_cluster = b.cluster.clone();
@@ -18905,7 +22588,12 @@ pub async fn backup_restore(
 ) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
     let overwrite = query.get("overwrite").map(|v| v == "true").unwrap_or(false);
-    match backup::restore_by_id(&path.into_inner(), overwrite) {
+    // Blocking pool: the restore itself is long, and it may first wait
+    // for a restore slot (crate::jobs).
+    let id = path.into_inner();
+    let result = web::block(move || backup::restore_by_id(&id, overwrite)).await
+        .unwrap_or_else(|e| Err(format!("task failed: {}", e)));
+    match result {
         Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
         Err(e) if e.starts_with("CONTAINER_EXISTS:") => {
             let name = e.strip_prefix("CONTAINER_EXISTS:").unwrap_or("");
@@ -18919,6 +22607,68 @@ pub async fn backup_restore(
     }
 }
 
+#[derive(Deserialize)]
+pub struct BackupFilesQuery {
+    #[serde(default)]
+    pub snapshot: String,
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct BackupFilesRestore {
+    #[serde(default)]
+    pub snapshot: String,
+    pub paths: Vec<String>,
+    pub dest: String,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// File restores write anywhere on the host, so they're admin-only.
+fn file_restore_caller(req: &HttpRequest, state: &web::Data<AppState>) -> Result<(), HttpResponse> {
+    let caller = require_auth(req, state)?;
+    require_admin_caller(req, &caller, "restore files to the host")
+}
+
+fn backup_files_response(result: Result<Result<serde_json::Value, String>, actix_web::error::BlockingError>) -> HttpResponse {
+    match result {
+        Ok(Ok(v)) => HttpResponse::Ok().json(v),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("task failed: {}", e) })),
+    }
+}
+
+/// GET /api/backups/{id}/files?path= — one directory of a backup archive
+pub async fn backup_files(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<BackupFilesQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    let dir = query.into_inner().path;
+    backup_files_response(web::block(move || {
+        backup::catalog::browse_backup(&id, &dir).map(|entries| serde_json::json!({ "path": dir, "entries": entries }))
+    }).await)
+}
+
+/// POST /api/backups/{id}/files/restore — restore picked files/folders
+/// into a host directory. Body: `{"paths": [...], "dest": "/root/restored", "overwrite": false}`
+pub async fn backup_files_restore(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<BackupFilesRestore>,
+) -> HttpResponse {
+    if let Err(e) = file_restore_caller(&req, &state) { return e; }
+    let id = path.into_inner();
+    let b = body.into_inner();
+    backup_files_response(web::block(move || {
+        backup::catalog::restore_backup_files(&id, &b.paths, &b.dest, b.overwrite)
+            .map(|msg| serde_json::json!({ "message": msg }))
+    }).await)
+}
+
 /// GET /api/backups/targets — list available backup targets
 pub async fn backup_targets(
     req: HttpRequest, state: web::Data<AppState>,
@@ -18954,8 +22704,10 @@ pub async fn backup_container_mounts(
 pub async fn backup_schedules_list(
     req: HttpRequest, state: web::Data<AppState>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
-    HttpResponse::Ok().json(backup::list_schedules())
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), backup::list_schedules(),
+        |s| Some((crate::auth::tenancy::Kind::Schedule, s.id.as_str()))))
 }
 
 /// POST /api/backups/test-storage — try to set up the destination (mount
@@ -18984,7 +22736,11 @@ pub async fn backup_schedule_create(
     req: HttpRequest, state: web::Data<AppState>,
     body: web::Json<CreateScheduleRequest>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    if let Err(e) = crate::auth::tenancy::check_backup_targets(scope.as_deref(), (!body.backup_all).then_some(&body.targets[..])) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
     // Cluster-wide scheduler UI sends only `{type:"pbs"}`; fill in saved
     // server/credentials so the schedule is runnable without a second round-trip.
     let mut storage = body.storage.clone();
@@ -18996,6 +22752,9 @@ pub async fn backup_schedule_create(
             return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
         }
     }
+    if let Err(e) = body.io_limits.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
     backup::merge_pbs_secrets(&mut storage);
     // Edit vs create: when the body carries an existing id, update that schedule in
     // place and PRESERVE its created_at + last_run (otherwise an edit would reset the
@@ -19004,6 +22763,12 @@ pub async fn backup_schedule_create(
         .filter(|id| !id.is_empty())
         .and_then(|id| backup::list_schedules().into_iter().find(|s| s.id == id));
     let is_edit = editing.is_some();
+    // The id is in the body, so require_auth's path check can't see it.
+    if let (Some(tenant), Some(s)) = (scope.as_deref(), &editing)
+        && crate::auth::tenancy::Ownership::load().owner(crate::auth::tenancy::Kind::Schedule, &s.id) != Some(tenant)
+    {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Schedule not found" }));
+    }
     let schedule = backup::BackupSchedule {
         id: editing.as_ref().map(|s| s.id.clone())
             .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
@@ -19021,12 +22786,18 @@ pub async fn backup_schedule_create(
             .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
         pre_command: body.pre_command.clone(),
         post_command: body.post_command.clone(),
+        io_limits: body.io_limits.clone(),
     };
     match backup::save_schedule(schedule) {
-        Ok(s) => HttpResponse::Ok().json(serde_json::json!({
-            "message": format!("Schedule '{}' {}", s.name, if is_edit { "updated" } else { "created" }),
-            "schedule": s,
-        })),
+        Ok(s) => {
+            if !is_edit {
+                crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Schedule, &s.id);
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": format!("Schedule '{}' {}", s.name, if is_edit { "updated" } else { "created" }),
+                "schedule": s,
+            }))
+        }
         Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
     }
 }
@@ -19140,7 +22911,7 @@ pub async fn pbs_snapshots(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct PbsRestoreRequest {
     pub snapshot: String,
     pub archive: String,
@@ -19159,6 +22930,21 @@ pub struct PbsRestoreRequest {
     /// UI now offers a picker. Older nodes ignore this field.
     #[serde(default)]
     pub storage: String,
+    /// What to restore into — see `backup::PbsRestoreAs`. Default `auto`.
+    #[serde(default)]
+    pub restore_as: backup::PbsRestoreAs,
+    /// CPU / memory for the VM a `vm_disk` restore creates.
+    #[serde(default)]
+    pub vm: backup::PbsVmSpec,
+    /// Cluster node to restore on; empty = this node. Progress is then
+    /// polled from that node's /api/backups/pbs/restore/progress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<String>,
+    /// PBS connection sent along by the node that forwarded the restore,
+    /// so the target needn't have PBS configured itself. Only honoured
+    /// from cluster-node callers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pbs: Option<backup::BackupStorage>,
 }
 fn default_pbs_target_dir() -> String { "/var/lib/wolfstack/restored".to_string() }
 
@@ -19167,7 +22953,25 @@ pub async fn pbs_restore(
     req: HttpRequest, state: web::Data<AppState>,
     body: web::Json<PbsRestoreRequest>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let mut body = body.into_inner();
+    match create_target(&state, body.target_node.take().as_deref()) {
+        Err(resp) => return resp,
+        Ok(Some(node)) => {
+            // The peer restores with this node's PBS connection and runs the
+            // progress tracking itself.
+            body.pbs = Some(backup::load_pbs_config());
+            return forward_create(&req, &state, &caller, &node, "/api/backups/pbs/restore", &serde_json::json!(body), 30).await;
+        }
+        Ok(None) => {}
+    }
+    let config = match body.pbs.take() {
+        Some(forwarded) if caller == "cluster-node" => forwarded,
+        _ => backup::load_pbs_config(),
+    };
+    if config.pbs_server.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "PBS is not configured" }));
+    }
 
     // Check if a restore is already running (auto-expire after 30 min)
     {
@@ -19187,13 +22991,14 @@ pub async fn pbs_restore(
         }
     }
 
-    let config = backup::load_pbs_config();
     let snapshot = body.snapshot.clone();
     let archive = body.archive.clone();
     let target_dir = body.target_dir.clone();
     let overwrite = body.overwrite;
     let new_name = body.new_name.clone();
     let target_storage = body.storage.clone();
+    let restore_as = body.restore_as;
+    let vm_spec = body.vm;
 
     // Reset progress state
     {
@@ -19208,6 +23013,7 @@ pub async fn pbs_restore(
             message: String::new(),
             started_at: Some(std::time::Instant::now()),
         };
+        publish_job("pbs_restore", &snapshot, &*progress);
     }
 
     // Spawn background thread
@@ -19215,10 +23021,14 @@ pub async fn pbs_restore(
     std::thread::spawn(move || {
         match backup::restore_from_pbs_with_progress(&config, &snapshot, &archive, &target_dir, |text, pct| {
             if let Ok(mut progress) = state_clone.pbs_restore_progress.lock() {
+                let shown = progress.percentage.map(|p| p as u32);
                 progress.progress_text = text;
                 progress.percentage = pct;
+                if pct.map(|p| p as u32) != shown {
+                    publish_job("pbs_restore", &progress.snapshot, &*progress);
+                }
             }
-        }, overwrite, &new_name, &target_storage) {
+        }, overwrite, &new_name, &target_storage, restore_as, vm_spec) {
             Ok(msg) => {
                 if let Ok(mut progress) = state_clone.pbs_restore_progress.lock() {
                     progress.active = false;
@@ -19227,6 +23037,7 @@ pub async fn pbs_restore(
                     progress.message = msg;
                     progress.percentage = Some(100.0);
                     progress.progress_text = "Restore complete!".to_string();
+                    publish_job("pbs_restore", &progress.snapshot, &*progress);
                 }
             }
             Err(ref e) if e == "TARGET_EXISTS" => {
@@ -19236,6 +23047,7 @@ pub async fn pbs_restore(
                     progress.success = Some(false);
                     progress.message = "TARGET_EXISTS".to_string();
                     progress.progress_text = "Target already has files from a previous restore".to_string();
+                    publish_job("pbs_restore", &progress.snapshot, &*progress);
                 }
             }
             Err(e) => {
@@ -19245,6 +23057,7 @@ pub async fn pbs_restore(
                     progress.success = Some(false);
                     progress.message = e;
                     progress.progress_text = "Restore failed".to_string();
+                    publish_job("pbs_restore", &progress.snapshot, &*progress);
                 }
             }
         }
@@ -19364,6 +23177,137 @@ pub async fn pbs_config_save(
     }
 }
 
+// ─── PBS datastore housekeeping (see backup::pbs_admin) ───
+
+/// Prune, GC and schedule changes act on the shared PBS datastore, so
+/// they're admin-only; viewing usage needs any login.
+fn pbs_admin_caller(req: &HttpRequest, state: &web::Data<AppState>) -> Result<(), HttpResponse> {
+    let caller = require_auth(req, state)?;
+    require_admin_caller(req, &caller, "manage PBS housekeeping")
+}
+
+/// GET /api/backups/pbs/datastore — usage, GC/prune schedules, prune jobs,
+/// backup groups and recent tasks for the configured datastore
+pub async fn pbs_datastore(
+    req: HttpRequest, state: web::Data<AppState>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::datastore_overview(&config).await {
+        Ok(o) => HttpResponse::Ok().json(o),
+        Err(e) => HttpResponse::BadGateway().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PbsGcScheduleRequest {
+    #[serde(default)]
+    pub schedule: String,
+}
+
+/// PUT /api/backups/pbs/datastore/gc-schedule — set or clear (empty) the GC schedule
+pub async fn pbs_set_gc_schedule(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<PbsGcScheduleRequest>,
+) -> HttpResponse {
+    if let Err(e) = pbs_admin_caller(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::set_gc_schedule(&config, &body.schedule).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "GC schedule saved" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/backups/pbs/prune-jobs — create or update a prune job
+pub async fn pbs_save_prune_job(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<backup::pbs_admin::PruneJob>,
+) -> HttpResponse {
+    if let Err(e) = pbs_admin_caller(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::save_prune_job(&config, &body).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Prune job saved" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/backups/pbs/prune-jobs/{id}
+pub async fn pbs_delete_prune_job(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(e) = pbs_admin_caller(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::delete_prune_job(&config, &path.into_inner()).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Prune job deleted" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/backups/pbs/jobs — start GC, verify, an ad-hoc prune or a
+/// prune job now. Body: `{"action": "gc" | "verify" | "prune" | "prune_job", ...}`.
+/// Returns the PBS task id to poll with /api/backups/pbs/tasks.
+pub async fn pbs_run_job(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<backup::pbs_admin::JobRequest>,
+) -> HttpResponse {
+    if let Err(e) = pbs_admin_caller(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::run_job(&config, &body).await {
+        Ok(upid) => {
+            tracing::info!("PBS job {:?} started on {}: {}", body.0, config.pbs_datastore, upid);
+            HttpResponse::Ok().json(serde_json::json!({ "upid": upid }))
+        }
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PbsTaskQuery {
+    pub upid: String,
+}
+
+/// GET /api/backups/pbs/tasks?upid= — status and log of a PBS task
+pub async fn pbs_task_status(
+    req: HttpRequest, state: web::Data<AppState>,
+    query: web::Query<PbsTaskQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let config = backup::load_pbs_config();
+    match backup::pbs_admin::task_status(&config, &query.upid).await {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::BadGateway().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// GET /api/backups/pbs/files?snapshot=&path= — one directory of a PBS
+/// file-level snapshot
+pub async fn pbs_files(
+    req: HttpRequest, state: web::Data<AppState>,
+    query: web::Query<BackupFilesQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let q = query.into_inner();
+    backup_files_response(web::block(move || {
+        backup::catalog::browse_pbs_snapshot(&q.snapshot, &q.path)
+            .map(|entries| serde_json::json!({ "path": q.path, "entries": entries }))
+    }).await)
+}
+
+/// POST /api/backups/pbs/files/restore — restore picked files/folders from
+/// a PBS snapshot. Body: `{"snapshot": "ct/web/2026-...Z", "paths": [...], "dest": "/root/restored"}`
+pub async fn pbs_files_restore(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<BackupFilesRestore>,
+) -> HttpResponse {
+    if let Err(e) = file_restore_caller(&req, &state) { return e; }
+    let b = body.into_inner();
+    backup_files_response(web::block(move || {
+        backup::catalog::restore_pbs_files(&b.snapshot, &b.paths, &b.dest, b.overwrite)
+            .map(|msg| serde_json::json!({ "message": msg }))
+    }).await)
+}
+
 // ─── Wolfram Memory Compression ───
 
 /// GET /api/wolfram/status — check if wolfram is installed and read stats
@@ -20208,8 +24152,13 @@ pub async fn storage_list_mounts(
     req: HttpRequest,
     state: web::Data<AppState>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
-    HttpResponse::Ok().json(storage::list_mounts())
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    match web::block(storage::list_mounts_with_usage).await {
+        Ok(mounts) => HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), mounts,
+            |m| Some((crate::auth::tenancy::Kind::Mount, m.id.as_str())))),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
 }
 
 /// GET /api/storage/available — list mounted storage suitable for container attachment
@@ -20242,6 +24191,10 @@ pub struct CreateMountRequest {
     pub smb_options: Option<String>,
     #[serde(default)]
     pub smb_config: Option<storage::SmbConfig>,
+    #[serde(default)]
+    pub quota_percent: Option<u8>,
+    #[serde(default)]
+    pub io_limits: crate::io_throttle::IoLimits,
     #[serde(default = "default_do_mount")]
     pub do_mount: bool,
 }
@@ -20254,7 +24207,7 @@ pub async fn storage_create_mount(
     state: web::Data<AppState>,
     body: web::Json<CreateMountRequest>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
     
     let mount = storage::StorageMount {
         id: String::new(),
@@ -20272,6 +24225,10 @@ pub async fn storage_create_mount(
         status: "unmounted".to_string(),
         error_message: None,
         created_at: String::new(),
+        quota_percent: body.quota_percent.filter(|q| (1..=100).contains(q)),
+        usage: None,
+        health: None,
+        io_limits: body.io_limits.clone(),
     };
     
     let do_mount = body.do_mount;
@@ -20280,6 +24237,7 @@ pub async fn storage_create_mount(
     let result = web::block(move || storage::create_mount(mount, do_mount)).await;
     match result {
         Ok(Ok(created)) => {
+            crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Mount, &created.id);
             // If global, sync to cluster nodes
             if created.global {
                 let _ = sync_mount_to_cluster(&state, &created).await;
@@ -20604,19 +24562,57 @@ pub struct SystemLogsQuery {
     pub lines: Option<usize>,
     pub search: Option<String>,
     pub unit: Option<String>,
+    #[serde(default)]
+    pub download: Option<String>,
 }
 
-/// GET /api/system/logs — read system journal logs
+/// GET /api/system/logs — read system journal logs. `download=1` streams
+/// the matching journal as a text attachment — every line unless `lines`
+/// is given — instead of returning at most 5000 as JSON.
 pub async fn system_logs(
     req: HttpRequest,
     state: web::Data<AppState>,
     query: web::Query<SystemLogsQuery>,
 ) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
+    let query = query.into_inner();
+    if wants_download(&query.download) {
+        let args = storage::journalctl_args(query.lines, query.search.as_deref(), query.unit.as_deref());
+        let child = tokio::process::Command::new("journalctl")
+            .args(&args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let disposition = actix_web::http::header::ContentDisposition {
+            disposition: actix_web::http::header::DispositionType::Attachment,
+            parameters: vec![actix_web::http::header::DispositionParam::Filename(
+                format!("system-{}.log", chrono::Utc::now().format("%Y%m%d-%H%M%S")))],
+        };
+        return match child.map_err(|e| e.to_string()).and_then(streaming::child_lines_stream) {
+            Ok(stream) => HttpResponse::Ok()
+                .content_type("text/plain; charset=utf-8")
+                .insert_header(disposition)
+                .streaming(stream),
+            // No journald (Unraid, Alpine): the syslog tail is bounded, so
+            // it can go out in one piece.
+            Err(_) => {
+                let lines = query.lines.unwrap_or(5000).min(5000);
+                let (search, unit) = (query.search, query.unit);
+                let logs = web::block(move || storage::read_system_logs(lines, search.as_deref(), unit.as_deref()))
+                    .await.unwrap_or_default();
+                HttpResponse::Ok()
+                    .content_type("text/plain; charset=utf-8")
+                    .insert_header(disposition)
+                    .body(logs.join("\n") + "\n")
+            }
+        };
+    }
     let lines = query.lines.unwrap_or(200).min(5000);
-    let search = query.search.as_deref();
-    let unit = query.unit.as_deref();
-    let logs = storage::read_system_logs(lines, search, unit);
+    let (search, unit) = (query.search, query.unit);
+    let logs = web::block(move || storage::read_system_logs(lines, search.as_deref(), unit.as_deref()))
+        .await.unwrap_or_default();
     HttpResponse::Ok().json(serde_json::json!({
         "lines": logs,
         "count": logs.len(),
@@ -20668,6 +24664,95 @@ pub async fn storage_sync_s3(
     }
 }
 
+// ─── S3 Object Browser ───
+
+#[derive(Deserialize)]
+pub struct S3ObjectQuery {
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub key: String,
+}
+
+/// GET /api/storage/mounts/{id}/objects?prefix= — one level of an S3 mount's bucket
+pub async fn storage_s3_list(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, query: web::Query<S3ObjectQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match storage::s3_list_objects(&path.into_inner(), &query.prefix).await {
+        Ok(listing) => HttpResponse::Ok().json(listing),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// GET /api/storage/mounts/{id}/objects/download?key= — stream an object to the browser
+pub async fn storage_s3_download(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, query: web::Query<S3ObjectQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match storage::s3_get_object(&path.into_inner(), &query.key).await {
+        Ok(bytes) => {
+            let filename = query.key.rsplit('/').next().unwrap_or("object").replace('"', "");
+            HttpResponse::Ok()
+                .content_type("application/octet-stream")
+                .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+                .body(bytes)
+        }
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// PUT /api/storage/mounts/{id}/objects?key= — upload the raw request body as an object
+pub async fn storage_s3_upload(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, query: web::Query<S3ObjectQuery>, body: web::Bytes,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match storage::s3_put_object(&path.into_inner(), &query.key, &body).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": format!("Uploaded {}", query.key) })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/storage/mounts/{id}/objects?key= — delete an object
+pub async fn storage_s3_delete(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, query: web::Query<S3ObjectQuery>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match storage::s3_delete_object(&path.into_inner(), &query.key).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": format!("Deleted {}", query.key) })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct S3PresignRequest {
+    pub key: String,
+    /// `get` (download link) or `put` (upload link)
+    #[serde(default = "default_presign_method")]
+    pub method: String,
+    #[serde(default = "default_presign_expiry")]
+    pub expires_secs: u32,
+}
+
+fn default_presign_method() -> String { "get".to_string() }
+fn default_presign_expiry() -> u32 { 3600 }
+
+/// POST /api/storage/mounts/{id}/objects/presign — presigned URL for one object
+pub async fn storage_s3_presign(
+    req: HttpRequest, state: web::Data<AppState>,
+    path: web::Path<String>, body: web::Json<S3PresignRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match storage::s3_presign(&path.into_inner(), &body.key, &body.method, body.expires_secs).await {
+        Ok(url) => HttpResponse::Ok().json(serde_json::json!({ "url": url })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ─── Disk Partition Info ───
 
 /// GET /api/storage/disk-info — list all block devices, partitions, filesystems & free space
@@ -20686,6 +24771,97 @@ pub async fn storage_wolfdisk_status(req: HttpRequest, state: web::Data<AppState
     }
 }
 
+/// GET /api/storage/wolfdisk/volumes — this node's WolfDisk health and the
+/// volumes on its WolfDisk mount (the same on every node, being replicated).
+pub async fn wolfdisk_volumes(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match web::block(|| (storage::wolfdisk::health(), storage::wolfdisk::list_volumes())).await {
+        Ok((health, volumes)) => HttpResponse::Ok().json(serde_json::json!({ "health": health, "volumes": volumes })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WolfDiskVolumeRequest {
+    pub name: String,
+    pub size_gb: u32,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// POST /api/storage/wolfdisk/volumes — create a volume on the WolfDisk mount.
+pub async fn wolfdisk_volume_create(req: HttpRequest, state: web::Data<AppState>, body: web::Json<WolfDiskVolumeRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    match storage::wolfdisk::create_volume(body.name.trim(), body.size_gb, &body.description, &caller) {
+        Ok(vol) => HttpResponse::Ok().json(vol),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/storage/wolfdisk/volumes/{name} — delete a detached volume and
+/// its data, cluster-wide. Admin only.
+pub async fn wolfdisk_volume_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(resp) = require_admin_caller(&req, &caller, "delete WolfDisk volumes") { return resp; }
+    let name = path.into_inner();
+    match web::block(move || storage::wolfdisk::delete_volume(&name)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "ok": true })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/storage/wolfdisk/volumes/{name}/attach — attach a volume to a
+/// container or stopped VM on this node. Sent to the node the guest is on.
+pub async fn wolfdisk_volume_attach(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<storage::wolfdisk::AttachOptions>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    // The guest is named in the body, not the path, so the tenancy check
+    // in require_auth doesn't see it — a tenant may only mount into their
+    // own containers and VMs.
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, &body.kind, &body.target) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    let name = path.into_inner();
+    let opts = body.into_inner();
+    let st = state.clone();
+    // qcow2 creation, LXC config edits and VM config rewrites — keep them
+    // (and the VM mutex) off the actix worker.
+    match web::block(move || {
+        let hostname = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+        let vms = st.vms.lock().unwrap();
+        storage::wolfdisk::attach(&name, &opts, &vms, &hostname)
+    }).await {
+        Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WolfDiskDetachRequest {
+    pub kind: String,
+    pub target: String,
+}
+
+/// POST /api/storage/wolfdisk/volumes/{name}/detach
+pub async fn wolfdisk_volume_detach(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<WolfDiskDetachRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, &body.kind, &body.target) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    let name = path.into_inner();
+    let WolfDiskDetachRequest { kind, target } = body.into_inner();
+    let st = state.clone();
+    match web::block(move || {
+        let vms = st.vms.lock().unwrap();
+        storage::wolfdisk::detach(&name, &kind, &target, &vms)
+    }).await {
+        Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({ "message": msg })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 pub async fn storage_disk_info(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
 
@@ -21083,13 +25259,13 @@ pub async fn zfs_datasets(req: HttpRequest, state: web::Data<AppState>, query: w
     if pool.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'pool' parameter" }));
     }
-    // Validate pool name (alphanumeric, dash, underscore, dot only)
-    if !pool.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/') {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid pool name" }));
-    }
+    let pool = match crate::validate::DatasetName::parse(&pool) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
 
     let output = std::process::Command::new("zfs")
-        .args(["list", "-H", "-r", "-o", "name,used,avail,refer,mountpoint,compression,compressratio", &pool])
+        .args(["list", "-H", "-r", "-o", "name,used,avail,refer,mountpoint,compression,compressratio", pool.as_str()])
         .output();
 
     match output {
@@ -21125,13 +25301,19 @@ pub async fn zfs_snapshots(req: HttpRequest, state: web::Data<AppState>, query:
     if let Err(e) = require_auth(&req, &state) { return e; }
     let dataset = query.get("dataset").cloned().unwrap_or_default();
 
-    let mut args = vec!["list", "-t", "snapshot", "-H", "-o", "name,creation,used,refer"];
-    if !dataset.is_empty() {
-        if !dataset.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/' || c == '@') {
-            return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid dataset name" }));
+    let dataset = if dataset.is_empty() {
+        None
+    } else {
+        match crate::validate::DatasetName::parse(&dataset) {
+            Ok(d) => Some(d),
+            Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
         }
+    };
+
+    let mut args = vec!["list", "-t", "snapshot", "-H", "-o", "name,creation,used,refer"];
+    if let Some(ref dataset) = dataset {
         args.push("-r");
-        args.push(&dataset);
+        args.push(dataset.as_str());
     }
 
     let output = std::process::Command::new("zfs").args(&args).output();
@@ -21170,13 +25352,10 @@ pub async fn zfs_create_snapshot(req: HttpRequest, state: web::Data<AppState>, b
     if dataset.is_empty() || snap_name.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'dataset' or 'name'" }));
     }
-    // Validate names
-    let valid_chars = |s: &str| s.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/');
-    if !valid_chars(dataset) || !valid_chars(snap_name) {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid characters in dataset or snapshot name" }));
-    }
-
-    let snapshot_full = format!("{}@{}", dataset, snap_name);
+    let snapshot_full = match crate::validate::DatasetName::parse(dataset).and_then(|d| d.snapshot(snap_name)) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
     let output = std::process::Command::new("zfs")
         .args(["snapshot", &snapshot_full])
         .output();
@@ -21198,11 +25377,9 @@ pub async fn zfs_delete_snapshot(req: HttpRequest, state: web::Data<AppState>, b
     if let Err(e) = require_auth(&req, &state) { return e; }
     let snapshot = body.get("snapshot").and_then(|v| v.as_str()).unwrap_or("");
 
-    if snapshot.is_empty() || !snapshot.contains('@') {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid snapshot name (must contain @)" }));
-    }
-    if !snapshot.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/' || c == '@') {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid characters in snapshot name" }));
+    // Must be dataset@snap — a bare dataset here would destroy the dataset.
+    if let Err(e) = crate::validate::DatasetName::parse_snapshot(snapshot) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
     }
 
     let output = std::process::Command::new("zfs")
@@ -21380,6 +25557,16 @@ fn sanitize_file_path(path: &str) -> Result<std::path::PathBuf, String> {
     if !p.is_absolute() {
         return Err("Path must be absolute".into());
     }
+    // Inside an LXC root filesystem, resolve symlinks the way the
+    // container sees them — an absolute link planted in the container
+    // (`etc/x -> /etc`) must not lead to the host's own files.
+    let lexical = lexical_normalize(p);
+    if let Some((prefix, root)) = containers::lxc_storage::rootfs_containing(&lexical) {
+        let rel = lexical.strip_prefix(&prefix)
+            .or_else(|_| lexical.strip_prefix(&root))
+            .unwrap_or(std::path::Path::new(""));
+        return containers::lxc_storage::resolve_in_root(&root, rel);
+    }
     // Canonicalize to resolve .. and symlinks
     match p.canonicalize() {
         Ok(canonical) => Ok(canonical),
@@ -21399,19 +25586,32 @@ fn sanitize_file_path(path: &str) -> Result<std::path::PathBuf, String> {
     }
 }
 
+/// `path` with `.` and `..` applied textually (no symlinks followed).
+fn lexical_normalize(path: &std::path::Path) -> std::path::PathBuf {
+    let mut out = std::path::PathBuf::new();
+    for c in path.components() {
+        match c {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => { out.pop(); }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Host paths the file manager never exposes (credentials, the cluster
+/// secret and join tokens, kernel pseudo-filesystems).
+const SENSITIVE_PATHS: &[&str] = &["/etc/wolfstack", "/etc/shadow", "/etc/gshadow", "/proc", "/sys", "/root/.ssh"];
+
 /// Check if a path is sensitive and should not be accessible via the file manager
 fn is_sensitive_path(path: &std::path::Path) -> bool {
-    let s = path.to_string_lossy();
-    s == "/etc/wolfstack"
-        || s.starts_with("/etc/wolfstack/")
-        || s == "/etc/shadow"
-        || s == "/etc/gshadow"
-        || s == "/proc"
-        || s.starts_with("/proc/")
-        || s == "/sys"
-        || s.starts_with("/sys/")
-        || s == "/root/.ssh"
-        || s.starts_with("/root/.ssh/")
+    SENSITIVE_PATHS.iter().any(|p| path.starts_with(p))
+}
+
+/// Whether a directory holds a sensitive path somewhere below it — an
+/// archive of it would carry that path along.
+fn holds_sensitive_path(dir: &std::path::Path) -> bool {
+    SENSITIVE_PATHS.iter().any(|p| std::path::Path::new(p).starts_with(dir))
 }
 
 /// GET /api/files/browse?path=/some/path — list directory contents
@@ -21742,6 +25942,301 @@ pub async fn files_download(
     }
 }
 
+fn file_mtime_secs(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// GET /api/files/archive?path=/some/dir&format=zip|tar.gz — stream a
+/// folder as an archive, built on the fly (nothing is staged on disk).
+pub async fn files_archive(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let path = query.get("path").cloned().unwrap_or_default();
+    if path.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path'" }));
+    }
+    let canonical = match sanitize_file_path(&path) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+    if is_sensitive_path(&canonical) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Access to this path is restricted" }));
+    }
+    if holds_sensitive_path(&canonical) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "This folder contains restricted paths (credentials or WolfStack secrets) — archive a subfolder instead"
+        }));
+    }
+    if !canonical.is_dir() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Not a directory" }));
+    }
+    let format = query.get("format").map(|s| s.as_str()).unwrap_or("");
+    let cmd = match crate::file_transfer::archive_command(&canonical, format) {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+
+    // kill_on_drop: if the browser cancels the download the stream is
+    // dropped, and so is the archiver instead of compressing to nowhere.
+    let mut child = match tokio::process::Command::new(cmd.program)
+        .args(&cmd.args)
+        .current_dir(&cmd.cwd)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Cannot run {} (is it installed?): {}", cmd.program, e)
+        })),
+    };
+    let Some(mut stdout) = child.stdout.take() else {
+        return HttpResponse::InternalServerError().json(serde_json::json!({ "error": "Archiver has no output pipe" }));
+    };
+
+    let stream = async_stream::stream! {
+        use tokio::io::AsyncReadExt;
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match stdout.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => yield Ok::<_, actix_web::Error>(web::Bytes::copy_from_slice(&buf[..n])),
+                Err(e) => {
+                    yield Err(actix_web::error::ErrorInternalServerError(e));
+                    break;
+                }
+            }
+        }
+        let _ = child.wait().await;
+    };
+
+    HttpResponse::Ok()
+        .content_type(cmd.content_type)
+        .insert_header(actix_web::http::header::ContentDisposition {
+            disposition: actix_web::http::header::DispositionType::Attachment,
+            parameters: vec![actix_web::http::header::DispositionParam::Filename(cmd.filename)],
+        })
+        // Already compressed — keep the Compress middleware off it.
+        .insert_header(("Content-Encoding", "identity"))
+        .streaming(stream)
+}
+
+/// Resolve the `path` (target directory) and `name` query parameters of
+/// a chunked upload to (directory, part file, final file).
+fn chunked_upload_paths(
+    query: &std::collections::HashMap<String, String>,
+) -> Result<(std::path::PathBuf, std::path::PathBuf, std::path::PathBuf), HttpResponse> {
+    let dir = query.get("path").cloned().unwrap_or_default();
+    let name = query.get("name").cloned().unwrap_or_default();
+    if dir.is_empty() || name.is_empty() {
+        return Err(HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path' or 'name'" })));
+    }
+    let canonical_dir = sanitize_file_path(&dir)
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))?;
+    if is_sensitive_path(&canonical_dir) {
+        return Err(HttpResponse::Forbidden().json(serde_json::json!({ "error": "Access denied: protected path" })));
+    }
+    if !canonical_dir.is_dir() {
+        return Err(HttpResponse::BadRequest().json(serde_json::json!({ "error": "Target is not a directory" })));
+    }
+    let file_name = crate::file_transfer::upload_file_name(&name)
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))?;
+    let part = crate::file_transfer::part_path(&canonical_dir, file_name)
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))?;
+    let target = canonical_dir.join(file_name);
+    Ok((canonical_dir, part, target))
+}
+
+/// GET /api/files/upload/chunk?path=/dir&name=file — bytes received so
+/// far for a resumable upload (0 when none is in progress).
+pub async fn files_upload_status(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let (_, part, target) = match chunked_upload_paths(&query) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    let offset = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+    HttpResponse::Ok().json(serde_json::json!({
+        "offset": offset,
+        "exists": tokio::fs::try_exists(&target).await.unwrap_or(false),
+    }))
+}
+
+/// POST /api/files/upload/chunk?path=/dir&name=file&offset=N&total=M[&overwrite=1]
+/// — append one raw chunk to a resumable upload. The file appears under
+/// its real name once `total` bytes have arrived; an existing file of
+/// that name is only replaced with `overwrite=1`.
+pub async fn files_upload_chunk(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+    mut body: web::Payload,
+) -> HttpResponse {
+    use crate::file_transfer::{check_chunk, ChunkCheck, MAX_CHUNK_BYTES};
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let (_, part, target) = match chunked_upload_paths(&query) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    let parse = |k: &str| query.get(k).and_then(|v| v.parse::<u64>().ok());
+    let (Some(offset), Some(total)) = (parse("offset"), parse("total")) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing or invalid 'offset' / 'total'" }));
+    };
+    let overwrite = matches!(query.get("overwrite").map(|s| s.as_str()), Some("1") | Some("true"));
+    if offset == 0 && !overwrite && tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": format!("{} already exists — upload with overwrite=1 to replace it", target.display()),
+            "exists": true,
+        }));
+    }
+
+    let mut chunk = web::BytesMut::new();
+    while let Some(item) = body.next().await {
+        let bytes = match item {
+            Ok(b) => b,
+            Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Upload interrupted: {}", e) })),
+        };
+        if chunk.len() + bytes.len() > MAX_CHUNK_BYTES {
+            return HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                "error": format!("Chunk larger than {} MB", MAX_CHUNK_BYTES / (1024 * 1024))
+            }));
+        }
+        chunk.extend_from_slice(&bytes);
+    }
+
+    // Offset check and append happen under the upload's lock; a second
+    // chunk arriving meanwhile is told to resync.
+    let Some(_lock) = crate::file_transfer::UploadLock::acquire(&part) else {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Another chunk of this upload is being written — check the upload status and resume",
+        }));
+    };
+    let current = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+    match check_chunk(current, offset, chunk.len() as u64, total) {
+        ChunkCheck::Accept => {}
+        ChunkCheck::Mismatch => return HttpResponse::Conflict().json(serde_json::json!({
+            "error": format!("Chunk starts at {} but {} bytes have been received — resume from there", offset, current),
+            "offset": current,
+        })),
+        ChunkCheck::TooLong => return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Chunk runs past the declared total size",
+            "offset": current,
+        })),
+    }
+
+    let mut file = match tokio::fs::OpenOptions::new().create(true).append(true).open(&part).await {
+        Ok(f) => f,
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Cannot open {}: {}", part.display(), e)
+        })),
+    };
+    if let Err(e) = file.write_all(&chunk).await {
+        return HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("Write failed: {}", e) }));
+    }
+    if let Err(e) = file.flush().await {
+        return HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("Write failed: {}", e) }));
+    }
+    drop(file);
+
+    let received = offset + chunk.len() as u64;
+    if received < total {
+        return HttpResponse::Ok().json(serde_json::json!({ "offset": received, "complete": false }));
+    }
+    if !overwrite && tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": format!("{} already exists — upload with overwrite=1 to replace it", target.display()),
+            "exists": true,
+        }));
+    }
+    if let Err(e) = tokio::fs::rename(&part, &target).await {
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Upload received but could not be moved into place: {}", e)
+        }));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "offset": received,
+        "complete": true,
+        "path": target.to_string_lossy(),
+    }))
+}
+
+/// DELETE /api/files/upload/chunk?path=/dir&name=file — abandon a
+/// resumable upload and remove its partial file.
+pub async fn files_upload_abort(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let (_, part, _) = match chunked_upload_paths(&query) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    match tokio::fs::remove_file(&part).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Upload discarded" })),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound =>
+            HttpResponse::Ok().json(serde_json::json!({ "message": "No upload in progress" })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("{}", e) })),
+    }
+}
+
+/// GET /api/files/docker/volumes — named Docker volumes and their host
+/// mountpoints, so the host file manager can open one directly.
+pub async fn files_docker_volumes(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    match web::block(containers::docker_volumes).await {
+        Ok(volumes) => {
+            let volumes: Vec<_> = volumes.into_iter()
+                .filter(|v| std::path::Path::new(&v.mountpoint).is_dir())
+                .collect();
+            HttpResponse::Ok().json(volumes)
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// GET /api/files/lxc/rootfs?container=NAME — host path of an LXC
+/// container's root filesystem. Browsing that path with the host file
+/// manager works on stopped containers and gets the host-side features
+/// (archives, resumable uploads, edit conflict checks); symlinks under
+/// it resolve inside the container (see `sanitize_file_path`).
+pub async fn files_lxc_rootfs(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    let container = query.get("container").cloned().unwrap_or_default();
+    if container.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" }));
+    }
+    // The container comes in the query, which the tenancy path check
+    // doesn't see — check ownership here.
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, "lxc", &container) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": e }));
+    }
+    match web::block(move || containers::lxc_storage::host_rootfs_dir(&container)).await {
+        Ok(Ok(path)) => HttpResponse::Ok().json(serde_json::json!({ "path": path })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// GET /api/files/search?path=/start&query=pattern — recursive search using find
 pub async fn files_search(
     req: HttpRequest,
@@ -21900,14 +26395,16 @@ pub async fn files_read(
         Ok(m) => m,
         Err(e) => return HttpResponse::NotFound().json(serde_json::json!({ "error": format!("File not found: {}", e) })),
     };
-    if metadata.len() > 2 * 1024 * 1024 {
-        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "File too large to edit (max 2MB)" }));
+    if metadata.len() > crate::file_transfer::MAX_EDIT_BYTES {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "File too large to edit (max 2MB) — download it instead" }));
     }
     match std::fs::read_to_string(&canonical) {
         Ok(content) => HttpResponse::Ok().json(serde_json::json!({
             "path": path,
             "content": content,
             "size": metadata.len(),
+            // Echoed back on save so a concurrent change isn't clobbered.
+            "modified": file_mtime_secs(&metadata),
         })),
         Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": format!("Cannot read file (binary?): {}", e) })),
     }
@@ -21942,25 +26439,57 @@ pub async fn files_write(
     if canonical.is_dir() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Cannot write to a directory" }));
     }
+    if content.len() as u64 > crate::file_transfer::MAX_EDIT_BYTES {
+        return HttpResponse::PayloadTooLarge().json(serde_json::json!({ "error": "Content too large to save from the editor (max 2MB)" }));
+    }
+    // Optimistic concurrency: the editor sends the mtime it loaded. If
+    // the file has changed since, refuse rather than silently overwrite
+    // someone else's edit (or a package upgrade's).
+    if let Some(expected) = body.get("expected_modified").and_then(|v| v.as_u64()) {
+        if let Ok(meta) = std::fs::metadata(&canonical) {
+            let current = file_mtime_secs(&meta);
+            if current != expected {
+                return HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "File changed on disk since it was opened",
+                    "modified": current,
+                }));
+            }
+        }
+    }
+    // Syntax-check known config formats unless the editor says the user
+    // chose to save anyway.
+    if !body.get("skip_check").and_then(|v| v.as_bool()).unwrap_or(false)
+        && let Err(e) = crate::config_check::check(&canonical.to_string_lossy(), content)
+    {
+        return HttpResponse::UnprocessableEntity().json(serde_json::json!({ "error": e, "check_failed": true }));
+    }
     match std::fs::write(&canonical, content) {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "message": "File saved", "path": path })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("Write failed: {}", e) })),
     }
 }
 
+/// The container file manager names its container in the query string or
+/// body, which the path-based tenancy check in `require_auth` never sees.
+fn container_access(req: &HttpRequest, caller: &str, kind: crate::auth::tenancy::Kind, name: &str) -> Result<(), HttpResponse> {
+    crate::auth::tenancy::check_named(req, caller, kind, name)
+        .map_err(|e| HttpResponse::NotFound().json(serde_json::json!({ "error": e })))
+}
+
 /// GET /api/files/docker/browse?container=ID&path=/ — browse files inside a Docker container
 pub async fn files_docker_browse(
     req: HttpRequest,
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let path = query.get("path").cloned().unwrap_or_else(|| "/".into());
 
     if container.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, &container) { return e; }
 
     // Use docker exec to list directory contents with stat-like output
     let output = std::process::Command::new("docker")
@@ -22035,12 +26564,13 @@ pub async fn files_docker_mkdir(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let path = body.get("path").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, container) { return e; }
 
     let output = std::process::Command::new("docker")
         .args(["exec", container, "mkdir", "-p", path])
@@ -22059,12 +26589,13 @@ pub async fn files_docker_delete(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let path = body.get("path").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, container) { return e; }
 
     let critical = ["/", "/etc", "/usr", "/bin", "/sbin", "/lib", "/boot", "/proc", "/sys", "/dev", "/var"];
     if critical.contains(&path) {
@@ -22088,13 +26619,14 @@ pub async fn files_docker_rename(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let from = body.get("from").and_then(|v| v.as_str()).unwrap_or("");
     let to = body.get("to").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || from.is_empty() || to.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing fields" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, container) { return e; }
 
     let output = std::process::Command::new("docker")
         .args(["exec", container, "mv", from, to])
@@ -22113,12 +26645,13 @@ pub async fn files_docker_download(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let path = query.get("path").cloned().unwrap_or_default();
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, &container) { return e; }
 
     // docker cp to a temp file, off the async worker: cp of a large
     // file takes as long as the file is big — running it inline
@@ -22214,12 +26747,13 @@ pub async fn files_docker_upload(
     query: web::Query<std::collections::HashMap<String, String>>,
     mut payload: actix_multipart::Multipart,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let dir = query.get("path").cloned().unwrap_or_else(|| "/tmp".into());
     if container.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, &container) { return e; }
 
     use futures::StreamExt;
     let mut uploaded = Vec::new();
@@ -22305,11 +26839,12 @@ pub async fn files_docker_read(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = match query.get("container") {
         Some(c) => c.clone(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" })),
     };
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, &container) { return e; }
     let path = match query.get("path") {
         Some(p) => p.clone(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path'" })),
@@ -22333,11 +26868,12 @@ pub async fn files_docker_write(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = match body.get("container").and_then(|v| v.as_str()) {
         Some(c) => c.to_string(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" })),
     };
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Docker, &container) { return e; }
     let path = match body.get("path").and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path'" })),
@@ -22390,13 +26926,14 @@ pub async fn files_lxc_browse(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let path = query.get("path").cloned().unwrap_or_else(|| "/".into());
 
     if container.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, &container) { return e; }
 
     // Use a POSIX shell one-liner that works everywhere (BusyBox, Alpine, Debian, etc.)
     // Output format per line: TYPE<tab>SIZE<tab>NAME
@@ -22480,12 +27017,13 @@ pub async fn files_lxc_mkdir(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let path = body.get("path").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, container) { return e; }
 
     let output = lxc_exec_cmd(container, &["mkdir", "-p", path]).output();
 
@@ -22502,12 +27040,13 @@ pub async fn files_lxc_delete(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let path = body.get("path").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, container) { return e; }
 
     let critical = ["/", "/etc", "/usr", "/bin", "/sbin", "/lib", "/boot", "/proc", "/sys", "/dev", "/var"];
     if critical.contains(&path) {
@@ -22529,13 +27068,14 @@ pub async fn files_lxc_rename(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = body.get("container").and_then(|v| v.as_str()).unwrap_or("");
     let from = body.get("from").and_then(|v| v.as_str()).unwrap_or("");
     let to = body.get("to").and_then(|v| v.as_str()).unwrap_or("");
     if container.is_empty() || from.is_empty() || to.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing fields" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, container) { return e; }
 
     let output = lxc_exec_cmd(container, &["mv", from, to]).output();
 
@@ -22552,12 +27092,13 @@ pub async fn files_lxc_download(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let path = query.get("path").cloned().unwrap_or_default();
     if container.is_empty() || path.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container' or 'path'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, &container) { return e; }
 
     // lxc-attach/pct-exec cat, off the async worker — the exec runs as
     // long as the file is big and blocked the event loop inline
@@ -22601,12 +27142,13 @@ pub async fn files_lxc_upload(
     query: web::Query<std::collections::HashMap<String, String>>,
     mut payload: actix_multipart::Multipart,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = query.get("container").cloned().unwrap_or_default();
     let dir = query.get("path").cloned().unwrap_or_else(|| "/tmp".into());
     if container.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" }));
     }
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, &container) { return e; }
 
     use futures::StreamExt;
     let mut uploaded = Vec::new();
@@ -22709,11 +27251,12 @@ pub async fn files_lxc_read(
     state: web::Data<AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = match query.get("container") {
         Some(c) => c.clone(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" })),
     };
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, &container) { return e; }
     let path = match query.get("path") {
         Some(p) => p.clone(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path'" })),
@@ -22737,11 +27280,12 @@ pub async fn files_lxc_write(
     state: web::Data<AppState>,
     body: web::Json<serde_json::Value>,
 ) -> HttpResponse {
-    if let Err(e) = require_auth(&req, &state) { return e; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
     let container = match body.get("container").and_then(|v| v.as_str()) {
         Some(c) => c.to_string(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'container'" })),
     };
+    if let Err(e) = container_access(&req, &caller, crate::auth::tenancy::Kind::Lxc, &container) { return e; }
     let path = match body.get("path").and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
         None => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Missing 'path'" })),
@@ -22964,24 +27508,64 @@ pub fn build_config_bundle() -> serde_json::Value {
     serde_json::Value::Object(bundle)
 }
 
-/// Export all WolfStack configuration as a downloadable JSON file
+#[derive(Deserialize)]
+pub struct ConfigFormatQuery {
+    /// "yaml" selects the declarative document (see config_doc); anything
+    /// else is the JSON bundle.
+    #[serde(default)]
+    pub format: String,
+    /// Import only: report what the document would change without applying it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Export all WolfStack configuration as a downloadable JSON file, or with
+/// `?format=yaml` as a declarative document for version control
 pub async fn config_export(
-    req: HttpRequest, state: web::Data<AppState>,
+    req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfigFormatQuery>,
 ) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
+    if query.format == "yaml" {
+        let nodes = state.cluster.get_all_nodes();
+        let result = web::block(move || crate::config_doc::to_yaml(&crate::config_doc::export(&nodes))).await;
+        return match result {
+            Ok(Ok(yaml)) => HttpResponse::Ok()
+                .insert_header(("Content-Type", "application/yaml"))
+                .insert_header(("Content-Disposition", "attachment; filename=\"wolfstack-config.yaml\""))
+                .body(yaml),
+            Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+            Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "export task failed" })),
+        };
+    }
     HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .insert_header(("Content-Disposition", "attachment; filename=\"wolfstack-config.json\""))
         .json(build_config_bundle())
 }
 
-/// Import WolfStack configuration from a JSON bundle
+/// Import WolfStack configuration from a JSON bundle, or a declarative
+/// document (`?format=yaml`, a YAML content type, or a body carrying
+/// `apiVersion`) applied idempotently — `dry_run=true` only reports the plan
 pub async fn config_import(
-    req: HttpRequest, state: web::Data<AppState>, body: web::Json<serde_json::Value>,
+    req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfigFormatQuery>, body: web::Bytes,
 ) -> HttpResponse {
     if let Err(e) = require_auth(&req, &state) { return e; }
 
-    let bundle = body.into_inner();
+    let text = String::from_utf8_lossy(&body).to_string();
+    let bundle: Option<serde_json::Value> = serde_json::from_str(&text).ok();
+    let declarative = query.format == "yaml"
+        || req.headers().get("Content-Type").and_then(|v| v.to_str().ok()).is_some_and(|ct| ct.contains("yaml"))
+        || bundle.as_ref().is_some_and(|b| b.get("apiVersion").is_some());
+    if declarative {
+        return config_apply_document(&state, &text, query.dry_run).await;
+    }
+
+    let bundle = match bundle {
+        Some(b) => b,
+        None => return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid config bundle — expected JSON (or pass format=yaml for a declarative document)"
+        })),
+    };
     let obj = match bundle.as_object() {
         Some(o) => o,
         None => return HttpResponse::BadRequest().json(serde_json::json!({
@@ -22993,6 +27577,46 @@ pub async fn config_import(
     HttpResponse::Ok().json(config_import_summary(imported, errors))
 }
 
+/// Parse and apply a declarative config document (YAML or JSON). Cluster
+/// nodes go through `import_nodes` like the bundle path; every other section
+/// is config_doc's.
+async fn config_apply_document(state: &web::Data<AppState>, text: &str, dry_run: bool) -> HttpResponse {
+    let doc = match crate::config_doc::parse(text) {
+        Ok(d) => d,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+    let mut report = crate::config_doc::ApplyReport { dry_run, ..Default::default() };
+    if let Some(nodes) = crate::config_doc::node_records(&doc) {
+        match import_nodes(&nodes, state, dry_run) {
+            Ok(added) => {
+                for id in &added { report.push("nodes", id, "create"); }
+            }
+            Err(e) => report.errors.push(format!("nodes: {}", e)),
+        }
+    }
+    let report = match web::block(move || {
+        crate::config_doc::apply(&doc, &mut report);
+        report
+    }).await {
+        Ok(r) => r,
+        Err(_) => return HttpResponse::InternalServerError().json(serde_json::json!({ "error": "apply task failed" })),
+    };
+    if !dry_run && report.changed() > 0 {
+        tracing::info!("config document applied: {} change(s), {} error(s)", report.changed(), report.errors.len());
+    }
+    let message = match (dry_run, report.changed()) {
+        (_, 0) => "Already up to date — nothing to change".to_string(),
+        (true, n) => format!("{} change(s) would be applied", n),
+        (false, n) => format!("Applied {} change(s)", n),
+    };
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": message,
+        "dry_run": report.dry_run,
+        "changes": report.changes,
+        "errors": report.errors,
+    }))
+}
+
 /// Apply a parsed config bundle to THIS node: merge cluster nodes, overwrite
 /// the simple config files, and merge backup schedules. Shared by the HTTP
 /// upload-import (`config_import`) and the restore-from-saved-backup endpoint
@@ -23011,8 +27635,8 @@ fn apply_config_bundle(
 
     // Import nodes — merge with existing, skip self
     if let Some(nodes_val) = obj.get("nodes") {
-        match import_nodes(nodes_val, state) {
-            Ok(count) => imported.push(format!("{} nodes", count)),
+        match import_nodes(nodes_val, state, false) {
+            Ok(added) => imported.push(format!("{} nodes", added.len())),
             Err(e) => errors.push(format!("nodes: {}", e)),
         }
     }
@@ -23104,8 +27728,9 @@ fn write_json_file(path: &str, val: &serde_json::Value) -> Result<(), String> {
         .map_err(|e| format!("Failed to write {}: {}", path, e))
 }
 
-/// Import nodes into the cluster, merging with existing. Returns count of added nodes.
-fn import_nodes(nodes_val: &serde_json::Value, state: &web::Data<AppState>) -> Result<usize, String> {
+/// Import nodes into the cluster, merging with existing. Returns the ids of
+/// the added nodes; `dry_run` works them out without adding or saving.
+fn import_nodes(nodes_val: &serde_json::Value, state: &web::Data<AppState>, dry_run: bool) -> Result<Vec<String>, String> {
     // nodes.json — and therefore the exported bundle's "nodes" — is a JSON
     // ARRAY of Node (see ClusterState::save_nodes, which serializes
     // `Vec<&Node>`). The old importer parsed it as a map, so EVERY config
@@ -23125,7 +27750,7 @@ fn import_nodes(nodes_val: &serde_json::Value, state: &web::Data<AppState>) -> R
     };
 
     let self_id = state.cluster.self_id.clone();
-    let mut added = 0;
+    let mut added = Vec::new();
 
     {
         let mut nodes = state.cluster.nodes.write()
@@ -23155,15 +27780,20 @@ fn import_nodes(nodes_val: &serde_json::Value, state: &web::Data<AppState>) -> R
             if dup {
                 continue;
             }
+            added.push(node.id.clone());
+            if dry_run {
+                continue;
+            }
             node.is_self = false;
             node.online = false; // re-confirmed on next poll
             nodes.insert(node.id.clone(), node);
-            added += 1;
         }
     }
 
     // Persist
-    state.cluster.save_nodes();
+    if !dry_run {
+        state.cluster.save_nodes();
+    }
 
     Ok(added)
 }
@@ -23362,6 +27992,113 @@ pub async fn config_backup_restore(
     HttpResponse::Ok().json(config_import_summary(imported, errors))
 }
 
+// ─── System config (disaster recovery) ───
+//
+// The JSON bundle above carries the settings an operator usually wants to move
+// around; it deliberately leaves out users, secrets and node identity. For
+// rebuilding a dead control-plane node we need ALL of it, so these two
+// endpoints hand out and take back the same `config-wolfstack-*.tar.gz` the
+// "WolfStack configuration" backup target writes (whole /etc/wolfstack —
+// users, cluster secret, storage mounts, schedules, IP mappings, nodes.json —
+// plus /etc/wolfnet, /etc/wolfusb and VM definitions). Restore takes the file
+// as an upload because a freshly installed node has no backup list to pick
+// from. Both are admin-only and audited: the archive holds credentials.
+
+fn config_dr_audit(caller: &str, req: &HttpRequest, action: &str, detail: String, status: u16) {
+    crate::compat::audit_log(&crate::compat::AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        key_name: caller.to_string(),
+        key_id: "system-config".to_string(),
+        method: action.to_string(),
+        path: detail,
+        ip: crate::netaddr::canonical_ip_str(req.connection_info().peer_addr().unwrap_or("")).into_owned(),
+        status,
+    });
+}
+
+/// GET /api/config/system-backup — download a full system-config tarball
+pub async fn config_system_backup(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if !crate::auth::session_user_is_admin(&caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "System config backup and restore need an admin" }));
+    }
+    let result = web::block(|| {
+        let (path, _) = backup::backup_config()?;
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let bytes = std::fs::read(&path).map_err(|e| format!("read {}: {}", path.display(), e));
+        let _ = std::fs::remove_file(&path);
+        bytes.map(|b| (name, b))
+    }).await;
+    match result {
+        Ok(Ok((name, bytes))) => {
+            config_dr_audit(&caller, &req, "DOWNLOAD", name.clone(), 200);
+            HttpResponse::Ok()
+                .content_type("application/gzip")
+                // Generated by backup_config: config-wolfstack-<timestamp>.tar.gz.
+                .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", name)))
+                .body(bytes)
+        }
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "backup task failed" })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SystemRestoreQuery {
+    /// "replace" — this node takes over the backed-up node's identity, TLS and
+    /// cluster membership; "new" — keep this node's own.
+    #[serde(default)]
+    pub mode: String,
+}
+
+/// POST /api/config/system-restore?mode=replace|new — restore an uploaded
+/// system-config tarball onto this node (raw .tar.gz body)
+pub async fn config_system_restore(
+    req: HttpRequest, state: web::Data<AppState>, query: web::Query<SystemRestoreQuery>, body: web::Bytes,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if !crate::auth::session_user_is_admin(&caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "System config backup and restore need an admin" }));
+    }
+    let new_machine = match query.mode.as_str() {
+        "replace" => false,
+        "new" => true,
+        _ => return HttpResponse::BadRequest().json(serde_json::json!({ "error": "mode must be 'replace' or 'new'" })),
+    };
+    if body.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Upload a config-wolfstack-*.tar.gz file" }));
+    }
+    let detail = format!("mode={} size={}", query.mode, body.len());
+    let result = web::block(move || {
+        let path = std::env::temp_dir().join(format!("wolfstack-config-upload-{}.tar.gz", uuid::Uuid::new_v4()));
+        crate::paths::write_secure(&path.to_string_lossy(), &body[..])
+            .map_err(|e| format!("write upload: {}", e))?;
+        let r = backup::restore_config_archive(&path, new_machine);
+        let _ = std::fs::remove_file(&path);
+        r
+    }).await;
+    match result {
+        Ok(Ok(message)) => {
+            config_dr_audit(&caller, &req, "RESTORE", detail, 200);
+            tracing::warn!("system config restored from upload by {} ({}) — restarting", caller,
+                if new_machine { "new machine" } else { "replacement node" });
+            // Restart straight away: the running process still holds the old
+            // cluster/user state in memory and would write it back over the
+            // restored files on its next save.
+            let restart = installer::restart_cert_service("wolfstack").unwrap_or_default();
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": format!("{} {}", message.trim_end_matches(" Restart services to apply changes."), restart),
+                "restarting": true,
+            }))
+        }
+        Ok(Err(e)) => {
+            config_dr_audit(&caller, &req, "RESTORE", detail, 400);
+            HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))
+        }
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "restore task failed" })),
+    }
+}
+
 /// Path the web-triggered upgrade streams its stdout+stderr to. The
 /// previous run is overwritten (the most recent failure is what an
 /// operator wants to inspect; older runs aren't useful). Permissions are
@@ -23925,6 +28662,148 @@ pub async fn user_prefs_patch(req: HttpRequest, state: web::Data<AppState>, body
     }
 }
 
+// ─── Typed preferences (/api/preferences) ───
+//
+// A stable, typed view over the per-user store above for API clients. The
+// SPA keeps writing its own localStorage-shaped keys; each typed field
+// maps onto one of them, stored in the same encoding the SPA uses (plain
+// string, or JSON text for structured values), so both sides see the same
+// preference.
+
+/// (API field, stored key, stored as JSON text)
+const TYPED_PREF_KEYS: &[(&str, &str, bool)] = &[
+    ("theme", "wolfstack-theme", false),
+    ("dashboard_layout", "wolfstack_dashboard", true),
+    ("default_node", "wolfstack_default_node", false),
+    ("favourite_containers", "wolfstack_favourite_containers", true),
+];
+
+/// Stored prefs → typed view. Missing keys come back as null.
+fn typed_prefs_view(stored: &serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    let mut out = serde_json::Map::new();
+    for (field, key, is_json) in TYPED_PREF_KEYS {
+        let v = match stored.get(*key) {
+            Some(serde_json::Value::String(s)) if *is_json => serde_json::from_str(s).unwrap_or(serde_json::Value::Null),
+            Some(v) => v.clone(),
+            None => serde_json::Value::Null,
+        };
+        out.insert(field.to_string(), v);
+    }
+    serde_json::Value::Object(out)
+}
+
+/// Typed update → stored-key patch (null clears). Unknown fields and
+/// wrongly-typed values are rejected rather than silently dropped.
+fn typed_prefs_patch(body: &serde_json::Value) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let obj = body.as_object().ok_or("Expected a JSON object")?;
+    let mut patch = serde_json::Map::new();
+    for (field, value) in obj {
+        let (_, key, _) = TYPED_PREF_KEYS.iter().find(|(f, _, _)| f == field)
+            .ok_or_else(|| format!("Unknown preference '{}'", field))?;
+        let stored = match (field.as_str(), value) {
+            (_, serde_json::Value::Null) => serde_json::Value::Null,
+            ("theme" | "default_node", serde_json::Value::String(s)) if s.len() <= 128 => value.clone(),
+            ("theme" | "default_node", _) => return Err(format!("'{}' must be a string", field)),
+            ("dashboard_layout", serde_json::Value::Object(o)) if o.get("widgets").map(|w| w.is_array()).unwrap_or(false) => {
+                serde_json::Value::String(value.to_string())
+            }
+            ("dashboard_layout", _) => return Err("'dashboard_layout' must be an object with a 'widgets' array".to_string()),
+            ("favourite_containers", serde_json::Value::Array(items))
+                if items.iter().all(|i| i.get("runtime").and_then(|r| r.as_str()).is_some()
+                    && i.get("name").and_then(|n| n.as_str()).is_some()) => {
+                serde_json::Value::String(value.to_string())
+            }
+            ("favourite_containers", _) => {
+                return Err("'favourite_containers' must be an array of {runtime, name, node_id} objects".to_string())
+            }
+            _ => unreachable!("every TYPED_PREF_KEYS field is matched above"),
+        };
+        patch.insert(key.to_string(), stored);
+    }
+    Ok(patch)
+}
+
+/// GET /api/preferences — dashboard layout, theme, default node and
+/// favourite containers for the current user
+pub async fn preferences_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let username = match require_auth(&req, &state) {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let path = match user_prefs_path(&username) {
+        Some(p) => p,
+        None => return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Not a user account" })),
+    };
+    let stored: serde_json::Map<String, serde_json::Value> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default();
+    HttpResponse::Ok().json(typed_prefs_view(&stored))
+}
+
+/// PUT /api/preferences — update the given fields (null clears one);
+/// fields left out are kept
+pub async fn preferences_put(req: HttpRequest, state: web::Data<AppState>, body: web::Json<serde_json::Value>) -> HttpResponse {
+    let username = match require_auth(&req, &state) {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let path = match user_prefs_path(&username) {
+        Some(p) => p,
+        None => return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Not a user account" })),
+    };
+    let patch = match typed_prefs_patch(&body) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+    let dir = std::path::Path::new(&path).parent().unwrap_or(std::path::Path::new("/tmp"));
+    let _ = std::fs::create_dir_all(dir);
+
+    let mut prefs: serde_json::Map<String, serde_json::Value> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default();
+    for (k, v) in patch {
+        if v.is_null() {
+            prefs.remove(&k);
+        } else {
+            prefs.insert(k, v);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&prefs).unwrap_or_default();
+    if json.len() > 65536 {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Preferences too large (max 64 KB)" }));
+    }
+    match std::fs::write(&path, &json) {
+        Ok(_) => HttpResponse::Ok().json(typed_prefs_view(&prefs)),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("Failed to save: {}", e) })),
+    }
+}
+
+// ─── OpenAPI ───
+
+/// OpenAPI 3 document and TypeScript client for this build. build.rs
+/// generates both from the route table and the handlers' extractor types
+/// (see build/openapi.rs), so they can't drift from the routes served.
+const OPENAPI_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/openapi.json"));
+const OPENAPI_CLIENT_TS: &str = include_str!(concat!(env!("OUT_DIR"), "/wolfstack-client.ts"));
+
+/// GET /api/openapi.json — the OpenAPI document for every JSON endpoint.
+pub async fn openapi_json(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().content_type("application/json").body(OPENAPI_JSON)
+}
+
+/// GET /api/openapi/client.ts — generated TypeScript client, as a download.
+pub async fn openapi_client_ts(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok()
+        .content_type("application/typescript; charset=utf-8")
+        .insert_header(("Content-Disposition", "attachment; filename=\"wolfstack-client.ts\""))
+        .body(OPENAPI_CLIENT_TS)
+}
+
 /// GET /api/ping — lightweight liveness probe for the frontend connection
 /// monitor. Auth-required so the client can distinguish three states: 200 =
 /// reachable + session valid, 401 = reachable but session gone (→ login),
@@ -23935,6 +28814,25 @@ pub async fn ping(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse
     HttpResponse::Ok().json(serde_json::json!({ "ok": true }))
 }
 
+/// GET /healthz — liveness probe for load balancers and orchestrators.
+/// No auth: 200 whenever the process can answer a request at all.
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+/// GET /readyz — readiness probe. No auth: 200 once the listeners are up,
+/// 503 while starting and from the moment a graceful shutdown begins, so
+/// a load balancer drains the node before it goes away.
+pub async fn readyz() -> HttpResponse {
+    let phase = crate::lifecycle::phase();
+    let body = serde_json::json!({ "status": phase.as_str() });
+    if phase == crate::lifecycle::Phase::Ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
 // ─── Home-dashboard widget fetch proxy ───
 
 /// Pooled client for the home-dashboard widget fetch proxy (RSS feeds,
@@ -25347,6 +30245,136 @@ pub async fn mysql_dump(
     }
 }
 
+/// POST /api/mysql/overview — databases with size and table count
+pub async fn mysql_overview(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<MysqlCredsRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let params = body.to_params();
+    match tokio::time::timeout(std::time::Duration::from_secs(30),
+        crate::mysql_editor::database_overview(&params)).await
+    {
+        Ok(Ok(dbs)) => HttpResponse::Ok().json(serde_json::json!({ "databases": dbs })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "Database overview timed out after 30 seconds" })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MysqlUserRequest {
+    #[serde(flatten)]
+    pub conn: MysqlCredsRequest,
+    /// `list`, `grants`, `create`, `password`, `drop`, `grant` or `revoke`.
+    pub action: String,
+    #[serde(default)]
+    pub account_user: String,
+    #[serde(default)]
+    pub account_host: String,
+    #[serde(default)]
+    pub account_password: String,
+    #[serde(default)]
+    pub privileges: Vec<String>,
+    /// Scope for grant/revoke — `*` or empty means every database.
+    #[serde(default)]
+    pub on_database: String,
+    #[serde(default)]
+    pub on_table: Option<String>,
+}
+
+/// POST /api/mysql/users — account and grant management
+pub async fn mysql_users(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<MysqlUserRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(e) => return e };
+    let params = body.conn.to_params();
+    if params.db_type != crate::mysql_editor::DbType::Mysql {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "User management is MySQL/MariaDB-only" }));
+    }
+    let (u, h) = (body.account_user.as_str(), body.account_host.as_str());
+    let fut = async {
+        match body.action.as_str() {
+            "list" => crate::mysql_editor::list_users(&params).await
+                .map(|users| serde_json::json!({ "users": users })),
+            "grants" => crate::mysql_editor::show_grants(&params, u, h).await
+                .map(|grants| serde_json::json!({ "grants": grants })),
+            "create" => crate::mysql_editor::create_user(&params, u, h, &body.account_password).await
+                .map(|_| serde_json::json!({ "message": format!("User {}@{} created", u, h) })),
+            "password" => crate::mysql_editor::set_user_password(&params, u, h, &body.account_password).await
+                .map(|_| serde_json::json!({ "message": format!("Password changed for {}@{}", u, h) })),
+            "drop" => crate::mysql_editor::drop_user(&params, u, h).await
+                .map(|_| serde_json::json!({ "message": format!("User {}@{} dropped", u, h) })),
+            "grant" | "revoke" => crate::mysql_editor::change_grants(
+                &params, u, h, &body.privileges, &body.on_database, body.on_table.as_deref(), body.action == "revoke",
+            ).await.map(|sql| serde_json::json!({ "message": sql })),
+            other => Err(format!("Unknown action '{}'", other)),
+        }
+    };
+    match tokio::time::timeout(std::time::Duration::from_secs(30), fut).await {
+        Ok(Ok(v)) => {
+            if !matches!(body.action.as_str(), "list" | "grants") {
+                tracing::info!("MySQL account change by {}: {} {}@{} on {}:{}",
+                    caller, body.action, u, h, params.host, params.port);
+            }
+            HttpResponse::Ok().json(v)
+        }
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "Request timed out after 30 seconds" })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MysqlSlowLogRequest {
+    #[serde(flatten)]
+    pub conn: MysqlCredsRequest,
+    /// Present = update settings before reading.
+    #[serde(default)]
+    pub set: Option<MysqlSlowLogSettings>,
+    #[serde(default = "default_slow_log_limit")]
+    pub limit: usize,
+}
+
+#[derive(Deserialize)]
+pub struct MysqlSlowLogSettings {
+    pub enabled: bool,
+    pub long_query_time: f64,
+    #[serde(default)]
+    pub to_table: bool,
+}
+
+fn default_slow_log_limit() -> usize { 100 }
+
+/// POST /api/mysql/slow-log — slow query log settings and recent entries
+pub async fn mysql_slow_log(
+    req: HttpRequest, state: web::Data<AppState>,
+    body: web::Json<MysqlSlowLogRequest>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let params = body.conn.to_params();
+    if params.db_type != crate::mysql_editor::DbType::Mysql {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "The slow query log viewer is MySQL/MariaDB-only" }));
+    }
+    let fut = async {
+        if let Some(set) = &body.set {
+            crate::mysql_editor::set_slow_log(&params, set.enabled, set.long_query_time, set.to_table).await?;
+        }
+        let settings = crate::mysql_editor::slow_log_settings(&params).await?;
+        // Entries are best-effort: a FILE-only log on a remote server is
+        // unreadable, but the settings are still worth showing.
+        let (entries, entries_error) = match crate::mysql_editor::slow_log_entries(&params, body.limit).await {
+            Ok(e) => (e, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        Ok::<_, String>(serde_json::json!({ "settings": settings, "entries": entries, "entries_error": entries_error }))
+    };
+    match tokio::time::timeout(std::time::Duration::from_secs(30), fut).await {
+        Ok(Ok(v)) => HttpResponse::Ok().json(v),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": "Request timed out after 30 seconds" })),
+    }
+}
+
 // ─── App Store ───
 
 /// GET /api/appstore/apps?q=<query>&category=<cat> — list/search available apps
@@ -25509,12 +30537,12 @@ pub async fn appstore_compose_put(
 }
 
 /// POST /api/system/prepare-install-package — write a shell script that
-/// installs a single OS package (currently just the mount-helper packages
-/// nfs-common / cifs-utils) and return a session_id the UI opens as a
+/// installs a single OS package (the mount-helper packages nfs-common /
+/// cifs-utils, and snmpd for the SNMP agent) and return a session_id the UI opens as a
 /// live-terminal console so the user can watch the install run.
 #[derive(Deserialize)]
 pub struct PreparePkgInstallRequest {
-    /// One of the `mount.*` binaries the user needs — e.g. "mount.cifs".
+    /// One of the `mount.*` binaries the user needs — e.g. "mount.cifs" — or "snmpd".
     pub binary: String,
 }
 
@@ -27969,7 +32997,7 @@ pub async fn security_apply_updates(
 // ─── Issues Scanner ───
 
 /// Parse human-readable size strings (e.g. "1.2G", "450M", "32K") to MB
-fn parse_size_to_mb(s: &str) -> f64 {
+pub(crate) fn parse_size_to_mb(s: &str) -> f64 {
     let s = s.trim();
     if s.is_empty() { return 0.0; }
     let (num_str, suffix) = if s.ends_with(|c: char| c.is_alphabetic()) {
@@ -28065,349 +33093,11 @@ pub fn repair_for(issue: &Issue) -> Option<IssueRepair> {
     }
 }
 
-/// Collect system issues (reusable — called by HTTP handler and background scheduler)
+/// Collect system issues (reusable — called by HTTP handler and background scheduler).
+/// Every run is recorded in the issue history.
 pub fn collect_issues(metrics: &crate::monitoring::SystemMetrics) -> Vec<Issue> {
-    let mut issues: Vec<Issue> = Vec::new();
-    let mem_pct = metrics.memory_percent;
-
-    // ── CPU check ──
-    if metrics.cpu_usage_percent > 90.0 {
-        issues.push(Issue {
-            severity: "critical".into(),
-            category: "cpu".into(),
-            title: "CPU usage critically high".into(),
-            detail: format!("CPU at {:.1}% — system may be unresponsive", metrics.cpu_usage_percent),
-        });
-    } else if metrics.cpu_usage_percent > 75.0 {
-        issues.push(Issue {
-            severity: "warning".into(),
-            category: "cpu".into(),
-            title: "CPU usage elevated".into(),
-            detail: format!("CPU at {:.1}% — monitor for sustained load", metrics.cpu_usage_percent),
-        });
-    }
-
-    // ── Memory check ──
-    if mem_pct > 90.0 {
-        issues.push(Issue {
-            severity: "critical".into(),
-            category: "memory".into(),
-            title: "Memory usage critically high".into(),
-            detail: format!("Memory at {:.1}% — OOM risk", mem_pct),
-        });
-    } else if mem_pct > 80.0 {
-        issues.push(Issue {
-            severity: "warning".into(),
-            category: "memory".into(),
-            title: "Memory usage elevated".into(),
-            detail: format!("Memory at {:.1}%", mem_pct),
-        });
-    }
-
-    // ── Disk checks (free space, not just %) ──
-    for disk in &metrics.disks {
-        // Skip /boot/ and /etc/pve mounts unless >99% — managed by the OS/Proxmox
-        if (disk.mount_point.starts_with("/boot") || disk.mount_point == "/etc/pve") && disk.usage_percent <= 99.0 {
-            continue;
-        }
-
-        let total_gb = disk.total_bytes as f64 / 1_073_741_824.0;
-        let used_gb = disk.used_bytes as f64 / 1_073_741_824.0;
-        let free_gb = disk.available_bytes as f64 / 1_073_741_824.0;
-        let size_detail = format!("{} — {:.1} GB used / {:.1} GB total ({:.1} GB free, {:.1}%)",
-            disk.mount_point, used_gb, total_gb, free_gb, disk.usage_percent);
-
-        if free_gb < 2.0 {
-            issues.push(Issue {
-                severity: "critical".into(),
-                category: "disk".into(),
-                title: format!("Disk {} almost full ({:.1} GB free)", disk.mount_point, free_gb),
-                detail: size_detail,
-            });
-        } else if free_gb < 10.0 {
-            issues.push(Issue {
-                severity: "warning".into(),
-                category: "disk".into(),
-                title: format!("Disk {} low on space ({:.1} GB free)", disk.mount_point, free_gb),
-                detail: size_detail,
-            });
-        }
-    }
-
-    // ── Failing physical disks (SMART) ──
-    // The free-space loop above catches FULL disks; this catches DYING ones.
-    // Uses the SAME evaluator the Storage page and the Issues alerting watcher
-    // use (array::disk_smart_health + DiskSmart::failing_reasons — Backblaze
-    // SMART 5/187/197/198, NVMe wear/spare, overall-health FAILED), so a drive
-    // the operator sees reddened under Storage also shows up HERE, on the page
-    // where they run upgrades (a failing SSD used to raise nothing on this scan
-    // — collect_issues never checked disk health; PapaSchlumpf 2026-07-01). The
-    // standby guard is gated on rotational-ness so an SSD/NVMe whose power-mode
-    // probe can't confirm an active state is still read (never skipped), while a
-    // spun-down HDD is left asleep. Physical-disk SMART is host-level and
-    // independent of `metrics`, so it's read directly here.
-    for dev in crate::array::list_physical_disks() {
-        let guard = crate::array::disk_is_rotational(&dev);
-        if let Some(health) = crate::array::disk_smart_health(&dev, guard) {
-            let reasons = health.failing_reasons();
-            if !reasons.is_empty() {
-                issues.push(Issue {
-                    severity: "critical".into(),
-                    category: "disk".into(),
-                    title: format!("Disk {} is failing (SMART)", dev),
-                    detail: format!("{} — back up and plan replacement: {}", dev, reasons.join("; ")),
-                });
-            }
-        }
-    }
-
-    // ── Swap check ──
-    if metrics.swap_total_bytes > 0 {
-        let swap_pct = (metrics.swap_used_bytes as f64 / metrics.swap_total_bytes as f64) * 100.0;
-        if swap_pct > 50.0 {
-            issues.push(Issue {
-                severity: "warning".into(),
-                category: "swap".into(),
-                title: "Significant swap usage".into(),
-                detail: format!("Swap at {:.1}% — system may be low on RAM", swap_pct),
-            });
-        }
-    }
-
-    // ── Load average check ──
-    let cpu_count = metrics.cpu_count.max(1) as f64;
-    if metrics.load_avg.one > cpu_count * 2.0 {
-        issues.push(Issue {
-            severity: "critical".into(),
-            category: "load".into(),
-            title: "Load average extremely high".into(),
-            detail: format!("Load {:.2} ({:.1}× CPU count {})", metrics.load_avg.one, metrics.load_avg.one / cpu_count, metrics.cpu_count),
-        });
-    } else if metrics.load_avg.one > cpu_count {
-        issues.push(Issue {
-            severity: "warning".into(),
-            category: "load".into(),
-            title: "Load average high".into(),
-            detail: format!("Load {:.2} (>{} CPUs)", metrics.load_avg.one, metrics.cpu_count),
-        });
-    }
-
-    // ── Failed systemd services ──
-    if let Ok(output) = std::process::Command::new("systemctl")
-        .args(["--failed", "--no-legend", "--plain"])
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if let Some(unit) = parts.first() {
-                if !unit.is_empty() {
-                    issues.push(Issue {
-                        severity: "warning".into(),
-                        category: "service".into(),
-                        title: format!("Service {} failed", unit),
-                        detail: format!("systemd unit {} is in failed state", unit),
-                    });
-                }
-            }
-        }
-    }
-
-    // ── Stopped Docker containers ──
-    if let Ok(output) = std::process::Command::new("docker")
-        .args(["ps", "-a", "--filter", "status=exited", "--format", "{{.Names}}"])
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stopped: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
-        if !stopped.is_empty() {
-            issues.push(Issue {
-                severity: "info".into(),
-                category: "container".into(),
-                title: format!("{} stopped Docker container(s)", stopped.len()),
-                detail: format!("Stopped: {}", stopped.join(", ")),
-            });
-        }
-    }
-
-    // ── Kubernetes cluster issues ──
-    let k8s_clusters = crate::kubernetes::list_clusters();
-    for cluster in &k8s_clusters {
-        let status = crate::kubernetes::get_cluster_status(&cluster.kubeconfig_path);
-        if !status.healthy {
-            issues.push(Issue {
-                severity: "warning".into(),
-                category: "kubernetes".into(),
-                title: format!("K8s cluster '{}' unhealthy", cluster.name),
-                detail: format!("{}/{} nodes ready, {}/{} pods running",
-                    status.nodes_ready, status.nodes_total,
-                    status.pods_running, status.pods_total),
-            });
-        }
-
-        // Check for NotReady nodes
-        let nodes = crate::kubernetes::get_nodes(&cluster.kubeconfig_path);
-        for n in &nodes {
-            if n.status != "Ready" {
-                issues.push(Issue {
-                    severity: "critical".into(),
-                    category: "kubernetes".into(),
-                    title: format!("K8s node '{}' not ready", n.name),
-                    detail: format!("Cluster '{}', status: {}, role: {}", cluster.name, n.status, n.roles),
-                });
-            }
-        }
-
-        // Check for problem pods (exclude Succeeded/Completed pods — these are finished Jobs/init containers)
-        let pods = crate::kubernetes::get_pods(&cluster.kubeconfig_path, None);
-        let active_pods: Vec<_> = pods.iter().filter(|p| p.status != "Succeeded").collect();
-        let failed = active_pods.iter().filter(|p| p.status == "Failed" || p.status == "Unknown").count();
-        let pending = active_pods.iter().filter(|p| p.status == "Pending").count();
-        let high_restarts = active_pods.iter().filter(|p| p.restarts >= 10).count();
-
-        if failed > 0 {
-            issues.push(Issue {
-                severity: "critical".into(),
-                category: "kubernetes".into(),
-                title: format!("{} failed pod(s) in cluster '{}'", failed, cluster.name),
-                detail: active_pods.iter().filter(|p| p.status == "Failed" || p.status == "Unknown")
-                    .take(5).map(|p| format!("{}/{}", p.namespace, p.name)).collect::<Vec<_>>().join(", "),
-            });
-        }
-        if pending > 0 {
-            issues.push(Issue {
-                severity: "warning".into(),
-                category: "kubernetes".into(),
-                title: format!("{} pending pod(s) in cluster '{}'", pending, cluster.name),
-                detail: active_pods.iter().filter(|p| p.status == "Pending")
-                    .take(5).map(|p| format!("{}/{}", p.namespace, p.name)).collect::<Vec<_>>().join(", "),
-            });
-        }
-        if high_restarts > 0 {
-            issues.push(Issue {
-                severity: "warning".into(),
-                category: "kubernetes".into(),
-                title: format!("{} pod(s) with high restarts in cluster '{}'", high_restarts, cluster.name),
-                detail: active_pods.iter().filter(|p| p.restarts >= 10)
-                    .take(5).map(|p| format!("{}/{} ({}x)", p.namespace, p.name, p.restarts)).collect::<Vec<_>>().join(", "),
-            });
-        }
-    }
-
-    // ── Clearable disk space ──
-
-    // Journal logs
-    if let Ok(output) = std::process::Command::new("journalctl")
-        .args(["--disk-usage"])
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        // Parse "Archived and active journals take up 1.2G in the file system."
-        if let Some(size_str) = stdout.split("take up ").nth(1).and_then(|s| s.split(' ').next()) {
-            let size_mb = parse_size_to_mb(size_str);
-            if size_mb > 500.0 {
-                issues.push(Issue {
-                    severity: "info".into(),
-                    category: "disk".into(),
-                    title: format!("Journal logs using {}", size_str),
-                    detail: format!("Run 'journalctl --vacuum-size=200M' to reclaim space"),
-                });
-            }
-        }
-    }
-
-    // Package cache (apt)
-    if let Ok(output) = std::process::Command::new("du")
-        .args(["-sh", "/var/cache/apt/archives"])
-        .output()
-    {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(size_str) = stdout.split_whitespace().next() {
-                let size_mb = parse_size_to_mb(size_str);
-                if size_mb > 200.0 {
-                    issues.push(Issue {
-                        severity: "info".into(),
-                        category: "disk".into(),
-                        title: format!("APT cache using {}", size_str),
-                        detail: "Run 'apt clean' to reclaim space".into(),
-                    });
-                }
-            }
-        }
-    }
-
-    // Package cache (dnf/yum)
-    if let Ok(output) = std::process::Command::new("du")
-        .args(["-sh", "/var/cache/dnf"])
-        .output()
-    {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(size_str) = stdout.split_whitespace().next() {
-                let size_mb = parse_size_to_mb(size_str);
-                if size_mb > 200.0 {
-                    issues.push(Issue {
-                        severity: "info".into(),
-                        category: "disk".into(),
-                        title: format!("DNF cache using {}", size_str),
-                        detail: "Run 'dnf clean all' to reclaim space".into(),
-                    });
-                }
-            }
-        }
-    }
-
-    // /tmp usage
-    if let Ok(output) = std::process::Command::new("du")
-        .args(["-sh", "/tmp"])
-        .output()
-    {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(size_str) = stdout.split_whitespace().next() {
-                let size_mb = parse_size_to_mb(size_str);
-                if size_mb > 500.0 {
-                    issues.push(Issue {
-                        severity: "info".into(),
-                        category: "disk".into(),
-                        title: format!("/tmp using {}", size_str),
-                        detail: "Temporary files may be safe to clean up".into(),
-                    });
-                }
-            }
-        }
-    }
-
-    // Docker unused images
-    if let Ok(output) = std::process::Command::new("docker")
-        .args(["system", "df", "--format", "{{.Reclaimable}}"])
-        .output()
-    {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            // Sum up any reclaimable amounts
-            let mut total_mb = 0.0f64;
-            let mut total_str = String::new();
-            for line in stdout.lines() {
-                let clean = line.trim().split('(').next().unwrap_or("").trim();
-                if !clean.is_empty() && clean != "0B" {
-                    let mb = parse_size_to_mb(clean);
-                    total_mb += mb;
-                    if total_str.is_empty() { total_str = clean.to_string(); }
-                }
-            }
-            if total_mb > 500.0 {
-                issues.push(Issue {
-                    severity: "info".into(),
-                    category: "container".into(),
-                    title: format!("Docker reclaimable space: {:.0} MB", total_mb),
-                    detail: "Run 'docker system prune' to reclaim unused images/containers".into(),
-                });
-            }
-        }
-    }
-
+    let issues = crate::issue_checks::run_enabled(metrics);
+    crate::issue_history::record(&issues);
     issues
 }
 
@@ -28424,7 +33114,7 @@ pub async fn scan_issues(
     // never run on the actix worker thread.
     let st = state.clone().into_inner();
     let (metrics, issues) = tokio::task::spawn_blocking(move || {
-        let metrics = st.monitor.lock().unwrap().collect();
+        let metrics = crate::monitoring::inventory::metrics_blocking(&st);
         let issues = collect_issues(&metrics);
         (metrics, issues)
     }).await.unwrap();
@@ -28451,6 +33141,101 @@ pub async fn scan_issues(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct IssueHistoryQuery {
+    /// Unix seconds or RFC 3339; default 90 days before `until`
+    #[serde(default)]
+    pub since: String,
+    /// Unix seconds or RFC 3339; default now
+    #[serde(default)]
+    pub until: String,
+    #[serde(default)]
+    pub category: String,
+    /// Most recent occurrences to return (stats cover them all); 0 = all
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `json` (default) or `csv`
+    #[serde(default)]
+    pub format: String,
+    /// `cluster` (default) or `local`
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// Unix seconds, an RFC 3339 time or a `YYYY-MM-DD` date (midnight UTC).
+fn parse_history_time(s: &str) -> Option<i64> {
+    if let Ok(t) = s.parse::<i64>() {
+        return Some(t);
+    }
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(t.timestamp());
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc().timestamp())
+}
+
+/// GET /api/issues/history?since=&until=&category= — issue occurrences
+/// overlapping the window and mean time to resolution per category, as
+/// JSON or a CSV download of the occurrences
+pub async fn issue_history(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<IssueHistoryQuery>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let time = |s: &str, default: i64| -> Result<i64, HttpResponse> {
+        if s.is_empty() {
+            return Ok(default);
+        }
+        parse_history_time(s).ok_or_else(|| HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("'{}' is not a date, RFC 3339 time or unix seconds", s)
+        })))
+    };
+    let until = match time(&query.until, chrono::Utc::now().timestamp()) { Ok(t) => t, Err(resp) => return resp };
+    let since = match time(&query.since, until - 90 * 86400) { Ok(t) => t, Err(resp) => return resp };
+    if since > until {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "since must be before until" }));
+    }
+
+    let mut occurrences = if query.scope == "local" {
+        let me = match state.cluster.get_all_nodes().into_iter().find(|n| n.is_self) {
+            Some(n) => n,
+            None => return HttpResponse::ServiceUnavailable().json(serde_json::json!({ "error": "Node not registered yet" })),
+        };
+        let category = query.category.clone();
+        web::block(move || crate::issue_history::collect_local(&me, since, until, &category)).await.unwrap_or_default()
+    } else {
+        crate::issue_history::collect_cluster(&state, since, until, &query.category).await
+    };
+    occurrences.sort_by(|a, b| b.first_seen.cmp(&a.first_seen).then_with(|| a.node.cmp(&b.node)));
+
+    if query.format == "csv" {
+        let filename = format!(
+            "wolfstack-issue-history-{}-{}.csv",
+            chrono::DateTime::from_timestamp(since, 0).map(|d| d.format("%Y%m%d").to_string()).unwrap_or_default(),
+            chrono::DateTime::from_timestamp(until, 0).map(|d| d.format("%Y%m%d").to_string()).unwrap_or_default(),
+        );
+        return HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+            .body(crate::issue_history::to_csv(&occurrences));
+    }
+    let (stats, overall) = crate::issue_history::stats(&occurrences, until);
+    let total = occurrences.len();
+    if let Some(limit) = query.limit.filter(|l| *l > 0) {
+        occurrences.truncate(limit);
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "since": since,
+        "until": until,
+        "total": total,
+        "occurrences": occurrences,
+        "stats": stats,
+        "overall": overall,
+    }))
+}
+
 /// GET /api/alerts?since=ID — get recent alerts for the Tasks window
 pub async fn get_alert_log(
     req: HttpRequest,
@@ -28620,6 +33405,48 @@ fn reclaim_dnf_mb() -> f64 {
     (before - after).max(0.0)
 }
 
+/// GET /api/issues/checks — built-in checks and checks.d scripts with
+/// their enabled state on this node
+pub async fn list_issue_checks(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(crate::issue_checks::list_checks).await {
+        Ok(v) => HttpResponse::Ok().json(v),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct IssueCheckToggle {
+    pub id: String,
+    pub enabled: bool,
+}
+
+/// POST /api/issues/checks — enable or disable one check on this node
+pub async fn set_issue_check(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<IssueCheckToggle>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let body = body.into_inner();
+    let result = web::block(move || {
+        if !crate::issue_checks::is_known_check(&body.id) {
+            return Err(format!("Unknown check '{}'", body.id));
+        }
+        let mut config = crate::issue_checks::IssueChecksConfig::load();
+        config.set_enabled(&body.id, body.enabled);
+        config.save()
+    }).await;
+    match result {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "status": "saved" })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 /// POST /api/issues/clean — run safe cleanup to free disk space
 pub async fn clean_system(
     req: HttpRequest,
@@ -28913,7 +33740,9 @@ fn run_repair(kind: &str, target: &str) -> Result<serde_json::Value, String> {
 pub async fn alerts_config_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let config = crate::alerting::AlertConfig::load();
-    HttpResponse::Ok().json(config.to_masked_json())
+    let mut v = config.to_masked_json();
+    v["held_alerts"] = crate::alerting::held_count().into();
+    HttpResponse::Ok().json(v)
 }
 
 pub async fn alerts_config_save(req: HttpRequest, state: web::Data<AppState>, body: web::Json<serde_json::Value>) -> HttpResponse {
@@ -28941,6 +33770,12 @@ pub async fn alerts_config_save(req: HttpRequest, state: web::Data<AppState>, bo
     if let Some(t) = v.get("container_memory_threshold").and_then(|v| v.as_f64()) {
         config.container_memory_threshold = t as f32;
     }
+    if let Some(m) = v.get("wolfnet_stale_minutes").and_then(|v| v.as_u64()) {
+        config.wolfnet_stale_minutes = m.min(24 * 60);
+    }
+    if let Some(n) = v.get("attack_alert_threshold").and_then(|v| v.as_u64()) {
+        config.attack_alert_threshold = n.min(1_000_000);
+    }
     if let Some(i) = v.get("check_interval_secs").and_then(|v| v.as_u64()) {
         // Clamp to sensible range: 30 seconds to 1 hour
         config.check_interval_secs = i.max(30).min(3600);
@@ -28993,6 +33828,23 @@ pub async fn alerts_config_save(req: HttpRequest, state: web::Data<AppState>, bo
     if let Some(b) = v.get("recovery_notifications").and_then(|v| v.as_bool()) {
         config.recovery_notifications = b;
     }
+    // Per-channel quiet hours — the object replaces the map wholesale;
+    // unknown channel names are dropped.
+    if let Some(m) = v.get("channel_quiet_hours").and_then(|v| v.as_object()) {
+        config.channel_quiet_hours = m.iter()
+            .filter(|(k, _)| crate::alerting::CHANNELS.contains(&k.as_str()))
+            .filter_map(|(k, q)| Some((k.clone(), serde_json::from_value(q.clone()).ok()?)))
+            .collect();
+    }
+    // Digest intervals in minutes per category, max a day. Compromise
+    // is never batched so it isn't accepted here.
+    if let Some(m) = v.get("digest_minutes").and_then(|v| v.as_object()) {
+        config.digest_minutes = m.iter()
+            .filter(|(k, _)| matches!(k.as_str(), "brute_force" | "posture" | "threshold" | "lifecycle"))
+            .filter_map(|(k, n)| Some((k.clone(), n.as_u64()?.min(24 * 60))))
+            .filter(|(_, n)| *n > 0)
+            .collect();
+    }
     // Discord bot token is write-only from the UI's perspective —
     // masked in to_masked_json so the frontend never sees it back.
     // Only overwrite when a non-empty value arrives, so a save with
@@ -32023,7 +36875,12 @@ pub struct WolfRunAdoptRequest {
 
 /// POST /api/wolfrun/services/adopt — adopt an existing container into WolfRun
 pub async fn wolfrun_adopt(req: HttpRequest, state: web::Data<AppState>, body: web::Json<WolfRunAdoptRequest>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    // The container may be on any node, and ownership is recorded per
+    // node — adopting it into a service needs an unscoped caller.
+    if crate::auth::tenancy::scope(&req, &caller).is_some() {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Not available to tenant accounts" }));
+    }
 
     let runtime = match body.runtime.as_deref().unwrap_or("docker") {
         "lxc" => crate::wolfrun::Runtime::Lxc,
@@ -32061,6 +36918,73 @@ pub async fn wolfrun_failover_events(req: HttpRequest, state: web::Data<AppState
     HttpResponse::Ok().json(events)
 }
 
+/// GET /api/wolfrun/autoscale — every service with an autoscaling policy,
+/// with the load the leader last measured for it (only known on the leader).
+pub async fn wolfrun_autoscale_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let rows: Vec<serde_json::Value> = state.wolfrun.list(None).into_iter()
+        .filter_map(|svc| {
+            let policy = svc.autoscale.clone()?;
+            Some(serde_json::json!({
+                "service_id": svc.id,
+                "service_name": svc.name,
+                "cluster_name": svc.cluster_name,
+                "replicas": svc.replicas,
+                "min_replicas": svc.min_replicas,
+                "max_replicas": svc.max_replicas,
+                "policy": policy,
+                "load": crate::wolfrun::autoscale::last_load(&svc.id),
+            }))
+        })
+        .collect();
+    HttpResponse::Ok().json(rows)
+}
+
+/// GET /api/wolfrun/autoscale/events?service_id= — scaling actions taken by
+/// this node while it was leader
+pub async fn wolfrun_autoscale_events(req: HttpRequest, state: web::Data<AppState>, query: web::Query<std::collections::HashMap<String, String>>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(crate::wolfrun::autoscale::events(query.get("service_id").map(|s| s.as_str())))
+}
+
+/// PUT /api/wolfrun/services/{id}/autoscale — set a service's policy
+pub async fn wolfrun_autoscale_set(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<crate::wolfrun::autoscale::AutoscalePolicy>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    let mut policy = body.into_inner();
+    if let Err(e) = policy.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    // Keep the leader's cooldown clock across edits.
+    policy.last_scaled_at = state.wolfrun.get(&id)
+        .and_then(|s| s.autoscale).map(|p| p.last_scaled_at).unwrap_or(0);
+    if !state.wolfrun.set_autoscale(&id, Some(policy.clone())) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Service not found" }));
+    }
+    let wolfrun = Arc::clone(&state.wolfrun);
+    let cluster = Arc::clone(&state.cluster);
+    let secret = state.cluster_secret.clone();
+    actix_web::rt::spawn(async move {
+        crate::wolfrun::broadcast_to_cluster(&wolfrun, &cluster, &secret).await;
+    });
+    HttpResponse::Ok().json(policy)
+}
+
+/// DELETE /api/wolfrun/services/{id}/autoscale — remove a service's policy
+pub async fn wolfrun_autoscale_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    if !state.wolfrun.set_autoscale(&path.into_inner(), None) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Service not found" }));
+    }
+    let wolfrun = Arc::clone(&state.wolfrun);
+    let cluster = Arc::clone(&state.cluster);
+    let secret = state.cluster_secret.clone();
+    actix_web::rt::spawn(async move {
+        crate::wolfrun::broadcast_to_cluster(&wolfrun, &cluster, &secret).await;
+    });
+    HttpResponse::Ok().json(serde_json::json!({ "deleted": true }))
+}
+
 /// POST /api/wolfrun/sync — receive WolfRun services from a cluster peer
 pub async fn wolfrun_sync(req: HttpRequest, state: web::Data<AppState>, body: web::Json<Vec<crate::wolfrun::WolfRunService>>) -> HttpResponse {
     // Authenticate via cluster secret (inter-node auth) or session auth
@@ -32172,11 +37096,28 @@ pub async fn statuspage_monitors_list(req: HttpRequest, state: web::Data<AppStat
             "uptime_percent": uptime,
             "history": history,
             "recent_checks": recent_checks,
+            "locations": state.statuspage.latest_by_location(&m.id),
         })
     }).collect();
     HttpResponse::Ok().json(serde_json::json!({ "monitors": monitors }))
 }
 
+/// GET /api/statuspage/monitors/{id}/history — every retained check
+/// result (oldest first, up to 24 h at the default interval) for the
+/// response-time chart, plus daily uptime.
+pub async fn statuspage_monitor_history(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    if !state.statuspage.config.read().unwrap().monitors.iter().any(|m| m.id == id) {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Monitor not found" }));
+    }
+    HttpResponse::Ok().json(serde_json::json!({
+        "results": state.statuspage.recent_results(&id),
+        "daily": state.statuspage.get_daily_uptime(&id),
+        "uptime_percent": state.statuspage.uptime_percent(&id),
+    }))
+}
+
 /// POST /api/statuspage/monitors — create or update a monitor
 pub async fn statuspage_monitor_save(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::statuspage::Monitor>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -32276,6 +37217,13 @@ pub async fn statuspage_page_save(req: HttpRequest, state: web::Data<AppState>,
             "error": "Slug must be lowercase alphanumeric with hyphens only"
         }));
     }
+    if let Some(color) = page.accent_color.as_deref().filter(|c| !c.is_empty())
+        && !crate::statuspage::valid_accent_color(color)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Accent colour must be a hex colour like #1e90ff"
+        }));
+    }
 
     let mut config = state.statuspage.config.write().unwrap();
 
@@ -32407,6 +37355,231 @@ pub async fn statuspage_sync(req: HttpRequest, state: web::Data<AppState>, body:
     HttpResponse::Ok().json(serde_json::json!({ "synced": true }))
 }
 
+#[derive(Deserialize)]
+pub struct StatusPagePeerResult {
+    pub monitor_id: String,
+    pub result: crate::statuspage::CheckResult,
+}
+
+#[derive(Deserialize)]
+pub struct StatusPagePeerResults {
+    #[serde(default)]
+    pub results: Vec<StatusPagePeerResult>,
+}
+
+/// POST /api/statuspage/results — check results a peer ran for monitors
+/// pinned to particular nodes. Cluster secret only: a user session has
+/// no business writing uptime history.
+pub async fn statuspage_results(req: HttpRequest, state: web::Data<AppState>, body: web::Json<StatusPagePeerResults>) -> HttpResponse {
+    let secret = req.headers().get("X-WolfStack-Secret")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !crate::auth::validate_inter_node_secret(secret, &state.cluster_secret) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Cluster secret required" }));
+    }
+    let results = body.into_inner().results.into_iter()
+        .map(|r| (r.monitor_id, r.result))
+        .collect();
+    let recorded = crate::statuspage::record_peer_results(&state.statuspage, results);
+    HttpResponse::Ok().json(serde_json::json!({ "recorded": recorded }))
+}
+
+// ─── Webhooks — lifecycle events POSTed to external URLs ───
+
+fn webhooks_broadcast_soon(state: &web::Data<AppState>) {
+    let cluster = state.cluster.clone();
+    let secret = state.cluster_secret.clone();
+    actix_web::rt::spawn(async move {
+        crate::events::webhooks::broadcast_to_cluster(&cluster, &secret).await;
+    });
+}
+
+#[derive(Deserialize)]
+pub struct WebhookUpsertBody {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Create: optional caller-chosen secret (generated otherwise).
+    /// Update: replaces the secret.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Update only: generate a fresh secret and return it once.
+    #[serde(default)]
+    pub rotate_secret: bool,
+}
+
+fn webhook_not_found(id: &str) -> String {
+    format!("Webhook '{}' not found", id)
+}
+
+/// GET /api/events/types — event names a webhook (or function) can subscribe to.
+pub async fn events_types(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let types: Vec<serde_json::Value> = crate::events::EVENT_TYPES.iter()
+        .map(|(id, label)| serde_json::json!({ "id": id, "label": label }))
+        .collect();
+    HttpResponse::Ok().json(types)
+}
+
+/// GET /api/webhooks — configured webhooks (secrets omitted).
+pub async fn webhooks_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let hooks: Vec<serde_json::Value> = crate::events::webhooks::config().webhooks.iter()
+        .map(|w| w.public_json())
+        .collect();
+    HttpResponse::Ok().json(hooks)
+}
+
+/// POST /api/webhooks — add a webhook. The signing secret is returned
+/// in this response only.
+pub async fn webhooks_create(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<WebhookUpsertBody>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "manage webhooks") { return resp; }
+    let body = body.into_inner();
+    let url = body.url.unwrap_or_default().trim().to_string();
+    let name = body.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).unwrap_or_else(|| url.clone());
+    let events = body.events.unwrap_or_default();
+    if let Err(e) = crate::events::webhooks::validate_url(&url)
+        .and_then(|_| crate::events::webhooks::validate_events(&events))
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    let secret = body.secret.filter(|s| !s.is_empty()).unwrap_or_else(crate::events::webhooks::generate_secret);
+    let hook = crate::events::webhooks::Webhook {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        url,
+        secret: secret.clone(),
+        events,
+        enabled: body.enabled.unwrap_or(true),
+        created: chrono::Utc::now().to_rfc3339(),
+    };
+    let view = hook.public_json();
+    match crate::events::webhooks::update(|hooks| { hooks.push(hook); Ok(()) }) {
+        Ok(_) => {
+            webhooks_broadcast_soon(&state);
+            let mut view = view;
+            view["secret"] = serde_json::json!(secret);
+            HttpResponse::Ok().json(view)
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// PUT /api/webhooks/{id} — edit a webhook; only the fields sent change.
+pub async fn webhooks_update(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<WebhookUpsertBody>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "manage webhooks") { return resp; }
+    let id = path.into_inner();
+    let body = body.into_inner();
+    if let Some(url) = &body.url
+        && let Err(e) = crate::events::webhooks::validate_url(url)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    if let Some(events) = &body.events
+        && let Err(e) = crate::events::webhooks::validate_events(events)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    let new_secret = if body.rotate_secret {
+        Some(crate::events::webhooks::generate_secret())
+    } else {
+        body.secret.clone().filter(|s| !s.is_empty())
+    };
+    let result = crate::events::webhooks::update(|hooks| {
+        let hook = hooks.iter_mut().find(|w| w.id == id).ok_or_else(|| webhook_not_found(&id))?;
+        if let Some(name) = body.name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+            hook.name = name.to_string();
+        }
+        if let Some(url) = &body.url { hook.url = url.trim().to_string(); }
+        if let Some(events) = &body.events { hook.events = events.clone(); }
+        if let Some(enabled) = body.enabled { hook.enabled = enabled; }
+        if let Some(secret) = &new_secret { hook.secret = secret.clone(); }
+        Ok(())
+    });
+    match result {
+        Ok(cfg) => {
+            webhooks_broadcast_soon(&state);
+            let mut view = cfg.webhooks.iter().find(|w| w.id == id).map(|w| w.public_json()).unwrap_or_default();
+            if body.rotate_secret && let Some(secret) = new_secret {
+                view["secret"] = serde_json::json!(secret);
+            }
+            HttpResponse::Ok().json(view)
+        }
+        Err(e) if e == webhook_not_found(&id) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// DELETE /api/webhooks/{id}
+pub async fn webhooks_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "manage webhooks") { return resp; }
+    let id = path.into_inner();
+    let result = crate::events::webhooks::update(|hooks| {
+        let before = hooks.len();
+        hooks.retain(|w| w.id != id);
+        if hooks.len() == before { Err(webhook_not_found(&id)) } else { Ok(()) }
+    });
+    match result {
+        Ok(_) => {
+            webhooks_broadcast_soon(&state);
+            HttpResponse::Ok().json(serde_json::json!({ "deleted": true }))
+        }
+        Err(e) if e == webhook_not_found(&id) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// POST /api/webhooks/{id}/test — send a signed `ping` event now and
+/// report how the receiver answered.
+pub async fn webhooks_test(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let user = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &user, "manage webhooks") { return resp; }
+    let id = path.into_inner();
+    let Some(hook) = crate::events::webhooks::config().webhooks.into_iter().find(|w| w.id == id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": webhook_not_found(&id) }));
+    };
+    let event = crate::events::new_event("ping", serde_json::json!({
+        "message": "WolfStack webhook test",
+        "requested_by": user,
+    }));
+    let delivery = crate::events::webhooks::deliver(&hook, &event).await;
+    HttpResponse::Ok().json(delivery)
+}
+
+/// GET /api/webhooks/{id}/deliveries — recent delivery attempts on this node.
+pub async fn webhooks_deliveries(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(crate::events::webhooks::deliveries(&path.into_inner()))
+}
+
+/// POST /api/webhooks/sync — receive the webhook list from a cluster peer.
+pub async fn webhooks_sync(
+    req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::events::webhooks::WebhookConfig>,
+) -> HttpResponse {
+    let secret = req.headers().get("X-WolfStack-Secret")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !crate::auth::validate_inter_node_secret(secret, &state.cluster_secret) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Invalid cluster secret" }));
+    }
+    match crate::events::webhooks::merge_from_peer(body.into_inner()) {
+        Ok(applied) => HttpResponse::Ok().json(serde_json::json!({ "synced": applied })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════
 // ─── WolfFunctions — serverless functions across the cluster ───
 // ═══════════════════════════════════════════════════════════════
@@ -33081,6 +38254,25 @@ pub async fn statuspage_public_page(state: web::Data<AppState>, path: web::Path<
     }
 }
 
+/// Public JSON for a status page — CORS-open so a customer's own site
+/// can fetch it. 404 for unknown or disabled pages alike.
+fn statuspage_json_response(state: &web::Data<AppState>, page: Option<crate::statuspage::StatusPage>) -> HttpResponse {
+    match page.and_then(|p| crate::statuspage::public_page_json(&state.statuspage, &p)) {
+        Some(body) => HttpResponse::Ok()
+            .insert_header(("Access-Control-Allow-Origin", "*"))
+            .insert_header(("Cache-Control", "max-age=30"))
+            .json(body),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Status page not found" })),
+    }
+}
+
+/// GET /status/{slug}/status.json — public machine-readable page status
+pub async fn statuspage_public_json(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let cluster_name = local_cluster_name(&state.cluster);
+    let page = state.statuspage.find_page_by_slug_and_cluster(&path.into_inner(), &cluster_name);
+    statuspage_json_response(&state, page)
+}
+
 // ─── Configurator API ───
 
 /// Query parameters for targeting a container or the host
@@ -33293,6 +38485,23 @@ pub async fn nginx_generate_config(req: HttpRequest, state: web::Data<AppState>,
     HttpResponse::Ok().json(serde_json::json!({ "config": config }))
 }
 
+/// POST /api/configurator/nginx/publish — proxy a domain to a container's
+/// WolfNet IP:port, optionally with a Let's Encrypt certificate
+pub async fn nginx_publish_site(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::configurator::nginx::PublishSiteParams>, query: web::Query<ConfiguratorTarget>) -> HttpResponse {
+    let user = match require_auth(&req, &state) { Ok(u) => u, Err(r) => return r };
+    let target = match parse_exec_target(&query) { Ok(t) => t, Err(r) => return r };
+    let params = body.into_inner();
+    let domain = params.domain.clone();
+    match web::block(move || crate::configurator::nginx::publish_site(&target, &params)).await {
+        Ok(Ok(steps)) => {
+            tracing::info!("Site {} published by {}", domain, user);
+            HttpResponse::Ok().json(serde_json::json!({ "steps": steps }))
+        }
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": format!("{}", e) })),
+    }
+}
+
 /// POST /api/configurator/nginx/bootstrap — install nginx and create default config
 pub async fn nginx_bootstrap(req: HttpRequest, state: web::Data<AppState>, query: web::Query<ConfiguratorTarget>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
@@ -34097,7 +39306,7 @@ pub(crate) fn compose_stack_count() -> u32 {
 fn compose_config_images(dir: &std::path::Path, compose_file: &std::path::Path) -> Vec<String> {
     match Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "config", "--images"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(dir))
         .current_dir(dir)
         .output()
     {
@@ -34210,7 +39419,7 @@ async fn compose_list_stacks(
             // Get stack status via docker compose ps
             let status = Command::new("docker")
                 .args(["compose", "-f", &compose_file.to_string_lossy(), "ps", "--format", "json"])
-                .envs(compose_secrets_env())
+                .envs(compose_secrets_env(&path))
                 .current_dir(&path)
                 .output();
 
@@ -34309,7 +39518,7 @@ async fn compose_delete_stack(
     let compose_file = compose_file_in(&dir);
     match Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "down", "--remove-orphans"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output()
     {
@@ -34415,7 +39624,7 @@ async fn compose_up(
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "up", "-d", "--remove-orphans"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34480,7 +39689,7 @@ async fn compose_down(
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "down", "--remove-orphans"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34521,7 +39730,7 @@ async fn compose_pull(
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "pull"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34579,7 +39788,7 @@ async fn compose_restart(
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file.to_string_lossy(), "restart"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34632,7 +39841,7 @@ async fn compose_logs(
 
     let output = Command::new("docker")
         .args(&cmd_args)
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34695,7 +39904,7 @@ async fn compose_validate(
 
     let output = Command::new("docker")
         .args(["compose", "-f", &file_str, "config", "--quiet"])
-        .envs(compose_secrets_env())
+        .envs(compose_secrets_env(&dir))
         .current_dir(&dir)
         .output();
 
@@ -34782,14 +39991,26 @@ const COMPOSE_ENV_DENYLIST: &[&str] = &[
 /// inspect`) — inherent to env-var secrets, not to this injection.
 /// (Gary KO4BSR 2026-06-10: secrets referenced from compose came through
 /// blank because nothing ever injected them.)
-pub(crate) fn compose_secrets_env() -> Vec<(String, String)> {
+///
+/// The stack's own env store (see `containers::env_store`, scope = the
+/// stack directory's name) follows the Secrets Manager, so a value stored
+/// for the stack wins over a global secret of the same name.
+pub(crate) fn compose_secrets_env(dir: &std::path::Path) -> Vec<(String, String)> {
+    let stack = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
     load_secrets()
         .into_iter()
-        .filter(|s| is_injectable_secret_key(&s.key))
         .map(|s| (s.key, s.value))
+        .chain(containers::env_store::compose_env(&stack))
+        .filter(|(k, _)| is_injectable_secret_key(k))
         .collect()
 }
 
+/// A Secrets-Manager value by key, for `${secret:KEY}` references in
+/// container env (see `containers::env_store`).
+pub(crate) fn named_secret(key: &str) -> Option<String> {
+    load_secrets().into_iter().find(|s| s.key == key).map(|s| s.value)
+}
+
 /// Combined gate for `compose_secrets_env` — a valid env-var name that isn't
 /// a behaviour-altering variable. Split out so it's unit-testable without
 /// touching the on-disk store.
@@ -35845,18 +41066,10 @@ pub async fn oidc_callback(
 
     // Create session and set cookie
     let token = state.sessions.create_session(&username);
-    let mut cookie = Cookie::build("wolfstack_session", &token)
-        .path("/")
-        .http_only(true)
-        .same_site(actix_web::cookie::SameSite::Lax)
-        .max_age(actix_web::cookie::time::Duration::hours(8))
-        .finish();
-    if state.tls_enabled {
-        cookie.set_secure(true);
-    }
 
     HttpResponse::Found()
-        .cookie(cookie)
+        .cookie(session_cookie(&state, &token))
+        .cookie(csrf_cookie(&state, &token))
         .append_header(("Location", "/index.html"))
         .finish()
 }
@@ -36479,6 +41692,96 @@ async fn system_deps_install(
 
 // ─── Host mail relay (msmtp sendmail shim) ───
 
+// ═══════════════════════════════════════════════
+// ─── Capacity policy (reservations / overcommit) ───
+// ═══════════════════════════════════════════════
+
+/// GET /api/capacity/policy — this node's reservation and overcommit
+/// policy, with the host size, what running guests have committed and
+/// the ceiling the policy works out to.
+async fn capacity_policy_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let policy = crate::capacity_policy::Policy::load();
+    let host = crate::capacity_policy::host();
+    let committed = tokio::task::spawn_blocking(crate::capacity_policy::committed).await.unwrap_or_default();
+    HttpResponse::Ok().json(serde_json::json!({
+        "policy": policy,
+        "host": host,
+        "committed": committed,
+        "allowed": policy.allowed(&host),
+    }))
+}
+
+/// POST /api/capacity/policy — replace this node's capacity policy
+/// (admin only). Enforced by the container and VM create endpoints.
+async fn capacity_policy_set(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::capacity_policy::Policy>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if let Err(resp) = require_admin_caller(&req, &caller, "change the capacity policy") { return resp; }
+    let policy = body.into_inner();
+    if let Err(e) = policy.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    match policy.save() {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true, "policy": policy })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+// ═══════════════════════════════════════════════
+// ─── Disk housekeeping ───
+// ═══════════════════════════════════════════════
+
+/// GET /api/housekeeping — this node's housekeeping settings and the
+/// report of the last run
+async fn housekeeping_get(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(serde_json::json!({
+        "config": crate::housekeeping::Config::load(),
+        "last_report": crate::housekeeping::last_report(),
+    }))
+}
+
+/// POST /api/housekeeping/config — replace this node's housekeeping
+/// settings (admin only)
+async fn housekeeping_set(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::housekeeping::Config>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if !crate::auth::caller_is_admin(&req, &caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Only admin users can change housekeeping settings" }));
+    }
+    let mut config = body.into_inner();
+    if let Err(e) = config.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    // The schedule's bookkeeping isn't the client's to set.
+    config.last_run = crate::housekeeping::Config::load().last_run;
+    match config.save() {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true, "config": config })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HousekeepingRunRequest {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// POST /api/housekeeping/run — run the enabled housekeeping tasks now;
+/// `{"dry_run": true}` only reports what would be removed. A real run is
+/// admin only.
+async fn housekeeping_run(req: HttpRequest, state: web::Data<AppState>, body: web::Json<HousekeepingRunRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let dry_run = body.dry_run;
+    if !dry_run && !crate::auth::caller_is_admin(&req, &caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Only admin users can run housekeeping" }));
+    }
+    let config = crate::housekeeping::Config::load();
+    match web::block(move || crate::housekeeping::run(&config, dry_run, "manual")).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 // ═══════════════════════════════════════════════
 // ─── UPS power management ───
 // ═══════════════════════════════════════════════
@@ -36613,6 +41916,44 @@ async fn mail_relay_test(
     }
 }
 
+/// GET /api/mail/diagnostics — DNS/TCP/TLS/AUTH check against the
+/// configured SMTP relay, plus the outbox and recent send failures.
+async fn mail_diagnostics(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let config = state.ai_agent.config.lock().unwrap().clone();
+    match tokio::task::spawn_blocking(move || crate::mail_outbox::diagnose(&config)).await {
+        Ok(steps) => {
+            let outbox = crate::mail_outbox::Outbox::load();
+            HttpResponse::Ok().json(serde_json::json!({
+                "steps": steps,
+                "ok": steps.iter().all(|s| s.ok),
+                "queued": outbox.queued,
+                "failures": outbox.failures,
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/mail/outbox/retry — retry every queued alert email now
+async fn mail_outbox_retry(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let config = state.ai_agent.config.lock().unwrap().clone();
+    match tokio::task::spawn_blocking(move || crate::mail_outbox::retry_queued(&config)).await {
+        Ok((sent, remaining)) => HttpResponse::Ok().json(serde_json::json!({ "sent": sent, "remaining": remaining })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// DELETE /api/mail/outbox/{id} — discard a queued alert email
+async fn mail_outbox_discard(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match crate::mail_outbox::discard(&path.into_inner()) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "ok": true })),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+    }
+}
+
 /// GET /api/danger/pending — list dangerous ops awaiting confirmation
 /// or auto-rollback. Polled by the frontend to render the countdown
 /// banner. No auth required beyond standard — cluster-secret callers
@@ -37983,6 +43324,7 @@ fn gateway_audit(
         hostname,
         cluster,
     });
+    if let Some(entry) = log.last() { entry.publish(); }
     while log.len() > 200 {
         log.remove(0);
     }
@@ -39196,12 +44538,8 @@ pub async fn federation_status(req: HttpRequest, state: web::Data<AppState>) ->
         }
     }
 
-    // Self metrics from SystemMonitor.collect — same call the
-    // dashboard uses, refreshes CPU + memory in place. Holding
-    // the lock across `collect()` is fine because the call is
-    // microseconds long.
-    if let Ok(mut monitor) = state.monitor.lock() {
-        let m = monitor.collect();
+    // Self metrics — the same snapshot the dashboard reads.
+    if let Some(m) = crate::monitoring::inventory::metrics(&state).await {
         mem_total_mb = m.memory_total_bytes / 1024 / 1024;
         mem_used_mb = m.memory_used_bytes / 1024 / 1024;
         cpu_pct = m.cpu_usage_percent;
@@ -39380,6 +44718,292 @@ pub async fn install_setup_sh(_req: HttpRequest) -> HttpResponse {
         .body(body)
 }
 
+// ─── Bare-metal provisioning ───
+
+/// Provisioning profiles mint pre-approved cluster joins, so managing
+/// them takes an admin (directly or through another node's proxy).
+fn require_provisioning_admin(req: &HttpRequest, state: &AppState) -> Result<String, HttpResponse> {
+    let caller = require_auth(req, state)?;
+    require_admin_caller(req, &caller, "manage provisioning")?;
+    Ok(caller)
+}
+
+/// GET /api/provision/profiles — profiles plus the installable OS list
+pub async fn provision_profiles_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_provisioning_admin(&req, &state) { return resp; }
+    let cfg = crate::provisioning::ProvisioningConfig::load();
+    let profiles: Vec<serde_json::Value> = cfg.profiles.iter().map(|p| p.to_public_json()).collect();
+    let os: Vec<serde_json::Value> = crate::provisioning::OS_IMAGES.iter()
+        .map(|o| serde_json::json!({ "id": o.id, "label": o.label }))
+        .collect();
+    HttpResponse::Ok().json(serde_json::json!({ "profiles": profiles, "os": os }))
+}
+
+/// POST /api/provision/profiles — create, or update when `id` is set
+pub async fn provision_profile_save(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::provisioning::ProvisionProfile>) -> HttpResponse {
+    let caller = match require_provisioning_admin(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    match web::block(move || crate::provisioning::save_profile(body.into_inner())).await {
+        Ok(Ok(profile)) => {
+            cluster_join_audit(&state, &caller, "info", "Provisioning profile saved",
+                &format!("Profile '{}' ({}) enrols into cluster '{}'", profile.name, profile.id,
+                    if profile.cluster_name.is_empty() { state.cluster.get_self_cluster_name() } else { profile.cluster_name.clone() }),
+                "");
+            HttpResponse::Ok().json(profile.to_public_json())
+        }
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// DELETE /api/provision/profiles/{id}
+pub async fn provision_profile_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_provisioning_admin(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    let id = path.into_inner();
+    let id2 = id.clone();
+    match web::block(move || crate::provisioning::delete_profile(&id2)).await {
+        Ok(Ok(true)) => {
+            cluster_join_audit(&state, &caller, "info", "Provisioning profile deleted", &format!("Profile {}", id), "");
+            HttpResponse::Ok().json(serde_json::json!({ "deleted": true }))
+        }
+        Ok(Ok(false)) => HttpResponse::NotFound().json(serde_json::json!({ "error": "Profile not found" })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/provision/profiles/{id}/rotate — new enrolment token
+pub async fn provision_profile_rotate(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_provisioning_admin(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    let id2 = id.clone();
+    match web::block(move || crate::provisioning::rotate_token(&id2)).await {
+        Ok(Ok(Some(profile))) => {
+            cluster_join_audit(&state, &caller, "info", "Provisioning token rotated", &format!("Profile {}", id), "");
+            HttpResponse::Ok().json(profile.to_public_json())
+        }
+        Ok(Ok(None)) => HttpResponse::NotFound().json(serde_json::json!({ "error": "Profile not found" })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// Resolve the profile behind an artifact URL. Unknown tokens count
+/// toward the per-IP lockout so the token space can't be walked.
+fn provision_profile_for(req: &HttpRequest, state: &AppState, token: &str) -> Result<crate::provisioning::ProvisionProfile, HttpResponse> {
+    let peer_ip = req.peer_addr().map(|a| a.ip().to_canonical().to_string()).unwrap_or_default();
+    if state.login_limiter.is_locked_out(&peer_ip) {
+        state.login_limiter.audit_blocked(&peer_ip, "provisioning");
+        return Err(HttpResponse::TooManyRequests().body("Too many failed attempts\n"));
+    }
+    match crate::provisioning::ProvisioningConfig::load().by_token(token) {
+        Some(p) => Ok(p.clone()),
+        None => {
+            state.login_limiter.record_failure(&peer_ip);
+            Err(HttpResponse::NotFound().body("Unknown provisioning token\n"))
+        }
+    }
+}
+
+/// GET /api/provision/boot/{token}/{file} — netboot artifacts for a
+/// profile: boot.ipxe, preseed.cfg, user-data, meta-data, firstboot.sh.
+/// Unauthenticated by design (firmware and installers carry no session);
+/// the profile token in the path is the credential.
+pub async fn provision_artifact(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>) -> HttpResponse {
+    let (token, file) = path.into_inner();
+    let profile = match provision_profile_for(&req, &state, &token) { Ok(p) => p, Err(resp) => return resp };
+    let (content_type, body) = match file.as_str() {
+        "boot.ipxe" => ("text/plain; charset=utf-8", profile.ipxe_script()),
+        "preseed.cfg" => ("text/plain; charset=utf-8", profile.preseed()),
+        "user-data" => ("text/cloud-config; charset=utf-8", profile.autoinstall_user_data()),
+        "meta-data" => ("text/plain; charset=utf-8", profile.meta_data()),
+        // cloud-init asks for it; we have nothing to add.
+        "vendor-data" => ("text/plain; charset=utf-8", String::new()),
+        "firstboot.sh" => ("text/x-shellscript; charset=utf-8", profile.firstboot_script()),
+        _ => return HttpResponse::NotFound().body("Unknown provisioning artifact\n"),
+    };
+    HttpResponse::Ok().content_type(content_type).insert_header(("Cache-Control", "no-store")).body(body)
+}
+
+#[derive(Deserialize)]
+pub struct ProvisionEnrollRequest {
+    #[serde(default)]
+    pub hostname: String,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub mac: String,
+    pub join_token: String,
+    pub provision_key: String,
+}
+
+/// POST /api/provision/boot/{token}/enroll — called by `firstboot.sh` once
+/// WolfStack is running on the new machine. Joins it (by the address the
+/// request came from) into the profile's cluster via the normal add-node
+/// path, with the machine's provisioning key standing in for admin
+/// credentials.
+pub async fn provision_enroll(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<ProvisionEnrollRequest>) -> HttpResponse {
+    let token = path.into_inner();
+    let profile = match provision_profile_for(&req, &state, &token) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    let address = req.peer_addr().map(|a| a.ip().to_canonical().to_string()).unwrap_or_default();
+    let body = body.into_inner();
+    if body.join_token.trim().is_empty() || body.provision_key.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "join_token and provision_key are required" }));
+    }
+    let hostname: String = body.hostname.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.').take(253).collect();
+    // The MAC in the body is whatever the caller says; the allow-list is
+    // checked against the one this node actually sees.
+    let seen_mac = {
+        let address = address.clone();
+        web::block(move || crate::provisioning::neighbour_mac(&address)).await.ok().flatten()
+    };
+    let mut entry = crate::provisioning::Enrollment {
+        ts: chrono::Utc::now().timestamp().max(0) as u64,
+        hostname: hostname.clone(),
+        address: address.clone(),
+        mac: seen_mac.clone()
+            .or_else(|| crate::provisioning::normalize_mac(&body.mac))
+            .unwrap_or_default(),
+        ok: false,
+        detail: String::new(),
+    };
+    // Take the enrolment before joining, so two machines racing for a
+    // profile's last use can't both get in.
+    let id = profile.id.clone();
+    let ts = entry.ts;
+    let reserved = web::block(move || crate::provisioning::reserve_use(&id, seen_mac.as_deref(), ts)).await;
+    match reserved {
+        Ok(Ok(())) => {}
+        Ok(Err(reason)) => {
+            entry.detail = reason.clone();
+            let id = profile.id.clone();
+            let _ = web::block(move || crate::provisioning::record_enrollment(&id, entry)).await;
+            return HttpResponse::Forbidden().json(serde_json::json!({ "error": reason }));
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+    tracing::info!(target: "provisioning", "Enrolling {} ({}) via profile '{}'", address, hostname, profile.name);
+    let add = AddServerRequest {
+        address: address.clone(),
+        port: body.port,
+        join_token: Some(body.join_token.trim().to_string()),
+        cluster_name: Some(profile.cluster_name.clone())
+            .filter(|c| !c.is_empty())
+            .or_else(|| Some(state.cluster.get_self_cluster_name()).filter(|c| !c.is_empty())),
+        provision_key: Some(body.provision_key.trim().to_string()),
+        ..Default::default()
+    };
+    let resp = join_wolfstack_node(state.clone(), add, format!("provisioning:{}", profile.name)).await;
+    entry.ok = resp.status().is_success();
+    entry.detail = if entry.ok { "joined".into() } else { format!("join failed (HTTP {})", resp.status().as_u16()) };
+    let id = profile.id.clone();
+    let _ = web::block(move || {
+        if !entry.ok { crate::provisioning::release_use(&id); }
+        crate::provisioning::record_enrollment(&id, entry)
+    }).await;
+    resp
+}
+
+// ─── Reboot windows ───
+
+/// Windows carry shell hooks that run as root on every target, so managing
+/// them takes an admin, like provisioning.
+fn require_maintenance_admin(req: &HttpRequest, state: &AppState) -> Result<String, HttpResponse> {
+    let caller = require_auth(req, state)?;
+    require_admin_caller(req, &caller, "manage reboot windows")?;
+    Ok(caller)
+}
+
+/// GET /api/maintenance/windows — windows plus recent runs
+pub async fn maintenance_windows_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_maintenance_admin(&req, &state) { return resp; }
+    let cfg = crate::maintenance::MaintenanceConfig::load();
+    let mut runs = crate::maintenance::load_runs();
+    runs.truncate(20);
+    HttpResponse::Ok().json(serde_json::json!({ "windows": cfg.windows, "runs": runs }))
+}
+
+/// POST /api/maintenance/windows — create, or update when `id` is set
+pub async fn maintenance_window_save(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::maintenance::RebootWindow>) -> HttpResponse {
+    if let Err(resp) = require_maintenance_admin(&req, &state) { return resp; }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    match web::block(move || crate::maintenance::save_window(body.into_inner())).await {
+        Ok(Ok(window)) => HttpResponse::Ok().json(window),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// DELETE /api/maintenance/windows/{id}
+pub async fn maintenance_window_delete(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_maintenance_admin(&req, &state) { return resp; }
+    let id = path.into_inner();
+    match web::block(move || crate::maintenance::delete_window(&id)).await {
+        Ok(Ok(true)) => HttpResponse::Ok().json(serde_json::json!({ "deleted": true })),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(serde_json::json!({ "error": "Window not found" })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/maintenance/windows/{id}/run — start the window now
+pub async fn maintenance_window_run(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_maintenance_admin(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    let Some(window) = crate::maintenance::MaintenanceConfig::load().windows.into_iter().find(|w| w.id == id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Window not found" }));
+    };
+    match crate::maintenance::trigger(state.clone(), window, &caller) {
+        Ok(run) => HttpResponse::Ok().json(run),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
+}
+
+/// GET /api/maintenance/runs/{id}
+pub async fn maintenance_run_get(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(resp) = require_maintenance_admin(&req, &state) { return resp; }
+    let id = path.into_inner();
+    match crate::maintenance::load_runs().into_iter().find(|r| r.id == id) {
+        Some(run) => HttpResponse::Ok().json(run),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "Run not found" })),
+    }
+}
+
+/// POST /api/maintenance/reboot/begin — run the pre-hook, stop guests and
+/// reboot this node. Called by the node driving a reboot window.
+pub async fn maintenance_reboot_begin(req: HttpRequest, state: web::Data<AppState>, body: web::Json<crate::maintenance::BeginRequest>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(resp) => return resp };
+    if caller != "cluster-node" {
+        if caller.starts_with("apikey:") || !crate::auth::session_user_is_admin(&caller) {
+            return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Only admin users can reboot nodes" }));
+        }
+        if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    }
+    match crate::maintenance::begin(&state, body.into_inner()).await {
+        Ok(job) => HttpResponse::Ok().json(job),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RebootStatusQuery {
+    #[serde(default)]
+    pub job: String,
+}
+
+/// GET /api/maintenance/reboot/status?job= — this node's reboot job
+pub async fn maintenance_reboot_status(req: HttpRequest, state: web::Data<AppState>, query: web::Query<RebootStatusQuery>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match crate::maintenance::load_job().filter(|j| query.job.is_empty() || j.id == query.job) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "No such reboot job on this node" })),
+    }
+}
+
 /// DELETE /api/federation/tokens/{prefix} — revoke a token by its
 /// first-8-char prefix (the visible part in the list).
 pub async fn federation_tokens_revoke(
@@ -40431,6 +46055,7 @@ fn serde_qs_from_map(
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg
         .configure(crate::vms::api::config)
+        .configure(v1::configure)
         .configure(crate::tui::configure)
         .configure(crate::networking::router::api::configure)
         // Dangerous-op deadman switch endpoints. See src/danger.rs.
@@ -40442,6 +46067,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/system/deps/check",   web::get().to(system_deps_check))
         .route("/api/system/deps/install", web::post().to(system_deps_install))
         // Host mail relay (msmtp sendmail shim)
+        .route("/api/capacity/policy", web::get().to(capacity_policy_get))
+        .route("/api/capacity/policy", web::post().to(capacity_policy_set))
+        .route("/api/housekeeping", web::get().to(housekeeping_get))
+        .route("/api/housekeeping/config", web::post().to(housekeeping_set))
+        .route("/api/housekeeping/run", web::post().to(housekeeping_run))
         .route("/api/ups/status", web::get().to(ups_status))
         .route("/api/ups/config", web::post().to(ups_save_config))
         .route("/api/ups/poll",   web::post().to(ups_poll_now))
@@ -40449,6 +46079,9 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/mail-relay/enable",  web::post().to(mail_relay_enable))
         .route("/api/mail-relay/disable", web::post().to(mail_relay_disable))
         .route("/api/mail-relay/test",    web::post().to(mail_relay_test))
+        .route("/api/mail/diagnostics",   web::get().to(mail_diagnostics))
+        .route("/api/mail/outbox/retry",  web::post().to(mail_outbox_retry))
+        .route("/api/mail/outbox/{id}",   web::delete().to(mail_outbox_discard))
         // Fleet Logs (loghub) — cluster log aggregation / retention / search.
         .route("/api/logs/entitlement",    web::get().to(logs_entitlement))
         // Ingest carries log batches: lift the 2 MB Json default to 64 MB so
@@ -40468,6 +46101,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         // Auth (no auth required)
         .route("/api/auth/login", web::post().to(login))
         .route("/api/auth/logout", web::post().to(logout))
+        .route("/api/auth/reauth", web::post().to(reauth))
         .route("/api/auth/check", web::get().to(auth_check))
         .route("/api/auth/passkey/available", web::get().to(passkey_available))
         .route("/api/auth/passkey/register/start", web::post().to(passkey_register_start))
@@ -40494,6 +46128,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/settings/login-disabled", web::post().to(set_login_disabled))
         .route("/api/settings/site", web::post().to(set_site))
         .route("/api/settings/roles", web::post().to(set_roles))
+        .route("/api/settings/labels", web::post().to(set_labels))
         .route("/api/cluster/roles/available", web::get().to(cluster_roles_available))
         .route("/api/cluster/roles/summary", web::get().to(cluster_roles_summary))
         // WolfHost DNS tier — internal cluster-authed apply endpoints, fanned
@@ -40530,10 +46165,14 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/auth/users/{username}/2fa/confirm", web::post().to(confirm_2fa))
         .route("/api/auth/users/{username}/2fa/disable", web::post().to(disable_2fa))
         .route("/api/auth/users/{username}/clusters", web::put().to(update_user_clusters))
+        .route("/api/auth/users/{username}/tenant", web::put().to(update_user_tenant))
+        .route("/api/tenancy", web::get().to(tenancy_get))
+        .route("/api/tenancy/assign", web::post().to(tenancy_assign))
         // Dashboard
         .route("/api/metrics", web::get().to(get_metrics))
         .route("/api/metrics/history", web::get().to(get_metrics_history))
         .route("/api/metrics/processes", web::get().to(get_top_processes))
+        .route("/api/metrics/cache-services", web::get().to(get_cache_services))
         .route("/api/metrics/processes/{pid}/kill", web::post().to(kill_process))
         .route("/api/systemd", web::get().to(list_services))
         .route("/api/systemd/{name}/action", web::post().to(systemd_service_action))
@@ -40569,14 +46208,27 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/edge/cloudflare-tunnel/install/{proxy_id}", web::post().to(cloudflare_tunnel_install))
         .route("/api/cluster/wolfnet-sync", web::post().to(wolfnet_sync_cluster))
         .route("/api/cluster/diagnose", web::post().to(cluster_diagnose))
+        .route("/api/cluster/diagnose/ping", web::post().to(diagnose_ping))
+        .route("/api/cluster/diagnose/traceroute", web::post().to(diagnose_traceroute))
+        .route("/api/cluster/diagnose/mtr", web::post().to(diagnose_mtr))
+        .route("/api/cluster/diagnose/port", web::post().to(diagnose_port))
+        .route("/api/cluster/diagnose/dns", web::post().to(diagnose_dns))
+        .route("/api/cluster/diagnose/reach", web::post().to(diagnose_reach))
+        .route("/api/cluster/diagnose/facts", web::post().to(diagnose_facts))
         .route("/api/nodes", web::get().to(get_nodes))
         .route("/api/nodes", web::post().to(add_node))
         .route("/api/cluster/proxmox-cleanup", web::get().to(proxmox_cleanup_notice))
         .route("/api/cluster/proxmox-cleanup/dismiss", web::post().to(proxmox_cleanup_dismiss))
+        .route("/api/nodes/bulk", web::post().to(nodes_bulk))
+        .route("/api/nodes/archived", web::get().to(list_archived_nodes))
+        .route("/api/nodes/{id}/readdress", web::post().to(readdress::readdress_node))
+        .route("/api/nodes/{id}/archive", web::post().to(archive_node))
+        .route("/api/nodes/{id}/restore", web::post().to(restore_node))
         .route("/api/nodes/{id}", web::get().to(get_node))
         .route("/api/nodes/{id}", web::delete().to(remove_node))
         .route("/api/nodes/{id}/settings", web::patch().to(update_node_settings))
         // Proxmox integration
+        .route("/api/pve/clusters", web::get().to(pve_clusters))
         .route("/api/nodes/{id}/pve/resources", web::get().to(get_pve_resources))
         .route("/api/nodes/{id}/pve/test", web::post().to(pve_test_connection))
         .route("/api/nodes/{id}/pve/{vmid}/{action}", web::post().to(pve_guest_action))
@@ -40585,6 +46237,23 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/components/{name}/detail", web::get().to(get_component_detail))
         .route("/api/components/{name}/config", web::put().to(save_component_config))
         .route("/api/components/{name}/install", web::post().to(install_component))
+        .route("/api/components/versions", web::get().to(component_versions))
+        .route("/api/components/install-jobs", web::get().to(component_install_jobs))
+        .route("/api/components/install-jobs/{id}", web::get().to(component_install_job))
+        .route("/api/components/{name}/install-cluster", web::post().to(install_component_cluster))
+        .route("/api/components/{name}/upgrade", web::get().to(component_upgrade_info))
+        .route("/api/components/{name}/upgrade", web::post().to(component_upgrade))
+        .route("/api/components/{name}/rollback", web::post().to(component_rollback))
+        .route("/api/config-history", web::get().to(config_history_list))
+        .route("/api/config-history/file", web::get().to(config_history_file))
+        .route("/api/config-history/revision", web::get().to(config_history_revision))
+        .route("/api/config-history/diff", web::get().to(config_history_diff))
+        .route("/api/config-history/rollback", web::post().to(config_history_rollback))
+        .route("/api/drift", web::get().to(drift_get))
+        .route("/api/drift/scan", web::post().to(drift_scan))
+        .route("/api/drift/baselines", web::post().to(drift_pin))
+        .route("/api/drift/baselines", web::delete().to(drift_unpin))
+        .route("/api/drift/sync", web::post().to(drift_sync))
         .route("/api/install/{tech}", web::post().to(install_runtime))
         // Services
         .route("/api/services/{name}/action", web::post().to(service_action))
@@ -40626,6 +46295,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         // Containers
         .route("/api/containers/status", web::get().to(container_runtime_status))
         .route("/api/containers/{runtime}/{id}/web-url", web::get().to(container_web_url))
+        .route("/api/containers/{runtime}/{id}/mount-deps", web::get().to(container_mount_deps))
+        .route("/api/containers/{runtime}/{id}/mount-deps", web::put().to(container_mount_deps_set))
+        .route("/api/autostart", web::get().to(autostart_list))
+        .route("/api/autostart/{kind}/{name}", web::put().to(autostart_set))
         .route("/api/containers/install", web::post().to(install_container_runtime))
         .route("/api/containers/install-component", web::post().to(install_component_in_container))
         .route("/api/containers/running", web::get().to(list_running_containers))
@@ -40640,9 +46313,20 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/containers/docker/search", web::get().to(docker_search))
         .route("/api/containers/docker/pull", web::post().to(docker_pull))
         .route("/api/containers/docker/create", web::post().to(docker_create))
+        .route("/api/containers/docker/storage-quota", web::get().to(docker_storage_quota))
         .route("/api/containers/docker/stats", web::get().to(docker_stats))
         .route("/api/containers/docker/images", web::get().to(docker_images))
         .route("/api/containers/docker/images/{id}", web::delete().to(docker_remove_image))
+        .route("/api/containers/docker/cves", web::get().to(docker_image_cves))
+        .route("/api/containers/docker/cves/scan", web::post().to(docker_image_cves_scan))
+        .route("/api/containers/docker/build", web::post().to(docker_build))
+        .route("/api/containers/docker/builds", web::get().to(docker_builds_list))
+        .route("/api/containers/docker/builds/{id}", web::get().to(docker_build_status))
+        .route("/api/containers/docker/networks", web::get().to(docker_networks_list))
+        .route("/api/containers/docker/networks", web::post().to(docker_network_create))
+        .route("/api/containers/docker/networks/{name}", web::delete().to(docker_network_remove))
+        .route("/api/containers/docker/{id}/networks/connect", web::post().to(docker_network_connect))
+        .route("/api/containers/docker/{id}/networks/disconnect", web::post().to(docker_network_disconnect))
         .route("/api/containers/docker/{id}/logs", web::get().to(docker_logs))
         .route("/api/containers/docker/{id}/action", web::post().to(docker_action))
         .route("/api/containers/docker/{id}/clone", web::post().to(docker_clone))
@@ -40656,9 +46340,48 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         // LXC
         .route("/api/containers/lxc", web::get().to(lxc_list))
         .route("/api/containers/lxc/templates", web::get().to(lxc_templates))
+        .route("/api/containers/lxc/image-sources", web::get().to(lxc_image_sources_get))
+        .route("/api/containers/lxc/image-sources", web::post().to(lxc_image_sources_save))
+        .route("/api/containers/lxc/image-sources/custom", web::post().to(lxc_custom_image_add))
+        .route("/api/containers/lxc/image-sources/custom/{id}", web::delete().to(lxc_custom_image_remove))
+        .route("/api/containers/lxc/image-sources/cache", web::delete().to(lxc_image_cache_clear))
         .route("/api/containers/lxc/create", web::post().to(lxc_create))
         .route("/api/containers/lxc/import", web::post().to(lxc_import_endpoint))
         .route("/api/containers/lxc/import-external", web::post().to(lxc_import_external))
+        // Chunked node-to-node transfer (the archive half of a clone /
+        // migrate / template deploy). A chunk list for a very large archive
+        // runs past the 2 MB Json default; a chunk is capped at its maximum
+        // compressed size.
+        .service(
+            web::resource("/api/transfer/begin")
+                .app_data(web::JsonConfig::default().limit(64 * 1024 * 1024))
+                .route(web::post().to(transfer_begin)),
+        )
+        .service(
+            web::resource("/api/transfer/{id}/chunks/{hash}")
+                .app_data(web::PayloadConfig::new(crate::transfer::MAX_CHUNK + 64 * 1024))
+                .route(web::put().to(transfer_chunk)),
+        )
+        .route("/api/transfer/{id}/finish", web::post().to(transfer_finish))
+        // Container templates (golden images)
+        .route("/api/templates", web::get().to(templates_list))
+        .route("/api/templates", web::post().to(templates_create))
+        .route("/api/templates/settings", web::get().to(templates_settings_get))
+        .route("/api/templates/settings", web::post().to(templates_settings_save))
+        .route("/api/templates/{id}", web::delete().to(templates_delete))
+        .route("/api/templates/{id}/deploy", web::post().to(templates_deploy))
+        // Console command history & saved snippets
+        .route("/api/console/history", web::get().to(console_history))
+        .route("/api/console/snippets", web::get().to(console_snippets_list))
+        .route("/api/console/snippets", web::post().to(console_snippets_save))
+        .route("/api/console/snippets/{id}", web::delete().to(console_snippets_delete))
+        .route("/api/reports/inventory", web::get().to(reports_inventory))
+        .route("/api/reports/inventory/schedule", web::get().to(reports_inventory_schedule_get))
+        .route("/api/reports/inventory/schedule", web::put().to(reports_inventory_schedule_put))
+        .route("/api/reports/inventory/send", web::post().to(reports_inventory_send))
+        .route("/api/reports/availability", web::get().to(reports_availability))
+        .route("/api/reports/availability/history", web::get().to(reports_availability_history))
+        .route("/api/reports/capacity", web::get().to(reports_capacity))
         .route("/api/containers/transfer-token", web::post().to(generate_transfer_token))
         .route("/api/storage/list", web::get().to(storage_list))
         .route("/api/storage/filesystems", web::get().to(storage_filesystems))
@@ -40679,12 +46402,20 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/containers/lxc/{name}/parsed-config", web::get().to(lxc_parsed_config))
         .route("/api/containers/lxc/{name}/settings", web::post().to(lxc_update_settings))
         .route("/api/containers/lxc/{name}/export", web::post().to(lxc_export_endpoint))
+        .route("/api/containers/lxc/criu-check", web::get().to(lxc_criu_check))
         .route("/api/containers/lxc/{name}/migrate", web::post().to(lxc_migrate))
         .route("/api/containers/lxc/{name}/disk", web::get().to(lxc_disk_info))
         .route("/api/containers/lxc/{name}/disk/resize", web::post().to(lxc_disk_resize))
         .route("/api/containers/lxc/{name}/disk/migrate", web::post().to(lxc_disk_migrate))
         .route("/api/containers/lxc/{name}/migrate-external", web::post().to(lxc_migrate_external))
+        .route("/api/migration", web::get().to(migration_list))
+        .route("/api/jobs", web::get().to(jobs_status))
+        .route("/api/jobs/limits", web::put().to(jobs_set_limits))
+        .route("/api/snmp", web::get().to(snmp_get))
+        .route("/api/snmp", web::put().to(snmp_set))
+        .route("/api/snmp/mib", web::get().to(snmp_mib))
         .route("/api/migration/{id}/status", web::get().to(migration_status))
+        .route("/api/migration/{id}/bwlimit", web::post().to(migration_bwlimit))
         // Network Conflicts
         .route("/api/network/conflicts", web::get().to(network_conflicts))
         // WolfNet
@@ -40829,12 +46560,26 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/storage/mounts/{id}/unmount", web::post().to(storage_do_unmount))
         .route("/api/storage/mounts/{id}/sync", web::post().to(storage_sync_mount))
         .route("/api/storage/mounts/{id}/sync-s3", web::post().to(storage_sync_s3))
+        .route("/api/storage/mounts/{id}/objects", web::get().to(storage_s3_list))
+        .route("/api/storage/mounts/{id}/objects", web::put().to(storage_s3_upload))
+        .route("/api/storage/mounts/{id}/objects", web::delete().to(storage_s3_delete))
+        .route("/api/storage/mounts/{id}/objects/download", web::get().to(storage_s3_download))
+        .route("/api/storage/mounts/{id}/objects/presign", web::post().to(storage_s3_presign))
         .route("/api/storage/providers", web::get().to(storage_list_providers))
         .route("/api/storage/providers/{name}/install", web::post().to(storage_install_provider))
         .route("/api/storage/providers/{name}/action", web::post().to(storage_provider_action))
         .route("/api/storage/providers/{name}/config", web::get().to(storage_provider_config))
         .route("/api/storage/providers/{name}/config", web::post().to(storage_provider_config_save))
         .route("/api/system/logs", web::get().to(system_logs))
+        .route("/api/system/api-stats", web::get().to(api_stats))
+        .route("/api/system/api-stats", web::delete().to(api_stats_reset))
+        .route("/api/system/inventory", web::get().to(inventory_status))
+        .route("/api/hardware", web::get().to(hardware_inventory))
+        .route("/api/system/selfcheck", web::get().to(system_selfcheck))
+        .route("/api/system/crash-reports", web::get().to(crash_reports))
+        .route("/api/system/crash-reports", web::delete().to(crash_reports_clear))
+        .route("/api/system/crash-reports/{id}", web::get().to(crash_report_get))
+        .route("/metrics", web::get().to(prometheus_metrics))
         // Cluster-internal file operations — require cluster-secret auth.
         // Used by WolfAgent tools to read/write/delete files across the
         // cluster; never reachable via a human session token.
@@ -40846,6 +46591,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/storage/disk-info", web::get().to(storage_disk_info))
         .route("/api/storage/wolfdisk/status", web::get().to(storage_wolfdisk_status))
         .route("/api/storage/wolfdisk/dedicate-disk", web::post().to(disk_dedicate_wolfdisk))
+        .route("/api/storage/wolfdisk/volumes", web::get().to(wolfdisk_volumes))
+        .route("/api/storage/wolfdisk/volumes", web::post().to(wolfdisk_volume_create))
+        .route("/api/storage/wolfdisk/volumes/{name}", web::delete().to(wolfdisk_volume_delete))
+        .route("/api/storage/wolfdisk/volumes/{name}/attach", web::post().to(wolfdisk_volume_attach))
+        .route("/api/storage/wolfdisk/volumes/{name}/detach", web::post().to(wolfdisk_volume_detach))
         // Disk Partitioning & Formatting
         .route("/api/storage/disk/partition-table", web::post().to(disk_create_partition_table))
         .route("/api/storage/disk/partition", web::post().to(disk_create_partition))
@@ -40870,11 +46620,17 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/files/rename", web::post().to(files_rename))
         .route("/api/files/upload", web::post().to(files_upload))
         .route("/api/files/download", web::get().to(files_download))
+        .route("/api/files/archive", web::get().to(files_archive))
+        .route("/api/files/upload/chunk", web::get().to(files_upload_status))
+        .route("/api/files/upload/chunk", web::post().to(files_upload_chunk))
+        .route("/api/files/upload/chunk", web::delete().to(files_upload_abort))
         .route("/api/files/search", web::get().to(files_search))
         .route("/api/files/chmod", web::post().to(files_chmod))
         .route("/api/files/read", web::get().to(files_read))
         .route("/api/files/write", web::post().to(files_write))
         // Docker File Manager
+        .route("/api/files/docker/volumes", web::get().to(files_docker_volumes))
+        .route("/api/files/lxc/rootfs", web::get().to(files_lxc_rootfs))
         .route("/api/files/docker/browse", web::get().to(files_docker_browse))
         .route("/api/files/docker/mkdir", web::post().to(files_docker_mkdir))
         .route("/api/files/docker/delete", web::post().to(files_docker_delete))
@@ -40903,6 +46659,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/networking/dns", web::get().to(net_get_dns))
         .route("/api/networking/dns", web::post().to(net_set_dns))
         .route("/api/networking/wolfnet", web::get().to(net_get_wolfnet))
+        .route("/api/networking/wolfnet/health", web::get().to(net_wolfnet_health))
         .route("/api/networking/wolfnet/config", web::get().to(net_get_wolfnet_config))
         .route("/api/networking/wolfnet/config", web::put().to(net_save_wolfnet_config))
         .route("/api/networking/wolfnet/peers", web::post().to(net_add_wolfnet_peer))
@@ -40926,6 +46683,8 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/networking/ip-mappings/{id}", web::put().to(net_update_ip_mapping))
         .route("/api/networking/available-ips", web::get().to(net_available_ips))
         .route("/api/networking/listening-ports", web::get().to(net_listening_ports))
+        .route("/api/networking/connections", web::get().to(net_connections))
+        .route("/api/networking/capture", web::post().to(net_capture))
         // WireGuard bridge
         .route("/api/networking/wireguard", web::get().to(net_wireguard_list))
         .route("/api/networking/wireguard/{cluster}", web::get().to(net_wireguard_get))
@@ -40946,8 +46705,12 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         // Brute-force lockout management
         .route("/api/security/auth-config", web::get().to(security_auth_config_get))
         .route("/api/security/auth-config", web::post().to(security_auth_config_set))
+        .route("/api/security/session-policy", web::get().to(security_session_policy_get))
+        .route("/api/security/session-policy", web::post().to(security_session_policy_set))
         .route("/api/security/auth-lockouts", web::get().to(security_auth_lockouts))
         .route("/api/security/auth-unblock", web::post().to(security_auth_unblock))
+        .route("/api/security/attacks", web::get().to(security_attacks))
+        .route("/api/security/attacks/block", web::post().to(security_attacks_block))
         .route("/api/security/gandalf", web::get().to(gandalf_get))
         .route("/api/security/gandalf", web::post().to(gandalf_set))
         .route("/api/security/auth-unblock-peer", web::post().to(security_auth_unblock_peer))
@@ -41084,10 +46847,20 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/backups/pbs/config/full", web::get().to(pbs_config_get_full))
         .route("/api/backups/pbs/config", web::get().to(pbs_config_get))
         .route("/api/backups/pbs/config", web::post().to(pbs_config_save))
+        .route("/api/backups/pbs/datastore", web::get().to(pbs_datastore))
+        .route("/api/backups/pbs/datastore/gc-schedule", web::put().to(pbs_set_gc_schedule))
+        .route("/api/backups/pbs/prune-jobs", web::post().to(pbs_save_prune_job))
+        .route("/api/backups/pbs/prune-jobs/{id}", web::delete().to(pbs_delete_prune_job))
+        .route("/api/backups/pbs/jobs", web::post().to(pbs_run_job))
+        .route("/api/backups/pbs/tasks", web::get().to(pbs_task_status))
+        .route("/api/backups/pbs/files", web::get().to(pbs_files))
+        .route("/api/backups/pbs/files/restore", web::post().to(pbs_files_restore))
         // Generic backup {id} routes — after specific routes
         .route("/api/backups/{id}", web::delete().to(backup_delete))
         .route("/api/backups/{id}/restore/stream", web::post().to(backup_restore_stream))
         .route("/api/backups/{id}/restore", web::post().to(backup_restore))
+        .route("/api/backups/{id}/files", web::get().to(backup_files))
+        .route("/api/backups/{id}/files/restore", web::post().to(backup_files_restore))
         // Console WebSocket
         .route("/ws/console/{type}/{name}", web::get().to(crate::console::console_ws))
         // Remote Console WebSocket proxy (bridges browser ↔ remote node's console)
@@ -41124,8 +46897,15 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/user/preferences", web::get().to(user_prefs_get))
         .route("/api/user/preferences", web::post().to(user_prefs_save))
         .route("/api/user/preferences", web::patch().to(user_prefs_patch))
+        .route("/api/preferences", web::get().to(preferences_get))
+        .route("/api/preferences", web::put().to(preferences_put))
         // Lightweight liveness probe for the connection-loss banner
         .route("/api/ping", web::get().to(ping))
+        // Unauthenticated load-balancer probes (see crate::lifecycle)
+        .route("/healthz", web::get().to(healthz))
+        .route("/readyz", web::get().to(readyz))
+        .route("/api/openapi.json", web::get().to(openapi_json))
+        .route("/api/openapi/client.ts", web::get().to(openapi_client_ts))
         // Home-dashboard widget fetch proxy (RSS / weather — no CORS upstream)
         .route("/api/dashboard/fetch-proxy", web::get().to(dashboard_fetch_proxy))
         // Per-user interface lock (idle lock + PIN)
@@ -41161,11 +46941,19 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/mysql/data", web::post().to(mysql_data))
         .route("/api/mysql/query", web::post().to(mysql_query))
         .route("/api/mysql/dump", web::post().to(mysql_dump))
+        .route("/api/mysql/overview", web::post().to(mysql_overview))
+        .route("/api/mysql/users", web::post().to(mysql_users))
+        .route("/api/mysql/slow-log", web::post().to(mysql_slow_log))
         // Agent (cluster-secret auth — inter-node communication)
         .route("/api/agent/status", web::get().to(agent_status))
         .route("/api/agent/storage/apply", web::post().to(agent_storage_apply))
         .route("/api/ports", web::get().to(get_ports))
         .route("/api/ports", web::post().to(set_ports))
+        .route("/api/daemon-config", web::get().to(daemon_config_get))
+        .route("/api/daemon-config", web::put().to(daemon_config_put))
+        .route("/api/daemon-config/reload", web::post().to(daemon_config_reload))
+        .route("/api/logging", web::get().to(logging_get))
+        .route("/api/logging", web::put().to(logging_update))
         .route("/api/reverse-proxy/config", web::get().to(reverse_proxy_config_get))
         .route("/api/reverse-proxy/config", web::post().to(reverse_proxy_config_save))
         .route("/api/certs", web::get().to(certs_list))
@@ -41211,10 +46999,21 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/cluster-home", web::get().to(cluster_browser_homepage))
         .route("/api/agent/cluster-name", web::post().to(agent_set_cluster_name))
         .route("/api/agent/display-name", web::post().to(agent_set_display_name))
+        .route("/api/agent/readdress", web::post().to(readdress::agent_readdress))
+        .route("/api/agent/readdress/challenge", web::get().to(readdress::readdress_challenge))
         .route("/api/clusters/{old}/rename", web::post().to(cluster_rename_handler))
         .route("/api/agent/wolfnet-routes", web::post().to(agent_set_wolfnet_routes))
         .route("/api/wolfnet/used-ips", web::get().to(wolfnet_used_ips_endpoint))
         .route("/api/wolfnet/active-ips", web::get().to(wolfnet_active_ips_endpoint))
+        .route("/api/wolfnet/ipam", web::get().to(wolfnet_ipam_get))
+        .route("/api/wolfnet/ipam/reservations", web::post().to(wolfnet_ipam_reserve))
+        .route("/api/wolfnet/ipam/reservations/{ip}", web::delete().to(wolfnet_ipam_unreserve))
+        .route("/api/wolfnet/ipam/pools/{prefix}", web::put().to(wolfnet_ipam_set_pool))
+        .route("/api/wolfnet/ipam/pools/{prefix}", web::delete().to(wolfnet_ipam_reset_pool))
+        .route("/api/wolfnet/ipam/subnets/{prefix}", web::put().to(wolfnet_ipam_set_subnet))
+        .route("/api/wolfnet/ipam/subnets/{prefix}", web::delete().to(wolfnet_ipam_remove_subnet))
+        .route("/api/wolfnet/ipam/grant", web::post().to(wolfnet_ipam_grant))
+        .route("/api/wolfnet/ipam/sync", web::post().to(wolfnet_ipam_sync))
         .route("/api/wolfnet/routes", web::get().to(wolfnet_routes_debug))
         .route("/api/wolfnet/routes/announce", web::post().to(wolfnet_routes_announce))
         // Geolocation proxy (ip-api.com is HTTP-only, browsers block mixed content on HTTPS pages)
@@ -41285,12 +47084,18 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/config/backups/{name}", web::get().to(config_backup_download))
         .route("/api/config/backups/{name}", web::delete().to(config_backup_delete))
         .route("/api/config/backups/{name}/restore", web::post().to(config_backup_restore))
+        .route("/api/config/system-backup", web::get().to(config_system_backup))
+        .route("/api/config/system-restore", web::post().to(config_system_restore))
         .route("/api/upgrade", web::post().to(system_upgrade))
         .route("/api/upgrade/log", web::get().to(system_upgrade_log))
         // Issues Scanner
         .route("/api/issues/scan", web::get().to(scan_issues))
+        .route("/api/issues/history", web::get().to(issue_history))
         .route("/api/issues/clean", web::post().to(clean_system))
         .route("/api/issues/repair", web::post().to(repair_issue))
+        .route("/api/issues/checks", web::get().to(list_issue_checks))
+        .route("/api/issues/checks", web::post().to(set_issue_check))
+        .route("/api/ws/events", web::get().to(event_stream::ws_events))
         .route("/api/alerts", web::get().to(get_alert_log))
         // v24.0.0 — paginated, filterable history view used by the
         // new Alerts page. Reads the same in-memory alert_log as the
@@ -41407,11 +47212,15 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/wolfrun/sync", web::post().to(wolfrun_sync))
         .route("/api/wolfrun/failover-events", web::get().to(wolfrun_failover_events))
         .route("/api/wolfrun/reconcile", web::post().to(wolfrun_reconcile))
+        .route("/api/wolfrun/autoscale", web::get().to(wolfrun_autoscale_list))
+        .route("/api/wolfrun/autoscale/events", web::get().to(wolfrun_autoscale_events))
         .route("/api/wolfrun/services/{id}", web::get().to(wolfrun_get))
         .route("/api/wolfrun/services/{id}", web::delete().to(wolfrun_delete))
         .route("/api/wolfrun/services/{id}/scale", web::post().to(wolfrun_scale))
         .route("/api/wolfrun/services/{id}/action", web::post().to(wolfrun_service_action))
         .route("/api/wolfrun/services/{id}/settings", web::post().to(wolfrun_settings))
+        .route("/api/wolfrun/services/{id}/autoscale", web::put().to(wolfrun_autoscale_set))
+        .route("/api/wolfrun/services/{id}/autoscale", web::delete().to(wolfrun_autoscale_delete))
         .route("/api/wolfrun/services/{id}/update", web::post().to(wolfrun_update))
         .route("/api/wolfrun/services/{id}/portforward", web::get().to(wolfrun_portforward_list))
         .route("/api/wolfrun/services/{id}/portforward", web::post().to(wolfrun_portforward_add))
@@ -41431,6 +47240,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/configurator/nginx/error-log", web::get().to(nginx_error_log))
         .route("/api/configurator/nginx/generate", web::post().to(nginx_generate_config))
         .route("/api/configurator/nginx/bootstrap", web::post().to(nginx_bootstrap))
+        .route("/api/configurator/nginx/publish", web::post().to(nginx_publish_site))
         // Apache (WolfServe)
         .route("/api/configurator/apache/sites", web::get().to(apache_list_sites))
         .route("/api/configurator/apache/sites", web::post().to(apache_create_site))
@@ -41547,6 +47357,22 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         // cloud-init has no session cookie and the upstream
         // artefact is public anyway. 5-min cache.
         .route("/api/install/setup.sh", web::get().to(install_setup_sh))
+        // Bare-metal provisioning. The boot/ routes are public and
+        // token-gated — netbooting firmware has no session.
+        .route("/api/provision/profiles", web::get().to(provision_profiles_list))
+        .route("/api/provision/profiles", web::post().to(provision_profile_save))
+        .route("/api/provision/profiles/{id}", web::delete().to(provision_profile_delete))
+        .route("/api/provision/profiles/{id}/rotate", web::post().to(provision_profile_rotate))
+        .route("/api/provision/boot/{token}/enroll", web::post().to(provision_enroll))
+        .route("/api/provision/boot/{token}/{file}", web::get().to(provision_artifact))
+        // Reboot windows
+        .route("/api/maintenance/windows", web::get().to(maintenance_windows_list))
+        .route("/api/maintenance/windows", web::post().to(maintenance_window_save))
+        .route("/api/maintenance/windows/{id}", web::delete().to(maintenance_window_delete))
+        .route("/api/maintenance/windows/{id}/run", web::post().to(maintenance_window_run))
+        .route("/api/maintenance/runs/{id}", web::get().to(maintenance_run_get))
+        .route("/api/maintenance/reboot/begin", web::post().to(maintenance_reboot_begin))
+        .route("/api/maintenance/reboot/status", web::get().to(maintenance_reboot_status))
         // Platform calibration & access tokens
         .route("/api/platform/status", web::get().to(platform_status))
         .route("/api/platform/apply", web::post().to(platform_apply))
@@ -41560,6 +47386,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/secrets", web::post().to(secrets_save))
         .route("/api/secrets/{key}", web::get().to(secrets_get))
         .route("/api/secrets/{key}", web::delete().to(secrets_delete))
+        .route("/api/env-store", web::get().to(env_store_list))
+        .route("/api/env-store/{scope}", web::get().to(env_store_get))
+        .route("/api/env-store/{scope}", web::put().to(env_store_set))
+        .route("/api/env-store/{scope}", web::delete().to(env_store_delete))
         // Wolfram — memory compression daemon
         .route("/api/wolfram/status", web::get().to(wolfram_status))
         .route("/api/wolfram/install", web::post().to(wolfram_install))
@@ -41587,6 +47417,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/statuspage/monitors", web::get().to(statuspage_monitors_list))
         .route("/api/statuspage/monitors", web::post().to(statuspage_monitor_save))
         .route("/api/statuspage/monitors/{id}", web::delete().to(statuspage_monitor_delete))
+        .route("/api/statuspage/monitors/{id}/history", web::get().to(statuspage_monitor_history))
         .route("/api/statuspage/pages", web::get().to(statuspage_pages_list))
         .route("/api/statuspage/pages", web::post().to(statuspage_page_save))
         .route("/api/statuspage/pages/{id}", web::delete().to(statuspage_page_delete))
@@ -41594,6 +47425,15 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/statuspage/incidents", web::post().to(statuspage_incident_save))
         .route("/api/statuspage/incidents/{id}", web::delete().to(statuspage_incident_delete))
         .route("/api/statuspage/sync", web::post().to(statuspage_sync))
+        .route("/api/statuspage/results", web::post().to(statuspage_results))
+        .route("/api/events/types", web::get().to(events_types))
+        .route("/api/webhooks", web::get().to(webhooks_list))
+        .route("/api/webhooks", web::post().to(webhooks_create))
+        .route("/api/webhooks/sync", web::post().to(webhooks_sync))
+        .route("/api/webhooks/{id}", web::put().to(webhooks_update))
+        .route("/api/webhooks/{id}", web::delete().to(webhooks_delete))
+        .route("/api/webhooks/{id}/test", web::post().to(webhooks_test))
+        .route("/api/webhooks/{id}/deliveries", web::get().to(webhooks_deliveries))
         // Patreon integration
         .route("/api/patreon/connect", web::get().to(patreon_connect))
         .route("/api/patreon/callback", web::get().to(patreon_callback))
@@ -41645,7 +47485,8 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/api/nodes/{id}/ips", web::get().to(node_ips_list))
         // Status Page (public — NO auth)
         .route("/status", web::get().to(statuspage_public_index))
-        .route("/status/{slug}", web::get().to(statuspage_public_page));
+        .route("/status/{slug}", web::get().to(statuspage_public_page))
+        .route("/status/{slug}/status.json", web::get().to(statuspage_public_json));
 }
 
 // ═══════════════════════════════════════════════════
@@ -42291,7 +48132,7 @@ async fn enumerate_local_ips(state: &web::Data<AppState>) -> Vec<serde_json::Val
 
     // VMs — only WolfNet IP is known without guest-agent probing,
     // which is fine for DB hosting (typically one WolfNet IP per VM).
-    let vms = state.vms.lock().unwrap().list_vms();
+    let vms = crate::monitoring::inventory::vms(state).await;
     for vm in vms {
         if let Some(ip) = &vm.wolfnet_ip {
             if !ip.trim().is_empty() {
@@ -42475,12 +48316,20 @@ pub async fn statuspage_public_page_dedicated(state: web::Data<AppState>, path:
     }
 }
 
+/// GET /status/{slug}/status.json on the dedicated port — slug-only lookup,
+/// as `statuspage_public_page_dedicated`.
+pub async fn statuspage_public_json_dedicated(state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let page = state.statuspage.find_page_by_slug(&path.into_inner());
+    statuspage_json_response(&state, page)
+}
+
 /// Minimal config for the dedicated status page HTTP listener (port 8550).
 pub fn configure_statuspage_only(cfg: &mut web::ServiceConfig) {
     cfg
         .route("/", web::get().to(statuspage_public_index))
         .route("/status", web::get().to(statuspage_public_index))
-        .route("/status/{slug}", web::get().to(statuspage_public_page_dedicated));
+        .route("/status/{slug}", web::get().to(statuspage_public_page_dedicated))
+        .route("/status/{slug}/status.json", web::get().to(statuspage_public_json_dedicated));
 }
 
 #[cfg(test)]
@@ -43329,3 +49178,146 @@ mod autofix_command_tests {
         assert_eq!(AUTOFIX_FINDING_TYPES.len(), 2);
     }
 }
+
+#[cfg(test)]
+mod typed_prefs_tests {
+    use super::{typed_prefs_patch, typed_prefs_view};
+    use serde_json::json;
+
+    #[test]
+    fn view_decodes_spa_encoded_values() {
+        let stored = json!({
+            "wolfstack-theme": "midnight",
+            "wolfstack_dashboard": "{\"widgets\":[{\"type\":\"clock\"}]}",
+            "wolfstack_favourite_containers": "not json",
+            "wolfstack_bookmarks": "[]"
+        });
+        let view = typed_prefs_view(stored.as_object().unwrap());
+        assert_eq!(view["theme"], "midnight");
+        assert_eq!(view["dashboard_layout"]["widgets"][0]["type"], "clock");
+        assert!(view["favourite_containers"].is_null());
+        assert!(view["default_node"].is_null());
+        assert!(view.get("bookmarks").is_none());
+    }
+
+    #[test]
+    fn patch_encodes_and_validates() {
+        let patch = typed_prefs_patch(&json!({
+            "default_node": "node-a",
+            "favourite_containers": [{ "node_id": "node-a", "runtime": "docker", "name": "web" }],
+            "theme": null
+        })).unwrap();
+        assert_eq!(patch["wolfstack_default_node"], "node-a");
+        assert!(patch["wolfstack_favourite_containers"].as_str().unwrap().contains("\"web\""));
+        assert!(patch["wolfstack-theme"].is_null());
+
+        assert!(typed_prefs_patch(&json!({ "bookmarks": [] })).unwrap_err().contains("Unknown"));
+        assert!(typed_prefs_patch(&json!({ "theme": 3 })).is_err());
+        assert!(typed_prefs_patch(&json!({ "dashboard_layout": { "cols": 3 } })).is_err());
+        assert!(typed_prefs_patch(&json!({ "favourite_containers": [{ "name": "web" }] })).is_err());
+    }
+}
+
+#[cfg(test)]
+mod openapi_tests {
+    use super::OPENAPI_JSON;
+
+    fn spec() -> serde_json::Value {
+        serde_json::from_str(OPENAPI_JSON).expect("generated OpenAPI document is valid JSON")
+    }
+
+    #[test]
+    fn covers_scoped_and_multiline_routes() {
+        let spec = spec();
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/openapi.json"));
+        // web::scope("/api/vms") routes get their prefix.
+        assert!(paths["/api/vms"]["get"].is_object());
+        // Routes whose `.to(…)` sits on the next line.
+        assert!(paths["/api/cluster/secret/rotate-commit"]["post"].is_object());
+        assert_eq!(paths["/api/webhooks/{id}"]["put"]["parameters"][0]["name"], "id");
+    }
+
+    #[test]
+    fn request_bodies_and_queries_are_typed() {
+        let spec = spec();
+        let op = &spec["paths"]["/api/webhooks"]["post"];
+        assert_eq!(op["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/WebhookUpsertBody");
+        let schema = &spec["components"]["schemas"]["WebhookUpsertBody"];
+        assert_eq!(schema["properties"]["events"]["type"], "array");
+        assert_eq!(schema["properties"]["rotate_secret"]["type"], "boolean");
+        let params = spec["paths"]["/api/reports/capacity"]["get"]["parameters"].as_array().unwrap();
+        assert!(params.iter().any(|p| p["name"] == "scope" && p["in"] == "query"));
+    }
+
+    #[test]
+    fn every_ref_resolves() {
+        let spec = spec();
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        let text = OPENAPI_JSON;
+        let prefix = "#/components/schemas/";
+        for (i, _) in text.match_indices(prefix) {
+            let name: String = text[i + prefix.len()..].chars().take_while(|c| *c != '"').collect();
+            assert!(schemas.contains_key(&name), "dangling $ref {}", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod migration_progress_tests {
+    use super::*;
+
+    #[test]
+    fn rate_and_eta_follow_byte_counts() {
+        let tasks: MigrationTasks = Default::default();
+        let id = migration_create(&tasks);
+        migration_update(&tasks, &id, "upload", "Transferring…");
+        migration_progress(&tasks, &id, Some(0), Some(10_000_000), None);
+        {
+            // Pretend the first sample was two seconds ago.
+            let mut map = tasks.write().unwrap();
+            let task = map.get_mut(&id).unwrap();
+            task.rate_sample = Some((std::time::Instant::now() - std::time::Duration::from_secs(2), 0));
+        }
+        migration_progress(&tasks, &id, Some(2_000_000), None, None);
+        let task = tasks.read().unwrap()[&id].clone();
+        let rate = task.rate_bps.unwrap();
+        assert!((990_000..=1_000_000).contains(&rate), "rate {}", rate);
+        assert_eq!(task.eta_secs, Some(8_000_000 / rate));
+
+        // A new stage starts from a blank rate.
+        migration_update(&tasks, &id, "start", "Starting…");
+        assert!(tasks.read().unwrap()[&id].rate_bps.is_none());
+    }
+
+    #[test]
+    fn bwlimit_reaches_the_running_transfer() {
+        let tasks: MigrationTasks = Default::default();
+        let id = migration_create(&tasks);
+        let throttle = migration_throttle(&tasks, &id);
+        assert!(migration_set_bwlimit(&tasks, &id, Some(80)));
+        assert_eq!(throttle.load(std::sync::atomic::Ordering::Relaxed), 10_000_000);
+        assert!(migration_set_bwlimit(&tasks, &id, Some(0)));
+        assert_eq!(throttle.load(std::sync::atomic::Ordering::Relaxed), 0);
+        assert_eq!(tasks.read().unwrap()[&id].bwlimit_mbps, None);
+        assert!(!migration_set_bwlimit(&tasks, "mig_missing", Some(10)));
+    }
+}
+
+#[cfg(test)]
+mod sensitive_path_tests {
+    use super::{holds_sensitive_path, is_sensitive_path};
+    use std::path::Path;
+
+    #[test]
+    fn archives_of_parents_of_secrets_are_refused() {
+        assert!(is_sensitive_path(Path::new("/etc/wolfstack/cluster_secret")));
+        assert!(!is_sensitive_path(Path::new("/etc/shadowsocks")));
+        assert!(holds_sensitive_path(Path::new("/etc")));
+        assert!(holds_sensitive_path(Path::new("/root")));
+        assert!(holds_sensitive_path(Path::new("/etc/wolfstack")));
+        assert!(!holds_sensitive_path(Path::new("/etc/nginx")));
+        assert!(!holds_sensitive_path(Path::new("/srv")));
+    }
+}
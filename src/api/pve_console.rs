@@ -34,8 +34,12 @@ pub async fn pve_vnc_ticket(
     state: web::Data<AppState>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, Error> {
-    if let Err(resp) = super::require_auth(&req, &state) { return Ok(resp); }
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
     let vmid_str = path.into_inner();
+    // Proxmox guests aren't tenant resources.
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, "pve", &vmid_str) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
     let vmid: u64 = match vmid_str.parse() {
         Ok(v) => v,
         Err(_) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid VMID" }))),
@@ -127,8 +131,12 @@ pub async fn pve_vnc_ws(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    if let Err(resp) = super::require_auth(&req, &state) { return Ok(resp); }
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
     let vmid_str = path.into_inner();
+    // Proxmox guests aren't tenant resources.
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, "pve", &vmid_str) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
     let vmid: u64 = match vmid_str.parse() {
         Ok(v) => v,
         Err(_) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "Invalid VMID" }))),
@@ -239,8 +247,11 @@ pub async fn vm_vnc_ws(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    if let Err(resp) = super::require_auth(&req, &state) { return Ok(resp); }
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
     let vm_name = path.into_inner();
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, "vm", &vm_name) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
 
     // Pick the connection strategy from the VmConfig. Prefer ws_port
     // when present (native QEMU), fall back to raw TCP vnc_port
@@ -353,9 +364,13 @@ pub async fn pve_console_ws(
     path: web::Path<(String, String)>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    if let Err(resp) = super::require_auth(&req, &state) { return Ok(resp); }
+    let caller = match super::require_auth(&req, &state) { Ok(u) => u, Err(resp) => return Ok(resp) };
 
     let (node_id, vmid_str) = path.into_inner();
+    // Proxmox guests aren't tenant resources.
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &caller, "pve", &vmid_str) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
 
     let vmid: u64 = match vmid_str.parse() {
         Ok(v) => v,
@@ -0,0 +1,262 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Node re-address endpoints — see `crate::agent::readdress`.
+//!
+//! `POST /api/nodes/{id}/readdress` runs the whole flow from the node the
+//! operator is logged into: check the new address from here, have every
+//! online peer check it too (`verify_only`), then commit here and on each
+//! peer through `POST /api/agent/readdress`. A peer that fails to commit
+//! rolls everyone back to the old address, so the cluster never ends up
+//! split between the two.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use super::{build_node_urls, require_auth, require_cluster_auth, require_recent_auth, AppState, API_HTTP_CLIENT};
+use crate::agent::readdress;
+
+#[derive(Deserialize)]
+pub struct ChallengeQuery {
+    pub nonce: String,
+}
+
+/// GET /api/agent/readdress/challenge?nonce= — prove this node holds the
+/// cluster secret without sending it. No auth: the caller is checking an
+/// address it doesn't trust yet.
+pub async fn readdress_challenge(state: web::Data<AppState>, query: web::Query<ChallengeQuery>) -> HttpResponse {
+    if query.nonce.len() < 16 || query.nonce.len() > 128 {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "nonce must be 16-128 characters" }));
+    }
+    let self_id = state.cluster.self_id.clone();
+    HttpResponse::Ok().json(serde_json::json!({
+        "self_id": self_id,
+        "proof": readdress::proof(&state.cluster_secret, &query.nonce, &self_id),
+    }))
+}
+
+/// Check that `address:port` answers as node `self_id` of this cluster.
+async fn probe(state: &AppState, address: &str, port: u16, self_id: &str) -> Result<(), String> {
+    use rand::RngCore;
+    use subtle::ConstantTimeEq;
+    let mut buf = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut buf);
+    let nonce = hex::encode(buf);
+    let host = crate::netaddr::bracket_host(address);
+    let mut last_err = String::from("no answer");
+    // Only the address itself — not build_node_urls, whose WolfNet fallback
+    // would pass without the new address being reachable at all.
+    for scheme in ["https", "http"] {
+        let url = format!("{}://{}:{}/api/agent/readdress/challenge?nonce={}", scheme, host, port, nonce);
+        let resp = match API_HTTP_CLIENT.get(&url).timeout(std::time::Duration::from_secs(5)).send().await {
+            Ok(r) => r,
+            Err(e) => { last_err = e.to_string(); continue; }
+        };
+        if !resp.status().is_success() {
+            last_err = format!("HTTP {} (WolfStack too old for re-address?)", resp.status().as_u16());
+            continue;
+        }
+        let data: serde_json::Value = resp.json().await.unwrap_or_default();
+        let got_id = data.get("self_id").and_then(|v| v.as_str()).unwrap_or("");
+        if got_id != self_id {
+            return Err(format!("{}:{} is a different node ({})", address, port, if got_id.is_empty() { "not WolfStack" } else { got_id }));
+        }
+        let got = data.get("proof").and_then(|v| v.as_str()).unwrap_or("");
+        let want = readdress::proof(&state.cluster_secret, &nonce, self_id);
+        if !bool::from(got.as_bytes().ct_eq(want.as_bytes())) {
+            return Err(format!("{}:{} answered as {} but isn't in this cluster", address, port, self_id));
+        }
+        return Ok(());
+    }
+    Err(format!("{}:{} is not reachable: {}", address, port, last_err))
+}
+
+/// Body of `POST /api/agent/readdress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentReaddress {
+    /// Global self_id of the node being re-addressed.
+    pub self_id: String,
+    pub old_address: String,
+    pub old_port: u16,
+    pub address: String,
+    pub port: u16,
+    /// Only check the new address from here; change nothing.
+    #[serde(default)]
+    pub verify_only: bool,
+}
+
+/// Apply a re-address to this node's own state: WolfNet first (the part
+/// that can fail), then the membership records, pinned against gossip.
+/// Returns how many records changed.
+fn apply_local(state: &AppState, r: &AgentReaddress) -> Result<usize, String> {
+    let ids = readdress::matching_records(&state.cluster.get_all_nodes(), &r.self_id, &r.old_address, r.old_port);
+    let hostnames: std::collections::BTreeSet<String> = ids.iter()
+        .filter_map(|id| state.cluster.get_node(id))
+        .map(|n| n.hostname)
+        .filter(|h| !h.is_empty())
+        .collect();
+    if r.address != r.old_address {
+        for h in &hostnames {
+            crate::networking::readdress_wolfnet_peer(h, &r.old_address, &r.address)
+                .map_err(|e| format!("WolfNet peer '{}': {}", h, e))?;
+        }
+    }
+    for id in &ids {
+        state.cluster.update_node_settings(
+            id, None, Some(r.address.clone()), Some(r.port), None, None, None, None, None, None, None,
+        );
+        readdress::pin(id, &r.address, r.port);
+    }
+    Ok(ids.len())
+}
+
+/// POST /api/agent/readdress — one peer's part of a re-address, sent by
+/// the node running it. Cluster-secret only.
+pub async fn agent_readdress(req: HttpRequest, state: web::Data<AppState>, body: web::Json<AgentReaddress>) -> HttpResponse {
+    if let Err(e) = require_cluster_auth(&req, &state) { return e; }
+    let r = body.into_inner();
+    if let Err(e) = readdress::validate_address(&r.address) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    // The node being moved has nothing to update about itself.
+    if r.self_id == state.cluster.self_id {
+        return HttpResponse::Ok().json(serde_json::json!({ "ok": true, "updated": 0 }));
+    }
+    if r.verify_only {
+        return match probe(&state, &r.address, r.port, &r.self_id).await {
+            Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "ok": true })),
+            Err(e) => HttpResponse::BadGateway().json(serde_json::json!({ "error": e })),
+        };
+    }
+    match apply_local(&state, &r) {
+        Ok(n) => HttpResponse::Ok().json(serde_json::json!({ "ok": true, "updated": n })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReaddressRequest {
+    pub address: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+/// POST /api/nodes/{id}/readdress — move a node to a new address across
+/// the whole cluster. Body: `{"address": "10.0.1.5", "port": 8553}` (port
+/// optional, defaults to the current one). Every other WolfStack node must
+/// be online: one that misses the change would gossip the old address
+/// back when it returns — archive it first if it's gone for good.
+pub async fn readdress_node(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ReaddressRequest>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    if caller == "cluster-node" || !crate::auth::session_user_is_admin(&caller) {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "error": "Admin only" }));
+    }
+    if let Err(resp) = require_recent_auth(&req, &state) { return resp; }
+    let id = path.into_inner();
+    let Some(node) = state.cluster.get_node(&id) else {
+        return HttpResponse::NotFound().json(serde_json::json!({ "error": "Node not found" }));
+    };
+    if node.is_self {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Re-address this node from another node in the cluster"
+        }));
+    }
+    if node.node_type != "wolfstack" {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "Only WolfStack nodes can be re-addressed" }));
+    }
+    let address = body.address.trim().to_string();
+    if let Err(e) = readdress::validate_address(&address) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+    let port = body.port.filter(|p| *p != 0).unwrap_or(node.port);
+    if address == node.address && port == node.port {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "That is already the node's address" }));
+    }
+    let self_id = node.self_id.clone().filter(|s| !s.is_empty()).unwrap_or_else(|| node.id.clone());
+    let change = AgentReaddress {
+        self_id: self_id.clone(),
+        old_address: node.address.clone(),
+        old_port: node.port,
+        address: address.clone(),
+        port,
+        verify_only: true,
+    };
+
+    // 1. Reachable from here, as the same node.
+    if let Err(e) = probe(&state, &address, port, &self_id).await {
+        return HttpResponse::BadGateway().json(serde_json::json!({ "error": e }));
+    }
+
+    // 2. Every other peer is online and can reach it too.
+    let peers: Vec<crate::agent::Node> = state.cluster.get_all_nodes().into_iter()
+        .filter(|n| !n.is_self && n.node_type == "wolfstack" && n.id != node.id)
+        .filter(|n| n.self_id.as_deref() != Some(self_id.as_str()))
+        .collect();
+    let offline: Vec<String> = peers.iter().filter(|n| !n.online).map(|n| n.hostname.clone()).collect();
+    if !offline.is_empty() {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": format!("Offline node(s) would miss the change: {}. Bring them online or archive them first.", offline.join(", ")),
+            "offline": offline,
+        }));
+    }
+    let mut failed = Vec::new();
+    for peer in &peers {
+        let urls = build_node_urls(&peer.address, peer.port, "/api/agent/readdress");
+        if let Err(e) = super::post_json_to_node(&state, &urls, &serde_json::json!(change), 20).await {
+            failed.push(serde_json::json!({ "node": peer.hostname, "error": e }));
+        }
+    }
+    if !failed.is_empty() {
+        return HttpResponse::BadGateway().json(serde_json::json!({
+            "error": "Not every node can reach the new address — nothing was changed",
+            "failed": failed,
+        }));
+    }
+
+    // 3. Commit here, then on each peer; undo everything if a peer fails.
+    let commit = AgentReaddress { verify_only: false, ..change };
+    let rollback = AgentReaddress {
+        old_address: commit.address.clone(),
+        old_port: commit.port,
+        address: commit.old_address.clone(),
+        port: commit.old_port,
+        ..commit.clone()
+    };
+    if let Err(e) = apply_local(&state, &commit) {
+        return HttpResponse::InternalServerError().json(serde_json::json!({ "error": e }));
+    }
+    let mut committed: Vec<&crate::agent::Node> = Vec::new();
+    for peer in &peers {
+        let urls = build_node_urls(&peer.address, peer.port, "/api/agent/readdress");
+        match super::post_json_to_node(&state, &urls, &serde_json::json!(commit), 20).await {
+            Ok(_) => committed.push(peer),
+            Err(e) => {
+                tracing::warn!("readdress {}: {} failed to commit ({}) — rolling back", node.hostname, peer.hostname, e);
+                for done in &committed {
+                    let urls = build_node_urls(&done.address, done.port, "/api/agent/readdress");
+                    if let Err(re) = super::post_json_to_node(&state, &urls, &serde_json::json!(rollback), 20).await {
+                        tracing::warn!("readdress rollback on {} failed: {}", done.hostname, re);
+                    }
+                }
+                let _ = apply_local(&state, &rollback);
+                return HttpResponse::BadGateway().json(serde_json::json!({
+                    "error": format!("{} couldn't apply the change ({}) — rolled back to {}:{}", peer.hostname, e, commit.old_address, commit.old_port),
+                }));
+            }
+        }
+    }
+    tracing::info!("Node {} re-addressed {}:{} -> {}:{} on {} peer(s)",
+        node.hostname, commit.old_address, commit.old_port, address, port, peers.len());
+    HttpResponse::Ok().json(serde_json::json!({
+        "ok": true,
+        "address": address,
+        "port": port,
+        "peers": peers.len(),
+    }))
+}
@@ -0,0 +1,137 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Streaming bodies for large downloads and uploads.
+//!
+//! Container exports, full log dumps and image imports run to gigabytes;
+//! reading one into a `Vec` before responding (or buffering an upload
+//! before writing it) is how a 5 GB LXC export OOM'd a node. These
+//! helpers move data a chunk at a time in both directions, and both are
+//! pull-driven — a file is only read as fast as the client drains the
+//! response, an upload only read as fast as the disk takes it — so
+//! memory stays flat however big the transfer.
+
+use actix_web::{web, HttpResponse};
+use futures::{Stream, StreamExt};
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+
+const CHUNK: usize = 64 * 1024;
+
+/// Write an upload (request payload or multipart field) to `dest` as it
+/// arrives. A client disconnect or a failed write removes the partial
+/// file — a truncated archive must never look like a finished one.
+/// Returns the bytes written.
+pub(crate) async fn stream_to_file<S, E>(body: &mut S, dest: &Path) -> Result<u64, String>
+where
+    S: Stream<Item = Result<web::Bytes, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    let mut file = tokio::fs::File::create(dest).await
+        .map_err(|e| format!("Cannot create {}: {}", dest.display(), e))?;
+    let mut written = 0u64;
+    let result = async {
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| format!("Upload interrupted: {}", e))?;
+            file.write_all(&chunk).await.map_err(|e| format!("Write error: {}", e))?;
+            written += chunk.len() as u64;
+        }
+        file.flush().await.map_err(|e| format!("Write error: {}", e))
+    }.await;
+    if let Err(e) = result {
+        drop(file);
+        let _ = tokio::fs::remove_file(dest).await;
+        return Err(e);
+    }
+    Ok(written)
+}
+
+/// Body stream over any async reader, 64 KiB at a time.
+pub(crate) fn reader_stream<R>(mut reader: R) -> impl Stream<Item = Result<web::Bytes, actix_web::Error>>
+where
+    R: AsyncRead + Unpin + 'static,
+{
+    async_stream::stream! {
+        let mut buf = vec![0u8; CHUNK];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => yield Ok(web::Bytes::copy_from_slice(&buf[..n])),
+                Err(e) => {
+                    yield Err(actix_web::error::ErrorInternalServerError(e));
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Download response for a file that exists only to be downloaded (an
+/// export archive). The file is opened and then unlinked straight away:
+/// the open handle keeps the data readable for the stream, and the disk
+/// space comes back when the download finishes — or is abandoned —
+/// without a cleanup task having to notice.
+pub(crate) async fn download_and_remove(path: &Path, filename: &str, headers: &[(&str, String)]) -> Result<HttpResponse, String> {
+    let file = tokio::fs::File::open(path).await
+        .map_err(|e| format!("Read archive: {}", e))?;
+    let len = file.metadata().await.map_err(|e| format!("Read archive: {}", e))?.len();
+    let _ = tokio::fs::remove_file(path).await;
+    let mut resp = HttpResponse::Ok();
+    resp.content_type("application/octet-stream")
+        .insert_header(actix_web::http::header::ContentDisposition {
+            disposition: actix_web::http::header::DispositionType::Attachment,
+            parameters: vec![actix_web::http::header::DispositionParam::Filename(filename.to_string())],
+        })
+        // Already compressed — keep the Compress middleware off it.
+        .insert_header(("Content-Encoding", "identity"));
+    for (name, value) in headers {
+        resp.insert_header((*name, value.clone()));
+    }
+    // Sized so the browser can show progress.
+    Ok(resp.body(actix_web::body::SizedStream::new(len, reader_stream(file))))
+}
+
+/// A child's stdout and stderr merged line by line, as a text body. Lines
+/// are never split across the two pipes; the child is killed if the
+/// client goes away, and reaped when both pipes close.
+pub(crate) fn child_lines_stream(mut child: tokio::process::Child) -> Result<impl Stream<Item = Result<web::Bytes, actix_web::Error>>, String> {
+    let stdout = child.stdout.take().ok_or("child has no stdout pipe")?;
+    let stderr = child.stderr.take().ok_or("child has no stderr pipe")?;
+    Ok(async_stream::stream! {
+        let mut out = BufReader::new(stdout);
+        let mut err = BufReader::new(stderr);
+        let (mut out_buf, mut err_buf) = (Vec::new(), Vec::new());
+        let (mut out_done, mut err_done) = (false, false);
+        while !(out_done && err_done) {
+            // read_until is cancel-safe: a read that loses the race keeps
+            // its partial line in the buffer for the next round.
+            let (read, from_out) = tokio::select! {
+                r = out.read_until(b'\n', &mut out_buf), if !out_done => (r, true),
+                r = err.read_until(b'\n', &mut err_buf), if !err_done => (r, false),
+            };
+            let buf = if from_out { &mut out_buf } else { &mut err_buf };
+            match read {
+                Ok(0) => {
+                    if from_out { out_done = true } else { err_done = true }
+                    if !buf.is_empty() {
+                        buf.push(b'\n');
+                        yield Ok(web::Bytes::from(std::mem::take(buf)));
+                    }
+                }
+                Ok(_) => {
+                    // Only the last line of a stream can lack its newline.
+                    if !buf.ends_with(b"\n") {
+                        buf.push(b'\n');
+                    }
+                    yield Ok(web::Bytes::from(std::mem::take(buf)));
+                }
+                Err(e) => {
+                    yield Err(actix_web::error::ErrorInternalServerError(e));
+                    break;
+                }
+            }
+        }
+        let _ = child.wait().await;
+    })
+}
@@ -0,0 +1,626 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Stable machine API under `/api/v1` — the contract an external Terraform /
+//! OpenTofu provider is built on. The rest of `/api` serves the web UI and
+//! changes shape whenever the UI needs it; nothing here changes incompatibly
+//! without a `/api/v2`.
+//!
+//! * Every resource has a stable id: a node its node id, a container
+//!   `{runtime}/{name}`, a VM its name, a mount or IP mapping its id (which
+//!   the client picks when it creates one).
+//! * Representations are configuration only — no metrics, no secrets — so a
+//!   resource's `ETag` changes when the resource does and not otherwise.
+//! * `PUT` is create-or-update and idempotent: repeating it changes nothing
+//!   and returns the same `ETag`. Fields a `PUT` leaves out keep their value,
+//!   which is how secrets (never returned) survive an update.
+//! * `PUT` and `DELETE` honour `If-Match` (412 when the resource changed
+//!   underneath the caller) and `PUT` honours `If-None-Match: *` (create only).
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::{AppState, require_auth, fire_container_event, wolfnet_ip_active_elsewhere};
+use crate::config_doc::{overlay, spec, node_spec, MOUNT_RUNTIME};
+use crate::containers::{self, ContainerInfo};
+use crate::monitoring::inventory;
+use crate::networking::{self, IpMapping};
+use crate::storage::{self, StorageMount};
+
+pub const VERSION: &str = "v1";
+
+/// Serialises v1 writes, so the precondition check and the write it guards
+/// can't interleave with another v1 write to the same resource.
+static WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1")
+            .route("", web::get().to(index))
+            .route("/nodes", web::get().to(list_nodes))
+            .route("/nodes/{id}", web::get().to(get_node))
+            .route("/containers", web::get().to(list_containers))
+            .route("/containers/{runtime}/{name}", web::get().to(get_container))
+            .route("/containers/{runtime}/{name}/state", web::put().to(put_container_state))
+            .route("/vms", web::get().to(list_vms))
+            .route("/vms/{name}", web::get().to(get_vm))
+            .route("/vms/{name}/state", web::put().to(put_vm_state))
+            .route("/mounts", web::get().to(list_mounts))
+            .route("/mounts/{id}", web::get().to(get_mount))
+            .route("/mounts/{id}", web::put().to(put_mount))
+            .route("/mounts/{id}", web::delete().to(delete_mount))
+            .route("/ip-mappings", web::get().to(list_ip_mappings))
+            .route("/ip-mappings/{id}", web::get().to(get_ip_mapping))
+            .route("/ip-mappings/{id}", web::put().to(put_ip_mapping))
+            .route("/ip-mappings/{id}", web::delete().to(delete_ip_mapping)),
+    );
+}
+
+// ─── Conditional requests ───
+
+/// Strong ETag of a representation. serde_json maps are ordered, so equal
+/// values always hash alike.
+pub fn etag(v: &Value) -> String {
+    let digest = Sha256::digest(serde_json::to_vec(v).unwrap_or_default());
+    format!("\"{}\"", hex::encode(&digest[..12]))
+}
+
+/// The `If-Match` / `If-None-Match` headers of a write, taken off the request
+/// so they can travel to the blocking pool with the write itself.
+#[derive(Debug, Default, Clone)]
+struct Conditions {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+}
+
+impl Conditions {
+    fn of(req: &HttpRequest) -> Self {
+        let header = |name: &str| req.headers().get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string());
+        Conditions { if_match: header("If-Match"), if_none_match: header("If-None-Match") }
+    }
+
+    /// Check against the resource's current ETag (`None` = it doesn't exist).
+    fn check(&self, current: Option<&str>) -> Result<(), String> {
+        let listed = |header: &str, tag: &str| header == "*"
+            || header.split(',').any(|t| t.trim().trim_start_matches("W/") == tag);
+        if let Some(want) = &self.if_match
+            && !current.is_some_and(|cur| listed(want, cur))
+        {
+            return Err(match current {
+                Some(cur) => format!("If-Match {} does not match the current ETag {}", want, cur),
+                None => "If-Match given but the resource does not exist".into(),
+            });
+        }
+        if let Some(none) = &self.if_none_match
+            && let Some(cur) = current
+            && listed(none, cur)
+        {
+            return Err("If-None-Match: the resource already exists".into());
+        }
+        Ok(())
+    }
+}
+
+/// What a write did. Each maps to one status code in `respond`.
+enum Outcome {
+    Created(Value),
+    Updated(Value),
+    Unchanged(Value),
+    Deleted,
+    NotFound(String),
+    Precondition(String),
+    Invalid(String),
+    Failed(String),
+}
+
+fn with_etag(mut resp: actix_web::HttpResponseBuilder, body: Value) -> HttpResponse {
+    resp.insert_header(("ETag", etag(&body))).json(body)
+}
+
+fn respond(outcome: Outcome) -> HttpResponse {
+    let error = |mut resp: actix_web::HttpResponseBuilder, e: String| resp.json(serde_json::json!({ "error": e }));
+    match outcome {
+        Outcome::Created(v) => with_etag(HttpResponse::Created(), v),
+        Outcome::Updated(v) | Outcome::Unchanged(v) => with_etag(HttpResponse::Ok(), v),
+        Outcome::Deleted => HttpResponse::NoContent().finish(),
+        Outcome::NotFound(e) => error(HttpResponse::NotFound(), e),
+        Outcome::Precondition(e) => error(HttpResponse::PreconditionFailed(), e),
+        Outcome::Invalid(e) => error(HttpResponse::BadRequest(), e),
+        Outcome::Failed(e) => error(HttpResponse::InternalServerError(), e),
+    }
+}
+
+async fn blocking(f: impl FnOnce() -> Outcome + Send + 'static) -> HttpResponse {
+    respond(web::block(f).await.unwrap_or_else(|e| Outcome::Failed(e.to_string())))
+}
+
+fn found(item: Option<Value>, what: &str, id: &str) -> HttpResponse {
+    match item {
+        Some(v) => with_etag(HttpResponse::Ok(), v),
+        None => respond(Outcome::NotFound(format!("{} '{}' not found", what, id))),
+    }
+}
+
+/// Nodes and IP mappings belong to the node's operator, never a tenant.
+fn require_unscoped(req: &HttpRequest, caller: &str) -> Result<(), HttpResponse> {
+    match crate::auth::tenancy::scope(req, caller) {
+        None => Ok(()),
+        Some(_) => Err(HttpResponse::Forbidden().json(serde_json::json!({ "error": "This resource needs an admin" }))),
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !id.starts_with('.')
+}
+
+/// GET /api/v1 — API version and the resources it serves
+async fn index(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    HttpResponse::Ok().json(serde_json::json!({
+        "version": VERSION,
+        "server_version": env!("CARGO_PKG_VERSION"),
+        "resources": ["nodes", "containers", "vms", "mounts", "ip-mappings"],
+    }))
+}
+
+// ─── Nodes (read-only — membership changes go through the join flow) ───
+
+/// GET /api/v1/nodes — cluster nodes as this node knows them
+async fn list_nodes(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    let nodes: Vec<Value> = state.cluster.get_all_nodes().iter().map(node_spec).collect();
+    HttpResponse::Ok().json(nodes)
+}
+
+/// GET /api/v1/nodes/{id} — one cluster node
+async fn get_node(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    let id = path.into_inner();
+    let node = state.cluster.get_all_nodes().into_iter().find(|n| n.id == id).map(|n| node_spec(&n));
+    found(node, "node", &id)
+}
+
+// ─── Containers ───
+
+fn container_state(c: &ContainerInfo) -> &'static str {
+    match c.state.to_ascii_lowercase().as_str() {
+        "running" => "running",
+        "paused" | "frozen" => "paused",
+        _ => "stopped",
+    }
+}
+
+fn container_spec(c: &ContainerInfo) -> Value {
+    serde_json::json!({
+        "id": format!("{}/{}", c.runtime, c.name),
+        "runtime": c.runtime,
+        "name": c.name,
+        "image": c.image,
+        "state": container_state(c),
+        "autostart": c.autostart,
+    })
+}
+
+fn containers_for(runtime: &str, fresh: bool) -> Vec<ContainerInfo> {
+    match (runtime, fresh) {
+        ("docker", false) => containers::docker_list_all_cached(),
+        ("docker", true) => containers::docker_list_all(),
+        ("lxc", false) => containers::lxc_list_all_cached(),
+        ("lxc", true) => containers::lxc_list_all(),
+        _ => Vec::new(),
+    }
+}
+
+fn container_kind(runtime: &str) -> crate::auth::tenancy::Kind {
+    if runtime == "lxc" { crate::auth::tenancy::Kind::Lxc } else { crate::auth::tenancy::Kind::Docker }
+}
+
+/// GET /api/v1/containers — Docker and LXC containers on this node
+async fn list_containers(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let all = web::block(|| {
+        let mut all = containers_for("docker", false);
+        all.extend(containers_for("lxc", false));
+        all
+    }).await.unwrap_or_default();
+    let visible = crate::auth::tenancy::visible(scope.as_deref(), all,
+        |c| Some((container_kind(&c.runtime), c.name.as_str())));
+    HttpResponse::Ok().json(visible.iter().map(container_spec).collect::<Vec<_>>())
+}
+
+fn container_path(path: web::Path<(String, String)>) -> Result<(String, String), HttpResponse> {
+    let (runtime, name) = path.into_inner();
+    if !matches!(runtime.as_str(), "docker" | "lxc") {
+        return Err(HttpResponse::NotFound().json(serde_json::json!({ "error": "runtime must be 'docker' or 'lxc'" })));
+    }
+    crate::validate::ContainerName::parse(&name)
+        .map(|n| (runtime, n.into_string()))
+        .map_err(|e| HttpResponse::BadRequest().json(serde_json::json!({ "error": e })))
+}
+
+/// GET /api/v1/containers/{runtime}/{name} — one container
+async fn get_container(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let (runtime, name) = match container_path(path) { Ok(p) => p, Err(e) => return e };
+    let (rt, n) = (runtime.clone(), name.clone());
+    let item = web::block(move || containers_for(&rt, false).iter().find(|c| c.name == n).map(container_spec))
+        .await.unwrap_or_default();
+    found(item, "container", &format!("{}/{}", runtime, name))
+}
+
+#[derive(Deserialize)]
+pub struct StateBody {
+    /// "running" or "stopped".
+    pub state: String,
+}
+
+/// PUT /api/v1/containers/{runtime}/{name}/state — start or stop a container (idempotent)
+async fn put_container_state(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>, body: web::Json<StateBody>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let (runtime, name) = match container_path(path) { Ok(p) => p, Err(e) => return e };
+    let want = body.into_inner().state;
+    if !matches!(want.as_str(), "running" | "stopped") {
+        return respond(Outcome::Invalid("state must be 'running' or 'stopped'".into()));
+    }
+    if want == "running" {
+        let ip = if runtime == "docker" {
+            containers::docker_effective_wolfnet_ip(&name)
+        } else {
+            containers::lxc_get_wolfnet_ip(&name)
+        };
+        if let Some(ip) = ip
+            && let Some(holder) = wolfnet_ip_active_elsewhere(&state, &ip).await
+        {
+            return HttpResponse::Conflict().json(serde_json::json!({
+                "error": format!("WolfNet IP already in use: {} (active on {})", ip.trim(), holder)
+            }));
+        }
+    }
+    let conditions = Conditions::of(&req);
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(current) = containers_for(&runtime, true).into_iter().find(|c| c.name == name) else {
+            return Outcome::NotFound(format!("container '{}/{}' not found", runtime, name));
+        };
+        let spec = container_spec(&current);
+        if let Err(e) = conditions.check(Some(&etag(&spec))) { return Outcome::Precondition(e); }
+        if container_state(&current) == want {
+            return Outcome::Unchanged(spec);
+        }
+        let (action, result) = match (runtime.as_str(), want.as_str()) {
+            ("docker", "running") if container_state(&current) == "paused" => ("unpause", containers::docker_unpause(&name)),
+            ("docker", "running") => ("start", containers::docker_start(&name)),
+            ("docker", _) => ("stop", containers::docker_stop(&name)),
+            (_, "running") if container_state(&current) == "paused" => ("unfreeze", containers::lxc_unfreeze(&name)),
+            (_, "running") => ("start", containers::lxc_start(&name)),
+            _ => ("stop", containers::lxc_stop(&name)),
+        };
+        if let Err(e) = result { return Outcome::Failed(e); }
+        fire_container_event(&runtime, &name, action);
+        inventory::DOCKER.invalidate();
+        inventory::LXC.invalidate();
+        match containers_for(&runtime, true).into_iter().find(|c| c.name == name) {
+            Some(c) => Outcome::Updated(container_spec(&c)),
+            None => Outcome::Failed(format!("container '{}/{}' disappeared", runtime, name)),
+        }
+    }).await
+}
+
+// ─── VMs ───
+
+fn vm_spec(vm: &crate::vms::manager::VmConfig) -> Value {
+    serde_json::json!({
+        "id": vm.name,
+        "name": vm.name,
+        "cpus": vm.cpus,
+        "memory_mb": vm.memory_mb,
+        "disk_size_gb": vm.disk_size_gb,
+        "auto_start": vm.auto_start,
+        "wolfnet_ip": vm.wolfnet_ip,
+        "state": if vm.running { "running" } else { "stopped" },
+    })
+}
+
+/// GET /api/v1/vms — VMs on this node
+async fn list_vms(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let vms = crate::auth::tenancy::visible(scope.as_deref(), inventory::vms(&state).await,
+        |vm| Some((crate::auth::tenancy::Kind::Vm, vm.name.as_str())));
+    HttpResponse::Ok().json(vms.iter().map(vm_spec).collect::<Vec<_>>())
+}
+
+/// GET /api/v1/vms/{name} — one VM
+async fn get_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let name = path.into_inner();
+    let vm = inventory::vms(&state).await.iter().find(|v| v.name == name).map(vm_spec);
+    found(vm, "VM", &name)
+}
+
+/// PUT /api/v1/vms/{name}/state — start or gracefully stop a VM (idempotent)
+async fn put_vm_state(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<StateBody>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let name = path.into_inner();
+    let want = body.into_inner().state;
+    if !matches!(want.as_str(), "running" | "stopped") {
+        return respond(Outcome::Invalid("state must be 'running' or 'stopped'".into()));
+    }
+    if want == "running" {
+        let ip = inventory::vms(&state).await.into_iter()
+            .find(|v| v.name == name)
+            .and_then(|v| v.wolfnet_ip);
+        if let Some(ip) = ip.filter(|s| !s.trim().is_empty())
+            && let Some(holder) = wolfnet_ip_active_elsewhere(&state, &ip).await
+        {
+            return HttpResponse::Conflict().json(serde_json::json!({
+                "error": format!("WolfNet IP already in use: {} (active on {})", ip.trim(), holder)
+            }));
+        }
+    }
+    let conditions = Conditions::of(&req);
+    let st = state.clone();
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let find = || st.vms.lock().unwrap().list_vms().into_iter().find(|v| v.name == name);
+        let Some(current) = find() else {
+            return Outcome::NotFound(format!("VM '{}' not found", name));
+        };
+        let spec = vm_spec(&current);
+        if let Err(e) = conditions.check(Some(&etag(&spec))) { return Outcome::Precondition(e); }
+        if current.running == (want == "running") {
+            return Outcome::Unchanged(spec);
+        }
+        let result = {
+            let manager = st.vms.lock().unwrap();
+            if want == "running" { manager.start_vm(&name) } else { manager.stop_vm(&name, false) }
+        };
+        inventory::VMS.invalidate();
+        if let Err(e) = result { return Outcome::Failed(e); }
+        match find() {
+            Some(vm) => Outcome::Updated(vm_spec(&vm)),
+            None => Outcome::Failed(format!("VM '{}' disappeared", name)),
+        }
+    }).await
+}
+
+// ─── Storage mounts ───
+
+fn mount_spec(m: &StorageMount) -> Value {
+    spec(m, MOUNT_RUNTIME)
+}
+
+/// GET /api/v1/mounts — storage mount definitions
+async fn list_mounts(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    let mounts = crate::auth::tenancy::visible(scope.as_deref(), storage::load_config().mounts,
+        |m| Some((crate::auth::tenancy::Kind::Mount, m.id.as_str())));
+    HttpResponse::Ok().json(mounts.iter().map(mount_spec).collect::<Vec<_>>())
+}
+
+/// GET /api/v1/mounts/{id} — one storage mount definition
+async fn get_mount(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    let mount = storage::load_config().mounts.iter().find(|m| m.id == id).map(mount_spec);
+    found(mount, "mount", &id)
+}
+
+/// PUT /api/v1/mounts/{id} — create or update a storage mount definition.
+/// Creating doesn't mount it; `auto_mount` / the Storage page do that. A
+/// tenant-scoped caller can update its tenant's mounts but not create one
+/// (the tenancy check refuses ids the tenant doesn't own).
+async fn put_mount(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<Value>,
+) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    if !valid_id(&id) {
+        return respond(Outcome::Invalid(format!("Invalid mount id '{}'", id)));
+    }
+    let mut body = body.into_inner();
+    let Some(obj) = body.as_object_mut() else {
+        return respond(Outcome::Invalid("expected a JSON object".into()));
+    };
+    obj.retain(|k, _| !MOUNT_RUNTIME.contains(&k.as_str()));
+    obj.insert("id".into(), Value::String(id.clone()));
+    let conditions = Conditions::of(&req);
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = storage::load_config();
+        let existing = config.mounts.iter().position(|m| m.id == id);
+        let current = existing.map(|i| mount_spec(&config.mounts[i]));
+        if let Err(e) = conditions.check(current.as_ref().map(etag).as_deref()) { return Outcome::Precondition(e); }
+        match existing {
+            Some(i) => {
+                let merged = overlay(&serde_json::to_value(&config.mounts[i]).unwrap_or_default(), &body);
+                let mount: StorageMount = match serde_json::from_value(merged) {
+                    Ok(m) => m,
+                    Err(e) => return Outcome::Invalid(e.to_string()),
+                };
+                let next = mount_spec(&mount);
+                if Some(&next) == current.as_ref() {
+                    return Outcome::Unchanged(next);
+                }
+                config.mounts[i] = mount;
+                match storage::save_config(&config) {
+                    Ok(()) => Outcome::Updated(next),
+                    Err(e) => Outcome::Failed(e),
+                }
+            }
+            None => {
+                let stamped = overlay(&serde_json::json!({ "created_at": chrono::Utc::now().to_rfc3339() }), &body);
+                let mount: StorageMount = match serde_json::from_value(stamped) {
+                    Ok(m) => m,
+                    Err(e) => return Outcome::Invalid(e.to_string()),
+                };
+                match storage::create_mount(mount, false) {
+                    Ok(m) => Outcome::Created(mount_spec(&m)),
+                    Err(e) => Outcome::Invalid(e),
+                }
+            }
+        }
+    }).await
+}
+
+/// DELETE /api/v1/mounts/{id} — unmount and remove a storage mount definition
+async fn delete_mount(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    if let Err(e) = require_auth(&req, &state) { return e; }
+    let id = path.into_inner();
+    let conditions = Conditions::of(&req);
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(current) = storage::load_config().mounts.iter().find(|m| m.id == id).map(mount_spec) else {
+            return Outcome::NotFound(format!("mount '{}' not found", id));
+        };
+        if let Err(e) = conditions.check(Some(&etag(&current))) { return Outcome::Precondition(e); }
+        match storage::remove_mount(&id) {
+            Ok(_) => Outcome::Deleted,
+            Err(e) => Outcome::Failed(e),
+        }
+    }).await
+}
+
+// ─── IP mappings ───
+
+fn mapping_spec(m: &IpMapping) -> Value {
+    spec(m, &[])
+}
+
+/// GET /api/v1/ip-mappings — public IP → WolfNet IP mappings
+async fn list_ip_mappings(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    HttpResponse::Ok().json(networking::list_ip_mappings().iter().map(mapping_spec).collect::<Vec<_>>())
+}
+
+/// GET /api/v1/ip-mappings/{id} — one IP mapping
+async fn get_ip_mapping(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    let id = path.into_inner();
+    let mapping = networking::list_ip_mappings().iter().find(|m| m.id == id).map(mapping_spec);
+    found(mapping, "IP mapping", &id)
+}
+
+/// PUT /api/v1/ip-mappings/{id} — create or update an IP mapping.
+/// `enabled` is reported but read-only here; a new mapping starts enabled.
+async fn put_ip_mapping(
+    req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<Value>,
+) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    let id = path.into_inner();
+    if !valid_id(&id) {
+        return respond(Outcome::Invalid(format!("Invalid mapping id '{}'", id)));
+    }
+    let mut body = body.into_inner();
+    let Some(obj) = body.as_object_mut() else {
+        return respond(Outcome::Invalid("expected a JSON object".into()));
+    };
+    obj.remove("enabled");
+    let conditions = Conditions::of(&req);
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let existing = networking::list_ip_mappings().into_iter().find(|m| m.id == id);
+        let current = existing.as_ref().map(mapping_spec);
+        if let Err(e) = conditions.check(current.as_ref().map(etag).as_deref()) { return Outcome::Precondition(e); }
+        let base = current.clone().unwrap_or_else(|| serde_json::json!({ "protocol": "all", "label": "", "enabled": true }));
+        let mut merged = overlay(&base, &body);
+        merged["id"] = Value::String(id.clone());
+        let m: IpMapping = match serde_json::from_value(merged) {
+            Ok(m) => m,
+            Err(e) => return Outcome::Invalid(e.to_string()),
+        };
+        let next = mapping_spec(&m);
+        if Some(&next) == current.as_ref() {
+            return Outcome::Unchanged(next);
+        }
+        let result = if existing.is_some() {
+            networking::update_ip_mapping(&id, &m.public_ip, &m.wolfnet_ip,
+                m.ports.as_deref(), m.dest_ports.as_deref(), &m.protocol, &m.label)
+        } else {
+            networking::add_ip_mapping_with_id(Some(&id), &m.public_ip, &m.wolfnet_ip,
+                m.ports.as_deref(), m.dest_ports.as_deref(), &m.protocol, &m.label)
+        };
+        match (result, existing.is_some()) {
+            (Ok(saved), true) => Outcome::Updated(mapping_spec(&saved)),
+            (Ok(saved), false) => Outcome::Created(mapping_spec(&saved)),
+            (Err(e), _) => Outcome::Invalid(e),
+        }
+    }).await
+}
+
+/// DELETE /api/v1/ip-mappings/{id} — remove an IP mapping and its rules
+async fn delete_ip_mapping(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(c) => c, Err(e) => return e };
+    if let Err(e) = require_unscoped(&req, &caller) { return e; }
+    let id = path.into_inner();
+    let conditions = Conditions::of(&req);
+    blocking(move || {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(current) = networking::list_ip_mappings().iter().find(|m| m.id == id).map(mapping_spec) else {
+            return Outcome::NotFound(format!("IP mapping '{}' not found", id));
+        };
+        if let Err(e) = conditions.check(Some(&etag(&current))) { return Outcome::Precondition(e); }
+        match networking::remove_ip_mapping(&id) {
+            Ok(_) => Outcome::Deleted,
+            Err(e) => Outcome::Failed(e),
+        }
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn etag_is_stable_and_content_sensitive() {
+        let a = serde_json::json!({ "id": "nas", "enabled": true });
+        let b = serde_json::json!({ "enabled": true, "id": "nas" });
+        assert_eq!(etag(&a), etag(&b), "key order doesn't matter");
+        assert_ne!(etag(&a), etag(&serde_json::json!({ "id": "nas", "enabled": false })));
+        assert!(etag(&a).starts_with('"') && etag(&a).ends_with('"'));
+    }
+
+    #[test]
+    fn preconditions() {
+        let tag = etag(&serde_json::json!({ "id": "x" }));
+        let none = Conditions::default();
+        assert!(none.check(None).is_ok() && none.check(Some(&tag)).is_ok());
+
+        let matching = Conditions { if_match: Some(format!("\"other\", W/{}", tag)), ..Default::default() };
+        assert!(matching.check(Some(&tag)).is_ok());
+        assert!(matching.check(Some("\"changed\"")).is_err(), "lost update is refused");
+        assert!(matching.check(None).is_err(), "If-Match on a missing resource fails");
+        let any = Conditions { if_match: Some("*".into()), ..Default::default() };
+        assert!(any.check(Some(&tag)).is_ok() && any.check(None).is_err());
+
+        let create_only = Conditions { if_none_match: Some("*".into()), ..Default::default() };
+        assert!(create_only.check(None).is_ok());
+        assert!(create_only.check(Some(&tag)).is_err());
+    }
+
+    #[test]
+    fn ids_are_path_safe() {
+        assert!(valid_id("nas-01") && valid_id("web_2.backup"));
+        for bad in ["", ".hidden", "a/b", "a b", "..", &"x".repeat(65)] {
+            assert!(!valid_id(bad), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Recent intrusion attempts, grouped by attacker.
+//!
+//! Every failed login on every surface already funnels through
+//! [`LoginRateLimiter::record_failure_with`](super::LoginRateLimiter) —
+//! the WolfStack UI directly, host sshd and Proxmox via
+//! [`log_monitor`](super::log_monitor), and container sshd / web auth via
+//! `bruteforce`. The limiter's own audit log is 500 mixed rows, which a
+//! public host fills in minutes; this keeps the failures alone, long
+//! enough to answer "who has been hammering us today, at what, and with
+//! which usernames". The sources tag the username (`sshd:root`,
+//! `container-web:admin`), so the surface is recovered from the tag.
+//!
+//! In memory only — a restart starts the picture afresh, the blocks
+//! themselves persist in the limiter.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// Failures kept per node.
+const MAX_EVENTS: usize = 20_000;
+/// Usernames listed per attacker.
+const MAX_USERNAMES: usize = 8;
+/// Attackers returned per summary.
+pub const MAX_ATTACKERS: usize = 200;
+/// The surge alert looks at this window.
+pub const SURGE_WINDOW_SECS: u64 = 600;
+
+#[derive(Debug, Clone, PartialEq)]
+struct AttackEvent {
+    timestamp: u64,
+    ip: String,
+    username: String,
+    source: &'static str,
+}
+
+static EVENTS: LazyLock<Mutex<VecDeque<AttackEvent>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+/// When the last surge alert went out, so one long attack alerts once per window.
+static LAST_SURGE_ALERT: Mutex<u64> = Mutex::new(0);
+
+/// Split a limiter username into (surface, username). Untagged names
+/// come from the WolfStack login page.
+fn split_source(tagged: &str) -> (&'static str, &str) {
+    if let Some((tag, user)) = tagged.split_once(':') {
+        let source = match tag {
+            "sshd" | "sshd-invalid-user" => Some("ssh"),
+            "pveproxy" | "pvedaemon" => Some("proxmox"),
+            "container-ssh" => Some("container-ssh"),
+            "container-web" => Some("web"),
+            _ => None,
+        };
+        if let Some(source) = source {
+            return (source, user);
+        }
+    }
+    ("wolfstack", tagged)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Record one failed login. Called by the limiter for every untrusted
+/// failure, whether or not lockouts are enabled.
+pub fn record(ip: &str, tagged_username: &str) {
+    record_at(ip, tagged_username, now_secs());
+}
+
+fn record_at(ip: &str, tagged_username: &str, timestamp: u64) {
+    let (source, username) = split_source(tagged_username);
+    let mut events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    if events.len() >= MAX_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(AttackEvent { timestamp, ip: ip.to_string(), username: username.to_string(), source });
+}
+
+/// One attacking IP.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Attacker {
+    pub ip: String,
+    pub attempts: u64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// Surfaces hit: ssh, proxmox, wolfstack, container-ssh, web.
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Most-tried usernames first.
+    #[serde(default)]
+    pub usernames: Vec<String>,
+    /// Nodes that saw it (filled in by [`merge`]).
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    /// Currently kernel-blocked (on any node, once merged).
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttackSummary {
+    pub since: u64,
+    pub total: u64,
+    /// Attempts per surface.
+    #[serde(default)]
+    pub by_source: BTreeMap<String, u64>,
+    /// Busiest first, at most [`MAX_ATTACKERS`].
+    #[serde(default)]
+    pub attackers: Vec<Attacker>,
+    /// Distinct IPs before the cap.
+    #[serde(default)]
+    pub unique_ips: usize,
+}
+
+fn summarise(events: &VecDeque<AttackEvent>, since: u64, blocked: &dyn Fn(&str) -> bool) -> AttackSummary {
+    let mut summary = AttackSummary { since, ..Default::default() };
+    let mut by_ip: HashMap<&str, (Attacker, HashMap<&str, u64>)> = HashMap::new();
+    for e in events.iter().filter(|e| e.timestamp >= since) {
+        summary.total += 1;
+        *summary.by_source.entry(e.source.to_string()).or_default() += 1;
+        let (a, users) = by_ip.entry(e.ip.as_str()).or_insert_with(|| (Attacker {
+            ip: e.ip.clone(), first_seen: e.timestamp, ..Default::default()
+        }, HashMap::new()));
+        a.attempts += 1;
+        a.first_seen = a.first_seen.min(e.timestamp);
+        a.last_seen = a.last_seen.max(e.timestamp);
+        if !a.sources.iter().any(|s| s == e.source) {
+            a.sources.push(e.source.to_string());
+        }
+        if !e.username.is_empty() {
+            *users.entry(e.username.as_str()).or_default() += 1;
+        }
+    }
+    summary.unique_ips = by_ip.len();
+    let mut attackers: Vec<Attacker> = by_ip.into_values().map(|(mut a, users)| {
+        let mut users: Vec<(&str, u64)> = users.into_iter().collect();
+        users.sort_by(|x, y| y.1.cmp(&x.1).then(x.0.cmp(y.0)));
+        a.usernames = users.into_iter().take(MAX_USERNAMES).map(|(u, _)| u.to_string()).collect();
+        a.blocked = blocked(&a.ip);
+        a
+    }).collect();
+    attackers.sort_by(|a, b| b.attempts.cmp(&a.attempts).then(b.last_seen.cmp(&a.last_seen)));
+    attackers.truncate(MAX_ATTACKERS);
+    summary.attackers = attackers;
+    summary
+}
+
+/// This node's failures over the last `hours`; `blocked` says whether an
+/// IP is currently locked out here.
+pub fn summary(hours: u64, blocked: &dyn Fn(&str) -> bool) -> AttackSummary {
+    let since = now_secs().saturating_sub(hours.max(1) * 3600);
+    let events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    summarise(&events, since, blocked)
+}
+
+/// Fold per-node summaries into one cluster view, keyed by attacker IP.
+pub fn merge(nodes: Vec<(String, AttackSummary)>) -> AttackSummary {
+    let mut out = AttackSummary { since: u64::MAX, ..Default::default() };
+    let mut by_ip: HashMap<String, Attacker> = HashMap::new();
+    for (node, s) in nodes {
+        out.since = out.since.min(s.since);
+        out.total += s.total;
+        for (src, n) in s.by_source {
+            *out.by_source.entry(src).or_default() += n;
+        }
+        for a in s.attackers {
+            let m = by_ip.entry(a.ip.clone()).or_insert_with(|| Attacker {
+                ip: a.ip.clone(), first_seen: a.first_seen, ..Default::default()
+            });
+            m.attempts += a.attempts;
+            m.first_seen = m.first_seen.min(a.first_seen);
+            m.last_seen = m.last_seen.max(a.last_seen);
+            m.blocked |= a.blocked;
+            for src in a.sources {
+                if !m.sources.contains(&src) {
+                    m.sources.push(src);
+                }
+            }
+            for u in a.usernames {
+                if m.usernames.len() < MAX_USERNAMES && !m.usernames.contains(&u) {
+                    m.usernames.push(u);
+                }
+            }
+            if !m.nodes.contains(&node) {
+                m.nodes.push(node.clone());
+            }
+        }
+    }
+    if out.since == u64::MAX {
+        out.since = 0;
+    }
+    out.unique_ips = by_ip.len();
+    let mut attackers: Vec<Attacker> = by_ip.into_values().collect();
+    attackers.sort_by(|a, b| b.attempts.cmp(&a.attempts).then(b.last_seen.cmp(&a.last_seen)));
+    attackers.truncate(MAX_ATTACKERS);
+    out.attackers = attackers;
+    out
+}
+
+/// (title, message) when the last [`SURGE_WINDOW_SECS`] saw at least
+/// `threshold` failures — at most once per window. 0 disables it.
+pub fn surge_alert(threshold: u64) -> Option<(String, String)> {
+    if threshold == 0 {
+        return None;
+    }
+    let now = now_secs();
+    let s = {
+        let events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+        summarise(&events, now.saturating_sub(SURGE_WINDOW_SECS), &|_| false)
+    };
+    if s.total < threshold {
+        return None;
+    }
+    {
+        let mut last = LAST_SURGE_ALERT.lock().unwrap_or_else(|e| e.into_inner());
+        if now.saturating_sub(*last) < SURGE_WINDOW_SECS {
+            return None;
+        }
+        *last = now;
+    }
+    Some(surge_message(&s))
+}
+
+fn surge_message(s: &AttackSummary) -> (String, String) {
+    let title = format!("🚨 {} failed logins from {} IP(s) in {} minutes",
+        s.total, s.unique_ips, SURGE_WINDOW_SECS / 60);
+    let surfaces: Vec<String> = s.by_source.iter().map(|(k, v)| format!("{} {}", k, v)).collect();
+    let mut msg = format!("Login failures by surface: {}.\n\nBusiest sources:\n", surfaces.join(", "));
+    for a in s.attackers.iter().take(5) {
+        msg.push_str(&format!("  {} — {} attempt(s) on {} (users: {})\n",
+            a.ip, a.attempts, a.sources.join("/"),
+            if a.usernames.is_empty() { "-".to_string() } else { a.usernames.join(", ") }));
+    }
+    msg.push_str("\nIPs crossing the lockout threshold are kernel-blocked cluster-wide when brute-force lockout is enabled. See Fleet Security → Recent attacks.");
+    (title, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(ts: u64, ip: &str, user: &str) -> AttackEvent {
+        let (source, username) = split_source(user);
+        AttackEvent { timestamp: ts, ip: ip.into(), username: username.into(), source }
+    }
+
+    #[test]
+    fn sources_come_from_the_username_tag() {
+        assert_eq!(split_source("sshd:root"), ("ssh", "root"));
+        assert_eq!(split_source("pvedaemon:root@pam"), ("proxmox", "root@pam"));
+        assert_eq!(split_source("container-web:admin"), ("web", "admin"));
+        assert_eq!(split_source("admin"), ("wolfstack", "admin"));
+        assert_eq!(split_source("odd:name"), ("wolfstack", "odd:name"));
+    }
+
+    #[test]
+    fn groups_by_ip_within_the_window() {
+        let events: VecDeque<AttackEvent> = vec![
+            ev(50, "1.1.1.1", "sshd:old"),
+            ev(100, "1.1.1.1", "sshd:root"),
+            ev(110, "1.1.1.1", "sshd:root"),
+            ev(120, "1.1.1.1", "admin"),
+            ev(130, "2.2.2.2", "pveproxy:root@pam"),
+        ].into();
+        let s = summarise(&events, 100, &|ip| ip == "2.2.2.2");
+        assert_eq!((s.total, s.unique_ips), (4, 2));
+        assert_eq!(s.by_source["ssh"], 2);
+        let top = &s.attackers[0];
+        assert_eq!((top.ip.as_str(), top.attempts, top.first_seen, top.last_seen), ("1.1.1.1", 3, 100, 120));
+        assert_eq!(top.sources, ["ssh", "wolfstack"]);
+        assert_eq!(top.usernames, ["root", "admin"]);
+        assert!(!top.blocked && s.attackers[1].blocked);
+    }
+
+    #[test]
+    fn merges_nodes_by_ip() {
+        let a = |ip: &str, n, src: &str, blocked| Attacker {
+            ip: ip.into(), attempts: n, first_seen: 10, last_seen: 20,
+            sources: vec![src.into()], usernames: vec!["root".into()], blocked, ..Default::default()
+        };
+        let s1 = AttackSummary { since: 5, total: 3, attackers: vec![a("1.1.1.1", 3, "ssh", false)], ..Default::default() };
+        let s2 = AttackSummary { since: 7, total: 9, attackers: vec![a("1.1.1.1", 4, "web", true), a("3.3.3.3", 5, "ssh", false)], ..Default::default() };
+        let m = merge(vec![("n1".into(), s1), ("n2".into(), s2)]);
+        assert_eq!((m.since, m.total, m.unique_ips), (5, 12, 2));
+        let top = &m.attackers[0];
+        assert_eq!((top.ip.as_str(), top.attempts, top.blocked), ("1.1.1.1", 7, true));
+        assert_eq!(top.nodes, ["n1", "n2"]);
+        assert_eq!(top.sources, ["ssh", "web"]);
+        let (title, _) = surge_message(&m);
+        assert!(title.contains("12 failed logins from 2 IP(s)"));
+    }
+}
@@ -0,0 +1,121 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Cross-site request forgery protection for the dashboard session.
+//!
+//! The session cookie is sent by the browser with any request to this
+//! host, including a form or `fetch` on another site. SameSite=Lax keeps
+//! it off cross-site POSTs in current browsers, but not off same-site
+//! ones (another port or subdomain of the same domain) or older browsers.
+//! So a state-changing request authenticated by the session cookie must
+//! also carry the session's CSRF token in [`HEADER`]: the token is issued
+//! at login in the JS-readable [`COOKIE`], and a page on another origin
+//! can neither read that cookie nor set the header without a CORS
+//! preflight we never answer. On top of that, a request whose `Origin`
+//! or `Sec-Fetch-Site` says it came from another site is refused outright.
+//!
+//! Requests authenticated some other way — cluster secret, join token,
+//! API key — are not affected: a browser can't attach those headers
+//! cross-site either.
+
+use actix_web::http::{header::HeaderMap, Method};
+
+/// JS-readable cookie holding the session's CSRF token.
+pub const COOKIE: &str = "wolfstack_csrf";
+/// Request header the dashboard echoes the token in.
+pub const HEADER: &str = "X-CSRF-Token";
+
+/// Headers that authenticate a request without the session cookie.
+const CREDENTIAL_HEADERS: &[&str] = &[
+    "X-WolfStack-Secret", "X-WolfStack-Join-Token", "X-API-Key", "Authorization",
+];
+
+/// Methods that change state and so need the token.
+pub fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+}
+
+/// True if the request carries its own credentials rather than relying
+/// on the session cookie.
+pub fn has_credential_header(headers: &HeaderMap) -> bool {
+    CREDENTIAL_HEADERS.iter().any(|h| headers.contains_key(*h))
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// True if the browser says the request came from another site. Checks
+/// `Sec-Fetch-Site` where sent, then `Origin` against the `Host` (or the
+/// `X-Forwarded-Host` a reverse proxy sets). No headers at all is not
+/// cross-site — that's curl or an old browser, which the token covers.
+pub fn is_cross_origin(headers: &HeaderMap) -> bool {
+    if header(headers, "Sec-Fetch-Site") == Some("cross-site") {
+        return true;
+    }
+    let Some(origin) = header(headers, "Origin") else { return false };
+    // A sandboxed or privacy-redirected context sends "null".
+    let Some(origin_host) = origin.split_once("://").map(|(_, rest)| rest) else { return true };
+    let origin_host = origin_host.trim_end_matches('/');
+    let same = |h: Option<&str>| h.is_some_and(|h| h.eq_ignore_ascii_case(origin_host));
+    !(same(header(headers, "Host")) || same(header(headers, "X-Forwarded-Host")))
+}
+
+/// Check a state-changing, cookie-authenticated request against the
+/// session's token. `Err` carries the message for the 403.
+pub fn verify(headers: &HeaderMap, expected: &str) -> Result<(), &'static str> {
+    if is_cross_origin(headers) {
+        return Err("Cross-origin request refused");
+    }
+    match header(headers, HEADER) {
+        Some(provided) if super::validate_cluster_secret(provided, expected) => Ok(()),
+        Some(_) => Err("Invalid CSRF token — reload the page"),
+        None => Err("Missing CSRF token — reload the page"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.insert(HeaderName::from_bytes(k.as_bytes()).unwrap(), HeaderValue::from_str(v).unwrap());
+        }
+        h
+    }
+
+    #[test]
+    fn token_must_match() {
+        let ok = headers(&[("Host", "node1:8553"), ("Origin", "https://node1:8553"), (HEADER, "abc")]);
+        assert!(verify(&ok, "abc").is_ok());
+        assert!(verify(&ok, "abd").is_err());
+        assert!(verify(&headers(&[("Host", "node1:8553")]), "abc").is_err());
+    }
+
+    #[test]
+    fn other_origins_are_refused_even_with_the_token() {
+        for bad in [
+            vec![("Host", "node1:8553"), ("Origin", "https://evil.example"), (HEADER, "abc")],
+            vec![("Host", "node1:8553"), ("Origin", "null"), (HEADER, "abc")],
+            vec![("Host", "node1:8553"), ("Sec-Fetch-Site", "cross-site"), (HEADER, "abc")],
+        ] {
+            assert!(verify(&headers(&bad), "abc").is_err(), "{bad:?}");
+        }
+        // Behind a reverse proxy the Host is rewritten; X-Forwarded-Host is the public one.
+        let proxied = headers(&[("Host", "127.0.0.1:8553"), ("X-Forwarded-Host", "dash.example.com"),
+                                ("Origin", "https://dash.example.com"), (HEADER, "abc")]);
+        assert!(verify(&proxied, "abc").is_ok());
+    }
+
+    #[test]
+    fn safe_methods_and_credential_headers_are_exempt() {
+        assert!(!is_state_changing(&Method::GET));
+        assert!(is_state_changing(&Method::POST) && is_state_changing(&Method::DELETE));
+        assert!(has_credential_header(&headers(&[("X-API-Key", "wsk_x")])));
+        assert!(!has_credential_header(&headers(&[("Cookie", "wolfstack_session=x")])));
+    }
+}
@@ -25,7 +25,9 @@
 //!
 //! Modern Debian/Trixie/Proxmox installs often ship without rsyslog —
 //! `/var/log/auth.log` doesn't exist. journald is the universal source.
-//! We `journalctl -f -o cat` and stream-parse line-by-line.
+//! We `journalctl -f -o cat` and stream-parse line-by-line. Hosts with no
+//! journald at all fall back to `tail -F` on the syslog auth file — the
+//! lines are the same.
 //!
 //! ## Patterns
 //!
@@ -57,7 +59,8 @@ use crate::auth::LoginRateLimiter;
 /// async I/O would buy us nothing.
 ///
 /// Returns immediately; the thread runs for the lifetime of the
-/// process. If journalctl isn't available, the thread exits silently
+/// process. If journalctl isn't available it follows /var/log/auth.log
+/// (or /var/log/secure) instead; with neither, the thread exits
 /// (operator just doesn't get this coverage — WolfStack UI lockout
 /// still works).
 pub fn start_monitor(limiter: Arc<LoginRateLimiter>) {
@@ -83,13 +86,31 @@ fn tail_journal_loop(
 ) {
     loop {
         if !run_one_journal_session(&limiter, &dedup) {
-            tracing::warn!("auth-monitor: journalctl unavailable, exiting monitor thread");
+            break;
+        }
+        std::thread::sleep(Duration::from_secs(5));
+    }
+    // No journald (Alpine, Devuan, containers with rsyslog only) — the
+    // same sshd / pve lines land in the classic syslog auth file.
+    let Some(path) = AUTH_LOG_FILES.iter().find(|p| std::path::Path::new(p).exists()) else {
+        tracing::warn!("auth-monitor: neither journalctl nor an auth log file is available, exiting monitor thread");
+        return;
+    };
+    tracing::info!("auth-monitor: journalctl unavailable, following {}", path);
+    loop {
+        let mut cmd = Command::new("tail");
+        cmd.args(["-F", "-n", "0", path]);
+        if !run_one_session(cmd, &limiter, &dedup) {
+            tracing::warn!("auth-monitor: cannot follow {}, exiting monitor thread", path);
             return;
         }
         std::thread::sleep(Duration::from_secs(5));
     }
 }
 
+/// Debian/Ubuntu, then RHEL-family.
+const AUTH_LOG_FILES: &[&str] = &["/var/log/auth.log", "/var/log/secure"];
+
 /// One follow-session. Returns true if the operator should try again
 /// (transient failure), false if journalctl can't be spawned at all
 /// (permanent — operator's system doesn't have it).
@@ -100,31 +121,43 @@ fn run_one_journal_session(
     // Follow journal, no pager, raw lines, only since "now" so we
     // don't replay history on every restart. Filter to the units
     // we care about. `-o cat` strips the systemd metadata prefix.
-    let child = Command::new("journalctl")
-        .args([
-            "--follow",
-            "--no-pager",
-            "--output=short",
-            "--since=now",
-            "_COMM=sshd",
-            "_COMM=sshd-session",
-            "_COMM=pvedaemon",
-            "_COMM=pveproxy",
-        ])
+    let mut cmd = Command::new("journalctl");
+    cmd.args([
+        "--follow",
+        "--no-pager",
+        "--output=short",
+        "--since=now",
+        "_COMM=sshd",
+        "_COMM=sshd-session",
+        "_COMM=pvedaemon",
+        "_COMM=pveproxy",
+    ]);
+    run_one_session(cmd, limiter, dedup)
+}
+
+/// Stream one follower child's stdout through the parser. Same return
+/// contract as [`run_one_journal_session`].
+fn run_one_session(
+    mut cmd: Command,
+    limiter: &Arc<LoginRateLimiter>,
+    dedup: &Arc<Mutex<HashMap<(String, String), Instant>>>,
+) -> bool {
+    let prog = cmd.get_program().to_string_lossy().to_string();
+    let child = cmd
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .spawn();
     let mut child = match child {
         Ok(c) => c,
         Err(e) => {
-            tracing::warn!("auth-monitor: cannot spawn journalctl: {}", e);
+            tracing::warn!("auth-monitor: cannot spawn {}: {}", prog, e);
             return false;
         }
     };
     let stdout = match child.stdout.take() {
         Some(s) => s,
         None => {
-            tracing::error!("auth-monitor: journalctl produced no stdout");
+            tracing::error!("auth-monitor: {} produced no stdout", prog);
             let _ = child.kill();
             return true;
         }
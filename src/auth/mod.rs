@@ -15,15 +15,16 @@ pub mod oidc;
 #[allow(dead_code)]
 pub mod webauthn;
 pub mod log_monitor;
+pub mod attacks;
+pub mod csrf;
+pub mod read_only;
+pub mod tenancy;
 
 use std::collections::HashMap;
 use std::sync::RwLock;
 use std::time::{Duration, Instant};
 use tracing::warn;
 
-/// Session token lifetime (8 hours)
-const SESSION_LIFETIME: Duration = Duration::from_secs(8 * 3600);
-
 // Old static MAX_LOGIN_ATTEMPTS / LOGIN_LOCKOUT_WINDOW constants were
 // removed when the lockout system became operator-configurable.
 // See `LoginLockoutConfig` for the per-policy fields.
@@ -163,6 +164,21 @@ pub fn session_user_is_admin(username: &str) -> bool {
     linux_user_is_privileged(username)
 }
 
+/// Set by a node proxying a request for a user who isn't an admin. The
+/// peer only sees `cluster-node`, so without it every relayed request
+/// would pass an admin check.
+pub const NON_ADMIN_HEADER: &str = "X-WolfStack-Non-Admin";
+
+/// Whether the caller of `req` may use admin-only endpoints. A cluster
+/// peer counts as admin only when it relays an unscoped request the
+/// proxying node didn't mark with [`NON_ADMIN_HEADER`].
+pub fn caller_is_admin(req: &actix_web::HttpRequest, caller: &str) -> bool {
+    if caller == "cluster-node" {
+        return tenancy::scope(req, caller).is_none() && !req.headers().contains_key(NON_ADMIN_HEADER);
+    }
+    session_user_is_admin(caller)
+}
+
 /// True if a Linux account is `root` or belongs to the `sudo` / `wheel`
 /// group — the conventional "can administer this host" set across Debian
 /// (`sudo`) and RHEL/Arch (`wheel`). Reads /etc/group + /etc/passwd; if
@@ -486,45 +502,209 @@ pub fn validate_cluster_secret(provided: &str, expected: &str) -> bool {
 
 // Pure-Rust password hashing (replaces C libcrypt dependency)
 
+/// How long sessions last — operator-configurable on the Security page,
+/// persisted to `session-policy.json`. Lifetime and idle changes apply to
+/// sessions already open, not just new ones.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SessionPolicy {
+    /// Absolute lifetime of a session (hours).
+    #[serde(default = "default_lifetime_hours")]
+    pub lifetime_hours: u64,
+    /// Sign a session out after this many minutes without a request, and
+    /// the dashboard after this long without keyboard or mouse input.
+    /// 0 = no idle timeout. "Remember me" sessions are exempt.
+    #[serde(default)]
+    pub idle_minutes: u64,
+    /// Lifetime of a "remember me" session (days). 0 hides the option.
+    #[serde(default = "default_remember_days")]
+    pub remember_days: u64,
+    /// Sensitive actions (removing a node, managing users) need the
+    /// password entered within this many minutes. 0 = never ask again.
+    #[serde(default = "default_reauth_minutes")]
+    pub reauth_minutes: u64,
+}
+
+fn default_lifetime_hours() -> u64 { 8 }
+fn default_remember_days() -> u64 { 30 }
+fn default_reauth_minutes() -> u64 { 15 }
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            lifetime_hours: default_lifetime_hours(),
+            idle_minutes: 0,
+            remember_days: default_remember_days(),
+            reauth_minutes: default_reauth_minutes(),
+        }
+    }
+}
+
+impl SessionPolicy {
+    fn config_path() -> String {
+        format!("{}/session-policy.json", crate::paths::get().config_dir)
+    }
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::config_path())
+            .ok()
+            .and_then(|s| serde_json::from_str::<Self>(&s).ok())
+            .map(Self::clamped)
+            .unwrap_or_default()
+    }
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path();
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::paths::write_secure(&path, &json)
+            .map_err(|e| format!("save session policy: {}", e))
+    }
+    /// Pull every field into a sane range — a session can't outlive 30
+    /// days (365 remembered), and an idle or re-auth window over a day is
+    /// no window at all.
+    pub fn clamped(self) -> Self {
+        Self {
+            lifetime_hours: self.lifetime_hours.clamp(1, 720),
+            idle_minutes: self.idle_minutes.min(1440),
+            remember_days: self.remember_days.min(365),
+            reauth_minutes: self.reauth_minutes.min(1440),
+        }
+    }
+    /// How long a session lasts from login.
+    pub fn lifetime(&self, remember: bool) -> Duration {
+        if remember && self.remember_days > 0 {
+            Duration::from_secs(self.remember_days * 86400)
+        } else {
+            Duration::from_secs(self.lifetime_hours * 3600)
+        }
+    }
+}
+
 /// Active session
 struct Session {
     username: String,
     created: Instant,
+    /// Signed in with "remember me": the longer lifetime, no idle timeout.
+    remember: bool,
+    /// Seconds after `created` of the last request on this session.
+    /// Atomic so `validate` can bump it under the read lock.
+    last_seen: std::sync::atomic::AtomicU64,
+    /// When the password (or passkey / SSO) was last presented.
+    authenticated: Instant,
+    /// Anti-CSRF token issued with the session (see `csrf`).
+    csrf: String,
+}
+
+impl Session {
+    fn live(&self, policy: &SessionPolicy) -> bool {
+        let age = self.created.elapsed();
+        if age >= policy.lifetime(self.remember) {
+            return false;
+        }
+        if self.remember || policy.idle_minutes == 0 {
+            return true;
+        }
+        let idle = age.as_secs().saturating_sub(self.last_seen.load(std::sync::atomic::Ordering::Relaxed));
+        idle < policy.idle_minutes * 60
+    }
 }
 
 /// Session manager
 pub struct SessionManager {
     sessions: RwLock<HashMap<String, Session>>,
+    policy: RwLock<SessionPolicy>,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
         Self {
             sessions: RwLock::new(HashMap::new()),
+            policy: RwLock::new(SessionPolicy::load()),
         }
     }
 
     /// Create a new session for a user, returns the session token
     pub fn create_session(&self, username: &str) -> String {
+        self.create_session_with(username, false)
+    }
+
+    /// Create a session, optionally a "remember me" one (ignored when the
+    /// policy has remembering switched off).
+    pub fn create_session_with(&self, username: &str, remember: bool) -> String {
         let token = uuid::Uuid::new_v4().to_string();
+        let remember = remember && self.policy().remember_days > 0;
         let mut sessions = self.sessions.write().unwrap();
         sessions.insert(token.clone(), Session {
             username: username.to_string(),
             created: Instant::now(),
+            remember,
+            last_seen: Default::default(),
+            authenticated: Instant::now(),
+            csrf: uuid::Uuid::new_v4().simple().to_string(),
         });
 
         token
     }
 
-    /// Validate a session token, returns the username if valid
+    /// Validate a session token, returns the username if valid. Counts as
+    /// activity for the idle timeout.
     pub fn validate(&self, token: &str) -> Option<String> {
+        let policy = self.policy();
         let sessions = self.sessions.read().unwrap();
-        if let Some(session) = sessions.get(token) {
-            if session.created.elapsed() < SESSION_LIFETIME {
-                return Some(session.username.clone());
-            }
+        let session = sessions.get(token).filter(|s| s.live(&policy))?;
+        session.last_seen.store(session.created.elapsed().as_secs(), std::sync::atomic::Ordering::Relaxed);
+        Some(session.username.clone())
+    }
+
+    /// The CSRF token of a live session
+    pub fn csrf_token(&self, token: &str) -> Option<String> {
+        let policy = self.policy();
+        let sessions = self.sessions.read().unwrap();
+        sessions.get(token)
+            .filter(|s| s.live(&policy))
+            .map(|s| s.csrf.clone())
+    }
+
+    /// What a session's cookies should live for: the rest of its lifetime.
+    pub fn cookie_max_age(&self, token: &str) -> Duration {
+        let policy = self.policy();
+        let sessions = self.sessions.read().unwrap();
+        match sessions.get(token) {
+            Some(s) => policy.lifetime(s.remember).saturating_sub(s.created.elapsed()),
+            None => policy.lifetime(false),
         }
-        None
+    }
+
+    /// Whether the session's password was entered recently enough for a
+    /// sensitive action (see `SessionPolicy::reauth_minutes`).
+    pub fn recently_authenticated(&self, token: &str) -> bool {
+        let reauth = self.policy().reauth_minutes;
+        let sessions = self.sessions.read().unwrap();
+        sessions.get(token).is_some_and(|s| reauth == 0 || s.authenticated.elapsed() < Duration::from_secs(reauth * 60))
+    }
+
+    /// Record that the session's user just re-entered their password.
+    pub fn mark_authenticated(&self, token: &str) {
+        if let Some(s) = self.sessions.write().unwrap().get_mut(token) {
+            s.authenticated = Instant::now();
+        }
+    }
+
+    /// Whether a session was opened with "remember me".
+    pub fn is_remembered(&self, token: &str) -> bool {
+        self.sessions.read().unwrap().get(token).is_some_and(|s| s.remember)
+    }
+
+    pub fn policy(&self) -> SessionPolicy {
+        self.policy.read().unwrap().clone()
+    }
+
+    /// Save and apply a new policy; returns it as stored (clamped).
+    pub fn set_policy(&self, policy: SessionPolicy) -> Result<SessionPolicy, String> {
+        let policy = policy.clamped();
+        policy.save()?;
+        *self.policy.write().unwrap() = policy.clone();
+        Ok(policy)
     }
 
     /// Destroy a session
@@ -547,8 +727,9 @@ impl SessionManager {
 
     /// Clean up expired sessions
     pub fn cleanup(&self) {
+        let policy = self.policy();
         let mut sessions = self.sessions.write().unwrap();
-        sessions.retain(|_, s| s.created.elapsed() < SESSION_LIFETIME);
+        sessions.retain(|_, s| s.live(&policy));
     }
 }
 
@@ -870,7 +1051,7 @@ pub fn protected_client_ips() -> Vec<String> {
     out
 }
 
-fn ip_in_cidr(target: &std::net::IpAddr, net: &std::net::IpAddr, prefix: u8) -> bool {
+pub(crate) fn ip_in_cidr(target: &std::net::IpAddr, net: &std::net::IpAddr, prefix: u8) -> bool {
     match (target, net) {
         (std::net::IpAddr::V4(t), std::net::IpAddr::V4(n)) => {
             if prefix > 32 { return false; }
@@ -1114,6 +1295,7 @@ impl LoginRateLimiter {
             tracing::info!("auth: failed login for {} from trusted IP {} (no lockout)", username, ip);
             return false;
         }
+        attacks::record(ip, username);
         if !cfg.enabled {
             self.audit_push(AuthLogEntry {
                 timestamp: now_secs(),
@@ -2497,6 +2679,57 @@ pub fn is_safe_name(name: &str) -> bool {
         && !name.contains("..")
 }
 
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    fn session(remember: bool) -> Session {
+        Session {
+            username: "u".into(),
+            created: Instant::now() - Duration::from_secs(3600),
+            remember,
+            last_seen: Default::default(),
+            authenticated: Instant::now(),
+            csrf: String::new(),
+        }
+    }
+
+    #[test]
+    fn policy_is_clamped_into_range() {
+        let p = SessionPolicy { lifetime_hours: 0, idle_minutes: 99_999, remember_days: 9_999, reauth_minutes: 5 }.clamped();
+        assert_eq!((p.lifetime_hours, p.idle_minutes, p.remember_days, p.reauth_minutes), (1, 1440, 365, 5));
+        assert_eq!(p.lifetime(false), Duration::from_secs(3600));
+        assert_eq!(p.lifetime(true), Duration::from_secs(365 * 86400));
+    }
+
+    #[test]
+    fn idle_timeout_spares_remembered_sessions() {
+        // Last seen at creation, an hour ago.
+        let policy = SessionPolicy { idle_minutes: 30, ..Default::default() };
+        assert!(!session(false).live(&policy));
+        assert!(session(true).live(&policy));
+        assert!(session(false).live(&SessionPolicy::default()), "idle 0 = no idle timeout");
+        let short = SessionPolicy { lifetime_hours: 1, ..Default::default() };
+        assert!(!session(false).live(&short), "lifetime still applies");
+    }
+}
+
+#[cfg(test)]
+mod caller_admin_tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn relayed_requests_are_admin_only_when_unscoped_and_unmarked() {
+        assert!(caller_is_admin(&TestRequest::default().to_http_request(), "cluster-node"));
+        let tenant = TestRequest::default().insert_header((tenancy::TENANT_HEADER, "acme")).to_http_request();
+        assert!(!caller_is_admin(&tenant, "cluster-node"));
+        let viewer = TestRequest::default().insert_header((NON_ADMIN_HEADER, "1")).to_http_request();
+        assert!(!caller_is_admin(&viewer, "cluster-node"));
+        assert!(!caller_is_admin(&TestRequest::default().to_http_request(), "apikey:ci"));
+    }
+}
+
 #[cfg(test)]
 mod secret_tests {
     use super::*;
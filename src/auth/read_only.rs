@@ -0,0 +1,97 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Read-only mode (`server.read_only` in wolfstack.toml).
+//!
+//! For a public demo dashboard or a NOC screen: everything can be looked
+//! at, nothing can be changed. While it is on, every state-changing
+//! request to `/api/` (or a public `/fn/` trigger) is answered 403 with
+//! [`message`] before it reaches a handler, whoever is logged in — an
+//! admin session or API key included. So are the console and VNC
+//! websockets: they're GETs, but each one is an interactive shell or
+//! desktop. Logging in and out still works.
+//!
+//! Calls from cluster peers carrying the cluster secret are let through:
+//! they are admin actions taken on another node's dashboard (or the
+//! cluster keeping itself in sync), not visitors to this one. The flag is
+//! re-read on every request, so `systemctl reload wolfstack` switches it
+//! either way; turning it off through the API is one of the writes it
+//! refuses.
+
+use actix_web::http::Method;
+
+/// Shown when no `server.read_only_message` is set.
+pub const DEFAULT_MESSAGE: &str = "This dashboard is read-only — changes are disabled.";
+
+/// Writes that don't change anything on the node.
+const ALLOWED: &[&str] = &[
+    "/api/auth/login",
+    "/api/auth/logout",
+    "/api/auth/passkey/login/start",
+    "/api/auth/passkey/login/finish",
+];
+
+pub fn enabled() -> bool {
+    crate::daemon_config::get().server.read_only
+}
+
+/// Text for the 403 (and the dashboard's banner).
+pub fn message() -> String {
+    crate::daemon_config::get().server.read_only_message.clone()
+        .unwrap_or_else(|| DEFAULT_MESSAGE.to_string())
+}
+
+/// Interactive sessions, refused whatever the method: the console and VNC
+/// websocket upgrades, and the ticket that opens a Proxmox VNC session.
+const INTERACTIVE: &[&str] = &[
+    "/ws/console/",
+    "/ws/remote-console/",
+    "/ws/pve-console/",
+    "/ws/pve-vnc/",
+    "/ws/vm-vnc/",
+    "/ws/container-vnc/",
+    "/api/pve-vnc-ticket/",
+];
+
+/// Whether read-only mode refuses `method path`. Ignores the flag — the
+/// caller checks [`enabled`] first.
+pub fn refuses(method: &Method, path: &str) -> bool {
+    if INTERACTIVE.iter().any(|p| path.starts_with(p)) {
+        return true;
+    }
+    super::csrf::is_state_changing(method)
+        && (path.starts_with("/api/") || path.starts_with("/fn/"))
+        && !ALLOWED.contains(&path.trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_api_writes_are_refused() {
+        assert!(refuses(&Method::POST, "/api/containers/docker/web/stop"));
+        assert!(refuses(&Method::DELETE, "/api/nodes/abc"));
+        assert!(refuses(&Method::PUT, "/api/logs/config"));
+        assert!(refuses(&Method::POST, "/api/daemon-config/reload"));
+        assert!(!refuses(&Method::GET, "/api/nodes"));
+        assert!(!refuses(&Method::HEAD, "/api/metrics"));
+        assert!(!refuses(&Method::POST, "/api/auth/login"));
+        assert!(!refuses(&Method::POST, "/api/auth/logout/"));
+        assert!(refuses(&Method::POST, "/fn/deploy-hook"));
+        assert!(!refuses(&Method::POST, "/status/subscribe"));
+    }
+
+    #[test]
+    fn consoles_are_refused() {
+        assert!(refuses(&Method::GET, "/ws/console/lxc/x"));
+        assert!(refuses(&Method::GET, "/ws/remote-console/node-b/host/host"));
+        assert!(refuses(&Method::GET, "/ws/pve-console/node-b/101"));
+        assert!(refuses(&Method::GET, "/ws/pve-vnc/101"));
+        assert!(refuses(&Method::GET, "/ws/vm-vnc/win11"));
+        assert!(refuses(&Method::GET, "/ws/container-vnc/lxc/desk"));
+        assert!(refuses(&Method::GET, "/api/pve-vnc-ticket/101"));
+        assert!(!refuses(&Method::GET, "/ws/api/nodes"));
+    }
+}
@@ -0,0 +1,309 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Tenancy — confining a team's users to the team's own resources.
+//!
+//! A WolfStack user may carry a `tenant` (see `WolfUser::tenant`). A
+//! non-admin user with one is *tenant-scoped*: the container, VM, storage
+//! mount, backup schedule and backup lists only show what that tenant
+//! owns, and any request naming someone else's resource by path answers
+//! 404 as if it didn't exist. Admins and users without a tenant see
+//! everything, as before.
+//!
+//! Ownership is recorded per node in `tenancy.json`, since that is where
+//! the resources live: a tenant-scoped user creating a resource claims it
+//! for their tenant, and an admin can assign or release anything with
+//! `POST /api/tenancy/assign`. Unowned resources are admin-only. A backup
+//! belongs to whoever owns the container or VM it was taken of.
+//!
+//! Requests a tenant-scoped user makes through another node's proxy carry
+//! [`TENANT_HEADER`] alongside the cluster secret, so the node that holds
+//! the resource applies the same scope.
+//!
+//! Host-level pages (networking, firewall, cluster settings) are not
+//! tenant resources; what a non-admin can do there is governed by their
+//! role, not by tenancy.
+
+use actix_web::HttpRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Carries a proxied user's tenant to the node that holds the resource.
+pub const TENANT_HEADER: &str = "X-WolfStack-Tenant";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Docker,
+    Lxc,
+    Vm,
+    Mount,
+    Schedule,
+    /// A backup entry. Never stored — it follows its container or VM.
+    Backup,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Docker => "docker",
+            Kind::Lxc => "lxc",
+            Kind::Vm => "vm",
+            Kind::Mount => "mount",
+            Kind::Schedule => "schedule",
+            Kind::Backup => "backup",
+        }
+    }
+}
+
+fn key(kind: Kind, id: &str) -> String {
+    format!("{}/{}", kind.as_str(), id)
+}
+
+fn ownership_path() -> String {
+    format!("{}/tenancy.json", crate::paths::get().config_dir)
+}
+
+/// Which tenant owns which resource on this node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ownership {
+    /// `"<kind>/<id>"` → tenant.
+    #[serde(default)]
+    pub owners: BTreeMap<String, String>,
+}
+
+impl Ownership {
+    pub fn load() -> Self {
+        std::fs::read_to_string(ownership_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = ownership_path();
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write tenancy config: {}", e))
+    }
+
+    pub fn owner(&self, kind: Kind, id: &str) -> Option<&str> {
+        if kind == Kind::Backup {
+            let entry = crate::backup::list_backups().into_iter().find(|b| b.id == id)?;
+            let (kind, name) = backup_target(&entry.target)?;
+            return self.owner(kind, name);
+        }
+        self.owners.get(&key(kind, id)).map(String::as_str)
+    }
+
+    /// Set (or with `None`, clear) the owner of a resource.
+    pub fn assign(&mut self, kind: Kind, id: &str, tenant: Option<&str>) -> Result<(), String> {
+        if kind == Kind::Backup {
+            return Err("a backup belongs to the owner of its container or VM — assign that instead".into());
+        }
+        match tenant {
+            Some(t) => { self.owners.insert(key(kind, id), t.to_string()); }
+            None => { self.owners.remove(&key(kind, id)); }
+        }
+        Ok(())
+    }
+}
+
+/// The resource a backup was taken of, if it is one a tenant can own
+/// (host folders, databases and config backups are admin-only).
+pub fn backup_target(target: &crate::backup::BackupTarget) -> Option<(Kind, &str)> {
+    let kind = match target.target_type {
+        crate::backup::BackupTargetType::Docker => Kind::Docker,
+        crate::backup::BackupTargetType::Lxc => Kind::Lxc,
+        crate::backup::BackupTargetType::Vm => Kind::Vm,
+        _ => return None,
+    };
+    Some((kind, &target.name))
+}
+
+/// Tenant names: the same rules as a username.
+pub fn validate_tenant(tenant: &str) -> Result<(), String> {
+    if tenant.len() > 64 || !super::is_safe_name(tenant) {
+        return Err("Invalid tenant — use alphanumeric, dash, underscore only (max 64)".into());
+    }
+    Ok(())
+}
+
+/// The tenant `caller` is confined to, or `None` when they see everything.
+/// Cluster peers are unscoped unless they forward a user's tenant.
+pub fn scope(req: &HttpRequest, caller: &str) -> Option<String> {
+    if caller == "cluster-node" {
+        return req.headers().get(TENANT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+    }
+    let store = super::users::UserStore::load();
+    let user = store.find(caller)?;
+    if user.role == "admin" {
+        return None;
+    }
+    user.tenant.clone().filter(|t| !t.is_empty())
+}
+
+/// The resource a request path names, if any. Collection endpoints
+/// (`/api/vms/create`, `/api/containers/docker/stats`, …) name none.
+pub fn resource_of(path: &str) -> Option<(Kind, String)> {
+    let rest = path.strip_prefix("/api/")?;
+    let segs: Vec<&str> = rest.split('/').collect();
+    let (kind, id, reserved): (Kind, &str, &[&str]) = match segs.as_slice() {
+        ["containers", "docker", id, ..] => (Kind::Docker, *id,
+            &["search", "pull", "create", "storage-quota", "stats", "images", "import", "networks", "build", "builds"]),
+        ["containers", "lxc", id, ..] => (Kind::Lxc, *id,
+            &["templates", "image-sources", "create", "import", "import-external", "stats", "criu-check"]),
+        ["vms", id, ..] => (Kind::Vm, *id,
+            &["wolfnet", "events", "prerequisites", "create", "storage", "isos", "host-devices",
+              "import-external", "discover-libvirt", "adopt-libvirt"]),
+        ["storage", "mounts", id, ..] => (Kind::Mount, *id, &[]),
+        ["backups", "schedules", id, ..] => (Kind::Schedule, *id, &[]),
+        // The stable machine API (api/v1.rs) names the same resources.
+        ["v1", "containers", "docker", id, ..] => (Kind::Docker, *id, &[]),
+        ["v1", "containers", "lxc", id, ..] => (Kind::Lxc, *id, &[]),
+        ["v1", "vms", id, ..] => (Kind::Vm, *id, &[]),
+        ["v1", "mounts", id, ..] => (Kind::Mount, *id, &[]),
+        ["backups", id, ..] => (Kind::Backup, *id,
+            &["stream", "delete-failed", "targets", "schedules", "test-storage", "import",
+              "scan-folder", "restore-from-path", "pbs"]),
+        _ => return None,
+    };
+    if id.is_empty() || reserved.contains(&id) {
+        return None;
+    }
+    Some((kind, id.to_string()))
+}
+
+/// Refuse a tenant-scoped caller a resource their tenant doesn't own.
+/// Called from `require_auth`, so every handler is covered.
+pub fn check(req: &HttpRequest, caller: &str) -> Result<(), String> {
+    let Some((kind, id)) = resource_of(req.path()) else { return Ok(()) };
+    let Some(tenant) = scope(req, caller) else { return Ok(()) };
+    owned_by(&Ownership::load(), &tenant, kind, &id)
+}
+
+/// The console/VNC websockets name their target in ways the path check
+/// doesn't cover (and may be relayed from another node), so each console
+/// handler checks its own target. A tenant-scoped caller gets consoles on
+/// their tenant's containers and VMs only — never a host shell.
+pub fn check_console(req: &HttpRequest, caller: &str, ctype: &str, name: &str) -> Result<(), String> {
+    let Some(tenant) = scope(req, caller) else { return Ok(()) };
+    let kind = match ctype {
+        "docker" | "docker-vnc" => Kind::Docker,
+        "lxc" | "lxc-vnc" | "pct" | "pct-vnc" => Kind::Lxc,
+        "vm" | "vm-vnc" => Kind::Vm,
+        _ => return Err("This console needs an admin".into()),
+    };
+    owned_by(&Ownership::load(), &tenant, kind, name)
+}
+
+/// Like [`check`], for handlers that name their container or VM in the
+/// body or query string, where the path check can't see it.
+pub fn check_named(req: &HttpRequest, caller: &str, kind: Kind, name: &str) -> Result<(), String> {
+    let Some(tenant) = scope(req, caller) else { return Ok(()) };
+    owned_by(&Ownership::load(), &tenant, kind, name)
+}
+
+/// `Ok` if `tenant` owns the resource; otherwise the error reads as if it
+/// didn't exist.
+fn owned_by(ownership: &Ownership, tenant: &str, kind: Kind, id: &str) -> Result<(), String> {
+    if ownership.owner(kind, id) == Some(tenant) {
+        Ok(())
+    } else {
+        Err(format!("{} '{}' not found", kind.as_str(), id))
+    }
+}
+
+/// Drop from `items` what a scope can't see. `None` sees everything;
+/// items `resource` maps to `None` are admin-only.
+pub fn visible<T>(scope: Option<&str>, items: Vec<T>, resource: impl Fn(&T) -> Option<(Kind, &str)>) -> Vec<T> {
+    let Some(tenant) = scope else { return items };
+    let ownership = Ownership::load();
+    items.into_iter()
+        .filter(|item| resource(item).is_some_and(|(kind, id)| ownership.owner(kind, id) == Some(tenant)))
+        .collect()
+}
+
+/// Whether a scope may back up `targets` (`None` = everything on the
+/// node, which a tenant-scoped caller never may).
+pub fn check_backup_targets(scope: Option<&str>, targets: Option<&[crate::backup::BackupTarget]>) -> Result<(), String> {
+    let Some(tenant) = scope else { return Ok(()) };
+    let Some(targets) = targets else {
+        return Err("Pick the containers or VMs to back up — backing up the whole node needs an admin".into());
+    };
+    let ownership = Ownership::load();
+    for target in targets {
+        match backup_target(target) {
+            Some((kind, id)) if ownership.owner(kind, id) == Some(tenant) => {}
+            _ => return Err(format!("'{}' not found", target.name)),
+        }
+    }
+    Ok(())
+}
+
+/// Record a resource a tenant-scoped caller just created as their
+/// tenant's. No-op for unscoped callers.
+pub fn claim(req: &HttpRequest, caller: &str, kind: Kind, id: &str) {
+    let Some(tenant) = scope(req, caller) else { return };
+    let mut ownership = Ownership::load();
+    if ownership.assign(kind, id, Some(&tenant)).is_ok()
+        && let Err(e) = ownership.save()
+    {
+        tracing::warn!("tenancy: could not record {} '{}' for tenant '{}': {}", kind.as_str(), id, tenant, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_paths_are_recognised() {
+        assert_eq!(resource_of("/api/containers/docker/web/action"), Some((Kind::Docker, "web".into())));
+        assert_eq!(resource_of("/api/containers/lxc/db"), Some((Kind::Lxc, "db".into())));
+        assert_eq!(resource_of("/api/vms/win11/volumes/data/resize"), Some((Kind::Vm, "win11".into())));
+        assert_eq!(resource_of("/api/storage/mounts/m1/objects"), Some((Kind::Mount, "m1".into())));
+        assert_eq!(resource_of("/api/backups/schedules/s1/run"), Some((Kind::Schedule, "s1".into())));
+        assert_eq!(resource_of("/api/backups/b1/restore"), Some((Kind::Backup, "b1".into())));
+        assert_eq!(resource_of("/api/v1/containers/lxc/db/state"), Some((Kind::Lxc, "db".into())));
+        assert_eq!(resource_of("/api/v1/mounts/m1"), Some((Kind::Mount, "m1".into())));
+        // Collections and create endpoints name no resource.
+        assert_eq!(resource_of("/api/containers/docker"), None);
+        assert_eq!(resource_of("/api/containers/docker/create"), None);
+        assert_eq!(resource_of("/api/containers/lxc/image-sources/custom"), None);
+        assert_eq!(resource_of("/api/vms"), None);
+        assert_eq!(resource_of("/api/vms/create"), None);
+        assert_eq!(resource_of("/api/backups/schedules"), None);
+        assert_eq!(resource_of("/api/backups/pbs/status"), None);
+        assert_eq!(resource_of("/api/nodes/abc"), None);
+        assert_eq!(resource_of("/api/v1/ip-mappings/x"), None);
+    }
+
+    #[test]
+    fn ownership_assigns_and_releases() {
+        let mut o = Ownership::default();
+        o.assign(Kind::Docker, "web", Some("team-a")).unwrap();
+        assert_eq!(o.owner(Kind::Docker, "web"), Some("team-a"));
+        assert_eq!(o.owner(Kind::Lxc, "web"), None);
+        o.assign(Kind::Docker, "web", None).unwrap();
+        assert_eq!(o.owner(Kind::Docker, "web"), None);
+        assert!(o.assign(Kind::Backup, "b1", Some("team-a")).is_err());
+    }
+
+    #[test]
+    fn only_the_owning_tenant_may_name_a_resource() {
+        let mut o = Ownership::default();
+        o.assign(Kind::Lxc, "db", Some("team-a")).unwrap();
+        assert!(owned_by(&o, "team-a", Kind::Lxc, "db").is_ok());
+        assert_eq!(owned_by(&o, "team-b", Kind::Lxc, "db"), Err("lxc 'db' not found".into()));
+        // Same name, other runtime: a different resource.
+        assert!(owned_by(&o, "team-a", Kind::Docker, "db").is_err());
+        // Unowned resources are admin-only.
+        assert!(owned_by(&o, "team-a", Kind::Vm, "win11").is_err());
+    }
+}
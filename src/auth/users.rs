@@ -119,6 +119,11 @@ pub struct WolfUser {
     /// this list so an operator can't lock themselves out.
     #[serde(default)]
     pub allowed_clusters: Vec<String>,
+    /// Tenant (team) this user belongs to — see `auth::tenancy`. A
+    /// non-admin user with a tenant only sees and operates on resources
+    /// that tenant owns. `None` = not tenant-scoped.
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 impl WolfUser {
@@ -0,0 +1,587 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Guest and node availability history, for SLA reporting.
+//!
+//! Once a minute each node compares its Docker, LXC and VM lists (the
+//! background inventory snapshots) with what it saw last and appends any
+//! state change to `<availability_dir>/YYYY-MM.jsonl`. A guest is
+//! `running`, `stopped` (by WolfStack — the UI, API, a backup, UPS
+//! shutdown), `crashed` (went down without being asked: a non-zero Docker
+//! exit, or an LXC/VM that stopped on its own) or `gone` (deleted).
+//!
+//! The node's own downtime comes from a heartbeat file touched every tick:
+//! on start, a heartbeat older than a few ticks is recorded as the node
+//! going `down` at that time and `up` now. While the node is down its
+//! guests count as down too.
+//!
+//! Each month's file opens with every guest's state carried over at the
+//! first instant of the month, so a month is self-contained. The report
+//! (`/api/reports/availability`) turns a month into availability
+//! percentages per guest and per node; like the inventory export, the
+//! node serving it asks its peers for their own rows. Thirteen months of
+//! history are kept.
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{LazyLock, Mutex};
+
+use crate::agent::Node;
+use crate::api::AppState;
+
+const TICK_SECS: u64 = 60;
+/// A heartbeat older than this at start-up means the node was down.
+const DOWN_AFTER_SECS: u64 = 3 * TICK_SECS;
+/// A stop WolfStack was asked for within this long counts as planned.
+const REQUEST_WINDOW_SECS: u64 = 600;
+/// Ticks a guest must be missing from its runtime's list before it's `gone`
+/// — one empty `docker ps` from a restarting daemon shouldn't delete history.
+const GONE_AFTER_MISSES: u32 = 2;
+const KEEP_MONTHS: usize = 13;
+
+/// One line of the history file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    /// Unix seconds.
+    pub ts: u64,
+    /// `node`, `docker`, `lxc` or `vm`.
+    pub kind: String,
+    pub name: String,
+    /// Guests: `running`, `stopped`, `crashed` or `gone`. Node: `up` or `down`.
+    pub state: String,
+}
+
+/// One line of the report. `availability_percent` is `None` when nothing
+/// was observed in the month (a peer that didn't answer, say).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AvailabilityRow {
+    pub cluster: String,
+    pub node: String,
+    /// Lets the UI fetch a guest's history from the node that has it.
+    #[serde(default)]
+    pub node_id: String,
+    pub kind: String,
+    pub name: String,
+    #[serde(default)]
+    pub availability_percent: Option<f64>,
+    /// Seconds the guest existed (and WolfStack was watching) this month.
+    #[serde(default)]
+    pub observed_secs: u64,
+    #[serde(default)]
+    pub downtime_secs: u64,
+    #[serde(default)]
+    pub starts: u32,
+    /// Stops WolfStack was asked for.
+    #[serde(default)]
+    pub stops: u32,
+    /// Crashes for a guest; periods offline for a node.
+    #[serde(default)]
+    pub outages: u32,
+}
+
+pub const CSV_HEADER: &str = "cluster,node,kind,name,availability_percent,observed_hours,downtime_minutes,starts,stops,outages";
+
+fn dir() -> String {
+    crate::paths::get().availability_dir
+}
+
+fn month_of(ts: u64) -> String {
+    chrono::DateTime::from_timestamp(ts as i64, 0)
+        .unwrap_or_default()
+        .format("%Y-%m")
+        .to_string()
+}
+
+/// First and one-past-last second of a `YYYY-MM` month.
+pub fn month_bounds(month: &str) -> Option<(u64, u64)> {
+    use chrono::{Datelike, NaiveDate};
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)?
+    };
+    let secs = |d: NaiveDate| d.and_hms_opt(0, 0, 0).map(|t| t.and_utc().timestamp() as u64);
+    Some((secs(start)?, secs(next)?))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Every change recorded for `month`, oldest first.
+pub fn load_month(month: &str) -> Vec<StateChange> {
+    std::fs::read_to_string(format!("{}/{}.jsonl", dir(), month))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+// ─── Sampler ───
+
+type Key = (String, String);
+
+#[derive(Default)]
+struct Tracker {
+    /// Last recorded state per (kind, name); the node is ("node", hostname).
+    last: HashMap<Key, String>,
+    missing: HashMap<Key, u32>,
+    /// When WolfStack was last asked to stop / restart / pause a guest.
+    requested: HashMap<Key, u64>,
+    /// Month whose file has had its carried-over states written.
+    month: String,
+    hostname: String,
+}
+
+static TRACKER: LazyLock<Mutex<Tracker>> = LazyLock::new(|| Mutex::new(Tracker::default()));
+
+/// Note that WolfStack is about to stop, restart or pause a guest, so the
+/// stop it's about to see is recorded as planned rather than a crash.
+/// `kind` is `docker`, `lxc` or `vm`.
+pub fn note_requested(kind: &str, name: &str) {
+    TRACKER.lock().unwrap().requested.insert((kind.to_string(), name.to_string()), now_secs());
+}
+
+impl Tracker {
+    fn append(&mut self, change: StateChange) {
+        let path = format!("{}/{}.jsonl", dir(), month_of(change.ts));
+        let line = serde_json::to_string(&change).unwrap_or_default();
+        let written = std::fs::create_dir_all(dir()).and_then(|_| {
+            let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(f, "{}", line)
+        });
+        if let Err(e) = written {
+            tracing::warn!("availability: failed to write {}: {}", path, e);
+        }
+        self.last.insert((change.kind, change.name), change.state);
+    }
+
+    fn record(&mut self, ts: u64, kind: &str, name: &str, state: &str) {
+        let key = (kind.to_string(), name.to_string());
+        if self.last.get(&key).map(String::as_str) == Some(state) {
+            return;
+        }
+        self.append(StateChange { ts, kind: kind.to_string(), name: name.to_string(), state: state.to_string() });
+    }
+
+    /// Start a new month's file with every known state carried over, and
+    /// drop files past the retention.
+    fn open_month(&mut self, now: u64) {
+        let month = month_of(now);
+        if self.month == month {
+            return;
+        }
+        self.month = month.clone();
+        let path = format!("{}/{}.jsonl", dir(), month);
+        if std::path::Path::new(&path).exists() {
+            return;
+        }
+        let start = month_bounds(&month).map(|(s, _)| s).unwrap_or(now);
+        let mut carried: Vec<StateChange> = self.last.iter()
+            .filter(|(_, state)| state.as_str() != "gone")
+            .map(|((kind, name), state)| StateChange { ts: start, kind: kind.clone(), name: name.clone(), state: state.clone() })
+            .collect();
+        carried.sort_by(|a, b| (&a.kind, &a.name).cmp(&(&b.kind, &b.name)));
+        for change in carried {
+            self.append(change);
+        }
+        prune();
+    }
+
+    /// State of a guest that isn't running now.
+    fn not_running(&self, key: &Key, now: u64, docker_status: Option<&str>) -> String {
+        match self.last.get(key).map(String::as_str) {
+            Some(s @ ("stopped" | "crashed")) => return s.to_string(),
+            // Unknown or re-created: nothing to have crashed from.
+            None | Some("gone") => return "stopped".to_string(),
+            _ => {}
+        }
+        if self.requested.get(key).is_some_and(|t| now.saturating_sub(*t) <= REQUEST_WINDOW_SECS) {
+            return "stopped".to_string();
+        }
+        match docker_status {
+            Some(status) if docker_exit_code(status) == Some(0) => "stopped".to_string(),
+            _ => "crashed".to_string(),
+        }
+    }
+
+    /// Compare one runtime's current list with the last states. `None` =
+    /// no snapshot this tick, so nothing about that runtime is concluded.
+    fn observe(&mut self, now: u64, kind: &str, guests: Option<Vec<(String, bool, Option<String>)>>) {
+        let Some(guests) = guests else { return };
+        let mut seen = std::collections::HashSet::new();
+        for (name, running, docker_status) in guests {
+            let key = (kind.to_string(), name.clone());
+            self.missing.remove(&key);
+            let state = if running {
+                "running".to_string()
+            } else {
+                self.not_running(&key, now, docker_status.as_deref())
+            };
+            self.record(now, kind, &name, &state);
+            seen.insert(name);
+        }
+        let vanished: Vec<Key> = self.last.iter()
+            .filter(|((k, n), state)| k == kind && state.as_str() != "gone" && !seen.contains(n))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in vanished {
+            let misses = self.missing.entry(key.clone()).or_insert(0);
+            *misses += 1;
+            if *misses >= GONE_AFTER_MISSES {
+                self.missing.remove(&key);
+                self.record(now, &key.0, &key.1, "gone");
+            }
+        }
+    }
+}
+
+/// Exit code from a Docker status line such as `Exited (137) 2 minutes ago`.
+fn docker_exit_code(status: &str) -> Option<i32> {
+    let rest = status.split_once("Exited (")?.1;
+    rest.split_once(')')?.0.trim().parse().ok()
+}
+
+fn prune() {
+    let Ok(entries) = std::fs::read_dir(dir()) else { return };
+    let mut months: Vec<String> = entries.flatten()
+        .filter_map(|e| e.file_name().to_str()?.strip_suffix(".jsonl").map(str::to_string))
+        .collect();
+    months.sort();
+    let excess = months.len().saturating_sub(KEEP_MONTHS);
+    for month in &months[..excess] {
+        let _ = std::fs::remove_file(format!("{}/{}.jsonl", dir(), month));
+    }
+}
+
+fn heartbeat_path() -> String {
+    format!("{}/heartbeat", dir())
+}
+
+/// Start the once-a-minute sampler. Call after the inventory collectors.
+pub fn start(hostname: String) {
+    tokio::spawn(async move {
+        let now = now_secs();
+        let last_alive: Option<u64> = std::fs::read_to_string(heartbeat_path())
+            .ok()
+            .and_then(|s| s.trim().parse().ok());
+        {
+            let mut t = TRACKER.lock().unwrap();
+            t.hostname = hostname.clone();
+            // Pick up where we left off: the last month with a file.
+            let mut month = month_of(now);
+            let mut history = load_month(&month);
+            if history.is_empty() && let Some(prev) = last_alive.map(month_of) {
+                month = prev;
+                history = load_month(&month);
+            }
+            for change in history {
+                t.last.insert((change.kind, change.name), change.state);
+            }
+            if let Some(alive) = last_alive.filter(|a| now.saturating_sub(*a) > DOWN_AFTER_SECS)
+                && t.last.get(&("node".to_string(), hostname.clone())).map(String::as_str) == Some("up")
+            {
+                t.record(alive, "node", &hostname, "down");
+            }
+            t.month = month;
+            t.open_month(now);
+            t.record(now, "node", &hostname, "up");
+        }
+        // Give the inventory collectors a tick to fill their slots.
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        loop {
+            let now = now_secs();
+            if let Err(e) = std::fs::create_dir_all(dir())
+                .and_then(|_| std::fs::write(heartbeat_path(), now.to_string()))
+            {
+                tracing::warn!("availability: failed to write heartbeat: {}", e);
+            }
+            sample(now);
+            tokio::time::sleep(std::time::Duration::from_secs(TICK_SECS)).await;
+        }
+    });
+}
+
+fn sample(now: u64) {
+    use crate::monitoring::inventory;
+    let containers = |list: Option<Vec<crate::containers::ContainerInfo>>, docker: bool| {
+        list.map(|cs| cs.into_iter()
+            .map(|c| {
+                let running = c.state == "running";
+                (c.name, running, docker.then_some(c.status))
+            })
+            .collect::<Vec<_>>())
+    };
+    let docker = if crate::containers::has_docker_cached() { containers(inventory::DOCKER.get(), true) } else { None };
+    let lxc = if crate::containers::has_lxc_cached() { containers(inventory::LXC.get(), false) } else { None };
+    let vms = inventory::VMS.get()
+        .map(|vms| vms.into_iter().map(|vm| (vm.name, vm.running, None)).collect::<Vec<_>>());
+
+    let mut t = TRACKER.lock().unwrap();
+    t.open_month(now);
+    let hostname = t.hostname.clone();
+    t.record(now, "node", &hostname, "up");
+    t.observe(now, "docker", docker);
+    t.observe(now, "lxc", lxc);
+    t.observe(now, "vm", vms);
+    let stale = now.saturating_sub(REQUEST_WINDOW_SECS);
+    t.requested.retain(|_, at| *at >= stale);
+}
+
+// ─── Report ───
+
+/// Seconds of `[a, b)` not covered by any of `gaps`.
+fn covered(a: u64, b: u64, gaps: &[(u64, u64)]) -> u64 {
+    let mut secs = b.saturating_sub(a);
+    for &(ga, gb) in gaps {
+        let (lo, hi) = (ga.max(a), gb.min(b));
+        if hi > lo {
+            secs -= hi - lo;
+        }
+    }
+    secs
+}
+
+/// Availability for every guest and the node in `[start, end)` from that
+/// month's changes. Rows carry kind and name only; the caller fills in
+/// cluster and node.
+pub fn compute(changes: &[StateChange], start: u64, end: u64) -> Vec<AvailabilityRow> {
+    let mut by_key: HashMap<Key, Vec<&StateChange>> = HashMap::new();
+    for c in changes.iter().filter(|c| c.ts < end) {
+        by_key.entry((c.kind.clone(), c.name.clone())).or_default().push(c);
+    }
+    for list in by_key.values_mut() {
+        list.sort_by_key(|c| c.ts);
+    }
+
+    // Node downtime, which every guest shares.
+    let mut node_down: Vec<(u64, u64)> = Vec::new();
+    for list in by_key.iter().filter(|((k, _), _)| k == "node").map(|(_, l)| l) {
+        for (i, c) in list.iter().enumerate() {
+            if c.state == "down" {
+                let until = list.get(i + 1).map(|n| n.ts).unwrap_or(end);
+                node_down.push((c.ts.max(start), until));
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for ((kind, name), list) in &by_key {
+        let mut row = AvailabilityRow { kind: kind.clone(), name: name.clone(), ..Default::default() };
+        let mut up = 0;
+        for (i, c) in list.iter().enumerate() {
+            let from = c.ts.max(start);
+            let until = list.get(i + 1).map(|n| n.ts).unwrap_or(end).max(from);
+            let is_up = match c.state.as_str() {
+                "gone" => continue,
+                "running" => true,
+                "up" => kind == "node",
+                _ => false,
+            };
+            row.observed_secs += until - from;
+            if is_up {
+                up += if kind == "node" { until - from } else { covered(from, until, &node_down) };
+            }
+            if i > 0 && list[i - 1].state != c.state {
+                match c.state.as_str() {
+                    "running" | "up" => row.starts += 1,
+                    "stopped" => row.stops += 1,
+                    "crashed" | "down" => row.outages += 1,
+                    _ => {}
+                }
+            }
+        }
+        row.downtime_secs = row.observed_secs - up;
+        row.availability_percent = (row.observed_secs > 0)
+            .then(|| (up as f64 / row.observed_secs as f64 * 10000.0).round() / 100.0);
+        rows.push(row);
+    }
+    rows
+}
+
+/// This node's rows for `month`.
+pub fn collect_local(self_node: &Node, month: &str) -> Vec<AvailabilityRow> {
+    let Some((start, end)) = month_bounds(month) else { return Vec::new() };
+    let end = end.min(now_secs());
+    let cluster = crate::inventory_report::cluster_of(self_node);
+    let mut rows = compute(&load_month(month), start, end);
+    for r in rows.iter_mut() {
+        r.cluster = cluster.clone();
+        r.node = self_node.hostname.clone();
+        r.node_id = self_node.id.clone();
+    }
+    rows
+}
+
+/// Rows for the whole cluster: this node's plus each WolfStack peer's own
+/// `scope=local` report. Peers that don't answer get an empty node row.
+pub async fn collect_cluster(state: &web::Data<AppState>, month: &str) -> Vec<AvailabilityRow> {
+    let nodes = state.cluster.get_all_nodes();
+    let mut rows = match nodes.iter().find(|n| n.is_self).cloned() {
+        Some(me) => {
+            let month = month.to_string();
+            web::block(move || collect_local(&me, &month)).await.unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let peers: Vec<Node> = nodes.into_iter().filter(|n| !n.is_self && n.node_type == "wolfstack").collect();
+    let secret = state.cluster_secret.clone();
+    let handles: Vec<_> = peers.into_iter().map(|peer| {
+        let secret = secret.clone();
+        let path = format!("/api/reports/availability?scope=local&format=json&month={}", month);
+        tokio::spawn(async move {
+            if peer.online {
+                for url in crate::api::build_node_urls(&peer.address, peer.port, &path) {
+                    let Ok(resp) = crate::api::API_HTTP_CLIENT.get(&url)
+                        .timeout(std::time::Duration::from_secs(30))
+                        .header("X-WolfStack-Secret", &secret)
+                        .send().await
+                    else { continue };
+                    if !resp.status().is_success() { continue; }
+                    if let Ok(v) = resp.json::<serde_json::Value>().await
+                        && let Ok(rows) = serde_json::from_value::<Vec<AvailabilityRow>>(v["rows"].clone())
+                    {
+                        return rows;
+                    }
+                }
+            }
+            vec![AvailabilityRow {
+                cluster: crate::inventory_report::cluster_of(&peer),
+                node: peer.hostname.clone(),
+                node_id: peer.id.clone(),
+                kind: "node".to_string(),
+                name: peer.hostname.clone(),
+                ..Default::default()
+            }]
+        })
+    }).collect();
+    for h in handles {
+        if let Ok(peer_rows) = h.await {
+            rows.extend(peer_rows);
+        }
+    }
+    sort_rows(&mut rows);
+    rows
+}
+
+/// Cluster, node, then the node before its guests, then kind and name.
+pub fn sort_rows(rows: &mut [AvailabilityRow]) {
+    let rank = |k: &str| match k { "node" => 0, "vm" => 1, "lxc" => 2, "docker" => 3, _ => 4 };
+    rows.sort_by(|a, b| {
+        (a.cluster.as_str(), a.node.as_str(), rank(&a.kind), a.name.as_str())
+            .cmp(&(b.cluster.as_str(), b.node.as_str(), rank(&b.kind), b.name.as_str()))
+    });
+}
+
+pub fn to_csv(rows: &[AvailabilityRow]) -> String {
+    use crate::inventory_report::csv_field;
+    let mut out = String::from(CSV_HEADER);
+    out.push_str("\r\n");
+    for r in rows {
+        let fields = [
+            csv_field(&r.cluster),
+            csv_field(&r.node),
+            csv_field(&r.kind),
+            csv_field(&r.name),
+            r.availability_percent.map(|p| format!("{:.2}", p)).unwrap_or_default(),
+            format!("{:.1}", r.observed_secs as f64 / 3600.0),
+            format!("{:.1}", r.downtime_secs as f64 / 60.0),
+            r.starts.to_string(),
+            r.stops.to_string(),
+            r.outages.to_string(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ch(ts: u64, kind: &str, name: &str, state: &str) -> StateChange {
+        StateChange { ts, kind: kind.into(), name: name.into(), state: state.into() }
+    }
+
+    fn row<'a>(rows: &'a [AvailabilityRow], name: &str) -> &'a AvailabilityRow {
+        rows.iter().find(|r| r.name == name).unwrap()
+    }
+
+    #[test]
+    fn month_bounds_roll_over_december() {
+        let (s, e) = month_bounds("2026-12").unwrap();
+        assert_eq!(e - s, 31 * 86400);
+        assert_eq!(month_of(e), "2027-01");
+        assert!(month_bounds("2026-13").is_none());
+    }
+
+    #[test]
+    fn crashes_and_node_downtime_count_against_guests() {
+        let changes = vec![
+            ch(0, "node", "pve1", "up"),
+            ch(0, "vm", "web", "running"),
+            ch(0, "docker", "db", "running"),
+            ch(100, "vm", "web", "crashed"),
+            ch(150, "vm", "web", "running"),
+            ch(200, "docker", "db", "stopped"),
+            ch(300, "docker", "db", "running"),
+            ch(600, "node", "pve1", "down"),
+            ch(700, "node", "pve1", "up"),
+        ];
+        let rows = compute(&changes, 0, 1000);
+        let web = row(&rows, "web");
+        assert_eq!((web.observed_secs, web.downtime_secs, web.outages, web.starts), (1000, 150, 1, 1));
+        assert_eq!(web.availability_percent, Some(85.0));
+        let db = row(&rows, "db");
+        assert_eq!((db.downtime_secs, db.stops, db.outages), (200, 1, 0));
+        let node = row(&rows, "pve1");
+        assert_eq!((node.downtime_secs, node.outages, node.starts), (100, 1, 1));
+        assert_eq!(node.availability_percent, Some(90.0));
+    }
+
+    #[test]
+    fn gone_guests_stop_being_observed() {
+        let changes = vec![ch(0, "lxc", "old", "running"), ch(400, "lxc", "old", "gone")];
+        let old = &compute(&changes, 0, 1000)[0];
+        assert_eq!((old.observed_secs, old.downtime_secs), (400, 0));
+        assert_eq!(old.availability_percent, Some(100.0));
+    }
+
+    #[test]
+    fn unrequested_stops_are_crashes() {
+        let key = ("lxc".to_string(), "web".to_string());
+        let mut t = Tracker::default();
+        t.last.insert(key.clone(), "running".into());
+        assert_eq!(t.not_running(&key, 1000, None), "crashed");
+        t.requested.insert(key.clone(), 900);
+        assert_eq!(t.not_running(&key, 1000, None), "stopped");
+
+        let d = ("docker".to_string(), "api".to_string());
+        t.last.insert(d.clone(), "running".into());
+        assert_eq!(t.not_running(&d, 1000, Some("Exited (0) 3 seconds ago")), "stopped");
+        assert_eq!(t.not_running(&d, 1000, Some("Exited (137) 3 seconds ago")), "crashed");
+        t.last.insert(d.clone(), "crashed".into());
+        assert_eq!(t.not_running(&d, 1000, Some("Exited (0) 3 seconds ago")), "crashed");
+        assert_eq!(t.not_running(&("vm".into(), "new".into()), 1000, None), "stopped");
+    }
+
+    #[test]
+    fn csv_formats_hours_and_minutes() {
+        let r = AvailabilityRow {
+            cluster: "prod".into(), node: "pve1".into(), kind: "vm".into(), name: "web".into(),
+            availability_percent: Some(99.5), observed_secs: 7200, downtime_secs: 90, outages: 1,
+            ..Default::default()
+        };
+        let csv = to_csv(&[r]);
+        assert_eq!(csv.lines().nth(1).unwrap(), "prod,pve1,vm,web,99.50,2.0,1.5,0,0,1");
+    }
+}
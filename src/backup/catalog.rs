@@ -0,0 +1,544 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Backup catalog browsing — file-level restore.
+//!
+//! Lists the files inside a backup and restores just the ones picked, so
+//! getting one config file back doesn't mean extracting a whole container.
+//! Tarball backups (local, NFS, SMB, WolfDisk, S3, PBS blobs) are listed
+//! with `tar -tv`; PBS file-level (pxar) snapshots with
+//! `proxmox-backup-client catalog dump`, which reads the snapshot's catalog
+//! without downloading any file data.
+//!
+//! Paths are relative to the archive root. A PBS snapshot can hold several
+//! pxar archives, so its paths start with the archive name
+//! (`root.pxar/etc/hosts`).
+//!
+//! Picked items are extracted into a staging dir first and then copied to
+//! `<dest>/<name>`, so nothing outside the selection is ever written.
+//! Catalogs are cached for a while: listing a large tarball means reading
+//! it end to end, and the browser asks for one directory at a time.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::{BackupEntry, BackupStorage, StorageType};
+
+const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+const CACHE_MAX: usize = 8;
+
+/// One file or directory in a backup.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    /// Path relative to the archive root, no leading or trailing slash.
+    pub path: String,
+    /// "file", "dir", "link" or "other" (devices, fifos, sockets).
+    pub kind: String,
+    pub size: u64,
+    /// "YYYY-MM-DD HH:MM:SS"; empty when the source doesn't record it.
+    pub mtime: String,
+}
+
+/// Every entry in one backup.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    pub entries: Vec<CatalogEntry>,
+    /// What tar member names carry in front of `path` ("./" for archives
+    /// made with `tar -C dir .`), needed to name members when extracting.
+    pub member_prefix: String,
+}
+
+impl Catalog {
+    /// Is `path` in the backup — listed itself, or a directory implied by
+    /// a deeper entry?
+    pub fn contains(&self, path: &str) -> bool {
+        let under = format!("{}/", path);
+        self.entries.iter().any(|e| e.path == path || e.path.starts_with(&under))
+    }
+
+    fn kind_of(&self, path: &str) -> &str {
+        self.entries.iter().find(|e| e.path == path).map(|e| e.kind.as_str()).unwrap_or("dir")
+    }
+}
+
+type CachedCatalog = (Instant, Arc<Catalog>);
+
+static CATALOGS: LazyLock<Mutex<HashMap<String, CachedCatalog>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn cached(key: &str, load: impl FnOnce() -> Result<Catalog, String>) -> Result<Arc<Catalog>, String> {
+    if let Ok(cache) = CATALOGS.lock()
+        && let Some((at, cat)) = cache.get(key)
+        && at.elapsed() < CACHE_TTL
+    {
+        return Ok(cat.clone());
+    }
+    let cat = Arc::new(load()?);
+    if let Ok(mut cache) = CATALOGS.lock() {
+        cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        while cache.len() >= CACHE_MAX {
+            let Some(oldest) = cache.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) else { break };
+            cache.remove(&oldest);
+        }
+        cache.insert(key.to_string(), (Instant::now(), cat.clone()));
+    }
+    Ok(cat)
+}
+
+/// Split off the first `n` whitespace-separated fields, returning them and
+/// the untouched remainder (names can contain runs of spaces).
+fn split_fields(line: &str, n: usize) -> Option<(Vec<&str>, &str)> {
+    let mut fields = Vec::with_capacity(n);
+    let mut rest = line.trim_start();
+    for _ in 0..n {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    Some((fields, rest))
+}
+
+fn normalise_path(name: &str) -> String {
+    let name = name.strip_prefix("./").unwrap_or(name);
+    name.trim_matches('/').to_string()
+}
+
+/// One line of `tar -tv --full-time` (GNU):
+/// `-rw-r--r-- root/root 220 2026-01-02 03:04:05 ./etc/hosts`.
+/// Returns the entry and the member prefix it was listed with.
+pub fn parse_tar_line(line: &str) -> Option<(CatalogEntry, &'static str)> {
+    let (fields, name) = split_fields(line, 5)?;
+    let (kind, name) = match fields[0].chars().next()? {
+        '-' => ("file", name),
+        'd' => ("dir", name),
+        'l' => ("link", name.split(" -> ").next().unwrap_or(name)),
+        'h' => ("file", name.split(" link to ").next().unwrap_or(name)),
+        _ => ("other", name),
+    };
+    let prefix = if name.starts_with("./") { "./" } else if name.starts_with('/') { "/" } else { "" };
+    let path = normalise_path(name);
+    if path.is_empty() || path == "." {
+        return None;
+    }
+    Some((CatalogEntry {
+        path,
+        kind: kind.to_string(),
+        size: fields[2].parse().unwrap_or(0),
+        mtime: format!("{} {}", fields[3], fields[4]),
+    }, prefix))
+}
+
+/// One line of `proxmox-backup-client catalog dump`:
+/// `f "/root.pxar.didx/etc/hosts" 220 2026-01-02T03:04:05Z`, or just the
+/// type and path for anything that isn't a regular file.
+pub fn parse_catalog_line(line: &str) -> Option<CatalogEntry> {
+    let (kind, rest) = line.split_once(' ')?;
+    let kind = match kind {
+        "f" | "h" => "file",
+        "d" => "dir",
+        "l" => "link",
+        "b" | "c" | "p" | "s" => "other",
+        _ => return None,
+    };
+    // Rust Debug quoting: only \" and \\ can appear in a path we care about.
+    let rest = rest.strip_prefix('"')?;
+    let mut path = String::new();
+    let mut chars = rest.char_indices();
+    let mut end = None;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => { if let Some((_, n)) = chars.next() { path.push(n); } }
+            '"' => { end = Some(i); break; }
+            _ => path.push(c),
+        }
+    }
+    let tail = rest[end? + 1..].trim();
+    let path = path.trim_matches('/');
+    let (archive, inner) = path.split_once('/').unwrap_or((path, ""));
+    let archive = archive.strip_suffix(".didx").unwrap_or(archive);
+    if archive.is_empty() {
+        return None;
+    }
+    let path = if inner.is_empty() { archive.to_string() } else { format!("{}/{}", archive, inner) };
+    let mut tail = tail.split_whitespace();
+    let size = tail.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let mtime = tail.next().unwrap_or("").trim_end_matches('Z').replacen('T', " ", 1);
+    Some(CatalogEntry { path, kind: kind.to_string(), size, mtime })
+}
+
+/// The direct children of `dir` ("" for the root), directories first.
+/// Directories the archive only implies (no entry of their own) are
+/// filled in.
+pub fn list_dir(entries: &[CatalogEntry], dir: &str) -> Vec<CatalogEntry> {
+    let dir = dir.trim_matches('/');
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    let mut children: BTreeMap<&str, CatalogEntry> = BTreeMap::new();
+    for e in entries {
+        let Some(rest) = e.path.strip_prefix(&prefix) else { continue };
+        if rest.is_empty() {
+            continue;
+        }
+        match rest.split_once('/') {
+            None => { children.insert(rest, e.clone()); }
+            Some((name, _)) => {
+                children.entry(name).or_insert_with(|| CatalogEntry {
+                    path: format!("{}{}", prefix, name),
+                    kind: "dir".into(),
+                    size: 0,
+                    mtime: String::new(),
+                });
+            }
+        }
+    }
+    let mut out: Vec<CatalogEntry> = children.into_values().collect();
+    out.sort_by(|a, b| (a.kind != "dir").cmp(&(b.kind != "dir")).then_with(|| a.path.cmp(&b.path)));
+    out
+}
+
+/// Check a restore selection against the catalog and the destination.
+/// Returns the selected paths, cleaned.
+pub fn check_selection(cat: &Catalog, paths: &[String], dest: &str, overwrite: bool) -> Result<Vec<String>, String> {
+    if !dest.starts_with('/') {
+        return Err("Restore destination must be an absolute path".into());
+    }
+    if dest.split('/').any(|c| c == "..") {
+        return Err("Restore destination must not contain '..'".into());
+    }
+    super::reject_dangerous_root(dest, false)?;
+    if paths.is_empty() {
+        return Err("Pick at least one file or folder to restore".into());
+    }
+    let mut names = HashSet::new();
+    let mut out = Vec::new();
+    for p in paths {
+        let p = p.trim_matches('/');
+        if p.is_empty() || p.split('/').any(|c| c == ".." || c == "." || c.is_empty()) {
+            return Err(format!("Invalid path '{}'", p));
+        }
+        if !cat.contains(p) {
+            return Err(format!("'{}' is not in this backup", p));
+        }
+        let name = p.rsplit('/').next().unwrap_or(p);
+        if !names.insert(name.to_string()) {
+            return Err(format!("Two selected items are both named '{}' — restore them separately", name));
+        }
+        if !overwrite && Path::new(dest).join(name).symlink_metadata().is_ok() {
+            return Err(format!("{}/{} already exists — tick overwrite to replace it", dest.trim_end_matches('/'), name));
+        }
+        out.push(p.to_string());
+    }
+    Ok(out)
+}
+
+// ─── Tarball backups ───
+
+/// Where the archive for `entry` can be read: in place on mounted storage,
+/// or downloaded into staging (the bool says the copy must be removed).
+fn archive_path(entry: &BackupEntry) -> Result<(PathBuf, bool), String> {
+    let dir = match entry.storage.storage_type {
+        StorageType::Local | StorageType::Wolfdisk => entry.storage.resolved_local_path(),
+        StorageType::Nfs => super::ensure_nfs_mounted(&entry.storage)?,
+        StorageType::Smb => super::ensure_smb_mounted(&entry.storage)?,
+        StorageType::S3 | StorageType::Pbs => return super::retrieve_backup(entry).map(|p| (p, true)),
+        StorageType::Remote => return Err("Backups on a remote node are browsed on that node".into()),
+    };
+    let path = Path::new(&dir).join(&entry.filename);
+    if !path.exists() {
+        return Err(format!("Backup file not found: {}", path.display()));
+    }
+    Ok((path, false))
+}
+
+fn tar_catalog(archive: &Path) -> Result<Catalog, String> {
+    let out = Command::new("tar")
+        .arg("-tvf").arg(archive).arg("--full-time")
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("Cannot list archive: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let mut cat = Catalog::default();
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        if let Some((e, prefix)) = parse_tar_line(line) {
+            if cat.entries.is_empty() {
+                cat.member_prefix = prefix.to_string();
+            }
+            cat.entries.push(e);
+        }
+    }
+    Ok(cat)
+}
+
+fn find_entry(id: &str) -> Result<BackupEntry, String> {
+    super::load_config().entries.into_iter().find(|e| e.id == id)
+        .ok_or_else(|| format!("Backup not found: {}", id))
+}
+
+fn entry_catalog(entry: &BackupEntry) -> Result<Arc<Catalog>, String> {
+    if super::is_pbs_file_level_entry(entry) {
+        let snapshot = super::pbs_file_level_snapshot(entry)?;
+        return pbs_catalog(&entry.storage, &snapshot);
+    }
+    if !entry.filename.contains(".tar") && !entry.filename.ends_with(".tgz") {
+        return Err(format!("{} is not a tar archive and can't be browsed", entry.filename));
+    }
+    cached(&format!("entry:{}", entry.id), || {
+        let (archive, temp) = archive_path(entry)?;
+        let cat = tar_catalog(&archive);
+        if temp {
+            let _ = fs::remove_file(&archive);
+        }
+        cat
+    })
+}
+
+/// List directory `dir` of backup `id`.
+pub fn browse_backup(id: &str, dir: &str) -> Result<Vec<CatalogEntry>, String> {
+    let entry = find_entry(id)?;
+    let cat = entry_catalog(&entry)?;
+    Ok(list_dir(&cat.entries, dir))
+}
+
+fn new_stage() -> Result<PathBuf, String> {
+    let stage = super::ensure_staging_dir()?.join(format!("catalog-restore-{}", Uuid::new_v4().simple()));
+    fs::create_dir_all(&stage).map_err(|e| format!("Failed to create staging dir: {}", e))?;
+    Ok(stage)
+}
+
+/// Copy each staged item to `<dest>/<name>`.
+fn place(items: &[(PathBuf, String)], dest: &str, overwrite: bool) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| format!("Cannot create '{}': {}", dest, e))?;
+    for (src, name) in items {
+        if src.symlink_metadata().is_err() {
+            return Err(format!("'{}' was not extracted from the backup", name));
+        }
+        let target = Path::new(dest).join(name);
+        let mut cp = Command::new("cp");
+        cp.arg("-a");
+        if overwrite {
+            // -T: merge a directory into an existing one of the same name
+            // rather than nesting it inside.
+            cp.arg("-T");
+        }
+        let out = cp.arg(src).arg(&target).output()
+            .map_err(|e| format!("Failed to run cp: {}", e))?;
+        if !out.status.success() {
+            return Err(format!("Copying {} failed: {}", target.display(), String::from_utf8_lossy(&out.stderr).trim()));
+        }
+    }
+    Ok(())
+}
+
+fn done_message(paths: &[String], dest: &str) -> String {
+    match paths {
+        [one] => format!("Restored {} to {}", one, dest),
+        _ => format!("Restored {} items to {}", paths.len(), dest),
+    }
+}
+
+/// Restore `paths` from backup `id` into directory `dest`.
+pub fn restore_backup_files(id: &str, paths: &[String], dest: &str, overwrite: bool) -> Result<String, String> {
+    let entry = find_entry(id)?;
+    let dest = dest.trim();
+    if super::is_pbs_file_level_entry(&entry) {
+        let snapshot = super::pbs_file_level_snapshot(&entry)?;
+        let cat = pbs_catalog(&entry.storage, &snapshot)?;
+        let paths = check_selection(&cat, paths, dest, overwrite)?;
+        return extract_from_pbs(&entry.storage, &snapshot, &cat, &paths, dest, overwrite);
+    }
+    let cat = entry_catalog(&entry)?;
+    let paths = check_selection(&cat, paths, dest, overwrite)?;
+
+    let (archive, temp) = archive_path(&entry)?;
+    let result = (|| {
+        let stage = new_stage()?;
+        let out = Command::new("tar")
+            .arg("-xf").arg(&archive).arg("-C").arg(&stage).arg("--")
+            .args(paths.iter().map(|p| format!("{}{}", cat.member_prefix, p)))
+            .output()
+            .map_err(|e| format!("Failed to run tar: {}", e));
+        let placed = out.and_then(|out| {
+            if !out.status.success() {
+                return Err(format!("Extract failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+            }
+            let items: Vec<(PathBuf, String)> = paths.iter()
+                .map(|p| (stage.join(p), p.rsplit('/').next().unwrap_or(p).to_string()))
+                .collect();
+            place(&items, dest, overwrite)
+        });
+        let _ = fs::remove_dir_all(&stage);
+        placed
+    })();
+    if temp {
+        let _ = fs::remove_file(&archive);
+    }
+    result?;
+    tracing::info!("catalog restore: {} item(s) from backup {} to {}", paths.len(), id, dest);
+    Ok(done_message(&paths, dest))
+}
+
+// ─── PBS snapshots ───
+
+/// `type/id/time`, as PBS names snapshots.
+pub fn validate_snapshot(snapshot: &str) -> Result<(), String> {
+    let parts: Vec<&str> = snapshot.split('/').collect();
+    if parts.len() != 3
+        || parts.iter().any(|p| p.is_empty() || p.starts_with('-') || p.chars().any(char::is_whitespace))
+    {
+        return Err(format!("Invalid PBS snapshot '{}'", snapshot));
+    }
+    Ok(())
+}
+
+fn pbs_catalog(storage: &BackupStorage, snapshot: &str) -> Result<Arc<Catalog>, String> {
+    validate_snapshot(snapshot)?;
+    super::ensure_pbs_client_installed()?;
+    let repo = super::pbs_repo_string(storage);
+    cached(&format!("pbs:{}:{}:{}", repo, storage.pbs_namespace, snapshot), || {
+        let mut cmd = Command::new("proxmox-backup-client");
+        cmd.args(["catalog", "dump", snapshot, "--repository", &repo]);
+        super::pbs_apply_common(&mut cmd, storage);
+        let out = cmd.output().map_err(|e| format!("Failed to read PBS catalog: {}", e))?;
+        if !out.status.success() {
+            return Err(format!(
+                "Snapshot has no file catalog (only file-level snapshots can be browsed): {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ));
+        }
+        Ok(Catalog {
+            entries: String::from_utf8_lossy(&out.stdout).lines().filter_map(parse_catalog_line).collect(),
+            member_prefix: String::new(),
+        })
+    })
+}
+
+/// List directory `dir` of PBS snapshot `snapshot` on the configured PBS.
+pub fn browse_pbs_snapshot(snapshot: &str, dir: &str) -> Result<Vec<CatalogEntry>, String> {
+    let storage = super::load_pbs_config();
+    let cat = pbs_catalog(&storage, snapshot)?;
+    Ok(list_dir(&cat.entries, dir))
+}
+
+/// Restore `paths` from PBS snapshot `snapshot` into directory `dest`.
+pub fn restore_pbs_files(snapshot: &str, paths: &[String], dest: &str, overwrite: bool) -> Result<String, String> {
+    let storage = super::load_pbs_config();
+    let cat = pbs_catalog(&storage, snapshot)?;
+    let dest = dest.trim();
+    let paths = check_selection(&cat, paths, dest, overwrite)?;
+    extract_from_pbs(&storage, snapshot, &cat, &paths, dest, overwrite)
+}
+
+/// Pull single paths out of a pxar archive with `proxmox-file-restore
+/// extract`, which fetches only the chunks those files need.
+fn extract_from_pbs(
+    storage: &BackupStorage,
+    snapshot: &str,
+    cat: &Catalog,
+    paths: &[String],
+    dest: &str,
+    overwrite: bool,
+) -> Result<String, String> {
+    let present = Command::new("which").arg("proxmox-file-restore").output()
+        .map(|o| o.status.success()).unwrap_or(false);
+    if !present {
+        return Err(format!("{}proxmox-file-restore|proxmox-file-restore|proxmox-file-restore",
+            crate::storage::MISSING_PACKAGE_MARKER));
+    }
+    let stage = new_stage()?;
+    let result = (|| {
+        let mut items = Vec::new();
+        for (i, p) in paths.iter().enumerate() {
+            let (archive, inner) = p.split_once('/').unwrap_or((p, ""));
+            let out_dir = stage.join(i.to_string());
+            let mut cmd = Command::new("proxmox-file-restore");
+            cmd.arg("extract").arg(snapshot).arg(format!("{}.didx/{}", archive, inner)).arg(&out_dir)
+                .args(["--repository", &super::pbs_repo_string(storage)]);
+            super::pbs_apply_common(&mut cmd, storage);
+            let out = cmd.output().map_err(|e| format!("Failed to run proxmox-file-restore: {}", e))?;
+            if !out.status.success() {
+                return Err(format!("Extracting {} failed: {}", p, String::from_utf8_lossy(&out.stderr).trim()));
+            }
+            let name = p.rsplit('/').next().unwrap_or(p).to_string();
+            // A directory's contents land in out_dir; a single file lands
+            // either as out_dir itself or inside it under its own name.
+            let src = if cat.kind_of(p) != "dir" && out_dir.is_dir() { out_dir.join(&name) } else { out_dir };
+            items.push((src, name));
+        }
+        place(&items, dest, overwrite)
+    })();
+    let _ = fs::remove_dir_all(&stage);
+    result?;
+    tracing::info!("catalog restore: {} item(s) from PBS snapshot {} to {}", paths.len(), snapshot, dest);
+    Ok(done_message(paths, dest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, kind: &str) -> CatalogEntry {
+        CatalogEntry { path: path.into(), kind: kind.into(), size: 0, mtime: String::new() }
+    }
+
+    #[test]
+    fn tar_lines() {
+        let (e, prefix) = parse_tar_line("-rw-r--r-- root/root       220 2026-01-02 03:04:05 ./etc/my  hosts").unwrap();
+        assert_eq!(prefix, "./");
+        assert_eq!(e, CatalogEntry { path: "etc/my  hosts".into(), kind: "file".into(), size: 220, mtime: "2026-01-02 03:04:05".into() });
+        let (e, _) = parse_tar_line("lrwxrwxrwx root/root 0 2026-01-02 03:04:05 rootfs/bin -> usr/bin").unwrap();
+        assert_eq!((e.path.as_str(), e.kind.as_str()), ("rootfs/bin", "link"));
+        let (e, prefix) = parse_tar_line("drwxr-xr-x root/root 0 2026-01-02 03:04:05 etc/").unwrap();
+        assert_eq!((e.path.as_str(), e.kind.as_str(), prefix), ("etc", "dir", ""));
+        assert!(parse_tar_line("drwxr-xr-x root/root 0 2026-01-02 03:04:05 ./").is_none());
+        assert!(parse_tar_line("garbage").is_none());
+    }
+
+    #[test]
+    fn pbs_catalog_lines() {
+        let e = parse_catalog_line(r#"f "/root.pxar.didx/etc/ho\"sts" 220 2026-01-02T03:04:05Z"#).unwrap();
+        assert_eq!(e, CatalogEntry { path: "root.pxar/etc/ho\"sts".into(), kind: "file".into(), size: 220, mtime: "2026-01-02 03:04:05".into() });
+        let e = parse_catalog_line(r#"d "/root.pxar.didx""#).unwrap();
+        assert_eq!((e.path.as_str(), e.kind.as_str()), ("root.pxar", "dir"));
+        assert_eq!(parse_catalog_line(r#"l "/root.pxar.didx/bin""#).unwrap().kind, "link");
+        assert!(parse_catalog_line("Catalog dump done").is_none());
+    }
+
+    #[test]
+    fn lists_one_level_with_implied_dirs() {
+        let entries = vec![
+            entry("etc/hosts", "file"),
+            entry("etc/ssh/sshd_config", "file"),
+            entry("var", "dir"),
+            entry("a.txt", "file"),
+        ];
+        let root: Vec<String> = list_dir(&entries, "").iter().map(|e| e.path.clone()).collect();
+        assert_eq!(root, vec!["etc", "var", "a.txt"]);
+        let etc: Vec<String> = list_dir(&entries, "/etc/").iter().map(|e| e.path.clone()).collect();
+        assert_eq!(etc, vec!["etc/ssh", "etc/hosts"]);
+        assert!(list_dir(&entries, "var").is_empty());
+    }
+
+    #[test]
+    fn selection_is_checked() {
+        let cat = Catalog {
+            entries: vec![entry("etc/hosts", "file"), entry("etc/ssh/sshd_config", "file"), entry("srv/hosts", "file")],
+            member_prefix: String::new(),
+        };
+        let dest = "/nonexistent-wolfstack-test";
+        assert_eq!(check_selection(&cat, &["etc/ssh".into()], dest, false).unwrap(), vec!["etc/ssh"]);
+        assert!(check_selection(&cat, &["etc/passwd".into()], dest, false).is_err());
+        assert!(check_selection(&cat, &["etc/../etc/hosts".into()], dest, false).is_err());
+        assert!(check_selection(&cat, &["etc/hosts".into(), "srv/hosts".into()], dest, false).is_err());
+        assert!(check_selection(&cat, &["etc/hosts".into()], "relative/dir", false).is_err());
+        assert!(check_selection(&cat, &["etc/hosts".into()], "/proc/x", false).is_err());
+        assert!(check_selection(&cat, &[], dest, false).is_err());
+    }
+}
@@ -18,9 +18,21 @@ use tracing::{error, info, warn};
 use chrono::{Utc, Datelike};
 use uuid::Uuid;
 
+pub mod catalog;
+pub mod pbs_admin;
+
 fn backup_config_path() -> String { crate::paths::get().backup_config }
 fn backup_staging_dir() -> String { crate::paths::get().backup_staging_dir }
 
+pub const PBS_CONFIG_PATH: &str = "/etc/wolfstack/pbs/config.json";
+
+/// Credentials sealed at rest in every `BackupStorage` the backup config
+/// and the PBS config hold.
+pub const BACKUP_SECRETS: crate::secrets::SecretFields = crate::secrets::SecretFields {
+    label: "backup",
+    fields: &["secret_key", "pbs_token_secret", "pbs_password", "smb_password"],
+};
+
 // ─── Data Types ───
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -34,6 +46,10 @@ pub enum BackupTargetType {
     /// folder path travels in `BackupTarget::system_path`; `name` carries
     /// an operator-supplied label used in the backup filename.
     SystemPath,
+    /// MySQL / MariaDB database dumped with `mysqldump`. `name` is the
+    /// database; the credentials come from the saved SQL connection named
+    /// by `BackupTarget::sql_connection_id`.
+    Mysql,
 }
 
 impl std::fmt::Display for BackupTargetType {
@@ -44,6 +60,7 @@ impl std::fmt::Display for BackupTargetType {
             Self::Vm => write!(f, "vm"),
             Self::Config => write!(f, "config"),
             Self::SystemPath => write!(f, "systempath"),
+            Self::Mysql => write!(f, "mysql"),
         }
     }
 }
@@ -84,6 +101,11 @@ pub struct BackupTarget {
     /// non-disruptive behaviour. Proxmox LXC ignores this (vzdump snapshots).
     #[serde(default)]
     pub stop_for_backup: bool,
+    /// For `Mysql` targets: id of the saved SQL connection (see
+    /// `sql_connections`) whose host and credentials `mysqldump` uses.
+    /// Referencing the profile keeps passwords out of backups.json.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sql_connection_id: String,
 }
 
 impl Default for BackupTarget {
@@ -97,6 +119,7 @@ impl Default for BackupTarget {
             exclude_mounts: Vec::new(),
             system_path: String::new(),
             stop_for_backup: false,
+            sql_connection_id: String::new(),
         }
     }
 }
@@ -913,6 +936,11 @@ pub struct BackupSchedule {
     /// backups that already succeeded.
     #[serde(default)]
     pub post_command: String,
+    /// IO priority and disk bandwidth caps for the run's backups, so a
+    /// nightly backup doesn't saturate the disks the guests live on. Hooks
+    /// run unthrottled.
+    #[serde(default)]
+    pub io_limits: crate::io_throttle::IoLimits,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1241,7 +1269,7 @@ impl Default for BackupConfig {
 
 pub fn load_config() -> BackupConfig {
     match fs::read_to_string(&backup_config_path()) {
-        Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+        Ok(data) => crate::secrets::from_sealed_json(&data, &BACKUP_SECRETS).unwrap_or_default(),
         Err(_) => BackupConfig::default(),
     }
 }
@@ -1250,9 +1278,9 @@ pub fn save_config(config: &BackupConfig) -> Result<(), String> {
     let path = backup_config_path();
     let dir = Path::new(&path).parent().unwrap();
     fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
-    let json = serde_json::to_string_pretty(config)
+    let json = crate::secrets::to_sealed_json(config, &BACKUP_SECRETS)
         .map_err(|e| format!("Failed to serialize backup config: {}", e))?;
-    fs::write(&path, json)
+    crate::paths::write_secure(&path, json)
         .map_err(|e| format!("Failed to write backup config: {}", e))
 }
 
@@ -2767,6 +2795,161 @@ pub fn restore_system_path(entry: &BackupEntry, target_dir: &str) -> Result<Stri
     Ok(format!("System folder restored into {}", dest))
 }
 
+/// Resolve a MySQL target's saved connection and write its credentials to
+/// a 0600 option file in the staging dir, so the password never appears
+/// in `ps` output. The caller removes the file when the client exits.
+fn mysql_defaults_file(connection_id: &str) -> Result<PathBuf, String> {
+    let conn = crate::sql_connections::load().connections.into_iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| format!("SQL connection '{}' not found — it may have been deleted", connection_id))?;
+    if !matches!(conn.kind, crate::sql_connections::SqlKind::Mysql | crate::sql_connections::SqlKind::Mariadb) {
+        return Err(format!("SQL connection '{}' is not a MySQL/MariaDB connection", conn.label));
+    }
+    let password = crate::sql_connections::plaintext_password(&conn, &crate::auth::load_cluster_secret())?;
+    // Option-file values may be double-quoted; inside quotes `\` and `"`
+    // need escaping.
+    let quote = |v: &str| format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""));
+    let body = format!(
+        "[client]\nhost={}\nport={}\nuser={}\npassword={}\n",
+        quote(&conn.host), conn.port, quote(&conn.username), quote(&password)
+    );
+    let path = ensure_staging_dir()?.join(format!(".mysql-{}.cnf", Uuid::new_v4().simple()));
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut f = fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path)
+        .map_err(|e| format!("Failed to write MySQL option file: {}", e))?;
+    f.write_all(body.as_bytes())
+        .map_err(|e| format!("Failed to write MySQL option file: {}", e))?;
+    Ok(path)
+}
+
+/// First of `names` that runs — MariaDB 11 ships `mariadb-dump` /
+/// `mariadb` and drops the `mysql*` compatibility symlinks on some distros.
+fn mysql_client_binary(names: &[&'static str]) -> Result<&'static str, String> {
+    names.iter().copied()
+        .find(|b| Command::new(b).arg("--version").output().map(|o| o.status.success()).unwrap_or(false))
+        .ok_or_else(|| format!("{} not found — install the MySQL or MariaDB client package", names[0]))
+}
+
+/// Database names go to mysqldump as argv, so there's no shell to escape —
+/// but a leading `-` would be read as an option.
+fn validate_mysql_database(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > 64 || name.starts_with('-')
+        || name.chars().any(|c| c.is_control() || c == '/' || c == '\\')
+    {
+        return Err(format!("Invalid MySQL database name '{}'", name));
+    }
+    Ok(())
+}
+
+/// API-boundary check for a `Mysql` target: sane database name and a
+/// saved MySQL/MariaDB connection to dump through.
+pub fn validate_mysql_target(target: &BackupTarget) -> Result<(), String> {
+    validate_mysql_database(&target.name)?;
+    let conn = crate::sql_connections::load().connections.into_iter()
+        .find(|c| c.id == target.sql_connection_id)
+        .ok_or_else(|| format!("SQL connection '{}' not found", target.sql_connection_id))?;
+    if !matches!(conn.kind, crate::sql_connections::SqlKind::Mysql | crate::sql_connections::SqlKind::Mariadb) {
+        return Err(format!("SQL connection '{}' is not a MySQL/MariaDB connection", conn.label));
+    }
+    Ok(())
+}
+
+/// Dump one database with `mysqldump | gzip` into the staging dir.
+/// `--single-transaction` gives a consistent InnoDB snapshot without
+/// locking; `--databases` makes the dump carry its own CREATE DATABASE /
+/// USE so a restore lands back in the same schema.
+pub fn backup_mysql(database: &str, connection_id: &str) -> Result<(PathBuf, u64), String> {
+    validate_mysql_database(database)?;
+    let dump_bin = mysql_client_binary(&["mysqldump", "mariadb-dump"])?;
+    let staging = ensure_staging_dir()?;
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let archive = staging.join(format!("mysql-{}-{}.sql.gz", sanitize_archive_name(database), timestamp));
+    let defaults = mysql_defaults_file(connection_id)?;
+    let result = mysqldump_to_gz(dump_bin, &defaults, database, &archive);
+    let _ = fs::remove_file(&defaults);
+    if let Err(e) = result {
+        let _ = fs::remove_file(&archive);
+        return Err(e);
+    }
+    let size = fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+    Ok((archive, size))
+}
+
+/// `mysqldump … | gzip -c > archive`, without a shell.
+fn mysqldump_to_gz(dump_bin: &str, defaults: &Path, database: &str, archive: &Path) -> Result<(), String> {
+    let out_file = fs::File::create(archive)
+        .map_err(|e| format!("Failed to create {}: {}", archive.display(), e))?;
+    let mut dump = Command::new(dump_bin)
+        .arg(format!("--defaults-extra-file={}", defaults.display()))
+        .args(["--single-transaction", "--quick", "--routines", "--triggers", "--events", "--databases", database])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", dump_bin, e))?;
+    let dump_out = dump.stdout.take().ok_or_else(|| format!("{} produced no output stream", dump_bin))?;
+    let gzip = Command::new("gzip")
+        .arg("-c")
+        .stdin(dump_out)
+        .stdout(out_file)
+        .output()
+        .map_err(|e| format!("Failed to run gzip: {}", e))?;
+    let dumped = dump.wait_with_output().map_err(|e| format!("{} failed: {}", dump_bin, e))?;
+    if !dumped.status.success() {
+        return Err(format!("{} failed: {}", dump_bin, String::from_utf8_lossy(&dumped.stderr).trim()));
+    }
+    if !gzip.status.success() {
+        return Err(format!("gzip failed: {}", String::from_utf8_lossy(&gzip.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// `gzip -dc archive | mysql`, without a shell.
+fn gz_to_mysql(client_bin: &str, defaults: &Path, archive: &Path) -> Result<(), String> {
+    let mut gunzip = Command::new("gzip")
+        .arg("-dc")
+        .arg(archive)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run gzip: {}", e))?;
+    let sql = gunzip.stdout.take().ok_or_else(|| "gzip produced no output stream".to_string())?;
+    let imported = Command::new(client_bin)
+        .arg(format!("--defaults-extra-file={}", defaults.display()))
+        .stdin(sql)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", client_bin, e))?;
+    let unzipped = gunzip.wait_with_output().map_err(|e| format!("gzip failed: {}", e))?;
+    if !unzipped.status.success() {
+        return Err(format!("Dump decompress failed: {}", String::from_utf8_lossy(&unzipped.stderr).trim()));
+    }
+    if !imported.status.success() {
+        return Err(format!("MySQL import failed: {}", String::from_utf8_lossy(&imported.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Import a `mysql-*.sql.gz` dump through the target's saved connection.
+/// The dump recreates its own tables (DROP TABLE IF EXISTS + CREATE), so
+/// this overwrites the database's tables with the backed-up copy.
+pub fn restore_mysql(entry: &BackupEntry) -> Result<String, String> {
+    validate_mysql_database(&entry.target.name)?;
+    let client_bin = mysql_client_binary(&["mysql", "mariadb"])?;
+    let local_path = retrieve_backup(entry)?;
+    let defaults = match mysql_defaults_file(&entry.target.sql_connection_id) {
+        Ok(d) => d,
+        Err(e) => {
+            let _ = fs::remove_file(&local_path);
+            return Err(e);
+        }
+    };
+
+    let result = gz_to_mysql(client_bin, &defaults, &local_path);
+    let _ = fs::remove_file(&defaults);
+    let _ = fs::remove_file(&local_path);
+    result.map(|_| format!("MySQL database '{}' restored from {}", entry.target.name, entry.filename))
+}
+
 /// Backup everything on the server
 pub fn backup_all(storage: &BackupStorage) -> Vec<BackupEntry> {
     let mut entries = Vec::new();
@@ -2934,6 +3117,13 @@ fn backup_comments_with_cluster(target: &BackupTarget, cluster: &str) -> String
                 format!("System folder: {} ({})", target.name, target.system_path)
             }
         }
+        BackupTargetType::Mysql => {
+            let label = crate::sql_connections::load().connections.into_iter()
+                .find(|c| c.id == target.sql_connection_id)
+                .map(|c| format!("{} ({}:{})", c.label, c.host, c.port))
+                .unwrap_or_else(|| target.sql_connection_id.clone());
+            format!("MySQL database: {} via {} (mysqldump)", target.name, label)
+        }
     };
     format!("[{}] {}", cluster, detail)
 }
@@ -2991,6 +3181,7 @@ fn create_backup_entry(target: BackupTarget, storage: &BackupStorage) -> BackupE
             backup_system_path(&target.name, &target.system_path, &target.exclude_mounts),
             String::new(), Vec::new(),
         ),
+        BackupTargetType::Mysql => (backup_mysql(&target.name, &target.sql_connection_id), String::new(), Vec::new()),
     };
 
     match result {
@@ -3342,6 +3533,13 @@ fn store_remote(local_path: &Path, remote_url: &str, filename: &str) -> Result<(
 
 /// Build the PBS repository string: user!token@server:datastore
 fn pbs_repo_string(storage: &BackupStorage) -> String {
+    format!("{}@{}:{}", pbs_auth_id(storage), storage.pbs_server, storage.pbs_datastore)
+}
+
+/// The PBS principal the saved credentials log in as: `user@realm`, or
+/// `user@realm!tokenid` for token auth. Shared by the repo string and the
+/// REST calls in `pbs_admin`.
+pub(crate) fn pbs_auth_id(storage: &BackupStorage) -> String {
     // PBS token-auth repo form: `user@realm!tokenid@server:datastore`.
     // The principal is `user@realm!tokenid`. Operators paste the token in
     // assorted ways: the bare id (`wolfstack-backup`) in the token field, OR the
@@ -3351,7 +3549,7 @@ fn pbs_repo_string(storage: &BackupStorage) -> String {
     // `root@pam!root@pam!wolfstack-backup` PBS rejects as "token disabled".
     let user = storage.pbs_user.trim();
     let token = storage.pbs_token_name.trim();
-    let principal = if token.is_empty() {
+    if token.is_empty() {
         user.to_string()
     } else if token.contains('!') || token.contains('@') {
         // The token field already holds the full `user@realm!tokenid`.
@@ -3361,8 +3559,7 @@ fn pbs_repo_string(storage: &BackupStorage) -> String {
         user.to_string()
     } else {
         format!("{}!{}", user, token)
-    };
-    format!("{}@{}:{}", principal, storage.pbs_server, storage.pbs_datastore)
+    }
 }
 
 /// Normalize a PBS server TLS fingerprint to the colon-separated form
@@ -3707,6 +3904,8 @@ fn pbs_file_level_applies(target: &BackupTarget) -> bool {
         // restore a single config file from any snapshot (wabil 2026-07-08).
         BackupTargetType::Config => true,
         BackupTargetType::Vm => false,
+        // A dump is a single stream, not a file tree.
+        BackupTargetType::Mysql => false,
     }
 }
 
@@ -4006,6 +4205,10 @@ fn build_pxar_pairs(target: &BackupTarget) -> Result<(String, String, Vec<PxarPa
             Err("PBS file-level backup isn't available for VMs (disk images are \
                  not a file tree) — using the disk-image backup instead".into())
         }
+        BackupTargetType::Mysql => {
+            Err("PBS file-level backup isn't available for MySQL dumps — \
+                 using the compressed SQL dump instead".into())
+        }
     }
 }
 
@@ -4168,22 +4371,9 @@ fn make_pbs_file_level_entry(
     }
 }
 
-/// Full-archive restore of a PBS file-level (pxar) snapshot. Extracts the
-/// `root.pxar` filesystem tree into `target_dir` using
-/// `proxmox-backup-client restore <snapshot> <archive> <target>`.
-/// Per-FILE restore (picking one file out of the tree) is done through PBS's
-/// own web UI / `proxmox-backup-client catalog` + interactive restore — this
-/// function does the complete-archive case end to end.
-///
-/// `target_override` (non-empty) chooses where the tree lands; empty applies
-/// a type-appropriate default:
-///   • native LXC  → the container rootfs (`<base>/<name>/rootfs`)
-///   • SystemPath  → the original folder
-///   • Docker      → a staging dir under the restore area (operator then has
-///                   the files; container re-creation from a flat fs isn't
-///                   automatic — surfaced in the returned message)
-fn restore_pbs_file_level_entry(entry: &BackupEntry, target_override: &str) -> Result<String, String> {
-    ensure_pbs_client_installed()?;
+/// The PBS snapshot (`type/id/time`) holding a file-level entry: the
+/// newest one for the entry's type/id.
+fn pbs_file_level_snapshot(entry: &BackupEntry) -> Result<String, String> {
     let storage = &entry.storage;
     let repo = pbs_repo_string(storage);
 
@@ -4235,6 +4425,27 @@ fn restore_pbs_file_level_entry(entry: &BackupEntry, target_override: &str) -> R
     if snapshot.is_empty() {
         return Err(format!("No PBS file-level snapshot found for {}/{}", backup_type, backup_id));
     }
+    Ok(snapshot)
+}
+
+/// Full-archive restore of a PBS file-level (pxar) snapshot. Extracts the
+/// `root.pxar` filesystem tree into `target_dir` using
+/// `proxmox-backup-client restore <snapshot> <archive> <target>`.
+/// Per-FILE restore (picking one file out of the tree) is `catalog` — this
+/// function does the complete-archive case end to end.
+///
+/// `target_override` (non-empty) chooses where the tree lands; empty applies
+/// a type-appropriate default:
+///   • native LXC  → the container rootfs (`<base>/<name>/rootfs`)
+///   • SystemPath  → the original folder
+///   • Docker      → a staging dir under the restore area (operator then has
+///                   the files; container re-creation from a flat fs isn't
+///                   automatic — surfaced in the returned message)
+fn restore_pbs_file_level_entry(entry: &BackupEntry, target_override: &str) -> Result<String, String> {
+    ensure_pbs_client_installed()?;
+    let storage = &entry.storage;
+    let repo = pbs_repo_string(storage);
+    let snapshot = pbs_file_level_snapshot(entry)?;
 
     // Decide the target directory.
     let target_dir = if !target_override.trim().is_empty() {
@@ -4274,7 +4485,7 @@ fn restore_pbs_file_level_entry(entry: &BackupEntry, target_override: &str) -> R
     let note = match entry.target.target_type {
         BackupTargetType::Docker =>
             " — container filesystem extracted; rebuild the container from these \
-             files or use PBS's per-file restore for individual files.",
+             files or use Browse files to restore individual files.",
         _ => "",
     };
     Ok(format!("PBS file-level snapshot '{}' restored into {}{}", snapshot, target_dir, note))
@@ -6092,6 +6303,100 @@ fn apply_config_staging(staging: &Path, new_machine: bool) -> Result<String, Str
     })
 }
 
+/// Roots a system-config tarball may write to — exactly what
+/// `stage_config_bundle` puts in one. An uploaded archive is untrusted, so
+/// anything else (or any attempt to climb out of these) rejects the whole file.
+const CONFIG_ARCHIVE_ROOTS: &[&str] = &[
+    "etc/wolfstack",
+    "etc/wolfnet",
+    "etc/wolfusb",
+    "var/lib/wolfstack/vms",
+];
+
+/// Check a `tar tzf` listing of a system-config tarball. Every member must sit
+/// under (or be a parent dir of) one of `CONFIG_ARCHIVE_ROOTS`, and the archive
+/// must actually carry /etc/wolfstack. Returns the number of members.
+fn check_config_archive_listing(listing: &str) -> Result<usize, String> {
+    let mut count = 0;
+    let mut has_wolfstack = false;
+    for raw in listing.lines() {
+        let name = raw.trim();
+        let name = name.strip_prefix("./").unwrap_or(name).trim_end_matches('/');
+        if name.is_empty() || name == "." { continue; }
+        if name.starts_with('/') || name.contains('\0') || name.split('/').any(|c| c == "..") {
+            return Err(format!("archive entry '{}' is not a safe relative path", name));
+        }
+        let allowed = CONFIG_ARCHIVE_ROOTS.iter().any(|root| {
+            name == *root
+                || name.starts_with(&format!("{}/", root))
+                || root.starts_with(&format!("{}/", name))
+        });
+        if !allowed {
+            return Err(format!("archive entry '{}' is outside the WolfStack config paths", name));
+        }
+        has_wolfstack |= name == "etc/wolfstack" || name.starts_with("etc/wolfstack/");
+        count += 1;
+    }
+    if !has_wolfstack {
+        return Err("not a WolfStack config backup (no etc/wolfstack in the archive)".into());
+    }
+    Ok(count)
+}
+
+/// Refuse a staged tree holding symlinks or hard links — `stage_config_bundle`
+/// copies link targets as plain files, so a link can only mean a crafted
+/// archive trying to point a later `cp -a` outside the config dirs.
+fn check_staged_config_tree(dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+    for entry in fs::read_dir(dir).map_err(|e| format!("read {}: {}", dir.display(), e))?.flatten() {
+        let meta = fs::symlink_metadata(entry.path())
+            .map_err(|e| format!("stat {}: {}", entry.path().display(), e))?;
+        if meta.file_type().is_symlink() || (meta.is_file() && meta.nlink() > 1) {
+            return Err(format!("archive contains a link ({}) — refusing to restore",
+                entry.path().strip_prefix(dir).unwrap_or(&entry.path()).display()));
+        }
+        if meta.is_dir() {
+            check_staged_config_tree(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Restore a system-config tarball that isn't in this node's backup list —
+/// the disaster-recovery path: a freshly installed node has no backups.json,
+/// so the operator uploads the `config-wolfstack-*.tar.gz` taken on the dead
+/// node. `new_machine = false` makes this box take over the old node's
+/// identity, TLS and cluster membership (a replacement); `true` keeps this
+/// box's own, same as `restore_config_backup`. The archive is validated
+/// before and after extraction; the caller owns `archive`.
+pub fn restore_config_archive(archive: &Path, new_machine: bool) -> Result<String, String> {
+    let list = Command::new("tar")
+        .args(["tzf", &archive.to_string_lossy()])
+        .output()
+        .map_err(|e| format!("tar list failed to start: {}", e))?;
+    if !list.status.success() {
+        return Err(format!("not a readable .tar.gz: {}", String::from_utf8_lossy(&list.stderr).trim()));
+    }
+    let members = check_config_archive_listing(&String::from_utf8_lossy(&list.stdout))?;
+
+    let staging = make_config_restore_staging()?;
+    let output = Command::new("tar")
+        .args(["xzf", &archive.to_string_lossy(), "-C", &staging.to_string_lossy(), "--no-same-owner"])
+        .output()
+        .map_err(|e| format!("Failed to extract config backup: {}", e))?;
+    if !output.status.success() {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Config extract failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    if let Err(e) = check_staged_config_tree(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    info!("config restore: applying uploaded system-config archive ({} entries, new_machine={})",
+        members, new_machine);
+    apply_config_staging(&staging, new_machine)
+}
+
 /// Restore from a backup entry (auto-detects type). Non-streaming path:
 /// an LXC restore here uses the node's default storage — the streaming
 /// restore (`restore_by_id_with_log`) is the one that honours a storage
@@ -6115,6 +6420,7 @@ pub fn restore_backup(entry: &BackupEntry, overwrite: bool) -> Result<String, St
         // itself (contents-only). The streaming/targeted path
         // (restore_entry_with_log) lets the operator choose an explicit dir.
         BackupTargetType::SystemPath => restore_system_path(entry, ""),
+        BackupTargetType::Mysql => restore_mysql(entry),
     }
 }
 
@@ -6127,6 +6433,8 @@ pub fn list_backups() -> Vec<BackupEntry> {
 
 /// Create a backup (single target or all)
 pub fn create_backup(target: Option<BackupTarget>, storage: BackupStorage) -> Vec<BackupEntry> {
+    let label = target.as_ref().map_or("Backup all".to_string(), |t| format!("Backup {}", t.name));
+    let _slot = crate::jobs::acquire(crate::jobs::Category::Backup, crate::jobs::Priority::Normal, &label, |why| info!("{} queued: {}", label, why));
     let mut config = load_config();
 
     let new_entries = match target {
@@ -6150,6 +6458,10 @@ pub fn create_backup_with_log(
     // Bake the concrete Local directory in up front (see with_concrete_local)
     // so restore is independent of any later default-dir change.
     let storage = storage.with_concrete_local(&crate::paths::get().backup_local_dir);
+    let label = target.as_ref().map_or("Backup all".to_string(), |t| format!("Backup {}", t.name));
+    let _slot = crate::jobs::acquire(crate::jobs::Category::Backup, crate::jobs::Priority::Normal, &label, |why| {
+        let _ = log.send(format!("Queued — {}. Starts when a slot frees up.", why));
+    });
     let targets = match target {
         Some(t) => vec![t],
         None => list_available_targets(),
@@ -6290,6 +6602,10 @@ pub fn create_backup_with_log(
                 }
                 (backup_system_path(&t.name, &t.system_path, &t.exclude_mounts), String::new(), Vec::new())
             }
+            BackupTargetType::Mysql => {
+                let _ = log.send(format!("  Dumping MySQL database '{}' with mysqldump...", t.name));
+                (backup_mysql(&t.name, &t.sql_connection_id), String::new(), Vec::new())
+            }
         };
 
         let id = Uuid::new_v4().to_string();
@@ -6649,6 +6965,8 @@ pub fn restore_by_id(id: &str, overwrite: bool) -> Result<String, String> {
     let config = load_config();
     let entry = config.entries.iter().find(|e| e.id == id)
         .ok_or_else(|| format!("Backup not found: {}", id))?;
+    let label = format!("Restore {}", entry.target.name);
+    let _slot = crate::jobs::acquire(crate::jobs::Category::Restore, crate::jobs::Priority::Normal, &label, |why| info!("{} queued: {}", label, why));
     restore_backup(entry, overwrite)
 }
 
@@ -6662,6 +6980,10 @@ pub fn restore_by_id_with_log(id: &str, overwrite: bool, storage: &str, new_name
     let config = load_config();
     let entry = config.entries.iter().find(|e| e.id == id)
         .ok_or_else(|| format!("Backup not found: {}", id))?;
+    let _slot = crate::jobs::acquire(crate::jobs::Category::Restore, crate::jobs::Priority::Normal,
+        &format!("Restore {}", entry.target.name), |why| {
+            let _ = log.send(format!("Queued — {}. Starts when a slot frees up.", why));
+        });
     restore_entry_with_log(entry, overwrite, storage, new_name, new_machine, log)
 }
 
@@ -6768,6 +7090,15 @@ fn restore_entry_with_log(entry: &BackupEntry, overwrite: bool, storage: &str, n
             }
             result
         }
+        BackupTargetType::Mysql => {
+            let _ = log.send(format!("Importing dump into MySQL database '{}'...", entry.target.name));
+            let result = restore_mysql(entry);
+            match &result {
+                Ok(msg) => { let _ = log.send(format!("✅ {}", msg)); }
+                Err(e) => { let _ = log.send(format!("❌ {}", e)); }
+            }
+            result
+        }
     }
 }
 
@@ -6801,6 +7132,11 @@ pub fn restore_from_path(
     if matches!(target_type, BackupTargetType::SystemPath) {
         return Err("System-folder backups can't be restored from a folder — restore them from the Backups list.".into());
     }
+    // A dump's filename names the database but not the connection it came
+    // from, so there's nowhere to import it to.
+    if matches!(target_type, BackupTargetType::Mysql) {
+        return Err("MySQL dumps can't be restored from a folder — restore them from the Backups list.".into());
+    }
     let size_bytes = fs::metadata(Path::new(source_path).join(filename))
         .map(|m| m.len()).unwrap_or(0);
     let entry = BackupEntry {
@@ -6873,6 +7209,7 @@ pub fn scan_backup_folder(path: &str) -> Result<Vec<ScannedBackup>, String> {
             // SystemPath files are filtered out above by `is_backup`, but the
             // match must stay exhaustive.
             BackupTargetType::SystemPath => "systempath",
+            BackupTargetType::Mysql => "mysql",
         }.to_string();
         let name = extract_name_from_filename(&fname);
         out.push(ScannedBackup { filename: fname, target_type: type_str, name, size_bytes: size, modified });
@@ -6928,33 +7265,66 @@ pub fn set_schedule_enabled(id: &str, enabled: bool) -> Result<String, String> {
     Ok(format!("Schedule '{}' {}", name, if enabled { "enabled" } else { "disabled" }))
 }
 
-/// Prune a schedule's completed backups down to `retention`, deleting the oldest
-/// files + entries first. Shared by the nightly scheduler and the on-demand
-/// "Run Now" so both prune identically.
-fn prune_schedule_backups(config: &mut BackupConfig, schedule_id: &str, retention: usize) {
+/// Indices of a schedule's completed backups beyond the `retention` newest,
+/// highest index first — the order they can be removed in.
+fn retention_excess(config: &BackupConfig, schedule_id: &str, retention: usize) -> Vec<usize> {
     let mut schedule_entries: Vec<usize> = config.entries.iter().enumerate()
         .filter(|(_, e)| e.schedule_id == schedule_id && e.status == BackupStatus::Completed)
         .map(|(i, _)| i)
         .collect();
     // Newest first; anything past `retention` is removed.
     schedule_entries.sort_by(|a, b| config.entries[*b].created_at.cmp(&config.entries[*a].created_at));
-    if schedule_entries.len() > retention {
-        // Remove strictly highest-index-first: `Vec::remove` shifts every
-        // later element down, so any other order leaves stale indices that
-        // panic (observed 2026-07-05: "len is 9 but the index is 9") or —
-        // worse — silently delete the WRONG backup's file. The slice is
-        // ordered by created_at, which is NOT index order (entries from
-        // different targets interleave), so it must be re-sorted here.
-        let mut to_remove: Vec<usize> = schedule_entries[retention..].to_vec();
-        to_remove.sort_unstable_by(|a, b| b.cmp(a));
-        for &idx in &to_remove {
-            // Single source of truth for removing a backup's stored artifact
-            // (local file / S3 object / remote copy; PBS delegated to its GC).
-            // Keeps retention and explicit-delete from drifting.
-            delete_backup_file(&config.entries[idx]);
-            config.entries.remove(idx);
+    if schedule_entries.len() <= retention {
+        return Vec::new();
+    }
+    // Remove strictly highest-index-first: `Vec::remove` shifts every
+    // later element down, so any other order leaves stale indices that
+    // panic (observed 2026-07-05: "len is 9 but the index is 9") or —
+    // worse — silently delete the WRONG backup's file. The slice is
+    // ordered by created_at, which is NOT index order (entries from
+    // different targets interleave), so it must be re-sorted here.
+    let mut to_remove: Vec<usize> = schedule_entries[retention..].to_vec();
+    to_remove.sort_unstable_by(|a, b| b.cmp(a));
+    to_remove
+}
+
+/// Prune a schedule's completed backups down to `retention`, deleting the oldest
+/// files + entries first. Shared by the nightly scheduler and the on-demand
+/// "Run Now" so both prune identically.
+fn prune_schedule_backups(config: &mut BackupConfig, schedule_id: &str, retention: usize) {
+    for idx in retention_excess(config, schedule_id, retention) {
+        // Single source of truth for removing a backup's stored artifact
+        // (local file / S3 object / remote copy; PBS delegated to its GC).
+        // Keeps retention and explicit-delete from drifting.
+        delete_backup_file(&config.entries[idx]);
+        config.entries.remove(idx);
+    }
+}
+
+/// Backups beyond their schedule's retention across every schedule, as
+/// (filename, size). Normally pruned when a schedule runs; this catches
+/// what piles up after a retention is lowered or a schedule stops running.
+/// With `dry_run` nothing is touched. Used by housekeeping.
+pub fn enforce_retention(dry_run: bool) -> Result<Vec<(String, u64)>, String> {
+    let mut config = load_config();
+    let schedules: Vec<(String, usize)> = config.schedules.iter()
+        .filter(|s| s.retention > 0)
+        .map(|s| (s.id.clone(), s.retention as usize))
+        .collect();
+    let mut excess = Vec::new();
+    for (schedule_id, retention) in &schedules {
+        for idx in retention_excess(&config, schedule_id, *retention) {
+            let e = &config.entries[idx];
+            excess.push((e.filename.clone(), e.size_bytes));
         }
+        if !dry_run {
+            prune_schedule_backups(&mut config, schedule_id, *retention);
+        }
+    }
+    if !dry_run && !excess.is_empty() {
+        save_config(&config)?;
     }
+    Ok(excess)
 }
 
 #[cfg(test)]
@@ -7018,6 +7388,20 @@ mod prune_tests {
         prune_schedule_backups(&mut config, "missing-schedule", 0);
         assert_eq!(config.entries.len(), 2);
     }
+
+    /// The housekeeping dry run reports exactly what a prune would remove.
+    #[test]
+    fn retention_excess_lists_oldest_highest_index_first() {
+        let mut config = BackupConfig::default();
+        config.entries = vec![
+            mk("old",    "2026-07-01T03:00:00Z", "s"),
+            mk("newest", "2026-07-04T03:00:00Z", "s"),
+            mk("older",  "2026-06-30T03:00:00Z", "s"),
+            mk("new",    "2026-07-03T03:00:00Z", "s"),
+        ];
+        assert_eq!(retention_excess(&config, "s", 2), vec![2, 0]);
+        assert!(retention_excess(&config, "s", 4).is_empty());
+    }
 }
 
 /// Run a scheduled backup on demand (Gary 2026-06-25 "Run Now"), ignoring the
@@ -7122,6 +7506,13 @@ fn hook_failure_entry(schedule: &BackupSchedule, phase: &str, err: &str) -> Back
 /// them. Returned entries are already tagged with the schedule id and include
 /// synthetic entries for hook failures.
 fn execute_schedule_run(schedule: &BackupSchedule) -> (Vec<BackupEntry>, ScheduleRunSummary) {
+    // Taken before the pre-command so a hook that quiesces an app isn't
+    // left holding it while the run waits for a slot.
+    let _slot = crate::jobs::acquire(
+        crate::jobs::Category::Backup, crate::jobs::Priority::Low,
+        &format!("Scheduled backup '{}'", schedule.name),
+        |why| info!("Schedule '{}' queued: {}", schedule.name, why),
+    );
     let mut entries: Vec<BackupEntry> = Vec::new();
     let mut pre_ok = true;
 
@@ -7142,6 +7533,7 @@ fn execute_schedule_run(schedule: &BackupSchedule) -> (Vec<BackupEntry>, Schedul
         // saved server/credentials (same as both pre-existing call sites did).
         let mut storage = schedule.storage.clone();
         merge_pbs_secrets(&mut storage);
+        let _throttle = crate::io_throttle::apply(&schedule.io_limits, &format!("backup-{}", schedule.id));
         let backups: Vec<BackupEntry> = if schedule.backup_all {
             backup_all(&storage)
         } else {
@@ -7379,6 +7771,23 @@ pub fn list_available_targets() -> Vec<BackupTarget> {
         });
     }
 
+    // MySQL / MariaDB databases named on a saved SQL connection. Profiles
+    // without a default database aren't listed — there's nothing to dump.
+    for conn in crate::sql_connections::load().connections {
+        if !matches!(conn.kind, crate::sql_connections::SqlKind::Mysql | crate::sql_connections::SqlKind::Mariadb)
+            || conn.database.trim().is_empty()
+        {
+            continue;
+        }
+        targets.push(BackupTarget {
+            target_type: BackupTargetType::Mysql,
+            name: conn.database.trim().to_string(),
+            specs: Some(format!("{} — {}:{}", conn.label, conn.host, conn.port)),
+            sql_connection_id: conn.id,
+            ..Default::default()
+        });
+    }
+
     // Config is always available
     targets.push(BackupTarget {
         target_type: BackupTargetType::Config,
@@ -7573,6 +7982,7 @@ fn guess_target_type(filename: &str) -> BackupTargetType {
     else if filename.starts_with("lxc-") { BackupTargetType::Lxc }
     else if filename.starts_with("vm-") { BackupTargetType::Vm }
     else if filename.starts_with("systempath-") { BackupTargetType::SystemPath }
+    else if filename.starts_with("mysql-") { BackupTargetType::Mysql }
     else { BackupTargetType::Config }
 }
 
@@ -7838,7 +8248,86 @@ fn build_pct_lookup() -> std::collections::HashMap<String, (String, String)> {
     map
 }
 
+/// What a PBS restore produces. `Auto` is the long-standing behaviour:
+/// `ct`/`vm` snapshots become a guest and file-level trees land in a
+/// folder. `Lxc` turns a file-level tree (a container's rootfs) straight
+/// into a new native LXC container, and `VmDisk` turns a disk image
+/// archive (`drive-scsi0.img` from a Proxmox VM backup) into a new VM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PbsRestoreAs {
+    #[default]
+    Auto,
+    Lxc,
+    VmDisk,
+}
+
+/// Sizing for a VM created by a `VmDisk` restore. Zero = VmManager default.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PbsVmSpec {
+    #[serde(default)]
+    pub cpus: u32,
+    #[serde(default)]
+    pub memory_mb: u32,
+}
+
+/// Size of a file, or of everything under a directory.
+fn path_size_bytes(path: &Path) -> u64 {
+    if path.is_file() {
+        path.metadata().map(|m| m.len()).unwrap_or(0)
+    } else {
+        dir_size_bytes(&path.to_string_lossy())
+    }
+}
+
+/// Run a `proxmox-backup-client restore`, reporting how much has landed at
+/// `watch` every 2s. Err carries the client's stderr (or exit code).
+fn run_pbs_restore_watched<F>(mut cmd: Command, watch: &Path, on_progress: &F) -> Result<(), String>
+where
+    F: Fn(String, Option<f64>),
+{
+    // Capture stderr for error reporting — stdout can be null since we monitor dir size
+    use std::process::Stdio;
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd.spawn()
+        .map_err(|e| format!("Failed to start proxmox-backup-client: {}", e))?;
+
+    loop {
+        // Check if child is still running
+        match child.try_wait() {
+            Ok(Some(_status)) => break,  // Process finished
+            Ok(None) => {},               // Still running
+            Err(_) => break,
+        }
+        on_progress(format!("Downloaded: {}", format_size_human(path_size_bytes(watch))), None);
+        std::thread::sleep(std::time::Duration::from_secs(2));
+    }
+
+    let status = child.wait().map_err(|e| format!("PBS restore wait failed: {}", e))?;
+    if status.success() {
+        return Ok(());
+    }
+    // Read stderr for the actual error message
+    let stderr_output = if let Some(stderr) = child.stderr.take() {
+        use std::io::Read;
+        let mut buf = String::new();
+        let mut reader = std::io::BufReader::new(stderr);
+        let _ = reader.read_to_string(&mut buf);
+        buf
+    } else {
+        String::new()
+    };
+    Err(if stderr_output.trim().is_empty() {
+        format!("exit code {}", status.code().unwrap_or(-1))
+    } else {
+        stderr_output.trim().to_string()
+    })
+}
+
 /// Restore with real-time progress tracking via callback
+#[allow(clippy::too_many_arguments)]
 pub fn restore_from_pbs_with_progress<F>(
     storage: &BackupStorage,
     snapshot: &str,
@@ -7848,10 +8337,14 @@ pub fn restore_from_pbs_with_progress<F>(
     overwrite: bool,
     new_name: &str,
     target_storage: &str,
+    restore_as: PbsRestoreAs,
+    vm_spec: PbsVmSpec,
 ) -> Result<String, String>
 where
     F: Fn(String, Option<f64>),
 {
+    let _slot = crate::jobs::acquire(crate::jobs::Category::Restore, crate::jobs::Priority::Normal,
+        &format!("PBS restore {}", snapshot), |why| on_progress(format!("Queued — {}", why), None));
     let repo = pbs_repo_string(storage);
 
     // Parse snapshot "type/id/timestamp" to determine backup kind and ID
@@ -7896,6 +8389,26 @@ where
     // the filesystem. Restore the whole tree into a clearly-named directory
     // under the restore area; per-FILE restore is done from PBS's own UI.
     let is_file_level = actual_archive == "root.pxar";
+    if restore_as == PbsRestoreAs::VmDisk {
+        let _ = fs::remove_dir_all(&stage);
+        if !actual_archive.ends_with(".img") {
+            return Err(format!(
+                "Snapshot '{}' has no disk image (.img) archive to restore as a VM — only Proxmox VM backups do.",
+                snapshot));
+        }
+        let vm_name = if new_name.trim().is_empty() { snap_id } else { new_name.trim() };
+        return restore_pbs_image_as_vm(storage, &repo, &snapshot_fixed, &actual_archive, vm_name, vm_spec, &on_progress);
+    }
+    if restore_as == PbsRestoreAs::Lxc {
+        let _ = fs::remove_dir_all(&stage);
+        if !is_file_level {
+            return Err(format!(
+                "Snapshot '{}' isn't a file-level backup — restore it as its original type to get the guest back.",
+                snapshot));
+        }
+        let name = if new_name.trim().is_empty() { snap_id } else { new_name.trim() };
+        return restore_pbs_tree_as_lxc(storage, &repo, &snapshot_fixed, name, overwrite, &on_progress);
+    }
     if is_file_level {
         on_progress(format!("Restoring file-level tree {}...", actual_archive), Some(2.0));
         let out_dir = ensure_staging_dir().unwrap_or_else(|_| std::env::temp_dir())
@@ -7948,63 +8461,8 @@ where
         cmd.env("PBS_PASSWORD", pbs_pw);
     }
 
-    // Capture stderr for error reporting — stdout can be null since we monitor dir size
-    use std::process::Stdio;
-    cmd.stdout(Stdio::null());
-    cmd.stderr(Stdio::piped());
-
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
-        Err(e) => {
-            let _ = fs::remove_dir_all(&stage);
-            return Err(format!("Failed to start proxmox-backup-client: {}", e));
-        }
-    };
-
     // Monitor staging-dir size growth while the download runs
-    let target_path = stage.to_string_lossy().to_string();
-    let progress_fn = &on_progress;
-
-    loop {
-        // Check if child is still running
-        match child.try_wait() {
-            Ok(Some(_status)) => break,  // Process finished
-            Ok(None) => {},               // Still running
-            Err(_) => break,
-        }
-
-        // Measure directory size
-        let dir_size = dir_size_bytes(&target_path);
-        let size_str = format_size_human(dir_size);
-        progress_fn(format!("Downloaded: {}", size_str), None);
-
-        std::thread::sleep(std::time::Duration::from_secs(2));
-    }
-
-    let status = match child.wait() {
-        Ok(s) => s,
-        Err(e) => {
-            let _ = fs::remove_dir_all(&stage);
-            return Err(format!("PBS restore wait failed: {}", e));
-        }
-    };
-
-    if !status.success() {
-        // Read stderr for the actual error message
-        let stderr_output = if let Some(stderr) = child.stderr.take() {
-            use std::io::Read;
-            let mut buf = String::new();
-            let mut reader = std::io::BufReader::new(stderr);
-            let _ = reader.read_to_string(&mut buf);
-            buf
-        } else {
-            String::new()
-        };
-        let err_detail = if stderr_output.trim().is_empty() {
-            format!("exit code {}", status.code().unwrap_or(-1))
-        } else {
-            stderr_output.trim().to_string()
-        };
+    if let Err(err_detail) = run_pbs_restore_watched(cmd, &stage, &on_progress) {
         let _ = fs::remove_dir_all(&stage);
         return Err(format!("PBS restore failed for '{}': {}", snapshot_fixed, err_detail));
     }
@@ -8044,6 +8502,112 @@ where
     result
 }
 
+/// Extract a file-level snapshot's `root.pxar` straight into a new native
+/// LXC container's rootfs and give it a bootable config. Ownership is kept
+/// (no `--ignore-ownership`) so the rootfs UIDs survive.
+fn restore_pbs_tree_as_lxc<F>(
+    storage: &BackupStorage,
+    repo: &str,
+    snapshot: &str,
+    name: &str,
+    overwrite: bool,
+    on_progress: &F,
+) -> Result<String, String>
+where
+    F: Fn(String, Option<f64>),
+{
+    if !crate::auth::is_safe_name(name) {
+        return Err(format!(
+            "'{}' is not a valid container name — use letters, digits, '-', '_' and '.' only, with no '..'.", name));
+    }
+    if crate::containers::is_proxmox() {
+        return Err("Restoring a file-level snapshot as a container needs a native LXC node — \
+                    pick a non-Proxmox node, or restore it as its original type (a folder).".to_string());
+    }
+    let container_dir = format!("/var/lib/lxc/{}", name);
+    if Path::new(&container_dir).exists() {
+        if !overwrite {
+            return Err(format!(
+                "A container already exists at {} — re-run the restore with \"replace\" enabled to overwrite it.",
+                container_dir));
+        }
+        let _ = Command::new("lxc-stop").args(["-n", name, "-k"]).output();
+        fs::remove_dir_all(&container_dir)
+            .map_err(|e| format!("Failed to remove the existing container at {}: {}", container_dir, e))?;
+    }
+    let rootfs = PathBuf::from(format!("{}/rootfs", container_dir));
+    fs::create_dir_all(&rootfs)
+        .map_err(|e| format!("Failed to create container directory {}: {}", container_dir, e))?;
+
+    on_progress(format!("Restoring root.pxar into container '{}'...", name), Some(2.0));
+    let mut cmd = Command::new("proxmox-backup-client");
+    cmd.arg("restore").arg(snapshot).arg("root.pxar").arg(&rootfs)
+       .arg("--repository").arg(repo);
+    pbs_apply_common(&mut cmd, storage);
+    if let Err(e) = run_pbs_restore_watched(cmd, &rootfs, on_progress) {
+        let _ = fs::remove_dir_all(&container_dir);
+        return Err(format!("PBS restore failed for '{}': {}", snapshot, e));
+    }
+    // A file-level backup of a Docker container or a folder is not an OS
+    // tree — refuse it here rather than leave a container that can't boot.
+    if !["sbin", "etc", "bin", "usr"].iter().any(|d| rootfs.join(d).exists()) {
+        let _ = fs::remove_dir_all(&container_dir);
+        return Err(format!(
+            "Snapshot '{}' holds no root filesystem (no sbin, etc or bin) — restore it as its original type (a folder) instead.",
+            snapshot));
+    }
+    on_progress("Writing container config...".to_string(), Some(95.0));
+    crate::containers::lxc_write_bootable_config(&container_dir, name, None);
+    let _ = Command::new("chown").args(["root:root", &container_dir]).output();
+    let _ = Command::new("chmod").args(["755", &container_dir]).output();
+    Ok(format!(
+        "File-level snapshot restored as LXC container '{}' — start it from the Containers page. \
+         Its network is a fresh veth on lxcbr0; adjust it in Settings → Resources if needed.",
+        name))
+}
+
+/// Download a disk image archive (`drive-scsi0.img`) from PBS as a raw
+/// file and create a new VM that boots from it. VmManager converts the
+/// image on create, on native KVM, libvirt and Proxmox hosts alike.
+fn restore_pbs_image_as_vm<F>(
+    storage: &BackupStorage,
+    repo: &str,
+    snapshot: &str,
+    archive: &str,
+    vm_name: &str,
+    spec: PbsVmSpec,
+    on_progress: &F,
+) -> Result<String, String>
+where
+    F: Fn(String, Option<f64>),
+{
+    validate_vm_name_for_restore(vm_name)?;
+    let raw = ensure_staging_dir()?
+        .join(format!("pbs-disk-{}-{}.raw", vm_name, Uuid::new_v4().simple()));
+
+    on_progress(format!("Downloading {}...", archive), Some(2.0));
+    let mut cmd = Command::new("proxmox-backup-client");
+    cmd.arg("restore").arg(snapshot).arg(archive).arg(&raw)
+       .arg("--repository").arg(repo);
+    pbs_apply_common(&mut cmd, storage);
+    if let Err(e) = run_pbs_restore_watched(cmd, &raw, on_progress) {
+        let _ = fs::remove_file(&raw);
+        return Err(format!("PBS restore failed for '{}': {}", snapshot, e));
+    }
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+    let size_gb = path_size_bytes(&raw).div_ceil(GIB).max(1) as u32;
+    on_progress(format!("Creating VM '{}' from the {} GiB disk...", vm_name, size_gb), Some(90.0));
+    let mut config = crate::vms::manager::VmConfig::new(vm_name.to_string(), spec.cpus, spec.memory_mb, size_gb);
+    config.import_image = Some(raw.to_string_lossy().to_string());
+    let result = crate::vms::manager::VmManager::new().create_vm(config);
+    let _ = fs::remove_file(&raw);
+    result?;
+    Ok(format!(
+        "Disk '{}' restored as VM '{}' ({} GiB) — check its CPU, memory and network, then start it from the VMs page.",
+        archive, vm_name, size_gb))
+}
+
 /// Recursively calculate directory size in bytes
 fn dir_size_bytes(path: &str) -> u64 {
     let mut total = 0u64;
@@ -8243,9 +8807,8 @@ fn resolve_pbs_file_level(explicitly_set: bool, requested: bool, saved: bool) ->
 
 /// PBS configuration — stored in /etc/wolfstack/pbs/config.json
 pub fn load_pbs_config() -> BackupStorage {
-    let path = "/etc/wolfstack/pbs/config.json";
-    if let Ok(content) = fs::read_to_string(path) {
-        if let Ok(storage) = serde_json::from_str::<BackupStorage>(&content) {
+    if let Ok(content) = fs::read_to_string(PBS_CONFIG_PATH) {
+        if let Ok(storage) = crate::secrets::from_sealed_json::<BackupStorage>(&content, &BACKUP_SECRETS) {
             return storage;
         }
     }
@@ -8257,12 +8820,11 @@ pub fn load_pbs_config() -> BackupStorage {
 
 /// Save PBS configuration
 pub fn save_pbs_config(storage: &BackupStorage) -> Result<(), String> {
-    let path = "/etc/wolfstack/pbs/config.json";
     fs::create_dir_all("/etc/wolfstack/pbs")
         .map_err(|e| format!("Failed to create PBS config dir: {}", e))?;
-    let json = serde_json::to_string_pretty(storage)
+    let json = crate::secrets::to_sealed_json(storage, &BACKUP_SECRETS)
         .map_err(|e| format!("Failed to serialize PBS config: {}", e))?;
-    fs::write(path, json)
+    crate::paths::write_secure(PBS_CONFIG_PATH, json)
         .map_err(|e| format!("Failed to write PBS config: {}", e))?;
     Ok(())
 }
@@ -8526,6 +9088,7 @@ mod schedule_hook_tests {
             created_at: String::new(),
             pre_command: pre.to_string(),
             post_command: post.to_string(),
+            io_limits: Default::default(),
         }
     }
 
@@ -8606,3 +9169,56 @@ mod schedule_hook_tests {
             entries.iter().map(|e| &e.error).collect::<Vec<_>>());
     }
 }
+
+#[cfg(test)]
+mod mysql_target_tests {
+    use super::*;
+
+    #[test]
+    fn dump_filenames_round_trip_to_mysql_targets() {
+        assert_eq!(guess_target_type("mysql-shop-20260101-020000.sql.gz"), BackupTargetType::Mysql);
+        assert_eq!(BackupTargetType::Mysql.to_string(), "mysql");
+    }
+
+    #[test]
+    fn database_names_that_look_like_options_are_refused() {
+        assert!(validate_mysql_database("shop_prod").is_ok());
+        assert!(validate_mysql_database("--all-databases").is_err());
+        assert!(validate_mysql_database("").is_err());
+        assert!(validate_mysql_database("a/b").is_err());
+        assert!(validate_mysql_database(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn connection_id_is_optional_on_the_wire() {
+        let t: BackupTarget = serde_json::from_str(r#"{"type":"docker","name":"web"}"#).unwrap();
+        assert!(t.sql_connection_id.is_empty());
+        let json = serde_json::to_string(&t).unwrap();
+        assert!(!json.contains("sql_connection_id"));
+        let m: BackupTarget = serde_json::from_str(r#"{"type":"mysql","name":"shop","sql_connection_id":"c1"}"#).unwrap();
+        assert_eq!(m.target_type, BackupTargetType::Mysql);
+    }
+}
+
+#[cfg(test)]
+mod config_archive_tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_config_bundle_listing() {
+        let listing = "./\n./etc/\n./etc/wolfstack/\n./etc/wolfstack/users.json\n./etc/wolfnet/config.toml\n\
+                       ./var/\n./var/lib/\n./var/lib/wolfstack/vms/web/config.json\n";
+        assert_eq!(check_config_archive_listing(listing), Ok(7));
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_config_roots() {
+        for bad in ["./etc/shadow", "etc/wolfstack/../../root/.ssh/authorized_keys",
+                    "/etc/wolfstack/users.json", "var/lib/wolfstack/images/x.qcow2", "etc/wolfstack-evil/x"] {
+            let listing = format!("./etc/wolfstack/users.json\n{}\n", bad);
+            assert!(check_config_archive_listing(&listing).is_err(), "{} should be refused", bad);
+        }
+        assert!(check_config_archive_listing("./etc/wolfnet/config.toml\n").is_err(),
+            "an archive without /etc/wolfstack isn't a config backup");
+    }
+}
@@ -0,0 +1,594 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! PBS datastore housekeeping — usage, GC and prune schedules, and
+//! on-demand prune / garbage-collect / verify jobs.
+//!
+//! `proxmox-backup-client` only covers the backup side (upload, list,
+//! restore), so this talks to the PBS REST API at
+//! `https://<server>:8007/api2/json` with the credentials already saved
+//! in the PBS config: an API token header, or a ticket from
+//! `/access/ticket` for password logins.
+//!
+//! TLS is checked the way `proxmox-backup-client` checks it: with a
+//! fingerprint configured the server certificate must match it (the usual
+//! self-signed PBS), otherwise it must chain to a trusted root. The token
+//! or password is never sent to a server that fails that check.
+//!
+//! What the token may do is up to PBS: reading usage needs Datastore.Audit,
+//! jobs need Datastore.Modify / Datastore.Prune / Datastore.Verify, and the
+//! schedule settings need Datastore.Allocate on the datastore. Sections the
+//! token can't read are reported in `warnings` rather than failing the page.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::BackupStorage;
+
+/// The keep-* retention options PBS prune jobs and prune runs take.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeepOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_hourly: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_daily: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_weekly: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_monthly: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_yearly: Option<u64>,
+}
+
+const KEEP_KEYS: [&str; 6] = ["keep-last", "keep-hourly", "keep-daily", "keep-weekly", "keep-monthly", "keep-yearly"];
+
+impl KeepOptions {
+    fn values(&self) -> [Option<u64>; 6] {
+        [self.keep_last, self.keep_hourly, self.keep_daily, self.keep_weekly, self.keep_monthly, self.keep_yearly]
+    }
+
+    /// True when no keep-* option is set — PBS would keep everything.
+    pub fn is_empty(&self) -> bool {
+        self.values().iter().all(|v| v.is_none_or(|n| n == 0))
+    }
+
+    /// The options as PBS form parameters (`keep-daily=7`), zeroes dropped.
+    pub fn params(&self) -> Vec<(String, String)> {
+        KEEP_KEYS.iter().zip(self.values())
+            .filter_map(|(k, v)| v.filter(|n| *n > 0).map(|n| (k.to_string(), n.to_string())))
+            .collect()
+    }
+
+    /// Names of the options that are unset, for PBS's `delete` parameter.
+    fn unset(&self) -> Vec<&'static str> {
+        KEEP_KEYS.iter().zip(self.values())
+            .filter(|(_, v)| v.is_none_or(|n| n == 0))
+            .map(|(k, _)| *k)
+            .collect()
+    }
+
+    /// Read keep-* options out of a PBS object.
+    pub fn from_pbs(v: &serde_json::Value) -> Self {
+        let get = |k: &str| v.get(k).and_then(|x| x.as_u64());
+        KeepOptions {
+            keep_last: get("keep-last"),
+            keep_hourly: get("keep-hourly"),
+            keep_daily: get("keep-daily"),
+            keep_weekly: get("keep-weekly"),
+            keep_monthly: get("keep-monthly"),
+            keep_yearly: get("keep-yearly"),
+        }
+    }
+}
+
+/// A PBS prune job (`/config/prune`) on the configured datastore.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneJob {
+    pub id: String,
+    /// PBS calendar event, e.g. `daily` or `sat 03:00`.
+    pub schedule: String,
+    #[serde(default)]
+    pub disable: bool,
+    #[serde(default)]
+    pub ns: String,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub keep: KeepOptions,
+}
+
+impl PruneJob {
+    fn from_pbs(v: &serde_json::Value) -> Self {
+        let s = |k: &str| v.get(k).and_then(|x| x.as_str()).unwrap_or("").to_string();
+        PruneJob {
+            id: s("id"),
+            schedule: s("schedule"),
+            disable: v.get("disable").and_then(|x| x.as_bool()).unwrap_or(false),
+            ns: s("ns"),
+            comment: s("comment"),
+            keep: KeepOptions::from_pbs(v),
+        }
+    }
+}
+
+/// One backup group (`vm/101`, `ct/web-1`, `host/files`) on the datastore.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupGroup {
+    pub backup_type: String,
+    pub backup_id: String,
+    pub backup_count: u64,
+    pub last_backup: i64,
+    pub owner: String,
+}
+
+/// Everything the PBS housekeeping card shows.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatastoreOverview {
+    pub server: String,
+    pub store: String,
+    pub namespace: String,
+    pub total: u64,
+    pub used: u64,
+    pub avail: u64,
+    /// Last garbage-collection result as PBS reports it.
+    pub gc_status: Option<serde_json::Value>,
+    pub gc_schedule: String,
+    /// Datastore-level prune settings from PBS before 2.2 (no prune jobs).
+    pub legacy_prune_schedule: String,
+    pub legacy_keep: KeepOptions,
+    /// None when this PBS has no prune jobs (before 2.2).
+    pub prune_jobs: Option<Vec<PruneJob>>,
+    pub groups: Vec<BackupGroup>,
+    pub tasks: Vec<serde_json::Value>,
+    pub warnings: Vec<String>,
+}
+
+/// A job that can be started from WolfStack.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum JobRequest {
+    Gc,
+    Verify {
+        /// Skip snapshots that already verified OK.
+        #[serde(default)]
+        ignore_verified: bool,
+    },
+    Prune {
+        #[serde(default)]
+        keep: KeepOptions,
+        #[serde(default)]
+        dry_run: bool,
+    },
+    /// Run a configured prune job now.
+    PruneJob { id: String },
+}
+
+/// `https://host:8007/api2/json` for a PBS server as it's typed in the
+/// config — `host`, `host:port`, `[v6]:port` or a bare IPv6 address.
+pub fn api_base(server: &str) -> String {
+    let s = server.trim().trim_end_matches('/');
+    let s = s.strip_prefix("https://").unwrap_or(s);
+    let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((h, tail)) => (format!("[{}]", h), tail.strip_prefix(':').unwrap_or("")),
+            None => (s.to_string(), ""),
+        }
+    } else if s.matches(':').count() == 1 {
+        let (h, p) = s.split_once(':').unwrap_or((s, ""));
+        (h.to_string(), p)
+    } else if s.contains(':') {
+        (format!("[{}]", s), "")
+    } else {
+        (s.to_string(), "")
+    };
+    let port = if port.is_empty() { "8007" } else { port };
+    format!("https://{}:{}/api2/json", host, port)
+}
+
+/// Loose check on a PBS calendar event before it's sent — PBS does the
+/// real parse and its error comes back to the operator verbatim.
+pub fn validate_schedule(schedule: &str) -> Result<(), String> {
+    if schedule.len() > 128 {
+        return Err("Schedule is too long".into());
+    }
+    if !schedule.chars().all(|c| c.is_ascii_alphanumeric() || " :/*,.-+".contains(c)) {
+        return Err(format!("'{}' is not a PBS calendar event (e.g. daily, sat 03:00, *:0/30)", schedule));
+    }
+    Ok(())
+}
+
+/// PBS job ids: 3–32 characters of letters, digits, `_`, `-` and `.`.
+pub fn validate_job_id(id: &str) -> Result<(), String> {
+    let ok = (3..=32).contains(&id.len())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && !id.starts_with(['-', '.']);
+    if ok { Ok(()) } else { Err(format!("'{}' is not a valid job id (3-32 letters, digits, _ - .)", id)) }
+}
+
+/// Accepts exactly the server certificate whose SHA-256 is the configured
+/// fingerprint; handshake signatures are still verified.
+#[derive(Debug)]
+struct FingerprintVerifier {
+    want: Vec<u8>,
+    algs: rustls::crypto::WebPkiSupportedAlgorithms,
+}
+
+impl rustls::client::danger::ServerCertVerifier for FingerprintVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        use sha2::{Digest, Sha256};
+        if Sha256::digest(end_entity.as_ref()).as_slice() == self.want.as_slice() {
+            Ok(rustls::client::danger::ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General("PBS certificate does not match the configured fingerprint".into()))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algs)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algs)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.algs.supported_schemes()
+    }
+}
+
+fn http_client(fingerprint: &str) -> Result<reqwest::Client, String> {
+    let builder = reqwest::Client::builder().timeout(Duration::from_secs(30));
+    if fingerprint.trim().is_empty() {
+        return builder.build().map_err(|e| e.to_string());
+    }
+    let want: Vec<u8> = hex::decode(
+        fingerprint.chars().filter(|c| c.is_ascii_hexdigit()).collect::<String>(),
+    )
+    .ok()
+    .filter(|b| b.len() == 32)
+    .ok_or("PBS fingerprint must be a SHA-256 fingerprint (64 hex digits)")?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = FingerprintVerifier { want, algs: provider.signature_verification_algorithms };
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    builder.use_preconfigured_tls(config).build().map_err(|e| e.to_string())
+}
+
+enum Auth {
+    /// `Authorization: PBSAPIToken=user@realm!token:secret`
+    Token(String),
+    /// Password login: auth cookie plus the CSRF token writes need.
+    Ticket { cookie: String, csrf: String },
+}
+
+/// An authenticated session against the configured PBS datastore.
+struct PbsApi {
+    base: String,
+    store: String,
+    ns: String,
+    client: reqwest::Client,
+    auth: Auth,
+}
+
+impl PbsApi {
+    async fn connect(storage: &BackupStorage) -> Result<Self, String> {
+        if storage.pbs_server.trim().is_empty() || storage.pbs_datastore.trim().is_empty() {
+            return Err("PBS is not configured".into());
+        }
+        let base = api_base(&storage.pbs_server);
+        let client = http_client(&storage.pbs_fingerprint)?;
+        let auth = if !storage.pbs_token_secret.is_empty() {
+            Auth::Token(format!("PBSAPIToken={}:{}", super::pbs_auth_id(storage), storage.pbs_token_secret))
+        } else if !storage.pbs_password.is_empty() {
+            let resp = client.post(format!("{}/access/ticket", base))
+                .form(&[("username", storage.pbs_user.trim()), ("password", storage.pbs_password.as_str())])
+                .send().await
+                .map_err(|e| format!("Can't reach PBS at {}: {}", storage.pbs_server, e))?;
+            if !resp.status().is_success() {
+                return Err(format!("PBS login failed (HTTP {})", resp.status().as_u16()));
+            }
+            let body: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+            let data = &body["data"];
+            let ticket = data["ticket"].as_str().ok_or("PBS login returned no ticket")?;
+            Auth::Ticket {
+                cookie: format!("PBSAuthCookie={}", urlencoding::encode(ticket)),
+                csrf: data["CSRFPreventionToken"].as_str().unwrap_or("").to_string(),
+            }
+        } else {
+            return Err("PBS has no API token secret or password saved".into());
+        };
+        Ok(PbsApi {
+            base,
+            store: storage.pbs_datastore.trim().to_string(),
+            ns: storage.pbs_namespace.trim().to_string(),
+            client,
+            auth,
+        })
+    }
+
+    /// Call `path` (under /api2/json) and return its `data`. GET params go
+    /// in the query string, anything else as a form body.
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        params: &[(String, String)],
+    ) -> Result<serde_json::Value, String> {
+        let url = format!("{}{}", self.base, path);
+        let is_get = method == reqwest::Method::GET;
+        let mut req = self.client.request(method, &url);
+        req = if is_get { req.query(params) } else { req.form(params) };
+        req = match &self.auth {
+            Auth::Token(h) => req.header("Authorization", h),
+            Auth::Ticket { cookie, csrf } => {
+                let req = req.header("Cookie", cookie);
+                if is_get { req } else { req.header("CSRFPreventionToken", csrf) }
+            }
+        };
+        let resp = req.send().await.map_err(|e| format!("PBS request failed: {}", e))?;
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let msg = body.get("message").and_then(|m| m.as_str())
+                .map(|m| m.trim().to_string())
+                .unwrap_or_else(|| status.canonical_reason().unwrap_or("error").to_string());
+            return Err(format!("PBS {}: {}", status.as_u16(), msg));
+        }
+        Ok(body.get("data").cloned().unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn get(&self, path: &str, params: &[(String, String)]) -> Result<serde_json::Value, String> {
+        self.call(reqwest::Method::GET, path, params).await
+    }
+
+    fn store_path(&self, tail: &str) -> String {
+        format!("/admin/datastore/{}{}", urlencoding::encode(&self.store), tail)
+    }
+
+    fn ns_param(&self) -> Vec<(String, String)> {
+        if self.ns.is_empty() { Vec::new() } else { vec![("ns".into(), self.ns.clone())] }
+    }
+
+    async fn prune_jobs(&self) -> Result<Option<Vec<PruneJob>>, String> {
+        match self.get("/config/prune", &[]).await {
+            Ok(v) => Ok(Some(
+                v.as_array().map(|a| a.as_slice()).unwrap_or_default().iter()
+                    .filter(|j| j.get("store").and_then(|s| s.as_str()) == Some(self.store.as_str()))
+                    .map(PruneJob::from_pbs)
+                    .collect(),
+            )),
+            // PBS before 2.2 has no prune jobs, only datastore-level settings.
+            Err(e) if e.starts_with("PBS 404") => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Usage, schedules, prune jobs, groups and recent tasks for the datastore.
+pub async fn datastore_overview(storage: &BackupStorage) -> Result<DatastoreOverview, String> {
+    let api = PbsApi::connect(storage).await?;
+    let status = api.get(&api.store_path("/status"), &[("verbose".into(), "true".into())]).await?;
+    let mut out = DatastoreOverview {
+        server: storage.pbs_server.clone(),
+        store: api.store.clone(),
+        namespace: api.ns.clone(),
+        total: status["total"].as_u64().unwrap_or(0),
+        used: status["used"].as_u64().unwrap_or(0),
+        avail: status["avail"].as_u64().unwrap_or(0),
+        gc_status: status.get("gc-status").cloned(),
+        ..Default::default()
+    };
+
+    match api.get(&format!("/config/datastore/{}", urlencoding::encode(&api.store)), &[]).await {
+        Ok(cfg) => {
+            out.gc_schedule = cfg["gc-schedule"].as_str().unwrap_or("").to_string();
+            out.legacy_prune_schedule = cfg["prune-schedule"].as_str().unwrap_or("").to_string();
+            out.legacy_keep = KeepOptions::from_pbs(&cfg);
+        }
+        Err(e) => out.warnings.push(format!("Datastore settings: {}", e)),
+    }
+    match api.prune_jobs().await {
+        Ok(jobs) => out.prune_jobs = jobs,
+        Err(e) => out.warnings.push(format!("Prune jobs: {}", e)),
+    }
+    match api.get(&api.store_path("/groups"), &api.ns_param()).await {
+        Ok(groups) => {
+            out.groups = groups.as_array().map(|a| a.as_slice()).unwrap_or_default().iter()
+                .map(|g| BackupGroup {
+                    backup_type: g["backup-type"].as_str().unwrap_or("").to_string(),
+                    backup_id: g["backup-id"].as_str().unwrap_or("").to_string(),
+                    backup_count: g["backup-count"].as_u64().unwrap_or(0),
+                    last_backup: g["last-backup"].as_i64().unwrap_or(0),
+                    owner: g["owner"].as_str().unwrap_or("").to_string(),
+                })
+                .collect();
+            out.groups.sort_by(|a, b| (&a.backup_type, &a.backup_id).cmp(&(&b.backup_type, &b.backup_id)));
+        }
+        Err(e) => out.warnings.push(format!("Backup groups: {}", e)),
+    }
+    let task_params = [("store".to_string(), api.store.clone()), ("limit".to_string(), "15".to_string())];
+    match api.get("/nodes/localhost/tasks", &task_params).await {
+        Ok(tasks) => out.tasks = tasks.as_array().cloned().unwrap_or_default(),
+        Err(e) => out.warnings.push(format!("Recent tasks: {}", e)),
+    }
+    Ok(out)
+}
+
+/// Set (or with an empty string, clear) the datastore's GC schedule.
+pub async fn set_gc_schedule(storage: &BackupStorage, schedule: &str) -> Result<(), String> {
+    let schedule = schedule.trim();
+    validate_schedule(schedule)?;
+    let api = PbsApi::connect(storage).await?;
+    let param = if schedule.is_empty() {
+        ("delete".to_string(), "gc-schedule".to_string())
+    } else {
+        ("gc-schedule".to_string(), schedule.to_string())
+    };
+    api.call(reqwest::Method::PUT, &format!("/config/datastore/{}", urlencoding::encode(&api.store)), &[param]).await?;
+    Ok(())
+}
+
+/// Create the prune job, or update it if one with that id already exists
+/// on this datastore.
+pub async fn save_prune_job(storage: &BackupStorage, job: &PruneJob) -> Result<(), String> {
+    validate_job_id(&job.id)?;
+    let schedule = job.schedule.trim();
+    if schedule.is_empty() {
+        return Err("A prune job needs a schedule".into());
+    }
+    validate_schedule(schedule)?;
+    if job.keep.is_empty() {
+        return Err("Set at least one keep option — a prune job without one keeps everything".into());
+    }
+    let api = PbsApi::connect(storage).await?;
+    let Some(existing) = api.prune_jobs().await? else {
+        return Err("This PBS has no prune jobs (PBS 2.2 or newer needed)".into());
+    };
+    let mut params = vec![
+        ("schedule".to_string(), schedule.to_string()),
+        ("disable".to_string(), job.disable.to_string()),
+    ];
+    params.extend(job.keep.params());
+    let ns = job.ns.trim();
+    if !ns.is_empty() {
+        params.push(("ns".into(), ns.into()));
+    }
+    if !job.comment.trim().is_empty() {
+        params.push(("comment".into(), job.comment.trim().into()));
+    }
+    if existing.iter().any(|j| j.id == job.id) {
+        let mut delete: Vec<&str> = job.keep.unset();
+        if ns.is_empty() { delete.push("ns"); }
+        if job.comment.trim().is_empty() { delete.push("comment"); }
+        params.extend(delete.into_iter().map(|d| ("delete".to_string(), d.to_string())));
+        api.call(reqwest::Method::PUT, &format!("/config/prune/{}", urlencoding::encode(&job.id)), &params).await?;
+    } else {
+        params.push(("id".into(), job.id.clone()));
+        params.push(("store".into(), api.store.clone()));
+        api.call(reqwest::Method::POST, "/config/prune", &params).await?;
+    }
+    Ok(())
+}
+
+/// Delete a prune job — only one that belongs to the configured datastore.
+pub async fn delete_prune_job(storage: &BackupStorage, id: &str) -> Result<(), String> {
+    validate_job_id(id)?;
+    let api = PbsApi::connect(storage).await?;
+    let jobs = api.prune_jobs().await?.unwrap_or_default();
+    if !jobs.iter().any(|j| j.id == id) {
+        return Err(format!("Prune job '{}' not found on datastore {}", id, api.store));
+    }
+    api.call(reqwest::Method::DELETE, &format!("/config/prune/{}", urlencoding::encode(id)), &[]).await?;
+    Ok(())
+}
+
+/// Start a job and return its PBS task id (UPID).
+pub async fn run_job(storage: &BackupStorage, job: &JobRequest) -> Result<String, String> {
+    let api = PbsApi::connect(storage).await?;
+    let data = match job {
+        JobRequest::Gc => api.call(reqwest::Method::POST, &api.store_path("/gc"), &[]).await?,
+        JobRequest::Verify { ignore_verified } => {
+            let mut params = api.ns_param();
+            params.push(("ignore-verified".into(), ignore_verified.to_string()));
+            api.call(reqwest::Method::POST, &api.store_path("/verify"), &params).await?
+        }
+        JobRequest::Prune { keep, dry_run } => {
+            if keep.is_empty() {
+                return Err("Set at least one keep option".into());
+            }
+            let mut params = api.ns_param();
+            params.extend(keep.params());
+            params.push(("dry-run".into(), dry_run.to_string()));
+            api.call(reqwest::Method::POST, &api.store_path("/prune-datastore"), &params).await?
+        }
+        JobRequest::PruneJob { id } => {
+            validate_job_id(id)?;
+            api.call(reqwest::Method::POST, &format!("/admin/prune/{}/run", urlencoding::encode(id)), &[]).await?
+        }
+    };
+    data.as_str().map(str::to_string).ok_or_else(|| "PBS did not return a task id".into())
+}
+
+/// Status and log of a PBS task started by `run_job`.
+pub async fn task_status(storage: &BackupStorage, upid: &str) -> Result<serde_json::Value, String> {
+    if !upid.starts_with("UPID:") || upid.len() > 512 {
+        return Err("Not a PBS task id".into());
+    }
+    let api = PbsApi::connect(storage).await?;
+    let path = format!("/nodes/localhost/tasks/{}", urlencoding::encode(upid));
+    let status = api.get(&format!("{}/status", path), &[]).await?;
+    let log = api.get(&format!("{}/log", path), &[("limit".into(), "500".into())]).await.unwrap_or_default();
+    let lines: Vec<String> = log.as_array().map(|a| a.as_slice()).unwrap_or_default().iter()
+        .filter_map(|l| l.get("t").and_then(|t| t.as_str()).map(str::to_string))
+        .collect();
+    Ok(serde_json::json!({
+        "upid": upid,
+        "status": status.get("status").and_then(|s| s.as_str()).unwrap_or("unknown"),
+        "exitstatus": status.get("exitstatus").and_then(|s| s.as_str()),
+        "log": lines,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_base_handles_ports_and_ipv6() {
+        assert_eq!(api_base("pbs.lan"), "https://pbs.lan:8007/api2/json");
+        assert_eq!(api_base("pbs.lan:8008"), "https://pbs.lan:8008/api2/json");
+        assert_eq!(api_base("https://pbs.lan/"), "https://pbs.lan:8007/api2/json");
+        assert_eq!(api_base("[fd00::7]:8007"), "https://[fd00::7]:8007/api2/json");
+        assert_eq!(api_base("fd00::7"), "https://[fd00::7]:8007/api2/json");
+    }
+
+    #[test]
+    fn keep_options_round_trip() {
+        let keep = KeepOptions::from_pbs(&serde_json::json!({ "keep-daily": 7, "keep-weekly": 4, "keep-last": 0 }));
+        assert_eq!(keep.keep_daily, Some(7));
+        assert_eq!(keep.params(), vec![
+            ("keep-daily".to_string(), "7".to_string()),
+            ("keep-weekly".to_string(), "4".to_string()),
+        ]);
+        assert_eq!(keep.unset(), vec!["keep-last", "keep-hourly", "keep-monthly", "keep-yearly"]);
+        assert!(!keep.is_empty());
+        assert!(KeepOptions { keep_last: Some(0), ..Default::default() }.is_empty());
+    }
+
+    #[test]
+    fn schedule_and_job_id_validation() {
+        assert!(validate_schedule("daily").is_ok());
+        assert!(validate_schedule("mon..fri 02:30").is_ok());
+        assert!(validate_schedule("*:0/30").is_ok());
+        assert!(validate_schedule("daily; rm -rf /").is_err());
+        assert!(validate_job_id("nightly-prune").is_ok());
+        assert!(validate_job_id("ab").is_err());
+        assert!(validate_job_id("../x").is_err());
+        assert!(validate_job_id("-abc").is_err());
+    }
+}
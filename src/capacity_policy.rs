@@ -0,0 +1,262 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Resource reservation and overcommit policy for this node.
+//!
+//! Before a container or VM is created, its memory and CPU limits are
+//! added to what the node's running guests already have and compared
+//! against what the policy allows:
+//!
+//! - **memory**: `(host RAM − reserve_memory_mb) × memory_overcommit`
+//! - **CPU**: `host CPUs × (1 − reserve_cpu_percent / 100) × cpu_overcommit`
+//!
+//! The reservation is what the host keeps for itself (WolfStack, the
+//! kernel, page cache). In `warn` mode a create that goes over still
+//! happens and the response carries a `capacity_warning`; in `reject`
+//! mode it is refused with 409 before anything is touched; `off` skips
+//! the check.
+//!
+//! Only limits count. A guest created without a memory or CPU limit can
+//! use the whole host, so it adds nothing to the committed total — set
+//! limits on guests if the policy is to mean anything. Stopped guests
+//! aren't counted either.
+//!
+//! The policy lives in `capacity_policy.json` next to the other per-node
+//! config and is edited through `/api/capacity/policy` (on another node
+//! via the node proxy).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Off,
+    #[default]
+    Warn,
+    Reject,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub mode: Mode,
+    /// RAM kept back for the host, in MB.
+    pub reserve_memory_mb: u64,
+    /// Share of the host's CPUs kept back for the host, 0–90.
+    pub reserve_cpu_percent: u32,
+    /// How far guest memory limits may add up past what's left after the
+    /// reservation. 1.0 = no overcommit.
+    pub memory_overcommit: f64,
+    /// Likewise for vCPUs / CPU limits.
+    pub cpu_overcommit: f64,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Warn,
+            reserve_memory_mb: 2048,
+            reserve_cpu_percent: 10,
+            memory_overcommit: 1.0,
+            cpu_overcommit: 4.0,
+        }
+    }
+}
+
+fn policy_path() -> String {
+    format!("{}/capacity_policy.json", crate::paths::get().config_dir)
+}
+
+impl Policy {
+    pub fn load() -> Self {
+        std::fs::read_to_string(policy_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = policy_path();
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write capacity policy: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reserve_cpu_percent > 90 {
+            return Err("reserve_cpu_percent must be 0-90".into());
+        }
+        for (name, ratio) in [("memory_overcommit", self.memory_overcommit), ("cpu_overcommit", self.cpu_overcommit)] {
+            if !ratio.is_finite() || !(0.1..=100.0).contains(&ratio) {
+                return Err(format!("{} must be between 0.1 and 100", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// What guests may add up to on a host of this size.
+    pub fn allowed(&self, host: &Resources) -> Resources {
+        let memory = host.memory_mb.saturating_sub(self.reserve_memory_mb) as f64 * self.memory_overcommit;
+        let cpus = host.cpus * (1.0 - self.reserve_cpu_percent as f64 / 100.0) * self.cpu_overcommit;
+        Resources { memory_mb: memory as u64, cpus }
+    }
+}
+
+/// An amount of memory and CPU — a host, a guest, or a sum of guests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Resources {
+    pub memory_mb: u64,
+    pub cpus: f64,
+}
+
+impl Resources {
+    /// From the create forms' limit strings: memory as "512m" / "2g" /
+    /// bare MB, CPUs as a count ("2", "1.5") or a cpuset ("0-3,6").
+    /// Missing or unparseable limits are zero.
+    pub fn from_limits(memory: Option<&str>, cpus: Option<&str>) -> Self {
+        Self {
+            memory_mb: memory.map(crate::containers::parse_mem_to_mb).unwrap_or(0),
+            cpus: cpus.map(cpu_count).unwrap_or(0.0),
+        }
+    }
+
+    fn add(&mut self, other: Resources) {
+        self.memory_mb += other.memory_mb;
+        self.cpus += other.cpus;
+    }
+}
+
+/// CPUs a limit string stands for: a plain number, or the size of a
+/// cpuset list.
+fn cpu_count(s: &str) -> f64 {
+    let s = s.trim();
+    if let Ok(n) = s.parse::<f64>() {
+        return if n.is_finite() && n > 0.0 { n } else { 0.0 };
+    }
+    let mut count = 0u32;
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((a, b)) => match (a.trim().parse::<u32>(), b.trim().parse::<u32>()) {
+                (Ok(a), Ok(b)) if b >= a => count += b - a + 1,
+                _ => return 0.0,
+            },
+            None if part.parse::<u32>().is_ok() => count += 1,
+            None => return 0.0,
+        }
+    }
+    count as f64
+}
+
+/// This host's RAM and CPU count.
+pub fn host() -> Resources {
+    let memory_kb = std::fs::read_to_string("/proc/meminfo").ok()
+        .and_then(|s| s.lines()
+            .find(|l| l.starts_with("MemTotal:"))
+            .and_then(|l| l.split_whitespace().nth(1))
+            .and_then(|v| v.parse::<u64>().ok()))
+        .unwrap_or(0);
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    Resources { memory_mb: memory_kb / 1024, cpus: cpus as f64 }
+}
+
+/// The limits of every running Docker container, LXC container and VM.
+pub fn committed() -> Resources {
+    let mut total = Resources::default();
+    for c in crate::containers::docker_list_all_cached() {
+        if c.state != "running" { continue; }
+        let Ok(inspect) = crate::containers::docker_inspect(&c.name) else { continue };
+        let memory = inspect.pointer("/HostConfig/Memory").and_then(|v| v.as_u64()).unwrap_or(0);
+        let nano_cpus = inspect.pointer("/HostConfig/NanoCpus").and_then(|v| v.as_u64()).unwrap_or(0);
+        total.add(Resources { memory_mb: memory / 1048576, cpus: nano_cpus as f64 / 1e9 });
+    }
+    for c in crate::containers::lxc_list_all_cached() {
+        if c.state != "running" { continue; }
+        let Some(cfg) = crate::containers::lxc_parse_config(&c.name) else { continue };
+        total.add(Resources::from_limits(Some(&cfg.memory_limit), Some(&cfg.cpus)));
+    }
+    for vm in crate::vms::manager::VmManager::new().list_vms_readonly() {
+        if !vm.running { continue; }
+        total.add(Resources { memory_mb: vm.memory_mb as u64, cpus: vm.cpus as f64 });
+    }
+    total
+}
+
+/// What a guest asking for `demand` would push past `allowed`, as one
+/// message per resource. Empty when it fits.
+pub fn assess(policy: &Policy, host: &Resources, committed: &Resources, demand: &Resources) -> Vec<String> {
+    let allowed = policy.allowed(host);
+    let mut over = Vec::new();
+    let memory = committed.memory_mb + demand.memory_mb;
+    if demand.memory_mb > 0 && memory > allowed.memory_mb {
+        over.push(format!(
+            "memory: {} MB committed + {} MB requested exceeds the {} MB allowed ({} MB host, {} MB reserved, overcommit {}x)",
+            committed.memory_mb, demand.memory_mb, allowed.memory_mb,
+            host.memory_mb, policy.reserve_memory_mb, policy.memory_overcommit));
+    }
+    let cpus = committed.cpus + demand.cpus;
+    if demand.cpus > 0.0 && cpus > allowed.cpus + 1e-9 {
+        over.push(format!(
+            "CPU: {:.1} committed + {:.1} requested exceeds the {:.1} allowed ({} host CPUs, {}% reserved, overcommit {}x)",
+            committed.cpus, demand.cpus, allowed.cpus,
+            host.cpus, policy.reserve_cpu_percent, policy.cpu_overcommit));
+    }
+    over
+}
+
+/// Check a new guest against this node's policy. `Err` means refuse the
+/// create (reject mode); `Ok(Some(..))` is a warning to pass back.
+pub fn check(what: &str, demand: Resources) -> Result<Option<String>, String> {
+    let policy = Policy::load();
+    if policy.mode == Mode::Off || (demand.memory_mb == 0 && demand.cpus == 0.0) {
+        return Ok(None);
+    }
+    let over = assess(&policy, &host(), &committed(), &demand);
+    if over.is_empty() {
+        return Ok(None);
+    }
+    let msg = format!("{} exceeds this node's capacity policy — {}", what, over.join("; "));
+    match policy.mode {
+        Mode::Reject => Err(msg),
+        _ => {
+            tracing::warn!("{}", msg);
+            Ok(Some(msg))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservation_and_overcommit_set_the_ceiling() {
+        let policy = Policy::default();
+        let host = Resources { memory_mb: 16384, cpus: 8.0 };
+        let allowed = policy.allowed(&host);
+        assert_eq!(allowed.memory_mb, 14336);
+        assert!((allowed.cpus - 28.8).abs() < 1e-9);
+
+        let committed = Resources { memory_mb: 12288, cpus: 20.0 };
+        assert!(assess(&policy, &host, &committed, &Resources { memory_mb: 2048, cpus: 8.0 }).is_empty());
+        let over = assess(&policy, &host, &committed, &Resources { memory_mb: 4096, cpus: 10.0 });
+        assert_eq!(over.len(), 2);
+        assert!(over[0].starts_with("memory:") && over[1].starts_with("CPU:"));
+        // An unlimited guest is never over.
+        assert!(assess(&policy, &host, &Resources { memory_mb: 99999, cpus: 99.0 }, &Resources::default()).is_empty());
+    }
+
+    #[test]
+    fn limit_strings_are_understood() {
+        let r = Resources::from_limits(Some("2g"), Some("1.5"));
+        assert_eq!(r.memory_mb, 2048);
+        assert!((r.cpus - 1.5).abs() < 1e-9);
+        assert_eq!(cpu_count("0-3,6"), 5.0);
+        assert_eq!(cpu_count("abc"), 0.0);
+        assert_eq!(Resources::from_limits(None, Some("")), Resources::default());
+        assert!(Policy { reserve_cpu_percent: 95, ..Default::default() }.validate().is_err());
+        assert!(Policy { memory_overcommit: 0.0, ..Default::default() }.validate().is_err());
+        assert!(Policy::default().validate().is_ok());
+    }
+}
@@ -290,9 +290,7 @@ fn ceph_text(args: &[&str]) -> Result<String, String> {
 
 /// Run a ceph-volume or other system command
 fn run_cmd(cmd: &str, args: &[&str]) -> Result<String, String> {
-    let output = Command::new(cmd)
-        .args(args)
-        .output()
+    let output = crate::logging::run_traced(Command::new(cmd).args(args))
         .map_err(|e| format!("Failed to run {}: {}", cmd, e))?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
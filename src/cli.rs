@@ -0,0 +1,415 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Headless management subcommands — `wolfstack node list`,
+//! `wolfstack ct start NAME`, `wolfstack backup run SCHEDULE`,
+//! `wolfstack issues`.
+//!
+//! They don't touch state directly: each one calls the running daemon's
+//! API over loopback, so the daemon stays the single writer and every
+//! action goes through the same code (and audit trail) as the dashboard.
+//! Requests authenticate with this server's join token, sent as
+//! `X-WolfStack-Join-Token`. The token file is root-only and the daemon
+//! only accepts it from 127.0.0.1/::1, so these commands need `sudo` and
+//! a shell on the box — nothing more.
+//!
+//! Output is a plain table by default; `--json` prints the API response
+//! as-is for scripting.
+
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::Value;
+use std::time::Duration;
+
+const JOIN_TOKEN_PATH: &str = "/etc/wolfstack/join-token";
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Cluster nodes
+    #[command(subcommand)]
+    Node(NodeCommand),
+    /// Docker and LXC containers on this node
+    #[command(subcommand)]
+    Ct(CtCommand),
+    /// Backup schedules
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// Scan this node for issues. Exits 2 when any issue is critical.
+    Issues,
+    /// snmpd's pass_persist helper for the WolfStack MIB (run by snmpd)
+    #[command(hide = true)]
+    SnmpPass,
+}
+
+#[derive(Subcommand)]
+pub enum NodeCommand {
+    /// List every node this server knows about
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum CtCommand {
+    /// List containers
+    List,
+    /// Start a container
+    Start(CtTarget),
+    /// Stop a container
+    Stop(CtTarget),
+    /// Restart a container
+    Restart(CtTarget),
+}
+
+#[derive(Args)]
+pub struct CtTarget {
+    /// Container name (or Docker ID)
+    pub name: String,
+    /// Only needed when a Docker and an LXC container share the name
+    #[arg(long, value_enum)]
+    pub runtime: Option<Runtime>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Runtime {
+    Docker,
+    Lxc,
+}
+
+impl Runtime {
+    fn as_str(self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Lxc => "lxc",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommand {
+    /// List backup schedules
+    List,
+    /// Run a backup schedule now
+    Run {
+        /// Schedule name or ID
+        schedule: String,
+    },
+}
+
+/// Options shared by every subcommand.
+#[derive(Args)]
+pub struct Options {
+    /// Print the raw API response as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// API base URL (default: this node's API port on localhost)
+    #[arg(long, global = true, value_name = "URL")]
+    pub api_url: Option<String>,
+}
+
+/// Run a subcommand and return the process exit code.
+pub async fn run(command: &Command, opts: &Options) -> i32 {
+    // Run by snmpd as its own user: no join token to read, and stdout
+    // belongs to the pass_persist protocol.
+    if let Command::SnmpPass = command {
+        return crate::snmp::pass_persist();
+    }
+    let client = match Client::new(opts.api_url.as_deref()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let result = match command {
+        Command::Node(NodeCommand::List) => node_list(&client, opts.json).await,
+        Command::Ct(CtCommand::List) => ct_list(&client, opts.json).await,
+        Command::Ct(CtCommand::Start(t)) => ct_action(&client, t, "start", opts.json).await,
+        Command::Ct(CtCommand::Stop(t)) => ct_action(&client, t, "stop", opts.json).await,
+        Command::Ct(CtCommand::Restart(t)) => ct_action(&client, t, "restart", opts.json).await,
+        Command::Backup(BackupCommand::List) => backup_list(&client, opts.json).await,
+        Command::Backup(BackupCommand::Run { schedule }) => backup_run(&client, schedule, opts.json).await,
+        Command::Issues => issues(&client, opts.json).await,
+        Command::SnmpPass => unreachable!("handled above"),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+struct Client {
+    http: reqwest::Client,
+    /// Candidate base URLs, tried in order until one connects.
+    bases: Vec<String>,
+    token: String,
+}
+
+impl Client {
+    fn new(api_url: Option<&str>) -> Result<Self, String> {
+        let token = std::fs::read_to_string(JOIN_TOKEN_PATH)
+            .map(|t| t.trim().to_string())
+            .map_err(|e| format!("Cannot read {}: {} (run as root, e.g. with sudo)", JOIN_TOKEN_PATH, e))?;
+        if token.is_empty() {
+            return Err(format!("{} is empty — start the wolfstack service once to create it", JOIN_TOKEN_PATH));
+        }
+        let bases = match api_url {
+            Some(url) => vec![url.trim_end_matches('/').to_string()],
+            None => {
+                // The API port serves HTTPS when TLS is on and plain HTTP
+                // with --no-tls; self-signed installs also keep a plain
+                // inter-node listener, which still answers if the admin
+                // port demands a client certificate.
+                let ports = crate::ports::PortConfig::load();
+                vec![
+                    format!("https://127.0.0.1:{}", ports.api),
+                    format!("http://127.0.0.1:{}", ports.api),
+                    format!("http://127.0.0.1:{}", ports.inter_node),
+                ]
+            }
+        };
+        let http = reqwest::Client::builder()
+            // Loopback to our own listener, whose cert names the public
+            // hostname (or is self-signed) — never 127.0.0.1.
+            .danger_accept_invalid_certs(true)
+            .connect_timeout(Duration::from_secs(3))
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { http, bases, token })
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<Value, String> {
+        let mut last_err = String::new();
+        for base in &self.bases {
+            let mut req = self.http.request(method.clone(), format!("{}{}", base, path))
+                .header("X-WolfStack-Join-Token", &self.token);
+            if let Some(b) = &body {
+                req = req.json(b);
+            }
+            let resp = match req.send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    last_err = format!("{}: {}", base, e);
+                    continue;
+                }
+            };
+            let status = resp.status();
+            let text = resp.text().await.map_err(|e| e.to_string())?;
+            let value: Value = match serde_json::from_str(&text) {
+                Ok(v) => v,
+                // Plain HTTP sent to the TLS port gets a non-JSON reply.
+                Err(_) if status == reqwest::StatusCode::BAD_REQUEST => {
+                    last_err = format!("{}: HTTP {}", base, status);
+                    continue;
+                }
+                Err(_) => Value::String(text),
+            };
+            if !status.is_success() {
+                let msg = value["error"].as_str().map(String::from).unwrap_or_else(|| format!("HTTP {}", status));
+                return Err(msg);
+            }
+            return Ok(value);
+        }
+        Err(format!("Cannot reach the WolfStack API ({}). Is the wolfstack service running?", last_err))
+    }
+
+    async fn get(&self, path: &str) -> Result<Value, String> {
+        self.request(reqwest::Method::GET, path, None).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value, String> {
+        self.request(reqwest::Method::POST, path, Some(body)).await
+    }
+}
+
+fn print_json(v: &Value) {
+    println!("{}", serde_json::to_string_pretty(v).unwrap_or_default());
+}
+
+/// Print left-aligned columns sized to their widest cell.
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(c, w)| format!("{:<w$}", c, w = *w)).collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+fn text(v: &Value, key: &str) -> String {
+    match &v[key] {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+async fn node_list(client: &Client, json: bool) -> Result<i32, String> {
+    let nodes = client.get("/api/nodes").await?;
+    if json {
+        print_json(&nodes);
+        return Ok(0);
+    }
+    let rows: Vec<Vec<String>> = nodes.as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter().map(|n| {
+        let name = if n["is_self"].as_bool() == Some(true) { format!("{} *", text(n, "hostname")) } else { text(n, "hostname") };
+        vec![
+            name,
+            format!("{}:{}", text(n, "address"), text(n, "port")),
+            if n["online"].as_bool() == Some(true) { "online".into() } else { "offline".into() },
+            text(n, "node_type"),
+            n["cluster_name"].as_str().unwrap_or("WolfStack").to_string(),
+        ]
+    }).collect();
+    print_table(&["HOSTNAME", "ADDRESS", "STATUS", "TYPE", "CLUSTER"], &rows);
+    Ok(0)
+}
+
+/// Docker and LXC lists from this node. A runtime that isn't installed
+/// just contributes nothing.
+async fn containers(client: &Client) -> Result<Vec<Value>, String> {
+    let mut all = Vec::new();
+    let mut errors = Vec::new();
+    for rt in [Runtime::Docker, Runtime::Lxc] {
+        match client.get(&format!("/api/containers/{}", rt.as_str())).await {
+            Ok(Value::Array(list)) => all.extend(list),
+            Ok(_) => {}
+            Err(e) => errors.push(format!("{}: {}", rt.as_str(), e)),
+        }
+    }
+    if all.is_empty() && errors.len() == 2 {
+        return Err(errors.join("; "));
+    }
+    Ok(all)
+}
+
+async fn ct_list(client: &Client, json: bool) -> Result<i32, String> {
+    let list = containers(client).await?;
+    if json {
+        print_json(&Value::Array(list));
+        return Ok(0);
+    }
+    let rows: Vec<Vec<String>> = list.iter().map(|c| vec![
+        text(c, "name"),
+        text(c, "runtime"),
+        text(c, "state"),
+        text(c, "image"),
+        text(c, "ip_address"),
+    ]).collect();
+    print_table(&["NAME", "RUNTIME", "STATE", "IMAGE", "IP"], &rows);
+    Ok(0)
+}
+
+async fn ct_action(client: &Client, target: &CtTarget, action: &str, json: bool) -> Result<i32, String> {
+    let runtime = match target.runtime {
+        Some(rt) => rt,
+        None => {
+            let list = containers(client).await?;
+            let matches: Vec<&str> = list.iter()
+                .filter(|c| c["name"].as_str() == Some(target.name.as_str()) || c["id"].as_str() == Some(target.name.as_str()))
+                .filter_map(|c| c["runtime"].as_str())
+                .collect();
+            match (matches.contains(&"docker"), matches.contains(&"lxc")) {
+                (true, true) => return Err(format!(
+                    "'{}' is both a Docker and an LXC container — pass --runtime docker or --runtime lxc", target.name)),
+                (true, false) => Runtime::Docker,
+                (false, true) => Runtime::Lxc,
+                (false, false) => return Err(format!("No container named '{}' on this node", target.name)),
+            }
+        }
+    };
+    let path = format!("/api/containers/{}/{}/action", runtime.as_str(), urlencoding::encode(&target.name));
+    let resp = client.post(&path, serde_json::json!({ "action": action })).await?;
+    if json {
+        print_json(&resp);
+    } else {
+        println!("{}", resp["message"].as_str().unwrap_or("Done"));
+    }
+    Ok(0)
+}
+
+async fn backup_list(client: &Client, json: bool) -> Result<i32, String> {
+    let schedules = client.get("/api/backups/schedules").await?;
+    if json {
+        print_json(&schedules);
+        return Ok(0);
+    }
+    let rows: Vec<Vec<String>> = schedules.as_array().map(|a| a.as_slice()).unwrap_or(&[]).iter().map(|s| vec![
+        text(s, "id"),
+        text(s, "name"),
+        text(s, "frequency"),
+        text(s, "time"),
+        if s["enabled"].as_bool() == Some(true) { "yes".into() } else { "no".into() },
+        match text(s, "last_run") { r if r.is_empty() => "never".into(), r => r },
+    ]).collect();
+    print_table(&["ID", "NAME", "FREQUENCY", "TIME", "ENABLED", "LAST RUN"], &rows);
+    Ok(0)
+}
+
+async fn backup_run(client: &Client, schedule: &str, json: bool) -> Result<i32, String> {
+    let schedules = client.get("/api/backups/schedules").await?;
+    let list = schedules.as_array().map(|a| a.as_slice()).unwrap_or(&[]);
+    let by_id = list.iter().find(|s| s["id"].as_str() == Some(schedule));
+    let by_name: Vec<&Value> = list.iter()
+        .filter(|s| s["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(schedule)))
+        .collect();
+    let found = match (by_id, by_name.as_slice()) {
+        (Some(s), _) => s,
+        (None, [s]) => *s,
+        (None, []) => return Err(format!("No backup schedule named '{}' (see `wolfstack backup list`)", schedule)),
+        (None, _) => return Err(format!("Several schedules are named '{}' — use the ID instead", schedule)),
+    };
+    let id = found["id"].as_str().unwrap_or_default();
+    let resp = client.post(&format!("/api/backups/schedules/{}/run", urlencoding::encode(id)), Value::Null).await?;
+    if json {
+        print_json(&resp);
+    } else {
+        println!("{}", resp["message"].as_str().unwrap_or("Backup started"));
+    }
+    Ok(0)
+}
+
+async fn issues(client: &Client, json: bool) -> Result<i32, String> {
+    let scan = client.get("/api/issues/scan").await?;
+    let list = scan["issues"].as_array().map(|a| a.as_slice()).unwrap_or(&[]);
+    let critical = list.iter().any(|i| i["severity"].as_str() == Some("critical"));
+    if json {
+        print_json(&scan);
+    } else if list.is_empty() {
+        println!("No issues found on {}.", text(&scan, "hostname"));
+    } else {
+        for i in list {
+            println!("[{}] {} — {}", text(i, "severity").to_uppercase(), text(i, "category"), text(i, "title"));
+            let detail = text(i, "detail");
+            if !detail.is_empty() {
+                println!("    {}", detail);
+            }
+        }
+    }
+    Ok(if critical { 2 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_renders_non_strings() {
+        let v = serde_json::json!({ "a": "x", "b": 3, "c": null, "d": true });
+        assert_eq!(text(&v, "a"), "x");
+        assert_eq!(text(&v, "b"), "3");
+        assert_eq!(text(&v, "c"), "");
+        assert_eq!(text(&v, "missing"), "");
+        assert_eq!(text(&v, "d"), "true");
+    }
+}
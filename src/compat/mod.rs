@@ -509,9 +509,25 @@ pub fn validate_key(raw: &str, ip: Option<&str>) -> Option<ApiKey> {
     Some(r)
 }
 
+/// The `/api/v1` machine API path a key scope should treat `path` as — the
+/// legacy path of the same resource, so a "storage" key covers v1 mounts.
+fn v1_equivalent(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("/api/v1/")?;
+    let (legacy, tail) = [
+        ("containers", "/api/containers"),
+        ("vms", "/api/vms"),
+        ("mounts", "/api/storage/mounts"),
+        ("ip-mappings", "/api/networking/ip-mappings"),
+        ("nodes", "/api/nodes"),
+    ].iter().find_map(|(v1, legacy)| rest.strip_prefix(v1).map(|tail| (*legacy, tail)))?;
+    Some(format!("{}{}", legacy, tail))
+}
+
 pub fn scope_allows(key: &ApiKey, method: &str, path: &str) -> bool {
     if key.scopes.contains(&"*".to_string()) { return true; }
     if method == "GET" && key.scopes.contains(&"read".to_string()) { return true; }
+    let v1 = v1_equivalent(path);
+    let path = v1.as_deref().unwrap_or(path);
     for s in &key.scopes {
         let ok = match s.as_str() {
             "containers" => path.starts_with("/api/containers") || path.starts_with("/api/docker") || path.starts_with("/api/lxc"),
@@ -644,6 +660,9 @@ mod tests {
         assert!(scope_allows(&k, "GET", "/api/vms"));
         assert!(scope_allows(&k, "POST", "/api/containers/create"));
         assert!(!scope_allows(&k, "POST", "/api/vms/create"));
+        assert!(scope_allows(&k, "PUT", "/api/v1/containers/lxc/db/state"));
+        assert!(!scope_allows(&k, "PUT", "/api/v1/vms/win11/state"));
+        assert!(!scope_allows(&k, "PUT", "/api/v1/mounts/nas"));
     }
 
     #[test]
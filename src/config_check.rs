@@ -0,0 +1,223 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Syntax checks for config files before WolfStack writes them.
+//!
+//! A typo saved through the dashboard shouldn't be what takes a service
+//! down at its next restart. [`check`] picks a checker from the file's path
+//! and returns the parser's own error for the save to send back:
+//!
+//! - `.toml`, `.yaml`/`.yml` and `.json` (every Wolf component config) are
+//!   parsed in-process;
+//! - systemd units go through `systemd-analyze verify`, sudoers files
+//!   through `visudo -c`, `sshd_config` through `sshd -t`, all on a copy in
+//!   a scratch directory;
+//! - files under `/etc/nginx` are tested in place with `nginx -t` — nginx
+//!   only checks its whole include tree — and the original is put straight
+//!   back whatever the result;
+//! - system crontabs (`/etc/crontab`, `/etc/cron.d/*`) and LXC configs are
+//!   checked line by line.
+//!
+//! When a checker's tool isn't installed the file is let through — this
+//! catches mistakes, it isn't a gate on hosts that can't check.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Check `content` as the new contents of `path`. `Err` carries the
+/// checker's message, ready to show the user.
+pub fn check(path: &str, content: &str) -> Result<(), String> {
+    let name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = Path::new(path).extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "toml" => return toml::from_str::<toml::Value>(content).map(|_| ()).map_err(|e| format!("Invalid TOML: {}", e)),
+        "yaml" | "yml" => return serde_yaml::from_str::<serde_yaml::Value>(content).map(|_| ()).map_err(|e| format!("Invalid YAML: {}", e)),
+        "json" => return serde_json::from_str::<serde_json::Value>(content).map(|_| ()).map_err(|e| format!("Invalid JSON: {}", e)),
+        "service" | "socket" | "timer" | "mount" | "automount" | "path" | "target" | "slice"
+            if path.contains("/systemd/") => return with_scratch_copy(&name, content, |f| tool("systemd-analyze", &["verify", f])),
+        _ => {}
+    }
+    if path == "/etc/sudoers" || path.starts_with("/etc/sudoers.d/") {
+        return with_scratch_copy(&name, content, |f| tool("visudo", &["-c", "-f", f]));
+    }
+    if path == "/etc/ssh/sshd_config" || path.starts_with("/etc/ssh/sshd_config.d/") {
+        return with_scratch_copy(&name, content, |f| tool("sshd", &["-t", "-f", f]));
+    }
+    if path.starts_with("/etc/nginx/") {
+        return nginx_in_place(path, content);
+    }
+    if path == "/etc/crontab" || path.starts_with("/etc/cron.d/") {
+        return check_crontab(content, true);
+    }
+    if path.starts_with("/var/lib/lxc/") && name == "config" {
+        return check_lxc_config(content);
+    }
+    Ok(())
+}
+
+/// Run a checker. A missing tool passes; a failing one returns what it
+/// printed.
+fn tool(cmd: &str, args: &[&str]) -> Result<(), String> {
+    match Command::new(cmd).args(args).output() {
+        Ok(out) if out.status.success() => Ok(()),
+        Ok(out) => {
+            let mut msg = String::from_utf8_lossy(&out.stderr).trim().to_string();
+            if msg.is_empty() { msg = String::from_utf8_lossy(&out.stdout).trim().to_string(); }
+            Err(format!("{} rejected the file: {}", cmd, msg))
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+/// Write `content` to a scratch file called `name` (unit checkers care
+/// about the suffix) and check that.
+fn with_scratch_copy(name: &str, content: &str, f: impl FnOnce(&str) -> Result<(), String>) -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("wolfstack-check-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let file = dir.join(name);
+    let result = std::fs::write(&file, content)
+        .map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+        .and_then(|_| f(&file.to_string_lossy()));
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn nginx_in_place(path: &str, content: &str) -> Result<(), String> {
+    if Command::new("nginx").arg("-v").output().is_err() { return Ok(()); }
+    let original = std::fs::read(path).ok();
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    let result = tool("nginx", &["-t"]);
+    let _ = match original {
+        Some(bytes) => std::fs::write(path, bytes),
+        None => std::fs::remove_file(path),
+    };
+    result
+}
+
+/// One crontab schedule field against its range. Accepts `*`, numbers,
+/// `a-b` ranges, `/n` steps, comma lists and (where `names` is given) the
+/// three-letter month/day names.
+fn cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<(), String> {
+    let value = |v: &str| -> Result<u32, String> {
+        if let Some(i) = names.iter().position(|n| n.eq_ignore_ascii_case(v)) {
+            return Ok(min + i as u32);
+        }
+        v.parse::<u32>().ok().filter(|n| (min..=max).contains(n))
+            .ok_or_else(|| format!("'{}' is out of range {}-{}", v, min, max))
+    };
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (r, Some(s)),
+            None => (part, None),
+        };
+        if let Some(s) = step && s.parse::<u32>().map_or(true, |n| n == 0) {
+            return Err(format!("bad step '{}'", s));
+        }
+        if range == "*" { continue; }
+        match range.split_once('-') {
+            Some((a, b)) => {
+                if value(a)? > value(b)? { return Err(format!("range '{}' runs backwards", range)); }
+            }
+            None => { value(range)?; }
+        }
+    }
+    Ok(())
+}
+
+/// One crontab line. System crontabs carry a user column before the command.
+pub fn check_cron_line(line: &str, system: bool) -> Result<(), String> {
+    if line.contains('\n') { return Err("an entry must be a single line".to_string()); }
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') { return Ok(()); }
+    // Environment assignments: NAME=value.
+    if let Some((k, _)) = line.split_once('=')
+        && !k.trim().is_empty()
+        && k.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Ok(());
+    }
+    let need = if system { 2 } else { 1 };
+    if let Some(rest) = line.strip_prefix('@') {
+        let mut parts = rest.split_whitespace();
+        let keyword = parts.next().unwrap_or("");
+        if !["reboot", "yearly", "annually", "monthly", "weekly", "daily", "midnight", "hourly"].contains(&keyword) {
+            return Err(format!("unknown schedule '@{}'", keyword));
+        }
+        return if parts.count() >= need { Ok(()) } else { Err("missing command".to_string()) };
+    }
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 5 + need {
+        return Err(format!("expected 5 schedule fields{} and a command", if system { ", a user" } else { "" }));
+    }
+    const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    const DAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+    let checks = [("minute", 0, 59, &[][..]), ("hour", 0, 23, &[]), ("day of month", 1, 31, &[]), ("month", 1, 12, MONTHS), ("day of week", 0, 7, DAYS)];
+    for (field, (what, min, max, names)) in fields.iter().zip(checks) {
+        cron_field(field, min, max, names).map_err(|e| format!("{}: {}", what, e))?;
+    }
+    Ok(())
+}
+
+fn check_crontab(content: &str, system: bool) -> Result<(), String> {
+    for (i, line) in content.lines().enumerate() {
+        check_cron_line(line, system).map_err(|e| format!("Line {}: {}", i + 1, e))?;
+    }
+    // cron ignores a last line without a newline.
+    if !content.is_empty() && !content.ends_with('\n') {
+        return Err("A crontab must end with a newline".to_string());
+    }
+    Ok(())
+}
+
+/// LXC configs are `lxc.key = value` lines; anything else stops the
+/// container from starting.
+pub fn check_lxc_config(content: &str) -> Result<(), String> {
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let Some((key, _)) = line.split_once('=') else {
+            return Err(format!("Line {}: expected 'key = value'", i + 1));
+        };
+        if !key.trim().starts_with("lxc.") {
+            return Err(format!("Line {}: '{}' is not an LXC setting (keys start with lxc.)", i + 1, key.trim()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_formats() {
+        assert!(check("/etc/wolfnet/config.toml", "[network]\nport = 9600\n").is_ok());
+        assert!(check("/etc/wolfnet/config.toml", "[network\nport = 9600\n").unwrap_err().starts_with("Invalid TOML"));
+        assert!(check("/opt/app/compose.yml", "a: [1, 2").is_err());
+        assert!(check("/opt/app/settings.json", "{\"a\": 1}").is_ok());
+        assert!(check("/etc/motd", "anything at all").is_ok());
+    }
+
+    #[test]
+    fn cron_lines() {
+        assert!(check_cron_line("*/5 * * * * /usr/bin/backup", false).is_ok());
+        assert!(check_cron_line("0 3 * jan-mar mon-fri root /opt/job", true).is_ok());
+        assert!(check_cron_line("MAILTO=ops@example.com", false).is_ok());
+        assert!(check_cron_line("@daily root /opt/job", true).is_ok());
+        assert!(check_cron_line("60 * * * * /x", false).unwrap_err().starts_with("minute"));
+        assert!(check_cron_line("* * * * /x", false).is_err());
+        assert!(check_cron_line("0 3 * * * /opt/job", true).is_err());
+        assert!(check_cron_line("*/0 * * * * /x", false).is_err());
+        assert!(check_cron_line("@sometimes /x", false).is_err());
+        assert!(check_cron_line("0 3 * * * /x\n* * * * * /evil", false).is_err());
+        assert!(check_crontab("0 3 * * * root /x", true).is_err());
+    }
+
+    #[test]
+    fn lxc_config_lines() {
+        assert!(check_lxc_config("# comment\nlxc.uts.name = web\nlxc.net.0.type = veth\n").is_ok());
+        assert!(check_lxc_config("lxc.uts.name = web\nnet0 = veth\n").is_err());
+        assert!(check_lxc_config("lxc.uts.name web\n").is_err());
+    }
+}
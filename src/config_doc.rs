@@ -0,0 +1,511 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Declarative cluster config document — the `format=yaml` side of
+//! `GET/POST /api/config/export|import`.
+//!
+//! Unlike the JSON bundle (a snapshot of the raw config files), this is meant
+//! to live in git and be re-applied: applying the same document twice changes
+//! nothing the second time. The rules:
+//!
+//! * A section left out of the document is left alone on the node.
+//! * List items (mounts, backup schedules, IP mappings) match existing ones by
+//!   `id`; a listed item is created or updated, an unlisted one is kept —
+//!   unless the document sets `prune: true`, which deletes it. Cluster nodes
+//!   are only ever added, never pruned: membership is too costly to lose to a
+//!   stale file.
+//! * Fields merge into what's already there, so a field left out keeps its
+//!   current value. Secrets are never exported, which is what keeps a document
+//!   in a repository credential-free while still round-tripping cleanly.
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub const API_VERSION: &str = "wolfstack/v1";
+pub const KIND: &str = "ClusterConfig";
+
+/// Credential fields dropped from every exported item, wherever they appear.
+const ALERT_SECRETS: &[&str] = &[
+    "discord_webhook", "slack_webhook", "telegram_bot_token", "discord_bot_token",
+    "twilio_auth_token", "ntfy_topic", "ntfy_token",
+];
+
+/// Live state that isn't configuration — never exported, never compared.
+pub(crate) const MOUNT_RUNTIME: &[&str] = &["status", "error_message", "usage", "health", "created_at"];
+const SCHEDULE_RUNTIME: &[&str] = &["last_run", "created_at"];
+/// The node fields a document declares; everything else on a Node record is
+/// discovered at runtime (metrics, counts, online) or secret (PVE tokens).
+const NODE_FIELDS: &[&str] = &[
+    "id", "self_id", "hostname", "address", "port", "node_type", "tls",
+    "pve_node_name", "pve_cluster_name", "cluster_name", "site", "display_name", "labels",
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigDocument {
+    #[serde(rename = "apiVersion", default)]
+    pub api_version: String,
+    #[serde(default)]
+    pub kind: String,
+    /// exported_from / exported_at / version — informational only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Delete mounts, schedules and IP mappings the document doesn't list.
+    #[serde(default)]
+    pub prune: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mounts: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_schedules: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_mappings: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerting: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Change {
+    pub section: String,
+    pub id: String,
+    /// "create", "update", "delete" or "unchanged".
+    pub action: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ApplyReport {
+    pub dry_run: bool,
+    pub changes: Vec<Change>,
+    pub errors: Vec<String>,
+}
+
+impl ApplyReport {
+    pub fn push(&mut self, section: &str, id: &str, action: &str) {
+        self.changes.push(Change { section: section.into(), id: id.into(), action: action.into() });
+    }
+
+    /// Changes that actually did (or, dry-run, would) something.
+    pub fn changed(&self) -> usize {
+        self.changes.iter().filter(|c| c.action != "unchanged").count()
+    }
+}
+
+fn secret_keys() -> Vec<&'static str> {
+    let mut keys: Vec<&str> = crate::storage::STORAGE_SECRETS.fields.to_vec();
+    keys.extend_from_slice(crate::backup::BACKUP_SECRETS.fields);
+    keys.extend_from_slice(ALERT_SECRETS);
+    keys
+}
+
+/// Remove `keys` from `v` at every depth.
+fn strip(v: &mut Value, keys: &[&str]) {
+    match v {
+        Value::Object(map) => {
+            map.retain(|k, _| !keys.contains(&k.as_str()));
+            for child in map.values_mut() {
+                strip(child, keys);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|i| strip(i, keys)),
+        _ => {}
+    }
+}
+
+/// Deep-merge `doc` over `base`: objects merge key by key, anything else
+/// (including arrays) is replaced. Keys the document leaves out keep their
+/// current value — that's how omitted secrets survive an apply.
+pub(crate) fn overlay(base: &Value, doc: &Value) -> Value {
+    match (base, doc) {
+        (Value::Object(b), Value::Object(d)) => {
+            let mut out = b.clone();
+            for (k, v) in d {
+                let merged = match b.get(k) {
+                    Some(existing) => overlay(existing, v),
+                    None => v.clone(),
+                };
+                out.insert(k.clone(), merged);
+            }
+            Value::Object(out)
+        }
+        _ => doc.clone(),
+    }
+}
+
+fn export_items<T: Serialize>(items: &[T], runtime: &[&str], secrets: &[&str]) -> Vec<Value> {
+    items.iter()
+        .filter_map(|i| serde_json::to_value(i).ok())
+        .map(|mut v| {
+            strip(&mut v, secrets);
+            if let Some(map) = v.as_object_mut() {
+                map.retain(|k, _| !runtime.contains(&k.as_str()));
+            }
+            v
+        })
+        .collect()
+}
+
+/// One item as a document carries it: no secrets, no `runtime` fields.
+/// Also the representation the `/api/v1` machine API serves.
+pub(crate) fn spec<T: Serialize>(item: &T, runtime: &[&str]) -> Value {
+    export_items(std::slice::from_ref(item), runtime, &secret_keys()).pop().unwrap_or(Value::Null)
+}
+
+/// A cluster node as a document declares it (see `NODE_FIELDS`).
+pub(crate) fn node_spec(node: &crate::agent::Node) -> Value {
+    let mut v = serde_json::to_value(node).unwrap_or(Value::Null);
+    if let Some(map) = v.as_object_mut() {
+        map.retain(|k, val| NODE_FIELDS.contains(&k.as_str()) && !val.is_null());
+    }
+    v
+}
+
+/// Build the document for this node. `nodes` is the cluster membership as
+/// this node sees it.
+pub fn export(nodes: &[crate::agent::Node]) -> ConfigDocument {
+    let secrets = secret_keys();
+    let nodes = nodes.iter().map(node_spec).collect();
+    let mut alerting = serde_json::to_value(crate::alerting::AlertConfig::load()).unwrap_or(Value::Null);
+    strip(&mut alerting, &secrets);
+
+    let mut metadata = BTreeMap::new();
+    metadata.insert("exported_from".into(), hostname::get()
+        .map(|h| h.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "unknown".into()));
+    metadata.insert("exported_at".into(), chrono::Utc::now().to_rfc3339());
+    metadata.insert("version".into(), env!("CARGO_PKG_VERSION").to_string());
+
+    ConfigDocument {
+        api_version: API_VERSION.into(),
+        kind: KIND.into(),
+        metadata,
+        prune: false,
+        nodes: Some(nodes),
+        mounts: Some(export_items(&crate::storage::list_mounts(), MOUNT_RUNTIME, &secrets)),
+        backup_schedules: Some(export_items(&crate::backup::list_schedules(), SCHEDULE_RUNTIME, &secrets)),
+        ip_mappings: Some(export_items(&crate::networking::list_ip_mappings(), &[], &secrets)),
+        alerting: Some(alerting),
+    }
+}
+
+pub fn to_yaml(doc: &ConfigDocument) -> Result<String, String> {
+    let body = serde_yaml::to_string(doc).map_err(|e| format!("serialize: {}", e))?;
+    Ok(format!("# WolfStack declarative cluster config — re-apply with POST /api/config/import?format=yaml\n\
+                # Secrets are not exported; fields left out keep their current value on apply.\n{}", body))
+}
+
+/// Parse a YAML (or JSON — YAML is a superset) document and check its header.
+pub fn parse(text: &str) -> Result<ConfigDocument, String> {
+    let doc: ConfigDocument = serde_yaml::from_str(text).map_err(|e| format!("invalid document: {}", e))?;
+    if doc.api_version != API_VERSION || doc.kind != KIND {
+        return Err(format!("expected apiVersion: {} and kind: {} (got '{}' / '{}')",
+            API_VERSION, KIND, doc.api_version, doc.kind));
+    }
+    Ok(doc)
+}
+
+/// Node records from the document, filled out to deserialize as `Node` (the
+/// runtime fields a document doesn't carry start empty / offline).
+pub fn node_records(doc: &ConfigDocument) -> Option<Value> {
+    let nodes = doc.nodes.as_ref()?;
+    let filled = nodes.iter().map(|n| {
+        let defaults = serde_json::json!({
+            "last_seen": 0, "components": [], "online": false, "is_self": false,
+        });
+        overlay(&defaults, n)
+    }).collect();
+    Some(Value::Array(filled))
+}
+
+enum Step<T> {
+    Create(T),
+    Update(T),
+    Unchanged(String),
+    Delete(String),
+}
+
+/// Work out what applying `docs` to `existing` would do. `find` locates a
+/// document item among the existing ones. Every created / updated item is
+/// deserialized here, so a bad document fails before anything is written.
+fn plan<T: Serialize + DeserializeOwned>(
+    section: &str,
+    existing: &[T],
+    id_of: impl Fn(&T) -> String,
+    docs: &[Value],
+    find: impl Fn(&Value, &[T]) -> Option<usize>,
+    prune: bool,
+) -> Result<Vec<Step<T>>, String> {
+    let mut steps = Vec::new();
+    let mut seen = vec![false; existing.len()];
+    for (n, doc) in docs.iter().enumerate() {
+        if !doc.is_object() {
+            return Err(format!("{}[{}]: expected a mapping", section, n));
+        }
+        match find(doc, existing) {
+            Some(i) => {
+                seen[i] = true;
+                let current = serde_json::to_value(&existing[i]).map_err(|e| e.to_string())?;
+                let merged = overlay(&current, doc);
+                if merged == current {
+                    steps.push(Step::Unchanged(id_of(&existing[i])));
+                } else {
+                    let item = serde_json::from_value(merged)
+                        .map_err(|e| format!("{} '{}': {}", section, id_of(&existing[i]), e))?;
+                    steps.push(Step::Update(item));
+                }
+            }
+            None => {
+                let item = serde_json::from_value(doc.clone())
+                    .map_err(|e| format!("{}[{}]: {}", section, n, e))?;
+                steps.push(Step::Create(item));
+            }
+        }
+    }
+    if prune {
+        for (i, item) in existing.iter().enumerate() {
+            if !seen[i] {
+                steps.push(Step::Delete(id_of(item)));
+            }
+        }
+    }
+    Ok(steps)
+}
+
+fn find_by_id<T: Serialize>(doc: &Value, existing: &[T], id_of: impl Fn(&T) -> String) -> Option<usize> {
+    let id = doc.get("id")?.as_str()?;
+    existing.iter().position(|e| id_of(e) == id)
+}
+
+fn record<T>(report: &mut ApplyReport, section: &str, step: &Step<T>, id: &str) {
+    let action = match step {
+        Step::Create(_) => "create",
+        Step::Update(_) => "update",
+        Step::Unchanged(_) => "unchanged",
+        Step::Delete(_) => "delete",
+    };
+    report.push(section, id, action);
+}
+
+fn apply_mounts(docs: &[Value], prune: bool, report: &mut ApplyReport) -> Result<(), String> {
+    use crate::storage::{self, StorageMount};
+    let mut existing = storage::load_config().mounts;
+    // A new mount without created_at would fail to deserialize; stamp it.
+    let now = chrono::Utc::now().to_rfc3339();
+    let docs: Vec<Value> = docs.iter().map(|d| {
+        if find_by_id(d, &existing, |m: &StorageMount| m.id.clone()).is_none() && d.get("created_at").is_none() {
+            overlay(&serde_json::json!({ "created_at": now }), d)
+        } else {
+            d.clone()
+        }
+    }).collect();
+    let steps = plan("mounts", &existing, |m: &StorageMount| m.id.clone(), &docs,
+        |d, ex| find_by_id(d, ex, |m: &StorageMount| m.id.clone()), prune)?;
+    let mut dirty = false;
+    for step in steps {
+        let id = match &step {
+            Step::Create(m) | Step::Update(m) => m.id.clone(),
+            Step::Unchanged(id) | Step::Delete(id) => id.clone(),
+        };
+        record(report, "mounts", &step, &id);
+        if report.dry_run { continue; }
+        match step {
+            Step::Create(m) => if let Err(e) = storage::create_mount(m, false) {
+                report.errors.push(format!("mount '{}': {}", id, e));
+            },
+            Step::Update(m) => if let Some(slot) = existing.iter_mut().find(|x| x.id == m.id) {
+                *slot = m;
+                dirty = true;
+            },
+            Step::Delete(_) => if let Err(e) = storage::remove_mount(&id) {
+                report.errors.push(format!("mount '{}': {}", id, e));
+            },
+            Step::Unchanged(_) => {}
+        }
+    }
+    if dirty {
+        // Re-read so creates / removals above aren't clobbered, then write the
+        // updated definitions over it.
+        let mut config = storage::load_config();
+        for slot in config.mounts.iter_mut() {
+            if let Some(m) = existing.iter().find(|m| m.id == slot.id) {
+                *slot = m.clone();
+            }
+        }
+        storage::save_config(&config)?;
+    }
+    Ok(())
+}
+
+fn apply_schedules(docs: &[Value], prune: bool, report: &mut ApplyReport) -> Result<(), String> {
+    use crate::backup::{self, BackupSchedule};
+    let existing = backup::list_schedules();
+    let steps = plan("backup_schedules", &existing, |s: &BackupSchedule| s.id.clone(), docs,
+        |d, ex| find_by_id(d, ex, |s: &BackupSchedule| s.id.clone()), prune)?;
+    for step in steps {
+        let id = match &step {
+            Step::Create(s) | Step::Update(s) => s.id.clone(),
+            Step::Unchanged(id) | Step::Delete(id) => id.clone(),
+        };
+        record(report, "backup_schedules", &step, &id);
+        if report.dry_run { continue; }
+        let result = match step {
+            Step::Create(s) | Step::Update(s) => backup::save_schedule(s).map(|_| ()),
+            Step::Delete(_) => backup::delete_schedule(&id).map(|_| ()),
+            Step::Unchanged(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("schedule '{}': {}", id, e));
+        }
+    }
+    Ok(())
+}
+
+/// IP mappings match by `id`, falling back to public IP + ports + protocol:
+/// a mapping created from a document gets a fresh id on this node, and the
+/// fallback is what makes re-applying that document a no-op.
+fn find_mapping(doc: &Value, existing: &[crate::networking::IpMapping]) -> Option<usize> {
+    find_by_id(doc, existing, |m| m.id.clone()).or_else(|| {
+        let public_ip = doc.get("public_ip")?.as_str()?;
+        let ports = doc.get("ports").and_then(|p| p.as_str());
+        let protocol = doc.get("protocol").and_then(|p| p.as_str()).unwrap_or("all");
+        existing.iter().position(|m| m.public_ip == public_ip
+            && m.ports.as_deref() == ports && m.protocol == protocol)
+    })
+}
+
+fn apply_ip_mappings(docs: &[Value], prune: bool, report: &mut ApplyReport) -> Result<(), String> {
+    use crate::networking::{self, IpMapping};
+    let existing = networking::list_ip_mappings();
+    // New mappings are created through add_ip_mapping, which assigns its own
+    // id and enables them; fill those in so the item deserializes.
+    let docs: Vec<Value> = docs.iter().map(|d| {
+        if find_mapping(d, &existing).is_none() {
+            overlay(&serde_json::json!({ "id": "", "enabled": true }), d)
+        } else {
+            d.clone()
+        }
+    }).collect();
+    let steps = plan("ip_mappings", &existing, |m: &IpMapping| m.id.clone(), &docs, find_mapping, prune)?;
+    for step in steps {
+        let id = match &step {
+            Step::Create(m) => format!("{} → {}", m.public_ip, m.wolfnet_ip),
+            Step::Update(m) => m.id.clone(),
+            Step::Unchanged(id) | Step::Delete(id) => id.clone(),
+        };
+        record(report, "ip_mappings", &step, &id);
+        if report.dry_run { continue; }
+        let result = match step {
+            Step::Create(m) => networking::add_ip_mapping(&m.public_ip, &m.wolfnet_ip,
+                m.ports.as_deref(), m.dest_ports.as_deref(), &m.protocol, &m.label).map(|_| ()),
+            Step::Update(m) => networking::update_ip_mapping(&m.id, &m.public_ip, &m.wolfnet_ip,
+                m.ports.as_deref(), m.dest_ports.as_deref(), &m.protocol, &m.label).map(|_| ()),
+            Step::Delete(_) => networking::remove_ip_mapping(&id).map(|_| ()),
+            Step::Unchanged(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("IP mapping '{}': {}", id, e));
+        }
+    }
+    Ok(())
+}
+
+fn apply_alerting(doc: &Value, report: &mut ApplyReport) -> Result<(), String> {
+    let current = serde_json::to_value(crate::alerting::AlertConfig::load()).map_err(|e| e.to_string())?;
+    let merged = overlay(&current, doc);
+    if merged == current {
+        report.push("alerting", "alerting", "unchanged");
+        return Ok(());
+    }
+    let config: crate::alerting::AlertConfig = serde_json::from_value(merged)
+        .map_err(|e| format!("alerting: {}", e))?;
+    report.push("alerting", "alerting", "update");
+    if !report.dry_run {
+        config.save()?;
+    }
+    Ok(())
+}
+
+/// Apply everything but `nodes` (the API layer owns cluster membership).
+/// A section that fails to plan is reported and skipped; the rest still apply.
+pub fn apply(doc: &ConfigDocument, report: &mut ApplyReport) {
+    let sections: [(&str, Result<(), String>); 4] = [
+        ("mounts", doc.mounts.as_ref().map_or(Ok(()), |d| apply_mounts(d, doc.prune, report))),
+        ("backup_schedules", doc.backup_schedules.as_ref().map_or(Ok(()), |d| apply_schedules(d, doc.prune, report))),
+        ("ip_mappings", doc.ip_mappings.as_ref().map_or(Ok(()), |d| apply_ip_mappings(d, doc.prune, report))),
+        ("alerting", doc.alerting.as_ref().map_or(Ok(()), |d| apply_alerting(d, report))),
+    ];
+    for (section, result) in sections {
+        if let Err(e) = result {
+            report.errors.push(format!("{}: {}", section, e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Item { id: String, name: String, #[serde(default)] secret_key: String, nested: BTreeMap<String, String> }
+
+    fn items() -> Vec<Item> {
+        vec![
+            Item { id: "a".into(), name: "one".into(), secret_key: "s3cr3t".into(),
+                nested: [("x".to_string(), "1".to_string())].into() },
+            Item { id: "b".into(), name: "two".into(), secret_key: String::new(), nested: BTreeMap::new() },
+        ]
+    }
+
+    fn actions<T>(steps: &[Step<T>]) -> Vec<&'static str> {
+        steps.iter().map(|s| match s {
+            Step::Create(_) => "create", Step::Update(_) => "update",
+            Step::Unchanged(_) => "unchanged", Step::Delete(_) => "delete",
+        }).collect()
+    }
+
+    #[test]
+    fn export_then_reapply_is_a_no_op() {
+        let existing = items();
+        let exported = export_items(&existing, &[], &["secret_key"]);
+        assert!(exported.iter().all(|v| v.get("secret_key").is_none()), "secrets never leave the node");
+        let steps = plan("t", &existing, |i: &Item| i.id.clone(), &exported,
+            |d, ex| find_by_id(d, ex, |i: &Item| i.id.clone()), true).unwrap();
+        assert_eq!(actions(&steps), ["unchanged", "unchanged"]);
+    }
+
+    #[test]
+    fn plans_creates_updates_and_prunes() {
+        let existing = items();
+        let docs = vec![
+            serde_json::json!({ "id": "a", "nested": { "y": "2" } }),
+            serde_json::json!({ "id": "c", "name": "three", "nested": {} }),
+        ];
+        let steps = plan("t", &existing, |i: &Item| i.id.clone(), &docs,
+            |d, ex| find_by_id(d, ex, |i: &Item| i.id.clone()), false).unwrap();
+        assert_eq!(actions(&steps), ["update", "create"]);
+        let Step::Update(a) = &steps[0] else { unreachable!() };
+        assert_eq!((a.name.as_str(), a.secret_key.as_str()), ("one", "s3cr3t"), "omitted fields keep their value");
+        assert_eq!(a.nested.len(), 2, "nested maps merge");
+
+        let steps = plan("t", &existing, |i: &Item| i.id.clone(), &docs,
+            |d, ex| find_by_id(d, ex, |i: &Item| i.id.clone()), true).unwrap();
+        assert_eq!(actions(&steps), ["update", "create", "delete"]);
+
+        let bad = vec![serde_json::json!({ "id": "d" })];
+        assert!(plan("t", &existing, |i: &Item| i.id.clone(), &bad,
+            |d, ex| find_by_id(d, ex, |i: &Item| i.id.clone()), false).is_err(), "a new item must be complete");
+    }
+
+    #[test]
+    fn parse_checks_the_header_and_accepts_json() {
+        let yaml = "apiVersion: wolfstack/v1\nkind: ClusterConfig\nprune: true\nmounts:\n  - id: nas\n    name: NAS\n";
+        let doc = parse(yaml).unwrap();
+        assert!(doc.prune);
+        assert_eq!(doc.mounts.unwrap()[0]["name"], "NAS");
+        assert!(doc.backup_schedules.is_none(), "absent sections stay untouched");
+        assert!(parse(r#"{"apiVersion":"wolfstack/v1","kind":"ClusterConfig"}"#).is_ok());
+        assert!(parse("kind: Something\n").is_err());
+    }
+}
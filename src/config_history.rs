@@ -0,0 +1,315 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Version history for config files WolfStack edits.
+//!
+//! Every write made through [`save`] keeps a copy of the new contents as a
+//! revision under `paths.config_history_dir`, one directory per file with
+//! an `index.json` listing who changed it, when and why. Before writing,
+//! the file on disk is compared with the latest revision: if someone edited
+//! it by hand in the meantime, that version is kept too ("changed outside
+//! WolfStack"), and the very first save keeps the original. So any revision
+//! can be diffed against another or against the live file, and rolled back
+//! to. Plain timestamped copies rather than git — hosts don't all have git,
+//! and a config is small. The last 50 revisions of each file are kept.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+const KEEP_REVISIONS: usize = 50;
+/// Lines of unchanged context around each diff hunk.
+const CONTEXT_LINES: usize = 3;
+/// Upper bound on the LCS table (changed lines × changed lines) — beyond it
+/// the files are too different for a line diff to be useful anyway.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    /// Unix milliseconds, unique per file.
+    pub id: u64,
+    /// Unix seconds.
+    pub ts: i64,
+    /// Who saved it; empty for versions found on disk.
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub note: String,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FileHistory {
+    path: String,
+    /// Oldest first.
+    #[serde(default)]
+    revisions: Vec<Revision>,
+}
+
+/// A file with history, for the overview list.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackedFile {
+    pub path: String,
+    pub revisions: usize,
+    pub last_ts: i64,
+    pub last_user: String,
+}
+
+fn history_dir() -> PathBuf {
+    PathBuf::from(crate::paths::get().config_history_dir)
+}
+
+/// `/etc/wolfnet/config.toml` → `etc%2Fwolfnet%2Fconfig.toml`: one flat,
+/// reversible directory name per file. A leading dot is escaped too, so no
+/// path can come out as `.` or `..`.
+fn key(path: &str) -> String {
+    let flat = path.trim_start_matches('/').replace('%', "%25").replace('/', "%2F");
+    match flat.strip_prefix('.') {
+        Some(rest) => format!("%2E{}", rest),
+        None => flat,
+    }
+}
+
+fn file_dir(base: &Path, path: &str) -> PathBuf {
+    base.join(key(path))
+}
+
+fn load(base: &Path, path: &str) -> FileHistory {
+    std::fs::read_to_string(file_dir(base, path).join("index.json")).ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| FileHistory { path: path.to_string(), revisions: Vec::new() })
+}
+
+fn read_revision(base: &Path, path: &str, id: u64) -> Option<String> {
+    std::fs::read_to_string(file_dir(base, path).join(format!("{}.txt", id))).ok()
+}
+
+/// Keep `content` as a new revision of `path`. Revisions hold configs with
+/// passwords in them, so they're written 0600 like the rest of our state.
+fn push(base: &Path, hist: &mut FileHistory, content: &str, user: &str, note: &str) -> std::io::Result<()> {
+    let dir = file_dir(base, &hist.path);
+    std::fs::create_dir_all(&dir)?;
+    let now = chrono::Utc::now();
+    let last = hist.revisions.last().map(|r| r.id).unwrap_or(0);
+    let id = (now.timestamp_millis().max(0) as u64).max(last + 1);
+    crate::paths::write_secure(&dir.join(format!("{}.txt", id)).to_string_lossy(), content)?;
+    hist.revisions.push(Revision {
+        id,
+        ts: now.timestamp(),
+        user: user.to_string(),
+        note: note.to_string(),
+        bytes: content.len(),
+    });
+    while hist.revisions.len() > KEEP_REVISIONS {
+        let old = hist.revisions.remove(0);
+        let _ = std::fs::remove_file(dir.join(format!("{}.txt", old.id)));
+    }
+    let json = serde_json::to_string_pretty(&*hist).map_err(std::io::Error::other)?;
+    crate::paths::write_secure_atomic(&dir.join("index.json").to_string_lossy(), json)
+}
+
+fn save_in(base: &Path, path: &str, content: &str, user: &str, note: &str) -> std::io::Result<()> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut hist = load(base, path);
+    let latest = hist.revisions.last().and_then(|r| read_revision(base, path, r.id));
+    if let Ok(current) = std::fs::read_to_string(path)
+        && latest.as_deref() != Some(current.as_str())
+    {
+        let why = if hist.revisions.is_empty() { "Original, before the first WolfStack edit" } else { "Changed outside WolfStack" };
+        if let Err(e) = push(base, &mut hist, &current, "", why) {
+            warn!("config history: could not keep the previous {}: {}", path, e);
+        }
+    }
+    std::fs::write(path, content)?;
+    let latest = hist.revisions.last().and_then(|r| read_revision(base, path, r.id));
+    if latest.as_deref() != Some(content)
+        && let Err(e) = push(base, &mut hist, content, user, note)
+    {
+        warn!("config history: could not record {}: {}", path, e);
+    }
+    Ok(())
+}
+
+/// Write `content` to `path`, keeping the old and new versions in the
+/// file's history. Only the file write can fail the save — a history that
+/// can't be written is logged and skipped.
+pub fn save(path: &str, content: &str, user: &str, note: &str) -> std::io::Result<()> {
+    save_in(&history_dir(), path, content, user, note)
+}
+
+/// Every file with history, most recently changed first.
+pub fn tracked() -> Vec<TrackedFile> {
+    let Ok(entries) = std::fs::read_dir(history_dir()) else { return Vec::new() };
+    let mut files: Vec<TrackedFile> = entries.flatten()
+        .filter_map(|e| std::fs::read_to_string(e.path().join("index.json")).ok())
+        .filter_map(|s| serde_json::from_str::<FileHistory>(&s).ok())
+        .filter_map(|h| {
+            let last = h.revisions.last()?;
+            Some(TrackedFile { path: h.path.clone(), revisions: h.revisions.len(), last_ts: last.ts, last_user: last.user.clone() })
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.last_ts));
+    files
+}
+
+/// A file's revisions, newest first.
+pub fn revisions(path: &str) -> Vec<Revision> {
+    let mut revs = load(&history_dir(), path).revisions;
+    revs.reverse();
+    revs
+}
+
+/// The contents of the newest revision — the last version WolfStack wrote.
+pub fn latest(path: &str) -> Option<String> {
+    let base = history_dir();
+    let id = load(&base, path).revisions.last()?.id;
+    read_revision(&base, path, id)
+}
+
+/// The contents of one revision.
+pub fn content(path: &str, id: u64) -> Result<String, String> {
+    read_revision(&history_dir(), path, id).ok_or_else(|| format!("No revision {} of {}", id, path))
+}
+
+/// Unified diff from revision `from` to revision `to`, or to the file as
+/// it is now when `to` is `None`.
+pub fn diff(path: &str, from: u64, to: Option<u64>) -> Result<String, String> {
+    let old = content(path, from)?;
+    let (new, new_label) = match to {
+        Some(id) => (content(path, id)?, format!("{} (revision {})", path, id)),
+        None => (std::fs::read_to_string(path).unwrap_or_default(), format!("{} (current)", path)),
+    };
+    unified_diff(&old, &new, &format!("{} (revision {})", path, from), &new_label)
+}
+
+/// Put revision `id` back, recorded as a new revision of its own.
+pub fn rollback(path: &str, id: u64, user: &str) -> Result<(), String> {
+    let old = content(path, id)?;
+    save(path, &old, user, &format!("Rolled back to revision {}", id))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Line diff in unified format with [`CONTEXT_LINES`] of context. Empty
+/// when the two sides are identical.
+fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> Result<String, String> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // Most edits touch a few lines; only the differing middle needs the table.
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (am, bm) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if am.len().saturating_mul(bm.len()) > MAX_DIFF_CELLS {
+        return Err("The files differ too much to diff line by line".to_string());
+    }
+    // lcs[i][j] = longest common subsequence of am[i..] and bm[j..].
+    let w = bm.len() + 1;
+    let mut lcs = vec![0u32; (am.len() + 1) * w];
+    for i in (0..am.len()).rev() {
+        for j in (0..bm.len()).rev() {
+            lcs[i * w + j] = if am[i] == bm[j] {
+                lcs[(i + 1) * w + j + 1] + 1
+            } else {
+                lcs[(i + 1) * w + j].max(lcs[i * w + j + 1])
+            };
+        }
+    }
+    let mut ops: Vec<(char, &str)> = a[..prefix].iter().map(|l| (' ', *l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < am.len() || j < bm.len() {
+        if i < am.len() && j < bm.len() && am[i] == bm[j] {
+            ops.push((' ', am[i]));
+            i += 1;
+            j += 1;
+        } else if i < am.len() && (j == bm.len() || lcs[(i + 1) * w + j] >= lcs[i * w + j + 1]) {
+            ops.push(('-', am[i]));
+            i += 1;
+        } else {
+            ops.push(('+', bm[j]));
+            j += 1;
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|l| (' ', *l)));
+
+    let changes: Vec<usize> = ops.iter().enumerate().filter(|(_, (op, _))| *op != ' ').map(|(k, _)| k).collect();
+    if changes.is_empty() { return Ok(String::new()); }
+    // Group changes whose context would overlap into one hunk.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &k in &changes {
+        match hunks.last_mut() {
+            Some((_, end)) if k <= *end + 2 * CONTEXT_LINES + 1 => *end = k,
+            _ => hunks.push((k, k)),
+        }
+    }
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (first, last) in hunks {
+        let start = first.saturating_sub(CONTEXT_LINES);
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+        let old_line = 1 + ops[..start].iter().filter(|(op, _)| *op != '+').count();
+        let new_line = 1 + ops[..start].iter().filter(|(op, _)| *op != '-').count();
+        let old_count = ops[start..end].iter().filter(|(op, _)| *op != '+').count();
+        let new_count = ops[start..end].iter().filter(|(op, _)| *op != '-').count();
+        // An empty side is addressed by the line before it.
+        let at = |line: usize, count: usize| if count == 0 { line - 1 } else { line };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", at(old_line, old_count), old_count, at(new_line, new_count), new_count));
+        for (op, line) in &ops[start..end] {
+            out.push(*op);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_hunks_and_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let d = unified_diff(old, new, "old", "new").unwrap();
+        assert_eq!(d, "--- old\n+++ new\n\
+@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+@@ -10,3 +10,4 @@\n j\n k\n l\n+m\n");
+        assert_eq!(unified_diff(old, old, "old", "new").unwrap(), "");
+        assert_eq!(unified_diff("", "x\n", "o", "n").unwrap(), "--- o\n+++ n\n@@ -0,0 +1,1 @@\n+x\n");
+    }
+
+    #[test]
+    fn save_keeps_original_and_outside_edits() {
+        let base = std::env::temp_dir().join(format!("wscfghist-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&base).unwrap();
+        let file = base.join("app.conf").to_string_lossy().to_string();
+        std::fs::write(&file, "port = 1\n").unwrap();
+
+        save_in(&base, &file, "port = 2\n", "alice", "").unwrap();
+        let hist = load(&base, &file);
+        assert_eq!(hist.revisions.len(), 2);
+        assert_eq!(read_revision(&base, &file, hist.revisions[0].id).as_deref(), Some("port = 1\n"));
+        assert_eq!(hist.revisions[1].user, "alice");
+
+        // Hand edit, then a save: the hand edit is kept in between.
+        std::fs::write(&file, "port = 3\n").unwrap();
+        save_in(&base, &file, "port = 4\n", "bob", "").unwrap();
+        let hist = load(&base, &file);
+        assert_eq!(hist.revisions.len(), 4);
+        assert_eq!(hist.revisions[2].note, "Changed outside WolfStack");
+
+        // Saving identical contents adds nothing.
+        save_in(&base, &file, "port = 4\n", "bob", "").unwrap();
+        assert_eq!(load(&base, &file).revisions.len(), 4);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn keys_are_flat_and_distinct() {
+        assert_eq!(key("/etc/wolfnet/config.toml"), "etc%2Fwolfnet%2Fconfig.toml");
+        assert_ne!(key("/a/b%2Fc"), key("/a/b/c"));
+        assert_eq!(key(".."), "%2E.");
+    }
+}
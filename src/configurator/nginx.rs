@@ -372,6 +372,22 @@ pub fn bootstrap_nginx(target: &ExecTarget) -> Result<String, String> {
     }
 }
 
+/// `location /` block proxying to `proxy_pass`, with the forwarding and
+/// WebSocket upgrade headers every generated site uses
+fn proxy_location(proxy_pass: &str) -> String {
+    let mut block = String::from("    location / {\n");
+    block.push_str(&format!("        proxy_pass {};\n", proxy_pass));
+    block.push_str("        proxy_set_header Host $host;\n");
+    block.push_str("        proxy_set_header X-Real-IP $remote_addr;\n");
+    block.push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
+    block.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
+    block.push_str("        proxy_http_version 1.1;\n");
+    block.push_str("        proxy_set_header Upgrade $http_upgrade;\n");
+    block.push_str("        proxy_set_header Connection \"upgrade\";\n");
+    block.push_str("    }\n");
+    block
+}
+
 /// Generate a basic nginx site config from form parameters
 pub fn generate_site_config(params: &NginxSiteParams) -> String {
     let mut config = String::new();
@@ -407,16 +423,7 @@ pub fn generate_site_config(params: &NginxSiteParams) -> String {
     }
 
     if let Some(ref proxy) = params.proxy_pass {
-        config.push_str("    location / {\n");
-        config.push_str(&format!("        proxy_pass {};\n", proxy));
-        config.push_str("        proxy_set_header Host $host;\n");
-        config.push_str("        proxy_set_header X-Real-IP $remote_addr;\n");
-        config.push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
-        config.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
-        config.push_str("        proxy_http_version 1.1;\n");
-        config.push_str("        proxy_set_header Upgrade $http_upgrade;\n");
-        config.push_str("        proxy_set_header Connection \"upgrade\";\n");
-        config.push_str("    }\n");
+        config.push_str(&proxy_location(proxy));
     } else if let Some(ref root) = params.root {
         config.push_str(&format!("    root {};\n", root));
         config.push_str("    index index.html index.htm;\n");
@@ -441,3 +448,197 @@ pub fn generate_site_config(params: &NginxSiteParams) -> String {
 
     config
 }
+
+// ─── Publish a container behind the proxy ───
+//
+// The manual flow was: look up the container's WolfNet IP, write a vhost,
+// enable it, reload, run certbot, edit the vhost again for TLS, reload
+// again. `publish_site` does all of it and reports each step.
+
+/// Parameters for publishing a container (or any WolfNet address) on a domain
+#[derive(Debug, Deserialize)]
+pub struct PublishSiteParams {
+    pub domain: String,
+    /// `docker` or `lxc` — the WolfNet IP is looked up from `container`.
+    /// Leave empty and set `upstream_ip` to proxy to an address directly.
+    #[serde(default)]
+    pub container_type: String,
+    #[serde(default)]
+    pub container: String,
+    #[serde(default)]
+    pub upstream_ip: String,
+    pub upstream_port: u16,
+    /// Request a Let's Encrypt certificate and switch the vhost to HTTPS
+    #[serde(default)]
+    pub ssl: bool,
+    /// Let's Encrypt contact; empty uses the saved certbot default
+    #[serde(default)]
+    pub email: String,
+}
+
+/// Hostname check for `server_name` and `certbot -d` — letters, digits,
+/// dots and hyphens only, so nothing can break out of the config line.
+fn validate_domain(domain: &str) -> Result<(), String> {
+    let valid = !domain.is_empty()
+        && domain.len() <= 253
+        && domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if valid { Ok(()) } else { Err(format!("'{}' is not a valid domain name", domain)) }
+}
+
+/// Work out the `ip:port` to proxy to.
+fn resolve_upstream(params: &PublishSiteParams) -> Result<String, String> {
+    if params.upstream_port == 0 {
+        return Err("Upstream port is required".to_string());
+    }
+    let ip = match params.container_type.as_str() {
+        "" => params.upstream_ip.trim().to_string(),
+        "docker" => crate::containers::docker_effective_wolfnet_ip(&params.container)
+            .ok_or_else(|| format!("Docker container '{}' has no WolfNet IP", params.container))?,
+        "lxc" => crate::containers::lxc_get_wolfnet_ip(&params.container)
+            .ok_or_else(|| format!("LXC container '{}' has no WolfNet IP", params.container))?,
+        other => return Err(format!("Unsupported container type '{}'", other)),
+    };
+    let ip: std::net::Ipv4Addr = ip.parse().map_err(|_| format!("'{}' is not a valid IPv4 address", ip))?;
+    Ok(format!("{}:{}", ip, params.upstream_port))
+}
+
+/// Vhost for a published site. The ACME location is kept in every server
+/// block — including the HTTP→HTTPS redirect — so webroot renewals keep
+/// working after the site goes HTTPS-only.
+pub fn generate_publish_config(domain: &str, upstream: &str, acme_webroot: &str, ssl: Option<(&str, &str)>) -> String {
+    let acme = format!(
+        "    location /.well-known/acme-challenge/ {{\n        root {};\n        default_type \"text/plain\";\n    }}\n",
+        acme_webroot
+    );
+    let proxy = proxy_location(&format!("http://{}", upstream));
+    let mut config = format!("# Published by WolfStack: {} -> {}\n", domain, upstream);
+
+    match ssl {
+        None => {
+            config.push_str("server {\n    listen 80;\n    listen [::]:80;\n");
+            config.push_str(&format!("    server_name {};\n\n", domain));
+            config.push_str(&acme);
+            config.push('\n');
+            config.push_str(&proxy);
+            config.push_str("}\n");
+        }
+        Some((cert, key)) => {
+            config.push_str("server {\n    listen 443 ssl;\n    listen [::]:443 ssl;\n");
+            config.push_str(&format!("    server_name {};\n\n", domain));
+            config.push_str(&format!("    ssl_certificate {};\n", cert));
+            config.push_str(&format!("    ssl_certificate_key {};\n", key));
+            config.push_str("    ssl_protocols TLSv1.2 TLSv1.3;\n");
+            config.push_str("    ssl_ciphers HIGH:!aNULL:!MD5;\n\n");
+            config.push_str(&acme);
+            config.push('\n');
+            config.push_str(&proxy);
+            config.push_str("}\n\n");
+            config.push_str("server {\n    listen 80;\n    listen [::]:80;\n");
+            config.push_str(&format!("    server_name {};\n\n", domain));
+            config.push_str(&acme);
+            config.push('\n');
+            config.push_str("    location / {\n        return 301 https://$host$request_uri;\n    }\n");
+            config.push_str("}\n");
+        }
+    }
+    config
+}
+
+/// Create, enable and reload a proxy vhost for `domain`, optionally
+/// issuing a certificate and switching it to HTTPS. Returns a log line
+/// per completed step. Certificates are requested with certbot on this
+/// node, so HTTPS is only offered for the host's own proxy.
+pub fn publish_site(target: &ExecTarget, params: &PublishSiteParams) -> Result<Vec<String>, String> {
+    let domain = params.domain.trim().to_ascii_lowercase();
+    validate_domain(&domain)?;
+    if params.ssl && !matches!(target, ExecTarget::Host) {
+        return Err("HTTPS publishing is only supported for the host proxy — certbot runs on the host".to_string());
+    }
+    let upstream = resolve_upstream(params)?;
+    let mut steps = vec![format!("Upstream resolved to {}", upstream)];
+
+    let paths = nginx_paths(target);
+    let name = if paths.is_debian { domain.clone() } else { format!("{}.conf", domain) };
+    let existing = list_sites(target)?;
+    if existing.iter().any(|s| s.name == name) {
+        return Err(format!("A site named {} already exists — edit it instead", name));
+    }
+
+    let certbot_cfg = crate::certbot::CertbotConfig::load();
+    if params.ssl {
+        crate::certbot::ensure_webroot(&certbot_cfg)?;
+    }
+    save_site(target, &name, &generate_publish_config(&domain, &upstream, &certbot_cfg.webroot, None))?;
+    steps.push(format!("Wrote site {}", name));
+    enable_site(target, &name)?;
+    steps.push("Site enabled".to_string());
+    if let Err(e) = reload(target) {
+        // Don't leave a broken vhost behind to fail every later reload.
+        let _ = delete_site(target, &name);
+        return Err(format!("{}\nThe new site was removed.", e));
+    }
+    steps.push(format!("Proxy reloaded — http://{} is live", domain));
+
+    if !params.ssl {
+        return Ok(steps);
+    }
+
+    crate::certbot::issue(&[domain.clone()], &params.email, "webroot", None, false)
+        .map_err(|e| format!("{}\n\nhttp://{} is live, but the certificate request failed:\n{}", steps.join("\n"), domain, e))?;
+    steps.push(format!("Certificate issued for {}", domain));
+
+    let cert = format!("/etc/letsencrypt/live/{}/fullchain.pem", domain);
+    let key = format!("/etc/letsencrypt/live/{}/privkey.pem", domain);
+    save_site(target, &name, &generate_publish_config(&domain, &upstream, &certbot_cfg.webroot, Some((&cert, &key))))?;
+    reload(target).map_err(|e| format!("{}\n\nThe certificate was issued but the HTTPS config failed to load:\n{}", steps.join("\n"), e))?;
+    steps.push(format!("Switched to HTTPS — https://{} is live", domain));
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod publish_tests {
+    use super::*;
+
+    #[test]
+    fn rejects_bad_domains() {
+        assert!(validate_domain("app.example.com").is_ok());
+        assert!(validate_domain("localhost").is_err());
+        assert!(validate_domain("a.example.com; include /etc/shadow").is_err());
+        assert!(validate_domain("-bad.example.com").is_err());
+        assert!(validate_domain("*.example.com").is_err());
+    }
+
+    #[test]
+    fn resolves_direct_upstream() {
+        let p = PublishSiteParams {
+            domain: "app.example.com".into(), container_type: String::new(), container: String::new(),
+            upstream_ip: "10.10.10.5".into(), upstream_port: 8080, ssl: false, email: String::new(),
+        };
+        assert_eq!(resolve_upstream(&p).unwrap(), "10.10.10.5:8080");
+        let bad = PublishSiteParams { upstream_ip: "10.10.10.5; x".into(), ..p };
+        assert!(resolve_upstream(&bad).is_err());
+    }
+
+    #[test]
+    fn every_server_block_keeps_the_acme_location() {
+        let http = generate_publish_config("app.example.com", "10.0.0.2:80", "/var/lib/wolfstack/acme-webroot", None);
+        assert_eq!(http.matches("acme-challenge").count(), 1);
+        assert!(http.contains("proxy_pass http://10.0.0.2:80;"));
+
+        let https = generate_publish_config(
+            "app.example.com", "10.0.0.2:80", "/var/lib/wolfstack/acme-webroot",
+            Some(("/etc/letsencrypt/live/app.example.com/fullchain.pem", "/etc/letsencrypt/live/app.example.com/privkey.pem")),
+        );
+        assert_eq!(https.matches("acme-challenge").count(), 2);
+        assert!(https.contains("listen 443 ssl;"));
+        assert!(https.contains("location / {\n        return 301 https://$host$request_uri;\n    }"));
+        assert_eq!(https.matches('{').count(), https.matches('}').count());
+    }
+}
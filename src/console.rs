@@ -13,6 +13,8 @@ use futures::StreamExt;
 use tokio_tungstenite::tungstenite;
 use tracing::error;
 
+pub mod history;
+
 
 /// WebSocket console endpoint: /ws/console/{type}/{name}
 pub async fn console_ws(
@@ -22,11 +24,15 @@ pub async fn console_ws(
     state: web::Data<crate::api::AppState>,
 ) -> Result<HttpResponse, actix_web::Error> {
     // Require session authentication for WebSocket console access
-    if let Err(resp) = crate::api::require_auth(&req, &state) {
-        return Ok(resp);
-    }
+    let user = match crate::api::require_auth(&req, &state) {
+        Ok(u) => u,
+        Err(resp) => return Ok(resp),
+    };
 
     let (container_type, container_name) = path.into_inner();
+    if let Err(e) = crate::auth::tenancy::check_console(&req, &user, &container_type, &container_name) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": e })));
+    }
 
     // Validate container name to prevent command injection (except for compound install names)
     // k8s names use "cluster_id/pod/namespace[/container]" format — validate each part
@@ -56,10 +62,20 @@ pub async fn console_ws(
         None
     };
 
+    // Record typed commands — unless this is the far end of a remote
+    // console, which the proxying node already records with the real user.
+    let recorder = if history::is_interactive(&container_type) && user != "cluster-node" {
+        let ip = req.peer_addr().map(|a| a.ip().to_canonical().to_string()).unwrap_or_default();
+        let node = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+        Some(history::SessionRecorder::new(&user, &node, &container_type, &container_name, &ip))
+    } else {
+        None
+    };
+
     let (response, session, msg_stream) = actix_ws::handle(&req, body)?;
 
     // Use actix_rt::spawn (not tokio::spawn) so we can use non-Send types
-    actix_rt::spawn(console_session(session, msg_stream, container_type, container_name, update_script));
+    actix_rt::spawn(console_session(session, msg_stream, container_type, container_name, update_script, recorder));
 
     Ok(response)
 }
@@ -70,6 +86,7 @@ async fn console_session(
     ctype: String,
     name: String,
     update_script: Option<String>,
+    mut recorder: Option<history::SessionRecorder>,
 ) {
     // Create PTY
     let pty_system = native_pty_system();
@@ -682,11 +699,13 @@ async fn console_session(
                                 }
                             }
                         }
+                        if let Some(r) = recorder.as_mut() { r.feed(&text); }
                         if let Ok(mut w) = writer.lock() {
                             let _ = w.write_all(text.as_bytes());
                         }
                     }
                     Message::Binary(data) => {
+                        if let Some(r) = recorder.as_mut() { r.feed(&String::from_utf8_lossy(&data)); }
                         if let Ok(mut w) = writer.lock() {
                             let _ = w.write_all(&data);
                         }
@@ -722,9 +741,10 @@ pub async fn remote_console_ws(
     state: web::Data<crate::api::AppState>,
 ) -> Result<HttpResponse, actix_web::Error> {
     // Require session authentication
-    if let Err(resp) = crate::api::require_auth(&req, &state) {
-        return Ok(resp);
-    }
+    let user = match crate::api::require_auth(&req, &state) {
+        Ok(u) => u,
+        Err(resp) => return Ok(resp),
+    };
 
     let (node_id, ctype, name) = path.into_inner();
 
@@ -760,13 +780,21 @@ pub async fn remote_console_ws(
         return console_ws(req, web::Path::from((ctype, name)), body, state).await;
     }
 
+    let recorder = if history::is_interactive(&ctype) && user != "cluster-node" {
+        let ip = req.peer_addr().map(|a| a.ip().to_canonical().to_string()).unwrap_or_default();
+        Some(history::SessionRecorder::new(&user, &node.hostname, &ctype, &name, &ip))
+    } else {
+        None
+    };
     let secret = state.cluster_secret.clone();
+    let tenant = crate::auth::tenancy::scope(&req, &user);
     let (response, session, msg_stream) = actix_ws::handle(&req, body)?;
-    actix_rt::spawn(remote_console_bridge(session, msg_stream, node.address, node.port, ctype, name, secret));
+    actix_rt::spawn(remote_console_bridge(session, msg_stream, node.address, node.port, ctype, name, secret, tenant, recorder));
     Ok(response)
 }
 
 /// Bridge browser WS ↔ remote node's console WS
+#[allow(clippy::too_many_arguments)]
 async fn remote_console_bridge(
     mut session: actix_ws::Session,
     mut msg_stream: actix_ws::MessageStream,
@@ -775,6 +803,8 @@ async fn remote_console_bridge(
     ctype: String,
     name: String,
     cluster_secret: String,
+    tenant: Option<String>,
+    mut recorder: Option<history::SessionRecorder>,
 ) {
     // Simple percent-encode for URL path
     let encoded_name: String = name.bytes().map(|b| {
@@ -839,6 +869,11 @@ async fn remote_console_bridge(
         if let Ok(val) = tungstenite::http::HeaderValue::from_str(&cluster_secret) {
             ws_request.headers_mut().insert("X-WolfStack-Secret", val);
         }
+        // A tenant-scoped user's console is checked against their tenant
+        // on the node that holds the container.
+        if let Some(val) = tenant.as_deref().and_then(|t| tungstenite::http::HeaderValue::from_str(t).ok()) {
+            ws_request.headers_mut().insert(crate::auth::tenancy::TENANT_HEADER, val);
+        }
 
         match tokio::time::timeout(
             std::time::Duration::from_secs(3),
@@ -901,10 +936,12 @@ async fn remote_console_bridge(
             msg = msg_stream.next() => {
                 match msg {
                     Some(Ok(actix_ws::Message::Text(text))) => {
+                        if let Some(r) = recorder.as_mut() { r.feed(&text); }
                         if futures::SinkExt::send(&mut remote_sink,
                             tungstenite::Message::Text(text.to_string())).await.is_err() { break; }
                     }
                     Some(Ok(actix_ws::Message::Binary(data))) => {
+                        if let Some(r) = recorder.as_mut() { r.feed(&String::from_utf8_lossy(&data)); }
                         if futures::SinkExt::send(&mut remote_sink,
                             tungstenite::Message::Binary(data.to_vec())).await.is_err() { break; }
                     }
@@ -0,0 +1,377 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Console command history and saved snippets.
+//!
+//! Every line entered in an interactive WolfStack console (host, Docker,
+//! LXC, VM serial, k8s pod) is reconstructed from the keystrokes flowing
+//! browser → PTY and appended to `<config_dir>/console-history.jsonl`,
+//! with a matching row in the persistent audit log so console activity is
+//! investigable alongside API-key use. Lines are rebuilt from input, not
+//! read from the shell, so anything edited with cursor keys or tab
+//! completion is flagged `approximate`. A line starting with a space is
+//! not recorded — the same convention as bash's `HISTCONTROL=ignorespace`
+//! — so a password on the command line can be kept out.
+//!
+//! Proxied consoles are recorded once, on the node the browser talks to
+//! (which knows the real user); the far node sees the cluster secret and
+//! skips recording.
+//!
+//! Snippets are per-user saved commands in `<config_dir>/console-snippets.json`,
+//! sent into one or every pane of a terminal tab from the console UI.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Entries kept in the history file before the oldest half is dropped.
+const HISTORY_MAX: usize = 10_000;
+/// Longest line we bother tracking — beyond this it's a paste, not a command.
+const LINE_MAX: usize = 4096;
+
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+static SNIPPETS_LOCK: Mutex<()> = Mutex::new(());
+
+fn history_path() -> String {
+    format!("{}/console-history.jsonl", crate::paths::get().config_dir)
+}
+
+fn snippets_path() -> String {
+    format!("{}/console-snippets.json", crate::paths::get().config_dir)
+}
+
+/// Console types with a human at a shell. Install/upgrade streams are
+/// scripted and not worth recording.
+pub fn is_interactive(ctype: &str) -> bool {
+    matches!(ctype, "host" | "docker" | "lxc" | "vm" | "k8s")
+}
+
+// ─── Line reconstruction ───
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum EscState {
+    #[default]
+    Normal,
+    Esc,
+    Csi,
+}
+
+/// Rebuilds command lines from raw terminal input.
+#[derive(Debug, Default)]
+pub struct LineTracker {
+    buf: String,
+    edited: bool,
+    esc: EscState,
+    csi: String,
+}
+
+impl LineTracker {
+    /// Feed input; returns each completed line with its `approximate` flag.
+    pub fn feed(&mut self, input: &str) -> Vec<(String, bool)> {
+        let mut lines = Vec::new();
+        for c in input.chars() {
+            match self.esc {
+                EscState::Esc => {
+                    self.esc = if c == '[' || c == 'O' { EscState::Csi } else { EscState::Normal };
+                    self.csi.clear();
+                    if self.esc == EscState::Normal {
+                        self.edited = true; // Alt+key — readline word motion etc.
+                    }
+                    continue;
+                }
+                EscState::Csi => {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        self.esc = EscState::Normal;
+                        // Bracketed-paste markers wrap pasted text; they don't
+                        // move the cursor.
+                        if !(c == '~' && (self.csi == "200" || self.csi == "201")) {
+                            self.edited = true;
+                        }
+                    } else {
+                        self.csi.push(c);
+                    }
+                    continue;
+                }
+                EscState::Normal => {}
+            }
+            match c {
+                '\x1b' => self.esc = EscState::Esc,
+                '\r' | '\n' => {
+                    if !self.buf.trim().is_empty() && !self.buf.starts_with(' ') {
+                        lines.push((self.buf.trim_end().to_string(), self.edited));
+                    }
+                    self.buf.clear();
+                    self.edited = false;
+                }
+                '\x7f' | '\x08' => {
+                    self.buf.pop();
+                }
+                // Ctrl-C / Ctrl-U abandon the line.
+                '\x03' | '\x15' => {
+                    self.buf.clear();
+                    self.edited = false;
+                }
+                // Tab completion and Ctrl-R search change the line in ways
+                // we can't see.
+                '\t' | '\x12' => self.edited = true,
+                c if c.is_control() => {}
+                c => {
+                    if self.buf.len() < LINE_MAX {
+                        self.buf.push(c);
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
+
+// ─── History ───
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: String,
+    pub user: String,
+    /// Hostname of the node the console ran on.
+    pub node: String,
+    pub target_type: String,
+    pub target: String,
+    pub command: String,
+    #[serde(default)]
+    pub approximate: bool,
+}
+
+/// Append a command to the history and the audit log.
+pub fn record(entry: &HistoryEntry, ip: &str) {
+    crate::compat::audit_log(&crate::compat::AuditEntry {
+        timestamp: entry.timestamp.clone(),
+        key_name: entry.user.clone(),
+        key_id: "console".to_string(),
+        method: "CONSOLE".to_string(),
+        path: format!("{}:{}@{} $ {}", entry.target_type, entry.target, entry.node, entry.command),
+        ip: ip.to_string(),
+        status: 200,
+    });
+
+    let line = match serde_json::to_string(entry) { Ok(l) => l, Err(_) => return };
+    let _g = match HISTORY_LOCK.lock() { Ok(g) => g, Err(_) => return };
+    let path = history_path();
+    if let Ok(content) = std::fs::read_to_string(&path) {
+        let lines: Vec<&str> = content.lines().collect();
+        if lines.len() >= HISTORY_MAX {
+            let keep: String = lines[lines.len() - HISTORY_MAX / 2..].iter().map(|l| format!("{}\n", l)).collect();
+            let _ = std::fs::write(&path, keep);
+        }
+    }
+    use std::io::Write;
+    if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(f, "{}", line);
+    }
+}
+
+/// Per-session recorder: owns the line tracker and the context each
+/// completed line is recorded with.
+pub struct SessionRecorder {
+    user: String,
+    node: String,
+    target_type: String,
+    target: String,
+    ip: String,
+    tracker: LineTracker,
+}
+
+impl SessionRecorder {
+    pub fn new(user: &str, node: &str, target_type: &str, target: &str, ip: &str) -> Self {
+        SessionRecorder {
+            user: user.to_string(),
+            node: node.to_string(),
+            target_type: target_type.to_string(),
+            target: target.to_string(),
+            ip: ip.to_string(),
+            tracker: LineTracker::default(),
+        }
+    }
+
+    /// Feed browser input; records any lines it completes. Terminal
+    /// resize messages share the channel and are skipped.
+    pub fn feed(&mut self, input: &str) {
+        if input.starts_with("{\"type\":\"resize\"") {
+            return;
+        }
+        for (command, approximate) in self.tracker.feed(input) {
+            record(&HistoryEntry {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                user: self.user.clone(),
+                node: self.node.clone(),
+                target_type: self.target_type.clone(),
+                target: self.target.clone(),
+                command,
+                approximate,
+            }, &self.ip);
+        }
+    }
+}
+
+/// Filter for `search`. Empty fields match everything; `user` of `None`
+/// means every user (admins only — the endpoint enforces that).
+#[derive(Debug, Default)]
+pub struct HistoryQuery {
+    pub user: Option<String>,
+    pub text: String,
+    pub target: String,
+    pub limit: usize,
+}
+
+fn matches(entry: &HistoryEntry, q: &HistoryQuery) -> bool {
+    if let Some(u) = &q.user {
+        if &entry.user != u { return false; }
+    }
+    if !q.target.is_empty() && entry.target != q.target && entry.node != q.target {
+        return false;
+    }
+    q.text.is_empty() || entry.command.to_lowercase().contains(&q.text.to_lowercase())
+}
+
+/// Newest-first matching history; consecutive repeats of the same
+/// command on the same target are collapsed.
+pub fn search_in(content: &str, q: &HistoryQuery) -> Vec<HistoryEntry> {
+    let limit = if q.limit == 0 { 200 } else { q.limit.min(2000) };
+    let mut out: Vec<HistoryEntry> = Vec::new();
+    for line in content.lines().rev() {
+        let Ok(entry) = serde_json::from_str::<HistoryEntry>(line) else { continue };
+        if !matches(&entry, q) { continue; }
+        if let Some(last) = out.last() {
+            if last.command == entry.command && last.target == entry.target && last.node == entry.node {
+                continue;
+            }
+        }
+        out.push(entry);
+        if out.len() >= limit { break; }
+    }
+    out
+}
+
+pub fn search(q: &HistoryQuery) -> Vec<HistoryEntry> {
+    let content = std::fs::read_to_string(history_path()).unwrap_or_default();
+    search_in(&content, q)
+}
+
+// ─── Snippets ───
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub created: String,
+}
+
+fn load_snippets() -> HashMap<String, Vec<Snippet>> {
+    std::fs::read_to_string(snippets_path()).ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_snippets(all: &HashMap<String, Vec<Snippet>>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(all).map_err(|e| e.to_string())?;
+    std::fs::write(snippets_path(), json).map_err(|e| format!("Failed to save snippets: {}", e))
+}
+
+pub fn list_snippets(user: &str) -> Vec<Snippet> {
+    let mut list = load_snippets().remove(user).unwrap_or_default();
+    list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    list
+}
+
+/// Create a snippet, or update it when `id` names an existing one.
+pub fn save_snippet(user: &str, mut snippet: Snippet) -> Result<Snippet, String> {
+    snippet.name = snippet.name.trim().to_string();
+    if snippet.name.is_empty() || snippet.name.len() > 100 {
+        return Err("Snippet name must be 1–100 characters".to_string());
+    }
+    if snippet.command.trim().is_empty() || snippet.command.len() > 16 * 1024 {
+        return Err("Snippet command must be 1 byte to 16 KB".to_string());
+    }
+    let _g = SNIPPETS_LOCK.lock().map_err(|_| "Snippet store lock poisoned".to_string())?;
+    let mut all = load_snippets();
+    let list = all.entry(user.to_string()).or_default();
+    if let Some(existing) = list.iter_mut().find(|s| !snippet.id.is_empty() && s.id == snippet.id) {
+        existing.name = snippet.name;
+        existing.command = snippet.command;
+        existing.description = snippet.description;
+        let saved = existing.clone();
+        save_snippets(&all)?;
+        return Ok(saved);
+    }
+    snippet.id = uuid::Uuid::new_v4().to_string();
+    snippet.created = chrono::Utc::now().to_rfc3339();
+    list.push(snippet.clone());
+    save_snippets(&all)?;
+    Ok(snippet)
+}
+
+pub fn delete_snippet(user: &str, id: &str) -> Result<(), String> {
+    let _g = SNIPPETS_LOCK.lock().map_err(|_| "Snippet store lock poisoned".to_string())?;
+    let mut all = load_snippets();
+    let list = all.get_mut(user).ok_or("Snippet not found")?;
+    let before = list.len();
+    list.retain(|s| s.id != id);
+    if list.len() == before {
+        return Err("Snippet not found".to_string());
+    }
+    save_snippets(&all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_rebuilds_typed_lines() {
+        let mut t = LineTracker::default();
+        assert!(t.feed("apt upd").is_empty());
+        assert_eq!(t.feed("ate\r"), vec![("apt update".to_string(), false)]);
+        assert_eq!(t.feed("lss\x7f -la\r"), vec![("ls -la".to_string(), false)]);
+        assert!(t.feed("rm -rf /tmp/x\x03").is_empty());
+        assert!(t.feed("\r").is_empty());
+    }
+
+    #[test]
+    fn tracker_flags_edits_and_skips_space_prefixed_lines() {
+        let mut t = LineTracker::default();
+        assert_eq!(t.feed("systemctl rest\tnginx\r"), vec![("systemctl restnginx".to_string(), true)]);
+        assert_eq!(t.feed("\x1b[Aecho hi\r"), vec![("echo hi".to_string(), true)]);
+        assert!(t.feed(" mysql -psecret\r").is_empty());
+        // Bracketed paste is not an edit; a multi-line paste gives two lines.
+        assert_eq!(t.feed("\x1b[200~uptime\rdf -h\x1b[201~\r"),
+                   vec![("uptime".to_string(), false), ("df -h".to_string(), false)]);
+    }
+
+    fn entry(user: &str, target: &str, command: &str) -> String {
+        serde_json::to_string(&HistoryEntry {
+            timestamp: String::new(), user: user.into(), node: "node1".into(),
+            target_type: "lxc".into(), target: target.into(), command: command.into(), approximate: false,
+        }).unwrap()
+    }
+
+    #[test]
+    fn search_is_newest_first_filtered_and_deduped() {
+        let content = [
+            entry("alice", "web1", "apt update"),
+            entry("bob", "web1", "apt upgrade"),
+            entry("alice", "web2", "apt update"),
+            entry("alice", "web2", "apt update"),
+            entry("alice", "db1", "systemctl status mariadb"),
+        ].join("\n");
+        let q = HistoryQuery { user: Some("alice".into()), text: "APT".into(), ..Default::default() };
+        let hits: Vec<String> = search_in(&content, &q).into_iter().map(|e| e.target).collect();
+        assert_eq!(hits, vec!["web2", "web1"]);
+        let all = HistoryQuery { target: "web1".into(), ..Default::default() };
+        assert_eq!(search_in(&content, &all).len(), 2);
+    }
+}
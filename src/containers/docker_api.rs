@@ -0,0 +1,416 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Minimal Docker Engine API client over the daemon's Unix socket.
+//!
+//! The list, stats, inspect, logs, lifecycle and image calls in
+//! `containers` used to fork the `docker` CLI and scrape its `--format`
+//! output — a fork per call, and a parse that broke whenever a CLI release
+//! changed a column. Talking to `/var/run/docker.sock` directly returns the
+//! same data as JSON from the daemon itself.
+//!
+//! Blocking on purpose: every caller already runs on a blocking thread
+//! (`web::block` / `spawn_blocking`), same as the `Command::output()` it
+//! replaces. One connection per request with `Connection: close`, so the
+//! response ends at EOF and no pooling is needed.
+//!
+//! When the socket is missing or refuses the connection (rootless Docker
+//! elsewhere, `DOCKER_HOST=tcp://…`, permissions) calls return
+//! [`ApiError::Unavailable`] and callers fall back to the CLI. Create,
+//! recreate, exec and pull still use the CLI — their flag handling is
+//! where compose-style specs live and isn't worth duplicating.
+
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// Reads, inspect, list. A daemon that takes longer than this is wedged —
+/// the case `docker_status` caps with `timeout 10`.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Start/stop/restart: stop waits out the container's own grace period
+/// (10s by default, configurable per container) before the daemon answers.
+pub const LIFECYCLE_TIMEOUT: Duration = Duration::from_secs(180);
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// No usable socket — use the CLI instead.
+    Unavailable(String),
+    /// The daemon answered with an error, or stopped answering mid-call.
+    /// Formatted like the CLI's stderr so existing messages read the same.
+    Docker(String),
+}
+
+impl ApiError {
+    pub fn message(self) -> String {
+        match self {
+            ApiError::Unavailable(m) | ApiError::Docker(m) => m,
+        }
+    }
+}
+
+/// Socket path: `DOCKER_HOST=unix://…` when set, the standard path
+/// otherwise. `None` when `DOCKER_HOST` points somewhere this client can't
+/// follow (tcp, ssh) — the CLI handles those.
+pub fn socket_path() -> Option<String> {
+    match std::env::var("DOCKER_HOST") {
+        Ok(host) if !host.is_empty() => host.strip_prefix("unix://").map(|p| p.to_string()),
+        _ => Some(DEFAULT_SOCKET.to_string()),
+    }
+}
+
+/// Is there a socket to talk to? Doesn't check the daemon answers.
+pub fn available() -> bool {
+    socket_path().is_some_and(|p| std::path::Path::new(&p).exists())
+}
+
+/// Percent-encode a container name or ID for use as a path segment.
+pub fn seg(s: &str) -> String {
+    urlencoding::encode(s).into_owned()
+}
+
+/// Image references keep their `/` (`ghcr.io/org/app:tag`) — the daemon
+/// routes `/images/{name:.*}`.
+pub fn image_seg(s: &str) -> String {
+    urlencoding::encode(s).replace("%2F", "/")
+}
+
+/// Send one request; returns the status and de-chunked body.
+pub fn request(method: &str, path: &str, body: Option<&Value>, timeout: Duration) -> Result<(u16, Vec<u8>), ApiError> {
+    let socket = socket_path().ok_or_else(|| ApiError::Unavailable("DOCKER_HOST is not a unix socket".to_string()))?;
+    let started = Instant::now();
+    let mut stream = UnixStream::connect(&socket)
+        .map_err(|e| ApiError::Unavailable(format!("Cannot connect to {}: {}", socket, e)))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+
+    let payload = body.map(|b| b.to_string()).unwrap_or_default();
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: docker\r\nUser-Agent: WolfStack\r\nConnection: close\r\n", method, path);
+    if body.is_some() {
+        head.push_str(&format!("Content-Type: application/json\r\nContent-Length: {}\r\n", payload.len()));
+    } else if method != "GET" {
+        head.push_str("Content-Length: 0\r\n");
+    }
+    head.push_str("\r\n");
+    head.push_str(&payload);
+
+    let mut raw = Vec::new();
+    let io = stream.write_all(head.as_bytes()).and_then(|_| stream.read_to_end(&mut raw));
+    let result = match io {
+        Ok(_) => parse_response(&raw).map_err(ApiError::Docker),
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) =>
+            Err(ApiError::Docker(format!("Docker daemon did not answer {} {} within {}s", method, path, timeout.as_secs()))),
+        Err(e) => Err(ApiError::Docker(format!("Docker API {} {}: {}", method, path, e))),
+    };
+    tracing::debug!(target: crate::logging::EXEC_TARGET, method, path,
+        status = result.as_ref().map(|r| r.0).unwrap_or(0),
+        elapsed_ms = started.elapsed().as_millis() as u64, "docker api call");
+    result
+}
+
+/// Turn a non-2xx answer into the CLI's "Error response from daemon: …".
+fn check(method: &str, path: &str, status: u16, body: &[u8]) -> Result<(), ApiError> {
+    if (200..300).contains(&status) || status == 304 {
+        return Ok(());
+    }
+    let msg = serde_json::from_slice::<Value>(body).ok()
+        .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(|s| s.to_string()))
+        .unwrap_or_else(|| format!("{} {} returned HTTP {}", method, path, status));
+    Err(ApiError::Docker(format!("Error response from daemon: {}", msg)))
+}
+
+/// GET and parse the JSON body.
+pub fn get_json(path: &str) -> Result<Value, ApiError> {
+    let (status, body) = request("GET", path, None, READ_TIMEOUT)?;
+    check("GET", path, status, &body)?;
+    serde_json::from_slice(&body).map_err(|e| ApiError::Docker(format!("Docker API {}: bad JSON: {}", path, e)))
+}
+
+/// Daemon version from `/version`, capped at 10s like the CLI probe in
+/// `docker_status` — a wedged daemon accepts the connection and never
+/// answers.
+pub fn version() -> Result<String, ApiError> {
+    let (status, body) = request("GET", "/version", None, Duration::from_secs(10))?;
+    check("GET", "/version", status, &body)?;
+    let v: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    Ok(v.get("Version").and_then(|x| x.as_str()).unwrap_or("").to_string())
+}
+
+/// GET the raw body (logs).
+pub fn get_bytes(path: &str) -> Result<Vec<u8>, ApiError> {
+    let (status, body) = request("GET", path, None, READ_TIMEOUT)?;
+    check("GET", path, status, &body)?;
+    Ok(body)
+}
+
+/// POST or DELETE; returns the JSON body, `Null` when there is none
+/// (204, or 304 "already started/stopped", which the CLI also treats as
+/// success).
+pub fn call(method: &str, path: &str, body: Option<&Value>, timeout: Duration) -> Result<Value, ApiError> {
+    let (status, resp) = request(method, path, body, timeout)?;
+    check(method, path, status, &resp)?;
+    if resp.is_empty() {
+        return Ok(Value::Null);
+    }
+    Ok(serde_json::from_slice(&resp).unwrap_or(Value::Null))
+}
+
+/// Split a raw HTTP/1.1 response into status and body, decoding chunked
+/// transfer encoding.
+fn parse_response(raw: &[u8]) -> Result<(u16, Vec<u8>), String> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")
+        .ok_or("Docker API: truncated response")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+    let mut lines = head.lines();
+    let status: u16 = lines.next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|s| s.parse().ok())
+        .ok_or("Docker API: bad status line")?;
+    let mut chunked = false;
+    let mut length: Option<usize> = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked") {
+            chunked = true;
+        } else if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse().ok();
+        }
+    }
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body[..length.unwrap_or(body.len()).min(body.len())].to_vec()
+    };
+    Ok((status, body))
+}
+
+fn dechunk(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len());
+    loop {
+        let eol = data.windows(2).position(|w| w == b"\r\n").ok_or("Docker API: truncated chunk")?;
+        let size_str = String::from_utf8_lossy(&data[..eol]);
+        let size = usize::from_str_radix(size_str.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| format!("Docker API: bad chunk size '{}'", size_str))?;
+        data = &data[eol + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        if data.len() < size {
+            return Err("Docker API: truncated chunk".to_string());
+        }
+        out.extend_from_slice(&data[..size]);
+        data = data.get(size + 2..).unwrap_or(&[]);
+    }
+}
+
+/// Split a `/logs` body into lines. Without a TTY the daemon multiplexes
+/// stdout and stderr into frames (`[stream, 0, 0, 0, len_be32]` + payload);
+/// with one it sends the raw stream.
+pub fn demux_logs(body: &[u8]) -> Vec<String> {
+    let framed = body.len() >= 8 && body[0] <= 2 && body[1..4] == [0, 0, 0];
+    let text = if framed {
+        let mut out = Vec::with_capacity(body.len());
+        let mut rest = body;
+        while rest.len() >= 8 {
+            let len = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+            let end = (8 + len).min(rest.len());
+            out.extend_from_slice(&rest[8..end]);
+            rest = &rest[end..];
+        }
+        out
+    } else {
+        body.to_vec()
+    };
+    String::from_utf8_lossy(&text).lines().map(|l| l.to_string()).collect()
+}
+
+/// The CLI's `HumanSizeWithPrecision(n, 3)`: decimal units, three
+/// significant digits — `77.8MB`, `1.23GB`, `512B`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    let decimals = if size >= 100.0 { 0 } else if size >= 10.0 { 1 } else { 2 };
+    let mut s = format!("{:.*}", decimals, size);
+    if s.contains('.') {
+        s = s.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    format!("{}{}", s, UNITS[unit])
+}
+
+/// Unix timestamp in the CLI's `CreatedAt` layout.
+pub fn created_at(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M:%S +0000 UTC").to_string())
+        .unwrap_or_default()
+}
+
+/// `Ports` from `/containers/json` in the CLI's `{{.Ports}}` form:
+/// `0.0.0.0:8080->80/tcp`, `[::]:8080->80/tcp`, or `80/tcp` when not
+/// published.
+pub fn format_ports(ports: &Value) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for p in ports.as_array().into_iter().flatten() {
+        let private = p.get("PrivatePort").and_then(|v| v.as_u64()).unwrap_or(0);
+        let proto = p.get("Type").and_then(|v| v.as_str()).unwrap_or("tcp");
+        let s = match p.get("PublicPort").and_then(|v| v.as_u64()) {
+            Some(public) => {
+                let ip = p.get("IP").and_then(|v| v.as_str()).unwrap_or("0.0.0.0");
+                let ip = if ip.contains(':') { format!("[{}]", ip) } else { ip.to_string() };
+                format!("{}:{}->{}/{}", ip, public, private, proto)
+            }
+            None => format!("{}/{}", private, proto),
+        };
+        if !out.contains(&s) {
+            out.push(s);
+        }
+    }
+    out
+}
+
+/// Primary name from `/containers/json` `Names` (`["/web"]`); linked
+/// aliases (`/other/web`) are skipped.
+pub fn primary_name(names: &Value) -> String {
+    names.as_array().into_iter().flatten()
+        .filter_map(|n| n.as_str())
+        .map(|n| n.trim_start_matches('/'))
+        .find(|n| !n.contains('/'))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Usage numbers from one `/containers/{id}/stats?stream=false` sample,
+/// computed the way `docker stats` does: CPU from the delta against the
+/// previous sample, memory net of reclaimable page cache.
+pub struct StatsSample {
+    pub cpu_percent: f64,
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub memory_percent: f64,
+    pub net_input: u64,
+    pub net_output: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+    pub pids: u32,
+}
+
+pub fn stats_sample(v: &Value) -> StatsSample {
+    let u = |p: &str| v.pointer(p).and_then(|x| x.as_u64()).unwrap_or(0);
+    let cpu_delta = u("/cpu_stats/cpu_usage/total_usage").saturating_sub(u("/precpu_stats/cpu_usage/total_usage"));
+    let sys_delta = u("/cpu_stats/system_cpu_usage").saturating_sub(u("/precpu_stats/system_cpu_usage"));
+    let online = match u("/cpu_stats/online_cpus") {
+        0 => v.pointer("/cpu_stats/cpu_usage/percpu_usage").and_then(|x| x.as_array()).map(|a| a.len() as u64).unwrap_or(1),
+        n => n,
+    };
+    let cpu_percent = if cpu_delta > 0 && sys_delta > 0 {
+        cpu_delta as f64 / sys_delta as f64 * online as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    // cgroup v1 reports total_inactive_file, v2 inactive_file.
+    let usage = u("/memory_stats/usage");
+    let cache = match u("/memory_stats/stats/total_inactive_file") {
+        0 => u("/memory_stats/stats/inactive_file"),
+        n => n,
+    };
+    let memory_usage = if cache < usage { usage - cache } else { usage };
+    let memory_limit = u("/memory_stats/limit");
+    let memory_percent = if memory_limit > 0 { memory_usage as f64 / memory_limit as f64 * 100.0 } else { 0.0 };
+
+    let (mut net_input, mut net_output) = (0, 0);
+    for n in v.get("networks").and_then(|x| x.as_object()).into_iter().flat_map(|m| m.values()) {
+        net_input += n.get("rx_bytes").and_then(|x| x.as_u64()).unwrap_or(0);
+        net_output += n.get("tx_bytes").and_then(|x| x.as_u64()).unwrap_or(0);
+    }
+    let (mut block_read, mut block_write) = (0, 0);
+    for e in v.pointer("/blkio_stats/io_service_bytes_recursive").and_then(|x| x.as_array()).into_iter().flatten() {
+        let bytes = e.get("value").and_then(|x| x.as_u64()).unwrap_or(0);
+        match e.get("op").and_then(|x| x.as_str()).map(|s| s.to_ascii_lowercase()).as_deref() {
+            Some("read") => block_read += bytes,
+            Some("write") => block_write += bytes,
+            _ => {}
+        }
+    }
+
+    StatsSample {
+        cpu_percent,
+        memory_usage,
+        memory_limit,
+        memory_percent,
+        net_input,
+        net_output,
+        block_read,
+        block_write,
+        pids: u("/pids_stats/current") as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_response_is_decoded() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[{\"a\r\n5\r\n\":1}]\r\n0\r\n\r\n";
+        let (status, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, b"[{\"a\":1}]");
+
+        let raw = b"HTTP/1.1 404 Not Found\r\nContent-Length: 13\r\n\r\n{\"message\":1}";
+        assert_eq!(parse_response(raw).unwrap(), (404, b"{\"message\":1}".to_vec()));
+    }
+
+    #[test]
+    fn multiplexed_logs_are_split_into_lines() {
+        let mut body = vec![1, 0, 0, 0, 0, 0, 0, 6];
+        body.extend_from_slice(b"out 1\n");
+        body.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 6]);
+        body.extend_from_slice(b"err 1\n");
+        assert_eq!(demux_logs(&body), ["out 1", "err 1"]);
+        assert_eq!(demux_logs(b"2026-01-01T00:00:00Z tty line\n"), ["2026-01-01T00:00:00Z tty line"]);
+    }
+
+    #[test]
+    fn formatting_matches_the_cli() {
+        assert_eq!(human_size(512), "512B");
+        assert_eq!(human_size(77_800_000), "77.8MB");
+        assert_eq!(human_size(1_234_567_890), "1.23GB");
+        assert_eq!(human_size(100_000_000), "100MB");
+        assert_eq!(created_at(0), "1970-01-01 00:00:00 +0000 UTC");
+
+        let ports = serde_json::json!([
+            {"IP": "0.0.0.0", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp"},
+            {"IP": "::", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp"},
+            {"PrivatePort": 443, "Type": "tcp"},
+        ]);
+        assert_eq!(format_ports(&ports), ["0.0.0.0:8080->80/tcp", "[::]:8080->80/tcp", "443/tcp"]);
+        assert_eq!(primary_name(&serde_json::json!(["/app/db", "/db"])), "db");
+    }
+
+    #[test]
+    fn stats_match_docker_stats_arithmetic() {
+        let v = serde_json::json!({
+            "cpu_stats": {"cpu_usage": {"total_usage": 2_000_000}, "system_cpu_usage": 20_000_000, "online_cpus": 4},
+            "precpu_stats": {"cpu_usage": {"total_usage": 1_000_000}, "system_cpu_usage": 10_000_000},
+            "memory_stats": {"usage": 300, "limit": 1000, "stats": {"inactive_file": 100}},
+            "networks": {"eth0": {"rx_bytes": 10, "tx_bytes": 20}, "eth1": {"rx_bytes": 1, "tx_bytes": 2}},
+            "blkio_stats": {"io_service_bytes_recursive": [{"op": "read", "value": 7}, {"op": "Write", "value": 9}]},
+            "pids_stats": {"current": 3},
+        });
+        let s = stats_sample(&v);
+        assert!((s.cpu_percent - 40.0).abs() < 1e-9);
+        assert_eq!((s.memory_usage, s.memory_limit), (200, 1000));
+        assert!((s.memory_percent - 20.0).abs() < 1e-9);
+        assert_eq!((s.net_input, s.net_output, s.block_read, s.block_write, s.pids), (11, 22, 7, 9, 3));
+    }
+}
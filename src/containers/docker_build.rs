@@ -0,0 +1,350 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Building Docker images on the node — from an uploaded build context
+//! or straight from a Git repository.
+//!
+//! A build is a background job, like an ISO download: `start` validates
+//! the request and registers the job, `run` drives `docker build` on a
+//! blocking thread and appends every output line to the job's log, and
+//! the dashboard polls `GET /api/containers/docker/builds/{id}?since=N`
+//! for the lines it hasn't seen yet. The image is tagged locally, so it
+//! shows up under Images and in the create dialog's local-image list
+//! (which skips the registry pull).
+//!
+//! Git sources are handed to Docker as-is (`https://host/repo.git#ref:dir`)
+//! — the daemon clones them itself, which needs `git` on the node.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// Finished builds stay listed this long.
+const JOB_RETENTION_SECS: i64 = 3600;
+/// Log lines kept per build; older lines are dropped (and counted).
+const MAX_LOG_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildJob {
+    pub id: String,
+    pub tag: String,
+    /// `upload` or the Git URL.
+    pub source: String,
+    /// `building`, `done` or `failed`.
+    pub status: String,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    /// Lines dropped off the front of `log` once it passed MAX_LOG_LINES.
+    pub log_dropped: usize,
+    #[serde(skip)]
+    log: Vec<String>,
+}
+
+impl BuildJob {
+    /// Total lines the build has written — the `since` to ask for next.
+    pub fn log_total(&self) -> usize {
+        self.log_dropped + self.log.len()
+    }
+
+    /// Lines from absolute position `since` on (what's still kept of them).
+    pub fn log_since(&self, since: usize) -> &[String] {
+        let start = since.saturating_sub(self.log_dropped).min(self.log.len());
+        &self.log[start..]
+    }
+
+    fn push_line(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > MAX_LOG_LINES {
+            let extra = self.log.len() - MAX_LOG_LINES;
+            self.log.drain(..extra);
+            self.log_dropped += extra;
+        }
+    }
+}
+
+static JOBS: LazyLock<Mutex<HashMap<String, BuildJob>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn update_job(id: &str, f: impl FnOnce(&mut BuildJob)) {
+    if let Ok(mut jobs) = JOBS.lock()
+        && let Some(j) = jobs.get_mut(id)
+    {
+        f(j);
+    }
+}
+
+/// Current and recent builds, newest first.
+pub fn jobs() -> Vec<BuildJob> {
+    let now = chrono::Utc::now().timestamp();
+    let mut jobs = JOBS.lock().map(|mut m| {
+        m.retain(|_, j| j.finished_at.map(|t| now - t < JOB_RETENTION_SECS).unwrap_or(true));
+        m.values().cloned().collect::<Vec<_>>()
+    }).unwrap_or_default();
+    jobs.sort_by_key(|j| std::cmp::Reverse(j.started_at));
+    jobs
+}
+
+pub fn job(id: &str) -> Option<BuildJob> {
+    JOBS.lock().ok()?.get(id).cloned()
+}
+
+/// What to build. Exactly one of an uploaded context (the request body,
+/// a tar or tar.gz with the Dockerfile inside) or `git_url`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BuildRequest {
+    /// Local tag for the result, e.g. `myapp:1.4`.
+    #[serde(default)]
+    pub tag: String,
+    /// Dockerfile path inside the context. Docker's default when empty.
+    #[serde(default)]
+    pub dockerfile: String,
+    #[serde(default)]
+    pub git_url: String,
+    /// Branch, tag or commit.
+    #[serde(default)]
+    pub git_ref: String,
+    /// Sub-directory of the repository to use as the context.
+    #[serde(default)]
+    pub git_dir: String,
+    /// `--build-arg` values.
+    #[serde(default)]
+    pub build_args: HashMap<String, String>,
+    /// Always pull newer base images.
+    #[serde(default)]
+    pub pull: bool,
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+/// Image references: lowercase repository path, optional `:tag` (case
+/// allowed), no spaces or shell punctuation.
+pub fn validate_tag(tag: &str) -> Result<(), String> {
+    let (repo, version) = match tag.rsplit_once(':') {
+        Some((r, v)) if !v.contains('/') => (r, Some(v)),
+        _ => (tag, None),
+    };
+    if repo.is_empty() || tag.len() > 255 {
+        return Err("tag is required, e.g. myapp:1.0".into());
+    }
+    if !repo.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        || !repo.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._/-:".contains(c))
+    {
+        return Err(format!("'{}' isn't a valid image name — lowercase letters, digits, '.', '_', '-' and '/'", repo));
+    }
+    if let Some(v) = version
+        && (v.is_empty() || v.len() > 128 || !v.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c)))
+    {
+        return Err(format!("'{}' isn't a valid image tag", v));
+    }
+    Ok(())
+}
+
+/// Paths and refs inside the context / repository.
+fn safe_path(what: &str, s: &str) -> Result<(), String> {
+    if s.starts_with('/') || s.contains("..")
+        || !s.chars().all(|c| c.is_ascii_alphanumeric() || "._/-".contains(c))
+    {
+        return Err(format!("{} '{}' may only contain letters, digits, '.', '_', '-' and '/' and must be relative", what, s));
+    }
+    Ok(())
+}
+
+impl BuildRequest {
+    pub fn validate(&self, has_upload: bool) -> Result<(), String> {
+        validate_tag(&self.tag)?;
+        safe_path("dockerfile", &self.dockerfile)?;
+        match (has_upload, self.git_url.is_empty()) {
+            (true, false) => return Err("send either a build context or a git_url, not both".into()),
+            (false, true) => return Err("send a build context (tar / tar.gz body) or a git_url".into()),
+            (false, false) => {
+                let url = self.git_url.as_str();
+                let scheme_ok = ["https://", "http://", "ssh://", "git://", "git@"].iter().any(|p| url.starts_with(p));
+                if !scheme_ok || url.contains(['#', ' ', '\'', '"', '`', '$', ';']) || url.chars().any(|c| c.is_control()) {
+                    return Err(format!("'{}' isn't a Git URL WolfStack can build from", url));
+                }
+                safe_path("git_ref", &self.git_ref)?;
+                safe_path("git_dir", &self.git_dir)?;
+            }
+            (true, true) => {}
+        }
+        for key in self.build_args.keys() {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("build arg '{}' must be letters, digits and '_'", key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `docker build` context argument: `-` for an upload on stdin,
+    /// else `url#ref:dir`.
+    fn context(&self) -> String {
+        if self.git_url.is_empty() {
+            return "-".into();
+        }
+        let mut ctx = self.git_url.clone();
+        if !self.git_ref.is_empty() || !self.git_dir.is_empty() {
+            ctx.push('#');
+            ctx.push_str(&self.git_ref);
+            if !self.git_dir.is_empty() {
+                ctx.push(':');
+                ctx.push_str(&self.git_dir);
+            }
+        }
+        ctx
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec!["build".into(), "-t".into(), self.tag.clone()];
+        if !self.dockerfile.is_empty() {
+            args.push("-f".into());
+            args.push(self.dockerfile.clone());
+        }
+        let mut build_args: Vec<_> = self.build_args.iter().collect();
+        build_args.sort();
+        for (k, v) in build_args {
+            args.push("--build-arg".into());
+            args.push(format!("{}={}", k, v));
+        }
+        if self.pull { args.push("--pull".into()); }
+        if self.no_cache { args.push("--no-cache".into()); }
+        args.push(self.context());
+        args
+    }
+}
+
+/// Register a build. `upload` is the saved context archive, if any. The
+/// caller runs [`run`] with the returned job on a blocking thread.
+pub fn start(req: &BuildRequest, upload: Option<&str>) -> Result<BuildJob, String> {
+    req.validate(upload.is_some())?;
+    let job = BuildJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        tag: req.tag.clone(),
+        source: if upload.is_some() { "upload".into() } else { req.git_url.clone() },
+        status: "building".into(),
+        error: None,
+        started_at: chrono::Utc::now().timestamp(),
+        finished_at: None,
+        log_dropped: 0,
+        log: Vec::new(),
+    };
+    JOBS.lock().map_err(|_| "build jobs lock poisoned".to_string())?.insert(job.id.clone(), job.clone());
+    Ok(job)
+}
+
+/// Run `docker build`, streaming stdout and stderr into the job's log.
+/// Removes the uploaded context when done.
+pub fn run(id: &str, req: &BuildRequest, upload: Option<&str>) {
+    let result = run_inner(id, req, upload);
+    if let Some(path) = upload {
+        let _ = std::fs::remove_file(path);
+    }
+    super::invalidate_list_caches();
+    let now = chrono::Utc::now().timestamp();
+    update_job(id, |j| {
+        j.finished_at = Some(now);
+        match result {
+            Ok(()) => {
+                j.status = "done".into();
+                j.push_line(format!("Built and tagged {}", j.tag));
+            }
+            Err(e) => {
+                j.status = "failed".into();
+                j.push_line(format!("Build failed: {}", e));
+                j.error = Some(e);
+            }
+        }
+    });
+}
+
+fn run_inner(id: &str, req: &BuildRequest, upload: Option<&str>) -> Result<(), String> {
+    let stdin = match upload {
+        Some(path) => Stdio::from(std::fs::File::open(path).map_err(|e| format!("Cannot open build context: {}", e))?),
+        None => Stdio::null(),
+    };
+    let mut child = Command::new("docker")
+        .args(req.args())
+        // Line-per-step output from BuildKit instead of a redrawn TTY view.
+        .env("BUILDKIT_PROGRESS", "plain")
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run docker build: {}", e))?;
+
+    let last_line = Arc::new(Mutex::new(String::new()));
+    let readers: Vec<_> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+    ].into_iter().flatten().map(|stream| {
+        let id = id.to_string();
+        let last_line = last_line.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                if let Ok(mut last) = last_line.lock() {
+                    last.clone_from(&line);
+                }
+                update_job(&id, |j| j.push_line(line));
+            }
+        })
+    }).collect();
+    let status = child.wait().map_err(|e| format!("docker build: {}", e))?;
+    for r in readers {
+        let _ = r.join();
+    }
+    if status.success() {
+        Ok(())
+    } else {
+        let last = last_line.lock().map(|l| l.clone()).unwrap_or_default();
+        Err(if last.is_empty() { format!("docker build exited with {}", status) } else { last })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_requests_are_checked() {
+        assert!(validate_tag("myapp:1.4").is_ok());
+        assert!(validate_tag("registry.local:5000/team/app").is_ok());
+        assert!(validate_tag("MyApp").is_err());
+        assert!(validate_tag("app:").is_err());
+        assert!(validate_tag("app;rm").is_err());
+        let git = BuildRequest { tag: "app".into(), git_url: "https://example.com/app.git".into(), ..Default::default() };
+        assert!(git.validate(false).is_ok());
+        assert!(git.validate(true).is_err(), "upload and git_url together");
+        assert!(BuildRequest { tag: "app".into(), ..Default::default() }.validate(false).is_err());
+        assert!(BuildRequest { git_url: "file:///etc".into(), ..git.clone() }.validate(false).is_err());
+        assert!(BuildRequest { git_dir: "../x".into(), ..git.clone() }.validate(false).is_err());
+        assert!(BuildRequest { dockerfile: "/etc/passwd".into(), ..git }.validate(false).is_err());
+    }
+
+    #[test]
+    fn git_context_and_args() {
+        let req = BuildRequest {
+            tag: "app:2".into(), dockerfile: "docker/Dockerfile".into(),
+            git_url: "https://example.com/app.git".into(), git_ref: "v2".into(), git_dir: "svc".into(),
+            build_args: HashMap::from([("VERSION".to_string(), "2".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(req.args().join(" "),
+            "build -t app:2 -f docker/Dockerfile --build-arg VERSION=2 https://example.com/app.git#v2:svc");
+        let upload = BuildRequest { tag: "app".into(), ..Default::default() };
+        assert_eq!(upload.args().join(" "), "build -t app -");
+    }
+
+    #[test]
+    fn log_keeps_positions_when_trimmed() {
+        let mut job = start(&BuildRequest { tag: "t".into(), git_url: "https://e/x.git".into(), ..Default::default() }, None).unwrap();
+        for i in 0..MAX_LOG_LINES + 10 {
+            job.push_line(i.to_string());
+        }
+        assert_eq!(job.log_total(), MAX_LOG_LINES + 10);
+        assert_eq!(job.log_dropped, 10);
+        assert_eq!(job.log_since(0).first().map(String::as_str), Some("10"));
+        assert_eq!(job.log_since(MAX_LOG_LINES + 8).len(), 2);
+    }
+}
@@ -0,0 +1,297 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Custom Docker networks — list, create, remove, and attach/detach
+//! containers.
+//!
+//! Docker containers otherwise sit on the default bridge (plus WolfNet
+//! routing). This lets an operator make their own user-defined bridge
+//! (containers on it resolve each other by name) or a macvlan network on
+//! a host interface (containers get their own address on the LAN), and
+//! connect running containers to it. Attachments live in Docker itself,
+//! so `docker_inspect` (`NetworkSettings.Networks`) shows them without
+//! WolfStack keeping any state.
+//!
+//! The list goes through the Engine API with the CLI as the fallback, the
+//! same as the other reads; writes use the CLI, like create and pull.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::docker_api;
+
+/// Docker's own networks, and the one WolfNet routing depends on.
+/// Listed but never removed from here.
+const PROTECTED: &[&str] = &["bridge", "host", "none", "wolfnet"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DockerNetwork {
+    pub id: String,
+    pub name: String,
+    pub driver: String,
+    pub scope: String,
+    pub subnet: String,
+    pub gateway: String,
+    /// Host interface a macvlan/ipvlan network sits on.
+    pub parent: String,
+    pub internal: bool,
+    /// One of [`PROTECTED`] — the UI hides its delete button.
+    pub protected: bool,
+    /// Names of the containers attached to it.
+    pub containers: Vec<String>,
+}
+
+impl DockerNetwork {
+    fn from_inspect(v: &serde_json::Value) -> Self {
+        let s = |p: &str| v.pointer(p).and_then(|x| x.as_str()).unwrap_or("").to_string();
+        let name = s("/Name");
+        let mut containers: Vec<String> = v.get("Containers").and_then(|c| c.as_object())
+            .map(|m| m.values()
+                .filter_map(|c| c.get("Name").and_then(|n| n.as_str()).map(String::from))
+                .collect())
+            .unwrap_or_default();
+        containers.sort();
+        DockerNetwork {
+            id: s("/Id").chars().take(12).collect(),
+            protected: PROTECTED.contains(&name.as_str()),
+            name,
+            driver: s("/Driver"),
+            scope: s("/Scope"),
+            subnet: s("/IPAM/Config/0/Subnet"),
+            gateway: s("/IPAM/Config/0/Gateway"),
+            parent: s("/Options/parent"),
+            internal: v.get("Internal").and_then(|x| x.as_bool()).unwrap_or(false),
+            containers,
+        }
+    }
+}
+
+/// Every network on the host with its attached containers.
+pub fn list() -> Result<Vec<DockerNetwork>, String> {
+    let inspected: Vec<serde_json::Value> = match docker_api::get_json("/networks") {
+        // The list endpoint leaves `Containers` empty; inspect each one.
+        Ok(v) => v.as_array().into_iter().flatten()
+            .filter_map(|n| n.get("Id").and_then(|x| x.as_str()))
+            .filter_map(|id| docker_api::get_json(&format!("/networks/{}", docker_api::seg(id))).ok())
+            .collect(),
+        Err(docker_api::ApiError::Docker(e)) => return Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => {
+            let ids = docker_cli(&["network", "ls", "-q"])?;
+            let ids: Vec<&str> = ids.split_whitespace().collect();
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut args = vec!["network", "inspect"];
+            args.extend(ids);
+            serde_json::from_str(&docker_cli(&args)?)
+                .map_err(|e| format!("docker network inspect: bad JSON: {}", e))?
+        }
+    };
+    let mut networks: Vec<DockerNetwork> = inspected.iter().map(DockerNetwork::from_inspect).collect();
+    networks.sort_by(|a, b| b.protected.cmp(&a.protected).then_with(|| a.name.cmp(&b.name)));
+    Ok(networks)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateNetwork {
+    pub name: String,
+    /// "bridge" (default) or "macvlan".
+    #[serde(default)]
+    pub driver: String,
+    /// CIDR, e.g. "172.30.0.0/24" or the LAN's "192.168.1.0/24" for
+    /// macvlan. Docker picks one for a bridge when empty.
+    #[serde(default)]
+    pub subnet: String,
+    #[serde(default)]
+    pub gateway: String,
+    /// Narrower CIDR inside `subnet` Docker may hand out from — keeps
+    /// macvlan containers clear of the LAN's DHCP pool.
+    #[serde(default)]
+    pub ip_range: String,
+    /// Host interface for macvlan (e.g. "eth0", "eno1.20"). Required there.
+    #[serde(default)]
+    pub parent: String,
+    /// No route out of the network.
+    #[serde(default)]
+    pub internal: bool,
+}
+
+/// Network names: the container-name rules.
+fn validate_name(name: &str) -> Result<(), String> {
+    crate::validate::ContainerName::parse(name)
+        .map(|_| ())
+        .map_err(|e| e.replace("container name", "network name"))
+}
+
+fn valid_cidr(s: &str) -> bool {
+    let Some((addr, prefix)) = s.split_once('/') else { return false };
+    let Ok(prefix) = prefix.parse::<u8>() else { return false };
+    match addr.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => prefix <= 32,
+        Ok(std::net::IpAddr::V6(_)) => prefix <= 128,
+        Err(_) => false,
+    }
+}
+
+impl CreateNetwork {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_name(&self.name)?;
+        if PROTECTED.contains(&self.name.as_str()) {
+            return Err(format!("'{}' is a reserved network name", self.name));
+        }
+        match self.driver.as_str() {
+            "" | "bridge" => {}
+            "macvlan" => {
+                if self.parent.is_empty() {
+                    return Err("macvlan needs a parent interface".into());
+                }
+                if self.subnet.is_empty() {
+                    return Err("macvlan needs the parent network's subnet".into());
+                }
+            }
+            other => return Err(format!("unsupported driver '{}' — use bridge or macvlan", other)),
+        }
+        for (field, value) in [("subnet", &self.subnet), ("ip_range", &self.ip_range)] {
+            if !value.is_empty() && !valid_cidr(value) {
+                return Err(format!("{} '{}' is not a CIDR like 192.168.1.0/24", field, value));
+            }
+        }
+        if !self.gateway.is_empty() && self.gateway.parse::<std::net::IpAddr>().is_err() {
+            return Err(format!("gateway '{}' is not an IP address", self.gateway));
+        }
+        if (!self.gateway.is_empty() || !self.ip_range.is_empty()) && self.subnet.is_empty() {
+            return Err("gateway and ip_range need a subnet".into());
+        }
+        if !self.parent.is_empty() && !parent_interfaces().contains(&self.parent) {
+            return Err(format!("'{}' is not an interface on this host", self.parent));
+        }
+        Ok(())
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec!["network".into(), "create".into(), "--driver".into()];
+        args.push(if self.driver.is_empty() { "bridge".into() } else { self.driver.clone() });
+        for (flag, value) in [("--subnet", &self.subnet), ("--gateway", &self.gateway), ("--ip-range", &self.ip_range)] {
+            if !value.is_empty() {
+                args.push(flag.into());
+                args.push(value.clone());
+            }
+        }
+        if self.driver == "macvlan" {
+            args.push("-o".into());
+            args.push(format!("parent={}", self.parent));
+        }
+        if self.internal {
+            args.push("--internal".into());
+        }
+        args.push(self.name.clone());
+        args
+    }
+}
+
+pub fn create(spec: &CreateNetwork) -> Result<String, String> {
+    spec.validate()?;
+    let args = spec.args();
+    docker_cli(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Ok(format!("Network '{}' created", spec.name))
+}
+
+pub fn remove(name: &str) -> Result<String, String> {
+    validate_name(name)?;
+    if PROTECTED.contains(&name) {
+        return Err(format!("'{}' is managed by Docker or WolfStack and can't be removed", name));
+    }
+    docker_cli(&["network", "rm", name])?;
+    Ok(format!("Network '{}' removed", name))
+}
+
+/// Attach a container, optionally at a fixed address (only on networks
+/// with a subnet of their own).
+pub fn connect(network: &str, container: &str, ip: Option<&str>) -> Result<String, String> {
+    validate_name(network)?;
+    let mut args = vec!["network", "connect"];
+    if let Some(ip) = ip.filter(|ip| !ip.is_empty()) {
+        let flag = match ip.parse::<std::net::IpAddr>() {
+            Ok(std::net::IpAddr::V4(_)) => "--ip",
+            Ok(std::net::IpAddr::V6(_)) => "--ip6",
+            Err(_) => return Err(format!("'{}' is not an IP address", ip)),
+        };
+        args.extend([flag, ip]);
+    }
+    args.extend([network, container]);
+    docker_cli(&args)?;
+    super::invalidate_list_caches();
+    Ok(format!("'{}' connected to '{}'", container, network))
+}
+
+pub fn disconnect(network: &str, container: &str) -> Result<String, String> {
+    validate_name(network)?;
+    docker_cli(&["network", "disconnect", network, container])?;
+    super::invalidate_list_caches();
+    Ok(format!("'{}' disconnected from '{}'", container, network))
+}
+
+/// Host interfaces a macvlan network can sit on: physical NICs, bonds,
+/// VLAN sub-interfaces and bridges — not loopback, Docker's own bridges
+/// or container veths.
+pub fn parent_interfaces() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir("/sys/class/net").into_iter().flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| n != "lo"
+            && !n.starts_with("veth") && !n.starts_with("docker") && !n.starts_with("br-")
+            && !n.starts_with("lxcbr") && !n.starts_with("wolfnet") && !n.starts_with("tap"))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Run `docker <args>` (10 s cap — a wedged daemon must not hang the
+/// request), returning stdout or the daemon's error.
+fn docker_cli(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("timeout").arg("10").arg("docker").args(args).output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, driver: &str) -> CreateNetwork {
+        CreateNetwork {
+            name: name.into(), driver: driver.into(), subnet: String::new(), gateway: String::new(),
+            ip_range: String::new(), parent: String::new(), internal: false,
+        }
+    }
+
+    #[test]
+    fn create_specs_are_checked() {
+        assert!(spec("apps", "").validate().is_ok());
+        assert!(spec("bridge", "bridge").validate().is_err());
+        assert!(spec("bad name", "bridge").validate().is_err());
+        assert!(spec("lan", "overlay").validate().is_err());
+        assert!(spec("lan", "macvlan").validate().is_err());
+        let mut s = spec("apps", "bridge");
+        s.gateway = "172.30.0.1".into();
+        assert!(s.validate().is_err(), "gateway without subnet");
+        s.subnet = "172.30.0.0/24".into();
+        assert!(s.validate().is_ok());
+        s.subnet = "172.30.0.0/40".into();
+        assert!(s.validate().is_err());
+    }
+
+    #[test]
+    fn macvlan_args_carry_the_parent() {
+        let mut s = spec("lan", "macvlan");
+        s.subnet = "192.168.1.0/24".into();
+        s.ip_range = "192.168.1.192/27".into();
+        s.parent = "eth0".into();
+        assert_eq!(s.args().join(" "),
+            "network create --driver macvlan --subnet 192.168.1.0/24 --ip-range 192.168.1.192/27 -o parent=eth0 lan");
+    }
+}
@@ -0,0 +1,342 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Per-container environment and secret store.
+//!
+//! Env vars typed into the create form used to live only in the container
+//! itself — readable by anyone who can `docker inspect` it, and gone when
+//! a flow rebuilt the container from anything but its own inspect. Here
+//! they are kept per scope (a Docker container name, or a compose stack
+//! name) in `paths.container_env`, with every value sealed through
+//! [`crate::secrets`], and put back each time WolfStack creates or
+//! recreates that container:
+//!
+//! - plain variables go in through a temporary `--env-file` (0600, deleted
+//!   once docker has read it), so the values are never on a command line;
+//! - variables marked secret are not put in the environment at all. The
+//!   value is written to `<container_secrets_dir>/<scope>/<NAME>` (0600)
+//!   and mounted read-only at `/run/secrets/<NAME>`, and `<NAME>_FILE`
+//!   points at it — the convention the official postgres, mysql, mariadb
+//!   and friends images already follow. `docker inspect` only shows the
+//!   path.
+//!
+//! The container carries a `wolfstack.env` label listing what was
+//! injected, so a recreate can drop the old copies and apply the store's
+//! current values (including removals).
+//!
+//! Stored values are referenced by name elsewhere: `${secret:NAME}` in a
+//! template's or the create form's env resolves against the container's
+//! own scope and then the Secrets Manager, `${secret:scope/NAME}` against
+//! another scope. Compose stacks get their scope as process environment
+//! for `docker compose`, so the YAML uses ordinary `${NAME}`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const ENV_STORE_SECRETS: crate::secrets::SecretFields = crate::secrets::SecretFields {
+    label: "container-env",
+    fields: &["value"],
+};
+
+/// Label listing the variables injected from the store, comma-separated.
+pub const INJECTED_LABEL: &str = "wolfstack.env";
+
+/// Where secret files are mounted inside the container.
+const SECRETS_MOUNT: &str = "/run/secrets";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnvVar {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    /// Deliver as a mounted file instead of an environment variable.
+    #[serde(default)]
+    pub secret: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvStore {
+    #[serde(default)]
+    pub scopes: BTreeMap<String, Vec<EnvVar>>,
+}
+
+fn store_path() -> String {
+    crate::paths::get().container_env
+}
+
+fn secrets_dir(scope: &str) -> PathBuf {
+    Path::new(&crate::paths::get().container_secrets_dir).join(scope)
+}
+
+pub fn load() -> EnvStore {
+    std::fs::read_to_string(store_path())
+        .ok()
+        .and_then(|s| crate::secrets::from_sealed_json(&s, &ENV_STORE_SECRETS).ok())
+        .unwrap_or_default()
+}
+
+fn save(store: &EnvStore) -> Result<(), String> {
+    let json = crate::secrets::to_sealed_json(store, &ENV_STORE_SECRETS)?;
+    let path = store_path();
+    crate::paths::write_secure_atomic(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+pub fn vars(scope: &str) -> Vec<EnvVar> {
+    load().scopes.remove(scope).unwrap_or_default()
+}
+
+fn validate_scope(scope: &str) -> Result<(), String> {
+    crate::validate::ContainerName::parse(scope).map(|_| ())
+}
+
+fn validate_var_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if ok { Ok(()) } else { Err(format!("Invalid variable name '{}' — must match [A-Za-z_][A-Za-z0-9_]*", name)) }
+}
+
+/// Check a scope's new variables against what's stored. A secret sent
+/// with an empty value keeps its stored one, so the UI never has to read
+/// secrets back to save a change to something else.
+fn merge(existing: &[EnvVar], incoming: Vec<EnvVar>) -> Result<Vec<EnvVar>, String> {
+    let mut out: Vec<EnvVar> = Vec::new();
+    for mut v in incoming {
+        v.name = v.name.trim().to_string();
+        validate_var_name(&v.name)?;
+        if out.iter().any(|o| o.name == v.name) {
+            return Err(format!("'{}' is listed twice", v.name));
+        }
+        if v.secret && v.value.is_empty() {
+            match existing.iter().find(|e| e.name == v.name && e.secret) {
+                Some(e) => v.value = e.value.clone(),
+                None => return Err(format!("Secret '{}' needs a value", v.name)),
+            }
+        }
+        // --env-file is one variable per line.
+        if !v.secret && v.value.contains('\n') {
+            return Err(format!("'{}' spans several lines — mark it secret to deliver it as a file", v.name));
+        }
+        out.push(v);
+    }
+    Ok(out)
+}
+
+/// Replace a scope's variables. An empty list removes the scope.
+pub fn set_scope(scope: &str, incoming: Vec<EnvVar>) -> Result<(), String> {
+    validate_scope(scope)?;
+    let mut store = load();
+    let merged = merge(store.scopes.get(scope).map(Vec::as_slice).unwrap_or(&[]), incoming)?;
+    if merged.is_empty() {
+        store.scopes.remove(scope);
+    } else {
+        store.scopes.insert(scope.to_string(), merged);
+    }
+    save(&store)
+}
+
+/// Forget a scope and the secret files written for it.
+pub fn delete_scope(scope: &str) -> Result<(), String> {
+    validate_scope(scope)?;
+    let mut store = load();
+    if store.scopes.remove(scope).is_none() {
+        return Err(format!("No stored environment for '{}'", scope));
+    }
+    save(&store)?;
+    let _ = std::fs::remove_dir_all(secrets_dir(scope));
+    Ok(())
+}
+
+/// Replace every `${secret:NAME}` / `${secret:scope/NAME}` in `s`.
+/// `lookup(scope, name)` returns the value; unknown references fail so a
+/// typo can't deploy a container with an empty password.
+fn resolve_str(s: &str, scope: &str, lookup: &dyn Fn(&str, &str) -> Option<String>) -> Result<String, String> {
+    const OPEN: &str = "${secret:";
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find(OPEN) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + OPEN.len()..];
+        let end = after.find('}').ok_or_else(|| format!("Unterminated secret reference in '{}'", s))?;
+        let reference = &after[..end];
+        let (ref_scope, name) = reference.split_once('/').unwrap_or((scope, reference));
+        let value = lookup(ref_scope, name).ok_or_else(|| format!("Unknown secret '{}'", reference))?;
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Resolve secret references in `KEY=value` env entries for a container in
+/// `scope`: its own stored variables first, then the Secrets Manager.
+pub fn resolve_refs(env: &[String], scope: &str) -> Result<Vec<String>, String> {
+    if !env.iter().any(|e| e.contains("${secret:")) {
+        return Ok(env.to_vec());
+    }
+    let store = load();
+    let lookup = |sc: &str, name: &str| -> Option<String> {
+        store.scopes.get(sc)
+            .and_then(|vs| vs.iter().find(|v| v.name == name))
+            .map(|v| v.value.clone())
+            .or_else(|| if sc == scope { crate::api::named_secret(name) } else { None })
+    };
+    env.iter().map(|e| resolve_str(e, scope, &lookup)).collect()
+}
+
+/// What injecting a scope adds to a `docker create`: env-file lines,
+/// `NAME_FILE=` variables and read-only secret mounts.
+#[derive(Debug, Default, PartialEq)]
+struct Plan {
+    env_lines: Vec<String>,
+    file_env: Vec<String>,
+    mounts: Vec<(String, String)>,
+    keys: Vec<String>,
+}
+
+fn plan(vars: &[EnvVar], dir: &Path) -> Plan {
+    let mut p = Plan::default();
+    for v in vars {
+        if v.secret {
+            let target = format!("{}/{}", SECRETS_MOUNT, v.name);
+            p.file_env.push(format!("{}_FILE={}", v.name, target));
+            p.mounts.push((dir.join(&v.name).to_string_lossy().into_owned(), target));
+            p.keys.push(format!("{}_FILE", v.name));
+        } else {
+            p.env_lines.push(format!("{}={}", v.name, v.value));
+            p.keys.push(v.name.clone());
+        }
+    }
+    p
+}
+
+/// Extra `docker create` arguments for a scope. Holds the temporary env
+/// file, which is removed when this is dropped — keep it alive until the
+/// docker command has run.
+#[derive(Default)]
+pub struct DockerInjection {
+    pub args: Vec<String>,
+    /// Variable names injected — drop these from any inline env so the
+    /// store's value is the one that applies.
+    pub keys: Vec<String>,
+    env_file: Option<PathBuf>,
+}
+
+impl Drop for DockerInjection {
+    fn drop(&mut self) {
+        if let Some(f) = &self.env_file {
+            let _ = std::fs::remove_file(f);
+        }
+    }
+}
+
+/// Write out a scope's values for `docker create`. An empty scope injects
+/// nothing.
+pub fn docker_injection(scope: &str) -> Result<DockerInjection, String> {
+    let vars = vars(scope);
+    if vars.is_empty() {
+        return Ok(DockerInjection::default());
+    }
+    let dir = secrets_dir(scope);
+    let p = plan(&vars, &dir);
+    let mut inj = DockerInjection { args: Vec::new(), keys: p.keys.clone(), env_file: None };
+    for v in vars.iter().filter(|v| v.secret) {
+        let path = dir.join(&v.name);
+        crate::paths::write_secure(&path.to_string_lossy(), &v.value)
+            .map_err(|e| format!("Failed to write secret file {}: {}", path.display(), e))?;
+    }
+    if !p.env_lines.is_empty() {
+        let file = std::env::temp_dir().join(format!("wolfstack-env-{}", uuid::Uuid::new_v4()));
+        crate::paths::write_secure(&file.to_string_lossy(), p.env_lines.join("\n") + "\n")
+            .map_err(|e| format!("Failed to write env file: {}", e))?;
+        inj.args.push("--env-file".to_string());
+        inj.args.push(file.to_string_lossy().into_owned());
+        inj.env_file = Some(file);
+    }
+    for e in &p.file_env {
+        inj.args.push("-e".to_string());
+        inj.args.push(e.clone());
+    }
+    for (src, target) in &p.mounts {
+        inj.args.push("-v".to_string());
+        inj.args.push(format!("{}:{}:ro", src, target));
+    }
+    inj.args.push("--label".to_string());
+    inj.args.push(format!("{}={}", INJECTED_LABEL, p.keys.join(",")));
+    Ok(inj)
+}
+
+/// Drop `KEY=…` entries for the given names.
+pub fn without_keys(env: &[String], keys: &[String]) -> Vec<String> {
+    env.iter()
+        .filter(|e| !keys.iter().any(|k| e.split_once('=').map_or(e.as_str(), |(n, _)| n) == k))
+        .cloned()
+        .collect()
+}
+
+/// Before a recreate: remove what a previous injection put into the
+/// container's env, binds and labels, so the new injection starts clean.
+pub fn strip_injected(env: &mut Vec<String>, binds: &mut Vec<String>, labels: &mut Vec<(String, String)>) {
+    let Some(pos) = labels.iter().position(|(k, _)| k == INJECTED_LABEL) else { return };
+    let (_, list) = labels.remove(pos);
+    let keys: Vec<String> = list.split(',').filter(|k| !k.is_empty()).map(str::to_string).collect();
+    *env = without_keys(env, &keys);
+    let root = crate::paths::get().container_secrets_dir;
+    binds.retain(|b| !(b.starts_with(&format!("{}/", root)) && b.contains(&format!(":{}/", SECRETS_MOUNT))));
+}
+
+/// A compose stack's stored variables as process environment for
+/// `docker compose`. Secret or not, compose interpolates them as `${NAME}`.
+pub fn compose_env(stack: &str) -> Vec<(String, String)> {
+    vars(stack).into_iter().map(|v| (v.name, v.value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str, value: &str, secret: bool) -> EnvVar {
+        EnvVar { name: name.into(), value: value.into(), secret }
+    }
+
+    #[test]
+    fn merge_keeps_blank_secrets_and_validates() {
+        let existing = vec![var("DB_PASSWORD", "hunter2", true)];
+        let merged = merge(&existing, vec![var("DB_PASSWORD", "", true), var(" TZ ", "UTC", false)]).unwrap();
+        assert_eq!(merged, vec![var("DB_PASSWORD", "hunter2", true), var("TZ", "UTC", false)]);
+        assert!(merge(&[], vec![var("API_KEY", "", true)]).is_err());
+        assert!(merge(&[], vec![var("1BAD", "x", false)]).is_err());
+        assert!(merge(&[], vec![var("A", "x", false), var("A", "y", false)]).is_err());
+        assert!(merge(&[], vec![var("PEM", "a\nb", false)]).is_err());
+        assert!(merge(&[], vec![var("PEM", "a\nb", true)]).is_ok());
+    }
+
+    #[test]
+    fn references_resolve_by_scope() {
+        let lookup = |scope: &str, name: &str| match (scope, name) {
+            ("web", "DB_PASSWORD") => Some("hunter2".to_string()),
+            ("shared", "SMTP") => Some("mail:25".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            resolve_str("postgres://app:${secret:DB_PASSWORD}@db/${secret:shared/SMTP}", "web", &lookup).unwrap(),
+            "postgres://app:hunter2@db/mail:25"
+        );
+        assert_eq!(resolve_str("TZ=UTC", "web", &lookup).unwrap(), "TZ=UTC");
+        assert!(resolve_str("X=${secret:NOPE}", "web", &lookup).is_err());
+        assert!(resolve_str("X=${secret:DB_PASSWORD", "web", &lookup).is_err());
+    }
+
+    #[test]
+    fn plan_splits_env_and_files() {
+        let p = plan(&[var("TZ", "UTC", false), var("DB_PASSWORD", "hunter2", true)], Path::new("/etc/wolfstack/container-secrets/web"));
+        assert_eq!(p.env_lines, vec!["TZ=UTC"]);
+        assert_eq!(p.file_env, vec!["DB_PASSWORD_FILE=/run/secrets/DB_PASSWORD"]);
+        assert_eq!(p.mounts, vec![("/etc/wolfstack/container-secrets/web/DB_PASSWORD".to_string(), "/run/secrets/DB_PASSWORD".to_string())]);
+        assert_eq!(p.keys, vec!["TZ", "DB_PASSWORD_FILE"]);
+        let env = vec!["TZ=Europe/London".to_string(), "PATH=/bin".to_string()];
+        assert_eq!(without_keys(&env, &p.keys), vec!["PATH=/bin"]);
+    }
+}
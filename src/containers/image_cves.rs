@@ -0,0 +1,420 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Known CVEs per Docker image, from `trivy image`.
+//!
+//! The predictive vulnerability analyzer already counts HIGH/CRITICAL
+//! findings for the images of *running* containers, but only as a number
+//! on a proposal, and it rescans on every tick. This keeps a per-image
+//! record instead — every local image, running or not, with the critical
+//! CVE IDs themselves — cached in `/etc/wolfstack/image-cves.json` and
+//! keyed by image ID, so an image is rescanned only when it changes or its
+//! scan is older than [`RESCAN_HOURS`] (trivy's DB moves daily).
+//!
+//! The Docker Images table shows the counts, and the daily report gets a
+//! cluster summary built from each node's `scope=local` report.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+const CACHE_FILE: &str = "/etc/wolfstack/image-cves.json";
+/// A scan older than this is redone on the next refresh.
+pub const RESCAN_HOURS: i64 = 24;
+/// One image scan. Longer than the analyzer's budget — nothing waits on
+/// this, and a cold trivy DB download can take a minute on its own.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(300);
+/// Critical CVEs kept per image; the counts stay exact.
+const MAX_LISTED: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cve {
+    pub id: String,
+    #[serde(default)]
+    pub package: String,
+    #[serde(default)]
+    pub installed: String,
+    /// Empty when no fixed version has been released.
+    #[serde(default)]
+    pub fixed: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageScan {
+    /// `repository:tag`, or the ID for an untagged image.
+    pub image: String,
+    pub image_id: String,
+    /// RFC 3339.
+    pub scanned_at: String,
+    #[serde(default)]
+    pub critical: u32,
+    #[serde(default)]
+    pub high: u32,
+    /// The CRITICAL findings, at most [`MAX_LISTED`].
+    #[serde(default)]
+    pub cves: Vec<Cve>,
+    /// Set when trivy failed; the counts are then meaningless.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// This node's images, as served by `scope=local`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageCveReport {
+    #[serde(default)]
+    pub trivy_available: bool,
+    #[serde(default)]
+    pub scanning: bool,
+    #[serde(default)]
+    pub images: Vec<ImageScan>,
+}
+
+/// image ID → last scan.
+static CACHE: LazyLock<Mutex<HashMap<String, ImageScan>>> = LazyLock::new(|| {
+    let map = std::fs::read_to_string(CACHE_FILE)
+        .ok()
+        .and_then(|d| serde_json::from_str(&d).ok())
+        .unwrap_or_default();
+    Mutex::new(map)
+});
+
+static SCANNING: AtomicBool = AtomicBool::new(false);
+
+/// Clears [`SCANNING`] however the refresh ends.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        SCANNING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn save(cache: &HashMap<String, ImageScan>) {
+    let _ = std::fs::create_dir_all("/etc/wolfstack");
+    match serde_json::to_string(cache) {
+        Ok(json) => if let Err(e) = std::fs::write(CACHE_FILE, json) {
+            warn!("image_cves: failed to write {}: {}", CACHE_FILE, e);
+        },
+        Err(e) => warn!("image_cves: failed to serialise cache: {}", e),
+    }
+}
+
+/// Severity counts and the critical CVEs from `trivy image --format json`.
+/// Same defensive reading as the analyzer's counter: anything unexpected
+/// is no findings, never an error. A CVE that trivy reports for several
+/// targets in the image (say the OS layer and a bundled binary) is counted
+/// once per package.
+pub fn parse_trivy(text: &str) -> (u32, u32, Vec<Cve>) {
+    let Ok(v) = serde_json::from_str::<serde_json::Value>(text) else { return (0, 0, Vec::new()) };
+    let mut seen = std::collections::HashSet::new();
+    let (mut critical, mut high) = (0u32, 0u32);
+    let mut cves = Vec::new();
+    let str_of = |v: &serde_json::Value, k: &str| v.get(k).and_then(|s| s.as_str()).unwrap_or("").to_string();
+    for res in v.get("Results").and_then(|r| r.as_array()).into_iter().flatten() {
+        for vuln in res.get("Vulnerabilities").and_then(|x| x.as_array()).into_iter().flatten() {
+            let cve = Cve {
+                id: str_of(vuln, "VulnerabilityID"),
+                package: str_of(vuln, "PkgName"),
+                installed: str_of(vuln, "InstalledVersion"),
+                fixed: str_of(vuln, "FixedVersion"),
+                title: str_of(vuln, "Title"),
+            };
+            if !seen.insert((cve.id.clone(), cve.package.clone())) {
+                continue;
+            }
+            match vuln.get("Severity").and_then(|s| s.as_str()) {
+                Some("CRITICAL") => {
+                    critical += 1;
+                    if cves.len() < MAX_LISTED {
+                        cves.push(cve);
+                    }
+                }
+                Some("HIGH") => high += 1,
+                _ => {}
+            }
+        }
+    }
+    (critical, high, cves)
+}
+
+fn image_ref(img: &super::ContainerImage) -> String {
+    if img.repository.is_empty() || img.repository == "<none>" {
+        img.id.clone()
+    } else if img.tag.is_empty() || img.tag == "<none>" {
+        img.repository.clone()
+    } else {
+        format!("{}:{}", img.repository, img.tag)
+    }
+}
+
+fn scan_one(image: &str, image_id: &str) -> ImageScan {
+    let args = [
+        "image", "--quiet", "--format", "json",
+        "--scanners", "vuln", "--severity", "HIGH,CRITICAL", image,
+    ];
+    let mut scan = ImageScan {
+        image: image.to_string(),
+        image_id: image_id.to_string(),
+        scanned_at: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+    match crate::predictive::vulnerability::run_capped("trivy", &args, SCAN_TIMEOUT) {
+        Some(text) => (scan.critical, scan.high, scan.cves) = parse_trivy(&text),
+        None => {
+            warn!("image_cves: trivy scan of '{}' failed or timed out", image);
+            scan.error = Some("trivy scan failed or timed out".into());
+        }
+    }
+    scan
+}
+
+fn is_stale(scan: &ImageScan, now: chrono::DateTime<chrono::Utc>) -> bool {
+    scan.error.is_some()
+        || chrono::DateTime::parse_from_rfc3339(&scan.scanned_at)
+            .map(|t| now.signed_duration_since(t) > chrono::Duration::hours(RESCAN_HOURS))
+            .unwrap_or(true)
+}
+
+/// Scan every local image whose cached result is missing or stale (all of
+/// them with `force`), and drop cache entries for images that are gone.
+/// Blocking — one trivy run per image. Returns how many were scanned.
+pub fn refresh(force: bool) -> usize {
+    if !crate::predictive::vulnerability::trivy_available() {
+        return 0;
+    }
+    if SCANNING.swap(true, Ordering::SeqCst) {
+        return 0;
+    }
+    let _running = Running;
+    let images = super::docker_images();
+    if images.is_empty() {
+        // Docker down or unreachable — keep the cache rather than forget it.
+        return 0;
+    }
+    let now = chrono::Utc::now();
+    let todo: Vec<(String, String)> = {
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let before = cache.len();
+        cache.retain(|id, _| images.iter().any(|i| &i.id == id));
+        if cache.len() != before {
+            save(&cache);
+        }
+        let mut seen = std::collections::HashSet::new();
+        images.iter()
+            .filter(|i| seen.insert(i.id.clone()))
+            .filter(|i| force || cache.get(&i.id).is_none_or(|s| is_stale(s, now)))
+            .map(|i| (image_ref(i), i.id.clone()))
+            .collect()
+    };
+    for (image, id) in &todo {
+        let scan = scan_one(image, id);
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(id.clone(), scan);
+        save(&cache);
+    }
+    if !todo.is_empty() {
+        info!("image_cves: scanned {} image(s)", todo.len());
+    }
+    todo.len()
+}
+
+/// Whether a refresh is running right now.
+pub fn scanning() -> bool {
+    SCANNING.load(Ordering::SeqCst)
+}
+
+/// This node's cached results, worst first. Never runs trivy.
+pub fn local_report() -> ImageCveReport {
+    let mut images: Vec<ImageScan> = CACHE.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect();
+    images.sort_by(|a, b| (b.critical, b.high).cmp(&(a.critical, a.high)).then_with(|| a.image.cmp(&b.image)));
+    ImageCveReport {
+        trivy_available: crate::predictive::vulnerability::trivy_available(),
+        scanning: scanning(),
+        images,
+    }
+}
+
+/// A node's section of the cluster summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeImageCves {
+    pub cluster: String,
+    pub node: String,
+    #[serde(default)]
+    pub report: ImageCveReport,
+    /// Why there's no report (unreachable, older version).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// This node's report plus each online WolfStack peer's own `scope=local`
+/// report, fetched with the cluster secret.
+pub async fn collect_cluster(state: &actix_web::web::Data<crate::api::AppState>) -> Vec<NodeImageCves> {
+    let cluster_of = |n: &crate::agent::Node| n.cluster_name.clone().unwrap_or_else(|| "Default".to_string());
+    let nodes: Vec<crate::agent::Node> = state.cluster.get_all_nodes().into_iter()
+        .filter(|n| n.node_type == "wolfstack" && (n.is_self || n.has_docker))
+        .collect();
+    let secret = state.cluster_secret.clone();
+    let handles: Vec<_> = nodes.into_iter().map(|n| {
+        let secret = secret.clone();
+        let cluster = cluster_of(&n);
+        tokio::spawn(async move {
+            let mut entry = NodeImageCves { cluster, node: n.hostname.clone(), report: ImageCveReport::default(), error: None };
+            if n.is_self {
+                entry.report = tokio::task::spawn_blocking(local_report).await.unwrap_or_default();
+                return entry;
+            }
+            if !n.online {
+                entry.error = Some("offline".to_string());
+                return entry;
+            }
+            for url in crate::api::build_node_urls(&n.address, n.port, "/api/containers/docker/cves?scope=local") {
+                let resp = crate::api::API_HTTP_CLIENT.get(&url)
+                    .timeout(std::time::Duration::from_secs(10))
+                    .header("X-WolfStack-Secret", &secret)
+                    .send().await;
+                if let Ok(resp) = resp
+                    && resp.status().is_success()
+                    && let Ok(report) = resp.json::<ImageCveReport>().await
+                {
+                    entry.report = report;
+                    return entry;
+                }
+            }
+            entry.error = Some("no CVE data (unreachable or older WolfStack)".to_string());
+            entry
+        })
+    }).collect();
+    let mut out = Vec::new();
+    for h in handles {
+        if let Ok(entry) = h.await {
+            out.push(entry);
+        }
+    }
+    out.sort_by(|a, b| (a.cluster.as_str(), a.node.as_str()).cmp(&(b.cluster.as_str(), b.node.as_str())));
+    out
+}
+
+/// Images with at least one critical CVE, worst first.
+fn flagged(nodes: &[NodeImageCves]) -> Vec<(&NodeImageCves, &ImageScan)> {
+    let mut rows: Vec<(&NodeImageCves, &ImageScan)> = nodes.iter()
+        .flat_map(|n| n.report.images.iter().filter(|i| i.error.is_none() && i.critical > 0).map(move |i| (n, i)))
+        .collect();
+    rows.sort_by_key(|r| std::cmp::Reverse((r.1.critical, r.1.high)));
+    rows
+}
+
+fn top_ids(scan: &ImageScan, n: usize) -> String {
+    let mut ids: Vec<&str> = scan.cves.iter().map(|c| c.id.as_str()).collect();
+    ids.dedup();
+    let more = ids.len().saturating_sub(n);
+    let mut s = ids.into_iter().take(n).collect::<Vec<_>>().join(", ");
+    if more > 0 {
+        s.push_str(&format!(" +{} more", more));
+    }
+    s
+}
+
+/// "Docker image vulnerabilities" section for the daily report email.
+/// Empty when no node has trivy or any scanned image.
+pub fn email_section_html(nodes: &[NodeImageCves]) -> String {
+    use crate::daily_report::escape;
+    let scanned: usize = nodes.iter().map(|n| n.report.images.len()).sum();
+    if scanned == 0 {
+        return String::new();
+    }
+    let rows = flagged(nodes);
+    let mut html = format!(
+        r#"<h2>Docker Image Vulnerabilities</h2><p class="meta" style="margin-top:-6px;">trivy scans of {} image(s) &bull; {} with critical CVEs</p>"#,
+        scanned, rows.len()
+    );
+    if rows.is_empty() {
+        html.push_str(r#"<p class="meta">No image has a known critical CVE.</p>"#);
+        return html;
+    }
+    html.push_str(r#"<table><thead><tr><th>Image</th><th>Node</th><th>Critical</th><th>High</th><th>CVEs</th></tr></thead><tbody>"#);
+    for (n, i) in rows {
+        html.push_str(&format!(
+            r#"<tr><td><strong>{}</strong></td><td class="meta">{}<div>{}</div></td><td style="color:#dc2626;font-weight:600;">{}</td><td>{}</td><td class="meta" style="font-size:10px;">{}</td></tr>"#,
+            escape(&i.image), escape(&n.node), escape(&n.cluster), i.critical, i.high, escape(&top_ids(i, 5))
+        ));
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+/// Plain-text counterpart of [`email_section_html`].
+pub fn email_section_text(nodes: &[NodeImageCves]) -> String {
+    if nodes.iter().all(|n| n.report.images.is_empty()) {
+        return String::new();
+    }
+    let rows = flagged(nodes);
+    let mut out = "\n== Docker image vulnerabilities (trivy) ==\n".to_string();
+    if rows.is_empty() {
+        out.push_str("  No image has a known critical CVE.\n");
+    }
+    for (n, i) in rows {
+        out.push_str(&format!(
+            "  {} on {} — {} critical, {} high: {}\n",
+            i.image, n.node, i.critical, i.high, top_ids(i, 5)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trivy_json() {
+        let json = r#"{"Results":[
+            {"Target":"debian 12","Vulnerabilities":[
+                {"VulnerabilityID":"CVE-2024-1","PkgName":"openssl","InstalledVersion":"3.0.1","FixedVersion":"3.0.2","Severity":"CRITICAL","Title":"bad"},
+                {"VulnerabilityID":"CVE-2024-2","PkgName":"zlib","InstalledVersion":"1.2","Severity":"HIGH"}]},
+            {"Target":"usr/bin/app","Vulnerabilities":[
+                {"VulnerabilityID":"CVE-2024-1","PkgName":"openssl","Severity":"CRITICAL"}]},
+            {"Target":"clean","Vulnerabilities":null}]}"#;
+        let (critical, high, cves) = parse_trivy(json);
+        assert_eq!((critical, high), (1, 1));
+        assert_eq!(cves[0], Cve { id: "CVE-2024-1".into(), package: "openssl".into(),
+            installed: "3.0.1".into(), fixed: "3.0.2".into(), title: "bad".into() });
+        assert_eq!(parse_trivy("not json"), (0, 0, Vec::new()));
+    }
+
+    #[test]
+    fn stale_after_a_day_or_a_failure() {
+        let now = chrono::Utc::now();
+        let mut scan = ImageScan { scanned_at: (now - chrono::Duration::hours(2)).to_rfc3339(), ..Default::default() };
+        assert!(!is_stale(&scan, now));
+        scan.scanned_at = (now - chrono::Duration::hours(RESCAN_HOURS + 1)).to_rfc3339();
+        assert!(is_stale(&scan, now));
+        scan.scanned_at = now.to_rfc3339();
+        scan.error = Some("timeout".into());
+        assert!(is_stale(&scan, now));
+    }
+
+    #[test]
+    fn summary_lists_only_critical_images() {
+        let img = |name: &str, critical| ImageScan {
+            image: name.into(), critical,
+            cves: (0..critical).map(|i| Cve { id: format!("CVE-{}", i), ..Default::default() }).collect(),
+            ..Default::default()
+        };
+        let nodes = vec![NodeImageCves {
+            cluster: "Default".into(), node: "n1".into(), error: None,
+            report: ImageCveReport { trivy_available: true, scanning: false,
+                images: vec![img("nginx:1", 0), img("redis:6", 7), img("pg:9", 2)] },
+        }];
+        let rows = flagged(&nodes);
+        assert_eq!(rows.iter().map(|r| r.1.image.as_str()).collect::<Vec<_>>(), ["redis:6", "pg:9"]);
+        assert_eq!(top_ids(rows[0].1, 5), "CVE-0, CVE-1, CVE-2, CVE-3, CVE-4 +2 more");
+        assert!(email_section_text(&nodes).contains("redis:6 on n1 — 7 critical"));
+        assert!(email_section_html(&[]).is_empty());
+    }
+}
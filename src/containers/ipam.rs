@@ -0,0 +1,923 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Cluster-wide WolfNet IP address management.
+//!
+//! The allocator used to pick the lowest address missing from this node's
+//! view of the cluster, so two nodes creating containers at the same moment
+//! could hand out the same IP before either showed up in the other's route
+//! cache. Allocation now goes through a ledger:
+//!
+//! - **Leases** — every WolfNet IP a node has handed out or has in use,
+//!   keyed by address and owned by that node. A lease starts pending and
+//!   is bound once the address turns up on a workload; a pending lease
+//!   nobody used expires after [`PENDING_TTL_SECS`].
+//! - **Reservations** — addresses kept out of allocation (a printer, a VIP
+//!   planned for later…).
+//! - **Pools** — the allocatable range of each WolfNet subnet. Without one
+//!   the whole /24 is used, minus .1, .254 and .255.
+//! - **Overlay subnets** — extra /24s beside the one wolfnet0 sits on, each
+//!   assigned to a cluster or tenant whose new workloads are addressed from
+//!   it. The primary subnet stays shared (the nodes themselves live there);
+//!   an isolated overlay subnet is cut off from the other overlay subnets.
+//!
+//! One node hands out addresses for the whole cluster — the online
+//! WolfStack node with the lowest node id — so concurrent creates are
+//! serialised through its ledger lock. The others ask it over the
+//! inter-node API and only allocate for themselves (the old, racy way) when
+//! it can't be reached. Each node pushes its own leases to its peers and
+//! reservations, pools and overlay subnets are replicated on edit, so every
+//! ledger converges and any node can take over as allocator.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::agent::{ClusterState, Node};
+
+/// How long a handed-out address stays reserved before it's used.
+pub const PENDING_TTL_SECS: u64 = 1800;
+/// A lease granted this recently survives a push from its owner that
+/// doesn't list it yet — the push may have left before the grant landed.
+const GRANT_GRACE_SECS: u64 = 120;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn ledger_path() -> String {
+    format!("{}/wolfnet-ipam.json", crate::paths::get().config_dir)
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaseState {
+    /// Handed out, not seen on a workload yet.
+    Pending,
+    /// In use on the owning node.
+    Bound,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lease {
+    /// Node id of the owner.
+    pub node: String,
+    pub state: LeaseState,
+    /// Unix seconds.
+    pub granted_at: u64,
+}
+
+impl Lease {
+    fn expired(&self, now: u64) -> bool {
+        self.state == LeaseState::Pending && now >= self.granted_at + PENDING_TTL_SECS
+    }
+
+    /// Which of two nodes' leases on one address stands: the older one,
+    /// then the lower node id, so every ledger settles the same way.
+    fn beats(&self, other: &Lease) -> bool {
+        (self.granted_at, &self.node) < (other.granted_at, &other.node)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reservation {
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// Allocatable host range (last octet, inclusive) of one WolfNet /24.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pool {
+    pub start: u8,
+    pub end: u8,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Pool { start: 2, end: 253 }
+    }
+}
+
+impl Pool {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start == 0 || self.end == 255 || self.start > self.end {
+            return Err(format!("Pool range .{}-.{} must lie within .1-.254", self.start, self.end));
+        }
+        Ok(())
+    }
+}
+
+/// An overlay /24 beside wolfnet0's own.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OverlaySubnet {
+    /// Cluster whose new workloads are addressed from here; empty for none.
+    #[serde(default)]
+    pub cluster: String,
+    /// Tenant whose new workloads are addressed from here. Wins over a
+    /// cluster assignment.
+    #[serde(default)]
+    pub tenant: String,
+    /// No traffic between this subnet and the other overlay subnets. The
+    /// primary subnet stays reachable.
+    #[serde(default)]
+    pub isolated: bool,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Cluster-wide IPAM settings, replicated whole on every edit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IpamConfig {
+    /// Keyed by subnet prefix ("10.10.10").
+    #[serde(default)]
+    pub pools: BTreeMap<String, Pool>,
+    /// Keyed by address.
+    #[serde(default)]
+    pub reservations: BTreeMap<String, Reservation>,
+    /// Extra overlay subnets, keyed by prefix.
+    #[serde(default)]
+    pub subnets: BTreeMap<String, OverlaySubnet>,
+    /// Unix seconds of the last edit; the newest copy wins on sync.
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    #[serde(default)]
+    pub config: IpamConfig,
+    /// Keyed by address.
+    #[serde(default)]
+    pub leases: BTreeMap<String, Lease>,
+}
+
+impl Ledger {
+    /// An empty ledger when there's no file yet. A file that can't be
+    /// read or parsed is an error, not an empty ledger — saving over it
+    /// would forget every lease and reservation in the cluster.
+    pub fn load() -> Result<Self, String> {
+        let path = ledger_path();
+        match std::fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| format!("{} is corrupt: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path, e)),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = ledger_path();
+        if let Some(dir) = Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+}
+
+/// Serialises every load-modify-save of the ledger on this node — and so,
+/// on the allocator, every allocation in the cluster.
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+fn with_ledger<T>(f: impl FnOnce(&mut Ledger) -> T) -> Result<T, String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = Ledger::load()?;
+    let before = serde_json::to_value(&ledger).ok();
+    let out = f(&mut ledger);
+    if serde_json::to_value(&ledger).ok() != before {
+        ledger.save()?;
+    }
+    Ok(out)
+}
+
+// ─── Cluster ───
+
+struct Cluster {
+    state: Arc<ClusterState>,
+    secret: String,
+}
+
+static CLUSTER: OnceLock<Cluster> = OnceLock::new();
+
+/// Hook the allocator up to the cluster (call from main.rs). Until then —
+/// and in the CLI — allocation is local only.
+pub fn init(cluster: Arc<ClusterState>, cluster_secret: &str) {
+    let _ = CLUSTER.set(Cluster { state: cluster, secret: cluster_secret.to_string() });
+}
+
+/// The id a node goes by in lease records: its own node id.
+fn node_key(node: &Node) -> String {
+    node.self_id.clone().unwrap_or_else(|| node.id.clone())
+}
+
+fn self_key() -> String {
+    CLUSTER.get().map(|c| c.state.self_id.clone()).unwrap_or_default()
+}
+
+/// Same-cluster WolfStack peers that completed the join handshake.
+fn peers(c: &Cluster) -> Vec<Node> {
+    let self_cluster = c.state.get_self_cluster_name();
+    c.state.get_all_nodes().into_iter()
+        .filter(|n| !n.is_self && n.node_type == "wolfstack" && n.join_verified)
+        .filter(|n| n.cluster_name.as_deref().unwrap_or("WolfStack") == self_cluster)
+        .collect()
+}
+
+/// The peer that hands out addresses, or None when it's this node.
+fn allocator(c: &Cluster) -> Option<Node> {
+    let me = c.state.self_id.clone();
+    peers(c).into_iter()
+        .filter(|n| n.online && node_key(n) < me)
+        .min_by_key(node_key)
+}
+
+fn http_client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+// ─── Subnets ───
+
+/// The /24 prefix of an address — `10.10.20.5` → `10.10.20`.
+fn prefix_of(ip: &str) -> Option<String> {
+    let addr: std::net::Ipv4Addr = ip.split('/').next()?.trim().parse().ok()?;
+    let o = addr.octets();
+    Some(format!("{}.{}.{}", o[0], o[1], o[2]))
+}
+
+/// Which subnet a new workload goes on: the tenant's overlay subnet, else
+/// the cluster's, else the primary.
+fn choose_subnet(config: &IpamConfig, primary: &str, cluster: &str, tenant: Option<&str>) -> String {
+    let tenant = tenant.unwrap_or("");
+    let by_tenant = config.subnets.iter().find(|(_, s)| !tenant.is_empty() && s.tenant == tenant);
+    let by_cluster = || config.subnets.iter().find(|(_, s)| s.tenant.is_empty() && !s.cluster.is_empty() && s.cluster == cluster);
+    by_tenant.or_else(by_cluster)
+        .map(|(prefix, _)| prefix.clone())
+        .unwrap_or_else(|| primary.to_string())
+}
+
+/// The subnet prefix new workloads of `tenant` (None: unscoped) on this
+/// node are addressed from. None when WolfNet isn't configured.
+pub fn subnet_for(tenant: Option<&str>) -> Result<Option<String>, String> {
+    let Some(primary) = super::wolfnet_subnet_prefix() else { return Ok(None) };
+    let cluster = CLUSTER.get().map(|c| c.state.get_self_cluster_name()).unwrap_or_default();
+    Ok(Some(choose_subnet(&Ledger::load()?.config, &primary, &cluster, tenant)))
+}
+
+pub fn overlay_subnets() -> Result<BTreeMap<String, OverlaySubnet>, String> {
+    Ok(Ledger::load()?.config.subnets)
+}
+
+/// Every WolfNet subnet prefix in use: the primary, then the overlay
+/// subnets. Empty when WolfNet isn't configured.
+pub fn overlay_prefixes() -> Vec<String> {
+    let Some(primary) = super::wolfnet_subnet_prefix() else { return Vec::new() };
+    let mut prefixes = vec![primary];
+    let subnets = overlay_subnets().unwrap_or_else(|e| {
+        warn!("WolfNet IPAM: {}", e);
+        BTreeMap::new()
+    });
+    for prefix in subnets.into_keys() {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+/// The /24s a workload at `ip` routes over WolfNet, its own first: its
+/// subnet, the primary, and — unless either side is isolated — the other
+/// overlay subnets.
+fn routes_for(config: &IpamConfig, primary: &str, ip: &str) -> Vec<String> {
+    let own = prefix_of(ip).unwrap_or_else(|| primary.to_string());
+    let isolated = config.subnets.get(&own).is_some_and(|s| s.isolated);
+    let mut prefixes = vec![own.clone()];
+    if own != primary {
+        prefixes.push(primary.to_string());
+    }
+    if !isolated {
+        prefixes.extend(config.subnets.iter()
+            .filter(|(prefix, s)| **prefix != own && **prefix != primary && !s.isolated)
+            .map(|(prefix, _)| prefix.clone()));
+    }
+    prefixes.into_iter().map(|p| format!("{}.0/24", p)).collect()
+}
+
+/// The WolfNet routes to install inside a workload at `ip`.
+pub fn container_routes(ip: &str) -> Vec<String> {
+    let own = || prefix_of(ip).map(|p| vec![format!("{}.0/24", p)]).unwrap_or_default();
+    let Some(primary) = super::wolfnet_subnet_prefix() else { return own() };
+    match Ledger::load() {
+        Ok(ledger) => routes_for(&ledger.config, &primary, ip),
+        Err(e) => {
+            warn!("WolfNet IPAM: {}", e);
+            own()
+        }
+    }
+}
+
+/// (source, destination) prefix pairs the FORWARD chain drops: each
+/// isolated subnet against every other overlay subnet, both ways.
+pub fn isolation_pairs(subnets: &BTreeMap<String, OverlaySubnet>) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for (a, sa) in subnets {
+        for (b, sb) in subnets {
+            if a != b && (sa.isolated || sb.isolated) {
+                pairs.push((a.clone(), b.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+// ─── Allocation ───
+
+/// Pick `count` free addresses from `prefix`'s pool — never-used ones
+/// first, then released ones — skipping `used`, live leases and
+/// reservations. None when the pool can't supply them all.
+fn pick(ledger: &Ledger, prefix: &str, used: &HashSet<String>, history: &HashSet<String>,
+        count: usize, now: u64) -> Option<Vec<String>> {
+    let pool = ledger.config.pools.get(prefix).copied().unwrap_or_default();
+    let free: Vec<String> = (pool.start..=pool.end)
+        .map(|i| format!("{}.{}", prefix, i))
+        .filter(|ip| !used.contains(ip) && !ledger.config.reservations.contains_key(ip))
+        .filter(|ip| ledger.leases.get(ip).is_none_or(|l| l.expired(now)))
+        .collect();
+    let mut out: Vec<String> = free.iter().filter(|ip| !history.contains(*ip)).take(count).cloned().collect();
+    let short = count - out.len();
+    out.extend(free.iter().filter(|ip| history.contains(*ip)).take(short).cloned());
+    (out.len() == count).then_some(out)
+}
+
+/// Allocate from this node's ledger and lease the addresses to `node`.
+fn grant(node: &str, count: usize, prefix: &str, used: &HashSet<String>) -> Result<Vec<String>, String> {
+    let history = super::load_wolfnet_ip_history();
+    with_ledger(|ledger| -> Result<Vec<String>, String> {
+        let now = now();
+        let ips = pick(ledger, prefix, used, &history, count, now)
+            .ok_or_else(|| format!("No free WolfNet addresses left in {}.0/24", prefix))?;
+        for ip in &ips {
+            ledger.leases.insert(ip.clone(), Lease { node: node.to_string(), state: LeaseState::Pending, granted_at: now });
+        }
+        Ok(ips)
+    })?
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrantRequest {
+    /// Requesting node's id; the lease goes to it.
+    pub node: String,
+    pub count: usize,
+    /// Subnet to allocate from: the allocator's primary or one of the
+    /// overlay subnets.
+    pub prefix: String,
+    /// Addresses the requester sees in use that the allocator may not.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrantResponse {
+    pub ips: Vec<String>,
+}
+
+/// Allocator side of a peer's request.
+pub fn grant_for_peer(req: GrantRequest) -> Result<Vec<String>, String> {
+    if req.count == 0 || req.count > 254 {
+        return Err(format!("Invalid address count {}", req.count));
+    }
+    let (primary, mut used) = super::wolfnet_used_ip_set().ok_or("WolfNet is not configured on the allocator")?;
+    let prefix = req.prefix;
+    if prefix != primary && !Ledger::load()?.config.subnets.contains_key(&prefix) {
+        return Err(format!("{}.0/24 is neither the allocator's subnet ({}.0/24) nor an overlay subnet", prefix, primary));
+    }
+    used.extend(req.exclude);
+    let ips = grant(&req.node, req.count, &prefix, &used)?;
+    push_in_background();
+    Ok(ips)
+}
+
+/// Ask the allocator for addresses. Runs on its own thread: callers include
+/// async handlers, where a blocking reqwest call would panic.
+fn request_grant(c: &'static Cluster, node: &Node, body: GrantRequest) -> Result<Vec<String>, String> {
+    let urls = crate::api::build_node_urls(&node.address, node.port, "/api/wolfnet/ipam/grant");
+    std::thread::spawn(move || {
+        let client = http_client()?;
+        let mut last = String::from("no address to try");
+        for url in urls {
+            match client.post(&url).header("X-WolfStack-Secret", &c.secret).json(&body).send() {
+                Ok(resp) if resp.status().is_success() => {
+                    return resp.json::<GrantResponse>().map(|r| r.ips)
+                        .map_err(|e| format!("bad response: {}", e));
+                }
+                Ok(resp) => last = format!("HTTP {}", resp.status()),
+                Err(e) => last = e.to_string(),
+            }
+        }
+        Err(last)
+    }).join().unwrap_or_else(|_| Err("request thread panicked".into()))
+}
+
+/// Allocate `count` distinct WolfNet addresses for this node — through the
+/// cluster's allocator when there is one, locally otherwise — from the
+/// subnet [`subnet_for`] picks for `tenant`.
+pub fn allocate(count: usize, tenant: Option<&str>) -> Option<Vec<String>> {
+    if count == 0 {
+        return Some(Vec::new());
+    }
+    let (_, used) = super::wolfnet_used_ip_set()?;
+    let prefix = match subnet_for(tenant) {
+        Ok(prefix) => prefix?,
+        Err(e) => {
+            warn!("WolfNet IPAM: {}", e);
+            return None;
+        }
+    };
+    let me = self_key();
+    if let Some(c) = CLUSTER.get()
+        && let Some(node) = allocator(c)
+    {
+        let body = GrantRequest { node: me.clone(), count, prefix: prefix.clone(), exclude: used.iter().cloned().collect() };
+        match request_grant(c, &node, body) {
+            Ok(ips) if ips.len() == count => {
+                let now = now();
+                let recorded = with_ledger(|ledger| for ip in &ips {
+                    ledger.leases.insert(ip.clone(), Lease { node: me.clone(), state: LeaseState::Pending, granted_at: now });
+                });
+                if let Err(e) = recorded {
+                    warn!("WolfNet IPAM: could not record the lease on {:?}: {}", ips, e);
+                }
+                push_in_background();
+                return Some(ips);
+            }
+            Ok(ips) => warn!("WolfNet IPAM: allocator {} returned {} of {} addresses, allocating locally",
+                node.hostname, ips.len(), count),
+            Err(e) => warn!("WolfNet IPAM: allocator {} unavailable ({}), allocating locally", node.hostname, e),
+        }
+    }
+    match grant(&me, count, &prefix, &used) {
+        Ok(ips) => {
+            push_in_background();
+            Some(ips)
+        }
+        Err(e) => {
+            warn!("WolfNet IPAM: {}", e);
+            None
+        }
+    }
+}
+
+/// Addresses the ledger keeps from allocation: live leases and
+/// reservations. For allocators that don't go through [`allocate`].
+pub fn unavailable() -> Result<HashSet<String>, String> {
+    let ledger = Ledger::load()?;
+    let now = now();
+    Ok(ledger.leases.iter().filter(|(_, l)| !l.expired(now)).map(|(ip, _)| ip.clone())
+        .chain(ledger.config.reservations.keys().cloned())
+        .collect())
+}
+
+/// Lease an operator-chosen address to this node. Err when it's reserved
+/// or already leased to another node.
+pub fn claim(ip: &str) -> Result<(), String> {
+    let ip = ip.split('/').next().unwrap_or(ip).trim().to_string();
+    let me = self_key();
+    with_ledger(|ledger| -> Result<(), String> {
+        if let Some(r) = ledger.config.reservations.get(&ip) {
+            let why = if r.note.is_empty() { String::new() } else { format!(" ({})", r.note) };
+            return Err(format!("WolfNet IP {} is reserved{}", ip, why));
+        }
+        let now = now();
+        if let Some(lease) = ledger.leases.get(&ip)
+            && lease.node != me
+            && !lease.expired(now)
+        {
+            return Err(format!("WolfNet IP {} is already leased to node {}", ip, node_name(&lease.node)));
+        }
+        if ledger.leases.get(&ip).is_none_or(|l| l.node != me) {
+            ledger.leases.insert(ip.clone(), Lease { node: me.clone(), state: LeaseState::Pending, granted_at: now });
+        }
+        Ok(())
+    })??;
+    push_in_background();
+    Ok(())
+}
+
+/// Drop this node's lease on `ip` once its workload is gone.
+pub fn release(ip: &str) {
+    let me = self_key();
+    let released = with_ledger(|ledger| {
+        if ledger.leases.get(ip).is_some_and(|l| l.node == me) {
+            ledger.leases.remove(ip);
+            true
+        } else {
+            false
+        }
+    });
+    match released {
+        Ok(true) => push_in_background(),
+        Ok(false) => {}
+        Err(e) => warn!("WolfNet IPAM: could not release {}: {}", ip, e),
+    }
+}
+
+// ─── Sync ───
+
+/// What a node pushes to its peers: all of its own leases, plus the
+/// cluster settings as it has them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaseSync {
+    pub node: String,
+    #[serde(default)]
+    pub leases: BTreeMap<String, Lease>,
+    #[serde(default)]
+    pub config: Option<IpamConfig>,
+}
+
+/// Fold a peer's push into `ledger`: its leases replace whatever we held
+/// for it, conflicts go to the older lease, and its settings win when
+/// newer. Our own leases are never taken from a push.
+fn merge_into(ledger: &mut Ledger, sync: LeaseSync, me: &str, now: u64) {
+    if sync.node != me {
+        ledger.leases.retain(|_, l| l.node != sync.node
+            || (l.state == LeaseState::Pending && now < l.granted_at + GRANT_GRACE_SECS));
+        for (ip, lease) in sync.leases {
+            if lease.node != sync.node {
+                continue;
+            }
+            match ledger.leases.get(&ip) {
+                Some(held) if !lease.beats(held) => {
+                    warn!("WolfNet IPAM: {} is leased to both {} and {}; keeping {}", ip, held.node, lease.node, held.node);
+                }
+                _ => {
+                    ledger.leases.insert(ip, lease);
+                }
+            }
+        }
+    }
+    if let Some(config) = sync.config
+        && config.updated_at > ledger.config.updated_at
+    {
+        ledger.config = config;
+    }
+}
+
+/// Apply a push received from a peer.
+pub fn merge(sync: LeaseSync) -> Result<(), String> {
+    let me = self_key();
+    with_ledger(|ledger| merge_into(ledger, sync, &me, now()))
+}
+
+/// Push this node's leases and settings to every online peer.
+fn push(c: &Cluster) {
+    let me = c.state.self_id.clone();
+    let sync = match with_ledger(|ledger| LeaseSync {
+        node: me.clone(),
+        leases: ledger.leases.iter().filter(|(_, l)| l.node == me).map(|(ip, l)| (ip.clone(), l.clone())).collect(),
+        config: Some(ledger.config.clone()),
+    }) {
+        Ok(sync) => sync,
+        Err(e) => {
+            warn!("WolfNet IPAM: not pushing leases: {}", e);
+            return;
+        }
+    };
+    let peers: Vec<Node> = peers(c).into_iter().filter(|n| n.online).collect();
+    if peers.is_empty() {
+        return;
+    }
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("WolfNet IPAM: {}", e);
+            return;
+        }
+    };
+    for peer in peers {
+        let sent = crate::api::build_node_urls(&peer.address, peer.port, "/api/wolfnet/ipam/sync").iter().any(|url| {
+            client.post(url).header("X-WolfStack-Secret", &c.secret).json(&sync).send()
+                .is_ok_and(|r| r.status().is_success())
+        });
+        if !sent {
+            warn!("WolfNet IPAM: failed to push leases to {}", peer.hostname);
+        }
+    }
+}
+
+/// Push in the background after a local change.
+pub fn push_in_background() {
+    if let Some(c) = CLUSTER.get() {
+        std::thread::spawn(move || push(c));
+    }
+}
+
+/// Bring this node's leases in line with `in_use`, the addresses its
+/// workloads actually hold: bind pending leases that came into use, adopt
+/// in-use addresses nobody holds (workloads from before IPAM, migrations
+/// in), drop leases whose workload is gone and expired pending ones, and
+/// forget leases of nodes no longer in the cluster.
+fn reconcile_leases(ledger: &mut Ledger, me: &str, in_use: &HashSet<String>, known: &HashSet<String>, now: u64) {
+    ledger.leases.retain(|ip, l| {
+        if l.node != me {
+            return known.contains(&l.node);
+        }
+        match l.state {
+            LeaseState::Bound => in_use.contains(ip),
+            LeaseState::Pending => in_use.contains(ip) || !l.expired(now),
+        }
+    });
+    for ip in in_use {
+        match ledger.leases.get_mut(ip) {
+            Some(l) if l.node == me => l.state = LeaseState::Bound,
+            Some(_) => {}
+            None => {
+                ledger.leases.insert(ip.clone(), Lease { node: me.to_string(), state: LeaseState::Bound, granted_at: now });
+            }
+        }
+    }
+}
+
+/// Periodic pass (main.rs): reconcile this node's leases against what's
+/// running here, then push them to the peers.
+pub fn reconcile() {
+    let Some(c) = CLUSTER.get() else { return };
+    let in_use: HashSet<String> = super::wolfnet_used_ips().into_iter().collect();
+    // The node's own wolfnet0 address is always there when WolfNet is up;
+    // an empty list means it's down, not that every workload vanished.
+    if in_use.is_empty() {
+        return;
+    }
+    let me = c.state.self_id.clone();
+    let known: HashSet<String> = peers(c).iter().map(node_key).chain([me.clone()]).collect();
+    if let Err(e) = with_ledger(|ledger| reconcile_leases(ledger, &me, &in_use, &known, now())) {
+        warn!("WolfNet IPAM: {}", e);
+        return;
+    }
+    push(c);
+}
+
+// ─── Admin ───
+
+fn node_name(id: &str) -> String {
+    CLUSTER.get()
+        .and_then(|c| {
+            if c.state.self_id == id {
+                return c.state.get_node(id).map(|n| n.hostname);
+            }
+            c.state.get_all_nodes().into_iter().find(|n| node_key(n) == id).map(|n| n.hostname)
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Ledger view for the UI.
+pub fn overview() -> Result<serde_json::Value, String> {
+    let ledger = Ledger::load()?;
+    let prefix = super::wolfnet_subnet_prefix();
+    let allocator_name = CLUSTER.get()
+        .map(|c| allocator(c).map(|n| n.hostname).unwrap_or_else(|| node_name(&c.state.self_id)))
+        .unwrap_or_default();
+    let now = now();
+    let leases: Vec<serde_json::Value> = ledger.leases.iter()
+        .filter(|(_, l)| !l.expired(now))
+        .map(|(ip, l)| serde_json::json!({
+            "ip": ip,
+            "node": l.node,
+            "node_name": node_name(&l.node),
+            "state": l.state,
+            "granted_at": l.granted_at,
+        }))
+        .collect();
+    Ok(serde_json::json!({
+        "prefix": prefix,
+        "default_pool": Pool::default(),
+        "allocator": allocator_name,
+        "cluster": CLUSTER.get().map(|c| c.state.get_self_cluster_name()).unwrap_or_default(),
+        "pools": ledger.config.pools,
+        "reservations": ledger.config.reservations,
+        "subnets": ledger.config.subnets,
+        "leases": leases,
+    }))
+}
+
+/// Edit the cluster settings and replicate them.
+fn edit_config(f: impl FnOnce(&mut IpamConfig) -> Result<(), String>) -> Result<(), String> {
+    with_ledger(|ledger| -> Result<(), String> {
+        let mut config = ledger.config.clone();
+        f(&mut config)?;
+        config.updated_at = now().max(ledger.config.updated_at + 1);
+        ledger.config = config;
+        Ok(())
+    })??;
+    push_in_background();
+    Ok(())
+}
+
+fn parse_ipv4(ip: &str) -> Result<std::net::Ipv4Addr, String> {
+    ip.trim().parse().map_err(|_| format!("'{}' is not an IPv4 address", ip))
+}
+
+pub fn reserve(ip: &str, note: &str) -> Result<(), String> {
+    let ip = parse_ipv4(ip)?.to_string();
+    let me = self_key();
+    let leased_to = Ledger::load()?.leases.get(&ip)
+        .filter(|l| !l.expired(now()) && l.node != me)
+        .map(|l| node_name(&l.node));
+    if let Some(node) = leased_to {
+        return Err(format!("{} is leased to {} — release it there first", ip, node));
+    }
+    edit_config(|config| {
+        config.reservations.insert(ip.clone(), Reservation {
+            note: note.trim().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        });
+        Ok(())
+    })
+}
+
+pub fn unreserve(ip: &str) -> Result<(), String> {
+    edit_config(|config| config.reservations.remove(ip.trim()).map(|_| ())
+        .ok_or_else(|| format!("{} is not reserved", ip)))
+}
+
+fn check_prefix(prefix: &str) -> Result<(), String> {
+    let parts: Vec<&str> = prefix.split('.').collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.parse::<u8>().is_err()) {
+        return Err(format!("'{}' is not a /24 prefix like 10.10.10", prefix));
+    }
+    Ok(())
+}
+
+/// Set (`Some`) or clear (`None`) the pool of the subnet `prefix`.
+pub fn set_pool(prefix: &str, pool: Option<Pool>) -> Result<(), String> {
+    check_prefix(prefix)?;
+    if let Some(pool) = pool {
+        pool.validate()?;
+    }
+    edit_config(|config| {
+        match pool {
+            Some(pool) => config.pools.insert(prefix.to_string(), pool),
+            None => config.pools.remove(prefix),
+        };
+        Ok(())
+    })?;
+    info!("WolfNet IPAM: pool for {}.0/24 {}", prefix,
+        pool.map(|p| format!("set to .{}-.{}", p.start, p.end)).unwrap_or_else(|| "reset".into()));
+    Ok(())
+}
+
+/// Add or update (`Some`) or remove (`None`) the overlay subnet `prefix`.
+/// A subnet can't be removed while addresses in it are leased.
+pub fn set_subnet(prefix: &str, subnet: Option<OverlaySubnet>) -> Result<(), String> {
+    check_prefix(prefix)?;
+    if super::wolfnet_subnet_prefix().as_deref() == Some(prefix) {
+        return Err(format!("{}.0/24 is WolfNet's primary subnet", prefix));
+    }
+    let subnet = subnet.map(|s| OverlaySubnet {
+        cluster: s.cluster.trim().to_string(),
+        tenant: s.tenant.trim().to_string(),
+        isolated: s.isolated,
+        note: s.note.trim().to_string(),
+    });
+    if subnet.is_none() {
+        let dot = format!("{}.", prefix);
+        let now = now();
+        let leased = Ledger::load()?.leases.iter().filter(|(ip, l)| ip.starts_with(&dot) && !l.expired(now)).count();
+        if leased > 0 {
+            return Err(format!("{}.0/24 still has {} leased address(es) — move or delete those workloads first", prefix, leased));
+        }
+    }
+    edit_config(|config| {
+        if let Some(s) = &subnet {
+            let taken = config.subnets.iter().find(|(p, other)| p.as_str() != prefix
+                && ((!s.tenant.is_empty() && other.tenant == s.tenant)
+                    || (s.tenant.is_empty() && other.tenant.is_empty() && !s.cluster.is_empty() && other.cluster == s.cluster)));
+            if let Some((p, _)) = taken {
+                return Err(format!("{}.0/24 is already assigned to that {}", p,
+                    if s.tenant.is_empty() { "cluster" } else { "tenant" }));
+            }
+            config.subnets.insert(prefix.to_string(), s.clone());
+            return Ok(());
+        }
+        config.subnets.remove(prefix).ok_or_else(|| format!("{}.0/24 is not an overlay subnet", prefix))?;
+        config.pools.remove(prefix);
+        Ok(())
+    })?;
+    info!("WolfNet IPAM: overlay subnet {}.0/24 {}", prefix, if subnet.is_some() { "saved" } else { "removed" });
+    std::thread::spawn(super::apply_overlay_subnets);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(node: &str, state: LeaseState, granted_at: u64) -> Lease {
+        Lease { node: node.into(), state, granted_at }
+    }
+
+    fn set(ips: &[&str]) -> HashSet<String> {
+        ips.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn pick_skips_leases_reservations_and_prefers_fresh() {
+        let mut ledger = Ledger::default();
+        ledger.config.pools.insert("10.0.0".into(), Pool { start: 10, end: 15 });
+        ledger.config.reservations.insert("10.0.0.11".into(), Reservation::default());
+        ledger.leases.insert("10.0.0.12".into(), lease("b", LeaseState::Pending, 1000));
+        ledger.leases.insert("10.0.0.13".into(), lease("b", LeaseState::Pending, 0));
+        let used = set(&["10.0.0.10"]);
+        let history = set(&["10.0.0.13"]);
+        // .13's pending lease expired, but it has been used before, so the
+        // untouched .14/.15 go first.
+        assert_eq!(pick(&ledger, "10.0.0", &used, &history, 2, 2000).unwrap(), vec!["10.0.0.14", "10.0.0.15"]);
+        assert_eq!(pick(&ledger, "10.0.0", &used, &history, 3, 2000).unwrap(), vec!["10.0.0.14", "10.0.0.15", "10.0.0.13"]);
+        assert!(pick(&ledger, "10.0.0", &used, &history, 4, 2000).is_none());
+        // A different subnet gets the default pool.
+        assert_eq!(pick(&ledger, "10.1.0", &used, &history, 1, 2000).unwrap(), vec!["10.1.0.2"]);
+    }
+
+    #[test]
+    fn subnets_by_tenant_then_cluster_and_isolation() {
+        let overlay = |cluster: &str, tenant: &str, isolated: bool| OverlaySubnet {
+            cluster: cluster.into(), tenant: tenant.into(), isolated, note: String::new(),
+        };
+        let mut config = IpamConfig::default();
+        config.subnets.insert("10.0.1".into(), overlay("edge", "", false));
+        config.subnets.insert("10.0.2".into(), overlay("edge", "acme", true));
+        config.subnets.insert("10.0.3".into(), overlay("", "", false));
+        assert_eq!(choose_subnet(&config, "10.0.0", "edge", Some("acme")), "10.0.2");
+        assert_eq!(choose_subnet(&config, "10.0.0", "edge", Some("other")), "10.0.1");
+        assert_eq!(choose_subnet(&config, "10.0.0", "edge", None), "10.0.1");
+        assert_eq!(choose_subnet(&config, "10.0.0", "core", None), "10.0.0");
+
+        assert_eq!(routes_for(&config, "10.0.0", "10.0.0.5"),
+            vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.3.0/24"]);
+        assert_eq!(routes_for(&config, "10.0.0", "10.0.1.5"),
+            vec!["10.0.1.0/24", "10.0.0.0/24", "10.0.3.0/24"]);
+        assert_eq!(routes_for(&config, "10.0.0", "10.0.2.5"),
+            vec!["10.0.2.0/24", "10.0.0.0/24"], "isolated: own subnet and the primary only");
+
+        let pairs = isolation_pairs(&config.subnets);
+        assert_eq!(pairs.len(), 4);
+        assert!(pairs.contains(&("10.0.1".into(), "10.0.2".into())));
+        assert!(pairs.contains(&("10.0.2".into(), "10.0.3".into())));
+        assert!(!pairs.contains(&("10.0.1".into(), "10.0.3".into())));
+    }
+
+    #[test]
+    fn merge_replaces_peer_leases_and_keeps_older_on_conflict() {
+        let mut ledger = Ledger::default();
+        ledger.leases.insert("10.0.0.5".into(), lease("me", LeaseState::Bound, 100));
+        ledger.leases.insert("10.0.0.6".into(), lease("b", LeaseState::Bound, 100));
+        ledger.leases.insert("10.0.0.7".into(), lease("c", LeaseState::Pending, 50));
+        let sync = LeaseSync {
+            node: "b".into(),
+            leases: BTreeMap::from([
+                ("10.0.0.5".into(), lease("b", LeaseState::Pending, 200)),
+                ("10.0.0.7".into(), lease("b", LeaseState::Pending, 10)),
+                ("10.0.0.8".into(), lease("b", LeaseState::Pending, 200)),
+                ("10.0.0.9".into(), lease("c", LeaseState::Pending, 200)),
+            ]),
+            config: Some(IpamConfig { updated_at: 5, ..Default::default() }),
+        };
+        ledger.leases.insert("10.0.0.10".into(), lease("b", LeaseState::Pending, 900));
+        merge_into(&mut ledger, sync, "me", 1000);
+        let owner = |ip: &str| ledger.leases.get(ip).map(|l| l.node.as_str());
+        assert_eq!(owner("10.0.0.5"), Some("me"));
+        assert_eq!(owner("10.0.0.6"), None, "b no longer holds it");
+        assert_eq!(owner("10.0.0.7"), Some("b"), "older lease wins");
+        assert_eq!(owner("10.0.0.8"), Some("b"));
+        assert_eq!(owner("10.0.0.9"), None, "a node only speaks for itself");
+        assert_eq!(owner("10.0.0.10"), Some("b"), "fresh grant outlives a push that predates it");
+        assert_eq!(ledger.config.updated_at, 5);
+    }
+
+    #[test]
+    fn reconcile_binds_adopts_and_drops() {
+        let mut ledger = Ledger::default();
+        ledger.leases.insert("10.0.0.2".into(), lease("me", LeaseState::Pending, 0));
+        ledger.leases.insert("10.0.0.3".into(), lease("me", LeaseState::Pending, 0));
+        ledger.leases.insert("10.0.0.4".into(), lease("me", LeaseState::Bound, 0));
+        ledger.leases.insert("10.0.0.5".into(), lease("gone", LeaseState::Bound, 0));
+        ledger.leases.insert("10.0.0.6".into(), lease("b", LeaseState::Bound, 0));
+        let in_use = set(&["10.0.0.2", "10.0.0.6", "10.0.0.7"]);
+        reconcile_leases(&mut ledger, "me", &in_use, &set(&["me", "b"]), PENDING_TTL_SECS);
+        let state = |ip: &str| ledger.leases.get(ip).map(|l| (l.node.as_str(), l.state));
+        assert_eq!(state("10.0.0.2"), Some(("me", LeaseState::Bound)));
+        assert_eq!(state("10.0.0.3"), None, "expired pending lease");
+        assert_eq!(state("10.0.0.4"), None, "workload gone");
+        assert_eq!(state("10.0.0.5"), None, "node left the cluster");
+        assert_eq!(state("10.0.0.6"), Some(("b", LeaseState::Bound)));
+        assert_eq!(state("10.0.0.7"), Some(("me", LeaseState::Bound)), "adopted");
+    }
+}
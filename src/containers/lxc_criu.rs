@@ -0,0 +1,186 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Live LXC migration with CRIU — checkpoint a running container, ship the
+//! checkpoint with the rootfs, and restore it on the destination so its
+//! processes carry on where they were instead of booting from scratch.
+//!
+//! The container is frozen from the checkpoint until the restore, so this
+//! is "near-live": the downtime is the rootfs copy rather than a full
+//! shutdown + boot, and long-running processes keep their state.
+//!
+//! Only standalone LXC (`lxc-checkpoint`) is supported; Proxmox's `pct`
+//! has no checkpoint path. [`preflight`] turns away containers CRIU is
+//! known to trip over (nesting, FUSE, passed-through host devices) so the
+//! migrate falls back to a cold move before anything is stopped. When a
+//! restore fails anyway, [`restore_or_start`] cold-boots the container so
+//! it is never left down.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::LXC_DEFAULT_PATH;
+
+/// Checkpoint / restore cap — a dump writes the container's whole memory.
+const CRIU_TIMEOUT_SECS: &str = "900";
+
+fn has_tool(tool: &str) -> bool {
+    Command::new("sh").args(["-c", &format!("command -v {} >/dev/null 2>&1", tool)])
+        .status().map(|s| s.success()).unwrap_or(false)
+}
+
+/// Whether this host can checkpoint and restore LXC containers: `criu`
+/// and `lxc-checkpoint` installed and `criu check` happy with the kernel.
+/// Returns the CRIU version.
+pub fn host_preflight() -> Result<String, String> {
+    if super::is_proxmox() {
+        return Err("Proxmox containers have no checkpoint support (pct)".into());
+    }
+    if !has_tool("criu") {
+        return Err("criu is not installed".into());
+    }
+    if !has_tool("lxc-checkpoint") {
+        return Err("lxc-checkpoint is not installed".into());
+    }
+    let out = Command::new("criu").arg("check").output()
+        .map_err(|e| format!("Failed to run criu: {}", e))?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("criu check failed: {}", err.lines().last().unwrap_or("").trim()));
+    }
+    let version = Command::new("criu").arg("--version").output().ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().next().unwrap_or("").trim().to_string())
+        .unwrap_or_default();
+    Ok(version)
+}
+
+/// Config features CRIU can't checkpoint, one reason each.
+fn config_blockers(config: &str) -> Vec<String> {
+    let mut blockers = Vec::new();
+    let mut add = |reason: &str| if !blockers.iter().any(|b| b == reason) { blockers.push(reason.to_string()) };
+    for line in config.lines().map(str::trim).filter(|l| !l.starts_with('#')) {
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "lxc.include" if value.ends_with("nesting.conf") => add("nesting is enabled"),
+            "lxc.apparmor.allow_nesting" if value == "1" => add("nesting is enabled"),
+            "lxc.mount.entry" if value.contains("fuse") => add("a FUSE mount is configured"),
+            "lxc.mount.entry" if value.starts_with("/dev/") => add("host devices are passed through"),
+            _ => {}
+        }
+    }
+    blockers
+}
+
+/// Whether `name` can be live-migrated from this host.
+pub fn preflight(name: &str) -> Result<(), String> {
+    host_preflight()?;
+    if !super::lxc_is_running(name) {
+        return Err(format!("'{}' is not running", name));
+    }
+    let config = std::fs::read_to_string(format!("{}/{}/config", super::lxc_base_dir(name), name))
+        .map_err(|e| format!("Failed to read the container config: {}", e))?;
+    let blockers = config_blockers(&config);
+    if !blockers.is_empty() {
+        return Err(format!("CRIU can't checkpoint '{}': {}", name, blockers.join(", ")));
+    }
+    Ok(())
+}
+
+/// `lxc-checkpoint` for `name`, with `-P` when it lives off the default path.
+fn lxc_checkpoint(name: &str, dir: &Path, extra: &str) -> Result<(), String> {
+    let base = super::lxc_base_dir(name);
+    let mut cmd = Command::new("timeout");
+    cmd.args([CRIU_TIMEOUT_SECS, "lxc-checkpoint"]);
+    if base != LXC_DEFAULT_PATH {
+        cmd.args(["-P", &base]);
+    }
+    cmd.args(["-n", name, "-D"]).arg(dir).arg(extra);
+    let out = crate::logging::run_traced(&mut cmd)
+        .map_err(|e| format!("Failed to run lxc-checkpoint: {}", e))?;
+    if out.status.success() {
+        return Ok(());
+    }
+    // CRIU's own log says why; lxc-checkpoint's stderr rarely does.
+    let log = if extra == "-r" { "restore.log" } else { "dump.log" };
+    let tail: Vec<String> = std::fs::read_to_string(dir.join(log)).unwrap_or_default()
+        .lines().filter(|l| l.contains("Error")).map(String::from).collect();
+    let mut msg = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    if let Some(last) = tail.last() {
+        msg = format!("{} — {}", msg, last.trim());
+    }
+    Err(msg)
+}
+
+/// Checkpoint `name` into `dir` and stop it. On failure the container is
+/// left running.
+pub fn checkpoint(name: &str, dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    lxc_checkpoint(name, dir, "-s").map_err(|e| format!("Checkpoint failed: {}", e))
+}
+
+/// Restore `name` from the checkpoint in `dir` and confirm it is running.
+pub fn restore(name: &str, dir: &Path) -> Result<(), String> {
+    super::ensure_lxc_bridge();
+    lxc_checkpoint(name, dir, "-r").map_err(|e| format!("Restore failed: {}", e))?;
+    if !super::lxc_is_running(name) {
+        return Err("Restore reported success but the container is not running".into());
+    }
+    super::invalidate_list_caches();
+    Ok(())
+}
+
+/// Restore from the checkpoint, or cold-boot if that fails. `Ok(None)` is
+/// a restore; `Ok(Some(why))` a cold boot after the restore failed.
+pub fn restore_or_start(name: &str, dir: &Path) -> Result<Option<String>, String> {
+    match restore(name, dir) {
+        Ok(()) => Ok(None),
+        Err(e) => {
+            tracing::warn!("CRIU restore of '{}' failed, cold-starting: {}", name, e);
+            super::lxc_start(name).map(|_| Some(e.clone()))
+                .map_err(|start| format!("{}; cold start failed too: {}", e, start))
+        }
+    }
+}
+
+/// Pack a checkpoint directory into `<dir>.tar.gz`.
+pub fn pack(dir: &Path) -> Result<PathBuf, String> {
+    let archive = dir.with_extension("tar.gz");
+    let out = Command::new("tar").arg("czf").arg(&archive).arg("-C").arg(dir).arg(".").output()
+        .map_err(|e| format!("tar failed: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("tar failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(archive)
+}
+
+/// Unpack a checkpoint archive into `dir`.
+pub fn unpack(archive: &Path, dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let out = Command::new("tar").arg("xzf").arg(archive).arg("-C").arg(dir).output()
+        .map_err(|e| format!("tar failed: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("tar failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_config_is_reported() {
+        let plain = "lxc.uts.name = web\nlxc.net.0.type = veth\nlxc.mount.entry = /srv/data srv/data none bind 0 0\n";
+        assert!(config_blockers(plain).is_empty());
+        let nested = "lxc.include = /usr/share/lxc/config/nesting.conf\nlxc.apparmor.allow_nesting = 1\n\
+                      lxc.mount.entry = /dev/fuse dev/fuse none bind,create=file 0 0\n# lxc.mount.entry = /dev/ttyUSB0\n";
+        assert_eq!(config_blockers(nested), vec![
+            "nesting is enabled".to_string(),
+            "a FUSE mount is configured".to_string(),
+        ]);
+        assert_eq!(config_blockers("lxc.mount.entry = /dev/dri dev/dri none bind 0 0"),
+            vec!["host devices are passed through".to_string()]);
+    }
+}
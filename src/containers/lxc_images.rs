@@ -0,0 +1,543 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! LXC image sources for standalone hosts: mirrors, a verified download
+//! cache, and custom rootfs tarballs.
+//!
+//! Out of the box `lxc_create` lets `lxc-create -t download` fetch from
+//! images.linuxcontainers.org, which is slow from some regions and
+//! blocked outright in locked-down datacentres. Once a mirror is
+//! configured (or the public server is switched off), WolfStack fetches
+//! the image itself instead:
+//!
+//! 1. `meta/1.0/index-system` is read from the first mirror that answers,
+//!    falling back to the public server unless `skip_public` is set.
+//! 2. `rootfs.tar.xz` and the build's `SHA256SUMS` are downloaded into
+//!    the cache, and the tarball's digest is checked before it's kept.
+//! 3. The container is created from the cached tarball, so a second
+//!    container of the same release needs no network at all.
+//!
+//! Custom images are operator-supplied rootfs tarballs (a local path or
+//! an http(s) URL, optionally with an expected SHA-256) that show up in
+//! the template list next to the distro images.
+//!
+//! Proxmox nodes are unaffected: `pveam` and PVE's own template storage
+//! already cover mirrors and uploads there.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const PUBLIC_SERVER: &str = "https://images.linuxcontainers.org";
+const DEFAULT_CACHE_DIR: &str = "/var/cache/wolfstack/lxc-images";
+const INDEX_PATH: &str = "/meta/1.0/index-system";
+const VERIFIED_MARKER: &str = ".verified";
+
+fn settings_path() -> String {
+    format!("{}/lxc-images.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageSettings {
+    /// Base URLs of image-server mirrors, tried in order.
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Never contact images.linuxcontainers.org.
+    #[serde(default)]
+    pub skip_public: bool,
+    /// Download cache. Empty = /var/cache/wolfstack/lxc-images.
+    #[serde(default)]
+    pub cache_dir: String,
+    #[serde(default)]
+    pub custom: Vec<CustomImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomImage {
+    pub id: String,
+    /// Shown as the distribution in the create form.
+    pub name: String,
+    pub release: String,
+    pub architecture: String,
+    /// Where the operator said the tarball lives (path or URL).
+    pub source: String,
+    /// Local tarball the container is created from.
+    pub path: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub size_bytes: u64,
+    pub added_at: i64,
+}
+
+impl ImageSettings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = settings_path();
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        if self.cache_dir.is_empty() {
+            PathBuf::from(DEFAULT_CACHE_DIR)
+        } else {
+            PathBuf::from(&self.cache_dir)
+        }
+    }
+
+    /// Image servers to try, in order.
+    pub fn servers(&self) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .mirrors
+            .iter()
+            .map(|m| m.trim().trim_end_matches('/').to_string())
+            .filter(|m| !m.is_empty())
+            .collect();
+        if !self.skip_public {
+            out.push(PUBLIC_SERVER.to_string());
+        }
+        out
+    }
+
+    /// Whether WolfStack should download images itself rather than
+    /// leaving it to `lxc-create -t download`.
+    pub fn managed_downloads(&self) -> bool {
+        self.skip_public || self.mirrors.iter().any(|m| !m.trim().is_empty())
+    }
+}
+
+/// Save mirror and cache settings, keeping the registered custom images.
+pub fn save_sources(mirrors: Vec<String>, skip_public: bool, cache_dir: String) -> Result<(), String> {
+    for m in &mirrors {
+        let m = m.trim();
+        if !m.is_empty() && !m.starts_with("http://") && !m.starts_with("https://") {
+            return Err(format!("Mirror '{}' must be an http:// or https:// URL", m));
+        }
+    }
+    let cache_dir = cache_dir.trim().trim_end_matches('/').to_string();
+    if !cache_dir.is_empty() && !cache_dir.starts_with('/') {
+        return Err("Cache directory must be an absolute path".to_string());
+    }
+    let mut settings = ImageSettings::load();
+    settings.mirrors = mirrors.into_iter().map(|m| m.trim().to_string()).filter(|m| !m.is_empty()).collect();
+    settings.skip_public = skip_public;
+    settings.cache_dir = cache_dir;
+    if settings.skip_public && settings.mirrors.is_empty() {
+        return Err("Add at least one mirror before disabling the public image server".to_string());
+    }
+    settings.save()
+}
+
+// ─── Downloads ───
+
+/// Fetch `url` into `dest`, with wget or curl — whichever the host has.
+fn download(url: &str, dest: &Path) -> Result<(), String> {
+    let dest_s = dest.to_string_lossy().to_string();
+    let wget = Command::new("wget").args(["-q", "--timeout=30", "-O", &dest_s, url]).output();
+    if matches!(&wget, Ok(o) if o.status.success()) {
+        return Ok(());
+    }
+    let curl = Command::new("curl")
+        .args(["-fsSL", "--connect-timeout", "15", "-o", &dest_s, url])
+        .output()
+        .map_err(|e| format!("Neither wget nor curl could fetch {}: {}", url, e))?;
+    if curl.status.success() {
+        Ok(())
+    } else {
+        let _ = std::fs::remove_file(dest);
+        Err(format!("Download of {} failed: {}", url, String::from_utf8_lossy(&curl.stderr).trim()))
+    }
+}
+
+fn fetch_text(url: &str) -> Result<String, String> {
+    let out = Command::new("wget").args(["-qO-", "--timeout=15", url]).output();
+    let out = match out {
+        Ok(o) if o.status.success() => o,
+        _ => Command::new("curl")
+            .args(["-fsSL", "--connect-timeout", "10", url])
+            .output()
+            .map_err(|e| e.to_string())?,
+    };
+    if !out.status.success() {
+        return Err(format!("{} unreachable", url));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+/// The image index from the first server that answers, with that server.
+pub fn fetch_index(settings: &ImageSettings) -> Result<(String, String), String> {
+    let mut errors = Vec::new();
+    for server in settings.servers() {
+        match fetch_text(&format!("{}{}", server, INDEX_PATH)) {
+            Ok(text) if !text.trim().is_empty() => return Ok((server, text)),
+            Ok(_) => errors.push(format!("{}: empty index", server)),
+            Err(e) => errors.push(e),
+        }
+    }
+    Err(format!("No image server reachable ({})", errors.join("; ")))
+}
+
+/// One line of `index-system`:
+/// `distribution;release;architecture;variant;build;path`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub distribution: String,
+    pub release: String,
+    pub architecture: String,
+    pub variant: String,
+    pub build: String,
+    pub path: String,
+}
+
+pub fn parse_index(text: &str) -> Vec<IndexEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let p: Vec<&str> = line.split(';').map(str::trim).collect();
+            if p.len() < 6 || p[..4].iter().any(|f| f.is_empty()) || p[5].is_empty() {
+                return None;
+            }
+            Some(IndexEntry {
+                distribution: p[0].to_string(),
+                release: p[1].to_string(),
+                architecture: p[2].to_string(),
+                variant: p[3].to_string(),
+                build: p[4].to_string(),
+                path: p[5].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Digest for `file` in a `SHA256SUMS` listing (`<hex>  <name>`, with or
+/// without the binary-mode `*`).
+pub fn sha256_for(sums: &str, file: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let digest = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == file && digest.len() == 64).then(|| digest.to_ascii_lowercase())
+    })
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+    let mut f = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut h = Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = f.read(&mut buf).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        h.update(&buf[..n]);
+    }
+    Ok(h.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Cached `rootfs.tar.xz` for a distro image, downloading and verifying
+/// it first if this build isn't cached yet. Older builds of the same
+/// image are removed once the new one is in place. Blocking.
+pub fn cached_rootfs(distribution: &str, release: &str, architecture: &str, variant: &str) -> Result<PathBuf, String> {
+    let settings = ImageSettings::load();
+    let (server, index) = fetch_index(&settings)?;
+    let entry = parse_index(&index)
+        .into_iter()
+        .find(|e| e.distribution == distribution && e.release == release && e.architecture == architecture && e.variant == variant)
+        .ok_or_else(|| format!("{} {} ({}, {}) is not on {}", distribution, release, architecture, variant, server))?;
+
+    let image_dir = settings.cache_dir().join(distribution).join(release).join(architecture).join(variant);
+    let build_dir = image_dir.join(&entry.build);
+    let rootfs = build_dir.join("rootfs.tar.xz");
+    if rootfs.exists() && build_dir.join(VERIFIED_MARKER).exists() {
+        return Ok(rootfs);
+    }
+
+    std::fs::create_dir_all(&build_dir).map_err(|e| format!("Failed to create {}: {}", build_dir.display(), e))?;
+    let base = format!("{}/{}", server, entry.path.trim_matches('/'));
+    let sums = fetch_text(&format!("{}/SHA256SUMS", base))?;
+    let expected = sha256_for(&sums, "rootfs.tar.xz").ok_or("SHA256SUMS has no entry for rootfs.tar.xz")?;
+
+    let part = build_dir.join("rootfs.tar.xz.part");
+    download(&format!("{}/rootfs.tar.xz", base), &part)?;
+    let actual = sha256_file(&part)?;
+    if actual != expected {
+        let _ = std::fs::remove_file(&part);
+        return Err(format!("Checksum mismatch for {} {} from {}: expected {}, got {}", distribution, release, server, expected, actual));
+    }
+    std::fs::rename(&part, &rootfs).map_err(|e| e.to_string())?;
+    let _ = std::fs::write(build_dir.join(VERIFIED_MARKER), &actual);
+
+    if let Ok(rd) = std::fs::read_dir(&image_dir) {
+        for e in rd.flatten() {
+            if e.file_name().to_string_lossy() != entry.build {
+                let _ = std::fs::remove_dir_all(e.path());
+            }
+        }
+    }
+    Ok(rootfs)
+}
+
+/// A cached distro image, for the settings page.
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedImage {
+    pub distribution: String,
+    pub release: String,
+    pub architecture: String,
+    pub variant: String,
+    pub build: String,
+    pub size_bytes: u64,
+}
+
+/// Every verified image in the cache (`<dist>/<rel>/<arch>/<variant>/<build>`).
+pub fn list_cache() -> Vec<CachedImage> {
+    let root = ImageSettings::load().cache_dir();
+    let mut out = Vec::new();
+    let dirs = |p: &Path| -> Vec<(String, PathBuf)> {
+        std::fs::read_dir(p)
+            .map(|rd| {
+                rd.flatten()
+                    .filter(|e| e.path().is_dir())
+                    .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    for (dist, p1) in dirs(&root) {
+        if dist == "custom" {
+            continue;
+        }
+        for (rel, p2) in dirs(&p1) {
+            for (arch, p3) in dirs(&p2) {
+                for (variant, p4) in dirs(&p3) {
+                    for (build, p5) in dirs(&p4) {
+                        if !p5.join(VERIFIED_MARKER).exists() {
+                            continue;
+                        }
+                        let size_bytes = std::fs::metadata(p5.join("rootfs.tar.xz")).map(|m| m.len()).unwrap_or(0);
+                        out.push(CachedImage {
+                            distribution: dist.clone(),
+                            release: rel.clone(),
+                            architecture: arch.clone(),
+                            variant: variant.clone(),
+                            build,
+                            size_bytes,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    out.sort_by(|a, b| (&a.distribution, &a.release).cmp(&(&b.distribution, &b.release)));
+    out
+}
+
+/// Drop every cached distro image. Custom image downloads are kept —
+/// they may not be fetchable again.
+pub fn clear_cache() -> Result<(), String> {
+    let root = ImageSettings::load().cache_dir();
+    let rd = match std::fs::read_dir(&root) {
+        Ok(rd) => rd,
+        Err(_) => return Ok(()),
+    };
+    for e in rd.flatten() {
+        if e.file_name() != "custom" {
+            std::fs::remove_dir_all(e.path()).map_err(|err| format!("{}: {}", e.path().display(), err))?;
+        }
+    }
+    Ok(())
+}
+
+// ─── Custom images ───
+
+fn valid_label(s: &str) -> bool {
+    !s.is_empty() && s.len() <= 64 && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+fn is_tarball(name: &str) -> bool {
+    [".tar.gz", ".tgz", ".tar.xz", ".tar.zst"].iter().any(|ext| name.ends_with(ext))
+}
+
+/// Register a rootfs tarball as a creatable template. `source` is an
+/// absolute path on this host or an http(s) URL, which is downloaded into
+/// the cache. When `sha256` is given the tarball must match it. Blocking.
+pub fn add_custom(name: &str, release: &str, architecture: &str, source: &str, sha256: &str) -> Result<CustomImage, String> {
+    let (name, release, architecture, source) = (name.trim(), release.trim(), architecture.trim(), source.trim());
+    if !valid_label(name) || !valid_label(release) {
+        return Err("Name and release may only contain letters, digits, '-', '_' and '.'".to_string());
+    }
+    let architecture = if architecture.is_empty() { super::host_container_arch() } else { architecture };
+    if !valid_label(architecture) {
+        return Err(format!("Invalid architecture '{}'", architecture));
+    }
+    let expected = sha256.trim().to_ascii_lowercase();
+    if !expected.is_empty() && (expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit())) {
+        return Err("SHA-256 must be 64 hex characters".to_string());
+    }
+
+    let mut settings = ImageSettings::load();
+    if settings.custom.iter().any(|c| c.name == name && c.release == release && c.architecture == architecture) {
+        return Err(format!("A custom image {} {} ({}) is already registered", name, release, architecture));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let file_name = source.rsplit('/').next().unwrap_or("").split('?').next().unwrap_or("").to_string();
+    if !is_tarball(&file_name) {
+        return Err("Source must be a .tar.gz, .tar.xz or .tar.zst rootfs tarball".to_string());
+    }
+    let (path, downloaded) = if source.starts_with("http://") || source.starts_with("https://") {
+        let dir = settings.cache_dir().join("custom").join(&id);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let dest = dir.join(&file_name);
+        if let Err(e) = download(source, &dest) {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+        (dest, true)
+    } else {
+        let p = PathBuf::from(source);
+        if !p.is_absolute() || !p.is_file() {
+            return Err(format!("{} is not a file on this host", source));
+        }
+        (p, false)
+    };
+
+    let cleanup = |p: &Path| {
+        if downloaded {
+            if let Some(dir) = p.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+    };
+    let actual = match sha256_file(&path) {
+        Ok(d) => d,
+        Err(e) => {
+            cleanup(&path);
+            return Err(e);
+        }
+    };
+    if !expected.is_empty() && actual != expected {
+        cleanup(&path);
+        return Err(format!("Checksum mismatch: expected {}, got {}", expected, actual));
+    }
+
+    let image = CustomImage {
+        id,
+        name: name.to_string(),
+        release: release.to_string(),
+        architecture: architecture.to_string(),
+        source: source.to_string(),
+        size_bytes: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        path: path.to_string_lossy().to_string(),
+        sha256: actual,
+        added_at: chrono::Utc::now().timestamp(),
+    };
+    settings.custom.push(image.clone());
+    if let Err(e) = settings.save() {
+        cleanup(&path);
+        return Err(e);
+    }
+    Ok(image)
+}
+
+/// Unregister a custom image, deleting the tarball only if WolfStack
+/// downloaded it (a local source belongs to the operator).
+pub fn remove_custom(id: &str) -> Result<(), String> {
+    let mut settings = ImageSettings::load();
+    let pos = settings.custom.iter().position(|c| c.id == id).ok_or("No custom image with that id")?;
+    let image = settings.custom.remove(pos);
+    settings.save()?;
+    let custom_root = settings.cache_dir().join("custom");
+    if let Some(dir) = Path::new(&image.path).parent() {
+        if dir.starts_with(&custom_root) && dir != custom_root {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+    Ok(())
+}
+
+pub fn find_custom(distribution: &str, release: &str, architecture: &str) -> Option<CustomImage> {
+    ImageSettings::load()
+        .custom
+        .into_iter()
+        .find(|c| c.name == distribution && c.release == release && c.architecture == architecture)
+}
+
+/// Custom images as create-form templates (variant `custom`).
+pub fn custom_templates() -> Vec<super::LxcTemplate> {
+    ImageSettings::load()
+        .custom
+        .into_iter()
+        .map(|c| super::LxcTemplate {
+            distribution: c.name,
+            release: c.release,
+            architecture: c.architecture,
+            variant: "custom".to_string(),
+        })
+        .collect()
+}
+
+/// The tarball `lxc_create` should build `distribution`/`release` from,
+/// or None to leave it to `lxc-create -t download` (no mirror configured
+/// and not a custom image). Blocking — may download.
+pub fn rootfs_for(distribution: &str, release: &str, architecture: &str) -> Result<Option<PathBuf>, String> {
+    if let Some(c) = find_custom(distribution, release, architecture) {
+        let p = PathBuf::from(&c.path);
+        if !p.is_file() {
+            return Err(format!("Custom image tarball {} is missing", c.path));
+        }
+        return Ok(Some(p));
+    }
+    if !ImageSettings::load().managed_downloads() {
+        return Ok(None);
+    }
+    cached_rootfs(distribution, release, architecture, "default").map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_lines_need_all_six_fields() {
+        let text = "debian;bookworm;amd64;default;20260101_05:24;/images/debian/bookworm/amd64/default/20260101_05:24/\n\
+                    ubuntu;noble;arm64;cloud;20260101_07:42;/images/ubuntu/noble/arm64/cloud/20260101_07:42/\n\
+                    broken;line\n\
+                    alpine;;amd64;default;x;/images/alpine/\n";
+        let entries = parse_index(text);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].release, "bookworm");
+        assert_eq!(entries[0].build, "20260101_05:24");
+        assert_eq!(entries[1].variant, "cloud");
+    }
+
+    #[test]
+    fn sha256sums_lookup_matches_exact_name() {
+        let a = "a".repeat(64);
+        let b = "B".repeat(64);
+        let sums = format!("{}  meta.tar.xz\n{} *rootfs.tar.xz\n", a, b);
+        assert_eq!(sha256_for(&sums, "rootfs.tar.xz"), Some("b".repeat(64)));
+        assert_eq!(sha256_for(&sums, "meta.tar.xz"), Some(a));
+        assert_eq!(sha256_for(&sums, "rootfs.squashfs"), None);
+    }
+
+    #[test]
+    fn mirrors_come_before_the_public_server() {
+        let mut s = ImageSettings { mirrors: vec!["https://mirror.dc.local/lxc/".into(), " ".into()], ..Default::default() };
+        assert_eq!(s.servers(), vec!["https://mirror.dc.local/lxc".to_string(), PUBLIC_SERVER.to_string()]);
+        assert!(s.managed_downloads());
+        s.skip_public = true;
+        assert_eq!(s.servers(), vec!["https://mirror.dc.local/lxc".to_string()]);
+        assert!(!ImageSettings::default().managed_downloads());
+    }
+}
@@ -17,9 +17,13 @@
 //!   • **btrfs subvolume** — `btrfs qgroup limit NEWSIZE`.
 
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ffi::OsString;
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -201,6 +205,142 @@ pub fn resize(name: &str, new_size_bytes: u64) -> Result<String, String> {
     }
 }
 
+/// Host directory holding a container's root filesystem, so the host
+/// file manager can browse it — works whether or not the container is
+/// running, unlike `lxc-attach`. Directory, btrfs and LVM-hosted rootfs
+/// are already plain directories; a ZFS dataset is resolved to its
+/// mountpoint. Proxmox only mounts the volume at
+/// `/var/lib/lxc/<vmid>/rootfs` while the container runs (or after
+/// `pct mount`), so an empty directory there means "not reachable now".
+pub fn host_rootfs_dir(name: &str) -> Result<String, String> {
+    let info = inspect(name)?;
+    if info.proxmox {
+        let dir = format!("/var/lib/lxc/{}/rootfs", info.pve_vmid);
+        let populated = fs::read_dir(&dir).map(|mut d| d.next().is_some()).unwrap_or(false);
+        if !populated {
+            return Err(format!(
+                "rootfs of {} is not mounted on the host — start the container or run `pct mount {}`",
+                name, info.pve_vmid
+            ));
+        }
+        return Ok(dir);
+    }
+    if info.rootfs.is_empty() {
+        return Err(format!("no lxc.rootfs.path in the config of {}", name));
+    }
+    if !info.rootfs.starts_with('/') {
+        // zfs:tank/lxc/<name> — a dataset, not a path.
+        let out = Command::new("zfs")
+            .args(["get", "-H", "-o", "value", "mountpoint", &info.rootfs])
+            .output()
+            .map_err(|e| format!("zfs get: {}", e))?;
+        let mp = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        if !out.status.success() || !mp.starts_with('/') {
+            return Err(format!("ZFS dataset {} has no mountpoint", info.rootfs));
+        }
+        return Ok(mp);
+    }
+    if Path::new(&info.rootfs).is_dir() {
+        Ok(info.rootfs)
+    } else {
+        Err(format!("rootfs {} is a block device, not a directory — browse it from inside the running container", info.rootfs))
+    }
+}
+
+/// `(as configured, canonical)` rootfs directories of every LXC container
+/// on this host. Cached for a minute: ZFS mountpoints need a `zfs get`.
+fn known_rootfs_dirs() -> Vec<(PathBuf, PathBuf)> {
+    static CACHE: LazyLock<Mutex<Option<(Instant, Vec<(PathBuf, PathBuf)>)>>> = LazyLock::new(|| Mutex::new(None));
+    let mut cache = CACHE.lock().unwrap();
+    if let Some((at, dirs)) = cache.as_ref()
+        && at.elapsed() < Duration::from_secs(60)
+    {
+        return dirs.clone();
+    }
+    let dirs: Vec<(PathBuf, PathBuf)> = super::lxc_list_all_cached().into_iter()
+        .filter_map(|c| host_rootfs_dir(&c.name).ok())
+        .filter_map(|d| {
+            let canonical = Path::new(&d).canonicalize().ok()?;
+            Some((PathBuf::from(d), canonical))
+        })
+        .collect();
+    *cache = Some((Instant::now(), dirs.clone()));
+    dirs
+}
+
+/// The container root filesystem a host path points into, as the
+/// `(prefix the path uses, canonical root)` pair. `path` should already
+/// be free of `.`/`..` components.
+pub fn rootfs_containing(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    // /var/lib/lxc/<name>/rootfs needs no lookup (and covers Proxmox,
+    // whose mounts come and go with the container).
+    let comps: Vec<Component> = path.components().collect();
+    if comps.len() >= 6 && path.starts_with("/var/lib/lxc") && comps[5].as_os_str() == "rootfs" {
+        let prefix: PathBuf = comps[..6].iter().collect();
+        if let Ok(canonical) = prefix.canonicalize() {
+            return Some((prefix, canonical));
+        }
+    }
+    known_rootfs_dirs().into_iter()
+        .filter(|(raw, canonical)| path.starts_with(raw) || path.starts_with(canonical))
+        .max_by_key(|(raw, _)| raw.as_os_str().len())
+}
+
+/// Resolve `rel` inside the container root `root` the way the container
+/// sees it: absolute symlinks restart at `root` and `..` stops there, so
+/// a link planted in the container (`etc/x -> /etc`) can't lead the host
+/// file manager to the host's own files. Missing trailing components are
+/// kept as-is, for paths about to be created.
+pub fn resolve_in_root(root: &Path, rel: &Path) -> Result<PathBuf, String> {
+    let mut pending: VecDeque<OsString> = VecDeque::new();
+    push_components(&mut pending, rel, false);
+    let mut out = root.to_path_buf();
+    let mut links = 0;
+    while let Some(c) = pending.pop_front() {
+        if c == ".." {
+            if out != root {
+                out.pop();
+            }
+            continue;
+        }
+        let next = out.join(&c);
+        match fs::symlink_metadata(&next) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                links += 1;
+                if links > 40 {
+                    return Err("Too many levels of symbolic links".into());
+                }
+                let target = fs::read_link(&next).map_err(|e| format!("{}: {}", next.display(), e))?;
+                if target.is_absolute() {
+                    out = root.to_path_buf();
+                }
+                push_components(&mut pending, &target, true);
+            }
+            _ => out = next,
+        }
+    }
+    Ok(out)
+}
+
+/// Queue `path`'s normal and `..` components, at the front when a symlink
+/// target is being spliced in.
+fn push_components(pending: &mut VecDeque<OsString>, path: &Path, front: bool) {
+    let comps: Vec<OsString> = path.components()
+        .filter_map(|c| match c {
+            Component::Normal(n) => Some(n.to_os_string()),
+            Component::ParentDir => Some(OsString::from("..")),
+            _ => None,
+        })
+        .collect();
+    if front {
+        for c in comps.into_iter().rev() {
+            pending.push_front(c);
+        }
+    } else {
+        pending.extend(comps);
+    }
+}
+
 /// Migrate a stopped container's rootfs to a different storage path.
 /// Native: rsync over (preserving perms/xattrs), update lxc.rootfs.path
 /// in the config, optionally remove the old. Proxmox: callers should
@@ -530,6 +670,23 @@ mod tests {
         assert_eq!(parse_rootfs(cfg), "/var/lib/lxc/ct1/rootfs");
     }
     #[test]
+    fn symlinks_resolve_inside_the_root() {
+        let root = std::env::temp_dir().join(format!("wolfstack-rootfs-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("etc/app")).unwrap();
+        fs::create_dir_all(root.join("usr/share")).unwrap();
+        std::os::unix::fs::symlink("/etc", root.join("etc/hostetc")).unwrap();
+        std::os::unix::fs::symlink("../../..", root.join("usr/share/up")).unwrap();
+        std::os::unix::fs::symlink("app", root.join("etc/current")).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(resolve_in_root(&root, Path::new("etc/hostetc/shadow")).unwrap(), root.join("etc/shadow"));
+        assert_eq!(resolve_in_root(&root, Path::new("usr/share/up/etc")).unwrap(), root.join("etc"));
+        assert_eq!(resolve_in_root(&root, Path::new("etc/current/new.conf")).unwrap(), root.join("etc/app/new.conf"));
+        assert_eq!(resolve_in_root(&root, Path::new("")).unwrap(), root);
+        let _ = fs::remove_dir_all(&root);
+    }
+    #[test]
     fn parse_rootfs_no_prefix() {
         let cfg = "lxc.rootfs.path = /tank/lxc/ct2\n";
         assert_eq!(parse_rootfs(cfg), "/tank/lxc/ct2");
@@ -8,9 +8,19 @@
 //! LXC: communicates via lxc-* CLI commands
 //! WolfNet: Optional overlay network integration for container networking
 
+pub mod docker_api;
+pub mod docker_build;
 pub mod docker_dns;
+pub mod docker_networks;
+pub mod env_store;
+pub mod image_cves;
 pub mod image_watcher;
+pub mod ipam;
+pub mod lxc_criu;
+pub mod lxc_images;
 pub mod lxc_storage;
+pub mod mount_deps;
+pub mod startup_order;
 
 use serde::{Deserialize, Serialize};
 use std::os::unix::fs::PermissionsExt;
@@ -113,6 +123,69 @@ pub fn setup_wolfnet_forwarding() {
             ]).output();
         }
     }
+
+    apply_overlay_subnets();
+}
+
+/// Overlay subnets applied on this host since startup, so removed ones can
+/// be torn down.
+static OVERLAY_APPLIED: Mutex<std::collections::BTreeSet<String>> = Mutex::new(std::collections::BTreeSet::new());
+
+/// Host side of the extra WolfNet overlay subnets (see `ipam::OverlaySubnet`).
+/// wolfnet0 only carries its own /24, so each overlay subnet gets a kernel
+/// route into it; its sources are exempted from the MASQUERADE above so they
+/// keep their address across the mesh; and isolated subnets get DROPs against
+/// the other overlay subnets in a WOLFNET-ISOLATION chain jumped to ahead of
+/// the blanket wolfnet0 ACCEPTs. Idempotent.
+pub fn apply_overlay_subnets() {
+    let Some(primary) = wolfnet_subnet_prefix() else { return };
+    // Leave the routes as they are rather than tear them down over a
+    // ledger that can't be read.
+    let mut subnets = match ipam::overlay_subnets() {
+        Ok(subnets) => subnets,
+        Err(e) => {
+            warn!("WolfNet: not applying overlay subnets: {}", e);
+            return;
+        }
+    };
+    subnets.remove(&primary);
+    let mut applied = OVERLAY_APPLIED.lock().unwrap_or_else(|e| e.into_inner());
+
+    for prefix in applied.iter().filter(|p| !subnets.contains_key(*p)) {
+        let cidr = format!("{}.0/24", prefix);
+        let _ = Command::new("ip").args(["route", "del", &cidr, "dev", "wolfnet0"]).output();
+        let _ = Command::new("iptables").args([
+            "-t", "nat", "-D", "POSTROUTING", "-s", &cidr, "-o", "wolfnet0", "-j", "RETURN"
+        ]).output();
+        info!("WolfNet: removed overlay subnet {}", cidr);
+    }
+    applied.clear();
+
+    for prefix in subnets.keys() {
+        let cidr = format!("{}.0/24", prefix);
+        let _ = Command::new("ip").args(["route", "replace", &cidr, "dev", "wolfnet0"]).output();
+        let check = Command::new("iptables").args([
+            "-t", "nat", "-C", "POSTROUTING", "-s", &cidr, "-o", "wolfnet0", "-j", "RETURN"
+        ]).output();
+        if check.map(|o| !o.status.success()).unwrap_or(true) {
+            let _ = Command::new("iptables").args([
+                "-t", "nat", "-I", "POSTROUTING", "1", "-s", &cidr, "-o", "wolfnet0", "-j", "RETURN"
+            ]).output();
+        }
+        applied.insert(prefix.clone());
+    }
+
+    let _ = Command::new("iptables").args(["-N", "WOLFNET-ISOLATION"]).output();
+    let _ = Command::new("iptables").args(["-F", "WOLFNET-ISOLATION"]).output();
+    for (src, dst) in ipam::isolation_pairs(&subnets) {
+        let _ = Command::new("iptables").args([
+            "-A", "WOLFNET-ISOLATION", "-s", &format!("{}.0/24", src), "-d", &format!("{}.0/24", dst), "-j", "DROP"
+        ]).output();
+    }
+    for _ in 0..2 {
+        let _ = Command::new("iptables").args(["-D", "FORWARD", "-j", "WOLFNET-ISOLATION"]).output();
+    }
+    let _ = Command::new("iptables").args(["-I", "FORWARD", "1", "-j", "WOLFNET-ISOLATION"]).output();
 }
 
 /// Add interfaces to the firewalld trusted zone (if firewalld is running).
@@ -256,6 +329,7 @@ pub fn release_wolfnet_ip(ip: &str) {
     // table immediately), the caller is telling us the underlying
     // source of truth (VM config, container labels, etc.) has changed.
     invalidate_wolfnet_ips_cache();
+    ipam::release(ip);
     if removed {
         info!("WolfNet: released route for {}", ip);
     }
@@ -311,8 +385,13 @@ pub fn flush_routes_to_disk(routes: &std::collections::HashMap<String, String>)
 // ─── Lightweight container counting (for monitoring loops) ───
 // These avoid the expensive per-container docker inspect calls that docker_list_all() performs.
 
-/// Count all Docker containers with a single subprocess call (no per-container inspect).
+/// Count all Docker containers with a single API call or subprocess (no per-container inspect).
 fn docker_count_inner() -> u32 {
+    match docker_api::get_json("/containers/json?all=1") {
+        Ok(v) => return v.as_array().map(|a| a.len() as u32).unwrap_or(0),
+        Err(docker_api::ApiError::Docker(_)) => return 0,
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
     Command::new("docker")
         .args(["ps", "-aq"])
         .output()
@@ -406,8 +485,12 @@ static LXC_STATS_CACHE: Mutex<Option<(Vec<ContainerStats>, Instant)>> = Mutex::n
 const LIST_CACHE_TTL_SECS: u64 = 5;
 const IMAGES_CACHE_TTL_SECS: u64 = 60;
 
-/// Cached docker_list_all — reuses result for 5 seconds.
+/// Cached docker_list_all — the background inventory snapshot, else a
+/// result reused for 5 seconds.
 pub fn docker_list_all_cached() -> Vec<ContainerInfo> {
+    if let Some(val) = crate::monitoring::inventory::DOCKER.get() {
+        return val;
+    }
     {
         let cache = DOCKER_LIST_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -421,8 +504,12 @@ pub fn docker_list_all_cached() -> Vec<ContainerInfo> {
     val
 }
 
-/// Cached docker_stats — reuses result for 5 seconds.
+/// Cached docker_stats — the background inventory snapshot, else a
+/// result reused for 5 seconds.
 pub fn docker_stats_cached() -> Vec<ContainerStats> {
+    if let Some(val) = crate::monitoring::inventory::DOCKER_STATS.get() {
+        return val;
+    }
     {
         let cache = DOCKER_STATS_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -451,8 +538,12 @@ pub fn docker_images_cached() -> Vec<ContainerImage> {
     val
 }
 
-/// Cached lxc_list_all — reuses result for 5 seconds.
+/// Cached lxc_list_all — the background inventory snapshot, else a
+/// result reused for 5 seconds.
 pub fn lxc_list_all_cached() -> Vec<ContainerInfo> {
+    if let Some(val) = crate::monitoring::inventory::LXC.get() {
+        return val;
+    }
     {
         let cache = LXC_LIST_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -466,8 +557,12 @@ pub fn lxc_list_all_cached() -> Vec<ContainerInfo> {
     val
 }
 
-/// Cached lxc_stats — reuses result for 5 seconds.
+/// Cached lxc_stats — the background inventory snapshot, else a
+/// result reused for 5 seconds.
 pub fn lxc_stats_cached() -> Vec<ContainerStats> {
+    if let Some(val) = crate::monitoring::inventory::LXC_STATS.get() {
+        return val;
+    }
     {
         let cache = LXC_STATS_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -485,6 +580,8 @@ pub fn lxc_stats_cached() -> Vec<ContainerStats> {
 pub fn invalidate_count_caches() {
     *DOCKER_COUNT_CACHE.lock().unwrap() = None;
     *LXC_COUNT_CACHE.lock().unwrap() = None;
+    crate::monitoring::inventory::DOCKER.invalidate();
+    crate::monitoring::inventory::LXC.invalidate();
 }
 
 /// Invalidate all container list/stats caches (call after create/delete/start/stop).
@@ -495,6 +592,10 @@ pub fn invalidate_list_caches() {
     *DOCKER_IMAGES_CACHE.lock().unwrap() = None;
     *LXC_LIST_CACHE.lock().unwrap() = None;
     *LXC_STATS_CACHE.lock().unwrap() = None;
+    crate::monitoring::inventory::DOCKER.invalidate();
+    crate::monitoring::inventory::DOCKER_STATS.invalidate();
+    crate::monitoring::inventory::LXC.invalidate();
+    crate::monitoring::inventory::LXC_STATS.invalidate();
 }
 
 /// Invalidate just the Docker list cache. Used by write paths that change
@@ -502,10 +603,14 @@ pub fn invalidate_list_caches() {
 /// UI doesn't read back the pre-change snapshot for the next 5 seconds.
 pub fn invalidate_docker_list_cache() {
     *DOCKER_LIST_CACHE.lock().unwrap() = None;
+    crate::monitoring::inventory::DOCKER.invalidate();
 }
 
-/// Count Docker containers (cached for 5s).
+/// Count Docker containers (inventory snapshot, else cached for 5s).
 pub fn docker_count() -> u32 {
+    if let Some(list) = crate::monitoring::inventory::DOCKER.get() {
+        return list.len() as u32;
+    }
     {
         let cache = DOCKER_COUNT_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -519,8 +624,11 @@ pub fn docker_count() -> u32 {
     val
 }
 
-/// Count LXC containers (cached for 5s).
+/// Count LXC containers (inventory snapshot, else cached for 5s).
 pub fn lxc_count() -> u32 {
+    if let Some(list) = crate::monitoring::inventory::LXC.get() {
+        return list.len() as u32;
+    }
     {
         let cache = LXC_COUNT_CACHE.lock().unwrap();
         if let Some((val, ts)) = &*cache {
@@ -779,10 +887,12 @@ mod container_dnat_tests {
 pub fn cleanup_stale_wolfnet_routes() {
     let local_ips: std::collections::HashSet<String> = wolfnet_used_ips_cached().into_iter().collect();
 
-    let prefix = match wolfnet_subnet_prefix() {
-        Some(p) => format!("{}.", p),
-        None => return, // WolfNet not configured
-    };
+    // The primary subnet first, then any overlay subnets.
+    let prefixes: Vec<String> = ipam::overlay_prefixes().into_iter().map(|p| format!("{}.", p)).collect();
+    if prefixes.is_empty() {
+        return; // WolfNet not configured
+    }
+    let in_wolfnet = |ip: &str| prefixes.iter().any(|p| ip.starts_with(p.as_str()));
 
     // Get all kernel routes in the WolfNet range
     let output = match Command::new("ip").args(["route", "show"]).output() {
@@ -794,7 +904,7 @@ pub fn cleanup_stale_wolfnet_routes() {
     let mut removed = 0;
     for line in text.lines() {
         let ip = match line.split_whitespace().next() {
-            Some(ip) if ip.starts_with(&prefix) && !ip.contains('/') => ip,
+            Some(ip) if in_wolfnet(ip) && !ip.contains('/') => ip,
             _ => continue,
         };
 
@@ -886,12 +996,13 @@ pub fn cleanup_stale_wolfnet_routes() {
                 .args(["--target", &pid_out, "--net", "ip", "addr", "add", &format!("{}/32", label), "dev", "eth0"])
                 .output(); // Silently ignores EEXIST
 
-            // Ensure container can route WolfNet subnet via its network's gateway
+            // Ensure container can route its WolfNet subnets via its network's gateway
             // with src hint so the container uses its WolfNet IP as source
-            let wn_subnet = format!("{}.0/24", prefix.trim_end_matches('.'));
-            let _ = Command::new("nsenter")
-                .args(["--target", &pid_out, "--net", "ip", "route", "replace", &wn_subnet, "via", &gw, "src", &label])
-                .output();
+            for wn_subnet in ipam::container_routes(&label) {
+                let _ = Command::new("nsenter")
+                    .args(["--target", &pid_out, "--net", "ip", "route", "replace", &wn_subnet, "via", &gw, "src", &label])
+                    .output();
+            }
 
             // For containers on custom Docker networks (not docker0), also set up DNAT
             // so that traffic arriving at the host for this WolfNet IP gets redirected
@@ -924,7 +1035,8 @@ pub fn cleanup_stale_wolfnet_routes() {
                                 "-m", "comment", "--comment", WOLFNET_CT_COMMENT,
                             ]).output().map(|o| o.status.success()).unwrap_or(false);
                             if correct { continue; }
-                            purge_container_dnat_for_ip(chain, &label, &prefix);
+                            let label_prefix = prefixes.iter().find(|p| label.starts_with(p.as_str())).unwrap_or(&prefixes[0]);
+                            purge_container_dnat_for_ip(chain, &label, label_prefix);
                             let _ = Command::new("iptables").args([
                                 "-t", "nat", "-A", chain, "-d", &label,
                                 "-j", "DNAT", "--to-destination", &docker_ip,
@@ -947,20 +1059,22 @@ pub fn cleanup_stale_wolfnet_routes() {
         // IpMapping rules. Runs only when `docker ps` genuinely SUCCEEDED
         // (checked above), so a dockerd hiccup can't be mistaken for
         // "everything orphaned".
-        let mut present: std::collections::HashSet<String> = std::collections::HashSet::new();
-        for chain in ["PREROUTING", "OUTPUT"] {
-            if let Ok(out) = Command::new("iptables").args(["-t", "nat", "-S", chain]).output() {
-                for line in String::from_utf8_lossy(&out.stdout).lines() {
-                    if let Some(ip) = container_dnat_dst_ip(line, &prefix) {
-                        present.insert(ip);
+        for prefix in &prefixes {
+            let mut present: std::collections::HashSet<String> = std::collections::HashSet::new();
+            for chain in ["PREROUTING", "OUTPUT"] {
+                if let Ok(out) = Command::new("iptables").args(["-t", "nat", "-S", chain]).output() {
+                    for line in String::from_utf8_lossy(&out.stdout).lines() {
+                        if let Some(ip) = container_dnat_dst_ip(line, prefix) {
+                            present.insert(ip);
+                        }
                     }
                 }
             }
-        }
-        for ip in present {
-            if !claimed_ips.contains(&ip) {
-                purge_container_dnat_for_ip("PREROUTING", &ip, &prefix);
-                purge_container_dnat_for_ip("OUTPUT", &ip, &prefix);
+            for ip in present {
+                if !claimed_ips.contains(&ip) {
+                    purge_container_dnat_for_ip("PREROUTING", &ip, prefix);
+                    purge_container_dnat_for_ip("OUTPUT", &ip, prefix);
+                }
             }
         }
     }
@@ -998,7 +1112,7 @@ pub fn cleanup_stale_wolfnet_routes() {
 
             // Only act when the container's IP falls in WolfNet's range.
             // Other subnets aren't this loop's concern.
-            if !cip.starts_with(&prefix) { continue; }
+            if !in_wolfnet(&cip) { continue; }
             // Belt-and-braces: don't fight the labelled path if we
             // somehow disagree about what's a wolfnet IP.
             if local_ips.contains(&cip) { continue; }
@@ -1030,7 +1144,7 @@ pub fn cleanup_stale_wolfnet_routes() {
         // LXC has to sanitise its own.
         let Some(cip) = first_reportable_ip(&c.ip_address) else { continue };
         let cip = cip.as_str();
-        if !cip.starts_with(&prefix) { continue; }
+        if !in_wolfnet(cip) { continue; }
         if local_ips.contains(cip) { continue; }
         // The WolfNet-IP-labelled / lxcbr0 path is repaired elsewhere.
         if lxc_get_wolfnet_ip(&c.name).is_some() { continue; }
@@ -1054,7 +1168,7 @@ pub fn cleanup_stale_wolfnet_routes() {
         .args(["ps", "--format", "{{.Names}}"])
         .output()
     {
-        let wn_subnet = format!("{}.0/24", prefix.trim_end_matches('.'));
+        let all_subnets: Vec<String> = prefixes.iter().map(|p| format!("{}0/24", p)).collect();
         let text = String::from_utf8_lossy(&output.stdout);
         for name in text.lines().filter(|l| !l.is_empty()) {
             let pid_out = Command::new("docker")
@@ -1067,22 +1181,27 @@ pub fn cleanup_stale_wolfnet_routes() {
             let (bridge_dev, gw) = docker_bridge_info(name);
             bridge_devs.insert(bridge_dev);
 
-            // Add route for WolfNet subnet via the Docker gateway (idempotent).
+            // Add routes for the WolfNet subnets via the Docker gateway (idempotent).
             // If the container has a WolfNet IP, include `src <wolfnet_ip>` so the
             // kernel uses it as the source address — otherwise this `replace` would
             // clobber the src hint set by the per-WolfNet-IP loop above, leaving
             // traffic sourced from the container's Docker IP (breaks TCP/MTU paths).
+            // Without a WolfNet IP its traffic is masqueraded, so every subnet is
+            // reachable; with one, isolation decides which subnets it routes.
             let wolfnet_ip = docker_effective_wolfnet_ip(name);
-            let mut args: Vec<String> = vec![
-                "--target".into(), pid_out.clone(), "--net".into(),
-                "ip".into(), "route".into(), "replace".into(),
-                wn_subnet.clone(), "via".into(), gw.clone(),
-            ];
-            if let Some(ref ip) = wolfnet_ip {
-                args.push("src".into());
-                args.push(ip.clone());
+            let subnets = wolfnet_ip.as_deref().map(ipam::container_routes).unwrap_or_else(|| all_subnets.clone());
+            for wn_subnet in subnets {
+                let mut args: Vec<String> = vec![
+                    "--target".into(), pid_out.clone(), "--net".into(),
+                    "ip".into(), "route".into(), "replace".into(),
+                    wn_subnet, "via".into(), gw.clone(),
+                ];
+                if let Some(ref ip) = wolfnet_ip {
+                    args.push("src".into());
+                    args.push(ip.clone());
+                }
+                let _ = Command::new("nsenter").args(&args).output();
             }
-            let _ = Command::new("nsenter").args(&args).output();
         }
     }
 
@@ -1345,6 +1464,16 @@ pub fn wolfnet_allocate_ip(host_ip: &str, extra_used: &[u8]) -> String {
         }
     }
 
+    // Leased elsewhere in the cluster or reserved in the IPAM ledger
+    for ip in ipam::unavailable().unwrap_or_else(|e| {
+        warn!("WolfNet IPAM: {}", e);
+        Default::default()
+    }) {
+        if let Some(last) = ip.strip_prefix(&format!("{}.", prefix)).and_then(|l| l.parse::<u8>().ok()) {
+            used_ips.insert(last);
+        }
+    }
+
     // Allocate from 100-254 range (reserving 1-99 for hosts)
     for i in 100..=254u8 {
         if !used_ips.contains(&i) {
@@ -1885,16 +2014,10 @@ pub fn docker_connect_wolfnet(container: &str, ip: &str) -> Result<String, Strin
             Err(_e) => {},
         }
 
-        // Add route to WolfNet subnet via gateway so container can reach other WolfNet hosts.
+        // Add routes to the WolfNet subnets via gateway so container can reach other WolfNet hosts.
         // The `src` hint ensures the kernel uses the WolfNet IP as source, not the
         // Docker bridge IP — critical for cross-node connectivity.
-        let ip_parts: Vec<&str> = ip.split('.').collect();
-        let subnet = if ip_parts.len() == 4 {
-            format!("{}.{}.{}.0/24", ip_parts[0], ip_parts[1], ip_parts[2])
-        } else {
-            wolfnet_subnet_prefix().map(|p| format!("{}.0/24", p)).unwrap_or_default()
-        };
-        if !subnet.is_empty() {
+        for subnet in ipam::container_routes(ip) {
             let _ = Command::new("nsenter")
                 .args(["--target", &container_pid, "--net", "ip", "route", "replace", &subnet, "via", &gateway, "src", ip])
                 .output();
@@ -2384,32 +2507,35 @@ fn ensure_lxcbr0_services(_used_lxc_net: bool) {
     }
 }
 
+/// Host PID of a running container's init process — the handle for
+/// `nsenter` into its namespaces. None when it isn't running.
+pub fn container_init_pid(runtime: &str, container: &str) -> Option<u32> {
+    let out = match runtime {
+        "docker" => Command::new("docker")
+            .args(["inspect", "--format", "{{.State.Pid}}", container])
+            .output(),
+        "lxc" => {
+            let base = lxc_base_dir(container);
+            let mut cmd = Command::new("lxc-info");
+            if base != LXC_DEFAULT_PATH {
+                cmd.args(["-P", &base]);
+            }
+            cmd.args(["-n", container, "-p", "-H"]).output()
+        }
+        _ => return None,
+    };
+    let pid: u32 = out.ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .and_then(|s| s.trim().parse().ok())?;
+    (pid != 0).then_some(pid)
+}
+
 /// Find the host-side veth name for a running LXC container, by
 /// reading the container's `eth0`'s `iflink` from inside its netns and
 /// mapping that ifindex back to a host interface. Returns None if the
 /// container isn't running, has no eth0, or nsenter isn't available.
 fn lxc_container_host_veth(container: &str) -> Option<String> {
-    let base = lxc_base_dir(container);
-    let mut args: Vec<String> = vec!["lxc-info".to_string()];
-    if base != LXC_DEFAULT_PATH {
-        args.push("-P".to_string());
-        args.push(base.clone());
-    }
-    args.extend([
-        "-n".to_string(),
-        container.to_string(),
-        "-p".to_string(),
-        "-H".to_string(),
-    ]);
-    let pid: u32 = Command::new(&args[0])
-        .args(&args[1..])
-        .output()
-        .ok()
-        .and_then(|o| String::from_utf8(o.stdout).ok())
-        .and_then(|s| s.trim().parse().ok())?;
-    if pid == 0 {
-        return None;
-    }
+    let pid = container_init_pid("lxc", container)?;
 
     let iflink: u32 = Command::new("nsenter")
         .args([
@@ -2606,11 +2732,10 @@ fn get_container_bridge_ip(container: &str, iface: &str) -> String {
     }
     // Derive from WolfNet IP so the last octet matches (10.10.10.X → 10.0.3.X)
     let wolfnet_ip_file = format!("{}/{}/.wolfnet/ip", base, container);
-    if let Ok(wolfnet_ip) = std::fs::read_to_string(&wolfnet_ip_file) {
-        let wolfnet_ip = wolfnet_ip.trim();
-        if let Some(last_octet) = wolfnet_ip.rsplit('.').next() {
-            return format!("10.0.3.{}", last_octet);
-        }
+    if let Ok(wolfnet_ip) = std::fs::read_to_string(&wolfnet_ip_file)
+        && let Some(bridge_ip) = bridge_ip_from_wolfnet(container, &wolfnet_ip)
+    {
+        return bridge_ip;
     }
     // Last resort: assign a fresh bridge IP
     warn!("Could not detect bridge IP for {}:{}, assigning new one", container, iface);
@@ -2943,13 +3068,8 @@ fn lxc_apply_wolfnet(container: &str) {
         let is_pve = is_proxmox();
         let _wolfnet_iface = if is_pve { "wn0" } else { "eth0" };
 
-        // Derive WolfNet subnet from the container's WolfNet IP
-        let wn_parts: Vec<&str> = ip.split('.').collect();
-        let wn_subnet = if wn_parts.len() == 4 {
-            format!("{}.{}.{}.0/24", wn_parts[0], wn_parts[1], wn_parts[2])
-        } else {
-            wolfnet_subnet_prefix().map(|p| format!("{}.0/24", p)).unwrap_or_default()
-        };
+        // The WolfNet subnets this container routes — its own first.
+        let wn_subnets = ipam::container_routes(ip);
 
         if is_pve {
             // Proxmox: wn0 is on lxcbr0 with NO IP/gateway in pct config.
@@ -2964,18 +3084,21 @@ fn lxc_apply_wolfnet(container: &str) {
             // Write persistent wn0 config for NetworkManager-based distros (Fedora, AlmaLinux, Rocky).
             // Without this, NM auto-manages wn0 with DHCP and overrides our manual IP assignments.
             // No gateway/DNS on wn0 — those stay on eth0 (vmbr0).
+            let nm_routes: String = wn_subnets.iter().enumerate()
+                .map(|(i, subnet)| format!("route{n}={},10.0.3.1\\nroute{n}_options=src={}\\n", subnet, ip, n = i + 1))
+                .collect();
             let nm_cmd = format!(
                 "if [ -d /etc/NetworkManager ]; then \
                      mkdir -p /etc/NetworkManager/system-connections && \
                      printf '[connection]\\nid=wn0\\ntype=ethernet\\ninterface-name=wn0\\nautoconnect=true\\n\\n\
-[ipv4]\\nmethod=manual\\naddress1={}/24\\naddress2={}/32\\nroute1={},10.0.3.1\\nroute1_options=src={}\\n\\n\
+[ipv4]\\nmethod=manual\\naddress1={}/24\\naddress2={}/32\\n{}\\n\
 [ipv6]\\nmethod=disabled\\n' \
                      > /etc/NetworkManager/system-connections/wn0.nmconnection && \
                      chmod 600 /etc/NetworkManager/system-connections/wn0.nmconnection && \
                      nmcli con reload 2>/dev/null && \
                      nmcli con up wn0 2>/dev/null; \
                  fi; true",
-                bridge_ip, ip, wn_subnet, ip
+                bridge_ip, ip, nm_routes
             );
             let mut nm_args: Vec<String> = attach_prefix.clone();
             nm_args.extend(["sh", "-c", &nm_cmd].iter().map(|s| s.to_string()));
@@ -3001,9 +3124,11 @@ fn lxc_apply_wolfnet(container: &str) {
             // The `src` hint ensures the kernel uses the WolfNet IP as source, not the
             // bridge IP — critical for cross-node connectivity (remote hosts reply to the
             // WolfNet IP, which gets routed back through the overlay).
-            let mut args: Vec<String> = attach_prefix.clone();
-            args.extend(["ip", "route", "replace", &wn_subnet, "via", "10.0.3.1", "dev", "wn0", "src", ip].iter().map(|s| s.to_string()));
-            let _ = Command::new("lxc-attach").args(&args).output();
+            for wn_subnet in &wn_subnets {
+                let mut args: Vec<String> = attach_prefix.clone();
+                args.extend(["ip", "route", "replace", wn_subnet, "via", "10.0.3.1", "dev", "wn0", "src", ip].iter().map(|s| s.to_string()));
+                let _ = Command::new("lxc-attach").args(&args).output();
+            }
 
             // Try to bring up eth0 via NetworkManager (write DHCP config if missing)
             let eth0_nm_cmd = "if [ -d /etc/NetworkManager ]; then \
@@ -3134,9 +3259,11 @@ fn lxc_apply_wolfnet(container: &str) {
             // Route WolfNet subnet with correct source IP — ensures the container
             // uses its WolfNet IP (not the bridge IP) as source when talking to
             // remote WolfNet hosts, so replies get routed back correctly.
-            let mut args: Vec<String> = attach_prefix.clone();
-            args.extend(["ip", "route", "replace", &wn_subnet, "via", "10.0.3.1", "src", ip].iter().map(|s| s.to_string()));
-            let _ = Command::new("lxc-attach").args(&args).output();
+            for wn_subnet in &wn_subnets {
+                let mut args: Vec<String> = attach_prefix.clone();
+                args.extend(["ip", "route", "replace", wn_subnet, "via", "10.0.3.1", "src", ip].iter().map(|s| s.to_string()));
+                let _ = Command::new("lxc-attach").args(&args).output();
+            }
 
             // Host route — via bridge IP so ARP resolves on lxcbr0
             let _ = Command::new("ip").args(["route", "del", &format!("{}/32", ip)]).output();
@@ -3247,7 +3374,7 @@ fn lxc_remove_wolfnet(container: &str, old_ip: &str) {
 
         if running {
             let mut cmd = format!("ip addr del {} dev eth0 2>/dev/null; ", wolfnet_cidr);
-            if let Some(subnet) = wolfnet_subnet_from_ip(old_ip) {
+            for subnet in ipam::container_routes(old_ip) {
                 cmd.push_str(&format!("ip route del {} 2>/dev/null; ", subnet));
             }
             // Re-apply the rewritten config across renderers: NM (reload+up) and
@@ -3418,6 +3545,27 @@ fn find_free_bridge_ip() -> u8 {
 /// Assign a bridge IP to a container. If a WolfNet IP is provided, derives the
 /// bridge IP from its last octet (e.g. x.x.x.101 → 10.0.3.101). Otherwise allocates
 /// the next free bridge IP. Writes network config in either case.
+/// The lxcbr0 IP matching a WolfNet IP's last octet (10.10.10.X → 10.0.3.X).
+/// With overlay subnets two local containers can share a last octet, so an
+/// overlay-subnet IP only gets the matching bridge IP when no other
+/// container here has that octet; None means pick a free one.
+fn bridge_ip_from_wolfnet(container: &str, wolfnet_ip: &str) -> Option<String> {
+    let octet = wolfnet_ip.trim().rsplit('.').next()?.parse::<u8>().ok()?;
+    let primary = wolfnet_subnet_prefix().map(|p| format!("{}.", p));
+    if primary.is_some_and(|p| !wolfnet_ip.starts_with(&p)) {
+        let shared = lxc_storage_paths().iter()
+            .filter_map(|path| std::fs::read_dir(path).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter(|e| e.file_name().to_string_lossy() != container)
+            .filter_map(|e| std::fs::read_to_string(e.path().join(".wolfnet/ip")).ok())
+            .any(|other| other.trim().rsplit('.').next() == Some(octet.to_string().as_str()));
+        if shared {
+            return None;
+        }
+    }
+    Some(format!("10.0.3.{}", octet))
+}
+
 fn assign_container_bridge_ip(container: &str) -> String {
     // Try to derive from wolfnet IP (deterministic — no allocation needed)
     let base = lxc_base_dir(container);
@@ -3427,8 +3575,8 @@ fn assign_container_bridge_ip(container: &str) -> String {
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty());
 
-    let bridge_ip = match wolfnet_ip.as_deref().and_then(|w| w.rsplit('.').next()) {
-        Some(last_octet) => format!("10.0.3.{}", last_octet),
+    let bridge_ip = match wolfnet_ip.as_deref().and_then(|w| bridge_ip_from_wolfnet(container, w)) {
+        Some(ip) => ip,
         None => {
             let last = find_free_bridge_ip();
             format!("10.0.3.{}", last)
@@ -3439,20 +3587,14 @@ fn assign_container_bridge_ip(container: &str) -> String {
     bridge_ip
 }
 
-/// Build the WolfNet /24 subnet from a /32 WolfNet IP — `10.10.20.5` → `10.10.20.0/24`.
-fn wolfnet_subnet_from_ip(ip: &str) -> Option<String> {
-    let parts: Vec<&str> = ip.split('.').collect();
-    if parts.len() != 4 { return None; }
-    Some(format!("{}.{}.{}.0/24", parts[0], parts[1], parts[2]))
-}
-
 /// Write persistent network config into the container's rootfs, covering
 /// every renderer we support (NM, systemd-networkd, netplan, ifupdown).
 ///
 /// `bridge_ip` is the lxcbr0 IP used for host↔container routing.
 /// `wolfnet_ip`, when present, is added as a secondary /32 on the same
-/// interface and routed via the bridge gateway with `src=wolfnet_ip` so
-/// the container uses its WolfNet IP as the source for WolfNet traffic.
+/// interface, and its WolfNet subnets (`ipam::container_routes`) are routed
+/// via the bridge gateway with `src=wolfnet_ip` so the container uses its
+/// WolfNet IP as the source for WolfNet traffic.
 ///
 /// Critical for distros that ship NetworkManager (Linux Mint, Ubuntu
 /// Desktop, Fedora): if the WolfNet IP is only added at runtime via
@@ -3461,7 +3603,7 @@ fn wolfnet_subnet_from_ip(ip: &str) -> Option<String> {
 /// boot.
 fn write_container_network_config(container: &str, bridge_ip: &str, wolfnet_ip: Option<&str>) {
     let rootfs = format!("{}/{}/rootfs", lxc_base_dir(container), container);
-    let wn_subnet = wolfnet_ip.and_then(wolfnet_subnet_from_ip);
+    let wn_subnets = wolfnet_ip.map(ipam::container_routes).unwrap_or_default();
     // When the container has its own vSwitch / public NIC, eth0 is the
     // WolfNet-only NIC here: it must NOT carry a default route, or it
     // would compete with (and on a route flush, replace) the public
@@ -3485,12 +3627,14 @@ fn write_container_network_config(container: &str, bridge_ip: &str, wolfnet_ip:
         } else {
             conf.push_str("Gateway=10.0.3.1\nDNS=10.0.3.1\nDNS=8.8.8.8\n");
         }
-        if let (Some(wip), Some(subnet)) = (wolfnet_ip, &wn_subnet) {
-            // Source-pinned route so reply traffic uses the WolfNet IP, not the bridge IP.
-            conf.push_str(&format!(
-                "\n[Route]\nDestination={}\nGateway=10.0.3.1\nPreferredSource={}\n",
-                subnet, wip
-            ));
+        if let Some(wip) = wolfnet_ip {
+            // Source-pinned routes so reply traffic uses the WolfNet IP, not the bridge IP.
+            for subnet in &wn_subnets {
+                conf.push_str(&format!(
+                    "\n[Route]\nDestination={}\nGateway=10.0.3.1\nPreferredSource={}\n",
+                    subnet, wip
+                ));
+            }
         }
         let _ = std::fs::write(format!("{}/eth0.network", networkd_dir), &conf);
     }
@@ -3509,11 +3653,13 @@ fn write_container_network_config(container: &str, bridge_ip: &str, wolfnet_ip:
         if !wolfnet_only {
             route_lines.push_str("        - to: default\n          via: 10.0.3.1\n");
         }
-        if let (Some(wip), Some(subnet)) = (wolfnet_ip, &wn_subnet) {
-            route_lines.push_str(&format!(
-                "        - to: {}\n          via: 10.0.3.1\n          from: {}\n",
-                subnet, wip
-            ));
+        if let Some(wip) = wolfnet_ip {
+            for subnet in &wn_subnets {
+                route_lines.push_str(&format!(
+                    "        - to: {}\n          via: 10.0.3.1\n          from: {}\n",
+                    subnet, wip
+                ));
+            }
         }
         let routes = if route_lines.is_empty() {
             String::new()
@@ -3550,12 +3696,15 @@ fn write_container_network_config(container: &str, bridge_ip: &str, wolfnet_ip:
         } else {
             conf.push_str("    gateway 10.0.3.1\n    dns-nameservers 10.0.3.1 8.8.8.8\n");
         }
-        if let (Some(wip), Some(subnet)) = (wolfnet_ip, &wn_subnet) {
-            // post-up adds the WolfNet IP as a secondary + the source-pinned subnet route
-            conf.push_str(&format!(
-                "    post-up ip addr add {}/32 dev eth0 || true\n    post-up ip route replace {} via 10.0.3.1 dev eth0 src {} || true\n",
-                wip, subnet, wip
-            ));
+        if let Some(wip) = wolfnet_ip {
+            // post-up adds the WolfNet IP as a secondary + the source-pinned subnet routes
+            conf.push_str(&format!("    post-up ip addr add {}/32 dev eth0 || true\n", wip));
+            for subnet in &wn_subnets {
+                conf.push_str(&format!(
+                    "    post-up ip route replace {} via 10.0.3.1 dev eth0 src {} || true\n",
+                    subnet, wip
+                ));
+            }
         }
         let _ = std::fs::write(&ifaces_path, &conf);
     }
@@ -3580,9 +3729,11 @@ fn write_container_network_config(container: &str, bridge_ip: &str, wolfnet_ip:
         if let Some(wip) = wolfnet_ip {
             ipv4.push_str(&format!("address2={}/32\n", wip));
         }
-        if let (Some(wip), Some(subnet)) = (wolfnet_ip, &wn_subnet) {
-            ipv4.push_str(&format!("route1={},10.0.3.1\n", subnet));
-            ipv4.push_str(&format!("route1_options=src={}\n", wip));
+        if let Some(wip) = wolfnet_ip {
+            for (i, subnet) in wn_subnets.iter().enumerate() {
+                ipv4.push_str(&format!("route{}={},10.0.3.1\n", i + 1, subnet));
+                ipv4.push_str(&format!("route{}_options=src={}\n", i + 1, wip));
+            }
         }
         if wolfnet_only {
             ipv4.push_str("dns=8.8.8.8;1.1.1.1;\n");
@@ -4204,6 +4355,37 @@ pub fn kvm_installed() -> bool {
 
 /// Check if Docker is installed and running
 pub fn docker_status() -> RuntimeStatus {
+    // A socket that answers /version settles all three questions in one
+    // round-trip; one that accepts but doesn't answer is a wedged daemon.
+    let (installed, running, version) = match docker_api::version() {
+        Ok(v) => (true, true, v),
+        Err(docker_api::ApiError::Docker(e)) => {
+            warn!("Docker socket present but daemon not answering: {}", e);
+            (true, false, String::new())
+        }
+        Err(docker_api::ApiError::Unavailable(_)) => docker_status_cli(),
+    };
+
+    let (container_count, running_count) = if running {
+        let total = docker_list_all().len();
+        let running_c = docker_list_running().len();
+        (total, running_c)
+    } else {
+        (0, 0)
+    };
+
+    RuntimeStatus {
+        name: "Docker".to_string(),
+        installed,
+        running,
+        version,
+        container_count,
+        running_count,
+    }
+}
+
+/// (installed, running, version) via the CLI, for hosts without the socket.
+fn docker_status_cli() -> (bool, bool, String) {
     let installed = Command::new("which")
         .arg("docker")
         .output()
@@ -4244,22 +4426,7 @@ pub fn docker_status() -> RuntimeStatus {
         String::new()
     };
 
-    let (container_count, running_count) = if running {
-        let total = docker_list_all().len();
-        let running_c = docker_list_running().len();
-        (total, running_c)
-    } else {
-        (0, 0)
-    };
-
-    RuntimeStatus {
-        name: "Docker".to_string(),
-        installed,
-        running,
-        version,
-        container_count,
-        running_count,
-    }
+    (installed, running, version)
 }
 
 /// Check if LXC is installed and running
@@ -4313,44 +4480,103 @@ pub fn docker_list_running() -> Vec<ContainerInfo> {
     docker_list(false)
 }
 
-fn docker_list(all: bool) -> Vec<ContainerInfo> {
+/// One row of `docker ps`, from the Engine API or the CLI.
+struct DockerPsRow {
+    id: String,
+    name: String,
+    image: String,
+    status: String,
+    state: String,
+    created: String,
+    ports: Vec<String>,
+    networks: String,
+}
+
+/// `docker ps [-a]` rows: `/containers/json` when the socket is there,
+/// the CLI's tab-separated `--format` output otherwise.
+fn docker_ps_rows(all: bool) -> Vec<DockerPsRow> {
+    match docker_api::get_json(&format!("/containers/json?all={}", all as u8)) {
+        Ok(v) => {
+            return v.as_array().into_iter().flatten().map(|c| {
+                let str_of = |k: &str| c.get(k).and_then(|x| x.as_str()).unwrap_or("").to_string();
+                let mut networks: Vec<&str> = c.pointer("/NetworkSettings/Networks")
+                    .and_then(|n| n.as_object())
+                    .map(|m| m.keys().map(|k| k.as_str()).collect())
+                    .unwrap_or_default();
+                networks.sort();
+                DockerPsRow {
+                    id: str_of("Id"),
+                    name: c.get("Names").map(docker_api::primary_name).unwrap_or_default(),
+                    image: str_of("Image"),
+                    status: str_of("Status"),
+                    state: str_of("State"),
+                    created: docker_api::created_at(c.get("Created").and_then(|x| x.as_i64()).unwrap_or(0)),
+                    ports: c.get("Ports").map(docker_api::format_ports).unwrap_or_default(),
+                    networks: networks.join(","),
+                }
+            }).collect();
+        }
+        Err(docker_api::ApiError::Docker(e)) => {
+            warn!("docker ps via API failed: {}", e);
+            return vec![];
+        }
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
+
     let mut cmd = Command::new("docker");
     cmd.args(["ps", "--format", "{{.ID}}\\t{{.Names}}\\t{{.Image}}\\t{{.Status}}\\t{{.State}}\\t{{.CreatedAt}}\\t{{.Ports}}\\t{{.Networks}}", "--no-trunc"]);
     if all {
         cmd.arg("-a");
     }
-
     let ps_out = match cmd.output() {
         Ok(o) => o,
         Err(_) => return vec![],
     };
-    let ps_text = String::from_utf8_lossy(&ps_out.stdout);
-    let lines: Vec<String> = ps_text.lines()
+    String::from_utf8_lossy(&ps_out.stdout).lines()
         .filter(|l| !l.is_empty())
-        .map(|s| s.to_string())
-        .collect();
+        .map(|line| {
+            let parts: Vec<&str> = line.split('\t').collect();
+            let part = |i: usize| parts.get(i).unwrap_or(&"").to_string();
+            DockerPsRow {
+                id: part(0),
+                name: part(1),
+                image: part(2),
+                status: part(3),
+                state: part(4),
+                created: part(5),
+                ports: parts.get(6).unwrap_or(&"")
+                    .split(", ")
+                    .filter(|p| !p.is_empty())
+                    .map(|p| p.to_string())
+                    .collect(),
+                networks: part(7),
+            }
+        })
+        .collect()
+}
 
-    // Collect every container ID from the ps output, then run ONE
-    // `docker inspect <id1> <id2> ...` call and index the result by ID.
-    // This replaces the previous per-container-inspect loop (2 inspect
-    // calls × N containers = 2N subprocesses) with exactly 2 docker
-    // invocations total. Adam Cogswell's Proxmox box wasn't even
+fn docker_list(all: bool) -> Vec<ContainerInfo> {
+    let rows = docker_ps_rows(all);
+
+    // Collect every container ID from the ps rows, then inspect them in
+    // one batch and index the result by ID. This replaces the previous
+    // per-container-inspect loop (2 inspect calls × N containers = 2N
+    // subprocesses). Adam Cogswell's Proxmox box wasn't even
     // running a Docker fleet, but the same N+1 pattern affected any
     // user with more than a handful of containers — ~100ms per inspect
     // × 30 containers = 3s before this fix.
-    let ids: Vec<String> = lines.iter()
-        .filter_map(|line| line.split('\t').next().map(|s| s.to_string()))
+    let ids: Vec<String> = rows.iter()
+        .map(|r| r.id.clone())
         .filter(|id| !id.is_empty())
         .collect();
     let inspect_map: std::collections::HashMap<String, DockerInspectFields> =
         docker_batched_inspect(&ids);
 
-    lines.iter()
-        .map(|line| {
-            let parts: Vec<&str> = line.split('\t').collect();
-            let cid = parts.first().copied().unwrap_or("").to_string();
-            let name = parts.get(1).unwrap_or(&"").to_string();
-            let state = parts.get(4).unwrap_or(&"").to_string();
+    rows.into_iter()
+        .map(|row| {
+            let cid = row.id.clone();
+            let name = row.name.clone();
+            let state = row.state.clone();
 
             // Look up batched inspect data. Containers that race with
             // a `docker rm` between `docker ps` and the inspect call
@@ -4416,20 +4642,14 @@ fn docker_list(all: bool) -> Vec<ContainerInfo> {
                         vec![]
                     };
 
-                    let net_name = parts.get(7).unwrap_or(&"").to_string();
-
                     ContainerInfo {
-                        id: parts.first().unwrap_or(&"").to_string(),
+                        id: row.id,
                         name,
-                        image: parts.get(2).unwrap_or(&"").to_string(),
-                        status: parts.get(3).unwrap_or(&"").to_string(),
-                        state: parts.get(4).unwrap_or(&"").to_string(),
-                        created: parts.get(5).unwrap_or(&"").to_string(),
-                        ports: parts.get(6).unwrap_or(&"")
-                            .split(", ")
-                            .filter(|p| !p.is_empty())
-                            .map(|p| p.to_string())
-                            .collect(),
+                        image: row.image,
+                        status: row.status,
+                        state: row.state,
+                        created: row.created,
+                        ports: row.ports,
                         runtime: "docker".to_string(),
                         ip_address: ip,
                         autostart,
@@ -4442,7 +4662,7 @@ fn docker_list(all: bool) -> Vec<ContainerInfo> {
                         services,
                 gateway: container_gateway,
                 mac_address: container_mac,
-                network_name: net_name,
+                network_name: row.networks,
                 restart_count: Some(fields.restart_count),
                 port_mappings: fields.port_mappings.clone(),
                 possible_ghost: false, // docker containers are never PVE husks
@@ -4476,12 +4696,12 @@ struct DockerInspectFields {
     port_mappings: Vec<PortMapping>,
 }
 
-/// Run ONE `docker inspect <id1> <id2> ...` and parse the resulting JSON
-/// array into a HashMap keyed by container ID. Replaces what used to be
-/// 2 inspect subprocesses per container — for a 30-container fleet
-/// that's 60 forks → 1 fork.
+/// Inspect a batch of containers and parse the results into a HashMap
+/// keyed by container ID. Replaces what used to be 2 inspect
+/// subprocesses per container — for a 30-container fleet that's 60
+/// forks → 1 fork, or none over the socket.
 ///
-/// Falls back to an empty map when `docker inspect` fails outright (the
+/// Falls back to an empty map when the inspect fails outright (the
 /// daemon's down). Per-container fallback isn't necessary because the
 /// caller treats a missing entry as default-fields, which is the same
 /// thing the old code did when an individual inspect failed.
@@ -4500,35 +4720,9 @@ fn docker_batched_inspect(ids: &[String])
     // positive there even when the operator declared `ports:`).
     let net_drivers = docker_network_drivers();
 
-    // Pass IDs as positional args. Avoid building a single space-joined
-    // string — IDs never contain spaces but argv passing is the right
-    // shape for the tool anyway.
-    let mut cmd = Command::new("docker");
-    cmd.arg("inspect");
-    for id in ids { cmd.arg(id); }
-
-    let out = match cmd.output() {
-        Ok(o) if o.status.success() => o,
-        Ok(o) => {
-            warn!(
-                "docker inspect (batched, {} ids) exited {}: {}",
-                ids.len(), o.status.code().unwrap_or(-1),
-                String::from_utf8_lossy(&o.stderr).trim()
-            );
-            return map;
-        }
-        Err(e) => {
-            warn!("docker inspect (batched, {} ids) spawn failed: {}", ids.len(), e);
-            return map;
-        }
-    };
-
-    let arr: Vec<serde_json::Value> = match serde_json::from_slice(&out.stdout) {
-        Ok(a) => a,
-        Err(e) => {
-            warn!("docker inspect output JSON parse failed: {}", e);
-            return map;
-        }
+    let arr = match docker_inspect_many(ids) {
+        Some(a) => a,
+        None => return map,
     };
 
     for entry in arr {
@@ -4568,6 +4762,63 @@ fn docker_batched_inspect(ids: &[String])
     map
 }
 
+/// Raw inspect JSON for each ID. Over the socket that's one cheap request
+/// per container (the API has no batch form); without it, ONE
+/// `docker inspect <id1> <id2> ...`. Containers removed in between are
+/// simply missing. `None` when the daemon can't be asked at all.
+fn docker_inspect_many(ids: &[String]) -> Option<Vec<serde_json::Value>> {
+    let mut arr = Vec::with_capacity(ids.len());
+    let mut via_api = true;
+    for id in ids {
+        match docker_api::get_json(&format!("/containers/{}/json", docker_api::seg(id))) {
+            Ok(v) => arr.push(v),
+            Err(docker_api::ApiError::Docker(e)) => {
+                if !e.contains("No such container") {
+                    warn!("docker inspect {} via API failed: {}", id, e);
+                }
+            }
+            Err(docker_api::ApiError::Unavailable(_)) => {
+                via_api = false;
+                break;
+            }
+        }
+    }
+    if via_api {
+        return Some(arr);
+    }
+
+    // Pass IDs as positional args. Avoid building a single space-joined
+    // string — IDs never contain spaces but argv passing is the right
+    // shape for the tool anyway.
+    let mut cmd = Command::new("docker");
+    cmd.arg("inspect");
+    for id in ids { cmd.arg(id); }
+
+    let out = match cmd.output() {
+        Ok(o) if o.status.success() => o,
+        Ok(o) => {
+            warn!(
+                "docker inspect (batched, {} ids) exited {}: {}",
+                ids.len(), o.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&o.stderr).trim()
+            );
+            return None;
+        }
+        Err(e) => {
+            warn!("docker inspect (batched, {} ids) spawn failed: {}", ids.len(), e);
+            return None;
+        }
+    };
+
+    match serde_json::from_slice(&out.stdout) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            warn!("docker inspect output JSON parse failed: {}", e);
+            None
+        }
+    }
+}
+
 /// Run `docker network ls --format '{{.Name}}\t{{.Driver}}'` once and
 /// return a `name → driver` map. Used by `parse_port_mappings` to skip
 /// the requested-vs-published port diff for `macvlan` / `ipvlan`
@@ -4581,6 +4832,23 @@ fn docker_batched_inspect(ids: &[String])
 /// what catches the with-`ports:` macvlan case (Frigate).
 fn docker_network_drivers() -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
+    match docker_api::get_json("/networks") {
+        Ok(v) => {
+            for n in v.as_array().into_iter().flatten() {
+                let name = n.get("Name").and_then(|x| x.as_str()).unwrap_or("");
+                let driver = n.get("Driver").and_then(|x| x.as_str()).unwrap_or("");
+                if !name.is_empty() && !driver.is_empty() {
+                    map.insert(name.to_string(), driver.to_string());
+                }
+            }
+            return map;
+        }
+        Err(docker_api::ApiError::Docker(e)) => {
+            warn!("docker network list via API failed: {}", e);
+            return map;
+        }
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
     let out = match Command::new("docker")
         .args(["network", "ls", "--format", "{{.Name}}\t{{.Driver}}"])
         .output()
@@ -4792,6 +5060,14 @@ fn split_port_proto(key: &str) -> (u16, String) {
 
 /// Get Docker container stats (one-shot)
 pub fn docker_stats() -> Vec<ContainerStats> {
+    match docker_stats_api() {
+        Ok(stats) => return stats,
+        Err(docker_api::ApiError::Docker(e)) => {
+            warn!("docker stats via API failed: {}", e);
+            return vec![];
+        }
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
     Command::new("docker")
         .args(["stats", "--no-stream", "--format", "{{.ID}}\\t{{.Name}}\\t{{.CPUPerc}}\\t{{.MemUsage}}\\t{{.MemPerc}}\\t{{.NetIO}}\\t{{.BlockIO}}\\t{{.PIDs}}"])
         .output()
@@ -4828,8 +5104,51 @@ pub fn docker_stats() -> Vec<ContainerStats> {
         .unwrap_or_default()
 }
 
+/// One stats sample per running container over the socket. The daemon
+/// takes a second per container to produce the CPU delta, so they are
+/// fetched in parallel — as `docker stats --no-stream` does.
+fn docker_stats_api() -> Result<Vec<ContainerStats>, docker_api::ApiError> {
+    let list = docker_api::get_json("/containers/json")?;
+    let running: Vec<(String, String)> = list.as_array().into_iter().flatten()
+        .map(|c| (
+            c.get("Id").and_then(|x| x.as_str()).unwrap_or("").to_string(),
+            c.get("Names").map(docker_api::primary_name).unwrap_or_default(),
+        ))
+        .filter(|(id, _)| !id.is_empty())
+        .collect();
+    let stats = std::thread::scope(|scope| {
+        let handles: Vec<_> = running.iter().map(|(id, name)| scope.spawn(move || {
+            let v = docker_api::get_json(&format!("/containers/{}/stats?stream=false", docker_api::seg(id))).ok()?;
+            let s = docker_api::stats_sample(&v);
+            Some(ContainerStats {
+                // Short ID, as `docker stats` prints it.
+                id: id.chars().take(12).collect(),
+                name: name.clone(),
+                cpu_percent: s.cpu_percent,
+                memory_usage: s.memory_usage,
+                memory_limit: s.memory_limit,
+                memory_percent: s.memory_percent,
+                net_input: s.net_input,
+                net_output: s.net_output,
+                block_read: s.block_read,
+                block_write: s.block_write,
+                pids: s.pids,
+                runtime: "docker".to_string(),
+            })
+        })).collect();
+        handles.into_iter().filter_map(|h| h.join().ok().flatten()).collect()
+    });
+    Ok(stats)
+}
+
 /// Get Docker container logs
 pub fn docker_logs(container: &str, lines: u32) -> Vec<String> {
+    let path = format!("/containers/{}/logs?stdout=1&stderr=1&timestamps=1&tail={}", docker_api::seg(container), lines);
+    match docker_api::get_bytes(&path) {
+        Ok(body) => return docker_api::demux_logs(&body),
+        Err(docker_api::ApiError::Docker(e)) => return vec![e],
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
     Command::new("docker")
         .args(["logs", "--tail", &lines.to_string(), "--timestamps", container])
         .output()
@@ -4848,7 +5167,8 @@ pub fn docker_logs(container: &str, lines: u32) -> Vec<String> {
 
 /// Start a Docker container
 pub fn docker_start(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["start", container])?;
+    mount_deps::ensure_ready("docker", container)?;
+    let result = docker_lifecycle("POST", &format!("/containers/{}/start", docker_api::seg(container)), &["start", container], container)?;
 
     // Re-apply WolfNet IP if configured (check override file first, then label)
     if let Some(ip) = docker_effective_wolfnet_ip(container) {
@@ -4868,14 +5188,16 @@ pub fn docker_start(container: &str) -> Result<String, String> {
 
 /// Stop a Docker container
 pub fn docker_stop(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["stop", container])?;
+    crate::availability::note_requested("docker", container);
+    let result = docker_lifecycle("POST", &format!("/containers/{}/stop", docker_api::seg(container)), &["stop", container], container)?;
     invalidate_docker_list_cache();
     Ok(result)
 }
 
 /// Restart a Docker container
 pub fn docker_restart(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["restart", container])?;
+    crate::availability::note_requested("docker", container);
+    let result = docker_lifecycle("POST", &format!("/containers/{}/restart", docker_api::seg(container)), &["restart", container], container)?;
     let self_id = crate::agent::self_node_id();
     crate::wolfusb::on_container_started(container, "docker", &self_id);
     invalidate_docker_list_cache();
@@ -4884,7 +5206,7 @@ pub fn docker_restart(container: &str) -> Result<String, String> {
 
 /// Remove a Docker container
 pub fn docker_remove(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["rm", "-f", container]);
+    let result = docker_lifecycle("DELETE", &format!("/containers/{}?force=1", docker_api::seg(container)), &["rm", "-f", container], container);
     if result.is_ok() {
         invalidate_count_caches();
         invalidate_docker_list_cache();
@@ -4894,20 +5216,29 @@ pub fn docker_remove(container: &str) -> Result<String, String> {
 
 /// Pause a Docker container
 pub fn docker_pause(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["pause", container])?;
+    crate::availability::note_requested("docker", container);
+    let result = docker_lifecycle("POST", &format!("/containers/{}/pause", docker_api::seg(container)), &["pause", container], container)?;
     invalidate_docker_list_cache();
     Ok(result)
 }
 
 /// Unpause a Docker container
 pub fn docker_unpause(container: &str) -> Result<String, String> {
-    let result = run_docker_cmd(&["unpause", container])?;
+    let result = docker_lifecycle("POST", &format!("/containers/{}/unpause", docker_api::seg(container)), &["unpause", container], container)?;
     invalidate_docker_list_cache();
     Ok(result)
 }
 
 /// List Docker images
 pub fn docker_images() -> Vec<ContainerImage> {
+    match docker_api::get_json("/images/json") {
+        Ok(v) => return v.as_array().into_iter().flatten().flat_map(docker_image_rows).collect(),
+        Err(docker_api::ApiError::Docker(e)) => {
+            warn!("docker images via API failed: {}", e);
+            return vec![];
+        }
+        Err(docker_api::ApiError::Unavailable(_)) => {}
+    }
     Command::new("docker")
         .args(["images", "--format", "{{.ID}}\\t{{.Repository}}\\t{{.Tag}}\\t{{.Size}}\\t{{.CreatedAt}}"])
         .output()
@@ -4931,6 +5262,38 @@ pub fn docker_images() -> Vec<ContainerImage> {
         .unwrap_or_default()
 }
 
+/// `docker images` rows for one `/images/json` entry: one per tag, or a
+/// single `<none>` row for a dangling image.
+fn docker_image_rows(img: &serde_json::Value) -> Vec<ContainerImage> {
+    let id = img.get("Id").and_then(|x| x.as_str()).unwrap_or("");
+    let id: String = id.trim_start_matches("sha256:").chars().take(12).collect();
+    let size = docker_api::human_size(img.get("Size").and_then(|x| x.as_u64()).unwrap_or(0));
+    let created = docker_api::created_at(img.get("Created").and_then(|x| x.as_i64()).unwrap_or(0));
+    let mut refs: Vec<(String, String)> = img.get("RepoTags").and_then(|x| x.as_array()).into_iter().flatten()
+        .filter_map(|t| t.as_str())
+        .filter(|t| *t != "<none>:<none>")
+        .map(|t| match t.rsplit_once(':') {
+            Some((repo, tag)) if !tag.contains('/') => (repo.to_string(), tag.to_string()),
+            _ => (t.to_string(), "<none>".to_string()),
+        })
+        .collect();
+    if refs.is_empty() {
+        // Untagged but pulled by digest: the CLI shows the repository.
+        let repo = img.get("RepoDigests").and_then(|x| x.as_array())
+            .and_then(|a| a.first()).and_then(|d| d.as_str())
+            .and_then(|d| d.split_once('@')).map(|(r, _)| r.to_string())
+            .unwrap_or_else(|| "<none>".to_string());
+        refs.push((repo, "<none>".to_string()));
+    }
+    refs.into_iter().map(|(repository, tag)| ContainerImage {
+        id: id.clone(),
+        repository,
+        tag,
+        size: size.clone(),
+        created: created.clone(),
+    }).collect()
+}
+
 /// Update Docker container configuration
 pub fn docker_update_config(container: &str, autostart: Option<bool>, memory_mb: Option<u64>, cpus: Option<f32>, wolfnet_ip: Option<String>) -> Result<String, String> {
     let mut messages = Vec::new();
@@ -5174,7 +5537,7 @@ pub fn docker_recreate_from_inspect(container: &str, inspect: &serde_json::Value
     }
 
     // Extract volume mounts: HostConfig.Binds
-    let volumes: Vec<String> = inspect.pointer("/HostConfig/Binds")
+    let mut volumes: Vec<String> = inspect.pointer("/HostConfig/Binds")
         .and_then(|v| v.as_array())
         .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
         .unwrap_or_default();
@@ -5198,11 +5561,20 @@ pub fn docker_recreate_from_inspect(container: &str, inspect: &serde_json::Value
     }
 
     // Extract labels
-    let labels: Vec<(String, String)> = inspect.pointer("/Config/Labels")
+    let mut labels: Vec<(String, String)> = inspect.pointer("/Config/Labels")
         .and_then(|v| v.as_object())
         .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string())).collect())
         .unwrap_or_default();
 
+    // Take out the previous env-store injection (its env vars, secret
+    // mounts and label); the store's current values go back in below.
+    let mut prior_env: Vec<String> = inspect.pointer("/Config/Env")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    env_store::strip_injected(&mut prior_env, &mut volumes, &mut labels);
+    let injection = env_store::docker_injection(&name)?;
+
     // Extract resource limits
     let memory = inspect.pointer("/HostConfig/Memory")
         .and_then(|v| v.as_i64())
@@ -5344,17 +5716,16 @@ pub fn docker_recreate_from_inspect(container: &str, inspect: &serde_json::Value
 
     // Env comes from /Config/Env on the provided inspect — either the
     // live container's (plain recreate) or the operator's edited spec
-    // (Edit Raw Config path).
-    let env_list: Vec<String> = inspect.pointer("/Config/Env")
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-        .unwrap_or_default();
+    // (Edit Raw Config path), less the previous env-store injection; the
+    // store's current values follow.
+    let env_list = env_store::without_keys(&prior_env, &injection.keys);
     for e in &env_list {
         if !e.is_empty() {
             args.push("-e".to_string());
             args.push(e.clone());
         }
     }
+    args.extend(injection.args.iter().cloned());
 
     // Entrypoint (must come before image — only accepts the executable)
     if !entrypoint.is_empty() {
@@ -5404,19 +5775,25 @@ pub fn docker_recreate_from_inspect(container: &str, inspect: &serde_json::Value
 
 /// Inspect a Docker container and return raw JSON
 pub fn docker_inspect(container: &str) -> Result<serde_json::Value, String> {
-    let output = Command::new("docker")
-        .args(["inspect", container])
-        .output()
-        .map_err(|e| e.to_string())?;
+    let json = match docker_api::get_json(&format!("/containers/{}/json", docker_api::seg(container))) {
+        Ok(v) => v,
+        Err(docker_api::ApiError::Docker(e)) => return Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => {
+            let output = Command::new("docker")
+                .args(["inspect", container])
+                .output()
+                .map_err(|e| e.to_string())?;
 
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
+            if !output.status.success() {
+                return Err(String::from_utf8_lossy(&output.stderr).to_string());
+            }
 
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+            serde_json::from_slice(&output.stdout)
+                .map_err(|e| format!("Failed to parse JSON: {}", e))?
+        }
+    };
 
-    // docker inspect returns an array, take the first element if possible
+    // The CLI returns an array, the API a single object
     let mut obj = if let Some(arr) = json.as_array() {
         arr.first().cloned().unwrap_or(json.clone())
     } else {
@@ -5547,14 +5924,56 @@ pub fn docker_set_wolfnet_ip(container: &str, ip: Option<&str>) -> Result<String
 
 /// Remove a Docker image by ID or name
 pub fn docker_remove_image(image: &str) -> Result<String, String> {
+    match docker_api::call("DELETE", &format!("/images/{}", docker_api::image_seg(image)), None, docker_api::READ_TIMEOUT) {
+        // [{"Untagged": …}, {"Deleted": …}] → the CLI's "Untagged: …" lines.
+        Ok(v) => Ok(v.as_array().into_iter().flatten()
+            .filter_map(|e| e.as_object())
+            .flat_map(|o| o.iter().map(|(k, v)| format!("{}: {}", k, v.as_str().unwrap_or(""))))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        Err(docker_api::ApiError::Docker(e)) => Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => run_docker_cmd(&["rmi", image]),
+    }
+}
+
+/// Prune unused Docker images. Dangling (untagged) images only, unless
+/// `all` is set — then every image no container references is removed.
+/// Returns docker's own summary line ("Total reclaimed space: …").
+pub fn docker_prune_images(all: bool) -> Result<String, String> {
+    // `dangling=false` is what `docker image prune -a` sends.
+    let path = if all {
+        format!("/images/prune?filters={}", urlencoding::encode(r#"{"dangling":["false"]}"#))
+    } else {
+        "/images/prune".to_string()
+    };
+    let summary = match docker_api::call("POST", &path, None, docker_api::LIFECYCLE_TIMEOUT) {
+        Ok(v) => format!("Total reclaimed space: {}",
+            docker_api::human_size(v.get("SpaceReclaimed").and_then(|x| x.as_u64()).unwrap_or(0))),
+        Err(docker_api::ApiError::Docker(e)) => return Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => {
+            let mut args = vec!["image", "prune", "-f"];
+            if all { args.push("-a"); }
+            let out = run_docker_cmd(&args)?;
+            out.lines().last().unwrap_or("Nothing to prune").to_string()
+        }
+    };
+    invalidate_list_caches();
+    Ok(summary)
+}
 
-    run_docker_cmd(&["rmi", image])
+/// Start/stop/remove-style call over the Engine API, or `docker <cli_args>`
+/// when the socket isn't there. Returns the container name, which is what
+/// the CLI prints.
+fn docker_lifecycle(method: &str, path: &str, cli_args: &[&str], container: &str) -> Result<String, String> {
+    match docker_api::call(method, path, None, docker_api::LIFECYCLE_TIMEOUT) {
+        Ok(_) => Ok(container.to_string()),
+        Err(docker_api::ApiError::Docker(e)) => Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => run_docker_cmd(cli_args),
+    }
 }
 
 fn run_docker_cmd(args: &[&str]) -> Result<String, String> {
-    let output = Command::new("docker")
-        .args(args)
-        .output()
+    let output = crate::logging::run_traced(Command::new("docker").args(args))
         .map_err(|e| format!("Failed to run docker: {}", e))?;
 
     if output.status.success() {
@@ -6433,6 +6852,7 @@ pub fn lxc_set_root_password(container: &str, password: &str) -> Result<String,
 /// or a short timeout, then surface the LXC log tail as the error so the
 /// user can act on it.
 pub fn lxc_start(container: &str) -> Result<String, String> {
+    mount_deps::ensure_ready("lxc", container)?;
     ensure_lxc_bridge();
     // On AppArmor-less LXC (Fedora/SELinux) an old `lxc.apparmor.profile` line
     // makes lxc-start reject the whole config — strip it first so the container
@@ -6665,6 +7085,7 @@ pub fn lxc_is_running(container: &str) -> bool {
 }
 
 pub fn lxc_stop(container: &str) -> Result<String, String> {
+    crate::availability::note_requested("lxc", container);
     if is_proxmox() {
         run_lxc_cmd(&["pct", "stop", container])
     } else {
@@ -6709,6 +7130,7 @@ fn lxc_is_ha_managed(container: &str) -> bool {
 /// changing the HA desired state, so HA never tears it back down. Non-HA and
 /// native-LXC containers keep the exact stop+start path they always used.
 pub fn lxc_restart(container: &str) -> Result<String, String> {
+    crate::availability::note_requested("lxc", container);
     if is_proxmox() && lxc_is_ha_managed(container) {
         // `pct reboot` only works on a running container (it errors on a
         // stopped one); if it isn't running, just start it — that's the
@@ -6735,6 +7157,7 @@ pub fn lxc_restart(container: &str) -> Result<String, String> {
 /// liblxc tools and its container runtime lives at the default LXC
 /// path (`/var/lib/lxc/<vmid>`), so no `-P` override is needed.
 pub fn lxc_freeze(container: &str) -> Result<String, String> {
+    crate::availability::note_requested("lxc", container);
     if is_proxmox() {
         run_lxc_cmd(&["lxc-freeze", "-n", container])
     } else {
@@ -6782,6 +7205,8 @@ pub fn lxc_destroy(container: &str) -> Result<String, String> {
     if result.is_ok() {
         invalidate_count_caches();
         release_lxc_vlan_allocations(container);
+        mount_deps::forget("lxc", container);
+        startup_order::forget("lxc", container);
         return result;
     }
     // Fallback for native LXC: if lxc-destroy still couldn't load/destroy the
@@ -6801,6 +7226,8 @@ pub fn lxc_destroy(container: &str) -> Result<String, String> {
         {
             invalidate_count_caches();
             release_lxc_vlan_allocations(container);
+            mount_deps::forget("lxc", container);
+            startup_order::forget("lxc", container);
             return Ok(format!(
                 "Removed '{}' by deleting its directory — lxc-destroy couldn't load its config.",
                 container
@@ -6835,6 +7262,7 @@ pub fn docker_remove_permanent(container: &str) -> Result<String, String> {
     if result.is_ok() {
         crate::networking::vlan::release_target_allocations(
             crate::networking::vlan::TargetKind::Docker, container);
+        mount_deps::forget("docker", container);
     }
     result
 }
@@ -6845,8 +7273,18 @@ pub fn lxc_config(container: &str) -> Option<String> {
     std::fs::read_to_string(&path).ok()
 }
 
-/// Save LXC container config (creates .bak backup first)
-pub fn lxc_save_config(container: &str, content: &str) -> Result<String, String> {
+/// The container whose config file lives at `path`, if it is one.
+pub fn lxc_of_config_path(path: &str) -> Option<String> {
+    let p = std::path::Path::new(path);
+    if p.file_name()? != "config" {
+        return None;
+    }
+    let name = p.parent()?.file_name()?.to_str()?.to_string();
+    (format!("{}/{}/config", lxc_base_dir(&name), name) == path).then_some(name)
+}
+
+/// Save LXC container config (the previous version is kept in config history)
+pub fn lxc_save_config(container: &str, content: &str, user: &str) -> Result<String, String> {
     let path = format!("{}/{}/config", lxc_base_dir(container), container);
     if !std::path::Path::new(&path).exists() {
         return Err(format!("Container '{}' config not found", container));
@@ -6856,10 +7294,9 @@ pub fn lxc_save_config(container: &str, content: &str) -> Result<String, String>
     if let Err(e) = validate_wolfnet_vlan_conflict(content) {
         return Err(e);
     }
+    crate::config_check::check_lxc_config(content)?;
 
-    let backup = format!("{}.bak", path);
-    let _ = std::fs::copy(&path, &backup);
-    std::fs::write(&path, content)
+    crate::config_history::save(&path, content, user, "Raw config edit")
         .map(|_| format!("Config saved for '{}'", container))
         .map_err(|e| format!("Failed to save config: {}", e))
 }
@@ -6985,6 +7422,14 @@ pub struct LxcParsedConfig {
     // size, quota) in the Add Mount modal.
     #[serde(default)]
     pub proxmox: bool,
+
+    // What the running container's cgroup enforces right now (see
+    // `lxc_live_limits`), same shapes as memory_limit / cpus. None when
+    // stopped. Filled in by the parsed-config endpoint only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_memory_limit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_cpus: Option<String>,
 }
 /// Parse a Proxmox-format config (/etc/pve/lxc/<vmid>.conf)
 /// Format: `key: value` with network as `net0: name=eth0,bridge=vmbr0,hwaddr=...,ip=...,gw=...`
@@ -7892,7 +8337,7 @@ fn pct_update_settings(container: &str, settings: &LxcSettingsUpdate) -> Result<
 }
 
 /// Parse memory string (e.g. "512M", "1G", "1024") to MB
-fn parse_mem_to_mb(mem: &str) -> u64 {
+pub fn parse_mem_to_mb(mem: &str) -> u64 {
     let mem = mem.trim();
     if mem.is_empty() { return 0; }
     // Normalise: strip trailing "B" so both "MB" and "M" work, "GB"
@@ -8293,15 +8738,29 @@ pub fn lxc_update_settings(container: &str, settings: &LxcSettingsUpdate) -> Res
         lxc_write_notes(container, n)?;
     }
 
+    // Memory / CPU limits changed on a running container: push them into
+    // its cgroup now rather than waiting for the next start to read the
+    // config. Only what the form actually changed is touched.
+    let memory_changed = settings.memory_limit.as_deref().filter(|m| *m != current.memory_limit);
+    let cpus_changed = settings.cpus.as_deref().filter(|c| *c != current.cpus);
+    let live_note = if (memory_changed.is_some() || cpus_changed.is_some()) && lxc_is_running(container) {
+        match lxc_apply_live_limits(container, memory_changed, cpus_changed) {
+            Ok(applied) => format!(" — applied live: {}", applied.join(", ")),
+            Err(e) => format!(" — resource limits take effect on restart (live update failed: {})", e),
+        }
+    } else {
+        String::new()
+    };
+
     // Drop the cached LXC list so the UI re-render picks up the new
     // settings immediately (see the equivalent invalidate in
     // pct_update_settings for the full rationale).
     invalidate_list_caches();
 
     Ok(if net_applied {
-        format!("Settings updated for '{}'. Restart the container to apply the network changes.", container)
+        format!("Settings updated for '{}'{}. Restart the container to apply the network changes.", container, live_note)
     } else {
-        format!("Settings updated for '{}'", container)
+        format!("Settings updated for '{}'{}", container, live_note)
     })
 }
 
@@ -8410,7 +8869,6 @@ pub fn lxc_set_network_link(container: &str, link: &str) -> Result<String, Strin
     Ok(format!("Network link set to {}", link))
 }
 
-/// Find the next available WolfNet IP not in use by any LXC container
 /// The set of WolfNet IPs already in use, cluster-wide as far as this node can
 /// see (config peers, live interfaces, local containers/VMs/Docker, WolfRun
 /// service VIPs + instances on any node, IP mappings, and the poll route cache
@@ -8613,56 +9071,27 @@ pub fn wolfnet_ip_previously_used(ip: &str) -> bool {
     !bare.is_empty() && load_wolfnet_ip_history().contains(bare)
 }
 
+/// Lease the next free WolfNet IP from the cluster-wide IPAM ledger (see
+/// `ipam`). PRISTINE addresses — never used before — go first: reusing a
+/// released IP risks colliding with routing the old node never withdrew, so
+/// fresh addresses are exhausted before recycling.
 pub fn next_available_wolfnet_ip() -> Option<String> {
-    let (prefix, used) = wolfnet_used_ip_set()?;
-    let history = load_wolfnet_ip_history();
-    // First choice: a PRISTINE address — free AND never used before. Reusing a
-    // released IP risks colliding with routing the old node never withdrew, so
-    // we exhaust fresh addresses before recycling.
-    for i in 2..=254u8 {
-        let candidate = format!("{}.{}", prefix, i);
-        if !used.contains(&candidate) && !history.contains(&candidate) {
-            return Some(candidate);
-        }
-    }
-    // Fallback: every fresh IP is taken — recycle the lowest released one.
-    for i in 2..=254u8 {
-        let candidate = format!("{}.{}", prefix, i);
-        if !used.contains(&candidate) {
-            return Some(candidate);
-        }
-    }
+    ipam::allocate(1, None)?.pop()
+}
 
-    None
+/// [`next_available_wolfnet_ip`] for a workload owned by `tenant`, from the
+/// tenant's overlay subnet when it has one.
+pub fn next_available_wolfnet_ip_for(tenant: Option<&str>) -> Option<String> {
+    ipam::allocate(1, tenant)?.pop()
 }
 
-/// Allocate `n` DISTINCT free WolfNet IPs in a single pass over the used set.
+/// Allocate `n` DISTINCT free WolfNet IPs in one lease.
 /// Used when one host provisions containers that will live on several hosts
 /// (e.g. a cross-host Galera cluster): the per-call allocator would re-hand the
 /// same lowest free address until each container's IP propagates, so we reserve
 /// the whole batch at once. Returns None if fewer than `n` are free.
 pub fn next_available_wolfnet_ips(n: usize) -> Option<Vec<String>> {
-    if n == 0 { return Some(Vec::new()); }
-    let (prefix, used) = wolfnet_used_ip_set()?;
-    let history = load_wolfnet_ip_history();
-    let mut out = Vec::with_capacity(n);
-    // Pristine (never-used) addresses first…
-    for i in 2..=254u8 {
-        let candidate = format!("{}.{}", prefix, i);
-        if !used.contains(&candidate) && !history.contains(&candidate) {
-            out.push(candidate);
-            if out.len() == n { return Some(out); }
-        }
-    }
-    // …then recycle released ones only if we still need more.
-    for i in 2..=254u8 {
-        let candidate = format!("{}.{}", prefix, i);
-        if !used.contains(&candidate) && !out.contains(&candidate) {
-            out.push(candidate);
-            if out.len() == n { return Some(out); }
-        }
-    }
-    None
+    ipam::allocate(n, None)
 }
 
 /// Detect duplicate MAC addresses and IP addresses across all LXC containers
@@ -8737,6 +9166,8 @@ pub fn detect_network_conflicts() -> Vec<NetworkConflict> {
 
 /// Autostart all enabled LXC containers, then re-apply WolfNet networking.
 /// lxc-autostart doesn't call our lxc_apply_wolfnet(), so we do it afterwards.
+/// When any guest has a startup order configured, autostart VMs are started
+/// here too, interleaved with the containers (see [`startup_order`]).
 pub fn lxc_autostart_all() {
     // Proxmox handles container autostart itself — skip
     if is_proxmox() { return; }
@@ -8748,8 +9179,42 @@ pub fn lxc_autostart_all() {
     // machine boot.
     if !host_recently_booted() { return; }
 
-    // Start containers with autostart enabled (timeout to prevent blocking startup)
-    let _ = Command::new("timeout").args(["30", "lxc-autostart"]).output();
+    let startup = startup_order::StartupOrder::load();
+    if !startup.is_empty() {
+        startup_order::autostart_all(&startup);
+        std::thread::sleep(std::time::Duration::from_secs(3));
+        reapply_wolfnet_routes();
+        return;
+    }
+
+    // Containers that require storage mounts wait for them first. When one
+    // never comes up, lxc-autostart can't skip just its containers, so walk
+    // its start list instead and hold those back.
+    let deps = mount_deps::MountDeps::load();
+    let order = if deps.lxc.is_empty() { Vec::new() } else { lxc_autostart_list() };
+    let needed = mount_deps::required_by(&deps, "lxc", order.iter().map(|(n, _)| n.as_str()));
+    let wait = if needed.is_empty() {
+        mount_deps::MountWait::default()
+    } else {
+        mount_deps::wait_for_mounts(&needed, std::time::Duration::from_secs(mount_deps::BOOT_WAIT_SECS))
+    };
+
+    if wait.failed.is_empty() {
+        // Start containers with autostart enabled (timeout to prevent blocking startup)
+        let _ = Command::new("timeout").args(["30", "lxc-autostart"]).output();
+    } else {
+        for (name, delay) in &order {
+            if let Some(id) = wait.blocking(&deps.required("lxc", name)) {
+                tracing::error!("Not autostarting '{}' — required storage mount '{}' is down: {}",
+                    name, id, wait.failed[id]);
+                continue;
+            }
+            if let Err(e) = run_lxc_cmd(&["lxc-start", "-n", name]) {
+                tracing::error!("Autostart of '{}' failed: {}", name, e);
+            }
+            std::thread::sleep(std::time::Duration::from_secs(*delay));
+        }
+    }
 
     // Give containers a moment to initialise their network interfaces
     std::thread::sleep(std::time::Duration::from_secs(3));
@@ -8758,11 +9223,27 @@ pub fn lxc_autostart_all() {
     reapply_wolfnet_routes();
 }
 
+/// What `lxc-autostart` would start, in its order: (name, seconds to wait
+/// before the next one).
+fn lxc_autostart_list() -> Vec<(String, u64)> {
+    Command::new("timeout").args(["10", "lxc-autostart", "-L"]).output().ok()
+        .map(|o| parse_autostart_list(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+fn parse_autostart_list(out: &str) -> Vec<(String, u64)> {
+    out.lines()
+        .filter_map(|l| {
+            let mut f = l.split_whitespace();
+            let name = f.next()?.to_string();
+            Some((name, f.next().and_then(|d| d.parse().ok()).unwrap_or(0)))
+        })
+        .collect()
+}
+
 fn run_lxc_cmd(args: &[&str]) -> Result<String, String> {
     let cmd = args[0];
-    let output = Command::new(cmd)
-        .args(&args[1..])
-        .output()
+    let output = crate::logging::run_traced(Command::new(cmd).args(&args[1..]))
         .map_err(|e| format!("Failed to run {}: {}", cmd, e))?;
 
     if output.status.success() {
@@ -8788,43 +9269,36 @@ pub fn lxc_list_templates() -> Vec<LxcTemplate> {
     if is_proxmox() {
         return lxc_list_templates_proxmox();
     }
+    // Registered custom rootfs tarballs are listed first.
+    let mut templates = lxc_images::custom_templates();
+    templates.extend(lxc_list_templates_standalone());
+    templates
+}
 
-    // Standalone: fetch from lxc image server index
-    let output = Command::new("wget")
-        .args(["-qO-", "https://images.linuxcontainers.org/meta/1.0/index-system"])
-        .output();
-
-    // If wget isn't available, try curl
-    let output = match output {
-        Ok(o) if o.status.success() => o,
-        _ => {
-            match Command::new("curl")
-                .args(["-sL", "https://images.linuxcontainers.org/meta/1.0/index-system"])
-                .output()
-            {
-                Ok(o) if o.status.success() => o,
-                _ => {
-                    // Return a curated list of common templates as fallback
-                    return vec![
-                        LxcTemplate { distribution: "ubuntu".into(), release: "24.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "ubuntu".into(), release: "22.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "ubuntu".into(), release: "20.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "debian".into(), release: "bookworm".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "debian".into(), release: "bullseye".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "alpine".into(), release: "3.19".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "alpine".into(), release: "3.18".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "fedora".into(), release: "39".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "centos".into(), release: "9-Stream".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "archlinux".into(), release: "current".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "rockylinux".into(), release: "9".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                        LxcTemplate { distribution: "opensuse".into(), release: "15.5".into(), architecture: host_container_arch().into(), variant: "default".into() },
-                    ];
-                }
-            }
+fn lxc_list_templates_standalone() -> Vec<LxcTemplate> {
+    // Fetch the image server index — configured mirrors first, then
+    // images.linuxcontainers.org unless the operator has disabled it.
+    let text = match lxc_images::fetch_index(&lxc_images::ImageSettings::load()) {
+        Ok((_server, text)) => text,
+        Err(_) => {
+            // Return a curated list of common templates as fallback
+            return vec![
+                LxcTemplate { distribution: "ubuntu".into(), release: "24.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "ubuntu".into(), release: "22.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "ubuntu".into(), release: "20.04".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "debian".into(), release: "bookworm".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "debian".into(), release: "bullseye".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "alpine".into(), release: "3.19".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "alpine".into(), release: "3.18".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "fedora".into(), release: "39".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "centos".into(), release: "9-Stream".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "archlinux".into(), release: "current".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "rockylinux".into(), release: "9".into(), architecture: host_container_arch().into(), variant: "default".into() },
+                LxcTemplate { distribution: "opensuse".into(), release: "15.5".into(), architecture: host_container_arch().into(), variant: "default".into() },
+            ];
         }
     };
 
-    let text = String::from_utf8_lossy(&output.stdout);
     let mut templates = Vec::new();
     let mut seen = std::collections::HashSet::new();
 
@@ -9048,6 +9522,13 @@ fn uptime_within_window(proc_uptime: &str, window_secs: f64) -> bool {
 mod autostart_gate_tests {
     use super::uptime_within_window;
 
+    #[test]
+    fn autostart_list_is_parsed_with_delays() {
+        assert_eq!(super::parse_autostart_list("db 5\nweb 0\ncache\n\n"), vec![
+            ("db".to_string(), 5), ("web".to_string(), 0), ("cache".to_string(), 0),
+        ]);
+    }
+
     #[test]
     fn autostart_only_within_boot_window() {
         // /proc/uptime is "<seconds-since-boot> <idle-seconds>".
@@ -9851,6 +10332,14 @@ pub struct ContainerExportMeta {
     pub lxc_config: Option<String>,
 }
 
+/// GNU tar with `--sort` and a gzip that takes `--rsyncable` (gzip 1.7+).
+static TAR_RSYNCABLE: std::sync::LazyLock<bool> = std::sync::LazyLock::new(|| {
+    Command::new("sh")
+        .args(["-c", "gzip --rsyncable -c </dev/null >/dev/null && tar --sort=name -cf /dev/null -T /dev/null"])
+        .stderr(std::process::Stdio::null())
+        .status().map(|s| s.success()).unwrap_or(false)
+});
+
 /// Export an LXC container to an archive file
 /// Returns (archive_path, metadata)
 pub fn lxc_export(container: &str) -> Result<(std::path::PathBuf, ContainerExportMeta), String> {
@@ -9898,8 +10387,17 @@ pub fn lxc_export(container: &str) -> Result<(std::path::PathBuf, ContainerExpor
         let archive_name = format!("{}.tar.gz", container);
         let archive_path = export_dir.join(&archive_name);
 
-        let output = Command::new("tar")
-            .args(["czf", archive_path.to_str().unwrap(),
+        let mut tar = Command::new("tar");
+        if *TAR_RSYNCABLE {
+            // Stable member order and a resettable gzip stream: a container
+            // that changed a little exports to an archive that differs a
+            // little, so a repeat transfer only ships the changed chunks.
+            tar.args(["--sort=name", "-I", "gzip --rsyncable", "-cf"]);
+        } else {
+            tar.arg("czf");
+        }
+        let output = tar
+            .args([archive_path.to_str().unwrap(),
                    "--exclude=./proc/*", "--exclude=./sys/*", "--exclude=./dev/*",
                    "-C", tar_source, "."])
             .output()
@@ -10153,6 +10651,9 @@ pub(crate) fn lxc_archive_is_vzdump(archive_path: &str) -> bool {
 pub(crate) fn lxc_extract_archive_to_rootfs(archive_path: &str, rootfs_target: &str) -> Result<(), String> {
     let tar_args: Vec<&str> = if archive_path.ends_with(".tar.zst") || archive_path.ends_with(".zst") {
         vec!["--zstd", "-xf", archive_path, "-C", rootfs_target]
+    } else if archive_path.ends_with(".tar.xz") {
+        // Image-server rootfs tarballs (lxc_images cache) are xz.
+        vec!["xJf", archive_path, "-C", rootfs_target]
     } else {
         vec!["xzf", archive_path, "-C", rootfs_target]
     };
@@ -10576,6 +11077,41 @@ pub fn pct_adopt_native_orphan(name: &str) -> Result<String, String> {
     Ok(vmid)
 }
 
+/// Create a standalone container from a rootfs tarball already on this
+/// host (see `lxc_images`). Extracts into `<storage>/<name>/rootfs` and
+/// writes the same bootable config an import synthesises.
+fn lxc_create_from_rootfs(name: &str, tarball: &std::path::Path, storage_path: Option<&str>) -> Result<String, String> {
+    let custom_base = storage_path.filter(|p| !p.is_empty() && *p != LXC_DEFAULT_PATH);
+    let base = custom_base.unwrap_or(LXC_DEFAULT_PATH);
+    let container_dir = format!("{}/{}", base, name);
+    if std::path::Path::new(&container_dir).exists() {
+        return Err(format!("Container '{}' already exists", name));
+    }
+    let rootfs = format!("{}/rootfs", container_dir);
+    std::fs::create_dir_all(&rootfs).map_err(|e| format!("Failed to create container dir: {}", e))?;
+    if let Err(e) = lxc_extract_archive_to_rootfs(&tarball.to_string_lossy(), &rootfs) {
+        let _ = std::fs::remove_dir_all(&container_dir);
+        return Err(format!("Failed to create LXC container: {}", e));
+    }
+    if let Some(path) = custom_base {
+        lxc_register_path(path);
+    }
+    lxc_write_bootable_config(&container_dir, name, None);
+    // Same swap default lxc_create applies to download-template containers.
+    let cfg_path = format!("{}/config", container_dir);
+    if let Ok(mut cfg) = std::fs::read_to_string(&cfg_path) {
+        if !cfg.contains("memory.swap.max") && !cfg.contains("memory.memsw") {
+            cfg.push_str("\nlxc.cgroup2.memory.swap.max = 0\n");
+            let _ = std::fs::write(&cfg_path, cfg);
+        }
+    }
+    lxc_ensure_network_config(name);
+    invalidate_count_caches();
+    let storage_info = custom_base.map(|p| format!(" on {}", p)).unwrap_or_default();
+    Ok(format!("Container '{}' created from {}{}", name,
+        tarball.file_name().unwrap_or_default().to_string_lossy(), storage_info))
+}
+
 /// Create an LXC container from a download template
 /// On Proxmox nodes, automatically uses `pct create` instead of `lxc-create`
 pub fn lxc_create(name: &str, distribution: &str, release: &str, architecture: &str,
@@ -10592,6 +11128,12 @@ pub fn lxc_create(name: &str, distribution: &str, release: &str, architecture: &
         return result.map(|(_vmid, msg)| msg);
     }
 
+    // A registered custom image, or a distro image from a configured
+    // mirror — WolfStack downloads and verifies those itself.
+    if let Some(rootfs) = lxc_images::rootfs_for(distribution, release, architecture)? {
+        return lxc_create_from_rootfs(name, &rootfs, storage_path);
+    }
+
     // Standalone: use native lxc-create
     let mut args = vec![
         "-t", "download",
@@ -10861,10 +11403,69 @@ pub fn docker_pull(image: &str) -> Result<String, String> {
 /// Create a Docker container from an image
 /// If wolfnet_ip is provided, the container will be connected to the WolfNet overlay network
 /// volumes: list of volume mount specs, e.g. ["/host/path:/container/path", "myvolume:/data"]
+/// storage: per-container writable-layer quota passed as `--storage-opt size=`
 pub fn docker_create(name: &str, image: &str, ports: &[String], env: &[String], wolfnet_ip: Option<&str>,
-                     memory: Option<&str>, cpus: Option<&str>, _storage: Option<&str>,
+                     memory: Option<&str>, cpus: Option<&str>, storage: Option<&str>,
                      volumes: &[String]) -> Result<String, String> {
-    docker_create_with_cmd(name, image, ports, env, wolfnet_ip, memory, cpus, _storage, volumes, &[])
+    docker_create_with_cmd(name, image, ports, env, wolfnet_ip, memory, cpus, storage, volumes, &[])
+}
+
+/// Docker sizes: a number with an optional k/m/g/t unit (optionally
+/// followed by b), e.g. `10g`, `512m`, `20GB`.
+fn valid_docker_size(s: &str) -> bool {
+    let lower = s.to_ascii_lowercase();
+    let trimmed = lower.strip_suffix('b').unwrap_or(&lower);
+    let digits = trimmed.trim_end_matches(['k', 'm', 'g', 't']);
+    trimmed.len() - digits.len() <= 1 && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Whether the filesystem holding `root_dir` supports overlay2 size
+/// quotas: xfs mounted with project quotas. Picks the longest mount
+/// point that prefixes the path, as the kernel does.
+fn overlay_quota_supported(proc_mounts: &str, root_dir: &str) -> bool {
+    proc_mounts
+        .lines()
+        .filter_map(|l| {
+            let f: Vec<&str> = l.split_whitespace().collect();
+            if f.len() < 4 { return None; }
+            let mp = f[1];
+            let under = root_dir == mp || mp == "/" || root_dir.starts_with(&format!("{}/", mp));
+            under.then_some((mp.len(), f[2], f[3]))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, fs, opts)| fs == "xfs" && opts.split(',').any(|o| o == "prjquota" || o == "pquota"))
+        .unwrap_or(false)
+}
+
+/// Check that `docker create --storage-opt size=` will be honoured.
+/// overlay2 (the default) only enforces it on xfs with pquota; Docker
+/// otherwise fails the create with an opaque driver error, so explain
+/// why up front.
+pub fn docker_storage_quota_support() -> Result<String, String> {
+    let out = Command::new("docker")
+        .args(["info", "--format", "{{.Driver}}|{{.DockerRootDir}}"])
+        .output()
+        .map_err(|e| format!("Failed to run docker info: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("docker info failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let (driver, root) = text.split_once('|').unwrap_or((text.as_str(), "/var/lib/docker"));
+    match driver {
+        "btrfs" | "zfs" | "devicemapper" | "windowsfilter" => Ok(driver.to_string()),
+        "overlay2" => {
+            let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+            if overlay_quota_supported(&mounts, root) {
+                Ok(driver.to_string())
+            } else {
+                Err(format!(
+                    "Disk quotas need {} on xfs mounted with pquota (overlay2 cannot enforce them on other filesystems)",
+                    root
+                ))
+            }
+        }
+        other => Err(format!("Docker storage driver '{}' does not support per-container disk quotas", other)),
+    }
 }
 
 /// Like `docker_create` but also passes `cmd` as positional args
@@ -10874,7 +11475,7 @@ pub fn docker_create(name: &str, image: &str, ports: &[String], env: &[String],
 /// calling `docker_create`.
 #[allow(clippy::too_many_arguments)]
 pub fn docker_create_with_cmd(name: &str, image: &str, ports: &[String], env: &[String], wolfnet_ip: Option<&str>,
-                     memory: Option<&str>, cpus: Option<&str>, _storage: Option<&str>,
+                     memory: Option<&str>, cpus: Option<&str>, storage: Option<&str>,
                      volumes: &[String], cmd: &[String]) -> Result<String, String> {
 
     // Pre-flight: refuse the create if any requested host port is
@@ -10908,6 +11509,14 @@ pub fn docker_create_with_cmd(name: &str, image: &str, ports: &[String], env: &[
             args.push(cpu.to_string());
         }
     }
+    if let Some(size) = storage.map(str::trim).filter(|s| !s.is_empty()) {
+        if !valid_docker_size(size) {
+            return Err(format!("Invalid disk quota '{}' — use a size like 10g or 512m", size));
+        }
+        docker_storage_quota_support()?;
+        args.push("--storage-opt".to_string());
+        args.push(format!("size={}", size));
+    }
 
     // Inject real DNS servers — on systemd-resolved hosts,
     // /etc/resolv.conf points at 127.0.0.53 which is unreachable
@@ -10948,13 +11557,18 @@ pub fn docker_create_with_cmd(name: &str, image: &str, ports: &[String], env: &[
         }
     }
 
-    // Add environment variables
-    for e in env {
+    // Add environment variables. `${secret:…}` references resolve here;
+    // anything the container's env store holds is injected after and
+    // takes precedence over the same name typed inline.
+    let injection = env_store::docker_injection(name)?;
+    let env = env_store::without_keys(&env_store::resolve_refs(env, name)?, &injection.keys);
+    for e in &env {
         if !e.is_empty() {
             args.push("-e".to_string());
             args.push(e.to_string());
         }
     }
+    args.extend(injection.args.iter().cloned());
 
     args.push(image.to_string());
 
@@ -10996,6 +11610,32 @@ pub fn docker_create_with_cmd(name: &str, image: &str, ports: &[String], env: &[
     }
 }
 
+#[cfg(test)]
+mod docker_quota_tests {
+    use super::*;
+
+    #[test]
+    fn docker_sizes() {
+        for ok in ["10g", "512m", "20GB", "1073741824", "2t"] {
+            assert!(valid_docker_size(ok), "{}", ok);
+        }
+        for bad in ["", "g", "10kg", "1.5g", "10 g", "10x", "-1g"] {
+            assert!(!valid_docker_size(bad), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn overlay_quota_needs_xfs_pquota_on_the_docker_root() {
+        let mounts = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                      /dev/sdb1 /var/lib/docker xfs rw,relatime,prjquota 0 0\n\
+                      /dev/sdc1 /srv xfs rw,relatime 0 0\n";
+        assert!(overlay_quota_supported(mounts, "/var/lib/docker"));
+        assert!(!overlay_quota_supported(mounts, "/srv/docker"));
+        assert!(!overlay_quota_supported(mounts, "/var/lib/docker2"));
+        assert!(!overlay_quota_supported("", "/var/lib/docker"));
+    }
+}
+
 /// One entry from `ss -tlnp` / `ss -ulnp`. Used by the pre-flight
 /// validator and by `predictive::port_conflict` to know which host
 /// processes already hold a port. Lives here (not in `predictive`)
@@ -12073,6 +12713,96 @@ fn lxc_cgroup_read(name: &str, key: &str) -> Option<u64> {
         })
 }
 
+/// Read a cgroup file verbatim via lxc-cgroup, for values that aren't a
+/// plain number (`max`, a `cpu.max` pair, a cpuset list). `None` when the
+/// container isn't running.
+fn lxc_cgroup_read_raw(name: &str, key: &str) -> Option<String> {
+    let base = lxc_base_dir(name);
+    let mut args: Vec<&str> = Vec::new();
+    if base != LXC_DEFAULT_PATH { args.extend_from_slice(&["-P", &base]); }
+    args.extend_from_slice(&["-n", name, key]);
+    let out = Command::new("lxc-cgroup").args(&args).output().ok()?;
+    if !out.status.success() { return None; }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Write a cgroup value on a running container via lxc-cgroup.
+fn lxc_cgroup_write(name: &str, key: &str, value: &str) -> Result<(), String> {
+    let base = lxc_base_dir(name);
+    let mut args: Vec<&str> = Vec::new();
+    if base != LXC_DEFAULT_PATH { args.extend_from_slice(&["-P", &base]); }
+    args.extend_from_slice(&["-n", name, key, value]);
+    let out = Command::new("lxc-cgroup").args(&args).output()
+        .map_err(|e| format!("lxc-cgroup {}: {}", key, e))?;
+    if !out.status.success() {
+        return Err(format!("lxc-cgroup {} = {}: {}", key, value,
+            String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// The memory and CPU limits the kernel is enforcing on a running
+/// container right now, in the shapes the settings form uses: memory in
+/// MB, CPUs as a core count (CFS quota) or a cpuset list (pin), empty for
+/// unlimited. `None` when the container isn't running. Differs from the
+/// config when a limit was edited on disk without a restart (or changed
+/// behind WolfStack's back).
+pub fn lxc_live_limits(name: &str) -> Option<(String, String)> {
+    if !lxc_is_running(name) { return None; }
+    let memory = lxc_cgroup_read_raw(name, "memory.max")?;
+    let memory = match memory.parse::<u64>() {
+        Ok(bytes) => (bytes / (1024 * 1024)).to_string(),
+        Err(_) => String::new(), // "max"
+    };
+    let cpus = match lxc_cgroup_read_raw(name, "cpu.max") {
+        Some(v) if !v.starts_with("max") =>
+            lxc_quota_cores_from_cpu_max(&v).map(|n| n.to_string()).unwrap_or(v),
+        _ => lxc_cgroup_read_raw(name, "cpuset.cpus").unwrap_or_default(),
+    };
+    Some((memory, cpus))
+}
+
+/// Apply memory / CPU limits to a running container's cgroup so they take
+/// effect without a restart (the config file is the caller's business —
+/// this only touches the live cgroup). `None` leaves that limit alone;
+/// an empty or zero value lifts it. A CPU limit switches cleanly between
+/// quota and pinning: the other mechanism is reset so an old pin can't
+/// outlive a new quota. Returns what was applied, for the status message.
+pub fn lxc_apply_live_limits(name: &str, memory: Option<&str>, cpus: Option<&str>) -> Result<Vec<String>, String> {
+    let mut applied = Vec::new();
+    if let Some(mem) = memory {
+        let mb = parse_mem_to_mb(mem);
+        if mb > 0 {
+            lxc_cgroup_write(name, "memory.max", &(mb * 1024 * 1024).to_string())?;
+            applied.push(format!("memory limit {} MB", mb));
+        } else {
+            lxc_cgroup_write(name, "memory.max", "max")?;
+            applied.push("memory unlimited".to_string());
+        }
+    }
+    if let Some(cpu) = cpus {
+        match lxc_parse_cpu_input(cpu) {
+            Some(LxcCpuLimit::Quota(n)) => {
+                // An empty cpuset.cpus inherits the parent's CPUs, i.e. unpins.
+                let _ = lxc_cgroup_write(name, "cpuset.cpus", "");
+                lxc_cgroup_write(name, "cpu.max", &format!("{} {}", n * LXC_CPU_PERIOD_US, LXC_CPU_PERIOD_US))?;
+                applied.push(LxcCpuLimit::Quota(n).describe());
+            }
+            Some(LxcCpuLimit::Pin(set)) => {
+                lxc_cgroup_write(name, "cpu.max", "max")?;
+                lxc_cgroup_write(name, "cpuset.cpus", &set)?;
+                applied.push(LxcCpuLimit::Pin(set).describe());
+            }
+            None => {
+                let _ = lxc_cgroup_write(name, "cpuset.cpus", "");
+                lxc_cgroup_write(name, "cpu.max", "max")?;
+                applied.push("CPU unlimited".to_string());
+            }
+        }
+    }
+    Ok(applied)
+}
+
 /// Read one key from a multi-line cgroup stat file (e.g. `memory.stat`) via
 /// lxc-cgroup. Matches the key as the exact first whitespace token so
 /// `inactive_file` never accidentally matches `total_inactive_file`.
@@ -12638,6 +13368,49 @@ pub fn docker_list_volumes(container: &str) -> Vec<ContainerMount> {
     }
 }
 
+/// A named Docker volume and where its data lives on the host.
+#[derive(Debug, Clone, Serialize)]
+pub struct DockerVolume {
+    pub name: String,
+    pub driver: String,
+    pub mountpoint: String,
+}
+
+/// Every named volume on this host, for browsing with the host file
+/// manager. Volumes on remote drivers report a mountpoint that may not
+/// exist until a container uses them — the caller filters on that.
+pub fn docker_volumes() -> Vec<DockerVolume> {
+    let names = match Command::new("docker").args(["volume", "ls", "-q"]).output() {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>(),
+        _ => return Vec::new(),
+    };
+    if names.is_empty() {
+        return Vec::new();
+    }
+    let mut args = vec!["volume".to_string(), "inspect".to_string(), "--format".to_string(),
+        "{{.Name}}\t{{.Driver}}\t{{.Mountpoint}}".to_string()];
+    args.extend(names);
+    match Command::new("docker").args(&args).output() {
+        Ok(o) => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split('\t').collect();
+                if parts.len() < 3 { return None; }
+                Some(DockerVolume {
+                    name: parts[0].to_string(),
+                    driver: parts[1].to_string(),
+                    mountpoint: parts[2].to_string(),
+                })
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
 /// Export a Docker container as a tar image for migration.
 /// Uses `docker commit` to snapshot the container state, then `docker save` to tar.
 #[allow(dead_code)]
@@ -0,0 +1,262 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Container → storage mount dependencies ("requires mount X").
+//!
+//! A container whose bind mounts come from a storage-manager mount (NFS,
+//! SMB, S3…) is useless — or worse, writes into the bare mount point —
+//! when it starts before that mount. Each container can name the mounts
+//! it needs:
+//!
+//! - Starting it by hand mounts any that are down first, and refuses to
+//!   start when one can't be mounted.
+//! - At boot, `lxc_autostart_all` waits for its autostart containers'
+//!   mounts (mounting any the auto-mount left behind) and holds back a
+//!   container whose mount never comes up.
+//! - Docker starts restart-policy containers itself, so [`settle_docker`]
+//!   fixes up afterwards: a container whose mount came up late is
+//!   restarted onto it, one whose mount never did is stopped.
+//!
+//! Kept in `container-mounts.json`, keyed by runtime then container name.
+//! Mounts deleted since are ignored rather than blocking the container.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// How long boot waits for required mounts before giving up on them.
+/// The LXC wait holds up the rest of the startup sequence, so keep it short.
+pub const BOOT_WAIT_SECS: u64 = 180;
+/// Pause between mount attempts while waiting.
+const RETRY_SECS: u64 = 10;
+
+fn config_path() -> String {
+    format!("{}/container-mounts.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MountDeps {
+    #[serde(default)]
+    pub lxc: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub docker: BTreeMap<String, Vec<String>>,
+}
+
+impl MountDeps {
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = config_path();
+        if let Some(dir) = Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    fn runtime(&self, runtime: &str) -> Option<&BTreeMap<String, Vec<String>>> {
+        match runtime {
+            "lxc" => Some(&self.lxc),
+            "docker" => Some(&self.docker),
+            _ => None,
+        }
+    }
+
+    fn runtime_mut(&mut self, runtime: &str) -> Result<&mut BTreeMap<String, Vec<String>>, String> {
+        match runtime {
+            "lxc" => Ok(&mut self.lxc),
+            "docker" => Ok(&mut self.docker),
+            _ => Err(format!("unsupported runtime '{}'", runtime)),
+        }
+    }
+
+    /// Mount ids `name` requires.
+    pub fn required(&self, runtime: &str, name: &str) -> Vec<String> {
+        self.runtime(runtime).and_then(|m| m.get(name)).cloned().unwrap_or_default()
+    }
+
+    /// Replace `name`'s requirements; an empty list clears them.
+    pub fn set(&mut self, runtime: &str, name: &str, mut ids: Vec<String>) -> Result<(), String> {
+        ids.sort();
+        ids.dedup();
+        let map = self.runtime_mut(runtime)?;
+        if ids.is_empty() {
+            map.remove(name);
+        } else {
+            map.insert(name.to_string(), ids);
+        }
+        Ok(())
+    }
+}
+
+/// Drop a container's requirements once it's gone for good.
+pub fn forget(runtime: &str, name: &str) {
+    let mut deps = MountDeps::load();
+    if deps.required(runtime, name).is_empty() {
+        return;
+    }
+    if deps.set(runtime, name, Vec::new()).is_ok() {
+        if let Err(e) = deps.save() {
+            warn!("mount deps: could not drop {} '{}': {}", runtime, name, e);
+        }
+    }
+}
+
+/// The storage mounts `ids` still name.
+fn existing(ids: &[String]) -> Vec<crate::storage::StorageMount> {
+    crate::storage::load_config().mounts.into_iter()
+        .filter(|m| ids.contains(&m.id))
+        .collect()
+}
+
+/// Mount whatever `name` requires before it starts. Err names the mount
+/// that couldn't be brought up.
+pub fn ensure_ready(runtime: &str, name: &str) -> Result<(), String> {
+    let ids = MountDeps::load().required(runtime, name);
+    if ids.is_empty() {
+        return Ok(());
+    }
+    for mount in existing(&ids) {
+        crate::storage::ensure_mounted(&mount.id).map_err(|e| format!(
+            "'{}' requires storage mount '{}' ({}), which could not be mounted: {}",
+            name, mount.name, mount.mount_point, e))?;
+    }
+    Ok(())
+}
+
+/// Outcome of a boot-time wait.
+#[derive(Debug, Default)]
+pub struct MountWait {
+    /// Mounts that never came up, with the last error.
+    pub failed: BTreeMap<String, String>,
+    /// Mounts that were down when the auto-mounts settled and came up
+    /// during the wait — anything started before then missed them.
+    pub late: BTreeSet<String>,
+}
+
+impl MountWait {
+    /// The first of `ids` that never came up, if any.
+    pub fn blocking<'a>(&self, ids: &'a [String]) -> Option<&'a String> {
+        ids.iter().find(|id| self.failed.contains_key(*id))
+    }
+}
+
+/// Let the boot auto-mounts settle, then mount anything in `ids` still
+/// down, retrying until `timeout` runs out.
+pub fn wait_for_mounts(ids: &BTreeSet<String>, timeout: Duration) -> MountWait {
+    let deadline = Instant::now() + timeout;
+    while !crate::storage::auto_mounts_settled() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_secs(2));
+    }
+    let all: Vec<String> = ids.iter().cloned().collect();
+    let mut pending: BTreeMap<String, String> = existing(&all).into_iter()
+        .filter(|m| !crate::storage::check_mounted(&m.mount_point))
+        .map(|m| (m.id, String::from("not mounted")))
+        .collect();
+    let mut wait = MountWait { late: pending.keys().cloned().collect(), ..Default::default() };
+    loop {
+        pending.retain(|id, err| match crate::storage::ensure_mounted(id) {
+            Ok(()) => {
+                info!("mount deps: '{}' is up", id);
+                false
+            }
+            Err(e) => {
+                *err = e;
+                true
+            }
+        });
+        if pending.is_empty() || Instant::now() + Duration::from_secs(RETRY_SECS) > deadline {
+            break;
+        }
+        std::thread::sleep(Duration::from_secs(RETRY_SECS));
+    }
+    wait.late.retain(|id| !pending.contains_key(id));
+    wait.failed = pending;
+    wait
+}
+
+/// Every mount id the given containers require.
+pub fn required_by<'a>(deps: &MountDeps, runtime: &str, names: impl IntoIterator<Item = &'a str>) -> BTreeSet<String> {
+    names.into_iter().flat_map(|n| deps.required(runtime, n)).collect()
+}
+
+/// Boot fix-up for Docker's own restart-policy starts: restart running
+/// containers onto mounts that came up late, stop ones whose mount never
+/// came up.
+pub fn settle_docker() {
+    let deps = MountDeps::load();
+    if deps.docker.is_empty() || !super::host_recently_booted() {
+        return;
+    }
+    let needed = required_by(&deps, "docker", deps.docker.keys().map(String::as_str));
+    let wait = wait_for_mounts(&needed, Duration::from_secs(BOOT_WAIT_SECS));
+    for (name, ids) in &deps.docker {
+        let running = super::docker_inspect(name).ok()
+            .and_then(|v| v.pointer("/State/Running").and_then(|r| r.as_bool()))
+            .unwrap_or(false);
+        if !running {
+            continue;
+        }
+        if let Some(id) = wait.blocking(ids) {
+            error!("mount deps: stopping Docker container '{}' — required mount '{}' is down: {}",
+                name, id, wait.failed[id]);
+            let _ = super::docker_stop(name);
+        } else if ids.iter().any(|id| wait.late.contains(id)) {
+            info!("mount deps: restarting Docker container '{}' onto its late mount", name);
+            let _ = super::docker_restart(name);
+        }
+    }
+}
+
+/// Storage mounts that hold one of `host_paths` — the mounts a container
+/// with those bind sources probably requires.
+pub fn covering(host_paths: &[String], mounts: &[crate::storage::StorageMount]) -> Vec<String> {
+    mounts.iter()
+        .filter(|m| {
+            let mp = m.mount_point.trim_end_matches('/');
+            !mp.is_empty() && host_paths.iter().any(|p| {
+                p == mp || p.strip_prefix(mp).is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+        .map(|m| m.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(id: &str, mp: &str) -> crate::storage::StorageMount {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": id, "type": "nfs",
+            "source": "nas:/vol", "mount_point": mp, "created_at": ""
+        })).unwrap()
+    }
+
+    #[test]
+    fn bind_sources_suggest_their_mounts() {
+        let mounts = vec![mount("media", "/mnt/wolfstack/media"), mount("backup", "/mnt/wolfstack/backup/")];
+        let paths = vec!["/mnt/wolfstack/media/films".to_string(), "/mnt/wolfstack/backup".to_string(),
+                         "/mnt/wolfstack/media2".to_string()];
+        assert_eq!(covering(&paths, &mounts), vec!["media".to_string(), "backup".to_string()]);
+        assert!(covering(&["/srv/data".to_string()], &mounts).is_empty());
+    }
+
+    #[test]
+    fn set_sorts_dedups_and_clears() {
+        let mut deps = MountDeps::default();
+        deps.set("lxc", "web", vec!["b".into(), "a".into(), "b".into()]).unwrap();
+        assert_eq!(deps.required("lxc", "web"), vec!["a".to_string(), "b".to_string()]);
+        assert!(deps.required("docker", "web").is_empty());
+        deps.set("lxc", "web", Vec::new()).unwrap();
+        assert!(deps.lxc.is_empty());
+        assert!(deps.set("vm", "web", vec!["a".into()]).is_err());
+    }
+}
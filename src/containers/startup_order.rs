@@ -0,0 +1,374 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Boot start order and dependencies for autostart guests.
+//!
+//! `lxc-autostart` and VM autostart bring everything up at once, with no
+//! idea that the app container needs its database first, or that both
+//! need the NFS mount. Each LXC container and VM can carry:
+//!
+//! - `order` — lower starts first; ties keep the runtime's own order
+//!   (`lxc.start.order` for containers, then VMs);
+//! - `delay_s` — seconds to wait after starting it before the next guest;
+//! - `depends_on` — guests (`lxc:db`, `vm:nas`) and storage mounts
+//!   (`mount:<id>`) that must be up first. A dependency always starts
+//!   before its dependents whatever their `order`. A guest whose
+//!   dependency failed to start or isn't running, or whose mount never
+//!   came up, is held back and logged instead of started half-working.
+//!
+//! With nothing configured, boot keeps the plain `lxc-autostart` path.
+//! Kept in `startup-order.json`, keyed by runtime then guest name. Not
+//! used on Proxmox, where pve-guests owns boot and has its own
+//! start/shutdown order.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+fn config_path() -> String {
+    format!("{}/startup-order.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GuestStartup {
+    /// Lower starts first.
+    #[serde(default)]
+    pub order: i32,
+    /// Seconds to wait after starting this guest before the next one.
+    #[serde(default)]
+    pub delay_s: u64,
+    /// `lxc:<name>`, `vm:<name>` or `mount:<storage mount id>`.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl GuestStartup {
+    fn is_default(&self) -> bool {
+        *self == GuestStartup::default()
+    }
+
+    /// The guest keys (`kind:name`) this one waits for.
+    pub fn guest_deps(&self) -> impl Iterator<Item = &str> {
+        self.depends_on.iter().map(String::as_str).filter(|d| !d.starts_with("mount:"))
+    }
+
+    /// The storage mount ids this one waits for.
+    pub fn mount_deps(&self) -> impl Iterator<Item = &str> {
+        self.depends_on.iter().filter_map(|d| d.strip_prefix("mount:"))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupOrder {
+    #[serde(default)]
+    pub lxc: BTreeMap<String, GuestStartup>,
+    #[serde(default)]
+    pub vm: BTreeMap<String, GuestStartup>,
+}
+
+/// `kind:name`, the form dependencies name a guest by.
+pub fn guest_key(kind: &str, name: &str) -> String {
+    format!("{}:{}", kind, name)
+}
+
+/// Check one `depends_on` entry's shape.
+fn parse_dep(dep: &str) -> Result<(&str, &str), String> {
+    match dep.split_once(':') {
+        Some((kind @ ("lxc" | "vm" | "mount"), name)) if !name.is_empty() => Ok((kind, name)),
+        _ => Err(format!("Invalid dependency '{}' — use lxc:<name>, vm:<name> or mount:<id>", dep)),
+    }
+}
+
+impl StartupOrder {
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = config_path();
+        if let Some(dir) = Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lxc.is_empty() && self.vm.is_empty()
+    }
+
+    fn runtime_mut(&mut self, kind: &str) -> Result<&mut BTreeMap<String, GuestStartup>, String> {
+        match kind {
+            "lxc" => Ok(&mut self.lxc),
+            "vm" => Ok(&mut self.vm),
+            _ => Err(format!("unsupported guest type '{}'", kind)),
+        }
+    }
+
+    /// `name`'s settings, or the defaults.
+    pub fn get(&self, kind: &str, name: &str) -> GuestStartup {
+        let map = match kind {
+            "lxc" => &self.lxc,
+            "vm" => &self.vm,
+            _ => return GuestStartup::default(),
+        };
+        map.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Replace `name`'s settings; the defaults clear them. Rejects
+    /// malformed or self dependencies and anything that closes a cycle.
+    pub fn set(&mut self, kind: &str, name: &str, mut entry: GuestStartup) -> Result<(), String> {
+        for dep in &entry.depends_on {
+            parse_dep(dep)?;
+        }
+        entry.depends_on.sort();
+        entry.depends_on.dedup();
+        if entry.depends_on.contains(&guest_key(kind, name)) {
+            return Err(format!("'{}' can't depend on itself", name));
+        }
+        let map = self.runtime_mut(kind)?;
+        let previous = if entry.is_default() {
+            map.remove(name)
+        } else {
+            map.insert(name.to_string(), entry)
+        };
+        let (_, cyclic) = plan(self.configured(), self);
+        if !cyclic.is_empty() {
+            let map = self.runtime_mut(kind)?;
+            match previous {
+                Some(p) => { map.insert(name.to_string(), p); }
+                None => { map.remove(name); }
+            }
+            return Err(format!("Dependency cycle between {}", cyclic.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Every configured guest and every guest one of them depends on.
+    fn configured(&self) -> Vec<BootGuest> {
+        let mut keys: BTreeSet<(String, String)> = BTreeSet::new();
+        for (kind, map) in [("lxc", &self.lxc), ("vm", &self.vm)] {
+            for (name, entry) in map {
+                keys.insert((kind.to_string(), name.clone()));
+                for dep in entry.guest_deps() {
+                    if let Ok((k, n)) = parse_dep(dep) {
+                        keys.insert((k.to_string(), n.to_string()));
+                    }
+                }
+            }
+        }
+        keys.into_iter().map(|(kind, name)| BootGuest { kind, name, delay_s: 0 }).collect()
+    }
+}
+
+/// Drop a guest's settings once it's gone for good. Dependents keep naming
+/// it, and are held back at boot until they're edited.
+pub fn forget(kind: &str, name: &str) {
+    let mut cfg = StartupOrder::load();
+    if cfg.get(kind, name).is_default() {
+        return;
+    }
+    if cfg.set(kind, name, GuestStartup::default()).is_ok()
+        && let Err(e) = cfg.save()
+    {
+        warn!("startup order: could not drop {} '{}': {}", kind, name, e);
+    }
+}
+
+/// A guest to start at boot, with the delay its runtime would use after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootGuest {
+    pub kind: String,
+    pub name: String,
+    pub delay_s: u64,
+}
+
+impl BootGuest {
+    pub fn key(&self) -> String {
+        guest_key(&self.kind, &self.name)
+    }
+}
+
+/// Order `guests` for starting: dependencies first, then by `order`, then
+/// as given. Dependencies outside `guests` don't affect the order. Also
+/// returns the keys of any guests caught in a cycle — those are placed by
+/// `order` alone.
+pub fn plan(guests: Vec<BootGuest>, cfg: &StartupOrder) -> (Vec<BootGuest>, Vec<String>) {
+    let keys: BTreeSet<String> = guests.iter().map(BootGuest::key).collect();
+    let mut remaining: Vec<(usize, BootGuest)> = guests.into_iter().enumerate().collect();
+    let mut placed: BTreeSet<String> = BTreeSet::new();
+    let mut out = Vec::new();
+    let mut cyclic = Vec::new();
+    while !remaining.is_empty() {
+        let sort_key = |i: usize, g: &BootGuest| (cfg.get(&g.kind, &g.name).order, i);
+        let ready = remaining.iter().enumerate()
+            .filter(|(_, (_, g))| cfg.get(&g.kind, &g.name).guest_deps()
+                .all(|d| !keys.contains(d) || placed.contains(d)))
+            .min_by_key(|(_, (i, g))| sort_key(*i, g))
+            .map(|(pos, _)| pos);
+        let pos = match ready {
+            Some(pos) => pos,
+            None => {
+                // Everything left waits on something else left: a cycle.
+                // Record it once and carry on by order.
+                if cyclic.is_empty() {
+                    cyclic = remaining.iter().map(|(_, g)| g.key()).collect();
+                }
+                remaining.iter().enumerate()
+                    .min_by_key(|(_, (i, g))| sort_key(*i, g))
+                    .map(|(pos, _)| pos)
+                    .unwrap_or(0)
+            }
+        };
+        let (_, g) = remaining.remove(pos);
+        placed.insert(g.key());
+        out.push(g);
+    }
+    (out, cyclic)
+}
+
+/// This node's autostart guests in their start order: LXC containers in
+/// `lxc-autostart`'s order, then VMs. Ones already running are left out.
+pub fn autostart_guests() -> Vec<BootGuest> {
+    let mut guests: Vec<BootGuest> = super::lxc_autostart_list().into_iter()
+        .filter(|(name, _)| !super::lxc_is_running(name))
+        .map(|(name, delay_s)| BootGuest { kind: "lxc".into(), name, delay_s })
+        .collect();
+    guests.extend(crate::vms::manager::VmManager::new().list_vms().into_iter()
+        .filter(|vm| vm.auto_start && !vm.running)
+        .map(|vm| BootGuest { kind: "vm".into(), name: vm.name, delay_s: 0 }));
+    guests
+}
+
+fn is_running(kind: &str, name: &str) -> bool {
+    match kind {
+        "lxc" => super::lxc_is_running(name),
+        "vm" => crate::vms::manager::running_vm_names().is_some_and(|s| s.contains(name)),
+        _ => false,
+    }
+}
+
+fn start(g: &BootGuest) -> Result<(), String> {
+    match g.kind.as_str() {
+        "lxc" => super::lxc_start(&g.name).map(|_| ()),
+        "vm" => crate::vms::manager::VmManager::new().start_vm(&g.name),
+        other => Err(format!("unsupported guest type '{}'", other)),
+    }
+}
+
+/// Boot-time start of every autostart LXC container and VM, honouring
+/// order, delays and dependencies. Caller checks this is a real boot.
+pub fn autostart_all(cfg: &StartupOrder) {
+    let (order, cyclic) = plan(autostart_guests(), cfg);
+    if !cyclic.is_empty() {
+        error!("startup order: dependency cycle between {} — starting them by order only", cyclic.join(", "));
+    }
+
+    // Wait once for every mount anything here needs, whether named in its
+    // startup dependencies or its required mounts.
+    let mount_deps = super::mount_deps::MountDeps::load();
+    let mounts_of = |g: &BootGuest| -> Vec<String> {
+        let mut ids: Vec<String> = cfg.get(&g.kind, &g.name).mount_deps().map(str::to_string).collect();
+        ids.extend(mount_deps.required(&g.kind, &g.name));
+        ids
+    };
+    let needed: BTreeSet<String> = order.iter().flat_map(&mounts_of).collect();
+    let wait = if needed.is_empty() {
+        super::mount_deps::MountWait::default()
+    } else {
+        super::mount_deps::wait_for_mounts(&needed, Duration::from_secs(super::mount_deps::BOOT_WAIT_SECS))
+    };
+
+    let mut failed: BTreeSet<String> = BTreeSet::new();
+    for g in &order {
+        let key = g.key();
+        let entry = cfg.get(&g.kind, &g.name);
+        if let Some(id) = wait.blocking(&mounts_of(g)) {
+            error!("startup order: holding back {} — required storage mount '{}' is down: {}",
+                key, id, wait.failed[id]);
+            failed.insert(key);
+            continue;
+        }
+        // Within a cycle, the members' dependencies on each other can't
+        // all be met — only the ones outside it are checked.
+        let in_cycle = |d: &str| cyclic.contains(&key) && cyclic.iter().any(|c| c == d);
+        let blocked = entry.guest_deps()
+            .filter(|d| !in_cycle(d))
+            .find(|d| failed.contains(*d) || parse_dep(d).map_or(true, |(k, n)| !is_running(k, n)));
+        if let Some(dep) = blocked {
+            error!("startup order: holding back {} — it depends on {}, which is not running", key, dep);
+            failed.insert(key);
+            continue;
+        }
+        match start(g) {
+            Ok(()) => info!("startup order: started {}", key),
+            Err(e) => {
+                error!("startup order: failed to start {}: {}", key, e);
+                failed.insert(key);
+                continue;
+            }
+        }
+        let delay = if entry.is_default() { g.delay_s } else { entry.delay_s };
+        if delay > 0 {
+            std::thread::sleep(Duration::from_secs(delay));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guest(kind: &str, name: &str) -> BootGuest {
+        BootGuest { kind: kind.into(), name: name.into(), delay_s: 0 }
+    }
+
+    fn entry(order: i32, deps: &[&str]) -> GuestStartup {
+        GuestStartup { order, delay_s: 0, depends_on: deps.iter().map(|d| d.to_string()).collect() }
+    }
+
+    fn names(gs: &[BootGuest]) -> Vec<String> {
+        gs.iter().map(BootGuest::key).collect()
+    }
+
+    #[test]
+    fn dependencies_start_first_then_order() {
+        let mut cfg = StartupOrder::default();
+        cfg.set("lxc", "app", entry(0, &["lxc:db", "mount:nfs"])).unwrap();
+        cfg.set("lxc", "db", entry(10, &["vm:nas"])).unwrap();
+        cfg.set("lxc", "cache", entry(-5, &[])).unwrap();
+        let guests = vec![guest("lxc", "app"), guest("lxc", "db"), guest("lxc", "web"), guest("lxc", "cache"), guest("vm", "nas")];
+        let (order, cyclic) = plan(guests, &cfg);
+        assert!(cyclic.is_empty());
+        assert_eq!(names(&order), vec!["lxc:cache", "lxc:web", "vm:nas", "lxc:db", "lxc:app"]);
+    }
+
+    #[test]
+    fn set_rejects_bad_self_and_cyclic_deps() {
+        let mut cfg = StartupOrder::default();
+        assert!(cfg.set("lxc", "app", entry(0, &["db"])).is_err());
+        assert!(cfg.set("lxc", "app", entry(0, &["lxc:app"])).is_err());
+        assert!(cfg.set("docker", "app", entry(0, &[])).is_err());
+        cfg.set("lxc", "app", entry(0, &["lxc:db"])).unwrap();
+        cfg.set("lxc", "db", entry(0, &["vm:nas"])).unwrap();
+        assert!(cfg.set("vm", "nas", entry(0, &["lxc:app"])).is_err());
+        assert!(cfg.vm.is_empty());
+        cfg.set("lxc", "app", GuestStartup::default()).unwrap();
+        assert!(!cfg.lxc.contains_key("app"));
+    }
+
+    #[test]
+    fn cycles_are_reported_and_placed_by_order() {
+        let mut cfg = StartupOrder::default();
+        cfg.lxc.insert("a".into(), entry(2, &["lxc:b"]));
+        cfg.lxc.insert("b".into(), entry(1, &["lxc:a"]));
+        let (order, cyclic) = plan(vec![guest("lxc", "a"), guest("lxc", "b")], &cfg);
+        assert_eq!(names(&order), vec!["lxc:b", "lxc:a"]);
+        assert_eq!(cyclic, vec!["lxc:a", "lxc:b"]);
+    }
+}
@@ -0,0 +1,311 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Crash reports.
+//!
+//! When the daemon panics, or `main` gives up (a listener that won't
+//! bind is the usual one), a JSON report goes to `paths.crash_dir`: the
+//! message and where it happened, a backtrace, the last log lines and a
+//! summary of the settings in force. systemd restarts the service and the
+//! journal rotates, so "the dashboard says connection refused" used to
+//! leave nothing to look at; the report survives both.
+//!
+//! Reports are listed and read through `GET /api/system/crash-reports`.
+//! At startup [`check_crash_loop`] warns if several landed in the last
+//! few minutes, naming the most recent, so a crash loop is obvious from
+//! the first lines of the log. Only the newest [`MAX_REPORTS`] are kept,
+//! and a panic site that keeps firing inside a background task is
+//! reported once per run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+use tracing::warn;
+
+/// Reports kept on disk; older ones are deleted as new ones arrive.
+pub const MAX_REPORTS: usize = 20;
+
+/// This many reports within [`CRASH_LOOP_WINDOW_SECS`] is a crash loop.
+const CRASH_LOOP_COUNT: usize = 3;
+const CRASH_LOOP_WINDOW_SECS: i64 = 15 * 60;
+
+static STARTED: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Set once the daemon installs the hook; CLI subcommands never do.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Panic sites already reported by this process.
+static REPORTED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashKind {
+    /// A thread or task panicked.
+    Panic,
+    /// `main` returned an error, e.g. a port that wouldn't bind.
+    Exit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    /// RFC 3339.
+    pub time: String,
+    pub version: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    #[serde(default)]
+    pub thread: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub backtrace: String,
+    #[serde(default)]
+    pub log_tail: Vec<String>,
+    /// Settings in force: wolfstack.toml, ports, command line, OS.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+/// What the list endpoint returns for each report.
+#[derive(Serialize, Clone, Debug)]
+pub struct CrashSummary {
+    pub id: String,
+    pub kind: CrashKind,
+    pub time: String,
+    pub version: String,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+impl From<&CrashReport> for CrashSummary {
+    fn from(r: &CrashReport) -> Self {
+        CrashSummary {
+            id: r.id.clone(),
+            kind: r.kind,
+            time: r.time.clone(),
+            version: r.version.clone(),
+            message: r.message.clone(),
+            location: r.location.clone(),
+        }
+    }
+}
+
+fn crash_dir() -> PathBuf {
+    PathBuf::from(crate::paths::get().crash_dir)
+}
+
+/// Report IDs are file stems we generated; anything else is refused so an
+/// ID from a URL can't name another file.
+fn valid_id(id: &str) -> bool {
+    id.starts_with("crash-") && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn config_summary() -> serde_json::Value {
+    let os = std::fs::read_to_string("/etc/os-release").ok()
+        .and_then(|s| s.lines()
+            .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+            .map(|v| v.trim_matches('"').to_string()));
+    let kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease").ok()
+        .map(|s| s.trim().to_string());
+    let args: Vec<String> = std::env::args().map(|a| crate::logging::redact_arg(&a)).collect();
+    serde_json::json!({
+        "args": args,
+        "wolfstack_toml": *crate::daemon_config::get(),
+        "ports": crate::ports::PortConfig::load(),
+        "log_filter": crate::logging::filters().0,
+        "os": os,
+        "kernel": kernel,
+    })
+}
+
+fn new_report(kind: CrashKind, message: String) -> CrashReport {
+    let now = chrono::Utc::now();
+    let pid = std::process::id();
+    CrashReport {
+        id: format!("crash-{}-{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), pid, rand::random::<u16>()),
+        kind,
+        time: now.to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        pid,
+        uptime_secs: STARTED.elapsed().as_secs(),
+        thread: None,
+        message,
+        location: None,
+        backtrace: String::new(),
+        log_tail: crate::logging::recent_lines(),
+        config: config_summary(),
+    }
+}
+
+/// Write `report` to the crash directory and prune the oldest beyond
+/// [`MAX_REPORTS`]. Returns the file written.
+fn write(report: &CrashReport) -> Result<PathBuf, String> {
+    write_to(&crash_dir(), report)
+}
+
+fn write_to(dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_vec_pretty(report).map_err(|e| e.to_string())?;
+    // The log tail can carry hostnames and addresses — root only.
+    crate::paths::write_secure(&path.to_string_lossy(), json)
+        .map_err(|e| format!("write {}: {}", path.display(), e))?;
+    let mut ids = report_ids(dir);
+    while ids.len() > MAX_REPORTS {
+        let _ = std::fs::remove_file(dir.join(format!("{}.json", ids.remove(0))));
+    }
+    Ok(path)
+}
+
+/// Report IDs in `dir`, oldest first (the ID starts with the timestamp).
+fn report_ids(dir: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = std::fs::read_dir(dir)
+        .map(|rd| rd.flatten()
+            .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json").map(String::from))
+            .filter(|id| valid_id(id))
+            .collect())
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
+/// Install the panic hook. Call once, right after logging is set up; the
+/// default hook still runs afterwards, so the panic also reaches stderr.
+pub fn install_panic_hook() {
+    LazyLock::force(&STARTED);
+    INSTALLED.store(true, Ordering::Relaxed);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let first = location.as_ref()
+            .is_none_or(|loc| REPORTED.try_lock().map(|mut seen| seen.insert(loc.clone())).unwrap_or(false));
+        if first {
+            let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "(non-string panic payload)".to_string());
+            let mut report = new_report(CrashKind::Panic, message);
+            report.thread = std::thread::current().name().map(String::from);
+            report.location = location;
+            report.backtrace = std::backtrace::Backtrace::force_capture().to_string();
+            match write(&report) {
+                Ok(path) => eprintln!("wolfstack: crash report written to {}", path.display()),
+                Err(e) => eprintln!("wolfstack: could not write crash report: {}", e),
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Record the daemon exiting with an error. No-op for CLI subcommands.
+pub fn record_exit_error(err: &std::io::Error) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let report = new_report(CrashKind::Exit, format!("wolfstack exited: {}", err));
+    match write(&report) {
+        Ok(path) => tracing::error!("wolfstack exited: {} — crash report written to {}", err, path.display()),
+        Err(e) => tracing::error!("wolfstack exited: {} (crash report not written: {})", err, e),
+    }
+}
+
+/// Summaries of the saved reports, newest first.
+pub fn list() -> Vec<CrashSummary> {
+    let dir = crash_dir();
+    report_ids(&dir).iter().rev()
+        .filter_map(|id| get_in(&dir, id).ok())
+        .map(|r| CrashSummary::from(&r))
+        .collect()
+}
+
+/// One full report.
+pub fn get(id: &str) -> Result<CrashReport, String> {
+    get_in(&crash_dir(), id)
+}
+
+fn get_in(dir: &Path, id: &str) -> Result<CrashReport, String> {
+    if !valid_id(id) {
+        return Err("invalid crash report id".into());
+    }
+    let data = std::fs::read(dir.join(format!("{}.json", id)))
+        .map_err(|_| format!("crash report '{}' not found", id))?;
+    serde_json::from_slice(&data).map_err(|e| format!("crash report '{}' is unreadable: {}", id, e))
+}
+
+/// Delete every saved report. Returns how many were removed.
+pub fn clear() -> usize {
+    let dir = crash_dir();
+    report_ids(&dir).iter()
+        .filter(|id| std::fs::remove_file(dir.join(format!("{}.json", id))).is_ok())
+        .count()
+}
+
+/// Warn at startup if the daemon looks to be crash-looping.
+pub fn check_crash_loop() {
+    let since = chrono::Utc::now() - chrono::Duration::seconds(CRASH_LOOP_WINDOW_SECS);
+    let recent: Vec<CrashSummary> = list().into_iter()
+        .filter(|r| chrono::DateTime::parse_from_rfc3339(&r.time).is_ok_and(|t| t >= since))
+        .collect();
+    if recent.len() >= CRASH_LOOP_COUNT {
+        let last = &recent[0];
+        warn!(
+            "crash loop: {} crash reports in the last {} minutes; the latest ({}) was \"{}\"{} — see GET /api/system/crash-reports/{}",
+            recent.len(), CRASH_LOOP_WINDOW_SECS / 60, last.time, last.message,
+            last.location.as_deref().map(|l| format!(" at {}", l)).unwrap_or_default(),
+            last.id,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(id: &str) -> CrashReport {
+        CrashReport {
+            id: id.to_string(),
+            kind: CrashKind::Panic,
+            time: chrono::Utc::now().to_rfc3339(),
+            version: "0".into(),
+            pid: 1,
+            uptime_secs: 0,
+            thread: None,
+            message: "boom".into(),
+            location: Some("src/main.rs:1:1".into()),
+            backtrace: String::new(),
+            log_tail: vec!["line".into()],
+            config: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn reports_round_trip_and_are_pruned_oldest_first() {
+        let dir = std::env::temp_dir().join(format!("wolfstack-crash-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for i in 0..MAX_REPORTS + 3 {
+            write_to(&dir, &report(&format!("crash-20260101T0000{:02}000Z-1-1", i))).unwrap();
+        }
+        let ids = report_ids(&dir);
+        assert_eq!(ids.len(), MAX_REPORTS);
+        assert_eq!(ids[0], "crash-20260101T000003000Z-1-1");
+        let back = get_in(&dir, &ids[0]).unwrap();
+        assert_eq!(back.message, "boom");
+        assert_eq!(back.log_tail, vec!["line"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn ids_cannot_name_other_files() {
+        assert!(valid_id("crash-20260101T000000000Z-42-7"));
+        for bad in ["", "../etc/passwd", "crash-../../x", "crash-a/b", "report", "crash-a.json"] {
+            assert!(!valid_id(bad), "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,550 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Daemon settings file — `/etc/wolfstack/wolfstack.toml`.
+//!
+//! One place for the runtime knobs that used to be CLI-only: listen port
+//! and bind address, TLS, the node-poll and self-monitor cadence, how much
+//! metrics history to keep in memory, and a few feature toggles. Every key
+//! is optional; a missing file means "all defaults", which is exactly how
+//! WolfStack behaved before this file existed.
+//!
+//! Precedence, highest first: command-line flag, `WOLFSTACK_*` environment
+//! variable (see [`ENV_VARS`]), `wolfstack.toml`, built-in default.
+//!
+//! The file is read at startup and again on [`reload`] — triggered by
+//! SIGHUP (`systemctl reload wolfstack`) or `POST /api/daemon-config/reload`.
+//! Background loops read [`get`] on every tick, so interval and history
+//! changes apply from their next cycle, and the HTTPS listener picks up a
+//! new or renewed certificate for new connections without dropping the
+//! ones already open. Keys in [`RESTART_KEYS`] still need a restart.
+//!
+//! `server.port` behaves like `--port`: on a manual launch it overrides
+//! `ports.json`; under systemd it seeds `ports.json` once and the Node Ports
+//! panel stays authoritative after that (see `ports::resolve_api_ports`).
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, LazyLock, OnceLock, RwLock};
+use tracing::{info, warn};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    pub server: ServerSection,
+    pub tls: TlsSection,
+    pub polling: PollingSection,
+    pub history: HistorySection,
+    pub features: FeaturesSection,
+    pub logging: LoggingSection,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    /// API port; unset = `ports.json` (default 8553).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Bind address; unset = 0.0.0.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind: Option<String>,
+    /// Agent-only mode, same as `--agent`.
+    pub agent: bool,
+    /// Refuse every change made through the API, and every console or VNC
+    /// session — for a demo dashboard or NOC screen. See `auth::read_only`.
+    pub read_only: bool,
+    /// What refused requests (and the dashboard banner) say.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsSection {
+    /// `false` is the same as `--no-tls`.
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Domain used to find a Let's Encrypt certificate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+impl Default for TlsSection {
+    fn default() -> Self {
+        Self { enabled: true, cert: None, key: None, domain: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollingSection {
+    /// How often this node polls its cluster peers.
+    pub node_poll_secs: u64,
+    /// How often this node samples its own metrics; unset = 2s, or 5s in
+    /// agent mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_monitor_secs: Option<u64>,
+    /// How often alert thresholds are checked; unset = the interval in
+    /// the Alerts settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert_check_secs: Option<u64>,
+}
+
+impl Default for PollingSection {
+    fn default() -> Self {
+        Self { node_poll_secs: 10, self_monitor_secs: None, alert_check_secs: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistorySection {
+    /// Metrics snapshots kept in memory for the dashboard charts — one per
+    /// self-monitor tick, so 300 × 2s ≈ 10 minutes.
+    pub metrics_snapshots: usize,
+}
+
+impl Default for HistorySection {
+    fn default() -> Self {
+        Self { metrics_snapshots: crate::monitoring::HISTORY_MAX_SNAPSHOTS }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesSection {
+    /// Public status page listener on the status port.
+    pub status_pages: bool,
+    /// Discord and Telegram chat receivers. They still idle until a bot
+    /// token is configured; `false` keeps them from starting at all.
+    pub discord_bot: bool,
+    pub telegram_bot: bool,
+    /// Periodic LLM health probe.
+    pub ai_health_check: bool,
+}
+
+impl Default for FeaturesSection {
+    fn default() -> Self {
+        Self { status_pages: true, discord_bot: true, telegram_bot: true, ai_health_check: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSection {
+    /// `text` (default) or `json` lines for a log pipeline.
+    pub format: crate::logging::LogFormat,
+    /// `RUST_LOG`-style filter; unset = `logging::DEFAULT_FILTER`.
+    /// `RUST_LOG` itself still takes precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+/// Environment variables that override `wolfstack.toml`, with the key
+/// each one sets.
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("WOLFSTACK_PORT", "server.port"),
+    ("WOLFSTACK_BIND", "server.bind"),
+    ("WOLFSTACK_AGENT", "server.agent"),
+    ("WOLFSTACK_READ_ONLY", "server.read_only"),
+    ("WOLFSTACK_TLS", "tls.enabled"),
+    ("WOLFSTACK_TLS_CERT", "tls.cert"),
+    ("WOLFSTACK_TLS_KEY", "tls.key"),
+    ("WOLFSTACK_TLS_DOMAIN", "tls.domain"),
+    ("WOLFSTACK_NODE_POLL_SECS", "polling.node_poll_secs"),
+    ("WOLFSTACK_SELF_MONITOR_SECS", "polling.self_monitor_secs"),
+    ("WOLFSTACK_ALERT_CHECK_SECS", "polling.alert_check_secs"),
+    ("WOLFSTACK_HISTORY_SNAPSHOTS", "history.metrics_snapshots"),
+    ("WOLFSTACK_FEATURE_STATUS_PAGES", "features.status_pages"),
+    ("WOLFSTACK_FEATURE_DISCORD_BOT", "features.discord_bot"),
+    ("WOLFSTACK_FEATURE_TELEGRAM_BOT", "features.telegram_bot"),
+    ("WOLFSTACK_FEATURE_AI_HEALTH_CHECK", "features.ai_health_check"),
+    ("WOLFSTACK_LOG_FORMAT", "logging.format"),
+];
+
+fn parse_bool(var: &str, v: &str) -> Result<bool, String> {
+    match v.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(format!("{}={:?} is not a boolean (use true/false)", var, v)),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(var: &str, v: &str) -> Result<T, String> {
+    v.trim().parse().map_err(|_| format!("{}={:?} is not a valid number", var, v))
+}
+
+/// An empty string clears an optional key back to its default.
+fn opt_string(v: &str) -> Option<String> {
+    let v = v.trim();
+    if v.is_empty() { None } else { Some(v.to_string()) }
+}
+
+impl DaemonConfig {
+    /// Read `wolfstack.toml`. A missing file is all-defaults; an
+    /// unreadable or invalid one is an error so the caller can decide
+    /// whether to fall back.
+    pub fn load_file() -> Result<Self, String> {
+        let path = crate::paths::get().daemon_config;
+        match std::fs::read_to_string(&path) {
+            Ok(s) => toml::from_str(&s).map_err(|e| format!("{}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path, e)),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = crate::paths::get().daemon_config;
+        let body = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let text = format!(
+            "# WolfStack daemon settings — read at startup.\n\
+             # Command-line flags and WOLFSTACK_* environment variables override these.\n\n{}",
+            body
+        );
+        crate::paths::write_secure(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    /// Apply `WOLFSTACK_*` overrides from `lookup` (the process
+    /// environment in production). Returns the variables that were
+    /// applied; a malformed value is an error naming the variable.
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Vec<String>, String> {
+        let mut applied = Vec::new();
+        for (var, _) in ENV_VARS {
+            let Some(v) = lookup(var) else { continue };
+            match *var {
+                "WOLFSTACK_PORT" => self.server.port = if v.trim().is_empty() { None } else { Some(parse_num(var, &v)?) },
+                "WOLFSTACK_BIND" => self.server.bind = opt_string(&v),
+                "WOLFSTACK_AGENT" => self.server.agent = parse_bool(var, &v)?,
+                "WOLFSTACK_READ_ONLY" => self.server.read_only = parse_bool(var, &v)?,
+                "WOLFSTACK_TLS" => self.tls.enabled = parse_bool(var, &v)?,
+                "WOLFSTACK_TLS_CERT" => self.tls.cert = opt_string(&v),
+                "WOLFSTACK_TLS_KEY" => self.tls.key = opt_string(&v),
+                "WOLFSTACK_TLS_DOMAIN" => self.tls.domain = opt_string(&v),
+                "WOLFSTACK_NODE_POLL_SECS" => self.polling.node_poll_secs = parse_num(var, &v)?,
+                "WOLFSTACK_SELF_MONITOR_SECS" => self.polling.self_monitor_secs = if v.trim().is_empty() { None } else { Some(parse_num(var, &v)?) },
+                "WOLFSTACK_ALERT_CHECK_SECS" => self.polling.alert_check_secs = if v.trim().is_empty() { None } else { Some(parse_num(var, &v)?) },
+                "WOLFSTACK_HISTORY_SNAPSHOTS" => self.history.metrics_snapshots = parse_num(var, &v)?,
+                "WOLFSTACK_FEATURE_STATUS_PAGES" => self.features.status_pages = parse_bool(var, &v)?,
+                "WOLFSTACK_FEATURE_DISCORD_BOT" => self.features.discord_bot = parse_bool(var, &v)?,
+                "WOLFSTACK_FEATURE_TELEGRAM_BOT" => self.features.telegram_bot = parse_bool(var, &v)?,
+                "WOLFSTACK_FEATURE_AI_HEALTH_CHECK" => self.features.ai_health_check = parse_bool(var, &v)?,
+                "WOLFSTACK_LOG_FORMAT" => self.logging.format = match v.trim().to_ascii_lowercase().as_str() {
+                    "text" => crate::logging::LogFormat::Text,
+                    "json" => crate::logging::LogFormat::Json,
+                    _ => return Err(format!("{}={:?} must be text or json", var, v)),
+                },
+                _ => continue,
+            }
+            applied.push(var.to_string());
+        }
+        Ok(applied)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(p) = self.server.port {
+            if p < 1024 {
+                return Err(format!("server.port {} is in the privileged range (<1024)", p));
+            }
+        }
+        if let Some(b) = &self.server.bind {
+            if b.parse::<std::net::IpAddr>().is_err() {
+                return Err(format!("server.bind {:?} is not an IP address", b));
+            }
+        }
+        if self.server.read_only_message.as_ref().is_some_and(|m| m.chars().count() > 500) {
+            return Err("server.read_only_message must be at most 500 characters".into());
+        }
+        match (&self.tls.cert, &self.tls.key) {
+            (Some(_), None) | (None, Some(_)) => {
+                return Err("tls.cert and tls.key must be set together".into());
+            }
+            (Some(cert), Some(key)) => {
+                for (name, path) in [("tls.cert", cert), ("tls.key", key)] {
+                    if !std::path::Path::new(path).is_file() {
+                        return Err(format!("{} {} does not exist", name, path));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+        if let Some(d) = &self.tls.domain {
+            if d.contains(|c: char| c.is_whitespace() || c == '/') {
+                return Err(format!("tls.domain {:?} is not a hostname", d));
+            }
+        }
+        if !(2..=3600).contains(&self.polling.node_poll_secs) {
+            return Err("polling.node_poll_secs must be between 2 and 3600".into());
+        }
+        if let Some(s) = self.polling.self_monitor_secs {
+            if !(1..=60).contains(&s) {
+                return Err("polling.self_monitor_secs must be between 1 and 60".into());
+            }
+        }
+        if let Some(s) = self.polling.alert_check_secs {
+            if !(30..=86_400).contains(&s) {
+                return Err("polling.alert_check_secs must be between 30 and 86400".into());
+            }
+        }
+        if !(30..=86_400).contains(&self.history.metrics_snapshots) {
+            return Err("history.metrics_snapshots must be between 30 and 86400".into());
+        }
+        if let Some(f) = &self.logging.filter {
+            crate::logging::validate_filter(f).map_err(|e| format!("logging.filter: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Logging settings for the subscriber, which has to exist before
+/// anything (including [`load_effective`]) can log its problems — so this
+/// reads the same sources silently and falls back to defaults.
+pub fn logging_bootstrap() -> LoggingSection {
+    let Ok(mut cfg) = DaemonConfig::load_file() else { return LoggingSection::default() };
+    if cfg.apply_env(|k| std::env::var(k).ok()).is_err() || cfg.validate().is_err() {
+        return LoggingSection::default();
+    }
+    cfg.logging
+}
+
+/// File settings with environment overrides applied, plus the variables
+/// that took effect. Problems are logged and skipped — a typo in the file
+/// must not stop the daemon from coming up.
+pub fn load_effective() -> (DaemonConfig, Vec<String>) {
+    let mut cfg = DaemonConfig::load_file().unwrap_or_else(|e| {
+        warn!("wolfstack.toml ignored ({}), using defaults", e);
+        DaemonConfig::default()
+    });
+    let applied = match cfg.apply_env(|k| std::env::var(k).ok()) {
+        Ok(a) => a,
+        Err(e) => {
+            warn!("Environment override ignored: {}", e);
+            Vec::new()
+        }
+    };
+    if let Err(e) = cfg.validate() {
+        warn!("Daemon settings invalid ({}), using defaults", e);
+        return (DaemonConfig::default(), applied);
+    }
+    (cfg, applied)
+}
+
+/// Keys that are only read while the daemon starts up: listeners, TLS on
+/// or off, agent mode, and the tasks spawned once at boot.
+pub const RESTART_KEYS: &[&str] = &[
+    "server.port",
+    "server.bind",
+    "server.agent",
+    "tls.enabled",
+    "features.status_pages",
+    "features.discord_bot",
+    "features.telegram_bot",
+    "logging.format",
+];
+
+struct Running {
+    config: Arc<DaemonConfig>,
+    env_overrides: Vec<String>,
+}
+
+/// Settings as first loaded, for telling which [`RESTART_KEYS`] a reload
+/// changed relative to what the listeners were actually started with.
+static BOOT: OnceLock<DaemonConfig> = OnceLock::new();
+
+static RUNNING: LazyLock<RwLock<Running>> = LazyLock::new(|| {
+    let (config, env_overrides) = load_effective();
+    let _ = BOOT.set(config.clone());
+    if !env_overrides.is_empty() {
+        info!("Daemon settings overridden from environment: {}", env_overrides.join(", "));
+    }
+    RwLock::new(Running { config: Arc::new(config), env_overrides })
+});
+
+/// Settings currently in force. Loops should call this each tick rather
+/// than hold on to the result, so a reload reaches them.
+pub fn get() -> Arc<DaemonConfig> {
+    RUNNING.read().unwrap().config.clone()
+}
+
+/// Environment variables that overrode the file on the last (re)load.
+pub fn env_overrides() -> Vec<String> {
+    RUNNING.read().unwrap().env_overrides.clone()
+}
+
+/// Keys in `config` that differ from startup but can't apply until the
+/// daemon restarts.
+pub fn pending_restart(config: &DaemonConfig) -> Vec<String> {
+    let boot = BOOT.get().cloned().unwrap_or_else(|| (*get()).clone());
+    changed_keys(&boot, config).into_iter()
+        .filter(|k| RESTART_KEYS.contains(&k.as_str()))
+        .collect()
+}
+
+/// `section.key` names whose values differ between two configs.
+pub fn changed_keys(old: &DaemonConfig, new: &DaemonConfig) -> Vec<String> {
+    let (old, new) = (serde_json::to_value(old).unwrap_or_default(), serde_json::to_value(new).unwrap_or_default());
+    let mut changed = Vec::new();
+    for (section, new_fields) in new.as_object().into_iter().flatten() {
+        let empty = serde_json::Map::new();
+        let new_fields = new_fields.as_object().unwrap_or(&empty);
+        let old_fields = old[section].as_object().unwrap_or(&empty);
+        // Optional keys are omitted when unset, so walk both sides.
+        let mut keys: Vec<&String> = new_fields.keys().chain(old_fields.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            if new_fields.get(key) != old_fields.get(key) {
+                changed.push(format!("{}.{}", section, key));
+            }
+        }
+    }
+    changed
+}
+
+/// Outcome of a [`reload`].
+#[derive(Serialize, Debug, Default)]
+pub struct ReloadReport {
+    /// Keys whose value changed.
+    pub changed: Vec<String>,
+    /// Keys that differ from startup and only take effect after a restart.
+    pub restart_required: Vec<String>,
+    /// Whether the HTTPS listener now serves a freshly loaded certificate.
+    pub tls_reloaded: bool,
+    /// Why the certificate was not reloaded, when TLS is on but it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_error: Option<String>,
+}
+
+/// Re-read `wolfstack.toml` and the environment and swap them in. Unlike
+/// startup, a broken file is an error here and the current settings stay
+/// in force — a typo must not quietly reset a running node to defaults.
+/// The TLS certificate is reloaded every time (not only when `tls.*`
+/// changed) so a SIGHUP after a certbot renewal picks the new cert up.
+pub fn reload() -> Result<ReloadReport, String> {
+    let mut next = DaemonConfig::load_file()?;
+    let env = next.apply_env(|k| std::env::var(k).ok())?;
+    next.validate()?;
+
+    let restart_required = pending_restart(&next);
+    let changed = {
+        let mut running = RUNNING.write().unwrap();
+        let changed = changed_keys(&running.config, &next);
+        running.config = Arc::new(next);
+        running.env_overrides = env;
+        changed
+    };
+    if changed.iter().any(|k| k == "logging.filter") {
+        let filter = get().logging.filter.clone();
+        if let Err(e) = crate::logging::set_configured(filter.as_deref()) {
+            warn!("Log filter not applied: {}", e);
+        }
+    }
+    let (tls_reloaded, tls_error) = match crate::tls_reload::reload(&get().tls) {
+        Ok(reloaded) => (reloaded, None),
+        Err(e) => (false, Some(e)),
+    };
+    Ok(ReloadReport { changed, restart_required, tls_reloaded, tls_error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_is_all_defaults() {
+        let cfg: DaemonConfig = toml::from_str("").unwrap();
+        assert_eq!(cfg, DaemonConfig::default());
+        assert!(cfg.tls.enabled);
+        assert_eq!(cfg.polling.node_poll_secs, 10);
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn partial_file_keeps_other_defaults() {
+        let cfg: DaemonConfig = toml::from_str(
+            "[server]\nport = 9443\n\n[features]\ndiscord_bot = false\n",
+        ).unwrap();
+        assert_eq!(cfg.server.port, Some(9443));
+        assert!(!cfg.features.discord_bot);
+        assert!(cfg.features.telegram_bot);
+        assert_eq!(cfg.history, HistorySection::default());
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<DaemonConfig>("[polling]\nnode_poll = 5\n").is_err());
+    }
+
+    #[test]
+    fn saved_file_round_trips() {
+        let mut cfg = DaemonConfig::default();
+        cfg.server.bind = Some("::".into());
+        cfg.polling.self_monitor_secs = Some(5);
+        let text = toml::to_string_pretty(&cfg).unwrap();
+        assert_eq!(toml::from_str::<DaemonConfig>(&text).unwrap(), cfg);
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let mut cfg = DaemonConfig::default();
+        cfg.server.bind = Some("10.0.0.1".into());
+        let env = |k: &str| match k {
+            "WOLFSTACK_BIND" => Some("".to_string()),
+            "WOLFSTACK_NODE_POLL_SECS" => Some("30".to_string()),
+            "WOLFSTACK_FEATURE_STATUS_PAGES" => Some("off".to_string()),
+            _ => None,
+        };
+        let applied = cfg.apply_env(env).unwrap();
+        assert_eq!(applied.len(), 3);
+        assert_eq!(cfg.server.bind, None);
+        assert_eq!(cfg.polling.node_poll_secs, 30);
+        assert!(!cfg.features.status_pages);
+    }
+
+    #[test]
+    fn bad_env_value_names_the_variable() {
+        let mut cfg = DaemonConfig::default();
+        let err = cfg.apply_env(|k| (k == "WOLFSTACK_PORT").then(|| "http".to_string())).unwrap_err();
+        assert!(err.contains("WOLFSTACK_PORT"));
+    }
+
+    #[test]
+    fn changed_keys_sees_set_and_cleared_options() {
+        let old = DaemonConfig::default();
+        let mut new = DaemonConfig::default();
+        new.polling.alert_check_secs = Some(120);
+        new.features.discord_bot = false;
+        assert_eq!(changed_keys(&old, &new), vec!["features.discord_bot", "polling.alert_check_secs"]);
+        assert_eq!(changed_keys(&new, &old), vec!["features.discord_bot", "polling.alert_check_secs"]);
+        assert!(changed_keys(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn validation_catches_bad_values() {
+        let mut cfg = DaemonConfig::default();
+        cfg.server.port = Some(80);
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = DaemonConfig::default();
+        cfg.server.bind = Some("localhost".into());
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = DaemonConfig::default();
+        cfg.tls.cert = Some("/etc/ssl/cert.pem".into());
+        assert!(cfg.validate().unwrap_err().contains("together"));
+
+        let mut cfg = DaemonConfig::default();
+        cfg.polling.node_poll_secs = 0;
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = DaemonConfig::default();
+        cfg.history.metrics_snapshots = 5;
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = DaemonConfig::default();
+        cfg.server.read_only_message = Some("x".repeat(501));
+        assert!(cfg.validate().is_err());
+    }
+}
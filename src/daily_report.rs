@@ -0,0 +1,203 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Rendering helpers for the daily report email.
+//!
+//! The report itself is assembled in the scheduled scan loop in main.rs
+//! (it needs the cluster, HTTP client and AI agent that loop already
+//! holds); this module keeps the pure pieces — sparklines, cluster
+//! grouping and the plain-text alternative — out of that loop so they can
+//! be tested.
+//!
+//! Sparklines are built from plain `<td>` bars rather than SVG or canvas:
+//! Gmail and Outlook strip inline SVG, but every client renders table
+//! cells with a background colour and a height.
+
+use std::collections::BTreeMap;
+
+use crate::api::Issue;
+use crate::monitoring::MetricsSnapshot;
+
+/// Bars per sparkline. MetricsHistory holds up to 300 samples; averaging
+/// them down keeps each chart narrow enough for a table cell on mobile.
+pub const SPARKLINE_BUCKETS: usize = 30;
+
+/// Minimal HTML escaping for text interpolated into the report.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Average `values` down to at most `buckets` points, preserving order.
+pub fn downsample(values: &[f32], buckets: usize) -> Vec<f32> {
+    if buckets == 0 || values.len() <= buckets {
+        return values.to_vec();
+    }
+    (0..buckets)
+        .map(|b| {
+            let start = b * values.len() / buckets;
+            let end = ((b + 1) * values.len() / buckets).max(start + 1);
+            let slice = &values[start..end];
+            slice.iter().sum::<f32>() / slice.len() as f32
+        })
+        .collect()
+}
+
+/// CPU% and memory% series from a node's metrics history, oldest first.
+pub fn history_series(snapshots: &[MetricsSnapshot]) -> (Vec<f32>, Vec<f32>) {
+    let cpu = snapshots.iter().map(|s| s.cpu_percent).collect();
+    let mem = snapshots.iter().map(|s| s.memory_percent).collect();
+    (cpu, mem)
+}
+
+/// Human span covered by a history, e.g. "last 25 min" — the ring buffer
+/// length depends on the sample interval, so the label is computed.
+pub fn history_span_label(snapshots: &[MetricsSnapshot]) -> String {
+    match (snapshots.first(), snapshots.last()) {
+        (Some(a), Some(b)) if b.timestamp > a.timestamp => {
+            let mins = (b.timestamp - a.timestamp) / 60;
+            if mins >= 120 { format!("last {} h", mins / 60) } else { format!("last {} min", mins.max(1)) }
+        }
+        _ => String::new(),
+    }
+}
+
+/// Email-safe bar sparkline for a 0–100 percentage series. Bar colour
+/// follows the same green / amber / red thresholds as the inventory bars.
+pub fn sparkline(values: &[f32]) -> String {
+    let points = downsample(values, SPARKLINE_BUCKETS);
+    if points.is_empty() {
+        return r#"<span class="meta">no history</span>"#.to_string();
+    }
+    let mut html = String::from(
+        r#"<table cellpadding="0" cellspacing="0" style="width:auto;border-collapse:collapse;margin:0;"><tr style="vertical-align:bottom;">"#,
+    );
+    for v in &points {
+        let v = v.clamp(0.0, 100.0);
+        let height = ((v / 100.0) * 24.0).round().max(1.0) as u32;
+        let color = if v > 80.0 { "#dc2626" } else if v > 50.0 { "#d97706" } else { "#16a34a" };
+        html.push_str(&format!(
+            r#"<td style="padding:0 1px 0 0;border:none;background:none;vertical-align:bottom;"><div style="width:3px;height:{}px;background:{};"></div></td>"#,
+            height, color
+        ));
+    }
+    html.push_str("</tr></table>");
+    html
+}
+
+/// Group `(cluster, host, issue)` triples by cluster name, keeping the
+/// scan order within each cluster.
+pub fn issues_by_cluster(issues: &[(String, String, Issue)]) -> BTreeMap<String, Vec<(String, Issue)>> {
+    let mut map: BTreeMap<String, Vec<(String, Issue)>> = BTreeMap::new();
+    for (cluster, host, issue) in issues {
+        map.entry(cluster.clone()).or_default().push((host.clone(), issue.clone()));
+    }
+    map
+}
+
+/// One inventory line for the plain-text alternative.
+pub struct TextNode {
+    pub cluster: String,
+    pub hostname: String,
+    pub online: bool,
+    pub cpu_percent: Option<f32>,
+    pub memory_percent: Option<f32>,
+}
+
+/// Plain-text version of the report, sent as the multipart fallback for
+/// clients that block HTML. Mirrors the HTML's per-cluster layout.
+pub fn plain_text(
+    date: &str,
+    nodes: &[TextNode],
+    issues: &[(String, String, Issue)],
+    ai_recommendations: &str,
+    dashboard_url: &str,
+) -> String {
+    let mut out = format!(
+        "WolfStack Daily Report — {}\nWolfStack v{} • {} node(s) scanned • {} issue(s)\n",
+        date, env!("CARGO_PKG_VERSION"), nodes.len(), issues.len()
+    );
+    let grouped = issues_by_cluster(issues);
+    let mut clusters: Vec<&str> = nodes.iter().map(|n| n.cluster.as_str()).collect();
+    clusters.extend(grouped.keys().map(|k| k.as_str()));
+    clusters.sort();
+    clusters.dedup();
+
+    for cluster in clusters {
+        out.push_str(&format!("\n== Cluster: {} ==\n", cluster));
+        for n in nodes.iter().filter(|n| n.cluster == cluster) {
+            let fmt = |v: Option<f32>| v.map(|p| format!("{:.0}%", p)).unwrap_or_else(|| "—".to_string());
+            out.push_str(&format!(
+                "  {} [{}] CPU {} • Memory {}\n",
+                n.hostname,
+                if n.online { "online" } else { "OFFLINE" },
+                fmt(n.cpu_percent),
+                fmt(n.memory_percent),
+            ));
+        }
+        match grouped.get(cluster) {
+            Some(list) if !list.is_empty() => {
+                out.push_str("  Issues:\n");
+                for (host, issue) in list {
+                    out.push_str(&format!(
+                        "    [{}] {} on {}: {}\n",
+                        issue.severity.to_uppercase(), issue.title, host, issue.detail
+                    ));
+                }
+            }
+            _ => out.push_str("  No issues detected.\n"),
+        }
+    }
+
+    if !ai_recommendations.is_empty() {
+        out.push_str("\n== AI Recommendations ==\n");
+        out.push_str(ai_recommendations);
+        out.push('\n');
+    }
+    out.push_str(&format!("\nOpen the dashboard: {}\n", dashboard_url));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(sev: &str, title: &str) -> Issue {
+        Issue { severity: sev.into(), category: "cpu".into(), title: title.into(), detail: "d".into() }
+    }
+
+    #[test]
+    fn downsample_averages_into_buckets() {
+        let values: Vec<f32> = (0..300).map(|i| (i % 10) as f32).collect();
+        let out = downsample(&values, 30);
+        assert_eq!(out.len(), 30);
+        assert!(out.iter().all(|v| (*v - 4.5).abs() < f32::EPSILON));
+        assert_eq!(downsample(&[1.0, 2.0], 30), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn sparkline_has_one_bar_per_bucket_and_clamps() {
+        let html = sparkline(&[0.0, 50.0, 150.0]);
+        assert_eq!(html.matches("<td").count(), 3);
+        assert!(html.contains("height:24px;background:#dc2626"));
+        assert!(html.contains("height:1px;"));
+        assert!(sparkline(&[]).contains("no history"));
+    }
+
+    #[test]
+    fn plain_text_groups_by_cluster() {
+        let nodes = vec![
+            TextNode { cluster: "prod".into(), hostname: "web1".into(), online: true, cpu_percent: Some(12.0), memory_percent: Some(40.0) },
+            TextNode { cluster: "lab".into(), hostname: "pi".into(), online: false, cpu_percent: None, memory_percent: None },
+        ];
+        let issues = vec![("prod".to_string(), "web1".to_string(), issue("critical", "Disk full"))];
+        let text = plain_text("2026-01-01", &nodes, &issues, "", "https://example:8553");
+        let lab = text.find("== Cluster: lab ==").unwrap();
+        let prod = text.find("== Cluster: prod ==").unwrap();
+        assert!(lab < prod);
+        assert!(text[lab..prod].contains("pi [OFFLINE]"));
+        assert!(text[lab..prod].contains("No issues detected."));
+        assert!(text[prod..].contains("[CRITICAL] Disk full on web1"));
+        assert!(text.ends_with("Open the dashboard: https://example:8553\n"));
+    }
+}
@@ -0,0 +1,320 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Config drift detection.
+//!
+//! Two kinds of drift are looked for, every 15 minutes and on demand:
+//!
+//! - **Desired state.** An admin pins a file — "`/etc/wolfdisk/config.toml`
+//!   should look like this node's copy" — and its SHA-256 becomes a
+//!   baseline. Baselines are cluster-wide: saved to `paths.drift_config`
+//!   and pushed to same-cluster peers on every change, newest `updated_at`
+//!   wins (as webhooks are). Each node compares its own copy with the
+//!   baseline, so a node whose file was edited, or never got the change,
+//!   shows up on that node's issues list.
+//! - **Last known good.** Every file WolfStack saved (see
+//!   [`crate::config_history`]) is compared with the last version it
+//!   wrote. A difference means someone changed it behind WolfStack's back.
+//!   Files with a baseline are only held to the baseline.
+//!
+//! The latest scan is kept in memory and fed to the `config_drift` issue
+//! check.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::api::Issue;
+
+const SCAN_INTERVAL_SECS: u64 = 900;
+
+fn config_path() -> String {
+    crate::paths::get().drift_config
+}
+
+/// A file every node in the cluster should have with exactly this content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub path: String,
+    pub sha256: String,
+    /// Hostname of the node whose copy was pinned.
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub pinned_by: String,
+    /// Unix seconds.
+    #[serde(default)]
+    pub pinned_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DriftConfig {
+    #[serde(default)]
+    pub baselines: Vec<Baseline>,
+    /// Unix millis of the last change; peers keep the newest copy.
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub path: String,
+    /// `desired` (differs from its baseline), `missing` (a baselined file
+    /// that isn't there) or `last_known_good` (changed since WolfStack
+    /// last saved it).
+    pub kind: String,
+    pub expected: String,
+    /// Hash of the file on disk; empty when it's missing.
+    pub actual: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Scan {
+    /// Unix seconds; 0 before the first scan.
+    pub at: i64,
+    pub checked: usize,
+    pub findings: Vec<Finding>,
+}
+
+static CONFIG: LazyLock<RwLock<DriftConfig>> = LazyLock::new(|| {
+    let cfg = std::fs::read_to_string(config_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    RwLock::new(cfg)
+});
+
+static LAST: LazyLock<RwLock<Scan>> = LazyLock::new(|| RwLock::new(Scan::default()));
+
+pub fn config() -> DriftConfig {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn last_scan() -> Scan {
+    LAST.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn write_file(cfg: &DriftConfig) -> Result<(), String> {
+    let path = config_path();
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(cfg).map_err(|e| e.to_string())?;
+    crate::paths::write_secure_atomic(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn update(f: impl FnOnce(&mut Vec<Baseline>) -> Result<(), String>) -> Result<DriftConfig, String> {
+    let mut cfg = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    let mut next = cfg.clone();
+    f(&mut next.baselines)?;
+    next.updated_at = chrono::Utc::now().timestamp_millis();
+    write_file(&next)?;
+    *cfg = next.clone();
+    Ok(next)
+}
+
+/// Make this node's copy of `path` the desired state for the cluster. The
+/// caller pushes the result to peers.
+pub fn pin(path: &str, user: &str, hostname: &str) -> Result<DriftConfig, String> {
+    let path = path.trim();
+    if !path.starts_with('/') || path.split('/').any(|c| c == "..") {
+        return Err("Path must be absolute".to_string());
+    }
+    let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let baseline = Baseline {
+        path: path.to_string(),
+        sha256: sha256_hex(&data),
+        source: hostname.to_string(),
+        pinned_by: user.to_string(),
+        pinned_at: chrono::Utc::now().timestamp(),
+    };
+    update(|list| {
+        list.retain(|b| b.path != baseline.path);
+        list.push(baseline);
+        list.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(())
+    })
+}
+
+pub fn unpin(path: &str) -> Result<DriftConfig, String> {
+    update(|list| {
+        let before = list.len();
+        list.retain(|b| b.path != path);
+        if list.len() == before { Err(format!("{} has no baseline", path)) } else { Ok(()) }
+    })
+}
+
+/// Adopt a peer's baselines if they are newer than ours. Returns whether
+/// they were.
+pub fn merge_from_peer(incoming: DriftConfig) -> Result<bool, String> {
+    let mut cfg = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    if incoming.updated_at <= cfg.updated_at {
+        return Ok(false);
+    }
+    write_file(&incoming)?;
+    *cfg = incoming;
+    Ok(true)
+}
+
+/// Compare live file hashes with the baselines and with the last version
+/// WolfStack saved. `read` returns a file's bytes, `None` when missing.
+fn compare(
+    baselines: &[Baseline],
+    tracked: &[(String, String)],
+    read: impl Fn(&str) -> Option<Vec<u8>>,
+) -> (usize, Vec<Finding>) {
+    let mut findings = Vec::new();
+    for b in baselines {
+        match read(&b.path) {
+            None => findings.push(Finding {
+                path: b.path.clone(),
+                kind: "missing".into(),
+                expected: b.sha256.clone(),
+                actual: String::new(),
+                detail: format!("{} is missing on this node; the cluster's copy was pinned from {}.", b.path, b.source),
+            }),
+            Some(data) => {
+                let actual = sha256_hex(&data);
+                if actual != b.sha256 {
+                    findings.push(Finding {
+                        path: b.path.clone(),
+                        kind: "desired".into(),
+                        expected: b.sha256.clone(),
+                        actual,
+                        detail: format!("{} differs from the cluster's desired state (pinned from {}).", b.path, b.source),
+                    });
+                }
+            }
+        }
+    }
+    let mut checked = baselines.len();
+    for (path, good) in tracked.iter().filter(|(p, _)| !baselines.iter().any(|b| &b.path == p)) {
+        checked += 1;
+        let Some(data) = read(path) else { continue };
+        let expected = sha256_hex(good.as_bytes());
+        let actual = sha256_hex(&data);
+        if actual != expected {
+            findings.push(Finding {
+                path: path.clone(),
+                kind: "last_known_good".into(),
+                expected,
+                actual,
+                detail: format!("{} was changed outside WolfStack since it was last saved here. Config history has both versions.", path),
+            });
+        }
+    }
+    (checked, findings)
+}
+
+/// Scan now and keep the result. Blocking — reads every managed file.
+pub fn scan() -> Scan {
+    let cfg = config();
+    let tracked: Vec<(String, String)> = crate::config_history::tracked().into_iter()
+        .filter_map(|f| crate::config_history::latest(&f.path).map(|c| (f.path, c)))
+        .collect();
+    let (checked, findings) = compare(&cfg.baselines, &tracked, |p| std::fs::read(p).ok());
+    let scan = Scan { at: chrono::Utc::now().timestamp(), checked, findings };
+    *LAST.write().unwrap_or_else(|e| e.into_inner()) = scan.clone();
+    scan
+}
+
+/// Scan shortly after start-up and every 15 minutes after.
+pub fn start() {
+    tokio::spawn(async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        loop {
+            if let Err(e) = tokio::task::spawn_blocking(scan).await {
+                warn!("Drift scan failed: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(SCAN_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+/// The `config_drift` issue check.
+pub fn check(_metrics: &crate::monitoring::SystemMetrics) -> Vec<Issue> {
+    last_scan().findings.into_iter().map(|f| Issue {
+        severity: if f.kind == "last_known_good" { "info" } else { "warning" }.into(),
+        category: "config".into(),
+        title: match f.kind.as_str() {
+            "missing" => format!("Managed config {} is missing", f.path),
+            "desired" => format!("{} has drifted from the cluster's desired state", f.path),
+            _ => format!("{} was changed outside WolfStack", f.path),
+        },
+        detail: f.detail,
+    }).collect()
+}
+
+/// Push the baselines to every online same-cluster peer.
+pub async fn broadcast_to_cluster(cluster: Arc<crate::agent::ClusterState>, cluster_secret: String) {
+    let cfg = config();
+    let self_cluster = cluster.get_self_cluster_name();
+    for node in cluster.get_all_nodes().iter().filter(|n| !n.is_self && n.online && n.node_type == "wolfstack") {
+        if node.cluster_name.as_deref().unwrap_or("WolfStack") != self_cluster {
+            continue;
+        }
+        let mut sent = false;
+        for url in crate::api::build_node_urls(&node.address, node.port, "/api/drift/sync") {
+            if let Ok(resp) = crate::api::API_HTTP_CLIENT.post(&url)
+                .timeout(Duration::from_secs(10))
+                .header("X-WolfStack-Secret", &cluster_secret)
+                .json(&cfg)
+                .send().await
+                && resp.status().is_success()
+            {
+                sent = true;
+                break;
+            }
+        }
+        if !sent {
+            warn!("Drift: failed to sync baselines to {}", node.hostname);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn baseline(path: &str, content: &str) -> Baseline {
+        Baseline { path: path.into(), sha256: sha256_hex(content.as_bytes()), source: "node-a".into(), pinned_by: String::new(), pinned_at: 0 }
+    }
+
+    #[test]
+    fn finds_desired_missing_and_last_known_good_drift() {
+        let files: HashMap<&str, &str> = HashMap::from([
+            ("/etc/wolfdisk/config.toml", "replicas = 2\n"),
+            ("/etc/wolfnet/config.toml", "port = 9600\n"),
+            ("/opt/wolfproxy/wolfproxy.toml", "edited by hand\n"),
+            ("/etc/app.conf", "same\n"),
+        ]);
+        let baselines = vec![
+            baseline("/etc/wolfdisk/config.toml", "replicas = 3\n"),
+            baseline("/etc/wolfnet/config.toml", "port = 9600\n"),
+            baseline("/etc/gone.conf", "x"),
+        ];
+        let tracked = vec![
+            ("/opt/wolfproxy/wolfproxy.toml".to_string(), "saved by wolfstack\n".to_string()),
+            ("/etc/app.conf".to_string(), "same\n".to_string()),
+            // Baselined: judged against the baseline only.
+            ("/etc/wolfnet/config.toml".to_string(), "port = 1\n".to_string()),
+        ];
+        let (checked, findings) = compare(&baselines, &tracked, |p| files.get(p).map(|c| c.as_bytes().to_vec()));
+        assert_eq!(checked, 5);
+        let kinds: Vec<(&str, &str)> = findings.iter().map(|f| (f.path.as_str(), f.kind.as_str())).collect();
+        assert_eq!(kinds, vec![
+            ("/etc/wolfdisk/config.toml", "desired"),
+            ("/etc/gone.conf", "missing"),
+            ("/opt/wolfproxy/wolfproxy.toml", "last_known_good"),
+        ]);
+    }
+}
@@ -0,0 +1,121 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Internal event bus for lifecycle events.
+//!
+//! Every WolfFunctions trigger event (node online/offline, alert fired,
+//! backup finished, monitor down/up, container lifecycle, UPS, …) is
+//! also published here once it has passed the leader gate, so cluster
+//! events appear once per cluster and node-local events once per node.
+//! Subscribers take a `tokio::sync::broadcast` receiver; the webhook
+//! emitter in [`webhooks`] is the built-in one.
+//!
+//! Publishing is fire-and-forget: with no receiver the event is simply
+//! dropped, and a receiver that falls behind loses the oldest events
+//! (it is told how many) rather than stalling the publisher.
+
+pub mod webhooks;
+
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+
+/// Events held for slow receivers before the oldest are dropped.
+const BUS_CAPACITY: usize = 256;
+
+/// Event names and descriptions offered in the UI. Names are the
+/// snake_case `TriggerEvent` variants; `ping` is only sent by the
+/// webhook test button.
+pub const EVENT_TYPES: &[(&str, &str)] = &[
+    ("node_online", "Node came online"),
+    ("node_offline", "Node went offline"),
+    ("container_created", "Container created"),
+    ("container_started", "Container started"),
+    ("container_stopped", "Container stopped"),
+    ("container_deleted", "Container deleted"),
+    ("container_updated", "Container image updated"),
+    ("container_update_failed", "Container image update failed"),
+    ("container_failover", "WolfRun failover (standby promoted)"),
+    ("backup_completed", "Backup completed"),
+    ("backup_failed", "Backup failed"),
+    ("alert_fired", "Alert fired"),
+    ("monitor_down", "Status monitor went down"),
+    ("monitor_up", "Status monitor recovered"),
+    ("ups_on_battery", "UPS on battery"),
+    ("ups_online", "UPS power restored"),
+    ("ups_stage_fired", "UPS shutdown stage fired"),
+];
+
+/// One published event, as delivered to subscribers and webhooks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Unique per event; webhook retries reuse it so receivers can dedup.
+    pub id: String,
+    pub event: String,
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    /// Cluster and hostname of the node that emitted the event.
+    pub cluster: String,
+    pub hostname: String,
+    pub payload: serde_json::Value,
+}
+
+static BUS: LazyLock<broadcast::Sender<Event>> = LazyLock::new(|| broadcast::channel(BUS_CAPACITY).0);
+
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    BUS.subscribe()
+}
+
+/// Build an event stamped with this node's identity.
+pub fn new_event(event: &str, payload: serde_json::Value) -> Event {
+    Event {
+        id: uuid::Uuid::new_v4().to_string(),
+        event: event.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        cluster: crate::predictive::threat_intel::this_node_cluster(),
+        hostname: hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_else(|_| "unknown".into()),
+        payload,
+    }
+}
+
+/// Publish an event to every current subscriber.
+pub fn publish(event: &str, payload: serde_json::Value) {
+    let _ = BUS.send(new_event(event, payload));
+}
+
+/// Whether anything outside WolfFunctions wants events — lets
+/// `fire_event_global` keep its no-subscriber fast path.
+pub fn has_subscribers() -> bool {
+    webhooks::any_enabled()
+}
+
+// ─── Live feed ───
+//
+// What the dashboard's `/api/ws/events` stream carries. Unlike the bus
+// above it is not leader-gated: every node's UI hears what that node
+// saw — the trigger events above, plus `alert` (an alert-log entry for
+// the Tasks panel) and `job` (progress of a long-running task). Nothing
+// is buffered for a closed browser tab; the UI reloads on reconnect.
+
+/// Live events held for a slow browser before the oldest are dropped.
+const LIVE_CAPACITY: usize = 512;
+
+static LIVE: LazyLock<broadcast::Sender<Event>> = LazyLock::new(|| broadcast::channel(LIVE_CAPACITY).0);
+
+pub fn subscribe_live() -> broadcast::Receiver<Event> {
+    LIVE.subscribe()
+}
+
+/// Whether a dashboard is listening — callers skip building a payload
+/// when nobody is.
+pub fn live_listeners() -> bool {
+    LIVE.receiver_count() > 0
+}
+
+/// Publish to the live feed. A no-op with no dashboard connected.
+pub fn live(event: &str, payload: serde_json::Value) {
+    if live_listeners() {
+        let _ = LIVE.send(new_event(event, payload));
+    }
+}
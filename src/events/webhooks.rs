@@ -0,0 +1,361 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Outbound webhooks — POST bus events to user-configured URLs.
+//!
+//! Each delivery is the [`Event`] as JSON with these headers:
+//!
+//! - `X-WolfStack-Event`: the event name (`node_offline`, …)
+//! - `X-WolfStack-Delivery`: the event id (same across retries)
+//! - `X-WolfStack-Timestamp`: unix seconds when the request was signed
+//! - `X-WolfStack-Signature`: `sha256=<hex>` HMAC-SHA256, keyed with the
+//!   webhook's secret, over `<timestamp>.<body>`
+//!
+//! Including the timestamp in the signed string lets receivers reject
+//! replays of an old, validly-signed request. Failed deliveries (network
+//! error or non-2xx) are retried twice with backoff; the last
+//! [`DELIVERY_LOG_CAP`] attempts are kept in memory for the UI.
+//!
+//! The webhook list is cluster-wide: it's saved to
+//! `<config_dir>/webhooks.json` (0600, it holds the signing secrets) and
+//! pushed to same-cluster peers on every change, newest `updated_at`
+//! wins — node-local events are emitted by whichever node they happen on.
+
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::Event;
+
+pub const DELIVERY_LOG_CAP: usize = 200;
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_secs(2), Duration::from_secs(10)];
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+});
+
+fn config_path() -> String {
+    format!("{}/webhooks.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// HMAC key. Never returned by the list API.
+    pub secret: String,
+    /// Event names to send; empty = every event.
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub created: String,
+}
+
+fn default_true() -> bool { true }
+
+impl Webhook {
+    pub fn wants(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event) || event == "ping")
+    }
+
+    /// API view: everything but the secret.
+    pub fn public_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "url": self.url,
+            "events": self.events,
+            "enabled": self.enabled,
+            "created": self.created,
+            "has_secret": !self.secret.is_empty(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    /// Unix millis of the last change; peers keep the newest copy.
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+static CONFIG: LazyLock<RwLock<WebhookConfig>> = LazyLock::new(|| {
+    let cfg = std::fs::read_to_string(config_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    RwLock::new(cfg)
+});
+
+pub fn config() -> WebhookConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+pub fn any_enabled() -> bool {
+    CONFIG.read().unwrap().webhooks.iter().any(|w| w.enabled)
+}
+
+fn write_file(cfg: &WebhookConfig) -> Result<(), String> {
+    let path = config_path();
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(cfg).map_err(|e| e.to_string())?;
+    crate::paths::write_secure_atomic(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Apply a change to the webhook list, stamp it and persist it. The
+/// caller pushes the result to peers.
+pub fn update<F: FnOnce(&mut Vec<Webhook>) -> Result<(), String>>(f: F) -> Result<WebhookConfig, String> {
+    let mut cfg = CONFIG.write().unwrap();
+    let mut next = cfg.clone();
+    f(&mut next.webhooks)?;
+    next.updated_at = chrono::Utc::now().timestamp_millis();
+    write_file(&next)?;
+    *cfg = next.clone();
+    Ok(next)
+}
+
+/// Adopt a peer's list if it is newer than ours. Returns whether it was.
+pub fn merge_from_peer(incoming: WebhookConfig) -> Result<bool, String> {
+    let mut cfg = CONFIG.write().unwrap();
+    if incoming.updated_at <= cfg.updated_at {
+        return Ok(false);
+    }
+    write_file(&incoming)?;
+    *cfg = incoming;
+    Ok(true)
+}
+
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err("Webhook URL must be http:// or https:// with a host".to_string());
+    }
+    Ok(())
+}
+
+pub fn validate_events(events: &[String]) -> Result<(), String> {
+    for e in events {
+        if !super::EVENT_TYPES.iter().any(|(name, _)| name == e) {
+            return Err(format!("Unknown event '{}'", e));
+        }
+    }
+    Ok(())
+}
+
+/// 32 random bytes, hex — the default signing secret.
+pub fn generate_secret() -> String {
+    use rand::RngCore;
+    let mut buf = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut buf);
+    hex::encode(buf)
+}
+
+/// `sha256=<hex>` over `<timestamp>.<body>`.
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac can take any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// One delivery attempt series, for the UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub webhook_id: String,
+    pub event_id: String,
+    pub event: String,
+    pub timestamp: String,
+    pub attempts: u32,
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+static DELIVERIES: LazyLock<Mutex<VecDeque<Delivery>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+fn log_delivery(d: Delivery) {
+    let mut log = DELIVERIES.lock().unwrap();
+    log.push_back(d);
+    while log.len() > DELIVERY_LOG_CAP {
+        log.pop_front();
+    }
+}
+
+/// Recent deliveries for one webhook, newest first.
+pub fn deliveries(webhook_id: &str) -> Vec<Delivery> {
+    DELIVERIES.lock().unwrap().iter().rev().filter(|d| d.webhook_id == webhook_id).cloned().collect()
+}
+
+/// POST one event to one webhook, retrying transient failures.
+pub async fn deliver(hook: &Webhook, event: &Event) -> Delivery {
+    let body = serde_json::to_vec(event).unwrap_or_default();
+    let started = std::time::Instant::now();
+    let mut delivery = Delivery {
+        webhook_id: hook.id.clone(),
+        event_id: event.id.clone(),
+        event: event.event.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        attempts: 0,
+        status: None,
+        error: None,
+        duration_ms: 0,
+    };
+    for attempt in 0..=RETRY_DELAYS.len() {
+        if attempt > 0 {
+            tokio::time::sleep(RETRY_DELAYS[attempt - 1]).await;
+        }
+        delivery.attempts += 1;
+        let ts = chrono::Utc::now().timestamp();
+        let result = CLIENT.post(&hook.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", concat!("WolfStack-Webhook/", env!("CARGO_PKG_VERSION")))
+            .header("X-WolfStack-Event", &event.event)
+            .header("X-WolfStack-Delivery", &event.id)
+            .header("X-WolfStack-Timestamp", ts.to_string())
+            .header("X-WolfStack-Signature", signature(&hook.secret, ts, &body))
+            .body(body.clone())
+            .send().await;
+        match result {
+            Ok(resp) => {
+                let status = resp.status();
+                delivery.status = Some(status.as_u16());
+                if status.is_success() {
+                    delivery.error = None;
+                    break;
+                }
+                delivery.error = Some(format!("HTTP {}", status));
+                // A 4xx other than 408/429 won't change on retry.
+                if status.is_client_error() && status.as_u16() != 408 && status.as_u16() != 429 {
+                    break;
+                }
+            }
+            Err(e) => {
+                delivery.status = None;
+                delivery.error = Some(e.to_string());
+            }
+        }
+    }
+    delivery.duration_ms = started.elapsed().as_millis() as u64;
+    if let Some(e) = &delivery.error {
+        warn!("Webhook '{}' failed for {} after {} attempt(s): {}", hook.name, event.event, delivery.attempts, e);
+    }
+    log_delivery(delivery.clone());
+    delivery
+}
+
+/// Run forever: deliver each bus event to every webhook that wants it.
+/// Each delivery is its own task so a slow receiver doesn't hold up
+/// the others.
+pub async fn run_dispatcher() {
+    let mut rx = super::subscribe();
+    info!("Webhook dispatcher started");
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                for hook in config().webhooks.into_iter().filter(|w| w.wants(&event.event)) {
+                    let event = event.clone();
+                    tokio::spawn(async move {
+                        deliver(&hook, &event).await;
+                    });
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                warn!("Webhook dispatcher fell behind; {} event(s) not delivered", n);
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Push the webhook list to every online same-cluster peer.
+pub async fn broadcast_to_cluster(cluster: &crate::agent::ClusterState, cluster_secret: &str) {
+    let cfg = config();
+    let nodes = cluster.get_all_nodes();
+    let self_cluster = nodes.iter().find(|n| n.is_self)
+        .and_then(|n| n.cluster_name.clone())
+        .unwrap_or_else(|| "WolfStack".to_string());
+    for node in nodes.iter().filter(|n| !n.is_self && n.online && n.node_type == "wolfstack") {
+        if node.cluster_name.as_deref().unwrap_or("WolfStack") != self_cluster {
+            continue;
+        }
+        let mut sent = false;
+        for url in crate::api::build_node_urls(&node.address, node.port, "/api/webhooks/sync") {
+            if let Ok(resp) = crate::api::API_HTTP_CLIENT.post(&url)
+                .timeout(Duration::from_secs(10))
+                .header("X-WolfStack-Secret", cluster_secret)
+                .json(&cfg)
+                .send().await
+            {
+                if resp.status().is_success() {
+                    sent = true;
+                    break;
+                }
+            }
+        }
+        if !sent {
+            warn!("Webhooks: failed to sync config to {}", node.hostname);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(events: &[&str]) -> Webhook {
+        Webhook {
+            id: "w1".into(), name: "ci".into(), url: "https://example.com/hook".into(), secret: "s3cret".into(),
+            events: events.iter().map(|e| e.to_string()).collect(), enabled: true, created: String::new(),
+        }
+    }
+
+    #[test]
+    fn signature_matches_reference_hmac() {
+        // echo -n '1700000000.{"a":1}' | openssl dgst -sha256 -hmac s3cret
+        assert_eq!(
+            signature("s3cret", 1_700_000_000, br#"{"a":1}"#),
+            "sha256=1698a50bc74d1ff1db85c4e0a5297c2ad9fdba245d5737cdb789e4cc6e098940"
+        );
+        assert_ne!(signature("s3cret", 1, b"x"), signature("s3cret", 2, b"x"), "timestamp is signed");
+        assert_ne!(signature("a", 1, b"x"), signature("b", 1, b"x"));
+    }
+
+    #[test]
+    fn event_filters() {
+        assert!(hook(&[]).wants("node_offline"));
+        assert!(hook(&["backup_failed"]).wants("backup_failed"));
+        assert!(!hook(&["backup_failed"]).wants("node_offline"));
+        assert!(hook(&["backup_failed"]).wants("ping"));
+        let mut off = hook(&[]);
+        off.enabled = false;
+        assert!(!off.wants("node_offline"));
+    }
+
+    #[test]
+    fn urls_and_events_are_validated() {
+        assert!(validate_url("https://hooks.example.com/x").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("not a url").is_err());
+        assert!(validate_events(&["node_online".into()]).is_ok());
+        assert!(validate_events(&["ping".into()]).is_err());
+        assert_eq!(generate_secret().len(), 64);
+    }
+}
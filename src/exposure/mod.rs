@@ -318,6 +318,9 @@ pub fn build_proxy(
             url: upstream_url.to_string(),
             weight: 1,
             max_conns: 0,
+            max_fails: 0,
+            fail_timeout_s: 0,
+            source: None,
         }],
         lb_strategy: Default::default(),
         tls,
@@ -374,7 +377,7 @@ pub fn reconcile_upstreams(
             continue;
         };
         if p.upstreams.len() != 1 || p.upstreams[0].url != url {
-            p.upstreams = vec![Upstream { url, weight: 1, max_conns: 0 }];
+            p.upstreams = vec![Upstream { url, weight: 1, max_conns: 0, max_fails: 0, fail_timeout_s: 0, source: None }];
             changed = true;
         }
     }
@@ -0,0 +1,183 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Helpers for the host file manager's bulk transfers: folder archives
+//! streamed on the fly, chunked/resumable uploads, and the size guard
+//! shared by the text editor's read and write endpoints.
+//!
+//! Single-file downloads don't need anything here — `NamedFile` already
+//! answers `Range` requests, so a browser or `curl -C -` can resume them.
+//!
+//! Chunked uploads write into a hidden `.<name>.wolfstack-part` file next
+//! to the destination. Each chunk names the byte offset it starts at; a
+//! chunk whose offset doesn't match the part file's length is refused
+//! with the current length, so a client that lost its connection asks
+//! for the status, seeks, and carries on. The part is renamed into place
+//! only once the declared total has arrived, so a half-uploaded ISO
+//! never appears under its real name, and never over an existing file
+//! unless the client asked to overwrite it. Chunks of one upload are
+//! written one at a time (see [`UploadLock`]).
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Largest file the in-browser editor will load or save.
+pub const MAX_EDIT_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Largest single chunk accepted by the chunked upload endpoint. The UI
+/// sends 8 MiB; the headroom covers other clients.
+pub const MAX_CHUNK_BYTES: usize = 64 * 1024 * 1024;
+
+const PART_SUFFIX: &str = ".wolfstack-part";
+
+/// The bare file name from an upload request — rejects anything that
+/// isn't a single path component.
+pub fn upload_file_name(name: &str) -> Result<&str, String> {
+    let n = name.trim();
+    if n.is_empty() || n == "." || n == ".." || n.contains('/') || n.contains('\0') {
+        return Err(format!("Invalid file name '{}'", name));
+    }
+    Ok(n)
+}
+
+/// Partial-upload path for `name` inside `dir`.
+pub fn part_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let n = upload_file_name(name)?;
+    Ok(dir.join(format!(".{}{}", n, PART_SUFFIX)))
+}
+
+/// What to do with a chunk that starts at `offset`, given the part file
+/// currently holds `current` bytes and the upload totals `total`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChunkCheck {
+    /// Append the chunk.
+    Accept,
+    /// The offset doesn't line up — the client must resume at `current`.
+    Mismatch,
+    /// The chunk would run past the declared total.
+    TooLong,
+}
+
+pub fn check_chunk(current: u64, offset: u64, chunk_len: u64, total: u64) -> ChunkCheck {
+    if offset != current {
+        ChunkCheck::Mismatch
+    } else if offset.saturating_add(chunk_len) > total {
+        ChunkCheck::TooLong
+    } else {
+        ChunkCheck::Accept
+    }
+}
+
+/// Part files with a chunk being written right now.
+static UPLOADS_IN_FLIGHT: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Held while one chunk of an upload is checked and appended, so two
+/// requests carrying the same offset can't both pass the offset check
+/// and append twice. Released on drop.
+pub struct UploadLock(PathBuf);
+
+impl UploadLock {
+    /// `None` when another chunk of the same upload is in progress.
+    pub fn acquire(part: &Path) -> Option<UploadLock> {
+        let mut set = UPLOADS_IN_FLIGHT.lock().unwrap();
+        set.insert(part.to_path_buf()).then(|| UploadLock(part.to_path_buf()))
+    }
+}
+
+impl Drop for UploadLock {
+    fn drop(&mut self) {
+        UPLOADS_IN_FLIGHT.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// A streaming archive of one directory.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArchiveCommand {
+    pub program: &'static str,
+    pub args: Vec<String>,
+    /// Run from the directory's parent so the archive holds `name/...`
+    /// rather than the full absolute path.
+    pub cwd: PathBuf,
+    pub filename: String,
+    pub content_type: &'static str,
+}
+
+/// Command that writes an archive of `dir` to stdout. `format` is `zip`
+/// or `tar.gz` (the default, since tar is on every host and zip isn't).
+pub fn archive_command(dir: &Path, format: &str) -> Result<ArchiveCommand, String> {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Cannot archive the filesystem root")?;
+    let cwd = dir.parent().ok_or("Cannot archive the filesystem root")?.to_path_buf();
+    match format {
+        "zip" => Ok(ArchiveCommand {
+            program: "zip",
+            // -r recurse, -q quiet, -y store symlinks as links (don't
+            // follow them out of the folder), "-" = stdout.
+            args: vec!["-r".into(), "-q".into(), "-y".into(), "-".into(), "--".into(), name.clone()],
+            cwd,
+            filename: format!("{}.zip", name),
+            content_type: "application/zip",
+        }),
+        "" | "tar.gz" | "tgz" => Ok(ArchiveCommand {
+            program: "tar",
+            args: vec!["-czf".into(), "-".into(), "--".into(), name.clone()],
+            cwd,
+            filename: format!("{}.tar.gz", name),
+            content_type: "application/gzip",
+        }),
+        other => Err(format!("Unsupported archive format '{}' (use zip or tar.gz)", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_names_are_single_components() {
+        assert_eq!(upload_file_name(" debian.iso "), Ok("debian.iso"));
+        assert!(upload_file_name("../etc/passwd").is_err());
+        assert!(upload_file_name("..").is_err());
+        assert!(upload_file_name("").is_err());
+        assert_eq!(
+            part_path(Path::new("/srv/iso"), "debian.iso").unwrap(),
+            PathBuf::from("/srv/iso/.debian.iso.wolfstack-part")
+        );
+    }
+
+    #[test]
+    fn chunks_must_line_up_and_fit() {
+        assert_eq!(check_chunk(0, 0, 10, 30), ChunkCheck::Accept);
+        assert_eq!(check_chunk(20, 20, 10, 30), ChunkCheck::Accept);
+        assert_eq!(check_chunk(10, 0, 10, 30), ChunkCheck::Mismatch);
+        assert_eq!(check_chunk(10, 20, 10, 30), ChunkCheck::Mismatch);
+        assert_eq!(check_chunk(20, 20, 11, 30), ChunkCheck::TooLong);
+    }
+
+    #[test]
+    fn one_chunk_at_a_time_per_upload() {
+        let part = Path::new("/srv/iso/.lock-test.iso.wolfstack-part");
+        let held = UploadLock::acquire(part).unwrap();
+        assert!(UploadLock::acquire(part).is_none());
+        assert!(UploadLock::acquire(Path::new("/srv/iso/.other.wolfstack-part")).is_some());
+        drop(held);
+        assert!(UploadLock::acquire(part).is_some());
+    }
+
+    #[test]
+    fn archives_run_from_the_parent() {
+        let zip = archive_command(Path::new("/srv/data/photos"), "zip").unwrap();
+        assert_eq!(zip.cwd, PathBuf::from("/srv/data"));
+        assert_eq!(zip.args.last().unwrap(), "photos");
+        assert_eq!(zip.filename, "photos.zip");
+        let tgz = archive_command(Path::new("/srv/data/photos"), "").unwrap();
+        assert_eq!(tgz.program, "tar");
+        assert_eq!(tgz.filename, "photos.tar.gz");
+        assert!(archive_command(Path::new("/"), "zip").is_err());
+        assert!(archive_command(Path::new("/srv"), "rar").is_err());
+    }
+}
@@ -0,0 +1,482 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Disk housekeeping — the cleanup jobs a node needs every so often,
+//! runnable on demand or once a day on a schedule:
+//!
+//! - **Docker images**: dangling layers left behind by rebuilds and pulls.
+//! - **Docker volumes**: anonymous volumes no container uses any more.
+//!   Named volumes are never touched — they're someone's data.
+//! - **Journal**: vacuum the systemd journal down to a size cap.
+//! - **Temp files**: leftover import/export/build archives under /tmp
+//!   older than a cut-off (a failed import or migration leaves them).
+//! - **Backups**: archives beyond their schedule's retention.
+//!
+//! Every run produces a [`Report`] of what was (or, in a dry run, would
+//! be) removed and the bytes involved; the latest one is kept in
+//! `housekeeping-report.json` for the UI. Settings live in
+//! `housekeeping.json` next to the other per-node config.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::containers::docker_api;
+
+/// Directories whose contents are WolfStack's own scratch space.
+const TEMP_DIRS: &[&str] = &["/tmp/wolfstack-imports", "/tmp/wolfstack-exports"];
+/// Files directly in /tmp with these prefixes are ours too — uploaded
+/// Docker/VM imports and build contexts.
+const TEMP_PREFIXES: &[&str] = &["wolfstack-import-", "wolfstack-build-"];
+
+fn default_hour() -> u32 { 3 }
+fn default_true() -> bool { true }
+fn default_journal_max_mb() -> u64 { 500 }
+fn default_temp_max_age_hours() -> u64 { 24 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Run once a day on the schedule below.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hour of the day (0–23, server time) the scheduled run happens at.
+    #[serde(default = "default_hour")]
+    pub hour: u32,
+    #[serde(default = "default_true")]
+    pub docker_images: bool,
+    #[serde(default)]
+    pub docker_volumes: bool,
+    #[serde(default = "default_true")]
+    pub journal: bool,
+    /// Size the journal is vacuumed down to.
+    #[serde(default = "default_journal_max_mb")]
+    pub journal_max_mb: u64,
+    #[serde(default = "default_true")]
+    pub temp_files: bool,
+    /// Temp files younger than this are left alone — they may belong to an
+    /// import that's still running.
+    #[serde(default = "default_temp_max_age_hours")]
+    pub temp_max_age_hours: u64,
+    #[serde(default = "default_true")]
+    pub backups: bool,
+    /// `YYYY-MM-DD` of the last scheduled run.
+    #[serde(default)]
+    pub last_run: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: default_hour(),
+            docker_images: true,
+            docker_volumes: false,
+            journal: true,
+            journal_max_mb: default_journal_max_mb(),
+            temp_files: true,
+            temp_max_age_hours: default_temp_max_age_hours(),
+            backups: true,
+            last_run: String::new(),
+        }
+    }
+}
+
+fn config_path() -> String {
+    format!("{}/housekeeping.json", crate::paths::get().config_dir)
+}
+
+fn report_path() -> String {
+    format!("{}/housekeeping-report.json", crate::paths::get().config_dir)
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+impl Config {
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        write_json(&config_path(), self)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err("hour must be 0-23".into());
+        }
+        if self.journal_max_mb < 50 {
+            return Err("journal_max_mb must be at least 50".into());
+        }
+        if self.temp_max_age_hours == 0 {
+            return Err("temp_max_age_hours must be at least 1".into());
+        }
+        Ok(())
+    }
+
+    /// The `YYYY-MM-DD` to run for, if the scheduled run is due: at or past
+    /// the configured hour and not already run today.
+    pub fn due(&self, now: chrono::DateTime<chrono::Local>) -> Option<String> {
+        use chrono::Timelike;
+        let today = now.format("%Y-%m-%d").to_string();
+        (self.enabled && now.hour() >= self.hour && self.last_run != today).then_some(today)
+    }
+}
+
+/// One thing removed (or that would be).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskReport {
+    pub task: String,
+    pub items: Vec<Item>,
+    pub bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TaskReport {
+    fn new(task: &str, result: Result<Vec<Item>, String>) -> Self {
+        match result {
+            Ok(items) => Self { task: task.into(), bytes: items.iter().map(|i| i.bytes).sum(), items, error: None },
+            Err(e) => Self { task: task.into(), items: Vec::new(), bytes: 0, error: Some(e) },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub dry_run: bool,
+    /// "manual" or "schedule".
+    pub trigger: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub tasks: Vec<TaskReport>,
+    pub total_bytes: u64,
+}
+
+/// The last run's report, if there has been one.
+pub fn last_report() -> Option<Report> {
+    std::fs::read_to_string(report_path()).ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+}
+
+/// Run every enabled task. With `dry_run` nothing is removed and the
+/// report lists what would be.
+pub fn run(config: &Config, dry_run: bool, trigger: &str) -> Report {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let mut tasks = Vec::new();
+    if config.docker_images {
+        tasks.push(TaskReport::new("docker_images", docker_images(dry_run)));
+    }
+    if config.docker_volumes {
+        tasks.push(TaskReport::new("docker_volumes", docker_volumes(dry_run)));
+    }
+    if config.journal {
+        tasks.push(TaskReport::new("journal", journal(config.journal_max_mb, dry_run)));
+    }
+    if config.temp_files {
+        tasks.push(TaskReport::new("temp_files", temp_files(config.temp_max_age_hours, dry_run)));
+    }
+    if config.backups {
+        let result = crate::backup::enforce_retention(dry_run)
+            .map(|v| v.into_iter().map(|(name, bytes)| Item { name, bytes }).collect());
+        tasks.push(TaskReport::new("backups", result));
+    }
+    let report = Report {
+        dry_run,
+        trigger: trigger.into(),
+        started_at,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+        total_bytes: tasks.iter().map(|t| t.bytes).sum(),
+        tasks,
+    };
+    if let Err(e) = write_json(&report_path(), &report) {
+        tracing::warn!("housekeeping: {}", e);
+    }
+    report
+}
+
+/// The scheduled run, if due. Called from the background loop.
+pub fn check_schedule() {
+    let config = Config::load();
+    let Some(today) = config.due(chrono::Local::now()) else { return };
+    let report = run(&config, false, "schedule");
+    tracing::info!("Housekeeping freed {} ({} task(s))",
+        docker_api::human_size(report.total_bytes), report.tasks.len());
+    // Re-load so a settings change made during the run isn't lost.
+    let mut config = Config::load();
+    config.last_run = today;
+    if let Err(e) = config.save() {
+        tracing::warn!("Failed to record housekeeping run: {}", e);
+    }
+}
+
+// ─── Docker ───
+
+fn dangling_filter() -> String {
+    urlencoding::encode(r#"{"dangling":["true"]}"#).into_owned()
+}
+
+fn docker_cli(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("timeout").arg("120").arg("docker").args(args).output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+fn docker_installed() -> bool {
+    Command::new("docker").arg("--version").output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// Dangling images — what `docker image prune` (without `-a`) removes.
+fn docker_images(dry_run: bool) -> Result<Vec<Item>, String> {
+    if !docker_installed() {
+        return Ok(Vec::new());
+    }
+    let items: Vec<Item> = match docker_api::get_json(&format!("/images/json?filters={}", dangling_filter())) {
+        Ok(v) => v.as_array().into_iter().flatten().map(|img| Item {
+            name: img.get("Id").and_then(|x| x.as_str()).unwrap_or("")
+                .trim_start_matches("sha256:").chars().take(12).collect(),
+            bytes: img.get("Size").and_then(|x| x.as_u64()).unwrap_or(0),
+        }).collect(),
+        Err(docker_api::ApiError::Docker(e)) => return Err(e),
+        // The CLI has no byte sizes to offer; list the IDs alone.
+        Err(docker_api::ApiError::Unavailable(_)) => docker_cli(&["images", "-q", "--no-trunc", "-f", "dangling=true"])?
+            .split_whitespace()
+            .map(|id| Item { name: id.trim_start_matches("sha256:").chars().take(12).collect(), bytes: 0 })
+            .collect(),
+    };
+    if !dry_run && !items.is_empty() {
+        crate::containers::docker_prune_images(false)?;
+    }
+    Ok(items)
+}
+
+/// Volumes Docker created for a container's anonymous mounts have a 64-hex
+/// name; newer engines also label them.
+fn is_anonymous_volume(name: &str, labels: Option<&serde_json::Value>) -> bool {
+    labels.and_then(|l| l.get("com.docker.volume.anonymous")).is_some()
+        || (name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Anonymous volumes no container references. Removed one by one rather
+/// than with `docker volume prune`, whose reach differs between engine
+/// versions (older ones take named volumes too).
+fn docker_volumes(dry_run: bool) -> Result<Vec<Item>, String> {
+    if !docker_installed() {
+        return Ok(Vec::new());
+    }
+    let items: Vec<Item> = match docker_api::get_json(&format!("/volumes?filters={}", dangling_filter())) {
+        Ok(v) => {
+            // Sizes come from `system df`, which is slow but the only place
+            // the engine reports them.
+            let sizes: std::collections::HashMap<String, u64> = docker_api::get_json("/system/df?type=volume").ok()
+                .and_then(|df| df.get("Volumes").and_then(|x| x.as_array()).cloned())
+                .into_iter().flatten()
+                .filter_map(|vol| Some((
+                    vol.get("Name")?.as_str()?.to_string(),
+                    vol.pointer("/UsageData/Size").and_then(|x| x.as_i64()).unwrap_or(0).max(0) as u64,
+                )))
+                .collect();
+            v.get("Volumes").and_then(|x| x.as_array()).into_iter().flatten()
+                .filter_map(|vol| {
+                    let name = vol.get("Name")?.as_str()?;
+                    is_anonymous_volume(name, vol.get("Labels")).then(|| Item {
+                        name: name.to_string(),
+                        bytes: sizes.get(name).copied().unwrap_or(0),
+                    })
+                })
+                .collect()
+        }
+        Err(docker_api::ApiError::Docker(e)) => return Err(e),
+        Err(docker_api::ApiError::Unavailable(_)) => docker_cli(&["volume", "ls", "-q", "-f", "dangling=true"])?
+            .split_whitespace()
+            .filter(|name| is_anonymous_volume(name, None))
+            .map(|name| Item { name: name.to_string(), bytes: 0 })
+            .collect(),
+    };
+    if dry_run {
+        return Ok(items);
+    }
+    let mut removed = Vec::new();
+    let mut failed = Vec::new();
+    for item in items {
+        match docker_cli(&["volume", "rm", &item.name]) {
+            Ok(_) => removed.push(item),
+            Err(e) => failed.push(format!("{}: {}", item.name, e)),
+        }
+    }
+    if !failed.is_empty() && removed.is_empty() {
+        return Err(failed.join("; "));
+    }
+    for f in &failed {
+        tracing::warn!("housekeeping: volume not removed — {}", f);
+    }
+    Ok(removed)
+}
+
+// ─── Journal ───
+
+/// Bytes from `journalctl --disk-usage` ("Archived and active journals
+/// take up 1.2G in the file system.").
+fn parse_journal_usage(out: &str) -> Option<u64> {
+    let size = out.split("take up ").nth(1)?.split_whitespace().next()?;
+    let split = size.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(size.len());
+    let num: f64 = size[..split].parse().ok()?;
+    let mult: f64 = match size[split..].to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" => 1024.0,
+        "M" => 1048576.0,
+        "G" => 1073741824.0,
+        "T" => 1099511627776.0,
+        _ => return None,
+    };
+    Some((num * mult) as u64)
+}
+
+fn journal_usage() -> Option<u64> {
+    let out = Command::new("journalctl").arg("--disk-usage").output().ok()?;
+    parse_journal_usage(&String::from_utf8_lossy(&out.stdout))
+}
+
+fn journal(max_mb: u64, dry_run: bool) -> Result<Vec<Item>, String> {
+    let Some(before) = journal_usage() else { return Ok(Vec::new()) };
+    let cap = max_mb * 1048576;
+    let name = format!("journal vacuumed to {} MB", max_mb);
+    if dry_run {
+        // journald only drops whole archived files, so this is an upper bound.
+        return Ok(if before > cap { vec![Item { name, bytes: before - cap }] } else { Vec::new() });
+    }
+    if before <= cap {
+        return Ok(Vec::new());
+    }
+    let out = Command::new("journalctl").arg(format!("--vacuum-size={}M", max_mb)).output()
+        .map_err(|e| format!("Failed to run journalctl: {}", e))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    let after = journal_usage().unwrap_or(before);
+    Ok(vec![Item { name, bytes: before.saturating_sub(after) }])
+}
+
+// ─── Temp files ───
+
+/// Size of a file, or of a directory's contents. Symlinks count as
+/// themselves and are never followed.
+fn disk_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else { return 0 };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path).into_iter().flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| disk_size(&e.path()))
+        .sum()
+}
+
+/// Our scratch files under `tmp` last modified more than `max_age` ago.
+fn stale_temp_paths(tmp: &Path, max_age: std::time::Duration) -> Vec<PathBuf> {
+    let now = std::time::SystemTime::now();
+    let old = |p: &Path| std::fs::symlink_metadata(p).and_then(|m| m.modified()).ok()
+        .and_then(|t| now.duration_since(t).ok())
+        .is_some_and(|age| age >= max_age);
+    let mut paths = Vec::new();
+    for dir in TEMP_DIRS {
+        let name = Path::new(dir).file_name().unwrap_or_default();
+        paths.extend(std::fs::read_dir(tmp.join(name)).into_iter().flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| old(p)));
+    }
+    paths.extend(std::fs::read_dir(tmp).into_iter().flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            TEMP_PREFIXES.iter().any(|p| name.starts_with(p)) && e.file_type().is_ok_and(|t| t.is_file())
+        })
+        .map(|e| e.path())
+        .filter(|p| old(p)));
+    paths.sort();
+    paths
+}
+
+fn temp_files(max_age_hours: u64, dry_run: bool) -> Result<Vec<Item>, String> {
+    let max_age = std::time::Duration::from_secs(max_age_hours * 3600);
+    let mut items = Vec::new();
+    for path in stale_temp_paths(Path::new("/tmp"), max_age) {
+        let bytes = disk_size(&path);
+        if !dry_run {
+            let is_dir = std::fs::symlink_metadata(&path).is_ok_and(|m| m.is_dir());
+            let result = if is_dir { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+            if let Err(e) = result {
+                tracing::warn!("housekeeping: failed to remove {}: {}", path.display(), e);
+                continue;
+            }
+        }
+        items.push(Item { name: path.display().to_string(), bytes });
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_usage_is_parsed() {
+        assert_eq!(parse_journal_usage("Archived and active journals take up 1.5G in the file system."), Some(1610612736));
+        assert_eq!(parse_journal_usage("Journals take up 512.0M on disk."), Some(536870912));
+        assert_eq!(parse_journal_usage("No journal files were found."), None);
+    }
+
+    #[test]
+    fn schedule_runs_once_a_day_after_the_hour() {
+        use chrono::TimeZone;
+        let at = |h| chrono::Local.with_ymd_and_hms(2026, 10, 16, h, 30, 0).unwrap();
+        let mut c = Config { enabled: true, hour: 3, ..Default::default() };
+        assert_eq!(c.due(at(2)), None);
+        assert_eq!(c.due(at(3)).as_deref(), Some("2026-10-16"));
+        c.last_run = "2026-10-16".into();
+        assert_eq!(c.due(at(23)), None);
+        c.enabled = false;
+        c.last_run.clear();
+        assert_eq!(c.due(at(4)), None);
+        assert!(Config { hour: 24, ..Default::default() }.validate().is_err());
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn only_stale_scratch_files_are_picked() {
+        let tmp = std::env::temp_dir().join(format!("wolfstack-hk-test-{}", std::process::id()));
+        let imports = tmp.join("wolfstack-imports");
+        std::fs::create_dir_all(imports.join("job")).unwrap();
+        std::fs::write(imports.join("job/rootfs.tar"), vec![0u8; 10]).unwrap();
+        std::fs::write(tmp.join("wolfstack-build-1.tar"), b"x").unwrap();
+        std::fs::write(tmp.join("unrelated.tar"), b"x").unwrap();
+
+        assert!(stale_temp_paths(&tmp, std::time::Duration::from_secs(3600)).is_empty());
+        let stale = stale_temp_paths(&tmp, std::time::Duration::ZERO);
+        let names: Vec<String> = stale.iter()
+            .map(|p| p.strip_prefix(&tmp).unwrap().display().to_string()).collect();
+        assert_eq!(names, vec!["wolfstack-build-1.tar", "wolfstack-imports/job"]);
+        assert_eq!(disk_size(&imports.join("job")), 10);
+        let _ = std::fs::remove_dir_all(&tmp);
+        assert!(is_anonymous_volume(&"a1".repeat(32), None));
+        assert!(!is_anonymous_volume("pgdata", None));
+    }
+}
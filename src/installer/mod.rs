@@ -5,8 +5,11 @@
 //! Installer — manages installation and status of Wolf suite components
 
 pub mod packages;
+pub mod rollout;
 pub mod self_signed;
 pub mod unraid_tools;
+pub mod upgrade;
+pub mod versions;
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -278,38 +281,54 @@ fn binary_exists(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-/// Get version of a component
+/// Get version of a component. Tries each binary the component may ship
+/// (`mysql` on older MariaDB installs, `postgres` where the client isn't
+/// installed), then whatever the service's unit actually runs — Wolf
+/// components installed under /opt aren't always on PATH.
 pub fn get_component_version(component: Component) -> Option<String> {
-    let (cmd, args): (&str, &[&str]) = match component {
-        Component::MariaDB => ("mariadb", &["--version"]),
-        Component::PostgreSQL => ("psql", &["--version"]),
-        Component::Certbot => ("certbot", &["--version"]),
-        Component::WolfNet => ("wolfnet", &["--version"]),
-        Component::WolfProxy => ("wolfproxy", &["--version"]),
-        Component::WolfServe => ("wolfserve", &["--version"]),
-        Component::WolfDisk => ("wolfdisk", &["--version"]),
-        Component::WolfScale => ("wolfscale", &["--version"]),
+    let candidates: &[&str] = match component {
+        Component::MariaDB => &["mariadb", "mysql", "mariadbd"],
+        Component::PostgreSQL => &["psql", "postgres"],
+        Component::Certbot => &["certbot"],
+        Component::WolfNet => &["wolfnet"],
+        Component::WolfProxy => &["wolfproxy"],
+        Component::WolfServe => &["wolfserve"],
+        Component::WolfDisk => &["wolfdisk"],
+        Component::WolfScale => &["wolfscale"],
     };
+    candidates.iter()
+        .find_map(|cmd| binary_version(cmd))
+        .or_else(|| unit_binary(component.service_name()).and_then(|bin| binary_version(&bin)))
+}
+
+fn binary_version(cmd: &str) -> Option<String> {
+    let out = Command::new(cmd).arg("--version").output().ok()?;
+    if !out.status.success() { return None; }
+    // Some tools (older certbot) print their version on stderr.
+    let text = if out.stdout.is_empty() { out.stderr } else { out.stdout };
+    version_token(&String::from_utf8_lossy(&text))
+}
 
-    Command::new(cmd)
-        .args(args)
+/// The absolute binary a systemd unit starts, if it has one.
+fn unit_binary(service: &str) -> Option<String> {
+    let out = Command::new("systemctl")
+        .args(["show", service, "-p", "ExecStart", "--value"])
         .output()
-        .ok()
-        .and_then(|o| {
-            if o.status.success() {
-                String::from_utf8(o.stdout).ok()
-                    .map(|s| {
-                        let trimmed = s.trim();
-                        // Extract just the version number (e.g. "wolfdisk 2.7.4" → "2.7.4")
-                        trimmed.split_whitespace()
-                            .find(|w| w.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false))
-                            .unwrap_or(trimmed)
-                            .to_string()
-                    })
-            } else {
-                None
-            }
-        })
+        .ok()?;
+    let show = String::from_utf8_lossy(&out.stdout);
+    let path = show.split("path=").nth(1)?.split_whitespace().next()?.to_string();
+    (path.starts_with('/') && std::path::Path::new(&path).is_file()).then_some(path)
+}
+
+/// The version number out of a `--version` line: "wolfdisk 2.7.4" → "2.7.4",
+/// "mariadb from 11.4.2-MariaDB, client 15.2" → "11.4.2", "v1.3.0" → "1.3.0".
+pub fn version_token(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .map(|w| w.trim_start_matches('v'))
+        .find(|w| w.chars().next().is_some_and(|c| c.is_ascii_digit()))
+        .map(|w| w.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect::<String>())
+        .map(|v| v.trim_end_matches('.').to_string())
+        .filter(|v| !v.is_empty())
 }
 
 /// Get status of all components
@@ -0,0 +1,230 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Install or update a component on several nodes at once.
+//!
+//! `POST /api/components/{name}/install` only touches the node it's sent
+//! to. A rollout fans that same request out to every selected node in
+//! parallel over the cluster channel (this node runs the installer
+//! directly), tracking each node as `pending` → `running` → `ok`/`failed`
+//! in an in-memory job the dashboard polls. Jobs are kept for an hour
+//! after they finish.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use super::Component;
+use crate::agent::Node;
+
+/// Installs pipe a setup script through bash and can compile from source
+/// on slow nodes; give each one half an hour before calling it failed.
+const INSTALL_TIMEOUT_SECS: u64 = 1800;
+const JOB_RETENTION_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeInstall {
+    pub node_id: String,
+    pub hostname: String,
+    /// `pending`, `running`, `ok` or `failed`.
+    pub status: String,
+    /// The installer's message on success, the error on failure.
+    pub message: String,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RolloutJob {
+    pub id: String,
+    /// Service name, e.g. `wolfdisk`.
+    pub component: String,
+    pub requested_by: String,
+    /// `running`, `done` (every node succeeded), `partial` or `failed`.
+    pub status: String,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub nodes: Vec<NodeInstall>,
+}
+
+impl RolloutJob {
+    /// Recount the nodes and settle the job once none are left to run.
+    fn tally(&mut self) {
+        self.succeeded = self.nodes.iter().filter(|n| n.status == "ok").count();
+        self.failed = self.nodes.iter().filter(|n| n.status == "failed").count();
+        if self.succeeded + self.failed < self.nodes.len() { return; }
+        self.status = match (self.succeeded, self.failed) {
+            (_, 0) => "done",
+            (0, _) => "failed",
+            _ => "partial",
+        }.to_string();
+        self.finished_at.get_or_insert_with(|| chrono::Utc::now().timestamp());
+    }
+}
+
+static JOBS: LazyLock<Mutex<HashMap<String, RolloutJob>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn update_node(job_id: &str, node_id: &str, f: impl FnOnce(&mut NodeInstall)) {
+    if let Ok(mut jobs) = JOBS.lock()
+        && let Some(job) = jobs.get_mut(job_id)
+    {
+        if let Some(n) = job.nodes.iter_mut().find(|n| n.node_id == node_id) {
+            f(n);
+        }
+        job.tally();
+    }
+}
+
+/// A rollout by id, if it hasn't expired.
+pub fn job(id: &str) -> Option<RolloutJob> {
+    JOBS.lock().ok()?.get(id).cloned()
+}
+
+/// Current and recent rollouts, newest first.
+pub fn jobs() -> Vec<RolloutJob> {
+    let now = chrono::Utc::now().timestamp();
+    let mut jobs = JOBS.lock().map(|mut m| {
+        m.retain(|_, j| j.finished_at.map(|t| now - t < JOB_RETENTION_SECS).unwrap_or(true));
+        m.values().cloned().collect::<Vec<_>>()
+    }).unwrap_or_default();
+    jobs.sort_by_key(|j| std::cmp::Reverse(j.started_at));
+    jobs
+}
+
+/// Register a rollout of `component` to `targets` and start installing on
+/// all of them. Refuses while another rollout of the same component is
+/// still running, so two setup scripts never race on one node.
+pub fn start(component: Component, targets: Vec<Node>, secret: String, requested_by: &str) -> Result<RolloutJob, String> {
+    if targets.is_empty() {
+        return Err("No WolfStack nodes selected".to_string());
+    }
+    let job = {
+        let mut jobs = JOBS.lock().map_err(|_| "Rollout registry poisoned".to_string())?;
+        if jobs.values().any(|j| j.component == component.service_name() && j.finished_at.is_none()) {
+            return Err(format!("{} is already being rolled out", component.name()));
+        }
+        let job = RolloutJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            component: component.service_name().to_string(),
+            requested_by: requested_by.to_string(),
+            status: "running".to_string(),
+            started_at: chrono::Utc::now().timestamp(),
+            finished_at: None,
+            succeeded: 0,
+            failed: 0,
+            nodes: targets.iter().map(|n| NodeInstall {
+                node_id: n.id.clone(),
+                hostname: n.display_name.clone().unwrap_or_else(|| n.hostname.clone()),
+                status: "pending".to_string(),
+                message: String::new(),
+                started_at: None,
+                finished_at: None,
+            }).collect(),
+        };
+        jobs.insert(job.id.clone(), job.clone());
+        job
+    };
+    for node in targets {
+        let (job_id, secret) = (job.id.clone(), secret.clone());
+        tokio::spawn(async move {
+            update_node(&job_id, &node.id, |n| {
+                n.status = "running".to_string();
+                n.started_at = Some(chrono::Utc::now().timestamp());
+            });
+            let result = install_on(&node, component, &secret).await;
+            update_node(&job_id, &node.id, |n| {
+                (n.status, n.message) = match result {
+                    Ok(msg) => ("ok".to_string(), msg),
+                    Err(e) => ("failed".to_string(), e),
+                };
+                n.finished_at = Some(chrono::Utc::now().timestamp());
+            });
+        });
+    }
+    Ok(job)
+}
+
+/// Run the install on one node: directly for ourselves, otherwise through
+/// the node's own install endpoint.
+async fn install_on(node: &Node, component: Component, secret: &str) -> Result<String, String> {
+    if node.is_self {
+        return tokio::task::spawn_blocking(move || super::install_component(component)).await
+            .map_err(|e| format!("install worker failed: {}", e))?;
+    }
+    if !node.online {
+        return Err("Node is offline".to_string());
+    }
+    let path = format!("/api/components/{}/install", component.service_name());
+    let mut last_err = String::new();
+    for url in crate::api::build_node_urls(&node.address, node.port, &path) {
+        match crate::api::API_HTTP_CLIENT.post(&url)
+            .timeout(Duration::from_secs(INSTALL_TIMEOUT_SECS))
+            .header("X-WolfStack-Secret", secret)
+            .json(&serde_json::json!({}))
+            .send().await
+        {
+            Ok(resp) => {
+                let ok = resp.status().is_success();
+                let data = resp.json::<serde_json::Value>().await.unwrap_or_default();
+                let field = |k: &str| data.get(k).and_then(|v| v.as_str()).map(str::to_string);
+                return if ok {
+                    Ok(field("message").unwrap_or_else(|| "Installed".to_string()))
+                } else {
+                    Err(field("error").unwrap_or_else(|| "Remote install failed".to_string()))
+                };
+            }
+            Err(e) => last_err = e.to_string(),
+        }
+    }
+    Err(format!("Node unreachable: {}", last_err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, status: &str) -> NodeInstall {
+        NodeInstall {
+            node_id: id.into(),
+            hostname: id.into(),
+            status: status.into(),
+            message: String::new(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn tally_settles_only_when_every_node_finishes() {
+        let mut job = RolloutJob {
+            id: "j".into(),
+            component: "wolfdisk".into(),
+            requested_by: "admin".into(),
+            status: "running".into(),
+            started_at: 0,
+            finished_at: None,
+            succeeded: 0,
+            failed: 0,
+            nodes: vec![node("a", "ok"), node("b", "running"), node("c", "failed")],
+        };
+        job.tally();
+        assert_eq!((job.status.as_str(), job.succeeded, job.failed), ("running", 1, 1));
+        assert!(job.finished_at.is_none());
+
+        job.nodes[1].status = "ok".into();
+        job.tally();
+        assert_eq!((job.status.as_str(), job.succeeded, job.failed), ("partial", 2, 1));
+        assert!(job.finished_at.is_some());
+
+        job.nodes[2].status = "ok".into();
+        job.tally();
+        assert_eq!(job.status, "done");
+        for n in &mut job.nodes { n.status = "failed".into(); }
+        job.tally();
+        assert_eq!(job.status, "failed");
+    }
+}
@@ -0,0 +1,587 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Blue/green upgrades for the Wolf components.
+//!
+//! The setup scripts replace a component's binary in place and restart it,
+//! so a bad release takes the service down until someone notices. This
+//! upgrades it in stages instead:
+//!
+//! 1. download the new binary next to the running one (`<bin>.green`),
+//!    check its SHA-256 when given, and make sure `--version` runs;
+//! 2. for components that can run twice on one host (WolfProxy,
+//!    WolfServe), start the new binary as a transient `<service>-green`
+//!    unit on a copy of the config with every listen port moved to a free
+//!    one and every `*_dir` pointed at a scratch directory, and check it
+//!    stays up and opens those ports. WolfNet (TUN device), WolfDisk
+//!    (FUSE mount) and WolfScale (it would join its own cluster twice)
+//!    stop at the `--version` check;
+//! 3. swap: stop the service, keep the old binary as `<bin>.blue`, move
+//!    the new one into place and start the service;
+//! 4. watch it for the soak period — still active, no automatic restarts,
+//!    listen ports open — and put `<bin>.blue` back if it isn't healthy.
+//!
+//! `<bin>.blue` stays after a good upgrade so it can be rolled back by
+//! hand later. Each upgrade is recorded in `component-upgrades.json`.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::Component;
+
+const KEEP_HISTORY: usize = 30;
+/// Sections whose ports are somewhere the component connects *to* — moving
+/// those would point the green instance at nothing.
+const OUTBOUND_SECTIONS: &[&str] = &[
+    "database", "db", "mysql", "mariadb", "postgres", "postgresql", "redis",
+    "upstream", "upstreams", "backend", "backends", "peer", "peers",
+    "smtp", "remote", "target", "targets",
+];
+
+static UPGRADE_LOCK: Mutex<()> = Mutex::new(());
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+fn history_file() -> String { crate::paths::get().component_upgrades }
+
+fn default_soak() -> u64 { 20 }
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpgradeRequest {
+    /// Where to fetch the new binary. Blank uses the component's release
+    /// build when one is published.
+    #[serde(default)]
+    pub binary_url: String,
+    #[serde(default)]
+    pub sha256: String,
+    /// Seconds each instance must stay healthy.
+    #[serde(default = "default_soak")]
+    pub soak_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeStep {
+    pub name: String,
+    pub ok: bool,
+    #[serde(default)]
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpgradeReport {
+    pub component: String,
+    pub ts: u64,
+    #[serde(default)]
+    pub from_version: String,
+    #[serde(default)]
+    pub to_version: String,
+    /// `upgraded`, `aborted` (nothing changed), `rolled_back` or
+    /// `rollback_failed`.
+    pub outcome: String,
+    #[serde(default)]
+    pub steps: Vec<UpgradeStep>,
+}
+
+impl UpgradeReport {
+    fn step(&mut self, name: &str, ok: bool, detail: impl Into<String>) -> bool {
+        let detail = detail.into();
+        info!("Upgrade {}: {} {} {}", self.component, name, if ok { "ok" } else { "FAILED" }, detail);
+        self.steps.push(UpgradeStep { name: name.to_string(), ok, detail });
+        ok
+    }
+}
+
+/// Whether the component can run a second copy beside the live one.
+pub fn runs_in_parallel(component: Component) -> bool {
+    matches!(component, Component::WolfProxy | Component::WolfServe)
+}
+
+/// The published release build for this host's architecture, if there is one.
+pub fn default_binary_url(component: Component) -> Option<String> {
+    match component {
+        Component::WolfScale => Some(format!("{}/wolfscale-{}", crate::wolfscale::WS_BINARY_BASE, std::env::consts::ARCH)),
+        _ => None,
+    }
+}
+
+pub fn history(component: Component) -> Vec<UpgradeReport> {
+    load_history().into_iter().filter(|r| r.component == component.service_name()).collect()
+}
+
+fn load_history() -> Vec<UpgradeReport> {
+    std::fs::read_to_string(history_file())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record(report: &UpgradeReport) {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all = load_history();
+    all.insert(0, report.clone());
+    all.truncate(KEEP_HISTORY);
+    let path = history_file();
+    if let Some(dir) = Path::new(&path).parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&all)
+        && let Err(e) = std::fs::write(&path, json) {
+        warn!("Could not write {}: {}", path, e);
+    }
+}
+
+/// `argv[]=` out of `systemctl show -p ExecStart --value`.
+fn parse_exec_start(show: &str) -> Vec<String> {
+    let Some(start) = show.find("argv[]=") else { return Vec::new() };
+    let rest = &show[start + "argv[]=".len()..];
+    let end = rest.find(" ;").unwrap_or(rest.len());
+    rest[..end].split_whitespace().map(str::to_string).collect()
+}
+
+fn unit_prop(unit: &str, prop: &str) -> String {
+    Command::new("systemctl")
+        .args(["show", unit, "-p", prop, "--value"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Processes left in a unit's cgroup — a daemon that forks leaves none in
+/// MainPID but still shows up here.
+fn unit_has_processes(unit: &str) -> bool {
+    let cg = unit_prop(unit, "ControlGroup");
+    if cg.is_empty() { return false; }
+    std::fs::read_to_string(format!("/sys/fs/cgroup{}/cgroup.procs", cg))
+        .map(|s| !s.trim().is_empty())
+        .unwrap_or(false)
+}
+
+fn port_open(port: u16) -> bool {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    std::net::TcpStream::connect_timeout(&addr, Duration::from_secs(2)).is_ok()
+}
+
+fn free_port() -> Option<u16> {
+    std::net::TcpListener::bind("0.0.0.0:0").ok()?.local_addr().ok().map(|a| a.port())
+}
+
+fn is_listen_key(key: &str) -> bool {
+    let k = key.to_ascii_lowercase();
+    k.contains("listen") || k.contains("bind")
+}
+
+fn is_port_key(key: &str) -> bool {
+    let k = key.to_ascii_lowercase();
+    k == "port" || k.ends_with("_port") || is_listen_key(&k)
+}
+
+fn swap_port(s: &str, alloc: &mut dyn FnMut(u16) -> Option<u16>, moved: &mut Vec<(u16, u16)>) -> Option<String> {
+    let (host, port) = s.rsplit_once(':')?;
+    let port: u16 = port.parse().ok()?;
+    let new = alloc(port)?;
+    moved.push((port, new));
+    Some(format!("{}:{}", host, new))
+}
+
+fn rewrite_table(
+    table: &mut toml::value::Table,
+    path: &[String],
+    scratch: &str,
+    alloc: &mut dyn FnMut(u16) -> Option<u16>,
+    moved: &mut Vec<(u16, u16)>,
+) {
+    let outbound = path.iter().any(|p| OUTBOUND_SECTIONS.contains(&p.to_ascii_lowercase().as_str()));
+    for (key, value) in table.iter_mut() {
+        match value {
+            toml::Value::Table(t) => {
+                let mut sub = path.to_vec();
+                sub.push(key.clone());
+                rewrite_table(t, &sub, scratch, alloc, moved);
+            }
+            toml::Value::Integer(n) if !outbound && is_port_key(key) && (1..=65535).contains(n) => {
+                if let Some(new) = alloc(*n as u16) {
+                    moved.push((*n as u16, new));
+                    *n = new as i64;
+                }
+            }
+            toml::Value::String(s) if !outbound && is_listen_key(key) => {
+                if let Some(new) = swap_port(s, alloc, moved) { *s = new; }
+            }
+            toml::Value::Array(items) if !outbound && is_listen_key(key) => {
+                for item in items.iter_mut() {
+                    if let toml::Value::String(s) = item
+                        && let Some(new) = swap_port(s, alloc, moved) {
+                        *s = new;
+                    }
+                }
+            }
+            toml::Value::String(s) if key.ends_with("_dir") || key == "pid_file" => {
+                *s = format!("{}/{}", scratch, key);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The config for the green instance: listen ports moved by `alloc`,
+/// directories and pid file under `scratch`. Returns the new text and the
+/// (old, new) port pairs.
+fn green_config(text: &str, scratch: &str, alloc: &mut dyn FnMut(u16) -> Option<u16>) -> Result<(String, Vec<(u16, u16)>), String> {
+    let mut doc: toml::value::Table = toml::from_str(text).map_err(|e| format!("config is not valid TOML: {}", e))?;
+    let mut moved = Vec::new();
+    rewrite_table(&mut doc, &[], scratch, alloc, &mut moved);
+    let out = toml::to_string(&doc).map_err(|e| e.to_string())?;
+    Ok((out, moved))
+}
+
+fn download(url: &str, dest: &str) -> Result<(), String> {
+    let out = Command::new("curl")
+        .args(["-fSL", "--connect-timeout", "15", "--max-time", "600", "-o", dest, url])
+        .output()
+        .map_err(|e| format!("failed to run curl: {}", e))?;
+    if !out.status.success() {
+        let _ = std::fs::remove_file(dest);
+        return Err(format!("download failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dest, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("chmod {}: {}", dest, e))?;
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &str) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(hex::encode(Sha256::digest(&data)))
+}
+
+fn binary_version(bin: &str) -> Result<String, String> {
+    let out = Command::new("timeout")
+        .args(["10", bin, "--version"])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(format!("exit {}: {}", out.status.code().unwrap_or(-1), String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    Ok(text.split_whitespace()
+        .find(|w| w.chars().next().is_some_and(|c| c.is_ascii_digit()))
+        .unwrap_or(&text)
+        .to_string())
+}
+
+fn systemctl(args: &[&str]) -> Result<(), String> {
+    let out = Command::new("systemctl").args(args).output().map_err(|e| e.to_string())?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn journal_tail(unit: &str) -> String {
+    Command::new("journalctl")
+        .args(["-u", unit, "--no-pager", "-n", "15", "-o", "cat"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Run the green binary beside the live service and check it comes up.
+fn trial_run(component: Component, argv: &[String], green: &str, soak: u64, report: &mut UpgradeReport) -> bool {
+    let service = component.service_name();
+    let Some(config_path) = component.config_path().filter(|p| Path::new(p).exists()) else {
+        return report.step("trial run", true, "no config file — skipped, --version check only");
+    };
+    let scratch = format!("{}/{}", crate::paths::get().component_upgrade_dir, service);
+    let _ = std::fs::remove_dir_all(&scratch);
+    if let Err(e) = std::fs::create_dir_all(&scratch) {
+        return report.step("trial run", false, format!("mkdir {}: {}", scratch, e));
+    }
+    let text = match std::fs::read_to_string(config_path) {
+        Ok(t) => t,
+        Err(e) => return report.step("trial run", false, format!("read {}: {}", config_path, e)),
+    };
+    let mut alloc = |_old: u16| free_port();
+    let (config, moved) = match green_config(&text, &scratch, &mut alloc) {
+        Ok(c) => c,
+        Err(e) => return report.step("trial run", false, e),
+    };
+    let file_name = Path::new(config_path).file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_else(|| "config.toml".into());
+    let green_config_path = format!("{}/{}", scratch, file_name);
+    if let Err(e) = std::fs::write(&green_config_path, config) {
+        return report.step("trial run", false, format!("write {}: {}", green_config_path, e));
+    }
+    // Same command line as the live unit, with the new binary and the
+    // copied config. Components that read their config from the working
+    // directory find the copy there.
+    let mut cmd: Vec<String> = argv.iter().map(|a| a.replace(config_path, &green_config_path)).collect();
+    cmd[0] = green.to_string();
+    let unit = format!("{}-green", service);
+    let _ = systemctl(&["stop", &unit]);
+    let mut run = Command::new("systemd-run");
+    run.arg(format!("--unit={}", unit))
+        .args(["--collect", "--property=RemainAfterExit=yes"])
+        .arg(format!("--working-directory={}", scratch))
+        .arg("--")
+        .args(&cmd);
+    match run.output() {
+        Ok(o) if o.status.success() => {}
+        Ok(o) => return report.step("trial run", false, format!("systemd-run: {}", String::from_utf8_lossy(&o.stderr).trim())),
+        Err(e) => return report.step("trial run", false, format!("systemd-run: {}", e)),
+    }
+    std::thread::sleep(Duration::from_secs(soak));
+    let alive = unit_has_processes(&unit);
+    let closed: Vec<String> = moved.iter().filter(|(_, new)| !port_open(*new)).map(|(old, new)| format!("{} (as {})", old, new)).collect();
+    let logs = journal_tail(&unit);
+    let _ = systemctl(&["stop", &unit]);
+    let _ = std::fs::remove_dir_all(&scratch);
+    let ports = moved.iter().map(|(old, new)| format!("{}→{}", old, new)).collect::<Vec<_>>().join(", ");
+    if !alive {
+        return report.step("trial run", false, format!("new binary exited within {}s: {}", soak, logs));
+    }
+    if !closed.is_empty() {
+        return report.step("trial run", false, format!("not listening on {}: {}", closed.join(", "), logs));
+    }
+    report.step("trial run", true, format!("ran {}s beside the live service{}", soak,
+        if ports.is_empty() { String::new() } else { format!(" on ports {}", ports) }))
+}
+
+/// Ports the live config listens on, for the post-swap check.
+fn live_ports(component: Component) -> Vec<u16> {
+    let Some(text) = component.config_path().and_then(|p| std::fs::read_to_string(p).ok()) else { return Vec::new() };
+    let mut alloc = |old: u16| Some(old);
+    green_config(&text, "", &mut alloc).map(|(_, moved)| moved.into_iter().map(|(p, _)| p).collect()).unwrap_or_default()
+}
+
+/// Active, no automatic restarts during the soak, and (for components
+/// that were trial-run) listening where the config says.
+fn healthy(component: Component, soak: u64) -> Result<(), String> {
+    let service = component.service_name();
+    let restarts_before = unit_prop(service, "NRestarts");
+    std::thread::sleep(Duration::from_secs(soak));
+    if unit_prop(service, "ActiveState") != "active" {
+        return Err(format!("service is {}: {}", unit_prop(service, "ActiveState"), journal_tail(service)));
+    }
+    if unit_prop(service, "NRestarts") != restarts_before {
+        return Err(format!("service restarted during the {}s soak: {}", soak, journal_tail(service)));
+    }
+    if runs_in_parallel(component) {
+        let closed: Vec<String> = live_ports(component).into_iter().filter(|p| !port_open(*p)).map(|p| p.to_string()).collect();
+        if !closed.is_empty() {
+            return Err(format!("not listening on {}", closed.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+/// Put `<bin>.blue` back and start the service on it.
+fn restore_blue(service: &str, bin: &str) -> Result<(), String> {
+    let blue = format!("{}.blue", bin);
+    if !Path::new(&blue).exists() {
+        return Err(format!("{} is missing", blue));
+    }
+    let _ = systemctl(&["stop", service]);
+    std::fs::copy(&blue, bin).map_err(|e| format!("restore {}: {}", bin, e))?;
+    systemctl(&["start", service])?;
+    std::thread::sleep(Duration::from_secs(5));
+    match unit_prop(service, "ActiveState").as_str() {
+        "active" => Ok(()),
+        other => Err(format!("service is {} after restoring", other)),
+    }
+}
+
+fn service_binary(service: &str) -> Result<(Vec<String>, String), String> {
+    let argv = parse_exec_start(&unit_prop(service, "ExecStart"));
+    let bin = argv.first().cloned().ok_or_else(|| format!("{}.service has no ExecStart", service))?;
+    if !bin.starts_with('/') || !Path::new(&bin).is_file() {
+        return Err(format!("{}.service runs {}, which isn't a binary WolfStack can swap", service, bin));
+    }
+    Ok((argv, bin))
+}
+
+/// Blue/green upgrade of a Wolf component. `Err` means nothing was
+/// attempted; anything after the download comes back as a report.
+pub fn upgrade(component: Component, req: UpgradeRequest) -> Result<UpgradeReport, String> {
+    if !matches!(component, Component::WolfNet | Component::WolfProxy | Component::WolfServe | Component::WolfDisk | Component::WolfScale) {
+        return Err(format!("{} is upgraded through the system package manager", component.name()));
+    }
+    let Ok(_guard) = UPGRADE_LOCK.try_lock() else {
+        return Err("Another component upgrade is already running".into());
+    };
+    let url = if req.binary_url.trim().is_empty() {
+        default_binary_url(component).ok_or_else(|| format!("No release build is published for {} — give the binary URL", component.name()))?
+    } else {
+        req.binary_url.trim().to_string()
+    };
+    if !url.starts_with("https://") {
+        return Err("binary_url must be an https:// URL".into());
+    }
+    let sha = req.sha256.trim().to_ascii_lowercase();
+    if !sha.is_empty() && (sha.len() != 64 || !sha.chars().all(|c| c.is_ascii_hexdigit())) {
+        return Err("sha256 must be 64 hex characters".into());
+    }
+    let soak = req.soak_secs.clamp(5, 300);
+    let service = component.service_name();
+    let (argv, bin) = service_binary(service)?;
+
+    let mut report = UpgradeReport {
+        component: service.to_string(),
+        ts: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        from_version: super::get_component_version(component).unwrap_or_default(),
+        outcome: "aborted".into(),
+        ..Default::default()
+    };
+    let green = format!("{}.green", bin);
+    let finish = |report: UpgradeReport| { record(&report); Ok(report) };
+
+    if let Err(e) = download(&url, &green) {
+        report.step("download", false, format!("{}: {}", url, e));
+        return finish(report);
+    }
+    report.step("download", true, url);
+    if !sha.is_empty() {
+        match sha256_file(&green) {
+            Ok(got) if got == sha => { report.step("checksum", true, "SHA-256 matches"); }
+            Ok(got) => {
+                report.step("checksum", false, format!("expected {}, got {}", sha, got));
+                let _ = std::fs::remove_file(&green);
+                return finish(report);
+            }
+            Err(e) => {
+                report.step("checksum", false, e);
+                let _ = std::fs::remove_file(&green);
+                return finish(report);
+            }
+        }
+    }
+    match binary_version(&green) {
+        Ok(v) => {
+            report.step("version", true, format!("new binary reports {}", v));
+            report.to_version = v;
+        }
+        Err(e) => {
+            report.step("version", false, format!("new binary won't run: {}", e));
+            let _ = std::fs::remove_file(&green);
+            return finish(report);
+        }
+    }
+    if runs_in_parallel(component) && !trial_run(component, &argv, &green, soak, &mut report) {
+        let _ = std::fs::remove_file(&green);
+        return finish(report);
+    }
+
+    // Swap.
+    if let Err(e) = std::fs::copy(&bin, format!("{}.blue", bin)) {
+        report.step("swap", false, format!("could not keep the old binary: {}", e));
+        let _ = std::fs::remove_file(&green);
+        return finish(report);
+    }
+    let _ = systemctl(&["stop", service]);
+    if let Err(e) = std::fs::rename(&green, &bin) {
+        report.step("swap", false, format!("could not move the new binary into place: {}", e));
+        let _ = systemctl(&["start", service]);
+        return finish(report);
+    }
+    if let Err(e) = systemctl(&["start", service]) {
+        report.step("swap", false, format!("start failed: {}", e));
+    } else {
+        report.step("swap", true, format!("{} now runs the new binary; the old one is kept as {}.blue", service, bin));
+        match healthy(component, soak) {
+            Ok(()) => {
+                report.step("health check", true, format!("healthy for {}s", soak));
+                report.outcome = "upgraded".into();
+                return finish(report);
+            }
+            Err(e) => { report.step("health check", false, e); }
+        }
+    }
+    match restore_blue(service, &bin) {
+        Ok(()) => {
+            report.step("rollback", true, format!("restored {}", report.from_version));
+            report.outcome = "rolled_back".into();
+        }
+        Err(e) => {
+            report.step("rollback", false, e);
+            report.outcome = "rollback_failed".into();
+        }
+    }
+    finish(report)
+}
+
+/// Go back to the binary kept by the last upgrade.
+pub fn rollback(component: Component) -> Result<UpgradeReport, String> {
+    let Ok(_guard) = UPGRADE_LOCK.try_lock() else {
+        return Err("A component upgrade is running".into());
+    };
+    let service = component.service_name();
+    let (_, bin) = service_binary(service)?;
+    if !Path::new(&format!("{}.blue", bin)).exists() {
+        return Err(format!("No previous {} binary is kept on this node", component.name()));
+    }
+    let mut report = UpgradeReport {
+        component: service.to_string(),
+        ts: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        from_version: super::get_component_version(component).unwrap_or_default(),
+        outcome: "rolled_back".into(),
+        ..Default::default()
+    };
+    if let Err(e) = restore_blue(service, &bin) {
+        report.step("rollback", false, e);
+        report.outcome = "rollback_failed".into();
+    } else {
+        report.to_version = super::get_component_version(component).unwrap_or_default();
+        report.step("rollback", true, format!("{} runs the previous binary again", service));
+    }
+    record(&report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_start_argv() {
+        let show = "{ path=/usr/local/bin/wolfproxy ; argv[]=/usr/local/bin/wolfproxy --config /opt/wolfproxy/wolfproxy.toml ; ignore_errors=no ; start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }";
+        assert_eq!(parse_exec_start(show), vec!["/usr/local/bin/wolfproxy", "--config", "/opt/wolfproxy/wolfproxy.toml"]);
+        assert!(parse_exec_start("").is_empty());
+    }
+
+    #[test]
+    fn green_config_moves_listen_ports_only() {
+        let text = r#"
+port = 80
+[server]
+listen = "0.0.0.0:443"
+https_port = 8443
+data_dir = "/var/lib/wolfserve"
+[database]
+host = "127.0.0.1"
+port = 3306
+[upstreams.app]
+bind_address = "10.0.0.5:9000"
+"#;
+        let mut next = 20000;
+        let mut alloc = |_old: u16| { next += 1; Some(next) };
+        let (out, moved) = green_config(text, "/tmp/green", &mut alloc).unwrap();
+        let doc: toml::Value = toml::from_str(&out).unwrap();
+        let mut olds: Vec<u16> = moved.iter().map(|(o, _)| *o).collect();
+        olds.sort();
+        assert_eq!(olds, vec![80, 443, 8443]);
+        assert!(doc["port"].as_integer().unwrap() > 20000);
+        assert!(doc["server"]["listen"].as_str().unwrap().starts_with("0.0.0.0:200"));
+        assert_eq!(doc["server"]["data_dir"].as_str(), Some("/tmp/green/data_dir"));
+        assert_eq!(doc["database"]["port"].as_integer(), Some(3306));
+        assert_eq!(doc["upstreams"]["app"]["bind_address"].as_str(), Some("10.0.0.5:9000"));
+    }
+
+    #[test]
+    fn bad_config_is_reported() {
+        let mut alloc = |p: u16| Some(p);
+        assert!(green_config("port = ", "/tmp/x", &mut alloc).is_err());
+    }
+}
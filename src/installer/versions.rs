@@ -0,0 +1,163 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Component version matrix across the cluster.
+//!
+//! Every node already gossips its `ComponentStatus` list (with versions)
+//! in its status report. Once a minute this snapshots the versions seen on
+//! the nodes of our own cluster; the matrix endpoint serves the snapshot
+//! and the `component_versions` issue check compares it against
+//! [`COMPAT_RULES`]. WolfNet and WolfDisk speak a wire protocol to their
+//! peers, so nodes on different release lines (major.minor) can't talk
+//! to each other reliably — that's raised as a warning on every node.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use super::Component;
+use crate::api::Issue;
+
+const REFRESH_SECS: u64 = 60;
+
+/// Components whose nodes must run the same release line, and why.
+pub const COMPAT_RULES: &[(Component, &str)] = &[
+    (Component::WolfNet, "WolfNet peers on different release lines can fail the handshake and drop off the mesh"),
+    (Component::WolfDisk, "WolfDisk only replicates between nodes on the same release line"),
+];
+
+/// One node's row of the matrix.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeVersions {
+    pub node_id: String,
+    pub hostname: String,
+    pub online: bool,
+    /// Service name → version, for installed components only. An empty
+    /// string means installed but the version couldn't be read.
+    pub versions: BTreeMap<String, String>,
+}
+
+static SNAPSHOT: RwLock<Vec<NodeVersions>> = RwLock::new(Vec::new());
+
+/// `2.7.4` → `2.7`. Versions without a minor part compare whole.
+fn release_line(version: &str) -> String {
+    version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+fn rows_for(nodes: &[crate::agent::Node], cluster: &str) -> Vec<NodeVersions> {
+    let mut rows: Vec<NodeVersions> = nodes.iter()
+        .filter(|n| n.node_type == "wolfstack")
+        .filter(|n| n.is_self || n.cluster_name.as_deref().unwrap_or("WolfStack") == cluster)
+        .map(|n| NodeVersions {
+            node_id: n.id.clone(),
+            hostname: n.display_name.clone().unwrap_or_else(|| n.hostname.clone()),
+            online: n.online,
+            versions: n.components.iter()
+                .filter(|c| c.installed)
+                .map(|c| (c.component.service_name().to_string(), c.version.clone().unwrap_or_default()))
+                .collect(),
+        })
+        .collect();
+    rows.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+    rows
+}
+
+/// Snapshot the cluster's versions now and every minute after.
+pub fn start(cluster: Arc<crate::agent::ClusterState>) {
+    tokio::spawn(async move {
+        loop {
+            let rows = rows_for(&cluster.get_all_nodes(), &cluster.get_self_cluster_name());
+            *SNAPSHOT.write().unwrap_or_else(|e| e.into_inner()) = rows;
+            tokio::time::sleep(std::time::Duration::from_secs(REFRESH_SECS)).await;
+        }
+    });
+}
+
+pub fn matrix() -> Vec<NodeVersions> {
+    SNAPSHOT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Release line → the online nodes on it, for one component. Nodes whose
+/// version couldn't be read are left out rather than guessed at.
+fn release_lines(rows: &[NodeVersions], component: Component) -> BTreeMap<String, Vec<String>> {
+    let mut lines: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in rows.iter().filter(|r| r.online) {
+        if let Some(v) = row.versions.get(component.service_name()).filter(|v| !v.is_empty()) {
+            lines.entry(release_line(v)).or_default().push(format!("{} ({})", row.hostname, v));
+        }
+    }
+    lines
+}
+
+/// One warning per rule the cluster breaks.
+pub fn mismatches(rows: &[NodeVersions]) -> Vec<Issue> {
+    COMPAT_RULES.iter().filter_map(|(component, why)| {
+        let lines = release_lines(rows, *component);
+        if lines.len() < 2 { return None; }
+        let spread = lines.iter()
+            .map(|(line, nodes)| format!("{}.x on {}", line, nodes.join(", ")))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Some(Issue {
+            severity: "warning".into(),
+            category: "version".into(),
+            title: format!("{} versions are incompatible across the cluster", component.name()),
+            detail: format!("{}. {} — upgrade them to the same release.", spread, why),
+        })
+    }).collect()
+}
+
+/// The `component_versions` issue check.
+pub fn check(_metrics: &crate::monitoring::SystemMetrics) -> Vec<Issue> {
+    mismatches(&matrix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::version_token;
+
+    fn row(host: &str, online: bool, versions: &[(&str, &str)]) -> NodeVersions {
+        NodeVersions {
+            node_id: host.into(),
+            hostname: host.into(),
+            online,
+            versions: versions.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn version_tokens() {
+        assert_eq!(version_token("wolfdisk 2.7.4").as_deref(), Some("2.7.4"));
+        assert_eq!(version_token("mariadb from 11.4.2-MariaDB, client 15.2 for debian").as_deref(), Some("11.4.2"));
+        assert_eq!(version_token("psql (PostgreSQL) 16.2").as_deref(), Some("16.2"));
+        assert_eq!(version_token("wolfnet v1.3.0").as_deref(), Some("1.3.0"));
+        assert_eq!(version_token("no version here"), None);
+    }
+
+    #[test]
+    fn patch_releases_are_compatible() {
+        let rows = vec![
+            row("a", true, &[("wolfnet", "2.7.4"), ("wolfdisk", "1.2.0")]),
+            row("b", true, &[("wolfnet", "2.7.1"), ("wolfdisk", "1.2.3")]),
+        ];
+        assert!(mismatches(&rows).is_empty());
+    }
+
+    #[test]
+    fn different_release_lines_warn() {
+        let rows = vec![
+            row("a", true, &[("wolfnet", "2.7.4")]),
+            row("b", true, &[("wolfnet", "2.8.0")]),
+            row("c", true, &[("wolfnet", "2.7.1"), ("wolfdisk", "1.2.0")]),
+            // Offline and unreadable versions don't count.
+            row("d", false, &[("wolfnet", "3.0.0")]),
+            row("e", true, &[("wolfnet", "")]),
+        ];
+        let issues = mismatches(&rows);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].title, "WolfNet versions are incompatible across the cluster");
+        assert!(issues[0].detail.starts_with("2.7.x on a (2.7.4), c (2.7.1); 2.8.x on b (2.8.0)."));
+    }
+}
@@ -0,0 +1,556 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Inventory export — every node and guest in the cluster as one flat
+//! list, for spreadsheets and asset registers.
+//!
+//! Each node reports its own rows (`scope=local`): itself, its Docker and
+//! LXC containers and its VMs, with allocated vCPUs / memory / disk, IP
+//! addresses and versions. The node serving the request fans out to its
+//! online WolfStack peers with the cluster secret, the same way
+//! `/api/containers/cluster` does, and adds a bare row for any peer it
+//! couldn't reach so the export still lists every machine.
+//!
+//! The monthly report mails the same rows — an HTML summary with the
+//! full CSV attached — through the SMTP settings the daily report uses.
+//! The schedule lives in `<config_dir>/inventory-report.json`; the month
+//! last sent is recorded there so a restart doesn't send it twice.
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::agent::Node;
+use crate::api::AppState;
+use crate::containers::ContainerInfo;
+use crate::vms::manager::VmConfig;
+
+/// One line of the inventory. Allocations are what the guest is given
+/// (limits / configured size), not live usage; `None` means unlimited or
+/// unknown.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InventoryRow {
+    pub cluster: String,
+    pub node: String,
+    /// `node`, `docker`, `lxc` or `vm`.
+    pub kind: String,
+    pub name: String,
+    pub state: String,
+    #[serde(default)]
+    pub vcpus: Option<f64>,
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    #[serde(default)]
+    pub disk_gb: Option<f64>,
+    #[serde(default)]
+    pub ip_addresses: Vec<String>,
+    /// WolfStack / OS / kernel for nodes, the image for Docker, the
+    /// distribution for LXC.
+    #[serde(default)]
+    pub version: String,
+}
+
+pub const CSV_HEADER: &str = "cluster,node,kind,name,state,vcpus,memory_mb,disk_gb,ip_addresses,version";
+
+pub fn cluster_of(node: &Node) -> String {
+    node.cluster_name.clone().unwrap_or_else(|| "Default".to_string())
+}
+
+/// Number of CPUs in an LXC cpuset such as `0-3,6`.
+pub fn cpuset_count(cpuset: &str) -> Option<f64> {
+    let mut count = 0u32;
+    for part in cpuset.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((a, b)) => {
+                let (a, b): (u32, u32) = (a.trim().parse().ok()?, b.trim().parse().ok()?);
+                count += b.checked_sub(a)? + 1;
+            }
+            None => {
+                part.parse::<u32>().ok()?;
+                count += 1;
+            }
+        }
+    }
+    (count > 0).then_some(count as f64)
+}
+
+/// Megabytes in an LXC memory limit (`512M`, `2G`, `1073741824`).
+pub fn size_to_mb(size: &str) -> Option<u64> {
+    let s = size.trim().trim_end_matches(['B', 'b']);
+    if s.is_empty() {
+        return None;
+    }
+    let (num, mult) = match s.chars().last()? {
+        'K' | 'k' => (&s[..s.len() - 1], 1.0 / 1024.0),
+        'M' | 'm' => (&s[..s.len() - 1], 1.0),
+        'G' | 'g' => (&s[..s.len() - 1], 1024.0),
+        'T' | 't' => (&s[..s.len() - 1], 1024.0 * 1024.0),
+        _ => (s, 1.0 / (1024.0 * 1024.0)),
+    };
+    let v: f64 = num.trim().parse().ok()?;
+    (v > 0.0).then(|| (v * mult).round() as u64)
+}
+
+fn split_ips(s: &str) -> Vec<String> {
+    s.split([',', ' ', ';', '\n'])
+        .map(|ip| ip.trim().split('/').next().unwrap_or("").to_string())
+        .filter(|ip| !ip.is_empty() && ip != "-")
+        .collect()
+}
+
+fn bytes_to_gb(bytes: u64) -> f64 {
+    (bytes as f64 / 1_073_741_824.0 * 10.0).round() / 10.0
+}
+
+/// The row for a node itself, from its gossiped metrics.
+pub fn node_row(node: &Node) -> InventoryRow {
+    let m = node.metrics.as_ref();
+    let mut ips = vec![node.address.clone()];
+    if let Some(p) = node.public_ip.as_ref().filter(|p| !p.is_empty() && **p != node.address) {
+        ips.push(p.clone());
+    }
+    let version = m.map(|m| {
+        let os = [m.os_name.clone(), m.os_version.clone()].into_iter().flatten().collect::<Vec<_>>().join(" ");
+        let mut parts = Vec::new();
+        if node.is_self {
+            parts.push(format!("WolfStack {}", env!("CARGO_PKG_VERSION")));
+        }
+        if !os.is_empty() {
+            parts.push(os);
+        }
+        if let Some(k) = m.kernel_version.as_ref() {
+            parts.push(format!("kernel {}", k));
+        }
+        parts.join(" · ")
+    }).unwrap_or_default();
+    InventoryRow {
+        cluster: cluster_of(node),
+        node: node.hostname.clone(),
+        kind: "node".to_string(),
+        name: node.display_name.clone().filter(|d| !d.is_empty()).unwrap_or_else(|| node.hostname.clone()),
+        state: if node.online { "online" } else { "offline" }.to_string(),
+        vcpus: m.map(|m| m.cpu_count as f64),
+        memory_mb: m.map(|m| m.memory_total_bytes / 1_048_576),
+        disk_gb: m.map(|m| bytes_to_gb(m.disks.iter().map(|d| d.total_bytes).sum())),
+        ip_addresses: ips,
+        version,
+    }
+}
+
+/// Row for a Docker container; `inspect` is `docker inspect` output,
+/// where the CPU and memory limits live.
+pub fn docker_row(cluster: &str, node: &str, c: &ContainerInfo, inspect: Option<&serde_json::Value>) -> InventoryRow {
+    let host = inspect.and_then(|i| i.get("HostConfig"));
+    let nano = host.and_then(|h| h.get("NanoCpus")).and_then(|v| v.as_u64()).unwrap_or(0);
+    let mem = host.and_then(|h| h.get("Memory")).and_then(|v| v.as_u64()).unwrap_or(0);
+    InventoryRow {
+        cluster: cluster.to_string(),
+        node: node.to_string(),
+        kind: "docker".to_string(),
+        name: c.name.clone(),
+        state: c.state.clone(),
+        vcpus: (nano > 0).then(|| nano as f64 / 1e9),
+        memory_mb: (mem > 0).then_some(mem / 1_048_576),
+        disk_gb: c.disk_total.map(bytes_to_gb),
+        ip_addresses: split_ips(&c.ip_address),
+        version: c.image.clone(),
+    }
+}
+
+/// Row for an LXC container; `cpuset` / `memory_limit` come from its
+/// parsed config.
+pub fn lxc_row(cluster: &str, node: &str, c: &ContainerInfo, cpuset: &str, memory_limit: &str) -> InventoryRow {
+    InventoryRow {
+        cluster: cluster.to_string(),
+        node: node.to_string(),
+        kind: "lxc".to_string(),
+        name: if c.hostname.is_empty() { c.name.clone() } else { format!("{} ({})", c.hostname, c.name) },
+        state: c.state.clone(),
+        vcpus: cpuset_count(cpuset),
+        memory_mb: size_to_mb(memory_limit),
+        disk_gb: c.disk_total.map(bytes_to_gb),
+        ip_addresses: split_ips(&c.ip_address),
+        version: c.version.clone().unwrap_or_default(),
+    }
+}
+
+pub fn vm_row(cluster: &str, node: &str, vm: &VmConfig) -> InventoryRow {
+    let disk = vm.disk_size_gb as u64 + vm.extra_disks.iter().map(|d| d.size_gb as u64).sum::<u64>();
+    InventoryRow {
+        cluster: cluster.to_string(),
+        node: node.to_string(),
+        kind: "vm".to_string(),
+        name: vm.name.clone(),
+        state: if vm.running { "running" } else { "stopped" }.to_string(),
+        vcpus: Some(vm.cpus as f64),
+        memory_mb: Some(vm.memory_mb as u64),
+        disk_gb: Some(disk as f64),
+        ip_addresses: vm.wolfnet_ip.iter().cloned().collect(),
+        version: String::new(),
+    }
+}
+
+/// This node's rows. Blocking: lists guests and inspects every Docker
+/// container for its limits.
+pub fn collect_local(self_node: &Node, vms: &[VmConfig]) -> Vec<InventoryRow> {
+    let cluster = cluster_of(self_node);
+    let host = self_node.hostname.as_str();
+    let mut rows = vec![node_row(self_node)];
+    for c in crate::containers::docker_list_all_cached() {
+        let inspect = crate::containers::docker_inspect(&c.name).ok();
+        rows.push(docker_row(&cluster, host, &c, inspect.as_ref()));
+    }
+    for c in crate::containers::lxc_list_all_cached() {
+        let (cpuset, mem) = crate::containers::lxc_parse_config(&c.name)
+            .map(|p| (p.cpus, p.memory_limit))
+            .unwrap_or_default();
+        rows.push(lxc_row(&cluster, host, &c, &cpuset, &mem));
+    }
+    rows.extend(vms.iter().map(|vm| vm_row(&cluster, host, vm)));
+    rows
+}
+
+/// Rows for the whole cluster: this node's, plus each WolfStack peer's
+/// own `scope=local` export. Peers that are offline or don't answer get
+/// their node row only. Legacy Proxmox-API entries are skipped, as in
+/// the daily report.
+pub async fn collect_cluster(state: &web::Data<AppState>) -> Vec<InventoryRow> {
+    let nodes = state.cluster.get_all_nodes();
+    let mut rows = match nodes.iter().find(|n| n.is_self).cloned() {
+        Some(me) => {
+            let vms = crate::monitoring::inventory::vms(state).await;
+            web::block(move || {
+                collect_local(&me, &vms)
+            }).await.unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let peers: Vec<Node> = nodes.into_iter().filter(|n| !n.is_self && n.node_type == "wolfstack").collect();
+    let secret = state.cluster_secret.clone();
+    let handles: Vec<_> = peers.into_iter().map(|peer| {
+        let secret = secret.clone();
+        tokio::spawn(async move {
+            if peer.online {
+                for url in crate::api::build_node_urls(&peer.address, peer.port, "/api/reports/inventory?scope=local&format=json") {
+                    let resp = crate::api::API_HTTP_CLIENT.get(&url)
+                        .timeout(std::time::Duration::from_secs(30))
+                        .header("X-WolfStack-Secret", &secret)
+                        .send().await;
+                    if let Ok(resp) = resp {
+                        if resp.status().is_success() {
+                            if let Ok(v) = resp.json::<serde_json::Value>().await {
+                                if let Ok(rows) = serde_json::from_value::<Vec<InventoryRow>>(v["rows"].clone()) {
+                                    return rows;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            // Older peer or unreachable: keep what gossip knows.
+            vec![node_row(&peer)]
+        })
+    }).collect();
+    for h in handles {
+        if let Ok(peer_rows) = h.await {
+            rows.extend(peer_rows);
+        }
+    }
+    sort_rows(&mut rows);
+    rows
+}
+
+/// Cluster, node, then nodes before their guests, then kind and name.
+pub fn sort_rows(rows: &mut [InventoryRow]) {
+    let rank = |k: &str| match k { "node" => 0, "vm" => 1, "lxc" => 2, "docker" => 3, _ => 4 };
+    rows.sort_by(|a, b| {
+        (a.cluster.as_str(), a.node.as_str(), rank(&a.kind), a.name.as_str())
+            .cmp(&(b.cluster.as_str(), b.node.as_str(), rank(&b.kind), b.name.as_str()))
+    });
+}
+
+/// RFC 4180 field quoting. Fields starting with a formula character are
+/// prefixed with `'` so a spreadsheet shows them as text instead of
+/// evaluating them (container names and images are user-controlled).
+pub fn csv_field(s: &str) -> String {
+    let s = if s.starts_with(['=', '+', '-', '@']) && s.parse::<f64>().is_err() {
+        format!("'{}", s)
+    } else {
+        s.to_string()
+    };
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+pub fn to_csv(rows: &[InventoryRow]) -> String {
+    let opt = |v: Option<String>| v.unwrap_or_default();
+    let mut out = String::from(CSV_HEADER);
+    out.push_str("\r\n");
+    for r in rows {
+        let fields = [
+            csv_field(&r.cluster),
+            csv_field(&r.node),
+            csv_field(&r.kind),
+            csv_field(&r.name),
+            csv_field(&r.state),
+            opt(r.vcpus.map(|v| format!("{}", (v * 100.0).round() / 100.0))),
+            opt(r.memory_mb.map(|v| v.to_string())),
+            opt(r.disk_gb.map(|v| format!("{}", v))),
+            csv_field(&r.ip_addresses.join(" ")),
+            csv_field(&r.version),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Per-cluster totals for the email summary.
+#[derive(Debug, Default, PartialEq)]
+pub struct ClusterTotals {
+    pub nodes: usize,
+    pub nodes_online: usize,
+    pub vms: usize,
+    pub lxc: usize,
+    pub docker: usize,
+    pub guest_vcpus: f64,
+    pub guest_memory_mb: u64,
+    pub guest_disk_gb: f64,
+}
+
+pub fn totals_by_cluster(rows: &[InventoryRow]) -> BTreeMap<String, ClusterTotals> {
+    let mut map: BTreeMap<String, ClusterTotals> = BTreeMap::new();
+    for r in rows {
+        let t = map.entry(r.cluster.clone()).or_default();
+        match r.kind.as_str() {
+            "node" => {
+                t.nodes += 1;
+                if r.state == "online" {
+                    t.nodes_online += 1;
+                }
+                continue;
+            }
+            "vm" => t.vms += 1,
+            "lxc" => t.lxc += 1,
+            "docker" => t.docker += 1,
+            _ => {}
+        }
+        t.guest_vcpus += r.vcpus.unwrap_or(0.0);
+        t.guest_memory_mb += r.memory_mb.unwrap_or(0);
+        t.guest_disk_gb += r.disk_gb.unwrap_or(0.0);
+    }
+    map
+}
+
+/// HTML body of the monthly email: a summary table per cluster and the
+/// node list. The full per-guest list is the CSV attachment.
+pub fn email_html(month: &str, rows: &[InventoryRow], dashboard_url: &str) -> String {
+    use crate::daily_report::escape;
+    let mut html = String::from(r#"<!DOCTYPE html><html><head><meta charset="utf-8"><style>
+body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f8f9fa;color:#1a1a2e;margin:0;padding:20px;}
+.container{max-width:900px;margin:0 auto;background:#fff;border-radius:12px;padding:24px;border:1px solid #e0e0e8;}
+table{width:100%;border-collapse:collapse;margin:8px 0 20px;font-size:13px;}
+th{text-align:left;background:#f1f2f6;padding:6px 8px;border-bottom:1px solid #e0e0e8;}
+td{padding:6px 8px;border-bottom:1px solid #eee;}
+.meta{color:#666;font-size:12px;}
+</style></head><body><div class="container">"#);
+    html.push_str(&format!(
+        r#"<h1>WolfStack Inventory — {}</h1><p class="meta">{} row(s) &bull; WolfStack v{} &bull; full list attached as CSV</p>"#,
+        escape(month), rows.len(), env!("CARGO_PKG_VERSION")
+    ));
+    html.push_str("<h2>Summary</h2><table><tr><th>Cluster</th><th>Nodes</th><th>VMs</th><th>LXC</th><th>Docker</th><th>Allocated vCPU</th><th>Allocated memory</th><th>Allocated disk</th></tr>");
+    for (cluster, t) in totals_by_cluster(rows) {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{} ({} online)</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{:.1} GB</td><td>{:.0} GB</td></tr>",
+            escape(&cluster), t.nodes, t.nodes_online, t.vms, t.lxc, t.docker,
+            t.guest_vcpus, t.guest_memory_mb as f64 / 1024.0, t.guest_disk_gb
+        ));
+    }
+    html.push_str("</table><h2>Nodes</h2><table><tr><th>Cluster</th><th>Node</th><th>State</th><th>CPUs</th><th>Memory</th><th>Disk</th><th>Addresses</th><th>Version</th></tr>");
+    for r in rows.iter().filter(|r| r.kind == "node") {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&r.cluster), escape(&r.name), escape(&r.state),
+            r.vcpus.map(|v| format!("{}", v)).unwrap_or_else(|| "—".into()),
+            r.memory_mb.map(|v| format!("{:.1} GB", v as f64 / 1024.0)).unwrap_or_else(|| "—".into()),
+            r.disk_gb.map(|v| format!("{:.0} GB", v)).unwrap_or_else(|| "—".into()),
+            escape(&r.ip_addresses.join(", ")), escape(&r.version)
+        ));
+    }
+    html.push_str(&format!(
+        r#"</table><p class="meta"><a href="{}">Open WolfStack</a></p></div></body></html>"#,
+        escape(dashboard_url)
+    ));
+    html
+}
+
+/// Plain-text alternative: the per-cluster summary only.
+pub fn email_text(month: &str, rows: &[InventoryRow]) -> String {
+    let mut out = format!("WolfStack Inventory — {}\n{} row(s); full list attached as CSV.\n", month, rows.len());
+    for (cluster, t) in totals_by_cluster(rows) {
+        out.push_str(&format!(
+            "\n{}: {} node(s) ({} online), {} VM, {} LXC, {} Docker — {:.1} vCPU, {:.1} GB memory, {:.0} GB disk allocated\n",
+            cluster, t.nodes, t.nodes_online, t.vms, t.lxc, t.docker,
+            t.guest_vcpus, t.guest_memory_mb as f64 / 1024.0, t.guest_disk_gb
+        ));
+    }
+    out
+}
+
+// ─── Monthly schedule ───
+
+fn schedule_path() -> String {
+    format!("{}/inventory-report.json", crate::paths::get().config_dir)
+}
+
+fn default_day() -> u32 { 1 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Day of the month to send on (1–28, so every month has it).
+    #[serde(default = "default_day")]
+    pub day_of_month: u32,
+    /// Recipient override; empty = the alert address in the SMTP settings.
+    #[serde(default)]
+    pub email_to: String,
+    /// `YYYY-MM` of the last report sent.
+    #[serde(default)]
+    pub last_sent: String,
+}
+
+impl Default for ReportSchedule {
+    fn default() -> Self {
+        Self { enabled: false, day_of_month: default_day(), email_to: String::new(), last_sent: String::new() }
+    }
+}
+
+impl ReportSchedule {
+    pub fn load() -> Self {
+        std::fs::read_to_string(schedule_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = schedule_path();
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+
+    /// The `YYYY-MM` to send for now, if a report is due: on or after the
+    /// configured day and not already sent this month.
+    pub fn due(&self, now: chrono::DateTime<chrono::Utc>) -> Option<String> {
+        use chrono::Datelike;
+        let month = now.format("%Y-%m").to_string();
+        (self.enabled && now.day() >= self.day_of_month.clamp(1, 28) && self.last_sent != month).then_some(month)
+    }
+}
+
+/// Build the cluster inventory and mail it. Used by the monthly loop and
+/// the "Send now" button; `month` labels the report.
+pub async fn send_report(state: &web::Data<AppState>, month: &str) -> Result<usize, String> {
+    let config = state.ai_agent.config.lock().unwrap().clone();
+    let schedule = ReportSchedule::load();
+    let to = if schedule.email_to.trim().is_empty() { config.email_to.clone() } else { schedule.email_to.trim().to_string() };
+    if config.smtp_host.is_empty() || to.is_empty() {
+        return Err("Email is not configured — set the SMTP server and alert address under AI / Email settings".to_string());
+    }
+    let rows = collect_cluster(state).await;
+    let hostname = state.cluster.get_all_nodes().into_iter().find(|n| n.is_self).map(|n| n.hostname).unwrap_or_default();
+    let html = email_html(month, &rows, &crate::ai::dashboard_base_url(&hostname));
+    let text = email_text(month, &rows);
+    let csv = to_csv(&rows);
+    let subject = format!("[WolfStack] Inventory report — {}", month);
+    let filename = format!("wolfstack-inventory-{}.csv", month);
+    let count = rows.len();
+    web::block(move || {
+        crate::ai::send_email_with_attachment(&config, &to, &subject, &html, &text, &filename, "text/csv", csv.into_bytes())
+    }).await.map_err(|e| e.to_string())??;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(kind: &str, name: &str) -> InventoryRow {
+        InventoryRow { cluster: "prod".into(), node: "pve1".into(), kind: kind.into(), name: name.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn cpuset_and_sizes_parse() {
+        assert_eq!(cpuset_count("0-3"), Some(4.0));
+        assert_eq!(cpuset_count("0-1,4,6-7"), Some(5.0));
+        assert_eq!(cpuset_count(""), None);
+        assert_eq!(cpuset_count("3-1"), None);
+        assert_eq!(size_to_mb("512M"), Some(512));
+        assert_eq!(size_to_mb("2G"), Some(2048));
+        assert_eq!(size_to_mb("1073741824"), Some(1024));
+        assert_eq!(size_to_mb(""), None);
+        assert_eq!(split_ips("10.0.0.5/24, 172.17.0.2"), vec!["10.0.0.5", "172.17.0.2"]);
+    }
+
+    #[test]
+    fn csv_quotes_and_neutralises_formulas() {
+        let mut r = row("docker", "=HYPERLINK(\"x\")");
+        r.version = "nginx:1.27, alpine".into();
+        r.vcpus = Some(0.5);
+        r.memory_mb = Some(256);
+        r.ip_addresses = vec!["10.0.0.5".into(), "10.0.0.6".into()];
+        let csv = to_csv(&[r]);
+        let line = csv.lines().nth(1).unwrap();
+        assert_eq!(csv.lines().next().unwrap(), CSV_HEADER);
+        assert_eq!(line, "prod,pve1,docker,\"'=HYPERLINK(\"\"x\"\")\",,0.5,256,,10.0.0.5 10.0.0.6,\"nginx:1.27, alpine\"");
+        assert_eq!(csv_field("-5"), "-5");
+    }
+
+    #[test]
+    fn totals_skip_nodes_in_guest_allocations() {
+        let mut n = row("node", "pve1");
+        n.state = "online".into();
+        n.vcpus = Some(32.0);
+        let mut vm = row("vm", "web");
+        vm.vcpus = Some(4.0);
+        vm.memory_mb = Some(4096);
+        vm.disk_gb = Some(40.0);
+        let t = totals_by_cluster(&[n, vm, row("docker", "redis")]);
+        let p = &t["prod"];
+        assert_eq!((p.nodes, p.nodes_online, p.vms, p.docker), (1, 1, 1, 1));
+        assert_eq!(p.guest_vcpus, 4.0);
+        assert_eq!(p.guest_memory_mb, 4096);
+    }
+
+    #[test]
+    fn rows_sort_nodes_before_guests() {
+        let mut rows = vec![row("docker", "a"), row("vm", "b"), row("node", "pve1")];
+        sort_rows(&mut rows);
+        assert_eq!(rows.iter().map(|r| r.kind.as_str()).collect::<Vec<_>>(), vec!["node", "vm", "docker"]);
+    }
+
+    #[test]
+    fn schedule_is_due_once_per_month() {
+        use chrono::TimeZone;
+        let mut s = ReportSchedule { enabled: true, day_of_month: 3, ..Default::default() };
+        let day2 = chrono::Utc.with_ymd_and_hms(2026, 10, 2, 9, 0, 0).unwrap();
+        let day5 = chrono::Utc.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        assert_eq!(s.due(day2), None);
+        assert_eq!(s.due(day5).as_deref(), Some("2026-10"));
+        s.last_sent = "2026-10".into();
+        assert_eq!(s.due(day5), None);
+        s.enabled = false;
+        s.last_sent.clear();
+        assert_eq!(s.due(day5), None);
+    }
+}
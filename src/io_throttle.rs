@@ -0,0 +1,291 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Disk IO throttling for long-running jobs — scheduled backups and S3
+//! mount syncs — so the 2 a.m. backup doesn't saturate the disks every
+//! guest is running on (and wake the alerting with the latency it causes).
+//!
+//! Two knobs, both per job:
+//! - **Priority** (`ionice`): set on the job's own thread with
+//!   `ioprio_set`. The tar / zstd / qemu-img / vzdump processes it spawns
+//!   inherit it. Honoured by the BFQ and CFQ schedulers; `none` and
+//!   `mq-deadline` ignore it, which is what the caps are for.
+//! - **Bandwidth caps** (MB/s): a cgroup v2 group under
+//!   `/sys/fs/cgroup/wolfstack-io` with `io.max` set on every disk. A
+//!   watcher moves each process the job's thread spawns (and anything they
+//!   had already forked) into it while the job runs; later forks inherit
+//!   it. Work the job does in-process — reading files for an S3 upload —
+//!   doesn't go through a child, so those readers pace themselves with
+//!   [`IoLimits::read_bytes_per_sec`].
+//!
+//! Moved processes leave wolfstack.service's cgroup, so a WolfStack
+//! restart mid-job doesn't kill them — the same as a job started by hand.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const CGROUP_PARENT: &str = "/sys/fs/cgroup/wolfstack-io";
+/// How often the watcher looks for newly spawned processes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+/// Generous upper bound for a cap — anything above is effectively none.
+const MAX_MBPS: u32 = 100_000;
+
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+const IOPRIO_CLASS_BE: libc::c_int = 2;
+const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IoLimits {
+    /// "" (normal), "low" (best-effort, lowest level) or "idle" (only
+    /// when nothing else wants the disk).
+    #[serde(default)]
+    pub priority: String,
+    /// Disk read cap in MB/s. None = uncapped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_mbps: Option<u32>,
+    /// Disk write cap in MB/s. None = uncapped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_mbps: Option<u32>,
+}
+
+impl IoLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.priority.is_empty() && self.read_mbps.is_none() && self.write_mbps.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !matches!(self.priority.as_str(), "" | "low" | "idle") {
+            return Err(format!("IO priority '{}' must be low, idle or empty", self.priority));
+        }
+        for (what, cap) in [("read", self.read_mbps), ("write", self.write_mbps)] {
+            if cap.is_some_and(|mbps| mbps == 0 || mbps > MAX_MBPS) {
+                return Err(format!("{} cap must be 1-{} MB/s", what, MAX_MBPS));
+            }
+        }
+        Ok(())
+    }
+
+    /// Read cap for in-process readers, in bytes/s (0 = uncapped).
+    pub fn read_bytes_per_sec(&self) -> u64 {
+        self.read_mbps.map(|m| m as u64 * 1_000_000).unwrap_or(0)
+    }
+
+    fn ioprio(&self) -> Option<libc::c_int> {
+        match self.priority.as_str() {
+            "low" => Some((IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | 7),
+            "idle" => Some(IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT),
+            _ => None,
+        }
+    }
+}
+
+fn current_tid() -> libc::pid_t {
+    // SAFETY: gettid takes no arguments and can't fail.
+    unsafe { libc::syscall(libc::SYS_gettid) as libc::pid_t }
+}
+
+fn ioprio_get() -> Option<libc::c_int> {
+    // SAFETY: who=0 is the calling thread; no pointers involved.
+    let v = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, 0) };
+    (v >= 0).then_some(v as libc::c_int)
+}
+
+fn ioprio_set(prio: libc::c_int) -> bool {
+    // SAFETY: as above.
+    unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, prio) == 0 }
+}
+
+/// The limits in force on this thread until dropped.
+pub struct Throttle {
+    prev_ioprio: Option<libc::c_int>,
+    cgroup: Option<PathBuf>,
+    stop: Arc<AtomicBool>,
+    watcher: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Apply `limits` to the calling thread and whatever it spawns until the
+/// returned guard drops. Every part is best effort: a host without cgroup
+/// v2 or the io controller still gets the priority, and a failure is
+/// logged, never fatal to the job.
+pub fn apply(limits: &IoLimits, label: &str) -> Throttle {
+    let mut throttle = Throttle { prev_ioprio: None, cgroup: None, stop: Arc::new(AtomicBool::new(false)), watcher: None };
+    if limits.is_unlimited() {
+        return throttle;
+    }
+    if let Some(prio) = limits.ioprio() {
+        let prev = ioprio_get();
+        if ioprio_set(prio) {
+            throttle.prev_ioprio = prev;
+        } else {
+            warn!("io throttle: could not set IO priority for {}", label);
+        }
+    }
+    if limits.read_mbps.is_some() || limits.write_mbps.is_some() {
+        match create_cgroup(limits, label) {
+            Ok(dir) => {
+                let tid = current_tid();
+                let stop = throttle.stop.clone();
+                let procs = dir.join("cgroup.procs");
+                throttle.watcher = Some(std::thread::spawn(move || watch(tid, &procs, &stop)));
+                throttle.cgroup = Some(dir);
+            }
+            Err(e) => warn!("io throttle: bandwidth caps unavailable for {}: {}", label, e),
+        }
+    }
+    throttle
+}
+
+impl Drop for Throttle {
+    fn drop(&mut self) {
+        if let Some(prev) = self.prev_ioprio {
+            ioprio_set(prev);
+        }
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(h) = self.watcher.take() {
+            let _ = h.join();
+        }
+        if let Some(dir) = &self.cgroup {
+            // Fails while a straggler still lives in it; the next job
+            // sweeps empty groups.
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// `io.max` lines capping every disk at the given rates.
+fn io_max_lines(devices: &[String], limits: &IoLimits) -> Vec<String> {
+    let rate = |cap: Option<u32>| cap.map(|m| (m as u64 * 1_000_000).to_string()).unwrap_or_else(|| "max".into());
+    devices.iter()
+        .map(|dev| format!("{} rbps={} wbps={}", dev, rate(limits.read_mbps), rate(limits.write_mbps)))
+        .collect()
+}
+
+/// MAJ:MIN of every block device worth capping — disks, md and dm —
+/// skipping loop, ram, zram, floppy and optical drives.
+fn block_devices() -> Vec<String> {
+    let mut devs: Vec<String> = std::fs::read_dir("/sys/block").into_iter().flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let n = e.file_name().to_string_lossy().to_string();
+            !["loop", "ram", "zram", "sr", "fd"].iter().any(|p| n.starts_with(p))
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path().join("dev")).ok())
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .collect();
+    devs.sort();
+    devs
+}
+
+fn enable_io(dir: &Path) -> Result<(), String> {
+    let control = dir.join("cgroup.subtree_control");
+    let current = std::fs::read_to_string(&control).unwrap_or_default();
+    if current.split_whitespace().any(|c| c == "io") {
+        return Ok(());
+    }
+    std::fs::write(&control, "+io").map_err(|e| format!("enable io in {}: {}", control.display(), e))
+}
+
+fn create_cgroup(limits: &IoLimits, label: &str) -> Result<PathBuf, String> {
+    let controllers = std::fs::read_to_string(Path::new(CGROUP_ROOT).join("cgroup.controllers"))
+        .map_err(|_| "cgroup v2 is not mounted".to_string())?;
+    if !controllers.split_whitespace().any(|c| c == "io") {
+        return Err("the io controller is not available".into());
+    }
+    enable_io(Path::new(CGROUP_ROOT))?;
+    std::fs::create_dir_all(CGROUP_PARENT).map_err(|e| format!("create {}: {}", CGROUP_PARENT, e))?;
+    enable_io(Path::new(CGROUP_PARENT))?;
+    sweep_empty_groups();
+
+    let name: String = label.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let dir = Path::new(CGROUP_PARENT).join(format!("{}-{}", name, current_tid()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    let devices = block_devices();
+    if devices.is_empty() {
+        let _ = std::fs::remove_dir(&dir);
+        return Err("no block devices found".into());
+    }
+    // One write per device — a device that can't be throttled shouldn't
+    // lose the caps on the rest.
+    let applied = io_max_lines(&devices, limits).iter()
+        .filter(|line| std::fs::write(dir.join("io.max"), line).is_ok())
+        .count();
+    if applied == 0 {
+        let _ = std::fs::remove_dir(&dir);
+        return Err("io.max was rejected for every device".into());
+    }
+    Ok(dir)
+}
+
+/// Remove groups left behind by jobs whose stragglers have since exited.
+fn sweep_empty_groups() {
+    for entry in std::fs::read_dir(CGROUP_PARENT).into_iter().flatten().filter_map(|e| e.ok()) {
+        if entry.path().is_dir() {
+            let _ = std::fs::remove_dir(entry.path());
+        }
+    }
+}
+
+/// Children of every thread of `pid`.
+fn children_of(pid: &str) -> Vec<String> {
+    std::fs::read_dir(format!("/proc/{}/task", pid)).into_iter().flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|t| std::fs::read_to_string(t.path().join("children")).ok())
+        .flat_map(|c| c.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Move what thread `tid` spawns into the group until `stop`. A process
+/// is moved once; anything it forks afterwards is born in the group.
+fn watch(tid: libc::pid_t, procs: &Path, stop: &AtomicBool) {
+    let own = format!("/proc/self/task/{}/children", tid);
+    let mut moved = std::collections::HashSet::new();
+    while !stop.load(Ordering::SeqCst) {
+        let mut queue: Vec<String> = std::fs::read_to_string(&own).unwrap_or_default()
+            .split_whitespace().map(String::from).collect();
+        while let Some(pid) = queue.pop() {
+            if !moved.insert(pid.clone()) {
+                continue;
+            }
+            let _ = std::fs::write(procs, &pid);
+            queue.extend(children_of(&pid));
+        }
+        std::thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_validated() {
+        assert!(IoLimits::default().validate().is_ok());
+        assert!(IoLimits::default().is_unlimited());
+        let low = IoLimits { priority: "low".into(), read_mbps: Some(50), write_mbps: None };
+        assert!(low.validate().is_ok());
+        assert_eq!(low.read_bytes_per_sec(), 50_000_000);
+        assert!(IoLimits { priority: "realtime".into(), ..Default::default() }.validate().is_err());
+        assert!(IoLimits { write_mbps: Some(0), ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn io_max_caps_every_device() {
+        let limits = IoLimits { priority: String::new(), read_mbps: None, write_mbps: Some(40) };
+        assert_eq!(io_max_lines(&["8:0".into(), "259:0".into()], &limits), vec![
+            "8:0 rbps=max wbps=40000000".to_string(),
+            "259:0 rbps=max wbps=40000000".to_string(),
+        ]);
+        assert_eq!(IoLimits { priority: "idle".into(), ..Default::default() }.ioprio(), Some(3 << 13));
+        assert_eq!(IoLimits { priority: "low".into(), ..Default::default() }.ioprio(), Some((2 << 13) | 7));
+    }
+}
@@ -0,0 +1,838 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Issue scanner checks — the registry behind `api::collect_issues`.
+//!
+//! Each built-in check is a small function from a metrics sample to zero or
+//! more `Issue`s, registered in [`BUILTIN_CHECKS`] with a stable id. Sites
+//! add their own checks by dropping executables into
+//! `/etc/wolfstack/checks.d/`; those run alongside the built-ins and their
+//! findings flow into the Issues page, scheduled scan emails and the alert
+//! log exactly like native ones. Any check — built-in or script — can be
+//! switched off per node from Settings (persisted in `issue-checks.json`).
+//!
+//! Script protocol (either form):
+//! - JSON on stdout: one object, an array of objects, or one object per
+//!   line, each `{"severity","title","detail","category"}` (`title`
+//!   required; severity defaults to warning, category to `custom`).
+//! - Nagios-style: exit 0 = OK, 1 = warning, 2 = critical, with the first
+//!   stdout line as the title and the rest as the detail. Any other exit
+//!   (3 = unknown, 124 = timed out) is reported as the check itself failing.
+//!
+//! Scripts run as root, so only root-owned files that aren't group- or
+//! world-writable are executed — anything else is listed as skipped.
+
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::process::Command;
+
+use crate::api::{parse_size_to_mb, Issue};
+use crate::monitoring::SystemMetrics;
+
+fn config_path() -> String {
+    format!("{}/issue-checks.json", crate::paths::get().config_dir)
+}
+
+fn scripts_dir() -> String {
+    format!("{}/checks.d", crate::paths::get().config_dir)
+}
+
+/// Per-node check settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueChecksConfig {
+    /// Ids of checks that are switched off — built-in ids (`cpu`, `swap`, …)
+    /// or `script:<filename>` for checks.d scripts.
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Wall-clock cap per script run. A hung script must not stall the
+    /// whole scan (which already waits on SMART and docker).
+    #[serde(default = "default_script_timeout")]
+    pub script_timeout_secs: u64,
+}
+
+fn default_script_timeout() -> u64 { 20 }
+
+impl Default for IssueChecksConfig {
+    fn default() -> Self {
+        Self { disabled: Vec::new(), script_timeout_secs: default_script_timeout() }
+    }
+}
+
+impl IssueChecksConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(config_path(), json).map_err(|e| format!("Failed to save check settings: {}", e))
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.iter().any(|d| d == id)
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+        self.disabled.retain(|d| d != id);
+        if !enabled {
+            self.disabled.push(id.to_string());
+        }
+    }
+}
+
+pub struct BuiltinCheck {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub category: &'static str,
+    run: fn(&SystemMetrics) -> Vec<Issue>,
+}
+
+/// Every built-in check, in the order findings appear on the Issues page.
+pub const BUILTIN_CHECKS: &[BuiltinCheck] = &[
+    BuiltinCheck { id: "cpu", name: "CPU usage", category: "cpu", run: check_cpu },
+    BuiltinCheck { id: "memory", name: "Memory usage", category: "memory", run: check_memory },
+    BuiltinCheck { id: "disk_space", name: "Disk free space", category: "disk", run: check_disk_space },
+    BuiltinCheck { id: "disk_smart", name: "Failing disks (SMART)", category: "disk", run: check_disk_smart },
+    BuiltinCheck { id: "swap", name: "Swap usage", category: "swap", run: check_swap },
+    BuiltinCheck { id: "load", name: "Load average", category: "load", run: check_load },
+    BuiltinCheck { id: "systemd_failed", name: "Failed systemd units", category: "service", run: check_systemd_failed },
+    BuiltinCheck { id: "docker_stopped", name: "Stopped Docker containers", category: "container", run: check_docker_stopped },
+    BuiltinCheck { id: "kubernetes", name: "Kubernetes clusters", category: "kubernetes", run: check_kubernetes },
+    BuiltinCheck { id: "reclaimable_space", name: "Reclaimable disk space", category: "disk", run: check_reclaimable_space },
+    BuiltinCheck { id: "cache_services", name: "Redis / Memcached fragmentation and evictions", category: "cache", run: check_cache_services },
+    BuiltinCheck { id: "mount_quota", name: "Storage mounts over their usage quota", category: "storage", run: check_mount_quota },
+    BuiltinCheck { id: "mount_health", name: "Stale or unresponsive storage mounts", category: "storage", run: check_mount_health },
+    BuiltinCheck { id: "container_disk_quota", name: "Containers near their disk quota", category: "container", run: check_container_disk_quota },
+    BuiltinCheck { id: "sec_sshd", name: "SSH root login and password authentication", category: "security", run: check_sec_sshd },
+    BuiltinCheck { id: "sec_open_ports", name: "Services exposed without a default-deny firewall", category: "security", run: check_sec_open_ports },
+    BuiltinCheck { id: "sec_world_writable", name: "World-writable files in system directories", category: "security", run: check_sec_world_writable },
+    BuiltinCheck { id: "sec_kernel", name: "Outdated running kernel", category: "security", run: check_sec_kernel },
+    BuiltinCheck { id: "sec_tls", name: "Weak TLS protocols or ciphers in web server configs", category: "security", run: check_sec_tls },
+    BuiltinCheck { id: "component_versions", name: "WolfNet / WolfDisk versions incompatible across the cluster", category: "version", run: crate::installer::versions::check },
+    BuiltinCheck { id: "config_drift", name: "Config files drifted from the cluster baseline or last saved version", category: "config", run: crate::drift::check },
+];
+
+/// A checks.d entry and whether it is allowed to run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptCheck {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Why the script won't be executed (ownership / permissions), if so.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// List executables in checks.d, sorted by filename. Dotfiles and editor
+/// backups (`~`, `.bak`, `.dpkg-*`) are ignored so a half-edited script
+/// doesn't run twice.
+pub fn list_scripts() -> Vec<ScriptCheck> {
+    let Ok(entries) = std::fs::read_dir(scripts_dir()) else { return Vec::new(); };
+    let mut out: Vec<ScriptCheck> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || name.ends_with('~') || name.ends_with(".bak") || name.contains(".dpkg-") {
+                return None;
+            }
+            let meta = std::fs::metadata(e.path()).ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            Some(ScriptCheck {
+                id: format!("script:{}", name),
+                path: e.path().to_string_lossy().to_string(),
+                skipped: script_unsafe_reason(meta.uid(), meta.permissions().mode()),
+                name,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+fn script_unsafe_reason(uid: u32, mode: u32) -> Option<String> {
+    if uid != 0 {
+        Some("not owned by root".to_string())
+    } else if mode & 0o022 != 0 {
+        Some("writable by group or others".to_string())
+    } else if mode & 0o100 == 0 {
+        Some("not executable".to_string())
+    } else {
+        None
+    }
+}
+
+fn normalise_severity(s: &str) -> String {
+    match s.to_ascii_lowercase().as_str() {
+        "critical" | "crit" | "error" => "critical".to_string(),
+        "info" | "notice" => "info".to_string(),
+        _ => "warning".to_string(),
+    }
+}
+
+fn issue_from_json(name: &str, v: &serde_json::Value) -> Option<Issue> {
+    let title = v["title"].as_str()?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some(Issue {
+        severity: normalise_severity(v["severity"].as_str().unwrap_or("warning")),
+        category: v["category"].as_str().filter(|c| !c.is_empty()).unwrap_or("custom").to_string(),
+        title: title.to_string(),
+        detail: v["detail"].as_str().map(|d| d.to_string())
+            .unwrap_or_else(|| format!("Reported by check script {}", name)),
+    })
+}
+
+/// Turn a script's stdout + exit code into issues (see module docs).
+fn parse_script_output(name: &str, stdout: &str, exit_code: Option<i32>) -> Vec<Issue> {
+    let trimmed = stdout.trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            return match v {
+                serde_json::Value::Array(items) => items.iter().filter_map(|i| issue_from_json(name, i)).collect(),
+                obj => issue_from_json(name, &obj).into_iter().collect(),
+            };
+        }
+        let per_line: Vec<Issue> = trimmed.lines()
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l.trim()).ok())
+            .filter_map(|v| issue_from_json(name, &v))
+            .collect();
+        if !per_line.is_empty() {
+            return per_line;
+        }
+    }
+
+    let mut lines = trimmed.lines();
+    let first = lines.next().unwrap_or("").trim();
+    let rest = lines.collect::<Vec<_>>().join("\n");
+    let severity = match exit_code {
+        Some(0) => return Vec::new(),
+        Some(1) => "warning",
+        Some(2) => "critical",
+        Some(124) => {
+            return vec![Issue {
+                severity: "warning".into(),
+                category: "custom".into(),
+                title: format!("Check script {} timed out", name),
+                detail: "The script was killed before it finished — see the timeout in issue check settings".into(),
+            }];
+        }
+        other => {
+            return vec![Issue {
+                severity: "warning".into(),
+                category: "custom".into(),
+                title: format!("Check script {} failed", name),
+                detail: match other {
+                    Some(c) => format!("Exited with status {}{}", c, if first.is_empty() { String::new() } else { format!(": {}", first) }),
+                    None => "Killed by a signal".to_string(),
+                },
+            }];
+        }
+    };
+    vec![Issue {
+        severity: severity.into(),
+        category: "custom".into(),
+        title: if first.is_empty() { format!("Check script {} reported a problem", name) } else { first.to_string() },
+        detail: if rest.trim().is_empty() { format!("Reported by check script {}", name) } else { rest },
+    }]
+}
+
+fn run_script(script: &ScriptCheck, timeout_secs: u64) -> Vec<Issue> {
+    let out = Command::new("timeout")
+        .arg(timeout_secs.clamp(1, 300).to_string())
+        .arg(&script.path)
+        .env("WOLFSTACK_CHECK", "1")
+        .output();
+    match out {
+        Ok(o) => parse_script_output(&script.name, &String::from_utf8_lossy(&o.stdout), o.status.code()),
+        Err(e) => vec![Issue {
+            severity: "warning".into(),
+            category: "custom".into(),
+            title: format!("Check script {} failed", script.name),
+            detail: format!("Could not run {}: {}", script.path, e),
+        }],
+    }
+}
+
+/// Run every enabled built-in check and checks.d script. Blocking — call
+/// from the blocking pool (several checks shell out).
+pub fn run_enabled(metrics: &SystemMetrics) -> Vec<Issue> {
+    let config = IssueChecksConfig::load();
+    let mut issues = Vec::new();
+    for check in BUILTIN_CHECKS.iter().filter(|c| config.is_enabled(c.id)) {
+        issues.extend((check.run)(metrics));
+    }
+    for script in list_scripts() {
+        if script.skipped.is_none() && config.is_enabled(&script.id) {
+            issues.extend(run_script(&script, config.script_timeout_secs));
+        }
+    }
+    issues
+}
+
+/// Every known check with its enabled state — drives the settings list.
+pub fn list_checks() -> serde_json::Value {
+    let config = IssueChecksConfig::load();
+    let builtin: Vec<serde_json::Value> = BUILTIN_CHECKS.iter().map(|c| serde_json::json!({
+        "id": c.id,
+        "name": c.name,
+        "category": c.category,
+        "source": "builtin",
+        "enabled": config.is_enabled(c.id),
+    })).collect();
+    let scripts: Vec<serde_json::Value> = list_scripts().into_iter().map(|s| serde_json::json!({
+        "id": s.id,
+        "name": s.name,
+        "category": "custom",
+        "source": "script",
+        "path": s.path,
+        "skipped": s.skipped,
+        "enabled": config.is_enabled(&s.id),
+    })).collect();
+    serde_json::json!({
+        "checks": builtin.into_iter().chain(scripts).collect::<Vec<_>>(),
+        "scripts_dir": scripts_dir(),
+        "script_timeout_secs": config.script_timeout_secs,
+    })
+}
+
+/// True for ids that name a registered built-in or an existing script.
+pub fn is_known_check(id: &str) -> bool {
+    BUILTIN_CHECKS.iter().any(|c| c.id == id) || list_scripts().iter().any(|s| s.id == id)
+}
+
+// ─── Built-in checks ───
+
+fn check_cpu(metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if metrics.cpu_usage_percent > 90.0 {
+        issues.push(Issue {
+            severity: "critical".into(),
+            category: "cpu".into(),
+            title: "CPU usage critically high".into(),
+            detail: format!("CPU at {:.1}% — system may be unresponsive", metrics.cpu_usage_percent),
+        });
+    } else if metrics.cpu_usage_percent > 75.0 {
+        issues.push(Issue {
+            severity: "warning".into(),
+            category: "cpu".into(),
+            title: "CPU usage elevated".into(),
+            detail: format!("CPU at {:.1}% — monitor for sustained load", metrics.cpu_usage_percent),
+        });
+    }
+    issues
+}
+
+fn check_memory(metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mem_pct = metrics.memory_percent;
+    if mem_pct > 90.0 {
+        issues.push(Issue {
+            severity: "critical".into(),
+            category: "memory".into(),
+            title: "Memory usage critically high".into(),
+            detail: format!("Memory at {:.1}% — OOM risk", mem_pct),
+        });
+    } else if mem_pct > 80.0 {
+        issues.push(Issue {
+            severity: "warning".into(),
+            category: "memory".into(),
+            title: "Memory usage elevated".into(),
+            detail: format!("Memory at {:.1}%", mem_pct),
+        });
+    }
+    issues
+}
+
+fn check_disk_space(metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for disk in &metrics.disks {
+        // Skip /boot/ and /etc/pve mounts unless >99% — managed by the OS/Proxmox
+        if (disk.mount_point.starts_with("/boot") || disk.mount_point == "/etc/pve") && disk.usage_percent <= 99.0 {
+            continue;
+        }
+
+        let total_gb = disk.total_bytes as f64 / 1_073_741_824.0;
+        let used_gb = disk.used_bytes as f64 / 1_073_741_824.0;
+        let free_gb = disk.available_bytes as f64 / 1_073_741_824.0;
+        let size_detail = format!("{} — {:.1} GB used / {:.1} GB total ({:.1} GB free, {:.1}%)",
+            disk.mount_point, used_gb, total_gb, free_gb, disk.usage_percent);
+
+        if free_gb < 2.0 {
+            issues.push(Issue {
+                severity: "critical".into(),
+                category: "disk".into(),
+                title: format!("Disk {} almost full ({:.1} GB free)", disk.mount_point, free_gb),
+                detail: size_detail,
+            });
+        } else if free_gb < 10.0 {
+            issues.push(Issue {
+                severity: "warning".into(),
+                category: "disk".into(),
+                title: format!("Disk {} low on space ({:.1} GB free)", disk.mount_point, free_gb),
+                detail: size_detail,
+            });
+        }
+    }
+    issues
+}
+
+fn check_disk_smart(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    // The free-space loop above catches FULL disks; this catches DYING ones.
+    // Uses the SAME evaluator the Storage page and the Issues alerting watcher
+    // use (array::disk_smart_health + DiskSmart::failing_reasons — Backblaze
+    // SMART 5/187/197/198, NVMe wear/spare, overall-health FAILED), so a drive
+    // the operator sees reddened under Storage also shows up HERE, on the page
+    // where they run upgrades (a failing SSD used to raise nothing on this scan
+    // — collect_issues never checked disk health; PapaSchlumpf 2026-07-01). The
+    // standby guard is gated on rotational-ness so an SSD/NVMe whose power-mode
+    // probe can't confirm an active state is still read (never skipped), while a
+    // spun-down HDD is left asleep. Physical-disk SMART is host-level and
+    // independent of `metrics`, so it's read directly here.
+    for dev in crate::array::list_physical_disks() {
+        let guard = crate::array::disk_is_rotational(&dev);
+        if let Some(health) = crate::array::disk_smart_health(&dev, guard) {
+            let reasons = health.failing_reasons();
+            if !reasons.is_empty() {
+                issues.push(Issue {
+                    severity: "critical".into(),
+                    category: "disk".into(),
+                    title: format!("Disk {} is failing (SMART)", dev),
+                    detail: format!("{} — back up and plan replacement: {}", dev, reasons.join("; ")),
+                });
+            }
+        }
+    }
+    issues
+}
+
+fn check_swap(metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if metrics.swap_total_bytes > 0 {
+        let swap_pct = (metrics.swap_used_bytes as f64 / metrics.swap_total_bytes as f64) * 100.0;
+        if swap_pct > 50.0 {
+            issues.push(Issue {
+                severity: "warning".into(),
+                category: "swap".into(),
+                title: "Significant swap usage".into(),
+                detail: format!("Swap at {:.1}% — system may be low on RAM", swap_pct),
+            });
+        }
+    }
+    issues
+}
+
+fn check_load(metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let cpu_count = metrics.cpu_count.max(1) as f64;
+    if metrics.load_avg.one > cpu_count * 2.0 {
+        issues.push(Issue {
+            severity: "critical".into(),
+            category: "load".into(),
+            title: "Load average extremely high".into(),
+            detail: format!("Load {:.2} ({:.1}× CPU count {})", metrics.load_avg.one, metrics.load_avg.one / cpu_count, metrics.cpu_count),
+        });
+    } else if metrics.load_avg.one > cpu_count {
+        issues.push(Issue {
+            severity: "warning".into(),
+            category: "load".into(),
+            title: "Load average high".into(),
+            detail: format!("Load {:.2} (>{} CPUs)", metrics.load_avg.one, metrics.cpu_count),
+        });
+    }
+    issues
+}
+
+fn check_systemd_failed(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if let Ok(output) = std::process::Command::new("systemctl")
+        .args(["--failed", "--no-legend", "--plain"])
+        .output()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for line in stdout.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let Some(unit) = parts.first() {
+                if !unit.is_empty() {
+                    issues.push(Issue {
+                        severity: "warning".into(),
+                        category: "service".into(),
+                        title: format!("Service {} failed", unit),
+                        detail: format!("systemd unit {} is in failed state", unit),
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+fn check_docker_stopped(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if let Ok(output) = std::process::Command::new("docker")
+        .args(["ps", "-a", "--filter", "status=exited", "--format", "{{.Names}}"])
+        .output()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stopped: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
+        if !stopped.is_empty() {
+            issues.push(Issue {
+                severity: "info".into(),
+                category: "container".into(),
+                title: format!("{} stopped Docker container(s)", stopped.len()),
+                detail: format!("Stopped: {}", stopped.join(", ")),
+            });
+        }
+    }
+    issues
+}
+
+fn check_kubernetes(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let k8s_clusters = crate::kubernetes::list_clusters();
+    for cluster in &k8s_clusters {
+        let status = crate::kubernetes::get_cluster_status(&cluster.kubeconfig_path);
+        if !status.healthy {
+            issues.push(Issue {
+                severity: "warning".into(),
+                category: "kubernetes".into(),
+                title: format!("K8s cluster '{}' unhealthy", cluster.name),
+                detail: format!("{}/{} nodes ready, {}/{} pods running",
+                    status.nodes_ready, status.nodes_total,
+                    status.pods_running, status.pods_total),
+            });
+        }
+
+        // Check for NotReady nodes
+        let nodes = crate::kubernetes::get_nodes(&cluster.kubeconfig_path);
+        for n in &nodes {
+            if n.status != "Ready" {
+                issues.push(Issue {
+                    severity: "critical".into(),
+                    category: "kubernetes".into(),
+                    title: format!("K8s node '{}' not ready", n.name),
+                    detail: format!("Cluster '{}', status: {}, role: {}", cluster.name, n.status, n.roles),
+                });
+            }
+        }
+
+        // Check for problem pods (exclude Succeeded/Completed pods — these are finished Jobs/init containers)
+        let pods = crate::kubernetes::get_pods(&cluster.kubeconfig_path, None);
+        let active_pods: Vec<_> = pods.iter().filter(|p| p.status != "Succeeded").collect();
+        let failed = active_pods.iter().filter(|p| p.status == "Failed" || p.status == "Unknown").count();
+        let pending = active_pods.iter().filter(|p| p.status == "Pending").count();
+        let high_restarts = active_pods.iter().filter(|p| p.restarts >= 10).count();
+
+        if failed > 0 {
+            issues.push(Issue {
+                severity: "critical".into(),
+                category: "kubernetes".into(),
+                title: format!("{} failed pod(s) in cluster '{}'", failed, cluster.name),
+                detail: active_pods.iter().filter(|p| p.status == "Failed" || p.status == "Unknown")
+                    .take(5).map(|p| format!("{}/{}", p.namespace, p.name)).collect::<Vec<_>>().join(", "),
+            });
+        }
+        if pending > 0 {
+            issues.push(Issue {
+                severity: "warning".into(),
+                category: "kubernetes".into(),
+                title: format!("{} pending pod(s) in cluster '{}'", pending, cluster.name),
+                detail: active_pods.iter().filter(|p| p.status == "Pending")
+                    .take(5).map(|p| format!("{}/{}", p.namespace, p.name)).collect::<Vec<_>>().join(", "),
+            });
+        }
+        if high_restarts > 0 {
+            issues.push(Issue {
+                severity: "warning".into(),
+                category: "kubernetes".into(),
+                title: format!("{} pod(s) with high restarts in cluster '{}'", high_restarts, cluster.name),
+                detail: active_pods.iter().filter(|p| p.restarts >= 10)
+                    .take(5).map(|p| format!("{}/{} ({}x)", p.namespace, p.name, p.restarts)).collect::<Vec<_>>().join(", "),
+            });
+        }
+    }
+    issues
+}
+
+fn check_cache_services(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let instances = crate::monitoring::cache_services::collect();
+    crate::monitoring::cache_services::issues_for(&instances)
+}
+
+fn check_mount_quota(_metrics: &SystemMetrics) -> Vec<Issue> {
+    crate::storage::quota_breaches()
+        .into_iter()
+        .map(|b| {
+            let gb = |bytes: u64| bytes as f64 / 1_073_741_824.0;
+            Issue {
+                // Past 95% the quota is academic — the mount is nearly full.
+                severity: if b.usage.percent >= 95.0 { "critical" } else { "warning" }.into(),
+                category: "storage".into(),
+                title: format!("Mount '{}' at {:.0}% (quota {}%)", b.name, b.usage.percent, b.quota_percent),
+                detail: format!("{} — {:.1} GB used / {:.1} GB total",
+                    b.mount_point, gb(b.usage.used_bytes), gb(b.usage.total_bytes)),
+            }
+        })
+        .collect()
+}
+
+/// Mounts the watchdog has marked degraded. Names come from the saved
+/// config, not `list_mounts`, whose mountpoint checks would stall on
+/// exactly these mounts.
+fn check_mount_health(_metrics: &SystemMetrics) -> Vec<Issue> {
+    crate::storage::load_config().mounts
+        .into_iter()
+        .filter_map(|m| {
+            let h = crate::storage::mount_health(&m.id).filter(|h| h.state == "degraded")?;
+            Some(Issue {
+                severity: "critical".into(),
+                category: "storage".into(),
+                title: format!("Mount '{}' is not responding", m.name),
+                detail: format!("{} — {} ({} remount attempt(s) since {})",
+                    m.mount_point, h.error.unwrap_or_default(), h.remount_attempts,
+                    h.since.unwrap_or_default()),
+            })
+        })
+        .collect()
+}
+
+/// Baseline findings as issues, with the fix appended to the detail.
+fn security_issues(findings: Vec<crate::security_baseline::Finding>) -> Vec<Issue> {
+    findings.into_iter().map(|f| Issue {
+        severity: f.severity.into(),
+        category: "security".into(),
+        title: f.title,
+        detail: format!("{} Fix: {}", f.detail, f.remediation),
+    }).collect()
+}
+
+fn check_sec_sshd(_metrics: &SystemMetrics) -> Vec<Issue> {
+    security_issues(crate::security_baseline::check_sshd())
+}
+
+fn check_sec_open_ports(_metrics: &SystemMetrics) -> Vec<Issue> {
+    security_issues(crate::security_baseline::check_open_ports())
+}
+
+fn check_sec_world_writable(_metrics: &SystemMetrics) -> Vec<Issue> {
+    security_issues(crate::security_baseline::check_world_writable())
+}
+
+fn check_sec_kernel(_metrics: &SystemMetrics) -> Vec<Issue> {
+    security_issues(crate::security_baseline::check_kernel())
+}
+
+fn check_sec_tls(_metrics: &SystemMetrics) -> Vec<Issue> {
+    security_issues(crate::security_baseline::check_tls())
+}
+
+/// LXC rootfs usage against its quota. Only backends with a real
+/// per-container limit count — a directory rootfs reports the parent
+/// filesystem, which `disk_space` already covers.
+fn check_container_disk_quota(_metrics: &SystemMetrics) -> Vec<Issue> {
+    use crate::containers::lxc_storage::{self, LxcBackend};
+    let mut issues = Vec::new();
+    for c in crate::containers::lxc_list_all_cached() {
+        if c.state != "running" { continue; }
+        let Ok(info) = lxc_storage::inspect(&c.name) else { continue; };
+        if !matches!(info.backend, LxcBackend::Zfs | LxcBackend::Lvm | LxcBackend::Proxmox) { continue; }
+        let (Some(size), Some(used)) = (info.size_bytes, info.used_bytes) else { continue; };
+        if size == 0 { continue; }
+        let pct = used as f64 / size as f64 * 100.0;
+        if pct < 90.0 { continue; }
+        issues.push(Issue {
+            severity: if pct >= 98.0 { "critical" } else { "warning" }.into(),
+            category: "container".into(),
+            title: format!("Container '{}' rootfs {:.0}% of quota", c.name, pct),
+            detail: format!("{:.1} GB used of {:.1} GB — grow it from the container's Storage tab",
+                used as f64 / 1_073_741_824.0, size as f64 / 1_073_741_824.0),
+        });
+    }
+    issues
+}
+
+fn check_reclaimable_space(_metrics: &SystemMetrics) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    // Journal logs
+    if let Ok(output) = std::process::Command::new("journalctl")
+        .args(["--disk-usage"])
+        .output()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // Parse "Archived and active journals take up 1.2G in the file system."
+        if let Some(size_str) = stdout.split("take up ").nth(1).and_then(|s| s.split(' ').next()) {
+            let size_mb = parse_size_to_mb(size_str);
+            if size_mb > 500.0 {
+                issues.push(Issue {
+                    severity: "info".into(),
+                    category: "disk".into(),
+                    title: format!("Journal logs using {}", size_str),
+                    detail: format!("Run 'journalctl --vacuum-size=200M' to reclaim space"),
+                });
+            }
+        }
+    }
+
+    // Package cache (apt)
+    if let Ok(output) = std::process::Command::new("du")
+        .args(["-sh", "/var/cache/apt/archives"])
+        .output()
+    {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(size_str) = stdout.split_whitespace().next() {
+                let size_mb = parse_size_to_mb(size_str);
+                if size_mb > 200.0 {
+                    issues.push(Issue {
+                        severity: "info".into(),
+                        category: "disk".into(),
+                        title: format!("APT cache using {}", size_str),
+                        detail: "Run 'apt clean' to reclaim space".into(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Package cache (dnf/yum)
+    if let Ok(output) = std::process::Command::new("du")
+        .args(["-sh", "/var/cache/dnf"])
+        .output()
+    {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(size_str) = stdout.split_whitespace().next() {
+                let size_mb = parse_size_to_mb(size_str);
+                if size_mb > 200.0 {
+                    issues.push(Issue {
+                        severity: "info".into(),
+                        category: "disk".into(),
+                        title: format!("DNF cache using {}", size_str),
+                        detail: "Run 'dnf clean all' to reclaim space".into(),
+                    });
+                }
+            }
+        }
+    }
+
+    // /tmp usage
+    if let Ok(output) = std::process::Command::new("du")
+        .args(["-sh", "/tmp"])
+        .output()
+    {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(size_str) = stdout.split_whitespace().next() {
+                let size_mb = parse_size_to_mb(size_str);
+                if size_mb > 500.0 {
+                    issues.push(Issue {
+                        severity: "info".into(),
+                        category: "disk".into(),
+                        title: format!("/tmp using {}", size_str),
+                        detail: "Temporary files may be safe to clean up".into(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Docker unused images
+    if let Ok(output) = std::process::Command::new("docker")
+        .args(["system", "df", "--format", "{{.Reclaimable}}"])
+        .output()
+    {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            // Sum up any reclaimable amounts
+            let mut total_mb = 0.0f64;
+            let mut total_str = String::new();
+            for line in stdout.lines() {
+                let clean = line.trim().split('(').next().unwrap_or("").trim();
+                if !clean.is_empty() && clean != "0B" {
+                    let mb = parse_size_to_mb(clean);
+                    total_mb += mb;
+                    if total_str.is_empty() { total_str = clean.to_string(); }
+                }
+            }
+            if total_mb > 500.0 {
+                issues.push(Issue {
+                    severity: "info".into(),
+                    category: "container".into(),
+                    title: format!("Docker reclaimable space: {:.0} MB", total_mb),
+                    detail: "Run 'docker system prune' to reclaim unused images/containers".into(),
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_array_and_lines_are_parsed() {
+        let arr = r#"[{"severity":"critical","title":"Backup stale","detail":"3 days","category":"backup"},{"title":"x"}]"#;
+        let issues = parse_script_output("b.sh", arr, Some(0));
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, "critical");
+        assert_eq!(issues[0].category, "backup");
+        assert_eq!(issues[1].severity, "warning");
+        assert_eq!(issues[1].category, "custom");
+
+        let lines = "{\"title\":\"a\",\"severity\":\"info\"}\n{\"title\":\"b\"}";
+        let issues = parse_script_output("l.sh", lines, Some(0));
+        assert_eq!(issues.iter().map(|i| i.title.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(issues[0].severity, "info");
+    }
+
+    #[test]
+    fn nagios_exit_codes_map_to_severity() {
+        assert!(parse_script_output("n", "OK all fine", Some(0)).is_empty());
+        let w = parse_script_output("n", "WARNING queue 120\nqueue length above 100", Some(1));
+        assert_eq!(w[0].severity, "warning");
+        assert_eq!(w[0].title, "WARNING queue 120");
+        assert_eq!(w[0].detail, "queue length above 100");
+        assert_eq!(parse_script_output("n", "CRITICAL", Some(2))[0].severity, "critical");
+        assert!(parse_script_output("n", "", Some(124))[0].title.contains("timed out"));
+        assert!(parse_script_output("n", "UNKNOWN", Some(3))[0].title.contains("failed"));
+        assert!(parse_script_output("n", "", None)[0].detail.contains("signal"));
+    }
+
+    #[test]
+    fn unsafe_scripts_are_refused() {
+        assert_eq!(script_unsafe_reason(0, 0o100755), None);
+        assert!(script_unsafe_reason(1000, 0o100755).is_some());
+        assert!(script_unsafe_reason(0, 0o100775).is_some());
+        assert!(script_unsafe_reason(0, 0o100757).is_some());
+        assert!(script_unsafe_reason(0, 0o100644).is_some());
+    }
+
+    #[test]
+    fn enable_toggle_round_trips() {
+        let mut cfg = IssueChecksConfig::default();
+        assert!(cfg.is_enabled("swap"));
+        cfg.set_enabled("swap", false);
+        cfg.set_enabled("swap", false);
+        assert_eq!(cfg.disabled, ["swap"]);
+        cfg.set_enabled("swap", true);
+        assert!(cfg.is_enabled("swap") && cfg.disabled.is_empty());
+    }
+
+    #[test]
+    fn builtin_ids_are_unique() {
+        let mut ids: Vec<&str> = BUILTIN_CHECKS.iter().map(|c| c.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), BUILTIN_CHECKS.len());
+    }
+}
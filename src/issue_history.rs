@@ -0,0 +1,466 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Issue history and mean-time-to-resolution, for ops reviews.
+//!
+//! Every `collect_issues` run — the Issues page, the scheduled alert scan,
+//! a peer asking for `/api/issues/scan`, or the sampler here every
+//! fifteen minutes — is folded into `<issue_history>`: an issue that
+//! wasn't open before opens an occurrence (first seen), one that still
+//! shows moves its last-seen on, and an open one that's gone from the
+//! scan is resolved at that scan's time.
+//!
+//! Issues are matched across scans by category and title with the
+//! numbers taken out, so "Disk / low on space (9.8 GB free)" is the same
+//! issue when it reads 9.6 GB next time. A change of severity (low on
+//! space → almost full) words the title differently and counts as a new
+//! issue.
+//!
+//! `/api/issues/history` returns the occurrences overlapping a window
+//! (ninety days by default) and, per category, how many there were and
+//! how long the resolved ones took. Like the availability report, the
+//! node serving it asks its peers for their own occurrences. Resolved
+//! occurrences are kept for 400 days.
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+
+use crate::agent::Node;
+use crate::api::{AppState, Issue};
+
+const SAMPLE_SECS: u64 = 15 * 60;
+const KEEP_SECS: i64 = 400 * 86400;
+/// Hard cap on stored occurrences; the oldest resolved ones go first.
+const MAX_OCCURRENCES: usize = 20_000;
+
+/// One stretch of time an issue was showing on this node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub id: u64,
+    /// Category and title with numbers normalised — see [`issue_key`].
+    pub key: String,
+    pub category: String,
+    /// Worst severity seen while open.
+    pub severity: String,
+    /// Title and detail as last seen.
+    pub title: String,
+    #[serde(default)]
+    pub detail: String,
+    /// Unix seconds.
+    pub first_seen: i64,
+    pub last_seen: i64,
+    /// `None` while the issue is still showing.
+    #[serde(default)]
+    pub resolved_at: Option<i64>,
+    /// Scans that reported it.
+    #[serde(default)]
+    pub sightings: u64,
+    /// Filled in by the report, not stored.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub node: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub node_id: String,
+}
+
+impl Occurrence {
+    /// Seconds from first seen to resolved, for resolved occurrences.
+    pub fn time_to_resolve(&self) -> Option<i64> {
+        self.resolved_at.map(|r| (r - self.first_seen).max(0))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub occurrences: Vec<Occurrence>,
+    /// When the last scan was recorded, so the sampler can skip a tick
+    /// that a manual or scheduled scan already covered.
+    #[serde(default)]
+    pub last_scan: i64,
+}
+
+/// Per-category totals for a window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub category: String,
+    pub occurrences: usize,
+    pub resolved: usize,
+    pub open: usize,
+    /// Mean, median and longest time to resolution of the resolved ones.
+    pub mttr_secs: Option<i64>,
+    pub median_secs: Option<i64>,
+    pub longest_secs: Option<i64>,
+}
+
+fn path() -> String {
+    crate::paths::get().issue_history
+}
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Matching key for an issue: category and title, with each number that
+/// starts a word (and any unit stuck to it, `92%`, `2.1G`) replaced by `#`.
+/// Digits inside a name (`nvme0n1`, `web-2`) are left alone.
+pub fn issue_key(category: &str, title: &str) -> String {
+    let mut out = String::with_capacity(category.len() + title.len() + 1);
+    out.push_str(category);
+    out.push(':');
+    let chars: Vec<char> = title.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let starts_word = i == 0 || !(chars[i - 1].is_alphanumeric() || matches!(chars[i - 1], '-' | '_' | '.'));
+        if c.is_ascii_digit() && starts_word {
+            while i < chars.len() && (chars[i].is_ascii_digit() || (chars[i] == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))) {
+                i += 1;
+            }
+            while i < chars.len() && (chars[i].is_alphabetic() || chars[i] == '%') {
+                i += 1;
+            }
+            out.push('#');
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+fn severity_rank(s: &str) -> u8 {
+    match s { "critical" => 2, "warning" => 1, _ => 0 }
+}
+
+/// Fold one scan's issues into the history at time `now`.
+pub fn apply(history: &mut History, issues: &[Issue], now: i64) {
+    let mut open: HashMap<String, usize> = history.occurrences.iter().enumerate()
+        .filter(|(_, o)| o.resolved_at.is_none())
+        .map(|(i, o)| (o.key.clone(), i))
+        .collect();
+    let mut seen = std::collections::HashSet::new();
+    for issue in issues {
+        let key = issue_key(&issue.category, &issue.title);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        if let Some(&i) = open.get(&key) {
+            let o = &mut history.occurrences[i];
+            o.last_seen = now;
+            o.title = issue.title.clone();
+            o.detail = issue.detail.clone();
+            o.sightings += 1;
+            if severity_rank(&issue.severity) > severity_rank(&o.severity) {
+                o.severity = issue.severity.clone();
+            }
+            continue;
+        }
+        history.next_id += 1;
+        history.occurrences.push(Occurrence {
+            id: history.next_id,
+            key: key.clone(),
+            category: issue.category.clone(),
+            severity: issue.severity.clone(),
+            title: issue.title.clone(),
+            detail: issue.detail.clone(),
+            first_seen: now,
+            last_seen: now,
+            resolved_at: None,
+            sightings: 1,
+            ..Default::default()
+        });
+        open.insert(key, history.occurrences.len() - 1);
+    }
+    for (key, i) in open {
+        if !seen.contains(&key) {
+            history.occurrences[i].resolved_at = Some(now);
+        }
+    }
+    history.last_scan = now;
+    prune(history, now);
+}
+
+fn prune(history: &mut History, now: i64) {
+    let cutoff = now - KEEP_SECS;
+    history.occurrences.retain(|o| o.resolved_at.is_none_or(|r| r >= cutoff));
+    let excess = history.occurrences.len().saturating_sub(MAX_OCCURRENCES);
+    if excess > 0 {
+        // Occurrences are pushed in first-seen order; drop the oldest
+        // resolved ones, never an open one.
+        let mut dropped = 0;
+        history.occurrences.retain(|o| {
+            if dropped < excess && o.resolved_at.is_some() {
+                dropped += 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+static HISTORY: LazyLock<Mutex<Option<History>>> = LazyLock::new(|| Mutex::new(None));
+
+fn load() -> History {
+    std::fs::read_to_string(path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Record a scan of this node. Called by `collect_issues`; blocking.
+pub fn record(issues: &[Issue]) {
+    let mut guard = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let history = guard.get_or_insert_with(load);
+    apply(history, issues, now_secs());
+    let written = serde_json::to_string(history)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            if let Some(dir) = std::path::Path::new(&path()).parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(path(), json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = written {
+        tracing::warn!("issue history: failed to write {}: {}", path(), e);
+    }
+}
+
+/// This node's occurrences overlapping `[since, until]`, optionally of
+/// one category. Blocking.
+pub fn query_local(since: i64, until: i64, category: &str) -> Vec<Occurrence> {
+    let mut guard = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let history = guard.get_or_insert_with(load);
+    history.occurrences.iter()
+        .filter(|o| o.first_seen <= until && o.resolved_at.is_none_or(|r| r >= since))
+        .filter(|o| category.is_empty() || o.category == category)
+        .cloned()
+        .collect()
+}
+
+/// Open critical and warning issues on this node, as of the last scan.
+pub fn open_counts() -> (u64, u64) {
+    let mut guard = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let history = guard.get_or_insert_with(load);
+    history.occurrences.iter()
+        .filter(|o| o.resolved_at.is_none())
+        .fold((0, 0), |(c, w), o| match o.severity.as_str() {
+            "critical" => (c + 1, w),
+            "warning" => (c, w + 1),
+            _ => (c, w),
+        })
+}
+
+/// Per-category stats for `occurrences`, sorted by category, and the
+/// same figures across all of them (category `all`). Only occurrences
+/// resolved by `until` count towards the resolution times.
+pub fn stats(occurrences: &[Occurrence], until: i64) -> (Vec<CategoryStats>, CategoryStats) {
+    fn summarise(category: &str, list: &[&Occurrence], until: i64) -> CategoryStats {
+        let mut times: Vec<i64> = list.iter()
+            .filter(|o| o.resolved_at.is_some_and(|r| r <= until))
+            .filter_map(|o| o.time_to_resolve())
+            .collect();
+        times.sort_unstable();
+        let n = times.len();
+        let median = match n {
+            0 => None,
+            _ if n % 2 == 1 => Some(times[n / 2]),
+            _ => Some((times[n / 2 - 1] + times[n / 2]) / 2),
+        };
+        CategoryStats {
+            category: category.to_string(),
+            occurrences: list.len(),
+            resolved: n,
+            open: list.len() - n,
+            mttr_secs: (n > 0).then(|| times.iter().sum::<i64>() / n as i64),
+            median_secs: median,
+            longest_secs: times.last().copied(),
+        }
+    }
+    let mut by_category: BTreeMap<&str, Vec<&Occurrence>> = BTreeMap::new();
+    for o in occurrences {
+        by_category.entry(o.category.as_str()).or_default().push(o);
+    }
+    let rows = by_category.iter().map(|(c, list)| summarise(c, list, until)).collect();
+    let all: Vec<&Occurrence> = occurrences.iter().collect();
+    (rows, summarise("all", &all, until))
+}
+
+/// This node's occurrences for the window, labelled with the node.
+pub fn collect_local(self_node: &Node, since: i64, until: i64, category: &str) -> Vec<Occurrence> {
+    let mut list = query_local(since, until, category);
+    for o in &mut list {
+        o.node = self_node.hostname.clone();
+        o.node_id = self_node.id.clone();
+    }
+    list
+}
+
+/// Occurrences from this node and every online WolfStack peer. A peer
+/// that doesn't answer (or predates issue history) is left out.
+pub async fn collect_cluster(state: &web::Data<AppState>, since: i64, until: i64, category: &str) -> Vec<Occurrence> {
+    let nodes = state.cluster.get_all_nodes();
+    let mut list = match nodes.iter().find(|n| n.is_self).cloned() {
+        Some(me) => {
+            let category = category.to_string();
+            web::block(move || collect_local(&me, since, until, &category)).await.unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let peers: Vec<Node> = nodes.into_iter().filter(|n| !n.is_self && n.online && n.node_type == "wolfstack").collect();
+    let secret = state.cluster_secret.clone();
+    let path = format!(
+        "/api/issues/history?scope=local&since={}&until={}&category={}&limit=0",
+        since, until, urlencoding::encode(category),
+    );
+    let handles: Vec<_> = peers.into_iter().map(|peer| {
+        let secret = secret.clone();
+        let path = path.clone();
+        tokio::spawn(async move {
+            for url in crate::api::build_node_urls(&peer.address, peer.port, &path) {
+                let Ok(resp) = crate::api::API_HTTP_CLIENT.get(&url)
+                    .timeout(std::time::Duration::from_secs(30))
+                    .header("X-WolfStack-Secret", &secret)
+                    .send().await
+                else { continue };
+                if !resp.status().is_success() { continue; }
+                if let Ok(v) = resp.json::<serde_json::Value>().await
+                    && let Ok(list) = serde_json::from_value::<Vec<Occurrence>>(v["occurrences"].clone())
+                {
+                    return list;
+                }
+            }
+            Vec::new()
+        })
+    }).collect();
+    for h in handles {
+        if let Ok(peer_list) = h.await {
+            list.extend(peer_list);
+        }
+    }
+    list
+}
+
+pub fn to_csv(occurrences: &[Occurrence]) -> String {
+    use crate::inventory_report::csv_field;
+    let time = |t: i64| chrono::DateTime::from_timestamp(t, 0).map(|d| d.to_rfc3339()).unwrap_or_default();
+    let mut out = String::from("node,category,severity,title,first_seen,last_seen,resolved_at,resolve_secs,detail\n");
+    for o in occurrences {
+        out.push_str(&[
+            csv_field(&o.node),
+            csv_field(&o.category),
+            csv_field(&o.severity),
+            csv_field(&o.title),
+            time(o.first_seen),
+            time(o.last_seen),
+            o.resolved_at.map(time).unwrap_or_default(),
+            o.time_to_resolve().map(|s| s.to_string()).unwrap_or_default(),
+            csv_field(&o.detail),
+        ].join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Start the sampler: a scan every fifteen minutes unless something else
+/// scanned this node in the meantime, so history builds up without
+/// anyone opening the Issues page.
+pub fn start(state: web::Data<AppState>) {
+    tokio::spawn(async move {
+        // Let the inventory collectors fill their slots first.
+        tokio::time::sleep(std::time::Duration::from_secs(120)).await;
+        loop {
+            let last_scan = {
+                let mut guard = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+                guard.get_or_insert_with(load).last_scan
+            };
+            if now_secs() - last_scan >= SAMPLE_SECS as i64 - 60 {
+                let st = state.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    let metrics = crate::monitoring::inventory::metrics_blocking(&st);
+                    crate::api::collect_issues(&metrics);
+                }).await;
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(SAMPLE_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(category: &str, severity: &str, title: &str) -> Issue {
+        Issue { severity: severity.into(), category: category.into(), title: title.into(), detail: String::new() }
+    }
+
+    #[test]
+    fn key_ignores_changing_numbers_but_not_names() {
+        assert_eq!(issue_key("disk", "Disk / low on space (9.8 GB free)"), "disk:Disk / low on space (# GB free)");
+        assert_eq!(issue_key("disk", "Journal logs using 2.1G"), issue_key("disk", "Journal logs using 812M"));
+        assert_eq!(issue_key("container", "Container 'web-2' rootfs 91% of quota"), "container:Container 'web-2' rootfs # of quota");
+        assert_ne!(issue_key("disk", "Disk /dev/nvme0n1 is failing (SMART)"), issue_key("disk", "Disk /dev/nvme1n1 is failing (SMART)"));
+    }
+
+    #[test]
+    fn occurrences_open_update_and_resolve() {
+        let mut h = History::default();
+        apply(&mut h, &[issue("disk", "warning", "Disk / low on space (9.8 GB free)")], 100);
+        apply(&mut h, &[
+            issue("disk", "critical", "Disk / low on space (9.1 GB free)"),
+            issue("service", "critical", "Service nginx.service failed"),
+        ], 200);
+        assert_eq!(h.occurrences.len(), 2);
+        let disk = &h.occurrences[0];
+        assert_eq!((disk.first_seen, disk.last_seen, disk.sightings), (100, 200, 2));
+        assert_eq!(disk.severity, "critical");
+        assert_eq!(disk.title, "Disk / low on space (9.1 GB free)");
+
+        apply(&mut h, &[issue("service", "critical", "Service nginx.service failed")], 300);
+        assert_eq!(h.occurrences[0].resolved_at, Some(300));
+        assert_eq!(h.occurrences[1].resolved_at, None);
+
+        // Coming back is a new occurrence.
+        apply(&mut h, &[issue("disk", "warning", "Disk / low on space (8.0 GB free)")], 400);
+        assert_eq!(h.occurrences.len(), 3);
+        assert_eq!(h.occurrences[1].resolved_at, Some(400));
+        assert_eq!(h.occurrences[2].first_seen, 400);
+        assert_eq!(h.last_scan, 400);
+    }
+
+    #[test]
+    fn old_resolved_occurrences_are_pruned() {
+        let mut h = History::default();
+        apply(&mut h, &[issue("cpu", "warning", "High CPU")], 0);
+        apply(&mut h, &[issue("load", "warning", "High load")], 10);
+        apply(&mut h, &[issue("load", "warning", "High load")], KEEP_SECS + 20);
+        assert_eq!(h.occurrences.len(), 1);
+        assert_eq!(h.occurrences[0].category, "load");
+    }
+
+    #[test]
+    fn stats_per_category() {
+        let occ = |category: &str, first: i64, resolved: Option<i64>| Occurrence {
+            category: category.into(), first_seen: first, resolved_at: resolved, ..Default::default()
+        };
+        let list = vec![
+            occ("disk", 0, Some(100)),
+            occ("disk", 0, Some(300)),
+            occ("disk", 0, None),
+            occ("service", 50, Some(110)),
+        ];
+        let (rows, all) = stats(&list, 1000);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], CategoryStats {
+            category: "disk".into(), occurrences: 3, resolved: 2, open: 1,
+            mttr_secs: Some(200), median_secs: Some(200), longest_secs: Some(300),
+        });
+        assert_eq!(rows[1].mttr_secs, Some(60));
+        assert_eq!((all.occurrences, all.resolved, all.mttr_secs, all.median_secs), (4, 3, Some(153), Some(100)));
+    }
+}
@@ -0,0 +1,349 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Concurrency limits for heavy jobs — backups, restores and migrations.
+//!
+//! Each of these is a disk-bound tar / zstd / qemu-img pipeline (and a
+//! migration adds the network on top), so a handful at once flattens the
+//! node they run on. Every such job takes a slot here before it starts.
+//! A job that finds its category — or the node — full waits in a queue,
+//! ordered by priority then arrival, and starts as soon as a running job
+//! finishes.
+//!
+//! Only queued jobs whose own category has room compete for a free slot,
+//! so a queue of backups never holds up a migration that could run. The
+//! slot is given back when the [`Slot`] is dropped, which also takes a
+//! still-queued job out of the queue if its task is cancelled.
+//!
+//! Limits are per node, saved to `paths.job_limits_config`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Condvar, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Backup,
+    Restore,
+    Migration,
+}
+
+impl Category {
+    fn plural(self) -> &'static str {
+        match self {
+            Category::Backup => "backups",
+            Category::Restore => "restores",
+            Category::Migration => "migrations",
+        }
+    }
+}
+
+/// Queue order. Scheduled work is `Low` so an operator waiting on a
+/// restore isn't stuck behind the night's backups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+const MAX_PER_CATEGORY: u32 = 16;
+const MAX_TOTAL: u32 = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobLimits {
+    /// Heavy jobs at once across every category; 0 = no overall cap.
+    #[serde(default = "default_max_total")]
+    pub max_total: u32,
+    #[serde(default = "default_max_backups")]
+    pub max_backups: u32,
+    #[serde(default = "default_max_restores")]
+    pub max_restores: u32,
+    #[serde(default = "default_max_migrations")]
+    pub max_migrations: u32,
+}
+
+fn default_max_total() -> u32 { 3 }
+fn default_max_backups() -> u32 { 2 }
+fn default_max_restores() -> u32 { 2 }
+fn default_max_migrations() -> u32 { 1 }
+
+impl Default for JobLimits {
+    fn default() -> Self {
+        Self {
+            max_total: default_max_total(),
+            max_backups: default_max_backups(),
+            max_restores: default_max_restores(),
+            max_migrations: default_max_migrations(),
+        }
+    }
+}
+
+impl JobLimits {
+    pub fn for_category(&self, category: Category) -> u32 {
+        match category {
+            Category::Backup => self.max_backups,
+            Category::Restore => self.max_restores,
+            Category::Migration => self.max_migrations,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for c in [Category::Backup, Category::Restore, Category::Migration] {
+            let n = self.for_category(c);
+            if n == 0 || n > MAX_PER_CATEGORY {
+                return Err(format!("Concurrent {} must be 1-{}", c.plural(), MAX_PER_CATEGORY));
+            }
+        }
+        if self.max_total > MAX_TOTAL {
+            return Err(format!("Overall limit must be 0 (none) to {}", MAX_TOTAL));
+        }
+        Ok(())
+    }
+
+    fn load() -> Self {
+        std::fs::read_to_string(crate::paths::get().job_limits_config)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// A running or queued job, as `GET /api/jobs` shows it.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: u64,
+    pub category: Category,
+    pub priority: Priority,
+    pub label: String,
+    /// Unix seconds it was queued, or started once running.
+    pub since: i64,
+}
+
+#[derive(Debug, Default)]
+struct Board {
+    next_id: u64,
+    running: Vec<JobInfo>,
+    queued: Vec<JobInfo>,
+}
+
+impl Board {
+    fn has_room(&self, limits: &JobLimits, category: Category) -> bool {
+        self.running.iter().filter(|j| j.category == category).count() < limits.for_category(category) as usize
+    }
+
+    /// Can queued job `id` start now? It needs room in its category and
+    /// overall, and must be first — by priority, then age — among the
+    /// queued jobs that have room too.
+    fn can_start(&self, limits: &JobLimits, id: u64) -> bool {
+        if limits.max_total > 0 && self.running.len() >= limits.max_total as usize {
+            return false;
+        }
+        let Some(me) = self.queued.iter().find(|j| j.id == id) else { return false };
+        if !self.has_room(limits, me.category) {
+            return false;
+        }
+        self.queued.iter()
+            .filter(|j| self.has_room(limits, j.category))
+            .min_by_key(|j| (std::cmp::Reverse(j.priority), j.id))
+            .is_some_and(|j| j.id == id)
+    }
+
+    /// Why queued job `id` is still waiting.
+    fn waiting_reason(&self, limits: &JobLimits, id: u64) -> String {
+        let Some(me) = self.queued.iter().find(|j| j.id == id) else { return String::new() };
+        if !self.has_room(limits, me.category) {
+            return format!("{} {} already running (limit {})",
+                self.running.iter().filter(|j| j.category == me.category).count(),
+                me.category.plural(), limits.for_category(me.category));
+        }
+        if limits.max_total > 0 && self.running.len() >= limits.max_total as usize {
+            return format!("{} heavy jobs already running on this node (limit {})", self.running.len(), limits.max_total);
+        }
+        "waiting behind higher-priority jobs".to_string()
+    }
+}
+
+static BOARD: LazyLock<(Mutex<Board>, Condvar)> = LazyLock::new(|| (Mutex::new(Board::default()), Condvar::new()));
+
+static LIMITS: LazyLock<RwLock<JobLimits>> = LazyLock::new(|| RwLock::new(JobLimits::load()));
+
+pub fn limits() -> JobLimits {
+    LIMITS.read().map(|l| l.clone()).unwrap_or_default()
+}
+
+/// Save new limits and apply them at once: a raised limit starts queued
+/// jobs straight away; a lowered one lets running jobs finish.
+pub fn set_limits(limits: JobLimits) -> Result<(), String> {
+    limits.validate()?;
+    let json = serde_json::to_string_pretty(&limits).map_err(|e| e.to_string())?;
+    crate::paths::write_secure_atomic(&crate::paths::get().job_limits_config, json).map_err(|e| e.to_string())?;
+    if let Ok(mut l) = LIMITS.write() {
+        *l = limits;
+    }
+    BOARD.1.notify_all();
+    Ok(())
+}
+
+/// A job's claim on the node: queued until [`acquire`] returns it, running
+/// after. Dropping it frees the slot.
+#[derive(Debug)]
+pub struct Slot {
+    id: u64,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let (lock, cvar) = &*BOARD;
+        let mut board = lock.lock().unwrap_or_else(|e| e.into_inner());
+        board.running.retain(|j| j.id != self.id);
+        board.queued.retain(|j| j.id != self.id);
+        cvar.notify_all();
+    }
+}
+
+fn enqueue(category: Category, priority: Priority, label: &str) -> Slot {
+    let mut board = BOARD.0.lock().unwrap_or_else(|e| e.into_inner());
+    board.next_id += 1;
+    let id = board.next_id;
+    board.queued.push(JobInfo {
+        id,
+        category,
+        priority,
+        label: label.to_string(),
+        since: chrono::Utc::now().timestamp(),
+    });
+    Slot { id }
+}
+
+/// Start queued job `id` if it can; otherwise why it can't.
+fn try_start(id: u64) -> Result<(), String> {
+    let limits = limits();
+    let mut board = BOARD.0.lock().unwrap_or_else(|e| e.into_inner());
+    if !board.can_start(&limits, id) {
+        return Err(board.waiting_reason(&limits, id));
+    }
+    if let Some(pos) = board.queued.iter().position(|j| j.id == id) {
+        let mut job = board.queued.remove(pos);
+        job.since = chrono::Utc::now().timestamp();
+        board.running.push(job);
+    }
+    Ok(())
+}
+
+/// Take a slot, waiting for one if the node is busy. `on_queued` is told
+/// why, once, if the job has to wait. For worker threads; async tasks use
+/// [`acquire_async`].
+pub fn acquire(category: Category, priority: Priority, label: &str, on_queued: impl FnOnce(&str)) -> Slot {
+    let slot = enqueue(category, priority, label);
+    let mut on_queued = Some(on_queued);
+    loop {
+        match try_start(slot.id) {
+            Ok(()) => return slot,
+            Err(reason) => {
+                if let Some(tell) = on_queued.take() {
+                    tell(&reason);
+                }
+            }
+        }
+        // The timeout covers a release that lands between try_start and
+        // the wait.
+        let (lock, cvar) = &*BOARD;
+        let board = lock.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = cvar.wait_timeout(board, Duration::from_secs(1));
+    }
+}
+
+/// [`acquire`] for async tasks. Dropping the future while it waits leaves
+/// the queue.
+pub async fn acquire_async(category: Category, priority: Priority, label: &str, on_queued: impl FnOnce(&str)) -> Slot {
+    let slot = enqueue(category, priority, label);
+    let mut on_queued = Some(on_queued);
+    loop {
+        match try_start(slot.id) {
+            Ok(()) => return slot,
+            Err(reason) => {
+                if let Some(tell) = on_queued.take() {
+                    tell(&reason);
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobsStatus {
+    pub limits: JobLimits,
+    pub running: Vec<JobInfo>,
+    /// In the order they'll start.
+    pub queued: Vec<JobInfo>,
+}
+
+pub fn status() -> JobsStatus {
+    let board = BOARD.0.lock().unwrap_or_else(|e| e.into_inner());
+    let mut queued = board.queued.clone();
+    queued.sort_by_key(|j| (std::cmp::Reverse(j.priority), j.id));
+    JobsStatus { limits: limits(), running: board.running.clone(), queued }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: u64, category: Category, priority: Priority) -> JobInfo {
+        JobInfo { id, category, priority, label: String::new(), since: 0 }
+    }
+
+    #[test]
+    fn category_and_total_limits() {
+        let limits = JobLimits::default();
+        let mut board = Board {
+            running: vec![job(1, Category::Backup, Priority::Normal), job(2, Category::Backup, Priority::Normal)],
+            queued: vec![job(3, Category::Backup, Priority::High), job(4, Category::Migration, Priority::Low)],
+            ..Default::default()
+        };
+        // Backups are full; the migration goes ahead of the higher-priority backup.
+        assert!(!board.can_start(&limits, 3));
+        assert!(board.waiting_reason(&limits, 3).starts_with("2 backups already running"));
+        assert!(board.can_start(&limits, 4));
+
+        board.running.push(job(5, Category::Restore, Priority::Normal));
+        assert!(!board.can_start(&limits, 4), "node-wide limit of 3 reached");
+        assert!(board.waiting_reason(&limits, 4).contains("limit 3"));
+        assert!(board.can_start(&JobLimits { max_total: 0, ..limits.clone() }, 4));
+    }
+
+    #[test]
+    fn priority_then_arrival() {
+        let limits = JobLimits::default();
+        let board = Board {
+            queued: vec![
+                job(1, Category::Backup, Priority::Low),
+                job(2, Category::Backup, Priority::Normal),
+                job(3, Category::Backup, Priority::Normal),
+            ],
+            ..Default::default()
+        };
+        assert!(board.can_start(&limits, 2));
+        assert!(!board.can_start(&limits, 3));
+        assert!(!board.can_start(&limits, 1));
+        assert!(!board.can_start(&limits, 99));
+    }
+
+    #[test]
+    fn limits_defaults_and_validation() {
+        let limits: JobLimits = serde_json::from_str("{}").unwrap();
+        assert_eq!(limits, JobLimits::default());
+        assert_eq!(limits.for_category(Category::Migration), 1);
+        assert_eq!(limits.for_category(Category::Backup), 2);
+        assert!(limits.validate().is_ok());
+        assert!(JobLimits { max_migrations: 0, ..limits.clone() }.validate().is_err());
+        assert!(JobLimits { max_total: 65, ..limits.clone() }.validate().is_err());
+        assert!(JobLimits { max_total: 0, ..limits }.validate().is_ok());
+    }
+}
@@ -0,0 +1,221 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Startup readiness and graceful shutdown.
+//!
+//! The daemon is *starting* until its listeners are bound, *ready* while
+//! it serves, and *stopping* once SIGTERM or SIGINT arrives. `/readyz`
+//! answers 200 only while ready, so a load balancer stops sending traffic
+//! the moment shutdown begins; `/healthz` answers 200 whenever the
+//! process can answer at all.
+//!
+//! On the signal ([`on_shutdown_signal`]) the listeners stop accepting and
+//! drain their in-flight requests, background loops that wait on
+//! [`stopping`] finish their current tick and exit, and once the servers
+//! have returned `main` calls [`persist`] to write cluster state and the
+//! metrics histories before the process exits. Previously actix's own
+//! handler stopped the servers and everything in memory since the last
+//! periodic save was lost.
+//!
+//! Under systemd the phases are also reported with `sd_notify`: `READY=1`
+//! when ready (so a `Type=notify` unit's start only succeeds once the
+//! ports are bound), `STOPPING=1` on shutdown, and `WATCHDOG=1` pings from
+//! [`spawn_watchdog`] when the unit sets `WatchdogSec=`. The pings come
+//! from a task on the async runtime, so a runtime wedged by a blocking
+//! call stops them and systemd restarts the daemon.
+
+use actix_web::dev::ServerHandle;
+use actix_web::web;
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::LazyLock;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Starting,
+    Ready,
+    Stopping,
+}
+
+impl Phase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Starting => "starting",
+            Phase::Ready => "ready",
+            Phase::Stopping => "stopping",
+        }
+    }
+}
+
+static PHASE: AtomicU8 = AtomicU8::new(0);
+
+/// Flips to `true` when shutdown begins; background loops watch it.
+static SHUTDOWN: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::channel(false).0);
+
+pub fn phase() -> Phase {
+    match PHASE.load(Ordering::Acquire) {
+        0 => Phase::Starting,
+        1 => Phase::Ready,
+        _ => Phase::Stopping,
+    }
+}
+
+/// The listeners are bound. No-op once shutdown has begun.
+pub fn mark_ready() {
+    if PHASE.compare_exchange(0, 1, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+        sd_notify("READY=1\nSTATUS=Serving");
+    }
+}
+
+/// Enter the stopping phase and wake everything waiting on [`stopping`].
+/// Returns false if shutdown had already begun.
+pub fn begin_shutdown() -> bool {
+    if PHASE.swap(2, Ordering::AcqRel) == 2 {
+        return false;
+    }
+    SHUTDOWN.send_replace(true);
+    sd_notify("STOPPING=1\nSTATUS=Shutting down");
+    true
+}
+
+/// Resolves once shutdown has begun (immediately if it already has).
+pub async fn stopping() {
+    let mut rx = SHUTDOWN.subscribe();
+    let _ = rx.wait_for(|stopping| *stopping).await;
+}
+
+/// Run `fut` unless shutdown begins first; `None` means it was abandoned.
+/// For a background loop's sleep, so the loop exits between ticks rather
+/// than being torn down halfway through one.
+pub async fn unless_stopping<F: Future>(fut: F) -> Option<F::Output> {
+    tokio::select! {
+        out = fut => Some(out),
+        _ = stopping() => None,
+    }
+}
+
+/// Stop `servers` gracefully on SIGTERM or SIGINT. The servers must be
+/// built with `disable_signals()` so actix doesn't act on the signal
+/// first. A second signal while draining stops them immediately.
+pub fn on_shutdown_signal(servers: Vec<ServerHandle>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    tokio::spawn(async move {
+        let (mut term, mut int) = match (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) {
+            (Ok(t), Ok(i)) => (t, i),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("shutdown: signal handlers not installed, stopping will not be graceful: {}", e);
+                return;
+            }
+        };
+        let name = tokio::select! {
+            _ = term.recv() => "SIGTERM",
+            _ = int.recv() => "SIGINT",
+        };
+        info!("shutdown: {} received — no longer ready, draining requests", name);
+        begin_shutdown();
+        let graceful = futures::future::join_all(servers.iter().map(|s| s.stop(true)));
+        tokio::select! {
+            _ = graceful => {}
+            _ = term.recv() => {}
+            _ = int.recv() => {}
+        }
+        // Forced: a no-op for any server that already finished draining.
+        futures::future::join_all(servers.iter().map(|s| s.stop(false))).await;
+    });
+}
+
+/// Send `state` to systemd's notification socket, if we were started
+/// with one. Returns false when there is no socket or the send failed.
+pub fn sd_notify(state: &str) -> bool {
+    use std::os::unix::net::UnixDatagram;
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else { return false };
+    let Ok(sock) = UnixDatagram::unbound() else { return false };
+    let path = std::path::PathBuf::from(path);
+    // A leading '@' names a Linux abstract socket.
+    #[cfg(target_os = "linux")]
+    if let Some(name) = path.to_str().and_then(|p| p.strip_prefix('@')) {
+        use std::os::linux::net::SocketAddrExt;
+        return std::os::unix::net::SocketAddr::from_abstract_name(name)
+            .and_then(|addr| sock.send_to_addr(state.as_bytes(), &addr))
+            .is_ok();
+    }
+    sock.send_to(state.as_bytes(), &path).is_ok()
+}
+
+/// The watchdog interval systemd asked for, if it is meant for us.
+fn watchdog_interval(usec: Option<&str>, pid: Option<&str>) -> Option<std::time::Duration> {
+    if pid.is_some_and(|p| p.trim().parse::<u32>().ok() != Some(std::process::id())) {
+        return None;
+    }
+    let usec: u64 = usec?.trim().parse().ok().filter(|u| *u > 0)?;
+    Some(std::time::Duration::from_micros(usec))
+}
+
+/// Ping systemd's watchdog at half the unit's `WatchdogSec=`. No-op when
+/// the unit has no watchdog.
+pub fn spawn_watchdog() {
+    let usec = std::env::var("WATCHDOG_USEC").ok();
+    let pid = std::env::var("WATCHDOG_PID").ok();
+    let Some(interval) = watchdog_interval(usec.as_deref(), pid.as_deref()) else { return };
+    info!("systemd watchdog: pinging every {:?}", interval / 2);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval / 2);
+        loop {
+            tick.tick().await;
+            sd_notify("WATCHDOG=1");
+        }
+    });
+}
+
+/// Write the in-memory state a restart would otherwise lose: the cluster
+/// snapshot and both metrics histories. Called once the listeners have
+/// stopped, so no request can change them underneath.
+pub async fn persist(state: web::Data<crate::api::AppState>) {
+    let done = tokio::task::spawn_blocking(move || {
+        state.cluster.save_snapshot();
+        let path = crate::paths::get().metrics_history;
+        if let Err(e) = state.metrics_history.read().unwrap().save(&path) {
+            warn!("shutdown: {}", e);
+        }
+        if let Err(e) = state.predictive_metrics.read().unwrap().save() {
+            warn!("shutdown: {}", e);
+        }
+    }).await;
+    match done {
+        Ok(()) => info!("shutdown: cluster state and metrics history saved"),
+        Err(e) => warn!("shutdown: saving state failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_wakes_waiters_and_is_sticky() {
+        mark_ready();
+        assert_eq!(phase(), Phase::Ready);
+        let waiter = tokio::spawn(stopping());
+        assert!(begin_shutdown());
+        assert!(!begin_shutdown());
+        waiter.await.unwrap();
+        // Late subscribers see it too, and nothing un-stops the daemon.
+        stopping().await;
+        assert_eq!(unless_stopping(std::future::pending::<()>()).await, None);
+        mark_ready();
+        assert_eq!(phase(), Phase::Stopping);
+    }
+
+    #[test]
+    fn watchdog_interval_honours_the_pid() {
+        let me = std::process::id().to_string();
+        assert_eq!(watchdog_interval(Some("120000000"), None), Some(std::time::Duration::from_secs(120)));
+        assert_eq!(watchdog_interval(Some("120000000"), Some(&me)), Some(std::time::Duration::from_secs(120)));
+        assert_eq!(watchdog_interval(Some("120000000"), Some("1")), None);
+        assert_eq!(watchdog_interval(Some("0"), None), None);
+        assert_eq!(watchdog_interval(None, None), None);
+    }
+}
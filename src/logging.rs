@@ -0,0 +1,412 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Log output: text or JSON lines, a filter that can be changed at
+//! runtime, and request IDs.
+//!
+//! - **Format** — `logging.format` in wolfstack.toml (or
+//!   `WOLFSTACK_LOG_FORMAT`). `json` writes one object per line with
+//!   `timestamp`, `level`, `target`, `message`, the event's fields and the
+//!   fields of every enclosing span, ready for a log pipeline. Fixed at
+//!   startup.
+//! - **Filter** — `RUST_LOG` if set, else `logging.filter`, else
+//!   [`DEFAULT_FILTER`]. `PUT /api/logging` changes it (whole filter or one
+//!   module) until the next restart or config reload.
+//! - **Request IDs** — [`request_id`] tags every API call with an
+//!   `X-Request-Id` (the caller's, if it sent a sane one) and runs the
+//!   handler inside a `request` span carrying it, so anything logged while
+//!   serving the call — including [`run_traced`] command executions and
+//!   node-proxy hops, which forward the header — shares the ID.
+//! - **Recent lines** — the last [`RECENT_LINES`] events that passed the
+//!   filter are kept in memory ([`recent_lines`]) for crash reports.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::HttpMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock, RwLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Instrument, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Filter used when neither `RUST_LOG` nor `logging.filter` is set.
+pub const DEFAULT_FILTER: &str = "wolfstack=info,actix_web=info,actix_http=off";
+
+/// Target of [`run_traced`] events — `wolfstack::exec=debug` shows every
+/// command WolfStack runs on behalf of a request.
+pub const EXEC_TARGET: &str = "wolfstack::exec";
+
+/// Log lines kept in memory for crash reports.
+pub const RECENT_LINES: usize = 200;
+
+const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error", "off"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+struct FilterState {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// Filter in force right now.
+    current: String,
+    /// Filter from startup or the last config reload — what reset returns to.
+    configured: String,
+}
+
+static FILTER: OnceLock<RwLock<FilterState>> = OnceLock::new();
+static FORMAT: OnceLock<LogFormat> = OnceLock::new();
+
+/// Install the global subscriber. Call once, before anything logs.
+pub fn init(format: LogFormat, configured_filter: Option<&str>) {
+    let filter = std::env::var("RUST_LOG").ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| configured_filter.map(String::from))
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string());
+    let env_filter = EnvFilter::try_new(&filter).unwrap_or_else(|e| {
+        eprintln!("Invalid log filter {:?} ({}), using {:?}", filter, e, DEFAULT_FILTER);
+        EnvFilter::new(DEFAULT_FILTER)
+    });
+    let (filter_layer, handle) = reload::Layer::new(env_filter);
+    let json = format == LogFormat::Json;
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(RecentLinesLayer)
+        .with(json.then_some(SpanFieldsLayer))
+        .with(json.then(|| tracing_subscriber::fmt::layer().event_format(JsonFormat)))
+        .with((!json).then(tracing_subscriber::fmt::layer))
+        .init();
+    let _ = FORMAT.set(format);
+    let _ = FILTER.set(RwLock::new(FilterState { handle, current: filter.clone(), configured: filter }));
+}
+
+pub fn format() -> LogFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// `(current, configured)` filter strings.
+pub fn filters() -> (String, String) {
+    match FILTER.get() {
+        Some(f) => {
+            let f = f.read().unwrap();
+            (f.current.clone(), f.configured.clone())
+        }
+        None => (String::new(), String::new()),
+    }
+}
+
+/// Check a filter string without applying it.
+pub fn validate_filter(filter: &str) -> Result<(), String> {
+    EnvFilter::try_new(filter).map(|_| ()).map_err(|e| format!("Invalid log filter: {}", e))
+}
+
+/// Replace the whole filter.
+pub fn set_filter(filter: &str) -> Result<(), String> {
+    let filter = filter.trim();
+    let parsed = EnvFilter::try_new(filter).map_err(|e| format!("Invalid log filter: {}", e))?;
+    let state = FILTER.get().ok_or("Logging is not initialised")?;
+    let mut state = state.write().unwrap();
+    state.handle.reload(parsed).map_err(|e| e.to_string())?;
+    state.current = filter.to_string();
+    Ok(())
+}
+
+/// Set one module's level, keeping the other directives. `level` of
+/// `"default"` drops the module's directive so it inherits again.
+pub fn set_module_level(module: &str, level: &str) -> Result<(), String> {
+    let module = module.trim();
+    let level = level.trim().to_ascii_lowercase();
+    if module.is_empty() || !module.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+        return Err(format!("Invalid module path {:?}", module));
+    }
+    if level != "default" && !LEVELS.contains(&level.as_str()) {
+        return Err(format!("Invalid level {:?} (use {} or default)", level, LEVELS.join(", ")));
+    }
+    let current = filters().0;
+    set_filter(&with_module_level(&current, module, &level))
+}
+
+/// Back to the configured filter, dropping runtime changes.
+pub fn reset() -> Result<(), String> {
+    let configured = filters().1;
+    set_filter(&configured)
+}
+
+/// A config reload changed `logging.filter`. `RUST_LOG` still wins, as it
+/// did at startup.
+pub fn set_configured(filter: Option<&str>) -> Result<(), String> {
+    if std::env::var("RUST_LOG").is_ok_and(|v| !v.trim().is_empty()) {
+        return Ok(());
+    }
+    let filter = filter.unwrap_or(DEFAULT_FILTER);
+    set_filter(filter)?;
+    if let Some(state) = FILTER.get() {
+        state.write().unwrap().configured = filter.to_string();
+    }
+    Ok(())
+}
+
+/// `filter` with `module`'s directive replaced by `module=level`
+/// (or removed, for `"default"`).
+fn with_module_level(filter: &str, module: &str, level: &str) -> String {
+    let new = format!("{}={}", module, level);
+    let mut directives: Vec<&str> = filter.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .filter(|d| d.split('=').next().map(str::trim) != Some(module))
+        .collect();
+    if level != "default" {
+        directives.push(&new);
+    }
+    directives.join(",")
+}
+
+// ─── Request IDs ───
+
+/// The request's ID, stored in the request extensions.
+#[derive(Clone)]
+pub struct RequestId(pub String);
+
+/// Caller-supplied IDs are reused only if short and plain, so they can't
+/// inject anything into log lines or headers.
+fn sane_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// Middleware: assign the request ID, run the handler in its span and
+/// echo it as `X-Request-Id`.
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let id = req.headers().get("X-Request-Id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| sane_request_id(v))
+        .map(String::from)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+    // peer_addr is the real client once reverse_proxy::resolve has run.
+    let client = req.peer_addr().map(|a| a.ip().to_canonical().to_string()).unwrap_or_default();
+    let span = tracing::info_span!("request", request_id = %id, client = %client, method = %req.method(), path = %req.path());
+    let mut res = next.call(req).instrument(span).await?.map_into_boxed_body();
+    if let Ok(v) = actix_web::http::header::HeaderValue::from_str(&id) {
+        res.headers_mut().insert(actix_web::http::header::HeaderName::from_static("x-request-id"), v);
+    }
+    Ok(res)
+}
+
+/// The current request's ID, if the middleware assigned one.
+pub fn request_id_of(req: &actix_web::HttpRequest) -> Option<String> {
+    req.extensions().get::<RequestId>().map(|r| r.0.clone())
+}
+
+// ─── Command tracing ───
+
+/// Hide values of `NAME=value` arguments whose name looks like a secret.
+pub(crate) fn redact_arg(arg: &str) -> String {
+    if let Some((name, _)) = arg.split_once('=') {
+        let upper = name.to_ascii_uppercase();
+        if ["PASS", "SECRET", "TOKEN", "KEY"].iter().any(|s| upper.contains(s)) {
+            return format!("{}=***", name);
+        }
+    }
+    arg.to_string()
+}
+
+/// `Command::output()` plus a debug event (target [`EXEC_TARGET`]) with the
+/// command line, exit status and duration — logged inside the caller's
+/// span, so it carries the request ID of the API call that ran it.
+pub fn run_traced(cmd: &mut std::process::Command) -> std::io::Result<std::process::Output> {
+    let started = std::time::Instant::now();
+    let result = cmd.output();
+    if tracing::enabled!(target: EXEC_TARGET, tracing::Level::DEBUG) {
+        let line: Vec<String> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|a| redact_arg(&a.to_string_lossy()))
+            .collect();
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(out) => tracing::debug!(target: EXEC_TARGET, command = %line.join(" "),
+                exit_code = out.status.code().unwrap_or(-1), elapsed_ms, "command finished"),
+            Err(e) => tracing::debug!(target: EXEC_TARGET, command = %line.join(" "),
+                error = %e, elapsed_ms, "command failed to start"),
+        }
+    }
+    result
+}
+
+// ─── Recent lines ───
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps the last [`RECENT_LINES`] events as plain text lines.
+struct RecentLinesLayer;
+
+impl<S: Subscriber> Layer<S> for RecentLinesLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let text = |v: serde_json::Value| match v {
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        };
+        let mut line = format!(
+            "{} {:>5} {}: {}",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            meta.level(),
+            meta.target(),
+            fields.0.remove("message").map(text).unwrap_or_default(),
+        );
+        for (name, value) in fields.0 {
+            line.push_str(&format!(" {}={}", name, text(value)));
+        }
+        let mut recent = RECENT.lock().unwrap_or_else(|p| p.into_inner());
+        if recent.len() >= RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// The most recent log lines, oldest first. Never blocks, as it runs in
+/// the panic hook: if the buffer is busy the tail comes back empty.
+pub fn recent_lines() -> Vec<String> {
+    match RECENT.try_lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(std::sync::TryLockError::Poisoned(p)) => p.into_inner().iter().cloned().collect(),
+        Err(std::sync::TryLockError::WouldBlock) => Vec::new(),
+    }
+}
+
+// ─── JSON output ───
+
+/// Field values of a span or event, as JSON.
+#[derive(Default)]
+struct JsonFields(serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
+
+/// Keeps each span's fields (e.g. `request_id`) in its extensions so
+/// [`JsonFormat`] can copy them onto every event inside the span.
+struct SpanFieldsLayer;
+
+impl<S> Layer<S> for SpanFieldsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = JsonFields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &tracing::span::Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<JsonFields>() {
+                values.record(fields);
+            }
+        }
+    }
+}
+
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let meta = event.metadata();
+        let mut line = serde_json::Map::new();
+        line.insert("timestamp".into(), chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true).into());
+        line.insert("level".into(), meta.level().to_string().into());
+        line.insert("target".into(), meta.target().into());
+        // Span fields first (outermost to innermost) so the event's own
+        // fields win on a name clash.
+        if let Some(scope) = ctx.event_scope() {
+            let mut spans = Vec::new();
+            for span in scope.from_root() {
+                spans.push(span.name());
+                if let Some(fields) = span.extensions().get::<JsonFields>() {
+                    line.extend(fields.0.clone());
+                }
+            }
+            line.insert("spans".into(), spans.join(":").into());
+        }
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        line.extend(fields.0);
+        writeln!(writer, "{}", serde_json::Value::Object(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_level_replaces_existing_directive() {
+        let f = with_module_level(DEFAULT_FILTER, "wolfstack", "debug");
+        assert_eq!(f, "actix_web=info,actix_http=off,wolfstack=debug");
+        let f = with_module_level(&f, "wolfstack::exec", "debug");
+        assert_eq!(f, "actix_web=info,actix_http=off,wolfstack=debug,wolfstack::exec=debug");
+        assert!(validate_filter(&f).is_ok());
+    }
+
+    #[test]
+    fn default_level_removes_directive() {
+        let f = with_module_level("wolfstack=info,wolfstack::agent=trace", "wolfstack::agent", "default");
+        assert_eq!(f, "wolfstack=info");
+    }
+
+    #[test]
+    fn request_ids_from_callers_are_checked() {
+        assert!(sane_request_id("3f2a-9c_1.x"));
+        assert!(!sane_request_id(""));
+        assert!(!sane_request_id("a b"));
+        assert!(!sane_request_id("id\r\nX-Evil: 1"));
+        assert!(!sane_request_id(&"a".repeat(65)));
+    }
+
+    #[test]
+    fn secret_looking_args_are_redacted() {
+        assert_eq!(redact_arg("MYSQL_ROOT_PASSWORD=hunter2"), "MYSQL_ROOT_PASSWORD=***");
+        assert_eq!(redact_arg("api_key=abc"), "api_key=***");
+        assert_eq!(redact_arg("TZ=Europe/London"), "TZ=Europe/London");
+        assert_eq!(redact_arg("--rm"), "--rm");
+    }
+}
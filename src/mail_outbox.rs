@@ -0,0 +1,285 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Alert email outbox and SMTP diagnostics.
+//!
+//! Alert mail used to be fire-and-forget: if the relay was down for the
+//! five minutes a disk filled up, the only trace was a `warn!` in the
+//! journal. Now a failed `send_alert_email` is recorded in a short
+//! failure log and — when the failure looks transient (connection, TLS,
+//! 4xx) — parked in a persistent outbox that a background task retries
+//! until it goes through or ages out.
+//!
+//! Both lists live in `mail-outbox.json` under the config dir so they
+//! survive restarts. Test emails bypass the outbox on purpose: the
+//! operator is watching for the result.
+//!
+//! [`diagnose`] walks the connection one layer at a time (DNS, TCP,
+//! TLS, AUTH) so "SMTP send: Connection error" becomes "port 587 is
+//! filtered" or "535 bad credentials".
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::ai::AiConfig;
+
+/// Queued mail older than this is dropped — a day-old "CPU at 95%" alert
+/// is noise by the time it arrives.
+const MAX_AGE_SECS: i64 = 24 * 60 * 60;
+const MAX_QUEUED: usize = 200;
+const MAX_FAILURES: usize = 50;
+/// How often the background task retries the queue.
+pub const RETRY_INTERVAL_SECS: u64 = 300;
+
+/// Serialises load-modify-save so a send failure and a retry pass can't
+/// lose each other's writes.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn outbox_path() -> String {
+    format!("{}/mail-outbox.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEmail {
+    pub id: String,
+    pub subject: String,
+    pub body: String,
+    pub queued_at: i64,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendFailure {
+    pub at: i64,
+    pub subject: String,
+    pub error: String,
+    /// `queued`, `permanent` (bad address / message — not retried) or
+    /// `expired` (aged out of the outbox).
+    pub outcome: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    #[serde(default)]
+    pub queued: Vec<QueuedEmail>,
+    /// Most recent first.
+    #[serde(default)]
+    pub failures: Vec<SendFailure>,
+}
+
+impl Outbox {
+    pub fn load() -> Self {
+        std::fs::read_to_string(outbox_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = outbox_path();
+        if let Some(parent) = std::path::Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+
+    fn push_failure(&mut self, subject: &str, error: &str, outcome: &str) {
+        self.failures.insert(0, SendFailure {
+            at: chrono::Utc::now().timestamp(),
+            subject: subject.to_string(),
+            error: error.to_string(),
+            outcome: outcome.to_string(),
+        });
+        self.failures.truncate(MAX_FAILURES);
+    }
+}
+
+/// Errors from building the message (bad To/From address) will fail the
+/// same way on every retry; everything else — connect, TLS, 4xx, even a
+/// 5xx auth failure the operator may fix — is worth retrying.
+pub fn is_retryable(error: &str) -> bool {
+    !(error.starts_with("Email to:") || error.starts_with("Email build:") || error.starts_with("Invalid From address"))
+}
+
+/// Record a failed alert send and queue it for retry when worthwhile.
+pub fn record_failure(subject: &str, body: &str, error: &str) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut outbox = Outbox::load();
+    if is_retryable(error) {
+        outbox.push_failure(subject, error, "queued");
+        outbox.queued.push(QueuedEmail {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            queued_at: chrono::Utc::now().timestamp(),
+            attempts: 1,
+            last_error: error.to_string(),
+        });
+        // Keep the newest when over the cap — they're the most relevant.
+        let excess = outbox.queued.len().saturating_sub(MAX_QUEUED);
+        outbox.queued.drain(..excess);
+    } else {
+        outbox.push_failure(subject, error, "permanent");
+    }
+    if let Err(e) = outbox.save() {
+        tracing::warn!("Could not persist mail outbox: {}", e);
+    }
+}
+
+/// Try every queued email once. Returns (sent, still queued). Blocking.
+pub fn retry_queued(config: &AiConfig) -> (usize, usize) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut outbox = Outbox::load();
+    if outbox.queued.is_empty() {
+        return (0, 0);
+    }
+    let now = chrono::Utc::now().timestamp();
+    let mut sent = 0;
+    let mut keep = Vec::new();
+    for mut mail in std::mem::take(&mut outbox.queued) {
+        if now - mail.queued_at > MAX_AGE_SECS {
+            let reason = format!("Gave up after {} attempts: {}", mail.attempts, mail.last_error);
+            outbox.push_failure(&mail.subject, &reason, "expired");
+            continue;
+        }
+        if !config.email_enabled || config.email_to.is_empty() {
+            keep.push(mail);
+            continue;
+        }
+        let subject = format!("{} (delayed)", mail.subject);
+        match crate::ai::send_text_email(config, &config.email_to, "WolfStack AI", &subject, &mail.body) {
+            Ok(()) => sent += 1,
+            Err(e) => {
+                mail.attempts += 1;
+                mail.last_error = e;
+                keep.push(mail);
+            }
+        }
+    }
+    outbox.queued = keep;
+    let remaining = outbox.queued.len();
+    if let Err(e) = outbox.save() {
+        tracing::warn!("Could not persist mail outbox: {}", e);
+    }
+    if sent > 0 {
+        tracing::info!("Mail outbox: delivered {} delayed alert email(s), {} still queued", sent, remaining);
+    }
+    (sent, remaining)
+}
+
+/// Drop a queued email without sending it.
+pub fn discard(id: &str) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut outbox = Outbox::load();
+    let before = outbox.queued.len();
+    outbox.queued.retain(|m| m.id != id);
+    if outbox.queued.len() == before {
+        return Err("No queued email with that id".to_string());
+    }
+    outbox.save()
+}
+
+/// One layer of the SMTP connection check.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagStep {
+    pub step: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+fn step(name: &str, result: Result<String, String>) -> DiagStep {
+    match result {
+        Ok(detail) => DiagStep { step: name.to_string(), ok: true, detail },
+        Err(detail) => DiagStep { step: name.to_string(), ok: false, detail },
+    }
+}
+
+/// Check DNS → TCP → TLS → AUTH against the configured relay, stopping at
+/// the first layer that fails. Sends no mail. Blocking.
+pub fn diagnose(config: &AiConfig) -> Vec<DiagStep> {
+    use std::net::ToSocketAddrs;
+
+    let mut steps = Vec::new();
+    if config.smtp_host.is_empty() {
+        steps.push(step("Configuration", Err("No SMTP host configured".to_string())));
+        return steps;
+    }
+    let target = format!("{}:{}", config.smtp_host, config.smtp_port);
+
+    let addrs: Vec<std::net::SocketAddr> = match (config.smtp_host.as_str(), config.smtp_port).to_socket_addrs() {
+        Ok(a) => a.collect(),
+        Err(e) => {
+            steps.push(step("DNS", Err(format!("Cannot resolve {}: {}", config.smtp_host, e))));
+            return steps;
+        }
+    };
+    steps.push(step("DNS", Ok(addrs.iter().map(|a| a.ip().to_string()).collect::<Vec<_>>().join(", "))));
+
+    let connected = addrs.iter().find_map(|a| std::net::TcpStream::connect_timeout(a, Duration::from_secs(10)).ok().map(|_| *a));
+    match connected {
+        Some(a) => steps.push(step("TCP", Ok(format!("Connected to {}", a)))),
+        None => {
+            steps.push(step("TCP", Err(format!(
+                "Could not connect to {} within 10s — the port may be blocked by a firewall or the provider", target
+            ))));
+            return steps;
+        }
+    }
+
+    // Handshake without credentials first, so a TLS problem isn't
+    // reported as an authentication one.
+    let tls_label = match config.smtp_tls.as_str() {
+        "tls" => "Implicit TLS",
+        "none" => "Plain SMTP (no encryption)",
+        _ => "STARTTLS",
+    };
+    let mut anonymous = config.clone();
+    anonymous.smtp_user.clear();
+    let handshake = crate::ai::build_smtp_mailer(&anonymous)
+        .and_then(|m| m.test_connection().map_err(|e| e.to_string()))
+        .and_then(|ok| if ok { Ok(format!("{} handshake succeeded", tls_label)) } else { Err("Server did not respond to NOOP".to_string()) });
+    let handshake_ok = handshake.is_ok();
+    steps.push(step("TLS", handshake));
+    if !handshake_ok {
+        return steps;
+    }
+
+    if config.smtp_user.is_empty() {
+        steps.push(step("AUTH", Ok("No username set — relaying unauthenticated".to_string())));
+    } else {
+        let auth = crate::ai::build_smtp_mailer(config)
+            .and_then(|m| m.test_connection().map_err(|e| e.to_string()))
+            .map(|_| format!("Logged in as {}", config.smtp_user));
+        steps.push(step("AUTH", auth));
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_errors_are_not_retried() {
+        assert!(!is_retryable("Email to: Missing domain"));
+        assert!(!is_retryable("Invalid From address 'x': bad"));
+        assert!(is_retryable("SMTP send: Connection error: timed out"));
+        assert!(is_retryable("SMTP STARTTLS: invalid dns name"));
+    }
+
+    #[test]
+    fn failure_log_is_capped_newest_first() {
+        let mut outbox = Outbox::default();
+        for i in 0..(MAX_FAILURES + 5) {
+            outbox.push_failure(&format!("s{}", i), "e", "queued");
+        }
+        assert_eq!(outbox.failures.len(), MAX_FAILURES);
+        assert_eq!(outbox.failures[0].subject, format!("s{}", MAX_FAILURES + 4));
+    }
+}
@@ -24,6 +24,8 @@ mod storage;
 mod networking;
 mod backup;
 mod browse;
+mod file_transfer;
+mod templates;
 mod galera;
 mod postgres_ha;
 mod mail_tier;
@@ -73,12 +75,35 @@ mod deps;
 mod exposure;
 mod bruteforce;
 mod mail_relay;
+mod mail_outbox;
 mod ups;
 mod systemcheck;
+mod issue_checks;
+mod daily_report;
+mod inventory_report;
+mod availability;
+mod issue_history;
+mod snmp;
+mod provisioning;
+mod maintenance;
+mod config_history;
+mod config_check;
+mod drift;
+mod events;
 mod security;
 mod secret_audit;
 mod secret_rotation;
 mod at_rest_crypto;
+mod secrets;
+mod validate;
+mod lifecycle;
+mod crash;
+mod selfcheck;
+mod capacity_policy;
+mod housekeeping;
+mod io_throttle;
+mod jobs;
+mod transfer;
 mod services_discovery;
 mod cluster_browser;
 mod compat;
@@ -92,6 +117,7 @@ mod dns_providers;
 mod edge;
 mod threat_intel;
 mod security_audit;
+mod security_baseline;
 mod scan_detector;
 mod antivirus;
 mod abuse_report;
@@ -101,10 +127,15 @@ mod local_ca;
 #[allow(dead_code)]
 mod integrations;
 mod cluster_join;
+mod cli;
+mod daemon_config;
+mod config_doc;
+mod tls_reload;
+mod logging;
 
 use actix_web::{web, App, HttpServer, HttpRequest, HttpResponse};
 use actix_files;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::{info, warn};
@@ -194,6 +225,13 @@ struct Cli {
     /// effect without `--leave-cluster`.
     #[arg(long)]
     rotate_cluster_secret: bool,
+
+    /// Headless management subcommands. Without one, the server starts.
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+
+    #[command(flatten)]
+    cli_opts: cli::Options,
 }
 
 /// Serve the login page for unauthenticated requests to /
@@ -274,17 +312,30 @@ async fn index_handler(req: HttpRequest, state: web::Data<api::AppState>) -> Htt
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("wolfstack=info".parse().unwrap())
-                .add_directive("actix_web=info".parse().unwrap())
-                .add_directive("actix_http=off".parse().unwrap()),
-        )
-        .init();
-
-    let cli = Cli::parse();
+    let result = run().await;
+    if let Err(e) = &result {
+        // A listener that won't bind ends up here — leave a report behind.
+        crash::record_exit_error(e);
+    }
+    result
+}
+
+async fn run() -> std::io::Result<()> {
+    // Initialize logging — text or JSON lines, filter from RUST_LOG or
+    // wolfstack.toml, adjustable at runtime via /api/logging.
+    let log_cfg = daemon_config::logging_bootstrap();
+    logging::init(log_cfg.format, log_cfg.filter.as_deref());
+
+    // Parsed via ArgMatches (rather than `Cli::parse`) so we can tell an
+    // explicit `--bind` from its default when merging wolfstack.toml.
+    let cli_matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&cli_matches).unwrap_or_else(|e| e.exit());
+
+    // Subcommands (`wolfstack node list`, `wolfstack ct start NAME`, …)
+    // drive the already-running daemon through its local API and exit.
+    if let Some(command) = &cli.command {
+        std::process::exit(crate::cli::run(command, &cli.cli_opts).await);
+    }
 
     // --show-token: print join token and exit (for CLI access without web UI)
     if cli.show_token {
@@ -478,6 +529,27 @@ async fn main() -> std::io::Result<()> {
     // (Caveat: `sudo -E` preserves the caller's environment — strip
     // INVOCATION_ID before a manual test run if the shell inherited one.)
     let running_as_systemd_service = std::env::var_os("INVOCATION_ID").is_some();
+
+    // Fold in /etc/wolfstack/wolfstack.toml (+ WOLFSTACK_* env overrides).
+    // Flags given on the command line still win.
+    tls_reload::remember_cli(tls_reload::CliTls {
+        cert: cli.tls_cert.clone(),
+        key: cli.tls_key.clone(),
+        domain: cli.tls_domain.clone(),
+    });
+    let daemon_cfg = daemon_config::get();
+    cli.port = cli.port.or(daemon_cfg.server.port);
+    if cli_matches.value_source("bind") != Some(clap::parser::ValueSource::CommandLine) {
+        if let Some(bind) = &daemon_cfg.server.bind {
+            cli.bind = bind.clone();
+        }
+    }
+    cli.no_tls |= !daemon_cfg.tls.enabled;
+    cli.tls_cert = cli.tls_cert.take().or_else(|| daemon_cfg.tls.cert.clone());
+    cli.tls_key = cli.tls_key.take().or_else(|| daemon_cfg.tls.key.clone());
+    cli.tls_domain = cli.tls_domain.take().or_else(|| daemon_cfg.tls.domain.clone());
+    cli.agent |= daemon_cfg.server.agent;
+
     let port_cfg = ports::PortConfig::load();
     let status_preferred = port_cfg.status; // captured before port_cfg is moved below
     let resolved = ports::resolve_api_ports(cli.port, running_as_systemd_service, port_cfg);
@@ -545,6 +617,14 @@ async fn main() -> std::io::Result<()> {
     info!("  Dashboard:  {}://{}", if cli.no_tls { "http" } else { "https" }, netaddr::host_port(&cli.bind, api_port));
     info!("  (C)Copyright Wolf Software Systems Ltd — https://wolf.uk.com");
     info!("  By Paul Clevett and my mate Claude - I have Autism");
+
+    // From here on we're the daemon: panics and a failed start leave a
+    // crash report, and a run of recent ones is called out in the log.
+    crash::install_panic_hook();
+    crash::check_crash_loop();
+    // systemd WatchdogSec= pings, if the unit asks for them.
+    lifecycle::spawn_watchdog();
+
     // Seed LXC storage paths from any mounted storage that has LXC containers
     if let Ok(entries) = std::fs::read_dir(&paths::get().storage_mount_base) {
         for entry in entries.flatten() {
@@ -678,6 +758,9 @@ async fn main() -> std::io::Result<()> {
         // parse (Fedora/SELinux builds) BEFORE autostart, so broken containers
         // list and start again. No-op on AppArmor hosts / Proxmox.
         containers::lxc_migrate_apparmor_configs();
+        // Docker's own restart-policy starts don't wait for the containers'
+        // required mounts — fix them up off this thread, it can wait minutes.
+        std::thread::spawn(containers::mount_deps::settle_docker);
         containers::lxc_autostart_all();
         networking::apply_all_wireguard_bridges();
         kubernetes::apply_all_wolfnet_routes();
@@ -773,6 +856,39 @@ async fn main() -> std::io::Result<()> {
         // events without threading state through their call sites.
         wolffunctions::init_event_hook(
             wolffunctions_state.clone(), cluster.clone(), cluster_secret.clone());
+        // The same events go out on the event bus; deliver them to webhooks.
+        tokio::spawn(events::webhooks::run_dispatcher());
+
+        // SIGHUP (`systemctl reload wolfstack`) re-reads wolfstack.toml and
+        // reloads the TLS certificate without a restart.
+        tokio::spawn(async {
+            let mut hup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => { warn!("SIGHUP handler not installed: {}", e); return; }
+            };
+            while hup.recv().await.is_some() {
+                match daemon_config::reload() {
+                    Ok(r) => {
+                        info!("SIGHUP: settings reloaded (changed: {}; TLS reloaded: {})",
+                            if r.changed.is_empty() { "none".to_string() } else { r.changed.join(", ") },
+                            r.tls_reloaded);
+                        if !r.restart_required.is_empty() {
+                            warn!("SIGHUP: {} only take effect after a restart", r.restart_required.join(", "));
+                        }
+                        if let Some(e) = r.tls_error {
+                            warn!("SIGHUP: TLS certificate not reloaded: {}", e);
+                        }
+                    }
+                    Err(e) => warn!("SIGHUP: wolfstack.toml not reloaded, keeping current settings: {}", e),
+                }
+            }
+        });
+
+        // libvirt lifecycle events for the VM list's live refresh. Its own
+        // thread: the watch blocks on the socket for the process lifetime.
+        if containers::is_libvirt() {
+            std::thread::spawn(vms::libvirt::watch);
+        }
 
         // Initialize Status Page monitoring state
         let statuspage_state = Arc::new(statuspage::StatusPageState::new());
@@ -803,7 +919,9 @@ async fn main() -> std::io::Result<()> {
         let bruteforce_state = bruteforce::BruteforceState::new(login_limiter.clone());
         let app_state = web::Data::new(api::AppState {
             monitor: monitor_arc.clone(),
-            metrics_history: Mutex::new(monitoring::MetricsHistory::new()),
+            // Graphs carry on from the history saved at the last shutdown.
+            metrics_history: std::sync::RwLock::new(monitoring::MetricsHistory::load(
+                &paths::get().metrics_history, daemon_cfg.history.metrics_snapshots)),
             cluster: cluster.clone(),
             sessions: sessions.clone(),
             vms: Mutex::new(vms_manager),
@@ -1272,6 +1390,7 @@ async fn main() -> std::io::Result<()> {
                                     hostname: hostname.clone(),
                                     cluster: cluster_name.clone(),
                                 });
+                                if let Some(entry) = log.last() { entry.publish(); }
                                 next_id += 1;
                             }
                             while log.len() > 200 { log.remove(0); }
@@ -1304,7 +1423,9 @@ async fn main() -> std::io::Result<()> {
                             // category_allowed guard for the email path
                             // doesn't need to be repeated here.
                             crate::alerting::send_alert(&alert_cfg, cat, &title, &body).await;
-                            if category_allowed && ai_cfg.email_enabled && !ai_cfg.email_to.is_empty() {
+                            if category_allowed && ai_cfg.email_enabled && !ai_cfg.email_to.is_empty()
+                                && crate::alerting::email_now(&alert_cfg, cat, &title, &body)
+                            {
                                 let cfg = ai_cfg.clone();
                                 let subj = title.clone();
                                 let b = body.clone();
@@ -1935,6 +2056,9 @@ async fn main() -> std::io::Result<()> {
         // already has its tools available.
         wolfagents::migrate_empty_allowed_tools();
 
+        // WolfNet IPAM: route allocations through the cluster's allocator node
+        containers::ipam::init(app_state.cluster.clone(), &cluster_secret);
+
         // WolfUSB: init with cluster secret and restore assignments on startup
         wolfusb::init(&cluster_secret);
         {
@@ -2020,6 +2144,33 @@ async fn main() -> std::io::Result<()> {
             });
         }
 
+        // Container and VM inventory, each source on its own interval, so
+        // the self-monitor tick and the dashboard read snapshots instead of
+        // listing inline.
+        monitoring::inventory::start(app_state.clone());
+
+        // Guest and node state changes, read from those snapshots once a
+        // minute, for the monthly availability report.
+        availability::start(hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_else(|_| "unknown".into()));
+
+        // Issue scans every fifteen minutes when nothing else has scanned,
+        // for the issue history and MTTR report.
+        issue_history::start(app_state.clone());
+
+        // Values for snmpd's WolfStack subtree, while SNMP is turned on.
+        snmp::start();
+
+        // Component versions across the cluster, for the version matrix and
+        // the WolfNet/WolfDisk compatibility issue check.
+        installer::versions::start(cluster.clone());
+
+        // Reboot windows: finish a reboot job this node was part of, resume
+        // a run it was driving, then fire windows on schedule.
+        maintenance::start(app_state.clone());
+
+        // Config drift: baselined and WolfStack-saved files, every 15 minutes.
+        drift::start();
+
         // Background: periodic self-monitoring update
         let state_clone = app_state.clone();
         let cluster_clone = cluster.clone();
@@ -2142,6 +2293,18 @@ async fn main() -> std::io::Result<()> {
             }
         });
 
+        // Alert email outbox: retry mail that failed to send (relay
+        // down, TLS hiccup) until it goes out or ages out.
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(mail_outbox::RETRY_INTERVAL_SECS)).await;
+                tokio::task::spawn_blocking(|| {
+                    let config = ai::AiConfig::load();
+                    mail_outbox::retry_queued(&config);
+                }).await.ok();
+            }
+        });
+
         let public_ip = public_ip.clone();
         let cached_status_bg = cached_status.clone();
         // Agent nodes: relax the self-monitor cadence 2s→5s. This is the
@@ -2149,21 +2312,33 @@ async fn main() -> std::io::Result<()> {
         // tick), and the MANAGER only polls the agent every 10s, so 5s is
         // still fresher than the consumer — cutting this loop's allocation
         // rate ~2.5× on RAM-constrained agents (agent-mode reduction).
-        let self_monitor_secs = if agent_mode { 5 } else { 2 };
+        // Re-read every tick so a config reload retunes it live.
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(Duration::from_secs(self_monitor_secs)).await;
+                let self_monitor_secs = daemon_config::get().polling.self_monitor_secs
+                    .unwrap_or(if agent_mode { 5 } else { 2 });
+                // Stops at shutdown so nothing is recorded after the
+                // history has been saved.
+                if lifecycle::unless_stopping(tokio::time::sleep(Duration::from_secs(self_monitor_secs))).await.is_none() {
+                    break;
+                }
                 // Run all blocking sysinfo/subprocess work off the async runtime
                 let sc = state_clone.clone();
                 let (metrics, components, docker_count, lxc_count, vm_count, compose_count, has_docker, has_lxc, has_kvm) =
                     tokio::task::spawn_blocking(move || {
                         let mut monitor = sc.monitor.lock().unwrap();
+                        let started = std::time::Instant::now();
                         let m = monitor.collect();
                         drop(monitor);  // release mutex before spawning subprocesses
+                        // The one place the monitor is sampled — handlers
+                        // and the other loops read this snapshot.
+                        monitoring::inventory::METRICS.publish(m.clone(), started.elapsed());
                         let c = installer::get_all_status_cached();
+                        // Counts come from the inventory snapshots — no
+                        // listing (or VM manager lock) on this tick.
                         let dc = containers::docker_count();
                         let lc = containers::lxc_count();
-                        let vc = sc.vms.lock().unwrap().list_vms().len() as u32;
+                        let vc = monitoring::inventory::vms_blocking(&sc).len() as u32;
                         // Compose count is a cheap dir scan, but it's still
                         // blocking I/O — keep it inside spawn_blocking with the
                         // other counts (v25.0.0 offloaded exactly this).
@@ -2175,7 +2350,8 @@ async fn main() -> std::io::Result<()> {
                     }).await.unwrap();
                 // Record historical snapshot
                 {
-                    let mut history = state_clone.metrics_history.lock().unwrap();
+                    let mut history = state_clone.metrics_history.write().unwrap();
+                    history.set_max_size(daemon_config::get().history.metrics_snapshots);
                     history.push(&metrics);
                 }
 
@@ -2196,6 +2372,7 @@ async fn main() -> std::io::Result<()> {
                     public_ip: public_ip.clone(),
                     known_nodes,
                     deleted_ids,
+                    archive_marks: cluster_clone.get_archive_marks(),
                     wolfnet_ips: containers::wolfnet_used_ips_cached(),
                     has_docker,
                     has_lxc,
@@ -2210,6 +2387,8 @@ async fn main() -> std::io::Result<()> {
                     // Self's assigned tier roles — gossiped so peers know the
                     // DNS / mail / ingress / host tier membership.
                     roles: cluster_clone.get_node(&cluster_clone.self_id).map(|n| n.roles).unwrap_or_default(),
+                    // Self's operator-set labels, for label selectors on peers.
+                    labels: cluster_clone.get_node(&cluster_clone.self_id).map(|n| n.labels).unwrap_or_default(),
                     // Propagate license to cluster nodes
                     license_key: if crate::compat::platform_ready() {
                         std::fs::read_to_string(crate::compat::dm_path()).ok().map(|s| s.trim().to_string())
@@ -2243,7 +2422,7 @@ async fn main() -> std::io::Result<()> {
         let ai_agent_poll = ai_agent.clone();
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(Duration::from_secs(10)).await;
+                tokio::time::sleep(Duration::from_secs(daemon_config::get().polling.node_poll_secs)).await;
                 let secret = auth::load_cluster_secret();
                 agent::poll_remote_nodes(cluster_poll.clone(), secret, Some(ai_agent_poll.clone())).await;
             }
@@ -2389,19 +2568,23 @@ async fn main() -> std::io::Result<()> {
         // that follow the system-prompt tool directive (Gemini, some
         // Claude revisions) would get UNEXPECTED_TOOL_CALL errors
         // because the no-tools fallback couldn't honour the prompt.
-        let discord_state = app_state.clone();
-        tokio::spawn(async move {
-            crate::discord_bot::supervise_forever(discord_state).await;
-        });
+        if daemon_cfg.features.discord_bot {
+            let discord_state = app_state.clone();
+            tokio::spawn(async move {
+                crate::discord_bot::supervise_forever(discord_state).await;
+            });
+        }
 
         // Telegram receiver — same idea as Discord but simpler
         // (HTTP long-polling; no gateway, no heartbeat). Idle until
         // the operator turns on telegram_receiver_enabled AND a
         // telegram_bot_token is set.
-        let telegram_state = app_state.clone();
-        tokio::spawn(async move {
-            crate::telegram_bot::supervise_forever(telegram_state).await;
-        });
+        if daemon_cfg.features.telegram_bot {
+            let telegram_state = app_state.clone();
+            tokio::spawn(async move {
+                crate::telegram_bot::supervise_forever(telegram_state).await;
+            });
+        }
 
         // Background: session + login rate limiter + reset token cleanup.
         // Also sweeps expired OIDC pending-flow state tokens — pre-v18.7.30
@@ -2450,6 +2633,96 @@ async fn main() -> std::io::Result<()> {
             }
         });
 
+        // Background: daily disk housekeeping (settings in housekeeping.json;
+        // off until enabled). Blocking — prunes and journal vacuums can take
+        // a while.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(300)).await;
+            loop {
+                if let Err(e) = tokio::task::spawn_blocking(housekeeping::check_schedule).await {
+                    tracing::error!("housekeeping::check_schedule panicked: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(600)).await;
+            }
+        });
+
+        // Background: storage mount watchdog (every 60s) — probes network
+        // and FUSE mounts, remounts stale ones and alerts when that fails.
+        // Starts after boot auto-mounts have had time to settle.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(120)).await;
+            loop {
+                match tokio::task::spawn_blocking(storage::check_mount_health).await {
+                    Ok(alerts) => for alert in alerts {
+                        crate::alerting::send_local_alert(
+                            crate::alerting::AlertCategory::Lifecycle, &alert.title, &alert.message,
+                        ).await;
+                    },
+                    Err(e) => tracing::error!("storage::check_mount_health panicked: {}", e),
+                }
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+
+        // Background: WolfNet tunnel telemetry (every 60s) — per-peer
+        // handshake age, traffic and endpoint history, alerting when a
+        // peer goes silent.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            loop {
+                match tokio::task::spawn_blocking(networking::wolfnet_health::sample).await {
+                    Ok(alerts) => for alert in alerts {
+                        crate::alerting::send_local_alert(
+                            crate::alerting::AlertCategory::Lifecycle, &alert.title, &alert.message,
+                        ).await;
+                    },
+                    Err(e) => tracing::error!("wolfnet_health::sample panicked: {}", e),
+                }
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+
+        // Background: intrusion-attempt surge alert (every 60s) — fires when
+        // failed logins across every surface pass the configured rate.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            loop {
+                let threshold = crate::alerting::AlertConfig::load().attack_alert_threshold;
+                if let Some((title, message)) = auth::attacks::surge_alert(threshold) {
+                    crate::alerting::send_local_alert(
+                        crate::alerting::AlertCategory::BruteForce, &title, &message,
+                    ).await;
+                }
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+
+        // Background: trivy CVE scan of local Docker images (hourly check;
+        // each image is only rescanned when it changed or its last scan is
+        // a day old, so most ticks do nothing).
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(300)).await;
+            loop {
+                if let Err(e) = tokio::task::spawn_blocking(|| containers::image_cves::refresh(false)).await {
+                    tracing::error!("image_cves::refresh panicked: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            }
+        });
+
+        // Background: WolfNet IPAM reconcile (every 60s) — binds, adopts and
+        // drops this node's leases to match its workloads, then pushes them
+        // to the peers so every ledger converges.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            loop {
+                if let Err(e) = tokio::task::spawn_blocking(containers::ipam::reconcile).await {
+                    tracing::error!("ipam::reconcile panicked: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+
         // Cluster service discovery — runs on demand only (triggered
         // by the Cluster Browser page on load). Restore the previous
         // sweep's cache from disk so the first API hit returns
@@ -2470,6 +2743,8 @@ async fn main() -> std::io::Result<()> {
         // re-renders when an upstream actually changed — and then also
         // replicates the router config, because the hosting node is
         // usually NOT the ingress node that renders the route.
+        // Containers auto-registered into a proxy's upstream pool are
+        // refreshed the same way.
         {
             let exp_state = app_state.clone();
             tokio::spawn(async move {
@@ -2483,7 +2758,9 @@ async fn main() -> std::io::Result<()> {
                         let changed = {
                             let mut cfg = router.config.write().unwrap();
                             let mut proxies = cfg.http_proxies.clone();
-                            if crate::exposure::reconcile_upstreams(&mut proxies, &cluster) {
+                            let exposed = crate::exposure::reconcile_upstreams(&mut proxies, &cluster);
+                            let pooled = crate::networking::router::auto_upstream::reconcile(&mut proxies, &cluster);
+                            if exposed || pooled {
                                 cfg.http_proxies = proxies.clone();
                                 let _ = cfg.save();
                                 Some(proxies)
@@ -2783,7 +3060,7 @@ async fn main() -> std::io::Result<()> {
                         // worker thread).
                         let ss = scan_state.clone();
                         let (metrics, local_issues) = tokio::task::spawn_blocking(move || {
-                            let metrics = ss.monitor.lock().unwrap().collect();
+                            let metrics = monitoring::inventory::metrics_blocking(&ss);
                             let issues = api::collect_issues(&metrics);
                             (metrics, issues)
                         }).await.unwrap();
@@ -2851,6 +3128,7 @@ async fn main() -> std::io::Result<()> {
                                         hostname: host.clone(),
                                         cluster: cluster.clone(),
                                     });
+                                    if let Some(entry) = log.last() { entry.publish(); }
                                     next_id += 1;
                                 }
                             }
@@ -2943,10 +3221,11 @@ async fn main() -> std::io::Result<()> {
                             // the webhook recipients see the same originator
                             // metadata.
                             let (subject, body) = crate::alerting::decorate_local(&subject, &body);
-                            if posture_allowed {
-                                if let Err(e) = ai::send_alert_email(&config, &subject, &body) {
-                                    tracing::warn!("Failed to send critical issues email: {}", e);
-                                }
+                            if posture_allowed
+                                && crate::alerting::email_now(&alert_config, crate::alerting::AlertCategory::Posture, &subject, &body)
+                                && let Err(e) = ai::send_alert_email(&config, &subject, &body)
+                            {
+                                tracing::warn!("Failed to send critical issues email: {}", e);
                             }
 
                             // Also send to webhook channels
@@ -3021,6 +3300,8 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
 @media print{.container{box-shadow:none;border:none;} .bar-fill{-webkit-print-color-adjust:exact;print-color-adjust:exact;}}
 </style></head><body><div class="container">"#);
 
+                            let dashboard_url = ai::dashboard_base_url(&local_hostname);
+
                             // Header
                             html.push_str(&format!(
                                 r#"<h1>WolfStack Daily Report</h1>
@@ -3042,39 +3323,103 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                                 info_count
                             ));
 
-                            // ─── Node Inventory Table ───
+                            // ─── Per-cluster sections: inventory with trend sparklines + issues ───
                             let all_nodes = scan_cluster.get_all_nodes();
-                            html.push_str(r#"<h2>Node Inventory</h2>
-                            <table><thead><tr><th>Node</th><th>Status</th><th>CPU</th><th>Memory</th><th>Docker</th><th>LXC</th><th>VMs</th></tr></thead><tbody>"#);
-                            for n in all_nodes.iter().filter(|n| n.node_type != "proxmox") {
-                                let status_class = if n.online { "online" } else { "offline" };
-                                let status_text = if n.online { "Online" } else { "Offline" };
-                                let (cpu_str, mem_str) = if let Some(ref m) = n.metrics {
-                                    let cpu = m.cpu_usage_percent;
-                                    let mem_pct = if m.memory_total_bytes > 0 {
-                                        (m.memory_used_bytes as f64 / m.memory_total_bytes as f64 * 100.0) as u64
-                                    } else { 0 };
-                                    let cpu_color = if cpu > 80.0 { "#dc2626" } else if cpu > 50.0 { "#d97706" } else { "#16a34a" };
-                                    let mem_color = if mem_pct > 90 { "#dc2626" } else if mem_pct > 70 { "#d97706" } else { "#16a34a" };
-                                    (
-                                        format!(r#"<div class="bar"><div class="bar-fill" style="width:{}%;background:{}"></div></div><span class="meta">{:.0}%</span>"#, cpu.min(100.0), cpu_color, cpu),
-                                        format!(r#"<div class="bar"><div class="bar-fill" style="width:{}%;background:{}"></div></div><span class="meta">{} / {}</span>"#, mem_pct.min(100), mem_color, fmt_bytes(m.memory_used_bytes), fmt_bytes(m.memory_total_bytes)),
-                                    )
-                                } else {
-                                    ("—".to_string(), "—".to_string())
-                                };
-                                let display_name = if n.hostname.is_empty() { &n.address } else { &n.hostname };
+                            // (Legacy Proxmox-API entries are no longer rendered — they're surfaced
+                            // through the deprecation banner so the user can remove them.)
+                            let report_nodes: Vec<&agent::Node> = all_nodes.iter().filter(|n| n.node_type != "proxmox").collect();
+                            let mut histories: std::collections::HashMap<String, Vec<monitoring::MetricsSnapshot>> = std::collections::HashMap::new();
+                            for n in &report_nodes {
+                                if n.is_self {
+                                    histories.insert(n.id.clone(), scan_state.metrics_history.read().unwrap().get_all());
+                                } else if n.online {
+                                    let url = node_api_url(n, "/api/metrics/history");
+                                    if let Ok(resp) = http_client.get(&url)
+                                        .header("X-WolfStack-Secret", scan_secret.as_str())
+                                        .timeout(std::time::Duration::from_secs(15))
+                                        .send().await
+                                    {
+                                        if let Ok(h) = resp.json::<Vec<monitoring::MetricsSnapshot>>().await {
+                                            histories.insert(n.id.clone(), h);
+                                        }
+                                    }
+                                }
+                            }
+                            let cluster_of = |n: &agent::Node| n.cluster_name.clone().unwrap_or_else(|| "Default".to_string());
+                            let issues_by_cluster = daily_report::issues_by_cluster(&all_issues);
+                            let mut report_clusters: Vec<String> = report_nodes.iter().map(|n| cluster_of(n)).collect();
+                            report_clusters.extend(issues_by_cluster.keys().cloned());
+                            report_clusters.sort();
+                            report_clusters.dedup();
+
+                            for cluster in &report_clusters {
+                                let cluster_issues = issues_by_cluster.get(cluster).map(|v| v.as_slice()).unwrap_or(&[]);
+                                let crit = cluster_issues.iter().filter(|(_, i)| i.severity == "critical").count();
+                                let warn = cluster_issues.iter().filter(|(_, i)| i.severity == "warning").count();
+                                let members: Vec<&&agent::Node> = report_nodes.iter().filter(|n| &cluster_of(n) == cluster).collect();
                                 html.push_str(&format!(
-                                    r#"<tr><td><strong>{}</strong><br><span class="meta">{} &bull; port {}</span></td><td><span class="badge {}">{}</span></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>"#,
-                                    display_name, n.address, n.port, status_class, status_text, cpu_str, mem_str,
-                                    if n.has_docker { format!("{}", n.docker_count) } else { "—".to_string() },
-                                    if n.has_lxc { format!("{}", n.lxc_count) } else { "—".to_string() },
-                                    if n.has_kvm { format!("{}", n.vm_count) } else { "—".to_string() },
+                                    r#"<h2>Cluster: {}</h2><p class="meta" style="margin-top:-6px;">{} node(s) &bull; {} critical &bull; {} warning(s)</p>"#,
+                                    daily_report::escape(cluster), members.len(), crit, warn
                                 ));
+
+                                if !members.is_empty() {
+                                    html.push_str(r#"<table><thead><tr><th>Node</th><th>Status</th><th>CPU</th><th>Memory</th><th>Trend</th><th>Docker</th><th>LXC</th><th>VMs</th></tr></thead><tbody>"#);
+                                    for n in members {
+                                        let status_class = if n.online { "online" } else { "offline" };
+                                        let status_text = if n.online { "Online" } else { "Offline" };
+                                        let (cpu_str, mem_str) = if let Some(ref m) = n.metrics {
+                                            let cpu = m.cpu_usage_percent;
+                                            let mem_pct = if m.memory_total_bytes > 0 {
+                                                (m.memory_used_bytes as f64 / m.memory_total_bytes as f64 * 100.0) as u64
+                                            } else { 0 };
+                                            let cpu_color = if cpu > 80.0 { "#dc2626" } else if cpu > 50.0 { "#d97706" } else { "#16a34a" };
+                                            let mem_color = if mem_pct > 90 { "#dc2626" } else if mem_pct > 70 { "#d97706" } else { "#16a34a" };
+                                            (
+                                                format!(r#"<div class="bar"><div class="bar-fill" style="width:{}%;background:{}"></div></div><span class="meta">{:.0}%</span>"#, cpu.min(100.0), cpu_color, cpu),
+                                                format!(r#"<div class="bar"><div class="bar-fill" style="width:{}%;background:{}"></div></div><span class="meta">{} / {}</span>"#, mem_pct.min(100), mem_color, fmt_bytes(m.memory_used_bytes), fmt_bytes(m.memory_total_bytes)),
+                                            )
+                                        } else {
+                                            ("—".to_string(), "—".to_string())
+                                        };
+                                        let trend = match histories.get(&n.id) {
+                                            Some(h) if !h.is_empty() => {
+                                                let (cpu_series, mem_series) = daily_report::history_series(h);
+                                                format!(
+                                                    r#"<span class="meta">CPU</span>{}<span class="meta">Mem</span>{}<span class="meta">{}</span>"#,
+                                                    daily_report::sparkline(&cpu_series),
+                                                    daily_report::sparkline(&mem_series),
+                                                    daily_report::history_span_label(h),
+                                                )
+                                            }
+                                            _ => "—".to_string(),
+                                        };
+                                        let display_name = if n.hostname.is_empty() { &n.address } else { &n.hostname };
+                                        html.push_str(&format!(
+                                            r#"<tr><td><strong>{}</strong><br><span class="meta">{} &bull; port {}</span></td><td><span class="badge {}">{}</span></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>"#,
+                                            daily_report::escape(display_name), daily_report::escape(&n.address), n.port, status_class, status_text, cpu_str, mem_str, trend,
+                                            if n.has_docker { format!("{}", n.docker_count) } else { "—".to_string() },
+                                            if n.has_lxc { format!("{}", n.lxc_count) } else { "—".to_string() },
+                                            if n.has_kvm { format!("{}", n.vm_count) } else { "—".to_string() },
+                                        ));
+                                    }
+                                    html.push_str("</tbody></table>");
+                                }
+
+                                if cluster_issues.is_empty() {
+                                    html.push_str(r#"<p style="color:#22c55e;font-size:13px;">No issues detected in this cluster.</p>"#);
+                                } else {
+                                    html.push_str(r#"<table><thead><tr><th>Severity</th><th>Node</th><th>Issue</th><th>Detail</th></tr></thead><tbody>"#);
+                                    for (host, issue) in cluster_issues {
+                                        let sev_class = match issue.severity.as_str() { "critical" => "critical", "warning" => "warning", _ => "info" };
+                                        html.push_str(&format!(
+                                            r#"<tr><td><span class="badge {}">{}</span></td><td><strong>{}</strong></td><td>{}</td><td class="meta">{}</td></tr>"#,
+                                            sev_class, issue.severity, daily_report::escape(host),
+                                            daily_report::escape(&issue.title), daily_report::escape(&issue.detail),
+                                        ));
+                                    }
+                                    html.push_str("</tbody></table>");
+                                }
                             }
-                            // (Legacy Proxmox-API entries are no longer rendered — they're surfaced
-                            // through the deprecation banner so the user can remove them.)
-                            html.push_str("</tbody></table>");
 
                             // ─── Docker Containers Table ───
                             {
@@ -3168,7 +3513,7 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
 
                             // ─── VMs Table (all nodes) ───
                             {
-                                let local_vms = scan_state.vms.lock().unwrap().list_vms();
+                                let local_vms = monitoring::inventory::vms(&scan_state).await;
                                 let mut all_vms: Vec<(String, crate::vms::manager::VmConfig)> = Vec::new();
                                 let local_host = {
                                     let nodes = scan_cluster.get_all_nodes();
@@ -3266,23 +3611,16 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                             }
 
 
-                            // ─── Issues Table ───
-                            if all_issues.is_empty() {
-                                html.push_str(r#"<h2>Issues</h2><p style="color:#22c55e;text-align:center;padding:20px;">All systems healthy — no issues detected.</p>"#);
-                            } else {
-                                html.push_str(r#"<h2>Issues</h2>
-                                <table><thead><tr><th>Severity</th><th>Node</th><th>Issue</th><th>Detail</th></tr></thead><tbody>"#);
-                                for (cluster, host, issue) in &all_issues {
-                                    let sev_class = match issue.severity.as_str() { "critical" => "critical", "warning" => "warning", _ => "info" };
-                                    html.push_str(&format!(
-                                        r#"<tr><td><span class="badge {}">{}</span></td><td><strong>{}</strong><br><span class="meta">{}</span></td><td>{}</td><td class="meta">{}</td></tr>"#,
-                                        sev_class, issue.severity, host, cluster, issue.title, issue.detail,
-                                    ));
-                                }
-                                html.push_str("</tbody></table>");
-                            }
+                            // ─── Capacity outlook (projected 90% dates) ───
+                            let capacity = predictive::capacity::collect_cluster(&scan_state).await;
+                            html.push_str(&predictive::capacity::email_section_html(&capacity));
+
+                            // ─── Docker image CVEs (trivy, per node) ───
+                            let image_cves = containers::image_cves::collect_cluster(&scan_state).await;
+                            html.push_str(&containers::image_cves::email_section_html(&image_cves));
 
                             // ─── AI Recommendations ───
+                            let mut ai_recs_text = String::new();
                             if !all_issues.is_empty() {
                                 let issues_summary: String = all_issues.iter()
                                     .map(|(_, host, i)| format!("- [{}] {} on {}: {}", i.severity, i.title, host, i.detail))
@@ -3304,17 +3642,30 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                                         r#"<h2 style="color:#eab308;">🤖 AI Recommendations</h2><div class="ai-box">{}</div>"#,
                                         escaped
                                     ));
+                                    ai_recs_text = ai_recs;
                                 }
                             }
 
                             // Footer
                             html.push_str(&format!(
-                                r#"<p style="color:#999;font-size:11px;text-align:center;margin-top:24px;border-top:1px solid #e0e0e8;padding-top:12px;">WolfStack v{} &bull; Generated {}</p>"#,
-                                env!("CARGO_PKG_VERSION"), chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+                                r#"<p style="text-align:center;margin-top:24px;"><a href="{0}" style="display:inline-block;background:#dc2626;color:#ffffff;padding:10px 20px;border-radius:8px;font-weight:600;">Open WolfStack Dashboard</a></p>
+                                <p style="color:#999;font-size:11px;text-align:center;margin-top:16px;border-top:1px solid #e0e0e8;padding-top:12px;">WolfStack v{1} &bull; Generated {2} &bull; <a href="{0}">{0}</a></p>"#,
+                                daily_report::escape(&dashboard_url), env!("CARGO_PKG_VERSION"), chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
                             ));
                             html.push_str("</div></body></html>");
 
-                            if let Err(e) = ai::send_html_email(&config, &subject, &html) {
+                            let text_nodes: Vec<daily_report::TextNode> = report_nodes.iter().map(|n| daily_report::TextNode {
+                                cluster: cluster_of(n),
+                                hostname: if n.hostname.is_empty() { n.address.clone() } else { n.hostname.clone() },
+                                online: n.online,
+                                cpu_percent: n.metrics.as_ref().map(|m| m.cpu_usage_percent),
+                                memory_percent: n.metrics.as_ref().map(|m| m.memory_percent),
+                            }).collect();
+                            let mut text = daily_report::plain_text(&today, &text_nodes, &all_issues, &ai_recs_text, &dashboard_url);
+                            text.push_str(&predictive::capacity::email_section_text(&capacity));
+                            text.push_str(&containers::image_cves::email_section_text(&image_cves));
+
+                            if let Err(e) = ai::send_html_email_with_text(&config, &subject, &html, &text) {
                                 tracing::warn!("Failed to send daily report email: {}", e);
                             }
                         }
@@ -3326,6 +3677,32 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
             }
         });
 
+        // Background: monthly inventory report email (schedule in
+        // inventory-report.json; checked hourly, sent once per month).
+        let inv_state = app_state.clone();
+        tokio::spawn(async move {
+            // Manager-only, like the daily report: it aggregates the fleet.
+            if agent_mode { return; }
+            tokio::time::sleep(Duration::from_secs(120)).await;
+            loop {
+                if let Some(month) = inventory_report::ReportSchedule::load().due(chrono::Utc::now()) {
+                    match inventory_report::send_report(&inv_state, &month).await {
+                        Ok(rows) => {
+                            tracing::info!("Sent monthly inventory report for {} ({} rows)", month, rows);
+                            // Re-load so a schedule edit made while sending isn't lost.
+                            let mut schedule = inventory_report::ReportSchedule::load();
+                            schedule.last_sent = month;
+                            if let Err(e) = schedule.save() {
+                                tracing::warn!("Failed to record inventory report send: {}", e);
+                            }
+                        }
+                        Err(e) => tracing::warn!("Failed to send monthly inventory report: {}", e),
+                    }
+                }
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            }
+        });
+
         // Background: AI health check loop
         let ai_state = app_state.clone();
         let ai_agent_bg = ai_agent.clone();
@@ -3344,9 +3721,11 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                 // interval (or flipping agent_enabled back on) takes
                 // effect within one cycle rather than at next process
                 // restart.
+                // `features.ai_health_check = false` in wolfstack.toml
+                // is the node-level off switch, re-read each cycle too.
                 let (run_check, interval) = {
                     let config = ai_agent_bg.config.lock().unwrap();
-                    let configured = config.is_configured();
+                    let configured = config.is_configured() && daemon_config::get().features.ai_health_check;
                     let mins = config.check_interval_minutes;
                     let secs = if configured && mins > 0 {
                         mins as u64 * 60
@@ -3362,12 +3741,10 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                     let (hostname, cpu_pct, mem_used_gb, mem_total_gb, disk_used_gb, disk_total_gb,
                          docker_count, lxc_count, vm_count, uptime_secs) =
                         tokio::task::spawn_blocking(move || {
-                            let mut monitor = ai_sc.monitor.lock().unwrap();
-                            let m = monitor.collect();
-                            drop(monitor);
+                            let m = monitoring::inventory::metrics_blocking(&ai_sc);
                             let docker_count = containers::docker_count();
                             let lxc_count = containers::lxc_count();
-                            let vm_count = ai_sc.vms.lock().unwrap().list_vms().len() as u32;
+                            let vm_count = monitoring::inventory::vms_blocking(&ai_sc).len() as u32;
 
                             let mem_used = m.memory_used_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
                             let mem_total = m.memory_total_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
@@ -3819,12 +4196,26 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                     }
                 }
 
-                // Use the configured interval (re-read each loop in case user changed it)
-                let interval = config.check_interval_secs.max(30);
+                // Use the configured interval (re-read each loop in case user changed it);
+                // `polling.alert_check_secs` in wolfstack.toml overrides it.
+                let interval = daemon_config::get().polling.alert_check_secs
+                    .unwrap_or(config.check_interval_secs)
+                    .max(30);
                 tokio::time::sleep(Duration::from_secs(interval)).await;
             }
         });
 
+        // Background: alert digests (every 60s). Separate from the
+        // threshold loop above because agent-mode nodes skip that one but
+        // still raise their own alerts (security scans etc.) that can be
+        // held by quiet hours or a digest interval.
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                alerting::flush_digests().await;
+            }
+        });
+
         // Background: WolfRun reconciliation loop (every 15s)
         let wolfrun_cluster = cluster.clone();
         let wolfrun_secret = cluster_secret.clone();
@@ -3837,6 +4228,8 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                 // Only the cluster leader runs reconciliation to prevent
                 // duplicate container creation and IP address conflicts
                 if wolfrun::is_leader(&wolfrun_cluster) {
+                    // Autoscaling adjusts replica counts for reconcile to act on
+                    wolfrun::autoscale::evaluate(&wolfrun_bg, &wolfrun_cluster, &wolfrun_secret).await;
                     wolfrun::reconcile(&wolfrun_bg, &wolfrun_cluster, &wolfrun_secret).await;
                     // Check failover — promote standby containers for offline nodes
                     wolfrun::check_failover(&wolfrun_bg, &wolfrun_cluster, &wolfrun_secret).await;
@@ -4201,6 +4594,10 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                 }
             }
 
+            // Lets a config reload (SIGHUP / API) swap in a new or
+            // renewed certificate for new connections.
+            crate::tls_reload::attach(&mut builder);
+
             Some(builder)
         });
 
@@ -4242,10 +4639,13 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
             let web_dir2 = web_dir.clone();
             let app_state2 = app_state.clone();
             let app_state3 = app_state.clone();
+            let app_state_shutdown = app_state.clone();
 
             let https_bind = netaddr::host_port(&cli.bind, api_port);
             let https_server = HttpServer::new(move || {
                 let app = App::new()
+                    // Base-path HTML rewriting needs the page before it's compressed.
+                    .wrap(actix_web::middleware::from_fn(reverse_proxy::rewrite_html))
                     // Compress responses (gzip/brotli/zstd, negotiated via
                     // Accept-Encoding). web/js/app.js is ~3.9 MB uncompressed —
                     // without this every page load shipped the whole thing in
@@ -4255,7 +4655,14 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                     // finished loading, so none of its functions existed).
                     // Compressed it's ~10x smaller and far less reset-prone.
                     .wrap(actix_web::middleware::Compress::default())
+                    .wrap(actix_web::middleware::from_fn(csrf_gate))
+                    .wrap(actix_web::middleware::from_fn(read_only_gate))
                     .wrap(actix_web::middleware::from_fn(mtls_gate))
+                    .wrap(actix_web::middleware::from_fn(monitoring::api_stats::track))
+                    .wrap(actix_web::middleware::from_fn(logging::request_id))
+                    // Outermost of the from_fn layers: rewrites the peer address
+                    // and path before anything else reads them.
+                    .wrap(actix_web::middleware::from_fn(reverse_proxy::resolve))
                     .app_data(app_state.clone())
                     .app_data(wolfhost_data.clone())
                     .app_data(actix_multipart::form::MultipartFormConfig::default().total_limit(2 * 1024 * 1024 * 1024))
@@ -4290,10 +4697,13 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
             .client_request_timeout(std::time::Duration::from_secs(3))
             .client_disconnect_timeout(std::time::Duration::from_millis(500))
             .workers(http_workers)
+            // SIGTERM/SIGINT are handled by lifecycle::on_shutdown_signal.
+            .disable_signals()
             .on_connect(mtls_on_connect)
             .bind_openssl(&https_bind, ssl_builder)
             .map_err(|e| {
                 tracing::error!("❌ Failed to bind HTTPS on {}: {}", https_bind, e);
+                selfcheck::explain_bind_failure(&https_bind, &e);
                 e
             })?
             .run();
@@ -4309,8 +4719,16 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                         let app = App::new()
                             // Compress responses — see the main server above
                             // (app.js is ~3.9 MB uncompressed otherwise).
+                            .wrap(actix_web::middleware::from_fn(reverse_proxy::rewrite_html))
                             .wrap(actix_web::middleware::Compress::default())
-                    .wrap(actix_web::middleware::from_fn(mtls_gate))
+                            .wrap(actix_web::middleware::from_fn(csrf_gate))
+                            .wrap(actix_web::middleware::from_fn(read_only_gate))
+                            .wrap(actix_web::middleware::from_fn(mtls_gate))
+                            .wrap(actix_web::middleware::from_fn(monitoring::api_stats::track))
+                            .wrap(actix_web::middleware::from_fn(logging::request_id))
+                            // Outermost of the from_fn layers: rewrites the peer address
+                            // and path before anything else reads them.
+                            .wrap(actix_web::middleware::from_fn(reverse_proxy::resolve))
                             .app_data(app_state2.clone())
                             .app_data(wolfhost_data2.clone())
                             .app_data(actix_multipart::form::MultipartFormConfig::default().total_limit(2 * 1024 * 1024 * 1024))
@@ -4332,9 +4750,11 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                     .client_request_timeout(std::time::Duration::from_secs(3))
                     .client_disconnect_timeout(std::time::Duration::from_millis(500))
                     .workers(http_workers)
+                    .disable_signals()
                     .bind(&http_bind)
                     .map_err(|e| {
                         tracing::error!("❌ Failed to bind HTTP on {}: {}", http_bind, e);
+                        selfcheck::explain_bind_failure(&http_bind, &e);
                         e
                     })?
                     .run();
@@ -4345,40 +4765,60 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
 
             // Dedicated status page listener — plain HTTP on the configured status port
             let sp_bind = netaddr::host_port(&cli.bind, status_port);
-            let sp_server = HttpServer::new(move || {
-                App::new()
-                    .app_data(app_state3.clone())
-                    .configure(api::configure_statuspage_only)
-            })
-            .workers(http_workers)
-            .bind(&sp_bind)
-            .map_err(|e| {
-                tracing::warn!("⚠️  Failed to bind status page listener on {}: {}", sp_bind, e);
-                e
-            });
+            let sp_server = if daemon_cfg.features.status_pages {
+                HttpServer::new(move || {
+                    App::new()
+                        .app_data(app_state3.clone())
+                        .configure(api::configure_statuspage_only)
+                })
+                .workers(http_workers)
+                .disable_signals()
+                .bind(&sp_bind)
+                .map_err(|e| {
+                    tracing::warn!("⚠️  Failed to bind status page listener on {}: {}", sp_bind, e);
+                    e
+                })
+            } else {
+                info!("   Status page listener disabled (features.status_pages in wolfstack.toml)");
+                Err(std::io::Error::other("status pages disabled"))
+            };
+
+            let sp_server = sp_server.map(|sp| sp.run());
+            let mut handles = vec![https_server.handle()];
+            handles.extend(http_server_opt.as_ref().map(|s| s.handle()));
+            if let Ok(sp) = &sp_server {
+                handles.push(sp.handle());
+            }
+            lifecycle::on_shutdown_signal(handles);
+            lifecycle::mark_ready();
+            let mut listeners = vec![("api", api_port)];
+            listeners.extend(inter_node_port.map(|p| ("inter_node", p)));
+            if sp_server.is_ok() {
+                listeners.push(("status", status_port));
+            }
+            selfcheck::spawn(&cli.bind, listeners);
 
             // Run the active set of listeners. Combinatorial: HTTPS is
             // always present; HTTP and SP each may or may not be. Four
             // branches; explicit so tokio::join!'s arity is fixed in
             // each.
-            match (http_server_opt, sp_server) {
+            let served = match (http_server_opt, sp_server) {
                 (Some(http), Ok(sp)) => {
-                    let (r1, r2, r3) = tokio::join!(https_server, http, sp.run());
-                    r1?; r2?; r3?;
+                    let (r1, r2, r3) = tokio::join!(https_server, http, sp);
+                    r1.and(r2).and(r3)
                 }
                 (Some(http), Err(_)) => {
                     let (r1, r2) = tokio::join!(https_server, http);
-                    r1?; r2?;
+                    r1.and(r2)
                 }
                 (None, Ok(sp)) => {
-                    let (r1, r2) = tokio::join!(https_server, sp.run());
-                    r1?; r2?;
-                }
-                (None, Err(_)) => {
-                    https_server.await?;
+                    let (r1, r2) = tokio::join!(https_server, sp);
+                    r1.and(r2)
                 }
-            }
-            Ok(())
+                (None, Err(_)) => https_server.await,
+            };
+            lifecycle::persist(app_state_shutdown).await;
+            served
         } else {
             if cli.no_tls {
                 info!("  ⚡ HTTP mode (TLS disabled via --no-tls)");
@@ -4393,10 +4833,14 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
             info!("");
 
             let app_state2 = app_state.clone();
+            let app_state_shutdown = app_state.clone();
 
+            let http_bind = netaddr::host_port(&cli.bind, api_port);
             // Start HTTP server (same as before — no breaking changes)
             let main_server = HttpServer::new(move || {
                 let app = App::new()
+                    // Base-path HTML rewriting needs the page before it's compressed.
+                    .wrap(actix_web::middleware::from_fn(reverse_proxy::rewrite_html))
                     // Compress responses (gzip/brotli/zstd, negotiated via
                     // Accept-Encoding). web/js/app.js is ~3.9 MB uncompressed —
                     // without this every page load shipped the whole thing in
@@ -4406,7 +4850,14 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
                     // finished loading, so none of its functions existed).
                     // Compressed it's ~10x smaller and far less reset-prone.
                     .wrap(actix_web::middleware::Compress::default())
+                    .wrap(actix_web::middleware::from_fn(csrf_gate))
+                    .wrap(actix_web::middleware::from_fn(read_only_gate))
                     .wrap(actix_web::middleware::from_fn(mtls_gate))
+                    .wrap(actix_web::middleware::from_fn(monitoring::api_stats::track))
+                    .wrap(actix_web::middleware::from_fn(logging::request_id))
+                    // Outermost of the from_fn layers: rewrites the peer address
+                    // and path before anything else reads them.
+                    .wrap(actix_web::middleware::from_fn(reverse_proxy::resolve))
                     .app_data(app_state.clone())
                     .app_data(wolfhost_data.clone())
                     .app_data(actix_multipart::form::MultipartFormConfig::default().total_limit(2 * 1024 * 1024 * 1024))
@@ -4438,33 +4889,58 @@ a{color:#dc2626;text-decoration:none;}a:hover{text-decoration:underline;}
             .client_request_timeout(std::time::Duration::from_secs(3))
             .client_disconnect_timeout(std::time::Duration::from_millis(500))
             .workers(http_workers)
-            .bind(netaddr::host_port(&cli.bind, api_port))?
+            // SIGTERM/SIGINT are handled by lifecycle::on_shutdown_signal.
+            .disable_signals()
+            .bind(&http_bind)
+            .map_err(|e| {
+                tracing::error!("❌ Failed to bind HTTP on {}: {}", http_bind, e);
+                selfcheck::explain_bind_failure(&http_bind, &e);
+                e
+            })?
             .run();
 
             // Dedicated status page listener — plain HTTP on the configured status port
             let sp_bind = netaddr::host_port(&cli.bind, status_port);
-            let sp_server = HttpServer::new(move || {
-                App::new()
-                    .app_data(app_state2.clone())
-                    .configure(api::configure_statuspage_only)
-            })
-            .workers(http_workers)
-            .bind(&sp_bind)
-            .map_err(|e| {
-                tracing::warn!("⚠️  Failed to bind status page listener on {}: {}", sp_bind, e);
-                e
-            });
+            let sp_server = if daemon_cfg.features.status_pages {
+                HttpServer::new(move || {
+                    App::new()
+                        .app_data(app_state2.clone())
+                        .configure(api::configure_statuspage_only)
+                })
+                .workers(http_workers)
+                .disable_signals()
+                .bind(&sp_bind)
+                .map_err(|e| {
+                    tracing::warn!("⚠️  Failed to bind status page listener on {}: {}", sp_bind, e);
+                    e
+                })
+            } else {
+                info!("   Status page listener disabled (features.status_pages in wolfstack.toml)");
+                Err(std::io::Error::other("status pages disabled"))
+            };
 
-            match sp_server {
+            let sp_server = sp_server.map(|sp| sp.run());
+            let mut handles = vec![main_server.handle()];
+            if let Ok(sp) = &sp_server {
+                handles.push(sp.handle());
+            }
+            lifecycle::on_shutdown_signal(handles);
+            lifecycle::mark_ready();
+            let mut listeners = vec![("api", api_port)];
+            if sp_server.is_ok() {
+                listeners.push(("status", status_port));
+            }
+            selfcheck::spawn(&cli.bind, listeners);
+
+            let served = match sp_server {
                 Ok(sp) => {
-                    let (r1, r2) = tokio::join!(main_server, sp.run());
-                    r1?; r2?;
+                    let (r1, r2) = tokio::join!(main_server, sp);
+                    r1.and(r2)
                 }
-                Err(_) => {
-                    main_server.await?;
-                }
-            }
-            Ok(())
+                Err(_) => main_server.await,
+            };
+            lifecycle::persist(app_state_shutdown).await;
+            served
         }
     }
 }
@@ -4674,7 +5150,9 @@ async fn mtls_gate(
     req: actix_web::dev::ServiceRequest,
     next: actix_web::middleware::Next<impl actix_web::body::MessageBody + 'static>,
 ) -> Result<actix_web::dev::ServiceResponse<actix_web::body::BoxBody>, actix_web::Error> {
-    if crate::mtls::is_active() {
+    // Load-balancer probes carry no client cert and reveal nothing.
+    let is_probe = matches!(req.path(), "/healthz" | "/readyz");
+    if crate::mtls::is_active() && !is_probe {
         // Inter-node (cluster-secret) requests bypass the client-cert rule:
         // peers hold no client cert and authenticate with the shared secret.
         let secret_ok = req
@@ -4704,6 +5182,56 @@ async fn mtls_gate(
     Ok(next.call(req).await?.map_into_boxed_body())
 }
 
+/// CSRF gate for the dashboard session (see `auth::csrf`). A
+/// state-changing request that is authenticated only by a live session
+/// cookie must come from this origin and echo the session's CSRF token;
+/// anything else passes through to the handler's own auth unchanged.
+async fn csrf_gate(
+    req: actix_web::dev::ServiceRequest,
+    next: actix_web::middleware::Next<impl actix_web::body::MessageBody + 'static>,
+) -> Result<actix_web::dev::ServiceResponse<actix_web::body::BoxBody>, actix_web::Error> {
+    if crate::auth::csrf::is_state_changing(req.method())
+        && !crate::auth::csrf::has_credential_header(req.headers())
+    {
+        let expected = req.cookie("wolfstack_session").and_then(|c| {
+            req.app_data::<actix_web::web::Data<crate::api::AppState>>()
+                .and_then(|st| st.sessions.csrf_token(c.value()))
+        });
+        if let Some(expected) = expected
+            && let Err(e) = crate::auth::csrf::verify(req.headers(), &expected)
+        {
+            tracing::warn!("csrf: refused {} {} from {}: {}", req.method(), req.path(),
+                req.connection_info().peer_addr().unwrap_or("?"), e);
+            return Ok(req
+                .into_response(actix_web::HttpResponse::Forbidden().json(serde_json::json!({ "error": e })))
+                .map_into_boxed_body());
+        }
+    }
+    Ok(next.call(req).await?.map_into_boxed_body())
+}
+
+/// Read-only mode (`server.read_only`): refuse writes before any handler
+/// runs — see `auth::read_only`. Peers presenting the cluster secret pass.
+async fn read_only_gate(
+    req: actix_web::dev::ServiceRequest,
+    next: actix_web::middleware::Next<impl actix_web::body::MessageBody + 'static>,
+) -> Result<actix_web::dev::ServiceResponse<actix_web::body::BoxBody>, actix_web::Error> {
+    if crate::auth::read_only::enabled() && crate::auth::read_only::refuses(req.method(), req.path()) {
+        let peer = req.headers().get("X-WolfStack-Secret").and_then(|v| v.to_str().ok())
+            .zip(req.app_data::<actix_web::web::Data<crate::api::AppState>>())
+            .is_some_and(|(secret, st)| crate::auth::validate_inter_node_secret(secret, &st.cluster_secret));
+        if !peer {
+            return Ok(req
+                .into_response(actix_web::HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": crate::auth::read_only::message(),
+                    "read_only": true,
+                })))
+                .map_into_boxed_body());
+        }
+    }
+    Ok(next.call(req).await?.map_into_boxed_body())
+}
+
 /// Find the web directory — check multiple locations
 fn find_web_dir() -> String {
     let candidates = [
@@ -0,0 +1,928 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Scheduled host reboot windows.
+//!
+//! A window is a UTC cron schedule plus a set of nodes (explicit ids and/or
+//! a label selector). When it fires, the node that holds the window walks
+//! the targets one at a time — peers first, itself last — and for each:
+//!
+//! 1. asks the target to begin (`POST /api/maintenance/reboot/begin`). The
+//!    target runs the pre-hook, gracefully stops its running guests
+//!    (remembering which), then reboots a few seconds after replying;
+//! 2. polls the target's job (`GET /api/maintenance/reboot/status`) until
+//!    the node is back and reports the job finished — on start-up the
+//!    target restarts the guests it stopped and runs the post-hook;
+//! 3. records every step, and stops the walk at the first failure unless
+//!    the window says otherwise.
+//!
+//! Both halves persist their progress: the target keeps its job in
+//! `reboot-pending.json` so the restore survives the reboot, and the
+//! orchestrator keeps the run in `reboot-runs.json` so a run that reboots
+//! its own node carries on after start-up. A finished run sends a report
+//! through the alert channels and stays in the history (last 50 runs).
+//! Windows live on the node they were created on; they aren't synced.
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+
+const TICK_SECS: u64 = 30;
+const POLL_SECS: u64 = 15;
+/// Pause between replying to `begin` and rebooting, so the reply gets out.
+const REBOOT_DELAY_SECS: u64 = 5;
+/// Grace after start-up before restoring guests — Docker and LXC may still
+/// be coming up.
+const RESTORE_DELAY_SECS: u64 = 20;
+const KEEP_RUNS: usize = 50;
+
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+static RUNS_LOCK: Mutex<()> = Mutex::new(());
+static JOB_LOCK: Mutex<()> = Mutex::new(());
+
+fn config_file() -> String { crate::paths::get().maintenance_config }
+fn runs_file() -> String { crate::paths::get().maintenance_runs }
+fn job_file() -> String { crate::paths::get().maintenance_job }
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// What happens to a node's guests around the reboot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuestPolicy {
+    /// Gracefully stop running containers and VMs, start them again after.
+    #[default]
+    StopRestart,
+    /// Reboot with guests as they are (their own autostart applies).
+    Leave,
+}
+
+fn default_true() -> bool { true }
+fn default_hook_timeout() -> u64 { 300 }
+fn default_stop_timeout() -> u64 { 180 }
+fn default_rejoin_timeout() -> u64 { 20 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebootWindow {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 5-field cron, UTC. Empty means "run now" only.
+    #[serde(default)]
+    pub cron: String,
+    #[serde(default)]
+    pub node_ids: Vec<String>,
+    /// Label selector (`role=db,env!=prod`), added to `node_ids`.
+    #[serde(default)]
+    pub selector: String,
+    #[serde(default)]
+    pub guest_policy: GuestPolicy,
+    /// Shell run on each node before its guests are stopped. A non-zero
+    /// exit skips that node's reboot.
+    #[serde(default)]
+    pub pre_hook: String,
+    /// Shell run on each node after its guests are back.
+    #[serde(default)]
+    pub post_hook: String,
+    #[serde(default = "default_hook_timeout")]
+    pub hook_timeout_secs: u64,
+    /// How long a VM gets to shut down before it's forced off.
+    #[serde(default = "default_stop_timeout")]
+    pub stop_timeout_secs: u64,
+    /// How long a node gets to reboot, rejoin and restore its guests.
+    #[serde(default = "default_rejoin_timeout")]
+    pub rejoin_timeout_minutes: u64,
+    /// Keep going with the remaining nodes after one fails.
+    #[serde(default)]
+    pub continue_on_failure: bool,
+}
+
+impl RebootWindow {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".into());
+        }
+        let cron = self.cron.trim();
+        if !cron.is_empty() {
+            if cron.split_whitespace().count() != 5 {
+                return Err("cron must have 5 fields (minute hour day month weekday)".into());
+            }
+            if !cron.chars().all(|c| c.is_ascii_digit() || " */,-".contains(c)) {
+                return Err("cron may only use digits and * / , -".into());
+            }
+        }
+        if self.node_ids.is_empty() && self.selector.trim().is_empty() {
+            return Err("pick at least one node or a label selector".into());
+        }
+        crate::agent::labels::LabelSelector::parse(&self.selector)
+            .map_err(|e| format!("Invalid selector: {}", e))?;
+        if !(10..=3600).contains(&self.hook_timeout_secs) {
+            return Err("hook_timeout_secs must be between 10 and 3600".into());
+        }
+        if !(10..=3600).contains(&self.stop_timeout_secs) {
+            return Err("stop_timeout_secs must be between 10 and 3600".into());
+        }
+        if !(5..=240).contains(&self.rejoin_timeout_minutes) {
+            return Err("rejoin_timeout_minutes must be between 5 and 240".into());
+        }
+        Ok(())
+    }
+
+    /// Whether the schedule fires at `now` (UTC) and hasn't already fired
+    /// this minute — a run started in the same minute counts.
+    pub fn is_due(&self, now: chrono::NaiveDateTime, runs: &[WindowRun]) -> bool {
+        use chrono::Timelike;
+        if !self.enabled || self.cron.trim().is_empty() {
+            return false;
+        }
+        if !crate::wolfflow::cron_matches(self.cron.trim(), &now) {
+            return false;
+        }
+        let minute = now.with_second(0).unwrap_or(now).and_utc().timestamp().max(0) as u64;
+        !runs.iter().any(|r| r.window.id == self.id && r.started >= minute)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    #[serde(default)]
+    pub windows: Vec<RebootWindow>,
+}
+
+impl MaintenanceConfig {
+    pub fn load() -> Self {
+        match std::fs::read_to_string(config_file()) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Hooks run as root, so the file is written like the other secrets.
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(&config_file(), json).map_err(|e| e.to_string())
+    }
+}
+
+/// Create (empty id) or replace a window.
+pub fn save_window(mut window: RebootWindow) -> Result<RebootWindow, String> {
+    window.validate()?;
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = MaintenanceConfig::load();
+    match cfg.windows.iter().position(|w| !window.id.is_empty() && w.id == window.id) {
+        Some(i) => cfg.windows[i] = window.clone(),
+        None => {
+            window.id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+            cfg.windows.push(window.clone());
+        }
+    }
+    cfg.save()?;
+    Ok(window)
+}
+
+pub fn delete_window(id: &str) -> Result<bool, String> {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = MaintenanceConfig::load();
+    let before = cfg.windows.len();
+    cfg.windows.retain(|w| w.id != id);
+    if cfg.windows.len() == before { return Ok(false); }
+    cfg.save()?;
+    Ok(true)
+}
+
+// ─── Run history (orchestrator side) ───
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub ts: u64,
+    pub name: String,
+    pub ok: bool,
+    #[serde(default)]
+    pub detail: String,
+}
+
+impl Step {
+    fn new(name: &str, ok: bool, detail: impl Into<String>) -> Self {
+        Self { ts: now(), name: name.to_string(), ok, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    #[default]
+    Pending,
+    Rebooting,
+    Done,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeRun {
+    pub node_id: String,
+    pub hostname: String,
+    #[serde(default)]
+    pub status: NodeStatus,
+    #[serde(default)]
+    pub job_id: String,
+    #[serde(default)]
+    pub started: u64,
+    #[serde(default)]
+    pub finished: u64,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    #[default]
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowRun {
+    pub id: String,
+    /// The window as it was when the run started.
+    pub window: RebootWindow,
+    /// `schedule` or the user who pressed "Run now".
+    #[serde(default)]
+    pub trigger: String,
+    pub started: u64,
+    #[serde(default)]
+    pub finished: u64,
+    #[serde(default)]
+    pub status: RunStatus,
+    #[serde(default)]
+    pub nodes: Vec<NodeRun>,
+}
+
+/// Newest first.
+pub fn load_runs() -> Vec<WindowRun> {
+    std::fs::read_to_string(runs_file())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn store_run(run: &WindowRun) {
+    let _guard = RUNS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut runs = load_runs();
+    match runs.iter().position(|r| r.id == run.id) {
+        Some(i) => runs[i] = run.clone(),
+        None => runs.insert(0, run.clone()),
+    }
+    runs.truncate(KEEP_RUNS);
+    let path = runs_file();
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string_pretty(&runs) {
+        Ok(json) => if let Err(e) = std::fs::write(&path, json) {
+            warn!("Reboot windows: could not write {}: {}", path, e);
+        },
+        Err(e) => warn!("Reboot windows: could not serialise runs: {}", e),
+    }
+}
+
+/// Peers in the order given, this node last — rebooting ourselves first
+/// would stall the walk until we're back.
+fn order_targets(mut nodes: Vec<crate::agent::Node>) -> Vec<crate::agent::Node> {
+    nodes.sort_by_key(|n| n.is_self);
+    nodes
+}
+
+fn resolve_targets(state: &AppState, window: &RebootWindow) -> Result<Vec<crate::agent::Node>, String> {
+    let selector = crate::agent::labels::LabelSelector::parse(&window.selector)
+        .map_err(|e| format!("Invalid selector: {}", e))?;
+    let mut nodes: Vec<crate::agent::Node> = Vec::new();
+    let all = state.cluster.get_all_nodes();
+    for id in &window.node_ids {
+        if let Some(n) = all.iter().find(|n| &n.id == id) {
+            nodes.push(n.clone());
+        }
+    }
+    if !selector.is_empty() {
+        for n in state.cluster.nodes_matching(&selector) {
+            if !nodes.iter().any(|x| x.id == n.id) {
+                nodes.push(n);
+            }
+        }
+    }
+    nodes.retain(|n| n.node_type == "wolfstack");
+    if nodes.is_empty() {
+        return Err("No WolfStack nodes match this window".into());
+    }
+    Ok(order_targets(nodes))
+}
+
+/// Start a run of `window` now. Only one run goes at a time.
+pub fn trigger(state: web::Data<AppState>, window: RebootWindow, trigger: &str) -> Result<WindowRun, String> {
+    if load_runs().iter().any(|r| r.status == RunStatus::Running) {
+        return Err("A reboot window is already running".into());
+    }
+    let targets = resolve_targets(&state, &window)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let run = WindowRun {
+        nodes: targets.iter().enumerate().map(|(i, n)| NodeRun {
+            node_id: n.id.clone(),
+            hostname: n.display_name.clone().unwrap_or_else(|| n.hostname.clone()),
+            job_id: format!("{}-{}", &id[..8], i),
+            ..Default::default()
+        }).collect(),
+        id,
+        window,
+        trigger: trigger.to_string(),
+        started: now(),
+        ..Default::default()
+    };
+    store_run(&run);
+    info!("Reboot window '{}' started ({}): {} node(s)", run.window.name, run.trigger, run.nodes.len());
+    let out = run.clone();
+    tokio::spawn(drive(state, run));
+    Ok(out)
+}
+
+/// Walk the run's nodes, resuming wherever it was left.
+async fn drive(state: web::Data<AppState>, mut run: WindowRun) {
+    for i in 0..run.nodes.len() {
+        if run.nodes[i].status == NodeStatus::Pending {
+            begin_node(&state, &mut run, i).await;
+            store_run(&run);
+        }
+        if run.nodes[i].status == NodeStatus::Rebooting {
+            wait_node(&state, &mut run, i).await;
+            store_run(&run);
+        }
+        if run.nodes[i].status == NodeStatus::Failed && !run.window.continue_on_failure {
+            for n in run.nodes.iter_mut().filter(|n| n.status == NodeStatus::Pending) {
+                n.status = NodeStatus::Skipped;
+            }
+            break;
+        }
+    }
+    run.finished = now();
+    run.status = if run.nodes.iter().all(|n| n.status == NodeStatus::Done) {
+        RunStatus::Succeeded
+    } else {
+        RunStatus::Failed
+    };
+    store_run(&run);
+    report(&run).await;
+}
+
+async fn begin_node(state: &AppState, run: &mut WindowRun, i: usize) {
+    let node = state.cluster.get_all_nodes().into_iter().find(|n| n.id == run.nodes[i].node_id);
+    let entry = &mut run.nodes[i];
+    entry.started = now();
+    let Some(node) = node.filter(|n| n.online) else {
+        entry.status = NodeStatus::Failed;
+        entry.finished = now();
+        entry.steps.push(Step::new("begin", false, "node is offline — not rebooting it"));
+        return;
+    };
+    let w = &run.window;
+    let req = BeginRequest {
+        job_id: entry.job_id.clone(),
+        guest_policy: w.guest_policy,
+        pre_hook: w.pre_hook.clone(),
+        post_hook: w.post_hook.clone(),
+        hook_timeout_secs: w.hook_timeout_secs,
+        stop_timeout_secs: w.stop_timeout_secs,
+    };
+    // Recorded before the call: if the target is us, we won't see the reply.
+    entry.status = NodeStatus::Rebooting;
+    let snapshot = run.clone();
+    store_run(&snapshot);
+    let entry = &mut run.nodes[i];
+    let timeout = req.hook_timeout_secs + req.stop_timeout_secs + 60;
+    let result = if node.is_self {
+        begin(state, req).await
+    } else {
+        call_begin(state, &node, &req, timeout).await
+    };
+    match result {
+        Ok(job) => {
+            entry.steps = job.steps.clone();
+            if job.status == JobStatus::Failed {
+                entry.status = NodeStatus::Failed;
+                entry.finished = now();
+            }
+        }
+        Err(e) => {
+            entry.status = NodeStatus::Failed;
+            entry.finished = now();
+            entry.steps.push(Step::new("begin", false, e));
+        }
+    }
+}
+
+async fn wait_node(state: &AppState, run: &mut WindowRun, i: usize) {
+    let deadline = run.nodes[i].started + run.window.rejoin_timeout_minutes * 60
+        + run.window.hook_timeout_secs * 2 + run.window.stop_timeout_secs;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(POLL_SECS)).await;
+        let entry = &mut run.nodes[i];
+        let node = state.cluster.get_all_nodes().into_iter().find(|n| n.id == entry.node_id);
+        let job = match node {
+            Some(n) if n.is_self => load_job().filter(|j| j.id == entry.job_id),
+            Some(n) => call_status(state, &n, &entry.job_id).await,
+            None => None,
+        };
+        if let Some(job) = job {
+            entry.steps = job.steps.clone();
+            match job.status {
+                JobStatus::Done => {
+                    entry.status = NodeStatus::Done;
+                    entry.finished = now();
+                    return;
+                }
+                JobStatus::Failed => {
+                    entry.status = NodeStatus::Failed;
+                    entry.finished = now();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        if now() > deadline {
+            entry.status = NodeStatus::Failed;
+            entry.finished = now();
+            entry.steps.push(Step::new("rejoin", false, format!(
+                "node did not come back and restore its guests within {} minutes",
+                run.window.rejoin_timeout_minutes)));
+            return;
+        }
+    }
+}
+
+async fn call_begin(state: &AppState, node: &crate::agent::Node, req: &BeginRequest, timeout: u64) -> Result<LocalJob, String> {
+    let mut last_err = String::new();
+    for url in crate::api::build_node_urls(&node.address, node.port, "/api/maintenance/reboot/begin") {
+        match crate::api::API_HTTP_CLIENT.post(&url)
+            .timeout(std::time::Duration::from_secs(timeout))
+            .header("X-WolfStack-Secret", state.cluster_secret.clone())
+            .json(req)
+            .send()
+            .await
+        {
+            Ok(r) => {
+                let ok = r.status().is_success();
+                let data = r.json::<serde_json::Value>().await.unwrap_or_default();
+                if !ok {
+                    return Err(data.get("error").and_then(|v| v.as_str()).unwrap_or("Remote request failed").to_string());
+                }
+                return serde_json::from_value(data).map_err(|e| format!("Unexpected reply: {}", e));
+            }
+            Err(e) => last_err = e.to_string(),
+        }
+    }
+    Err(format!("Node unreachable: {}", last_err))
+}
+
+/// `None` while the node is down or doesn't know the job yet.
+async fn call_status(state: &AppState, node: &crate::agent::Node, job_id: &str) -> Option<LocalJob> {
+    let path = format!("/api/maintenance/reboot/status?job={}", job_id);
+    for url in crate::api::build_node_urls(&node.address, node.port, &path) {
+        let Ok(r) = crate::api::API_HTTP_CLIENT.get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .header("X-WolfStack-Secret", state.cluster_secret.clone())
+            .send()
+            .await else { continue };
+        if !r.status().is_success() { return None; }
+        return r.json::<LocalJob>().await.ok();
+    }
+    None
+}
+
+fn report_text(run: &WindowRun) -> (String, String) {
+    let ok = run.nodes.iter().filter(|n| n.status == NodeStatus::Done).count();
+    let title = format!("Reboot window '{}': {}/{} nodes rebooted", run.window.name, ok, run.nodes.len());
+    let mut body = String::new();
+    for n in &run.nodes {
+        let status = match n.status {
+            NodeStatus::Done => "ok",
+            NodeStatus::Failed => "FAILED",
+            NodeStatus::Skipped => "skipped",
+            NodeStatus::Pending | NodeStatus::Rebooting => "unfinished",
+        };
+        body.push_str(&format!("{} — {}", n.hostname, status));
+        if let Some(step) = n.steps.iter().rev().find(|s| !s.ok) {
+            body.push_str(&format!(" ({}: {})", step.name, step.detail));
+        }
+        body.push('\n');
+    }
+    (title, body)
+}
+
+async fn report(run: &WindowRun) {
+    let (title, body) = report_text(run);
+    info!("{}", title);
+    crate::alerting::send_local_alert(crate::alerting::AlertCategory::Lifecycle, &title, &body).await;
+}
+
+// ─── Reboot job (target side) ───
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeginRequest {
+    pub job_id: String,
+    #[serde(default)]
+    pub guest_policy: GuestPolicy,
+    #[serde(default)]
+    pub pre_hook: String,
+    #[serde(default)]
+    pub post_hook: String,
+    #[serde(default = "default_hook_timeout")]
+    pub hook_timeout_secs: u64,
+    #[serde(default = "default_stop_timeout")]
+    pub stop_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    #[default]
+    Preparing,
+    Rebooting,
+    Restoring,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Guest {
+    /// `docker`, `lxc` or `vm`.
+    pub kind: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalJob {
+    pub id: String,
+    #[serde(default)]
+    pub status: JobStatus,
+    /// `/proc/sys/kernel/random/boot_id` when the reboot was requested.
+    #[serde(default)]
+    pub boot_id: String,
+    #[serde(default)]
+    pub stopped: Vec<Guest>,
+    #[serde(default)]
+    pub post_hook: String,
+    #[serde(default = "default_hook_timeout")]
+    pub hook_timeout_secs: u64,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+pub fn load_job() -> Option<LocalJob> {
+    std::fs::read_to_string(job_file()).ok().and_then(|s| serde_json::from_str(&s).ok())
+}
+
+fn save_job(job: &LocalJob) {
+    let _guard = JOB_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = job_file();
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(job)
+        && let Err(e) = crate::paths::write_secure_atomic(&path, json) {
+        warn!("Reboot windows: could not write {}: {}", path, e);
+    }
+}
+
+fn boot_id() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/random/boot_id").unwrap_or_default().trim().to_string()
+}
+
+async fn run_hook(cmd: &str, timeout_secs: u64) -> Result<String, String> {
+    let child = tokio::process::Command::new("bash")
+        .args(["-c", cmd])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start hook: {}", e))?;
+    let out = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), child.wait_with_output())
+        .await
+        .map_err(|_| format!("timed out after {}s", timeout_secs))?
+        .map_err(|e| e.to_string())?;
+    let mut text = String::from_utf8_lossy(&out.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&out.stderr));
+    let text: String = text.trim().chars().rev().take(1000).collect::<Vec<_>>().into_iter().rev().collect();
+    if out.status.success() {
+        Ok(text)
+    } else {
+        Err(format!("exit {}: {}", out.status.code().unwrap_or(-1), text))
+    }
+}
+
+fn running_guests(state: &AppState) -> Vec<Guest> {
+    let mut guests = Vec::new();
+    for c in crate::containers::docker_list_all_cached() {
+        if c.state == "running" {
+            guests.push(Guest { kind: "docker".into(), name: c.name });
+        }
+    }
+    for c in crate::containers::lxc_list_all_cached() {
+        if c.state == "running" {
+            guests.push(Guest { kind: "lxc".into(), name: c.name });
+        }
+    }
+    for vm in state.vms.lock().unwrap().list_vms_readonly() {
+        if vm.running {
+            guests.push(Guest { kind: "vm".into(), name: vm.name });
+        }
+    }
+    guests
+}
+
+fn vm_running(state: &AppState, name: &str) -> bool {
+    state.vms.lock().unwrap().list_vms_readonly().iter().any(|v| v.name == name && v.running)
+}
+
+/// Pre-hook, stop guests, then reboot shortly after returning. A failed
+/// pre-hook comes back as a `Failed` job and nothing is stopped.
+pub async fn begin(state: &AppState, req: BeginRequest) -> Result<LocalJob, String> {
+    if req.job_id.is_empty() {
+        return Err("job_id is required".into());
+    }
+    if let Some(job) = load_job() {
+        if job.id == req.job_id {
+            return Ok(job);
+        }
+        if matches!(job.status, JobStatus::Preparing | JobStatus::Rebooting | JobStatus::Restoring) {
+            return Err(format!("reboot job {} is still in progress on this node", job.id));
+        }
+    }
+    let mut job = LocalJob {
+        id: req.job_id.clone(),
+        post_hook: req.post_hook.clone(),
+        hook_timeout_secs: req.hook_timeout_secs,
+        boot_id: boot_id(),
+        ..Default::default()
+    };
+    save_job(&job);
+
+    if !req.pre_hook.trim().is_empty() {
+        match run_hook(&req.pre_hook, req.hook_timeout_secs).await {
+            Ok(out) => job.steps.push(Step::new("pre-hook", true, out)),
+            Err(e) => {
+                job.steps.push(Step::new("pre-hook", false, e));
+                job.status = JobStatus::Failed;
+                save_job(&job);
+                return Ok(job);
+            }
+        }
+    }
+
+    if req.guest_policy == GuestPolicy::StopRestart {
+        job.stopped = running_guests(state);
+        save_job(&job);
+        let mut failures = Vec::new();
+        for g in &job.stopped {
+            let result = match g.kind.as_str() {
+                "docker" => { let n = g.name.clone(); tokio::task::spawn_blocking(move || crate::containers::docker_stop(&n).map(|_| ())).await }
+                "lxc" => { let n = g.name.clone(); tokio::task::spawn_blocking(move || crate::containers::lxc_stop(&n).map(|_| ())).await }
+                _ => Ok(state.vms.lock().unwrap().stop_vm(&g.name, false)),
+            };
+            if let Err(e) = result.unwrap_or_else(|e| Err(e.to_string())) {
+                failures.push(format!("{} {}: {}", g.kind, g.name, e));
+            }
+        }
+        // A graceful VM stop only asks the guest; give it time, then force.
+        let deadline = now() + req.stop_timeout_secs;
+        let mut forced = Vec::new();
+        for g in job.stopped.iter().filter(|g| g.kind == "vm") {
+            while vm_running(state, &g.name) && now() < deadline {
+                tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+            }
+            if vm_running(state, &g.name) {
+                let _ = state.vms.lock().unwrap().stop_vm(&g.name, true);
+                forced.push(g.name.clone());
+            }
+        }
+        let mut detail = format!("{} guest(s) stopped", job.stopped.len());
+        if !forced.is_empty() {
+            detail.push_str(&format!("; forced off after {}s: {}", req.stop_timeout_secs, forced.join(", ")));
+        }
+        if !failures.is_empty() {
+            detail.push_str(&format!("; errors: {}", failures.join("; ")));
+        }
+        job.steps.push(Step::new("stop guests", failures.is_empty(), detail));
+    }
+
+    job.status = JobStatus::Rebooting;
+    job.steps.push(Step::new("reboot", true, "reboot requested"));
+    save_job(&job);
+    info!("Reboot window: job {} rebooting this node in {}s", job.id, REBOOT_DELAY_SECS);
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(REBOOT_DELAY_SECS)).await;
+        let out = tokio::process::Command::new("systemctl").arg("reboot").output().await;
+        let err = match out {
+            Ok(o) if o.status.success() => return,
+            Ok(o) => String::from_utf8_lossy(&o.stderr).trim().to_string(),
+            Err(e) => e.to_string(),
+        };
+        warn!("Reboot window: systemctl reboot failed: {}", err);
+        if let Some(mut job) = load_job() {
+            job.steps.push(Step::new("reboot", false, format!("systemctl reboot failed: {}", err)));
+            save_job(&job);
+        }
+    });
+    Ok(job)
+}
+
+/// Whether a job that was `Rebooting` at start-up actually saw a reboot.
+fn rebooted(job: &LocalJob, boot_now: &str) -> bool {
+    !job.boot_id.is_empty() && !boot_now.is_empty() && job.boot_id != boot_now
+}
+
+/// Finish a job interrupted by the reboot (or by a crash part-way): bring
+/// back the guests it stopped and run the post-hook.
+async fn restore(state: &AppState) {
+    let Some(mut job) = load_job() else { return };
+    if !matches!(job.status, JobStatus::Preparing | JobStatus::Rebooting | JobStatus::Restoring) {
+        return;
+    }
+    let mut ok = job.steps.iter().all(|s| s.ok);
+    match job.status {
+        JobStatus::Rebooting if rebooted(&job, &boot_id()) => {
+            job.steps.push(Step::new("rejoin", true, "node rebooted and WolfStack is back"));
+        }
+        JobStatus::Rebooting => {
+            job.steps.push(Step::new("rejoin", false, "WolfStack restarted but the host did not reboot"));
+            ok = false;
+        }
+        JobStatus::Preparing => {
+            job.steps.push(Step::new("rejoin", false, "WolfStack restarted before the reboot was requested"));
+            ok = false;
+        }
+        _ => {}
+    }
+    job.status = JobStatus::Restoring;
+    save_job(&job);
+    tokio::time::sleep(std::time::Duration::from_secs(RESTORE_DELAY_SECS)).await;
+
+    if !job.stopped.is_empty() {
+        let mut failures = Vec::new();
+        for g in &job.stopped {
+            let mut last = Ok(());
+            for attempt in 0..3 {
+                if attempt > 0 {
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                }
+                last = match g.kind.as_str() {
+                    "docker" => { let n = g.name.clone(); tokio::task::spawn_blocking(move || crate::containers::docker_start(&n).map(|_| ())).await.unwrap_or_else(|e| Err(e.to_string())) }
+                    "lxc" => { let n = g.name.clone(); tokio::task::spawn_blocking(move || crate::containers::lxc_start(&n).map(|_| ())).await.unwrap_or_else(|e| Err(e.to_string())) }
+                    _ if vm_running(state, &g.name) => Ok(()),
+                    _ => state.vms.lock().unwrap().start_vm(&g.name),
+                };
+                if last.is_ok() { break; }
+            }
+            if let Err(e) = last {
+                failures.push(format!("{} {}: {}", g.kind, g.name, e));
+            }
+        }
+        let mut detail = format!("{} of {} guest(s) started", job.stopped.len() - failures.len(), job.stopped.len());
+        if !failures.is_empty() {
+            detail.push_str(&format!("; errors: {}", failures.join("; ")));
+            ok = false;
+        }
+        job.steps.push(Step::new("start guests", failures.is_empty(), detail));
+        save_job(&job);
+    }
+
+    if !job.post_hook.trim().is_empty() {
+        match run_hook(&job.post_hook, job.hook_timeout_secs).await {
+            Ok(out) => job.steps.push(Step::new("post-hook", true, out)),
+            Err(e) => {
+                job.steps.push(Step::new("post-hook", false, e));
+                ok = false;
+            }
+        }
+    }
+    job.status = if ok { JobStatus::Done } else { JobStatus::Failed };
+    save_job(&job);
+    info!("Reboot window: job {} finished ({:?})", job.id, job.status);
+}
+
+/// Restore any interrupted local job, resume any run this node was driving,
+/// then fire windows as they come due.
+pub fn start(state: web::Data<AppState>) {
+    tokio::spawn(async move {
+        let restore_state = state.clone();
+        tokio::spawn(async move { restore(&restore_state).await });
+
+        for run in load_runs().into_iter().filter(|r| r.status == RunStatus::Running) {
+            info!("Reboot window '{}': resuming run {}", run.window.name, run.id);
+            tokio::spawn(drive(state.clone(), run));
+        }
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(TICK_SECS)).await;
+            let now = chrono::Utc::now().naive_utc();
+            let runs = load_runs();
+            for window in MaintenanceConfig::load().windows {
+                if !window.is_due(now, &runs) { continue; }
+                if let Err(e) = trigger(state.clone(), window.clone(), "schedule") {
+                    warn!("Reboot window '{}' not started: {}", window.name, e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> chrono::NaiveDateTime {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn window() -> RebootWindow {
+        RebootWindow {
+            id: "w1".into(),
+            name: "Patch Sunday".into(),
+            enabled: true,
+            cron: "0 3 * * 0".into(),
+            selector: "role=web".into(),
+            hook_timeout_secs: 300,
+            stop_timeout_secs: 180,
+            rejoin_timeout_minutes: 20,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn due_once_per_matching_minute() {
+        let w = window();
+        // 2026-10-18 is a Sunday.
+        assert!(w.is_due(at("2026-10-18 03:00:10"), &[]));
+        assert!(!w.is_due(at("2026-10-18 03:01:00"), &[]));
+        assert!(!w.is_due(at("2026-10-19 03:00:00"), &[]));
+
+        let started = at("2026-10-18 03:00:05").and_utc().timestamp() as u64;
+        let runs = vec![WindowRun { id: "r".into(), window: w.clone(), started, ..Default::default() }];
+        assert!(!w.is_due(at("2026-10-18 03:00:40"), &runs));
+
+        let off = RebootWindow { enabled: false, ..w.clone() };
+        assert!(!off.is_due(at("2026-10-18 03:00:10"), &[]));
+        let manual = RebootWindow { cron: String::new(), ..w };
+        assert!(!manual.is_due(at("2026-10-18 03:00:10"), &[]));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(window().validate().is_ok());
+        assert!(RebootWindow { cron: "0 3 * *".into(), ..window() }.validate().is_err());
+        assert!(RebootWindow { cron: "0 3 * * 0; rm".into(), ..window() }.validate().is_err());
+        assert!(RebootWindow { selector: String::new(), ..window() }.validate().is_err());
+        assert!(RebootWindow { selector: String::new(), node_ids: vec!["n1".into()], ..window() }.validate().is_ok());
+        assert!(RebootWindow { rejoin_timeout_minutes: 1, ..window() }.validate().is_err());
+    }
+
+    #[test]
+    fn reboot_detected_from_boot_id() {
+        let job = LocalJob { boot_id: "aaa".into(), ..Default::default() };
+        assert!(rebooted(&job, "bbb"));
+        assert!(!rebooted(&job, "aaa"));
+        assert!(!rebooted(&LocalJob::default(), "bbb"));
+    }
+
+    #[test]
+    fn report_names_the_failing_step() {
+        let run = WindowRun {
+            window: window(),
+            nodes: vec![
+                NodeRun { hostname: "web1".into(), status: NodeStatus::Done, ..Default::default() },
+                NodeRun {
+                    hostname: "web2".into(),
+                    status: NodeStatus::Failed,
+                    steps: vec![Step::new("pre-hook", false, "exit 1: drain failed")],
+                    ..Default::default()
+                },
+                NodeRun { hostname: "web3".into(), status: NodeStatus::Skipped, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let (title, body) = report_text(&run);
+        assert_eq!(title, "Reboot window 'Patch Sunday': 1/3 nodes rebooted");
+        assert!(body.contains("web2 — FAILED (pre-hook: exit 1: drain failed)"));
+        assert!(body.contains("web3 — skipped"));
+    }
+}
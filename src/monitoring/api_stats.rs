@@ -0,0 +1,274 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Per-endpoint API latency and error counts.
+//!
+//! [`track`] wraps every API call and files its duration under the route
+//! *pattern* (`/api/containers/docker/{id}/stats`, not the concrete path),
+//! so the table stays as small as the route list however many containers
+//! or nodes there are. Durations go into fixed Prometheus-style buckets —
+//! no per-request samples are kept — and percentiles in the summary are
+//! read off those buckets, so they are upper bounds, not exact values.
+//!
+//! Time is measured until the handler returns its response; streamed
+//! bodies (downloads, SSE, WebSocket upgrades) stop the clock at the
+//! first byte, not the last.
+//!
+//! Counters live in memory and start from zero at every restart (or
+//! [`reset`]), as Prometheus counters are expected to.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+/// Route label for `/api/` requests no route matched (404s from typos,
+/// old clients), so they share one row instead of one per path.
+pub const UNMATCHED: &str = "unmatched";
+
+/// Upper bounds (seconds) of the latency buckets.
+pub const BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Default, Clone)]
+struct EndpointStats {
+    count: u64,
+    /// 5xx responses, and handler errors that never produced one.
+    server_errors: u64,
+    /// 4xx responses — mostly auth failures and bad input.
+    client_errors: u64,
+    sum_secs: f64,
+    max_secs: f64,
+    /// Non-cumulative: `buckets[i]` counts requests in
+    /// `(BUCKETS[i-1], BUCKETS[i]]`; the last slot is everything slower.
+    buckets: [u64; BUCKETS.len() + 1],
+}
+
+struct Stats {
+    since: chrono::DateTime<chrono::Utc>,
+    /// Keyed by (method, route pattern).
+    endpoints: HashMap<(String, String), EndpointStats>,
+}
+
+static STATS: LazyLock<Mutex<Stats>> = LazyLock::new(|| Mutex::new(Stats {
+    since: chrono::Utc::now(),
+    endpoints: HashMap::new(),
+}));
+
+fn record(method: &str, route: &str, status: u16, secs: f64) {
+    let mut stats = STATS.lock().unwrap();
+    let e = stats.endpoints.entry((method.to_string(), route.to_string())).or_default();
+    e.count += 1;
+    if status >= 500 {
+        e.server_errors += 1;
+    } else if status >= 400 {
+        e.client_errors += 1;
+    }
+    e.sum_secs += secs;
+    e.max_secs = e.max_secs.max(secs);
+    let slot = BUCKETS.iter().position(|b| secs <= *b).unwrap_or(BUCKETS.len());
+    e.buckets[slot] += 1;
+}
+
+/// Middleware: time the call and record it under its route pattern. Only
+/// `/api/` routes are tracked; static files and the SPA are not. The
+/// pattern is only known once routing has run, so it is read from the
+/// response's request; a handler error that produced no response is
+/// counted as [`UNMATCHED`].
+pub async fn track(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    if !req.path().starts_with("/api/") {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    }
+    let method = req.method().to_string();
+    let started = Instant::now();
+    let result = next.call(req).await;
+    let secs = started.elapsed().as_secs_f64();
+    let (route, status) = match &result {
+        Ok(res) => (
+            res.request().match_pattern().unwrap_or_else(|| UNMATCHED.to_string()),
+            res.status().as_u16(),
+        ),
+        Err(e) => (UNMATCHED.to_string(), e.as_response_error().status_code().as_u16()),
+    };
+    record(&method, &route, status, secs);
+    Ok(result?.map_into_boxed_body())
+}
+
+/// Clear every counter.
+pub fn reset() {
+    let mut stats = STATS.lock().unwrap();
+    stats.endpoints.clear();
+    stats.since = chrono::Utc::now();
+}
+
+#[derive(Serialize, Clone)]
+pub struct EndpointSummary {
+    pub method: String,
+    pub route: String,
+    pub count: u64,
+    pub server_errors: u64,
+    pub client_errors: u64,
+    /// Share of calls that were 5xx, 0–1.
+    pub error_rate: f64,
+    pub avg_ms: f64,
+    /// Bucket upper bounds the percentile falls in; `None` when it is
+    /// past the last bucket (slower than 30s).
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub max_ms: f64,
+    /// Total time spent in this endpoint — what to sort by to find the
+    /// handlers that cost the most overall.
+    pub total_ms: f64,
+}
+
+/// Upper bound of the bucket holding quantile `q` (0–1).
+fn bucket_quantile(buckets: &[u64], count: u64, q: f64) -> Option<f64> {
+    if count == 0 {
+        return Some(0.0);
+    }
+    let rank = (q * count as f64).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (i, n) in buckets.iter().enumerate() {
+        seen += n;
+        if seen >= rank {
+            return BUCKETS.get(i).map(|b| b * 1000.0);
+        }
+    }
+    None
+}
+
+fn summarise(method: &str, route: &str, e: &EndpointStats) -> EndpointSummary {
+    let count = e.count.max(1) as f64;
+    EndpointSummary {
+        method: method.to_string(),
+        route: route.to_string(),
+        count: e.count,
+        server_errors: e.server_errors,
+        client_errors: e.client_errors,
+        error_rate: e.server_errors as f64 / count,
+        avg_ms: e.sum_secs * 1000.0 / count,
+        p50_ms: bucket_quantile(&e.buckets, e.count, 0.50),
+        p95_ms: bucket_quantile(&e.buckets, e.count, 0.95),
+        p99_ms: bucket_quantile(&e.buckets, e.count, 0.99),
+        max_ms: e.max_secs * 1000.0,
+        total_ms: e.sum_secs * 1000.0,
+    }
+}
+
+/// Every tracked endpoint, plus when counting started.
+pub fn summary() -> (chrono::DateTime<chrono::Utc>, Vec<EndpointSummary>) {
+    let stats = STATS.lock().unwrap();
+    let list = stats.endpoints.iter().map(|((m, r), e)| summarise(m, r, e)).collect();
+    (stats.since, list)
+}
+
+/// Sort by `key` (total, avg, p95, p99, max, count, errors), slowest or
+/// largest first. Unknown keys sort by total.
+pub fn sort(list: &mut [EndpointSummary], key: &str) {
+    let val = |s: &EndpointSummary| -> f64 {
+        match key {
+            "avg" => s.avg_ms,
+            "p95" => s.p95_ms.unwrap_or(f64::MAX),
+            "p99" => s.p99_ms.unwrap_or(f64::MAX),
+            "max" => s.max_ms,
+            "count" => s.count as f64,
+            "errors" => s.server_errors as f64,
+            _ => s.total_ms,
+        }
+    };
+    list.sort_by(|a, b| val(b).total_cmp(&val(a)).then_with(|| a.route.cmp(&b.route)));
+}
+
+fn escape_label(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Append the API metrics in Prometheus text format.
+pub fn render_prometheus(out: &mut String) {
+    use std::fmt::Write;
+    let stats = STATS.lock().unwrap();
+    let mut keys: Vec<&(String, String)> = stats.endpoints.keys().collect();
+    keys.sort();
+
+    out.push_str("# HELP wolfstack_api_request_duration_seconds Time to produce an API response, by route.\n");
+    out.push_str("# TYPE wolfstack_api_request_duration_seconds histogram\n");
+    for key in &keys {
+        let e = &stats.endpoints[*key];
+        let labels = format!("method=\"{}\",route=\"{}\"", escape_label(&key.0), escape_label(&key.1));
+        let mut cumulative = 0;
+        for (i, bound) in BUCKETS.iter().enumerate() {
+            cumulative += e.buckets[i];
+            let _ = writeln!(out, "wolfstack_api_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, cumulative);
+        }
+        let _ = writeln!(out, "wolfstack_api_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, e.count);
+        let _ = writeln!(out, "wolfstack_api_request_duration_seconds_sum{{{}}} {}", labels, e.sum_secs);
+        let _ = writeln!(out, "wolfstack_api_request_duration_seconds_count{{{}}} {}", labels, e.count);
+    }
+
+    out.push_str("# HELP wolfstack_api_request_errors_total API responses with an error status, by route and class (4xx/5xx).\n");
+    out.push_str("# TYPE wolfstack_api_request_errors_total counter\n");
+    for key in &keys {
+        let e = &stats.endpoints[*key];
+        let labels = format!("method=\"{}\",route=\"{}\"", escape_label(&key.0), escape_label(&key.1));
+        let _ = writeln!(out, "wolfstack_api_request_errors_total{{{},class=\"4xx\"}} {}", labels, e.client_errors);
+        let _ = writeln!(out, "wolfstack_api_request_errors_total{{{},class=\"5xx\"}} {}", labels, e.server_errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantiles_come_from_bucket_bounds() {
+        let mut b = [0u64; BUCKETS.len() + 1];
+        b[0] = 90; // ≤5ms
+        b[6] = 9;  // ≤500ms
+        b[9] = 1;  // ≤5s
+        assert_eq!(bucket_quantile(&b, 100, 0.50), Some(5.0));
+        assert_eq!(bucket_quantile(&b, 100, 0.95), Some(500.0));
+        assert_eq!(bucket_quantile(&b, 100, 0.99), Some(500.0));
+        assert_eq!(bucket_quantile(&b, 100, 1.0), Some(5000.0));
+        b[BUCKETS.len()] = 100;
+        assert_eq!(bucket_quantile(&b, 200, 0.99), None);
+    }
+
+    #[test]
+    fn records_land_in_the_right_bucket_and_class() {
+        record("GET", "/api/test/{id}/stats", 200, 0.004);
+        record("GET", "/api/test/{id}/stats", 500, 0.3);
+        record("GET", "/api/test/{id}/stats", 403, 31.0);
+        let (_, list) = summary();
+        let s = list.iter().find(|s| s.route == "/api/test/{id}/stats").unwrap();
+        assert_eq!(s.count, 3);
+        assert_eq!(s.server_errors, 1);
+        assert_eq!(s.client_errors, 1);
+        assert_eq!(s.max_ms, 31_000.0);
+
+        let mut out = String::new();
+        render_prometheus(&mut out);
+        assert!(out.contains("wolfstack_api_request_duration_seconds_bucket{method=\"GET\",route=\"/api/test/{id}/stats\",le=\"0.005\"} 1\n"));
+        assert!(out.contains("wolfstack_api_request_duration_seconds_bucket{method=\"GET\",route=\"/api/test/{id}/stats\",le=\"30\"} 2\n"));
+        assert!(out.contains("wolfstack_api_request_duration_seconds_bucket{method=\"GET\",route=\"/api/test/{id}/stats\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("wolfstack_api_request_errors_total{method=\"GET\",route=\"/api/test/{id}/stats\",class=\"5xx\"} 1\n"));
+    }
+
+    #[test]
+    fn sort_puts_slowest_first() {
+        let mk = |route: &str, total: f64| EndpointSummary {
+            method: "GET".into(), route: route.into(), count: 1, server_errors: 0, client_errors: 0,
+            error_rate: 0.0, avg_ms: total, p50_ms: Some(total), p95_ms: Some(total), p99_ms: Some(total),
+            max_ms: total, total_ms: total,
+        };
+        let mut list = vec![mk("/api/a", 1.0), mk("/api/b", 50.0), mk("/api/c", 5.0)];
+        sort(&mut list, "total");
+        assert_eq!(list.iter().map(|s| s.route.as_str()).collect::<Vec<_>>(), ["/api/b", "/api/c", "/api/a"]);
+    }
+}
@@ -0,0 +1,445 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Redis and Memcached health on this node.
+//!
+//! Instances are found from `ss -ltnp` (listening sockets owned by
+//! `redis-server` / `memcached`), falling back to the default ports, and
+//! probed over loopback with `INFO` / `stats`. Both speak plain text, so
+//! no client crate is needed.
+//!
+//! Evictions are cumulative counters; the rate is worked out against the
+//! previous sample, kept per instance in [`LAST_SAMPLES`]. The dashboard
+//! polls and the issue scan share those samples, so a rate always covers
+//! at least [`MIN_RATE_WINDOW_SECS`].
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api::Issue;
+
+const REDIS_DEFAULT_PORT: u16 = 6379;
+const MEMCACHED_DEFAULT_PORT: u16 = 11211;
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Shorter gaps reuse the previous rate rather than dividing by ~0.
+const MIN_RATE_WINDOW_SECS: f64 = 10.0;
+
+/// `mem_fragmentation_ratio` above this is reported — but only once the
+/// instance holds [`FRAGMENTATION_MIN_BYTES`], since a near-empty Redis
+/// routinely shows ratios of 5+ from allocator overhead alone.
+pub const FRAGMENTATION_WARN_RATIO: f64 = 1.5;
+pub const FRAGMENTATION_MIN_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Evictions per second treated as a storm (warning / critical).
+pub const EVICTION_WARN_PER_SEC: f64 = 50.0;
+pub const EVICTION_CRIT_PER_SEC: f64 = 500.0;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CacheInstance {
+    /// `redis` or `memcached`.
+    pub kind: String,
+    pub port: u16,
+    pub version: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub used_memory_bytes: u64,
+    /// `maxmemory` / `limit_maxbytes`; 0 = unlimited.
+    pub max_memory_bytes: u64,
+    pub connected_clients: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// hits / (hits + misses) as a percentage; None before any lookups.
+    pub hit_rate_percent: Option<f64>,
+    /// Cumulative since the instance started.
+    pub evictions: u64,
+    /// Since the previous sample; None on the first one.
+    pub evictions_per_sec: Option<f64>,
+    /// Redis only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragmentation_ratio: Option<f64>,
+}
+
+impl CacheInstance {
+    fn new(kind: &str, port: u16) -> Self {
+        CacheInstance {
+            kind: kind.to_string(),
+            port,
+            version: String::new(),
+            reachable: false,
+            error: None,
+            used_memory_bytes: 0,
+            max_memory_bytes: 0,
+            connected_clients: 0,
+            hits: 0,
+            misses: 0,
+            hit_rate_percent: None,
+            evictions: 0,
+            evictions_per_sec: None,
+            fragmentation_ratio: None,
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{}:{}", self.kind, self.port)
+    }
+}
+
+struct Sample {
+    at: Instant,
+    evictions: u64,
+    rate: Option<f64>,
+}
+
+static LAST_SAMPLES: Mutex<Option<HashMap<String, Sample>>> = Mutex::new(None);
+
+/// Listening ports owned by redis-server / memcached, from `ss -ltnpH`.
+/// Lines look like
+/// `LISTEN 0 511 127.0.0.1:6379 0.0.0.0:* users:(("redis-server",pid=812,fd=6))`.
+pub fn parse_ss_listeners(output: &str) -> Vec<(String, u16)> {
+    let mut found = Vec::new();
+    for line in output.lines() {
+        let kind = if line.contains("\"redis-server\"") {
+            "redis"
+        } else if line.contains("\"memcached\"") {
+            "memcached"
+        } else {
+            continue;
+        };
+        let port = line
+            .split_whitespace()
+            .nth(3)
+            .and_then(|local| local.rsplit(':').next())
+            .and_then(|p| p.parse::<u16>().ok());
+        if let Some(port) = port {
+            if !found.contains(&(kind.to_string(), port)) {
+                found.push((kind.to_string(), port));
+            }
+        }
+    }
+    found
+}
+
+fn port_open(port: u16) -> bool {
+    TcpStream::connect_timeout(&([127, 0, 0, 1], port).into(), Duration::from_millis(300)).is_ok()
+}
+
+/// Instances to probe: whatever `ss` reports, or the default ports when
+/// `ss` is missing or shows nothing (e.g. inside a minimal container).
+fn discover() -> Vec<(String, u16)> {
+    let from_ss = Command::new("ss")
+        .args(["-ltnpH"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_ss_listeners(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default();
+    if !from_ss.is_empty() {
+        return from_ss;
+    }
+    let mut found = Vec::new();
+    if port_open(REDIS_DEFAULT_PORT) {
+        found.push(("redis".to_string(), REDIS_DEFAULT_PORT));
+    }
+    if port_open(MEMCACHED_DEFAULT_PORT) {
+        found.push(("memcached".to_string(), MEMCACHED_DEFAULT_PORT));
+    }
+    found
+}
+
+fn connect(port: u16) -> Result<TcpStream, String> {
+    let stream = TcpStream::connect_timeout(&([127, 0, 0, 1], port).into(), IO_TIMEOUT)
+        .map_err(|e| format!("connect 127.0.0.1:{}: {}", port, e))?;
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    Ok(stream)
+}
+
+/// `requirepass` from the usual Redis config locations, so a password-
+/// protected local instance can still be read.
+fn redis_password() -> Option<String> {
+    for path in ["/etc/redis/redis.conf", "/etc/redis.conf", "/etc/redis/redis-server.conf"] {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            for line in text.lines() {
+                let mut parts = line.split_whitespace();
+                if parts.next() == Some("requirepass") {
+                    if let Some(pw) = parts.next() {
+                        return Some(pw.trim_matches('"').to_string());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// RESP-encode a command so passwords with spaces survive.
+fn resp_command(args: &[&str]) -> String {
+    let mut out = format!("*{}\r\n", args.len());
+    for a in args {
+        out.push_str(&format!("${}\r\n{}\r\n", a.len(), a));
+    }
+    out
+}
+
+/// Read one RESP reply: `+OK`, `-ERR …` or a `$<len>` bulk string.
+fn read_resp(reader: &mut BufReader<TcpStream>) -> Result<String, String> {
+    let mut header = String::new();
+    reader.read_line(&mut header).map_err(|e| e.to_string())?;
+    let header = header.trim_end();
+    if let Some(err) = header.strip_prefix('-') {
+        return Err(err.to_string());
+    }
+    if let Some(ok) = header.strip_prefix('+') {
+        return Ok(ok.to_string());
+    }
+    let len: usize = header
+        .strip_prefix('$')
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(|| format!("Unexpected reply '{}'", header))?;
+    let mut buf = vec![0u8; len + 2];
+    reader.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    buf.truncate(len);
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+fn redis_info(port: u16) -> Result<String, String> {
+    let stream = connect(port)?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    if let Some(pw) = redis_password() {
+        writer.write_all(resp_command(&["AUTH", &pw]).as_bytes()).map_err(|e| e.to_string())?;
+        read_resp(&mut reader).map_err(|e| format!("AUTH failed: {}", e))?;
+    }
+    writer.write_all(resp_command(&["INFO"]).as_bytes()).map_err(|e| e.to_string())?;
+    read_resp(&mut reader)
+}
+
+fn memcached_stats(port: u16) -> Result<String, String> {
+    let stream = connect(port)?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    writer.write_all(b"stats\r\n").map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let mut out = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        if line.trim_end() == "END" {
+            break;
+        }
+        if line.starts_with("ERROR") || line.starts_with("SERVER_ERROR") {
+            return Err(line.trim().to_string());
+        }
+        out.push_str(&line);
+    }
+    Ok(out)
+}
+
+fn hit_rate(hits: u64, misses: u64) -> Option<f64> {
+    let total = hits + misses;
+    if total == 0 { None } else { Some(hits as f64 * 100.0 / total as f64) }
+}
+
+/// Fill metrics from Redis `INFO` output (`key:value` lines).
+pub fn parse_redis_info(port: u16, text: &str) -> CacheInstance {
+    let fields: HashMap<&str, &str> = text
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.trim_end().split_once(':'))
+        .collect();
+    let num = |k: &str| fields.get(k).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    let mut inst = CacheInstance::new("redis", port);
+    inst.reachable = true;
+    inst.version = fields.get("redis_version").unwrap_or(&"").to_string();
+    inst.used_memory_bytes = num("used_memory");
+    inst.max_memory_bytes = num("maxmemory");
+    inst.connected_clients = num("connected_clients");
+    inst.hits = num("keyspace_hits");
+    inst.misses = num("keyspace_misses");
+    inst.hit_rate_percent = hit_rate(inst.hits, inst.misses);
+    inst.evictions = num("evicted_keys");
+    inst.fragmentation_ratio = fields.get("mem_fragmentation_ratio").and_then(|v| v.parse().ok());
+    inst
+}
+
+/// Fill metrics from memcached `stats` output (`STAT key value` lines).
+pub fn parse_memcached_stats(port: u16, text: &str) -> CacheInstance {
+    let fields: HashMap<&str, &str> = text
+        .lines()
+        .filter_map(|l| l.strip_prefix("STAT "))
+        .filter_map(|l| l.trim_end().split_once(' '))
+        .collect();
+    let num = |k: &str| fields.get(k).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    let mut inst = CacheInstance::new("memcached", port);
+    inst.reachable = true;
+    inst.version = fields.get("version").unwrap_or(&"").to_string();
+    inst.used_memory_bytes = num("bytes");
+    inst.max_memory_bytes = num("limit_maxbytes");
+    inst.connected_clients = num("curr_connections");
+    inst.hits = num("get_hits");
+    inst.misses = num("get_misses");
+    inst.hit_rate_percent = hit_rate(inst.hits, inst.misses);
+    inst.evictions = num("evictions");
+    inst
+}
+
+fn probe(kind: &str, port: u16) -> CacheInstance {
+    let result = if kind == "redis" {
+        redis_info(port).map(|t| parse_redis_info(port, &t))
+    } else {
+        memcached_stats(port).map(|t| parse_memcached_stats(port, &t))
+    };
+    result.unwrap_or_else(|e| {
+        let mut inst = CacheInstance::new(kind, port);
+        inst.error = Some(e);
+        inst
+    })
+}
+
+/// Work out evictions/sec against the previous sample of the same
+/// instance. A counter that went backwards means a restart — no rate.
+fn apply_rate(samples: &mut HashMap<String, Sample>, inst: &mut CacheInstance, now: Instant) {
+    if !inst.reachable {
+        return;
+    }
+    let key = inst.key();
+    match samples.get_mut(&key) {
+        Some(prev) => {
+            let secs = now.duration_since(prev.at).as_secs_f64();
+            if secs < MIN_RATE_WINDOW_SECS {
+                inst.evictions_per_sec = prev.rate;
+                return;
+            }
+            let rate = inst.evictions.checked_sub(prev.evictions).map(|d| d as f64 / secs);
+            *prev = Sample { at: now, evictions: inst.evictions, rate };
+            inst.evictions_per_sec = rate;
+        }
+        None => {
+            samples.insert(key, Sample { at: now, evictions: inst.evictions, rate: None });
+        }
+    }
+}
+
+/// Probe every local Redis / Memcached instance. Blocking.
+pub fn collect() -> Vec<CacheInstance> {
+    let mut instances: Vec<CacheInstance> = discover().iter().map(|(k, p)| probe(k, *p)).collect();
+    let now = Instant::now();
+    let mut guard = LAST_SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let samples = guard.get_or_insert_with(HashMap::new);
+    for inst in &mut instances {
+        apply_rate(samples, inst, now);
+    }
+    instances
+}
+
+/// Fragmentation and eviction-storm findings for the issue scan.
+pub fn issues_for(instances: &[CacheInstance]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for inst in instances {
+        let label = format!("{} on port {}", if inst.kind == "redis" { "Redis" } else { "Memcached" }, inst.port);
+        if let Some(ratio) = inst.fragmentation_ratio {
+            if ratio > FRAGMENTATION_WARN_RATIO && inst.used_memory_bytes >= FRAGMENTATION_MIN_BYTES {
+                issues.push(Issue {
+                    severity: "warning".into(),
+                    category: "cache".into(),
+                    title: format!("{} memory fragmentation {:.2}", label, ratio),
+                    detail: format!(
+                        "RSS is {:.2}× the {} MB Redis actually uses. Enable activedefrag or restart the instance during a quiet period.",
+                        ratio,
+                        inst.used_memory_bytes / 1024 / 1024
+                    ),
+                });
+            }
+        }
+        if let Some(rate) = inst.evictions_per_sec {
+            if rate >= EVICTION_WARN_PER_SEC {
+                issues.push(Issue {
+                    severity: if rate >= EVICTION_CRIT_PER_SEC { "critical" } else { "warning" }.into(),
+                    category: "cache".into(),
+                    title: format!("{} evicting {:.0} keys/s", label, rate),
+                    detail: format!(
+                        "The cache is full and discarding entries (hit rate {}). Raise the memory limit or shorten TTLs.",
+                        inst.hit_rate_percent.map(|h| format!("{:.1}%", h)).unwrap_or_else(|| "n/a".into())
+                    ),
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ss_output() {
+        let out = "LISTEN 0 511 127.0.0.1:6379 0.0.0.0:* users:((\"redis-server\",pid=812,fd=6))\n\
+                   LISTEN 0 511 [::1]:6379 [::]:* users:((\"redis-server\",pid=812,fd=7))\n\
+                   LISTEN 0 1024 0.0.0.0:11211 0.0.0.0:* users:((\"memcached\",pid=90,fd=26))\n\
+                   LISTEN 0 128 0.0.0.0:22 0.0.0.0:* users:((\"sshd\",pid=1,fd=3))";
+        assert_eq!(
+            parse_ss_listeners(out),
+            vec![("redis".to_string(), 6379), ("memcached".to_string(), 11211)]
+        );
+    }
+
+    #[test]
+    fn parses_redis_info() {
+        let info = "# Server\r\nredis_version:7.2.4\r\n# Clients\r\nconnected_clients:12\r\n\
+                    # Memory\r\nused_memory:209715200\r\nmaxmemory:0\r\nmem_fragmentation_ratio:1.82\r\n\
+                    # Stats\r\nkeyspace_hits:900\r\nkeyspace_misses:100\r\nevicted_keys:5\r\n";
+        let inst = parse_redis_info(6379, info);
+        assert_eq!(inst.version, "7.2.4");
+        assert_eq!(inst.connected_clients, 12);
+        assert_eq!(inst.hit_rate_percent, Some(90.0));
+        assert_eq!(inst.fragmentation_ratio, Some(1.82));
+        let issues = issues_for(&[inst]);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].title.contains("fragmentation"));
+    }
+
+    #[test]
+    fn parses_memcached_stats() {
+        let stats = "STAT version 1.6.21\r\nSTAT curr_connections 3\r\nSTAT bytes 1024\r\n\
+                     STAT limit_maxbytes 67108864\r\nSTAT get_hits 0\r\nSTAT get_misses 0\r\nSTAT evictions 7\r\n";
+        let inst = parse_memcached_stats(11211, stats);
+        assert_eq!(inst.version, "1.6.21");
+        assert_eq!(inst.max_memory_bytes, 67108864);
+        assert_eq!(inst.hit_rate_percent, None);
+        assert_eq!(inst.evictions, 7);
+        assert_eq!(inst.fragmentation_ratio, None);
+    }
+
+    #[test]
+    fn eviction_rate_needs_a_window_and_flags_storms() {
+        let mut samples = HashMap::new();
+        let t0 = Instant::now();
+        let mut a = parse_memcached_stats(11211, "STAT evictions 1000\r\n");
+        apply_rate(&mut samples, &mut a, t0);
+        assert_eq!(a.evictions_per_sec, None);
+
+        let mut b = parse_memcached_stats(11211, "STAT evictions 2000\r\n");
+        apply_rate(&mut samples, &mut b, t0 + Duration::from_secs(2));
+        assert_eq!(b.evictions_per_sec, None, "too soon — keeps previous rate");
+
+        let mut c = parse_memcached_stats(11211, "STAT evictions 11000\r\n");
+        apply_rate(&mut samples, &mut c, t0 + Duration::from_secs(20));
+        assert_eq!(c.evictions_per_sec, Some(500.0));
+        let issues = issues_for(&[c]);
+        assert_eq!(issues[0].severity, "critical");
+
+        let mut restarted = parse_memcached_stats(11211, "STAT evictions 3\r\n");
+        apply_rate(&mut samples, &mut restarted, t0 + Duration::from_secs(40));
+        assert_eq!(restarted.evictions_per_sec, None);
+    }
+}
@@ -0,0 +1,471 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Hardware inventory — what the box physically is.
+//!
+//! System, board and BIOS identity come from `/sys/class/dmi/id`; CPU
+//! sockets and memory modules from `dmidecode`; disks from `lsblk`; NIC
+//! drivers and firmware from `ethtool -i`; everything on the bus from
+//! `lspci`. A missing tool leaves its section empty and adds a warning
+//! rather than failing the snapshot.
+//!
+//! Hardware rarely changes, so [`super::inventory::HARDWARE`] refreshes
+//! every six hours. Each snapshot is also written to
+//! `paths.hardware_inventory` so the last one survives a restart and can
+//! be attached to a support ticket from the shell. Peers' snapshots are
+//! fetched on demand for the node detail endpoint and cached briefly.
+
+use crate::agent::Node;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a peer's snapshot is reused before asking it again.
+const REMOTE_TTL: Duration = Duration::from_secs(600);
+
+static REMOTE: std::sync::LazyLock<Mutex<HashMap<String, (Instant, HardwareInventory)>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HardwareInventory {
+    /// Unix seconds.
+    pub collected_at: u64,
+    pub system: SystemInfo,
+    #[serde(default)]
+    pub processors: Vec<Processor>,
+    #[serde(default)]
+    pub memory: Vec<MemoryModule>,
+    #[serde(default)]
+    pub disks: Vec<Disk>,
+    #[serde(default)]
+    pub nics: Vec<Nic>,
+    #[serde(default)]
+    pub pci: Vec<PciDevice>,
+    /// Sources that couldn't be read (tool not installed, no DMI, …).
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SystemInfo {
+    pub vendor: String,
+    pub product: String,
+    pub version: String,
+    pub serial: String,
+    pub uuid: String,
+    pub chassis_serial: String,
+    pub board_vendor: String,
+    pub board_product: String,
+    pub board_serial: String,
+    pub bios_vendor: String,
+    pub bios_version: String,
+    pub bios_date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Processor {
+    pub socket: String,
+    pub model: String,
+    pub cores: u32,
+    pub threads: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MemoryModule {
+    pub locator: String,
+    /// As dmidecode reports it, e.g. `32 GB`.
+    pub size: String,
+    pub kind: String,
+    pub speed: String,
+    pub manufacturer: String,
+    pub serial: String,
+    pub part_number: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Disk {
+    pub name: String,
+    pub model: String,
+    pub serial: String,
+    pub size_bytes: u64,
+    pub rotational: bool,
+    pub transport: String,
+    /// Firmware revision.
+    pub firmware: String,
+    pub wwn: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Nic {
+    pub name: String,
+    pub mac: String,
+    pub driver: String,
+    pub driver_version: String,
+    pub firmware_version: String,
+    pub bus_info: String,
+    /// Negotiated link speed; `None` when the link is down.
+    pub speed_mbps: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PciDevice {
+    pub slot: String,
+    pub class: String,
+    pub vendor: String,
+    pub device: String,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Vendors fill unused DMI strings with placeholders; treat them as unset.
+fn clean(value: &str) -> String {
+    let v = value.trim();
+    let lower = v.to_ascii_lowercase();
+    let placeholder = lower.is_empty()
+        || lower == "not specified"
+        || lower == "not present"
+        || lower == "unknown"
+        || lower == "none"
+        || lower == "default string"
+        || lower == "system serial number"
+        || lower == "system product name"
+        || lower == "system manufacturer"
+        || lower == "0123456789"
+        || lower.starts_with("to be filled")
+        || lower.starts_with("no module installed");
+    if placeholder { String::new() } else { v.to_string() }
+}
+
+fn run(cmd: &str, args: &[&str]) -> Result<String, String> {
+    match Command::new(cmd).args(args).output() {
+        Ok(out) if out.status.success() => Ok(String::from_utf8_lossy(&out.stdout).into_owned()),
+        Ok(out) => Err(format!("{} failed: {}", cmd, String::from_utf8_lossy(&out.stderr).trim())),
+        Err(e) => Err(format!("{} unavailable: {}", cmd, e)),
+    }
+}
+
+fn read_dmi_id(field: &str) -> String {
+    clean(&std::fs::read_to_string(format!("/sys/class/dmi/id/{}", field)).unwrap_or_default())
+}
+
+fn system_info() -> SystemInfo {
+    SystemInfo {
+        vendor: read_dmi_id("sys_vendor"),
+        product: read_dmi_id("product_name"),
+        version: read_dmi_id("product_version"),
+        serial: read_dmi_id("product_serial"),
+        uuid: read_dmi_id("product_uuid"),
+        chassis_serial: read_dmi_id("chassis_serial"),
+        board_vendor: read_dmi_id("board_vendor"),
+        board_product: read_dmi_id("board_name"),
+        board_serial: read_dmi_id("board_serial"),
+        bios_vendor: read_dmi_id("bios_vendor"),
+        bios_version: read_dmi_id("bios_version"),
+        bios_date: read_dmi_id("bios_date"),
+    }
+}
+
+/// Split `dmidecode` output into `(section title, fields)` records.
+/// Multi-line list values (indented twice) are skipped.
+fn parse_dmidecode(text: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut records = Vec::new();
+    let mut current: Option<(String, HashMap<String, String>)> = None;
+    let mut expect_title = false;
+    for line in text.lines() {
+        if line.starts_with("Handle ") {
+            if let Some(rec) = current.take() { records.push(rec); }
+            expect_title = true;
+            continue;
+        }
+        if expect_title {
+            if !line.trim().is_empty() {
+                current = Some((line.trim().to_string(), HashMap::new()));
+                expect_title = false;
+            }
+            continue;
+        }
+        if line.starts_with("\t\t") { continue; }
+        if let (Some((_, fields)), Some(rest)) = (current.as_mut(), line.strip_prefix('\t'))
+            && let Some((k, v)) = rest.split_once(':')
+        {
+            fields.insert(k.trim().to_string(), v.trim().to_string());
+        }
+    }
+    if let Some(rec) = current { records.push(rec); }
+    records
+}
+
+fn field(fields: &HashMap<String, String>, key: &str) -> String {
+    fields.get(key).map(|v| clean(v)).unwrap_or_default()
+}
+
+fn processors_and_memory(text: &str) -> (Vec<Processor>, Vec<MemoryModule>) {
+    let mut cpus = Vec::new();
+    let mut dimms = Vec::new();
+    for (title, f) in parse_dmidecode(text) {
+        match title.as_str() {
+            "Processor Information" => {
+                if f.get("Status").is_some_and(|s| s.contains("Unpopulated")) { continue; }
+                cpus.push(Processor {
+                    socket: field(&f, "Socket Designation"),
+                    model: field(&f, "Version"),
+                    cores: field(&f, "Core Count").parse().unwrap_or(0),
+                    threads: field(&f, "Thread Count").parse().unwrap_or(0),
+                });
+            }
+            "Memory Device" => {
+                let size = field(&f, "Size");
+                if size.is_empty() { continue; }
+                // The speed it actually runs at, when the BIOS says.
+                let configured = field(&f, "Configured Memory Speed");
+                dimms.push(MemoryModule {
+                    locator: field(&f, "Locator"),
+                    size,
+                    kind: field(&f, "Type"),
+                    speed: if configured.is_empty() { field(&f, "Speed") } else { configured },
+                    manufacturer: field(&f, "Manufacturer"),
+                    serial: field(&f, "Serial Number"),
+                    part_number: field(&f, "Part Number"),
+                });
+            }
+            _ => {}
+        }
+    }
+    (cpus, dimms)
+}
+
+/// `lsblk -J` output, physical disks only (zram reports itself as a
+/// disk too). Older lsblk prints numbers and booleans as strings, so both
+/// forms are accepted.
+fn parse_lsblk(json: &str) -> Vec<Disk> {
+    let v: serde_json::Value = serde_json::from_str(json).unwrap_or_default();
+    let text = |d: &serde_json::Value, k: &str| d.get(k).and_then(|x| x.as_str()).map(clean).unwrap_or_default();
+    v.get("blockdevices").and_then(|b| b.as_array()).map(|devs| {
+        devs.iter()
+            .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("disk"))
+            .filter(|d| !d.get("name").and_then(|n| n.as_str()).unwrap_or("").starts_with("zram"))
+            .map(|d| Disk {
+                name: text(d, "name"),
+                model: text(d, "model"),
+                serial: text(d, "serial"),
+                size_bytes: d.get("size").and_then(|s| s.as_u64().or_else(|| s.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0),
+                rotational: d.get("rota").and_then(|r| r.as_bool().or_else(|| r.as_str().map(|s| s == "1"))).unwrap_or(false),
+                transport: text(d, "tran"),
+                firmware: text(d, "rev"),
+                wwn: text(d, "wwn"),
+            })
+            .collect()
+    }).unwrap_or_default()
+}
+
+/// `ethtool -i` → (driver, version, firmware-version, bus-info).
+fn parse_ethtool(text: &str) -> (String, String, String, String) {
+    let mut out = (String::new(), String::new(), String::new(), String::new());
+    for line in text.lines() {
+        let Some((k, v)) = line.split_once(':') else { continue };
+        let v = clean(v);
+        match k.trim() {
+            "driver" => out.0 = v,
+            "version" => out.1 = v,
+            "firmware-version" => out.2 = v,
+            "bus-info" => out.3 = v,
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Physical NICs: interfaces backed by a device (skips lo, bridges,
+/// veths, bonds, WolfNet).
+fn nics(warnings: &mut Vec<String>) -> Vec<Nic> {
+    let mut out = Vec::new();
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else { return out };
+    let mut ethtool_missing = false;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let dir = entry.path();
+        if !dir.join("device").exists() { continue; }
+        let read = |f: &str| std::fs::read_to_string(dir.join(f)).unwrap_or_default().trim().to_string();
+        let mut nic = Nic {
+            name: name.clone(),
+            mac: read("address"),
+            speed_mbps: read("speed").parse::<i64>().ok().filter(|s| *s > 0).map(|s| s as u32),
+            ..Default::default()
+        };
+        match run("ethtool", &["-i", &name]) {
+            Ok(text) => {
+                (nic.driver, nic.driver_version, nic.firmware_version, nic.bus_info) = parse_ethtool(&text);
+            }
+            Err(_) => {
+                ethtool_missing = true;
+                nic.driver = std::fs::read_link(dir.join("device/driver")).ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or_default();
+            }
+        }
+        out.push(nic);
+    }
+    if ethtool_missing {
+        warnings.push("ethtool unavailable — NIC firmware versions not collected".into());
+    }
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+/// `lspci -mm` quotes each field: `00:1f.2 "SATA controller" "Intel…" "…"`.
+fn parse_lspci(text: &str) -> Vec<PciDevice> {
+    text.lines().filter_map(|line| {
+        let (slot, rest) = line.split_once(' ')?;
+        let quoted: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+        Some(PciDevice {
+            slot: slot.to_string(),
+            class: quoted.first()?.to_string(),
+            vendor: quoted.get(1).map(|s| s.to_string()).unwrap_or_default(),
+            device: quoted.get(2).map(|s| s.to_string()).unwrap_or_default(),
+        })
+    }).collect()
+}
+
+/// Take a snapshot. Blocking — runs a handful of subprocesses.
+pub fn collect() -> HardwareInventory {
+    let mut warnings = Vec::new();
+    let system = system_info();
+    if system.vendor.is_empty() && system.product.is_empty() {
+        warnings.push("No DMI data (/sys/class/dmi/id) — virtual machine or unsupported platform".into());
+    }
+    let (processors, memory) = match run("dmidecode", &["-t", "processor", "-t", "memory"]) {
+        Ok(text) => processors_and_memory(&text),
+        Err(e) => { warnings.push(e); (Vec::new(), Vec::new()) }
+    };
+    let disks = match run("lsblk", &["-J", "-b", "-d", "-o", "NAME,MODEL,SERIAL,SIZE,ROTA,TRAN,REV,TYPE,WWN"]) {
+        Ok(json) => parse_lsblk(&json),
+        Err(e) => { warnings.push(e); Vec::new() }
+    };
+    let nics = nics(&mut warnings);
+    let pci = match run("lspci", &["-mm"]) {
+        Ok(text) => parse_lspci(&text),
+        Err(e) => { warnings.push(e); Vec::new() }
+    };
+    HardwareInventory { collected_at: now(), system, processors, memory, disks, nics, pci, warnings }
+}
+
+/// Collector for the inventory slot: snapshot and persist it.
+pub fn collect_and_store() -> HardwareInventory {
+    let inv = collect();
+    let path = crate::paths::get().hardware_inventory;
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string_pretty(&inv) {
+        Ok(json) => if let Err(e) = std::fs::write(&path, json) {
+            tracing::warn!("hardware: could not write {}: {}", path, e);
+        },
+        Err(e) => tracing::warn!("hardware: could not serialise inventory: {}", e),
+    }
+    inv
+}
+
+/// This node's latest snapshot: the live slot, else the one on disk.
+pub fn local() -> Option<HardwareInventory> {
+    super::inventory::HARDWARE.get().or_else(|| {
+        let data = std::fs::read_to_string(crate::paths::get().hardware_inventory).ok()?;
+        serde_json::from_str(&data).ok()
+    })
+}
+
+/// A peer's snapshot from its `/api/hardware`, cached for ten minutes.
+/// `None` for offline peers and versions that predate the endpoint.
+pub async fn remote(node: &Node, secret: &str) -> Option<HardwareInventory> {
+    if let Some((at, inv)) = REMOTE.lock().unwrap().get(&node.id)
+        && at.elapsed() < REMOTE_TTL
+    {
+        return Some(inv.clone());
+    }
+    if !node.online || node.node_type != "wolfstack" { return None; }
+    for url in crate::api::build_node_urls(&node.address, node.port, "/api/hardware") {
+        let Ok(resp) = crate::api::API_HTTP_CLIENT.get(&url)
+            .timeout(Duration::from_secs(10))
+            .header("X-WolfStack-Secret", secret)
+            .send().await else { continue };
+        if !resp.status().is_success() { continue; }
+        if let Ok(inv) = resp.json::<HardwareInventory>().await {
+            REMOTE.lock().unwrap().insert(node.id.clone(), (Instant::now(), inv.clone()));
+            return Some(inv);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dmidecode_processors_and_populated_dimms() {
+        let text = "# dmidecode 3.4\n\
+Handle 0x0400, DMI type 4, 48 bytes\n\
+Processor Information\n\
+\tSocket Designation: CPU1\n\
+\tVersion: Intel(R) Xeon(R) Silver 4214 CPU @ 2.20GHz\n\
+\tStatus: Populated, Enabled\n\
+\tCore Count: 12\n\
+\tThread Count: 24\n\
+\tCharacteristics:\n\
+\t\t64-bit capable\n\
+\n\
+Handle 0x1100, DMI type 17, 84 bytes\n\
+Memory Device\n\
+\tSize: 32 GB\n\
+\tLocator: A1\n\
+\tType: DDR4\n\
+\tSpeed: 2933 MT/s\n\
+\tManufacturer: Samsung\n\
+\tSerial Number: 12AB34CD\n\
+\tPart Number: M393A4K40DB2-CVF\n\
+\tConfigured Memory Speed: 2400 MT/s\n\
+\n\
+Handle 0x1101, DMI type 17, 84 bytes\n\
+Memory Device\n\
+\tSize: No Module Installed\n\
+\tLocator: A2\n";
+        let (cpus, dimms) = processors_and_memory(text);
+        assert_eq!(cpus.len(), 1);
+        assert_eq!(cpus[0].cores, 12);
+        assert_eq!(cpus[0].threads, 24);
+        assert_eq!(dimms.len(), 1);
+        assert_eq!(dimms[0].serial, "12AB34CD");
+        assert_eq!(dimms[0].speed, "2400 MT/s");
+    }
+
+    #[test]
+    fn lsblk_accepts_old_and_new_json() {
+        let new = r#"{"blockdevices":[{"name":"sda","model":"SAMSUNG MZ7LH960","serial":"S45N","size":960197124096,"rota":false,"tran":"sata","rev":"HXT7404Q","type":"disk","wwn":"0x5002"},{"name":"sr0","type":"rom"},{"name":"zram0","size":0,"type":"disk"}]}"#;
+        let old = r#"{"blockdevices":[{"name":"sdb","model":"ST4000NM","serial":"ZC1","size":"4000787030016","rota":"1","tran":"sas","rev":"E004","type":"disk","wwn":null}]}"#;
+        let d = parse_lsblk(new);
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].firmware, "HXT7404Q");
+        assert!(!d[0].rotational);
+        let d = parse_lsblk(old);
+        assert_eq!(d[0].size_bytes, 4000787030016);
+        assert!(d[0].rotational);
+    }
+
+    #[test]
+    fn ethtool_lspci_and_placeholders() {
+        let (driver, version, fw, bus) = parse_ethtool("driver: ixgbe\nversion: 6.1.0\nfirmware-version: 0x800003df\nbus-info: 0000:3b:00.0\n");
+        assert_eq!((driver.as_str(), version.as_str(), fw.as_str(), bus.as_str()), ("ixgbe", "6.1.0", "0x800003df", "0000:3b:00.0"));
+        let pci = parse_lspci("3b:00.0 \"Ethernet controller\" \"Intel Corporation\" \"Ethernet Controller 10-Gigabit X540-AT2\" -r01 \"Dell\" \"X540\"\n");
+        assert_eq!(pci[0].class, "Ethernet controller");
+        assert_eq!(pci[0].device, "Ethernet Controller 10-Gigabit X540-AT2");
+        assert_eq!(clean("To Be Filled By O.E.M."), "");
+        assert_eq!(clean(" R640 "), "R640");
+    }
+}
@@ -0,0 +1,228 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Background inventory collector.
+//!
+//! The self-monitor loop used to list Docker and LXC containers and VMs
+//! inline on every 2-second tick, and the dashboard endpoints did the same
+//! on a cache miss — so a slow `docker ps` or `pct list` stalled the status
+//! report and piled requests up behind the VM manager's mutex. Instead each
+//! source is refreshed by its own task on its own interval, off the async
+//! runtime, into a [`Slot`]; `/api/agent/status`, the self-monitor loop,
+//! the VM list and the `*_cached` container functions all read the latest
+//! snapshot without waiting.
+//!
+//! Host metrics work the same way, except that the self-monitor loop is
+//! the only sampler and [`Slot::publish`]es each sample to [`METRICS`].
+//! Sharing one sampler also keeps CPU usage honest: sysinfo measures it
+//! since the previous refresh, so every extra caller of `collect()`
+//! shortened the window the next one saw.
+//!
+//! Readers get the last good snapshot however old it is — a wedged daemon
+//! leaves the previous list in place rather than blocking every caller.
+//! Write paths call [`Slot::invalidate`], which drops the snapshot (readers
+//! fall back to a direct call until the next collection) and wakes the
+//! collector to refresh straight away.
+
+use crate::api::AppState;
+use crate::containers::{self, ContainerInfo, ContainerStats};
+use crate::monitoring::SystemMetrics;
+use crate::monitoring::hardware::{self, HardwareInventory};
+use crate::vms::manager::VmConfig;
+use actix_web::web;
+use serde::Serialize;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::warn;
+
+struct Entry<T> {
+    data: T,
+    at: Instant,
+    took: Duration,
+}
+
+/// The latest snapshot of one inventory source.
+pub struct Slot<T> {
+    name: &'static str,
+    interval_secs: u64,
+    value: RwLock<Option<Entry<T>>>,
+    /// Bumped by `invalidate`, so a collection that started before the
+    /// write it raced with is thrown away instead of stored.
+    generation: AtomicU64,
+    refreshes: AtomicU64,
+    wake: Notify,
+}
+
+impl<T: Clone + Send + Sync + 'static> Slot<T> {
+    const fn new(name: &'static str, interval_secs: u64) -> Self {
+        Slot {
+            name,
+            interval_secs,
+            value: RwLock::new(None),
+            generation: AtomicU64::new(0),
+            refreshes: AtomicU64::new(0),
+            wake: Notify::const_new(),
+        }
+    }
+
+    /// Latest snapshot; `None` before the first collection or right after
+    /// an invalidate.
+    pub fn get(&self) -> Option<T> {
+        self.value.read().unwrap().as_ref().map(|e| e.data.clone())
+    }
+
+    /// Drop the snapshot and refresh now. Call after a write that changes
+    /// what this source returns.
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *self.value.write().unwrap() = None;
+        self.wake.notify_one();
+    }
+
+    /// Store a sample taken by a collector outside this module.
+    pub fn publish(&self, data: T, took: Duration) {
+        self.store(self.generation.load(Ordering::SeqCst), data, took);
+    }
+
+    fn store(&self, generation: u64, data: T, took: Duration) {
+        let mut value = self.value.write().unwrap();
+        if self.generation.load(Ordering::SeqCst) == generation {
+            *value = Some(Entry { data, at: Instant::now(), took });
+            self.refreshes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn status(&self) -> SourceStatus {
+        let value = self.value.read().unwrap();
+        SourceStatus {
+            source: self.name,
+            interval_secs: self.interval_secs,
+            age_ms: value.as_ref().map(|e| e.at.elapsed().as_millis() as u64),
+            last_collect_ms: value.as_ref().map(|e| e.took.as_millis() as u64),
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub static DOCKER: Slot<Vec<ContainerInfo>> = Slot::new("docker", 5);
+/// Stats take a second per container to sample, so less often.
+pub static DOCKER_STATS: Slot<Vec<ContainerStats>> = Slot::new("docker_stats", 10);
+pub static LXC: Slot<Vec<ContainerInfo>> = Slot::new("lxc", 5);
+pub static LXC_STATS: Slot<Vec<ContainerStats>> = Slot::new("lxc_stats", 10);
+/// Invalidated by every write under `/api/vms` and by libvirt lifecycle
+/// events, so an action shows up in the list straight away.
+pub static VMS: Slot<Vec<VmConfig>> = Slot::new("vms", 5);
+/// Filled by the self-monitor loop rather than a collector of its own.
+pub static METRICS: Slot<SystemMetrics> = Slot::new("metrics", 2);
+/// Hardware hardly changes; six-hourly keeps swapped disks and firmware
+/// updates current without running dmidecode all day.
+pub static HARDWARE: Slot<HardwareInventory> = Slot::new("hardware", 6 * 3600);
+
+/// Run `collect` on the blocking pool every `slot.interval_secs`, or as
+/// soon as the slot is invalidated.
+fn spawn_source<T, F>(slot: &'static Slot<T>, collect: F)
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn() -> T + Clone + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let generation = slot.generation.load(Ordering::SeqCst);
+            let started = Instant::now();
+            match tokio::task::spawn_blocking(collect.clone()).await {
+                Ok(data) => slot.store(generation, data, started.elapsed()),
+                Err(e) => warn!("inventory: {} collection panicked: {}", slot.name, e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(slot.interval_secs)) => {}
+                _ = slot.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// Start one collector per source. Runtimes that aren't installed are
+/// skipped per tick (cheap cached check), so installing Docker later
+/// starts filling its slot without a restart.
+pub fn start(state: web::Data<AppState>) {
+    spawn_source(&DOCKER, || if containers::has_docker_cached() { containers::docker_list_all() } else { vec![] });
+    spawn_source(&DOCKER_STATS, || if containers::has_docker_cached() { containers::docker_stats() } else { vec![] });
+    spawn_source(&LXC, || if containers::has_lxc_cached() { containers::lxc_list_all() } else { vec![] });
+    spawn_source(&LXC_STATS, || if containers::has_lxc_cached() { containers::lxc_stats() } else { vec![] });
+    spawn_source(&VMS, move || state.vms.lock().unwrap().list_vms());
+    spawn_source(&HARDWARE, hardware::collect_and_store);
+}
+
+/// The VM list for callers already on a blocking thread: the snapshot, or
+/// a listing under the manager lock when there isn't one.
+pub fn vms_blocking(state: &AppState) -> Vec<VmConfig> {
+    VMS.get().unwrap_or_else(|| state.vms.lock().unwrap().list_vms())
+}
+
+/// The VM list for request handlers; a missing snapshot is filled in on
+/// the blocking pool, never on the async worker.
+pub async fn vms(state: &web::Data<AppState>) -> Vec<VmConfig> {
+    if let Some(vms) = VMS.get() {
+        return vms;
+    }
+    let st = state.clone();
+    web::block(move || vms_blocking(&st)).await.unwrap_or_default()
+}
+
+/// Latest host metrics for callers on a blocking thread; sampled directly
+/// only before the self-monitor loop has published its first one.
+pub fn metrics_blocking(state: &AppState) -> SystemMetrics {
+    METRICS.get().unwrap_or_else(|| state.monitor.lock().unwrap().collect())
+}
+
+/// Latest host metrics for request handlers. `None` only if the fallback
+/// sample panicked.
+pub async fn metrics(state: &web::Data<AppState>) -> Option<SystemMetrics> {
+    if let Some(m) = METRICS.get() {
+        return Some(m);
+    }
+    let st = state.clone();
+    web::block(move || metrics_blocking(&st)).await.ok()
+}
+
+#[derive(Serialize)]
+pub struct SourceStatus {
+    pub source: &'static str,
+    pub interval_secs: u64,
+    /// Age of the current snapshot; `None` when there isn't one.
+    pub age_ms: Option<u64>,
+    /// How long the last collection took.
+    pub last_collect_ms: Option<u64>,
+    pub refreshes: u64,
+}
+
+/// Freshness of every snapshot, for `/api/system/inventory`.
+pub fn status() -> Vec<SourceStatus> {
+    vec![DOCKER.status(), DOCKER_STATS.status(), LXC.status(), LXC_STATS.status(), VMS.status(), METRICS.status(), HARDWARE.status()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidate_discards_a_collection_that_raced_it() {
+        static SLOT: Slot<u32> = Slot::new("test", 5);
+        let before = SLOT.generation.load(Ordering::SeqCst);
+        SLOT.store(before, 1, Duration::ZERO);
+        assert_eq!(SLOT.get(), Some(1));
+
+        // A collection starts, a write invalidates, then the stale result lands.
+        let started = SLOT.generation.load(Ordering::SeqCst);
+        SLOT.invalidate();
+        SLOT.store(started, 2, Duration::ZERO);
+        assert_eq!(SLOT.get(), None);
+
+        SLOT.store(SLOT.generation.load(Ordering::SeqCst), 3, Duration::ZERO);
+        assert_eq!(SLOT.get(), Some(3));
+        assert_eq!(SLOT.status().refreshes, 2);
+    }
+}
@@ -4,6 +4,11 @@
 
 //! System monitoring — collects CPU, RAM, disk, and network stats
 
+pub mod api_stats;
+pub mod inventory;
+pub mod hardware;
+pub mod cache_services;
+
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use sysinfo::{System, Disks, Networks};
@@ -252,7 +257,8 @@ pub fn classify_hardware(cpu_count: usize, total_memory_bytes: u64) -> String {
 
 // ─── Historical Metrics ───
 
-/// Maximum number of historical snapshots to keep (300 × 2s = ~10 min)
+/// Default number of historical snapshots to keep (300 × 2s = ~10 min);
+/// `history.metrics_snapshots` in wolfstack.toml overrides it.
 pub const HISTORY_MAX_SNAPSHOTS: usize = 300;
 
 /// A single disk's usage at a point in time
@@ -286,10 +292,10 @@ pub struct MetricsHistory {
 }
 
 impl MetricsHistory {
-    pub fn new() -> Self {
+    pub fn new(max_size: usize) -> Self {
         Self {
-            snapshots: VecDeque::with_capacity(HISTORY_MAX_SNAPSHOTS),
-            max_size: HISTORY_MAX_SNAPSHOTS,
+            snapshots: VecDeque::with_capacity(max_size),
+            max_size,
         }
     }
 
@@ -323,8 +329,38 @@ impl MetricsHistory {
         self.snapshots.push_back(snap);
     }
 
+    /// Change the retention, dropping the oldest snapshots if it shrank.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        while self.snapshots.len() > max_size {
+            self.snapshots.pop_front();
+        }
+    }
+
     /// Get all snapshots
     pub fn get_all(&self) -> Vec<MetricsSnapshot> {
         self.snapshots.iter().cloned().collect()
     }
+
+    /// Write the snapshots to `path` (on shutdown) so a restart doesn't
+    /// blank the dashboard graphs.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_vec(&self.snapshots).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(path, json)
+            .map_err(|e| format!("Failed to write metrics history: {}", e))
+    }
+
+    /// History saved by [`save`](Self::save), trimmed to `max_size`.
+    /// Missing or unreadable file = empty history.
+    pub fn load(path: &str, max_size: usize) -> Self {
+        let mut history = Self::new(max_size);
+        if let Ok(data) = std::fs::read(path) {
+            history.snapshots = serde_json::from_slice(&data).unwrap_or_default();
+            history.set_max_size(max_size);
+        }
+        history
+    }
 }
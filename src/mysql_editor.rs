@@ -928,6 +928,379 @@ fn resolve_wolfnet_hostname(ip: &str) -> String {
     String::new()
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Server administration — databases, accounts, grants, slow query log
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Databases with their on-disk size and table count, for the Databases tab.
+pub async fn database_overview(params: &ConnParams) -> Result<Vec<serde_json::Value>, String> {
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let rows: Vec<Row> = conn
+        .query(
+            "SELECT s.SCHEMA_NAME, s.DEFAULT_CHARACTER_SET_NAME, s.DEFAULT_COLLATION_NAME, \
+             COUNT(t.TABLE_NAME), COALESCE(SUM(t.DATA_LENGTH + t.INDEX_LENGTH), 0) \
+             FROM information_schema.SCHEMATA s \
+             LEFT JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = s.SCHEMA_NAME \
+             GROUP BY s.SCHEMA_NAME, s.DEFAULT_CHARACTER_SET_NAME, s.DEFAULT_COLLATION_NAME \
+             ORDER BY s.SCHEMA_NAME",
+        )
+        .await
+        .map_err(|e| format!("Database overview failed: {}", detailed_mysql_error(&e)))?;
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+
+    Ok(rows.into_iter().map(|row| {
+        let name: String = row.get(0).unwrap_or_default();
+        let size: Option<String> = row.get(4);
+        serde_json::json!({
+            "name": name,
+            "charset": row.get::<Option<String>, _>(1).flatten(),
+            "collation": row.get::<Option<String>, _>(2).flatten(),
+            "tables": row.get::<Option<u64>, _>(3).flatten().unwrap_or(0),
+            // SUM() comes back as DECIMAL — read as text, then parse.
+            "size_bytes": size.and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0) as u64,
+            "system": matches!(name.as_str(), "mysql" | "information_schema" | "performance_schema" | "sys"),
+        })
+    }).collect())
+}
+
+/// Privileges the Users tab may grant or revoke. Anything else (PROXY,
+/// SUPER, …) has to go through the SQL console on purpose.
+pub const GRANTABLE_PRIVILEGES: &[&str] = &[
+    "ALL PRIVILEGES", "SELECT", "INSERT", "UPDATE", "DELETE", "CREATE", "DROP", "ALTER",
+    "INDEX", "REFERENCES", "CREATE VIEW", "SHOW VIEW", "CREATE TEMPORARY TABLES",
+    "LOCK TABLES", "EXECUTE", "CREATE ROUTINE", "ALTER ROUTINE", "EVENT", "TRIGGER",
+    "PROCESS", "RELOAD", "REPLICATION CLIENT", "REPLICATION SLAVE", "GRANT OPTION",
+];
+
+/// Validate a `'user'@'host'` pair and render it as an escaped account
+/// literal. Account names can't be bound as parameters in CREATE USER /
+/// GRANT / SHOW GRANTS, so they're escaped instead.
+fn account_literal(user: &str, host: &str) -> Result<String, String> {
+    if user.is_empty() || user.len() > 80 {
+        return Err("User name must be 1–80 characters".into());
+    }
+    let host = if host.trim().is_empty() { "%" } else { host.trim() };
+    if host.len() > 255 {
+        return Err("Host must be at most 255 characters".into());
+    }
+    if user.chars().chain(host.chars()).any(|c| c.is_control()) {
+        return Err("User and host must not contain control characters".into());
+    }
+    Ok(format!("'{}'@'{}'", mysql_escape_string(user), mysql_escape_string(host)))
+}
+
+/// Build the `ON …` clause: `*.*`, `` `db`.* `` or `` `db`.`table` ``.
+fn grant_scope(database: &str, table: Option<&str>) -> Result<String, String> {
+    let quote = |s: &str| format!("`{}`", s.replace('`', "``"));
+    let database = database.trim();
+    if database.is_empty() || database == "*" {
+        return Ok("*.*".into());
+    }
+    reject_unsafe_identifier(database, "database")?;
+    match table.map(str::trim).filter(|t| !t.is_empty() && *t != "*") {
+        Some(t) => {
+            reject_unsafe_identifier(t, "table")?;
+            Ok(format!("{}.{}", quote(database), quote(t)))
+        }
+        None => Ok(format!("{}.*", quote(database))),
+    }
+}
+
+/// Normalise and allowlist a privilege list.
+fn privilege_list(privileges: &[String]) -> Result<String, String> {
+    if privileges.is_empty() {
+        return Err("Select at least one privilege".into());
+    }
+    let mut out: Vec<&str> = Vec::new();
+    for p in privileges {
+        let upper = p.trim().to_ascii_uppercase();
+        let upper = if upper == "ALL" { "ALL PRIVILEGES".to_string() } else { upper };
+        match GRANTABLE_PRIVILEGES.iter().find(|g| **g == upper) {
+            Some(g) if !out.contains(g) => out.push(g),
+            Some(_) => {}
+            None => return Err(format!("Unsupported privilege '{}'", p.trim())),
+        }
+    }
+    Ok(out.join(", "))
+}
+
+/// Run one administrative statement and disconnect.
+async fn exec_admin(params: &ConnParams, sql: &str, what: &str) -> Result<(), String> {
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let result = conn.query_drop(sql).await
+        .map_err(|e| format!("{} failed: {}", what, detailed_mysql_error(&e)));
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+    result
+}
+
+/// List accounts from `mysql.user`.
+pub async fn list_users(params: &ConnParams) -> Result<Vec<serde_json::Value>, String> {
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let rows: Vec<(String, String)> = conn
+        .query("SELECT User, Host FROM mysql.user ORDER BY User, Host")
+        .await
+        .map_err(|e| format!("Listing users failed (needs SELECT on mysql.user): {}", detailed_mysql_error(&e)))?;
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+    Ok(rows.into_iter().map(|(user, host)| serde_json::json!({ "user": user, "host": host })).collect())
+}
+
+/// `SHOW GRANTS FOR 'user'@'host'` — one GRANT statement per line.
+pub async fn show_grants(params: &ConnParams, user: &str, host: &str) -> Result<Vec<String>, String> {
+    let account = account_literal(user, host)?;
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let grants: Result<Vec<String>, String> = conn
+        .query(format!("SHOW GRANTS FOR {}", account))
+        .await
+        .map_err(|e| format!("SHOW GRANTS failed: {}", detailed_mysql_error(&e)));
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+    grants
+}
+
+pub async fn create_user(params: &ConnParams, user: &str, host: &str, password: &str) -> Result<(), String> {
+    let account = account_literal(user, host)?;
+    if password.is_empty() {
+        return Err("A password is required for new accounts".into());
+    }
+    exec_admin(params, &format!("CREATE USER {} IDENTIFIED BY '{}'", account, mysql_escape_string(password)), "CREATE USER").await
+}
+
+pub async fn set_user_password(params: &ConnParams, user: &str, host: &str, password: &str) -> Result<(), String> {
+    let account = account_literal(user, host)?;
+    if password.is_empty() {
+        return Err("Password must not be empty".into());
+    }
+    exec_admin(params, &format!("ALTER USER {} IDENTIFIED BY '{}'", account, mysql_escape_string(password)), "ALTER USER").await
+}
+
+pub async fn drop_user(params: &ConnParams, user: &str, host: &str) -> Result<(), String> {
+    let account = account_literal(user, host)?;
+    exec_admin(params, &format!("DROP USER {}", account), "DROP USER").await
+}
+
+/// GRANT (or REVOKE when `revoke`) privileges on a scope.
+pub async fn change_grants(
+    params: &ConnParams,
+    user: &str,
+    host: &str,
+    privileges: &[String],
+    database: &str,
+    table: Option<&str>,
+    revoke: bool,
+) -> Result<String, String> {
+    let account = account_literal(user, host)?;
+    let privs = privilege_list(privileges)?;
+    let scope = grant_scope(database, table)?;
+    let sql = if revoke {
+        format!("REVOKE {} ON {} FROM {}", privs, scope, account)
+    } else {
+        format!("GRANT {} ON {} TO {}", privs, scope, account)
+    };
+    exec_admin(params, &sql, if revoke { "REVOKE" } else { "GRANT" }).await?;
+    Ok(sql)
+}
+
+/// Current slow query log settings.
+pub async fn slow_log_settings(params: &ConnParams) -> Result<serde_json::Value, String> {
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let vars: Vec<(String, String)> = conn
+        .query("SHOW GLOBAL VARIABLES WHERE Variable_name IN ('slow_query_log', 'long_query_time', 'log_output', 'slow_query_log_file')")
+        .await
+        .map_err(|e| format!("Reading slow log settings failed: {}", detailed_mysql_error(&e)))?;
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+    let get = |k: &str| vars.iter().find(|(n, _)| n.eq_ignore_ascii_case(k)).map(|(_, v)| v.clone()).unwrap_or_default();
+    let output = get("log_output");
+    Ok(serde_json::json!({
+        "enabled": matches!(get("slow_query_log").to_ascii_uppercase().as_str(), "ON" | "1"),
+        "long_query_time": get("long_query_time").parse::<f64>().unwrap_or(10.0),
+        "log_output": output,
+        "to_table": output.to_ascii_uppercase().contains("TABLE"),
+        "file": get("slow_query_log_file"),
+    }))
+}
+
+/// Turn the slow log on/off and set the threshold. `to_table` adds TABLE
+/// to `log_output` so entries can be read over the connection (a FILE-only
+/// log is only readable when the server is on this node). SET GLOBAL does
+/// not survive a server restart — the UI says so.
+pub async fn set_slow_log(params: &ConnParams, enabled: bool, long_query_time: f64, to_table: bool) -> Result<(), String> {
+    if !(0.0..=3600.0).contains(&long_query_time) {
+        return Err("long_query_time must be between 0 and 3600 seconds".into());
+    }
+    let (pool, mut conn) = get_conn_with_timeout(params).await?;
+    let mut result = conn.query_drop(format!("SET GLOBAL long_query_time = {}", long_query_time)).await;
+    if result.is_ok() && to_table {
+        result = conn.query_drop("SET GLOBAL log_output = 'FILE,TABLE'").await;
+    }
+    if result.is_ok() {
+        result = conn.query_drop(format!("SET GLOBAL slow_query_log = {}", if enabled { "ON" } else { "OFF" })).await;
+    }
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+    result.map_err(|e| format!("Updating slow log settings failed (needs SUPER or SYSTEM_VARIABLES_ADMIN): {}", detailed_mysql_error(&e)))
+}
+
+/// Most recent slow queries, newest first. Reads `mysql.slow_log` when
+/// `log_output` includes TABLE, otherwise the log file — which only works
+/// for a server on this node.
+pub async fn slow_log_entries(params: &ConnParams, limit: usize) -> Result<Vec<serde_json::Value>, String> {
+    let limit = limit.clamp(1, 500);
+    let settings = slow_log_settings(params).await?;
+    if settings["to_table"].as_bool().unwrap_or(false) {
+        let (pool, mut conn) = get_conn_with_timeout(params).await?;
+        let rows: Result<Vec<Row>, String> = conn
+            .query(format!(
+                "SELECT CAST(start_time AS CHAR), user_host, TIME_TO_SEC(query_time), TIME_TO_SEC(lock_time), \
+                 rows_sent, rows_examined, db, CONVERT(sql_text USING utf8mb4) \
+                 FROM mysql.slow_log ORDER BY start_time DESC LIMIT {}", limit))
+            .await
+            .map_err(|e| format!("Reading mysql.slow_log failed: {}", detailed_mysql_error(&e)));
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(2), pool.disconnect()).await;
+        return Ok(rows?.into_iter().map(|r| serde_json::json!({
+            "time": r.get::<Option<String>, _>(0).flatten(),
+            "user_host": r.get::<Option<String>, _>(1).flatten(),
+            "query_time": r.get::<Option<String>, _>(2).flatten().and_then(|v| v.parse::<f64>().ok()),
+            "lock_time": r.get::<Option<String>, _>(3).flatten().and_then(|v| v.parse::<f64>().ok()),
+            "rows_sent": r.get::<Option<u64>, _>(4).flatten(),
+            "rows_examined": r.get::<Option<u64>, _>(5).flatten(),
+            "db": r.get::<Option<String>, _>(6).flatten(),
+            "sql": r.get::<Option<String>, _>(7).flatten().unwrap_or_default(),
+        })).collect());
+    }
+
+    let file = settings["file"].as_str().unwrap_or("").to_string();
+    if !is_localhost(&params.host) || file.is_empty() {
+        return Err("The slow log is written to a file on the database host. Enable \"Log to table\" to view it from here.".into());
+    }
+    // Only the tail matters; cap the read so a multi-GB log can't OOM us.
+    let text = tokio::task::spawn_blocking(move || -> Result<String, String> {
+        use std::io::{Read, Seek, SeekFrom};
+        let mut f = std::fs::File::open(&file).map_err(|e| format!("Cannot open {}: {}", file, e))?;
+        let len = f.metadata().map(|m| m.len()).unwrap_or(0);
+        let _ = f.seek(SeekFrom::Start(len.saturating_sub(4 * 1024 * 1024)));
+        let mut buf = Vec::new();
+        f.read_to_end(&mut buf).map_err(|e| format!("Cannot read {}: {}", file, e))?;
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }).await.map_err(|e| e.to_string())??;
+    let mut entries = parse_slow_log(&text);
+    entries.reverse();
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+/// Parse the text slow log format:
+/// `# Time:` / `# User@Host:` / `# Query_time: … Lock_time: … Rows_sent: … Rows_examined: …`
+/// headers followed by the statement (including `SET timestamp=…;` and
+/// `use db;` lines, which are folded into the entry rather than shown).
+pub fn parse_slow_log(text: &str) -> Vec<serde_json::Value> {
+    let mut entries = Vec::new();
+    let mut cur: Option<serde_json::Value> = None;
+    let mut sql = String::new();
+    let mut last_time: Option<String> = None;
+
+    let flush = |cur: &mut Option<serde_json::Value>, sql: &mut String, entries: &mut Vec<serde_json::Value>| {
+        if let Some(mut e) = cur.take() {
+            e["sql"] = serde_json::Value::String(sql.trim().to_string());
+            entries.push(e);
+        }
+        sql.clear();
+    };
+
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("# Time:") {
+            flush(&mut cur, &mut sql, &mut entries);
+            last_time = Some(rest.trim().to_string());
+        } else if let Some(rest) = line.strip_prefix("# User@Host:") {
+            flush(&mut cur, &mut sql, &mut entries);
+            cur = Some(serde_json::json!({ "time": last_time, "user_host": rest.trim(), "db": null }));
+        } else if let Some(rest) = line.strip_prefix("# Query_time:") {
+            let e = cur.get_or_insert_with(|| serde_json::json!({ "time": last_time, "db": null }));
+            let mut fields = format!("Query_time: {}", rest).split_whitespace().map(str::to_string).collect::<Vec<_>>().into_iter();
+            while let (Some(k), Some(v)) = (fields.next(), fields.next()) {
+                let key = match k.trim_end_matches(':') {
+                    "Query_time" => "query_time",
+                    "Lock_time" => "lock_time",
+                    "Rows_sent" => "rows_sent",
+                    "Rows_examined" => "rows_examined",
+                    _ => continue,
+                };
+                e[key] = if key.ends_with("_time") {
+                    v.parse::<f64>().map(serde_json::Value::from).unwrap_or(serde_json::Value::Null)
+                } else {
+                    v.parse::<u64>().map(serde_json::Value::from).unwrap_or(serde_json::Value::Null)
+                };
+            }
+        } else if line.starts_with('#') {
+            // Other comment headers (Thread_id, Schema, …) — skip.
+        } else if let Some(e) = cur.as_mut() {
+            let t = line.trim();
+            if t.to_ascii_lowercase().starts_with("set timestamp=") {
+                continue;
+            }
+            if let Some(db) = t.strip_prefix("use ").or_else(|| t.strip_prefix("USE ")) {
+                e["db"] = serde_json::Value::String(db.trim_end_matches(';').trim_matches('`').to_string());
+                continue;
+            }
+            sql.push_str(line);
+            sql.push('\n');
+        }
+    }
+    flush(&mut cur, &mut sql, &mut entries);
+    entries
+}
+
+#[cfg(test)]
+mod admin_tests {
+    use super::*;
+
+    #[test]
+    fn account_literals_are_escaped() {
+        assert_eq!(account_literal("app", "").unwrap(), "'app'@'%'");
+        assert_eq!(account_literal("o'brien", "10.0.0.%").unwrap(), "'o\\'brien'@'10.0.0.%'");
+        assert!(account_literal("", "%").is_err());
+        assert!(account_literal("a\nb", "%").is_err());
+    }
+
+    #[test]
+    fn privileges_are_allowlisted_and_deduped() {
+        let p = |v: &[&str]| privilege_list(&v.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+        assert_eq!(p(&["select", "Insert", "SELECT"]).unwrap(), "SELECT, INSERT");
+        assert_eq!(p(&["all"]).unwrap(), "ALL PRIVILEGES");
+        assert!(p(&["SELECT; DROP DATABASE x"]).is_err());
+        assert!(p(&["SUPER"]).is_err());
+        assert!(p(&[]).is_err());
+    }
+
+    #[test]
+    fn grant_scope_quotes_identifiers() {
+        assert_eq!(grant_scope("*", None).unwrap(), "*.*");
+        assert_eq!(grant_scope("shop", Some("")).unwrap(), "`shop`.*");
+        assert_eq!(grant_scope("we`ird", Some("orders")).unwrap(), "`we``ird`.`orders`");
+    }
+
+    #[test]
+    fn parses_text_slow_log() {
+        let log = "/usr/sbin/mysqld, Version: 8.0.36. started with:\n\
+                   # Time: 2026-03-01T10:00:00.123456Z\n\
+                   # User@Host: app[app] @ localhost []  Id:    42\n\
+                   # Query_time: 2.500123  Lock_time: 0.000101 Rows_sent: 10  Rows_examined: 500000\n\
+                   use shop;\n\
+                   SET timestamp=1772359200;\n\
+                   SELECT *\n  FROM orders WHERE note LIKE '%x%';\n\
+                   # User@Host: root[root] @ localhost []  Id:    43\n\
+                   # Query_time: 11.0  Lock_time: 0.0 Rows_sent: 0  Rows_examined: 0\n\
+                   SET timestamp=1772359300;\n\
+                   OPTIMIZE TABLE orders;\n";
+        let e = parse_slow_log(log);
+        assert_eq!(e.len(), 2);
+        assert_eq!(e[0]["db"], "shop");
+        assert_eq!(e[0]["query_time"], 2.500123);
+        assert_eq!(e[0]["rows_examined"], 500000);
+        assert_eq!(e[0]["sql"], "SELECT *\n  FROM orders WHERE note LIKE '%x%';");
+        assert_eq!(e[0]["time"], "2026-03-01T10:00:00.123456Z");
+        assert_eq!(e[1]["sql"], "OPTIMIZE TABLE orders;");
+        assert_eq!(e[1]["time"], "2026-03-01T10:00:00.123456Z");
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // PostgreSQL Support
 // ═══════════════════════════════════════════════════════════════════════════
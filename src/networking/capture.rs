@@ -0,0 +1,271 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! On-demand packet capture behind `/api/networking/capture`.
+//!
+//! Runs `tcpdump` on a host interface, or inside a container's network
+//! namespace (via `nsenter`, so the container needn't ship tcpdump), and
+//! hands back the .pcap. Every capture is bounded three ways — time,
+//! packet count and file size — whatever the caller asks for, and only one
+//! runs per node at a time so a stuck browser tab can't stack them up.
+
+use serde::Deserialize;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+pub const DEFAULT_SECS: u64 = 10;
+pub const MAX_SECS: u64 = 120;
+pub const DEFAULT_PACKETS: u64 = 1000;
+pub const MAX_PACKETS: u64 = 100_000;
+/// The capture is stopped once the file reaches this size.
+pub const MAX_BYTES: u64 = 32 * 1024 * 1024;
+const MAX_FILTER_LEN: usize = 1024;
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Releases the one-capture-at-a-time slot.
+struct Slot;
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        CAPTURING.store(false, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CaptureRequest {
+    /// Interface to capture on. On the host it must exist (or be "any");
+    /// inside a container it defaults to "any".
+    #[serde(default)]
+    pub interface: String,
+    /// Capture inside this container's network namespace instead.
+    #[serde(default)]
+    pub container: String,
+    /// "docker" or "lxc"; needed with `container`.
+    #[serde(default)]
+    pub runtime: String,
+    /// BPF filter expression, e.g. `port 53` or `host 1.2.3.4 and tcp`.
+    #[serde(default)]
+    pub filter: String,
+    /// Seconds to capture (default [`DEFAULT_SECS`], capped at [`MAX_SECS`]).
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// Packets to capture (default [`DEFAULT_PACKETS`], capped at [`MAX_PACKETS`]).
+    #[serde(default)]
+    pub max_packets: Option<u64>,
+    /// Bytes kept per packet; 0 = tcpdump's default (whole packet).
+    #[serde(default)]
+    pub snaplen: u32,
+}
+
+impl CaptureRequest {
+    pub fn duration(&self) -> u64 {
+        self.duration_secs.unwrap_or(DEFAULT_SECS).clamp(1, MAX_SECS)
+    }
+
+    pub fn packets(&self) -> u64 {
+        self.max_packets.unwrap_or(DEFAULT_PACKETS).clamp(1, MAX_PACKETS)
+    }
+
+    /// "eth0", "any in docker web" — for logs and the audit trail.
+    pub fn target(&self) -> String {
+        let iface = if self.interface.is_empty() { "any" } else { &self.interface };
+        if self.container.is_empty() {
+            iface.to_string()
+        } else {
+            format!("{} in {} {}", iface, self.runtime, self.container)
+        }
+    }
+}
+
+pub struct Capture {
+    pub data: Vec<u8>,
+    /// From tcpdump's closing summary, when it printed one.
+    pub packets: Option<u64>,
+    /// What ended it: "time", "packets" or "size".
+    pub stopped_by: &'static str,
+}
+
+fn valid_interface(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 15
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@'))
+}
+
+fn valid_container(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 128
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+/// Check the request before anything runs. Returns the interface to use.
+fn validate(req: &CaptureRequest) -> Result<String, String> {
+    if req.filter.len() > MAX_FILTER_LEN {
+        return Err(format!("Filter is longer than {} characters", MAX_FILTER_LEN));
+    }
+    if req.filter.chars().any(|c| c.is_control()) {
+        return Err("Filter contains control characters".into());
+    }
+    if !req.container.is_empty() {
+        if !valid_container(&req.container) {
+            return Err(format!("Invalid container name '{}'", req.container));
+        }
+        if !matches!(req.runtime.as_str(), "docker" | "lxc") {
+            return Err("runtime must be 'docker' or 'lxc'".into());
+        }
+    }
+    let iface = if req.interface.is_empty() { "any".to_string() } else { req.interface.clone() };
+    if !valid_interface(&iface) {
+        return Err(format!("Invalid interface name '{}'", iface));
+    }
+    if req.container.is_empty() && iface != "any"
+        && !std::path::Path::new(&format!("/sys/class/net/{}", iface)).exists() {
+        return Err(format!("Interface '{}' not found on this host", iface));
+    }
+    Ok(iface)
+}
+
+/// "123 packets captured" from tcpdump's stderr.
+fn captured_count(stderr: &str) -> Option<u64> {
+    stderr.lines()
+        .find_map(|l| l.trim().strip_suffix(" packets captured").or_else(|| l.trim().strip_suffix(" packet captured")))
+        .and_then(|n| n.trim().parse().ok())
+}
+
+fn interrupt(child: &std::process::Child) {
+    // SIGINT lets tcpdump flush the file and print its summary.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT); }
+}
+
+/// Run the capture. Blocking for up to [`MAX_SECS`] — keep it off the
+/// async runtime.
+pub fn run(req: &CaptureRequest) -> Result<Capture, String> {
+    let iface = validate(req)?;
+    if CAPTURING.swap(true, Ordering::SeqCst) {
+        return Err("Another packet capture is already running on this node".into());
+    }
+    let _slot = Slot;
+
+    let file = std::env::temp_dir().join(format!("wolfstack-capture-{}.pcap", uuid::Uuid::new_v4()));
+    let file_str = file.to_string_lossy().to_string();
+    let packets = req.packets().to_string();
+    let mut args: Vec<String> = vec!["-n".into(), "-U".into(), "-i".into(), iface,
+        "-c".into(), packets, "-w".into(), file_str];
+    if req.snaplen > 0 {
+        args.extend(["-s".into(), req.snaplen.min(262_144).to_string()]);
+    }
+    if !req.filter.trim().is_empty() {
+        // "--" so a filter can never be read as a tcpdump option.
+        args.extend(["--".into(), req.filter.trim().to_string()]);
+    }
+
+    let mut cmd = if req.container.is_empty() {
+        Command::new("tcpdump")
+    } else {
+        let pid = crate::containers::container_init_pid(&req.runtime, &req.container)
+            .ok_or_else(|| format!("{} container '{}' is not running", req.runtime, req.container))?;
+        let mut c = Command::new("nsenter");
+        c.args(["-t", &pid.to_string(), "-n", "tcpdump"]);
+        c
+    };
+    let mut child = cmd.args(&args)
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| if e.kind() == std::io::ErrorKind::NotFound {
+            "tcpdump is not installed on this node".to_string()
+        } else {
+            format!("Failed to start tcpdump: {}", e)
+        })?;
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(req.duration());
+    let mut stopped_by = "packets";
+    let mut signalled_at: Option<Instant> = None;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {}
+            Err(e) => {
+                let _ = child.kill();
+                let _ = std::fs::remove_file(&file);
+                return Err(format!("tcpdump: {}", e));
+            }
+        }
+        match signalled_at {
+            None => {
+                let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+                if size >= MAX_BYTES {
+                    stopped_by = "size";
+                } else if Instant::now() >= deadline {
+                    stopped_by = "time";
+                } else {
+                    std::thread::sleep(Duration::from_millis(200));
+                    continue;
+                }
+                interrupt(&child);
+                signalled_at = Some(Instant::now());
+            }
+            Some(at) if at.elapsed() > Duration::from_secs(5) => {
+                let _ = child.kill();
+            }
+            Some(_) => {}
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        use std::io::Read;
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    let data = std::fs::read(&file);
+    let _ = std::fs::remove_file(&file);
+    if signalled_at.is_none() && !status.success() {
+        let msg = stderr.lines().rfind(|l| !l.trim().is_empty()).unwrap_or("").trim().to_string();
+        return Err(if msg.is_empty() { format!("tcpdump exited with {}", status) } else { format!("tcpdump: {}", msg) });
+    }
+    let data = data.map_err(|e| format!("Capture produced no file: {}", e))?;
+    let packets = captured_count(&stderr);
+    info!("packet capture on {} ended ({}) after {:.1}s: {} bytes, {} packets",
+        req.target(), stopped_by, started.elapsed().as_secs_f32(), data.len(),
+        packets.map(|p| p.to_string()).unwrap_or_else(|| "?".into()));
+    Ok(Capture { data, packets, stopped_by })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_and_validation() {
+        let mut req = CaptureRequest { duration_secs: Some(9999), max_packets: Some(0), ..Default::default() };
+        assert_eq!((req.duration(), req.packets()), (MAX_SECS, 1));
+        assert_eq!(validate(&req).unwrap(), "any");
+
+        req.interface = "eth0; rm -rf /".into();
+        assert!(validate(&req).is_err());
+        req.interface = "nosuchnic0".into();
+        assert!(validate(&req).unwrap_err().contains("not found"));
+
+        req.interface = String::new();
+        req.container = "web".into();
+        assert!(validate(&req).is_err(), "container needs a runtime");
+        req.runtime = "docker".into();
+        assert_eq!(validate(&req).unwrap(), "any");
+        assert_eq!(req.target(), "any in docker web");
+
+        req.filter = "port 53\nand host x".into();
+        assert!(validate(&req).is_err());
+        req.filter = "x".repeat(MAX_FILTER_LEN + 1);
+        assert!(validate(&req).is_err());
+    }
+
+    #[test]
+    fn reads_tcpdump_summary() {
+        let stderr = "tcpdump: listening on any, link-type LINUX_SLL2\n42 packets captured\n50 packets received by filter\n0 packets dropped by kernel\n";
+        assert_eq!(captured_count(stderr), Some(42));
+        assert_eq!(captured_count("1 packet captured\n"), Some(1));
+        assert_eq!(captured_count("tcpdump: eth9: No such device exists"), None);
+    }
+}
@@ -0,0 +1,398 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Live connection table behind `/api/networking/connections` — "why is
+//! this container talking to the internet" without reaching for tcpdump.
+//!
+//! The source is the kernel's connection-tracking table (`conntrack -L`),
+//! which sees traffic forwarded for containers as well as the host's own.
+//! Without conntrack-tools it falls back to `ss`, which only sees the
+//! host's sockets. Each flow's endpoints are matched against the local
+//! Docker and LXC containers (bridge and WolfNet addresses), so NATed
+//! container traffic is attributed to the container rather than the host.
+//! Byte counts come from conntrack accounting and are zero when
+//! `net.netfilter.nf_conntrack_acct` is off.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::process::Command;
+
+/// Flows read from the table; the summary is computed over at most this many.
+const MAX_FLOWS: usize = 20_000;
+/// Entries in each top-N list.
+const TOP_N: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Connection {
+    pub proto: String,
+    /// TCP state; empty for UDP/ICMP.
+    pub state: String,
+    pub src: String,
+    pub dst: String,
+    pub sport: Option<u16>,
+    pub dport: Option<u16>,
+    /// Both directions, from conntrack accounting.
+    pub bytes: u64,
+    /// Local container the flow belongs to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// "outbound" when the container opened it, "inbound" when it's the
+    /// target; empty for host traffic.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub direction: String,
+    /// The far end is a public address.
+    pub external: bool,
+    /// Owning process (ss only).
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub process: String,
+    /// Reply-direction source — the real target of a DNAT.
+    #[serde(skip)]
+    reply_src: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Talker {
+    /// Address, or "container (address)".
+    pub name: String,
+    pub connections: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContainerConnections {
+    pub name: String,
+    pub runtime: String,
+    pub total: usize,
+    pub outbound: usize,
+    pub inbound: usize,
+    /// Connections whose far end is a public address.
+    pub external: usize,
+    pub bytes: u64,
+    pub states: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectionReport {
+    /// "conntrack" or "ss".
+    pub source: String,
+    pub total: usize,
+    /// The table held more than [`MAX_FLOWS`] flows; only those were read.
+    pub truncated: bool,
+    pub states: BTreeMap<String, usize>,
+    pub protocols: BTreeMap<String, usize>,
+    /// Busiest sources, by bytes then connection count.
+    pub top_talkers: Vec<Talker>,
+    /// Busiest far ends ("address:port").
+    pub top_destinations: Vec<Talker>,
+    pub containers: Vec<ContainerConnections>,
+    /// The flows matching the filter, busiest first, capped at the limit.
+    pub connections: Vec<Connection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionFilter {
+    /// Only flows of this container.
+    pub container: Option<String>,
+    /// Only flows to or from a public address.
+    pub external_only: bool,
+    /// Only flows in this TCP state.
+    pub state: Option<String>,
+    pub limit: usize,
+}
+
+/// Not private, loopback, link-local, CGNAT, multicast or unspecified.
+fn is_public(ip: &str) -> bool {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(v4)) => {
+            let o = v4.octets();
+            !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_multicast()
+                || v4.is_broadcast() || v4.is_unspecified()
+                || (o[0] == 100 && (64..128).contains(&o[1])))
+        }
+        Ok(IpAddr::V6(v6)) => {
+            let s = v6.segments();
+            !(v6.is_loopback() || v6.is_multicast() || v6.is_unspecified()
+                || (s[0] & 0xfe00) == 0xfc00 || (s[0] & 0xffc0) == 0xfe80)
+        }
+        Err(_) => false,
+    }
+}
+
+/// One line of `conntrack -L` (default format):
+/// `tcp 6 431999 ESTABLISHED src=… dst=… sport=… dport=… [packets=… bytes=…] src=… … [ASSURED] mark=0 use=1`.
+/// The first src/dst/sport/dport are the original direction, the second
+/// set the reply.
+fn parse_conntrack_line(line: &str) -> Option<Connection> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 4 || parts[0] == "conntrack" {
+        return None;
+    }
+    let mut c = Connection { proto: parts[0].to_string(), ..Default::default() };
+    let mut start = 3;
+    if !parts[3].contains('=') {
+        c.state = parts[3].to_string();
+        start = 4;
+    }
+    let mut srcs = 0;
+    for part in &parts[start..] {
+        let Some((k, v)) = part.split_once('=') else { continue };
+        match k {
+            "src" => {
+                srcs += 1;
+                if srcs == 1 { c.src = v.to_string() } else if srcs == 2 { c.reply_src = v.to_string() }
+            }
+            "dst" if srcs == 1 => c.dst = v.to_string(),
+            "sport" if srcs == 1 => c.sport = v.parse().ok(),
+            "dport" if srcs == 1 => c.dport = v.parse().ok(),
+            "bytes" => c.bytes += v.parse::<u64>().unwrap_or(0),
+            _ => {}
+        }
+    }
+    (!c.src.is_empty() && !c.dst.is_empty()).then_some(c)
+}
+
+/// Split `ss` address columns: `10.0.0.1:443`, `[::1]:22`, `[::ffff:1.2.3.4]:80`,
+/// `10.0.0.1%eth0:68`, `*:*`.
+fn split_host_port(addr: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = addr.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let host = host.split('%').next().unwrap_or(host);
+    let host = host.strip_prefix("::ffff:").filter(|h| h.contains('.')).unwrap_or(host);
+    Some((host.to_string(), port.parse().ok()))
+}
+
+/// One line of `ss -tunapH`: `tcp ESTAB 0 0 10.0.0.1:22 10.0.0.9:51234 users:(("sshd",pid=1,fd=4))`.
+/// Listening and unconnected sockets have no far end and are skipped.
+fn parse_ss_line(line: &str) -> Option<Connection> {
+    let cols: Vec<&str> = line.split_whitespace().collect();
+    if cols.len() < 6 || matches!(cols[1], "LISTEN" | "UNCONN") {
+        return None;
+    }
+    let (src, sport) = split_host_port(cols[4])?;
+    let (dst, dport) = split_host_port(cols[5])?;
+    if dst == "*" || dst.is_empty() {
+        return None;
+    }
+    let process = cols.get(6).and_then(|p| p.split('"').nth(1)).unwrap_or("").to_string();
+    Some(Connection {
+        proto: cols[0].to_string(),
+        state: cols[1].to_string(),
+        src, dst, sport, dport, process,
+        ..Default::default()
+    })
+}
+
+/// Tie each flow to a local container and mark public far ends. `owners`
+/// maps container addresses to (name, runtime).
+fn attribute(conns: &mut [Connection], owners: &HashMap<String, (String, String)>) {
+    for c in conns {
+        let (owner, far) = if let Some(o) = owners.get(&c.src) {
+            c.direction = "outbound".into();
+            (Some(o), c.dst.clone())
+        } else if let Some(o) = owners.get(&c.dst).or_else(|| owners.get(&c.reply_src)) {
+            c.direction = "inbound".into();
+            (Some(o), c.src.clone())
+        } else {
+            (None, if is_public(&c.dst) { c.dst.clone() } else { c.src.clone() })
+        };
+        c.container = owner.map(|(name, _)| name.clone());
+        c.external = is_public(&far);
+    }
+}
+
+fn top(map: HashMap<String, (usize, u64)>) -> Vec<Talker> {
+    let mut list: Vec<Talker> = map.into_iter()
+        .map(|(name, (connections, bytes))| Talker { name, connections, bytes })
+        .collect();
+    list.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(b.connections.cmp(&a.connections)).then(a.name.cmp(&b.name)));
+    list.truncate(TOP_N);
+    list
+}
+
+/// Aggregate `conns` and pick the ones `filter` lets through.
+fn summarize(source: &str, mut conns: Vec<Connection>, owners: &HashMap<String, (String, String)>,
+             filter: &ConnectionFilter) -> ConnectionReport {
+    attribute(&mut conns, owners);
+    let mut report = ConnectionReport { source: source.to_string(), total: conns.len(), ..Default::default() };
+    let mut talkers: HashMap<String, (usize, u64)> = HashMap::new();
+    let mut destinations: HashMap<String, (usize, u64)> = HashMap::new();
+    let mut containers: BTreeMap<String, ContainerConnections> = BTreeMap::new();
+    for c in &conns {
+        let state = if c.state.is_empty() { c.proto.to_uppercase() } else { c.state.clone() };
+        *report.states.entry(state.clone()).or_default() += 1;
+        *report.protocols.entry(c.proto.clone()).or_default() += 1;
+
+        let talker = match (&c.container, c.direction.as_str()) {
+            (Some(name), "outbound") => format!("{} ({})", name, c.src),
+            _ => c.src.clone(),
+        };
+        let t = talkers.entry(talker).or_default();
+        t.0 += 1;
+        t.1 += c.bytes;
+        let dest = match c.dport {
+            Some(port) => format!("{}:{}", c.dst, port),
+            None => c.dst.clone(),
+        };
+        let d = destinations.entry(dest).or_default();
+        d.0 += 1;
+        d.1 += c.bytes;
+
+        if let Some(name) = &c.container {
+            let runtime = owners.values().find(|(n, _)| n == name).map(|(_, r)| r.clone()).unwrap_or_default();
+            let entry = containers.entry(name.clone()).or_insert_with(|| ContainerConnections {
+                name: name.clone(), runtime, ..Default::default()
+            });
+            entry.total += 1;
+            entry.bytes += c.bytes;
+            if c.direction == "outbound" { entry.outbound += 1 } else { entry.inbound += 1 }
+            if c.external { entry.external += 1 }
+            *entry.states.entry(state).or_default() += 1;
+        }
+    }
+    report.top_talkers = top(talkers);
+    report.top_destinations = top(destinations);
+    report.containers = containers.into_values().collect();
+    report.containers.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(&b.name)));
+
+    conns.retain(|c| {
+        filter.container.as_ref().is_none_or(|name| c.container.as_ref() == Some(name))
+            && (!filter.external_only || c.external)
+            && filter.state.as_ref().is_none_or(|s| c.state.eq_ignore_ascii_case(s))
+    });
+    conns.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+    conns.truncate(filter.limit);
+    report.connections = conns;
+    report
+}
+
+/// Addresses of the local containers — bridge IPs and WolfNet IPs — to
+/// (name, runtime).
+fn container_addresses() -> HashMap<String, (String, String)> {
+    let mut owners = HashMap::new();
+    let docker = crate::containers::docker_list_all_cached();
+    let lxc = crate::containers::lxc_list_all_cached();
+    for c in docker.iter().chain(lxc.iter()) {
+        for addr in c.ip_address.split(',') {
+            let Some(ip) = addr.split_whitespace().next().and_then(|a| a.split('/').next()) else { continue };
+            if ip.parse::<IpAddr>().is_ok() {
+                owners.insert(ip.to_string(), (c.name.clone(), c.runtime.clone()));
+            }
+        }
+        let wolfnet = match c.runtime.as_str() {
+            "docker" => crate::containers::docker_effective_wolfnet_ip(&c.name),
+            _ => crate::containers::lxc_get_wolfnet_ip(&c.name),
+        };
+        if let Some(ip) = wolfnet {
+            owners.insert(ip, (c.name.clone(), c.runtime.clone()));
+        }
+    }
+    owners
+}
+
+fn read_conntrack() -> Result<(Vec<Connection>, bool), String> {
+    let out = Command::new("timeout").args(["15", "conntrack", "-L"]).output()
+        .map_err(|e| format!("couldn't run conntrack: {}", e))?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(if err.is_empty() { "conntrack not installed".into() } else { err });
+    }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let mut lines = text.lines().filter_map(parse_conntrack_line);
+    let conns: Vec<Connection> = lines.by_ref().take(MAX_FLOWS).collect();
+    Ok((conns, lines.next().is_some()))
+}
+
+fn read_ss() -> Result<(Vec<Connection>, bool), String> {
+    let out = Command::new("timeout").args(["15", "ss", "-tunapH"]).output()
+        .map_err(|e| format!("couldn't run ss: {}", e))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let mut lines = text.lines().filter_map(parse_ss_line);
+    let conns: Vec<Connection> = lines.by_ref().take(MAX_FLOWS).collect();
+    Ok((conns, lines.next().is_some()))
+}
+
+/// Build the report for this host. Blocking — run it off the async runtime.
+pub fn report(filter: &ConnectionFilter) -> Result<ConnectionReport, String> {
+    let (source, (conns, truncated), note) = match read_conntrack() {
+        Ok(read) => ("conntrack", read, None),
+        Err(e) => {
+            let read = read_ss().map_err(|ss| format!("conntrack: {}; ss: {}", e, ss))?;
+            ("ss", read, Some(format!(
+                "conntrack unavailable ({}) — showing this host's own sockets only; install conntrack-tools to see container traffic", e)))
+        }
+    };
+    let mut report = summarize(source, conns, &container_addresses(), filter);
+    report.truncated = truncated;
+    report.note = note;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conntrack_and_ss_lines() {
+        let c = parse_conntrack_line("tcp      6 431999 ESTABLISHED src=172.17.0.2 dst=1.1.1.1 sport=44321 dport=443 \
+            packets=5 bytes=300 src=1.1.1.1 dst=203.0.113.5 sport=443 dport=44321 packets=4 bytes=500 [ASSURED] mark=0 use=1").unwrap();
+        assert_eq!((c.proto.as_str(), c.state.as_str()), ("tcp", "ESTABLISHED"));
+        assert_eq!((c.src.as_str(), c.dst.as_str(), c.sport, c.dport), ("172.17.0.2", "1.1.1.1", Some(44321), Some(443)));
+        assert_eq!((c.bytes, c.reply_src.as_str()), (800, "1.1.1.1"));
+        let u = parse_conntrack_line("udp      17 29 src=10.0.3.5 dst=10.0.3.1 sport=5353 dport=53 [UNREPLIED] src=10.0.3.1 dst=10.0.3.5 sport=53 dport=5353 mark=0 use=1").unwrap();
+        assert_eq!((u.state.as_str(), u.dport), ("", Some(53)));
+        assert!(parse_conntrack_line("conntrack v1.4.7 (conntrack-tools): 2 flow entries have been shown.").is_none());
+
+        let s = parse_ss_line(r#"tcp   ESTAB  0  0  [::ffff:10.0.0.1]:22  [::ffff:10.0.0.9]:51234 users:(("sshd",pid=1,fd=4))"#).unwrap();
+        assert_eq!((s.src.as_str(), s.dst.as_str(), s.dport, s.process.as_str()), ("10.0.0.1", "10.0.0.9", Some(51234), "sshd"));
+        assert!(parse_ss_line("tcp   LISTEN 0  128  0.0.0.0:22  0.0.0.0:*").is_none());
+    }
+
+    #[test]
+    fn attributes_flows_to_containers() {
+        let owners = HashMap::from([
+            ("172.17.0.2".to_string(), ("web".to_string(), "docker".to_string())),
+            ("10.0.3.7".to_string(), ("db".to_string(), "lxc".to_string())),
+        ]);
+        let conn = |src: &str, dst: &str, reply_src: &str, bytes: u64| Connection {
+            proto: "tcp".into(), state: "ESTABLISHED".into(), src: src.into(), dst: dst.into(),
+            dport: Some(443), bytes, reply_src: reply_src.into(), ..Default::default()
+        };
+        let conns = vec![
+            conn("172.17.0.2", "1.1.1.1", "1.1.1.1", 900),
+            conn("172.17.0.2", "10.0.3.7", "10.0.3.7", 100),
+            // DNAT: a client hit the host's public IP, the reply comes from db.
+            conn("198.51.100.4", "203.0.113.5", "10.0.3.7", 50),
+            conn("192.168.1.10", "192.168.1.1", "192.168.1.1", 10),
+        ];
+        let filter = ConnectionFilter { limit: 10, ..Default::default() };
+        let r = summarize("conntrack", conns.clone(), &owners, &filter);
+        assert_eq!(r.total, 4);
+        let web = r.containers.iter().find(|c| c.name == "web").unwrap();
+        assert_eq!((web.total, web.outbound, web.external, web.bytes), (2, 2, 1, 1000));
+        let db = r.containers.iter().find(|c| c.name == "db").unwrap();
+        assert_eq!((db.runtime.as_str(), db.inbound, db.external), ("lxc", 1, 1));
+        assert_eq!(r.top_talkers[0].name, "web (172.17.0.2)");
+        assert_eq!(r.top_talkers[0].bytes, 1000);
+
+        let only_web_out = ConnectionFilter { container: Some("web".into()), external_only: true, limit: 10, ..Default::default() };
+        let r = summarize("conntrack", conns, &owners, &only_web_out);
+        assert_eq!(r.connections.len(), 1);
+        assert_eq!(r.connections[0].dst, "1.1.1.1");
+    }
+
+    #[test]
+    fn public_addresses() {
+        assert!(is_public("1.1.1.1"));
+        assert!(is_public("2606:4700::1111"));
+        for ip in ["10.0.0.1", "172.17.0.2", "192.168.1.1", "100.64.0.1", "127.0.0.1", "169.254.1.1", "fd00::1", "fe80::1", "junk"] {
+            assert!(!is_public(ip), "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,915 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Network diagnostics behind `/api/cluster/diagnose/*`.
+//!
+//! The probes — [`ping`], [`traceroute`], [`mtr`], [`check_port`] and
+//! [`dns_lookup`] — run on this host against any host the admin names,
+//! and return parsed results alongside the tool's raw output. Hosts are
+//! [`HostName`]s, so nothing that reaches `ping` can be read as a flag,
+//! and every tool runs under a timeout and is killed if it overruns.
+//!
+//! [`reach`] answers "why can't this node reach node B": it resolves B,
+//! pings it, connects to its API port, works out what this node's own
+//! OUTPUT firewall does with the connection, checks the WolfNet overlay
+//! to B, and folds in the [`LocalFacts`] B reports about itself (is the
+//! port listening, and on which address; what its INPUT chain does with
+//! traffic from us). Each step becomes a [`Finding`]; the first failure
+//! is the verdict.
+//!
+//! Firewall evaluation ([`firewall_verdict`]) walks `iptables -S` the way
+//! the kernel would — first match wins, jumps into user chains are
+//! followed, built-in chains fall back to their policy — for the match
+//! options that decide cluster traffic (`-s`, `-d`, `-p`, `--dport(s)`,
+//! `--ctstate`). Rules it can't evaluate (negations, ipsets, …) are
+//! skipped and listed as uncertain rather than guessed at.
+
+use crate::validate::HostName;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Output of a probe tool, trimmed to what the UI shows.
+const MAX_OUTPUT: usize = 64 * 1024;
+
+/// Run `cmd` with a hard timeout; the child is killed if it overruns.
+/// `Ok` carries (success, stdout, stderr) — a tool that ran but failed
+/// (host unreachable) is still `Ok`.
+async fn run(cmd: &str, args: &[&str], timeout: Duration) -> Result<(bool, String, String), String> {
+    let child = tokio::process::Command::new(cmd)
+        .args(args)
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(timeout, child).await {
+        Ok(Ok(out)) => {
+            let mut stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+            stdout.truncate(stdout.floor_char_boundary(MAX_OUTPUT));
+            Ok((out.status.success(), stdout, String::from_utf8_lossy(&out.stderr).trim().to_string()))
+        }
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(format!("`{}` is not installed on this node", cmd))
+        }
+        Ok(Err(e)) => Err(format!("Couldn't run `{}`: {}", cmd, e)),
+        Err(_) => Err(format!("`{}` timed out after {}s", cmd, timeout.as_secs())),
+    }
+}
+
+// ─── Ping ───
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PingResult {
+    pub host: String,
+    pub transmitted: u32,
+    pub received: u32,
+    pub loss_percent: f64,
+    pub rtt_min_ms: Option<f64>,
+    pub rtt_avg_ms: Option<f64>,
+    pub rtt_max_ms: Option<f64>,
+    pub output: String,
+}
+
+impl PingResult {
+    pub fn reachable(&self) -> bool {
+        self.received > 0
+    }
+}
+
+/// Pull the counts and round-trip times out of `ping`'s summary lines
+/// (iputils and busybox formats).
+fn parse_ping(host: &str, output: &str) -> PingResult {
+    let mut r = PingResult { host: host.to_string(), output: output.to_string(), ..Default::default() };
+    for line in output.lines() {
+        if line.contains("packets transmitted") {
+            for part in line.split(',') {
+                let part = part.trim();
+                let num = part.split_whitespace().next().unwrap_or("");
+                if part.ends_with("packets transmitted") {
+                    r.transmitted = num.parse().unwrap_or(0);
+                } else if part.contains("received") {
+                    r.received = num.parse().unwrap_or(0);
+                } else if part.ends_with("packet loss") {
+                    r.loss_percent = num.trim_end_matches('%').parse().unwrap_or(0.0);
+                }
+            }
+        } else if let Some((label, values)) = line.split_once(" = ")
+            && label.contains("min/avg/max")
+        {
+            let nums: Vec<f64> = values.trim_end_matches(" ms").split('/')
+                .filter_map(|v| v.trim().parse().ok())
+                .collect();
+            if nums.len() >= 3 {
+                (r.rtt_min_ms, r.rtt_avg_ms, r.rtt_max_ms) = (Some(nums[0]), Some(nums[1]), Some(nums[2]));
+            }
+        }
+    }
+    if r.transmitted > 0 && r.loss_percent == 0.0 && r.received < r.transmitted {
+        r.loss_percent = 100.0 * f64::from(r.transmitted - r.received) / f64::from(r.transmitted);
+    }
+    r
+}
+
+/// `count` echo requests (1–20), one second apart, 2 s wait each.
+pub async fn ping(host: &HostName, count: u32) -> Result<PingResult, String> {
+    let count = count.clamp(1, 20).to_string();
+    let (_, stdout, stderr) = run("ping", &["-n", "-c", &count, "-W", "2", host], Duration::from_secs(60)).await?;
+    let result = parse_ping(host, &stdout);
+    if result.transmitted == 0 {
+        // Name didn't resolve, no route, …: ping says why on stderr.
+        return Err(if stderr.is_empty() { "ping failed".into() } else { stderr });
+    }
+    Ok(result)
+}
+
+// ─── Traceroute ───
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraceHop {
+    pub hop: u32,
+    /// `None` when the hop didn't answer (`*`).
+    pub address: Option<String>,
+    pub rtt_ms: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TraceResult {
+    pub host: String,
+    pub hops: Vec<TraceHop>,
+    pub reached: bool,
+    pub output: String,
+}
+
+fn parse_traceroute(output: &str) -> Vec<TraceHop> {
+    output.lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            let hop: u32 = cols.next()?.parse().ok()?;
+            let address = cols.next().filter(|a| *a != "*").map(String::from);
+            let rtt_ms = cols.next().and_then(|v| v.parse().ok());
+            Some(TraceHop { hop, address, rtt_ms })
+        })
+        .collect()
+}
+
+/// Numeric traceroute, one probe per hop, up to `max_hops` (1–30).
+pub async fn traceroute(host: &HostName, max_hops: u32) -> Result<TraceResult, String> {
+    let max_hops = max_hops.clamp(1, 30).to_string();
+    let (_, stdout, stderr) = run(
+        "traceroute", &["-n", "-q", "1", "-w", "2", "-m", &max_hops, host], Duration::from_secs(90),
+    ).await?;
+    let hops = parse_traceroute(&stdout);
+    if hops.is_empty() {
+        return Err(if stderr.is_empty() { "traceroute produced no hops".into() } else { stderr });
+    }
+    let target = resolve(host).await.ok().and_then(|a| a.first().copied()).map(|ip| ip.to_string());
+    let reached = target.is_some_and(|t| hops.last().and_then(|h| h.address.as_deref()) == Some(t.as_str()));
+    Ok(TraceResult { host: host.to_string(), hops, reached, output: stdout })
+}
+
+// ─── mtr ───
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MtrHop {
+    pub hop: u32,
+    pub host: String,
+    pub loss_percent: f64,
+    pub sent: u32,
+    pub last_ms: f64,
+    pub avg_ms: f64,
+    pub best_ms: f64,
+    pub worst_ms: f64,
+    pub stdev_ms: f64,
+}
+
+/// Hops from `mtr --json` (`report.hubs`).
+fn parse_mtr(json: &str) -> Result<Vec<MtrHop>, String> {
+    let v: serde_json::Value = serde_json::from_str(json).map_err(|e| format!("unexpected mtr output: {}", e))?;
+    let hubs = v["report"]["hubs"].as_array().ok_or("mtr output has no hops")?;
+    let num = |h: &serde_json::Value, k: &str| h[k].as_f64().unwrap_or(0.0);
+    Ok(hubs.iter().enumerate().map(|(i, h)| MtrHop {
+        hop: h["count"].as_u64().map(|c| c as u32).unwrap_or(i as u32 + 1),
+        host: h["host"].as_str().unwrap_or("???").to_string(),
+        loss_percent: num(h, "Loss%"),
+        sent: num(h, "Snt") as u32,
+        last_ms: num(h, "Last"),
+        avg_ms: num(h, "Avg"),
+        best_ms: num(h, "Best"),
+        worst_ms: num(h, "Wrst"),
+        stdev_ms: num(h, "StDev"),
+    }).collect())
+}
+
+/// `mtr` report over `cycles` rounds (1–30): per-hop loss and latency.
+pub async fn mtr(host: &HostName, cycles: u32) -> Result<Vec<MtrHop>, String> {
+    let cycles = cycles.clamp(1, 30).to_string();
+    let (ok, stdout, stderr) = run(
+        "mtr", &["--report", "--json", "--no-dns", "-c", &cycles, host], Duration::from_secs(120),
+    ).await?;
+    if !ok && stdout.trim().is_empty() {
+        return Err(if stderr.is_empty() { "mtr failed".into() } else { stderr });
+    }
+    parse_mtr(&stdout)
+}
+
+// ─── TCP port check ───
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PortCheck {
+    pub host: String,
+    pub port: u16,
+    pub open: bool,
+    /// The address actually connected to (or tried last).
+    pub address: Option<String>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Try a TCP connection to `host:port` within `timeout`.
+pub async fn check_port(host: &HostName, port: u16, timeout: Duration) -> PortCheck {
+    let mut r = PortCheck { host: host.to_string(), port, ..Default::default() };
+    let addrs = match resolve(host).await {
+        Ok(a) => a,
+        Err(e) => {
+            r.error = Some(e);
+            return r;
+        }
+    };
+    for ip in addrs {
+        let addr = std::net::SocketAddr::new(ip, port);
+        r.address = Some(addr.to_string());
+        let start = Instant::now();
+        match tokio::time::timeout(timeout, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => {
+                r.open = true;
+                r.latency_ms = Some(start.elapsed().as_millis() as u64);
+                r.error = None;
+                return r;
+            }
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                r.error = Some("connection refused — reachable, but nothing is listening (or a firewall REJECTs it)".into());
+            }
+            Ok(Err(e)) => r.error = Some(e.to_string()),
+            Err(_) => r.error = Some(format!("no answer within {}s — dropped by a firewall or host down", timeout.as_secs())),
+        }
+    }
+    r
+}
+
+// ─── DNS ───
+
+/// Record types [`dns_lookup`] will ask for.
+pub const RECORD_TYPES: &[&str] = &["A", "AAAA", "CNAME", "MX", "NS", "TXT", "SOA", "SRV", "PTR", "CAA"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DnsResult {
+    pub name: String,
+    pub record_type: String,
+    /// Server asked; `None` = the system resolver.
+    pub server: Option<String>,
+    pub answers: Vec<String>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Addresses for `host` through the system resolver (an IP is itself).
+async fn resolve(host: &HostName) -> Result<Vec<IpAddr>, String> {
+    if let Some(ip) = host.ip() {
+        return Ok(vec![ip]);
+    }
+    let addrs: Vec<IpAddr> = tokio::net::lookup_host((host.as_str(), 0)).await
+        .map_err(|e| format!("cannot resolve {}: {}", host, e))?
+        .map(|a| a.ip())
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("{} has no addresses", host));
+    }
+    Ok(addrs)
+}
+
+/// Look `name` up. A/AAAA against the system resolver use it directly
+/// (what WolfStack itself would get); anything else, or a specific
+/// `server`, goes through `dig`.
+pub async fn dns_lookup(name: &HostName, record_type: &str, server: Option<&HostName>) -> DnsResult {
+    let record_type = record_type.trim().to_ascii_uppercase();
+    let mut r = DnsResult {
+        name: name.to_string(),
+        record_type: record_type.clone(),
+        server: server.map(|s| s.to_string()),
+        ..Default::default()
+    };
+    if !RECORD_TYPES.contains(&record_type.as_str()) {
+        r.error = Some(format!("record type must be one of {}", RECORD_TYPES.join(", ")));
+        return r;
+    }
+    let start = Instant::now();
+    if server.is_none() && (record_type == "A" || record_type == "AAAA") {
+        match resolve(name).await {
+            Ok(addrs) => r.answers = addrs.iter()
+                .filter(|a| a.is_ipv4() == (record_type == "A"))
+                .map(|a| a.to_string())
+                .collect(),
+            Err(e) => r.error = Some(e),
+        }
+    } else {
+        let at = server.map(|s| format!("@{}", s));
+        let mut args = vec!["+short", "+time=2", "+tries=1"];
+        if let Some(at) = &at {
+            args.push(at);
+        }
+        if record_type == "PTR" && name.ip().is_some() {
+            args.extend(["-x", name.as_str()]);
+        } else {
+            args.extend([name.as_str(), record_type.as_str()]);
+        }
+        match run("dig", &args, Duration::from_secs(15)).await {
+            Ok((true, stdout, _)) => r.answers = stdout.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with(';'))
+                .map(String::from)
+                .collect(),
+            Ok((false, stdout, stderr)) => {
+                let why = if stderr.is_empty() { stdout.trim().to_string() } else { stderr };
+                r.error = Some(if why.is_empty() { "lookup failed".into() } else { why });
+            }
+            Err(e) => r.error = Some(e),
+        }
+    }
+    r.latency_ms = start.elapsed().as_millis() as u64;
+    if r.error.is_none() && r.answers.is_empty() {
+        r.error = Some(format!("no {} records", record_type));
+    }
+    r
+}
+
+// ─── Firewall evaluation ───
+
+/// The connection whose fate [`firewall_verdict`] works out.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    pub src: Option<IpAddr>,
+    pub dst: Option<IpAddr>,
+    /// `tcp` or `udp`.
+    pub proto: &'static str,
+    pub dport: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Accept,
+    Drop,
+    Reject,
+    /// No firewall rules could be read.
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Verdict {
+    pub decision: Decision,
+    /// The rule (or `-P` policy line) that decided it.
+    pub rule: Option<String>,
+    /// Rules that might apply but couldn't be evaluated.
+    pub uncertain: Vec<String>,
+}
+
+fn parse_cidr(s: &str) -> Option<(IpAddr, u8)> {
+    match s.split_once('/') {
+        Some((ip, prefix)) => Some((ip.parse().ok()?, prefix.parse().ok()?)),
+        None => {
+            let ip: IpAddr = s.parse().ok()?;
+            Some((ip, if ip.is_ipv4() { 32 } else { 128 }))
+        }
+    }
+}
+
+/// Whether the rule's match options cover `probe`. `None` = can't tell.
+fn rule_matches(tokens: &[&str], probe: &Probe) -> Option<bool> {
+    let addr_matches = |spec: &str, ip: Option<IpAddr>| -> Option<bool> {
+        let (net, prefix) = parse_cidr(spec)?;
+        Some(match ip {
+            Some(ip) => crate::auth::ip_in_cidr(&ip.to_canonical(), &net, prefix),
+            // Unknown address: only a catch-all covers it for sure.
+            None => prefix == 0,
+        })
+    };
+    let port_matches = |spec: &str| -> Option<bool> {
+        let Some(port) = probe.dport else { return Some(false) };
+        Some(spec.split(',').any(|part| match part.split_once(':') {
+            Some((lo, hi)) => {
+                let lo: u16 = lo.parse().unwrap_or(0);
+                let hi: u16 = hi.parse().unwrap_or(u16::MAX);
+                (lo..=hi).contains(&port)
+            }
+            None => part.parse::<u16>().ok() == Some(port),
+        }))
+    };
+    let mut i = 0;
+    while i < tokens.len() {
+        let opt = tokens[i];
+        let val = tokens.get(i + 1).copied().unwrap_or("");
+        match opt {
+            "!" => return None,
+            "-A" | "-j" | "-g" | "--comment" | "--reject-with" | "--log-prefix" | "--log-level" => i += 2,
+            "-m" => {
+                if !matches!(val, "tcp" | "udp" | "comment" | "conntrack" | "state" | "multiport" | "limit") {
+                    return None;
+                }
+                i += 2;
+            }
+            "-s" => { if !addr_matches(val, probe.src)? { return Some(false) } i += 2; }
+            "-d" => { if !addr_matches(val, probe.dst)? { return Some(false) } i += 2; }
+            "-p" => {
+                if !(val == "all" || val == probe.proto) {
+                    return Some(false);
+                }
+                i += 2;
+            }
+            "--dport" | "--dports" | "--destination-port" | "--destination-ports" => {
+                if !port_matches(val)? { return Some(false) }
+                i += 2;
+            }
+            "--ctstate" | "--state" => {
+                // A new connection is state NEW.
+                if !val.split(',').any(|s| s == "NEW") {
+                    return Some(false);
+                }
+                i += 2;
+            }
+            // Interfaces: cluster traffic never uses loopback.
+            "-i" | "-o" => {
+                if val == "lo" && !probe.src.or(probe.dst).is_some_and(|ip| ip.is_loopback()) {
+                    return Some(false);
+                }
+                i += 2;
+            }
+            // Rate limits and the like don't decide reachability.
+            "--limit" | "--limit-burst" | "--sport" | "--sports" => i += 2,
+            _ => return None,
+        }
+    }
+    Some(true)
+}
+
+/// What `chain` does with `probe`, given `iptables -S` output for every
+/// chain in the table.
+pub fn firewall_verdict(rules: &str, chain: &str, probe: &Probe) -> Verdict {
+    let mut uncertain = Vec::new();
+    if rules.trim().is_empty() {
+        return Verdict { decision: Decision::Unknown, rule: None, uncertain };
+    }
+    let decided = walk_chain(rules, chain, probe, &mut uncertain, 0);
+    let (decision, rule) = decided.unwrap_or_else(|| {
+        // Built-in chain ran out of rules: its policy decides.
+        let policy = rules.lines().find(|l| l.starts_with(&format!("-P {} ", chain)));
+        match policy.and_then(|l| l.split_whitespace().nth(2)) {
+            Some("DROP") => (Decision::Drop, policy.map(String::from)),
+            _ => (Decision::Accept, policy.map(String::from)),
+        }
+    });
+    Verdict { decision, rule, uncertain }
+}
+
+/// First terminal decision in `chain`, following jumps. `None` = fell
+/// off the end (or hit RETURN).
+fn walk_chain(rules: &str, chain: &str, probe: &Probe, uncertain: &mut Vec<String>, depth: u32) -> Option<(Decision, Option<String>)> {
+    if depth > 16 {
+        return None;
+    }
+    let prefix = format!("-A {} ", chain);
+    for line in rules.lines().filter(|l| l.starts_with(&prefix)) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let target = tokens.iter().position(|t| *t == "-j" || *t == "-g")
+            .and_then(|i| tokens.get(i + 1).copied());
+        let Some(target) = target else { continue };
+        match rule_matches(&tokens, probe) {
+            Some(false) => continue,
+            None => {
+                if matches!(target, "DROP" | "REJECT") {
+                    uncertain.push(line.to_string());
+                }
+                continue;
+            }
+            Some(true) => {}
+        }
+        match target {
+            "ACCEPT" => return Some((Decision::Accept, Some(line.to_string()))),
+            "DROP" => return Some((Decision::Drop, Some(line.to_string()))),
+            "REJECT" => return Some((Decision::Reject, Some(line.to_string()))),
+            "RETURN" => return None,
+            "LOG" | "NFLOG" | "MARK" | "CONNMARK" => continue,
+            user if rules.lines().any(|l| l == format!("-N {}", user)) => {
+                if let Some(d) = walk_chain(rules, user, probe, uncertain, depth + 1) {
+                    return Some(d);
+                }
+            }
+            _ => continue,
+        }
+    }
+    None
+}
+
+/// `iptables -S` (or `ip6tables -S`) for the filter table; empty if
+/// unavailable.
+pub(crate) fn filter_rules(v6: bool) -> String {
+    let cmd = if v6 { "ip6tables" } else { "iptables" };
+    match std::process::Command::new(cmd).args(["-S"]).output() {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).into_owned(),
+        _ => String::new(),
+    }
+}
+
+// ─── Facts a node reports about itself ───
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LocalFacts {
+    pub hostname: String,
+    /// Whether `port` has a TCP listener.
+    pub listening: Option<bool>,
+    /// Addresses the listener is bound to (`127.0.0.1:8553` can't be reached from outside).
+    pub listen_addresses: Vec<String>,
+    /// What the INPUT chain does with a new TCP connection from the peer to `port`.
+    pub inbound: Option<Verdict>,
+    pub wolfnet_ip: Option<String>,
+    pub wolfnet_listen_port: Option<u16>,
+    /// What the INPUT chain does with WolfNet's UDP from the peer.
+    pub wolfnet_inbound: Option<Verdict>,
+}
+
+/// TCP listeners on `port`: their local addresses.
+fn listeners(port: u16) -> Option<Vec<String>> {
+    let out = std::process::Command::new("ss")
+        .args(["-H", "-ltn", &format!("sport = :{}", port)])
+        .output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).lines()
+        .filter_map(|l| l.split_whitespace().nth(3).map(String::from))
+        .collect())
+}
+
+/// This node's side of a reachability check from `peer`. Blocking.
+pub fn local_facts(peer: Option<IpAddr>, port: Option<u16>) -> LocalFacts {
+    let mut f = LocalFacts {
+        hostname: hostname::get().map(|h| h.to_string_lossy().into_owned()).unwrap_or_default(),
+        ..Default::default()
+    };
+    if let Some(port) = port {
+        let addrs = listeners(port);
+        f.listening = addrs.as_ref().map(|a| !a.is_empty());
+        f.listen_addresses = addrs.unwrap_or_default();
+    }
+    let rules = filter_rules(peer.is_some_and(|p| p.to_canonical().is_ipv6()));
+    if port.is_some() {
+        f.inbound = Some(firewall_verdict(&rules, "INPUT", &Probe { src: peer, dst: None, proto: "tcp", dport: port }));
+    }
+    if let Some(info) = super::get_wolfnet_local_info() {
+        f.wolfnet_ip = info["address"].as_str().map(String::from).filter(|s| !s.is_empty());
+        f.wolfnet_listen_port = info["listen_port"].as_u64().and_then(|p| u16::try_from(p).ok());
+        if let Some(wport) = f.wolfnet_listen_port {
+            f.wolfnet_inbound = Some(firewall_verdict(&rules, "INPUT",
+                &Probe { src: peer, dst: None, proto: "udp", dport: Some(wport) }));
+        }
+    }
+    f
+}
+
+// ─── "Why can't A reach B" ───
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Finding {
+    pub check: String,
+    pub status: Status,
+    pub detail: String,
+}
+
+/// The node being reached.
+#[derive(Clone, Debug)]
+pub struct ReachTarget {
+    pub hostname: String,
+    pub address: String,
+    pub port: u16,
+}
+
+/// Everything [`analyse`] works from; gathered by [`reach`].
+#[derive(Clone, Debug)]
+pub struct ReachFacts {
+    pub resolved: Result<Vec<IpAddr>, String>,
+    pub ping: Option<Result<PingResult, String>>,
+    pub port: Option<PortCheck>,
+    pub outbound: Option<Verdict>,
+    /// What the target says about itself, or why it couldn't be asked.
+    pub remote: Result<LocalFacts, String>,
+    pub wolfnet_local_ip: Option<String>,
+    /// The target's WolfNet IP is one of our configured peers.
+    pub wolfnet_peer_configured: bool,
+    pub wolfnet_ping: Option<Result<PingResult, String>>,
+}
+
+impl ReachFacts {
+    fn new(remote: Result<LocalFacts, String>) -> Self {
+        ReachFacts {
+            resolved: Ok(Vec::new()),
+            ping: None,
+            port: None,
+            outbound: None,
+            remote,
+            wolfnet_local_ip: None,
+            wolfnet_peer_configured: false,
+            wolfnet_ping: None,
+        }
+    }
+}
+
+fn finding(check: &str, status: Status, detail: impl Into<String>) -> Finding {
+    Finding { check: check.to_string(), status, detail: detail.into() }
+}
+
+fn verdict_text(v: &Verdict) -> String {
+    let decision = match v.decision {
+        Decision::Accept => "accepted",
+        Decision::Drop => "dropped",
+        Decision::Reject => "rejected",
+        Decision::Unknown => "unknown",
+    };
+    match &v.rule {
+        Some(rule) => format!("{} by `{}`", decision, rule),
+        None => decision.to_string(),
+    }
+}
+
+/// Turn the gathered facts into findings, most fundamental first.
+pub fn analyse(target: &ReachTarget, facts: &ReachFacts) -> Vec<Finding> {
+    let mut out = Vec::new();
+    let port = target.port;
+
+    match &facts.resolved {
+        Ok(addrs) => out.push(finding("dns", Status::Ok, format!("{} → {}", target.address,
+            addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")))),
+        Err(e) => {
+            out.push(finding("dns", Status::Fail, format!("{} — fix the node's address or this node's DNS", e)));
+            return out;
+        }
+    }
+
+    match &facts.ping {
+        Some(Ok(p)) if p.reachable() => out.push(finding("ping", Status::Ok, format!(
+            "{}/{} replies, avg {:.1} ms", p.received, p.transmitted, p.rtt_avg_ms.unwrap_or(0.0)))),
+        // Plenty of hosts drop ICMP; only a hint on its own.
+        Some(Ok(_)) => out.push(finding("ping", Status::Warn, "no ICMP replies — host down, or ICMP filtered")),
+        Some(Err(e)) => out.push(finding("ping", Status::Warn, e.clone())),
+        None => out.push(finding("ping", Status::Skipped, "not run")),
+    }
+
+    if let Some(v) = &facts.outbound {
+        match v.decision {
+            Decision::Drop | Decision::Reject => out.push(finding("local firewall", Status::Fail, format!(
+                "this node's OUTPUT chain blocks TCP to port {}: {}", port, verdict_text(v)))),
+            Decision::Accept => out.push(finding("local firewall", Status::Ok, format!("OUTPUT allows TCP to port {}", port))),
+            Decision::Unknown => out.push(finding("local firewall", Status::Skipped, "iptables rules not readable")),
+        }
+        if !v.uncertain.is_empty() {
+            out.push(finding("local firewall", Status::Warn, format!(
+                "rules that may also apply but couldn't be evaluated: {}", v.uncertain.join(" | "))));
+        }
+    }
+
+    let port_open = facts.port.as_ref().is_some_and(|p| p.open);
+    match &facts.port {
+        Some(p) if p.open => out.push(finding("api port", Status::Ok, format!(
+            "TCP {} open ({} ms)", p.address.as_deref().unwrap_or(""), p.latency_ms.unwrap_or(0)))),
+        Some(p) => out.push(finding("api port", Status::Fail, format!(
+            "TCP {}: {}", p.address.as_deref().unwrap_or(&target.address), p.error.as_deref().unwrap_or("closed")))),
+        None => {}
+    }
+
+    match &facts.remote {
+        Ok(r) => {
+            match (r.listening, r.listen_addresses.as_slice()) {
+                (Some(false), _) => out.push(finding("remote listener", Status::Fail, format!(
+                    "nothing is listening on port {} on {} — is WolfStack running there, and on this port?", port, r.hostname))),
+                (Some(true), addrs) if !addrs.is_empty() && addrs.iter().all(|a| a.starts_with("127.") || a.starts_with("[::1]")) =>
+                    out.push(finding("remote listener", Status::Fail, format!(
+                        "port {} is bound to loopback only ({}) — set server.bind in wolfstack.toml", port, addrs.join(", ")))),
+                (Some(true), addrs) => out.push(finding("remote listener", Status::Ok, format!("listening on {}", addrs.join(", ")))),
+                (None, _) => out.push(finding("remote listener", Status::Skipped, "`ss` not available on the target")),
+            }
+            if let Some(v) = &r.inbound {
+                match v.decision {
+                    Decision::Drop | Decision::Reject => out.push(finding("remote firewall", Status::Fail, format!(
+                        "{}'s INPUT chain blocks TCP {} from this node: {}", r.hostname, port, verdict_text(v)))),
+                    Decision::Accept => out.push(finding("remote firewall", Status::Ok, format!("INPUT allows TCP {} from this node", port))),
+                    Decision::Unknown => out.push(finding("remote firewall", Status::Skipped, "iptables rules not readable on the target")),
+                }
+                if !v.uncertain.is_empty() {
+                    out.push(finding("remote firewall", Status::Warn, format!(
+                        "rules that may also apply but couldn't be evaluated: {}", v.uncertain.join(" | "))));
+                }
+            }
+        }
+        // Expected when the API port is the problem; the checks above say why.
+        Err(e) => out.push(finding("remote checks", if port_open { Status::Warn } else { Status::Skipped },
+            format!("couldn't ask the target about itself: {}", e))),
+    }
+
+    let remote_wolfnet_ip = facts.remote.as_ref().ok().and_then(|r| r.wolfnet_ip.clone());
+    match (&facts.wolfnet_local_ip, &remote_wolfnet_ip) {
+        (None, _) => out.push(finding("wolfnet", Status::Skipped, "WolfNet is not running on this node")),
+        (Some(_), _) if !facts.wolfnet_peer_configured => out.push(finding("wolfnet", Status::Warn, format!(
+            "{} is not a WolfNet peer of this node — run a WolfNet sync from the cluster page", target.hostname))),
+        (Some(_), remote_ip) => {
+            match &facts.wolfnet_ping {
+                Some(Ok(p)) if p.reachable() => out.push(finding("wolfnet", Status::Ok, format!(
+                    "{} answers over WolfNet, avg {:.1} ms", p.host, p.rtt_avg_ms.unwrap_or(0.0)))),
+                Some(Ok(p)) => {
+                    let blocked = facts.remote.as_ref().ok()
+                        .and_then(|r| r.wolfnet_inbound.as_ref())
+                        .filter(|v| matches!(v.decision, Decision::Drop | Decision::Reject));
+                    let why = match (blocked, remote_ip) {
+                        (Some(v), _) => format!("the target's INPUT chain blocks WolfNet's UDP: {}", verdict_text(v)),
+                        (None, None) => "WolfNet may not be running on the target".to_string(),
+                        (None, Some(_)) => "peer configured but the tunnel isn't passing traffic — check the peer's endpoint and UDP reachability".to_string(),
+                    };
+                    out.push(finding("wolfnet", Status::Fail, format!("no replies from {} over WolfNet: {}", p.host, why)));
+                }
+                Some(Err(e)) => out.push(finding("wolfnet", Status::Warn, e.clone())),
+                None => out.push(finding("wolfnet", Status::Skipped, "target's WolfNet address unknown")),
+            }
+        }
+    }
+    out
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ReachReport {
+    pub target: String,
+    pub address: String,
+    pub port: u16,
+    /// `true` if nothing failed.
+    pub ok: bool,
+    /// One line: the first failure, or that all is well.
+    pub summary: String,
+    pub findings: Vec<Finding>,
+}
+
+/// Gather facts from this node towards `target` and analyse them.
+/// `remote` is what the target reported about itself.
+pub async fn reach(target: &ReachTarget, remote: Result<LocalFacts, String>) -> ReachReport {
+    let mut facts = ReachFacts::new(remote);
+    let host = HostName::parse(&target.address);
+    facts.resolved = match &host {
+        Ok(h) => resolve(h).await,
+        Err(e) => Err(e.clone()),
+    };
+    if let (Ok(host), Ok(addrs)) = (&host, &facts.resolved) {
+        let dst = addrs.first().copied();
+        let (ping_result, port_result) = tokio::join!(
+            ping(host, 3),
+            check_port(host, target.port, Duration::from_secs(5)),
+        );
+        facts.ping = Some(ping_result);
+        facts.port = Some(port_result);
+        let v6 = dst.is_some_and(|d| d.to_canonical().is_ipv6());
+        let port = target.port;
+        facts.outbound = tokio::task::spawn_blocking(move || {
+            firewall_verdict(&filter_rules(v6), "OUTPUT", &Probe { src: None, dst, proto: "tcp", dport: Some(port) })
+        }).await.ok();
+    }
+
+    let local = tokio::task::spawn_blocking(|| (super::get_wolfnet_local_info(), super::get_wolfnet_peers_list()))
+        .await.unwrap_or((None, Vec::new()));
+    facts.wolfnet_local_ip = local.0.as_ref()
+        .and_then(|i| i["address"].as_str()).filter(|s| !s.is_empty()).map(String::from);
+    let peer_ip = |p: &super::WolfNetPeer| p.ip.split('/').next().unwrap_or(&p.ip).to_string();
+    // The target's own word for its WolfNet IP, else a peer named after it.
+    let target_wolfnet_ip = facts.remote.as_ref().ok().and_then(|r| r.wolfnet_ip.clone())
+        .or_else(|| local.1.iter().find(|p| p.name.eq_ignore_ascii_case(&target.hostname)).map(peer_ip));
+    if let Some(ip) = &target_wolfnet_ip {
+        facts.wolfnet_peer_configured = local.1.iter().any(|p| peer_ip(p) == *ip);
+        if facts.wolfnet_local_ip.is_some() && facts.wolfnet_peer_configured
+            && let Ok(h) = HostName::parse(ip)
+        {
+            facts.wolfnet_ping = Some(ping(&h, 3).await);
+        }
+    }
+
+    let findings = analyse(target, &facts);
+    let first_fail = findings.iter().find(|f| f.status == Status::Fail);
+    ReachReport {
+        target: target.hostname.clone(),
+        address: target.address.clone(),
+        port: target.port,
+        ok: first_fail.is_none(),
+        summary: match first_fail {
+            Some(f) => format!("{}: {}", f.check, f.detail),
+            None => format!("{} is reachable on port {}", target.hostname, target.port),
+        },
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ping_summary_is_parsed() {
+        let out = "PING 10.0.0.2 (10.0.0.2) 56(84) bytes of data.\n\
+            64 bytes from 10.0.0.2: icmp_seq=1 ttl=64 time=0.412 ms\n\n\
+            --- 10.0.0.2 ping statistics ---\n\
+            3 packets transmitted, 2 received, 33.3333% packet loss, time 2003ms\n\
+            rtt min/avg/max/mdev = 0.401/0.456/0.512/0.045 ms\n";
+        let p = parse_ping("10.0.0.2", out);
+        assert_eq!((p.transmitted, p.received), (3, 2));
+        assert!((p.loss_percent - 33.3333).abs() < 1e-3);
+        assert_eq!((p.rtt_min_ms, p.rtt_avg_ms, p.rtt_max_ms), (Some(0.401), Some(0.456), Some(0.512)));
+        // Busybox.
+        let bb = parse_ping("h", "2 packets transmitted, 0 packets received, 100% packet loss\n");
+        assert!(!bb.reachable() && bb.loss_percent == 100.0);
+    }
+
+    #[test]
+    fn traceroute_and_mtr_hops_are_parsed() {
+        let hops = parse_traceroute("traceroute to 1.1.1.1 (1.1.1.1), 30 hops max, 60 byte packets\n 1  192.168.1.1  0.512 ms\n 2  *\n 3  1.1.1.1  9.8 ms\n");
+        assert_eq!(hops.len(), 3);
+        assert_eq!(hops[1], TraceHop { hop: 2, address: None, rtt_ms: None });
+        assert_eq!(hops[2].address.as_deref(), Some("1.1.1.1"));
+
+        let json = r#"{"report":{"mtr":{"dst":"1.1.1.1"},"hubs":[
+            {"count":1,"host":"192.168.1.1","Loss%":0.0,"Snt":10,"Last":0.5,"Avg":0.6,"Best":0.4,"Wrst":0.9,"StDev":0.1},
+            {"count":2,"host":"???","Loss%":100.0,"Snt":10,"Last":0.0,"Avg":0.0,"Best":0.0,"Wrst":0.0,"StDev":0.0}]}}"#;
+        let hops = parse_mtr(json).unwrap();
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[1].loss_percent, 100.0);
+        assert!(parse_mtr("not json").is_err());
+    }
+
+    const RULES: &str = "-P INPUT DROP\n-P FORWARD ACCEPT\n-P OUTPUT ACCEPT\n-N ufw-user-input\n\
+        -A INPUT -i lo -j ACCEPT\n\
+        -A INPUT -m conntrack --ctstate RELATED,ESTABLISHED -j ACCEPT\n\
+        -A INPUT -s 203.0.113.0/24 -j DROP\n\
+        -A INPUT -j ufw-user-input\n\
+        -A ufw-user-input -p tcp -m tcp --dport 22 -j ACCEPT\n\
+        -A ufw-user-input -s 10.0.0.0/8 -p tcp -m multiport --dports 8553:8554 -j ACCEPT\n\
+        -A ufw-user-input -m set --match-set blocklist src -j DROP\n";
+
+    fn tcp_from(src: &str, port: u16) -> Probe {
+        Probe { src: src.parse().ok(), dst: None, proto: "tcp", dport: Some(port) }
+    }
+
+    #[test]
+    fn firewall_walk_follows_jumps_and_falls_back_to_policy() {
+        let v = firewall_verdict(RULES, "INPUT", &tcp_from("10.1.2.3", 8553));
+        assert_eq!(v.decision, Decision::Accept);
+        assert!(v.rule.unwrap().contains("--dports 8553:8554"));
+
+        let v = firewall_verdict(RULES, "INPUT", &tcp_from("203.0.113.9", 8553));
+        assert_eq!(v.decision, Decision::Drop);
+        assert_eq!(v.rule.as_deref(), Some("-A INPUT -s 203.0.113.0/24 -j DROP"));
+
+        // Not in 10/8: no ACCEPT, the ipset rule is uncertain, policy drops.
+        let v = firewall_verdict(RULES, "INPUT", &tcp_from("192.168.5.5", 8553));
+        assert_eq!(v.decision, Decision::Drop);
+        assert_eq!(v.rule.as_deref(), Some("-P INPUT DROP"));
+        assert_eq!(v.uncertain.len(), 1);
+
+        assert_eq!(firewall_verdict("", "INPUT", &tcp_from("10.1.2.3", 1)).decision, Decision::Unknown);
+        assert_eq!(firewall_verdict(RULES, "OUTPUT", &tcp_from("10.1.2.3", 1)).decision, Decision::Accept);
+    }
+
+    fn target() -> ReachTarget {
+        ReachTarget { hostname: "node-b".into(), address: "10.0.0.2".into(), port: 8553 }
+    }
+
+    #[test]
+    fn analysis_names_the_blocking_layer() {
+        let ip: IpAddr = "10.0.0.2".parse().unwrap();
+        let mut facts = ReachFacts::new(Ok(LocalFacts { hostname: "node-b".into(), listening: Some(false), ..Default::default() }));
+        facts.resolved = Ok(vec![ip]);
+        facts.ping = Some(Ok(PingResult { host: "10.0.0.2".into(), transmitted: 3, received: 3, ..Default::default() }));
+        facts.port = Some(PortCheck { host: "10.0.0.2".into(), port: 8553, error: Some("refused".into()), ..Default::default() });
+        let f = analyse(&target(), &facts);
+        let fails: Vec<&str> = f.iter().filter(|f| f.status == Status::Fail).map(|f| f.check.as_str()).collect();
+        assert_eq!(fails, vec!["api port", "remote listener"]);
+
+        // Listening, but only on loopback.
+        facts.remote = Ok(LocalFacts { hostname: "node-b".into(), listening: Some(true),
+            listen_addresses: vec!["127.0.0.1:8553".into()], ..Default::default() });
+        assert!(analyse(&target(), &facts).iter().any(|f| f.check == "remote listener" && f.detail.contains("loopback")));
+
+        // Unresolvable: nothing past DNS is reported.
+        facts.resolved = Err("cannot resolve node-b".into());
+        let f = analyse(&target(), &facts);
+        assert_eq!(f.len(), 1);
+        assert_eq!(f[0].status, Status::Fail);
+    }
+}
@@ -15,10 +15,14 @@ use serde::{Deserialize, Serialize};
 use std::process::Command;
 use tracing::{info, warn};
 
+pub mod capture;
+pub mod connections;
+pub mod diagnostics;
 pub mod lan_bridge;
 pub mod router;
 pub mod vlan;
 pub mod vlan_attach;
+pub mod wolfnet_health;
 
 /// Network interface info
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1458,6 +1462,24 @@ pub fn edit_wolfnet_peer(
         if cleared_endpoint { "restarted" } else { "reloaded" }))
 }
 
+/// Point peer `name`'s endpoint at `new_host` when it currently dials
+/// `old_host` — the WolfNet half of a node re-address. The WolfNet port is
+/// kept. Ok(false) when there is nothing to change: no WolfNet here, no
+/// such peer, or an endpoint that goes somewhere else (relay, public name).
+pub fn readdress_wolfnet_peer(name: &str, old_host: &str, new_host: &str) -> Result<bool, String> {
+    if !std::path::Path::new("/etc/wolfnet/config.toml").exists() {
+        return Ok(false);
+    }
+    let Some(peer) = get_wolfnet_peers().into_iter().find(|p| p.name == name) else {
+        return Ok(false);
+    };
+    let Some(endpoint) = crate::agent::readdress::replace_endpoint_host(&peer.endpoint, old_host, new_host) else {
+        return Ok(false);
+    };
+    edit_wolfnet_peer(name, name, "", PeerEndpoint::Set(endpoint), None)?;
+    Ok(true)
+}
+
 /// Auto-fix a single peer's endpoint in the local wolfnet config if it
 /// matches a known-bad pattern that can't be reached from this node.
 /// Returns `Some(msg)` when a fix was applied, `None` when nothing
@@ -2472,6 +2494,29 @@ pub fn add_ip_mapping(
     protocol: &str,
     label: &str,
 ) -> Result<IpMapping, String> {
+    add_ip_mapping_with_id(None, public_ip, wolfnet_ip, ports, dest_ports, protocol, label)
+}
+
+/// `add_ip_mapping` with a caller-chosen id — the `/api/v1` machine API lets
+/// a client name the mapping it creates so it can find it again. `None`
+/// generates one. A chosen id must be unused and `[A-Za-z0-9_-]`, ≤ 64 chars.
+pub fn add_ip_mapping_with_id(
+    id: Option<&str>,
+    public_ip: &str,
+    wolfnet_ip: &str,
+    ports: Option<&str>,
+    dest_ports: Option<&str>,
+    protocol: &str,
+    label: &str,
+) -> Result<IpMapping, String> {
+    if let Some(id) = id {
+        if id.is_empty() || id.len() > 64 || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid mapping id '{}'", id));
+        }
+        if load_ip_mapping_config().mappings.iter().any(|m| m.id == id) {
+            return Err(format!("Mapping '{}' already exists", id));
+        }
+    }
     // Validate IPs
     if public_ip.parse::<std::net::Ipv4Addr>().is_err() {
         return Err(format!("Invalid public IP: {}", public_ip));
@@ -2579,7 +2624,7 @@ pub fn add_ip_mapping(
     }
 
     let mapping = IpMapping {
-        id: format!("{:x}", rand_id()),
+        id: id.map(str::to_string).unwrap_or_else(|| format!("{:x}", rand_id())),
         public_ip: public_ip.to_string(),
         wolfnet_ip: wolfnet_ip.to_string(),
         ports: ports.map(|s| s.to_string()),
@@ -4681,7 +4726,7 @@ fn detect_server_endpoint(port: u16) -> String {
 
 /// Run a command, return Ok(()) on success or Err with stderr
 fn run_cmd(cmd: &str, args: &[&str]) -> Result<(), String> {
-    let output = Command::new(cmd).args(args).output()
+    let output = crate::logging::run_traced(Command::new(cmd).args(args))
         .map_err(|e| format!("{} failed: {}", cmd, e))?;
     if output.status.success() {
         Ok(())
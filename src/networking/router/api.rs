@@ -4211,7 +4211,7 @@ pub async fn list_proxy_backends(req: HttpRequest, state: S, query: web::Query<T
     // VMs owned by a Proxmox VE cluster member (they carry a vmid).
     let mut vms_libvirt = Vec::new();
     let mut vms_proxmox = Vec::new();
-    for v in state.vms.lock().unwrap().list_vms() {
+    for v in crate::monitoring::inventory::vms(&state).await {
         // Skip VMs whose hosting node isn't in the requested cluster.
         if let Some(set) = &cluster_set {
             match &v.host_id {
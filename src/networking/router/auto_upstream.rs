@@ -0,0 +1,190 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Automatic upstream registration.
+//!
+//! Creating a container can opt it into an existing WolfRouter HTTP proxy
+//! (the sites WolfProxy/nginx serve): the container is added to that
+//! proxy's upstream pool with its own passive health-check settings, and
+//! taken out again when the container is destroyed. Scaling a service is
+//! then just creating and removing containers — the load balancer follows.
+//!
+//! The registration is an ordinary [`Upstream`] whose `source` names the
+//! container. Its URL is resolved the same way Internet Exposure does it
+//! (bridge IP when the container runs on the proxy's node, otherwise node
+//! address + published host port), and the 30s reconcile in main.rs keeps
+//! it current when the container restarts or moves. An upstream with no
+//! URL yet (container still starting) is skipped when rendering.
+
+use serde::{Deserialize, Serialize};
+
+use crate::networking::router::http_proxy::{ExposureSource, HttpProxy, Upstream};
+
+/// The `proxy` part of a container create request.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Registration {
+    /// WolfRouter HTTP proxy to join.
+    pub proxy_id: String,
+    /// Port the container serves HTTP on.
+    pub port: u16,
+    #[serde(default)]
+    pub scheme: String,
+    #[serde(default)]
+    pub weight: u32,
+    #[serde(default)]
+    pub max_fails: u32,
+    #[serde(default)]
+    pub fail_timeout_s: u32,
+}
+
+/// Node the proxy renders on — the one the upstream must be reachable
+/// from. Proxies replicated to several nodes use the first.
+pub fn ingress_node(p: &HttpProxy) -> Option<&str> {
+    p.targets.first().map(|t| t.node_id.as_str())
+}
+
+/// Check a registration before the container is created, so a typo in the
+/// proxy id fails the create instead of leaving an orphan.
+pub fn validate(proxies: &[HttpProxy], reg: &Registration) -> Result<(), String> {
+    if reg.port == 0 {
+        return Err("Pick the port the container serves HTTP on.".into());
+    }
+    let Some(p) = proxies.iter().find(|p| p.id == reg.proxy_id) else {
+        return Err(format!("HTTP proxy '{}' not found", reg.proxy_id));
+    };
+    if p.exposure.is_some() {
+        return Err(format!("'{}' is managed by Internet Exposure and can't take extra upstreams", p.id));
+    }
+    if ingress_node(p).is_none() {
+        return Err(format!("HTTP proxy '{}' has no target node", p.id));
+    }
+    Ok(())
+}
+
+/// Add (or replace) the container's upstream in the proxy. `url` is `None`
+/// when it can't be resolved yet; the reconcile fills it in.
+pub fn register(
+    proxies: &mut [HttpProxy],
+    kind: &str,
+    name: &str,
+    reg: &Registration,
+    url: Option<String>,
+) -> Result<(), String> {
+    validate(proxies, reg)?;
+    deregister(proxies, kind, name);
+    let Some(p) = proxies.iter_mut().find(|p| p.id == reg.proxy_id) else {
+        return Err(format!("HTTP proxy '{}' not found", reg.proxy_id));
+    };
+    p.upstreams.push(Upstream {
+        url: url.unwrap_or_default(),
+        weight: reg.weight.max(1),
+        max_conns: 0,
+        max_fails: reg.max_fails,
+        fail_timeout_s: reg.fail_timeout_s,
+        source: Some(ExposureSource {
+            workload_kind: kind.to_string(),
+            workload_ref: name.to_string(),
+            port: reg.port,
+            scheme: crate::exposure::normalise_scheme(&reg.scheme).to_string(),
+        }),
+    });
+    p.updated_at = chrono::Utc::now().to_rfc3339();
+    Ok(())
+}
+
+/// Remove every upstream registered for this container. Returns the ids of
+/// the proxies that changed.
+pub fn deregister(proxies: &mut [HttpProxy], kind: &str, name: &str) -> Vec<String> {
+    let mut changed = Vec::new();
+    for p in proxies.iter_mut() {
+        let before = p.upstreams.len();
+        p.upstreams.retain(|u| {
+            !u.source.as_ref().is_some_and(|s| s.workload_kind == kind && s.workload_ref == name)
+        });
+        if p.upstreams.len() != before {
+            p.updated_at = chrono::Utc::now().to_rfc3339();
+            changed.push(p.id.clone());
+        }
+    }
+    changed
+}
+
+/// Re-resolve every auto-registered upstream whose container runs on this
+/// node. Containers elsewhere are left to their own node's reconcile, and
+/// one that runs here but can't be reached keeps its last URL — the
+/// health check takes it out of rotation if it's really gone. Returns true
+/// if anything changed so the caller saves, applies and replicates.
+pub fn reconcile(proxies: &mut [HttpProxy], cluster: &crate::agent::ClusterState) -> bool {
+    let mut changed = false;
+    for p in proxies.iter_mut() {
+        let Some(ingress) = ingress_node(p).map(str::to_string) else { continue };
+        for up in p.upstreams.iter_mut() {
+            let Some(src) = &up.source else { continue };
+            let Ok(Some(url)) = crate::exposure::resolve_upstream_local(
+                &src.workload_kind, &src.workload_ref, src.port, &src.scheme, &ingress, cluster,
+            ) else {
+                continue;
+            };
+            if up.url != url {
+                up.url = url;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(id: &str) -> HttpProxy {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "server_names": [format!("{id}.example.com")],
+            "targets": [{ "node_id": "node-a", "runtime": { "kind": "host" } }],
+            "upstreams": [{ "url": "http://10.0.0.5:8080" }],
+        }))
+        .unwrap()
+    }
+
+    fn reg(proxy_id: &str) -> Registration {
+        Registration { proxy_id: proxy_id.into(), port: 80, scheme: String::new(), weight: 0, max_fails: 3, fail_timeout_s: 30 }
+    }
+
+    #[test]
+    fn register_and_deregister() {
+        let mut proxies = vec![proxy("web"), proxy("api")];
+        register(&mut proxies, "docker", "web-1", &reg("web"), Some("http://172.17.0.4:80".into())).unwrap();
+        register(&mut proxies, "docker", "web-2", &reg("web"), None).unwrap();
+        // Registering again replaces rather than duplicates.
+        register(&mut proxies, "docker", "web-1", &reg("web"), Some("http://172.17.0.9:80".into())).unwrap();
+        let urls: Vec<&str> = proxies[0].upstreams.iter().map(|u| u.url.as_str()).collect();
+        assert_eq!(urls, vec!["http://10.0.0.5:8080", "", "http://172.17.0.9:80"]);
+        assert_eq!(proxies[0].upstreams[2].weight, 1);
+        assert_eq!(proxies[0].upstreams[2].max_fails, 3);
+
+        assert_eq!(deregister(&mut proxies, "docker", "web-1"), vec!["web".to_string()]);
+        assert!(deregister(&mut proxies, "lxc", "web-2").is_empty());
+        assert_eq!(proxies[0].upstreams.len(), 2);
+        assert_eq!(proxies[1].upstreams.len(), 1);
+    }
+
+    #[test]
+    fn validate_rejects_unknown_and_exposure_proxies() {
+        let mut exposed = proxy("expose-blog");
+        exposed.exposure = Some(ExposureSource {
+            workload_kind: "docker".into(), workload_ref: "blog".into(), port: 80, scheme: "http".into(),
+        });
+        let mut orphan = proxy("orphan");
+        orphan.targets.clear();
+        let proxies = vec![proxy("web"), exposed, orphan];
+        assert!(validate(&proxies, &reg("web")).is_ok());
+        assert!(validate(&proxies, &reg("nope")).is_err());
+        assert!(validate(&proxies, &reg("expose-blog")).is_err());
+        assert!(validate(&proxies, &reg("orphan")).is_err());
+        assert!(validate(&proxies, &Registration { port: 0, ..reg("web") }).is_err());
+        assert_eq!(ingress_node(&proxies[0]), Some("node-a"));
+    }
+}
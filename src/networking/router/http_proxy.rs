@@ -152,6 +152,25 @@ pub struct Upstream {
     pub weight: u32,
     #[serde(default)]
     pub max_conns: u32,
+    /// Passive health check: after `max_fails` failed attempts within
+    /// `fail_timeout_s` the server is taken out of rotation for
+    /// `fail_timeout_s`. 0 leaves the proxy's defaults (1 try, 10s).
+    #[serde(default)]
+    pub max_fails: u32,
+    #[serde(default)]
+    pub fail_timeout_s: u32,
+    /// Set when the upstream was registered automatically for a container
+    /// (see [`super::auto_upstream`]) — the reconcile keeps its URL current
+    /// and destroying the container removes it. `None` for upstreams the
+    /// operator typed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ExposureSource>,
+}
+
+impl Upstream {
+    pub fn has_health_check(&self) -> bool {
+        self.max_fails > 0 || self.fail_timeout_s > 0
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
@@ -244,26 +263,11 @@ pub fn validate_id(id: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Container names follow Docker/LXC's own restrictions (alnum + a
-/// few separators). We don't enforce the full grammar; we just block
-/// the obvious shell-injection chars so a malicious config file
-/// can't `docker exec '; rm -rf /'`.
+/// Container names follow Docker/LXC's own grammar (see
+/// [`ContainerName`](crate::validate::ContainerName)), so a malicious
+/// config file can't `docker exec '; rm -rf /'`.
 pub fn validate_container_name(name: &str) -> Result<(), String> {
-    if name.is_empty() {
-        return Err("container name is required".into());
-    }
-    if name.len() > 128 {
-        return Err("container name too long".into());
-    }
-    for c in name.chars() {
-        if !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-') {
-            return Err(format!(
-                "container name '{}' contains '{}' — only ASCII alnum and '.', '_', '-' allowed",
-                name, c
-            ));
-        }
-    }
-    Ok(())
+    crate::validate::ContainerName::parse(name).map(|_| ())
 }
 
 // ─── Render pipeline ───────────────────────────────────────────────────
@@ -477,7 +481,12 @@ pub fn render(p: &HttpProxy) -> Result<String, String> {
         "wolfrouter_http_{}",
         p.id.replace('.', "_").replace('-', "_")
     );
-    let multi_backend = p.upstreams.len() > 1;
+    // An auto-registered upstream has no URL until its container has an
+    // address — leave it out rather than render `server ;`.
+    let upstreams: Vec<&Upstream> = p.upstreams.iter().filter(|u| !u.url.is_empty()).collect();
+    // Health-check parameters only exist on `server` lines, so a single
+    // backend that has them still gets an upstream{} block.
+    let multi_backend = upstreams.len() > 1 || upstreams.iter().any(|u| u.has_health_check());
 
     if multi_backend {
         writeln!(out, "upstream {} {{", upstream_name).ok();
@@ -486,12 +495,14 @@ pub fn render(p: &HttpProxy) -> Result<String, String> {
             LoadBalance::LeastConn => { writeln!(out, "    least_conn;").ok(); }
             LoadBalance::IpHash    => { writeln!(out, "    ip_hash;").ok(); }
         }
-        for up in &p.upstreams {
+        for up in &upstreams {
             let target = strip_scheme(&up.url);
             let mut extras = String::new();
             if up.weight > 1 { extras.push_str(&format!(" weight={}", up.weight)); }
             if up.max_conns > 0 { extras.push_str(&format!(" max_conns={}", up.max_conns)); }
-            writeln!(out, "    server {};{}", target, extras).ok();
+            if up.max_fails > 0 { extras.push_str(&format!(" max_fails={}", up.max_fails)); }
+            if up.fail_timeout_s > 0 { extras.push_str(&format!(" fail_timeout={}s", up.fail_timeout_s)); }
+            writeln!(out, "    server {}{};", target, extras).ok();
         }
         writeln!(out, "}}").ok();
         writeln!(out).ok();
@@ -591,7 +602,7 @@ pub fn render(p: &HttpProxy) -> Result<String, String> {
     }
 
     writeln!(out, "    location / {{").ok();
-    if p.upstreams.is_empty() {
+    if upstreams.is_empty() {
         writeln!(out, "        return 502 \"WolfRouter: no upstream configured for '{}'\";", p.id).ok();
         writeln!(out, "    }}").ok();
         writeln!(out, "}}").ok();
@@ -601,10 +612,10 @@ pub fn render(p: &HttpProxy) -> Result<String, String> {
         // Follow the first backend's scheme for the upstream{} block —
         // mixed-scheme backends are unusual and unsupported, but
         // matching first-backend is predictable.
-        let scheme = if p.upstreams[0].url.starts_with("https://") { "https" } else { "http" };
+        let scheme = if upstreams[0].url.starts_with("https://") { "https" } else { "http" };
         format!("{}://{}", scheme, upstream_name)
     } else {
-        p.upstreams[0].url.clone()
+        upstreams[0].url.clone()
     };
     writeln!(out, "        proxy_pass {};", pass_target).ok();
     writeln!(out, "        proxy_http_version 1.1;").ok();
@@ -660,7 +671,7 @@ mod tests {
             listen_ports: vec![],
             targets: vec![ProxyTarget { node_id: node_id.into(), runtime: TargetRuntime::Host }],
             edge: EdgeStrategy::Local,
-            upstreams: vec![Upstream { url: "http://10.0.0.5:8080".into(), weight: 1, max_conns: 0 }],
+            upstreams: vec![Upstream { url: "http://10.0.0.5:8080".into(), weight: 1, max_conns: 0, max_fails: 0, fail_timeout_s: 0, source: None }],
             lb_strategy: LoadBalance::default(),
             tls: None,
             force_https: false,
@@ -752,14 +763,28 @@ mod tests {
     fn render_multi_https_backends_uses_https_proxy_pass() {
         let mut p = simple("api", "node-a");
         p.upstreams = vec![
-            Upstream { url: "https://10.0.0.5:8443".into(), weight: 1, max_conns: 0 },
-            Upstream { url: "https://10.0.0.6:8443".into(), weight: 1, max_conns: 0 },
+            Upstream { url: "https://10.0.0.5:8443".into(), weight: 1, max_conns: 0, max_fails: 0, fail_timeout_s: 0, source: None },
+            Upstream { url: "https://10.0.0.6:8443".into(), weight: 1, max_conns: 0, max_fails: 0, fail_timeout_s: 0, source: None },
         ];
         let out = render(&p).unwrap();
         assert!(out.contains("proxy_pass https://wolfrouter_http_api"));
         assert!(out.contains("server 10.0.0.5:8443;"));
     }
 
+    #[test]
+    fn render_health_check_params_on_server_lines() {
+        let mut p = simple("pool", "node-a");
+        p.upstreams[0].weight = 2;
+        p.upstreams[0].max_fails = 3;
+        p.upstreams[0].fail_timeout_s = 30;
+        // Auto-registered, no address yet — left out.
+        p.upstreams.push(Upstream { url: String::new(), weight: 1, max_conns: 0, max_fails: 3, fail_timeout_s: 30, source: None });
+        let out = render(&p).unwrap();
+        assert!(out.contains("server 10.0.0.5:8080 weight=2 max_fails=3 fail_timeout=30s;"));
+        assert!(out.contains("proxy_pass http://wolfrouter_http_pool;"));
+        assert_eq!(out.matches("    server ").count(), 1);
+    }
+
     #[test]
     fn render_websocket_adds_upgrade_headers() {
         let mut p = simple("ws", "node-a");
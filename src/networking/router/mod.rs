@@ -33,6 +33,7 @@ pub mod host_dns;
 pub mod proxy;
 pub mod proxy_runtime;
 pub mod http_proxy;
+pub mod auto_upstream;
 pub mod health;
 
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,331 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! WolfNet tunnel telemetry.
+//!
+//! Tunnels that die quietly are the commonest cluster fault: the peer
+//! drops out of the overlay, the cluster API still looks fine, and
+//! nothing says so until a cross-node service breaks. A background loop
+//! in main.rs calls [`sample`] every minute. Each call reads the daemon's
+//! live peer table (`/var/run/wolfnet/status.json`) and records, per
+//! peer:
+//!
+//! - the handshake age;
+//! - rx/tx byte counters;
+//! - the endpoint.
+//!
+//! It also logs events: connects and disconnects, endpoint roaming,
+//! relay changes, and peers going silent or coming back. A peer that was
+//! up and then goes `wolfnet_stale_minutes` without a handshake raises an
+//! alert, and another when it recovers.
+//!
+//! History is in memory. A restart starts it afresh. The events also go
+//! to the journal.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use tracing::{info, warn};
+
+const STATUS_PATH: &str = "/var/run/wolfnet/status.json";
+/// Samples kept per peer — a day at one a minute.
+const MAX_SAMPLES: usize = 24 * 60;
+const MAX_EVENTS: usize = 500;
+/// Peers gone from the daemon's table this long are forgotten.
+const FORGET_AFTER_SECS: i64 = 24 * 60 * 60;
+
+/// One row of the daemon's live peer table.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct LivePeer {
+    #[serde(default)]
+    address: String,
+    #[serde(default)]
+    hostname: String,
+    #[serde(default)]
+    endpoint: String,
+    #[serde(default)]
+    connected: bool,
+    /// Seconds since the last handshake/packet, as of the file's write.
+    #[serde(default)]
+    last_seen_secs: Option<u64>,
+    #[serde(default)]
+    rx_bytes: u64,
+    #[serde(default)]
+    tx_bytes: u64,
+    #[serde(default)]
+    relay_via: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerSample {
+    /// Unix seconds.
+    pub t: i64,
+    pub connected: bool,
+    /// None until the peer's first handshake.
+    pub handshake_age_secs: Option<u64>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TunnelEvent {
+    /// RFC 3339.
+    pub timestamp: String,
+    pub address: String,
+    pub hostname: String,
+    /// "connected", "disconnected", "endpoint_changed", "relay_changed",
+    /// "stale" or "recovered".
+    pub kind: String,
+    pub detail: String,
+}
+
+#[derive(Default)]
+struct PeerTrack {
+    hostname: String,
+    endpoint: String,
+    relay_via: Option<String>,
+    connected: bool,
+    /// Has handshaken within the threshold since we started watching.
+    seen_healthy: bool,
+    stale: bool,
+    endpoint_changes: u32,
+    last_present: i64,
+    samples: VecDeque<PeerSample>,
+}
+
+#[derive(Default)]
+struct Telemetry {
+    peers: HashMap<String, PeerTrack>,
+    events: VecDeque<TunnelEvent>,
+}
+
+static TELEMETRY: LazyLock<Mutex<Telemetry>> = LazyLock::new(Default::default);
+
+/// An alert the sampler wants sent; the caller owns the async dispatch.
+#[derive(Debug, Clone)]
+pub struct TunnelAlert {
+    pub title: String,
+    pub message: String,
+}
+
+fn parse_status(json: &str) -> Option<Vec<LivePeer>> {
+    let v: serde_json::Value = serde_json::from_str(json).ok()?;
+    serde_json::from_value(v.get("peers")?.clone()).ok()
+}
+
+impl Telemetry {
+    fn event(&mut self, now: i64, address: &str, hostname: &str, kind: &str, detail: String) {
+        if matches!(kind, "disconnected" | "stale") {
+            warn!("wolfnet: peer {} ({}) {}: {}", hostname, address, kind, detail);
+        } else {
+            info!("wolfnet: peer {} ({}) {}: {}", hostname, address, kind, detail);
+        }
+        let timestamp = chrono::DateTime::from_timestamp(now, 0).unwrap_or_default().to_rfc3339();
+        self.events.push_back(TunnelEvent {
+            timestamp, address: address.into(), hostname: hostname.into(), kind: kind.into(), detail,
+        });
+        while self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    /// Fold one read of the peer table in. `age_offset` is how long ago
+    /// the daemon wrote it; `stale_secs` 0 turns the stale check off.
+    fn record(&mut self, now: i64, live: Vec<LivePeer>, age_offset: u64, stale_secs: u64) -> Vec<TunnelAlert> {
+        let mut alerts = Vec::new();
+        for peer in live {
+            if peer.address.is_empty() {
+                continue;
+            }
+            let age = peer.last_seen_secs.map(|s| s + age_offset);
+            let name = if peer.hostname.is_empty() { peer.address.clone() } else { peer.hostname.clone() };
+            let is_new = !self.peers.contains_key(&peer.address);
+            let track = self.peers.entry(peer.address.clone()).or_default();
+            let mut events: Vec<(&str, String)> = Vec::new();
+            if is_new {
+                track.connected = peer.connected;
+                track.endpoint = peer.endpoint.clone();
+                track.relay_via = peer.relay_via.clone();
+            } else {
+                if peer.connected != track.connected {
+                    events.push(if peer.connected {
+                        ("connected", format!("endpoint {}", peer.endpoint))
+                    } else {
+                        ("disconnected", match age {
+                            Some(a) => format!("last handshake {}s ago", a),
+                            None => "no handshake yet".into(),
+                        })
+                    });
+                }
+                if !peer.endpoint.is_empty() && peer.endpoint != track.endpoint {
+                    if !track.endpoint.is_empty() {
+                        track.endpoint_changes += 1;
+                        events.push(("endpoint_changed", format!("{} → {}", track.endpoint, peer.endpoint)));
+                    }
+                    track.endpoint = peer.endpoint.clone();
+                }
+                if peer.relay_via != track.relay_via {
+                    events.push(("relay_changed", match &peer.relay_via {
+                        Some(via) => format!("now relayed via {}", via),
+                        None => "direct again".into(),
+                    }));
+                }
+            }
+            track.connected = peer.connected;
+            track.relay_via = peer.relay_via.clone();
+            track.hostname = name.clone();
+            track.last_present = now;
+
+            if stale_secs > 0 {
+                let healthy = age.is_some_and(|a| a < stale_secs);
+                if healthy {
+                    if track.stale {
+                        track.stale = false;
+                        events.push(("recovered", format!("handshake {}s ago", age.unwrap_or(0))));
+                        alerts.push(TunnelAlert {
+                            title: format!("WolfNet peer {} is back", name),
+                            message: format!("The tunnel to {} ({}) completed a handshake again.", name, peer.address),
+                        });
+                    }
+                    track.seen_healthy = true;
+                } else if track.seen_healthy && !track.stale {
+                    track.stale = true;
+                    let silent = age.map(|a| format!("{} minutes", a / 60)).unwrap_or_else(|| "a while".into());
+                    events.push(("stale", format!("no handshake for {}", silent)));
+                    alerts.push(TunnelAlert {
+                        title: format!("WolfNet peer {} has gone silent", name),
+                        message: format!(
+                            "No handshake with {} ({}) for {} (threshold {} minutes). Endpoint: {}.\n\
+                             Traffic over WolfNet to that node is failing. Check the WolfNet \
+                             service on both ends, the endpoint's reachability and any firewall \
+                             in between.",
+                            name, peer.address, silent, stale_secs / 60,
+                            if track.endpoint.is_empty() { "none configured" } else { &track.endpoint }),
+                    });
+                }
+            }
+
+            track.samples.push_back(PeerSample {
+                t: now, connected: peer.connected, handshake_age_secs: age,
+                rx_bytes: peer.rx_bytes, tx_bytes: peer.tx_bytes,
+            });
+            while track.samples.len() > MAX_SAMPLES {
+                track.samples.pop_front();
+            }
+            for (kind, detail) in events {
+                self.event(now, &peer.address, &name, kind, detail);
+            }
+        }
+        self.peers.retain(|_, t| now - t.last_present < FORGET_AFTER_SECS);
+        alerts
+    }
+}
+
+/// Take one sample of every peer. Blocking (reads a file and the alert
+/// config); returns the alerts to send.
+pub fn sample() -> Vec<TunnelAlert> {
+    let Ok(meta) = std::fs::metadata(STATUS_PATH) else { return Vec::new() };
+    let Some(live) = std::fs::read_to_string(STATUS_PATH).ok().and_then(|s| parse_status(&s)) else {
+        return Vec::new();
+    };
+    // A daemon that stopped writing the file leaves old ages behind.
+    let age_offset = meta.modified().ok()
+        .and_then(|m| m.elapsed().ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let stale_secs = crate::alerting::AlertConfig::load().wolfnet_stale_minutes * 60;
+    let now = chrono::Utc::now().timestamp();
+    TELEMETRY.lock().unwrap().record(now, live, age_offset, stale_secs)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerHealth {
+    pub address: String,
+    pub hostname: String,
+    pub endpoint: String,
+    pub connected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relay_via: Option<String>,
+    /// Went silent past the alert threshold.
+    pub stale: bool,
+    pub handshake_age_secs: Option<u64>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub endpoint_changes: u32,
+    /// Oldest first, within the requested window.
+    pub samples: Vec<PeerSample>,
+}
+
+/// Every tracked peer with its last `minutes` of samples, and the most
+/// recent `events` events, newest first.
+pub fn report(minutes: u64, events: usize) -> serde_json::Value {
+    let since = chrono::Utc::now().timestamp() - (minutes * 60) as i64;
+    let t = TELEMETRY.lock().unwrap();
+    let mut peers: Vec<PeerHealth> = t.peers.iter().map(|(address, track)| {
+        let last = track.samples.back();
+        PeerHealth {
+            address: address.clone(),
+            hostname: track.hostname.clone(),
+            endpoint: track.endpoint.clone(),
+            connected: track.connected,
+            relay_via: track.relay_via.clone(),
+            stale: track.stale,
+            handshake_age_secs: last.and_then(|s| s.handshake_age_secs),
+            rx_bytes: last.map(|s| s.rx_bytes).unwrap_or(0),
+            tx_bytes: last.map(|s| s.tx_bytes).unwrap_or(0),
+            endpoint_changes: track.endpoint_changes,
+            samples: track.samples.iter().filter(|s| s.t >= since).cloned().collect(),
+        }
+    }).collect();
+    peers.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+    let events: Vec<&TunnelEvent> = t.events.iter().rev().take(events).collect();
+    serde_json::json!({
+        "stale_minutes": crate::alerting::AlertConfig::load().wolfnet_stale_minutes,
+        "peers": peers,
+        "events": events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(connected: bool, age: Option<u64>, endpoint: &str) -> LivePeer {
+        LivePeer {
+            address: "10.10.10.2".into(), hostname: "node2".into(), endpoint: endpoint.into(),
+            connected, last_seen_secs: age, rx_bytes: 100, tx_bytes: 200, relay_via: None,
+        }
+    }
+
+    #[test]
+    fn parses_daemon_status() {
+        let peers = parse_status(r#"{"peers":[{"address":"10.10.10.2","hostname":"node2","endpoint":"1.2.3.4:9600",
+            "connected":true,"last_seen_secs":3,"rx_bytes":10,"tx_bytes":20,"relay_via":null,"is_gateway":false}]}"#).unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!((peers[0].last_seen_secs, peers[0].tx_bytes), (Some(3), 20));
+        assert!(parse_status("{}").is_none());
+    }
+
+    #[test]
+    fn alerts_once_when_a_healthy_peer_goes_silent() {
+        let mut t = Telemetry::default();
+        // Never handshaken: tracked but not alerted on.
+        assert!(t.record(0, vec![peer(false, None, "")], 0, 300).is_empty());
+        assert!(t.record(60, vec![peer(true, Some(5), "1.2.3.4:9600")], 0, 300).is_empty());
+        // The file is 200s old, so a 150s handshake is really 350s.
+        let alerts = t.record(120, vec![peer(false, Some(150), "1.2.3.4:9600")], 200, 300);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].title.contains("gone silent"));
+        assert!(t.record(180, vec![peer(false, Some(500), "1.2.3.4:9600")], 0, 300).is_empty());
+        // Back on a new endpoint.
+        let alerts = t.record(240, vec![peer(true, Some(2), "5.6.7.8:9600")], 0, 300);
+        assert!(alerts[0].title.contains("is back"));
+
+        let kinds: Vec<&str> = t.events.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(kinds, ["connected", "disconnected", "stale", "connected", "endpoint_changed", "recovered"]);
+        let track = &t.peers["10.10.10.2"];
+        assert_eq!((track.samples.len(), track.endpoint_changes), (5, 1));
+        assert_eq!(track.samples[2].handshake_age_secs, Some(350));
+    }
+}
@@ -55,6 +55,8 @@ pub struct FileLocations {
     pub nodes_config: String,
     #[serde(default = "default_deleted_nodes_config")]
     pub deleted_nodes_config: String,
+    #[serde(default = "default_cluster_state")]
+    pub cluster_state: String,
     #[serde(default = "default_self_cluster_config")]
     pub self_cluster_config: String,
     #[serde(default = "default_self_site_config")]
@@ -63,6 +65,8 @@ pub struct FileLocations {
     pub self_display_name_config: String,
     #[serde(default = "default_self_roles_config")]
     pub self_roles_config: String,
+    #[serde(default = "default_self_labels_config")]
+    pub self_labels_config: String,
     #[serde(default = "default_pending_identity_config")]
     pub pending_identity_config: String,
     #[serde(default = "default_node_id_file")]
@@ -74,6 +78,35 @@ pub struct FileLocations {
     #[serde(default = "default_unraid_config")]
     pub unraid_config: String,
 
+    // ── Monitoring ────────────────────────────────
+    /// Metrics history, written on shutdown and read back at startup.
+    #[serde(default = "default_metrics_history")]
+    pub metrics_history: String,
+    /// Crash reports written by the panic hook and on a failed start.
+    #[serde(default = "default_crash_dir")]
+    pub crash_dir: String,
+    /// Guest and node state-change history for availability reports.
+    #[serde(default = "default_availability_dir")]
+    pub availability_dir: String,
+    /// Issue occurrences and resolution times from the issue scans.
+    #[serde(default = "default_issue_history")]
+    pub issue_history: String,
+    /// Last hardware inventory snapshot (DMI, disks, NICs, PCI).
+    #[serde(default = "default_hardware_inventory")]
+    pub hardware_inventory: String,
+    /// Blue/green component upgrade history.
+    #[serde(default = "default_component_upgrades")]
+    pub component_upgrades: String,
+    /// Scratch config and data for a component's trial instance.
+    #[serde(default = "default_component_upgrade_dir")]
+    pub component_upgrade_dir: String,
+    /// Revisions of config files edited through WolfStack.
+    #[serde(default = "default_config_history_dir")]
+    pub config_history_dir: String,
+    /// Cluster-wide config baselines for drift detection.
+    #[serde(default = "default_drift_config")]
+    pub drift_config: String,
+
     // ── Alerting ──────────────────────────────────
     #[serde(default = "default_alerts_config")]
     pub alerts_config: String,
@@ -84,6 +117,30 @@ pub struct FileLocations {
     #[serde(default = "default_statuspage_uptime")]
     pub statuspage_uptime: String,
 
+    // ── Provisioning ──────────────────────────────
+    #[serde(default = "default_provisioning_config")]
+    pub provisioning_config: String,
+
+    // ── Reboot windows ────────────────────────────
+    #[serde(default = "default_maintenance_config")]
+    pub maintenance_config: String,
+    /// History of reboot-window runs this node drove.
+    #[serde(default = "default_maintenance_runs")]
+    pub maintenance_runs: String,
+    /// This node's own in-flight reboot job, read back after the reboot.
+    #[serde(default = "default_maintenance_job")]
+    pub maintenance_job: String,
+
+    // ── Job concurrency ───────────────────────────
+    /// Per-node limits on concurrent backups, restores and migrations.
+    #[serde(default = "default_job_limits_config")]
+    pub job_limits_config: String,
+
+    // ── SNMP ──────────────────────────────────────
+    /// SNMP agent settings, including the community and v3 passphrases.
+    #[serde(default = "default_snmp_config")]
+    pub snmp_config: String,
+
     // ── AI Agent ──────────────────────────────────
     #[serde(default = "default_ai_config")]
     pub ai_config: String,
@@ -99,6 +156,8 @@ pub struct FileLocations {
     pub wolfrun_services: String,
     #[serde(default = "default_wolfrun_failover_events")]
     pub wolfrun_failover_events: String,
+    #[serde(default = "default_wolfrun_autoscale_events")]
+    pub wolfrun_autoscale_events: String,
 
     // ── WolfFunctions ─────────────────────────────
     #[serde(default = "default_wolffunctions_dir")]
@@ -161,6 +220,10 @@ pub struct FileLocations {
     // ── Containers ────────────────────────────────
     #[serde(default = "default_cluster_containers_dir")]
     pub cluster_containers_dir: String,
+    #[serde(default = "default_container_env")]
+    pub container_env: String,
+    #[serde(default = "default_container_secrets_dir")]
+    pub container_secrets_dir: String,
 
     // ── Icon Packs ────────────────────────────────
     #[serde(default = "default_icon_packs_dir")]
@@ -182,6 +245,10 @@ pub struct FileLocations {
     #[serde(default = "default_ports_config")]
     pub ports_config: String,
 
+    // ── Daemon settings (wolfstack.toml) ──────────
+    #[serde(default = "default_daemon_config")]
+    pub daemon_config: String,
+
     // ── SQL Connections (agent + wolfflow) ────────
     #[serde(default = "default_sql_connections_config")]
     pub sql_connections_config: String,
@@ -215,21 +282,42 @@ fn default_s3_cache_dir() -> String { "/var/cache/wolfstack/s3".into() }
 
 fn default_nodes_config() -> String { "/etc/wolfstack/nodes.json".into() }
 fn default_deleted_nodes_config() -> String { "/etc/wolfstack/deleted_nodes.json".into() }
+fn default_cluster_state() -> String { "/var/lib/wolfstack/cluster.json".into() }
 fn default_self_cluster_config() -> String { "/etc/wolfstack/self_cluster.json".into() }
 fn default_self_site_config() -> String { "/etc/wolfstack/self_site.json".into() }
 fn default_self_display_name_config() -> String { "/etc/wolfstack/self_display_name.json".into() }
 fn default_self_roles_config() -> String { "/etc/wolfstack/self_roles.json".into() }
+fn default_self_labels_config() -> String { "/etc/wolfstack/self_labels.json".into() }
 fn default_pending_identity_config() -> String { "/etc/wolfstack/pending_identity.json".into() }
 fn default_node_id_file() -> String { "/etc/wolfstack/node_id".into() }
 fn default_xo_pools_config() -> String { "/etc/wolfstack/xo_pools.json".into() }
 fn default_truenas_config() -> String { "/etc/wolfstack/truenas.json".into() }
 fn default_unraid_config() -> String { "/etc/wolfstack/unraid.json".into() }
 
+fn default_metrics_history() -> String { "/var/lib/wolfstack/metrics-history.json".into() }
+fn default_crash_dir() -> String { "/var/lib/wolfstack/crash".into() }
+fn default_availability_dir() -> String { "/var/lib/wolfstack/availability".into() }
+fn default_issue_history() -> String { "/var/lib/wolfstack/issue-history.json".into() }
+fn default_hardware_inventory() -> String { "/var/lib/wolfstack/hardware.json".into() }
+fn default_component_upgrades() -> String { "/var/lib/wolfstack/component-upgrades.json".into() }
+fn default_component_upgrade_dir() -> String { "/var/lib/wolfstack/upgrade".into() }
+fn default_config_history_dir() -> String { "/var/lib/wolfstack/config-history".into() }
+fn default_drift_config() -> String { "/etc/wolfstack/drift.json".into() }
+
 fn default_alerts_config() -> String { "/etc/wolfstack/alerts.json".into() }
 
 fn default_statuspage_config() -> String { "/etc/wolfstack/statuspage.json".into() }
 fn default_statuspage_uptime() -> String { "/etc/wolfstack/statuspage-uptime.json".into() }
 
+fn default_provisioning_config() -> String { "/etc/wolfstack/provisioning.json".into() }
+
+fn default_maintenance_config() -> String { "/etc/wolfstack/reboot-windows.json".into() }
+fn default_maintenance_runs() -> String { "/var/lib/wolfstack/reboot-runs.json".into() }
+fn default_maintenance_job() -> String { "/var/lib/wolfstack/reboot-pending.json".into() }
+
+fn default_job_limits_config() -> String { "/etc/wolfstack/job-limits.json".into() }
+fn default_snmp_config() -> String { "/etc/wolfstack/snmp.json".into() }
+
 fn default_ai_config() -> String { "/etc/wolfstack/ai-config.json".into() }
 fn default_ai_baseline() -> String { "/var/lib/wolfstack/ai-baseline.json".into() }
 fn default_ai_suppress_secret() -> String { "/etc/wolfstack/ai-suppress-secret".into() }
@@ -237,6 +325,7 @@ fn default_ai_suppress_secret() -> String { "/etc/wolfstack/ai-suppress-secret".
 fn default_wolfrun_dir() -> String { "/etc/wolfstack/wolfrun".into() }
 fn default_wolfrun_services() -> String { "/etc/wolfstack/wolfrun/services.json".into() }
 fn default_wolfrun_failover_events() -> String { "/etc/wolfstack/wolfrun/failover-events.json".into() }
+fn default_wolfrun_autoscale_events() -> String { "/etc/wolfstack/wolfrun/autoscale-events.json".into() }
 
 fn default_wolffunctions_dir() -> String { "/etc/wolfstack/wolffunctions".into() }
 fn default_wolffunctions_functions() -> String { "/etc/wolfstack/wolffunctions/functions.json".into() }
@@ -268,6 +357,8 @@ fn default_ip_mappings() -> String { "/etc/wolfstack/ip-mappings.json".into() }
 fn default_lxc_paths() -> String { "/etc/wolfstack/lxc-paths.json".into() }
 
 fn default_cluster_containers_dir() -> String { "/etc/wolfstack/cluster-containers".into() }
+fn default_container_env() -> String { "/etc/wolfstack/container-env.json".into() }
+fn default_container_secrets_dir() -> String { "/etc/wolfstack/container-secrets".into() }
 
 fn default_icon_packs_dir() -> String { "/etc/wolfstack/icon-packs".into() }
 
@@ -279,6 +370,8 @@ fn default_web_dir() -> String { "/opt/wolfstack/web".into() }
 
 fn default_ports_config() -> String { "/etc/wolfstack/ports.json".into() }
 
+fn default_daemon_config() -> String { "/etc/wolfstack/wolfstack.toml".into() }
+
 fn default_sql_connections_config() -> String { "/etc/wolfstack/sql-connections.json".into() }
 fn default_sql_audit_log() -> String { "/var/log/wolfstack/sql-audit.log".into() }
 
@@ -381,6 +474,28 @@ pub fn write_secure(path: &str, contents: impl AsRef<[u8]>) -> std::io::Result<(
     }
 }
 
+/// [`write_secure`], but atomic: the contents go to `<path>.tmp` (0600
+/// from creation), are fsynced, and are renamed over `path`. A crash or
+/// full disk mid-write leaves the previous file intact instead of a
+/// truncated one — for state a node must be able to read back at boot.
+pub fn write_secure_atomic(path: &str, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let tmp = format!("{}.tmp", path);
+    let result = write_secure(&tmp, contents)
+        .and_then(|_| std::fs::File::open(&tmp)?.sync_all())
+        .and_then(|_| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return result;
+    }
+    // Make the rename itself durable.
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        if let Ok(dir) = std::fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
 /// One-shot: tighten permissions on `/etc/wolfstack` and any known
 /// sensitive file that might already exist with 0644 from a pre-v18.7.27
 /// install. Called once from main at startup. Silent on failure
@@ -414,6 +529,7 @@ pub fn harden_existing() {
             locs.cluster_secret.clone(),
             "/etc/wolfstack/cluster-secret".to_string(),
             locs.nodes_config.clone(),
+            locs.cluster_state.clone(),
             "/etc/wolfstack/join-token".to_string(),
             "/etc/wolfstack/license.key".to_string(),
             locs.tls_key.clone(),
@@ -422,6 +538,7 @@ pub fn harden_existing() {
             "/etc/wolfstack/oidc.json".to_string(),          // OIDC client secrets
             "/etc/wolfstack/ai-config.json".to_string(),     // LLM API keys + SMTP pass
             "/etc/wolfstack/pbs/config.json".to_string(),    // PBS tokens
+            locs.snmp_config.clone(),                        // SNMP community + v3 passphrases
             "/etc/wolfstack/paths.json".to_string(),         // path remap — if attacker-controlled, can redirect secret writers
             "/etc/ppp/chap-secrets".to_string(),             // PPPoE passwords (WAN)
             "/etc/ppp/pap-secrets".to_string(),
@@ -0,0 +1,496 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Capacity planning — when will each node's RAM and disks reach 90%?
+//!
+//! The predictive [`MetricsHistory`](super::metrics::MetricsHistory)
+//! only keeps 24 hours, which is right for "this disk fills tonight" but
+//! useless for "we need more storage by March". This module keeps a
+//! separate long-horizon series: one sample per hour per resource for
+//! [`RETENTION_DAYS`], recorded from the orchestrator tick and persisted
+//! next to the short history.
+//!
+//! ## Projection
+//!
+//! Usage swings within a day (backups, page cache, log rotation), so the
+//! series is reduced to **daily peaks** first — what matters is when the
+//! worst moment of the day crosses the line.
+//!
+//! - **seasonal** (≥ 14 days of peaks): a least-squares trend over the
+//!   daily peaks plus a per-weekday offset (the mean residual for that
+//!   weekday). Catches "the Sunday full backup pushes it over first".
+//! - **linear** (≥ 3 days of peaks): the trend alone.
+//! - **linear** on the raw hourly samples when there is 12 h – 3 days of
+//!   history, so a new node gets a rough answer on day one.
+//!
+//! Flat or shrinking trends, and crossings more than
+//! [`HORIZON_DAYS`] out, report no date.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::metrics::{linear_fit, MetricSample};
+
+/// Usage level the report projects to.
+pub const TARGET_PCT: f64 = 90.0;
+/// How much hourly history is kept.
+pub const RETENTION_DAYS: i64 = 90;
+/// Crossings further out than this aren't reported.
+pub const HORIZON_DAYS: i64 = 365;
+/// The orchestrator ticks every 5 minutes; keep roughly one sample an hour.
+const MIN_SPACING_MINUTES: i64 = 55;
+const SEASONAL_MIN_DAYS: usize = 14;
+const LINEAR_MIN_DAYS: usize = 3;
+
+/// Resource key for RAM; disks are `disk:<mount>`.
+pub const MEMORY: &str = "memory";
+
+pub fn disk_resource(mount: &str) -> String {
+    format!("disk:{}", mount)
+}
+
+pub fn history_file() -> PathBuf {
+    if let Ok(p) = std::env::var("WOLFSTACK_CAPACITY_HISTORY_FILE") {
+        return PathBuf::from(p);
+    }
+    PathBuf::from("/etc/wolfstack/capacity_history.json")
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacityHistory {
+    /// `resource → hourly samples` (oldest first), values in percent.
+    #[serde(default)]
+    pub series: HashMap<String, VecDeque<MetricSample>>,
+}
+
+impl CapacityHistory {
+    pub fn load() -> Self {
+        std::fs::read_to_string(history_file())
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = history_file();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write capacity history: {}", e))
+    }
+
+    /// Append a sample unless the series already has one from the last
+    /// hour. Returns whether it was recorded.
+    pub fn record_at(&mut self, resource: &str, value: f64, ts: DateTime<Utc>) -> bool {
+        let buf = self.series.entry(resource.to_string()).or_default();
+        if buf.back().is_some_and(|last| ts - last.ts < Duration::minutes(MIN_SPACING_MINUTES)) {
+            return false;
+        }
+        buf.push_back(MetricSample { ts, value });
+        true
+    }
+
+    /// Drop samples older than the retention window, series for
+    /// resources that are gone (an unmounted disk), and empty series.
+    pub fn prune(&mut self, now: DateTime<Utc>, live: &HashSet<String>) {
+        let cutoff = now - Duration::days(RETENTION_DAYS);
+        self.series.retain(|k, buf| {
+            while buf.front().is_some_and(|s| s.ts < cutoff) {
+                buf.pop_front();
+            }
+            live.contains(k) && !buf.is_empty()
+        });
+    }
+}
+
+static HISTORY: LazyLock<Mutex<CapacityHistory>> = LazyLock::new(|| Mutex::new(CapacityHistory::load()));
+
+/// Record this tick's disk and memory usage. Called from the predictive
+/// orchestrator; only writes the file when an hourly sample was taken.
+pub fn record(mounts: &[(String, f64)], memory_pct: Option<f64>) {
+    let now = Utc::now();
+    let mut live: HashSet<String> = mounts.iter().map(|(m, _)| disk_resource(m)).collect();
+    let mut h = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let mut recorded = false;
+    for (mount, pct) in mounts {
+        recorded |= h.record_at(&disk_resource(mount), *pct, now);
+    }
+    if let Some(pct) = memory_pct {
+        live.insert(MEMORY.to_string());
+        recorded |= h.record_at(MEMORY, pct, now);
+    }
+    if recorded {
+        h.prune(now, &live);
+        if let Err(e) = h.save() {
+            tracing::warn!("capacity: {}", e);
+        }
+    }
+}
+
+/// Highest value per UTC day, oldest first.
+pub fn daily_peaks(samples: &VecDeque<MetricSample>) -> Vec<(NaiveDate, f64)> {
+    let mut peaks: Vec<(NaiveDate, f64)> = Vec::new();
+    for s in samples {
+        let day = s.ts.date_naive();
+        match peaks.last_mut() {
+            Some((d, v)) if *d == day => *v = v.max(s.value),
+            _ => peaks.push((day, s.value)),
+        }
+    }
+    peaks
+}
+
+/// One resource's outlook.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Projection {
+    /// `memory` or `disk:<mount>`.
+    pub resource: String,
+    pub label: String,
+    pub current_pct: f64,
+    /// Today's peak so far.
+    pub peak_pct: f64,
+    /// Trend of the daily peak, percentage points per day.
+    pub growth_pct_per_day: f64,
+    /// `seasonal`, `linear` or `insufficient`.
+    pub method: String,
+    pub history_days: f64,
+    /// Days until usage is projected to reach [`TARGET_PCT`]; 0 when it
+    /// already has, `None` for no crossing within [`HORIZON_DAYS`].
+    pub eta_days: Option<f64>,
+    /// `YYYY-MM-DD` of the projected crossing.
+    pub eta_date: Option<String>,
+}
+
+fn label_for(resource: &str) -> String {
+    match resource.strip_prefix("disk:") {
+        Some(mount) => format!("Disk {}", mount),
+        None if resource == MEMORY => "Memory".to_string(),
+        None => resource.to_string(),
+    }
+}
+
+fn at_midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).map(|d| d.and_utc()).unwrap_or_default()
+}
+
+/// Least-squares line over daily peaks: `(per_day, value_on(day))`.
+fn fit_peaks(peaks: &[(NaiveDate, f64)]) -> Option<(f64, impl Fn(NaiveDate) -> f64)> {
+    let series: VecDeque<MetricSample> = peaks.iter().map(|(d, v)| MetricSample { ts: at_midnight(*d), value: *v }).collect();
+    let (slope, intercept) = linear_fit(&series)?;
+    let t0 = series.front()?.ts;
+    let per_day = slope * 86_400.0;
+    Some((per_day, move |day: NaiveDate| intercept + slope * (at_midnight(day) - t0).num_seconds() as f64))
+}
+
+pub fn project(resource: &str, samples: &VecDeque<MetricSample>, now: DateTime<Utc>) -> Option<Projection> {
+    let last = samples.back()?;
+    let first = samples.front()?;
+    let peaks = daily_peaks(samples);
+    let today = now.date_naive();
+    let history_days = (last.ts - first.ts).num_minutes() as f64 / 1440.0;
+    let mut p = Projection {
+        resource: resource.to_string(),
+        label: label_for(resource),
+        current_pct: last.value,
+        peak_pct: peaks.last().map(|(_, v)| *v).unwrap_or(last.value),
+        growth_pct_per_day: 0.0,
+        method: "insufficient".to_string(),
+        history_days,
+        eta_days: None,
+        eta_date: None,
+    };
+    let mut eta: Option<NaiveDate> = None;
+
+    if peaks.len() >= SEASONAL_MIN_DAYS {
+        let (per_day, trend) = fit_peaks(&peaks)?;
+        let mut sum = [0.0f64; 7];
+        let mut count = [0u32; 7];
+        for (d, v) in &peaks {
+            let w = d.weekday().num_days_from_monday() as usize;
+            sum[w] += v - trend(*d);
+            count[w] += 1;
+        }
+        let offset = |d: NaiveDate| {
+            let w = d.weekday().num_days_from_monday() as usize;
+            if count[w] > 0 { sum[w] / count[w] as f64 } else { 0.0 }
+        };
+        p.method = "seasonal".to_string();
+        p.growth_pct_per_day = per_day;
+        eta = (1..=HORIZON_DAYS)
+            .map(|n| today + Duration::days(n))
+            .find(|d| trend(*d) + offset(*d) >= TARGET_PCT);
+    } else if peaks.len() >= LINEAR_MIN_DAYS {
+        let (per_day, trend) = fit_peaks(&peaks)?;
+        p.method = "linear".to_string();
+        p.growth_pct_per_day = per_day;
+        if per_day > 0.0 {
+            let days = ((TARGET_PCT - trend(today)) / per_day).ceil().max(1.0);
+            eta = (days <= HORIZON_DAYS as f64).then(|| today + Duration::days(days as i64));
+        }
+    } else if history_days >= 0.5 && samples.len() >= 6 {
+        let (slope, intercept) = linear_fit(samples)?;
+        let per_day = slope * 86_400.0;
+        p.method = "linear".to_string();
+        p.growth_pct_per_day = per_day;
+        if per_day > 0.0 {
+            let fitted_now = intercept + slope * (now - first.ts).num_seconds() as f64;
+            let days = ((TARGET_PCT - fitted_now) / per_day).max(0.0);
+            eta = (days <= HORIZON_DAYS as f64).then(|| today + Duration::days(days.ceil().max(1.0) as i64));
+        }
+    }
+
+    if p.current_pct >= TARGET_PCT || p.peak_pct >= TARGET_PCT {
+        eta = Some(today);
+    }
+    if let Some(d) = eta {
+        p.eta_days = Some((d - today).num_days() as f64);
+        p.eta_date = Some(d.format("%Y-%m-%d").to_string());
+    }
+    Some(p)
+}
+
+/// Projections for every resource recorded on this node, soonest first.
+pub fn local_projections() -> Vec<Projection> {
+    let now = Utc::now();
+    let h = HISTORY.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut out: Vec<Projection> = h.series.iter().filter_map(|(r, s)| project(r, s, now)).collect();
+    sort_projections(&mut out);
+    out
+}
+
+/// Soonest crossing first; no-crossing last, memory before disks.
+pub fn sort_projections(list: &mut [Projection]) {
+    list.sort_by(|a, b| {
+        let key = |p: &Projection| p.eta_days.unwrap_or(f64::MAX);
+        key(a).total_cmp(&key(b))
+            .then_with(|| (a.resource != MEMORY).cmp(&(b.resource != MEMORY)))
+            .then_with(|| a.resource.cmp(&b.resource))
+    });
+}
+
+/// A node's section of the cluster report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeCapacity {
+    pub cluster: String,
+    pub node: String,
+    pub online: bool,
+    #[serde(default)]
+    pub projections: Vec<Projection>,
+    /// Why there are no projections (unreachable, older version).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// This node's projections plus each online WolfStack peer's own
+/// `scope=local` report, fetched with the cluster secret.
+pub async fn collect_cluster(state: &actix_web::web::Data<crate::api::AppState>) -> Vec<NodeCapacity> {
+    let cluster_of = |n: &crate::agent::Node| n.cluster_name.clone().unwrap_or_else(|| "Default".to_string());
+    let nodes: Vec<crate::agent::Node> = state.cluster.get_all_nodes().into_iter()
+        .filter(|n| n.node_type == "wolfstack")
+        .collect();
+    let secret = state.cluster_secret.clone();
+    let handles: Vec<_> = nodes.into_iter().map(|n| {
+        let secret = secret.clone();
+        let cluster = cluster_of(&n);
+        tokio::spawn(async move {
+            let mut entry = NodeCapacity { cluster, node: n.hostname.clone(), online: n.online, projections: Vec::new(), error: None };
+            if n.is_self {
+                entry.projections = local_projections();
+                return entry;
+            }
+            if !n.online {
+                entry.error = Some("offline".to_string());
+                return entry;
+            }
+            for url in crate::api::build_node_urls(&n.address, n.port, "/api/reports/capacity?scope=local") {
+                let resp = crate::api::API_HTTP_CLIENT.get(&url)
+                    .timeout(std::time::Duration::from_secs(10))
+                    .header("X-WolfStack-Secret", &secret)
+                    .send().await;
+                if let Ok(resp) = resp {
+                    if resp.status().is_success() {
+                        if let Ok(v) = resp.json::<serde_json::Value>().await {
+                            if let Ok(list) = serde_json::from_value::<Vec<Projection>>(v["projections"].clone()) {
+                                entry.projections = list;
+                                return entry;
+                            }
+                        }
+                    }
+                }
+            }
+            entry.error = Some("no capacity data (unreachable or older WolfStack)".to_string());
+            entry
+        })
+    }).collect();
+    let mut out = Vec::new();
+    for h in handles {
+        if let Ok(entry) = h.await {
+            out.push(entry);
+        }
+    }
+    out.sort_by(|a, b| (a.cluster.as_str(), a.node.as_str()).cmp(&(b.cluster.as_str(), b.node.as_str())));
+    out
+}
+
+/// Resources projected to cross within the horizon, soonest first.
+fn upcoming(nodes: &[NodeCapacity]) -> Vec<(&NodeCapacity, &Projection)> {
+    let mut rows: Vec<(&NodeCapacity, &Projection)> = nodes.iter()
+        .flat_map(|n| n.projections.iter().filter(|p| p.eta_days.is_some()).map(move |p| (n, p)))
+        .collect();
+    rows.sort_by(|a, b| a.1.eta_days.unwrap_or(f64::MAX).total_cmp(&b.1.eta_days.unwrap_or(f64::MAX)));
+    rows
+}
+
+fn eta_label(p: &Projection) -> String {
+    match p.eta_days {
+        Some(d) if d <= 0.0 => "now".to_string(),
+        Some(d) => format!("in {:.0} day(s) ({})", d, p.eta_date.as_deref().unwrap_or("")),
+        None => "not within a year".to_string(),
+    }
+}
+
+/// "Capacity outlook" section for the daily report email.
+pub fn email_section_html(nodes: &[NodeCapacity]) -> String {
+    use crate::daily_report::escape;
+    let rows = upcoming(nodes);
+    let tracked: usize = nodes.iter().map(|n| n.projections.len()).sum();
+    let mut html = format!(
+        r#"<h2>Capacity Outlook</h2><p class="meta" style="margin-top:-6px;">Projected date each resource reaches {:.0}% from its daily-peak trend &bull; {} resource(s) tracked</p>"#,
+        TARGET_PCT, tracked
+    );
+    if rows.is_empty() {
+        html.push_str(&format!(
+            r#"<p class="meta">No disk or memory is projected to reach {:.0}% within a year.</p>"#,
+            TARGET_PCT
+        ));
+        return html;
+    }
+    html.push_str(r#"<table><thead><tr><th>Node</th><th>Resource</th><th>Now</th><th>Growth</th><th>Reaches 90%</th><th>Method</th></tr></thead><tbody>"#);
+    for (n, p) in rows {
+        let color = match p.eta_days {
+            Some(d) if d < 14.0 => "#dc2626",
+            Some(d) if d < 60.0 => "#d97706",
+            _ => "#166534",
+        };
+        html.push_str(&format!(
+            r#"<tr><td><strong>{}</strong><div class="meta">{}</div></td><td>{}</td><td>{:.0}%</td><td class="meta">{:+.2}%/day</td><td style="color:{};font-weight:600;">{}</td><td class="meta">{} &middot; {:.0}d history</td></tr>"#,
+            escape(&n.node), escape(&n.cluster), escape(&p.label), p.current_pct, p.growth_pct_per_day,
+            color, escape(&eta_label(p)), escape(&p.method), p.history_days
+        ));
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+/// Plain-text counterpart of [`email_section_html`].
+pub fn email_section_text(nodes: &[NodeCapacity]) -> String {
+    let rows = upcoming(nodes);
+    let mut out = format!("\n== Capacity outlook (projected {:.0}%) ==\n", TARGET_PCT);
+    if rows.is_empty() {
+        out.push_str("  Nothing projected to reach it within a year.\n");
+    }
+    for (n, p) in rows {
+        out.push_str(&format!(
+            "  {} — {}: {:.0}% now, {:+.2}%/day, {} [{}]\n",
+            n.node, p.label, p.current_pct, p.growth_pct_per_day, eta_label(p), p.method
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn record_keeps_one_sample_an_hour_and_prunes() {
+        let mut h = CapacityHistory::default();
+        let t = ts(2026, 10, 1, 0);
+        assert!(h.record_at(MEMORY, 40.0, t));
+        assert!(!h.record_at(MEMORY, 41.0, t + Duration::minutes(5)));
+        assert!(h.record_at(MEMORY, 42.0, t + Duration::minutes(60)));
+        assert!(h.record_at("disk:/old", 10.0, t));
+        let live: HashSet<String> = [MEMORY.to_string()].into_iter().collect();
+        h.prune(t + Duration::days(RETENTION_DAYS) + Duration::minutes(30), &live);
+        assert_eq!(h.series.len(), 1);
+        assert_eq!(h.series[MEMORY].len(), 1, "the first sample aged out");
+    }
+
+    #[test]
+    fn daily_peaks_take_the_max_per_day() {
+        let s: VecDeque<MetricSample> = [(1, 3, 50.0), (1, 15, 70.0), (2, 1, 55.0)]
+            .iter().map(|(d, h, v)| MetricSample { ts: ts(2026, 10, *d, *h), value: *v }).collect();
+        let peaks = daily_peaks(&s);
+        assert_eq!(peaks.iter().map(|(_, v)| *v).collect::<Vec<_>>(), vec![70.0, 55.0]);
+    }
+
+    #[test]
+    fn linear_projection_on_daily_peaks() {
+        // Five days rising 1 point a day from 60%, hourly samples.
+        let start = ts(2026, 10, 1, 0);
+        let s: VecDeque<MetricSample> = (0..5 * 24)
+            .map(|h| MetricSample { ts: start + Duration::hours(h), value: 60.0 + h as f64 / 24.0 })
+            .collect();
+        let p = project("disk:/var", &s, ts(2026, 10, 5, 23)).unwrap();
+        assert_eq!(p.method, "linear");
+        assert!((p.growth_pct_per_day - 1.0).abs() < 0.05, "{}", p.growth_pct_per_day);
+        let eta = p.eta_days.unwrap();
+        assert!((25.0..=27.0).contains(&eta), "eta {}", eta);
+        assert_eq!(p.label, "Disk /var");
+    }
+
+    #[test]
+    fn seasonal_projection_catches_the_weekly_spike() {
+        // Peaks rise 1 point a day; every Saturday runs 20 points higher.
+        // The trend alone crosses 90% around 11 Oct — the first Saturday
+        // after "now" (26 Sep) already does.
+        let start = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        let s: VecDeque<MetricSample> = (0..21)
+            .map(|d| {
+                let day = start + Duration::days(d);
+                let spike = if day.weekday() == chrono::Weekday::Sat { 20.0 } else { 0.0 };
+                MetricSample { ts: at_midnight(day) + Duration::hours(12), value: 50.0 + d as f64 + spike }
+            })
+            .collect();
+        let p = project(MEMORY, &s, ts(2026, 9, 21, 13)).unwrap();
+        assert_eq!(p.method, "seasonal");
+        assert_eq!(p.eta_date.as_deref(), Some("2026-09-26"));
+        assert_eq!(p.eta_days, Some(5.0));
+    }
+
+    #[test]
+    fn flat_usage_has_no_date_and_full_usage_is_now() {
+        let start = ts(2026, 10, 1, 0);
+        let flat: VecDeque<MetricSample> = (0..96).map(|h| MetricSample { ts: start + Duration::hours(h), value: 40.0 }).collect();
+        let p = project(MEMORY, &flat, ts(2026, 10, 4, 23)).unwrap();
+        assert_eq!(p.eta_days, None);
+
+        let full: VecDeque<MetricSample> = (0..2).map(|h| MetricSample { ts: start + Duration::hours(h), value: 93.0 }).collect();
+        let p = project(MEMORY, &full, ts(2026, 10, 1, 2)).unwrap();
+        assert_eq!(p.method, "insufficient");
+        assert_eq!(p.eta_days, Some(0.0));
+    }
+
+    #[test]
+    fn soonest_crossing_sorts_first() {
+        let mk = |r: &str, eta: Option<f64>| Projection {
+            resource: r.into(), label: label_for(r), current_pct: 0.0, peak_pct: 0.0, growth_pct_per_day: 0.0,
+            method: "linear".into(), history_days: 1.0, eta_days: eta, eta_date: None,
+        };
+        let mut list = vec![mk("disk:/", None), mk("disk:/var", Some(30.0)), mk(MEMORY, None), mk("disk:/data", Some(3.0))];
+        sort_projections(&mut list);
+        let order: Vec<&str> = list.iter().map(|p| p.resource.as_str()).collect();
+        assert_eq!(order, vec!["disk:/data", "disk:/var", "memory", "disk:/"]);
+    }
+}
@@ -72,6 +72,7 @@ pub mod threat_intel;
 pub mod unused_packages;
 pub mod notify;
 pub mod cluster;
+pub mod capacity;
 pub mod orchestrator;
 
 pub use proposal::{
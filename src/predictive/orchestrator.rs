@@ -32,7 +32,7 @@ use crate::predictive::{
     vulnerability, osv, port_conflict, wolfnet_dhcp, wolfnet_reachability,
     docker_wolfnet_collision, missing_subnet_route, compromise_indicators,
     tamper_detection, threat_intel,
-    unused_packages, notify, container_boot,
+    unused_packages, notify, container_boot, capacity,
 };
 
 /// Cadence between ticks once the loop is running.
@@ -94,8 +94,7 @@ const OSV_SAMPLE_TIMEOUT: Duration = Duration::from_secs(90);
 /// years of operation.
 const RESOLVED_RETENTION_DAYS: i64 = 90;
 
-/// Run forever. Spawned once from `main.rs`; never returns under
-/// normal operation.
+/// Run until shutdown. Spawned once from `main.rs`.
 pub async fn run_loop(
     proposals: Arc<RwLock<ProposalStore>>,
     acks: Arc<RwLock<AckStore>>,
@@ -103,10 +102,12 @@ pub async fn run_loop(
     monitor: Arc<Mutex<crate::monitoring::SystemMonitor>>,
     node_id: String,
 ) {
-    tokio::time::sleep(STARTUP_DELAY).await;
-    loop {
+    // Exits between ticks at shutdown, so its history save can't race
+    // the final one.
+    let mut wait = STARTUP_DELAY;
+    while crate::lifecycle::unless_stopping(tokio::time::sleep(wait)).await.is_some() {
         tick(&proposals, &acks, &metrics, &monitor, &node_id).await;
-        tokio::time::sleep(TICK_INTERVAL).await;
+        wait = TICK_INTERVAL;
     }
 }
 
@@ -227,6 +228,12 @@ pub async fn tick(
             tracing::warn!("predictive: failed to save metrics history: {}", e);
         }
     }
+    // Long-horizon hourly series for the capacity report (kept apart
+    // from the 24 h history above, which the fill-ETA analyzers fit).
+    capacity::record(
+        &host_facts.iter().map(|f| (f.mount.clone(), f.used_pct)).collect::<Vec<_>>(),
+        sys_metrics_opt.as_ref().map(|m| m.memory_percent as f64),
+    );
     {
         let mut a = lock_write(acks, "acks");
         let pruned = a.prune_expired();
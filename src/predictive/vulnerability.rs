@@ -354,7 +354,7 @@ fn terminate_child(child: &mut std::process::Child) {
 /// timeout / spawn failure / non-zero exit. We never want a hung
 /// child process to block the analyzer — better to skip the tick
 /// and try again in 5 minutes.
-fn run_capped(prog: &str, args: &[&str], timeout: Duration) -> Option<String> {
+pub(crate) fn run_capped(prog: &str, args: &[&str], timeout: Duration) -> Option<String> {
     use std::io::Read;
     use std::time::Instant;
     let mut child = Command::new(prog)
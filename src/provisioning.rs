@@ -0,0 +1,751 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Bare-metal provisioning — netboot a new machine into a WolfStack node.
+//!
+//! A provisioning profile carries a pre-approved enrolment token and the
+//! install settings (OS, disk, admin account, SSH keys, target cluster).
+//! The token-gated, unauthenticated `/api/provision/boot/{token}/…`
+//! endpoints serve everything the machine needs without keyboard time:
+//!
+//! 1. `boot.ipxe` — chained from DHCP/iPXE; boots the distro installer.
+//! 2. `preseed.cfg` (Debian) or `user-data` + `meta-data` (Ubuntu
+//!    autoinstall) — unattended OS install whose late command fetches…
+//! 3. `firstboot.sh` — installed as a one-shot unit that runs `setup.sh`
+//!    on first boot, then calls…
+//! 4. `POST enroll` — this node joins the machine into the cluster.
+//!
+//! Enrolment rides the normal join handshake. Instead of the target's
+//! admin password the machine hands over a random key it wrote to
+//! [`PROVISION_KEY_FILE`]; the handshake accepts that key once and
+//! deletes it, so a profile token alone can never graft an existing
+//! node onto a cluster.
+//!
+//! A profile's MAC allow-list is checked against this node's neighbour
+//! table, not against the MAC the machine reports, so it only admits
+//! machines on a network segment this node is attached to.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Per-machine key written by `firstboot.sh` and accepted once by the
+/// join handshake in place of admin credentials.
+pub const PROVISION_KEY_FILE: &str = "/etc/wolfstack/provision-key";
+
+/// Enrolment history kept per profile.
+const MAX_ENROLLMENTS: usize = 50;
+
+/// Serialises read-modify-write of the profile store — two machines
+/// enrolling at once must not lose each other's use count.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn config_file() -> String { crate::paths::get().provisioning_config }
+
+/// An installer the profiles can netboot.
+pub struct OsImage {
+    pub id: &'static str,
+    pub label: &'static str,
+    /// Ubuntu installs via autoinstall (cloud-init); Debian via preseed.
+    pub ubuntu: bool,
+    pub kernel: &'static str,
+    pub initrd: &'static str,
+    /// Live-server ISO the Ubuntu installer streams (Ubuntu only).
+    pub iso: &'static str,
+}
+
+pub const OS_IMAGES: &[OsImage] = &[
+    OsImage {
+        id: "debian-12",
+        label: "Debian 12 (bookworm)",
+        ubuntu: false,
+        kernel: "http://deb.debian.org/debian/dists/bookworm/main/installer-amd64/current/images/netboot/debian-installer/amd64/linux",
+        initrd: "http://deb.debian.org/debian/dists/bookworm/main/installer-amd64/current/images/netboot/debian-installer/amd64/initrd.gz",
+        iso: "",
+    },
+    OsImage {
+        id: "debian-13",
+        label: "Debian 13 (trixie)",
+        ubuntu: false,
+        kernel: "http://deb.debian.org/debian/dists/trixie/main/installer-amd64/current/images/netboot/debian-installer/amd64/linux",
+        initrd: "http://deb.debian.org/debian/dists/trixie/main/installer-amd64/current/images/netboot/debian-installer/amd64/initrd.gz",
+        iso: "",
+    },
+    OsImage {
+        id: "ubuntu-24.04",
+        label: "Ubuntu 24.04 LTS",
+        ubuntu: true,
+        kernel: "https://releases.ubuntu.com/24.04/netboot/amd64/linux",
+        initrd: "https://releases.ubuntu.com/24.04/netboot/amd64/initrd",
+        iso: "https://releases.ubuntu.com/24.04/ubuntu-24.04.3-live-server-amd64.iso",
+    },
+];
+
+pub fn os_image(id: &str) -> Option<&'static OsImage> {
+    OS_IMAGES.iter().find(|o| o.id == id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProvisionProfile {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Pre-approved enrolment token — part of every artifact URL.
+    #[serde(default)]
+    pub token: String,
+    /// Base URL the netbooting machine reaches this node on, e.g.
+    /// `http://10.0.0.5:8554`. iPXE and the Debian installer generally
+    /// cannot verify a self-signed certificate, so plain HTTP is normal.
+    pub server_url: String,
+    /// One of [`OS_IMAGES`].
+    pub os: String,
+    /// Overrides the Ubuntu live-server ISO URL (point releases move it).
+    #[serde(default)]
+    pub iso_url: String,
+    /// Cluster the new node joins; empty = this node's cluster.
+    #[serde(default)]
+    pub cluster_name: String,
+    /// Hostnames become `<prefix>-<last 6 MAC hex digits>`.
+    #[serde(default = "default_hostname_prefix")]
+    pub hostname_prefix: String,
+    /// Install disk, e.g. `/dev/sda`; empty = installer's choice.
+    #[serde(default)]
+    pub disk: String,
+    #[serde(default = "default_admin_user")]
+    pub admin_user: String,
+    /// crypt(3) hash (`openssl passwd -6`) for the admin account.
+    #[serde(default)]
+    pub password_hash: String,
+    #[serde(default)]
+    pub ssh_keys: Vec<String>,
+    /// When non-empty, only these MAC addresses may enrol. Checked
+    /// against the neighbour table ([`neighbour_mac`]), so machines behind
+    /// a router are refused.
+    #[serde(default)]
+    pub allowed_macs: Vec<String>,
+    /// 0 = unlimited.
+    #[serde(default)]
+    pub max_uses: u32,
+    #[serde(default)]
+    pub uses: u32,
+    /// Unix seconds; 0 = never expires.
+    #[serde(default)]
+    pub expires_at: u64,
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub enrollments: Vec<Enrollment>,
+}
+
+fn default_hostname_prefix() -> String { "wolfstack".into() }
+fn default_admin_user() -> String { "wolfadmin".into() }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Enrollment {
+    pub ts: u64,
+    pub hostname: String,
+    pub address: String,
+    #[serde(default)]
+    pub mac: String,
+    pub ok: bool,
+    /// Node id on success, the refusal reason otherwise.
+    #[serde(default)]
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProvisioningConfig {
+    #[serde(default)]
+    pub profiles: Vec<ProvisionProfile>,
+}
+
+impl ProvisioningConfig {
+    pub fn load() -> Self {
+        match std::fs::read_to_string(config_file()) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Tokens and password hashes live here — root-only, atomic.
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(&config_file(), json).map_err(|e| e.to_string())
+    }
+
+    /// Profile owning `token` (constant-time comparison).
+    pub fn by_token(&self, token: &str) -> Option<&ProvisionProfile> {
+        if token.is_empty() { return None; }
+        self.profiles.iter().find(|p| crate::auth::validate_cluster_secret(token, &p.token))
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Lower-case colon form (`aa:bb:cc:dd:ee:ff`), or None if not a MAC.
+pub fn normalize_mac(mac: &str) -> Option<String> {
+    let hex: String = mac.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let separators_ok = mac.chars().all(|c| c.is_ascii_hexdigit() || c == ':' || c == '-' || c == '.');
+    if hex.len() != 12 || !separators_ok { return None; }
+    let hex = hex.to_ascii_lowercase();
+    Some(hex.as_bytes().chunks(2).map(|c| std::str::from_utf8(c).unwrap_or("")).collect::<Vec<_>>().join(":"))
+}
+
+impl ProvisionProfile {
+    /// Validate operator input and normalise it in place. Everything
+    /// here ends up inside shell scripts, preseed or YAML, so anything
+    /// that could break out of a quoted value is refused outright.
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.len() > 64 || self.name.chars().any(|c| c.is_control() || c == '"' || c == '\'') {
+            return Err("Profile name is required (max 64 characters, no quotes)".into());
+        }
+        self.server_url = self.server_url.trim().trim_end_matches('/').to_string();
+        let url_ok = (self.server_url.starts_with("http://") || self.server_url.starts_with("https://"))
+            && self.server_url.chars().all(|c| c.is_ascii_alphanumeric() || "-._:/[]".contains(c));
+        if !url_ok {
+            return Err("Server URL must be a plain http:// or https:// address this node is reachable on".into());
+        }
+        if os_image(&self.os).is_none() {
+            return Err(format!("Unknown OS '{}'", self.os));
+        }
+        self.iso_url = self.iso_url.trim().to_string();
+        if !self.iso_url.is_empty()
+            && !((self.iso_url.starts_with("http://") || self.iso_url.starts_with("https://"))
+                && self.iso_url.chars().all(|c| c.is_ascii_alphanumeric() || "-._:/~%".contains(c)))
+        {
+            return Err("ISO URL must be a plain http:// or https:// address".into());
+        }
+        self.cluster_name = self.cluster_name.trim().to_string();
+        if self.cluster_name.len() > 64 || self.cluster_name.chars().any(|c| c.is_control()) {
+            return Err("Invalid cluster name".into());
+        }
+        self.hostname_prefix = self.hostname_prefix.trim().to_ascii_lowercase();
+        if self.hostname_prefix.is_empty() { self.hostname_prefix = default_hostname_prefix(); }
+        if self.hostname_prefix.len() > 40
+            || self.hostname_prefix.starts_with('-')
+            || !self.hostname_prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err("Hostname prefix may only contain letters, digits and hyphens (max 40)".into());
+        }
+        self.disk = self.disk.trim().to_string();
+        let disk_ok = self.disk.is_empty()
+            || self.disk.strip_prefix("/dev/").is_some_and(|d| {
+                !d.is_empty() && d.chars().all(|c| c.is_ascii_alphanumeric() || c == '/' || c == '-' || c == '_')
+            });
+        if !disk_ok {
+            return Err("Install disk must be a /dev path such as /dev/sda".into());
+        }
+        self.admin_user = self.admin_user.trim().to_string();
+        if self.admin_user.is_empty() { self.admin_user = default_admin_user(); }
+        let user_ok = self.admin_user.len() <= 32
+            && self.admin_user.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+            && self.admin_user.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !user_ok || self.admin_user == "root" {
+            return Err("Admin user must be a lower-case Linux username other than root".into());
+        }
+        self.password_hash = self.password_hash.trim().to_string();
+        if !self.password_hash.starts_with('$')
+            || !self.password_hash.chars().all(|c| c.is_ascii_alphanumeric() || "$./".contains(c))
+        {
+            return Err("Admin password must be a crypt hash — generate one with `openssl passwd -6`".into());
+        }
+        self.ssh_keys = self.ssh_keys.iter().map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+        for key in &self.ssh_keys {
+            let kind_ok = key.starts_with("ssh-") || key.starts_with("ecdsa-") || key.starts_with("sk-");
+            if !kind_ok || key.chars().any(|c| c.is_control() || c == '\'' || c == '"' || c == '\\') {
+                return Err("SSH keys must be single-line OpenSSH public keys without quotes".into());
+            }
+        }
+        let mut macs = Vec::new();
+        for mac in self.allowed_macs.iter().map(|m| m.trim()).filter(|m| !m.is_empty()) {
+            macs.push(normalize_mac(mac).ok_or_else(|| format!("'{}' is not a MAC address", mac))?);
+        }
+        self.allowed_macs = macs;
+        Ok(())
+    }
+
+    /// Why this profile can't enrol the machine with `mac` right now, if
+    /// it can't. `mac` is the one this node sees, `None` when the machine
+    /// isn't a neighbour.
+    pub fn refusal(&self, mac: Option<&str>, now: u64) -> Option<String> {
+        if self.expires_at > 0 && now >= self.expires_at {
+            return Some("Provisioning profile has expired".into());
+        }
+        if self.max_uses > 0 && self.uses >= self.max_uses {
+            return Some("Provisioning profile has no enrolments left".into());
+        }
+        if !self.allowed_macs.is_empty() {
+            let Some(mac) = mac else {
+                return Some("This profile only enrols listed MAC addresses, and the machine's can't be seen from this node — enrol it from the same network segment".into());
+            };
+            let allowed = normalize_mac(mac).map(|m| self.allowed_macs.contains(&m)).unwrap_or(false);
+            if !allowed {
+                return Some(format!("MAC address '{}' is not allowed by this profile", mac));
+            }
+        }
+        None
+    }
+
+    /// Prefix every artifact URL shares.
+    pub fn boot_base(&self) -> String {
+        format!("{}/api/provision/boot/{}", self.server_url, self.token)
+    }
+
+    fn image(&self) -> &'static OsImage {
+        os_image(&self.os).unwrap_or(&OS_IMAGES[0])
+    }
+
+    /// iPXE script: boot the distro installer pointed at our answers.
+    pub fn ipxe_script(&self) -> String {
+        let image = self.image();
+        let base = self.boot_base();
+        let args = if image.ubuntu {
+            let iso = if self.iso_url.is_empty() { image.iso } else { &self.iso_url };
+            format!("ip=dhcp url={} autoinstall ds=nocloud-net;s={}/ cloud-config-url=/dev/null", iso, base)
+        } else {
+            format!(
+                "auto=true priority=critical url={}/preseed.cfg netcfg/get_hostname={} netcfg/get_domain=local",
+                base, self.hostname_prefix
+            )
+        };
+        format!(
+            "#!ipxe\n\
+             # WolfStack provisioning — profile \"{name}\" ({label})\n\
+             dhcp\n\
+             kernel {kernel} initrd=initrd {args}\n\
+             initrd --name initrd {initrd}\n\
+             boot\n",
+            name = self.name, label = image.label,
+            kernel = image.kernel, initrd = image.initrd, args = args,
+        )
+    }
+
+    /// Shell command both installers run inside the target to fetch
+    /// `firstboot.sh` and register it as a one-shot unit.
+    fn late_command(&self) -> String {
+        format!(
+            "curl -fsSLk --retry 5 {}/firstboot.sh -o /usr/local/sbin/wolfstack-firstboot \
+             && chmod 700 /usr/local/sbin/wolfstack-firstboot \
+             && /usr/local/sbin/wolfstack-firstboot --install-unit",
+            self.boot_base()
+        )
+    }
+
+    /// Debian-installer preseed.
+    pub fn preseed(&self) -> String {
+        let mut out = format!(
+            "# WolfStack provisioning — profile \"{name}\"\n\
+             d-i debian-installer/locale string en_US.UTF-8\n\
+             d-i keyboard-configuration/xkb-keymap select us\n\
+             d-i netcfg/choose_interface select auto\n\
+             d-i netcfg/get_hostname string {prefix}\n\
+             d-i netcfg/get_domain string local\n\
+             d-i netcfg/hostname string {prefix}\n\
+             d-i mirror/country string manual\n\
+             d-i mirror/http/hostname string deb.debian.org\n\
+             d-i mirror/http/directory string /debian\n\
+             d-i mirror/http/proxy string\n\
+             d-i clock-setup/utc boolean true\n\
+             d-i time/zone string Etc/UTC\n\
+             d-i passwd/root-login boolean false\n\
+             d-i passwd/user-fullname string WolfStack Admin\n\
+             d-i passwd/username string {user}\n\
+             d-i passwd/user-password-crypted password {hash}\n",
+            name = self.name, prefix = self.hostname_prefix,
+            user = self.admin_user, hash = self.password_hash,
+        );
+        if !self.disk.is_empty() {
+            out.push_str(&format!("d-i partman-auto/disk string {}\n", self.disk));
+        }
+        out.push_str(
+            "d-i partman-auto/method string lvm\n\
+             d-i partman-auto-lvm/guided_size string max\n\
+             d-i partman-auto/choose_recipe select atomic\n\
+             d-i partman-lvm/device_remove_lvm boolean true\n\
+             d-i partman-md/device_remove_md boolean true\n\
+             d-i partman-lvm/confirm boolean true\n\
+             d-i partman-lvm/confirm_nooverwrite boolean true\n\
+             d-i partman-partitioning/confirm_write_new_label boolean true\n\
+             d-i partman/choose_partition select finish\n\
+             d-i partman/confirm boolean true\n\
+             d-i partman/confirm_nooverwrite boolean true\n\
+             d-i apt-setup/non-free-firmware boolean true\n\
+             tasksel tasksel/first multiselect standard, ssh-server\n\
+             d-i pkgsel/include string curl ca-certificates sudo\n\
+             d-i pkgsel/upgrade select full-upgrade\n\
+             popularity-contest popularity-contest/participate boolean false\n\
+             d-i grub-installer/only_debian boolean true\n\
+             d-i grub-installer/bootdev string default\n\
+             d-i finish-install/reboot_in_progress note\n",
+        );
+        out.push_str(&format!("d-i preseed/late_command string in-target sh -c '{}'\n", self.late_command()));
+        out
+    }
+
+    /// Ubuntu autoinstall user-data.
+    pub fn autoinstall_user_data(&self) -> String {
+        let storage_match = if self.disk.is_empty() {
+            String::new()
+        } else {
+            format!("\n      match:\n        path: \"{}\"", self.disk)
+        };
+        format!(
+            "#cloud-config\n\
+             # WolfStack provisioning — profile \"{name}\"\n\
+             autoinstall:\n\
+             \x20 version: 1\n\
+             \x20 locale: en_US.UTF-8\n\
+             \x20 keyboard:\n\
+             \x20   layout: us\n\
+             \x20 identity:\n\
+             \x20   hostname: \"{prefix}\"\n\
+             \x20   username: \"{user}\"\n\
+             \x20   password: \"{hash}\"\n\
+             \x20 ssh:\n\
+             \x20   install-server: true\n\
+             \x20   allow-pw: true\n\
+             \x20 storage:\n\
+             \x20   layout:\n\
+             \x20     name: lvm{storage_match}\n\
+             \x20 packages:\n\
+             \x20   - curl\n\
+             \x20   - ca-certificates\n\
+             \x20 late-commands:\n\
+             \x20   - curtin in-target --target=/target -- sh -c '{late}'\n",
+            name = self.name, prefix = self.hostname_prefix, user = self.admin_user,
+            hash = self.password_hash, storage_match = storage_match, late = self.late_command(),
+        )
+    }
+
+    pub fn meta_data(&self) -> String {
+        format!("instance-id: wolfstack-{}\nlocal-hostname: {}\n", self.id, self.hostname_prefix)
+    }
+
+    /// First-boot script: installs WolfStack via setup.sh, then enrols.
+    /// With `--install-unit` (run by the installer inside the target) it
+    /// only drops the SSH keys and registers itself as a one-shot unit.
+    pub fn firstboot_script(&self) -> String {
+        let keys = self.ssh_keys.join("\n");
+        format!(
+r#"#!/bin/bash
+# WolfStack first-boot provisioning — profile "{name}".
+# Runs once: installs WolfStack, then enrols this machine into the cluster.
+set -u
+BASE='{base}'
+SETUP_URL='{server}/api/install/setup.sh'
+GITHUB_SETUP='https://raw.githubusercontent.com/wolfsoftwaresystemsltd/WolfStack/master/setup.sh'
+ADMIN='{user}'
+PREFIX='{prefix}'
+PORT=8553
+MARKER=/etc/wolfstack/provisioned
+log() {{ echo "wolfstack-firstboot: $*"; }}
+
+if [ "${{1:-}}" = "--install-unit" ]; then
+    if [ -n '{has_keys}' ]; then
+        install -d -m 700 "/home/$ADMIN/.ssh"
+        cat >> "/home/$ADMIN/.ssh/authorized_keys" <<'KEYS'
+{keys}
+KEYS
+        chmod 600 "/home/$ADMIN/.ssh/authorized_keys"
+        chown -R "$ADMIN:" "/home/$ADMIN/.ssh"
+    fi
+    cat > /etc/systemd/system/wolfstack-firstboot.service <<'UNIT'
+[Unit]
+Description=WolfStack first-boot provisioning
+After=network-online.target
+Wants=network-online.target
+ConditionPathExists=!/etc/wolfstack/provisioned
+
+[Service]
+Type=oneshot
+ExecStart=/usr/local/sbin/wolfstack-firstboot
+RemainAfterExit=yes
+TimeoutStartSec=0
+
+[Install]
+WantedBy=multi-user.target
+UNIT
+    systemctl enable wolfstack-firstboot.service
+    exit 0
+fi
+
+[ -e "$MARKER" ] && exit 0
+
+IFACE=$(ip -o route show default | awk '{{print $5; exit}}')
+MAC=$(cat "/sys/class/net/$IFACE/address" 2>/dev/null || true)
+SUFFIX=$(printf '%s' "$MAC" | tr -d ':' | tail -c 6)
+if [ -n "$SUFFIX" ]; then
+    hostnamectl set-hostname "$PREFIX-$SUFFIX"
+fi
+
+if ! systemctl is-active --quiet wolfstack; then
+    log "installing WolfStack"
+    rm -f /tmp/wolfstack-setup.sh
+    (curl -fsSLk --max-time 60 "$SETUP_URL" -o /tmp/wolfstack-setup.sh \
+        || curl -fsSL --max-time 60 "$GITHUB_SETUP" -o /tmp/wolfstack-setup.sh) \
+        && [ -s /tmp/wolfstack-setup.sh ] \
+        && bash /tmp/wolfstack-setup.sh --yes || {{ log "setup.sh failed"; exit 1; }}
+fi
+
+for _ in $(seq 1 60); do
+    [ -s /etc/wolfstack/join-token ] && curl -sk -o /dev/null --max-time 5 "https://127.0.0.1:$PORT/" && break
+    sleep 5
+done
+
+umask 077
+KEY=$(od -An -tx1 -N32 /dev/urandom | tr -d ' \n')
+printf '%s' "$KEY" > /etc/wolfstack/provision-key
+JOIN_TOKEN=$(tr -d '[:space:]' < /etc/wolfstack/join-token)
+for _ in $(seq 1 30); do
+    BODY="{{\"hostname\":\"$(hostname)\",\"port\":$PORT,\"mac\":\"$MAC\",\"join_token\":\"$JOIN_TOKEN\",\"provision_key\":\"$KEY\"}}"
+    if curl -fsSk --max-time 120 -H 'Content-Type: application/json' -d "$BODY" "$BASE/enroll"; then
+        touch "$MARKER"
+        log "enrolled into the cluster"
+        exit 0
+    fi
+    log "enrolment failed, retrying"
+    sleep 20
+done
+exit 1
+"#,
+            name = self.name, base = self.boot_base(), server = self.server_url,
+            user = self.admin_user, prefix = self.hostname_prefix,
+            has_keys = if keys.is_empty() { "" } else { "1" }, keys = keys,
+        )
+    }
+
+    /// Profile as returned to the dashboard — enrolment token included
+    /// (admins need it for the DHCP config), password hash withheld.
+    pub fn to_public_json(&self) -> serde_json::Value {
+        let mut v = serde_json::to_value(self).unwrap_or_default();
+        if let Some(obj) = v.as_object_mut() {
+            obj.remove("password_hash");
+            obj.insert("has_password".into(), serde_json::json!(!self.password_hash.is_empty()));
+            obj.insert("boot_url".into(), serde_json::json!(format!("{}/boot.ipxe", self.boot_base())));
+        }
+        v
+    }
+}
+
+/// Create or update a profile. A blank `password_hash` on update keeps
+/// the stored one (the dashboard never sees it). Returns the saved profile.
+pub fn save_profile(mut profile: ProvisionProfile) -> Result<ProvisionProfile, String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let existing = cfg.profiles.iter().position(|p| !profile.id.is_empty() && p.id == profile.id);
+    match existing {
+        Some(i) => {
+            let old = &cfg.profiles[i];
+            if profile.password_hash.trim().is_empty() {
+                profile.password_hash = old.password_hash.clone();
+            }
+            profile.token = old.token.clone();
+            profile.uses = old.uses;
+            profile.created_at = old.created_at;
+            profile.enrollments = old.enrollments.clone();
+        }
+        None => {
+            profile.id = crate::cluster_join::generate_token()?[..12].to_string();
+            profile.token = crate::cluster_join::generate_token()?;
+            profile.uses = 0;
+            profile.created_at = now();
+            profile.enrollments.clear();
+        }
+    }
+    profile.validate()?;
+    match existing {
+        Some(i) => cfg.profiles[i] = profile.clone(),
+        None => cfg.profiles.push(profile.clone()),
+    }
+    cfg.save()?;
+    Ok(profile)
+}
+
+pub fn delete_profile(id: &str) -> Result<bool, String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let before = cfg.profiles.len();
+    cfg.profiles.retain(|p| p.id != id);
+    if cfg.profiles.len() == before { return Ok(false); }
+    cfg.save()?;
+    Ok(true)
+}
+
+/// Replace a profile's token, invalidating every artifact URL already
+/// configured in DHCP.
+pub fn rotate_token(id: &str) -> Result<Option<ProvisionProfile>, String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let Some(p) = cfg.profiles.iter_mut().find(|p| p.id == id) else { return Ok(None) };
+    p.token = crate::cluster_join::generate_token()?;
+    let out = p.clone();
+    cfg.save()?;
+    Ok(Some(out))
+}
+
+/// The hardware address the kernel's neighbour table holds for
+/// `address`. Only known for machines on a segment this node is attached
+/// to — across a router it's the router's, and the table has no entry.
+pub fn neighbour_mac(address: &str) -> Option<String> {
+    address.parse::<std::net::IpAddr>().ok()?;
+    let out = std::process::Command::new("ip").args(["neigh", "show", address]).output().ok()?;
+    parse_lladdr(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// `lladdr` from `ip neigh show` output, skipping failed entries.
+fn parse_lladdr(out: &str) -> Option<String> {
+    out.lines()
+        .filter(|line| !line.contains("FAILED") && !line.contains("INCOMPLETE"))
+        .find_map(|line| {
+            let mut words = line.split_whitespace();
+            words.find(|w| *w == "lladdr")?;
+            normalize_mac(words.next()?)
+        })
+}
+
+/// Check `profile_id` can enrol the machine with `mac` (see
+/// [`ProvisionProfile::refusal`]) and take one of its enrolments, under
+/// the store lock so concurrent machines can't both take the last one.
+/// A failed join hands it back with [`release_use`].
+pub fn reserve_use(profile_id: &str, mac: Option<&str>, now: u64) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let p = cfg.profiles.iter_mut().find(|p| p.id == profile_id)
+        .ok_or_else(|| "Provisioning profile not found".to_string())?;
+    if let Some(reason) = p.refusal(mac, now) {
+        return Err(reason);
+    }
+    p.uses = p.uses.saturating_add(1);
+    cfg.save()
+}
+
+/// Give back an enrolment [`reserve_use`] took for a join that failed.
+pub fn release_use(profile_id: &str) {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let Some(p) = cfg.profiles.iter_mut().find(|p| p.id == profile_id) else { return };
+    p.uses = p.uses.saturating_sub(1);
+    if let Err(e) = cfg.save() {
+        tracing::warn!("provisioning: could not release enrolment: {}", e);
+    }
+}
+
+/// Record an enrolment attempt in the profile's history.
+pub fn record_enrollment(profile_id: &str, entry: Enrollment) {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cfg = ProvisioningConfig::load();
+    let Some(p) = cfg.profiles.iter_mut().find(|p| p.id == profile_id) else { return };
+    p.enrollments.push(entry);
+    if p.enrollments.len() > MAX_ENROLLMENTS {
+        let excess = p.enrollments.len() - MAX_ENROLLMENTS;
+        p.enrollments.drain(..excess);
+    }
+    if let Err(e) = cfg.save() {
+        tracing::warn!("provisioning: could not record enrolment: {}", e);
+    }
+}
+
+/// Target side: does `provided` match the key `firstboot.sh` left on
+/// this machine?
+pub fn verify_local_key(provided: &str) -> bool {
+    let Ok(stored) = std::fs::read_to_string(PROVISION_KEY_FILE) else { return false };
+    let stored = stored.trim();
+    !stored.is_empty() && crate::auth::validate_cluster_secret(provided, stored)
+}
+
+/// Target side: the key is single-use — drop it once a join succeeds.
+pub fn clear_local_key() {
+    let _ = std::fs::remove_file(PROVISION_KEY_FILE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> ProvisionProfile {
+        ProvisionProfile {
+            id: "abc".into(),
+            name: "Rack 4".into(),
+            token: "t0k".into(),
+            server_url: "http://10.0.0.5:8554/".into(),
+            os: "debian-12".into(),
+            hostname_prefix: "Node".into(),
+            admin_user: "wolfadmin".into(),
+            password_hash: "$6$salt$abcdef./".into(),
+            ssh_keys: vec!["ssh-ed25519 AAAAC3 ops@example".into()],
+            allowed_macs: vec!["AA-BB-CC-00-11-22".into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_normalises_and_rejects_injection() {
+        let mut p = profile();
+        p.validate().unwrap();
+        assert_eq!(p.server_url, "http://10.0.0.5:8554");
+        assert_eq!(p.hostname_prefix, "node");
+        assert_eq!(p.allowed_macs, vec!["aa:bb:cc:00:11:22"]);
+
+        let mut bad = profile();
+        bad.server_url = "http://x/';reboot;'".into();
+        assert!(bad.validate().is_err());
+        let mut bad = profile();
+        bad.ssh_keys = vec!["ssh-rsa AAA'\nrm -rf /".into()];
+        assert!(bad.validate().is_err());
+        let mut bad = profile();
+        bad.password_hash = "plaintext".into();
+        assert!(bad.validate().is_err());
+        let mut bad = profile();
+        bad.admin_user = "root".into();
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn refusal_covers_expiry_uses_and_macs() {
+        let mut p = profile();
+        p.validate().unwrap();
+        assert!(p.refusal(Some("aa:bb:cc:00:11:22"), 100).is_none());
+        assert!(p.refusal(Some("aa:bb:cc:00:11:23"), 100).is_some());
+        // A MAC this node can't see can't be checked.
+        assert!(p.refusal(None, 100).is_some());
+        p.expires_at = 50;
+        assert!(p.refusal(Some("aa:bb:cc:00:11:22"), 100).is_some());
+        p.expires_at = 0;
+        p.max_uses = 1;
+        p.uses = 1;
+        assert!(p.refusal(Some("aa:bb:cc:00:11:22"), 100).is_some());
+        p.allowed_macs.clear();
+        p.uses = 0;
+        assert!(p.refusal(None, 100).is_none());
+    }
+
+    #[test]
+    fn neighbour_table_lladdr_is_parsed() {
+        assert_eq!(
+            parse_lladdr("10.0.0.9 dev eno1 lladdr AA:BB:CC:00:11:22 REACHABLE\n"),
+            Some("aa:bb:cc:00:11:22".into())
+        );
+        assert_eq!(parse_lladdr("10.0.0.9 dev eno1 FAILED\n"), None);
+        assert_eq!(parse_lladdr(""), None);
+    }
+
+    #[test]
+    fn artifacts_point_back_at_the_profile() {
+        let mut p = profile();
+        p.validate().unwrap();
+        let base = "http://10.0.0.5:8554/api/provision/boot/t0k";
+        assert!(p.ipxe_script().contains(&format!("url={}/preseed.cfg", base)));
+        assert!(p.preseed().contains(&format!("{}/firstboot.sh", base)));
+        assert!(p.firstboot_script().contains(&format!("BASE='{}'", base)));
+        p.os = "ubuntu-24.04".into();
+        assert!(p.ipxe_script().contains(&format!("ds=nocloud-net;s={}/", base)));
+        assert!(p.autoinstall_user_data().contains("username: \"wolfadmin\""));
+        assert!(p.to_public_json().get("password_hash").is_none());
+    }
+}
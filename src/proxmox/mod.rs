@@ -620,6 +620,20 @@ impl PveClient {
         Ok("standalone".to_string())
     }
 
+    /// Every node, guest and storage in the cluster in one call.
+    /// Source: GET /cluster/resources.
+    pub async fn cluster_resources(&self) -> Result<Vec<serde_json::Value>, String> {
+        let data = self.get("/cluster/resources").await?;
+        Ok(data.as_array().cloned().unwrap_or_default())
+    }
+
+    /// HA manager state: quorum, master, LRMs and managed services.
+    /// Source: GET /cluster/ha/status/current.
+    pub async fn ha_status(&self) -> Result<Vec<serde_json::Value>, String> {
+        let data = self.get("/cluster/ha/status/current").await?;
+        Ok(data.as_array().cloned().unwrap_or_default())
+    }
+
     // ─── Pool-driver support: VM clone, cloud-init, lifecycle ──
     //
     // The methods below back `pools::proxmox_driver`. They mirror
@@ -920,3 +934,227 @@ pub async fn poll_pve_node(
 
     Ok((status, lxc_count, vm_count, cluster_name, guests))
 }
+
+// ─── Cluster rollups ───
+//
+// One summary per PVE cluster, built from a single member's
+// /cluster/resources (PVE already aggregates the whole cluster there)
+// instead of adding up per-node polls. Cached for CLUSTER_CACHE_TTL so
+// every dashboard refresh doesn't hit the PVE API.
+
+const CLUSTER_CACHE_TTL: Duration = Duration::from_secs(30);
+
+type CachedSummary = (std::time::Instant, PveClusterSummary);
+
+static CLUSTER_CACHE: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, CachedSummary>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+/// HA manager rollup for one cluster.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PveHaSummary {
+    /// None when the cluster has no HA status (standalone node, no rights).
+    pub quorate: Option<bool>,
+    pub services: u32,
+    pub started: u32,
+    /// `sid` of services in error, fence or recovery.
+    pub problems: Vec<String>,
+}
+
+/// Cluster-wide totals for one PVE cluster.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PveClusterSummary {
+    /// Sidebar grouping key: pve_cluster_name, else cluster_name, else address.
+    pub name: String,
+    /// WolfStack node ids of the members with PVE credentials.
+    pub member_ids: Vec<String>,
+    pub nodes_total: u32,
+    pub nodes_online: u32,
+    pub cores_total: u32,
+    /// Cores busy right now (sum of cpu fraction × cores over online nodes).
+    pub cores_used: f64,
+    pub mem_total: u64,
+    pub mem_used: u64,
+    /// Shared storages are counted once, not once per node.
+    pub storage_total: u64,
+    pub storage_used: u64,
+    pub vms_total: u32,
+    pub vms_running: u32,
+    pub cts_total: u32,
+    pub cts_running: u32,
+    pub ha: PveHaSummary,
+    /// Member the data came from.
+    pub source_node: Option<String>,
+    pub updated_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Roll /cluster/resources and /cluster/ha/status/current up into totals.
+pub fn summarize_cluster(resources: &[serde_json::Value], ha: &[serde_json::Value]) -> PveClusterSummary {
+    let mut s = PveClusterSummary::default();
+    let mut storages = std::collections::HashSet::new();
+    let str_of = |v: &serde_json::Value, k: &str| v.get(k).and_then(|x| x.as_str()).unwrap_or("").to_string();
+    let u64_of = |v: &serde_json::Value, k: &str| v.get(k).and_then(|x| x.as_u64()).unwrap_or(0);
+    for r in resources {
+        let running = str_of(r, "status") == "running";
+        match str_of(r, "type").as_str() {
+            "node" => {
+                s.nodes_total += 1;
+                if str_of(r, "status") == "online" {
+                    s.nodes_online += 1;
+                    let maxcpu = u64_of(r, "maxcpu") as u32;
+                    s.cores_total += maxcpu;
+                    s.cores_used += r.get("cpu").and_then(|x| x.as_f64()).unwrap_or(0.0) * maxcpu as f64;
+                    s.mem_total += u64_of(r, "maxmem");
+                    s.mem_used += u64_of(r, "mem");
+                }
+            }
+            "qemu" | "lxc" if u64_of(r, "template") == 1 => {}
+            "qemu" => {
+                s.vms_total += 1;
+                if running { s.vms_running += 1; }
+            }
+            "lxc" => {
+                s.cts_total += 1;
+                if running { s.cts_running += 1; }
+            }
+            "storage" => {
+                if str_of(r, "status") != "available" { continue; }
+                let key = if u64_of(r, "shared") == 1 { str_of(r, "storage") } else { str_of(r, "id") };
+                if storages.insert(key) {
+                    s.storage_total += u64_of(r, "maxdisk");
+                    s.storage_used += u64_of(r, "disk");
+                }
+            }
+            _ => {}
+        }
+    }
+    for h in ha {
+        match str_of(h, "type").as_str() {
+            "quorum" => s.ha.quorate = Some(u64_of(h, "quorate") == 1),
+            "service" => {
+                s.ha.services += 1;
+                match str_of(h, "state").as_str() {
+                    "started" => s.ha.started += 1,
+                    "error" | "fence" | "recovery" => s.ha.problems.push(str_of(h, "sid")),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    s
+}
+
+/// Sidebar grouping key for a node with PVE credentials.
+pub fn cluster_key(node: &crate::agent::Node) -> String {
+    node.pve_cluster_name.clone().filter(|s| !s.is_empty())
+        .or_else(|| node.cluster_name.clone().filter(|s| !s.is_empty()))
+        .unwrap_or_else(|| node.address.clone())
+}
+
+/// PVE API port for a node: proxmox entries store it, WolfStack nodes on a
+/// PVE host use the default (same rule as the pool driver).
+fn api_port(node: &crate::agent::Node) -> u16 {
+    if node.port == 8553 { 8006 } else { node.port }
+}
+
+/// Fetch one cluster's rollup from the first member that answers.
+async fn fetch_cluster(name: String, members: Vec<crate::agent::Node>) -> PveClusterSummary {
+    let mut last_err = String::from("no member with PVE credentials");
+    for node in &members {
+        let (Some(token), Some(pve_name)) = (node.pve_token.as_deref(), node.pve_node_name.as_deref()) else { continue };
+        let client = PveClient::new(&node.address, api_port(node), token, node.pve_fingerprint.as_deref(), pve_name);
+        let (res, ha) = tokio::join!(client.cluster_resources(), client.ha_status());
+        match res {
+            Ok(resources) => {
+                // HA status needs Sys.Audit on / — a token without it still
+                // gets the resource totals.
+                let mut s = summarize_cluster(&resources, &ha.unwrap_or_default());
+                s.source_node = Some(node.id.clone());
+                s.name = name;
+                s.member_ids = members.iter().map(|n| n.id.clone()).collect();
+                s.updated_at = chrono::Utc::now().timestamp() as u64;
+                return s;
+            }
+            Err(e) => last_err = e,
+        }
+    }
+    PveClusterSummary {
+        name,
+        member_ids: members.iter().map(|n| n.id.clone()).collect(),
+        updated_at: chrono::Utc::now().timestamp() as u64,
+        error: Some(last_err),
+        ..Default::default()
+    }
+}
+
+/// Rollups for every PVE cluster among `nodes` (members with a PVE token
+/// and node name), served from cache unless older than the TTL or
+/// `refresh` is set.
+pub async fn cluster_summaries(nodes: &[crate::agent::Node], refresh: bool) -> Vec<PveClusterSummary> {
+    let mut groups: std::collections::BTreeMap<String, Vec<crate::agent::Node>> = std::collections::BTreeMap::new();
+    for n in nodes.iter().filter(|n| n.pve_token.is_some() && n.pve_node_name.is_some()) {
+        groups.entry(cluster_key(n)).or_default().push(n.clone());
+    }
+    let mut out = Vec::new();
+    let mut stale = Vec::new();
+    {
+        let cache = CLUSTER_CACHE.lock().unwrap_or_else(|p| p.into_inner());
+        for (name, members) in groups {
+            match cache.get(&name) {
+                Some((at, s)) if !refresh && at.elapsed() < CLUSTER_CACHE_TTL => out.push(s.clone()),
+                _ => stale.push(fetch_cluster(name, members)),
+            }
+        }
+    }
+    let fresh = futures::future::join_all(stale).await;
+    {
+        let mut cache = CLUSTER_CACHE.lock().unwrap_or_else(|p| p.into_inner());
+        let now = std::time::Instant::now();
+        for s in &fresh {
+            cache.insert(s.name.clone(), (now, s.clone()));
+        }
+    }
+    out.extend(fresh);
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn cluster_rollup_totals() {
+        let resources = vec![
+            json!({"type": "node", "node": "pve1", "status": "online", "maxcpu": 8, "cpu": 0.5, "maxmem": 1000, "mem": 400}),
+            json!({"type": "node", "node": "pve2", "status": "online", "maxcpu": 4, "cpu": 0.25, "maxmem": 500, "mem": 100}),
+            json!({"type": "node", "node": "pve3", "status": "offline", "maxcpu": 16, "maxmem": 9999}),
+            json!({"type": "qemu", "vmid": 100, "status": "running"}),
+            json!({"type": "qemu", "vmid": 101, "status": "stopped"}),
+            json!({"type": "qemu", "vmid": 9000, "status": "stopped", "template": 1}),
+            json!({"type": "lxc", "vmid": 200, "status": "running"}),
+            json!({"type": "storage", "id": "storage/pve1/ceph", "storage": "ceph", "shared": 1, "status": "available", "maxdisk": 100, "disk": 40}),
+            json!({"type": "storage", "id": "storage/pve2/ceph", "storage": "ceph", "shared": 1, "status": "available", "maxdisk": 100, "disk": 40}),
+            json!({"type": "storage", "id": "storage/pve1/local", "storage": "local", "shared": 0, "status": "available", "maxdisk": 50, "disk": 10}),
+            json!({"type": "storage", "id": "storage/pve2/local", "storage": "local", "shared": 0, "status": "available", "maxdisk": 50, "disk": 20}),
+            json!({"type": "storage", "id": "storage/pve3/local", "storage": "local", "shared": 0, "status": "unknown", "maxdisk": 50}),
+        ];
+        let ha = vec![
+            json!({"type": "quorum", "quorate": 1}),
+            json!({"type": "service", "sid": "vm:100", "state": "started"}),
+            json!({"type": "service", "sid": "ct:200", "state": "error"}),
+        ];
+        let s = summarize_cluster(&resources, &ha);
+        assert_eq!((s.nodes_total, s.nodes_online, s.cores_total), (3, 2, 12));
+        assert!((s.cores_used - 5.0).abs() < 1e-9);
+        assert_eq!((s.mem_total, s.mem_used), (1500, 500));
+        assert_eq!((s.storage_total, s.storage_used), (200, 70));
+        assert_eq!((s.vms_total, s.vms_running, s.cts_total, s.cts_running), (2, 1, 1, 1));
+        assert_eq!(s.ha.quorate, Some(true));
+        assert_eq!((s.ha.services, s.ha.started), (2, 1));
+        assert_eq!(s.ha.problems, vec!["ct:200".to_string()]);
+    }
+}
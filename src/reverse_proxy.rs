@@ -13,13 +13,38 @@
 //! the auto-detection can't cover — typically subpath proxying (e.g.
 //! `https://example.com/wolfstack/` → `:8553/`) or when the admin UI is
 //! reached via a different host than the public status pages.
+//!
+//! It also makes the daemon itself proxy-aware ([`resolve`]):
+//!
+//! - **Trusted proxies.** Requests from a listed proxy have their client
+//!   address taken from `X-Forwarded-For`, so the login limiter, audit
+//!   log, lockouts and loopback checks see the real client instead of the
+//!   proxy. `X-Forwarded-Proto` / `-Host` from them are honoured. Once
+//!   any proxy is listed, those headers are stripped from everyone else —
+//!   a client talking to the port directly can't claim to be someone else.
+//!   With none listed, behaviour is as before.
+//! - **Base path.** The dashboard can be served under a prefix
+//!   (`/wolfstack`). Requests arrive with or without it (whether or not
+//!   the proxy strips it), redirects get it added back, and HTML pages
+//!   get it on their asset links plus a small script that adds it to the
+//!   page's `fetch`, XHR, WebSocket and EventSource URLs ([`rewrite_html`]).
+//! - **CORS.** API-only deployments can let listed origins call the API
+//!   from a browser. Credentials are never allowed cross-origin — such
+//!   callers authenticate with an API key, not the session cookie.
 
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::{header, Method, Uri};
+use actix_web::middleware::Next;
+use actix_web::HttpResponse;
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
 use std::path::Path;
+use std::sync::{Arc, LazyLock, RwLock};
 
 const CONFIG_PATH: &str = "/etc/wolfstack/reverse-proxy.json";
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct ReverseProxyConfig {
     /// Absolute URL (no trailing slash) to prepend when building public
     /// links — e.g. `https://status.example.com` or
@@ -27,6 +52,42 @@ pub struct ReverseProxyConfig {
     /// fall back to the browser's current origin.
     #[serde(default)]
     pub public_base_url: String,
+    /// Proxy addresses (IP or CIDR) whose `X-Forwarded-*` headers are
+    /// believed. Empty = none; forwarded headers are passed through as-is.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+    /// Path prefix the dashboard is served under, e.g. `/wolfstack`.
+    /// Empty = served at the root.
+    #[serde(default)]
+    pub base_path: String,
+    /// Origins (`https://app.example.com`, or `*`) allowed to call the API
+    /// cross-origin. Empty = same-origin only.
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+}
+
+/// Config in force, so the per-request middleware doesn't read the file.
+static CURRENT: LazyLock<RwLock<Arc<ReverseProxyConfig>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(ReverseProxyConfig::load().normalised())));
+
+/// The saved config, normalised.
+pub fn current() -> Arc<ReverseProxyConfig> {
+    CURRENT.read().unwrap().clone()
+}
+
+/// Parse an `IP` or `IP/prefix` entry.
+fn parse_net(entry: &str) -> Option<(IpAddr, u8)> {
+    match entry.split_once('/') {
+        Some((ip, prefix)) => {
+            let ip: IpAddr = ip.parse().ok()?;
+            let prefix: u8 = prefix.parse().ok()?;
+            (prefix <= if ip.is_ipv4() { 32 } else { 128 }).then_some((ip, prefix))
+        }
+        None => {
+            let ip: IpAddr = entry.parse().ok()?;
+            Some((ip, if ip.is_ipv4() { 32 } else { 128 }))
+        }
+    }
 }
 
 impl ReverseProxyConfig {
@@ -42,13 +103,322 @@ impl ReverseProxyConfig {
             std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
         let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
-        std::fs::write(CONFIG_PATH, json).map_err(|e| e.to_string())
+        std::fs::write(CONFIG_PATH, json).map_err(|e| e.to_string())?;
+        *CURRENT.write().unwrap() = Arc::new(self.normalised());
+        Ok(())
     }
 
     /// Normalise trailing slashes off so callers can always `format!("{base}/status/{slug}")`.
     pub fn normalised(&self) -> Self {
+        let list = |v: &[String]| v.iter()
+            .map(|s| s.trim().trim_end_matches('/').to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let base = self.base_path.trim().trim_matches('/');
         Self {
             public_base_url: self.public_base_url.trim().trim_end_matches('/').to_string(),
+            trusted_proxies: list(&self.trusted_proxies),
+            base_path: if base.is_empty() { String::new() } else { format!("/{}", base) },
+            cors_origins: list(&self.cors_origins),
+        }
+    }
+
+    /// Check a normalised config before it is saved.
+    pub fn validate(&self) -> Result<(), String> {
+        let u = &self.public_base_url;
+        if !u.is_empty() {
+            if !(u.starts_with("http://") || u.starts_with("https://")) {
+                return Err("public_base_url must start with http:// or https://".into());
+            }
+            if u.contains(' ') || u.contains('\n') {
+                return Err("public_base_url must not contain whitespace".into());
+            }
+        }
+        for p in &self.trusted_proxies {
+            if parse_net(p).is_none() {
+                return Err(format!("trusted proxy '{}' is not an IP address or CIDR", p));
+            }
+        }
+        if !self.base_path.is_empty()
+            && (!self.base_path.chars().all(|c| c.is_ascii_alphanumeric() || "/-_.~".contains(c))
+                || self.base_path.split('/').any(|s| s == "." || s == ".."))
+        {
+            return Err(format!("base_path '{}' may only contain letters, digits and / - _ . ~", self.base_path));
+        }
+        for o in &self.cors_origins {
+            let ok = o == "*" || o.split_once("://").is_some_and(|(scheme, host)| {
+                matches!(scheme, "http" | "https") && !host.is_empty() && !host.contains(['/', ' ', '*'])
+            });
+            if !ok {
+                return Err(format!("CORS origin '{}' must be '*' or scheme://host[:port] with no path", o));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.trusted_proxies.iter()
+            .filter_map(|p| parse_net(p))
+            .any(|(net, prefix)| crate::auth::ip_in_cidr(&ip, &net, prefix))
+    }
+
+    /// The client behind `peer`: walk `X-Forwarded-For` from the right,
+    /// skipping our own trusted proxies, and stop at the first hop we
+    /// don't trust — anything left of it was written by the client.
+    fn client_addr(&self, peer: IpAddr, forwarded_for: &str) -> IpAddr {
+        let mut client = peer;
+        for hop in forwarded_for.rsplit(',') {
+            if !self.is_trusted_proxy(client) {
+                break;
+            }
+            match crate::netaddr::strip_port(hop).parse::<IpAddr>() {
+                Ok(ip) => client = ip.to_canonical(),
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    fn cors_allows(&self, origin: &str) -> bool {
+        self.cors_origins.iter().any(|o| o == "*" || o.eq_ignore_ascii_case(origin))
+    }
+
+    /// `path` with the base path taken off, if it carries it.
+    fn strip_base<'a>(&self, path: &'a str) -> Option<&'a str> {
+        if self.base_path.is_empty() {
+            return None;
+        }
+        match path.strip_prefix(self.base_path.as_str())? {
+            "" => Some("/"),
+            rest if rest.starts_with('/') => Some(rest),
+            _ => None,
+        }
+    }
+}
+
+const FORWARDED_HEADERS: &[&str] = &[
+    "X-Forwarded-For", "X-Forwarded-Proto", "X-Forwarded-Host", "X-Real-IP", "Forwarded",
+];
+
+/// Apply trusted-proxy, base-path and CORS handling. Registered
+/// outermost, before anything reads the peer address or the path.
+pub async fn resolve(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let cfg = current();
+
+    if !cfg.trusted_proxies.is_empty()
+        && let Some(peer) = req.peer_addr()
+    {
+        if cfg.is_trusted_proxy(peer.ip()) {
+            let xff = req.headers().get("X-Forwarded-For")
+                .or_else(|| req.headers().get("X-Real-IP"))
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let client = cfg.client_addr(peer.ip(), &xff);
+            req.head_mut().peer_addr = Some(SocketAddr::new(client, peer.port()));
+        } else {
+            for h in FORWARDED_HEADERS {
+                req.headers_mut().remove(*h);
+            }
+        }
+    }
+
+    if let Some(stripped) = cfg.strip_base(req.path()) {
+        let pq = match req.uri().query() {
+            Some(q) => format!("{}?{}", stripped, q),
+            None => stripped.to_string(),
+        };
+        let mut parts = req.head().uri.clone().into_parts();
+        parts.path_and_query = pq.parse().ok();
+        if let Ok(uri) = Uri::from_parts(parts) {
+            req.match_info_mut().get_mut().update(&uri);
+            req.head_mut().uri = uri;
+        }
+    }
+
+    let origin = req.headers().get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+        .filter(|o| cfg.cors_allows(o))
+        .map(String::from);
+    if let Some(origin) = &origin
+        && req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        let requested = req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS).cloned();
+        let mut preflight = HttpResponse::NoContent();
+        preflight
+            .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.as_str()))
+            .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, PUT, PATCH, DELETE, OPTIONS"))
+            .insert_header((header::ACCESS_CONTROL_MAX_AGE, "600"))
+            .insert_header((header::VARY, "Origin"));
+        if let Some(h) = requested {
+            preflight.insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, h));
+        }
+        return Ok(req.into_response(preflight.finish()));
+    }
+
+    let mut res = next.call(req).await?.map_into_boxed_body();
+    if let Some(origin) = origin
+        && let Ok(v) = header::HeaderValue::from_str(&origin)
+    {
+        let h = res.headers_mut();
+        h.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, v);
+        h.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, header::HeaderValue::from_static("X-Request-Id"));
+        h.append(header::VARY, header::HeaderValue::from_static("Origin"));
+    }
+    if !cfg.base_path.is_empty()
+        && let Some(loc) = res.headers().get(header::LOCATION).and_then(|v| v.to_str().ok())
+        && loc.starts_with('/') && !loc.starts_with("//") && cfg.strip_base(loc).is_none()
+        && let Ok(v) = header::HeaderValue::from_str(&format!("{}{}", cfg.base_path, loc))
+    {
+        res.headers_mut().insert(header::LOCATION, v);
+    }
+    Ok(res)
+}
+
+/// Script injected into HTML pages under a base path: puts the prefix on
+/// same-host URLs the page's scripts request.
+const BASE_PATH_SHIM: &str = r#"<script>(function(){var B=__BASE__;
+function fix(u){if(typeof u!=='string'&&!(u instanceof URL))return u;try{var x=new URL(u,location.href);
+if(x.host===location.host&&x.pathname!==B&&x.pathname.indexOf(B+'/')!==0){x.pathname=B+x.pathname;return x.href;}}catch(e){}return u;}
+var f=window.fetch;window.fetch=function(i,o){return f.call(this,fix(i),o);};
+var op=XMLHttpRequest.prototype.open;XMLHttpRequest.prototype.open=function(m,u){arguments[1]=fix(u);return op.apply(this,arguments);};
+var wo=window.open;window.open=function(u){if(u)arguments[0]=fix(u);return wo.apply(this,arguments);};
+['WebSocket','EventSource'].forEach(function(n){var C=window[n];if(!C)return;
+var W=function(u,p){return p===undefined?new C(fix(u)):new C(fix(u),p);};W.prototype=C.prototype;
+['CONNECTING','OPEN','CLOSING','CLOSED'].forEach(function(k){if(k in C)W[k]=C[k];});window[n]=W;});
+window.WOLFSTACK_BASE_PATH=B;})();</script>"#;
+
+/// `html` with root-relative `src`/`href`/`action` links prefixed with
+/// `base` and the URL shim injected at the top of `<head>`.
+fn rewrite_html_body(html: &str, base: &str) -> String {
+    let mut out = html.to_string();
+    for attr in ["src=\"/", "href=\"/", "action=\"/", "src='/", "href='/"] {
+        let quote = &attr[attr.len() - 2..attr.len() - 1];
+        let name = &attr[..attr.len() - 2];
+        let prefixed = format!("{}{}{}/", name, quote, base);
+        let already = format!("{}/", base.trim_start_matches('/'));
+        // Protocol-relative `//host` links are left alone.
+        let mut rebuilt = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        while let Some(i) = rest.find(attr) {
+            let after = &rest[i + attr.len()..];
+            rebuilt.push_str(&rest[..i]);
+            if after.starts_with('/') || after.starts_with(&already) {
+                rebuilt.push_str(attr);
+            } else {
+                rebuilt.push_str(&prefixed);
+            }
+            rest = after;
         }
+        rebuilt.push_str(rest);
+        out = rebuilt;
+    }
+    let shim = BASE_PATH_SHIM.replace("__BASE__", &serde_json::to_string(base).unwrap_or_default());
+    match out.find("<head>") {
+        Some(i) => out.insert_str(i + "<head>".len(), &shim),
+        None => out.insert_str(0, &shim),
+    }
+    out
+}
+
+/// Rewrite HTML responses for the base path. Registered inside
+/// `Compress` so it sees the uncompressed page.
+pub async fn rewrite_html(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let base = current().base_path.clone();
+    let res = next.call(req).await?.map_into_boxed_body();
+    let is_html = res.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|c| c.starts_with("text/html"));
+    if base.is_empty() || !is_html || res.status() != actix_web::http::StatusCode::OK {
+        return Ok(res);
+    }
+    let (req, res) = res.into_parts();
+    let (mut res, body) = res.into_parts();
+    let bytes = actix_web::body::to_bytes(body).await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
+    let html = rewrite_html_body(&String::from_utf8_lossy(&bytes), &base);
+    // The rewritten page depends on the base path, not just the file.
+    for h in [header::ETAG, header::LAST_MODIFIED, header::CONTENT_LENGTH] {
+        res.headers_mut().remove(h);
+    }
+    Ok(ServiceResponse::new(req, res.set_body(html).map_into_boxed_body()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(proxies: &[&str]) -> ReverseProxyConfig {
+        ReverseProxyConfig {
+            trusted_proxies: proxies.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn forwarded_for_stops_at_the_first_untrusted_hop() {
+        let c = cfg(&["10.0.0.0/8", "::1"]);
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        // Client → edge proxy (10.0.0.2) → local proxy (10.0.0.1) → us.
+        assert_eq!(c.client_addr(ip("10.0.0.1"), "203.0.113.7, 10.0.0.2"), ip("203.0.113.7"));
+        // A spoofed entry the client prepended is left of the real one.
+        assert_eq!(c.client_addr(ip("10.0.0.1"), "1.2.3.4, 203.0.113.7"), ip("203.0.113.7"));
+        // Untrusted peer: headers are ignored.
+        assert_eq!(c.client_addr(ip("198.51.100.9"), "1.2.3.4"), ip("198.51.100.9"));
+        assert_eq!(c.client_addr(ip("::ffff:10.0.0.1"), "[2001:db8::5]:4711"), ip("2001:db8::5"));
+    }
+
+    #[test]
+    fn normalise_and_validate() {
+        let c = ReverseProxyConfig {
+            base_path: " wolfstack/ ".into(),
+            trusted_proxies: vec!["127.0.0.1".into(), " ".into()],
+            cors_origins: vec!["https://app.example.com/".into()],
+            ..Default::default()
+        }.normalised();
+        assert_eq!(c.base_path, "/wolfstack");
+        assert_eq!(c.trusted_proxies, vec!["127.0.0.1"]);
+        assert_eq!(c.cors_origins, vec!["https://app.example.com"]);
+        assert!(c.validate().is_ok());
+
+        for bad in [
+            ReverseProxyConfig { trusted_proxies: vec!["10.0.0.0/33".into()], ..Default::default() },
+            ReverseProxyConfig { base_path: "/a/../b".into(), ..Default::default() },
+            ReverseProxyConfig { base_path: "/a b".into(), ..Default::default() },
+            ReverseProxyConfig { cors_origins: vec!["https://a.com/path".into()], ..Default::default() },
+            ReverseProxyConfig { cors_origins: vec!["a.com".into()], ..Default::default() },
+        ] {
+            assert!(bad.validate().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn base_path_is_stripped_only_at_a_segment_boundary() {
+        let c = ReverseProxyConfig { base_path: "/ws".into(), ..Default::default() };
+        assert_eq!(c.strip_base("/ws/api/nodes"), Some("/api/nodes"));
+        assert_eq!(c.strip_base("/ws"), Some("/"));
+        assert_eq!(c.strip_base("/wsx/api"), None);
+        assert_eq!(c.strip_base("/api/nodes"), None);
+    }
+
+    #[test]
+    fn html_links_get_the_prefix() {
+        let out = rewrite_html_body(
+            r#"<head><script src="/js/app.js"></script><a href="//cdn.example/x"><link href="/ws/css/a.css"><a href="https://x/">"#,
+            "/ws",
+        );
+        assert!(out.contains(r#"src="/ws/js/app.js""#));
+        assert!(out.contains(r#"href="//cdn.example/x""#));
+        assert!(out.contains(r#"href="/ws/css/a.css""#) && !out.contains("/ws/ws/"));
+        assert!(out.contains(r#"href="https://x/""#));
+        assert!(out.starts_with("<head><script>(function(){var B=\"/ws\";"));
     }
 }
@@ -246,6 +246,9 @@ pub struct ReencryptReport {
     pub dns_providers: usize,
     pub cloud_providers: usize,
     pub xo_tokens: usize,
+    pub pve_tokens: usize,
+    /// SMTP / LLM / S3 / PBS / SMB credentials in the config files.
+    pub config_secrets: usize,
     /// Human-readable per-store errors, e.g. "oidc: parse failed". Empty
     /// on a fully-clean pass. A non-empty list is NOT fatal: rotation
     /// already committed the new secret; the affected store's blobs are
@@ -263,6 +266,8 @@ impl ReencryptReport {
             dns_providers: 0,
             cloud_providers: 0,
             xo_tokens: 0,
+            pve_tokens: 0,
+            config_secrets: 0,
             errors: Vec::new(),
         }
     }
@@ -275,6 +280,8 @@ impl ReencryptReport {
             + self.dns_providers
             + self.cloud_providers
             + self.xo_tokens
+            + self.pve_tokens
+            + self.config_secrets
     }
 }
 
@@ -342,13 +349,26 @@ pub fn reencrypt_all_at_rest(old: &str, new: &str) -> ReencryptReport {
         }
     }
 
+    // PVE API tokens in the persisted cluster state (cluster.json).
+    match crate::agent::reencrypt_cluster_state_at_rest(old, new) {
+        Ok(n) => report.pve_tokens = n,
+        Err(e) => report.errors.push(format!("cluster-state: {}", e)),
+    }
+
+    // Credentials sealed inside the config files (ai / storage / backup / PBS).
+    match crate::secrets::reencrypt_at_rest(old, new) {
+        Ok(n) => report.config_secrets = n,
+        Err(e) => report.errors.push(format!("config-secrets: {}", e)),
+    }
+
     if report.errors.is_empty() {
         tracing::info!(target: "secret_rotation",
             "at-rest re-encrypt complete: re-keyed {} SQL password(s), {} OIDC secret(s), \
              {} integration credential(s), {} DNS provider(s), {} cloud provider(s), \
-             {} XO token(s) — total {}",
+             {} XO token(s), {} PVE token(s), {} config credential(s) — total {}",
             report.sql_passwords, report.oidc_secrets, report.integration_credentials,
-            report.dns_providers, report.cloud_providers, report.xo_tokens, report.total());
+            report.dns_providers, report.cloud_providers, report.xo_tokens, report.pve_tokens,
+            report.config_secrets, report.total());
     } else {
         // A partial failure is non-fatal but must be loud — the operator
         // may need to re-enter a credential for the failed store(s).
@@ -1159,6 +1179,8 @@ pub fn run_startup_at_rest_migration() {
         crate::edge::CloudProviderStore::load().migrate_to_v2());
     log_startup_migrate("xo-pools",
         crate::xo::XoStore::load().migrate_to_v2());
+    // Plaintext credentials in ai-config / storage / backup / PBS configs.
+    crate::secrets::seal_files_at_startup();
 }
 
 /// POST /api/security/migrate-at-rest-credentials — operator-triggered
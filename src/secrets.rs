@@ -0,0 +1,261 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Encryption for credentials kept inside config files: the SMTP password
+//! and LLM keys in ai-config.json, S3 and SMB secrets in storage.json,
+//! the S3 / PBS / SMB secrets in the backup and PBS configs, and every
+//! value in the per-container environment store. PVE tokens are sealed
+//! the same way in cluster.json (see `agent`).
+//!
+//! Each file declares its secret fields once ([`SecretFields`]); the
+//! module's save runs through [`to_sealed_json`] and its load through
+//! [`from_sealed_json`], which seal / open every field with that name
+//! wherever it sits in the document. Structs hold plaintext in memory and
+//! on the wire — only the file changes — so API responses, cluster pushes
+//! and the code that uses the credentials are untouched.
+//!
+//! Values are `at_rest_crypto` v2 strings (AES-256-GCM, key derived from
+//! the per-install cluster secret) under a per-file purpose label. Setting
+//! `WOLFSTACK_SECRETS_PASSPHRASE`, or the systemd credential
+//! `wolfstack-secrets-passphrase`, mixes a passphrase into that label, so
+//! the files then need both to read. Nodes that share these files through
+//! dashboard sync must use the same passphrase (or none).
+//!
+//! Nothing is ever lost to a key problem: plaintext left by older builds
+//! is read as-is and sealed on the next save (or at startup by
+//! [`seal_files_at_startup`]); a value that won't open — wrong passphrase,
+//! secret rotated without a restart — stays sealed in memory and is
+//! written back unchanged rather than replaced by an empty string.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+/// The secret fields of one config file.
+pub struct SecretFields {
+    /// Purpose label the key is derived under. Never rename — it changes
+    /// the key and strands every stored value.
+    pub label: &'static str,
+    /// Field names sealed wherever they appear in the document.
+    pub fields: &'static [&'static str],
+}
+
+const PASSPHRASE_ENV: &str = "WOLFSTACK_SECRETS_PASSPHRASE";
+const PASSPHRASE_CREDENTIAL: &str = "wolfstack-secrets-passphrase";
+
+/// SHA-256 of the optional passphrase, hex. Read once per process.
+fn passphrase_tag() -> Option<&'static str> {
+    static TAG: OnceLock<Option<String>> = OnceLock::new();
+    TAG.get_or_init(|| {
+        let phrase = std::env::var(PASSPHRASE_ENV).ok()
+            .or_else(|| {
+                let dir = std::env::var("CREDENTIALS_DIRECTORY").ok()?;
+                std::fs::read_to_string(std::path::Path::new(&dir).join(PASSPHRASE_CREDENTIAL)).ok()
+            })
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())?;
+        let digest = ring::digest::digest(&ring::digest::SHA256, phrase.as_bytes());
+        Some(digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect())
+    }).as_deref()
+}
+
+fn purpose_for(label: &str, tag: Option<&str>) -> Vec<u8> {
+    match tag {
+        Some(t) => format!("secrets:{}:{}", label, t).into_bytes(),
+        None => format!("secrets:{}", label).into_bytes(),
+    }
+}
+
+fn purpose(label: &str) -> Vec<u8> {
+    purpose_for(label, passphrase_tag())
+}
+
+/// Seal one value in place. Empty and already-sealed values are left
+/// alone; so is the plaintext when the key is unavailable (stale after a
+/// rotation) — the next save after the restart seals it.
+fn seal(value: &mut String, label: &str) {
+    if value.is_empty() || crate::at_rest_crypto::is_v2_format(value) {
+        return;
+    }
+    if let Ok(sealed) = crate::at_rest_crypto::encrypt(value.as_bytes(), &purpose(label)) {
+        *value = sealed;
+    }
+}
+
+/// Open one value in place. Plaintext passes through; a sealed value that
+/// won't open is kept sealed so a later save can't destroy it.
+fn open(value: &mut String, label: &str) {
+    if !crate::at_rest_crypto::is_v2_format(value) {
+        return;
+    }
+    match crate::at_rest_crypto::decrypt_v2(value, &purpose(label)) {
+        Some(bytes) => *value = String::from_utf8_lossy(&bytes).into_owned(),
+        None => {
+            static WARNED: AtomicBool = AtomicBool::new(false);
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                warn!("secrets: a stored {} credential did not decrypt — check {} matches the node that wrote it \
+                       (or that wolfstack was restarted after a cluster-secret rotation); it is left as it is",
+                    label, PASSPHRASE_ENV);
+            }
+        }
+    }
+}
+
+/// Apply `f` to every string field named in `spec`, at any depth.
+fn walk(v: &mut serde_json::Value, spec: &SecretFields, f: &mut dyn FnMut(&mut String)) {
+    match v {
+        serde_json::Value::Object(map) => {
+            for (key, val) in map.iter_mut() {
+                match val {
+                    serde_json::Value::String(s) if spec.fields.contains(&key.as_str()) => f(s),
+                    _ => walk(val, spec, f),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                walk(item, spec, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pretty JSON for `value` with its secret fields sealed — what a store
+/// writes to disk.
+pub fn to_sealed_json<T: Serialize>(value: &T, spec: &SecretFields) -> Result<String, String> {
+    let mut doc = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    walk(&mut doc, spec, &mut |s| seal(s, spec.label));
+    serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())
+}
+
+/// Parse a store's file, opening its secret fields.
+pub fn from_sealed_json<T: DeserializeOwned>(content: &str, spec: &SecretFields) -> Result<T, String> {
+    let mut doc: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    walk(&mut doc, spec, &mut |s| open(s, spec.label));
+    serde_json::from_value(doc).map_err(|e| e.to_string())
+}
+
+/// `value` with its secret fields opened, for a store that parses its
+/// file itself (with migrations) before it has the struct.
+pub fn open_all<T: Serialize + DeserializeOwned>(value: T, spec: &SecretFields) -> T {
+    let Ok(mut doc) = serde_json::to_value(&value) else { return value };
+    walk(&mut doc, spec, &mut |s| open(s, spec.label));
+    serde_json::from_value(doc).unwrap_or(value)
+}
+
+/// Every file holding [`SecretFields`], with its current path.
+fn files() -> Vec<(String, &'static SecretFields)> {
+    let p = crate::paths::get();
+    vec![
+        (p.ai_config, &crate::ai::AI_CONFIG_SECRETS),
+        (p.storage_config, &crate::storage::STORAGE_SECRETS),
+        (p.backup_config, &crate::backup::BACKUP_SECRETS),
+        (crate::backup::PBS_CONFIG_PATH.to_string(), &crate::backup::BACKUP_SECRETS),
+        (p.container_env, &crate::containers::env_store::ENV_STORE_SECRETS),
+    ]
+}
+
+/// Rewrite `path` with `f` applied to its secret fields, if `f` reports a
+/// change. Returns the number of fields changed.
+fn rewrite(path: &str, spec: &SecretFields, f: &mut dyn FnMut(&mut String) -> bool) -> Result<usize, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("read {}: {}", path, e)),
+    };
+    let mut doc: serde_json::Value = serde_json::from_str(&content).map_err(|e| format!("parse {}: {}", path, e))?;
+    let mut changed = 0;
+    walk(&mut doc, spec, &mut |s| if f(s) { changed += 1 });
+    if changed > 0 {
+        let json = serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(path, json).map_err(|e| format!("write {}: {}", path, e))?;
+    }
+    Ok(changed)
+}
+
+/// Seal any plaintext credentials left in the config files by an older
+/// build. Idempotent; run at startup after `at_rest_crypto::init`.
+pub fn seal_files_at_startup() {
+    for (path, spec) in files() {
+        let result = rewrite(&path, spec, &mut |s| {
+            let before = s.is_empty() || crate::at_rest_crypto::is_v2_format(s);
+            seal(s, spec.label);
+            !before && crate::at_rest_crypto::is_v2_format(s)
+        });
+        match result {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("secrets: sealed {} plaintext credential(s) in {}", n, path),
+            Err(e) => warn!("secrets: could not seal credentials in {}: {}", path, e),
+        }
+    }
+}
+
+/// Re-key every sealed config credential from the OLD cluster secret to
+/// the NEW one, for a cluster-secret rotation. Same loss-free contract as
+/// `at_rest_crypto::reencrypt_v2_field`. Returns the number re-keyed.
+pub fn reencrypt_at_rest(old: &str, new: &str) -> Result<usize, String> {
+    if old == new {
+        return Ok(0);
+    }
+    let mut total = 0;
+    let mut errors = Vec::new();
+    for (path, spec) in files() {
+        let label = purpose(spec.label);
+        let result = rewrite(&path, spec, &mut |s| {
+            match crate::at_rest_crypto::reencrypt_v2_field(s, &label, old, new) {
+                crate::at_rest_crypto::ReencryptOutcome::Rekeyed(v) => { *s = v; true }
+                _ => false,
+            }
+        });
+        match result {
+            Ok(n) => total += n,
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() { Ok(total) } else { Err(errors.join("; ")) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: SecretFields = SecretFields { label: "test", fields: &["password", "secret_key"] };
+
+    #[test]
+    fn nested_fields_round_trip_sealed() {
+        crate::at_rest_crypto::init("wsc_test_secrets");
+        let doc = serde_json::json!({
+            "name": "nas",
+            "password": "hunter2",
+            "targets": [{ "secret_key": "abc", "region": "eu" }, { "secret_key": "" }],
+        });
+        let sealed = to_sealed_json(&doc, &SPEC).unwrap();
+        assert!(!sealed.contains("hunter2") && !sealed.contains("\"abc\""));
+        assert!(sealed.contains("\"nas\"") && sealed.contains("\"eu\""));
+        let back: serde_json::Value = from_sealed_json(&sealed, &SPEC).unwrap();
+        assert_eq!(back, doc);
+        // Plaintext from an older build reads as-is.
+        let legacy: serde_json::Value = from_sealed_json(&doc.to_string(), &SPEC).unwrap();
+        assert_eq!(legacy, doc);
+    }
+
+    #[test]
+    fn unopenable_values_are_kept_not_blanked() {
+        crate::at_rest_crypto::init("wsc_test_secrets");
+        let mut v = "v2:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string();
+        open(&mut v, "test");
+        assert!(v.starts_with("v2:"));
+        let mut s = v.clone();
+        seal(&mut s, "test");
+        assert_eq!(s, v);
+    }
+
+    #[test]
+    fn passphrase_changes_the_purpose() {
+        assert_eq!(purpose_for("ai-config", None), b"secrets:ai-config".to_vec());
+        assert_ne!(purpose_for("ai-config", Some("ab")), purpose_for("ai-config", None));
+    }
+}
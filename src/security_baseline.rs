@@ -0,0 +1,396 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Host security baseline — the hardening checks behind the "security"
+//! entries in the Issues scanner (`issue_checks::BUILTIN_CHECKS`).
+//!
+//! Where `security` hunts for attacks in progress, these are the slow-moving
+//! posture problems: sshd letting root or passwords in, services listening
+//! on every interface with no default-deny firewall in front of them,
+//! world-writable files where only root should write, a kernel that's been
+//! upgraded but not booted, and web servers still offering TLS 1.0/1.1 or
+//! broken ciphers. Every finding carries a remediation hint, and each check
+//! can be switched off per node like any other issue check.
+//!
+//! Read-only, and every check swallows its own errors — a missing tool
+//! means no finding, never a failed scan.
+
+use std::process::Command;
+
+/// One baseline problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// "critical", "warning" or "info" — the Issues page severities.
+    pub severity: &'static str,
+    pub title: String,
+    pub detail: String,
+    /// What to do about it.
+    pub remediation: String,
+}
+
+impl Finding {
+    fn new(severity: &'static str, title: impl Into<String>, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self { severity, title: title.into(), detail: detail.into(), remediation: remediation.into() }
+    }
+}
+
+// ─── sshd ───
+
+/// Root login and password authentication, from the effective sshd config.
+pub fn check_sshd() -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let root_login = crate::security::sshd_effective("permitrootlogin");
+    // Proxmox needs root SSH between cluster nodes and re-asserts it.
+    if root_login.as_deref() == Some("yes") && !crate::containers::is_proxmox() {
+        findings.push(Finding::new("critical", "SSH allows root login with a password",
+            "sshd has PermitRootLogin yes — root is the first account every SSH scanner tries.",
+            "Add `PermitRootLogin prohibit-password` (or `no`) in /etc/ssh/sshd_config.d/10-hardening.conf, \
+             check your key works, then `systemctl reload ssh`."));
+    }
+    if crate::security::sshd_effective("passwordauthentication").as_deref() == Some("yes") {
+        findings.push(Finding::new("warning", "SSH password authentication is enabled",
+            "sshd has PasswordAuthentication yes, so every account's password is open to brute force.",
+            "Set `PasswordAuthentication no` in /etc/ssh/sshd_config.d/10-hardening.conf once every admin \
+             has a key installed, then `systemctl reload ssh`."));
+    }
+    findings
+}
+
+// ─── Listening ports vs firewall ───
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub proto: String,
+    pub addr: String,
+    pub port: u16,
+    pub process: String,
+}
+
+/// Ports that are meant to be reachable on a WolfStack node.
+const EXPECTED_PORTS: &[u16] = &[22, 80, 443, 8552, 8553, 9600];
+/// Proxmox's own web UI / proxy ports.
+const PROXMOX_PORTS: &[u16] = &[3128, 8006, 8007];
+
+/// Non-loopback listeners from `ss -tulnpH`.
+fn parse_listeners(ss: &str) -> Vec<Listener> {
+    ss.lines().filter_map(|line| {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.len() < 5 {
+            return None;
+        }
+        let (addr, port) = cols[4].rsplit_once(':')?;
+        let addr = addr.trim_start_matches('[').trim_end_matches(']');
+        let addr = addr.split('%').next().unwrap_or(addr);
+        if addr.starts_with("127.") || addr == "::1" {
+            return None;
+        }
+        Some(Listener {
+            proto: if cols[0].starts_with("udp") { "udp" } else { "tcp" }.to_string(),
+            addr: addr.to_string(),
+            port: port.parse().ok()?,
+            process: cols.get(6).and_then(|p| p.split('"').nth(1)).unwrap_or("").to_string(),
+        })
+    }).collect()
+}
+
+/// Whether an `iptables -S INPUT` / `nft list ruleset` dump shows inbound
+/// traffic dropped by default.
+fn default_deny(iptables: &str, nft: &str) -> bool {
+    let ipt = iptables.lines().any(|l| {
+        let l = l.trim();
+        l == "-P INPUT DROP" || l == "-P INPUT REJECT" || l == "-A INPUT -j DROP" || l == "-A INPUT -j REJECT"
+            || l.starts_with("-A INPUT -j REJECT ")
+    });
+    let nft = nft.lines().any(|l| {
+        let l = l.trim().to_ascii_lowercase();
+        l.contains("hook input") && (l.contains("policy drop") || l.contains("policy reject"))
+    });
+    ipt || nft
+}
+
+fn firewall_manager_active() -> bool {
+    let ufw = Command::new("ufw").arg("status").output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains("Status: active"))
+        .unwrap_or(false);
+    ufw || Command::new("systemctl").args(["is-active", "--quiet", "firewalld"]).status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Services other than the expected ones that answer on every interface
+/// (or a routable address) with nothing dropping inbound traffic.
+fn exposed(listeners: &[Listener], firewalled: bool, expected: &[u16]) -> Vec<Listener> {
+    if firewalled {
+        return Vec::new();
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    listeners.iter()
+        .filter(|l| !expected.contains(&l.port))
+        // Link-local and multicast listeners (mDNS, DHCPv6) aren't reachable off-link.
+        .filter(|l| !l.addr.starts_with("fe80") && !l.addr.starts_with("169.254.") && !l.addr.starts_with("224."))
+        .filter(|l| seen.insert((l.proto.clone(), l.port)))
+        .cloned()
+        .collect()
+}
+
+pub fn check_open_ports() -> Vec<Finding> {
+    let Ok(out) = Command::new("ss").args(["-tulnpH"]).output() else { return Vec::new() };
+    if !out.status.success() {
+        return Vec::new();
+    }
+    let listeners = parse_listeners(&String::from_utf8_lossy(&out.stdout));
+    let iptables = Command::new("iptables").args(["-S", "INPUT"]).output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string()).unwrap_or_default();
+    let nft = Command::new("nft").args(["list", "chains"]).output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string()).unwrap_or_default();
+    let firewalled = default_deny(&iptables, &nft) || firewall_manager_active();
+    let mut expected = EXPECTED_PORTS.to_vec();
+    if crate::containers::is_proxmox() {
+        expected.extend_from_slice(PROXMOX_PORTS);
+    }
+    let open = exposed(&listeners, firewalled, &expected);
+    if open.is_empty() {
+        return Vec::new();
+    }
+    let list: Vec<String> = open.iter().take(15).map(|l| format!("{}/{}{}", l.proto, l.port,
+        if l.process.is_empty() { String::new() } else { format!(" ({})", l.process) })).collect();
+    vec![Finding::new("warning",
+        format!("{} service(s) reachable with no default-deny firewall", open.len()),
+        format!("Inbound traffic is accepted by default and these listen beyond loopback: {}{}.",
+            list.join(", "), if open.len() > 15 { ", …" } else { "" }),
+        "Bind services that don't need outside access to 127.0.0.1, and put a default-deny firewall in \
+         front of the rest (e.g. `ufw default deny incoming`, then `ufw allow` for SSH, 8553 and the ports you \
+         publish).")]
+}
+
+// ─── World-writable files ───
+
+/// Where a world-writable file means any local user can become root.
+const PROTECTED_PATHS: &[&str] = &[
+    "/etc", "/root", "/boot", "/usr/bin", "/usr/sbin", "/usr/local/bin", "/usr/local/sbin",
+    "/usr/lib/systemd", "/var/spool/cron",
+];
+const MAX_LISTED: usize = 20;
+
+pub fn check_world_writable() -> Vec<Finding> {
+    let paths: Vec<&str> = PROTECTED_PATHS.iter().copied()
+        .filter(|p| std::path::Path::new(p).is_dir())
+        .collect();
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    // Regular files writable by others, and directories writable by others
+    // without the sticky bit. Symlinks are always 0777 and don't count.
+    let out = Command::new("timeout").arg("60").arg("find").args(&paths)
+        .args(["-xdev", "(", "-type", "f", "-perm", "-0002", ")", "-o",
+               "(", "-type", "d", "-perm", "-0002", "!", "-perm", "-1000", ")", "-print"])
+        .output();
+    let Ok(out) = out else { return Vec::new() };
+    let text = String::from_utf8_lossy(&out.stdout);
+    let hits: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    if hits.is_empty() {
+        return Vec::new();
+    }
+    let shown: Vec<&str> = hits.iter().take(MAX_LISTED).copied().collect();
+    vec![Finding::new("critical",
+        format!("{} world-writable path(s) in system directories", hits.len()),
+        format!("Any local user or compromised service can modify these, and root will run or read them: {}{}",
+            shown.join(", "), if hits.len() > MAX_LISTED { ", …" } else { "" }),
+        format!("Remove write access for others, e.g. `chmod o-w {}`, and find out what loosened them — a \
+                 package script, an over-broad `chmod -R`, or an intruder.", shown.join(" ")))]
+}
+
+// ─── Kernel ───
+
+/// Compare kernel release strings numerically chunk by chunk
+/// ("6.8.12-4-pve" < "6.8.12-10-pve").
+fn kernel_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let chunks = |s: &str| -> Vec<u64> {
+        s.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty()).filter_map(|p| p.parse().ok()).collect()
+    };
+    chunks(a).cmp(&chunks(b))
+}
+
+/// The newest installed kernel if it's newer than the running one.
+fn newer_kernel(running: &str, installed: &[String]) -> Option<String> {
+    installed.iter()
+        .filter(|k| kernel_cmp(k, running) == std::cmp::Ordering::Greater)
+        .max_by(|a, b| kernel_cmp(a, b))
+        .cloned()
+}
+
+pub fn check_kernel() -> Vec<Finding> {
+    let running = match Command::new("uname").arg("-r").output() {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).trim().to_string(),
+        _ => return Vec::new(),
+    };
+    // Containers share the host's kernel; there's nothing to reboot into.
+    let installed: Vec<String> = std::fs::read_dir("/lib/modules").map(|entries| entries.flatten()
+        .filter(|e| e.path().join("modules.dep").exists() || e.path().join("kernel").is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()).unwrap_or_default();
+    let reboot_flag = std::path::Path::new("/var/run/reboot-required").exists();
+    match newer_kernel(&running, &installed) {
+        Some(newer) => vec![Finding::new("warning",
+            "Running an outdated kernel",
+            format!("Kernel {} is running but {} is installed — security fixes in the new kernel aren't active until a reboot.",
+                running, newer),
+            "Reboot the node in a maintenance window (migrate or stop its guests first).")],
+        None if reboot_flag => vec![Finding::new("info",
+            "A reboot is pending",
+            "/var/run/reboot-required is set — updated packages (often libc or the kernel) need a reboot to take effect.",
+            "Reboot the node in a maintenance window.")],
+        None => Vec::new(),
+    }
+}
+
+// ─── TLS configuration ───
+
+/// Web server, proxy and OpenSSL configs worth reading for TLS settings.
+fn tls_config_files() -> Vec<std::path::PathBuf> {
+    let mut files: Vec<std::path::PathBuf> = [
+        "/etc/nginx/nginx.conf", "/etc/haproxy/haproxy.cfg", "/etc/ssl/openssl.cnf",
+        "/etc/apache2/mods-enabled/ssl.conf", "/etc/httpd/conf.d/ssl.conf",
+    ].iter().map(std::path::PathBuf::from).filter(|p| p.is_file()).collect();
+    for dir in ["/etc/nginx/conf.d", "/etc/nginx/sites-enabled", "/etc/apache2/sites-enabled", "/etc/httpd/conf.d"] {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            let mut found: Vec<_> = entries.flatten().map(|e| e.path())
+                .filter(|p| p.is_file() && !files.contains(p))
+                .collect();
+            found.sort();
+            files.extend(found);
+        }
+    }
+    files
+}
+
+/// A cipher-list entry that enables something broken.
+fn weak_cipher(token: &str) -> bool {
+    let t = token.trim().to_ascii_lowercase();
+    if t.is_empty() || t.starts_with('!') || t.starts_with('-') {
+        return false;
+    }
+    ["rc4", "3des", "des-cbc", "md5", "null", "export"].iter().any(|w| t.contains(w))
+        || t.starts_with("des-") || t.contains("-des-")
+}
+
+/// Weak protocol or cipher settings in one config file's text.
+fn weak_tls_settings(text: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    for raw in text.lines() {
+        let line = raw.split('#').next().unwrap_or("").trim().trim_end_matches(';');
+        if line.is_empty() {
+            continue;
+        }
+        let lower = line.to_ascii_lowercase();
+        let mut words = lower.split_whitespace();
+        let Some(key) = words.next() else { continue };
+        let values: Vec<&str> = words.filter(|w| *w != "=").collect();
+        match key.trim_end_matches('=') {
+            // nginx `ssl_protocols TLSv1 TLSv1.2`, Apache `SSLProtocol all -SSLv3`
+            "ssl_protocols" | "sslprotocol" | "sslproxyprotocol" => {
+                let old: Vec<&str> = values.iter()
+                    .map(|v| v.trim_start_matches('+'))
+                    .filter(|v| matches!(*v, "sslv2" | "sslv3" | "tlsv1" | "tlsv1.1"))
+                    .collect();
+                let all_unrestricted = values.contains(&"all")
+                    && !(values.contains(&"-tlsv1") && values.contains(&"-tlsv1.1"));
+                if !old.is_empty() {
+                    problems.push(format!("`{}` enables {}", line, old.join(", ")));
+                } else if all_unrestricted {
+                    problems.push(format!("`{}` still allows TLS 1.0/1.1", line));
+                }
+            }
+            // HAProxy `ssl-min-ver TLSv1.0`, OpenSSL `MinProtocol = TLSv1`
+            "ssl-min-ver" | "minprotocol"
+                if values.first().is_some_and(|v| matches!(*v, "sslv3" | "tlsv1" | "tlsv1.0" | "tlsv1.1")) => {
+                problems.push(format!("`{}` allows TLS older than 1.2", line));
+            }
+            "ssl_ciphers" | "sslciphersuite" | "ssl-default-bind-ciphers" | "ciphers" | "cipherstring" => {
+                let list = values.join(" ").trim_matches('"').to_string();
+                let weak: Vec<String> = list.split([':', ' ', ',']).filter(|t| weak_cipher(t)).map(String::from).collect();
+                if !weak.is_empty() {
+                    problems.push(format!("`{}` enables weak ciphers ({})", line, weak.join(", ")));
+                }
+                if list.contains("@seclevel=0") {
+                    problems.push(format!("`{}` sets OpenSSL security level 0", line));
+                }
+            }
+            _ => {}
+        }
+        // HAProxy's bind options can set the floor inline.
+        if (lower.contains("ssl-default-bind-options") || lower.starts_with("bind "))
+            && let Some(ver) = lower.split("ssl-min-ver").nth(1).and_then(|r| r.split_whitespace().next())
+            && matches!(ver, "sslv3" | "tlsv1.0" | "tlsv1.1") {
+            problems.push(format!("`{}` allows TLS older than 1.2", line));
+        }
+    }
+    problems
+}
+
+pub fn check_tls() -> Vec<Finding> {
+    tls_config_files().into_iter().filter_map(|path| {
+        let text = std::fs::read_to_string(&path).ok()?;
+        let problems = weak_tls_settings(&text);
+        if problems.is_empty() {
+            return None;
+        }
+        let path = path.display().to_string();
+        Some(Finding::new("warning",
+            format!("Weak TLS settings in {}", path),
+            problems.join("; "),
+            format!("Allow only TLS 1.2 and 1.3 (nginx: `ssl_protocols TLSv1.2 TLSv1.3;`, Apache: \
+                     `SSLProtocol -all +TLSv1.2 +TLSv1.3`, HAProxy: `ssl-min-ver TLSv1.2`) and drop RC4/3DES/NULL/EXPORT \
+                     ciphers from {}, then reload the service.", path)))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposed_listeners_only_without_a_firewall() {
+        let ss = "tcp LISTEN 0 128 0.0.0.0:22 0.0.0.0:* users:((\"sshd\",pid=1,fd=3))\n\
+                  tcp LISTEN 0 128 127.0.0.1:5432 0.0.0.0:* users:((\"postgres\",pid=2,fd=3))\n\
+                  tcp LISTEN 0 128 [::]:6379 [::]:* users:((\"redis-server\",pid=3,fd=6))\n\
+                  tcp LISTEN 0 128 0.0.0.0:6379 0.0.0.0:* users:((\"redis-server\",pid=3,fd=5))\n\
+                  udp UNCONN 0 0 [fe80::1%eth0]:546 [::]:* \n";
+        let listeners = parse_listeners(ss);
+        assert_eq!(listeners.len(), 4);
+        let open = exposed(&listeners, false, EXPECTED_PORTS);
+        assert_eq!(open.len(), 1);
+        assert_eq!((open[0].port, open[0].process.as_str()), (6379, "redis-server"));
+        assert!(exposed(&listeners, true, EXPECTED_PORTS).is_empty());
+
+        assert!(default_deny("-P INPUT DROP\n-A INPUT -p tcp --dport 22 -j ACCEPT", ""));
+        assert!(default_deny("-P INPUT ACCEPT", "\tchain input { type filter hook input priority filter; policy drop;"));
+        assert!(!default_deny("-P INPUT ACCEPT\n-A INPUT -s 1.2.3.4 -j DROP", "type filter hook input priority 0; policy accept;"));
+    }
+
+    #[test]
+    fn kernel_versions_compare_numerically() {
+        let installed = vec!["6.8.12-4-pve".to_string(), "6.8.12-10-pve".to_string(), "6.5.13-1-pve".to_string()];
+        assert_eq!(newer_kernel("6.8.12-4-pve", &installed).as_deref(), Some("6.8.12-10-pve"));
+        assert_eq!(newer_kernel("6.8.12-10-pve", &installed), None);
+        assert_eq!(kernel_cmp("6.10.0", "6.9.12"), std::cmp::Ordering::Greater);
+    }
+
+    #[test]
+    fn flags_weak_tls_settings() {
+        let nginx = "server {\n  ssl_protocols TLSv1 TLSv1.1 TLSv1.2;\n  ssl_ciphers HIGH:!aNULL:!MD5:RC4-SHA;\n}\n# ssl_protocols SSLv3;";
+        let p = weak_tls_settings(nginx);
+        assert_eq!(p.len(), 2, "{:?}", p);
+        assert!(p[0].contains("tlsv1, tlsv1.1"));
+        assert!(p[1].contains("rc4-sha"));
+
+        assert!(weak_tls_settings("ssl_protocols TLSv1.2 TLSv1.3;\nssl_ciphers HIGH:!aNULL:!MD5;").is_empty());
+        assert_eq!(weak_tls_settings("SSLProtocol all -SSLv3").len(), 1);
+        assert!(weak_tls_settings("SSLProtocol all -SSLv3 -TLSv1 -TLSv1.1").is_empty());
+        assert_eq!(weak_tls_settings("MinProtocol = TLSv1\nCipherString = DEFAULT@SECLEVEL=0").len(), 2);
+        assert_eq!(weak_tls_settings("  ssl-default-bind-options ssl-min-ver TLSv1.0 no-tls-tickets").len(), 1);
+        assert!(!weak_cipher("ECDHE-RSA-AES128-GCM-SHA256"));
+        assert!(weak_cipher("DES-CBC3-SHA"));
+    }
+}
@@ -0,0 +1,330 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Port pre-flight checks.
+//!
+//! "Active (running)" in systemd says nothing about whether anyone can
+//! connect: a listener can fail to bind, or bind fine behind an INPUT
+//! chain that drops everything (the stock Proxmox firewall, a leftover
+//! `nft` ruleset). A few seconds after the listeners start, [`spawn`]
+//! connects to each one over loopback and reads the iptables and nftables
+//! rules for anything that would stop other hosts reaching the port, and
+//! logs what it finds. `GET /api/system/selfcheck` runs the same checks
+//! on demand.
+//!
+//! A listener that can't bind at all is explained by
+//! [`explain_bind_failure`] (who holds the port, or why we may not have
+//! it), and a node that can't be reached while being added to a cluster
+//! gets a hint from [`join_hint`].
+
+use crate::networking::diagnostics::{self, Decision, Probe};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Time allowed for the loopback connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The listeners the daemon started: (bind address, [(name, port)]).
+static LISTENERS: OnceLock<(String, Vec<(&'static str, u16)>)> = OnceLock::new();
+
+static LAST: RwLock<Option<SelfCheck>> = RwLock::new(None);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PortReport {
+    /// `api`, `inter_node` or `status`.
+    pub name: String,
+    pub port: u16,
+    /// The loopback-side address connected to.
+    pub address: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Firewall rules that stop other hosts reaching the port.
+    pub blocked_by: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfCheck {
+    /// RFC 3339.
+    pub time: String,
+    /// Every listener reachable and none firewalled.
+    pub ok: bool,
+    pub ports: Vec<PortReport>,
+}
+
+/// Where to connect to reach a listener bound to `bind`: loopback for a
+/// wildcard bind, else the bound address itself.
+fn loopback_target(bind: &str, port: u16) -> SocketAddr {
+    let host = bind.trim().trim_start_matches('[').trim_end_matches(']');
+    let ip = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Ok(IpAddr::V6(ip)) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        Ok(ip) => ip,
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    SocketAddr::new(ip, port)
+}
+
+/// Ports in an nft `dport` match: `22`, `8550-8599` or `{ 22, 8553 }`.
+fn nft_dport_covers(line: &str, port: u16) -> Option<bool> {
+    let rest = line.split_once("dport ")?.1.trim_start();
+    let spec = match rest.strip_prefix('{') {
+        Some(set) => set.split('}').next().unwrap_or(""),
+        None => rest.split_whitespace().next().unwrap_or(""),
+    };
+    Some(spec.split(',').map(str::trim).any(|p| match p.split_once('-') {
+        Some((lo, hi)) => match (lo.trim().parse::<u16>(), hi.trim().parse::<u16>()) {
+            (Ok(lo), Ok(hi)) => (lo..=hi).contains(&port),
+            _ => false,
+        },
+        None => p.parse::<u16>().ok() == Some(port),
+    }))
+}
+
+/// Rules in `nft list ruleset` output that stop new TCP connections to
+/// `port` in an input-hook chain: the first rule naming the port if it
+/// drops or rejects, or the chain's `policy drop` if no rule accepts it.
+/// iptables-nft's own `ip`/`ip6 filter` tables are skipped when
+/// `skip_iptables_tables` — [`iptables_blocking`] already read them.
+fn nft_blocking(ruleset: &str, port: u16, skip_iptables_tables: bool) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut table = String::new();
+    let mut chain: Option<String> = None;
+    let mut is_input = false;
+    let mut policy_drop = false;
+    let mut decided = false;
+    for line in ruleset.lines().map(str::trim) {
+        if let Some(t) = line.strip_prefix("table ") {
+            table = t.trim_end_matches('{').trim().to_string();
+            continue;
+        }
+        if let Some(c) = line.strip_prefix("chain ") {
+            chain = Some(c.trim_end_matches('{').trim().to_string());
+            (is_input, policy_drop, decided) = (false, false, false);
+            continue;
+        }
+        let Some(chain_name) = &chain else { continue };
+        if skip_iptables_tables && (table == "ip filter" || table == "ip6 filter") {
+            if line == "}" {
+                chain = None;
+            }
+            continue;
+        }
+        if line.starts_with("type ") && line.contains("hook input") {
+            is_input = true;
+            policy_drop = line.contains("policy drop");
+            continue;
+        }
+        if line == "}" {
+            if is_input && policy_drop && !decided {
+                found.push(format!("nftables: table {} chain {} has policy drop and no rule accepting tcp dport {}", table, chain_name, port));
+            }
+            chain = None;
+            continue;
+        }
+        if !is_input || decided || line.contains("udp dport") || nft_dport_covers(line, port) != Some(true) {
+            continue;
+        }
+        decided = true;
+        let verdict = line.split_whitespace().last().unwrap_or("");
+        if verdict == "drop" || line.contains(" reject") {
+            found.push(format!("nftables: table {} chain {}: {}", table, chain_name, line));
+        }
+    }
+    found
+}
+
+/// What the iptables INPUT chain does with a new TCP connection to
+/// `port` from a host it has no specific rule for.
+fn iptables_blocking(rules: &str, port: u16) -> Option<String> {
+    let probe = Probe { src: None, dst: None, proto: "tcp", dport: Some(port) };
+    let v = diagnostics::firewall_verdict(rules, "INPUT", &probe);
+    match v.decision {
+        Decision::Drop | Decision::Reject => Some(format!("iptables: {}", v.rule.unwrap_or_else(|| "INPUT policy".into()))),
+        Decision::Accept | Decision::Unknown => None,
+    }
+}
+
+fn command_stdout(cmd: &str, args: &[&str]) -> String {
+    match std::process::Command::new(cmd).args(args).output() {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).into_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Firewall rules blocking each of `ports`. Blocking.
+fn firewall_blocks(ports: &[u16]) -> Vec<Vec<String>> {
+    let iptables = diagnostics::filter_rules(false);
+    let nft = command_stdout("nft", &["list", "ruleset"]);
+    ports.iter().map(|&port| {
+        let mut blocked: Vec<String> = iptables_blocking(&iptables, port).into_iter().collect();
+        blocked.extend(nft_blocking(&nft, port, !iptables.trim().is_empty()));
+        blocked
+    }).collect()
+}
+
+/// Run the checks against the listeners [`spawn`] registered. `None`
+/// until the daemon has started them.
+pub async fn run() -> Option<SelfCheck> {
+    let (bind, listeners) = LISTENERS.get()?;
+    let ports: Vec<u16> = listeners.iter().map(|(_, p)| *p).collect();
+    let blocks = tokio::task::spawn_blocking(move || firewall_blocks(&ports)).await.unwrap_or_default();
+    let mut reports = Vec::new();
+    for (i, (name, port)) in listeners.iter().enumerate() {
+        let addr = loopback_target(bind, *port);
+        let error = match tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(_) => Some(format!("no answer within {}s", CONNECT_TIMEOUT.as_secs())),
+        };
+        reports.push(PortReport {
+            name: name.to_string(),
+            port: *port,
+            address: addr.to_string(),
+            reachable: error.is_none(),
+            error,
+            blocked_by: blocks.get(i).cloned().unwrap_or_default(),
+        });
+    }
+    let check = SelfCheck {
+        time: chrono::Utc::now().to_rfc3339(),
+        ok: reports.iter().all(|r| r.reachable && r.blocked_by.is_empty()),
+        ports: reports,
+    };
+    *LAST.write().unwrap() = Some(check.clone());
+    Some(check)
+}
+
+/// The most recent result, if a check has run.
+pub fn last() -> Option<SelfCheck> {
+    LAST.read().unwrap().clone()
+}
+
+fn log(check: &SelfCheck) {
+    for p in &check.ports {
+        match &p.error {
+            None => info!("self-check: {} port {} answers on {}", p.name, p.port, p.address),
+            Some(e) => warn!(
+                "self-check: {} port {} does not answer on {} even from this host ({}) — the listener is not accepting connections",
+                p.name, p.port, p.address, e
+            ),
+        }
+        for rule in &p.blocked_by {
+            warn!(
+                "self-check: {} port {} is blocked for other hosts by {} — browsers and cluster peers won't reach it; allow it, e.g. `iptables -I INPUT -p tcp --dport {} -j ACCEPT`",
+                p.name, p.port, rule, p.port
+            );
+        }
+    }
+}
+
+/// Record the listeners and check them once they've had a moment to
+/// start accepting.
+pub fn spawn(bind: &str, listeners: Vec<(&'static str, u16)>) {
+    if LISTENERS.set((bind.to_string(), listeners)).is_err() {
+        return;
+    }
+    tokio::spawn(async {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        if let Some(check) = run().await {
+            log(&check);
+        }
+    });
+}
+
+/// Log why binding `addr` failed: who holds the port, or what we lack.
+pub fn explain_bind_failure(addr: &str, err: &std::io::Error) {
+    match err.kind() {
+        std::io::ErrorKind::AddrInUse => {
+            let port = addr.rsplit(':').next().unwrap_or("");
+            let holder = command_stdout("ss", &["-H", "-ltnp", &format!("sport = :{}", port)]);
+            match holder.lines().next().and_then(|l| l.split("users:").nth(1)) {
+                Some(users) => error!("   {} is already in use by {} — stop it or move WolfStack in Settings → Node Ports (/etc/wolfstack/ports.json)", addr, users.trim()),
+                None => error!("   {} is already in use — another WolfStack, or a service on the same port; change it in /etc/wolfstack/ports.json", addr),
+            }
+        }
+        std::io::ErrorKind::AddrNotAvailable => {
+            error!("   {} is not an address of this host — fix server.bind in wolfstack.toml or --bind", addr);
+        }
+        std::io::ErrorKind::PermissionDenied => {
+            error!("   not allowed to bind {} — ports below 1024 need root or CAP_NET_BIND_SERVICE", addr);
+        }
+        _ => {}
+    }
+}
+
+/// A hint for an add-node attempt that couldn't reach `address:port`,
+/// from a fresh TCP connection and this node's own OUTPUT chain.
+pub async fn join_hint(address: &str, port: u16) -> Option<String> {
+    let host = crate::validate::HostName::parse(address).ok()?;
+    let check = diagnostics::check_port(&host, port, Duration::from_secs(5)).await;
+    if check.open {
+        return None;
+    }
+    let dst = check.address.as_deref().and_then(|a| a.parse::<SocketAddr>().ok()).map(|a| a.ip());
+    let outbound = tokio::task::spawn_blocking(move || {
+        let rules = diagnostics::filter_rules(dst.is_some_and(|d| d.is_ipv6()));
+        diagnostics::firewall_verdict(&rules, "OUTPUT", &Probe { src: None, dst, proto: "tcp", dport: Some(port) })
+    }).await.ok();
+    if let Some(v) = outbound.filter(|v| matches!(v.decision, Decision::Drop | Decision::Reject)) {
+        return Some(format!("this node's own firewall blocks outgoing TCP {}: {}", port, v.rule.unwrap_or_default()));
+    }
+    let why = check.error.unwrap_or_default();
+    Some(format!(
+        "TCP {}:{} — {}. On the target, check that WolfStack is running and look for \"self-check\" lines in its log (journalctl -u wolfstack) or GET /api/system/selfcheck",
+        address, port, why
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_binds_are_checked_over_loopback() {
+        assert_eq!(loopback_target("0.0.0.0", 8553).to_string(), "127.0.0.1:8553");
+        assert_eq!(loopback_target("::", 8553).to_string(), "[::1]:8553");
+        assert_eq!(loopback_target("[::]", 8553).to_string(), "[::1]:8553");
+        assert_eq!(loopback_target("10.0.0.5", 8553).to_string(), "10.0.0.5:8553");
+    }
+
+    #[test]
+    fn nft_rules_blocking_the_port_are_reported() {
+        let ruleset = "table inet filter {\n\
+            \tchain input {\n\
+            \t\ttype filter hook input priority filter; policy drop;\n\
+            \t\tct state established,related accept\n\
+            \t\ttcp dport { 22, 8550-8552 } accept\n\
+            \t\ttcp dport 8554 counter packets 0 bytes 0 reject with tcp reset\n\
+            \t}\n\
+            \tchain output {\n\
+            \t\ttype filter hook output priority filter; policy accept;\n\
+            \t}\n\
+            }\n\
+            table ip filter {\n\
+            \tchain INPUT {\n\
+            \t\ttype filter hook input priority filter; policy drop;\n\
+            \t}\n\
+            }\n";
+        assert!(nft_blocking(ruleset, 8550, true).is_empty());
+        let blocked = nft_blocking(ruleset, 8553, true);
+        assert_eq!(blocked.len(), 1);
+        assert!(blocked[0].contains("policy drop"), "{blocked:?}");
+        let blocked = nft_blocking(ruleset, 8554, true);
+        assert!(blocked[0].contains("reject with tcp reset"), "{blocked:?}");
+        // Without iptables the ip filter table counts too.
+        assert_eq!(nft_blocking(ruleset, 8550, false).len(), 1);
+    }
+
+    #[test]
+    fn iptables_policy_drop_without_an_accept_is_reported() {
+        let rules = "-P INPUT DROP\n-A INPUT -i lo -j ACCEPT\n-A INPUT -p tcp -m tcp --dport 8553 -j ACCEPT\n";
+        assert_eq!(iptables_blocking(rules, 8553), None);
+        assert_eq!(iptables_blocking(rules, 8550).as_deref(), Some("iptables: -P INPUT DROP"));
+        assert_eq!(iptables_blocking("", 8550), None);
+    }
+}
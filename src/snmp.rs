@@ -0,0 +1,707 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Optional SNMP agent, for monitoring systems that only speak SNMP.
+//!
+//! WolfStack doesn't answer SNMP itself: it writes `/etc/snmp/snmpd.conf`
+//! for net-snmp's `snmpd` (keeping the original aside and putting it back
+//! when SNMP is turned off), so v2c communities, v3 users, access control
+//! and the standard MIBs (system, interfaces, HOST-RESOURCES, UCD) all
+//! come from a well-tested agent. Everything is read-only.
+//!
+//! WolfStack's own objects — node metrics, container and VM counts, open
+//! issues, per-filesystem usage — live under `base_oid` (the net-snmp
+//! playpen by default; set your own enterprise OID if you have one).
+//! `snmpd` hands that subtree to `wolfstack snmp-pass`, a `pass_persist`
+//! helper that answers from `/run/wolfstack-snmp.json`. The daemon
+//! rewrites that file every fifteen seconds while SNMP is on; the helper
+//! runs as snmpd's user and never talks to the daemon. `wsDataAge` says
+//! how old the file is, so an NMS can alert when WolfStack stops updating
+//! it. The matching MIB is served at `/api/snmp/mib`.
+
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::process::Command;
+
+const SNAPSHOT_PATH: &str = "/run/wolfstack-snmp.json";
+const SNMPD_CONF: &str = "/etc/snmp/snmpd.conf";
+/// The distro's snmpd.conf, kept while WolfStack manages the file.
+const SNMPD_CONF_ORIGINAL: &str = "/etc/snmp/snmpd.conf.wolfstack-orig";
+const MANAGED_MARKER: &str = "# Managed by WolfStack";
+const SNAPSHOT_SECS: u64 = 15;
+
+const AUTH_PROTOCOLS: &[&str] = &["SHA", "SHA-256", "SHA-512", "MD5"];
+const PRIV_PROTOCOLS: &[&str] = &["AES", "DES", ""];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnmpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// snmpd `agentAddress`, e.g. `udp:161` or `udp:10.0.0.5:161,udp6:[::]:161`.
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    #[serde(default)]
+    pub v2c_enabled: bool,
+    /// Read-only SNMPv2c community.
+    #[serde(default)]
+    pub community: String,
+    /// Who may use the community: `default` (anyone) or an address/CIDR.
+    #[serde(default = "default_community_source")]
+    pub community_source: String,
+    #[serde(default)]
+    pub v3_enabled: bool,
+    #[serde(default)]
+    pub v3_user: String,
+    /// SHA, SHA-256, SHA-512 or MD5.
+    #[serde(default = "default_auth_protocol")]
+    pub v3_auth_protocol: String,
+    #[serde(default)]
+    pub v3_auth_pass: String,
+    /// AES, DES, or empty for authentication without privacy.
+    #[serde(default = "default_priv_protocol")]
+    pub v3_priv_protocol: String,
+    #[serde(default)]
+    pub v3_priv_pass: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub contact: String,
+    /// Root of the WolfStack subtree; must sit under enterprises.
+    #[serde(default = "default_base_oid")]
+    pub base_oid: String,
+}
+
+fn default_listen() -> String { "udp:161".into() }
+fn default_community_source() -> String { "default".into() }
+fn default_auth_protocol() -> String { "SHA".into() }
+fn default_priv_protocol() -> String { "AES".into() }
+fn default_base_oid() -> String { ".1.3.6.1.4.1.8072.9999.1".into() }
+
+impl Default for SnmpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_listen(),
+            v2c_enabled: false,
+            community: String::new(),
+            community_source: default_community_source(),
+            v3_enabled: false,
+            v3_user: String::new(),
+            v3_auth_protocol: default_auth_protocol(),
+            v3_auth_pass: String::new(),
+            v3_priv_protocol: default_priv_protocol(),
+            v3_priv_pass: String::new(),
+            location: String::new(),
+            contact: String::new(),
+            base_oid: default_base_oid(),
+        }
+    }
+}
+
+impl SnmpConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(crate::paths::get().snmp_config)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::paths::write_secure_atomic(&crate::paths::get().snmp_config, json)
+            .map_err(|e| format!("Failed to save SNMP config: {}", e))
+    }
+
+    /// The config with the community and passphrases blanked, for the UI.
+    pub fn masked(&self) -> Self {
+        Self {
+            community: String::new(),
+            v3_auth_pass: String::new(),
+            v3_priv_pass: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Take the community and passphrases from `previous` where this one
+    /// leaves them blank — the UI never sees them, so blank means unchanged.
+    pub fn keep_secrets(&mut self, previous: &SnmpConfig) {
+        if self.community.is_empty() {
+            self.community = previous.community.clone();
+        }
+        if self.v3_auth_pass.is_empty() {
+            self.v3_auth_pass = previous.v3_auth_pass.clone();
+        }
+        if self.v3_priv_pass.is_empty() {
+            self.v3_priv_pass = previous.v3_priv_pass.clone();
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        // snmpd.conf is whitespace-separated and `#` starts a comment, so
+        // anything written into it must be a single plain token.
+        let token = |what: &str, v: &str| -> Result<(), String> {
+            if v.chars().any(|c| c.is_whitespace() || c.is_control() || c == '#' || c == '"' || c == '\'') {
+                return Err(format!("{} can't contain spaces, quotes or #", what));
+            }
+            Ok(())
+        };
+        if self.listen.trim().is_empty() {
+            return Err("Enter a listen address, e.g. udp:161".into());
+        }
+        token("Listen address", &self.listen)?;
+        for (what, v) in [("Location", &self.location), ("Contact", &self.contact)] {
+            if v.len() > 255 || v.chars().any(char::is_control) {
+                return Err(format!("{} must be a single line of up to 255 characters", what));
+            }
+        }
+        let arcs = self.base_oid.strip_prefix(".1.3.6.1.4.1.")
+            .ok_or("Base OID must sit under enterprises (.1.3.6.1.4.1.…)")?;
+        if arcs.split('.').any(|a| a.parse::<u32>().is_err()) {
+            return Err(format!("'{}' is not a numeric OID", self.base_oid));
+        }
+        if !self.enabled {
+            return Ok(());
+        }
+        if !self.v2c_enabled && !self.v3_enabled {
+            return Err("Turn on SNMPv2c, SNMPv3 or both".into());
+        }
+        if self.v2c_enabled {
+            if self.community.is_empty() || self.community.len() > 64 {
+                return Err("Enter a community of up to 64 characters".into());
+            }
+            token("Community", &self.community)?;
+            token("Allowed source", &self.community_source)?;
+            if self.community_source.is_empty() {
+                return Err("Enter who may use the community, or 'default' for anyone".into());
+            }
+        }
+        if self.v3_enabled {
+            if self.v3_user.is_empty() || self.v3_user.len() > 32
+                || !self.v3_user.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                return Err("SNMPv3 user must be 1-32 letters, digits, '-', '_' or '.'".into());
+            }
+            if !AUTH_PROTOCOLS.contains(&self.v3_auth_protocol.as_str()) {
+                return Err(format!("Authentication protocol must be one of {}", AUTH_PROTOCOLS.join(", ")));
+            }
+            if !PRIV_PROTOCOLS.contains(&self.v3_priv_protocol.as_str()) {
+                return Err("Privacy protocol must be AES, DES or none".into());
+            }
+            // net-snmp refuses passphrases shorter than 8 characters.
+            if self.v3_auth_pass.len() < 8 {
+                return Err("SNMPv3 authentication passphrase must be at least 8 characters".into());
+            }
+            token("Authentication passphrase", &self.v3_auth_pass)?;
+            if !self.v3_priv_protocol.is_empty() {
+                if self.v3_priv_pass.len() < 8 {
+                    return Err("SNMPv3 privacy passphrase must be at least 8 characters".into());
+                }
+                token("Privacy passphrase", &self.v3_priv_pass)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// snmpd.conf for `cfg`, handing the WolfStack subtree to `helper`.
+pub fn render_snmpd_conf(cfg: &SnmpConfig, helper: &str) -> String {
+    let mut out = vec![
+        format!("{} — changes here are overwritten.", MANAGED_MARKER),
+        "# Configure SNMP in WolfStack under Settings → SNMP.".to_string(),
+        format!("agentAddress {}", cfg.listen),
+    ];
+    if !cfg.location.is_empty() {
+        out.push(format!("sysLocation {}", cfg.location));
+    }
+    if !cfg.contact.is_empty() {
+        out.push(format!("sysContact {}", cfg.contact));
+    }
+    out.push("sysServices 72".into());
+    out.push(String::new());
+    out.push("view wolfstack included .1.3.6.1.2.1".into());
+    out.push("view wolfstack included .1.3.6.1.4.1.2021".into());
+    out.push(format!("view wolfstack included {}", cfg.base_oid));
+    out.push(String::new());
+    if cfg.v2c_enabled {
+        let source = cfg.community_source.as_str();
+        if source == "default" || !source.contains(':') {
+            out.push(format!("rocommunity {} {} -V wolfstack", cfg.community, source));
+        }
+        if source == "default" || source.contains(':') {
+            out.push(format!("rocommunity6 {} {} -V wolfstack", cfg.community, source));
+        }
+    }
+    if cfg.v3_enabled {
+        let (level, privacy) = if cfg.v3_priv_protocol.is_empty() {
+            ("auth", String::new())
+        } else {
+            ("priv", format!(" {} {}", cfg.v3_priv_protocol, cfg.v3_priv_pass))
+        };
+        out.push(format!("createUser {} {} {}{}", cfg.v3_user, cfg.v3_auth_protocol, cfg.v3_auth_pass, privacy));
+        out.push(format!("rouser {} {} -V wolfstack", cfg.v3_user, level));
+    }
+    out.push(String::new());
+    out.push(format!("pass_persist {} {} snmp-pass", cfg.base_oid, helper));
+    out.join("\n") + "\n"
+}
+
+pub fn snmpd_installed() -> bool {
+    ["/usr/sbin/snmpd", "/sbin/snmpd", "/usr/bin/snmpd"].iter().any(|p| std::path::Path::new(p).exists())
+}
+
+pub fn snmpd_running() -> bool {
+    Command::new("systemctl").args(["is-active", "--quiet", "snmpd"])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+fn managed_by_us() -> bool {
+    std::fs::read_to_string(SNMPD_CONF).is_ok_and(|s| s.starts_with(MANAGED_MARKER))
+}
+
+fn systemctl(args: &[&str]) -> Result<(), String> {
+    let out = Command::new("systemctl").args(args).output()
+        .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("systemctl {} failed: {}", args.join(" "), String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Bring snmpd in line with `cfg`: write its config and (re)start it, or
+/// put the original config back and stop it. Blocking.
+pub fn apply(cfg: &SnmpConfig) -> Result<String, String> {
+    if !cfg.enabled {
+        if !managed_by_us() {
+            return Ok("SNMP agent is off".into());
+        }
+        let _ = systemctl(&["disable", "--now", "snmpd"]);
+        if std::path::Path::new(SNMPD_CONF_ORIGINAL).exists() {
+            std::fs::rename(SNMPD_CONF_ORIGINAL, SNMPD_CONF)
+                .map_err(|e| format!("Failed to restore {}: {}", SNMPD_CONF, e))?;
+        } else {
+            let _ = std::fs::remove_file(SNMPD_CONF);
+        }
+        let _ = std::fs::remove_file(SNAPSHOT_PATH);
+        return Ok("SNMP agent stopped and the original snmpd config restored".into());
+    }
+    if !snmpd_installed() {
+        return Err(format!("{}snmpd|snmpd|net-snmp", crate::storage::MISSING_PACKAGE_MARKER));
+    }
+    // The binary being replaced by an upgrade shows as "… (deleted)".
+    let exe = std::env::current_exe().map_err(|e| format!("Can't find the wolfstack binary: {}", e))?;
+    let helper = exe.to_string_lossy().trim_end_matches(" (deleted)").to_string();
+    if helper.chars().any(char::is_whitespace) {
+        return Err(format!("The wolfstack binary path '{}' contains spaces, which snmpd can't run", helper));
+    }
+    if std::path::Path::new(SNMPD_CONF).exists() && !managed_by_us() && !std::path::Path::new(SNMPD_CONF_ORIGINAL).exists() {
+        std::fs::copy(SNMPD_CONF, SNMPD_CONF_ORIGINAL)
+            .map_err(|e| format!("Failed to keep a copy of {}: {}", SNMPD_CONF, e))?;
+    }
+    // The helper answers from the snapshot, so have one before snmpd asks.
+    write_snapshot(cfg);
+    std::fs::create_dir_all("/etc/snmp").map_err(|e| format!("Failed to create /etc/snmp: {}", e))?;
+    // Holds the community and v3 passphrases; snmpd reads it as root
+    // before dropping privileges.
+    crate::paths::write_secure_atomic(SNMPD_CONF, render_snmpd_conf(cfg, &helper))
+        .map_err(|e| format!("Failed to write {}: {}", SNMPD_CONF, e))?;
+    systemctl(&["enable", "snmpd"])?;
+    systemctl(&["restart", "snmpd"])?;
+    Ok(format!("SNMP agent listening on {}", cfg.listen))
+}
+
+// ─── Snapshot ───
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDisk {
+    pub mount: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub percent: f32,
+}
+
+/// What the helper answers from, written by the daemon.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix seconds.
+    pub updated: i64,
+    /// Where the objects are rooted, so the helper needn't read the config.
+    pub base_oid: String,
+    pub hostname: String,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub cpu_count: u64,
+    pub cpu_percent: f32,
+    pub load: [f32; 3],
+    pub mem_total_bytes: u64,
+    pub mem_used_bytes: u64,
+    pub swap_total_bytes: u64,
+    pub swap_used_bytes: u64,
+    pub docker: (u64, u64),
+    pub lxc: (u64, u64),
+    pub vms: (u64, u64),
+    pub issues_critical: u64,
+    pub issues_warning: u64,
+    pub disks: Vec<SnapshotDisk>,
+}
+
+/// Snapshot from the inventory slots; `None` before the first metrics sample.
+fn collect_snapshot(base_oid: &str) -> Option<Snapshot> {
+    use crate::monitoring::inventory;
+    let m = inventory::METRICS.get()?;
+    let count = |list: Option<Vec<crate::containers::ContainerInfo>>| {
+        let list = list.unwrap_or_default();
+        (list.len() as u64, list.iter().filter(|c| c.state == "running").count() as u64)
+    };
+    let vms = inventory::VMS.get().unwrap_or_default();
+    let (issues_critical, issues_warning) = crate::issue_history::open_counts();
+    Some(Snapshot {
+        updated: chrono::Utc::now().timestamp(),
+        base_oid: base_oid.to_string(),
+        hostname: m.hostname.clone(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_secs: m.uptime_secs,
+        cpu_count: m.cpu_count as u64,
+        cpu_percent: m.cpu_usage_percent,
+        load: [m.load_avg.one as f32, m.load_avg.five as f32, m.load_avg.fifteen as f32],
+        mem_total_bytes: m.memory_total_bytes,
+        mem_used_bytes: m.memory_used_bytes,
+        swap_total_bytes: m.swap_total_bytes,
+        swap_used_bytes: m.swap_used_bytes,
+        docker: count(inventory::DOCKER.get()),
+        lxc: count(inventory::LXC.get()),
+        vms: (vms.len() as u64, vms.iter().filter(|v| v.running).count() as u64),
+        issues_critical,
+        issues_warning,
+        disks: m.disks.iter().map(|d| SnapshotDisk {
+            mount: d.mount_point.clone(),
+            total_bytes: d.total_bytes,
+            used_bytes: d.used_bytes,
+            percent: d.usage_percent,
+        }).collect(),
+    })
+}
+
+fn write_snapshot(cfg: &SnmpConfig) {
+    let Some(snapshot) = collect_snapshot(&cfg.base_oid) else { return };
+    let Ok(json) = serde_json::to_string(&snapshot) else { return };
+    // World-readable on purpose: the helper runs as snmpd's user, and
+    // nothing here is more than SNMP already gives out.
+    let tmp = format!("{}.tmp", SNAPSHOT_PATH);
+    if let Err(e) = std::fs::write(&tmp, json).and_then(|_| std::fs::rename(&tmp, SNAPSHOT_PATH)) {
+        tracing::warn!("snmp: failed to write {}: {}", SNAPSHOT_PATH, e);
+    }
+}
+
+/// Keep the snapshot fresh while SNMP is on.
+pub fn start() {
+    tokio::spawn(async move {
+        loop {
+            let _ = tokio::task::spawn_blocking(|| {
+                let cfg = SnmpConfig::load();
+                if cfg.enabled {
+                    write_snapshot(&cfg);
+                }
+            }).await;
+            tokio::time::sleep(std::time::Duration::from_secs(SNAPSHOT_SECS)).await;
+        }
+    });
+}
+
+// ─── MIB ───
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Gauge(u64),
+    Ticks(u64),
+}
+
+impl Value {
+    /// Type name in the pass_persist protocol.
+    fn pass_type(&self) -> &'static str {
+        match self { Value::Str(_) => "string", Value::Gauge(_) => "gauge", Value::Ticks(_) => "timeticks" }
+    }
+
+    fn syntax(&self) -> &'static str {
+        match self { Value::Str(_) => "DisplayString", Value::Gauge(_) => "Gauge32", Value::Ticks(_) => "TimeTicks" }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Gauge(n) | Value::Ticks(n) => (*n).min(u32::MAX as u64).to_string(),
+        }
+    }
+}
+
+fn mb(bytes: u64) -> Value {
+    Value::Gauge(bytes / (1024 * 1024))
+}
+
+fn hundredths(v: f32) -> Value {
+    Value::Gauge((v.max(0.0) * 100.0).round() as u64)
+}
+
+struct Scalar {
+    /// Group and object number under the base; the instance is `.0`.
+    oid: [u32; 2],
+    name: &'static str,
+    description: &'static str,
+    value: fn(&Snapshot, i64) -> Value,
+}
+
+const GROUPS: &[(u32, &str)] = &[(1, "wsNode"), (2, "wsGuests"), (3, "wsIssues")];
+
+const SCALARS: &[Scalar] = &[
+    Scalar { oid: [1, 1], name: "wsHostname", description: "Node hostname.", value: |s, _| Value::Str(s.hostname.clone()) },
+    Scalar { oid: [1, 2], name: "wsVersion", description: "WolfStack version.", value: |s, _| Value::Str(s.version.clone()) },
+    Scalar { oid: [1, 3], name: "wsUptime", description: "Host uptime.", value: |s, _| Value::Ticks(s.uptime_secs * 100) },
+    Scalar { oid: [1, 4], name: "wsCpuCount", description: "Logical CPUs.", value: |s, _| Value::Gauge(s.cpu_count) },
+    Scalar { oid: [1, 5], name: "wsCpuPercent", description: "CPU usage, percent.", value: |s, _| Value::Gauge(s.cpu_percent.max(0.0).round() as u64) },
+    Scalar { oid: [1, 6], name: "wsLoad1", description: "1-minute load average, hundredths.", value: |s, _| hundredths(s.load[0]) },
+    Scalar { oid: [1, 7], name: "wsLoad5", description: "5-minute load average, hundredths.", value: |s, _| hundredths(s.load[1]) },
+    Scalar { oid: [1, 8], name: "wsLoad15", description: "15-minute load average, hundredths.", value: |s, _| hundredths(s.load[2]) },
+    Scalar { oid: [1, 9], name: "wsMemTotalMB", description: "Memory, MiB.", value: |s, _| mb(s.mem_total_bytes) },
+    Scalar { oid: [1, 10], name: "wsMemUsedMB", description: "Memory in use, MiB.", value: |s, _| mb(s.mem_used_bytes) },
+    Scalar { oid: [1, 11], name: "wsSwapTotalMB", description: "Swap, MiB.", value: |s, _| mb(s.swap_total_bytes) },
+    Scalar { oid: [1, 12], name: "wsSwapUsedMB", description: "Swap in use, MiB.", value: |s, _| mb(s.swap_used_bytes) },
+    Scalar { oid: [1, 13], name: "wsDataAge", description: "Seconds since WolfStack last updated these values; alert if it keeps rising.", value: |s, now| Value::Gauge((now - s.updated).max(0) as u64) },
+    Scalar { oid: [2, 1], name: "wsDockerTotal", description: "Docker containers.", value: |s, _| Value::Gauge(s.docker.0) },
+    Scalar { oid: [2, 2], name: "wsDockerRunning", description: "Running Docker containers.", value: |s, _| Value::Gauge(s.docker.1) },
+    Scalar { oid: [2, 3], name: "wsLxcTotal", description: "LXC containers.", value: |s, _| Value::Gauge(s.lxc.0) },
+    Scalar { oid: [2, 4], name: "wsLxcRunning", description: "Running LXC containers.", value: |s, _| Value::Gauge(s.lxc.1) },
+    Scalar { oid: [2, 5], name: "wsVmTotal", description: "Virtual machines.", value: |s, _| Value::Gauge(s.vms.0) },
+    Scalar { oid: [2, 6], name: "wsVmRunning", description: "Running virtual machines.", value: |s, _| Value::Gauge(s.vms.1) },
+    Scalar { oid: [3, 1], name: "wsIssuesCritical", description: "Open critical issues.", value: |s, _| Value::Gauge(s.issues_critical) },
+    Scalar { oid: [3, 2], name: "wsIssuesWarning", description: "Open warnings.", value: |s, _| Value::Gauge(s.issues_warning) },
+];
+
+/// Column number, name, description and value of the disk table
+/// (`base.4.1.<column>.<index>`); column 1 is the not-accessible index.
+type DiskColumn = (u32, &'static str, &'static str, fn(&SnapshotDisk) -> Value);
+
+const DISK_COLUMNS: &[DiskColumn] = &[
+    (2, "wsDiskMount", "Mount point.", |d| Value::Str(d.mount.clone())),
+    (3, "wsDiskTotalMB", "Size, MiB.", |d| mb(d.total_bytes)),
+    (4, "wsDiskUsedMB", "Used, MiB.", |d| mb(d.used_bytes)),
+    (5, "wsDiskUsedPercent", "Used, percent.", |d| Value::Gauge(d.percent.max(0.0).round() as u64)),
+];
+
+pub fn parse_oid(s: &str) -> Option<Vec<u32>> {
+    let s = s.trim().trim_start_matches('.');
+    if s.is_empty() {
+        return Some(Vec::new());
+    }
+    s.split('.').map(|a| a.parse().ok()).collect()
+}
+
+fn format_oid(oid: &[u32]) -> String {
+    oid.iter().map(|a| format!(".{}", a)).collect()
+}
+
+/// Every object under `base`, in OID order.
+pub fn objects(snapshot: &Snapshot, base: &[u32], now: i64) -> Vec<(Vec<u32>, Value)> {
+    let mut out: Vec<(Vec<u32>, Value)> = SCALARS.iter()
+        .map(|s| ([base, &s.oid, &[0]].concat(), (s.value)(snapshot, now)))
+        .collect();
+    for (column, _, _, value) in DISK_COLUMNS {
+        for (i, disk) in snapshot.disks.iter().enumerate() {
+            out.push(([base, &[4, 1, *column, i as u32 + 1]].concat(), value(disk)));
+        }
+    }
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+/// Answer one pass_persist `get` or `getnext`.
+pub fn answer(command: &str, oid: &[u32], objects: &[(Vec<u32>, Value)]) -> String {
+    let found = match command {
+        "get" => objects.iter().find(|(o, _)| o.as_slice() == oid),
+        "getnext" => objects.iter().find(|(o, _)| o.as_slice() > oid),
+        _ => None,
+    };
+    match found {
+        Some((o, v)) => format!("{}\n{}\n{}\n", format_oid(o), v.pass_type(), v.text()),
+        None => "NONE\n".to_string(),
+    }
+}
+
+/// `wolfstack snmp-pass`: the pass_persist loop snmpd runs. Returns when
+/// snmpd closes stdin. Nothing may be logged here — stdout is the protocol.
+pub fn pass_persist() -> i32 {
+    use std::io::Write;
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut stdout = std::io::stdout();
+    while let Some(Ok(line)) = lines.next() {
+        let reply = match line.trim() {
+            "PING" => "PONG\n".to_string(),
+            cmd @ ("get" | "getnext") => {
+                let Some(Ok(oid)) = lines.next() else { break };
+                let objects = std::fs::read_to_string(SNAPSHOT_PATH)
+                    .ok()
+                    .and_then(|s| serde_json::from_str::<Snapshot>(&s).ok())
+                    .and_then(|snap| {
+                        let base = parse_oid(&snap.base_oid)?;
+                        Some(objects(&snap, &base, chrono::Utc::now().timestamp()))
+                    })
+                    .unwrap_or_default();
+                match parse_oid(&oid) {
+                    Some(oid) => answer(cmd, &oid, &objects),
+                    None => "NONE\n".to_string(),
+                }
+            }
+            "set" => {
+                let _ = lines.next();
+                let _ = lines.next();
+                "not-writable\n".to_string()
+            }
+            "" => continue,
+            _ => "NONE\n".to_string(),
+        };
+        if stdout.write_all(reply.as_bytes()).and_then(|_| stdout.flush()).is_err() {
+            break;
+        }
+    }
+    0
+}
+
+/// WOLFSTACK-MIB for objects rooted at `base_oid`.
+pub fn mib(base_oid: &str) -> String {
+    let root = base_oid.trim_start_matches('.').split('.').skip(1).collect::<Vec<_>>().join(" ");
+    let object = |name: &str, syntax: &str, access: &str, description: &str, parent: &str| {
+        format!(
+            "{name} OBJECT-TYPE\n    SYNTAX      {syntax}\n    MAX-ACCESS  {access}\n    STATUS      current\n    DESCRIPTION \"{description}\"\n    ::= {{ {parent} }}\n\n",
+        )
+    };
+    let sample = Snapshot::default();
+    let mut out = format!(
+        "WOLFSTACK-MIB DEFINITIONS ::= BEGIN\n\n\
+         IMPORTS\n    MODULE-IDENTITY, OBJECT-TYPE, Integer32, Gauge32, TimeTicks\n        FROM SNMPv2-SMI\n    DisplayString\n        FROM SNMPv2-TC;\n\n\
+         wolfstackMIB MODULE-IDENTITY\n    LAST-UPDATED \"202610160000Z\"\n    ORGANIZATION \"Wolf Software Systems Ltd\"\n    CONTACT-INFO \"https://wolf.uk.com\"\n\
+         \x20   DESCRIPTION \"WolfStack node metrics, guest counts, open issues and filesystem usage.\"\n    ::= {{ iso {} }}\n\n",
+        root,
+    );
+    for (n, name) in GROUPS {
+        out.push_str(&format!("{} OBJECT IDENTIFIER ::= {{ wolfstackMIB {} }}\n", name, n));
+    }
+    out.push('\n');
+    for s in SCALARS {
+        let group = GROUPS.iter().find(|(n, _)| *n == s.oid[0]).map(|(_, g)| *g).unwrap_or("wolfstackMIB");
+        let syntax = (s.value)(&sample, 0).syntax();
+        out.push_str(&object(s.name, syntax, "read-only", s.description, &format!("{} {}", group, s.oid[1])));
+    }
+    out.push_str(&object("wsDiskTable", "SEQUENCE OF WsDiskEntry", "not-accessible", "Mounted filesystems.", "wolfstackMIB 4"));
+    out.push_str(&format!(
+        "wsDiskEntry OBJECT-TYPE\n    SYNTAX      WsDiskEntry\n    MAX-ACCESS  not-accessible\n    STATUS      current\n\
+         \x20   DESCRIPTION \"One filesystem.\"\n    INDEX       {{ wsDiskIndex }}\n    ::= {{ wsDiskTable 1 }}\n\n\
+         WsDiskEntry ::= SEQUENCE {{\n    wsDiskIndex Integer32,\n{}\n}}\n\n",
+        DISK_COLUMNS.iter()
+            .map(|(_, name, _, value)| format!("    {} {}", name, value(&SnapshotDisk::default()).syntax()))
+            .collect::<Vec<_>>()
+            .join(",\n"),
+    ));
+    out.push_str(&object("wsDiskIndex", "Integer32 (1..2147483647)", "not-accessible", "Row number.", "wsDiskEntry 1"));
+    for (column, name, description, value) in DISK_COLUMNS {
+        let syntax = value(&SnapshotDisk::default()).syntax();
+        out.push_str(&object(name, syntax, "read-only", description, &format!("wsDiskEntry {}", column)));
+    }
+    out.push_str("END\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            updated: 1000,
+            base_oid: ".1.3.6.1.4.1.8072.9999.1".into(),
+            hostname: "node-1".into(),
+            uptime_secs: 60,
+            load: [0.5, 1.25, 2.0],
+            mem_total_bytes: 8 << 30,
+            docker: (3, 2),
+            disks: vec![
+                SnapshotDisk { mount: "/".into(), total_bytes: 100 << 20, used_bytes: 40 << 20, percent: 40.0 },
+                SnapshotDisk { mount: "/var".into(), total_bytes: 200 << 20, used_bytes: 50 << 20, percent: 25.0 },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn get_and_walk_follow_oid_order() {
+        let base = parse_oid(".1.3.6.1.4.1.8072.9999.1").unwrap();
+        let objects = objects(&snapshot(), &base, 1030);
+        let oid = |s: &str| parse_oid(&format!(".1.3.6.1.4.1.8072.9999.1{}", s)).unwrap();
+        assert_eq!(answer("get", &oid(".1.1.0"), &objects), ".1.3.6.1.4.1.8072.9999.1.1.1.0\nstring\nnode-1\n");
+        assert_eq!(answer("get", &oid(".1.3.0"), &objects), ".1.3.6.1.4.1.8072.9999.1.1.3.0\ntimeticks\n6000\n");
+        assert_eq!(answer("get", &oid(".1.7.0"), &objects), ".1.3.6.1.4.1.8072.9999.1.1.7.0\ngauge\n125\n");
+        assert_eq!(answer("get", &oid(".1.9.0"), &objects), ".1.3.6.1.4.1.8072.9999.1.1.9.0\ngauge\n8192\n");
+        assert_eq!(answer("get", &oid(".1.13.0"), &objects), ".1.3.6.1.4.1.8072.9999.1.1.13.0\ngauge\n30\n");
+        assert_eq!(answer("get", &oid(".1.1"), &objects), "NONE\n");
+        // A walk from the base starts at the first scalar; the table is column by column.
+        assert!(answer("getnext", &base, &objects).starts_with(".1.3.6.1.4.1.8072.9999.1.1.1.0\n"));
+        assert!(answer("getnext", &oid(".3.2.0"), &objects).starts_with(".1.3.6.1.4.1.8072.9999.1.4.1.2.1\nstring\n/\n"));
+        assert!(answer("getnext", &oid(".4.1.2.2"), &objects).starts_with(".1.3.6.1.4.1.8072.9999.1.4.1.3.1\ngauge\n100\n"));
+        assert_eq!(answer("getnext", &oid(".4.1.5.2"), &objects), "NONE\n");
+    }
+
+    #[test]
+    fn config_validation() {
+        let mut cfg = SnmpConfig { enabled: true, ..Default::default() };
+        assert!(cfg.validate().is_err());
+        cfg.v2c_enabled = true;
+        cfg.community = "monitor".into();
+        assert!(cfg.validate().is_ok());
+        cfg.community = "bad community".into();
+        assert!(cfg.validate().is_err());
+        cfg.community = "monitor".into();
+        cfg.v3_enabled = true;
+        cfg.v3_user = "nms".into();
+        cfg.v3_auth_pass = "short".into();
+        assert!(cfg.validate().is_err());
+        cfg.v3_auth_pass = "authpass1".into();
+        cfg.v3_priv_pass = "privpass1".into();
+        assert!(cfg.validate().is_ok());
+        cfg.base_oid = ".1.3.6.1.2.1.99".into();
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn snmpd_conf_lines() {
+        let cfg = SnmpConfig {
+            enabled: true,
+            v2c_enabled: true,
+            community: "monitor".into(),
+            community_source: "10.0.0.0/24".into(),
+            v3_enabled: true,
+            v3_user: "nms".into(),
+            v3_auth_pass: "authpass1".into(),
+            v3_priv_pass: "privpass1".into(),
+            ..Default::default()
+        };
+        let conf = render_snmpd_conf(&cfg, "/usr/local/bin/wolfstack");
+        assert!(conf.starts_with(MANAGED_MARKER));
+        assert!(conf.contains("\nrocommunity monitor 10.0.0.0/24 -V wolfstack\n"));
+        assert!(!conf.contains("rocommunity6"));
+        assert!(conf.contains("\ncreateUser nms SHA authpass1 AES privpass1\nrouser nms priv -V wolfstack\n"));
+        assert!(conf.contains("\npass_persist .1.3.6.1.4.1.8072.9999.1 /usr/local/bin/wolfstack snmp-pass\n"));
+    }
+
+    #[test]
+    fn mib_names_every_object() {
+        let mib = mib(".1.3.6.1.4.1.8072.9999.1");
+        assert!(mib.contains("::= { iso 3 6 1 4 1 8072 9999 1 }"));
+        for s in SCALARS {
+            assert!(mib.contains(&format!("{} OBJECT-TYPE", s.name)));
+        }
+        assert!(mib.contains("    wsDiskUsedPercent Gauge32\n}"));
+        assert!(mib.trim_end().ends_with("END"));
+    }
+}
@@ -532,6 +532,17 @@ static POOLS: LazyLock<Mutex<HashMap<String, PoolHandle>>> =
 /// Internal enum holding the concrete pool for each backend. Kept
 /// behind a Mutex<HashMap> so the dispatcher can swap in a fresh
 /// pool after a credential change.
+/// Decrypt a profile's stored password. Also used by the backup module's
+/// mysqldump targets, which hand the plaintext to the dump client via a
+/// 0600 option file rather than a driver pool.
+pub fn plaintext_password(conn: &SqlConnection, cluster_secret: &str) -> Result<String, String> {
+    if conn.password.is_empty() {
+        return Ok(String::new());
+    }
+    crate::auth::oidc::decrypt_secret(&conn.password, cluster_secret)
+        .map_err(|_| password_decrypt_error(&conn.id))
+}
+
 #[derive(Clone)]
 enum PoolHandle {
     Mysql(mysql_async::Pool),
@@ -547,12 +558,7 @@ fn get_or_build_pool(conn: &SqlConnection, cluster_secret: &str) -> Result<PoolH
     // Resolve the plaintext password only here, at pool-build time.
     // Once hyper/tokio-postgres has copied it into the connection,
     // the plaintext goes out of scope immediately.
-    let password = if conn.password.is_empty() {
-        String::new()
-    } else {
-        crate::auth::oidc::decrypt_secret(&conn.password, cluster_secret)
-            .map_err(|_| password_decrypt_error(&conn.id))?
-    };
+    let password = plaintext_password(conn, cluster_secret)?;
 
     let handle = match conn.kind {
         SqlKind::Mariadb | SqlKind::Mysql => {
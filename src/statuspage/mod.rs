@@ -85,6 +85,10 @@ pub enum CheckType {
         url: String,
         #[serde(default = "default_expected_status")]
         expected_status: u16,
+        /// Text the response body must contain — catches the "200 OK
+        /// but it's an error page" outage. Empty = status code only.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        keyword: String,
     },
     Tcp {
         host: String,
@@ -130,10 +134,23 @@ pub struct Monitor {
     pub timeout_secs: u64,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Node ids that run this check. Empty = every node (the original
+    /// behaviour). Pinning a check to a few nodes in different places
+    /// tells "the site is down" from "one uplink is down"; the pinned
+    /// nodes share their results so every node shows the same status.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<String>,
 }
 
 fn default_true() -> bool { true }
 
+impl Monitor {
+    /// Whether the node `self_id` runs this monitor's checks.
+    pub fn runs_on(&self, self_id: &str) -> bool {
+        self.nodes.is_empty() || self.nodes.iter().any(|n| n == self_id)
+    }
+}
+
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -177,6 +194,9 @@ pub struct CheckResult {
     pub latency_ms: u32,
     #[serde(default)]
     pub error: Option<String>,
+    /// Hostname of the node that ran the check.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -199,6 +219,17 @@ pub struct StatusPage {
     pub logo_url: Option<String>,
     #[serde(default)]
     pub footer_text: Option<String>,
+    /// One-line description shown under the title.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Brand colour (`#rrggbb`) for the header rule and links; `None`
+    /// keeps the theme's own colours.
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Left off the `/status` index — reachable only by its slug, for
+    /// pages meant for one customer.
+    #[serde(default)]
+    pub unlisted: bool,
     /// Theme for the public status page (dark, light, midnight, datacenter, forest, amber, glass, deepred)
     #[serde(default)]
     pub theme: Option<String>,
@@ -295,6 +326,10 @@ const MAX_RESULTS: usize = 1440;
 /// Maximum daily uptime entries (90 days)
 const MAX_DAILY_ENTRIES: usize = 90;
 
+/// How often main.rs calls `run_checks`. A monitor runs on the first
+/// tick at least `interval_secs` (less half a tick) after its last run.
+const RUN_TICK_SECS: u64 = 30;
+
 pub struct StatusPageState {
     pub config: RwLock<StatusPageConfig>,
     /// Recent check results per monitor ID
@@ -307,6 +342,9 @@ pub struct StatusPageState {
     /// only: after a restart the node re-observes an ongoing outage and
     /// re-fires monitor_down once, which beats persisting per-check state.
     pub down_since: RwLock<HashMap<String, u64>>,
+    /// Monitor ID → unix time this node last ran it, for `interval_secs`.
+    /// Separate from `results` because those also hold peers' results.
+    last_run: RwLock<HashMap<String, u64>>,
 }
 
 impl StatusPageState {
@@ -319,9 +357,32 @@ impl StatusPageState {
             results: RwLock::new(HashMap::new()),
             daily_uptime: RwLock::new(daily_uptime),
             down_since: RwLock::new(HashMap::new()),
+            last_run: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Whether this node's run of `monitor` is due at `now`.
+    fn is_due(&self, monitor: &Monitor, now: u64) -> bool {
+        self.last_run.read().unwrap().get(&monitor.id)
+            .is_none_or(|last| now + RUN_TICK_SECS / 2 >= last + monitor.interval_secs)
+    }
+
+    /// Every retained check result for a monitor, oldest first — the
+    /// response-time history.
+    pub fn recent_results(&self, monitor_id: &str) -> Vec<CheckResult> {
+        let results = self.results.read().unwrap();
+        results.get(monitor_id).map(|d| d.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Latest result from each location that has checked a monitor.
+    pub fn latest_by_location(&self, monitor_id: &str) -> HashMap<String, CheckResult> {
+        let mut out = HashMap::new();
+        for r in self.recent_results(monitor_id) {
+            out.insert(r.location.clone(), r);
+        }
+        out
+    }
+
     /// Check if any status pages are configured
     pub fn has_any_pages(&self) -> bool {
         let config = self.config.read().unwrap();
@@ -459,11 +520,11 @@ impl StatusPageState {
         config.pages.iter().find(|p| p.slug == slug).cloned()
     }
 
-    /// List page slugs + titles for a specific cluster only
+    /// List page slugs + titles for a specific cluster only (unlisted pages excluded — this feeds the public index)
     pub fn list_pages_for_cluster(&self, cluster: &str) -> Vec<(String, String, bool)> {
         let config = self.config.read().unwrap();
         config.pages.iter()
-            .filter(|p| p.cluster == cluster)
+            .filter(|p| p.cluster == cluster && !p.unlisted)
             .map(|p| (p.slug.clone(), p.title.clone(), p.enabled))
             .collect()
     }
@@ -589,6 +650,64 @@ pub async fn broadcast_to_cluster(
     }
 }
 
+/// Send this node's results for node-pinned monitors to its online
+/// same-cluster peers (`POST /api/statuspage/results`), so nodes that
+/// don't run a check still show its status, history and incidents.
+async fn share_results(
+    results: &[(String, CheckResult)],
+    cluster: &crate::agent::ClusterState,
+    cluster_secret: &str,
+) {
+    let nodes = cluster.get_all_nodes();
+    let self_cluster = nodes.iter()
+        .find(|n| n.is_self)
+        .and_then(|n| n.cluster_name.clone())
+        .unwrap_or_else(|| "WolfStack".to_string());
+    let body = serde_json::json!({
+        "results": results.iter()
+            .map(|(id, r)| serde_json::json!({ "monitor_id": id, "result": r }))
+            .collect::<Vec<_>>(),
+    });
+    let client = &*SP_RPC_CLIENT;
+    for node in nodes.iter().filter(|n| !n.is_self && n.online) {
+        if node.cluster_name.as_deref().unwrap_or("WolfStack") != self_cluster {
+            continue;
+        }
+        for url in crate::api::build_node_urls(&node.address, node.port, "/api/statuspage/results") {
+            match client.post(&url)
+                .header("X-WolfStack-Secret", cluster_secret)
+                .json(&body)
+                .send().await
+            {
+                Ok(resp) => {
+                    let ok = resp.status().is_success();
+                    drain_response(resp).await;
+                    if ok { break; }
+                }
+                Err(e) => tracing::debug!("StatusPage results: {} failed: {}", url, e),
+            }
+        }
+    }
+}
+
+/// Record results a peer ran for node-pinned monitors. Ignores unknown
+/// monitors and ones this node runs for everyone anyway. Returns how
+/// many were recorded.
+pub fn record_peer_results(state: &StatusPageState, results: Vec<(String, CheckResult)>) -> usize {
+    let pinned: std::collections::HashSet<String> = state.config.read().unwrap().monitors.iter()
+        .filter(|m| !m.nodes.is_empty())
+        .map(|m| m.id.clone())
+        .collect();
+    let mut recorded = 0;
+    for (id, result) in results {
+        if pinned.contains(&id) {
+            state.record_result(&id, result);
+            recorded += 1;
+        }
+    }
+    recorded
+}
+
 /// Pull status page config from cluster peers.
 /// Tries each online same-cluster peer until one responds with data.
 pub async fn pull_from_peers(
@@ -739,13 +858,25 @@ pub async fn run_checks(
         return;
     }
 
-    for monitor in &monitors {
+    let location = cluster.get_all_nodes().into_iter()
+        .find(|n| n.is_self)
+        .map(|n| n.hostname)
+        .unwrap_or_default();
+    let tick = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // Results of node-pinned monitors, sent to the peers that don't run them.
+    let mut shared: Vec<(String, CheckResult)> = Vec::new();
+
+    for monitor in monitors.iter().filter(|m| m.runs_on(&cluster.self_id) && state.is_due(m, tick)) {
+        state.last_run.write().unwrap().insert(monitor.id.clone(), tick);
         let timeout = std::time::Duration::from_secs(monitor.timeout_secs);
         let start = std::time::Instant::now();
 
         let (success, error) = match &monitor.check {
-            CheckType::Http { url, expected_status } => {
-                run_http_check(url, *expected_status, timeout).await
+            CheckType::Http { url, expected_status, keyword } => {
+                run_http_check(url, *expected_status, keyword, timeout).await
             }
             CheckType::Tcp { host, port } => {
                 run_tcp_check(host, *port, timeout).await
@@ -767,15 +898,25 @@ pub async fn run_checks(
             .unwrap_or_default()
             .as_secs();
 
-        state.record_result(&monitor.id, CheckResult {
+        let result = CheckResult {
             timestamp: now,
             success,
             latency_ms,
             error,
-        });
+            location: location.clone(),
+        };
+        if !monitor.nodes.is_empty() {
+            shared.push((monitor.id.clone(), result.clone()));
+        }
+        state.record_result(&monitor.id, result);
     }
 
-    // Auto-create/resolve incidents on each page
+    if !shared.is_empty() {
+        share_results(&shared, cluster, cluster_secret).await;
+    }
+
+    // Auto-create/resolve incidents on each page — over every monitor,
+    // including pinned ones whose results arrive from peers.
     auto_manage_incidents(state);
 
     // WolfFunctions monitor_down / monitor_up trigger events + channel
@@ -887,7 +1028,7 @@ fn fire_monitor_transition_events(state: &Arc<StatusPageState>, monitors: &[Moni
     }
 }
 
-async fn run_http_check(url: &str, expected_status: u16, timeout: std::time::Duration) -> (bool, Option<String>) {
+async fn run_http_check(url: &str, expected_status: u16, keyword: &str, timeout: std::time::Duration) -> (bool, Option<String>) {
     // Shared pool (SP_HTTP_CHECK_CLIENT) carries no default timeout —
     // each monitor has its own `timeout_secs`, so we set it per
     // request via RequestBuilder::timeout. Previously this function
@@ -904,11 +1045,13 @@ async fn run_http_check(url: &str, expected_status: u16, timeout: std::time::Dur
             // the response; dropping it unread was the second half of
             // the leak. Errors from drain are ignored — the check's
             // pass/fail verdict is already decided by status code.
-            let _ = resp.bytes().await;
-            if status == expected_status {
-                (true, None)
-            } else {
+            let body = resp.bytes().await.unwrap_or_default();
+            if status != expected_status {
                 (false, Some(format!("Expected {}, got {}", expected_status, status)))
+            } else if !keyword.is_empty() && !String::from_utf8_lossy(&body).contains(keyword) {
+                (false, Some(format!("Keyword \"{}\" not found", keyword)))
+            } else {
+                (true, None)
             }
         }
         Err(e) => {
@@ -1282,6 +1425,55 @@ fn theme_colors(theme: Option<&str>) -> ThemeColors {
     }
 }
 
+/// `#rgb` / `#rrggbb` only — the value is pasted into a stylesheet.
+pub fn valid_accent_color(color: &str) -> bool {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Machine-readable twin of the public page (`/status/{slug}/status.json`)
+/// for customer dashboards and badges. Carries exactly what the HTML page
+/// shows — the page's own monitors and incidents — and nothing about the
+/// checks themselves (URLs, hosts, nodes). `None` for a disabled page.
+pub fn public_page_json(state: &Arc<StatusPageState>, page: &StatusPage) -> Option<serde_json::Value> {
+    if !page.enabled {
+        return None;
+    }
+    let overall = state.page_overall_status(page);
+    let config = state.config.read().unwrap();
+    let services: Vec<serde_json::Value> = page.monitor_ids.iter()
+        .filter_map(|mid| config.monitors.iter().find(|m| m.id == *mid))
+        .map(|m| {
+            let status = state.monitor_status(&m.id);
+            serde_json::json!({
+                "name": m.name,
+                "status": status,
+                "status_label": status.label(),
+                "uptime_percent": (state.uptime_percent(&m.id) * 100.0).round() / 100.0,
+            })
+        })
+        .collect();
+    let incidents: Vec<serde_json::Value> = config.incidents.iter()
+        .filter(|i| page.incident_ids.contains(&i.id) && i.status != IncidentStatus::Resolved)
+        .map(|i| serde_json::json!({
+            "title": i.title,
+            "status": i.status,
+            "impact": i.impact,
+            "created_at": i.created_at,
+            "latest_update": i.updates.last().map(|u| &u.message),
+        }))
+        .collect();
+    Some(serde_json::json!({
+        "title": page.title,
+        "description": page.description,
+        "status": overall,
+        "status_label": overall.label(),
+        "services": services,
+        "active_incidents": incidents,
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
 /// Render the public status page for a specific page slug (only if it belongs to this cluster)
 pub fn render_public_page(state: &Arc<StatusPageState>, slug: &str, local_cluster: &str) -> Option<String> {
     let page = state.find_page_by_slug_and_cluster(slug, local_cluster)?;
@@ -1399,6 +1591,19 @@ pub fn render_public_page_inner(state: &Arc<StatusPageState>, page: &StatusPage)
 
     let footer_text = page.footer_text.as_deref().unwrap_or("Powered by WolfStack");
 
+    let description_html = page.description.as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| format!(r#"<p class="description">{}</p>"#, html_escape(d)))
+        .unwrap_or_default();
+
+    // Re-checked here as well as on save: the config file can be edited by hand.
+    let accent_css = page.accent_color.as_deref()
+        .filter(|c| valid_accent_color(c))
+        .map(|c| format!(
+            "body {{ border-top: 4px solid {c}; }} .header h1 {{ color: {c}; }} .footer a {{ color: {c}; }}"
+        ))
+        .unwrap_or_default();
+
     Some(format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -1475,6 +1680,8 @@ pub fn render_public_page_inner(state: &Arc<StatusPageState>, page: &StatusPage)
         .incident-pagination button:hover:not(:disabled) {{ background: {card_border}; }}
         .incident-pagination button:disabled {{ opacity: 0.4; cursor: default; }}
         .incident-pagination .page-info {{ font-size: 0.8rem; color: {muted}; }}
+        .description {{ margin-top: 0.5rem; font-size: 0.9rem; color: {secondary}; }}
+        {accent_css}
     </style>
 </head>
 <body>
@@ -1482,6 +1689,7 @@ pub fn render_public_page_inner(state: &Arc<StatusPageState>, page: &StatusPage)
         <div class="header">
             {logo}
             <h1>{title}</h1>
+            {description}
         </div>
         <div class="overall-status">{overall_emoji} {overall_label}</div>
         <div class="section-title">Services</div>
@@ -1543,6 +1751,8 @@ pub fn render_public_page_inner(state: &Arc<StatusPageState>, page: &StatusPage)
         services = services_html,
         incidents = incidents_html,
         footer = html_escape(footer_text),
+        description = description_html,
+        accent_css = accent_css,
     ))
 }
 
@@ -1721,4 +1931,36 @@ mod tests {
             assert!(result.is_ok(), "{} failed: {:?}", label, result.err());
         }
     }
+
+    #[test]
+    fn accent_colors() {
+        for ok in ["#fff", "#1e90FF"] {
+            assert!(valid_accent_color(ok), "{}", ok);
+        }
+        for bad in ["", "fff", "#ffff", "#12345g", "red", "#fff;}body{"] {
+            assert!(!valid_accent_color(bad), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn monitor_nodes_and_interval() {
+        let json = r#"{"id":"m1","name":"Web","cluster":"WolfStack","check":{"type":"http","url":"https://x","keyword":"Welcome"},"interval_secs":120,"nodes":["a","b"]}"#;
+        let m: Monitor = serde_json::from_str(json).unwrap();
+        assert!(m.runs_on("a") && !m.runs_on("c"));
+        assert!(matches!(&m.check, CheckType::Http { keyword, .. } if keyword == "Welcome"));
+        let everywhere = Monitor { nodes: Vec::new(), ..m.clone() };
+        assert!(everywhere.runs_on("c"));
+
+        let state = StatusPageState {
+            config: RwLock::new(StatusPageConfig::default()),
+            results: RwLock::new(HashMap::new()),
+            daily_uptime: RwLock::new(HashMap::new()),
+            down_since: RwLock::new(HashMap::new()),
+            last_run: RwLock::new(HashMap::new()),
+        };
+        assert!(state.is_due(&m, 1000), "never run → due");
+        state.last_run.write().unwrap().insert("m1".into(), 1000);
+        assert!(!state.is_due(&m, 1090));
+        assert!(state.is_due(&m, 1105), "within half a tick of the interval");
+    }
 }
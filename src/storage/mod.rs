@@ -11,7 +11,7 @@
 //! - NFS storage via mount -t nfs
 //! - SMB/CIFS storage via mount -t cifs (Synology/QNAP NAS with default SMB shares)
 //! - Local directory bind mounts
-//! - WolfDisk mounts via wolfdisk CLI
+//! - WolfDisk mounts via wolfdisk CLI, and WolfDisk volumes for containers/VMs (see [`wolfdisk`])
 //! - Global mounts replicated across the cluster
 //! - Import of S3 configs from rclone.conf
 
@@ -22,7 +22,16 @@ use std::process::Command;
 use tracing::{warn, error, info};
 use chrono::Utc;
 
+pub mod wolfdisk;
+
 fn config_path() -> String { crate::paths::get().storage_config }
+
+/// Credentials sealed at rest in storage.json: S3 secret keys and SMB
+/// passwords.
+pub const STORAGE_SECRETS: crate::secrets::SecretFields = crate::secrets::SecretFields {
+    label: "storage",
+    fields: &["secret_access_key", "password"],
+};
 const MOUNT_BASE: &str = "/mnt/wolfstack";
 
 // ─── Data Types ───
@@ -45,9 +54,8 @@ pub enum MountType {
 }
 
 /// Per-mount SMB/CIFS credentials + options. Kept separate from S3Config so
-/// the two don't share a struct shape for no reason. `password` is stored in
-/// /etc/wolfstack/storage.json in plain text (same policy as S3 secrets) —
-/// file is root-owned.
+/// the two don't share a struct shape for no reason. `password` is sealed in
+/// /etc/wolfstack/storage.json like the S3 secrets (`STORAGE_SECRETS`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmbConfig {
     #[serde(default)]
@@ -104,6 +112,21 @@ pub struct StorageMount {
     #[serde(default)]
     pub error_message: Option<String>,
     pub created_at: String,
+    /// Raise an issue when the mounted filesystem is fuller than this
+    /// percentage. None = no quota alerting for this mount.
+    #[serde(default)]
+    pub quota_percent: Option<u8>,
+    /// IO priority and read cap for S3 syncs of this mount.
+    #[serde(default)]
+    pub io_limits: crate::io_throttle::IoLimits,
+    /// Live capacity, filled in by `list_mounts_with_usage`.
+    /// Never meaningful in the saved config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<MountUsage>,
+    /// Watchdog verdict, filled in by `list_mounts_with_usage` for mounts
+    /// the watchdog has probed. Never meaningful in the saved config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<MountHealth>,
 }
 
 fn default_status() -> String { "unmounted".to_string() }
@@ -124,7 +147,7 @@ impl Default for StorageConfig {
 pub fn load_config() -> StorageConfig {
     match fs::read_to_string(&config_path()) {
         Ok(content) => {
-            serde_json::from_str(&content).unwrap_or_else(|e| {
+            crate::secrets::from_sealed_json(&content, &STORAGE_SECRETS).unwrap_or_else(|e| {
                 error!("Failed to parse storage config: {}", e);
                 StorageConfig::default()
             })
@@ -139,9 +162,9 @@ pub fn save_config(config: &StorageConfig) -> Result<(), String> {
     let dir = Path::new(&path).parent().unwrap();
     fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
 
-    let json = serde_json::to_string_pretty(config)
+    let json = crate::secrets::to_sealed_json(config, &STORAGE_SECRETS)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&path, json)
+    crate::paths::write_secure(&path, json)
         .map_err(|e| format!("Failed to write config: {}", e))?;
     Ok(())
 }
@@ -184,6 +207,99 @@ pub fn list_mounts() -> Vec<StorageMount> {
     config.mounts
 }
 
+/// `list_mounts` plus live capacity for every mounted entry. Separate
+/// because it statvfs()'s each mount — callers that only need the
+/// config (browse roots, k8s PVs) shouldn't pay for that.
+pub fn list_mounts_with_usage() -> Vec<StorageMount> {
+    let mut mounts = list_mounts();
+    for mount in &mut mounts {
+        mount.health = mount_health(&mount.id);
+        let degraded = mount.health.as_ref().is_some_and(|h| h.state == "degraded");
+        // A degraded mount would only leave another statvfs thread hung.
+        if mount.status == "mounted" && !degraded {
+            mount.usage = mount_usage(&mount.mount_point);
+        }
+    }
+    mounts
+}
+
+// ─── Usage & Quotas ───
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub percent: f64,
+}
+
+/// How long to wait for statvfs on a mount point. A hung NFS/SMB server
+/// or a dead FUSE daemon blocks statvfs uninterruptibly — the storage
+/// list and the issue scan must not hang with it.
+const USAGE_TIMEOUT_SECS: u64 = 3;
+
+fn statvfs_usage(path: &str) -> Option<MountUsage> {
+    let cpath = std::ffi::CString::new(path).ok()?;
+    // SAFETY: zero-init is valid for libc::statvfs (POD struct).
+    let mut sv: libc::statvfs = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::statvfs(cpath.as_ptr(), &mut sv as *mut _) };
+    if rc != 0 { return None; }
+    let block_size = if sv.f_frsize > 0 { sv.f_frsize as u64 } else { sv.f_bsize as u64 };
+    let total_bytes = (sv.f_blocks as u64).saturating_mul(block_size);
+    let used_bytes = (sv.f_blocks.saturating_sub(sv.f_bfree) as u64).saturating_mul(block_size);
+    // Match df: percent of what's usable by non-root (used + available).
+    let avail_bytes = (sv.f_bavail as u64).saturating_mul(block_size);
+    Some(MountUsage { total_bytes, used_bytes, percent: usage_percent(used_bytes, avail_bytes) })
+}
+
+fn usage_percent(used: u64, avail: u64) -> f64 {
+    let denom = used.saturating_add(avail);
+    if denom == 0 { return 0.0; }
+    (used as f64 / denom as f64 * 1000.0).round() / 10.0
+}
+
+/// Capacity of a mounted filesystem, or None when it can't be read in
+/// time. The probe thread is left behind if statvfs never returns.
+pub fn mount_usage(mount_point: &str) -> Option<MountUsage> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let path = mount_point.to_string();
+    std::thread::spawn(move || {
+        let _ = tx.send(statvfs_usage(&path));
+    });
+    rx.recv_timeout(std::time::Duration::from_secs(USAGE_TIMEOUT_SECS)).ok().flatten()
+}
+
+/// A mount that is over its configured usage quota.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaBreach {
+    pub id: String,
+    pub name: String,
+    pub mount_point: String,
+    pub quota_percent: u8,
+    pub usage: MountUsage,
+}
+
+fn quota_breached(quota_percent: Option<u8>, usage: Option<&MountUsage>) -> bool {
+    match (quota_percent, usage) {
+        (Some(q), Some(u)) if q > 0 => u.percent >= q as f64,
+        _ => false,
+    }
+}
+
+/// Mounted entries whose usage has reached their `quota_percent`.
+pub fn quota_breaches() -> Vec<QuotaBreach> {
+    list_mounts_with_usage()
+        .into_iter()
+        .filter(|m| quota_breached(m.quota_percent, m.usage.as_ref()))
+        .filter_map(|m| Some(QuotaBreach {
+            quota_percent: m.quota_percent?,
+            usage: m.usage?,
+            id: m.id,
+            name: m.name,
+            mount_point: m.mount_point,
+        }))
+        .collect()
+}
+
 // ─── Mount Operations ───
 
 // ─── Shutdown ordering for WebUI network mounts ─────────────────────────────
@@ -263,42 +379,14 @@ fn remove_mount_shutdown_dropin(mount_point: &str) {
     }
 }
 
-/// True if `mount_point` is unsafe to mount a filesystem over — a critical
-/// system directory (or a path at/under one). Mounting over these hides the
-/// running system's own files even though the disk is intact: a mount over
-/// `/dev` hides `/dev/null` and every process loses the ability to exec; over
-/// `/usr`/`/bin`/`/lib` it hides every binary; over `/` it hides everything.
-/// Enforced at the single mount chokepoint so it covers BOTH operator-created
-/// mounts and cluster-replicated ones (a global-scoped mount fans out to every
-/// peer). Rejects relative paths and `..` traversal too.
+/// True if `mount_point` is unsafe to mount a filesystem over — not a
+/// valid [`MountPoint`](crate::validate::MountPoint): relative, `..`
+/// traversal, or a critical system directory (or a path under one).
+/// Enforced at the single mount chokepoint so it covers BOTH
+/// operator-created mounts and cluster-replicated ones (a global-scoped
+/// mount fans out to every peer).
 fn is_unsafe_mount_target(mount_point: &str) -> bool {
-    let p = mount_point.trim();
-    if p.is_empty() || !p.starts_with('/') { return true; }
-    if p.split('/').any(|seg| seg == "..") { return true; }
-    // Normalise: collapse duplicate/trailing slashes.
-    let mut norm = String::from("/");
-    for seg in p.split('/').filter(|s| !s.is_empty()) {
-        if norm.len() > 1 { norm.push('/'); }
-        norm.push_str(seg);
-    }
-    if norm == "/" { return true; }
-    // Whole OS trees that must never host a storage mount — rejected at the
-    // directory itself AND any path under it (mounting over /usr/bin hides
-    // every binary just as surely as mounting over /usr).
-    const UNSAFE_TREES: &[&str] = &[
-        "/dev", "/proc", "/sys", "/run", "/boot", "/etc", "/usr",
-        "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/libx32", "/root",
-    ];
-    for root in UNSAFE_TREES {
-        if norm == *root || norm.starts_with(&format!("{}/", root)) {
-            return true;
-        }
-    }
-    // /var and /home: the directory itself is unsafe, but normal data mounts
-    // legitimately live *under* them (/var/lib/vz, /home/user/data) — allow
-    // those, reject only the bare top-level dir.
-    if norm == "/var" || norm == "/home" { return true; }
-    false
+    crate::validate::MountPoint::parse(mount_point).is_err()
 }
 
 /// Mount a storage entry by ID
@@ -442,6 +530,9 @@ pub fn create_mount(mut mount: StorageMount, do_mount: bool) -> Result<StorageMo
     if mount.mount_point.is_empty() {
         mount.mount_point = format!("{}/{}", MOUNT_BASE, mount.id);
     }
+    // Validate and normalise, so "/mnt/data/" can't sneak past the
+    // duplicate check below as a second "/mnt/data".
+    mount.mount_point = crate::validate::MountPoint::parse(&mount.mount_point)?.into_string();
     
     // Set created_at
     if mount.created_at.is_empty() {
@@ -503,6 +594,11 @@ pub fn apply_replicated_mount(mut incoming: StorageMount) -> Result<StorageMount
     if incoming.mount_point.is_empty() {
         incoming.mount_point = format!("{}/{}", MOUNT_BASE, incoming.id);
     }
+    // Normalise when valid; an invalid one is kept as sent so the row
+    // lands in error state via mount_storage's check, visible on this peer.
+    if let Ok(mp) = crate::validate::MountPoint::parse(&incoming.mount_point) {
+        incoming.mount_point = mp.into_string();
+    }
     if incoming.created_at.is_empty() {
         incoming.created_at = Utc::now().to_rfc3339();
     }
@@ -632,6 +728,20 @@ pub fn update_mount(id: &str, updates: serde_json::Value) -> Result<StorageMount
             smb.domain = v.to_string();
         }
     }
+    // null or 0 clears the quota.
+    if let Some(q) = updates.get("quota_percent") {
+        mount.quota_percent = match q.as_u64() {
+            None | Some(0) => None,
+            Some(p) if p <= 100 => Some(p as u8),
+            Some(p) => return Err(format!("Quota must be a percentage (1-100), got {}", p)),
+        };
+    }
+    if let Some(v) = updates.get("io_limits") {
+        let limits: crate::io_throttle::IoLimits = serde_json::from_value(v.clone())
+            .map_err(|e| format!("Invalid io_limits: {}", e))?;
+        limits.validate()?;
+        mount.io_limits = limits;
+    }
     
     // Apply S3 config updates
     if let Some(s3_updates) = updates.get("s3_config") {
@@ -1054,6 +1164,13 @@ pub fn package_for_helper(binary: &str) -> Option<(&'static str, &'static str)>
         ("mount.cifs", DistroFamily::Arch)    => Some(("pacman",  "cifs-utils")),
         ("mount.cifs", DistroFamily::Unknown) => Some(("apt-get", "cifs-utils")),
 
+        // Not a mount helper: the SNMP agent (Settings → SNMP).
+        ("snmpd", DistroFamily::Debian)  => Some(("apt-get", "snmpd")),
+        ("snmpd", DistroFamily::RedHat)  => Some(("dnf",     "net-snmp")),
+        ("snmpd", DistroFamily::Suse)    => Some(("zypper",  "net-snmp")),
+        ("snmpd", DistroFamily::Arch)    => Some(("pacman",  "net-snmp")),
+        ("snmpd", DistroFamily::Unknown) => Some(("apt-get", "snmpd")),
+
         _ => None,
     }
 }
@@ -1658,21 +1775,39 @@ pub fn sync_to_s3(id: &str) -> Result<String, String> {
         .build()
         .map_err(|e| format!("Failed to create runtime: {}", e))?;
 
+    // The reads happen on this thread, so its IO priority covers them; the
+    // read cap is paced below since there's no child process to cgroup.
+    let _throttle = crate::io_throttle::apply(
+        &crate::io_throttle::IoLimits { priority: mount.io_limits.priority.clone(), ..Default::default() },
+        &format!("s3-sync-{}", mount.id));
+    let mut pacer = ReadPacer {
+        limit: std::sync::atomic::AtomicU64::new(mount.io_limits.read_bytes_per_sec()),
+        read: 0,
+        since: std::time::Instant::now(),
+    };
     let uploaded = rt.block_on(async {
         let mut count = 0usize;
-        sync_dir_to_s3(&bucket, &cache_dir, &cache_dir, &mut count).await?;
+        sync_dir_to_s3(&bucket, &cache_dir, &cache_dir, &mut count, &mut pacer).await?;
         Ok::<usize, String>(count)
     })?;
 
     Ok(format!("Synced {} files to S3", uploaded))
 }
 
+/// Bytes read so far by an S3 sync, held to the mount's read cap.
+struct ReadPacer {
+    limit: std::sync::atomic::AtomicU64,
+    read: u64,
+    since: std::time::Instant,
+}
+
 /// Recursively sync a local directory to S3
 async fn sync_dir_to_s3(
     bucket: &s3::bucket::Bucket,
     base_dir: &str,
     current_dir: &str,
     count: &mut usize,
+    pacer: &mut ReadPacer,
 ) -> Result<(), String> {
     let entries = fs::read_dir(current_dir)
         .map_err(|e| format!("Failed to read dir {}: {}", current_dir, e))?;
@@ -1682,7 +1817,7 @@ async fn sync_dir_to_s3(
         let path = entry.path();
 
         if path.is_dir() {
-            Box::pin(sync_dir_to_s3(bucket, base_dir, path.to_str().unwrap_or(""), count)).await?;
+            Box::pin(sync_dir_to_s3(bucket, base_dir, path.to_str().unwrap_or(""), count, pacer)).await?;
         } else if path.is_file() {
             let key = path.strip_prefix(base_dir)
                 .map_err(|e| format!("Path error: {}", e))?
@@ -1694,6 +1829,8 @@ async fn sync_dir_to_s3(
 
             let content = fs::read(&path)
                 .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            pacer.read += content.len() as u64;
+            crate::transfer::pace(&pacer.limit, pacer.read, pacer.since).await;
 
             bucket.put_object(&key, &content).await
                 .map_err(|e| format!("Failed to upload {}: {}", key, e))?;
@@ -1705,6 +1842,159 @@ async fn sync_dir_to_s3(
     Ok(())
 }
 
+// ─── S3 Object Browser ───
+//
+// Direct bucket access for S3 mounts, so objects can be inspected without
+// mounting. Uses the mount's own credentials; nothing touches the local
+// cache directory the rust-s3 mount path syncs into.
+
+/// Longest presigned URL lifetime — SigV4 caps it at 7 days.
+const S3_PRESIGN_MAX_SECS: u32 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct S3ObjectEntry {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct S3Listing {
+    pub bucket: String,
+    pub prefix: String,
+    /// "Folders" one level below `prefix` (common prefixes, ending in `/`).
+    pub prefixes: Vec<String>,
+    pub objects: Vec<S3ObjectEntry>,
+}
+
+/// Bucket handle for an S3 mount, with the same region/endpoint rules as
+/// the mount and sync paths.
+fn s3_bucket_for_mount(id: &str) -> Result<(Box<s3::bucket::Bucket>, String), String> {
+    use s3::bucket::Bucket;
+    use s3::creds::Credentials;
+    use s3::region::Region;
+
+    let config = load_config();
+    let mount = config.mounts.iter().find(|m| m.id == id)
+        .ok_or_else(|| format!("Mount '{}' not found", id))?;
+    let s3 = mount.s3_config.as_ref().ok_or("Not an S3 mount")?;
+    if s3.bucket.is_empty() {
+        return Err("This S3 mount has no bucket configured".to_string());
+    }
+
+    let credentials = Credentials::new(
+        Some(&s3.access_key_id),
+        Some(&s3.secret_access_key),
+        None, None, None,
+    ).map_err(|e| format!("Invalid S3 credentials: {}", e))?;
+
+    let region = if !s3.endpoint.is_empty() {
+        let endpoint = if !s3.endpoint.starts_with("http://") && !s3.endpoint.starts_with("https://") {
+            format!("https://{}", s3.endpoint)
+        } else {
+            s3.endpoint.clone()
+        };
+        let r = effective_s3_region(s3);
+        Region::Custom {
+            region: if r.is_empty() { "us-east-1".to_string() } else { r },
+            endpoint,
+        }
+    } else {
+        s3.region.parse::<Region>().unwrap_or(Region::UsEast1)
+    };
+
+    let bucket = Bucket::new(&s3.bucket, region, credentials)
+        .map_err(|e| format!("Failed to create S3 bucket handle: {}", e))?
+        .with_path_style();
+    Ok((bucket, s3.bucket.clone()))
+}
+
+/// Object keys as the browser sends them: no leading slash (S3 would
+/// store it as part of the key), not empty.
+fn normalise_s3_key(key: &str) -> Result<String, String> {
+    let key = key.trim_start_matches('/');
+    if key.is_empty() {
+        return Err("Object key is required".to_string());
+    }
+    if key.len() > 1024 {
+        return Err("Object key is longer than S3's 1024-byte limit".to_string());
+    }
+    Ok(key.to_string())
+}
+
+fn s3_status_error(action: &str, key: &str, code: u16) -> Result<(), String> {
+    if (200..300).contains(&code) {
+        Ok(())
+    } else {
+        Err(with_s3_credential_hint(format!("{} '{}' failed: HTTP {}", action, key, code)))
+    }
+}
+
+/// One level of the bucket under `prefix`, like a directory listing.
+pub async fn s3_list_objects(id: &str, prefix: &str) -> Result<S3Listing, String> {
+    let (bucket, name) = s3_bucket_for_mount(id)?;
+    let prefix = prefix.trim_start_matches('/').to_string();
+    let pages = bucket.list(prefix.clone(), Some("/".to_string())).await
+        .map_err(|e| with_s3_credential_hint(format!("Failed to list S3 bucket '{}': {}", name, e)))?;
+
+    let mut listing = S3Listing { bucket: name, prefix: prefix.clone(), prefixes: Vec::new(), objects: Vec::new() };
+    for page in pages {
+        for p in page.common_prefixes.unwrap_or_default() {
+            listing.prefixes.push(p.prefix);
+        }
+        for obj in page.contents {
+            // The "folder" marker object for the prefix itself.
+            if obj.key == prefix {
+                continue;
+            }
+            listing.objects.push(S3ObjectEntry { key: obj.key, size: obj.size, last_modified: obj.last_modified });
+        }
+    }
+    Ok(listing)
+}
+
+/// Download an object into memory.
+pub async fn s3_get_object(id: &str, key: &str) -> Result<Vec<u8>, String> {
+    let key = normalise_s3_key(key)?;
+    let (bucket, _) = s3_bucket_for_mount(id)?;
+    let resp = bucket.get_object(&key).await
+        .map_err(|e| with_s3_credential_hint(format!("Failed to download '{}': {}", key, e)))?;
+    s3_status_error("Download", &key, resp.status_code())?;
+    Ok(resp.bytes().to_vec())
+}
+
+/// Upload (or overwrite) an object.
+pub async fn s3_put_object(id: &str, key: &str, data: &[u8]) -> Result<(), String> {
+    let key = normalise_s3_key(key)?;
+    let (bucket, _) = s3_bucket_for_mount(id)?;
+    let resp = bucket.put_object(&key, data).await
+        .map_err(|e| with_s3_credential_hint(format!("Failed to upload '{}': {}", key, e)))?;
+    s3_status_error("Upload", &key, resp.status_code())
+}
+
+/// Delete an object. S3 returns success for keys that don't exist.
+pub async fn s3_delete_object(id: &str, key: &str) -> Result<(), String> {
+    let key = normalise_s3_key(key)?;
+    let (bucket, _) = s3_bucket_for_mount(id)?;
+    let resp = bucket.delete_object(&key).await
+        .map_err(|e| with_s3_credential_hint(format!("Failed to delete '{}': {}", key, e)))?;
+    s3_status_error("Delete", &key, resp.status_code())
+}
+
+/// Presigned URL for downloading (`get`) or uploading (`put`) one object
+/// without credentials. Signing is local — no request is made.
+pub async fn s3_presign(id: &str, key: &str, method: &str, expires_secs: u32) -> Result<String, String> {
+    let key = normalise_s3_key(key)?;
+    let expires = expires_secs.clamp(60, S3_PRESIGN_MAX_SECS);
+    let (bucket, _) = s3_bucket_for_mount(id)?;
+    match method {
+        "get" => bucket.presign_get(&key, expires, None).await,
+        "put" => bucket.presign_put(&key, expires, None, None).await,
+        other => return Err(format!("Unsupported presign method '{}' (use get or put)", other)),
+    }
+    .map_err(|e| format!("Failed to presign '{}': {}", key, e))
+}
+
 // ─── Rclone Config Import ───
 
 /// Parse an rclone.conf file contents and extract S3 remotes as StorageMount definitions
@@ -1788,6 +2078,10 @@ fn rclone_section_to_mount(
         status: "unmounted".to_string(),
         error_message: None,
         created_at: Utc::now().to_rfc3339(),
+        quota_percent: None,
+        usage: None,
+        health: None,
+        io_limits: Default::default(),
     })
 }
 
@@ -1972,6 +2266,288 @@ pub fn auto_mount_all() {
     });
 }
 
+/// Whether every boot auto-mount has been tried — the flag
+/// `auto_mount_all` writes for wolfstack-mounts.target.
+pub fn auto_mounts_settled() -> bool {
+    Path::new(MOUNTS_READY_FLAG).exists()
+}
+
+/// Mount entry `id` unless it already is. For callers that depend on a
+/// mount (container start) rather than the operator's mount button.
+pub fn ensure_mounted(id: &str) -> Result<(), String> {
+    let config = load_config();
+    let mount = config.mounts.iter().find(|m| m.id == id)
+        .ok_or_else(|| format!("Mount '{}' not found", id))?;
+    if check_mounted(&mount.mount_point) {
+        return Ok(());
+    }
+    mount_storage(id).map(|_| ())
+}
+
+// ─── Mount Health Watchdog ───
+//
+// NFS/SMB servers go away and FUSE daemons (s3fs, sshfs, wolfdisk) die,
+// leaving a mount that hangs every stat() — and every container with a bind
+// into it. `check_mount_health` runs from a background loop in main.rs: each
+// mounted network/FUSE entry gets a statfs with a deadline, and one that
+// fails or times out is marked degraded, lazily unmounted and mounted again.
+// Remounts back off; when they keep failing an alert goes out, and another
+// when the mount comes back. Containers started before a remount still hold
+// the dead bind and need a restart — the recovery alert says so.
+
+/// Probe deadline. A little more slack than USAGE_TIMEOUT_SECS — a false
+/// "degraded" here costs a remount.
+const HEALTH_PROBE_TIMEOUT_SECS: u64 = 5;
+/// First remount retry delay; doubles per failure up to the max.
+const REMOUNT_BACKOFF_SECS: u64 = 60;
+const REMOUNT_BACKOFF_MAX_SECS: u64 = 30 * 60;
+/// Failed remounts before the alert — one retry absorbs a server blip.
+const ALERT_AFTER_ATTEMPTS: u32 = 2;
+
+/// Watchdog state of one mount, as shown in the storage list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountHealth {
+    /// "healthy" or "degraded".
+    pub state: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// When the mount was first found degraded (RFC 3339).
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub remount_attempts: u32,
+    pub checked_at: String,
+}
+
+struct HealthEntry {
+    health: MountHealth,
+    next_remount: Option<std::time::Instant>,
+    alerted: bool,
+    /// Held by the probe thread until statfs returns — a probe stuck on
+    /// a dead mount blocks the next one instead of piling up threads.
+    probing: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+static MOUNT_HEALTH: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, HealthEntry>>> =
+    std::sync::LazyLock::new(Default::default);
+
+/// An alert the watchdog wants sent; the caller owns the async dispatch.
+#[derive(Debug, Clone)]
+pub struct MountAlert {
+    pub title: String,
+    pub message: String,
+}
+
+pub fn mount_health(id: &str) -> Option<MountHealth> {
+    MOUNT_HEALTH.lock().unwrap().get(id).map(|e| e.health.clone())
+}
+
+/// Network and FUSE mounts — the ones that go stale. Local bind and
+/// disk mounts fail loudly instead of hanging.
+fn is_watched_mount(t: &MountType) -> bool {
+    is_network_mount(t) || *t == MountType::Wolfdisk
+}
+
+fn is_fuse_mount(t: &MountType) -> bool {
+    matches!(t, MountType::S3 | MountType::Sshfs | MountType::Wolfdisk)
+}
+
+/// Whether `mount_point` is a target in /proc/self/mountinfo content.
+/// Reads the table rather than stat()ing the path, which would hang on
+/// exactly the mounts this is looking for.
+fn mountinfo_has(mountinfo: &str, mount_point: &str) -> bool {
+    let want = mount_point.trim_end_matches('/');
+    mountinfo.lines()
+        .filter_map(|l| l.split(' ').nth(4))
+        .any(|target| unescape_mountinfo(target) == want)
+}
+
+/// Undo the kernel's octal escaping of spaces, tabs, newlines and `\`.
+fn unescape_mountinfo(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b)) {
+            let code = (bytes[i + 1] - b'0') * 64 + (bytes[i + 2] - b'0') * 8 + (bytes[i + 3] - b'0');
+            out.push(code);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn in_mount_table(mount_point: &str) -> bool {
+    fs::read_to_string("/proc/self/mountinfo")
+        .map(|m| mountinfo_has(&m, mount_point))
+        .unwrap_or(false)
+}
+
+/// statfs the mount with a deadline. The probe thread is left behind
+/// when it hangs; `probing` stays set until it returns.
+fn probe_mount(mount_point: &str, probing: &std::sync::Arc<std::sync::atomic::AtomicBool>) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+    if probing.load(Ordering::SeqCst) {
+        return Err("an earlier statfs is still hung".into());
+    }
+    if !in_mount_table(mount_point) {
+        return Err("no longer in the mount table".into());
+    }
+    let cpath = std::ffi::CString::new(mount_point).map_err(|_| "bad mount point".to_string())?;
+    probing.store(true, Ordering::SeqCst);
+    let flag = probing.clone();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        // SAFETY: zero-init is valid for libc::statvfs (POD struct).
+        let mut sv: libc::statvfs = unsafe { std::mem::zeroed() };
+        let rc = unsafe { libc::statvfs(cpath.as_ptr(), &mut sv as *mut _) };
+        let result = if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error().to_string()) };
+        flag.store(false, Ordering::SeqCst);
+        let _ = tx.send(result);
+    });
+    match rx.recv_timeout(std::time::Duration::from_secs(HEALTH_PROBE_TIMEOUT_SECS)) {
+        Ok(result) => result.map_err(|e| format!("statfs failed: {}", e)),
+        Err(_) => Err(format!("statfs timed out after {}s", HEALTH_PROBE_TIMEOUT_SECS)),
+    }
+}
+
+/// Delay before the next remount after `attempts` failed ones.
+fn remount_backoff(attempts: u32) -> std::time::Duration {
+    let secs = REMOUNT_BACKOFF_SECS.saturating_mul(1u64 << attempts.saturating_sub(1).min(16));
+    std::time::Duration::from_secs(secs.min(REMOUNT_BACKOFF_MAX_SECS))
+}
+
+/// Detach the dead mount and mount it again. Lazy unmount because a
+/// hung filesystem can't be unmounted cleanly and may still be busy.
+fn remount(mount: &StorageMount) -> Result<(), String> {
+    let mp = &mount.mount_point;
+    if in_mount_table(mp) {
+        if is_fuse_mount(&mount.mount_type) {
+            let _ = Command::new("fusermount").args(["-uz", mp]).output();
+        }
+        if in_mount_table(mp) {
+            let out = Command::new("umount").args(["-l", mp]).output()
+                .map_err(|e| format!("Failed to run umount: {}", e))?;
+            if !out.status.success() && in_mount_table(mp) {
+                return Err(format!("umount -l failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+            }
+        }
+    }
+    mount_storage(&mount.id).map(|_| ())
+}
+
+/// One watchdog pass over every mounted network/FUSE entry. Blocking —
+/// probes wait up to HEALTH_PROBE_TIMEOUT_SECS and remounts shell out.
+/// Returns the alerts to send.
+pub fn check_mount_health() -> Vec<MountAlert> {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    let config = load_config();
+    let watched: Vec<StorageMount> = {
+        let mut table = MOUNT_HEALTH.lock().unwrap();
+        let watched: Vec<StorageMount> = config.mounts.into_iter()
+            .filter(|m| m.enabled && is_watched_mount(&m.mount_type))
+            // "error" stays watched while degraded: a failed remount
+            // records it, and the watchdog keeps retrying. An operator
+            // unmount sets "unmounted" and drops it.
+            .filter(|m| m.status == "mounted"
+                || (m.status == "error" && table.get(&m.id).is_some_and(|e| e.health.state == "degraded")))
+            .collect();
+        table.retain(|id, _| watched.iter().any(|m| &m.id == id));
+        watched
+    };
+
+    let mut alerts = Vec::new();
+    for mount in watched {
+        let probing = {
+            let mut table = MOUNT_HEALTH.lock().unwrap();
+            table.entry(mount.id.clone()).or_insert_with(|| HealthEntry {
+                health: MountHealth {
+                    state: "healthy".into(), error: None, since: None,
+                    remount_attempts: 0, checked_at: String::new(),
+                },
+                next_remount: None,
+                alerted: false,
+                probing: Arc::new(AtomicBool::new(false)),
+            }).probing.clone()
+        };
+        let mut result = probe_mount(&mount.mount_point, &probing);
+
+        let now = std::time::Instant::now();
+        let remount_due = result.is_err() && {
+            let table = MOUNT_HEALTH.lock().unwrap();
+            table.get(&mount.id).and_then(|e| e.next_remount).is_none_or(|t| t <= now)
+        };
+        if let Err(e) = &result {
+            warn!("storage watchdog: mount '{}' ({}) is degraded: {}", mount.name, mount.mount_point, e);
+        }
+        if remount_due {
+            // A fresh flag — a probe still hung on the detached mount
+            // must not block probing its replacement.
+            let fresh = Arc::new(AtomicBool::new(false));
+            result = remount(&mount).and_then(|_| probe_mount(&mount.mount_point, &fresh));
+            let mut table = MOUNT_HEALTH.lock().unwrap();
+            if let Some(entry) = table.get_mut(&mount.id) {
+                entry.probing = fresh;
+                entry.health.remount_attempts += 1;
+            }
+        }
+
+        let mut table = MOUNT_HEALTH.lock().unwrap();
+        let Some(entry) = table.get_mut(&mount.id) else { continue };
+        entry.health.checked_at = Utc::now().to_rfc3339();
+        match result {
+            Ok(()) => {
+                if entry.health.state == "degraded" {
+                    info!("storage watchdog: mount '{}' recovered after {} remount(s)",
+                        mount.name, entry.health.remount_attempts);
+                    if entry.alerted {
+                        alerts.push(MountAlert {
+                            title: format!("Storage mount '{}' recovered", mount.name),
+                            message: format!(
+                                "{} is responding again after {} remount attempt(s). Containers started \
+                                 before the remount still hold the old mount — restart them.",
+                                mount.mount_point, entry.health.remount_attempts),
+                        });
+                    }
+                }
+                entry.health = MountHealth {
+                    state: "healthy".into(), error: None, since: None,
+                    remount_attempts: 0, checked_at: entry.health.checked_at.clone(),
+                };
+                entry.next_remount = None;
+                entry.alerted = false;
+            }
+            Err(e) => {
+                if entry.health.state != "degraded" {
+                    entry.health.state = "degraded".into();
+                    entry.health.since = Some(entry.health.checked_at.clone());
+                }
+                entry.health.error = Some(e.clone());
+                if remount_due {
+                    error!("storage watchdog: remount of '{}' failed: {}", mount.name, e);
+                    entry.next_remount = Some(now + remount_backoff(entry.health.remount_attempts));
+                }
+                if entry.health.remount_attempts >= ALERT_AFTER_ATTEMPTS && !entry.alerted {
+                    entry.alerted = true;
+                    alerts.push(MountAlert {
+                        title: format!("Storage mount '{}' is down", mount.name),
+                        message: format!(
+                            "{} ({}) stopped responding and {} remount attempt(s) failed: {}. \
+                             Containers using it may hang; WolfStack keeps retrying.",
+                            mount.mount_point, mount.source, entry.health.remount_attempts, e),
+                    });
+                }
+            }
+        }
+    }
+    alerts
+}
+
 // ─── Container Mount Integration ───
 
 /// Get all mounted storage entries that can be attached to containers
@@ -2366,13 +2942,18 @@ pub fn install_provider(name: &str) -> Result<String, String> {
 
 // ─── System Logs ───
 
-/// Read system logs from journalctl
-pub fn read_system_logs(lines: usize, search: Option<&str>, unit: Option<&str>) -> Vec<String> {
+/// journalctl arguments for [`read_system_logs`] and the streamed log
+/// download: the last `lines` entries (all of them when `None`), optionally
+/// for one unit and matching a pattern.
+pub fn journalctl_args(lines: Option<usize>, search: Option<&str>, unit: Option<&str>) -> Vec<String> {
     let mut args = vec![
         "--no-pager".to_string(),
-        "-n".to_string(), lines.to_string(),
         "--output".to_string(), "short-iso".to_string(),
     ];
+    if let Some(n) = lines {
+        args.push("-n".to_string());
+        args.push(n.to_string());
+    }
     if let Some(u) = unit {
         if !u.is_empty() {
             args.push("-u".to_string());
@@ -2385,6 +2966,12 @@ pub fn read_system_logs(lines: usize, search: Option<&str>, unit: Option<&str>)
             args.push(s.to_string());
         }
     }
+    args
+}
+
+/// Read system logs from journalctl
+pub fn read_system_logs(lines: usize, search: Option<&str>, unit: Option<&str>) -> Vec<String> {
+    let args = journalctl_args(Some(lines), search, unit);
 
     match Command::new("journalctl").args(&args).output() {
         Ok(o) if o.status.success() => {
@@ -3138,6 +3725,40 @@ mod mounts_target_tests {
     }
 }
 
+#[cfg(test)]
+mod mount_health_tests {
+    use super::*;
+
+    #[test]
+    fn mount_table_lookup_handles_escapes() {
+        let info = "36 35 98:0 / /mnt/wolfstack/nas rw,noatime master:1 - nfs4 nas:/vol rw\n\
+                    37 35 0:51 / /mnt/wolfstack/my\\040share rw - cifs //nas/share rw\n";
+        assert!(mountinfo_has(info, "/mnt/wolfstack/nas"));
+        assert!(mountinfo_has(info, "/mnt/wolfstack/nas/"));
+        assert!(mountinfo_has(info, "/mnt/wolfstack/my share"));
+        assert!(!mountinfo_has(info, "/mnt/wolfstack"));
+        assert_eq!(unescape_mountinfo("a\\134b\\011"), "a\\b\t");
+    }
+
+    #[test]
+    fn remount_backoff_doubles_to_the_cap() {
+        assert_eq!(remount_backoff(1).as_secs(), 60);
+        assert_eq!(remount_backoff(2).as_secs(), 120);
+        assert_eq!(remount_backoff(5).as_secs(), 960);
+        assert_eq!(remount_backoff(6).as_secs(), REMOUNT_BACKOFF_MAX_SECS);
+        assert_eq!(remount_backoff(u32::MAX).as_secs(), REMOUNT_BACKOFF_MAX_SECS);
+    }
+
+    #[test]
+    fn only_network_and_fuse_mounts_are_watched() {
+        assert!(is_watched_mount(&MountType::Nfs));
+        assert!(is_watched_mount(&MountType::S3));
+        assert!(is_watched_mount(&MountType::Wolfdisk));
+        assert!(!is_watched_mount(&MountType::Directory));
+        assert!(!is_watched_mount(&MountType::Disk));
+    }
+}
+
 #[cfg(test)]
 mod mount_dropin_tests {
     use super::*;
@@ -3165,6 +3786,14 @@ mod mount_dropin_tests {
         assert!(MOUNT_DROPIN_BODY.starts_with("[Unit]\n"));
     }
 
+    #[test]
+    fn s3_keys_are_normalised() {
+        assert_eq!(normalise_s3_key("/backups/a.tar").unwrap(), "backups/a.tar");
+        assert!(normalise_s3_key("").is_err());
+        assert!(normalise_s3_key("///").is_err());
+        assert!(normalise_s3_key(&"k".repeat(1025)).is_err());
+    }
+
     fn s3cfg(region: &str, endpoint: &str) -> S3Config {
         S3Config {
             access_key_id: "k".into(), secret_access_key: "s".into(),
@@ -3237,4 +3866,18 @@ mod mount_dropin_tests {
         // Empty incoming id never matches on id — falls through to mount_point.
         assert_eq!(replicated_mount_match(&existing, &sm("", "/mnt/b")), Some(1));
     }
+
+    #[test]
+    fn quota_breach_uses_df_style_percent() {
+        // 90 used + 10 available → 90%, regardless of root-reserved blocks.
+        assert_eq!(usage_percent(90, 10), 90.0);
+        assert_eq!(usage_percent(0, 0), 0.0);
+        let usage = MountUsage { total_bytes: 120, used_bytes: 90, percent: 90.0 };
+        assert!(quota_breached(Some(90), Some(&usage)));
+        assert!(quota_breached(Some(80), Some(&usage)));
+        assert!(!quota_breached(Some(95), Some(&usage)));
+        assert!(!quota_breached(Some(0), Some(&usage)));
+        assert!(!quota_breached(None, Some(&usage)));
+        assert!(!quota_breached(Some(50), None));
+    }
 }
@@ -0,0 +1,384 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! WolfDisk volumes — named, replicated storage for containers and VMs.
+//!
+//! A volume is a top-level directory on the WolfDisk mount (the same thing
+//! the gateway wizard lists as a "WolfDisk volume"). WolfDisk replicates
+//! it, so every node with WolfDisk mounted sees the same volumes. Volumes
+//! created here carry a `.wolfstack-volume.json` recording their size,
+//! who made them and what they're attached to; because that file lives on
+//! WolfDisk too, attachments made on one node are visible from all of them.
+//!
+//! - **Containers** get the volume bind-mounted (`lxc.mount.entry` or a PVE
+//!   mount point). Any number of containers can share one.
+//! - **VMs** get a qcow2 of the volume's size inside it, registered as an
+//!   extra disk. The image follows the volume, so detaching and attaching
+//!   on another node keeps the data. A disk image has one writer, so a
+//!   volume attached to a VM can't be attached to anything else.
+//!
+//! Nothing is created unless the WolfDisk mount is live — otherwise the
+//! directory would land on the node's own disk and never replicate.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::containers;
+use crate::vms::manager::VmManager;
+
+const META_FILE: &str = ".wolfstack-volume.json";
+
+/// Peers seen within this many seconds count as up.
+const PEER_UP_SECS: u64 = 5;
+
+/// Where a volume is attached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    /// `lxc` or `vm`.
+    pub kind: String,
+    /// Container or VM name.
+    pub target: String,
+    /// Hostname of the node the container or VM lives on.
+    #[serde(default)]
+    pub node: String,
+    /// Mount point inside the container, or the disk image for a VM.
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub attached_at: i64,
+}
+
+/// What WolfStack keeps in a volume's `.wolfstack-volume.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VolumeMeta {
+    /// Size in GB: the disk size for VMs, a soft quota for containers.
+    #[serde(default)]
+    pub size_gb: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub created_by: String,
+    /// Unix seconds.
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Volume {
+    pub name: String,
+    pub path: String,
+    /// False for directories WolfStack didn't create (no metadata file).
+    pub managed: bool,
+    pub used_bytes: u64,
+    #[serde(flatten)]
+    pub meta: VolumeMeta,
+}
+
+/// WolfDisk's state on this node, for the volumes panel.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Health {
+    pub installed: bool,
+    pub mounted: bool,
+    pub mount_path: String,
+    /// The daemon has rewritten its status file in the last 10 seconds.
+    pub running: bool,
+    pub role: String,
+    pub replication_mode: String,
+    pub replication_factor: usize,
+    pub index_version: u64,
+    pub peers_total: usize,
+    pub peers_up: usize,
+    /// `healthy`, `degraded` (peers down, or fewer nodes than the
+    /// replication factor) or `offline` (not running or not mounted).
+    pub state: String,
+}
+
+fn mount_path() -> String {
+    super::read_wolfdisk_info().map(|i| i.mount_path).unwrap_or_else(|| "/mnt/wolfdisk".to_string())
+}
+
+fn is_mounted(path: &str) -> bool {
+    std::fs::read_to_string("/proc/mounts").map(|m| {
+        m.lines().any(|l| l.split_whitespace().nth(1) == Some(path))
+    }).unwrap_or(false)
+}
+
+fn health_state(h: &Health) -> &'static str {
+    if !h.running || !h.mounted {
+        "offline"
+    } else if h.peers_up < h.peers_total || (h.replication_mode == "replicated" && h.peers_total + 1 < h.replication_factor) {
+        "degraded"
+    } else {
+        "healthy"
+    }
+}
+
+pub fn health() -> Health {
+    let info = super::read_wolfdisk_info();
+    let root = mount_path();
+    let mut h = Health {
+        installed: super::has_wolfdisk(),
+        mounted: is_mounted(&root),
+        mount_path: root,
+        replication_mode: info.as_ref().map(|i| i.replication_mode.clone()).unwrap_or_default(),
+        replication_factor: info.as_ref().map(|i| i.replication_factor).unwrap_or(0),
+        ..Default::default()
+    };
+    if let Some(status) = super::wolfdisk_cluster_status() {
+        h.running = status.get("status_age_secs").and_then(|v| v.as_u64()).is_none_or(|age| age <= 10);
+        h.role = status.get("role").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        h.index_version = status.get("index_version").and_then(|v| v.as_u64()).unwrap_or(0);
+        let peers = status.get("peers").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        h.peers_total = peers.len();
+        h.peers_up = peers.iter()
+            .filter(|p| p.get("last_seen_secs_ago").and_then(|v| v.as_u64()).is_some_and(|s| s < PEER_UP_SECS))
+            .count();
+    }
+    h.state = health_state(&h).to_string();
+    h
+}
+
+/// Volume names become directory names and VM disk names.
+fn validate_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && name.len() <= 63
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && name.chars().next().is_some_and(|c| c.is_ascii_alphanumeric());
+    if ok { Ok(()) } else {
+        Err("Volume names are 1-63 letters, digits, '-' or '_', starting with a letter or digit".to_string())
+    }
+}
+
+/// The volume's directory, provided WolfDisk is mounted and it exists.
+fn volume_dir(name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    let root = mount_path();
+    if !is_mounted(&root) {
+        return Err(format!("WolfDisk is not mounted at {} on this node", root));
+    }
+    let dir = Path::new(&root).join(name);
+    if !dir.is_dir() {
+        return Err(format!("No WolfDisk volume named '{}'", name));
+    }
+    Ok(dir)
+}
+
+fn read_meta(dir: &Path) -> Option<VolumeMeta> {
+    serde_json::from_str(&std::fs::read_to_string(dir.join(META_FILE)).ok()?).ok()
+}
+
+fn write_meta(dir: &Path, meta: &VolumeMeta) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(meta).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(META_FILE), json).map_err(|e| format!("Failed to write volume metadata: {}", e))
+}
+
+/// Bytes under `dir`, not following symlinks.
+fn dir_usage(dir: &Path) -> u64 {
+    let Ok(rd) = std::fs::read_dir(dir) else { return 0 };
+    rd.flatten().map(|e| match e.metadata() {
+        Ok(m) if m.is_dir() => dir_usage(&e.path()),
+        Ok(m) if m.is_file() => m.len(),
+        _ => 0,
+    }).sum()
+}
+
+/// Every volume on the WolfDisk mount, by name. Empty when not mounted.
+pub fn list_volumes() -> Vec<Volume> {
+    let root = mount_path();
+    if !is_mounted(&root) { return Vec::new(); }
+    let Ok(rd) = std::fs::read_dir(&root) else { return Vec::new() };
+    let mut volumes: Vec<Volume> = rd.flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|e| {
+            let name = e.file_name().to_str()?.to_string();
+            if name.starts_with('.') { return None; }
+            let dir = e.path();
+            let meta = read_meta(&dir);
+            Some(Volume {
+                name,
+                path: dir.to_string_lossy().to_string(),
+                managed: meta.is_some(),
+                used_bytes: dir_usage(&dir),
+                meta: meta.unwrap_or_default(),
+            })
+        })
+        .collect();
+    volumes.sort_by(|a, b| a.name.cmp(&b.name));
+    volumes
+}
+
+pub fn create_volume(name: &str, size_gb: u32, description: &str, user: &str) -> Result<Volume, String> {
+    validate_name(name)?;
+    if size_gb == 0 {
+        return Err("Size must be at least 1 GB".to_string());
+    }
+    let root = mount_path();
+    if !is_mounted(&root) {
+        return Err(format!("WolfDisk is not mounted at {} on this node — volumes created now wouldn't replicate", root));
+    }
+    let dir = Path::new(&root).join(name);
+    if dir.exists() {
+        return Err(format!("A WolfDisk volume named '{}' already exists", name));
+    }
+    std::fs::create_dir(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let meta = VolumeMeta {
+        size_gb,
+        description: description.trim().to_string(),
+        created_by: user.to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        attachments: Vec::new(),
+    };
+    if let Err(e) = write_meta(&dir, &meta) {
+        let _ = std::fs::remove_dir(&dir);
+        return Err(e);
+    }
+    Ok(Volume { name: name.to_string(), path: dir.to_string_lossy().to_string(), managed: true, used_bytes: 0, meta })
+}
+
+/// Delete a volume and everything in it. Refused while it's attached.
+pub fn delete_volume(name: &str) -> Result<(), String> {
+    let dir = volume_dir(name)?;
+    if let Some(meta) = read_meta(&dir)
+        && let Some(a) = meta.attachments.first()
+    {
+        return Err(format!("'{}' is attached to {} '{}' on {} — detach it first", name, a.kind, a.target, a.node));
+    }
+    std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete {}: {}", dir.display(), e))
+}
+
+/// Whether one more `kind` attachment fits alongside `existing`.
+fn can_attach(existing: &[Attachment], kind: &str, target: &str) -> Result<(), String> {
+    if let Some(a) = existing.iter().find(|a| a.kind == kind && a.target == target) {
+        return Err(format!("Already attached to {} '{}'", a.kind, a.target));
+    }
+    if let Some(vm) = existing.iter().find(|a| a.kind == "vm") {
+        return Err(format!("Attached to VM '{}' on {}; a VM disk can't be shared", vm.target, vm.node));
+    }
+    if kind == "vm" && !existing.is_empty() {
+        return Err("A volume has to be detached from every container before a VM can use it".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttachOptions {
+    /// `lxc` or `vm`.
+    pub kind: String,
+    pub target: String,
+    /// Mount point inside a container; defaults to `/mnt/<volume>`.
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub read_only: bool,
+    /// Disk bus for a VM (virtio, scsi, ide).
+    #[serde(default)]
+    pub bus: Option<String>,
+}
+
+/// Attach a volume to a container or a stopped VM on this node.
+pub fn attach(name: &str, opts: &AttachOptions, vms: &VmManager, node: &str) -> Result<String, String> {
+    let dir = volume_dir(name)?;
+    let mut meta = read_meta(&dir).unwrap_or_default();
+    let (kind, target) = (opts.kind.as_str(), opts.target.as_str());
+    can_attach(&meta.attachments, kind, target)?;
+    let dir_str = dir.to_string_lossy().to_string();
+    let (message, path) = match kind {
+        "lxc" => {
+            let container_path = match opts.path.trim() {
+                "" => format!("/mnt/{}", name),
+                p => p.to_string(),
+            };
+            let msg = containers::lxc_add_mount(target, &containers::LxcMountOptions {
+                host_path: dir_str,
+                container_path: container_path.clone(),
+                read_only: opts.read_only,
+                shared: true,
+                backup: false,
+                quota: false,
+                size_gb: None,
+            })?;
+            (msg, container_path)
+        }
+        "vm" => {
+            if meta.size_gb == 0 {
+                return Err(format!("'{}' has no size set; VMs need one for their disk", name));
+            }
+            let disk = vms.attach_volume_file(target, name, meta.size_gb, &dir_str, opts.bus.as_deref())?;
+            (format!("Attached {} to '{}'", disk.display(), target), disk.to_string_lossy().to_string())
+        }
+        _ => return Err(format!("Unknown attachment type '{}' (use lxc or vm)", kind)),
+    };
+    meta.attachments.push(Attachment {
+        kind: kind.to_string(),
+        target: target.to_string(),
+        node: node.to_string(),
+        path,
+        attached_at: chrono::Utc::now().timestamp(),
+    });
+    write_meta(&dir, &meta)?;
+    Ok(message)
+}
+
+/// Undo [`attach`]. A VM's disk image stays in the volume so the data
+/// survives and can be attached again.
+pub fn detach(name: &str, kind: &str, target: &str, vms: &VmManager) -> Result<String, String> {
+    let dir = volume_dir(name)?;
+    let mut meta = read_meta(&dir).unwrap_or_default();
+    let message = match kind {
+        "lxc" => containers::lxc_remove_mount(target, &dir.to_string_lossy())?,
+        "vm" => {
+            vms.remove_volume(target, name, false)?;
+            format!("Detached '{}' from '{}'", name, target)
+        }
+        _ => return Err(format!("Unknown attachment type '{}' (use lxc or vm)", kind)),
+    };
+    meta.attachments.retain(|a| !(a.kind == kind && a.target == target));
+    write_meta(&dir, &meta)?;
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(kind: &str, target: &str) -> Attachment {
+        Attachment { kind: kind.into(), target: target.into(), node: "node-a".into(), path: String::new(), attached_at: 0 }
+    }
+
+    #[test]
+    fn volume_names() {
+        assert!(validate_name("web-data_1").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("-x").is_err());
+        assert!(validate_name(".hidden").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("..").is_err());
+        assert!(validate_name(&"a".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn vm_disks_are_exclusive() {
+        assert!(can_attach(&[], "vm", "db").is_ok());
+        let shared = [attachment("lxc", "web1")];
+        assert!(can_attach(&shared, "lxc", "web2").is_ok());
+        assert!(can_attach(&shared, "lxc", "web1").is_err());
+        assert!(can_attach(&shared, "vm", "db").is_err());
+        assert!(can_attach(&[attachment("vm", "db")], "lxc", "web1").is_err());
+    }
+
+    #[test]
+    fn health_states() {
+        let mut h = Health { mounted: true, running: true, replication_mode: "replicated".into(), replication_factor: 3, peers_total: 2, peers_up: 2, ..Default::default() };
+        assert_eq!(health_state(&h), "healthy");
+        h.peers_up = 1;
+        assert_eq!(health_state(&h), "degraded");
+        // Every peer up, but too few nodes for three replicas.
+        h.peers_total = 1;
+        assert_eq!(health_state(&h), "degraded");
+        h.mounted = false;
+        assert_eq!(health_state(&h), "offline");
+    }
+}
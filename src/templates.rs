@@ -0,0 +1,421 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Container template library ("golden images").
+//!
+//! A template is a saved copy of an existing container that can be
+//! provisioned again, on this node or any other, in one click:
+//!
+//! - **LXC** templates hold the `lxc_export` archive (vzdump on Proxmox,
+//!   rootfs tarball elsewhere) plus its `ContainerExportMeta`, and deploy
+//!   through the same import path a cross-node clone uses.
+//! - **Docker** templates hold the run spec read from `docker inspect` —
+//!   image, ports, env, volumes and limits — and deploy as pull + create.
+//!   The image itself isn't saved; the registry is the source of truth.
+//!
+//! Each template is a directory `<library>/<id>/` holding `template.json`
+//! and, for LXC, the archive. The library defaults to the config dir but
+//! can be pointed at a shared storage mount (NFS, CephFS, …) so every
+//! node sees the same list.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::containers::ContainerExportMeta;
+
+const TEMPLATE_FILE: &str = "template.json";
+
+fn settings_path() -> String {
+    format!("{}/templates.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibrarySettings {
+    /// Directory holding the templates. Empty = `<config_dir>/templates`.
+    #[serde(default)]
+    pub library_path: String,
+}
+
+impl LibrarySettings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = settings_path();
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+}
+
+/// Set the library location. The directory must already exist (a shared
+/// mount that isn't mounted yet shouldn't be silently created on the
+/// local disk underneath it).
+pub fn set_library_path(path: &str) -> Result<(), String> {
+    let path = path.trim();
+    if !path.is_empty() {
+        if !path.starts_with('/') {
+            return Err("Library path must be absolute".to_string());
+        }
+        if !Path::new(path).is_dir() {
+            return Err(format!("{} is not a directory", path));
+        }
+    }
+    LibrarySettings { library_path: path.trim_end_matches('/').to_string() }.save()
+}
+
+pub fn library_dir() -> PathBuf {
+    let settings = LibrarySettings::load();
+    if settings.library_path.is_empty() {
+        PathBuf::from(format!("{}/templates", crate::paths::get().config_dir))
+    } else {
+        PathBuf::from(settings.library_path)
+    }
+}
+
+/// Run spec for a Docker template — the `docker_create` arguments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DockerSpec {
+    pub image: String,
+    #[serde(default)]
+    pub ports: Vec<String>,
+    #[serde(default)]
+    pub env: Vec<String>,
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    #[serde(default)]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub cpus: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// `lxc` or `docker`.
+    pub kind: String,
+    pub created_at: i64,
+    #[serde(default)]
+    pub source_node: String,
+    #[serde(default)]
+    pub source_container: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker: Option<DockerSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lxc: Option<ContainerExportMeta>,
+    /// Archive file name inside the template directory (LXC only).
+    #[serde(default)]
+    pub archive: String,
+    #[serde(default)]
+    pub size_bytes: u64,
+}
+
+/// Template ids are generated UUIDs; anything else is refused before it
+/// gets near a path join.
+pub fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+fn template_dir(id: &str) -> Result<PathBuf, String> {
+    if !valid_id(id) {
+        return Err(format!("Invalid template id '{}'", id));
+    }
+    Ok(library_dir().join(id))
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name is required".to_string());
+    }
+    if name.len() > 100 {
+        return Err("Template name must be 100 characters or fewer".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Every template in the library, newest first. Unreadable entries are
+/// skipped rather than failing the whole list.
+pub fn list() -> Vec<Template> {
+    let mut out: Vec<Template> = std::fs::read_dir(library_dir())
+        .map(|rd| {
+            rd.flatten()
+                .filter_map(|e| std::fs::read_to_string(e.path().join(TEMPLATE_FILE)).ok())
+                .filter_map(|s| serde_json::from_str(&s).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    out
+}
+
+pub fn get(id: &str) -> Result<Template, String> {
+    let dir = template_dir(id)?;
+    let s = std::fs::read_to_string(dir.join(TEMPLATE_FILE)).map_err(|_| format!("Template '{}' not found", id))?;
+    serde_json::from_str(&s).map_err(|e| format!("Template '{}' is corrupt: {}", id, e))
+}
+
+/// Full path of an LXC template's archive.
+pub fn archive_path(t: &Template) -> Result<PathBuf, String> {
+    if t.archive.is_empty() || t.archive.contains('/') {
+        return Err(format!("Template '{}' has no archive", t.name));
+    }
+    Ok(template_dir(&t.id)?.join(&t.archive))
+}
+
+fn write_template(dir: &Path, t: &Template) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(t).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(TEMPLATE_FILE), json).map_err(|e| format!("Failed to write template: {}", e))
+}
+
+fn new_template(kind: &str, name: &str, description: &str, source_node: &str, container: &str) -> Result<Template, String> {
+    Ok(Template {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: validate_name(name)?,
+        description: description.trim().to_string(),
+        kind: kind.to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        source_node: source_node.to_string(),
+        source_container: container.to_string(),
+        docker: None,
+        lxc: None,
+        archive: String::new(),
+        size_bytes: 0,
+    })
+}
+
+/// `docker_create` arguments reproducing the container `inspect` describes.
+pub fn docker_spec_from_inspect(inspect: &serde_json::Value) -> Result<DockerSpec, String> {
+    let image = inspect
+        .pointer("/Config/Image")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or("Container has no image")?
+        .to_string();
+
+    // PATH is baked into the image; carrying it over only pins the old one.
+    let env = inspect
+        .pointer("/Config/Env")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|e| e.as_str()).filter(|e| !e.starts_with("PATH=")).map(String::from).collect())
+        .unwrap_or_default();
+
+    let mut ports = Vec::new();
+    if let Some(bindings) = inspect.pointer("/HostConfig/PortBindings").and_then(|v| v.as_object()) {
+        for (container_port, hosts) in bindings {
+            let (port, proto) = container_port.split_once('/').unwrap_or((container_port, "tcp"));
+            for host in hosts.as_array().into_iter().flatten() {
+                let host_port = host.get("HostPort").and_then(|v| v.as_str()).unwrap_or("");
+                if host_port.is_empty() {
+                    continue;
+                }
+                let mut p = format!("{}:{}", host_port, port);
+                if proto != "tcp" {
+                    p.push('/');
+                    p.push_str(proto);
+                }
+                ports.push(p);
+            }
+        }
+    }
+    ports.sort();
+
+    let mut volumes = Vec::new();
+    for m in inspect.pointer("/Mounts").and_then(|v| v.as_array()).into_iter().flatten() {
+        let dest = m.get("Destination").and_then(|v| v.as_str()).unwrap_or("");
+        let src = match m.get("Type").and_then(|v| v.as_str()) {
+            Some("volume") => m.get("Name").and_then(|v| v.as_str()).unwrap_or(""),
+            Some("bind") => m.get("Source").and_then(|v| v.as_str()).unwrap_or(""),
+            _ => continue,
+        };
+        if src.is_empty() || dest.is_empty() {
+            continue;
+        }
+        let rw = m.get("RW").and_then(|v| v.as_bool()).unwrap_or(true);
+        volumes.push(if rw { format!("{}:{}", src, dest) } else { format!("{}:{}:ro", src, dest) });
+    }
+
+    let memory = inspect
+        .pointer("/HostConfig/Memory")
+        .and_then(|v| v.as_u64())
+        .filter(|b| *b > 0)
+        .map(|b| format!("{}m", b / (1024 * 1024)));
+    let cpus = inspect
+        .pointer("/HostConfig/NanoCpus")
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n > 0)
+        .map(|n| {
+            let c = n as f64 / 1e9;
+            if c.fract() == 0.0 { format!("{}", c as u64) } else { format!("{:.2}", c) }
+        });
+
+    Ok(DockerSpec { image, ports, env, volumes, memory, cpus })
+}
+
+/// Save a Docker container's run spec as a template. Blocking.
+pub fn create_from_docker(container: &str, name: &str, description: &str, source_node: &str) -> Result<Template, String> {
+    let inspect = crate::containers::docker_inspect(container)?;
+    let mut t = new_template("docker", name, description, source_node, container)?;
+    t.docker = Some(docker_spec_from_inspect(&inspect)?);
+    let dir = template_dir(&t.id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    write_template(&dir, &t)?;
+    Ok(t)
+}
+
+/// Move a file, falling back to copy + delete across filesystems (the
+/// export lands in /tmp; the library is often on another disk or a
+/// network mount).
+fn move_file(from: &Path, to: &Path) -> Result<u64, String> {
+    if std::fs::rename(from, to).is_ok() {
+        return std::fs::metadata(to).map(|m| m.len()).map_err(|e| e.to_string());
+    }
+    let n = std::fs::copy(from, to).map_err(|e| format!("Failed to copy archive to {}: {}", to.display(), e))?;
+    let _ = std::fs::remove_file(from);
+    Ok(n)
+}
+
+/// Export an LXC container into the library. The container is stopped
+/// for a consistent export and started again if it was running. Blocking.
+pub fn create_from_lxc(container: &str, name: &str, description: &str, source_node: &str) -> Result<Template, String> {
+    let mut t = new_template("lxc", name, description, source_node, container)?;
+    let dir = template_dir(&t.id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let was_running = crate::containers::lxc_is_running(container);
+    if was_running {
+        let _ = crate::containers::lxc_stop(container);
+    }
+    let exported = crate::containers::lxc_export(container);
+    if was_running {
+        let _ = crate::containers::lxc_start(container);
+    }
+    let (export_path, meta) = match exported {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(format!("Export failed: {}", e));
+        }
+    };
+
+    // Keep the exported file name — lxc_import recognises vzdump
+    // archives by their `vzdump-` prefix.
+    t.archive = export_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let moved = move_file(&export_path, &dir.join(&t.archive));
+    crate::containers::lxc_export_cleanup(export_path.to_str().unwrap_or(""));
+    match moved {
+        Ok(size) => t.size_bytes = size,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+    }
+    t.lxc = Some(meta);
+    if let Err(e) = write_template(&dir, &t) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+    Ok(t)
+}
+
+pub fn delete(id: &str) -> Result<(), String> {
+    let dir = template_dir(id)?;
+    if !dir.join(TEMPLATE_FILE).exists() {
+        return Err(format!("Template '{}' not found", id));
+    }
+    std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete template: {}", e))
+}
+
+/// Provision `new_name` from a template on this node. LXC deploys get a
+/// fresh identity exactly like a clone (new MAC, bridge IP and WolfNet
+/// IP) and are left stopped. Blocking.
+pub fn deploy_local(t: &Template, new_name: &str, storage: Option<&str>) -> Result<String, String> {
+    match t.kind.as_str() {
+        "docker" => {
+            let spec = t.docker.as_ref().ok_or("Template has no Docker spec")?;
+            crate::containers::docker_pull(&spec.image)?;
+            crate::containers::docker_create(
+                new_name,
+                &spec.image,
+                &spec.ports,
+                &spec.env,
+                None,
+                spec.memory.as_deref(),
+                spec.cpus.as_deref(),
+                None,
+                &spec.volumes,
+            )
+        }
+        "lxc" => {
+            let archive = archive_path(t)?;
+            if !archive.exists() {
+                return Err(format!("Template archive {} is missing", archive.display()));
+            }
+            let carried = t.lxc.as_ref().and_then(|m| m.lxc_config.as_deref());
+            let outcome = crate::containers::lxc_import(&archive.to_string_lossy(), new_name, storage, carried)?;
+            let wolfnet_marker = format!("{}/{}/.wolfnet", crate::containers::lxc_base_dir(new_name), new_name);
+            let _ = std::fs::remove_dir_all(&wolfnet_marker);
+            crate::containers::lxc_clone_fixup_ip(new_name);
+            if let Some(ip) = crate::containers::next_available_wolfnet_ip() {
+                let _ = crate::containers::lxc_attach_wolfnet(new_name, &ip);
+            }
+            Ok(outcome.message)
+        }
+        other => Err(format!("Unknown template kind '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_cannot_escape_the_library() {
+        assert!(valid_id("0b6e2c1a-4f3d-4a8e-9c2b-1d2e3f4a5b6c"));
+        assert!(!valid_id("../etc"));
+        assert!(!valid_id("a/b"));
+        assert!(!valid_id(""));
+    }
+
+    #[test]
+    fn docker_spec_reads_ports_volumes_and_limits() {
+        let inspect = serde_json::json!({
+            "Config": {
+                "Image": "nginx:1.27",
+                "Env": ["PATH=/usr/bin", "TZ=Europe/London"]
+            },
+            "HostConfig": {
+                "PortBindings": {
+                    "80/tcp": [{"HostIp": "", "HostPort": "8080"}],
+                    "53/udp": [{"HostIp": "", "HostPort": "5353"}],
+                    "443/tcp": [{"HostIp": "", "HostPort": ""}]
+                },
+                "Memory": 536870912u64,
+                "NanoCpus": 1500000000u64
+            },
+            "Mounts": [
+                {"Type": "volume", "Name": "webdata", "Source": "/var/lib/docker/volumes/webdata/_data", "Destination": "/usr/share/nginx/html", "RW": true},
+                {"Type": "bind", "Source": "/etc/nginx/conf.d", "Destination": "/etc/nginx/conf.d", "RW": false},
+                {"Type": "tmpfs", "Destination": "/run"}
+            ]
+        });
+        let spec = docker_spec_from_inspect(&inspect).unwrap();
+        assert_eq!(spec.image, "nginx:1.27");
+        assert_eq!(spec.env, vec!["TZ=Europe/London"]);
+        assert_eq!(spec.ports, vec!["5353:53/udp", "8080:80"]);
+        assert_eq!(spec.volumes, vec!["webdata:/usr/share/nginx/html", "/etc/nginx/conf.d:/etc/nginx/conf.d:ro"]);
+        assert_eq!(spec.memory.as_deref(), Some("512m"));
+        assert_eq!(spec.cpus.as_deref(), Some("1.50"));
+        assert!(docker_spec_from_inspect(&serde_json::json!({"Config": {}})).is_err());
+    }
+}
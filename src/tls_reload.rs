@@ -0,0 +1,88 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Live certificate reload for the HTTPS listener.
+//!
+//! OpenSSL fixes an acceptor's certificate when it is built, and actix owns
+//! the acceptor from then on, so a renewed or newly configured cert used to
+//! need a restart. Instead the listener gets a servername callback — OpenSSL
+//! runs it on every ClientHello, with or without SNI — that points each new
+//! connection at the most recently loaded context. Connections already open
+//! keep the context they started with, so a reload drops nothing.
+//!
+//! Only the certificate and key are swapped: `SSL_set_SSL_CTX` leaves the
+//! client-verification settings of the original acceptor alone, so the
+//! mTLS gate stays exactly as it was at startup.
+
+use crate::daemon_config::TlsSection;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslContext, SslFiletype, SslMethod};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use tracing::info;
+
+/// TLS flags given on the command line, which outrank wolfstack.toml.
+#[derive(Clone, Default)]
+pub struct CliTls {
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub domain: Option<String>,
+}
+
+static CLI: OnceLock<CliTls> = OnceLock::new();
+/// Set once the HTTPS listener carries the reload hook.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Context for new connections; `None` until the first reload, meaning
+/// "keep the one the acceptor was built with".
+static CURRENT: RwLock<Option<SslContext>> = RwLock::new(None);
+
+/// Record the command-line TLS flags before they are merged with the
+/// config file, so a reload resolves paths with the same precedence.
+pub fn remember_cli(cli: CliTls) {
+    let _ = CLI.set(cli);
+}
+
+/// Cert and key paths: explicit paths from the command line or config,
+/// otherwise the Let's Encrypt / self-signed lookup used at startup.
+pub fn resolve_paths(cfg: &TlsSection) -> Option<(String, String)> {
+    let cli = CLI.get().cloned().unwrap_or_default();
+    match (cli.cert.or_else(|| cfg.cert.clone()), cli.key.or_else(|| cfg.key.clone())) {
+        (Some(cert), Some(key)) => Some((cert, key)),
+        _ => crate::installer::find_tls_certificate(cli.domain.or_else(|| cfg.domain.clone()).as_deref()),
+    }
+}
+
+/// Install the reload hook on the HTTPS acceptor.
+pub fn attach(builder: &mut SslAcceptorBuilder) {
+    builder.set_servername_callback(|ssl, _alert| {
+        if let Some(ctx) = CURRENT.read().ok().and_then(|c| c.clone()) {
+            // A failure here just leaves the startup certificate in place.
+            let _ = ssl.set_ssl_context(&ctx);
+        }
+        Ok(())
+    });
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+fn build_context(cert: &str, key: &str) -> Result<SslContext, String> {
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())
+        .map_err(|e| format!("SSL acceptor: {}", e))?;
+    builder.set_certificate_chain_file(cert).map_err(|e| format!("Cannot load TLS cert '{}': {}", cert, e))?;
+    builder.set_private_key_file(key, SslFiletype::PEM).map_err(|e| format!("Cannot load TLS key '{}': {}", key, e))?;
+    builder.check_private_key().map_err(|_| format!("TLS key '{}' does not match cert '{}'", key, cert))?;
+    Ok(builder.build().into_context())
+}
+
+/// Load the certificate again and serve it to new connections. Returns
+/// `Ok(false)` when the listener isn't serving TLS (turning TLS on or off
+/// needs a restart). On error the current certificate stays in use.
+pub fn reload(cfg: &TlsSection) -> Result<bool, String> {
+    if !ACTIVE.load(Ordering::Relaxed) || !cfg.enabled {
+        return Ok(false);
+    }
+    let (cert, key) = resolve_paths(cfg).ok_or("No TLS certificate found")?;
+    let ctx = build_context(&cert, &key)?;
+    *CURRENT.write().unwrap() = Some(ctx);
+    info!("TLS certificate reloaded from {}", cert);
+    Ok(true)
+}
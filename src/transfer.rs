@@ -0,0 +1,562 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Chunked node-to-node file transfer — how container archives reach
+//! another node in the cluster.
+//!
+//! The sender cuts the file into content-defined chunks (a gear rolling
+//! hash picks the boundaries, so an insertion early in the file only
+//! changes the chunks around it) and tells the receiver the list of
+//! chunk hashes. The receiver answers with the ones it doesn't already
+//! hold, the sender uploads just those (zstd-compressed, one request
+//! each, optionally held to a bandwidth cap) and the receiver assembles
+//! the file.
+//!
+//! The receiver keeps the chunks of the last file sent under each `key`
+//! (e.g. one per source container), which gives:
+//!
+//! - **resume** — an interrupted transfer re-sent later only uploads the
+//!   chunks that didn't arrive;
+//! - **delta** — re-sending a container that changed a little only
+//!   uploads the changed chunks. Standalone LXC exports are compressed
+//!   with `gzip --rsyncable` so small rootfs changes stay local in the
+//!   archive too.
+//!
+//! Chunk stores unused for two weeks are dropped. Nothing is held in
+//! memory beyond one chunk, whatever the file size.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Receiver-side chunk stores and open sessions.
+const ROOT: &str = "/var/lib/wolfstack/transfer";
+/// Where finished files are assembled for the import that follows.
+const ASSEMBLY_DIR: &str = "/tmp/wolfstack-imports";
+
+const MIN_CHUNK: usize = 256 * 1024;
+pub const MAX_CHUNK: usize = 4 * 1024 * 1024;
+/// A boundary is a hash with these low bits clear — ~1 MiB chunks.
+const BOUNDARY_MASK: u64 = (1 << 20) - 1;
+/// Chunk stores and sessions untouched this long are removed.
+const KEEP_SECS: u64 = 14 * 86400;
+/// Upload attempts per chunk before the transfer gives up.
+const CHUNK_ATTEMPTS: u32 = 3;
+
+/// Gear table: one fixed pseudo-random word per byte value (splitmix64),
+/// so every node cuts the same file at the same places.
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x5772_6f6c_6653_7461;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// sha256, hex.
+    pub hash: String,
+    pub len: u32,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Cut `reader` into content-defined chunks, calling `each` with every one.
+fn for_each_chunk<R: Read>(mut reader: R, mut each: impl FnMut(&[u8])) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 256 * 1024];
+    let mut cur: Vec<u8> = Vec::with_capacity(MAX_CHUNK);
+    let mut h: u64 = 0;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let mut start = 0;
+        for i in 0..n {
+            h = (h << 1).wrapping_add(GEAR[buf[i] as usize]);
+            let len = cur.len() + i + 1 - start;
+            if len >= MIN_CHUNK && (h & BOUNDARY_MASK == 0 || len >= MAX_CHUNK) {
+                cur.extend_from_slice(&buf[start..=i]);
+                each(&cur);
+                cur.clear();
+                h = 0;
+                start = i + 1;
+            }
+        }
+        cur.extend_from_slice(&buf[start..n]);
+    }
+    if !cur.is_empty() {
+        each(&cur);
+    }
+    Ok(())
+}
+
+/// The chunk list of a file.
+pub fn manifest(path: &Path) -> Result<Vec<Chunk>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let mut chunks = Vec::new();
+    for_each_chunk(std::io::BufReader::new(file), |c| chunks.push(Chunk { hash: sha256_hex(c), len: c.len() as u32 }))
+        .map_err(|e| format!("Read error on {}: {}", path.display(), e))?;
+    Ok(chunks)
+}
+
+// ─── Receiver ───
+
+#[derive(Debug, Deserialize)]
+pub struct BeginRequest {
+    /// What the file is a version of — chunks are kept per key for the
+    /// next transfer to reuse.
+    pub key: String,
+    pub file_name: String,
+    pub size: u64,
+    pub chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BeginResponse {
+    pub id: String,
+    /// Hashes the receiver still needs.
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    store: String,
+    file_name: String,
+    size: u64,
+    chunks: Vec<Chunk>,
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+}
+
+fn safe_file_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 200 && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+fn session_path(root: &Path, id: &str) -> PathBuf {
+    root.join("sessions").join(format!("{}.json", id))
+}
+
+fn load_session(root: &Path, id: &str) -> Result<Session, String> {
+    if !is_hex(id, 32) {
+        return Err("Invalid transfer id".into());
+    }
+    let data = std::fs::read_to_string(session_path(root, id))
+        .map_err(|_| format!("Unknown transfer {}", id))?;
+    serde_json::from_str(&data).map_err(|e| format!("Corrupt transfer session {}: {}", id, e))
+}
+
+fn age_secs(path: &Path) -> u64 {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+        .and_then(|t| t.elapsed().ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Drop chunk stores and sessions nobody has used in [`KEEP_SECS`].
+fn prune_stale(root: &Path) {
+    for sub in ["stores", "sessions"] {
+        for entry in std::fs::read_dir(root.join(sub)).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if age_secs(&path) > KEEP_SECS {
+                let _ = if path.is_dir() { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+            }
+        }
+    }
+}
+
+fn begin_in(root: &Path, req: &BeginRequest) -> Result<BeginResponse, String> {
+    if req.key.is_empty() || req.key.len() > 512 {
+        return Err("Invalid transfer key".into());
+    }
+    if !safe_file_name(&req.file_name) {
+        return Err(format!("Invalid file name '{}'", req.file_name));
+    }
+    if req.chunks.iter().any(|c| !is_hex(&c.hash, 64) || c.len == 0 || c.len as usize > MAX_CHUNK) {
+        return Err("Invalid chunk list".into());
+    }
+    if req.chunks.iter().map(|c| c.len as u64).sum::<u64>() != req.size {
+        return Err("Chunk lengths don't add up to the file size".into());
+    }
+    prune_stale(root);
+
+    let store = sha256_hex(req.key.as_bytes())[..32].to_string();
+    let mut id_hash = Sha256::new();
+    id_hash.update(store.as_bytes());
+    id_hash.update(req.file_name.as_bytes());
+    id_hash.update(req.size.to_le_bytes());
+    for c in &req.chunks {
+        id_hash.update(c.hash.as_bytes());
+    }
+    let id = hex::encode(id_hash.finalize())[..32].to_string();
+
+    let store_dir = root.join("stores").join(&store);
+    std::fs::create_dir_all(&store_dir).map_err(|e| format!("Cannot create {}: {}", store_dir.display(), e))?;
+    std::fs::create_dir_all(root.join("sessions")).map_err(|e| format!("Cannot create session dir: {}", e))?;
+    let session = Session { store, file_name: req.file_name.clone(), size: req.size, chunks: req.chunks.clone() };
+    let json = serde_json::to_string(&session).map_err(|e| e.to_string())?;
+    std::fs::write(session_path(root, &id), json).map_err(|e| format!("Cannot write session: {}", e))?;
+    // Touch the store so a store in use is never pruned as stale.
+    let _ = std::fs::File::open(&store_dir).and_then(|f| f.set_modified(std::time::SystemTime::now()));
+
+    let mut seen = HashSet::new();
+    let missing = req.chunks.iter()
+        .filter(|c| seen.insert(c.hash.as_str()))
+        .filter(|c| !store_dir.join(&c.hash).exists())
+        .map(|c| c.hash.clone())
+        .collect();
+    Ok(BeginResponse { id, missing })
+}
+
+fn put_chunk_in(root: &Path, id: &str, hash: &str, compressed: &[u8]) -> Result<(), String> {
+    let session = load_session(root, id)?;
+    if !session.chunks.iter().any(|c| c.hash == hash) {
+        return Err("Chunk is not part of this transfer".into());
+    }
+    let data = zstd::bulk::decompress(compressed, MAX_CHUNK)
+        .map_err(|e| format!("Chunk does not decompress: {}", e))?;
+    if sha256_hex(&data) != hash {
+        return Err("Chunk does not match its hash".into());
+    }
+    let dest = root.join("stores").join(&session.store).join(hash);
+    // Write-then-rename so a half-written chunk never counts as present.
+    let tmp = dest.with_extension(format!("part-{}", std::process::id()));
+    std::fs::write(&tmp, &data).map_err(|e| format!("Cannot write chunk: {}", e))?;
+    std::fs::rename(&tmp, &dest).map_err(|e| format!("Cannot store chunk: {}", e))
+}
+
+/// Assemble into `out_dir`, then trim the store to this file's chunks
+/// (plus any another open session of the same store still needs).
+fn finish_in(root: &Path, id: &str, out_dir: &Path) -> Result<PathBuf, String> {
+    let session = load_session(root, id)?;
+    let store_dir = root.join("stores").join(&session.store);
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Cannot create {}: {}", out_dir.display(), e))?;
+    let out_path = out_dir.join(format!("transfer-{}-{}", id, session.file_name));
+    let result = (|| {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&out_path)
+            .map_err(|e| format!("Cannot create {}: {}", out_path.display(), e))?);
+        for c in &session.chunks {
+            let data = std::fs::read(store_dir.join(&c.hash))
+                .map_err(|_| format!("Chunk {} never arrived", &c.hash[..12]))?;
+            out.write_all(&data).map_err(|e| format!("Write error: {}", e))?;
+        }
+        out.flush().map_err(|e| format!("Write error: {}", e))
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&out_path);
+        return Err(e);
+    }
+    let size = std::fs::metadata(&out_path).map(|m| m.len()).unwrap_or(0);
+    if size != session.size {
+        let _ = std::fs::remove_file(&out_path);
+        return Err(format!("Assembled {} bytes, expected {}", size, session.size));
+    }
+
+    let mut keep: HashSet<String> = session.chunks.iter().map(|c| c.hash.clone()).collect();
+    for entry in std::fs::read_dir(root.join("sessions")).into_iter().flatten().flatten() {
+        if entry.path() == session_path(root, id) { continue; }
+        if let Some(other) = std::fs::read_to_string(entry.path()).ok()
+            .and_then(|d| serde_json::from_str::<Session>(&d).ok())
+            .filter(|s| s.store == session.store)
+        {
+            keep.extend(other.chunks.into_iter().map(|c| c.hash));
+        }
+    }
+    for entry in std::fs::read_dir(&store_dir).into_iter().flatten().flatten() {
+        if !keep.contains(entry.file_name().to_string_lossy().as_ref()) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    let _ = std::fs::remove_file(session_path(root, id));
+    Ok(out_path)
+}
+
+/// Start (or resume) receiving a file.
+pub fn begin(req: &BeginRequest) -> Result<BeginResponse, String> {
+    begin_in(Path::new(ROOT), req)
+}
+
+/// Store one chunk of a transfer (zstd-compressed on the wire).
+pub fn put_chunk(id: &str, hash: &str, compressed: &[u8]) -> Result<(), String> {
+    put_chunk_in(Path::new(ROOT), id, hash, compressed)
+}
+
+/// Assemble a completed transfer, returning the file's path.
+pub fn finish(id: &str) -> Result<PathBuf, String> {
+    finish_in(Path::new(ROOT), id, Path::new(ASSEMBLY_DIR))
+}
+
+/// The assembled file of a finished transfer, for the import naming it.
+pub fn assembled(id: &str) -> Result<PathBuf, String> {
+    if !is_hex(id, 32) {
+        return Err("Invalid transfer id".into());
+    }
+    let prefix = format!("transfer-{}-", id);
+    std::fs::read_dir(ASSEMBLY_DIR).into_iter().flatten().flatten()
+        .map(|e| e.path())
+        .find(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with(&prefix)))
+        .ok_or_else(|| format!("Transfer {} has not been completed", id))
+}
+
+// ─── Sender ───
+
+#[derive(Debug)]
+pub enum SendError {
+    /// The receiver predates chunked transfer — send the file whole.
+    Unsupported,
+    /// Couldn't connect; nothing was sent.
+    Connect(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Unsupported => write!(f, "the target does not support chunked transfer"),
+            SendError::Connect(e) | SendError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A completed send.
+pub struct Sent {
+    pub id: String,
+    pub size: u64,
+    /// Bytes actually uploaded (before compression) — the rest the
+    /// receiver already had.
+    pub uploaded: u64,
+}
+
+fn read_chunk(path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; len];
+    file.read_exact(&mut buf).map_err(|e| format!("Read error: {}", e))?;
+    zstd::bulk::compress(&buf, 3).map_err(|e| format!("Compression failed: {}", e))
+}
+
+/// Hold a transfer to `bwlimit` bytes/s (0 = no cap): having put `bytes`
+/// on the wire since `since`, wait out whatever time that many bytes
+/// should have taken. Read per call, so a cap changed mid-transfer
+/// applies from the next chunk.
+pub async fn pace(bwlimit: &AtomicU64, bytes: u64, since: Instant) {
+    let limit = bwlimit.load(Ordering::Relaxed);
+    if limit == 0 {
+        return;
+    }
+    let due = Duration::from_secs_f64(bytes as f64 / limit as f64);
+    if let Some(wait) = due.checked_sub(since.elapsed()) {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// A file as a streamed request body for a target that takes a plain
+/// upload, read 1 MiB at a time and held to `bwlimit` like [`send`].
+/// `progress` gets the bytes read so far.
+pub fn paced_body(
+    file: tokio::fs::File,
+    bwlimit: Arc<AtomicU64>,
+    progress: impl FnMut(u64) + Send + 'static,
+) -> reqwest::Body {
+    use tokio::io::AsyncReadExt;
+    let stream = futures::stream::unfold((Some(file), 0u64, progress), move |(file, done, mut progress)| {
+        let bwlimit = bwlimit.clone();
+        async move {
+            let mut file = file?;
+            let started = Instant::now();
+            let mut buf = vec![0u8; 1024 * 1024];
+            match file.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    progress(done + n as u64);
+                    pace(&bwlimit, n as u64, started).await;
+                    Some((Ok(buf), (Some(file), done + n as u64, progress)))
+                }
+                // End the stream after reporting the error.
+                Err(e) => Some((Err(e), (None, done, progress))),
+            }
+        }
+    });
+    reqwest::Body::wrap_stream(stream)
+}
+
+/// Send `path` to the node at `base_url` (scheme://host:port). Only the
+/// chunks the receiver lacks are uploaded, held to `bwlimit` bytes/s
+/// (see [`pace`]). `progress` gets (bytes done, total).
+pub async fn send(
+    client: &reqwest::Client,
+    base_url: &str,
+    secret: &str,
+    path: &Path,
+    key: &str,
+    bwlimit: &AtomicU64,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<Sent, SendError> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let p = path.to_path_buf();
+    let chunks = tokio::task::spawn_blocking(move || manifest(&p)).await
+        .map_err(|e| SendError::Failed(e.to_string()))?
+        .map_err(SendError::Failed)?;
+    let size: u64 = chunks.iter().map(|c| c.len as u64).sum();
+
+    let begin = serde_json::json!({ "key": key, "file_name": file_name, "size": size, "chunks": chunks });
+    let resp = client.post(format!("{}/api/transfer/begin", base_url))
+        .timeout(Duration::from_secs(120))
+        .header("X-WolfStack-Secret", secret)
+        .json(&begin)
+        .send().await
+        .map_err(|e| if e.is_connect() { SendError::Connect(e.to_string()) } else { SendError::Failed(e.to_string()) })?;
+    if matches!(resp.status().as_u16(), 404 | 405) {
+        return Err(SendError::Unsupported);
+    }
+    if !resp.status().is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(SendError::Failed(format!("Transfer refused: {}", body)));
+    }
+    let plan: BeginResponse = resp.json().await.map_err(|e| SendError::Failed(format!("Bad transfer reply: {}", e)))?;
+
+    let mut missing: HashSet<String> = plan.missing.into_iter().collect();
+    let mut done = size - chunks.iter().filter(|c| missing.contains(&c.hash)).map(|c| c.len as u64).sum::<u64>();
+    let mut uploaded = 0u64;
+    progress(done, size);
+
+    let mut offset = 0u64;
+    for c in &chunks {
+        let chunk_offset = offset;
+        offset += c.len as u64;
+        // The same chunk can occur twice in a file — upload it once.
+        if !missing.remove(&c.hash) {
+            continue;
+        }
+        let chunk_started = Instant::now();
+        let p = path.to_path_buf();
+        let len = c.len as usize;
+        let body = tokio::task::spawn_blocking(move || read_chunk(&p, chunk_offset, len)).await
+            .map_err(|e| SendError::Failed(e.to_string()))?
+            .map_err(SendError::Failed)?;
+        let url = format!("{}/api/transfer/{}/chunks/{}", base_url, plan.id, c.hash);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = client.put(&url)
+                .timeout(Duration::from_secs(300))
+                .header("X-WolfStack-Secret", secret)
+                .body(body.clone())
+                .send().await;
+            match result {
+                Ok(r) if r.status().is_success() => break,
+                Ok(r) if attempt >= CHUNK_ATTEMPTS => {
+                    let text = r.text().await.unwrap_or_default();
+                    return Err(SendError::Failed(format!("Chunk upload refused: {}", text)));
+                }
+                Err(e) if attempt >= CHUNK_ATTEMPTS => {
+                    return Err(SendError::Failed(format!("Chunk upload failed: {}", e)));
+                }
+                _ => tokio::time::sleep(Duration::from_secs(2 * attempt as u64)).await,
+            }
+        }
+        uploaded += c.len as u64;
+        done += c.len as u64;
+        progress(done, size);
+        pace(bwlimit, body.len() as u64, chunk_started).await;
+    }
+
+    let resp = client.post(format!("{}/api/transfer/{}/finish", base_url, plan.id))
+        .timeout(Duration::from_secs(1800))
+        .header("X-WolfStack-Secret", secret)
+        .send().await
+        .map_err(|e| SendError::Failed(format!("Transfer finish failed: {}", e)))?;
+    if !resp.status().is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(SendError::Failed(format!("Transfer finish failed: {}", body)));
+    }
+    Ok(Sent { id: plan.id, size, uploaded })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len).map(|_| { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x as u8 }).collect()
+    }
+
+    fn chunks_of(data: &[u8]) -> Vec<Chunk> {
+        let mut out = Vec::new();
+        for_each_chunk(data, |c| out.push(Chunk { hash: sha256_hex(c), len: c.len() as u32 })).unwrap();
+        out
+    }
+
+    #[test]
+    fn an_insertion_only_changes_nearby_chunks() {
+        let data = noise(12 * 1024 * 1024, 42);
+        let before = chunks_of(&data);
+        assert!(before.iter().all(|c| (c.len as usize) <= MAX_CHUNK));
+        assert!(before.iter().rev().skip(1).all(|c| (c.len as usize) >= MIN_CHUNK));
+        assert_eq!(before.iter().map(|c| c.len as usize).sum::<usize>(), data.len());
+
+        let mut edited = data[..5_000_000].to_vec();
+        edited.extend_from_slice(b"a few inserted bytes");
+        edited.extend_from_slice(&data[5_000_000..]);
+        let after = chunks_of(&edited);
+        let old: HashSet<&str> = before.iter().map(|c| c.hash.as_str()).collect();
+        let changed = after.iter().filter(|c| !old.contains(c.hash.as_str())).count();
+        assert!(changed <= 2, "{} of {} chunks changed", changed, after.len());
+    }
+
+    #[test]
+    fn receiver_resumes_and_assembles() {
+        let root = std::env::temp_dir().join(format!("wolfstack-transfer-test-{}", std::process::id()));
+        let out = root.join("out");
+        let data = noise(3 * 1024 * 1024, 7);
+        let chunks = chunks_of(&data);
+        let req = BeginRequest { key: "lxc:node1:web".into(), file_name: "web.tar.gz".into(), size: data.len() as u64, chunks: chunks.clone() };
+
+        let plan = begin_in(&root, &req).unwrap();
+        assert_eq!(plan.missing.len(), chunks.len());
+        // Only the first chunk arrives before the "connection drops".
+        let first = zstd::bulk::compress(&data[..chunks[0].len as usize], 3).unwrap();
+        assert!(put_chunk_in(&root, &plan.id, &chunks[1].hash, &first).is_err(), "hash mismatch refused");
+        put_chunk_in(&root, &plan.id, &chunks[0].hash, &first).unwrap();
+        assert!(finish_in(&root, &plan.id, &out).is_err());
+
+        let resumed = begin_in(&root, &req).unwrap();
+        assert_eq!(resumed.id, plan.id);
+        assert_eq!(resumed.missing.len(), chunks.len() - 1);
+        let mut offset = 0;
+        for c in &chunks {
+            let piece = &data[offset..offset + c.len as usize];
+            offset += c.len as usize;
+            if resumed.missing.contains(&c.hash) {
+                put_chunk_in(&root, &plan.id, &c.hash, &zstd::bulk::compress(piece, 3).unwrap()).unwrap();
+            }
+        }
+        let path = finish_in(&root, &plan.id, &out).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        // The next version of the same key starts from these chunks.
+        assert!(begin_in(&root, &req).unwrap().missing.is_empty());
+        assert!(begin_in(&root, &BeginRequest { file_name: "../x".into(), ..req }).is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -211,7 +211,8 @@ pub async fn tui_login_submit(
             // Lax, not Strict — Strict drops the cookie on top-level
             // navigation and broke login on Microsoft Edge. See api/mod.rs.
             .same_site(actix_web::cookie::SameSite::Lax)
-            .max_age(actix_web::cookie::time::Duration::hours(8))
+            .max_age(actix_web::cookie::time::Duration::seconds(
+                state.sessions.cookie_max_age(&token).as_secs() as i64))
             .finish();
         if state.tls_enabled {
             cookie.set_secure(true);
@@ -479,6 +479,9 @@ fn stop_all_vms(state: &UpsState, app: &crate::api::AppState) {
             Err(e) => log_event(state, "error", format!("stop VM '{}': {}", name, e)),
         }
     }
+    if !managed.is_empty() {
+        crate::monitoring::inventory::VMS.invalidate();
+    }
     // 2. Sweep unmanaged guests the tools know about. Overlap with the
     // managed set is harmless — a second shutdown of a stopping guest
     // just logs an error.
@@ -0,0 +1,359 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Typed names for values that end up on a command line or in a path.
+//!
+//! Container names, ZFS datasets, mount points and hosts to probe arrive
+//! from URLs, JSON bodies and cluster peers, and go straight into `lxc-*`,
+//! `docker`, `zfs`, `mount` and `ping` invocations or `/var/lib/lxc/<name>`
+//! paths. Each handler used to check them its own way (or not at all) —
+//! one allowed Unicode letters, another a leading `-` that the tool then
+//! read as a flag.
+//!
+//! A value only becomes a [`ContainerName`], [`DatasetName`],
+//! [`MountPoint`] or [`HostName`] through its `parse`, so code holding one
+//! knows it was checked. The grammars are deliberately narrower than what
+//! the tools accept: ASCII only, never starting with `-`, no `..`, no
+//! whitespace or control characters.
+
+use std::fmt;
+use std::ops::Deref;
+
+macro_rules! str_newtype {
+    ($name:ident) => {
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+/// A Docker or LXC container name (or Docker ID):
+/// `[A-Za-z0-9][A-Za-z0-9_.-]*`, at most 128 characters, no `..`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContainerName(String);
+str_newtype!(ContainerName);
+
+impl ContainerName {
+    pub const MAX_LEN: usize = 128;
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.is_empty() {
+            return Err("container name is required".into());
+        }
+        if s.len() > Self::MAX_LEN {
+            return Err("container name too long".into());
+        }
+        if let Some(c) = s.chars().find(|c| !(c.is_ascii_alphanumeric() || "_.-".contains(*c))) {
+            return Err(format!(
+                "container name '{}' contains '{}' — only ASCII alnum and '.', '_', '-' allowed",
+                s.escape_default(), c.escape_default()
+            ));
+        }
+        if !s.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            return Err(format!("container name '{}' must start with a letter or digit", s));
+        }
+        if s.contains("..") {
+            return Err(format!("container name '{}' must not contain '..'", s));
+        }
+        Ok(ContainerName(s.to_string()))
+    }
+}
+
+/// A host to probe: an IPv4/IPv6 literal or a DNS name of
+/// `[A-Za-z0-9_-]` labels separated by dots, at most 253 characters.
+/// Never starts with `-`, so it can't be read as a flag by `ping` & co.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HostName(String);
+str_newtype!(HostName);
+
+impl HostName {
+    pub const MAX_LEN: usize = 253;
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("host is required".into());
+        }
+        let bare = s.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(s);
+        if let Ok(ip) = bare.parse::<std::net::IpAddr>() {
+            return Ok(HostName(ip.to_string()));
+        }
+        if s.len() > Self::MAX_LEN {
+            return Err("host name too long".into());
+        }
+        let name = s.strip_suffix('.').unwrap_or(s);
+        for label in name.split('.') {
+            if label.is_empty() || label.len() > 63 {
+                return Err(format!("host '{}' has an empty or over-long label", s.escape_default()));
+            }
+            if label.starts_with('-') {
+                return Err(format!("host '{}' must not have a label starting with '-'", s));
+            }
+            if let Some(c) = label.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+                return Err(format!("host '{}' contains '{}' — use a host name or IP address", s.escape_default(), c.escape_default()));
+            }
+        }
+        Ok(HostName(name.to_string()))
+    }
+
+    /// The address, if this is an IP literal rather than a name.
+    pub fn ip(&self) -> Option<std::net::IpAddr> {
+        self.0.parse().ok()
+    }
+}
+
+/// Characters ZFS allows in a dataset or snapshot component, minus the
+/// space (which we never create and which breaks `-H` parsing).
+fn is_zfs_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_.:-".contains(c)
+}
+
+fn check_zfs_component(comp: &str, what: &str) -> Result<(), String> {
+    if comp.is_empty() {
+        return Err(format!("{} has an empty component", what));
+    }
+    if comp == "." || comp == ".." {
+        return Err(format!("{} must not contain '{}' components", what, comp));
+    }
+    if comp.starts_with('-') {
+        return Err(format!("{} component '{}' must not start with '-'", what, comp));
+    }
+    if let Some(c) = comp.chars().find(|c| !is_zfs_char(*c)) {
+        return Err(format!("{} contains '{}' — only ASCII alnum and '_', '.', ':', '-' allowed", what, c.escape_default()));
+    }
+    Ok(())
+}
+
+/// A ZFS pool or dataset: `pool[/child...]`. The pool starts with a
+/// letter; every component is non-empty and made of `[A-Za-z0-9_.:-]`.
+/// Snapshots (`dataset@snap`) go through [`DatasetName::parse_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DatasetName(String);
+str_newtype!(DatasetName);
+
+impl DatasetName {
+    /// ZFS's own limit on a full name.
+    pub const MAX_LEN: usize = 255;
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.is_empty() {
+            return Err("dataset name is required".into());
+        }
+        if s.len() > Self::MAX_LEN {
+            return Err("dataset name too long".into());
+        }
+        if s.contains('@') {
+            return Err(format!("'{}' is a snapshot, not a dataset", s.escape_default()));
+        }
+        for comp in s.split('/') {
+            check_zfs_component(comp, "dataset name")?;
+        }
+        if !s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(format!("pool name in '{}' must start with a letter", s));
+        }
+        Ok(DatasetName(s.to_string()))
+    }
+
+    /// Split and check a full snapshot name, `dataset@snap`.
+    pub fn parse_snapshot(s: &str) -> Result<(Self, String), String> {
+        let (dataset, snap) = s.split_once('@')
+            .ok_or_else(|| "snapshot name must be dataset@snapshot".to_string())?;
+        let dataset = Self::parse(dataset)?;
+        let full = dataset.snapshot(snap)?;
+        if full.len() > Self::MAX_LEN {
+            return Err("snapshot name too long".into());
+        }
+        Ok((dataset, snap.to_string()))
+    }
+
+    /// The full name of snapshot `snap` of this dataset, checked.
+    pub fn snapshot(&self, snap: &str) -> Result<String, String> {
+        check_zfs_component(snap, "snapshot name")?;
+        Ok(format!("{}@{}", self.0, snap))
+    }
+}
+
+/// Whole OS trees that must never host a storage mount — rejected at the
+/// directory itself AND any path under it (mounting over /usr/bin hides
+/// every binary just as surely as mounting over /usr).
+const UNSAFE_TREES: &[&str] = &[
+    "/dev", "/proc", "/sys", "/run", "/boot", "/etc", "/usr",
+    "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/libx32", "/root",
+];
+
+/// A directory a filesystem may be mounted on: absolute, normalised
+/// (duplicate and trailing slashes collapsed), no `.`/`..` components,
+/// no control characters, and not a critical system directory.
+///
+/// Mounting over `/dev` hides `/dev/null` and every process loses the
+/// ability to exec; over `/usr`/`/bin`/`/lib` it hides every binary;
+/// over `/` it hides everything — all with the disk itself intact.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MountPoint(String);
+str_newtype!(MountPoint);
+
+impl MountPoint {
+    pub const MAX_LEN: usize = 4096;
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let p = s.trim();
+        if p.is_empty() {
+            return Err("mount point is required".into());
+        }
+        if p.len() > Self::MAX_LEN {
+            return Err("mount point too long".into());
+        }
+        if !p.starts_with('/') {
+            return Err(format!("mount point '{}' must be an absolute path", p.escape_default()));
+        }
+        // fstab and mount units are whitespace/line based.
+        if p.chars().any(|c| c.is_control()) {
+            return Err("mount point must not contain control characters".into());
+        }
+        let mut norm = String::new();
+        for seg in p.split('/').filter(|s| !s.is_empty()) {
+            if seg == "." || seg == ".." {
+                return Err(format!("mount point '{}' must not contain '{}'", p, seg));
+            }
+            norm.push('/');
+            norm.push_str(seg);
+        }
+        if norm.is_empty() {
+            return Err("refusing to mount over '/'".into());
+        }
+        for root in UNSAFE_TREES {
+            if norm == *root || norm.starts_with(&format!("{}/", root)) {
+                return Err(format!("refusing to mount over critical system path '{}'", norm));
+            }
+        }
+        // /var and /home: the directory itself is unsafe, but normal data
+        // mounts legitimately live *under* them (/var/lib/vz,
+        // /home/user/data) — allow those, reject only the bare dir.
+        if norm == "/var" || norm == "/home" {
+            return Err(format!("refusing to mount over critical system path '{}'", norm));
+        }
+        Ok(MountPoint(norm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn container_names() {
+        for ok in ["web", "db-1", "my_app.v2", "a", "0abc", "f3e1c9d2b7a4"] {
+            assert!(ContainerName::parse(ok).is_ok(), "{ok:?}");
+        }
+        for bad in ["", "-rm", ".hidden", "_x", "a b", "a;b", "a/b", "a..b", "né", "x$(id)", "a\nb"] {
+            assert!(ContainerName::parse(bad).is_err(), "{bad:?}");
+        }
+        assert!(ContainerName::parse(&"a".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn dataset_names() {
+        for ok in ["tank", "tank/vm-100-disk-0", "rpool/data/sub.vol", "pool1/a:b"] {
+            assert!(DatasetName::parse(ok).is_ok(), "{ok:?}");
+        }
+        for bad in ["", "1tank", "-tank", "tank/", "/tank", "tank//x", "tank/-o", "tank/..",
+                    "tank/a b", "tank@s", "tänk", "tank/$(id)"] {
+            assert!(DatasetName::parse(bad).is_err(), "{bad:?}");
+        }
+        let (ds, snap) = DatasetName::parse_snapshot("tank/data@daily-2024").unwrap();
+        assert_eq!((ds.as_str(), snap.as_str()), ("tank/data", "daily-2024"));
+        for bad in ["tank", "tank@", "tank@-x", "tank@a@b", "@snap", "tank@a/b"] {
+            assert!(DatasetName::parse_snapshot(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn host_names() {
+        assert_eq!(HostName::parse(" node1.lan. ").unwrap().as_str(), "node1.lan");
+        assert_eq!(HostName::parse("[2001:db8::1]").unwrap().ip(), "2001:db8::1".parse().ok());
+        assert!(HostName::parse("10.0.0.1").unwrap().ip().is_some());
+        for bad in ["", "-c 100", "-f", "a..b", "a b", "a;b", "host/x", "$(id)", "ex-.-ample"] {
+            assert!(HostName::parse(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn mount_points_are_normalised() {
+        assert_eq!(MountPoint::parse(" /mnt//data/ ").unwrap().as_str(), "/mnt/data");
+        assert!(MountPoint::parse("/mnt/./data").is_err());
+        assert!(MountPoint::parse("/mnt/da\nta").is_err());
+    }
+
+    /// Hostile-biased random input: whatever gets through must hold the
+    /// invariants the callers rely on, and parsing must be idempotent.
+    fn random_input(rng: &mut impl Rng) -> String {
+        const ALPHABET: &[char] = &[
+            'a', 'Z', '0', '9', '-', '_', '.', ':', '/', '@', ' ', ';', '|', '&', '$', '`',
+            '\'', '"', '\\', '\n', '\0', '*', '?', '~', 'é', '(', ')', '<', '>',
+        ];
+        let len = rng.gen_range(0..24);
+        (0..len).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())]).collect()
+    }
+
+    #[test]
+    fn fuzz_parsers_uphold_invariants() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20_000 {
+            let s = random_input(&mut rng);
+
+            if let Ok(n) = ContainerName::parse(&s) {
+                assert!(!n.starts_with('-') && !n.contains("..") && n.len() <= ContainerName::MAX_LEN, "{s:?}");
+                assert!(n.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)), "{s:?}");
+                assert_eq!(ContainerName::parse(&n).unwrap(), n);
+            }
+
+            if let Ok(d) = DatasetName::parse(&s) {
+                assert!(d.chars().all(|c| is_zfs_char(c) || c == '/'), "{s:?}");
+                assert!(d.split('/').all(|c| !c.is_empty() && !c.starts_with('-') && c != ".."), "{s:?}");
+                assert_eq!(DatasetName::parse(&d).unwrap(), d);
+            }
+            if let Ok((d, snap)) = DatasetName::parse_snapshot(&s) {
+                assert_eq!(format!("{}@{}", d, snap), s);
+            }
+
+            if let Ok(h) = HostName::parse(&s) {
+                assert!(!h.starts_with('-') && !h.contains(char::is_whitespace), "{s:?}");
+                assert_eq!(HostName::parse(&h).unwrap(), h);
+            }
+
+            if let Ok(m) = MountPoint::parse(&s) {
+                assert!(m.starts_with('/') && !m.ends_with('/') && !m.contains("//"), "{s:?}");
+                assert!(m.split('/').all(|c| c != ".." && c != "."), "{s:?}");
+                assert!(!m.chars().any(|c| c.is_control()), "{s:?}");
+                assert_eq!(MountPoint::parse(&m).unwrap(), m);
+            }
+        }
+    }
+}
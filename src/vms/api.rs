@@ -3,6 +3,10 @@
 // https://wolf.uk.com
 
 use actix_web::{web, HttpResponse, HttpRequest};
+use actix_web::http::StatusCode;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
 use serde::Deserialize;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -23,9 +27,35 @@ static VM_MIGRATION_CLIENT: std::sync::LazyLock<reqwest::Client> =
 use crate::api::{
     AppState, MigrationTasks, require_auth, build_node_urls,
     migration_create, migration_update, migration_fail, migration_done, migration_progress,
+    migration_set_bwlimit, migration_throttle, migration_slot,
 };
-use super::manager::{VmConfig, StorageVolume, UsbDevice, PciDevice};
+use super::manager::{VmConfig, VmManager, StorageVolume, UsbDevice, PciDevice};
 use super::passthrough;
+use crate::monitoring::inventory;
+
+/// Run `f` against VM `name` holding only that VM's lock. The manager mutex
+/// is taken just long enough to copy the manager, so disk conversions and
+/// hypervisor commands on one VM don't stall every other VM request.
+/// Blocking — call from `web::block`.
+fn with_vm_lock<T>(state: &AppState, name: &str, f: impl FnOnce(&VmManager) -> T) -> T {
+    let manager = state.vms.lock().unwrap().clone();
+    let lock = VmManager::vm_lock(name);
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+    f(&manager)
+}
+
+/// `virsh domstate` wording for a libvirt domain, over the libvirt socket
+/// when it answers; empty when the domain is unknown.
+fn libvirt_state(name: &str) -> String {
+    match super::libvirt::domain_state(name) {
+        Ok(d) => d.map(|d| d.state.to_string()).unwrap_or_default(),
+        Err(_) => std::process::Command::new("virsh")
+            .args(["domstate", name])
+            .output().ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default(),
+    }
+}
 
 /// Format a byte count for human display: "1.4 GB" / "812 MB" / etc.
 fn format_bytes_human(b: u64) -> String {
@@ -72,47 +102,43 @@ async fn poll_export_archive_size(
     }
 }
 
-/// Build a reqwest body that streams `archive_bytes` in 4 MiB chunks
-/// while updating the migration task's bytes_done counter. Each call
-/// returns a fresh stream so callers can retry across multiple import
-/// URLs without having to pre-clone the whole archive again. Reports
-/// *reads-from-memory*, not TCP ACKs — on slow networks the reported
+/// Build a reqwest body that streams the archive from disk while
+/// updating the migration task's bytes_done counter, held to the task's
+/// bandwidth cap (see `migration_set_bwlimit`). Each call opens the file
+/// afresh so callers can retry across multiple import URLs. Reports
+/// *reads-from-disk*, not TCP ACKs — on slow networks the reported
 /// percent races ahead of actual wire transmission by up to one
 /// kernel-TCP-buffer worth of bytes, which is acceptable feedback.
-fn build_progress_body(
-    archive_bytes: &[u8],
+async fn build_progress_body(
+    archive: &std::path::Path,
     total: u64,
     tasks: MigrationTasks,
     tid: String,
-) -> (reqwest::Body, u64) {
-    let archive = Arc::new(archive_bytes.to_vec());
-    let chunk_size: usize = 4 * 1024 * 1024;
-    let arc = archive.clone();
-    let stream = futures::stream::unfold(0usize, move |pos| {
-        let arc = arc.clone();
-        let tasks = tasks.clone();
-        let tid = tid.clone();
-        async move {
-            if pos >= arc.len() { return None; }
-            let end = (pos + chunk_size).min(arc.len());
-            let chunk = arc[pos..end].to_vec();
-            let new_pos = end;
-            migration_progress(&tasks, &tid, Some(new_pos as u64), Some(arc.len() as u64), None);
-            Some((Ok::<Vec<u8>, std::io::Error>(chunk), new_pos))
-        }
-    });
-    (reqwest::Body::wrap_stream(stream), total)
+) -> Result<reqwest::Body, String> {
+    let file = tokio::fs::File::open(archive).await
+        .map_err(|e| format!("Read archive: {}", e))?;
+    let throttle = migration_throttle(&tasks, &tid);
+    Ok(crate::transfer::paced_body(file, throttle, move |done| {
+        migration_progress(&tasks, &tid, Some(done), Some(total), None);
+    }))
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api/vms")
+            .wrap(actix_web::middleware::from_fn(invalidate_vm_snapshot))
             .route("", web::get().to(list_vms))
             .route("/wolfnet/health", web::get().to(wolfnet_health))
+            .route("/events", web::get().to(vm_events))
             .route("/prerequisites", web::get().to(vm_prerequisites))
             .route("/prerequisites/install", web::post().to(vm_prerequisites_install))
             .route("/create", web::post().to(create_vm))
             .route("/storage", web::get().to(list_storage))
+            .route("/isos", web::get().to(iso_list))
+            .route("/isos", web::delete().to(iso_delete))
+            .route("/isos/dirs", web::post().to(iso_set_dirs))
+            .route("/isos/download", web::post().to(iso_download))
+            .route("/isos/downloads", web::get().to(iso_downloads))
             .route("/host-devices", web::get().to(host_devices))
             .route("/import-external", web::post().to(vm_import_external))
             .route("/discover-libvirt", web::get().to(discover_libvirt))
@@ -128,6 +154,14 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/{name}/volumes", web::post().to(add_volume))
             .route("/{name}/volumes/{vol}", web::delete().to(remove_volume))
             .route("/{name}/volumes/{vol}/resize", web::post().to(resize_volume))
+            .route("/{name}/import-disk", web::post().to(vm_import_disk))
+            .route("/{name}/passthrough", web::get().to(vm_passthrough_get))
+            .route("/{name}/passthrough", web::put().to(vm_passthrough_set))
+            .route("/{name}/passthrough/check", web::post().to(vm_passthrough_check))
+            .route("/{name}/resources/cpus", web::post().to(vm_resize_cpus))
+            .route("/{name}/resources/memory", web::post().to(vm_resize_memory))
+            .route("/{name}/resources/disk", web::post().to(vm_grow_disk))
+            .route("/{name}/hotplug", web::put().to(vm_hotplug_options))
             .route("/{name}/vnc-password", web::get().to(vm_vnc_password))
             .route("/{name}/start-command", web::get().to(vm_start_command))
             .route("/{name}", web::put().to(update_vm))
@@ -212,26 +246,238 @@ async fn vm_prerequisites_install(
 /// WolfStack are tagged with `in_use_by` so the picker can grey them out.
 async fn host_devices(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let vms = {
-        let manager = state.vms.lock().unwrap();
-        manager.list_vms()
-    };
+    let vms = inventory::vms(&state).await;
     let ownership = passthrough::build_ownership(&vms);
     let response = passthrough::list_host_devices(&ownership);
     HttpResponse::Ok().json(response)
 }
 
-async fn list_vms(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+#[derive(Deserialize)]
+struct PassthroughRequest {
+    #[serde(default)]
+    usb_devices: Vec<UsbDevice>,
+    #[serde(default)]
+    pci_devices: Vec<PciDevice>,
+}
+
+/// GET /api/vms/{name}/passthrough — the VM's assigned USB/PCI devices
+/// plus a validation pass (device present, IOMMU group viable, not
+/// claimed by another VM).
+async fn vm_passthrough_get(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    // list_vms() runs qm/virsh/pgrep subprocesses; doing it inline (while holding
-    // the std Mutex) blocked an actix request worker and serialised every VM
-    // request behind it — under the page-load burst the list came up blank for
-    // 10-20s. Offload to the blocking pool; the lock is taken inside it.
-    let state = state.clone(); // clone the Arc-backed web::Data, lock inside the block
-    match web::block(move || state.vms.lock().unwrap().list_vms()).await {
-        Ok(vms) => HttpResponse::Ok().json(vms),
-        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({"error": "vm list unavailable"})),
+    let name = path.into_inner();
+    let vms = inventory::vms(&state).await;
+    let result = web::block(move || {
+        let vm = vms.iter().find(|v| v.name == name).cloned()
+            .ok_or_else(|| format!("VM '{}' not found", name))?;
+        let check = passthrough::check_assignment(&vm.name, &vm.usb_devices, &vm.pci_devices, &vms);
+        Ok::<_, String>((vm, check))
+    }).await;
+    match result {
+        Ok(Ok((vm, check))) => HttpResponse::Ok().json(serde_json::json!({
+            "usb_devices": vm.usb_devices,
+            "pci_devices": vm.pci_devices,
+            "check": check,
+        })),
+        Ok(Err(e)) => HttpResponse::NotFound().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/vms/{name}/passthrough/check — validate a proposed device
+/// set without saving it, so the picker can warn as boxes are ticked.
+async fn vm_passthrough_check(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<PassthroughRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let body = body.into_inner();
+    let vms = inventory::vms(&state).await;
+    match web::block(move || {
+        passthrough::check_assignment(&name, &body.usb_devices, &body.pci_devices, &vms)
+    }).await {
+        Ok(check) => HttpResponse::Ok().json(check),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// PUT /api/vms/{name}/passthrough — replace the VM's passthrough set.
+/// Refused with the full check when it has errors; warnings come back
+/// alongside the success.
+async fn vm_passthrough_set(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<PassthroughRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let mut body = body.into_inner();
+    for p in &mut body.pci_devices {
+        if let Ok(bdf) = passthrough::normalize_bdf(&p.bdf) { p.bdf = bdf; }
+    }
+    for u in &mut body.usb_devices {
+        u.vendor_id = u.vendor_id.to_ascii_lowercase();
+        u.product_id = u.product_id.to_ascii_lowercase();
+    }
+    let st = state.clone();
+    let result = web::block(move || {
+        let vms = inventory::vms_blocking(&st);
+        if !vms.iter().any(|v| v.name == name) {
+            return Err((404, format!("VM '{}' not found", name), None));
+        }
+        let check = passthrough::check_assignment(&name, &body.usb_devices, &body.pci_devices, &vms);
+        if !check.errors.is_empty() {
+            return Err((400, check.errors.join("; "), Some(check)));
+        }
+        with_vm_lock(&st, &name, |manager| {
+            manager.update_vm(&name, None, None, None, None, None, None, None, None, None, None, None, None, None,
+                              Some(body.usb_devices), Some(body.pci_devices),
+                              None, None, None, None, None, None, None, None, None)
+        })
+            .map(|msg| (msg, check))
+            .map_err(|e| (500, e, None))
+    }).await;
+    match result {
+        Ok(Ok((msg, check))) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true, "message": msg, "warnings": check.warnings,
+        })),
+        Ok(Err((code, e, check))) => {
+            let body = serde_json::json!({ "error": e, "check": check });
+            match code {
+                404 => HttpResponse::NotFound().json(body),
+                400 => HttpResponse::BadRequest().json(body),
+                _ => HttpResponse::InternalServerError().json(body),
+            }
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+// ─── Live resource changes ───
+
+#[derive(Deserialize)]
+struct CpuResizeRequest {
+    cpus: u32,
+}
+
+#[derive(Deserialize)]
+struct MemoryResizeRequest {
+    memory_mb: u32,
+}
+
+#[derive(Deserialize)]
+struct DiskGrowRequest {
+    /// "os", a volume name, a PVE slot or a libvirt target.
+    #[serde(default = "default_os_disk")]
+    disk: String,
+    size_gb: u32,
+}
+
+fn default_os_disk() -> String { "os".to_string() }
+
+#[derive(Deserialize)]
+struct HotplugOptionsRequest {
+    max_cpus: Option<u32>,
+    balloon: Option<bool>,
+}
+
+fn resource_response(result: Result<Result<String, String>, actix_web::error::BlockingError>) -> HttpResponse {
+    match result {
+        Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({ "success": true, "message": msg })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// POST /api/vms/{name}/resources/cpus — change a running VM's vCPUs.
+async fn vm_resize_cpus(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<CpuResizeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let st = state.clone();
+    resource_response(web::block(move || with_vm_lock(&st, &name, |m| m.hotplug_cpus(&name, body.cpus))).await)
+}
+
+/// POST /api/vms/{name}/resources/memory — set a running VM's memory
+/// (balloon target).
+async fn vm_resize_memory(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<MemoryResizeRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let st = state.clone();
+    resource_response(web::block(move || with_vm_lock(&st, &name, |m| m.hotplug_memory(&name, body.memory_mb))).await)
+}
+
+/// POST /api/vms/{name}/resources/disk — grow a disk, online when the VM
+/// is running.
+async fn vm_grow_disk(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<DiskGrowRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let st = state.clone();
+    resource_response(web::block(move || with_vm_lock(&st, &name, |m| m.grow_disk(&name, &body.disk, body.size_gb))).await)
+}
+
+/// PUT /api/vms/{name}/hotplug — native vCPU ceiling and balloon, applied
+/// at the next start.
+async fn vm_hotplug_options(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<HotplugOptionsRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let name = path.into_inner();
+    let st = state.clone();
+    resource_response(web::block(move || {
+        with_vm_lock(&st, &name, |m| m.set_hotplug_options(&name, body.max_cpus, body.balloon))
+            .map(|_| "Saved — takes effect the next time the VM starts".to_string())
+    }).await)
+}
+
+async fn list_vms(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
+    let scope = crate::auth::tenancy::scope(&req, &caller);
+    // list_vms() runs qm/virsh/pgrep subprocesses under the manager mutex;
+    // doing it per request serialised every VM request behind it — under
+    // the page-load burst the list came up blank for 10-20s. Serve the
+    // background snapshot instead (listed on the blocking pool when empty).
+    HttpResponse::Ok().json(crate::auth::tenancy::visible(scope.as_deref(), inventory::vms(&state).await,
+        |vm| Some((crate::auth::tenancy::Kind::Vm, vm.name.as_str()))))
+}
+
+/// Middleware: any write under `/api/vms` may change the list (create,
+/// delete, start/stop, edits), so drop the snapshot once it has run and
+/// the next read lists afresh.
+async fn invalidate_vm_snapshot(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let write = req.method() != actix_web::http::Method::GET;
+    let res = next.call(req).await;
+    if write {
+        inventory::VMS.invalidate();
     }
+    res
+}
+
+/// GET /api/vms/events — libvirt lifecycle events as server-sent events
+/// (`event: vm`, JSON data), so the VM list can refresh the moment a
+/// domain starts, stops or crashes instead of on its next poll. Stays
+/// open and silent on hosts without libvirt.
+async fn vm_events(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let mut rx = super::libvirt::subscribe();
+    let stream = async_stream::stream! {
+        yield Ok::<_, actix_web::Error>(web::Bytes::from_static(b": connected\n\n"));
+        // A comment every 30s keeps proxies from timing out an idle stream.
+        let mut keepalive = tokio::time::interval(Duration::from_secs(30));
+        keepalive.tick().await;
+        loop {
+            let chunk = tokio::select! {
+                ev = rx.recv() => match ev {
+                    Ok(ev) => format!("event: vm\ndata: {}\n\n", serde_json::to_string(&ev).unwrap_or_default()),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+            };
+            yield Ok(web::Bytes::from(chunk));
+        }
+    };
+    HttpResponse::Ok()
+        .insert_header(("Content-Type", "text/event-stream"))
+        // identity so Compress doesn't buffer the stream
+        .insert_header(("Content-Encoding", "identity"))
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream)
 }
 
 /// GET /api/vms/wolfnet/health — per-VM WolfNet plumbing status.
@@ -247,45 +493,128 @@ async fn list_vms(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse
 /// frontend's VM table can fold the health state into a status pill.
 async fn wolfnet_health(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let vms = {
-        let manager = state.vms.lock().unwrap();
-        manager.list_vms()
-    };
-    let mut out: Vec<serde_json::Value> = Vec::new();
-    for vm in vms {
-        let wolfnet_ip = match vm.wolfnet_ip.as_deref() {
-            Some(ip) if !ip.is_empty() => ip.to_string(),
-            _ => continue, // skip VMs without WolfNet
-        };
-        // Pure inspection — safe to call from a request handler.
-        let tap = crate::vms::manager::VmManager::tap_name(&vm.name);
-        let health = crate::vms::manager::probe_wolfnet_tap_health(&tap, &wolfnet_ip);
-        out.push(serde_json::json!({
-            "vm": vm.name,
-            "running": vm.running,
-            "ok": health.ok(),
-            "tap": health.tap,
-            "gateway_ip": health.gateway_ip,
-            "wolfnet_ip": health.wolfnet_ip,
-            "tap_exists": health.tap_exists,
-            "tap_up": health.tap_up,
-            "gateway_assigned": health.gateway_assigned,
-            "dnsmasq_pid": health.dnsmasq_pid,
-            "dnsmasq_alive": health.dnsmasq_alive,
-            "dnsmasq_owns_tap": health.dnsmasq_owns_tap,
-            "lease_present": health.lease_present,
-            "failures": health.failures,
-        }));
-    }
+    let vms = inventory::vms(&state).await;
+    // The probes read sysfs and /proc, so run them off the async worker.
+    let out = web::block(move || {
+        let mut out: Vec<serde_json::Value> = Vec::new();
+        for vm in vms {
+            let wolfnet_ip = match vm.wolfnet_ip.as_deref() {
+                Some(ip) if !ip.is_empty() => ip.to_string(),
+                _ => continue, // skip VMs without WolfNet
+            };
+            // Pure inspection — no side effects.
+            let tap = crate::vms::manager::VmManager::tap_name(&vm.name);
+            let health = crate::vms::manager::probe_wolfnet_tap_health(&tap, &wolfnet_ip);
+            out.push(serde_json::json!({
+                "vm": vm.name,
+                "running": vm.running,
+                "ok": health.ok(),
+                "tap": health.tap,
+                "gateway_ip": health.gateway_ip,
+                "wolfnet_ip": health.wolfnet_ip,
+                "tap_exists": health.tap_exists,
+                "tap_up": health.tap_up,
+                "gateway_assigned": health.gateway_assigned,
+                "dnsmasq_pid": health.dnsmasq_pid,
+                "dnsmasq_alive": health.dnsmasq_alive,
+                "dnsmasq_owns_tap": health.dnsmasq_owns_tap,
+                "lease_present": health.lease_present,
+                "failures": health.failures,
+            }));
+        }
+        out
+    }).await.unwrap_or_default();
     HttpResponse::Ok().json(out)
 }
 
 /// List available storage locations on the host
 async fn list_storage(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let manager = state.vms.lock().unwrap();
-    let locations = manager.list_storage_locations();
-    HttpResponse::Ok().json(locations)
+    let st = state.clone();
+    match web::block(move || st.vms.lock().unwrap().list_storage_locations()).await {
+        Ok(locations) => HttpResponse::Ok().json(locations),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+// ─── ISO library ───
+
+/// GET /api/vms/isos — images across every library directory
+async fn iso_list(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match web::block(|| (super::iso_library::list(), super::iso_library::library_dirs(), super::iso_library::LibrarySettings::load())).await {
+        Ok((isos, dirs, settings)) => HttpResponse::Ok().json(serde_json::json!({
+            "isos": isos,
+            "dirs": dirs,
+            "extra_dirs": settings.extra_dirs,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+struct IsoPathQuery {
+    path: String,
+}
+
+/// DELETE /api/vms/isos?path=
+async fn iso_delete(req: HttpRequest, state: web::Data<AppState>, query: web::Query<IsoPathQuery>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let path = query.into_inner().path;
+    match web::block(move || super::iso_library::delete(&path)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "message": "Image deleted" })),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+struct IsoDirsRequest {
+    #[serde(default)]
+    dirs: Vec<String>,
+}
+
+/// POST /api/vms/isos/dirs — extra directories to include in the library
+async fn iso_set_dirs(req: HttpRequest, state: web::Data<AppState>, body: web::Json<IsoDirsRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    match super::iso_library::set_extra_dirs(body.into_inner().dirs) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Library directories saved" })),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Deserialize)]
+struct IsoDownloadRequest {
+    url: String,
+    /// Save as; defaults to the URL's file name.
+    #[serde(default)]
+    file_name: String,
+    /// Library directory; defaults to /var/lib/wolfstack/isos.
+    #[serde(default)]
+    dir: String,
+    #[serde(default)]
+    sha256: String,
+}
+
+/// POST /api/vms/isos/download — fetch an ISO from a URL in the background
+async fn iso_download(req: HttpRequest, state: web::Data<AppState>, body: web::Json<IsoDownloadRequest>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let b = body.into_inner();
+    let started = web::block(move || super::iso_library::start_download(&b.url, &b.file_name, &b.dir, &b.sha256)).await;
+    match started {
+        Ok(Ok(job)) => {
+            tokio::spawn(super::iso_library::run_download(job.clone()));
+            HttpResponse::Ok().json(job)
+        }
+        Ok(Err(e)) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// GET /api/vms/isos/downloads — progress of current and recent downloads
+async fn iso_downloads(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    HttpResponse::Ok().json(super::iso_library::jobs())
 }
 
 #[derive(Deserialize)]
@@ -379,6 +708,12 @@ struct CreateVmRequest {
     /// never passed through a shell.
     #[serde(default)]
     extra_qemu_args: String,
+    /// Guest OS type — see `VmConfig::os_type`. Empty = not recorded.
+    #[serde(default)]
+    os_type: String,
+    /// RDP connection hint ("host[:port]") — see `VmConfig::rdp_address`.
+    #[serde(default)]
+    rdp_address: String,
 }
 
 fn default_bios_type() -> String { "seabios".to_string() }
@@ -406,8 +741,19 @@ fn validate_media_path(path: Option<&str>, field: &str) -> Result<(), HttpRespon
 
 fn default_os_bus() -> String { "virtio".to_string() }
 
+/// Reject an `os_type` outside Proxmox's `ostype` list (it's passed straight
+/// to `qm --ostype`). Empty = not recorded.
+fn validate_os_type(os_type: &str) -> Result<(), HttpResponse> {
+    if os_type.is_empty() || super::manager::PVE_OS_TYPES.contains(&os_type) {
+        return Ok(());
+    }
+    Err(HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("invalid os_type '{}': expected one of {}", os_type, super::manager::PVE_OS_TYPES.join(" | "))
+    })))
+}
+
 async fn create_vm(req: HttpRequest, state: web::Data<AppState>, body: web::Json<CreateVmRequest>) -> HttpResponse {
-    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let caller = match require_auth(&req, &state) { Ok(u) => u, Err(resp) => return resp };
 
     // Media paths are embedded in comma-delimited hypervisor args
     // (qm `--ideN <path>,media=cdrom`, virt-install `--disk ...,path=<path>`,
@@ -416,7 +762,13 @@ async fn create_vm(req: HttpRequest, state: web::Data<AppState>, body: web::Json
     if let Err(resp) = validate_media_path(body.iso_path.as_deref(), "ISO path") { return resp; }
     if let Err(resp) = validate_media_path(body.drivers_iso.as_deref(), "VirtIO drivers ISO path") { return resp; }
 
-    let manager = state.vms.lock().unwrap();
+    let capacity_warning = match crate::capacity_policy::check(
+        &format!("VM '{}'", body.name),
+        crate::capacity_policy::Resources { memory_mb: body.memory_mb as u64, cpus: body.cpus as f64 },
+    ) {
+        Ok(w) => w,
+        Err(e) => return HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    };
 
     let mut config = VmConfig::new(
         body.name.clone(),
@@ -449,6 +801,12 @@ async fn create_vm(req: HttpRequest, state: web::Data<AppState>, body: web::Json
     config.vnc_external = body.vnc_external;
     config.notes = body.notes.clone();
     config.extra_qemu_args = body.extra_qemu_args.clone();
+    if let Err(resp) = validate_os_type(&body.os_type) { return resp; }
+    config.os_type = body.os_type.clone();
+    config.rdp_address = match super::manager::normalize_rdp_address(&body.rdp_address) {
+        Ok(a) => a,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
 
     // Network mode + bridge fields. Validate mode at the boundary so a
     // typo in the request can't silently fall through to the default
@@ -511,9 +869,17 @@ async fn create_vm(req: HttpRequest, state: web::Data<AppState>, body: web::Json
         d
     }).collect();
 
-    match manager.create_vm(config) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    // Disk creation and the hypervisor define/import can take a while,
+    // so run them on the blocking pool under the new name's lock.
+    let st = state.clone();
+    let new_name = config.name.clone();
+    match web::block(move || with_vm_lock(&st, &new_name, |m| m.create_vm(config))).await {
+        Ok(Ok(_)) => {
+            crate::auth::tenancy::claim(&req, &caller, crate::auth::tenancy::Kind::Vm, &body.name);
+            HttpResponse::Ok().json(serde_json::json!({ "success": true, "capacity_warning": capacity_warning }))
+        }
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
     }
 }
 
@@ -550,6 +916,10 @@ struct UpdateVmRequest {
     /// Operator-supplied extra QEMU args (e.g. Windows-11 audio). Empty
     /// string clears it. Tokenised server-side; never shell-evaluated.
     extra_qemu_args: Option<String>,
+    /// Guest OS type. Empty string clears it. See `VmConfig::os_type`.
+    os_type: Option<String>,
+    /// RDP connection hint. Empty string clears it.
+    rdp_address: Option<String>,
 }
 
 async fn update_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<UpdateVmRequest>) -> HttpResponse {
@@ -561,6 +931,14 @@ async fn update_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path
     if let Err(resp) = validate_media_path(body.iso_path.as_deref(), "ISO path") { return resp; }
     if let Err(resp) = validate_media_path(body.drivers_iso.as_deref(), "VirtIO drivers ISO path") { return resp; }
 
+    if let Some(ref os) = body.os_type
+        && let Err(resp) = validate_os_type(os) { return resp; }
+    if let Some(ref rdp) = body.rdp_address
+        && let Err(e) = super::manager::normalize_rdp_address(rdp)
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": e }));
+    }
+
     // Validate network_mode at the boundary (same as CreateVmRequest).
     if let Some(ref nm) = body.network_mode {
         if !matches!(nm.as_str(), "" | "wolfnet" | "bridge" | "nat") {
@@ -570,42 +948,80 @@ async fn update_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path
         }
     }
 
-    let manager = state.vms.lock().unwrap();
-    match manager.update_vm(&name, body.cpus, body.memory_mb, body.iso_path.clone(),
-                            body.wolfnet_ip.clone(), body.disk_size_gb,
-                            body.os_disk_bus.clone(), body.net_model.clone(),
-                            body.drivers_iso.clone(), body.auto_start,
-                            body.bios_type.clone(),
-                            body.tpm, body.secure_boot,
-                            body.extra_nics.clone(),
-                            body.usb_devices.clone(),
-                            body.pci_devices.clone(),
-                            body.network_mode.clone(),
-                            body.bridge.clone(),
-                            body.bridge_ip_mode.clone(),
-                            body.bridge_ip.clone(),
-                            body.bridge_gateway.clone(),
-                            body.boot_order.clone(),
-                            body.vnc_external,
-                            body.notes.clone(),
-                            body.extra_qemu_args.clone()) {
+    // A changed passthrough set goes through the same IOMMU / ownership
+    // validation as PUT /passthrough. Unchanged sets are let through so a
+    // CPU or memory edit isn't blocked by a device that's since been
+    // unplugged.
+    if body.usb_devices.is_some() || body.pci_devices.is_some() {
+        let vms = inventory::vms(&state).await;
+        if let Some(current) = vms.iter().find(|v| v.name == name) {
+            let usb = body.usb_devices.clone().unwrap_or_else(|| current.usb_devices.clone());
+            let pci = body.pci_devices.clone().unwrap_or_else(|| current.pci_devices.clone());
+            if usb != current.usb_devices || pci != current.pci_devices {
+                let check = passthrough::check_assignment(&name, &usb, &pci, &vms);
+                if !check.errors.is_empty() {
+                    return HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": format!("Passthrough: {}", check.errors.join("; ")),
+                        "check": check,
+                    }));
+                }
+            }
+        }
+    }
+
+    // Disk resizes and hypervisor edits shell out, so run them on the
+    // blocking pool.
+    let b = body.into_inner();
+    let st = state.clone();
+    let result = web::block(move || with_vm_lock(&st, &name, |manager| {
+        let msg = manager.update_vm(&name, b.cpus, b.memory_mb, b.iso_path,
+                                    b.wolfnet_ip, b.disk_size_gb,
+                                    b.os_disk_bus, b.net_model,
+                                    b.drivers_iso, b.auto_start,
+                                    b.bios_type,
+                                    b.tpm, b.secure_boot,
+                                    b.extra_nics,
+                                    b.usb_devices,
+                                    b.pci_devices,
+                                    b.network_mode,
+                                    b.bridge,
+                                    b.bridge_ip_mode,
+                                    b.bridge_ip,
+                                    b.bridge_gateway,
+                                    b.boot_order,
+                                    b.vnc_external,
+                                    b.notes,
+                                    b.extra_qemu_args)?;
+        // OS type / RDP hint are metadata rather than hardware, so they
+        // save after the hardware edit and apply immediately.
+        if b.os_type.is_some() || b.rdp_address.is_some() {
+            manager.set_guest_hints(&name, b.os_type, b.rdp_address)?;
+        }
+        Ok::<_, String>(msg)
+    })).await;
+
+    match result {
         // Some(msg) is a non-fatal advisory (e.g. libvirt hardware edits that
         // apply on next boot) the UI shows next to the success toast.
-        Ok(msg) => HttpResponse::Ok().json(serde_json::json!({ "success": true, "message": msg })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Ok(Ok(msg)) => HttpResponse::Ok().json(serde_json::json!({ "success": true, "message": msg })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
     }
 }
 
 async fn get_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-
-    match manager.get_vm(&name) {
-        Some(vm) => {
-            // Attach the hypervisor backend so the editor can tailor its UI
-            // (running-VM note wording, Proxmox OS-disk-bus lock).
-            let platform = manager.vm_platform(&name);
+    let st = state.clone();
+    let found = web::block(move || {
+        let manager = st.vms.lock().unwrap();
+        // Attach the hypervisor backend so the editor can tailor its UI
+        // (running-VM note wording, Proxmox OS-disk-bus lock).
+        manager.get_vm(&name).map(|vm| (vm, manager.vm_platform(&name)))
+    }).await.ok().flatten();
+
+    match found {
+        Some((vm, platform)) => {
             match serde_json::to_value(&vm) {
                 Ok(mut v) => {
                     if let Some(obj) = v.as_object_mut() {
@@ -625,11 +1041,15 @@ async fn get_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path<St
 async fn delete_vm(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-    
-    match manager.delete_vm(&name) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    let st = state.clone();
+    match web::block(move || {
+        with_vm_lock(&st, &name, |m| m.delete_vm(&name))?;
+        crate::containers::startup_order::forget("vm", &name);
+        Ok::<(), String>(())
+    }).await {
+        Ok(Ok(_)) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
     }
 }
 
@@ -646,10 +1066,9 @@ async fn vm_action(req: HttpRequest, state: web::Data<AppState>, path: web::Path
     // elsewhere in the cluster. Read the IP under a brief lock, then release
     // it before the async cluster check (never hold the mutex across await).
     if body.action == "start" {
-        let ip = {
-            let manager = state.vms.lock().unwrap();
-            manager.get_vm(&name).and_then(|c| c.wolfnet_ip)
-        };
+        let ip = inventory::vms(&state).await.into_iter()
+            .find(|v| v.name == name)
+            .and_then(|c| c.wolfnet_ip);
         if let Some(ip) = ip.filter(|s| !s.trim().is_empty())
             && let Some(holder) = crate::api::wolfnet_ip_active_elsewhere(&state, &ip).await
         {
@@ -659,24 +1078,28 @@ async fn vm_action(req: HttpRequest, state: web::Data<AppState>, path: web::Path
         }
     }
 
-    let manager = state.vms.lock().unwrap();
-
-    let result = match body.action.as_str() {
-        "start" => manager.start_vm(&name),
-        // Graceful ACPI shutdown — tries to let the guest close cleanly.
-        // qm / virsh / SIGTERM variants depending on backend.
-        "stop" => manager.stop_vm(&name, false),
-        // Power-yank — equivalent to the old `stop` behaviour. For when
-        // the guest is wedged or the user needs an immediate halt.
-        "force-stop" => manager.stop_vm(&name, true),
-        // Reboot the guest (qm/virsh reboot; native QMP system_reset).
-        "restart" => manager.restart_vm(&name),
-        // Suspend/resume to RAM (qm/virsh suspend|resume; native QMP stop/cont).
-        "pause" => manager.pause_vm(&name),
-        "resume" => manager.resume_vm(&name),
-        _ => Err(format!("Unknown action: {}", body.action)),
-    };
-    
+    // Start/stop wait on the hypervisor (up to minutes for a graceful
+    // shutdown), so run on the blocking pool, not the actix worker.
+    let st = state.clone();
+    let action = body.into_inner().action;
+    let result = web::block(move || with_vm_lock(&st, &name, |manager| {
+        match action.as_str() {
+            "start" => manager.start_vm(&name),
+            // Graceful ACPI shutdown — tries to let the guest close cleanly.
+            // qm / virsh / SIGTERM variants depending on backend.
+            "stop" => manager.stop_vm(&name, false),
+            // Power-yank — equivalent to the old `stop` behaviour. For when
+            // the guest is wedged or the user needs an immediate halt.
+            "force-stop" => manager.stop_vm(&name, true),
+            // Reboot the guest (qm/virsh reboot; native QMP system_reset).
+            "restart" => manager.restart_vm(&name),
+            // Suspend/resume to RAM (qm/virsh suspend|resume; native QMP stop/cont).
+            "pause" => manager.pause_vm(&name),
+            "resume" => manager.resume_vm(&name),
+            _ => Err(format!("Unknown action: {}", action)),
+        }
+    })).await.unwrap_or_else(|e| Err(e.to_string()));
+
     match result {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
@@ -718,25 +1141,32 @@ async fn vm_clone(
     let new_name = body.new_name.clone();
     let full = body.full;
 
-    // Plan FIRST under the lock, synchronously. prepare_clone is
-    // fast (validation + a list_vms read) and its failures are all
-    // user-input problems — return them as 400 so the frontend
-    // distinguishes them from runtime errors. The lock is dropped
-    // before we hand the plan off to the blocking executor.
-    let plan = match state.vms.lock().unwrap().prepare_clone(&name, &new_name) {
-        Ok(p) => p,
-        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
-    };
-
-    // Execute the actual clone in a blocking task so the multi-minute
-    // disk copy / qm clone / virt-clone subprocess doesn't block the
-    // actix worker. Lock is already released; the executor owns the
-    // plan and doesn't touch shared state.
-    let result = web::block(move || super::manager::execute_clone(plan, &new_name, full)).await;
+    // Plan against the inventory snapshot. prepare_clone's failures are
+    // all user-input problems — return them as 400 so the frontend
+    // distinguishes them from runtime errors.
+    //
+    // The multi-minute disk copy / qm clone / virt-clone runs on the
+    // blocking pool holding both names' VM locks (never the manager
+    // mutex), so nothing else changes the source or claims the new name
+    // mid-copy. Locks are taken in name order so two opposing clones
+    // can't deadlock.
+    let all = inventory::vms(&state).await;
+    let st = state.clone();
+    let result = web::block(move || {
+        let (first, second) = if name < new_name { (&name, &new_name) } else { (&new_name, &name) };
+        with_vm_lock(&st, first, |manager| {
+            // Identical names are refused by prepare_clone; don't lock twice.
+            let lock = (first != second).then(|| VmManager::vm_lock(second));
+            let _guard = lock.as_ref().map(|l| l.lock().unwrap_or_else(|e| e.into_inner()));
+            let plan = manager.prepare_clone(&name, &new_name, &all).map_err(|e| (400, e))?;
+            super::manager::execute_clone(plan, &new_name, full).map_err(|e| (500, e))
+        })
+    }).await;
 
     match result {
         Ok(Ok(_))  => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Ok(Err((400, e))) => HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+        Ok(Err((_, e))) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
         Err(e)     => HttpResponse::InternalServerError().json(serde_json::json!({
             "error": format!("clone task failed: {}", e)
         })),
@@ -746,11 +1176,14 @@ async fn vm_clone(
 async fn vm_logs(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-
-    let log_path = manager.base_dir.join(format!("{}.log", name));
-    let log_content = std::fs::read_to_string(&log_path)
-        .unwrap_or_else(|_| "No logs available for this VM.".to_string());
+    let st = state.clone();
+    let log_name = name.clone();
+    let log_content = web::block(move || {
+        let log_path = st.vms.lock().unwrap().base_dir.join(format!("{}.log", log_name));
+        std::fs::read_to_string(&log_path)
+    }).await
+        .ok().and_then(|r| r.ok())
+        .unwrap_or_else(|| "No logs available for this VM.".to_string());
 
     HttpResponse::Ok().json(serde_json::json!({ "name": name, "logs": log_content }))
 }
@@ -763,14 +1196,15 @@ async fn vm_logs(req: HttpRequest, state: web::Data<AppState>, path: web::Path<S
 async fn vm_vnc_password(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let (password, port, external) = {
-        let manager = state.vms.lock().unwrap();
+    let st = state.clone();
+    let (password, port, external) = web::block(move || {
+        let manager = st.vms.lock().unwrap();
         let pw = manager.read_runtime_vnc_password(&name);
         let vm = manager.get_vm(&name);
         let port = vm.as_ref().and_then(|v| v.vnc_port);
         let external = vm.as_ref().map(|v| v.vnc_external).unwrap_or(false);
         (pw, port, external)
-    };
+    }).await.unwrap_or_default();
     HttpResponse::Ok().json(serde_json::json!({
         "password": password,
         "vnc_port": port,
@@ -786,9 +1220,10 @@ async fn vm_vnc_password(req: HttpRequest, state: web::Data<AppState>, path: web
 async fn vm_start_command(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
-    let (command, source) = {
-        let manager = state.vms.lock().unwrap();
-        manager.start_command(&name)
+    let st = state.clone();
+    let (command, source) = match web::block(move || st.vms.lock().unwrap().start_command(&name)).await {
+        Ok(r) => r,
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
     };
     HttpResponse::Ok().json(serde_json::json!({
         "command": command,
@@ -804,7 +1239,12 @@ async fn vm_start_command(req: HttpRequest, state: web::Data<AppState>, path: we
 async fn vm_serial_status(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
+    // qm / virsh probes — keep them off the actix worker.
+    let st = state.clone();
+    json_response(web::block(move || serial_status(&st, name)).await)
+}
 
+fn serial_status(state: &AppState, name: String) -> (StatusCode, serde_json::Value) {
     let backend = if crate::containers::is_proxmox() {
         "pve"
     } else if crate::containers::is_libvirt() {
@@ -821,7 +1261,7 @@ async fn vm_serial_status(req: HttpRequest, state: web::Data<AppState>, path: we
             let vmid = manager.qm_vmid_by_name(&name);
             drop(manager);
             let Some(vmid) = vmid else {
-                return HttpResponse::NotFound().json(serde_json::json!({"error": format!("VM '{}' not found", name)}));
+                return (StatusCode::NOT_FOUND, serde_json::json!({"error": format!("VM '{}' not found", name)}));
             };
             // `qm config` lists current config; a `serial0:` line means an
             // emulated UART is wired to a socket we can attach to.
@@ -853,13 +1293,7 @@ async fn vm_serial_status(req: HttpRequest, state: web::Data<AppState>, path: we
             // the conservative answer is "both present". If either is
             // missing, vm_add_serial() will top up just the missing half.
             configured = xml.contains("<serial ") && xml.contains("<console ");
-            let state = std::process::Command::new("virsh")
-                .args(["domstate", &name])
-                .output()
-                .ok()
-                .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-                .unwrap_or_default();
-            running = state.trim() == "running";
+            running = libvirt_state(&name) == "running";
         }
         _ => {
             // Standalone QEMU. Three distinct states:
@@ -881,7 +1315,7 @@ async fn vm_serial_status(req: HttpRequest, state: web::Data<AppState>, path: we
         }
     }
 
-    HttpResponse::Ok().json(serde_json::json!({
+    (StatusCode::OK, serde_json::json!({
         "backend": backend,
         "configured": configured,
         "running": running,
@@ -897,14 +1331,18 @@ async fn vm_serial_status(req: HttpRequest, state: web::Data<AppState>, path: we
 async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let name = path.into_inner();
+    let st = state.clone();
+    json_response(web::block(move || add_serial(&st, name)).await)
+}
 
+fn add_serial(state: &AppState, name: String) -> (StatusCode, serde_json::Value) {
     if crate::containers::is_proxmox() {
         let vmid = {
             let m = state.vms.lock().unwrap();
             m.qm_vmid_by_name(&name)
         };
         let Some(vmid) = vmid else {
-            return HttpResponse::NotFound().json(serde_json::json!({"error": format!("VM '{}' not found in Proxmox", name)}));
+            return (StatusCode::NOT_FOUND, serde_json::json!({"error": format!("VM '{}' not found in Proxmox", name)}));
         };
         // Check running-ness so we can tell the user whether a reboot is
         // needed for the new device to show up in the guest.
@@ -921,16 +1359,16 @@ async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::
             .map_err(|e| format!("Failed to run qm set: {}", e));
         match output {
             Ok(o) if o.status.success() => {
-                HttpResponse::Ok().json(serde_json::json!({
+                (StatusCode::OK, serde_json::json!({
                     "ok": true,
                     "message": "serial0 added (socket)",
                     "requires_reboot": running,
                 }))
             }
-            Ok(o) => HttpResponse::InternalServerError().json(serde_json::json!({
+            Ok(o) => (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({
                 "error": format!("qm set failed: {}", String::from_utf8_lossy(&o.stderr).trim())
             })),
-            Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"error": e})),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({"error": e})),
         }
     } else if crate::containers::is_libvirt() {
         // libvirt: a working serial setup wants a matching <serial>/<console>
@@ -939,13 +1377,7 @@ async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::
         // attach each missing half separately. Console devices aren't
         // hot-pluggable so we always write to the persisted XML (`--config`)
         // and tell the caller to reboot if the domain is currently up.
-        let running = std::process::Command::new("virsh")
-            .args(["domstate", &name])
-            .output().ok()
-            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-            .unwrap_or_default()
-            .trim()
-            .to_string() == "running";
+        let running = libvirt_state(&name) == "running";
 
         let xml_dump = std::process::Command::new("virsh")
             .args(["dumpxml", &name])
@@ -968,7 +1400,7 @@ async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::
         // Shouldn't happen (caller checks configured=false before calling)
         // but handle gracefully if everything's already wired.
         if pieces.is_empty() {
-            return HttpResponse::Ok().json(serde_json::json!({
+            return (StatusCode::OK, serde_json::json!({
                 "ok": true,
                 "message": "serial + console already configured",
                 "requires_reboot": false,
@@ -1006,11 +1438,11 @@ async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::
         }
 
         if !errors.is_empty() {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
+            return (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({
                 "error": format!("virsh attach-device failed: {}", errors.join("; "))
             }));
         }
-        HttpResponse::Ok().json(serde_json::json!({
+        (StatusCode::OK, serde_json::json!({
             "ok": true,
             "message": format!("attached: {}", attached.join(", ")),
             "requires_reboot": running,
@@ -1026,19 +1458,37 @@ async fn vm_add_serial(req: HttpRequest, state: web::Data<AppState>, path: web::
             m.check_running(&name)
         };
         if running {
-            HttpResponse::BadRequest().json(serde_json::json!({
+            (StatusCode::BAD_REQUEST, serde_json::json!({
                 "error": "This VM was started before serial-console support was added. Stop and start it again to enable the terminal."
             }))
         } else {
-            HttpResponse::BadRequest().json(serde_json::json!({
+            (StatusCode::BAD_REQUEST, serde_json::json!({
                 "error": "Start the VM first — standalone QEMU creates its serial socket at boot time."
             }))
         }
     }
 }
 
+/// Render a `(status, body)` pair computed on the blocking pool.
+fn json_response(result: Result<(StatusCode, serde_json::Value), actix_web::error::BlockingError>) -> HttpResponse {
+    match result {
+        Ok((code, body)) => HttpResponse::build(code).json(body),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 // ─── Storage Volume Endpoints ───
 
+/// Volume changes create, copy or resize disk images, so they run on the
+/// blocking pool; this maps the outcome to the usual JSON replies.
+fn volume_response(result: Result<Result<(), String>, actix_web::error::BlockingError>) -> HttpResponse {
+    match result {
+        Ok(Ok(())) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
 #[derive(Deserialize)]
 struct AddVolumeRequest {
     name: String,
@@ -1051,25 +1501,19 @@ struct AddVolumeRequest {
 async fn add_volume(req: HttpRequest, state: web::Data<AppState>, path: web::Path<String>, body: web::Json<AddVolumeRequest>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let vm_name = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-
-    match manager.add_volume(&vm_name, &body.name, body.size_gb, 
-                             body.storage_path.as_deref(), body.format.as_deref(),
-                             body.bus.as_deref()) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
-    }
+    let st = state.clone();
+    volume_response(web::block(move || {
+        with_vm_lock(&st, &vm_name, |m| m.add_volume(&vm_name, &body.name, body.size_gb,
+                                                     body.storage_path.as_deref(), body.format.as_deref(),
+                                                     body.bus.as_deref()))
+    }).await)
 }
 
 async fn remove_volume(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let (vm_name, vol_name) = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-
-    match manager.remove_volume(&vm_name, &vol_name, true) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
-    }
+    let st = state.clone();
+    volume_response(web::block(move || with_vm_lock(&st, &vm_name, |m| m.remove_volume(&vm_name, &vol_name, true))).await)
 }
 
 #[derive(Deserialize)]
@@ -1080,12 +1524,9 @@ struct ResizeVolumeRequest {
 async fn resize_volume(req: HttpRequest, state: web::Data<AppState>, path: web::Path<(String, String)>, body: web::Json<ResizeVolumeRequest>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
     let (vm_name, vol_name) = path.into_inner();
-    let manager = state.vms.lock().unwrap();
-
-    match manager.resize_volume(&vm_name, &vol_name, body.size_gb) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
-    }
+    let size_gb = body.size_gb;
+    let st = state.clone();
+    volume_response(web::block(move || with_vm_lock(&st, &vm_name, |m| m.resize_volume(&vm_name, &vol_name, size_gb))).await)
 }
 
 // ─── VM Migration Endpoints ───
@@ -1117,6 +1558,9 @@ struct VmMigrateRequest {
     target_address: Option<String>,
     #[serde(default)]
     target_port: Option<u16>,
+    /// Cap on the upload, in Mbit/s. None = uncapped.
+    #[serde(default)]
+    bwlimit_mbps: Option<u32>,
 }
 
 #[derive(Deserialize)]
@@ -1148,6 +1592,9 @@ struct VmMigrateExternalRequest {
     /// Request PVE-managed import on the target.
     #[serde(default)]
     proxmox: bool,
+    /// Cap on the upload, in Mbit/s. None = uncapped.
+    #[serde(default)]
+    bwlimit_mbps: Option<u32>,
 }
 
 /// Intra-cluster migration preflight. Talks to the target node BEFORE
@@ -1241,6 +1688,17 @@ async fn migrate_preflight_intra(
     Ok(())
 }
 
+/// Force-stop (`start == false`) or start the source VM around a
+/// migration export, on the blocking pool — the hypervisor call can take
+/// a while and the VM's lock may be held by another blocking task.
+async fn migration_power(state: &web::Data<AppState>, name: &str, start: bool) -> Result<(), String> {
+    let st = state.clone();
+    let name = name.to_string();
+    tokio::task::spawn_blocking(move || with_vm_lock(&st, &name, |manager| {
+        if start { manager.start_vm(&name) } else { manager.stop_vm(&name, true) }
+    })).await.unwrap_or_else(|e| Err(format!("power task join: {}", e)))
+}
+
 /// POST /api/vms/{name}/migrate — migrate VM to another cluster node.
 /// Spawns a background task so the HTTP call returns immediately with a
 /// `task_id`; the frontend polls `/api/migration/{id}/status` to drive
@@ -1297,6 +1755,7 @@ async fn vm_migrate(
                     site: None,
                     display_name: None,
                     roles: Vec::new(),
+                    labels: Default::default(),
                 }
             } else {
                 return HttpResponse::NotFound().json(serde_json::json!({"error": "Target node not found"}));
@@ -1318,6 +1777,7 @@ async fn vm_migrate(
 
     let tasks = state.migration_tasks.clone();
     let task_id = migration_create(&tasks);
+    migration_set_bwlimit(&tasks, &task_id, body.bwlimit_mbps);
 
     let tid = task_id.clone();
     let state_clone = state.clone();
@@ -1328,6 +1788,7 @@ async fn vm_migrate(
     let target_label = body.target_node.clone();
 
     tokio::spawn(async move {
+        let _slot = migration_slot(&state_clone.migration_tasks, &tid, &format!("Migrate VM '{}' to {}", name, target_label)).await;
         // Preflight — refuse BEFORE stopping the source if the target
         // already has a VM by this name, or has visibly insufficient
         // space on the chosen storage. Both failures otherwise lead to
@@ -1346,11 +1807,8 @@ async fn vm_migrate(
         }
 
         migration_update(&state_clone.migration_tasks, &tid, "stopping", &format!("Stopping VM '{}' for consistent export…", name));
-        {
-            let manager = state_clone.vms.lock().unwrap();
-            if let Err(e) = manager.stop_vm(&name, true) {
-                tracing::warn!("Failed to stop VM '{}' before migration: {}", name, e);
-            }
+        if let Err(e) = migration_power(&state_clone, &name, false).await {
+            tracing::warn!("Failed to stop VM '{}' before migration: {}", name, e);
         }
 
         migration_update(&state_clone.migration_tasks, &tid, "export",
@@ -1387,29 +1845,23 @@ async fn vm_migrate(
         let archive_path = match export_result {
             Ok(p) => p,
             Err(e) => {
-                let manager = state_clone.vms.lock().unwrap();
-                let _ = manager.start_vm(&name);
+                let _ = migration_power(&state_clone, &name, true).await;
                 migration_fail(&state_clone.migration_tasks, &tid, &format!("Export failed: {}", e));
                 return;
             }
         };
 
         // Source stays running from here — destination gets the consistent copy.
-        {
-            let manager = state_clone.vms.lock().unwrap();
-            let _ = manager.start_vm(&name);
-        }
+        let _ = migration_power(&state_clone, &name, true).await;
 
-        let archive_bytes = match std::fs::read(&archive_path) {
-            Ok(b) => b,
+        let total_bytes = match std::fs::metadata(&archive_path) {
+            Ok(m) => m.len(),
             Err(e) => {
                 super::manager::export_cleanup(archive_path.to_str().unwrap_or(""));
                 migration_fail(&state_clone.migration_tasks, &tid, &format!("Read archive: {}", e));
                 return;
             }
         };
-
-        let total_bytes = archive_bytes.len() as u64;
         migration_update(&state_clone.migration_tasks, &tid, "upload",
             &format!("Uploading {} to {}…", format_bytes_human(total_bytes), target_label));
         migration_progress(&state_clone.migration_tasks, &tid, Some(0), Some(total_bytes), Some(0.0));
@@ -1433,11 +1885,13 @@ async fn vm_migrate(
 
         for import_url in &import_urls {
             // Build a streaming body so upload progress is reported
-            // per-chunk. The whole archive already lives in memory, so
-            // we just slice it on the outgoing stream side.
-            let (body, _total) = build_progress_body(
-                &archive_bytes, total_bytes, state_clone.migration_tasks.clone(), tid.clone(),
-            );
+            // per-chunk, read from disk as it goes out.
+            let body = match build_progress_body(
+                &archive_path, total_bytes, state_clone.migration_tasks.clone(), tid.clone(),
+            ).await {
+                Ok(b) => b,
+                Err(e) => { last_err = Some(e); continue; }
+            };
             let part = reqwest::multipart::Part::stream_with_length(body, total_bytes)
                 .file_name(file_name.clone())
                 .mime_str("application/octet-stream").unwrap_or_else(|_| reqwest::multipart::Part::text("".to_string()));
@@ -1540,6 +1994,7 @@ async fn vm_disk_migrate(
     let tasks_for_task = tasks.clone();
 
     tokio::spawn(async move {
+        let _slot = migration_slot(&tasks_for_task, &tid, &format!("Move VM '{}' disks", name)).await;
         migration_update(&tasks_for_task, &tid, "disk_copy",
             &format!("Moving '{}' disks → {}{}…", name, target,
                 if expected_total > 0 { format!(" ({})", format_bytes_human(expected_total)) } else { String::new() }));
@@ -1763,6 +2218,7 @@ async fn vm_migrate_external(
 
     let tasks = state.migration_tasks.clone();
     let task_id = migration_create(&tasks);
+    migration_set_bwlimit(&tasks, &task_id, body.bwlimit_mbps);
     let tid = task_id.clone();
     let state_clone = state.clone();
     let target_url = body.target_url.clone();
@@ -1774,6 +2230,7 @@ async fn vm_migrate_external(
     let target_label = target_url.replace("https://", "").replace("http://", "").split('/').next().unwrap_or(&target_url).to_string();
 
     tokio::spawn(async move {
+        let _slot = migration_slot(&state_clone.migration_tasks, &tid, &format!("Migrate VM '{}' to {}", name, target_label)).await;
         migration_update(&state_clone.migration_tasks, &tid, "preflight", &format!("Checking connectivity to {}…", target_label));
         let preflight_urls = crate::api::build_external_urls(&target_url, "/api/storage/list");
         let preflight_client = &*VM_MIGRATION_CLIENT;
@@ -1842,11 +2299,8 @@ async fn vm_migrate_external(
         // see migrate_preflight_intra.)
 
         migration_update(&state_clone.migration_tasks, &tid, "stopping", &format!("Stopping VM '{}' for consistent export…", name));
-        {
-            let manager = state_clone.vms.lock().unwrap();
-            if let Err(e) = manager.stop_vm(&name, true) {
-                tracing::warn!("Failed to stop VM '{}' before migration: {}", name, e);
-            }
+        if let Err(e) = migration_power(&state_clone, &name, false).await {
+            tracing::warn!("Failed to stop VM '{}' before migration: {}", name, e);
         }
 
         migration_update(&state_clone.migration_tasks, &tid, "export",
@@ -1879,28 +2333,22 @@ async fn vm_migrate_external(
         let archive_path = match export_result {
             Ok(p) => p,
             Err(e) => {
-                let manager = state_clone.vms.lock().unwrap();
-                let _ = manager.start_vm(&name);
+                let _ = migration_power(&state_clone, &name, true).await;
                 migration_fail(&state_clone.migration_tasks, &tid, &format!("Export failed: {}", e));
                 return;
             }
         };
 
-        {
-            let manager = state_clone.vms.lock().unwrap();
-            let _ = manager.start_vm(&name);
-        }
+        let _ = migration_power(&state_clone, &name, true).await;
 
-        let archive_bytes = match std::fs::read(&archive_path) {
-            Ok(b) => b,
+        let total_bytes = match std::fs::metadata(&archive_path) {
+            Ok(m) => m.len(),
             Err(e) => {
                 super::manager::export_cleanup(archive_path.to_str().unwrap_or(""));
                 migration_fail(&state_clone.migration_tasks, &tid, &format!("Read archive: {}", e));
                 return;
             }
         };
-
-        let total_bytes = archive_bytes.len() as u64;
         migration_update(&state_clone.migration_tasks, &tid, "upload",
             &format!("Uploading {} to {}…", format_bytes_human(total_bytes), target_label));
         migration_progress(&state_clone.migration_tasks, &tid, Some(0), Some(total_bytes), Some(0.0));
@@ -1913,9 +2361,12 @@ async fn vm_migrate_external(
         let mut finished = false;
 
         for import_url in &import_urls {
-            let (body, _) = build_progress_body(
-                &archive_bytes, total_bytes, state_clone.migration_tasks.clone(), tid.clone(),
-            );
+            let body = match build_progress_body(
+                &archive_path, total_bytes, state_clone.migration_tasks.clone(), tid.clone(),
+            ).await {
+                Ok(b) => b,
+                Err(e) => { last_err = Some(e); continue; }
+            };
             let part = reqwest::multipart::Part::stream_with_length(body, total_bytes)
                 .file_name(file_name.clone())
                 .mime_str("application/octet-stream").unwrap_or_else(|_| reqwest::multipart::Part::text("".to_string()));
@@ -2140,13 +2591,89 @@ async fn vm_import_external(
     }
 }
 
+// ─── Disk image import ───
+
+#[derive(Deserialize)]
+struct ImportDiskRequest {
+    /// Host path to an .ova, .vmdk, .img/.raw, .vdi, .vhd(x) or .qcow2.
+    source: String,
+    /// Replace the VM's OS disk with the (first) imported disk instead of
+    /// attaching it as an extra volume.
+    #[serde(default)]
+    as_os_disk: bool,
+    /// Directory for extra volumes; defaults to the VM base dir.
+    #[serde(default)]
+    storage_path: Option<String>,
+    /// Extra-volume name, stored as `<vm>-<name>`.
+    #[serde(default)]
+    vol_name: Option<String>,
+    #[serde(default)]
+    bus: Option<String>,
+}
+
+const IMPORT_EXTENSIONS: &[&str] = &["ova", "vmdk", "img", "raw", "qcow2", "vdi", "vhd", "vhdx"];
+
+/// POST /api/vms/{name}/import-disk — convert a foreign disk image (OVA,
+/// VMDK, raw, …) to qcow2 and attach it to a stopped VM. Returns a
+/// `task_id`; progress is reported via `/api/migration/{id}/status`.
+async fn vm_import_disk(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ImportDiskRequest>,
+) -> HttpResponse {
+    if let Err(resp) = require_auth(&req, &state) { return resp; }
+    let vm_name = path.into_inner();
+    let source = std::path::PathBuf::from(body.source.trim());
+    if !source.is_file() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": format!("Image not found: {}", source.display())}));
+    }
+    let ext = source.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    if !IMPORT_EXTENSIONS.contains(&ext.as_str()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unsupported image type '.{}' (expected one of: {})", ext, IMPORT_EXTENSIONS.join(", "))
+        }));
+    }
+    let bus = body.bus.as_deref().unwrap_or("").trim().to_string();
+    if !matches!(bus.as_str(), "" | "virtio" | "scsi" | "sata" | "ide") {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": format!("Unknown disk bus '{}'", bus)}));
+    }
+    let vol_name = body.vol_name.as_deref().map(str::trim).filter(|v| !v.is_empty()).unwrap_or("import").to_string();
+    if let Err(e) = super::manager::validate_clone_vm_name(&vol_name) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": e}));
+    }
+
+    let as_os_disk = body.as_os_disk;
+    let storage_path = body.storage_path.clone();
+    let st = state.clone();
+    let name = vm_name.clone();
+    let dest_dir = match web::block(move || {
+        with_vm_lock(&st, &name, |m| m.import_disk_dir(&name, as_os_disk, storage_path.as_deref()))
+    }).await {
+        Ok(Ok(d)) => d,
+        Ok(Err(e)) => return HttpResponse::BadRequest().json(serde_json::json!({"error": e})),
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()})),
+    };
+
+    let task_id = migration_create(&state.migration_tasks);
+    let plan = super::disk_import::ImportPlan { vm_name, source, dest_dir, as_os_disk, vol_name, bus };
+    tokio::spawn(super::disk_import::run_import(state.clone(), task_id.clone(), plan));
+    HttpResponse::Ok().json(serde_json::json!({
+        "task_id": task_id,
+        "message": "Disk import started"
+    }))
+}
+
 // ─── Libvirt VM Discovery & Adoption ───
 
 /// GET /api/vms/discover-libvirt — discover VMs managed by libvirt
 async fn discover_libvirt(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let manager = state.vms.lock().unwrap();
-    HttpResponse::Ok().json(manager.discover_libvirt_vms())
+    let st = state.clone();
+    match web::block(move || st.vms.lock().unwrap().discover_libvirt_vms()).await {
+        Ok(vms) => HttpResponse::Ok().json(vms),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
+    }
 }
 
 #[derive(Deserialize)]
@@ -2157,14 +2684,15 @@ struct AdoptLibvirtRequest {
 /// POST /api/vms/adopt-libvirt — adopt a libvirt VM into WolfStack
 async fn adopt_libvirt(req: HttpRequest, state: web::Data<AppState>, body: web::Json<AdoptLibvirtRequest>) -> HttpResponse {
     if let Err(resp) = require_auth(&req, &state) { return resp; }
-    let manager = state.vms.lock().unwrap();
-    match manager.adopt_libvirt_vm(&body.name) {
-        Ok(config) => HttpResponse::Ok().json(serde_json::json!({
+    let st = state.clone();
+    match web::block(move || with_vm_lock(&st, &body.name, |m| m.adopt_libvirt_vm(&body.name))).await {
+        Ok(Ok(config)) => HttpResponse::Ok().json(serde_json::json!({
             "success": true,
             "message": format!("VM '{}' adopted successfully", config.name),
             "vm": config,
         })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e.to_string() })),
     }
 }
 
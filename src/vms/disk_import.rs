@@ -0,0 +1,248 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Import foreign disk images into an existing VM — the VMware migration
+//! path. Accepts an `.ova` (the VMDKs are pulled out of the tar), a bare
+//! `.vmdk`, raw `.img`/`.raw`, `.vdi`, `.vhd(x)` or `.qcow2`, converts
+//! each disk to qcow2 with `qemu-img convert -p` and registers the result
+//! as the VM's OS disk or as extra volumes.
+//!
+//! Progress goes through the migration task registry, so the UI polls
+//! `/api/migration/{id}/status` exactly as it does for a migration:
+//! `extract` (OVA only) → `convert` → `register` → `done`.
+//!
+//! Conversions are written as hidden `.import-*.qcow2` files in the
+//! destination directory and renamed into place at the end, so a failed
+//! or interrupted import never leaves a half-written disk attached.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use actix_web::web;
+
+use crate::api::{AppState, MigrationTasks, migration_done, migration_fail, migration_progress, migration_update};
+
+/// What the operator asked for, validated by the endpoint.
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub vm_name: String,
+    pub source: PathBuf,
+    /// Directory the converted disks are written to (see
+    /// `VmManager::import_disk_dir`).
+    pub dest_dir: PathBuf,
+    /// First disk replaces the VM's OS disk; any others become extras.
+    pub as_os_disk: bool,
+    pub vol_name: String,
+    pub bus: String,
+}
+
+pub fn is_ova(path: &Path) -> bool {
+    path.extension().map(|e| e.eq_ignore_ascii_case("ova")).unwrap_or(false)
+}
+
+/// Disk members of an OVA, in archive order (which follows the OVF's
+/// disk order, so the boot disk comes first). Members with absolute
+/// paths or `..` components are ignored.
+pub fn ova_disk_entries(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('/') && !l.split('/').any(|c| c == ".."))
+        .filter(|l| {
+            let lower = l.to_ascii_lowercase();
+            [".vmdk", ".img", ".raw", ".qcow2", ".vhd", ".vhdx"].iter().any(|ext| lower.ends_with(ext))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Last `(NN.NN/100%)` counter in a chunk of `qemu-img convert -p`
+/// output. qemu-img redraws the line with `\r`, so a read can hold
+/// several counters or a partial one.
+pub fn parse_convert_progress(output: &str) -> Option<f64> {
+    output
+        .rsplit('(')
+        .find_map(|seg| seg.split_once("/100%").and_then(|(p, _)| p.trim().parse::<f64>().ok()))
+}
+
+/// Map disk `index` of `count` at `percent` onto a single 0–100 bar.
+pub fn overall_percent(index: usize, count: usize, percent: f64) -> f64 {
+    (index as f64 + percent.clamp(0.0, 100.0) / 100.0) / count.max(1) as f64 * 100.0
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else { return 0; };
+    entries.flatten().map(|e| match e.metadata() {
+        Ok(md) if md.is_dir() => dir_size(&e.path()),
+        Ok(md) => md.len(),
+        Err(_) => 0,
+    }).sum()
+}
+
+/// Unpack the disk members of `ova` into `staging`, reporting bytes
+/// written against the archive size. Returns the extracted disk paths.
+async fn extract_ova(tasks: &MigrationTasks, tid: &str, ova: &Path, staging: &Path) -> Result<Vec<PathBuf>, String> {
+    let listing = tokio::process::Command::new("tar")
+        .arg("-tf").arg(ova)
+        .output().await
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !listing.status.success() {
+        return Err(format!("Not a readable OVA: {}", String::from_utf8_lossy(&listing.stderr).trim()));
+    }
+    let entries = ova_disk_entries(&String::from_utf8_lossy(&listing.stdout));
+    if entries.is_empty() {
+        return Err("The OVA contains no disk images".to_string());
+    }
+    std::fs::create_dir_all(staging).map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+
+    let total = std::fs::metadata(ova).map(|m| m.len()).unwrap_or(0);
+    let stop = Arc::new(AtomicBool::new(false));
+    let poller = {
+        let (tasks, tid, staging, stop) = (tasks.clone(), tid.to_string(), staging.to_path_buf(), stop.clone());
+        tokio::spawn(async move {
+            while !stop.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                migration_progress(&tasks, &tid, Some(dir_size(&staging)), Some(total), None);
+            }
+        })
+    };
+    let out = tokio::process::Command::new("tar")
+        .arg("-xf").arg(ova)
+        .arg("-C").arg(staging)
+        .arg("--")
+        .args(&entries)
+        .output().await;
+    stop.store(true, Ordering::Relaxed);
+    let _ = poller.await;
+    let out = out.map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("OVA extraction failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(entries.iter().map(|e| staging.join(e)).collect())
+}
+
+/// `qemu-img convert -p` one disk to qcow2, streaming the percent into
+/// the task.
+async fn convert(tasks: &MigrationTasks, tid: &str, src: &Path, dest: &Path, index: usize, count: usize) -> Result<(), String> {
+    use tokio::io::AsyncReadExt;
+
+    let src_str = src.to_string_lossy().to_string();
+    let mut child = tokio::process::Command::new("qemu-img")
+        .arg("convert").arg("-p")
+        .arg("-f").arg(super::manager::detect_image_format(&src_str))
+        .arg("-O").arg("qcow2")
+        .arg(src).arg(dest)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("qemu-img convert failed to start: {}", e))?;
+    let mut stdout = child.stdout.take().ok_or("missing stdout")?;
+    let mut stderr = child.stderr.take().ok_or("missing stderr")?;
+    let err_reader = tokio::spawn(async move {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf).await;
+        buf
+    });
+
+    let mut buf = [0u8; 4096];
+    loop {
+        match stdout.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if let Some(p) = parse_convert_progress(&String::from_utf8_lossy(&buf[..n])) {
+                    migration_progress(tasks, tid, None, None, Some(overall_percent(index, count, p)));
+                }
+            }
+        }
+    }
+    let status = child.wait().await.map_err(|e| format!("qemu-img wait: {}", e))?;
+    let err = err_reader.await.unwrap_or_default();
+    if !status.success() {
+        return Err(format!("qemu-img convert failed for {}: {}", src.display(), err.trim()));
+    }
+    Ok(())
+}
+
+/// Background task body: extract, convert each disk, register. Cleans up
+/// staging and partial conversions whatever the outcome.
+pub async fn run_import(state: web::Data<AppState>, tid: String, plan: ImportPlan) {
+    let tasks = state.migration_tasks.clone();
+    let job = uuid::Uuid::new_v4().to_string().replace('-', "")[..8].to_string();
+    let staging = plan.dest_dir.join(format!(".import-{}", job));
+    let mut converted: Vec<PathBuf> = Vec::new();
+
+    let result: Result<String, String> = async {
+        let sources = if is_ova(&plan.source) {
+            migration_update(&tasks, &tid, "extract", "Extracting disks from the OVA…");
+            extract_ova(&tasks, &tid, &plan.source, &staging).await?
+        } else {
+            vec![plan.source.clone()]
+        };
+
+        let count = sources.len();
+        migration_update(&tasks, &tid, "convert", "Converting to qcow2…");
+        for (i, src) in sources.iter().enumerate() {
+            migration_update(&tasks, &tid, "convert", &format!(
+                "Converting disk {} of {} ({})…", i + 1, count,
+                src.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+            ));
+            migration_progress(&tasks, &tid, None, None, Some(overall_percent(i, count, 0.0)));
+            let dest = plan.dest_dir.join(format!(".import-{}-{}.qcow2", job, i));
+            converted.push(dest.clone());
+            convert(&tasks, &tid, src, &dest, i, count).await?;
+        }
+
+        migration_update(&tasks, &tid, "register", "Attaching to the VM…");
+        let mut notes = Vec::new();
+        for (i, disk) in converted.iter().enumerate() {
+            let as_os = plan.as_os_disk && i == 0;
+            let (vm_name, disk, vol, bus) = (plan.vm_name.clone(), disk.clone(), plan.vol_name.clone(), plan.bus.clone());
+            let st = state.clone();
+            let note = web::block(move || st.vms.lock().unwrap().attach_imported_disk(&vm_name, &disk, as_os, &vol, &bus))
+                .await
+                .map_err(|e| e.to_string())??;
+            notes.push(note);
+        }
+        Ok(notes.join("; "))
+    }.await;
+
+    let _ = std::fs::remove_dir_all(&staging);
+    for disk in &converted {
+        // Only leftovers from a failed run still carry the hidden name.
+        let _ = std::fs::remove_file(disk);
+    }
+    match result {
+        Ok(msg) => migration_done(&tasks, &tid, &msg),
+        Err(e) => migration_fail(&tasks, &tid, &e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ova_listing_keeps_disks_in_order() {
+        let listing = "vm.ovf\nvm.mf\nvm-disk1.vmdk\nvm-disk2.VMDK\nnvram\n../evil.vmdk\n/abs.vmdk\n";
+        assert_eq!(ova_disk_entries(listing), vec!["vm-disk1.vmdk", "vm-disk2.VMDK"]);
+        assert!(is_ova(Path::new("/srv/import/Web01.OVA")));
+        assert!(!is_ova(Path::new("/srv/import/web01.vmdk")));
+    }
+
+    #[test]
+    fn convert_progress_takes_the_latest_counter() {
+        assert_eq!(parse_convert_progress("    (0.00/100%)\r    (12.50/100%)\r"), Some(12.5));
+        assert_eq!(parse_convert_progress("    (99.01/100%)\r    (100.0"), Some(99.01));
+        assert_eq!(parse_convert_progress("no progress here"), None);
+    }
+
+    #[test]
+    fn percent_spans_all_disks() {
+        assert_eq!(overall_percent(0, 1, 50.0), 50.0);
+        assert_eq!(overall_percent(1, 2, 50.0), 75.0);
+        assert_eq!(overall_percent(0, 0, 150.0), 100.0);
+    }
+}
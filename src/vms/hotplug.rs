@@ -0,0 +1,198 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Pure helpers for changing a running VM's resources — vCPU hot-plug,
+//! memory balloon and online disk growth. The backend calls (QMP for
+//! native QEMU, `qm` for Proxmox, `virsh` for libvirt) live on
+//! `VmManager`; this module turns QMP replies into plans so they can be
+//! tested without a running guest.
+//!
+//! Native QEMU only hot-plugs into headroom reserved at boot: vCPUs up to
+//! `-smp …,maxcpus=N`, memory down (and back up) through a virtio-balloon
+//! device. Disks grow via QMP `block_resize`, which also raises a
+//! capacity-change event in the guest — the guest still has to grow its
+//! partition and filesystem.
+
+use serde_json::Value;
+
+/// Device line added to native VMs with `balloon` enabled.
+pub const BALLOON_DEVICE: &str = "virtio-balloon-pci,id=balloon0";
+
+/// Prefix of the QOM ids WolfStack gives hot-plugged vCPUs, so unplug
+/// only ever removes CPUs we added (boot CPUs can't be removed).
+const CPU_ID_PREFIX: &str = "wscpu-";
+
+/// `-smp` value for `cpus` vCPUs with optional hot-plug headroom.
+pub fn smp_arg(cpus: u32, max_cpus: u32) -> String {
+    if max_cpus > cpus {
+        format!("cpus={},maxcpus={}", cpus, max_cpus)
+    } else {
+        cpus.to_string()
+    }
+}
+
+/// What to do to reach a vCPU target: `device_add` argument objects for
+/// new CPUs and `device_del` ids for hot-plugged ones to remove.
+#[derive(Debug, Default, PartialEq)]
+pub struct CpuPlan {
+    pub current: u32,
+    pub add: Vec<Value>,
+    pub remove: Vec<String>,
+}
+
+fn cpu_slot_id(props: &Value) -> String {
+    let part = |key: &str| props.get(key).and_then(Value::as_u64).map(|v| v.to_string()).unwrap_or_else(|| "0".to_string());
+    format!("{}s{}c{}t{}", CPU_ID_PREFIX, part("socket-id"), part("core-id"), part("thread-id"))
+}
+
+/// Plan a vCPU change from a `query-hotpluggable-cpus` reply. Slots with
+/// a `qom-path` are plugged; new CPUs fill the lowest free slots and
+/// removal takes the most recently added WolfStack CPUs first.
+pub fn plan_cpu_hotplug(hotpluggable: &Value, target: u32) -> Result<CpuPlan, String> {
+    let slots = hotpluggable.as_array().ok_or("Unexpected query-hotpluggable-cpus reply")?;
+    if target == 0 {
+        return Err("A VM needs at least one vCPU".to_string());
+    }
+    let (plugged, free): (Vec<&Value>, Vec<&Value>) = slots.iter().partition(|s| s.get("qom-path").is_some());
+    let current = plugged.iter()
+        .map(|s| s.get("vcpus-count").and_then(Value::as_u64).unwrap_or(1) as u32)
+        .sum::<u32>();
+    let mut plan = CpuPlan { current, ..Default::default() };
+
+    if target > current {
+        let needed = (target - current) as usize;
+        if needed > free.len() {
+            return Err(format!(
+                "Only {} more vCPU(s) can be hot-plugged (maximum {}) — raise the vCPU ceiling and restart the VM to go higher",
+                free.len(), current as usize + free.len()
+            ));
+        }
+        // QEMU lists slots highest-first; plug from the bottom up.
+        let mut free: Vec<&Value> = free;
+        free.reverse();
+        for slot in free.into_iter().take(needed) {
+            let driver = slot.get("type").and_then(Value::as_str).ok_or("Hot-pluggable CPU slot has no type")?;
+            let props = slot.get("props").cloned().unwrap_or_else(|| serde_json::json!({}));
+            let mut args = serde_json::json!({ "driver": driver, "id": cpu_slot_id(&props) });
+            if let (Some(obj), Some(p)) = (args.as_object_mut(), props.as_object()) {
+                for (k, v) in p {
+                    // node-id is reported but not accepted by device_add
+                    // unless NUMA is configured.
+                    if k != "node-id" {
+                        obj.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+            plan.add.push(args);
+        }
+    } else if target < current {
+        let needed = (current - target) as usize;
+        let ours: Vec<String> = plugged.iter()
+            .filter_map(|s| s.get("qom-path").and_then(Value::as_str))
+            .filter_map(|p| p.rsplit('/').next())
+            .filter(|id| id.starts_with(CPU_ID_PREFIX))
+            .map(str::to_string)
+            .collect();
+        if needed > ours.len() {
+            return Err(format!(
+                "Only {} hot-plugged vCPU(s) can be removed — vCPUs present at boot stay until the VM restarts",
+                ours.len()
+            ));
+        }
+        plan.remove = ours.into_iter().take(needed).collect();
+    }
+    Ok(plan)
+}
+
+/// `block_resize` handle for the drive in a `query-block` reply whose
+/// image is `path`: `("device", name)` for named drives, otherwise
+/// `("node-name", node)` for `-drive if=none` style anonymous ones.
+pub fn block_device_for(query_block: &Value, path: &str) -> Option<(&'static str, String)> {
+    query_block.as_array()?.iter().find_map(|entry| {
+        let inserted = entry.get("inserted")?;
+        let file = inserted.get("file").and_then(Value::as_str)
+            .or_else(|| inserted.get("image").and_then(|i| i.get("filename")).and_then(Value::as_str))?;
+        if file != path {
+            return None;
+        }
+        entry.get("device").and_then(Value::as_str).filter(|d| !d.is_empty()).map(|d| ("device", d.to_string()))
+            .or_else(|| inserted.get("node-name").and_then(Value::as_str).map(|n| ("node-name", n.to_string())))
+    })
+}
+
+/// Proxmox disk slot names accepted by `qm resize` (scsi0, virtio1, …).
+pub fn valid_pve_disk_slot(slot: &str) -> bool {
+    ["scsi", "virtio", "sata", "ide", "efidisk", "tpmstate"].iter().any(|bus| {
+        slot.strip_prefix(bus)
+            .map(|n| !n.is_empty() && n.len() <= 2 && n.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn slots() -> Value {
+        // Shape of `query-hotpluggable-cpus` for -smp cpus=2,maxcpus=4:
+        // highest slot first, boot CPUs carry a qom-path.
+        json!([
+            { "type": "qemu64-x86_64-cpu", "vcpus-count": 1, "props": { "socket-id": 3, "core-id": 0, "thread-id": 0 } },
+            { "type": "qemu64-x86_64-cpu", "vcpus-count": 1, "props": { "socket-id": 2, "core-id": 0, "thread-id": 0 },
+              "qom-path": "/machine/peripheral/wscpu-s2c0t0" },
+            { "type": "qemu64-x86_64-cpu", "vcpus-count": 1, "props": { "socket-id": 1, "core-id": 0, "thread-id": 0 },
+              "qom-path": "/machine/unattached/device[2]" },
+            { "type": "qemu64-x86_64-cpu", "vcpus-count": 1, "props": { "socket-id": 0, "core-id": 0, "thread-id": 0, "node-id": 0 },
+              "qom-path": "/machine/unattached/device[0]" }
+        ])
+    }
+
+    #[test]
+    fn smp_reserves_headroom_only_when_asked() {
+        assert_eq!(smp_arg(2, 0), "2");
+        assert_eq!(smp_arg(2, 2), "2");
+        assert_eq!(smp_arg(2, 8), "cpus=2,maxcpus=8");
+    }
+
+    #[test]
+    fn cpu_plan_adds_into_free_slots() {
+        let plan = plan_cpu_hotplug(&slots(), 4).unwrap();
+        assert_eq!(plan.current, 3);
+        assert_eq!(plan.add, vec![json!({
+            "driver": "qemu64-x86_64-cpu", "id": "wscpu-s3c0t0", "socket-id": 3, "core-id": 0, "thread-id": 0
+        })]);
+        assert!(plan_cpu_hotplug(&slots(), 5).unwrap_err().contains("Only 1 more"));
+        assert_eq!(plan_cpu_hotplug(&slots(), 3).unwrap(), CpuPlan { current: 3, ..Default::default() });
+    }
+
+    #[test]
+    fn cpu_plan_only_removes_hotplugged_cpus() {
+        let plan = plan_cpu_hotplug(&slots(), 2).unwrap();
+        assert_eq!(plan.remove, vec!["wscpu-s2c0t0".to_string()]);
+        assert!(plan_cpu_hotplug(&slots(), 1).unwrap_err().contains("Only 1 hot-plugged"));
+        assert!(plan_cpu_hotplug(&slots(), 0).is_err());
+    }
+
+    #[test]
+    fn block_device_matches_image_path() {
+        let reply = json!([
+            { "device": "virtio0", "inserted": { "file": "/var/lib/wolfstack/vms/web.qcow2", "node-name": "#block123" } },
+            { "device": "", "inserted": { "file": "/data/web-data.qcow2", "node-name": "#block456" } },
+            { "device": "ide1-cd0" }
+        ]);
+        assert_eq!(block_device_for(&reply, "/var/lib/wolfstack/vms/web.qcow2"), Some(("device", "virtio0".to_string())));
+        assert_eq!(block_device_for(&reply, "/data/web-data.qcow2"), Some(("node-name", "#block456".to_string())));
+        assert_eq!(block_device_for(&reply, "/nope.qcow2"), None);
+    }
+
+    #[test]
+    fn pve_slots_are_validated() {
+        assert!(valid_pve_disk_slot("scsi0"));
+        assert!(valid_pve_disk_slot("virtio12"));
+        assert!(!valid_pve_disk_slot("scsi"));
+        assert!(!valid_pve_disk_slot("scsi0; rm -rf /"));
+        assert!(!valid_pve_disk_slot("net0"));
+    }
+}
@@ -0,0 +1,387 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! ISO / disk-image library for the VM create flow.
+//!
+//! The library is not a separate store — it's a view over the places
+//! ISOs already live on this node:
+//!
+//! - `/var/lib/wolfstack/isos`, where URL downloads land by default;
+//! - on Proxmox, `template/iso` under every storage that allows `iso`
+//!   content (what the PVE UI shows);
+//! - an `iso` / `isos` folder at the root of each mounted storage mount,
+//!   so one NFS share can serve the whole cluster;
+//! - any extra directories the operator adds.
+//!
+//! Each directory is scanned one level deep. Downloads run in the
+//! background into a `.part` file, hash the bytes as they arrive and are
+//! renamed into place only if the optional SHA-256 matches, so a
+//! truncated or tampered image never shows up in the list.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+pub const DEFAULT_ISO_DIR: &str = "/var/lib/wolfstack/isos";
+const EXTENSIONS: &[&str] = &[".iso", ".img"];
+/// Finished / failed downloads are kept this long for the UI to report.
+const JOB_RETENTION_SECS: i64 = 3600;
+
+/// Public-internet downloads: a connect timeout but no total deadline —
+/// a 6 GB Windows ISO takes as long as it takes.
+static DOWNLOAD_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(15))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+});
+
+fn settings_path() -> String {
+    format!("{}/iso-library.json", crate::paths::get().config_dir)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibrarySettings {
+    /// Extra directories to scan for images.
+    #[serde(default)]
+    pub extra_dirs: Vec<String>,
+}
+
+impl LibrarySettings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = settings_path();
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+}
+
+pub fn set_extra_dirs(dirs: Vec<String>) -> Result<(), String> {
+    let mut clean = Vec::new();
+    for d in dirs {
+        let d = d.trim().trim_end_matches('/').to_string();
+        if d.is_empty() {
+            continue;
+        }
+        if !d.starts_with('/') {
+            return Err(format!("'{}' is not an absolute path", d));
+        }
+        if !clean.contains(&d) {
+            clean.push(d);
+        }
+    }
+    LibrarySettings { extra_dirs: clean }.save()
+}
+
+/// A directory the library scans, and where it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryDir {
+    pub path: String,
+    /// `default`, `proxmox:<storage>`, `mount:<name>` or `custom`.
+    pub source: String,
+}
+
+/// Every directory the library covers on this node. Only directories
+/// that exist are returned (the default one is created on first use).
+pub fn library_dirs() -> Vec<LibraryDir> {
+    let mut dirs = vec![LibraryDir { path: DEFAULT_ISO_DIR.to_string(), source: "default".to_string() }];
+    if crate::containers::is_proxmox() {
+        for s in crate::containers::pvesm_list_storage() {
+            if s.status == "active" && s.content.iter().any(|c| c == "iso") {
+                if let Some(p) = s.path.as_deref() {
+                    dirs.push(LibraryDir { path: format!("{}/template/iso", p.trim_end_matches('/')), source: format!("proxmox:{}", s.id) });
+                }
+            }
+        }
+    }
+    for m in crate::storage::list_mounts() {
+        if m.status != "mounted" {
+            continue;
+        }
+        for sub in ["iso", "isos"] {
+            let p = format!("{}/{}", m.mount_point.trim_end_matches('/'), sub);
+            if Path::new(&p).is_dir() {
+                dirs.push(LibraryDir { path: p, source: format!("mount:{}", m.name) });
+            }
+        }
+    }
+    for d in LibrarySettings::load().extra_dirs {
+        dirs.push(LibraryDir { path: d, source: "custom".to_string() });
+    }
+    let mut seen = std::collections::HashSet::new();
+    dirs.retain(|d| seen.insert(d.path.clone()) && (d.source == "default" || Path::new(&d.path).is_dir()));
+    dirs
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IsoEntry {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified: i64,
+    pub source: String,
+}
+
+pub fn is_image_name(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    !name.starts_with('.') && EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+/// Every image in the library, sorted by name.
+pub fn list() -> Vec<IsoEntry> {
+    let mut out = Vec::new();
+    for dir in library_dirs() {
+        let Ok(rd) = std::fs::read_dir(&dir.path) else { continue };
+        for e in rd.flatten() {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !is_image_name(&name) {
+                continue;
+            }
+            let Ok(md) = e.metadata() else { continue };
+            if !md.is_file() {
+                continue;
+            }
+            out.push(IsoEntry {
+                path: e.path().to_string_lossy().to_string(),
+                name,
+                size_bytes: md.len(),
+                modified: md
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0),
+                source: dir.source.clone(),
+            });
+        }
+    }
+    out.sort_by(|a, b| a.name.to_ascii_lowercase().cmp(&b.name.to_ascii_lowercase()).then(a.path.cmp(&b.path)));
+    out
+}
+
+/// Delete an image — only files inside a library directory.
+pub fn delete(path: &str) -> Result<(), String> {
+    let p = Path::new(path);
+    let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let parent = p.parent().map(|d| d.to_string_lossy().to_string()).unwrap_or_default();
+    if !is_image_name(&name) || !library_dirs().iter().any(|d| d.path == parent) {
+        return Err("Not an image in the ISO library".to_string());
+    }
+    std::fs::remove_file(p).map_err(|e| format!("Failed to delete {}: {}", path, e))
+}
+
+/// File name a download should be saved as: the caller's choice, else the
+/// last URL path segment. Must look like an image and must not contain a
+/// comma (hypervisor args are comma-separated — see validate_media_path).
+pub fn download_file_name(url: &str, requested: &str) -> Result<String, String> {
+    let name = if requested.trim().is_empty() {
+        let path = url.split(['?', '#']).next().unwrap_or("");
+        path.rsplit('/').next().unwrap_or("").to_string()
+    } else {
+        requested.trim().to_string()
+    };
+    let name = urlencoding::decode(&name).map(|n| n.into_owned()).unwrap_or_else(|_| name.clone());
+    if name.is_empty() || name.contains('/') || name.contains(',') || name.starts_with('.') || name.chars().any(|c| c.is_control()) {
+        return Err(format!("'{}' is not a usable file name — give the download a name", name));
+    }
+    if !is_image_name(&name) {
+        return Err(format!("'{}' doesn't end in .iso or .img", name));
+    }
+    Ok(name)
+}
+
+// ─── Downloads ───
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadJob {
+    pub id: String,
+    pub url: String,
+    pub path: String,
+    pub bytes_done: u64,
+    pub bytes_total: Option<u64>,
+    /// `downloading`, `verifying`, `done` or `failed`.
+    pub status: String,
+    pub error: Option<String>,
+    pub sha256: Option<String>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+static JOBS: LazyLock<Mutex<HashMap<String, DownloadJob>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn update_job(id: &str, f: impl FnOnce(&mut DownloadJob)) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        if let Some(j) = jobs.get_mut(id) {
+            f(j);
+        }
+    }
+}
+
+/// Current and recent downloads, newest first.
+pub fn jobs() -> Vec<DownloadJob> {
+    let now = chrono::Utc::now().timestamp();
+    let mut jobs = JOBS.lock().map(|mut m| {
+        m.retain(|_, j| j.finished_at.map(|t| now - t < JOB_RETENTION_SECS).unwrap_or(true));
+        m.values().cloned().collect::<Vec<_>>()
+    }).unwrap_or_default();
+    jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    jobs
+}
+
+/// Validate a download request and register its job. The caller spawns
+/// [`run_download`] with the returned job.
+pub fn start_download(url: &str, file_name: &str, dir: &str, sha256: &str) -> Result<DownloadJob, String> {
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("URL must start with http:// or https://".to_string());
+    }
+    let name = download_file_name(url, file_name)?;
+    let dir = if dir.trim().is_empty() { DEFAULT_ISO_DIR.to_string() } else { dir.trim().trim_end_matches('/').to_string() };
+    if !library_dirs().iter().any(|d| d.path == dir) {
+        return Err(format!("{} is not an ISO library directory", dir));
+    }
+    let expected = sha256.trim().to_ascii_lowercase();
+    if !expected.is_empty() && (expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit())) {
+        return Err("SHA-256 must be 64 hex characters".to_string());
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
+    let path = format!("{}/{}", dir, name);
+    if Path::new(&path).exists() {
+        return Err(format!("{} already exists", path));
+    }
+    let mut jobs = JOBS.lock().map_err(|_| "Download registry poisoned".to_string())?;
+    if jobs.values().any(|j| j.path == path && j.finished_at.is_none()) {
+        return Err(format!("{} is already downloading", name));
+    }
+    let job = DownloadJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: url.to_string(),
+        path,
+        bytes_done: 0,
+        bytes_total: None,
+        status: "downloading".to_string(),
+        error: None,
+        sha256: if expected.is_empty() { None } else { Some(expected) },
+        started_at: chrono::Utc::now().timestamp(),
+        finished_at: None,
+    };
+    jobs.insert(job.id.clone(), job.clone());
+    Ok(job)
+}
+
+fn part_path(path: &str) -> PathBuf {
+    let p = Path::new(path);
+    let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    p.with_file_name(format!(".{}.part", name))
+}
+
+/// Fetch the job's URL, hashing as it goes, then verify and move into place.
+pub async fn run_download(job: DownloadJob) {
+    let id = job.id.clone();
+    let result = download_inner(&job).await;
+    let now = chrono::Utc::now().timestamp();
+    match result {
+        Ok(digest) => update_job(&id, |j| {
+            j.status = "done".to_string();
+            j.sha256 = Some(digest);
+            j.finished_at = Some(now);
+        }),
+        Err(e) => {
+            let _ = tokio::fs::remove_file(part_path(&job.path)).await;
+            tracing::warn!("ISO download {} failed: {}", job.url, e);
+            update_job(&id, |j| {
+                j.status = "failed".to_string();
+                j.error = Some(e);
+                j.finished_at = Some(now);
+            });
+        }
+    }
+}
+
+async fn download_inner(job: &DownloadJob) -> Result<String, String> {
+    use futures::StreamExt;
+    use sha2::{Digest, Sha256};
+    use tokio::io::AsyncWriteExt;
+
+    let resp = DOWNLOAD_CLIENT.get(&job.url).send().await.map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("Server returned {}", resp.status()));
+    }
+    let total = resp.content_length();
+    update_job(&job.id, |j| j.bytes_total = total);
+
+    let part = part_path(&job.path);
+    let mut file = tokio::fs::File::create(&part).await.map_err(|e| format!("{}: {}", part.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut done: u64 = 0;
+    let mut last_report = std::time::Instant::now();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Connection lost after {} bytes: {}", done, e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("Write failed: {}", e))?;
+        hasher.update(&chunk);
+        done += chunk.len() as u64;
+        if last_report.elapsed() >= std::time::Duration::from_millis(500) {
+            update_job(&job.id, |j| j.bytes_done = done);
+            last_report = std::time::Instant::now();
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    update_job(&job.id, |j| {
+        j.bytes_done = done;
+        j.status = "verifying".to_string();
+    });
+    if let Some(t) = total {
+        if done != t {
+            return Err(format!("Download incomplete: got {} of {} bytes", done, t));
+        }
+    }
+    let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    if let Some(expected) = job.sha256.as_deref() {
+        if digest != expected {
+            return Err(format!("SHA-256 mismatch: expected {}, got {}", expected, digest));
+        }
+    }
+    tokio::fs::rename(&part, &job.path).await.map_err(|e| e.to_string())?;
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn download_names_come_from_the_url() {
+        assert_eq!(
+            download_file_name("https://releases.ubuntu.com/24.04/ubuntu-24.04.1-live-server-amd64.iso?x=1", "").unwrap(),
+            "ubuntu-24.04.1-live-server-amd64.iso"
+        );
+        assert_eq!(download_file_name("https://example.com/My%20Image.iso", "").unwrap(), "My Image.iso");
+        assert_eq!(download_file_name("https://example.com/download", "debian.iso").unwrap(), "debian.iso");
+        assert!(download_file_name("https://example.com/download", "").is_err());
+        assert!(download_file_name("https://example.com/a.iso", "win,11.iso").is_err());
+        assert!(download_file_name("https://example.com/a.iso", "../etc/x.iso").is_err());
+        assert!(download_file_name("https://example.com/setup.exe", "").is_err());
+    }
+
+    #[test]
+    fn only_visible_image_files_are_listed() {
+        assert!(is_image_name("debian-12.iso"));
+        assert!(is_image_name("Win11.ISO"));
+        assert!(is_image_name("jammy-server-cloudimg-amd64.img"));
+        assert!(!is_image_name(".debian-12.iso.part"));
+        assert!(!is_image_name("notes.txt"));
+    }
+}
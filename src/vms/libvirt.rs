@@ -0,0 +1,537 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! Minimal libvirt RPC client — the wire protocol `virsh` itself speaks to
+//! libvirtd over its Unix socket (XDR-encoded calls, program 0x20008086).
+//!
+//! Domain state and lifecycle used to go through `virsh domstate` / `virsh
+//! list` / `virsh start` and friends: a fork per call, output parsed as
+//! text, and a window between "list" and "state" in which a domain could
+//! change under us. Here one connection answers list-with-state in a
+//! single round trip, and [`watch`] subscribes to lifecycle events so the
+//! dashboard hears about a VM starting, stopping or crashing as it happens
+//! instead of on the next poll.
+//!
+//! Only the handful of procedures WolfStack needs are implemented; XML
+//! editing, device attach and the rest still use `virsh`. Every function
+//! returns [`LibvirtError::Unavailable`] when no socket answers (libvirt
+//! not installed, or a SASL-only setup) so callers can fall back to it.
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::LazyLock;
+use std::time::Duration;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Monolithic libvirtd first, then the modular per-driver daemon.
+const SOCKETS: [&str; 2] = ["/var/run/libvirt/libvirt-sock", "/var/run/libvirt/virtqemud-sock"];
+const URI: &str = "qemu:///system";
+
+const PROGRAM: u32 = 0x2000_8086;
+const PROTOCOL_VERSION: u32 = 1;
+const TYPE_CALL: u32 = 0;
+const TYPE_REPLY: u32 = 1;
+const TYPE_MESSAGE: u32 = 2;
+const STATUS_OK: u32 = 0;
+
+// Procedure numbers from libvirt's remote_protocol.x.
+const PROC_CONNECT_OPEN: u32 = 1;
+const PROC_CONNECT_CLOSE: u32 = 2;
+const PROC_DOMAIN_CREATE: u32 = 9;
+const PROC_DOMAIN_DESTROY: u32 = 12;
+const PROC_DOMAIN_LOOKUP_BY_NAME: u32 = 23;
+const PROC_DOMAIN_REBOOT: u32 = 27;
+const PROC_DOMAIN_RESUME: u32 = 28;
+const PROC_DOMAIN_SHUTDOWN: u32 = 33;
+const PROC_DOMAIN_SUSPEND: u32 = 34;
+const PROC_AUTH_LIST: u32 = 66;
+const PROC_AUTH_POLKIT: u32 = 70;
+const PROC_DOMAIN_GET_STATE: u32 = 212;
+const PROC_CONNECT_LIST_ALL_DOMAINS: u32 = 273;
+const PROC_DOMAIN_EVENT_CALLBACK_REGISTER_ANY: u32 = 316;
+const PROC_DOMAIN_EVENT_CALLBACK_LIFECYCLE: u32 = 318;
+
+const AUTH_NONE: i32 = 0;
+const AUTH_POLKIT: i32 = 2;
+const EVENT_ID_LIFECYCLE: i32 = 0;
+/// `VIR_ERR_NO_DOMAIN`
+const ERR_NO_DOMAIN: i32 = 42;
+
+/// Calls that answer immediately. Lifecycle calls return once libvirt has
+/// acted (shutdown is ACPI fire-and-forget), so one timeout covers both.
+const CALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum LibvirtError {
+    /// No socket answered, or it wants auth we don't speak — use `virsh`.
+    Unavailable(String),
+    /// libvirt refused the call: (`virErrorNumber`, message).
+    Libvirt(i32, String),
+}
+
+impl std::fmt::Display for LibvirtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibvirtError::Unavailable(m) => write!(f, "libvirt unavailable: {}", m),
+            LibvirtError::Libvirt(_, m) => f.write_str(m),
+        }
+    }
+}
+
+/// A domain's state in the words `virsh domstate` uses, so code that
+/// compared its output keeps working.
+pub fn state_name(state: i32) -> &'static str {
+    match state {
+        1 => "running",
+        2 => "idle",
+        3 => "paused",
+        4 => "in shutdown",
+        5 => "shut off",
+        6 => "crashed",
+        7 => "pmsuspended",
+        _ => "no state",
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainInfo {
+    pub name: String,
+    /// Hypervisor ID while running, -1 when shut off.
+    pub id: i32,
+    pub state: &'static str,
+}
+
+impl DomainInfo {
+    /// Has a live QEMU process — paused counts, as it does for the
+    /// runtime-XML check in `vms::manager`.
+    pub fn running(&self) -> bool {
+        !matches!(self.state, "shut off" | "crashed" | "no state")
+    }
+}
+
+/// Lifecycle verbs, named after the `virsh` commands they replace.
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    Start,
+    Shutdown,
+    Destroy,
+    Reboot,
+    Suspend,
+    Resume,
+}
+
+impl Action {
+    fn procedure(self) -> u32 {
+        match self {
+            Action::Start => PROC_DOMAIN_CREATE,
+            Action::Shutdown => PROC_DOMAIN_SHUTDOWN,
+            Action::Destroy => PROC_DOMAIN_DESTROY,
+            Action::Reboot => PROC_DOMAIN_REBOOT,
+            Action::Suspend => PROC_DOMAIN_SUSPEND,
+            Action::Resume => PROC_DOMAIN_RESUME,
+        }
+    }
+
+    pub fn virsh_verb(self) -> &'static str {
+        match self {
+            Action::Start => "start",
+            Action::Shutdown => "shutdown",
+            Action::Destroy => "destroy",
+            Action::Reboot => "reboot",
+            Action::Suspend => "suspend",
+            Action::Resume => "resume",
+        }
+    }
+}
+
+/// A lifecycle event as pushed to [`subscribe`]rs.
+#[derive(Debug, Clone, Serialize)]
+pub struct VmEvent {
+    pub name: String,
+    /// defined, undefined, started, suspended, resumed, stopped,
+    /// shutdown, pmsuspended or crashed.
+    pub event: &'static str,
+    /// State after the event, `virsh domstate` wording.
+    pub state: &'static str,
+    pub timestamp: String,
+}
+
+fn event_name(event: i32) -> &'static str {
+    match event {
+        0 => "defined",
+        1 => "undefined",
+        2 => "started",
+        3 => "suspended",
+        4 => "resumed",
+        5 => "stopped",
+        6 => "shutdown",
+        7 => "pmsuspended",
+        8 => "crashed",
+        _ => "unknown",
+    }
+}
+
+/// Resulting state for a lifecycle event.
+fn event_state(event: i32) -> &'static str {
+    match event {
+        2 | 4 => "running",
+        3 => "paused",
+        5 | 8 => "shut off",
+        6 => "in shutdown",
+        7 => "pmsuspended",
+        _ => "no state",
+    }
+}
+
+static EVENTS: LazyLock<broadcast::Sender<VmEvent>> = LazyLock::new(|| broadcast::channel(64).0);
+
+/// Receive lifecycle events while [`watch`] is running.
+pub fn subscribe() -> broadcast::Receiver<VmEvent> {
+    EVENTS.subscribe()
+}
+
+// ─── XDR ───
+
+#[derive(Default)]
+struct XdrWriter(Vec<u8>);
+
+impl XdrWriter {
+    fn u32(&mut self, v: u32) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+    fn i32(&mut self, v: i32) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+    fn string(&mut self, s: &str) -> &mut Self {
+        self.u32(s.len() as u32);
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.resize(self.0.len() + (4 - s.len() % 4) % 4, 0);
+        self
+    }
+    fn opt_string(&mut self, s: Option<&str>) -> &mut Self {
+        match s {
+            Some(s) => self.u32(1).string(s),
+            None => self.u32(0),
+        }
+    }
+    fn domain(&mut self, d: &Domain) -> &mut Self {
+        self.string(&d.name);
+        self.0.extend_from_slice(&d.uuid);
+        self.i32(d.id)
+    }
+}
+
+struct XdrReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> XdrReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        XdrReader { buf, pos: 0 }
+    }
+    fn take(&mut self, n: usize) -> Result<&'a [u8], LibvirtError> {
+        let end = self.pos.checked_add(n).filter(|e| *e <= self.buf.len())
+            .ok_or_else(|| LibvirtError::Libvirt(-1, "libvirt: truncated reply".to_string()))?;
+        let s = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(s)
+    }
+    fn u32(&mut self) -> Result<u32, LibvirtError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn i32(&mut self) -> Result<i32, LibvirtError> {
+        Ok(self.u32()? as i32)
+    }
+    fn string(&mut self) -> Result<String, LibvirtError> {
+        let len = self.u32()? as usize;
+        let s = String::from_utf8_lossy(self.take(len)?).to_string();
+        self.take((4 - len % 4) % 4)?;
+        Ok(s)
+    }
+    fn opt_string(&mut self) -> Result<Option<String>, LibvirtError> {
+        if self.u32()? == 0 { Ok(None) } else { self.string().map(Some) }
+    }
+    fn domain(&mut self) -> Result<Domain, LibvirtError> {
+        let name = self.string()?;
+        let mut uuid = [0u8; 16];
+        uuid.copy_from_slice(self.take(16)?);
+        Ok(Domain { name, uuid, id: self.i32()? })
+    }
+}
+
+/// `remote_nonnull_domain`: what every domain call takes.
+#[derive(Debug, Clone)]
+struct Domain {
+    name: String,
+    uuid: [u8; 16],
+    id: i32,
+}
+
+// ─── Connection ───
+
+struct Connection {
+    stream: UnixStream,
+    serial: u32,
+}
+
+impl Connection {
+    /// Connect, authenticate (none or polkit — root passes polkit) and
+    /// open `qemu:///system`.
+    fn open() -> Result<Connection, LibvirtError> {
+        let mut last_err = "no libvirt socket".to_string();
+        let stream = SOCKETS.iter()
+            .find_map(|path| match UnixStream::connect(path) {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    if std::path::Path::new(path).exists() {
+                        last_err = format!("{}: {}", path, e);
+                    }
+                    None
+                }
+            })
+            .ok_or(LibvirtError::Unavailable(last_err))?;
+        let _ = stream.set_read_timeout(Some(CALL_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(CALL_TIMEOUT));
+        let mut conn = Connection { stream, serial: 0 };
+
+        let auth = conn.call(PROC_AUTH_LIST, &[])?;
+        let mut r = XdrReader::new(&auth);
+        let types: Vec<i32> = (0..r.u32()?).map(|_| r.i32()).collect::<Result<_, _>>()?;
+        if types.contains(&AUTH_POLKIT) {
+            conn.call(PROC_AUTH_POLKIT, &[])?;
+        } else if !types.is_empty() && !types.contains(&AUTH_NONE) {
+            return Err(LibvirtError::Unavailable(format!("libvirt wants auth types {:?}", types)));
+        }
+
+        let mut args = XdrWriter::default();
+        args.opt_string(Some(URI)).u32(0);
+        conn.call(PROC_CONNECT_OPEN, &args.0)?;
+        Ok(conn)
+    }
+
+    fn send(&mut self, procedure: u32, args: &[u8]) -> Result<u32, LibvirtError> {
+        self.serial += 1;
+        let mut msg = XdrWriter::default();
+        msg.u32((28 + args.len()) as u32)
+            .u32(PROGRAM).u32(PROTOCOL_VERSION).u32(procedure)
+            .u32(TYPE_CALL).u32(self.serial).u32(STATUS_OK);
+        msg.0.extend_from_slice(args);
+        self.stream.write_all(&msg.0).map_err(io_err)?;
+        Ok(self.serial)
+    }
+
+    /// Read one packet: (procedure, type, serial, status, payload).
+    fn recv(&mut self) -> Result<(u32, u32, u32, u32, Vec<u8>), LibvirtError> {
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len).map_err(io_err)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if !(28..=32 * 1024 * 1024).contains(&len) {
+            return Err(LibvirtError::Libvirt(-1, format!("libvirt: bad packet length {}", len)));
+        }
+        let mut buf = vec![0u8; len - 4];
+        self.stream.read_exact(&mut buf).map_err(io_err)?;
+        let mut r = XdrReader::new(&buf);
+        let (_program, _version) = (r.u32()?, r.u32()?);
+        let (procedure, kind, serial, status) = (r.u32()?, r.u32()?, r.u32()?, r.u32()?);
+        Ok((procedure, kind, serial, status, buf[24..].to_vec()))
+    }
+
+    fn call(&mut self, procedure: u32, args: &[u8]) -> Result<Vec<u8>, LibvirtError> {
+        let serial = self.send(procedure, args)?;
+        loop {
+            let (_, kind, got, status, payload) = self.recv()?;
+            // Event messages can arrive between a call and its reply.
+            if kind != TYPE_REPLY || got != serial {
+                continue;
+            }
+            if status != STATUS_OK {
+                return Err(parse_error(&payload));
+            }
+            return Ok(payload);
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Result<Domain, LibvirtError> {
+        let mut args = XdrWriter::default();
+        args.string(name);
+        XdrReader::new(&self.call(PROC_DOMAIN_LOOKUP_BY_NAME, &args.0)?).domain()
+    }
+
+    fn state(&mut self, dom: &Domain) -> Result<i32, LibvirtError> {
+        let mut args = XdrWriter::default();
+        args.domain(dom).u32(0);
+        XdrReader::new(&self.call(PROC_DOMAIN_GET_STATE, &args.0)?).i32()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.send(PROC_CONNECT_CLOSE, &[]);
+    }
+}
+
+fn io_err(e: std::io::Error) -> LibvirtError {
+    LibvirtError::Libvirt(-1, format!("libvirt connection: {}", e))
+}
+
+/// `remote_error`: code, domain, then an optional message.
+fn parse_error(payload: &[u8]) -> LibvirtError {
+    let mut r = XdrReader::new(payload);
+    let code = r.i32().unwrap_or(-1);
+    let _domain = r.i32();
+    let message = r.opt_string().ok().flatten().unwrap_or_else(|| format!("libvirt error {}", code));
+    LibvirtError::Libvirt(code, message)
+}
+
+// ─── Public calls ───
+
+/// Is there a libvirt socket at all? Doesn't check the daemon answers.
+pub fn available() -> bool {
+    SOCKETS.iter().any(|p| std::path::Path::new(p).exists())
+}
+
+/// Every defined or running domain with its current state, over one
+/// connection.
+pub fn list_domains() -> Result<Vec<DomainInfo>, LibvirtError> {
+    let mut conn = Connection::open()?;
+    let mut args = XdrWriter::default();
+    args.i32(1).u32(0);
+    let reply = conn.call(PROC_CONNECT_LIST_ALL_DOMAINS, &args.0)?;
+    let mut r = XdrReader::new(&reply);
+    let domains: Vec<Domain> = (0..r.u32()?).map(|_| r.domain()).collect::<Result<_, _>>()?;
+    let mut out = Vec::with_capacity(domains.len());
+    for dom in domains {
+        // A domain undefined between the list and this call just drops out.
+        match conn.state(&dom) {
+            Ok(state) => out.push(DomainInfo { name: dom.name, id: dom.id, state: state_name(state) }),
+            Err(LibvirtError::Libvirt(ERR_NO_DOMAIN, _)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(out)
+}
+
+/// State of one domain; `Ok(None)` when libvirt doesn't know it.
+pub fn domain_state(name: &str) -> Result<Option<DomainInfo>, LibvirtError> {
+    let mut conn = Connection::open()?;
+    let dom = match conn.lookup(name) {
+        Ok(d) => d,
+        Err(LibvirtError::Libvirt(ERR_NO_DOMAIN, _)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let state = conn.state(&dom)?;
+    Ok(Some(DomainInfo { name: dom.name, id: dom.id, state: state_name(state) }))
+}
+
+/// Start, stop, reboot, pause or resume a domain.
+pub fn domain_action(name: &str, action: Action) -> Result<(), LibvirtError> {
+    let mut conn = Connection::open()?;
+    let dom = conn.lookup(name)?;
+    let mut args = XdrWriter::default();
+    args.domain(&dom);
+    if matches!(action, Action::Reboot) {
+        args.u32(0);
+    }
+    conn.call(action.procedure(), &args.0)?;
+    Ok(())
+}
+
+/// Follow lifecycle events for the life of the process, publishing each to
+/// [`subscribe`]rs. Blocking — run it on its own thread. Reconnects with
+/// backoff when libvirtd restarts; returns only if libvirt isn't there.
+pub fn watch() {
+    let mut backoff = Duration::from_secs(2);
+    loop {
+        match watch_once() {
+            Err(LibvirtError::Unavailable(e)) if !available() => {
+                info!("libvirt event watch not started: {}", e);
+                return;
+            }
+            Err(e) => warn!("libvirt event watch dropped: {} — reconnecting in {}s", e, backoff.as_secs()),
+            Ok(()) => {}
+        }
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(Duration::from_secs(60));
+    }
+}
+
+fn watch_once() -> Result<(), LibvirtError> {
+    let mut conn = Connection::open()?;
+    let mut args = XdrWriter::default();
+    args.i32(EVENT_ID_LIFECYCLE).u32(0); // all domains
+    conn.call(PROC_DOMAIN_EVENT_CALLBACK_REGISTER_ANY, &args.0)?;
+    // Events arrive whenever they happen; idle for hours is normal.
+    let _ = conn.stream.set_read_timeout(None);
+    info!("Watching libvirt domain lifecycle events");
+    loop {
+        let (procedure, kind, _, _, payload) = conn.recv()?;
+        if kind != TYPE_MESSAGE || procedure != PROC_DOMAIN_EVENT_CALLBACK_LIFECYCLE {
+            continue;
+        }
+        let ev = parse_lifecycle(&payload)?;
+        info!("VM '{}' {}", ev.name, ev.event);
+        // Changes made outside WolfStack (virsh, guest shutdown) too.
+        crate::monitoring::inventory::VMS.invalidate();
+        let _ = EVENTS.send(ev);
+    }
+}
+
+/// `remote_domain_event_callback_lifecycle_msg`: callback ID, then the
+/// domain, event and detail.
+fn parse_lifecycle(payload: &[u8]) -> Result<VmEvent, LibvirtError> {
+    let mut r = XdrReader::new(payload);
+    let _callback_id = r.i32()?;
+    let dom = r.domain()?;
+    let event = r.i32()?;
+    Ok(VmEvent {
+        name: dom.name,
+        event: event_name(event),
+        state: event_state(event),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xdr_strings_are_padded_and_round_trip() {
+        let dom = Domain { name: "web01".into(), uuid: [7; 16], id: 3 };
+        let mut w = XdrWriter::default();
+        w.domain(&dom).opt_string(Some("ab")).opt_string(None);
+        // "web01" is 5 bytes → 3 bytes of padding.
+        assert_eq!(&w.0[..12], &[0, 0, 0, 5, b'w', b'e', b'b', b'0', b'1', 0, 0, 0]);
+        let mut r = XdrReader::new(&w.0);
+        let back = r.domain().unwrap();
+        assert_eq!((back.name.as_str(), back.uuid, back.id), ("web01", [7; 16], 3));
+        assert_eq!(r.opt_string().unwrap().as_deref(), Some("ab"));
+        assert_eq!(r.opt_string().unwrap(), None);
+        assert!(r.u32().is_err());
+    }
+
+    #[test]
+    fn lifecycle_message_and_errors_decode() {
+        let mut w = XdrWriter::default();
+        w.i32(1).domain(&Domain { name: "db".into(), uuid: [0; 16], id: -1 }).i32(5).i32(0);
+        let ev = parse_lifecycle(&w.0).unwrap();
+        assert_eq!((ev.name.as_str(), ev.event, ev.state), ("db", "stopped", "shut off"));
+
+        let mut w = XdrWriter::default();
+        w.i32(ERR_NO_DOMAIN).i32(10).opt_string(Some("Domain not found: no domain with matching name 'x'"));
+        match parse_error(&w.0) {
+            LibvirtError::Libvirt(code, msg) => {
+                assert_eq!(code, ERR_NO_DOMAIN);
+                assert!(msg.starts_with("Domain not found"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
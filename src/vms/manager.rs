@@ -6,10 +6,12 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, LazyLock, Mutex};
 use tracing::{error, warn, info};
 use rand::Rng;
 use crate::containers;
 use crate::networking;
+use super::libvirt;
 use super::passthrough::{
     parse_libvirt_hostdevs, parse_proxmox_passthrough,
     find_conflicts, check_passthrough_steals_host_net,
@@ -286,6 +288,19 @@ pub struct VmConfig {
     #[serde(default)]
     pub extra_qemu_args: String,
 
+    /// vCPU ceiling for hot-plug on native QEMU: the VM boots with
+    /// `-smp cpus=<cpus>,maxcpus=<max_cpus>` so vCPUs can be added while
+    /// it runs. 0 (the default) = no headroom, `cpus` is fixed until the
+    /// next start. Proxmox and libvirt keep their own maximum.
+    #[serde(default)]
+    pub max_cpus: u32,
+    /// Attach a virtio-balloon device on native QEMU so the memory
+    /// target can be lowered (and raised back to `memory_mb`) while the
+    /// VM runs. Off by default — a Windows guest without the VirtIO
+    /// drivers shows it as an unknown device.
+    #[serde(default)]
+    pub balloon: bool,
+
     /// Whether the VM is currently paused/suspended (CPU frozen, RAM held).
     /// Computed at list time from a `<name>.paused` marker we write on
     /// pause_vm and clear on resume/start/stop — NOT read from a per-VM
@@ -297,6 +312,104 @@ pub struct VmConfig {
     /// won't flip it (documented limitation).
     #[serde(default)]
     pub paused: bool,
+
+    /// Guest OS, in Proxmox's `ostype` vocabulary ("l26", "win11", "win10",
+    /// "w2k8", "other", … — see [`PVE_OS_TYPES`]) so it maps straight onto
+    /// `qm create --ostype`. Read back from the PVE conf on Proxmox and from
+    /// the WolfStack sidecar elsewhere. Empty (every config written before
+    /// this field) = not recorded; [`Self::is_windows`] then falls back to
+    /// the hardware heuristic.
+    #[serde(default)]
+    pub os_type: String,
+
+    /// Where an RDP client should connect for this guest — "host" or
+    /// "host:port". Purely a hint for the dashboard's Remote Desktop
+    /// shortcut; WolfStack never dials it or opens a firewall for it.
+    /// Lives in the native config / sidecar JSON on every backend (neither
+    /// PVE nor libvirt has a field for it). Empty = no hint.
+    #[serde(default)]
+    pub rdp_address: String,
+
+    /// Whether the guest is (or looks like) Windows. Computed at list time
+    /// from [`Self::is_windows`] so the dashboard can badge Windows VMs and
+    /// offer RDP; like `paused` it's never authoritative on disk.
+    #[serde(default)]
+    pub windows: bool,
+}
+
+/// Guest OS types accepted for [`VmConfig::os_type`] — Proxmox's `ostype`
+/// list verbatim, so the value is valid on every backend.
+pub const PVE_OS_TYPES: &[&str] = &[
+    "l26", "l24", "win11", "win10", "win8", "win7", "wvista", "wxp",
+    "w2k8", "w2k3", "w2k", "solaris", "other",
+];
+
+/// True for the Windows members of [`PVE_OS_TYPES`].
+pub fn os_type_is_windows(os_type: &str) -> bool {
+    os_type.starts_with("win") || os_type.starts_with("w2k")
+        || matches!(os_type, "wxp" | "wvista")
+}
+
+/// Whether an ISO file name looks like Windows installer or driver media
+/// (Win11_23H2_English_x64.iso, virtio-win-0.1.240.iso, …). Mirrors the
+/// create modal's autoDetectWindowsIso so both sides agree.
+pub fn iso_looks_windows(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    if file.contains("windows") || file.contains("virtio-win") {
+        return true;
+    }
+    // "win10", "win_server", "win-2022" — but not "darwin" or "winamp".
+    file.match_indices("win").any(|(i, _)| {
+        let before_ok = i == 0 || !file.as_bytes()[i - 1].is_ascii_alphabetic();
+        let after = file.as_bytes().get(i + 3).copied();
+        before_ok && after.is_some_and(|c| c.is_ascii_digit() || c == b'_' || c == b'-')
+    })
+}
+
+/// Validate and normalise an RDP connection hint: "host" or "host:port",
+/// with IPv6 literals bracketed ("[fd00::5]:3390"). Empty is allowed
+/// (= no hint). The value ends up in a generated `.rdp` file and a link on
+/// the dashboard, so anything beyond a plain host and port is rejected.
+pub fn normalize_rdp_address(input: &str) -> Result<String, String> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Ok(String::new());
+    }
+    let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+        let (h, tail) = rest.split_once(']')
+            .ok_or_else(|| "RDP address: unterminated '[' in IPv6 literal".to_string())?;
+        if h.parse::<std::net::Ipv6Addr>().is_err() {
+            return Err(format!("RDP address: '{}' is not an IPv6 address", h));
+        }
+        match tail {
+            "" => (format!("[{}]", h), None),
+            t => match t.strip_prefix(':') {
+                Some(p) => (format!("[{}]", h), Some(p)),
+                None => return Err("RDP address: expected ':port' after ']'".to_string()),
+            },
+        }
+    } else if s.matches(':').count() > 1 {
+        return Err("RDP address: put IPv6 addresses in brackets, e.g. [fd00::5]:3389".to_string());
+    } else {
+        match s.split_once(':') {
+            Some((h, p)) => (h.to_string(), Some(p)),
+            None => (s.to_string(), None),
+        }
+    };
+    if !host.starts_with('[') {
+        let ok = !host.is_empty() && host.len() <= 253
+            && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        if !ok {
+            return Err(format!("RDP address: '{}' is not a valid host name or IP", host));
+        }
+    }
+    match port {
+        None => Ok(host),
+        Some(p) => match p.parse::<u16>() {
+            Ok(n) if n > 0 => Ok(format!("{}:{}", host, n)),
+            _ => Err(format!("RDP address: '{}' is not a valid port", p)),
+        },
+    }
 }
 
 fn default_net_model() -> String { "virtio".to_string() }
@@ -563,6 +676,11 @@ impl VmConfig {
     pub fn new(name: String, cpus: u32, memory_mb: u32, disk_size_gb: u32) -> Self {
         VmConfig {
             paused: false,
+            max_cpus: 0,
+            balloon: false,
+            os_type: String::new(),
+            rdp_address: String::new(),
+            windows: false,
             name,
             cpus,
             memory_mb,
@@ -611,10 +729,44 @@ impl VmConfig {
             _ => if self.wolfnet_ip.is_some() { "wolfnet" } else { "nat" },
         }
     }
+
+    /// Whether this guest is Windows. An explicit `os_type` wins; configs
+    /// without one fall back to what the create flow does for Windows —
+    /// Windows/virtio-win media attached, or an emulated NIC / IDE-SATA OS
+    /// disk (the installer has no VirtIO drivers).
+    pub fn is_windows(&self) -> bool {
+        if !self.os_type.is_empty() {
+            return os_type_is_windows(&self.os_type);
+        }
+        self.iso_path.as_deref().is_some_and(iso_looks_windows)
+            || self.drivers_iso.as_deref().is_some_and(iso_looks_windows)
+            || matches!(self.net_model.as_str(), "e1000" | "e1000e" | "rtl8139")
+            || matches!(self.os_disk_bus.as_str(), "ide" | "sata")
+    }
+
+    /// The PVE `--ostype` for this guest: the recorded `os_type`, else
+    /// "win11" when [`Self::is_windows`] fires (backward-compatible with
+    /// every Win10 paravirt behaviour), else "l26".
+    pub fn pve_ostype(&self) -> &str {
+        if PVE_OS_TYPES.contains(&self.os_type.as_str()) {
+            &self.os_type
+        } else if self.is_windows() {
+            "win11"
+        } else {
+            "l26"
+        }
+    }
+}
+
+/// Set the computed `windows` flag on a freshly listed batch of VMs.
+fn annotate_windows(vms: &mut [VmConfig]) {
+    for vm in vms.iter_mut() {
+        vm.windows = vm.is_windows();
+    }
 }
 
 /// Detect disk image format from file extension
-fn detect_image_format(path: &str) -> &str {
+pub(crate) fn detect_image_format(path: &str) -> &str {
     let lower = path.to_lowercase();
     if lower.ends_with(".qcow2") { "qcow2" }
     else if lower.ends_with(".vmdk") { "vmdk" }
@@ -828,6 +980,13 @@ fn clone_vm_native_impl(src: &VmConfig, base_dir: &Path, new_name: &str) -> Resu
     Ok(())
 }
 
+/// One lock per VM name, so a disk conversion or hypervisor command on one
+/// VM serialises against other changes to that VM without holding the
+/// `VmManager` mutex every VM page also needs. See [`VmManager::vm_lock`].
+static VM_LOCKS: LazyLock<Mutex<std::collections::HashMap<String, Arc<Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(std::collections::HashMap::new()));
+
+#[derive(Clone)]
 pub struct VmManager {
     pub base_dir: PathBuf,
 }
@@ -841,6 +1000,14 @@ impl VmManager {
         VmManager { base_dir }
     }
 
+    /// The lock for changes to VM `name`. API handlers hold it (and not the
+    /// manager mutex) across create, update, delete, start/stop, clone and
+    /// volume work; holding it for a new name also reserves that name.
+    pub fn vm_lock(name: &str) -> Arc<Mutex<()>> {
+        let mut locks = VM_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(name.to_string()).or_default().clone()
+    }
+
     pub fn list_vms(&self) -> Vec<VmConfig> {
         self.list_vms_impl(/*heal=*/true)
     }
@@ -857,12 +1024,14 @@ impl VmManager {
         if containers::is_proxmox() {
             let mut vms = self.qm_list_all();
             self.annotate_paused_impl(&mut vms, heal);
+            annotate_windows(&mut vms);
             return vms;
         }
         // On libvirt, discover VMs via virsh
         if containers::is_libvirt() {
             let mut vms = self.virsh_list_all();
             self.annotate_paused_impl(&mut vms, heal);
+            annotate_windows(&mut vms);
             return vms;
         }
 
@@ -908,6 +1077,7 @@ impl VmManager {
             }
         }
         self.annotate_paused_impl(&mut vms, heal);
+        annotate_windows(&mut vms);
         vms
     }
 
@@ -936,10 +1106,23 @@ impl VmManager {
     /// list zero VMs on a real Proxmox host.
     fn qm_list_all(&self) -> Vec<VmConfig> {
         let fast = qm_list_via_filesystem();
-        if !fast.is_empty() || pve_qemu_server_dir_readable() {
-            return fast;
+        let mut vms = if !fast.is_empty() || pve_qemu_server_dir_readable() {
+            fast
+        } else {
+            self.qm_list_via_subprocess()
+        };
+        // PVE has no field for the RDP hint, so it lives in the WolfStack
+        // sidecar (written by create / set_guest_hints). A plain file read
+        // per VM, and only for VMs that have one.
+        for vm in vms.iter_mut() {
+            if let Ok(text) = fs::read_to_string(self.vm_config_path(&vm.name))
+                && let Ok(sidecar) = serde_json::from_str::<VmConfig>(&text)
+            {
+                if vm.os_type.is_empty() { vm.os_type = sidecar.os_type; }
+                vm.rdp_address = sidecar.rdp_address;
+            }
         }
-        self.qm_list_via_subprocess()
+        vms
     }
 
     /// Original subprocess-driven path. Retained as a fallback for the
@@ -1076,6 +1259,11 @@ impl VmManager {
 
                 Some(VmConfig {
                     paused: false,
+                    max_cpus: 0,
+                    balloon: false,
+                    os_type: pve_conf_value(&qm_config_text, "ostype").unwrap_or_default(),
+                    rdp_address: String::new(),
+                    windows: false,
                     name,
                     cpus,
                     memory_mb,
@@ -1156,6 +1344,62 @@ impl VmManager {
     fn vm_config_path(&self, name: &str) -> PathBuf {
         self.base_dir.join(format!("{}.json", name))
     }
+
+    /// Write the guest OS type and/or RDP hint into the VM's JSON — the
+    /// full config for native VMs, the WolfStack sidecar on Proxmox and
+    /// libvirt (loaded or started minimal, same as the libvirt network
+    /// fields). `None` leaves a field alone; values must already be
+    /// validated. Skips creating a sidecar just to store nothing.
+    fn save_guest_hints(&self, name: &str, os_type: Option<&str>, rdp_address: Option<&str>) -> Result<(), String> {
+        let path = self.vm_config_path(name);
+        let existing = fs::read_to_string(&path).ok()
+            .and_then(|t| serde_json::from_str::<VmConfig>(&t).ok());
+        let empty = os_type.is_none_or(str::is_empty) && rdp_address.is_none_or(str::is_empty);
+        if existing.is_none() && empty {
+            return Ok(());
+        }
+        let mut cfg = existing.unwrap_or_else(|| VmConfig::new(name.to_string(), 1, 512, 1));
+        if let Some(os) = os_type { cfg.os_type = os.to_string(); }
+        if let Some(rdp) = rdp_address { cfg.rdp_address = rdp.to_string(); }
+        let json = serde_json::to_string_pretty(&cfg).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| format!("Failed to save VM config: {}", e))
+    }
+
+    /// Change a VM's guest OS type and/or RDP hint (Windows guest settings
+    /// on the VM editor). Takes effect immediately — neither touches the
+    /// running guest. On Proxmox the OS type also goes to `qm set --ostype`
+    /// so PVE's own tuning follows it; libvirt keeps its create-time
+    /// os-variant.
+    pub fn set_guest_hints(&self, name: &str, os_type: Option<String>, rdp_address: Option<String>) -> Result<(), String> {
+        if let Some(ref os) = os_type
+            && !os.is_empty() && !PVE_OS_TYPES.contains(&os.as_str())
+        {
+            return Err(format!("Unknown OS type '{}'", os));
+        }
+        let rdp_address = rdp_address.as_deref().map(normalize_rdp_address).transpose()?;
+        if containers::is_proxmox() {
+            let vmid = self.qm_vmid_by_name(name)
+                .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
+            if let Some(ref os) = os_type {
+                // An empty type drops the line so PVE falls back to its default.
+                let vmid_str = vmid.to_string();
+                let qm_args: Vec<&str> = if os.is_empty() {
+                    vec!["set", &vmid_str, "--delete", "ostype"]
+                } else {
+                    vec!["set", &vmid_str, "--ostype", os]
+                };
+                Self::vm_cli("qm", &qm_args)?;
+            }
+            // PVE's conf is authoritative for the type; only the hint is ours.
+            return self.save_guest_hints(name, None, rdp_address.as_deref());
+        }
+        let known = self.vm_config_path(name).exists()
+            || (containers::is_libvirt() && self.virsh_has_domain(name));
+        if !known {
+            return Err(format!("VM '{}' not found", name));
+        }
+        self.save_guest_hints(name, os_type.as_deref(), rdp_address.as_deref())
+    }
     
     fn vm_disk_path(&self, name: &str) -> PathBuf {
         self.base_dir.join(format!("{}.qcow2", name))
@@ -1273,22 +1517,17 @@ impl VmManager {
     /// `full` is honoured only on Proxmox (libvirt is always full,
     /// native is always full). Caller is responsible for any
     /// post-clone follow-up (start, console, etc.).
-    /// Fast, lock-friendly snapshot of everything `execute_clone`
-    /// needs to perform the clone WITHOUT holding the `VmManager`
-    /// mutex for the (potentially multi-minute) disk I/O. The API
-    /// handler calls this under the lock, drops the lock, and then
-    /// calls `execute_clone` on the returned plan.
+    /// Fast snapshot of everything `execute_clone` needs to perform the
+    /// clone WITHOUT holding the `VmManager` mutex for the (potentially
+    /// multi-minute) disk I/O. `all` is the caller's VM list — the API
+    /// handler passes the inventory snapshot rather than re-listing.
     ///
-    /// TOCTOU note: between the lock drop and `execute_clone`'s
-    /// final JSON write, a concurrent `create_vm` for the same name
-    /// could slip in and produce a duplicate. The window is
-    /// milliseconds for the platform paths (`qm clone`/`virt-clone`
-    /// themselves serialise on the hypervisor) and bounded by the
-    /// dest-disk `fs::copy` for native clones. We accept the risk
-    /// rather than hold the lock for the whole clone — name
-    /// collisions in the native path additionally fail at
+    /// The API handler holds [`VmManager::vm_lock`] for both names from
+    /// here until `execute_clone` returns, so a concurrent create of
+    /// `new_name` through the API waits and then finds it taken. Name
+    /// collisions from outside the API still fail in the native path at
     /// `dest_disk.exists()` and at `fs::write(new_config_path)`.
-    pub fn prepare_clone(&self, name: &str, new_name: &str)
+    pub fn prepare_clone(&self, name: &str, new_name: &str, all: &[VmConfig])
         -> Result<ClonePlan, String>
     {
         validate_clone_vm_name(name)?;
@@ -1296,7 +1535,6 @@ impl VmManager {
         if name == new_name {
             return Err("source and destination names are identical — pick a different new name".into());
         }
-        let all = self.list_vms();
         let src = all.iter().find(|v| v.name == name).cloned()
             .ok_or_else(|| format!("VM '{}' not found on this host", name))?;
         if all.iter().any(|v| v.name == new_name) {
@@ -1332,6 +1570,7 @@ impl VmManager {
                 if parts.len() != 4 || parts.iter().any(|p| p.parse::<u8>().is_err()) {
                     return Err(format!("Invalid WolfNet IP: '{}' — must be a valid IPv4 address", ip));
                 }
+                containers::ipam::claim(ip)?;
                 config.wolfnet_ip = Some(ip.to_string());
             } else {
                 config.wolfnet_ip = None;
@@ -1340,11 +1579,13 @@ impl VmManager {
 
         // On Proxmox, delegate to qm create
         if containers::is_proxmox() {
-            return self.qm_create(&config);
+            self.qm_create(&config)?;
+            return self.save_guest_hints(&config.name, None, Some(&config.rdp_address));
         }
         // On libvirt, delegate to virt-install
         if containers::is_libvirt() {
-            return self.virsh_create(&config);
+            self.virsh_create(&config)?;
+            return self.save_guest_hints(&config.name, Some(&config.os_type), Some(&config.rdp_address));
         }
 
         // Standalone: use QEMU directly
@@ -1471,7 +1712,7 @@ impl VmManager {
             "--scsi0".to_string(), format!("{}:{}", storage, config.disk_size_gb),
             "--scsihw".to_string(), "virtio-scsi-single".to_string(),
             "--net0".to_string(), format!("{},bridge={}", net0_model, net0_bridge),
-            "--ostype".to_string(), "l26".to_string(), // Linux 2.6+ kernel
+            "--ostype".to_string(), config.pve_ostype().to_string(),
             "--serial0".to_string(), "socket".to_string(), // Serial console for qm terminal
         ];
 
@@ -1739,6 +1980,40 @@ impl VmManager {
         Ok(())
     }
 
+    /// Register `<storage_path>/<name>.qcow2` as an extra disk of a stopped
+    /// VM, creating the image at `size_gb` if it isn't there yet. Used for
+    /// WolfDisk volumes, whose image moves between VMs and nodes with the
+    /// volume. Returns the image path.
+    pub fn attach_volume_file(&self, vm_name: &str, name: &str, size_gb: u32,
+                              storage_path: &str, bus: Option<&str>) -> Result<PathBuf, String> {
+        if self.check_running(vm_name) {
+            return Err("Cannot add volume while VM is running. Stop it first.".to_string());
+        }
+        let config_path = self.vm_config_path(vm_name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("VM not found: {}", e))?;
+        let mut config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        if config.extra_disks.iter().any(|d| d.name == name) {
+            return Err(format!("Volume '{}' already exists on VM '{}'", name, vm_name));
+        }
+        let vol = StorageVolume {
+            name: name.to_string(),
+            size_gb,
+            storage_path: storage_path.to_string(),
+            format: "qcow2".to_string(),
+            bus: bus.unwrap_or("virtio").to_string(),
+        };
+        let path = vol.file_path();
+        if !path.exists() {
+            self.create_volume_file(&vol)?;
+        }
+        config.extra_disks.push(vol);
+        let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(&config_path, json).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
     /// Remove a storage volume from a VM (must be stopped)
     pub fn remove_volume(&self, vm_name: &str, vol_name: &str, delete_file: bool) -> Result<(), String> {
         if self.check_running(vm_name) {
@@ -1813,6 +2088,90 @@ impl VmManager {
         Ok(())
     }
 
+    /// Directory an imported disk for `vm_name` should be converted into,
+    /// so the finished file can be renamed into place without a second
+    /// copy. The OS disk lives next to the VM's existing one; extra disks
+    /// go to `storage_path` or the VM base dir. The VM must be a stopped
+    /// WolfStack-managed VM.
+    pub fn import_disk_dir(&self, vm_name: &str, as_os_disk: bool, storage_path: Option<&str>) -> Result<PathBuf, String> {
+        let config_path = self.vm_config_path(vm_name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|_| format!("VM '{}' not found (disk import needs a WolfStack-managed VM)", vm_name))?;
+        let config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        if self.check_running(vm_name) {
+            return Err("Cannot import a disk while the VM is running. Stop it first.".to_string());
+        }
+        let dir = if as_os_disk {
+            self.vm_os_disk_path(&config).parent().map(Path::to_path_buf).unwrap_or_else(|| self.base_dir.clone())
+        } else {
+            storage_path.filter(|s| !s.trim().is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.base_dir.clone())
+        };
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create storage dir {}: {}", dir.display(), e))?;
+        Ok(dir)
+    }
+
+    /// Move a converted qcow2 into place and record it in the VM config —
+    /// either replacing the OS disk or as a new extra disk named
+    /// `<vm>-<vol_name>` (suffixed when the name is taken). `converted`
+    /// must already sit in the directory returned by `import_disk_dir`.
+    /// Returns a description of what was registered.
+    pub fn attach_imported_disk(&self, vm_name: &str, converted: &Path, as_os_disk: bool, vol_name: &str, bus: &str) -> Result<String, String> {
+        if self.check_running(vm_name) {
+            return Err("Cannot import a disk while the VM is running. Stop it first.".to_string());
+        }
+        let config_path = self.vm_config_path(vm_name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("VM not found: {}", e))?;
+        let mut config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        let size_gb = qcow2_virtual_size_gb(converted).unwrap_or(0);
+
+        if as_os_disk {
+            let disk_path = self.vm_os_disk_path(&config);
+            fs::rename(converted, &disk_path)
+                .map_err(|e| format!("Failed to move imported disk to {}: {}", disk_path.display(), e))?;
+            if size_gb > 0 {
+                config.disk_size_gb = size_gb;
+            }
+            if !bus.is_empty() {
+                config.os_disk_bus = bus.to_string();
+            }
+            let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+            fs::write(&config_path, json).map_err(|e| e.to_string())?;
+            return Ok(format!("Imported as the OS disk of '{}' ({})", vm_name, disk_path.display()));
+        }
+
+        let storage_path = converted.parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .ok_or("Converted disk has no parent directory")?;
+        let base = format!("{}-{}", vm_name, vol_name);
+        let mut name = base.clone();
+        let mut n = 2;
+        while config.extra_disks.iter().any(|d| d.name == name)
+            || Path::new(&storage_path).join(format!("{}.qcow2", name)).exists()
+        {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        let vol = StorageVolume {
+            name,
+            size_gb,
+            storage_path,
+            format: "qcow2".to_string(),
+            bus: if bus.is_empty() { "virtio".to_string() } else { bus.to_string() },
+        };
+        let dest = vol.file_path();
+        fs::rename(converted, &dest)
+            .map_err(|e| format!("Failed to move imported disk to {}: {}", dest.display(), e))?;
+        config.extra_disks.push(vol);
+        let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(&config_path, json).map_err(|e| e.to_string())?;
+        Ok(format!("Attached {} to '{}'", dest.display(), vm_name))
+    }
+
     /// List available storage locations (Proxmox-aware)
     pub fn list_storage_locations(&self) -> Vec<StorageLocation> {
         // On Proxmox, use pvesm for storage IDs
@@ -2930,9 +3289,8 @@ impl VmManager {
                 }
             }
 
-            let output = Command::new("virsh").args(["start", name]).output()
-                .map_err(|e| format!("Failed to run virsh start: {}", e))?;
-            if output.status.success() {
+            let started = Self::libvirt_action(name, libvirt::Action::Start);
+            if started.is_ok() {
                 // External VNC (libvirt): the domain listens on 0.0.0.0 with a
                 // password (set at create); open the firewall for the now-
                 // assigned (autoport) VNC port so external clients can reach it.
@@ -2946,8 +3304,7 @@ impl VmManager {
                 }
                 return Ok(());
             }
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("virsh start failed: {}", stderr.trim()));
+            return started;
         }
 
         if self.check_running(name) {
@@ -3104,7 +3461,7 @@ impl VmManager {
 
         cmd.arg("-name").arg(name)
            .arg("-m").arg(format!("{}M", config.memory_mb))
-           .arg("-smp").arg(format!("{}", config.cpus))
+           .arg("-smp").arg(super::hotplug::smp_arg(config.cpus, config.max_cpus))
            .arg("-drive").arg(format!("file={},format=qcow2,if={},index=0", actual_disk.display(), os_disk_if))
            .arg("-vnc").arg(&vnc_arg)
            .arg("-device").arg("qemu-xhci,id=xhci")
@@ -3114,6 +3471,9 @@ impl VmManager {
            .arg("-serial").arg("chardev:serial0")
            .arg("-qmp").arg(format!("unix:{},server,nowait", qmp_path))
            .arg("-daemonize");
+        if config.balloon {
+            cmd.arg("-device").arg(super::hotplug::BALLOON_DEVICE);
+        }
 
         // ARM64 requires the 'virt' machine type and UEFI firmware (no legacy BIOS)
         if is_arm64 {
@@ -3665,7 +4025,7 @@ impl VmManager {
 
         argv.push("-name".into()); argv.push(name.to_string());
         argv.push("-m".into()); argv.push(format!("{}M", config.memory_mb));
-        argv.push("-smp".into()); argv.push(format!("{}", config.cpus));
+        argv.push("-smp".into()); argv.push(super::hotplug::smp_arg(config.cpus, config.max_cpus));
         argv.push("-drive".into());
         argv.push(format!("file={},format=qcow2,if={},index=0", actual_disk.display(), os_disk_if));
         argv.push("-vnc".into()); argv.push(vnc_arg);
@@ -3677,6 +4037,9 @@ impl VmManager {
         argv.push("-serial".into()); argv.push("chardev:serial0".into());
         argv.push("-qmp".into()); argv.push(format!("unix:{},server,nowait", qmp_path));
         argv.push("-daemonize".into());
+        if config.balloon {
+            argv.push("-device".into()); argv.push(super::hotplug::BALLOON_DEVICE.into());
+        }
 
         if is_arm64 {
             argv.push("-M".into()); argv.push("virt".into());
@@ -3839,6 +4202,11 @@ impl VmManager {
         if !containers::host_recently_booted() {
             return;
         }
+        // With a startup order configured, VMs start later, in sequence with
+        // the LXC containers and after the storage mounts they depend on.
+        if !containers::startup_order::StartupOrder::load().is_empty() {
+            return;
+        }
 
         for vm in self.list_vms() {
             if vm.auto_start && !vm.running {
@@ -4763,6 +5131,7 @@ impl VmManager {
     /// plug). Graceful is the default for user-initiated stop actions;
     /// internal callers that need a fast, definite stop pass true.
     pub fn stop_vm(&self, name: &str, force: bool) -> Result<(), String> {
+        crate::availability::note_requested("vm", name);
         // Stopping clears any paused state (the marker is meaningless once the
         // VM is down, and start would clear it anyway — do it here too so a
         // graceful stop that lands before the next list looks right).
@@ -4805,20 +5174,19 @@ impl VmManager {
             let close_fw = || {
                 if external { if let Some(p) = vnc_port { vnc_firewall_close(p, name); } }
             };
-            let (action, label) = if force { ("destroy", "virsh destroy") } else { ("shutdown", "virsh shutdown") };
-            let output = Command::new("virsh").args([action, name]).output()
-                .map_err(|e| format!("Failed to run {}: {}", label, e))?;
-            if output.status.success() {
-                close_fw();
-                return Ok(());
-            }
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // "domain is not running" is not an error — VM is already stopped
-            if stderr.contains("not running") || stderr.contains("not found") {
-                close_fw();
-                return Ok(());
+            let action = if force { libvirt::Action::Destroy } else { libvirt::Action::Shutdown };
+            match Self::libvirt_action(name, action) {
+                Ok(()) => {
+                    close_fw();
+                    return Ok(());
+                }
+                // "domain is not running" is not an error — VM is already stopped
+                Err(e) if e.contains("not running") || e.contains("not found") => {
+                    close_fw();
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
             }
-            return Err(format!("{} failed: {}", label, stderr.trim()));
         }
 
         // Read config to get WolfNet IP for cleanup
@@ -4907,6 +5275,16 @@ impl VmManager {
         Err(format!("{} {} failed: {}", bin, args.join(" "), stderr.trim()))
     }
 
+    /// Lifecycle verb for a libvirt domain, over the libvirt socket, or
+    /// `virsh <verb> <name>` when that isn't reachable.
+    fn libvirt_action(name: &str, action: libvirt::Action) -> Result<(), String> {
+        match libvirt::domain_action(name, action) {
+            Ok(()) => Ok(()),
+            Err(libvirt::LibvirtError::Unavailable(_)) => Self::vm_cli("virsh", &[action.virsh_verb(), name]),
+            Err(e) => Err(format!("virsh {} {} failed: {}", action.virsh_verb(), name, e)),
+        }
+    }
+
     /// Send one QMP command to a native (raw-QEMU) VM's monitor socket and
     /// wait for its reply. QEMU is launched with
     /// `-qmp unix:/run/wolfstack-qmp-<name>.sock,server,nowait`, so this is
@@ -4916,6 +5294,12 @@ impl VmManager {
     /// existing qm/virsh calls). Returns Ok on a QMP `return`, Err on a QMP
     /// `error` or any I/O failure.
     fn qmp_execute(&self, name: &str, command: &str) -> Result<(), String> {
+        self.qmp_call(name, command, None).map(|_| ())
+    }
+
+    /// `qmp_execute` with optional `arguments`, returning the `return`
+    /// payload — hot-plug needs both (`device_add`, `query-block`, …).
+    pub(crate) fn qmp_call(&self, name: &str, command: &str, arguments: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
         use std::os::unix::net::UnixStream;
         use std::io::{BufRead, BufReader, Write};
         use std::time::Duration;
@@ -4936,7 +5320,7 @@ impl VmManager {
 
         // Read QMP replies until a `return`/`error` object — skipping the
         // `{"QMP":…}` greeting and any async `{"event":…}` lines.
-        let await_reply = |reader: &mut BufReader<UnixStream>| -> Result<(), String> {
+        let await_reply = |reader: &mut BufReader<UnixStream>| -> Result<serde_json::Value, String> {
             let mut line = String::new();
             loop {
                 line.clear();
@@ -4952,8 +5336,8 @@ impl VmManager {
                     let desc = err.get("desc").and_then(|d| d.as_str()).unwrap_or("unknown QMP error");
                     return Err(format!("QMP error: {}", desc));
                 }
-                if v.get("return").is_some() {
-                    return Ok(());
+                if let Some(ret) = v.get("return") {
+                    return Ok(ret.clone());
                 }
                 // greeting / event — keep reading.
             }
@@ -4964,7 +5348,11 @@ impl VmManager {
         writer.write_all(b"{\"execute\":\"qmp_capabilities\"}\n")
             .map_err(|e| format!("QMP write: {}", e))?;
         await_reply(&mut reader)?;
-        writer.write_all(format!("{{\"execute\":\"{}\"}}\n", command).as_bytes())
+        let mut request = serde_json::json!({ "execute": command });
+        if let Some(args) = arguments {
+            request["arguments"] = args;
+        }
+        writer.write_all(format!("{}\n", request).as_bytes())
             .map_err(|e| format!("QMP write: {}", e))?;
         await_reply(&mut reader)
     }
@@ -4974,12 +5362,13 @@ impl VmManager {
     /// `system_reset` — an in-place hard reset that keeps the process and
     /// networking up (honest difference, documented for the operator).
     pub fn restart_vm(&self, name: &str) -> Result<(), String> {
+        crate::availability::note_requested("vm", name);
         let result = if containers::is_proxmox() {
             let vmid = self.qm_vmid_by_name(name)
                 .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
             Self::vm_cli("qm", &["reboot", &vmid.to_string()])
         } else if containers::is_libvirt() && self.virsh_has_domain(name) {
-            Self::vm_cli("virsh", &["reboot", name])
+            Self::libvirt_action(name, libvirt::Action::Reboot)
         } else if !self.check_running(name) {
             Err("VM is not running".to_string())
         } else {
@@ -4997,6 +5386,7 @@ impl VmManager {
     /// `qm suspend`, libvirt `virsh suspend`, native QMP `stop`. Writes the
     /// paused marker so the UI shows a Resume control.
     pub fn pause_vm(&self, name: &str) -> Result<(), String> {
+        crate::availability::note_requested("vm", name);
         let result = if containers::is_proxmox() {
             let vmid = self.qm_vmid_by_name(name)
                 .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
@@ -5006,7 +5396,7 @@ impl VmManager {
             // Without it we'd risk a hibernate-to-disk that exits the process.
             Self::vm_cli("qm", &["suspend", &vmid.to_string(), "--todisk", "0"])
         } else if containers::is_libvirt() && self.virsh_has_domain(name) {
-            Self::vm_cli("virsh", &["suspend", name])
+            Self::libvirt_action(name, libvirt::Action::Suspend)
         } else if !self.check_running(name) {
             Err("VM is not running".to_string())
         } else {
@@ -5028,7 +5418,7 @@ impl VmManager {
                 .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
             Self::vm_cli("qm", &["resume", &vmid.to_string()])
         } else if containers::is_libvirt() && self.virsh_has_domain(name) {
-            Self::vm_cli("virsh", &["resume", name])
+            Self::libvirt_action(name, libvirt::Action::Resume)
         } else if !self.check_running(name) {
             // Stale marker — the native VM died while paused. Surface a clear
             // error; the marker is cleared below so the UI stops offering Resume.
@@ -5044,6 +5434,213 @@ impl VmManager {
         result
     }
 
+    // ─── Live resource changes ───
+
+    /// Change a running VM's vCPU count. Proxmox `qm set --vcpus` (within
+    /// its configured cores), libvirt `virsh setvcpus --live --config`,
+    /// native QMP `device_add`/`device_del` into the `max_cpus` headroom
+    /// reserved at boot. Returns a note for the operator.
+    pub fn hotplug_cpus(&self, name: &str, cpus: u32) -> Result<String, String> {
+        if cpus == 0 {
+            return Err("A VM needs at least one vCPU".to_string());
+        }
+        if containers::is_proxmox() {
+            let vmid = self.qm_vmid_by_name(name)
+                .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
+            Self::vm_cli("qm", &["set", &vmid.to_string(), "--vcpus", &cpus.to_string()])?;
+            return Ok(format!("{} vCPU(s) online (needs cpu hotplug enabled on the VM to apply live)", cpus));
+        }
+        if containers::is_libvirt() && self.virsh_has_domain(name) {
+            Self::vm_cli("virsh", &["setvcpus", name, &cpus.to_string(), "--live", "--config"])?;
+            return Ok(format!("{} vCPU(s) online", cpus));
+        }
+
+        let config_path = self.vm_config_path(name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("VM not found: {}", e))?;
+        let mut config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        if !self.check_running(name) {
+            return Err("VM is not running — change vCPUs in the VM settings instead".to_string());
+        }
+        if config.max_cpus <= config.cpus && cpus > config.cpus {
+            return Err("This VM was started without vCPU headroom — set a vCPU ceiling and restart it once to enable hot-plug".to_string());
+        }
+
+        let slots = self.qmp_call(name, "query-hotpluggable-cpus", None)?;
+        let plan = super::hotplug::plan_cpu_hotplug(&slots, cpus)?;
+        for args in plan.add {
+            self.qmp_call(name, "device_add", Some(args))?;
+        }
+        for id in &plan.remove {
+            self.qmp_call(name, "device_del", Some(serde_json::json!({ "id": id })))?;
+        }
+
+        // Boot with the new count next time so the change sticks.
+        config.cpus = cpus;
+        let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(&config_path, json).map_err(|e| e.to_string())?;
+        Ok(if plan.remove.is_empty() {
+            format!("{} vCPU(s) online", cpus)
+        } else {
+            format!("{} vCPU(s) requested — the guest releases removed CPUs once it has taken them offline", cpus)
+        })
+    }
+
+    /// Set a running VM's memory. Proxmox `qm set --memory` (live when
+    /// memory hot-plug is enabled, otherwise pending until restart),
+    /// libvirt `virsh setmem --live --config` (balloon, capped at the
+    /// domain's maximum), native QMP `balloon` between 256 MB and
+    /// `memory_mb`.
+    pub fn hotplug_memory(&self, name: &str, memory_mb: u32) -> Result<String, String> {
+        if memory_mb < 256 {
+            return Err("Memory must be at least 256 MB".to_string());
+        }
+        if containers::is_proxmox() {
+            let vmid = self.qm_vmid_by_name(name)
+                .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
+            Self::vm_cli("qm", &["set", &vmid.to_string(), "--memory", &memory_mb.to_string()])?;
+            return Ok(format!("Memory set to {} MB (live only with memory hotplug enabled, otherwise at next start)", memory_mb));
+        }
+        if containers::is_libvirt() && self.virsh_has_domain(name) {
+            Self::vm_cli("virsh", &["setmem", name, &format!("{}M", memory_mb), "--live", "--config"])?;
+            return Ok(format!("Balloon target set to {} MB", memory_mb));
+        }
+
+        let config = self.get_vm(name).ok_or_else(|| format!("VM '{}' not found", name))?;
+        if !self.check_running(name) {
+            return Err("VM is not running — change memory in the VM settings instead".to_string());
+        }
+        if !config.balloon {
+            return Err("This VM has no memory balloon — enable it and restart the VM once to resize memory live".to_string());
+        }
+        if memory_mb > config.memory_mb {
+            return Err(format!(
+                "The balloon can't go above the VM's boot memory ({} MB) — raise memory in the settings and restart to go higher",
+                config.memory_mb
+            ));
+        }
+        let bytes = memory_mb as u64 * 1024 * 1024;
+        self.qmp_call(name, "balloon", Some(serde_json::json!({ "value": bytes })))?;
+        Ok(format!("Balloon target set to {} MB", memory_mb))
+    }
+
+    /// Grow a VM disk to `size_gb`. `disk` is "os" for the OS disk, a
+    /// volume name for native extra disks, a PVE slot (scsi1, …) or a
+    /// libvirt target (vdb, …). Running VMs are resized online — QMP
+    /// `block_resize`, `virsh blockresize`, `qm resize` — which tells the
+    /// guest about the new capacity; stopped native VMs use
+    /// `qemu-img resize`. Shrinking is refused.
+    pub fn grow_disk(&self, name: &str, disk: &str, size_gb: u32) -> Result<String, String> {
+        if containers::is_proxmox() {
+            let vmid = self.qm_vmid_by_name(name)
+                .ok_or_else(|| format!("VM '{}' not found in Proxmox", name))?;
+            let slot = if disk == "os" {
+                let out = Command::new("qm").args(["config", &vmid.to_string()]).output()
+                    .map_err(|e| format!("Failed to run qm config: {}", e))?;
+                pve_os_disk_slot(&String::from_utf8_lossy(&out.stdout))
+                    .ok_or("Could not find the VM's OS disk in its Proxmox config")?
+            } else if super::hotplug::valid_pve_disk_slot(disk) {
+                disk.to_string()
+            } else {
+                return Err(format!("'{}' is not a Proxmox disk slot (e.g. scsi1)", disk));
+            };
+            Self::vm_cli("qm", &["resize", &vmid.to_string(), &slot, &format!("{}G", size_gb)])?;
+            return Ok(format!("{} grown to {} GB — extend the partition inside the guest to use it", slot, size_gb));
+        }
+        if containers::is_libvirt() && self.virsh_has_domain(name) {
+            let target = if disk == "os" {
+                let out = Command::new("virsh").args(["domblklist", name, "--details"]).output()
+                    .map_err(|e| format!("Failed to run virsh domblklist: {}", e))?;
+                String::from_utf8_lossy(&out.stdout).lines()
+                    .map(|l| l.split_whitespace().collect::<Vec<_>>())
+                    .find(|cols| cols.len() >= 3 && cols[1] == "disk")
+                    .map(|cols| cols[2].to_string())
+                    .ok_or("Could not find the VM's OS disk")?
+            } else if !disk.is_empty() && disk.chars().all(|c| c.is_ascii_alphanumeric()) {
+                disk.to_string()
+            } else {
+                return Err(format!("'{}' is not a disk target (e.g. vdb)", disk));
+            };
+            if !self.check_running(name) {
+                return Err("libvirt resizes disks online only — start the VM, or resize the image with qemu-img".to_string());
+            }
+            Self::vm_cli("virsh", &["blockresize", name, &target, &format!("{}G", size_gb)])?;
+            return Ok(format!("{} grown to {} GB — extend the partition inside the guest to use it", target, size_gb));
+        }
+
+        let config_path = self.vm_config_path(name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("VM not found: {}", e))?;
+        let mut config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        let (path, current) = if disk == "os" {
+            (self.vm_os_disk_path(&config), config.disk_size_gb)
+        } else {
+            let full_name = format!("{}-{}", name, disk);
+            let vol = config.extra_disks.iter()
+                .find(|d| d.name == full_name || d.name == disk)
+                .ok_or_else(|| format!("Volume '{}' not found", disk))?;
+            (vol.file_path(), vol.size_gb)
+        };
+        if size_gb <= current {
+            return Err(format!("New size must be larger than current size ({}G)", current));
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        if self.check_running(name) {
+            let blocks = self.qmp_call(name, "query-block", None)?;
+            let (key, handle) = super::hotplug::block_device_for(&blocks, &path_str)
+                .ok_or_else(|| format!("{} is not attached to the running VM", path_str))?;
+            let mut args = serde_json::json!({ "size": size_gb as u64 * 1024 * 1024 * 1024 });
+            args[key] = serde_json::Value::String(handle);
+            self.qmp_call(name, "block_resize", Some(args))?;
+        } else {
+            let output = Command::new("qemu-img")
+                .args(["resize", &path_str, &format!("{}G", size_gb)])
+                .output()
+                .map_err(|e| format!("qemu-img resize failed: {}", e))?;
+            if !output.status.success() {
+                return Err(format!("Resize failed: {}", String::from_utf8_lossy(&output.stderr)));
+            }
+        }
+
+        if disk == "os" {
+            config.disk_size_gb = size_gb;
+        } else if let Some(vol) = config.extra_disks.iter_mut()
+            .find(|d| d.name == format!("{}-{}", name, disk) || d.name == disk) {
+            vol.size_gb = size_gb;
+        }
+        let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(&config_path, json).map_err(|e| e.to_string())?;
+        Ok(format!("Disk grown to {} GB — extend the partition inside the guest to use it", size_gb))
+    }
+
+    /// Set the native hot-plug headroom (`max_cpus`, `balloon`). Both are
+    /// boot-time QEMU options, so they take effect at the next start.
+    pub fn set_hotplug_options(&self, name: &str, max_cpus: Option<u32>, balloon: Option<bool>) -> Result<(), String> {
+        if self.vm_platform(name) != "native" {
+            return Err("Hot-plug headroom only applies to native QEMU VMs — Proxmox and libvirt keep their own maximums".to_string());
+        }
+        let config_path = self.vm_config_path(name);
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("VM not found: {}", e))?;
+        let mut config: VmConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config: {}", e))?;
+        if let Some(max) = max_cpus {
+            if max != 0 && (max < config.cpus || max > 256) {
+                return Err(format!("vCPU ceiling must be 0 (none) or between {} and 256", config.cpus));
+            }
+            config.max_cpus = max;
+        }
+        if let Some(b) = balloon {
+            config.balloon = b;
+        }
+        let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(&config_path, json).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     /// Which hypervisor backend owns this VM: "proxmox", "libvirt", or
     /// "native". Lets the editor tailor its UI — e.g. "stop to edit" for
     /// native (which blocks running-VM edits) vs "applies on next start" for
@@ -5135,6 +5732,12 @@ impl VmManager {
     }
 
     pub fn get_vm(&self, name: &str) -> Option<VmConfig> {
+        let mut vm = self.get_vm_inner(name)?;
+        vm.windows = vm.is_windows();
+        Some(vm)
+    }
+
+    fn get_vm_inner(&self, name: &str) -> Option<VmConfig> {
         // On Proxmox, find VM in the qm list output
         if containers::is_proxmox() {
             return self.qm_list_all().into_iter().find(|vm| vm.name == name);
@@ -5381,25 +5984,52 @@ impl VmManager {
     /// (plain qemu with a JSON config in base_dir) are still managed
     /// natively even when libvirtd is running.
     fn virsh_has_domain(&self, name: &str) -> bool {
+        match libvirt::domain_state(name) {
+            Ok(found) => return found.is_some(),
+            Err(libvirt::LibvirtError::Unavailable(_)) => {}
+            Err(e) => warn!("libvirt lookup of '{}' failed, asking virsh: {}", name, e),
+        }
         Command::new("virsh").args(["domstate", name]).output()
             .map(|o| o.status.success())
             .unwrap_or(false)
     }
 
     fn virsh_list_all(&self) -> Vec<VmConfig> {
-        let output = match Command::new("virsh").args(["list", "--all", "--name"]).output() {
-            Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
-            _ => return vec![],
-        };
-
-        let libvirt_names: std::collections::HashSet<String> = output.lines()
-            .map(|l| l.trim().to_string())
-            .filter(|name| !name.is_empty())
-            .collect();
+        // Names and live state in one libvirt round trip. The state
+        // overrides what the XML-file fast path infers from /run, which
+        // lags a domain that is mid-start or mid-shutdown.
+        let (libvirt_names, states): (std::collections::HashSet<String>, Option<std::collections::HashMap<String, bool>>) =
+            match libvirt::list_domains() {
+                Ok(domains) => (
+                    domains.iter().map(|d| d.name.clone()).collect(),
+                    Some(domains.iter().map(|d| (d.name.clone(), d.running())).collect()),
+                ),
+                Err(e) => {
+                    if !matches!(e, libvirt::LibvirtError::Unavailable(_)) {
+                        warn!("libvirt domain list failed, falling back to virsh: {}", e);
+                    }
+                    let output = match Command::new("virsh").args(["list", "--all", "--name"]).output() {
+                        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
+                        _ => return vec![],
+                    };
+                    let names = output.lines()
+                        .map(|l| l.trim().to_string())
+                        .filter(|name| !name.is_empty())
+                        .collect();
+                    (names, None)
+                }
+            };
 
         let mut vms: Vec<VmConfig> = libvirt_names.iter()
             .filter_map(|n| self.virsh_vm_to_config(n))
             .collect();
+        if let Some(states) = &states {
+            for vm in vms.iter_mut() {
+                if let Some(&running) = states.get(&vm.name) {
+                    vm.running = running;
+                }
+            }
+        }
 
         // Pre-libvirt native VMs: JSON configs in base_dir for names
         // not defined in libvirt. These were created by WolfStack
@@ -5641,6 +6271,13 @@ impl VmManager {
             // authoritative (empty for any domain we never touched).
             extra_qemu_args: libvirt_xml_qemu_commandline(&dumpxml),
             paused: false,
+            max_cpus: 0,
+            balloon: false,
+            // Windows domains carry libosinfo metadata; the sidecar
+            // overlay below fills in anything else.
+            os_type: libvirt_xml_os_type(&dumpxml),
+            rdp_address: String::new(),
+            windows: false,
         };
 
         // Overlay adoption sidecar for WolfStack-specific fields that
@@ -5664,6 +6301,10 @@ impl VmManager {
                 if config.bridge_ip_mode.is_none() { config.bridge_ip_mode = sidecar.bridge_ip_mode; }
                 if config.bridge_ip.is_none() { config.bridge_ip = sidecar.bridge_ip; }
                 if config.bridge_gateway.is_none() { config.bridge_gateway = sidecar.bridge_gateway; }
+                // OS type / RDP hint are WolfStack-only; an explicit OS in the
+                // sidecar beats the libosinfo guess.
+                if !sidecar.os_type.is_empty() { config.os_type = sidecar.os_type; }
+                config.rdp_address = sidecar.rdp_address;
             }
         }
 
@@ -5836,6 +6477,13 @@ impl VmManager {
             // Extra QEMU args from the persistent domain's <qemu:commandline>.
             extra_qemu_args: libvirt_xml_qemu_commandline(&persistent),
             paused: false,
+            max_cpus: 0,
+            balloon: false,
+            // Windows domains carry libosinfo metadata; the sidecar
+            // overlay below fills in anything else.
+            os_type: libvirt_xml_os_type(&persistent),
+            rdp_address: String::new(),
+            windows: false,
         };
 
         // Same WolfStack sidecar overlay as the subprocess path: the domain
@@ -5853,6 +6501,10 @@ impl VmManager {
                 if config.bridge_ip_mode.is_none() { config.bridge_ip_mode = sidecar.bridge_ip_mode; }
                 if config.bridge_ip.is_none() { config.bridge_ip = sidecar.bridge_ip; }
                 if config.bridge_gateway.is_none() { config.bridge_gateway = sidecar.bridge_gateway; }
+                // OS type / RDP hint are WolfStack-only; an explicit OS in the
+                // sidecar beats the libosinfo guess.
+                if !sidecar.os_type.is_empty() { config.os_type = sidecar.os_type; }
+                config.rdp_address = sidecar.rdp_address;
             }
         }
 
@@ -5894,7 +6546,10 @@ impl VmManager {
             "--vcpus".to_string(), config.cpus.to_string(),
             "--memory".to_string(), config.memory_mb.to_string(),
             "--disk".to_string(), format!("path={},size={},format=qcow2,bus={}", disk_path, config.disk_size_gb, os_bus),
-            "--os-variant".to_string(), "generic".to_string(),
+            // Windows gets libosinfo's win10 profile (Hyper-V enlightenments,
+            // localtime RTC); "win10" is known to every osinfo-db still in
+            // use, where "win11" isn't. Everything else stays generic.
+            "--os-variant".to_string(), if config.is_windows() { "win10" } else { "generic" }.to_string(),
             "--graphics".to_string(), libvirt_graphics,
             "--noautoconsole".to_string(),
         ];
@@ -6329,6 +6984,11 @@ impl VmManager {
             // Carry over any existing <qemu:commandline> passthrough args.
             extra_qemu_args: libvirt_xml_qemu_commandline(&dumpxml),
             paused: false,
+            max_cpus: 0,
+            balloon: false,
+            os_type: libvirt_xml_os_type(&dumpxml),
+            rdp_address: String::new(),
+            windows: false,
         };
 
         // Save config
@@ -7244,18 +7904,10 @@ pub fn import_vm_proxmox(
     };
     let net0 = format!("{},bridge={}", net_model, bridge);
     let bios = if config.bios_type == "ovmf" { "ovmf" } else { "seabios" };
-    // OS type heuristic for PVE. WolfStack doesn't track OS family
-    // explicitly, but the existing new-VM flow uses:
-    //   - net_model = "e1000"/"e1000e"/"rtl8139" → Windows (virtio-net
-    //     drivers aren't in Win installer media)
-    //   - os_disk_bus = "ide"/"sata" → Windows (virtio-blk is in the
-    //     same boat on Win)
-    // Pick "win11" (most recent, backward-compatible with all Win10
-    // paravirt guest behaviour) when either signal fires; else "l26"
-    // (Linux 2.6+). Operator can fix post-import if wrong.
-    let looks_windows = matches!(config.net_model.as_str(), "e1000" | "e1000e" | "rtl8139")
-        || matches!(config.os_disk_bus.as_str(), "ide" | "sata");
-    let ostype = if looks_windows { "win11" } else { "l26" };
+    // OS type for PVE: the VM's recorded os_type, else the Windows
+    // heuristic (emulated NIC / IDE-SATA disk / Windows media → "win11"),
+    // else "l26". Operator can fix post-import if wrong.
+    let ostype = config.pve_ostype();
     let mut create = Command::new("qm");
     create.args([
         "create", &vmid.to_string(),
@@ -8251,6 +8903,11 @@ fn parse_pve_qemu_conf(vmid: u32, text: &str) -> Option<VmConfig> {
         notes,
         extra_qemu_args,
         paused: false,
+        max_cpus: 0,
+        balloon: false,
+        os_type: pve_conf_value(&main_section, "ostype").unwrap_or_default(),
+        rdp_address: String::new(),
+        windows: false,
     })
 }
 
@@ -8313,6 +8970,14 @@ fn pve_os_disk_storage(conf: &str) -> Option<String> {
     None
 }
 
+/// PVE slot holding the OS disk (first non-cdrom scsi0/virtio0/sata0/ide0),
+/// the name `qm resize` wants.
+fn pve_os_disk_slot(conf: &str) -> Option<String> {
+    ["scsi0", "virtio0", "sata0", "ide0"].iter()
+        .find(|key| pve_conf_value(conf, key).map(|v| !v.contains("media=cdrom")).unwrap_or(false))
+        .map(|key| key.to_string())
+}
+
 /// The OS disk bus in the editor's vocabulary. PVE's scsi0 (virtio-SCSI) and
 /// virtio0 (virtio-blk) are both the paravirtual fast path the editor labels
 /// "VirtIO"; ide0 → "ide", sata0 → "sata". Defaults to "virtio".
@@ -8854,16 +9519,22 @@ pub fn running_vm_names() -> Option<std::collections::HashSet<String>> {
         // None is the safe direction — every VM stays advertised — at the
         // cost of the start-time conflict check transiently counting
         // stopped VMs again until virsh recovers.
-        match Command::new("virsh").args(["list", "--name", "--state-running"]).output() {
-            Ok(o) if o.status.success() => {
-                for name in String::from_utf8_lossy(&o.stdout).lines() {
-                    let name = name.trim();
-                    if !name.is_empty() {
-                        set.insert(name.to_string());
+        match libvirt::list_domains() {
+            Ok(domains) => set.extend(domains.into_iter().filter(|d| d.running()).map(|d| d.name)),
+            Err(libvirt::LibvirtError::Libvirt(..)) => return None,
+            Err(libvirt::LibvirtError::Unavailable(_)) => {
+                match Command::new("virsh").args(["list", "--name", "--state-running"]).output() {
+                    Ok(o) if o.status.success() => {
+                        for name in String::from_utf8_lossy(&o.stdout).lines() {
+                            let name = name.trim();
+                            if !name.is_empty() {
+                                set.insert(name.to_string());
+                            }
+                        }
                     }
+                    _ => return None,
                 }
             }
-            _ => return None,
         }
     }
     // Native QEMU processes — also covers pre-libvirt VMs on libvirt hosts
@@ -8969,6 +9640,30 @@ fn libvirt_xml_inner_text_after_tag(xml: &str, tag_open_prefix: &str) -> Option<
     Some(xml.get(after_open..close_idx)?.trim().to_string())
 }
 
+/// Map the libosinfo metadata virt-install / virt-manager write into a
+/// domain (`<libosinfo:os id="http://microsoft.com/win/11"/>`) onto a
+/// [`PVE_OS_TYPES`] value. Only Windows is mapped — that's all the
+/// dashboard distinguishes — so anything else reads as "" (not recorded).
+fn libvirt_xml_os_type(xml: &str) -> String {
+    let Some(start) = xml.find("http://microsoft.com/") else { return String::new(); };
+    let id = &xml[start + "http://microsoft.com/".len()..];
+    let id = &id[..id.find(['"', '\'']).unwrap_or(id.len())];
+    let os = match id.strip_prefix("win/").unwrap_or(id) {
+        v if v.starts_with("11") => "win11",
+        v if v.starts_with("10") => "win10",
+        v if v.starts_with('8') => "win8",
+        v if v.starts_with('7') => "win7",
+        v if v.starts_with("vista") => "wvista",
+        v if v.starts_with("xp") => "wxp",
+        v if v.starts_with("2k3") => "w2k3",
+        v if v.starts_with("2k8") => "w2k8",
+        // Server 2012+ (2k12 … 2k25) and anything newer share the modern
+        // Windows guest profile.
+        _ => "win10",
+    };
+    os.to_string()
+}
+
 /// Extract the libvirt domain `<description>` text (operator notes), XML-
 /// unescaped. libvirt stores the description set by `virsh desc` in this
 /// element with the usual XML entity escaping (`&lt;`, `&amp;`, etc.). Empty
@@ -10143,3 +10838,78 @@ mod extra_qemu_args_tests {
         assert!(argv.iter().any(|a| a == "-name"));
     }
 }
+
+#[cfg(test)]
+mod windows_guest_tests {
+    use super::*;
+
+    #[test]
+    fn explicit_os_type_beats_heuristic() {
+        let mut cfg = VmConfig::new("w".to_string(), 2, 4096, 64);
+        cfg.net_model = "e1000".to_string();
+        cfg.os_type = "l26".to_string();
+        assert!(!cfg.is_windows());
+        assert_eq!(cfg.pve_ostype(), "l26");
+        cfg.os_type = "w2k8".to_string();
+        assert!(cfg.is_windows());
+        assert_eq!(cfg.pve_ostype(), "w2k8");
+    }
+
+    #[test]
+    fn heuristic_covers_media_and_hardware() {
+        let mut cfg = VmConfig::new("w".to_string(), 2, 4096, 64);
+        assert!(!cfg.is_windows());
+        assert_eq!(cfg.pve_ostype(), "l26");
+        cfg.iso_path = Some("/var/lib/wolfstack/isos/Win11_23H2_English_x64.iso".to_string());
+        assert!(cfg.is_windows());
+        assert_eq!(cfg.pve_ostype(), "win11");
+
+        let mut cfg = VmConfig::new("w".to_string(), 2, 4096, 64);
+        cfg.drivers_iso = Some("/isos/virtio-win-0.1.240.iso".to_string());
+        assert!(cfg.is_windows());
+
+        let mut cfg = VmConfig::new("w".to_string(), 2, 4096, 64);
+        cfg.os_disk_bus = "sata".to_string();
+        assert!(cfg.is_windows());
+    }
+
+    #[test]
+    fn iso_names() {
+        assert!(iso_looks_windows("/isos/en-us_windows_server_2022.iso"));
+        assert!(iso_looks_windows("Win10_22H2.iso"));
+        assert!(iso_looks_windows("/isos/win-2019-eval.iso"));
+        assert!(!iso_looks_windows("/isos/darwin10.iso"));
+        assert!(!iso_looks_windows("/isos/winamp-linux.iso"));
+        assert!(!iso_looks_windows("/isos/ubuntu-24.04-live-server-amd64.iso"));
+        // Only the file name counts, not the folder.
+        assert!(!iso_looks_windows("/mnt/windows-share/debian-12.iso"));
+    }
+
+    #[test]
+    fn rdp_address_normalisation() {
+        assert_eq!(normalize_rdp_address("  ").unwrap(), "");
+        assert_eq!(normalize_rdp_address("10.10.0.5").unwrap(), "10.10.0.5");
+        assert_eq!(normalize_rdp_address(" desk.example.com:3390 ").unwrap(), "desk.example.com:3390");
+        assert_eq!(normalize_rdp_address("[fd00::5]:3389").unwrap(), "[fd00::5]:3389");
+        assert_eq!(normalize_rdp_address("[fd00::5]").unwrap(), "[fd00::5]");
+        assert!(normalize_rdp_address("fd00::5").is_err());
+        assert!(normalize_rdp_address("host:0").is_err());
+        assert!(normalize_rdp_address("host:99999").is_err());
+        assert!(normalize_rdp_address("host name").is_err());
+        assert!(normalize_rdp_address("host\nfull address:s:evil").is_err());
+        assert!(normalize_rdp_address("[nope]:3389").is_err());
+    }
+
+    #[test]
+    fn libvirt_os_type_from_libosinfo() {
+        let xml = |id: &str| format!(
+            "<domain><metadata><libosinfo:libosinfo xmlns:libosinfo=\"http://libosinfo.org/xmlns/libvirt/domain/1.0\"><libosinfo:os id=\"{}\"/></libosinfo:libosinfo></metadata></domain>",
+            id);
+        assert_eq!(libvirt_xml_os_type(&xml("http://microsoft.com/win/11")), "win11");
+        assert_eq!(libvirt_xml_os_type(&xml("http://microsoft.com/win/10")), "win10");
+        assert_eq!(libvirt_xml_os_type(&xml("http://microsoft.com/win/2k8r2")), "w2k8");
+        assert_eq!(libvirt_xml_os_type(&xml("http://microsoft.com/win/2k22")), "win10");
+        assert_eq!(libvirt_xml_os_type(&xml("http://ubuntu.com/ubuntu/24.04")), "");
+        assert_eq!(libvirt_xml_os_type("<domain/>"), "");
+    }
+}
@@ -7,3 +7,7 @@ pub mod api;
 pub mod passthrough;
 pub mod vlan_learner;
 pub mod prereqs;
+pub mod iso_library;
+pub mod disk_import;
+pub mod hotplug;
+pub mod libvirt;
@@ -54,6 +54,10 @@ pub struct HostPciDevice {
     /// Current kernel driver bound to the device (e.g. "nvidia", "vfio-pci")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub driver: Option<String>,
+    /// Other passable devices in the same IOMMU group (BDFs). VFIO needs
+    /// the whole group, so the picker shows these next to the device.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub iommu_peers: Vec<String>,
     /// Stable identifier for frontend matching
     pub match_key: String,
     /// VM currently claiming this device (if any)
@@ -196,6 +200,7 @@ pub fn list_host_pci() -> Vec<HostPciDevice> {
             description: after_class.trim().to_string(),
             iommu_group,
             driver,
+            iommu_peers: Vec::new(),
             match_key: format!("pci:{}", bdf),
             in_use_by: None,
             in_use_running: false,
@@ -322,6 +327,14 @@ pub fn list_host_devices(ownership: &DeviceOwnership) -> HostDevicesResponse {
             p.in_use_running = *running;
         }
     }
+    let groups: Vec<(String, Option<u32>)> = pci.iter().map(|p| (p.bdf.clone(), p.iommu_group)).collect();
+    for p in &mut pci {
+        if p.iommu_group.is_none() { continue; }
+        p.iommu_peers = groups.iter()
+            .filter(|(bdf, g)| *g == p.iommu_group && *bdf != p.bdf)
+            .map(|(bdf, _)| bdf.clone())
+            .collect();
+    }
 
     HostDevicesResponse {
         usb,
@@ -380,6 +393,167 @@ pub fn find_conflicts(target: &VmConfig, others: &[VmConfig]) -> Vec<String> {
     conflicts
 }
 
+// ─── Assignment validation ───
+
+/// Result of checking a proposed passthrough set for one VM. `errors`
+/// would make the VM fail to start (or take the device from a running
+/// VM); `warnings` are worth reading but don't block the save.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PassthroughCheck {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Drivers that leave an IOMMU group viable for VFIO.
+fn vfio_safe_driver(driver: Option<&str>) -> bool {
+    matches!(driver, None | Some("vfio-pci") | Some("pci-stub"))
+}
+
+/// `0000:01:00.1` → `0000:01:00` — functions of the same physical card.
+fn pci_slot(bdf: &str) -> &str {
+    bdf.rsplit_once('.').map(|(slot, _)| slot).unwrap_or(bdf)
+}
+
+/// Validate `usb` + `pci` for VM `vm_name` against what the host has and
+/// what other VMs claim. Pure — the caller supplies the inventory.
+///
+/// PCI rules follow VFIO: every device in an IOMMU group must either go
+/// to the same VM or sit on no host driver, otherwise QEMU refuses with
+/// "group is not viable". Functions of the same card (GPU + its HDMI
+/// audio) are called out separately since that's the usual miss.
+pub fn validate_assignment(
+    vm_name: &str,
+    usb: &[UsbDevice],
+    pci: &[PciDevice],
+    host_usb: &[HostUsbDevice],
+    host_pci: &[HostPciDevice],
+    iommu_enabled: bool,
+    vms: &[VmConfig],
+) -> PassthroughCheck {
+    let mut check = PassthroughCheck::default();
+    let others: Vec<&VmConfig> = vms.iter().filter(|v| v.name != vm_name).collect();
+    let usb_owner = |key: &str| -> Option<(&str, bool)> {
+        others.iter().find(|v| v.usb_devices.iter().any(|u| u.match_key() == key))
+            .map(|v| (v.name.as_str(), v.running))
+    };
+    let pci_owner = |bdf: &str| -> Option<(&str, bool)> {
+        others.iter().find(|v| v.pci_devices.iter().any(|p| normalize_bdf(&p.bdf).map(|b| b == bdf).unwrap_or(false)))
+            .map(|v| (v.name.as_str(), v.running))
+    };
+
+    let mut seen = HashSet::new();
+    for u in usb {
+        let pinned = u.host_bus.as_deref().filter(|b| !b.is_empty());
+        let label = u.label.clone().unwrap_or_else(|| format!("{}:{}", u.vendor_id, u.product_id));
+        if pinned.is_none() {
+            let hex4 = |s: &str| s.len() == 4 && s.chars().all(|c| c.is_ascii_hexdigit());
+            if !hex4(&u.vendor_id) || !hex4(&u.product_id) {
+                check.errors.push(format!("USB device '{}' needs a 4-digit hex vendor and product ID", label));
+                continue;
+            }
+        }
+        if !seen.insert(u.match_key()) {
+            check.errors.push(format!("USB device '{}' is listed twice", label));
+            continue;
+        }
+        let present: Vec<&HostUsbDevice> = host_usb.iter().filter(|h| match pinned {
+            Some(bus) => h.host_bus == bus,
+            None => h.vendor_id.eq_ignore_ascii_case(&u.vendor_id) && h.product_id.eq_ignore_ascii_case(&u.product_id),
+        }).collect();
+        if present.is_empty() {
+            check.warnings.push(match pinned {
+                Some(bus) => format!("Nothing is plugged into USB port {} right now — '{}' must be there when the VM starts", bus, label),
+                None => format!("USB device '{}' is not plugged in — it must be present when the VM starts", label),
+            });
+        } else if pinned.is_none() && present.len() > 1 {
+            check.warnings.push(format!(
+                "{} identical '{}' devices are attached — pin this one to its USB port so the VM always gets the same dongle",
+                present.len(), label
+            ));
+        }
+        if let Some((owner, running)) = usb_owner(&u.match_key()) {
+            let msg = format!("USB device '{}' is also assigned to VM '{}'", label, owner);
+            if running { check.errors.push(format!("{} (running)", msg)); } else { check.warnings.push(format!("{} — only one of them can run at a time", msg)); }
+        }
+    }
+
+    let mut wanted: HashSet<String> = HashSet::new();
+    let mut normalized = Vec::new();
+    for p in pci {
+        match normalize_bdf(&p.bdf) {
+            Ok(bdf) => {
+                if wanted.insert(bdf.clone()) { normalized.push(bdf); } else { check.errors.push(format!("PCI device {} is listed twice", bdf)); }
+            }
+            Err(e) => check.errors.push(e),
+        }
+    }
+    if !normalized.is_empty() && !iommu_enabled {
+        check.errors.push("IOMMU is not enabled on this host — PCI passthrough cannot work until it is (see the preflight notes)".to_string());
+    }
+    let mut reported_groups = HashSet::new();
+    for bdf in &normalized {
+        let Some(dev) = host_pci.iter().find(|h| &h.bdf == bdf) else {
+            check.errors.push(format!("PCI device {} was not found on this host", bdf));
+            continue;
+        };
+        if let Some((owner, running)) = pci_owner(bdf) {
+            let msg = format!("PCI device {} is also assigned to VM '{}'", bdf, owner);
+            if running { check.errors.push(format!("{} (running)", msg)); } else { check.warnings.push(format!("{} — only one of them can run at a time", msg)); }
+        }
+        if !vfio_safe_driver(dev.driver.as_deref()) {
+            check.warnings.push(format!(
+                "PCI device {} ({}) is in use by the host driver '{}' — the host loses it when the VM starts",
+                bdf, dev.description, dev.driver.as_deref().unwrap_or("")
+            ));
+        }
+        let Some(group) = dev.iommu_group else {
+            if iommu_enabled {
+                check.errors.push(format!("PCI device {} has no IOMMU group and cannot be passed through", bdf));
+            }
+            continue;
+        };
+        if !reported_groups.insert(group) { continue; }
+        for peer in host_pci.iter().filter(|h| h.iommu_group == Some(group) && !wanted.contains(&h.bdf)) {
+            if let Some((owner, _)) = pci_owner(&peer.bdf) {
+                check.errors.push(format!(
+                    "IOMMU group {} is split between this VM and VM '{}' ({}) — a group can only go to one VM",
+                    group, owner, peer.bdf
+                ));
+            } else if !vfio_safe_driver(peer.driver.as_deref()) {
+                let hint = if pci_slot(&peer.bdf) == pci_slot(bdf) { "another function of the same card" } else { "a different device" };
+                check.errors.push(format!(
+                    "IOMMU group {} also contains {} ({}, {}), bound to '{}' — pass it through as well, or the VM will not start",
+                    group, peer.bdf, peer.description, hint, peer.driver.as_deref().unwrap_or("")
+                ));
+            } else {
+                check.warnings.push(format!(
+                    "IOMMU group {} also contains {} ({}) — it has no host driver, so it is unaffected, but it can't be given to another VM",
+                    group, peer.bdf, peer.description
+                ));
+            }
+        }
+    }
+    check
+}
+
+/// `validate_assignment` against the live host inventory, plus the
+/// "would this take the host's default-route NIC" check `start_vm`
+/// enforces, surfaced here so the operator hears about it at save time.
+pub fn check_assignment(vm_name: &str, usb: &[UsbDevice], pci: &[PciDevice], vms: &[VmConfig]) -> PassthroughCheck {
+    let host_usb = if usb.is_empty() { Vec::new() } else { list_host_usb() };
+    let host_pci = if pci.is_empty() { Vec::new() } else { list_host_pci() };
+    let iommu_enabled = host_preflight().iommu_enabled;
+    let mut check = validate_assignment(vm_name, usb, pci, &host_usb, &host_pci, iommu_enabled, vms);
+    if let Some(iface) = host_default_route_interface() {
+        if let Some(nic) = check_pci_steals_host_iface(pci, &iface, &pci_bdf_to_net_iface) {
+            check.errors.push(format!(
+                "PCI passthrough would take {}, the host's default-route NIC — the host would drop off the network", nic
+            ));
+        }
+    }
+    check
+}
+
 // ─── Native QEMU argument builders ───
 
 /// Is a USB device with `vendor_id:product_id` present on this host?
@@ -1211,6 +1385,88 @@ net0: virtio=AA:BB:CC:DD:EE:FF,bridge=vmbr0
         };
         assert!(find_conflicts(&target, &[stopped]).is_empty());
     }
+
+    fn host_pci(bdf: &str, group: u32, driver: Option<&str>) -> HostPciDevice {
+        HostPciDevice {
+            bdf: bdf.to_string(),
+            vendor_id: "10de".to_string(),
+            device_id: "1b80".to_string(),
+            class: "VGA compatible controller".to_string(),
+            description: "NVIDIA GP104".to_string(),
+            iommu_group: Some(group),
+            driver: driver.map(str::to_string),
+            iommu_peers: Vec::new(),
+            match_key: format!("pci:{}", bdf),
+            in_use_by: None,
+            in_use_running: false,
+        }
+    }
+
+    fn gpu(bdf: &str) -> PciDevice {
+        PciDevice { bdf: bdf.to_string(), pcie: true, primary_gpu: false, label: None }
+    }
+
+    #[test]
+    fn iommu_group_must_go_together() {
+        let host = vec![
+            host_pci("0000:01:00.0", 14, Some("vfio-pci")),
+            host_pci("0000:01:00.1", 14, Some("snd_hda_intel")),
+            host_pci("0000:02:00.0", 15, None),
+        ];
+        let check = validate_assignment("gaming", &[], &[gpu("01:00.0")], &[], &host, true, &[]);
+        assert_eq!(check.errors.len(), 1);
+        assert!(check.errors[0].contains("0000:01:00.1"));
+        assert!(check.errors[0].contains("same card"));
+
+        let both = [gpu("0000:01:00.0"), gpu("0000:01:00.1")];
+        let check = validate_assignment("gaming", &[], &both, &[], &host, true, &[]);
+        assert!(check.errors.is_empty(), "{:?}", check.errors);
+        // The audio function is on a host driver until the VM starts.
+        assert_eq!(check.warnings.len(), 1);
+
+        let check = validate_assignment("gaming", &[], &[gpu("0000:03:00.0")], &[], &host, true, &[]);
+        assert!(check.errors[0].contains("not found"));
+        let check = validate_assignment("gaming", &[], &[gpu("0000:02:00.0")], &[], &host, false, &[]);
+        assert!(check.errors[0].contains("IOMMU is not enabled"));
+    }
+
+    #[test]
+    fn iommu_group_cannot_be_split_across_vms() {
+        let host = vec![host_pci("0000:01:00.0", 14, None), host_pci("0000:01:00.1", 14, None)];
+        let other = VmConfig { pci_devices: vec![gpu("01:00.1")], ..VmConfig::new("other".to_string(), 1, 1024, 10) };
+        let check = validate_assignment("gaming", &[], &[gpu("0000:01:00.0")], &[], &host, true, &[other]);
+        assert_eq!(check.errors.len(), 1);
+        assert!(check.errors[0].contains("split"));
+    }
+
+    #[test]
+    fn identical_usb_dongles_suggest_port_pinning() {
+        let dongle = |bus: &str| HostUsbDevice {
+            vendor_id: "0529".to_string(),
+            product_id: "0001".to_string(),
+            host_bus: bus.to_string(),
+            description: "Aladdin HASP".to_string(),
+            match_key: "usb:0529:0001".to_string(),
+            in_use_by: None,
+            in_use_running: false,
+        };
+        let host = vec![dongle("1-3"), dongle("1-4")];
+        let by_id = UsbDevice { vendor_id: "0529".to_string(), product_id: "0001".to_string(), host_bus: None, label: None };
+        let check = validate_assignment("erp", &[by_id.clone()], &[], &host, &[], true, &[]);
+        assert!(check.errors.is_empty());
+        assert!(check.warnings[0].contains("pin"));
+
+        let pinned = UsbDevice { host_bus: Some("1-4".to_string()), ..by_id.clone() };
+        let check = validate_assignment("erp", &[pinned], &[], &host, &[], true, &[]);
+        assert_eq!(check, PassthroughCheck::default());
+
+        let running = VmConfig { running: true, usb_devices: vec![by_id.clone()], ..VmConfig::new("erp2".to_string(), 1, 1024, 10) };
+        let check = validate_assignment("erp", &[by_id.clone()], &[], &host, &[], true, &[running]);
+        assert!(check.errors[0].contains("erp2"));
+
+        let bad = UsbDevice { vendor_id: "xyz".to_string(), ..by_id };
+        assert_eq!(validate_assignment("erp", &[bad], &[], &host, &[], true, &[]).errors.len(), 1);
+    }
 }
 
 // ─── Network-safety preflight ──────────────────────────────────────
@@ -140,6 +140,14 @@ pub enum TriggerEvent {
     /// A UPS staged-shutdown threshold fired (payload names the stage
     /// percent and its actions) — hook custom wind-down logic here.
     UpsStageFired,
+    /// A Docker container or LXC container was created on this node.
+    ContainerCreated,
+    /// A container was started, restarted, unpaused or unfrozen.
+    ContainerStarted,
+    /// A container was stopped, paused or frozen.
+    ContainerStopped,
+    /// A container was removed or destroyed.
+    ContainerDeleted,
     /// Catch-all for event names this build doesn't know. A newer peer
     /// can sync a function subscribed to an event added after this
     /// release; mapping it here keeps the whole sync payload decodable
@@ -642,15 +650,21 @@ pub fn init_event_hook(
     let _ = EVENT_HOOK.set((state, cluster, cluster_secret));
 }
 
-/// Fire an event from anywhere (sync or async context). No-ops when
+/// Fire an event from anywhere (sync or async context). Goes to the
+/// dashboard live feed first, un-gated; past that it no-ops when
 /// nothing subscribes, so hot paths pay one RwLock read. `force_local`
 /// is used for events that originate on exactly one node (alerts,
 /// backups) — leader gating would drop them on non-leader nodes.
 pub fn fire_event_global(event: TriggerEvent, payload: serde_json::Value, force_local: bool) {
+    if crate::events::live_listeners()
+        && let Some(name) = serde_json::to_value(event).ok().and_then(|v| v.as_str().map(String::from))
+    {
+        crate::events::live(&name, payload.clone());
+    }
     let Some((state, cluster, secret)) = EVENT_HOOK.get() else { return; };
     let any_subscriber = state.config.read().unwrap().functions.iter()
         .any(|f| f.enabled && f.events.contains(&event));
-    if !any_subscriber { return; }
+    if !any_subscriber && !crate::events::has_subscribers() { return; }
     let (state, cluster, secret) = (state.clone(), cluster.clone(), secret.clone());
     // Works from spawn_blocking threads too — they carry the runtime context.
     match tokio::runtime::Handle::try_current() {
@@ -677,6 +691,10 @@ pub async fn fire_event(
     force_local: bool,
 ) {
     if !force_local && !crate::wolfrun::is_leader(cluster) { return; }
+    // Past the leader gate, so webhooks see each event exactly once.
+    if let Some(name) = serde_json::to_value(event).ok().and_then(|v| v.as_str().map(String::from)) {
+        crate::events::publish(&name, payload.clone());
+    }
     let self_cluster = self_cluster_name(cluster);
     let subscribed: Vec<WolfFunction> = state.config.read().unwrap().functions.iter()
         .filter(|f| f.enabled && f.cluster == self_cluster && f.events.contains(&event))
@@ -0,0 +1,391 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+
+//! WolfRun autoscaling — CPU/RAM thresholds that move a service's replica
+//! count between its min and max.
+//!
+//! A policy lives on the service itself (`WolfRunService::autoscale`), so
+//! it reaches every node with the normal WolfRun sync. The leader evaluates
+//! it on each 15s tick, just before reconciliation:
+//!
+//! - the service's load is the average `cpu_percent` / `memory_percent` of
+//!   its running (non-standby) instances, read from each node's container
+//!   stats;
+//! - any `*_high` threshold exceeded for `sustain_secs` scales **out** by
+//!   `step` — reconciliation then clones the new replicas onto the
+//!   least-loaded eligible nodes through the normal scheduler;
+//! - every configured `*_low` threshold undercut for `sustain_secs` scales
+//!   **in** by one, stopping and destroying the newest replica (a manual
+//!   scale-down only un-manages containers; an autoscaled replica has no
+//!   reason to outlive its load);
+//! - after either, the service rests for `cooldown_secs`.
+//!
+//! Every action is recorded as an event, newest last, capped at 200.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use super::{node_matches_id, Runtime, WolfRunService, WolfRunState, RPC_CLIENT_SHORT};
+use crate::agent::ClusterState;
+use crate::containers::ContainerStats;
+
+const MAX_EVENTS: usize = 200;
+
+fn default_true() -> bool { true }
+fn default_sustain() -> u64 { 120 }
+fn default_cooldown() -> u64 { 300 }
+fn default_step() -> u32 { 1 }
+
+/// Thresholds are percentages (0-100). Leave a metric's pair unset to
+/// ignore it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoscalePolicy {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub cpu_high: Option<f64>,
+    #[serde(default)]
+    pub cpu_low: Option<f64>,
+    #[serde(default)]
+    pub mem_high: Option<f64>,
+    #[serde(default)]
+    pub mem_low: Option<f64>,
+    /// How long a threshold must hold before acting.
+    #[serde(default = "default_sustain")]
+    pub sustain_secs: u64,
+    /// Quiet period after any scaling action.
+    #[serde(default = "default_cooldown")]
+    pub cooldown_secs: u64,
+    /// Replicas added per scale-out.
+    #[serde(default = "default_step")]
+    pub step: u32,
+    /// Unix seconds of the last action; set by the leader.
+    #[serde(default)]
+    pub last_scaled_at: u64,
+}
+
+impl AutoscalePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cpu_high.is_none() && self.mem_high.is_none() {
+            return Err("Set a CPU or memory scale-out threshold".to_string());
+        }
+        for (what, high, low) in [("CPU", self.cpu_high, self.cpu_low), ("Memory", self.mem_high, self.mem_low)] {
+            for v in [high, low].into_iter().flatten() {
+                if !(0.0..=100.0).contains(&v) {
+                    return Err(format!("{} thresholds are percentages (0-100)", what));
+                }
+            }
+            if let (Some(h), Some(l)) = (high, low) && l >= h {
+                return Err(format!("{} scale-in threshold must be below its scale-out threshold", what));
+            }
+        }
+        if self.step == 0 {
+            return Err("Step must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// One scaling action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoscaleEvent {
+    pub timestamp: u64,
+    pub service_id: String,
+    pub service_name: String,
+    /// `out` or `in`.
+    pub direction: String,
+    pub from_replicas: u32,
+    pub to_replicas: u32,
+    pub cpu_percent: f64,
+    pub memory_percent: f64,
+    pub reason: String,
+}
+
+/// A service's average load over its running instances.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Load {
+    pub cpu_percent: f64,
+    pub memory_percent: f64,
+    /// Instances that reported stats.
+    pub instances: usize,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pressure {
+    High(String),
+    Low(String),
+    Normal,
+}
+
+impl Pressure {
+    fn key(&self) -> u8 {
+        match self { Pressure::High(_) => 1, Pressure::Low(_) => 2, Pressure::Normal => 0 }
+    }
+}
+
+fn events_file() -> String { crate::paths::get().wolfrun_autoscale_events }
+
+static EVENTS: LazyLock<RwLock<Vec<AutoscaleEvent>>> = LazyLock::new(|| {
+    let events = std::fs::read_to_string(events_file()).ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    RwLock::new(events)
+});
+
+/// Service id → (pressure kind, since when). Leader-local.
+static PRESSURE_SINCE: LazyLock<Mutex<HashMap<String, (u8, u64)>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Service id → the load measured on the last tick. Leader-local.
+static LAST_LOAD: LazyLock<RwLock<HashMap<String, Load>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Scaling events, optionally for one service, oldest first.
+pub fn events(service_id: Option<&str>) -> Vec<AutoscaleEvent> {
+    let evts = EVENTS.read().unwrap_or_else(|e| e.into_inner());
+    evts.iter().filter(|e| service_id.is_none_or(|id| e.service_id == id)).cloned().collect()
+}
+
+/// The load measured for a service on this node's last evaluation, if it
+/// is the leader.
+pub fn last_load(service_id: &str) -> Option<Load> {
+    LAST_LOAD.read().unwrap_or_else(|e| e.into_inner()).get(service_id).copied()
+}
+
+fn record(event: AutoscaleEvent) {
+    let mut evts = EVENTS.write().unwrap_or_else(|e| e.into_inner());
+    evts.push(event);
+    if evts.len() > MAX_EVENTS {
+        let drain = evts.len() - MAX_EVENTS;
+        evts.drain(..drain);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&*evts) {
+        let _ = std::fs::create_dir_all(super::services_dir());
+        if let Err(e) = std::fs::write(events_file(), json) {
+            warn!("WolfRun: failed to save autoscale events: {}", e);
+        }
+    }
+}
+
+fn pressure(policy: &AutoscalePolicy, load: &Load) -> Pressure {
+    let mut high = Vec::new();
+    if let Some(h) = policy.cpu_high && load.cpu_percent > h {
+        high.push(format!("CPU {:.0}% > {:.0}%", load.cpu_percent, h));
+    }
+    if let Some(h) = policy.mem_high && load.memory_percent > h {
+        high.push(format!("memory {:.0}% > {:.0}%", load.memory_percent, h));
+    }
+    if !high.is_empty() {
+        return Pressure::High(high.join(", "));
+    }
+    let lows = [(policy.cpu_low, load.cpu_percent, "CPU"), (policy.mem_low, load.memory_percent, "memory")];
+    let configured: Vec<_> = lows.iter().filter_map(|(t, v, what)| t.map(|t| (t, *v, *what))).collect();
+    if !configured.is_empty() && configured.iter().all(|(t, v, _)| v < t) {
+        return Pressure::Low(configured.iter()
+            .map(|(t, v, what)| format!("{} {:.0}% < {:.0}%", what, v, t))
+            .collect::<Vec<_>>()
+            .join(", "));
+    }
+    Pressure::Normal
+}
+
+/// The replica count to move to, if any. `held_for` is how long the
+/// current pressure has lasted.
+fn decide(policy: &AutoscalePolicy, svc: &WolfRunService, pressure: &Pressure, held_for: u64, now: u64) -> Option<u32> {
+    if !policy.enabled || held_for < policy.sustain_secs {
+        return None;
+    }
+    if policy.last_scaled_at > 0 && now.saturating_sub(policy.last_scaled_at) < policy.cooldown_secs {
+        return None;
+    }
+    let floor = svc.min_replicas.max(1);
+    match pressure {
+        Pressure::High(_) if svc.replicas < svc.max_replicas => Some((svc.replicas + policy.step).min(svc.max_replicas)),
+        Pressure::Low(_) if svc.replicas > floor => Some(svc.replicas - 1),
+        _ => None,
+    }
+}
+
+/// Average the stats of the service's running instances. `stats` is keyed
+/// by node id.
+fn measure(svc: &WolfRunService, stats: &HashMap<String, Vec<ContainerStats>>) -> Load {
+    let samples: Vec<&ContainerStats> = svc.instances.iter()
+        .filter(|i| i.status == "running" && !i.standby)
+        .filter_map(|i| stats.get(&i.node_id)?.iter().find(|s| s.name == i.container_name))
+        .collect();
+    if samples.is_empty() {
+        return Load { at: now(), ..Default::default() };
+    }
+    let n = samples.len() as f64;
+    Load {
+        cpu_percent: samples.iter().map(|s| s.cpu_percent).sum::<f64>() / n,
+        memory_percent: samples.iter().map(|s| s.memory_percent).sum::<f64>() / n,
+        instances: samples.len(),
+        at: now(),
+    }
+}
+
+/// Container stats for one runtime on one node.
+async fn node_stats(node: &crate::agent::Node, runtime: &Runtime, cluster_secret: &str) -> Vec<ContainerStats> {
+    if node.is_self {
+        let f = match runtime {
+            Runtime::Docker => crate::containers::docker_stats_cached,
+            Runtime::Lxc => crate::containers::lxc_stats_cached,
+        };
+        return tokio::task::spawn_blocking(f).await.unwrap_or_default();
+    }
+    let path = match runtime {
+        Runtime::Docker => "/api/containers/docker/stats",
+        Runtime::Lxc => "/api/containers/lxc/stats",
+    };
+    for url in crate::api::build_node_urls(&node.address, node.port, path) {
+        if let Ok(resp) = RPC_CLIENT_SHORT.get(&url).header("X-WolfStack-Secret", cluster_secret).send().await
+            && resp.status().is_success()
+            && let Ok(stats) = resp.json::<Vec<ContainerStats>>().await
+        {
+            return stats;
+        }
+    }
+    Vec::new()
+}
+
+/// Evaluate every enabled policy in this node's cluster and scale where
+/// due. Leader only — called from the WolfRun loop before reconciliation.
+pub async fn evaluate(wolfrun: &WolfRunState, cluster: &ClusterState, cluster_secret: &str) {
+    let self_cluster = cluster.get_self_cluster_name();
+    let services: Vec<WolfRunService> = wolfrun.list(Some(&self_cluster)).into_iter()
+        .filter(|s| s.autoscale.as_ref().is_some_and(|p| p.enabled))
+        .collect();
+    if services.is_empty() { return; }
+    let nodes = cluster.get_all_nodes();
+
+    // One stats fetch per (node, runtime) for the whole tick.
+    let mut fetched: HashMap<(String, bool), Vec<ContainerStats>> = HashMap::new();
+    for svc in &services {
+        let is_docker = svc.runtime == Runtime::Docker;
+        let mut stats: HashMap<String, Vec<ContainerStats>> = HashMap::new();
+        for inst in svc.instances.iter().filter(|i| i.status == "running" && !i.standby) {
+            if stats.contains_key(&inst.node_id) { continue; }
+            let Some(node) = nodes.iter().find(|n| n.online && node_matches_id(n, &inst.node_id)) else { continue };
+            let key = (node.id.clone(), is_docker);
+            if !fetched.contains_key(&key) {
+                let s = node_stats(node, &svc.runtime, cluster_secret).await;
+                fetched.insert(key.clone(), s);
+            }
+            stats.insert(inst.node_id.clone(), fetched[&key].clone());
+        }
+        let load = measure(svc, &stats);
+        LAST_LOAD.write().unwrap_or_else(|e| e.into_inner()).insert(svc.id.clone(), load);
+        if load.instances == 0 { continue; }
+
+        let Some(policy) = svc.autoscale.clone() else { continue };
+        let p = pressure(&policy, &load);
+        let now = now();
+        let held_for = {
+            let mut since = PRESSURE_SINCE.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = since.entry(svc.id.clone()).or_insert((p.key(), now));
+            if entry.0 != p.key() { *entry = (p.key(), now); }
+            now - entry.1
+        };
+        let Some(target) = decide(&policy, svc, &p, held_for, now) else { continue };
+
+        let (direction, reason) = match &p {
+            Pressure::High(r) => ("out", r.clone()),
+            Pressure::Low(r) => ("in", r.clone()),
+            Pressure::Normal => continue,
+        };
+        if direction == "in" {
+            // Take out the newest replica; reconciliation clones from the
+            // older ones.
+            if let Some(victim) = svc.instances.iter().rev().find(|i| i.status == "running" && !i.standby)
+                && let Some(node) = nodes.iter().find(|n| node_matches_id(n, &victim.node_id))
+            {
+                wolfrun.remove_instance(&svc.id, &victim.container_name);
+                super::stop_and_remove(&RPC_CLIENT_SHORT, cluster_secret, node, &victim.container_name, &svc.runtime).await;
+            }
+        }
+        wolfrun.scale(&svc.id, target);
+        wolfrun.set_autoscale(&svc.id, Some(AutoscalePolicy { last_scaled_at: now, ..policy }));
+        PRESSURE_SINCE.lock().unwrap_or_else(|e| e.into_inner()).remove(&svc.id);
+        info!("WolfRun autoscale: {} {} → {} replicas ({})", svc.name, svc.replicas, target, reason);
+        record(AutoscaleEvent {
+            timestamp: now,
+            service_id: svc.id.clone(),
+            service_name: svc.name.clone(),
+            direction: direction.to_string(),
+            from_replicas: svc.replicas,
+            to_replicas: target,
+            cpu_percent: load.cpu_percent,
+            memory_percent: load.memory_percent,
+            reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AutoscalePolicy {
+        serde_json::from_value(serde_json::json!({ "cpu_high": 80.0, "cpu_low": 20.0 })).unwrap()
+    }
+
+    fn service(replicas: u32, min: u32, max: u32) -> WolfRunService {
+        serde_json::from_value(serde_json::json!({
+            "id": "svc-1", "name": "web", "image": "nginx", "replicas": replicas,
+            "min_replicas": min, "max_replicas": max, "cluster_name": "WolfStack",
+            "created_at": 0, "updated_at": 0,
+        })).unwrap()
+    }
+
+    fn load(cpu: f64, mem: f64) -> Load {
+        Load { cpu_percent: cpu, memory_percent: mem, instances: 2, at: 0 }
+    }
+
+    #[test]
+    fn policy_validation() {
+        assert!(policy().validate().is_ok());
+        let mut p = policy();
+        p.cpu_low = Some(90.0);
+        assert!(p.validate().is_err());
+        p = policy();
+        p.cpu_high = None;
+        assert!(p.validate().is_err());
+        p = policy();
+        p.mem_high = Some(120.0);
+        assert!(p.validate().is_err());
+    }
+
+    #[test]
+    fn pressure_needs_every_low_threshold() {
+        let mut p = policy();
+        assert!(matches!(pressure(&p, &load(85.0, 10.0)), Pressure::High(_)));
+        assert!(matches!(pressure(&p, &load(10.0, 90.0)), Pressure::Low(_)));
+        p.mem_low = Some(30.0);
+        assert_eq!(pressure(&p, &load(10.0, 50.0)), Pressure::Normal);
+        assert!(matches!(pressure(&p, &load(10.0, 25.0)), Pressure::Low(_)));
+    }
+
+    #[test]
+    fn decisions_respect_sustain_cooldown_and_bounds() {
+        let p = policy();
+        let high = Pressure::High(String::new());
+        let low = Pressure::Low(String::new());
+        assert_eq!(decide(&p, &service(2, 1, 5), &high, 60, 1000), None);
+        assert_eq!(decide(&p, &service(2, 1, 5), &high, 120, 1000), Some(3));
+        assert_eq!(decide(&p, &service(5, 1, 5), &high, 120, 1000), None);
+        assert_eq!(decide(&AutoscalePolicy { step: 3, ..p.clone() }, &service(4, 1, 5), &high, 120, 1000), Some(5));
+        assert_eq!(decide(&p, &service(2, 1, 5), &low, 120, 1000), Some(1));
+        assert_eq!(decide(&p, &service(1, 0, 5), &low, 120, 1000), None);
+        let cooling = AutoscalePolicy { last_scaled_at: 900, ..p };
+        assert_eq!(decide(&cooling, &service(2, 1, 5), &high, 120, 1000), None);
+        assert_eq!(decide(&cooling, &service(2, 1, 5), &high, 120, 1200), Some(3));
+    }
+}
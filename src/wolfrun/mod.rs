@@ -19,6 +19,8 @@ use tracing::warn;
 
 use crate::agent::ClusterState;
 
+pub mod autoscale;
+
 // ─── Shared HTTP client for cluster RPC ───
 //
 // Every HTTP call this module makes to a peer goes through one of these
@@ -226,6 +228,9 @@ pub struct WolfRunService {
     /// Failover enabled — pre-stage standby containers on other nodes
     #[serde(default)]
     pub failover: bool,
+    /// CPU/RAM autoscaling policy, evaluated by the leader
+    #[serde(default)]
+    pub autoscale: Option<autoscale::AutoscalePolicy>,
     pub created_at: u64,
     pub updated_at: u64,
 }
@@ -425,6 +430,7 @@ impl WolfRunState {
             lb_policy: "round_robin".to_string(),
             allowed_nodes: Vec::new(),
             failover,
+            autoscale: None,
             created_at: now,
             updated_at: now,
         };
@@ -496,6 +502,20 @@ impl WolfRunState {
         }
     }
 
+    /// Set or clear a service's autoscaling policy
+    pub fn set_autoscale(&self, id: &str, policy: Option<autoscale::AutoscalePolicy>) -> bool {
+        let mut svcs = self.services.write().unwrap();
+        if let Some(svc) = svcs.iter_mut().find(|s| s.id == id) {
+            svc.autoscale = policy;
+            svc.updated_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            drop(svcs);
+            self.save();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Update image for a service (for rolling updates — Docker only)
     pub fn update_image(&self, id: &str, image: String) -> bool {
         let mut svcs = self.services.write().unwrap();
@@ -584,6 +604,7 @@ impl WolfRunState {
             lb_policy: "round_robin".to_string(),
             allowed_nodes: Vec::new(),
             failover: false,
+            autoscale: None,
             created_at: now,
             updated_at: now,
         };
@@ -25,7 +25,7 @@ static WS_IO_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 /// (.github/workflows/wolfscale-release.yml). The installer appends
 /// `/wolfscale-<arch>` (x86_64 / aarch64) and falls back to a slow in-container
 /// source build only if the download fails.
-const WS_BINARY_BASE: &str = "https://github.com/wolfsoftwaresystemsltd/WolfScale/releases/download/wolfscale-latest";
+pub(crate) const WS_BINARY_BASE: &str = "https://github.com/wolfsoftwaresystemsltd/WolfScale/releases/download/wolfscale-latest";
 
 fn default_cluster_port() -> u16 { 7654 }
 fn default_api_port() -> u16 { 8080 }
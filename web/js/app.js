@@ -16,6 +16,7 @@ const _syncedPrefKeys = [
     'wolfstack_view_docker', 'wolfstack_view_lxc', 'wolfstack_view_vm',
     'wolfstack_tasklog_closed', 'wolfstack_hidden_features',
     'wolfstack_list_sort',
+    'wolfstack_default_node', 'wolfstack_favourite_containers',
 ];
 let _prefsLoaded = false;
 
@@ -76,6 +77,7 @@ async function loadUserPreferences() {
         }
         // Adopt the server-synced list sort (Docker/LXC/VM) on a fresh browser.
         if (typeof refreshListSortFromPref === 'function') refreshListSortFromPref();
+        maybeOpenDefaultNode();
     } catch (e) { /* silent — localStorage fallback works */ }
 }
 
@@ -116,6 +118,67 @@ function removePref(key) {
     }
 }
 
+// ─── Default node & favourite containers ───
+// Both are synced prefs (also exposed typed via /api/preferences), so the
+// start page and starred containers follow the user across browsers.
+
+// Open the user's default node once per page load — only when nothing else
+// was asked for (no #hash deep link) and they're still on the landing page.
+// Needs both the synced prefs and the node list, whichever arrives last.
+let _defaultNodeHandled = !!location.hash;
+function maybeOpenDefaultNode() {
+    if (_defaultNodeHandled || !_prefsLoaded || !Array.isArray(allNodes) || !allNodes.length) return;
+    _defaultNodeHandled = true;
+    const id = localStorage.getItem('wolfstack_default_node');
+    if (!id || currentPage !== 'datacenter' || currentNodeId) return;
+    if (allNodes.some(n => n.id === id)) selectServerView(id, 'dashboard');
+}
+
+function setDefaultNode(id) {
+    if (id) savePref('wolfstack_default_node', id);
+    else removePref('wolfstack_default_node');
+}
+
+function loadFavouriteContainers() {
+    try {
+        const list = JSON.parse(localStorage.getItem('wolfstack_favourite_containers') || '[]');
+        return Array.isArray(list) ? list : [];
+    } catch { return []; }
+}
+
+// A favourite matches on runtime + name and on the node, by id where the
+// list carries one and by hostname otherwise (the cluster-wide container
+// list only has node_hostname).
+function isFavouriteContainer(runtime, name, nodeId, nodeHostname) {
+    return loadFavouriteContainers().some(f => f.runtime === runtime && f.name === name
+        && ((nodeId && f.node_id === nodeId) || (nodeHostname && f.node_hostname === nodeHostname)));
+}
+
+function toggleFavouriteContainer(runtime, name, btn) {
+    const node = allNodes.find(n => n.id === currentNodeId);
+    const nodeId = currentNodeId || (allNodes.find(n => n.is_self) || {}).id || '';
+    const hostname = node ? node.hostname : '';
+    let list = loadFavouriteContainers();
+    const on = !isFavouriteContainer(runtime, name, nodeId, hostname);
+    list = list.filter(f => !(f.runtime === runtime && f.name === name && f.node_id === nodeId));
+    if (on) list.push({ node_id: nodeId, node_hostname: hostname, runtime, name });
+    savePref('wolfstack_favourite_containers', JSON.stringify(list));
+    if (btn) {
+        btn.textContent = on ? '★' : '☆';
+        btn.title = on ? 'Remove from favourites' : 'Add to favourites';
+        btn.style.color = on ? 'var(--warning)' : 'var(--text-muted)';
+    }
+}
+
+// Star toggle shown next to a container name in the Docker/LXC tables.
+function favouriteStarHtml(runtime, name) {
+    const node = allNodes.find(n => n.id === currentNodeId);
+    const on = isFavouriteContainer(runtime, name, currentNodeId, node ? node.hostname : '');
+    return `<button type="button" class="btn-link" style="background:none;border:none;padding:0 0 0 6px;cursor:pointer;font-size:14px;color:${on ? 'var(--warning)' : 'var(--text-muted)'};"
+        title="${on ? 'Remove from favourites' : 'Add to favourites'}"
+        onclick="event.stopPropagation(); toggleFavouriteContainer('${runtime}', '${escapeAttr(name)}', this)">${on ? '★' : '☆'}</button>`;
+}
+
 // ─── State ───
 let currentPage = 'datacenter';
 let currentComponent = null;
@@ -1214,7 +1277,7 @@ function showDangerConfirm(opts) {
 }
 
 /// Styled prompt dialog — returns a Promise<string|null>
-function showPrompt(message, title, defaultValue) {
+function showPrompt(message, title, defaultValue, inputType) {
     title = title || 'Input';
     return new Promise(function (resolve) {
         var overlay = document.createElement('div');
@@ -1228,7 +1291,7 @@ function showPrompt(message, title, defaultValue) {
         body.style.cssText = 'font-size:13px;line-height:1.6;color:var(--text-secondary,#a1a1aa);margin-bottom:12px';
         body.textContent = message;
         var input = document.createElement('input');
-        input.type = 'text';
+        input.type = inputType || 'text';
         input.className = 'form-control';
         input.value = defaultValue || '';
         input.style.cssText = 'width:100%;box-sizing:border-box;';
@@ -1261,6 +1324,48 @@ function showPrompt(message, title, defaultValue) {
     });
 }
 
+// Sensitive actions (removing a node, managing users) answer 403 with
+// reauth_required once this session's password is older than the session
+// policy's re-auth window. Ask for it, confirm via /api/auth/reauth, then
+// replay the original request. Concurrent refusals share one prompt.
+(function () {
+    const origFetch = window.fetch;
+    let pending = null;
+
+    async function confirmPassword() {
+        const password = await showPrompt('Enter your password to continue.', 'Confirm it\'s you', '', 'password');
+        if (!password) return false;
+        const body = { password, totp_code: '' };
+        for (;;) {
+            const r = await origFetch('/api/auth/reauth', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify(body),
+            });
+            const d = await r.json().catch(() => ({}));
+            if (r.ok && d.success) return true;
+            if (r.ok && d.requires_2fa && !body.totp_code) {
+                const code = await showPrompt('Enter your two-factor authentication code.', 'Confirm it\'s you', '');
+                if (!code) return false;
+                body.totp_code = code.trim();
+                continue;
+            }
+            showToast(d.error || 'Could not confirm your password', 'error');
+            return false;
+        }
+    }
+
+    window.fetch = async function (input, init) {
+        const resp = await origFetch.call(this, input, init);
+        // A Request object's body is already consumed — can't replay it.
+        if (resp.status !== 403 || input instanceof Request) return resp;
+        const d = await resp.clone().json().catch(() => null);
+        if (!d || !d.reauth_required) return resp;
+        if (!pending) pending = confirmPassword().finally(() => { pending = null; });
+        return (await pending) ? origFetch.call(this, input, init) : resp;
+    };
+})();
+
 // ─── Page Loading Overlay (modern blur + spinner) ───
 function showPageLoadingOverlay(pageEl) {
     // Remove any existing overlay first
@@ -1929,6 +2034,7 @@ function mountTrustFooter() {
         + '<span><strong style="color:var(--text-secondary,#cbd5e1);">WolfStack</strong> '
         + '<span id="ws-trust-version">…</span></span>'
         + '<span id="ws-trust-user" style="display:none;">as <strong style="color:var(--text-secondary,#cbd5e1);" id="ws-trust-user-name"></strong></span>'
+        + '<span id="ws-trust-readonly" style="display:none;color:var(--warning,#f59e0b);font-weight:600;">🔒 Read-only</span>'
         + '</div>'
         + '<div id="ws-trust-right" style="display:flex;align-items:center;gap:14px;flex-wrap:wrap;">'
         + '<span id="ws-trust-checked" style="display:none;">checked <span id="ws-trust-checked-time">…</span></span>'
@@ -1988,6 +2094,12 @@ async function refreshTrustFooter() {
         const r = await fetch('/api/auth/check');
         if (r.ok) {
             const d = await r.json();
+            // read_only carries the message when the node refuses writes.
+            const ro = document.getElementById('ws-trust-readonly');
+            if (ro && d.read_only) {
+                ro.title = d.read_only;
+                ro.style.display = '';
+            }
             if (d.authenticated && d.username) {
                 const wrap = document.getElementById('ws-trust-user');
                 const name = document.getElementById('ws-trust-user-name');
@@ -2140,6 +2252,7 @@ function selectView(page) {
         && !dashLeaveGuard(() => selectView(page))) return;
     closeSidebarMobile();
     if (typeof fleetLogsStopTail === 'function') fleetLogsStopTail();
+    stopVmEvents();
     // Close the embedded inbox terminal when leaving the inbox so the
     // WebSocket and xterm don't stay live in a hidden pane.
     if (currentPage === 'inbox' && page !== 'inbox' && typeof predTermClose === 'function') {
@@ -2340,6 +2453,7 @@ function selectServerView(nodeId, view) {
     if (containerPollTimer) { clearInterval(containerPollTimer); containerPollTimer = null; }
     if (_procPollTimer) { clearInterval(_procPollTimer); _procPollTimer = null; }
     if (_svcPollTimer) { clearInterval(_svcPollTimer); _svcPollTimer = null; }
+    stopVmEvents();
     if (view === 'dashboard') {
         // Clear history for new server view to show fresh data
         cpuHistory = [];
@@ -2387,11 +2501,11 @@ function selectServerView(nodeId, view) {
             openInlineTerminal('host', hostname);
         }
     }
-    if (view === 'vms') loadVms().finally(() => hidePageLoadingOverlay(el));
+    if (view === 'vms') { loadVms().finally(() => hidePageLoadingOverlay(el)); startVmEvents(); }
     if (view === 'storage') Promise.all([loadStorageProviders(), loadStorageMounts(), loadZfsStatus(), loadDiskInfo(), loadGlusterStatus()]).finally(() => hidePageLoadingOverlay(el));
     if (view === 'shares') { _gwClusterMode = null; gwLoad().finally(() => hidePageLoadingOverlay(el)); }
     if (view === 'syslogs') { loadSystemLogs(); hidePageLoadingOverlay(el); }
-    if (view === 'files') { if (!window._skipFileReset) { containerFileMode = null; currentFilePath = '/'; } window._skipFileReset = false; loadFiles().finally(() => hidePageLoadingOverlay(el)); }
+    if (view === 'files') { if (!window._skipFileReset) { containerFileMode = null; currentFilePath = '/'; } window._skipFileReset = false; loadFiles().finally(() => hidePageLoadingOverlay(el)); populateFileManagerTargets(); }
     if (view === 'networking') loadNetworking().finally(() => hidePageLoadingOverlay(el));
     if (view === 'backups') loadBackups().finally(() => hidePageLoadingOverlay(el));
     if (view === 'wolfnet') loadWolfNet().finally(() => hidePageLoadingOverlay(el));
@@ -4224,6 +4338,59 @@ function buildServerTree(nodes) {
 
     // Re-apply search filter if active
     filterSidebarNodes();
+    refreshPveClusterBadges(wsClusters);
+}
+
+// ─── PVE cluster rollups (sidebar badge) ───
+// Totals come from /api/pve/clusters, which the backend builds from each
+// PVE cluster's own /cluster/resources and caches — nothing is added up here.
+let pveClusterSummaries = [];
+let pveClusterSummariesAt = 0;
+
+function pveClusterTooltip(s) {
+    if (s.error) return `Proxmox: ${s.error}`;
+    const pct = (used, total) => total ? Math.round(used / total * 100) + '%' : '—';
+    const lines = [
+        `Proxmox cluster ${s.name}`,
+        `Nodes: ${s.nodes_online}/${s.nodes_total} online`,
+        `CPU: ${s.cores_used.toFixed(1)} of ${s.cores_total} cores busy`,
+        `RAM: ${formatBytes(s.mem_used)} / ${formatBytes(s.mem_total)} (${pct(s.mem_used, s.mem_total)})`,
+        `Storage: ${formatBytes(s.storage_used)} / ${formatBytes(s.storage_total)} (${pct(s.storage_used, s.storage_total)})`,
+        `VMs: ${s.vms_running}/${s.vms_total} running · CTs: ${s.cts_running}/${s.cts_total} running`,
+    ];
+    if (s.ha.quorate !== null && s.ha.quorate !== undefined) {
+        lines.push(`HA: ${s.ha.quorate ? 'quorate' : 'NOT quorate'}, ${s.ha.started}/${s.ha.services} services started`
+            + (s.ha.problems.length ? ` — problems: ${s.ha.problems.join(', ')}` : ''));
+    }
+    return lines.join('\n');
+}
+
+async function refreshPveClusterBadges(wsClusters) {
+    if (!allNodes.some(n => n.pve_node_name)) return;
+    if (Date.now() - pveClusterSummariesAt > 30000) {
+        pveClusterSummariesAt = Date.now();
+        try {
+            const resp = await fetch('/api/pve/clusters');
+            if (resp.ok) pveClusterSummaries = await resp.json();
+        } catch (e) { /* keep the last rollup */ }
+    }
+    Object.entries(wsClusters).forEach(([clusterName, members]) => {
+        const clusterId = 'cluster-' + clusterName.replace(/[^a-z0-9]/gi, '-');
+        const header = document.querySelector(`.server-node-header[data-cluster-id="${clusterId}"]`);
+        if (!header) return;
+        const ids = new Set(members.map(n => n.id));
+        const mine = pveClusterSummaries.filter(s => s.member_ids.some(id => ids.has(id)));
+        header.querySelectorAll('.pve-cluster-badge').forEach(el => el.remove());
+        if (!mine.length) return;
+        const bad = mine.some(s => s.error || s.ha.quorate === false || s.ha.problems.length || s.nodes_online < s.nodes_total);
+        const badge = document.createElement('span');
+        badge.className = 'pve-cluster-badge';
+        badge.textContent = 'PVE';
+        badge.title = mine.map(pveClusterTooltip).join('\n\n');
+        badge.style.cssText = `flex-shrink:0;font-size:9px;padding:1px 5px;border-radius:3px;background:${bad ? 'rgba(239,68,68,0.15)' : 'rgba(59, 130, 246,0.15)'};color:${bad ? 'var(--danger)' : 'var(--accent-light)'};`;
+        const settings = header.querySelector('.remove-server-btn');
+        header.insertBefore(badge, settings);
+    });
 }
 
 function toggleServerNode(nodeId) {
@@ -4895,7 +5062,10 @@ async function dashRefreshContainers(body, w) {
     const total = list.length;
     const running = list.filter(c => c.state === 'running').length;
     if (cfg.state === 'running') list = list.filter(c => c.state === 'running');
+    const fav = (c) => isFavouriteContainer(c.runtime, c.name, c.node_id, c.node_hostname);
+    if (cfg.state === 'favourites') list = list.filter(fav);
     list.sort((a, b) => {
+        if (fav(a) !== fav(b)) return fav(a) ? -1 : 1;
         if ((a.state === 'running') !== (b.state === 'running')) return a.state === 'running' ? -1 : 1;
         return (a.name || '').localeCompare(b.name || '');
     });
@@ -4913,7 +5083,7 @@ async function dashRefreshContainers(body, w) {
     };
     const rows = shown.map(c => `<div style="display:flex;align-items:center;gap:8px;padding:5px 0;border-bottom:1px solid var(--border);font-size:12px;">
         ${dot(c.state)}
-        <span style="font-weight:500;white-space:nowrap;overflow:hidden;text-overflow:ellipsis;min-width:0;" title="${escapeAttr(c.image || c.name || '')}">${escapeHtml(c.name || '?')}</span>
+        <span style="font-weight:500;white-space:nowrap;overflow:hidden;text-overflow:ellipsis;min-width:0;" title="${escapeAttr(c.image || c.name || '')}">${escapeHtml(c.name || '?')}</span>${fav(c) ? '<span style="color:var(--warning);flex-shrink:0;" title="Favourite">★</span>' : ''}
         <span style="font-size:9px;padding:1px 5px;border-radius:3px;background:var(--bg-tertiary);color:var(--text-muted);text-transform:uppercase;flex-shrink:0;">${escapeHtml(c.runtime || '?')}</span>
         <span style="margin-left:auto;font-size:11px;color:var(--text-muted);flex-shrink:0;">${escapeHtml(c.node_hostname || '')}</span>
     </div>`).join('');
@@ -4936,6 +5106,7 @@ function dashContainersConfigForm(w) {
         <select id="dashcfg-ct-state" class="form-control">
             <option value="all"${sel('all', cfg.state)}>All containers</option>
             <option value="running"${sel('running', cfg.state)}>Running only</option>
+            <option value="favourites"${sel('favourites', cfg.state)}>Favourites only</option>
         </select>
         <label class="dash-cfg-label">Max rows</label>
         <input type="number" id="dashcfg-ct-max" class="form-control" min="5" max="100" value="${cfg.max || 30}">`;
@@ -7516,6 +7687,10 @@ async function fetchTopProcesses() {
         renderProcessTable('top-cpu-table', data.top_cpu, 'cpu');
         renderProcessTable('top-mem-table', data.top_mem, 'mem');
     } catch(e) { /* silent */ }
+    try {
+        const cacheResp = await fetch(apiUrl('/api/metrics/cache-services'));
+        if (cacheResp.ok) renderCacheServices((await cacheResp.json()).instances || []);
+    } catch(e) { /* silent */ }
     // Also fetch systemd services in the same cycle
     try {
         var svcResp = await fetch(apiUrl('/api/systemd'));
@@ -7527,6 +7702,26 @@ async function fetchTopProcesses() {
     } catch(e) { /* silent */ }
 }
 
+function renderCacheServices(instances) {
+    const section = document.getElementById('cache-services-section');
+    const tbody = document.getElementById('cache-services-table');
+    if (!section || !tbody) return;
+    section.style.display = instances.length ? '' : 'none';
+    tbody.innerHTML = instances.map(i => {
+        const name = (i.kind === 'redis' ? 'Redis' : 'Memcached') + ' :' + i.port + (i.version ? ` <span style="color:var(--text-muted);">v${escapeHtml(i.version)}</span>` : '');
+        if (!i.reachable) {
+            return `<tr><td>${name}</td><td colspan="5" style="color:#ef4444;">${escapeHtml(i.error || 'unreachable')}</td></tr>`;
+        }
+        const mem = formatBytes(i.used_memory_bytes) + (i.max_memory_bytes ? ' / ' + formatBytes(i.max_memory_bytes) : '');
+        const hit = i.hit_rate_percent == null ? '—' : i.hit_rate_percent.toFixed(1) + '%';
+        const rate = i.evictions_per_sec == null ? '' : ` (${i.evictions_per_sec.toFixed(1)}/s)`;
+        const evColor = (i.evictions_per_sec || 0) >= 50 ? ' style="color:#f59e0b;"' : '';
+        const frag = i.fragmentation_ratio == null ? '—' : i.fragmentation_ratio.toFixed(2);
+        const fragColor = (i.fragmentation_ratio || 0) > 1.5 ? ' style="color:#f59e0b;"' : '';
+        return `<tr><td>${name}</td><td>${mem}</td><td>${hit}</td><td>${i.connected_clients}</td><td${evColor}>${i.evictions}${rate}</td><td${fragColor}>${frag}</td></tr>`;
+    }).join('');
+}
+
 function renderProcessTable(tableId, procs, type) {
     const tbody = document.getElementById(tableId);
     if (!tbody || !procs) return;
@@ -8343,6 +8538,8 @@ async function fetchNodes() {
 
         // Update 3D topology if viewing
         if (typeof topologyCheckUpdate === 'function') topologyCheckUpdate();
+
+        maybeOpenDefaultNode();
     } catch (e) {
         // A single node-fetch failure is NOT a connection-loss signal — this
         // endpoint aggregates cluster/WolfNet state and blips transiently even
@@ -8370,11 +8567,42 @@ async function loadComponents() {
         const components = await resp.json();
         renderComponents(components);
         renderServices(components);
+        loadComponentVersionMatrix();
     } catch (e) {
         console.error('Failed to load components:', e);
     }
 }
 
+// Cluster-wide: served by this node from the versions its peers gossip.
+async function loadComponentVersionMatrix() {
+    const el = document.getElementById('components-version-matrix');
+    if (!el) return;
+    try {
+        const resp = await fetch('/api/components/versions');
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        const nodes = data.nodes || [];
+        // Only columns something is installed for.
+        const cols = (data.components || []).filter(c => nodes.some(n => c in (n.versions || {})));
+        if (!nodes.length || !cols.length) {
+            el.innerHTML = '<p style="color:var(--text-muted);font-size:13px;margin:0;">No component versions reported yet.</p>';
+            return;
+        }
+        const warn = (data.issues || []).map(i => `<div style="color:var(--warning);font-size:12px;margin-bottom:8px;">⚠ <strong>${escapeHtml(i.title)}</strong> — ${escapeHtml(i.detail)}</div>`).join('');
+        el.innerHTML = warn + `<table class="data-table" style="font-size:12px;">
+            <thead><tr><th>Node</th>${cols.map(c => `<th>${escapeHtml(componentDisplayNames[c] || c)}</th>`).join('')}</tr></thead>
+            <tbody>${nodes.map(n => `<tr style="${n.online ? '' : 'opacity:0.5;'}">
+                <td>${escapeHtml(n.hostname)}${n.online ? '' : ' <span style="color:var(--text-muted);">(offline)</span>'}</td>
+                ${cols.map(c => {
+                    const v = (n.versions || {})[c];
+                    return `<td style="font-family:monospace;">${v === undefined ? '<span style="color:var(--text-muted);">—</span>' : escapeHtml(v || '?')}</td>`;
+                }).join('')}
+            </tr>`).join('')}</tbody></table>`;
+    } catch (e) {
+        el.innerHTML = '<p style="color:var(--danger);font-size:13px;margin:0;">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
 const componentIcons = {
     wolfnet: '', wolfproxy: '', wolfserve: '',
     wolfdisk: '', wolfscale: '', mariadb: '', certbot: ''
@@ -8580,6 +8808,25 @@ async function loadVms() {
     }
 }
 
+// Live VM state: libvirt lifecycle events over SSE trigger a list refresh,
+// so a VM started from virsh or crashing shows up without a manual reload.
+// Debounced — a reboot fires several events in a row.
+let _vmEvents = null;
+let _vmEventsTimer = null;
+function startVmEvents() {
+    stopVmEvents();
+    if (typeof EventSource === 'undefined') return;
+    _vmEvents = new EventSource(apiUrl('/api/vms/events'));
+    _vmEvents.addEventListener('vm', () => {
+        clearTimeout(_vmEventsTimer);
+        _vmEventsTimer = setTimeout(() => { if (currentPage === 'vms') loadVms(); }, 500);
+    });
+}
+function stopVmEvents() {
+    if (_vmEvents) { _vmEvents.close(); _vmEvents = null; }
+    clearTimeout(_vmEventsTimer);
+}
+
 function renderVms(vms) {
     _lastVms = vms;
     vms = sortList('vm', vms);
@@ -8645,7 +8892,7 @@ function renderVms(vms) {
 
         return `
             <tr data-name="${escapeAttr(vm.name)}">
-                <td><strong>${escapeHtml(vm.name)}</strong>${vm.iso_path ? `<br><small style="color:var(--text-muted);">${escapeHtml(vm.iso_path.split('/').pop())}</small>` : ''}</td>
+                <td><strong>${escapeHtml(vm.name)}</strong>${vmWindowsBadge(vm)}${vm.iso_path ? `<br><small style="color:var(--text-muted);">${escapeHtml(vm.iso_path.split('/').pop())}</small>` : ''}</td>
                 <td><span style="color:${statusColor}">● ${statusText}</span></td>
                 <td>${vm.cpus} vCPU / ${vm.memory_mb} MB</td>
                 <td>${wolfnetIp !== '—' ? `<span class="badge" style="background:var(--accent-bg); color:var(--accent);">${wolfnetIp}</span>` : '—'}</td>
@@ -8703,10 +8950,11 @@ function renderVms(vms) {
                         const serialBtn = vm.running
                             ? `<button class="btn btn-sm" style="${baseStyle}" onclick="openVmConsole('${vm.name}')" title="Serial terminal (guest must have serial console enabled)"><span class="ws-icon-clean-wrap" data-icon="terminal"></span></button>`
                             : '';
+                        const rdpBtn = vmRdpButton(vm, vm.running ? baseStyle : disabledStyle);
                         // Stable order regardless of state so the same icon
                         // sits in the same column slot whether the VM is up
                         // or down — operator muscle memory survives a reboot.
-                        return `${vncBtn} ${serialBtn} ${settingsBtn} ${backupBtn} ${cloneBtn} ${migrateBtn} ${diskMigrateBtn} ${powerBtn} ${deleteBtn}`;
+                        return `${vncBtn} ${serialBtn} ${rdpBtn} ${settingsBtn} ${backupBtn} ${cloneBtn} ${migrateBtn} ${diskMigrateBtn} ${powerBtn} ${deleteBtn}`;
                     })()}
                 </div></td>
             </tr>${storageSubRow}
@@ -10145,6 +10393,25 @@ function renderStorageMounts(mounts) {
             : isError
                 ? `<span class="badge" style="background:#ef4444; color:#fff; font-size:11px;" title="${m.error_message || ''}">Error</span>`
                 : '<span class="badge" style="background:var(--bg-tertiary); color:var(--text-muted); font-size:11px;">○ Unmounted</span>';
+        // The mount watchdog found it stale and is remounting it.
+        const health = m.health && m.health.state === 'degraded'
+            ? `<span class="badge" style="background:#f59e0b; color:#fff; font-size:11px; margin-left:4px;" title="${escapeHtml((m.health.error || '') + ' — ' + m.health.remount_attempts + ' remount attempt(s)')}">⚠ Degraded</span>`
+            : '';
+
+        // Capacity bar for mounted entries; red once over the usage quota.
+        let usageHtml = '';
+        if (m.usage && m.usage.total_bytes > 0) {
+            const pct = m.usage.percent;
+            const over = m.quota_percent && pct >= m.quota_percent;
+            const color = over ? '#ef4444' : pct > 80 ? '#f59e0b' : 'var(--success)';
+            const quota = m.quota_percent ? ` / quota ${m.quota_percent}%` : '';
+            usageHtml = `<div style="margin-top:4px; width:120px;" title="${formatBytes(m.usage.used_bytes)} of ${formatBytes(m.usage.total_bytes)}">
+                <div style="height:4px; background:var(--bg-tertiary); border-radius:2px; overflow:hidden;"><div style="height:100%; width:${Math.min(pct, 100)}%; background:${color};"></div></div>
+                <div style="font-size:10px; color:${over ? '#ef4444' : 'var(--text-muted)'};">${pct.toFixed(0)}% used${quota}</div>
+            </div>`;
+        } else if (m.quota_percent) {
+            usageHtml = `<div style="font-size:10px; color:var(--text-muted); margin-top:4px;">Quota ${m.quota_percent}%</div>`;
+        }
 
         const globalBadge = m.global
             ? '<span class="badge" style="background:rgba(59,130,246,0.15); color:#60a5fa; font-size:10px; margin-left:4px;">Global</span>'
@@ -10161,6 +10428,10 @@ function renderStorageMounts(mounts) {
             ? `<button class="btn btn-sm" style="background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border); font-size:11px; padding:2px 8px;" onclick="syncStorageMount('${m.id}')" title="Sync to all cluster nodes">Sync</button>`
             : '';
 
+        const browseBtn = m.type === 's3'
+            ? `<button class="btn btn-sm" style="background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border); font-size:11px; padding:2px 8px;" onclick="openS3Browser('${m.id}')" title="Browse objects without mounting">Browse</button>`
+            : '';
+
         // Source display — show bucket prominently for S3
         let sourceDisplay = m.source;
         if (m.type === 's3' && m.s3_config) {
@@ -10175,13 +10446,14 @@ function renderStorageMounts(mounts) {
             <td>${typeLabel}</td>
             <td style="font-size:12px; max-width:240px; overflow:hidden; text-overflow:ellipsis;" title="${m.source}">${sourceDisplay}</td>
             <td style="font-family:var(--font-mono); font-size:12px; max-width:200px; overflow:hidden; text-overflow:ellipsis; white-space:nowrap;" title="${m.mount_point}">${m.mount_point}</td>
-            <td>${statusBadge}</td>
+            <td>${statusBadge}${health}${usageHtml}</td>
             <td>${globalBadge}${autoBadge}</td>
             <td style="white-space:nowrap;">
                 <button class="btn btn-sm" style="background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border); font-size:11px; padding:2px 8px;" onclick="openEditMount('${m.id}')" title="Settings"><span class="ws-icon-clean-wrap" data-icon="settings"></span></button>
                 <button class="btn btn-sm" style="background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border); font-size:11px; padding:2px 8px;" onclick="duplicateStorageMount('${m.id}')" title="Duplicate"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button>
                 ${mountBtn}
                 ${syncBtn}
+                ${browseBtn}
                 <button class="btn btn-sm btn-danger" style="font-size:11px; padding:2px 8px;" onclick="deleteStorageMount('${m.id}', '${m.name}')"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
             </td>
         </tr>`;
@@ -10502,6 +10774,105 @@ async function deleteStorageMount(id, name) {
     }
 }
 
+// ─── S3 Object Browser ───
+
+let _s3BrowseMount = null;
+
+function openS3Browser(id) {
+    _s3BrowseMount = id;
+    showModal('<div id="s3-browser-body"></div>', 'S3 Objects', { noOk: false });
+    loadS3Objects('');
+}
+
+function s3ObjectsUrl(suffix, params) {
+    return apiUrl(`/api/storage/mounts/${encodeURIComponent(_s3BrowseMount)}/objects${suffix}?` + new URLSearchParams(params));
+}
+
+async function loadS3Objects(prefix) {
+    const body = document.getElementById('s3-browser-body');
+    if (!body) return;
+    body.dataset.prefix = prefix;
+    body.innerHTML = '<div class="spinner-sm"></div> Listing...';
+    try {
+        const resp = await fetch(s3ObjectsUrl('', { prefix }));
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'List failed');
+        const parent = prefix ? prefix.replace(/[^/]*\/$/, '') : null;
+        const row = 'display:flex; align-items:center; gap:8px; padding:4px 0; border-top:1px solid var(--border); font-size:12px;';
+        body.innerHTML = `
+            <div style="display:flex; align-items:center; gap:8px; margin-bottom:8px;">
+                <code style="flex:1; overflow:hidden; text-overflow:ellipsis;">${escapeHtml(data.bucket)}/${escapeHtml(prefix)}</code>
+                <label class="btn btn-sm" style="cursor:pointer;">Upload<input type="file" style="display:none;" onchange="uploadS3Object(this.files[0])"></label>
+            </div>
+            ${parent !== null ? `<div style="${row} cursor:pointer;" data-prefix="${escapeHtml(parent)}" onclick="loadS3Objects(this.dataset.prefix)">⬆ ..</div>` : ''}
+            ${data.prefixes.map(p => `<div style="${row} cursor:pointer;" data-prefix="${escapeHtml(p)}" onclick="loadS3Objects(this.dataset.prefix)">📁 ${escapeHtml(p.slice(prefix.length))}</div>`).join('')}
+            ${data.objects.map(o => `<div style="${row}" data-key="${escapeHtml(o.key)}">
+                <span style="flex:1; overflow:hidden; text-overflow:ellipsis;" title="${escapeHtml(o.last_modified)}">${escapeHtml(o.key.slice(prefix.length))}</span>
+                <span style="color:var(--text-muted);">${formatBytes(o.size)}</span>
+                <button class="btn btn-sm" onclick="downloadS3Object(this.parentElement.dataset.key)">Download</button>
+                <button class="btn btn-sm" onclick="presignS3Object(this.parentElement.dataset.key)">Link</button>
+                <button class="btn btn-sm btn-danger" onclick="deleteS3Object(this.parentElement.dataset.key)">✕</button>
+            </div>`).join('')}
+            ${!data.prefixes.length && !data.objects.length ? '<div style="color:var(--text-muted); font-size:12px;">Empty</div>' : ''}`;
+    } catch (e) {
+        body.innerHTML = `<div style="color:#ef4444;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+function s3CurrentPrefix() {
+    return (document.getElementById('s3-browser-body') || {}).dataset?.prefix || '';
+}
+
+function downloadS3Object(key) {
+    const a = document.createElement('a');
+    a.href = s3ObjectsUrl('/download', { key });
+    a.download = key.split('/').pop();
+    a.click();
+}
+
+async function uploadS3Object(file) {
+    if (!file) return;
+    const key = s3CurrentPrefix() + file.name;
+    showToast(`Uploading ${file.name}...`, 'info');
+    try {
+        const resp = await fetch(s3ObjectsUrl('', { key }), { method: 'PUT', body: file });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Upload failed');
+        showToast(data.message, 'success');
+        loadS3Objects(s3CurrentPrefix());
+    } catch (e) {
+        showToast('Upload failed: ' + e.message, 'error');
+    }
+}
+
+async function deleteS3Object(key) {
+    if (!confirm(`Delete ${key}? This cannot be undone.`)) return;
+    try {
+        const resp = await fetch(s3ObjectsUrl('', { key }), { method: 'DELETE' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Delete failed');
+        loadS3Objects(s3CurrentPrefix());
+    } catch (e) {
+        showToast('Delete failed: ' + e.message, 'error');
+    }
+}
+
+async function presignS3Object(key) {
+    try {
+        const resp = await fetch(apiUrl(`/api/storage/mounts/${encodeURIComponent(_s3BrowseMount)}/objects/presign`), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ key, method: 'get', expires_secs: 3600 }),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Presign failed');
+        try { await navigator.clipboard.writeText(data.url); showToast('Download link (valid 1 hour) copied', 'success'); }
+        catch { prompt('Download link (valid 1 hour):', data.url); }
+    } catch (e) {
+        showToast(e.message, 'error');
+    }
+}
+
 async function syncStorageMount(id) {
     try {
         const resp = await fetch(apiUrl(`/api/storage/mounts/${id}/sync`), { method: 'POST' });
@@ -10543,6 +10914,9 @@ function openEditMount(id) {
     document.getElementById('edit-mount-point').value = m.mount_point;
     document.getElementById('edit-mount-global').checked = !!m.global;
     document.getElementById('edit-mount-auto').checked = !!m.auto_mount;
+    document.getElementById('edit-mount-quota').value = m.quota_percent || '';
+    setIoLimitFields('edit-mount-io', m.io_limits);
+    document.getElementById('edit-mount-io-group').style.display = m.type === 's3' ? '' : 'none';
 
     // Hide all type-specific sections
     document.getElementById('edit-s3-fields').style.display = 'none';
@@ -10602,7 +10976,14 @@ async function saveStorageMountEdit() {
 
     if (!name) return showModal('Name is required');
 
-    const payload = { name, mount_point, global, auto_mount };
+    const quotaStr = document.getElementById('edit-mount-quota').value.trim();
+    const quota_percent = quotaStr ? parseInt(quotaStr, 10) : null;
+    if (quota_percent !== null && (isNaN(quota_percent) || quota_percent < 1 || quota_percent > 100)) {
+        return showToast('Usage alert must be a percentage between 1 and 100', 'error');
+    }
+
+    const payload = { name, mount_point, global, auto_mount, quota_percent };
+    if (type === 's3') payload.io_limits = readIoLimitFields('edit-mount-io');
 
     if (type === 's3') {
         const secretVal = document.getElementById('edit-s3-secret-key').value;
@@ -10701,6 +11082,75 @@ function browseContainerFiles(type, name, storagePath) {
     selectServerView(currentNodeId, 'files');
 }
 
+// ─── File manager target pickers ───
+// Node picker: every WolfStack node; switching re-opens the page on that
+// node, and apiUrl() routes the file API through node_proxy.
+// Location picker: the host filesystem, named Docker volumes and LXC
+// root filesystems (both browsed as host paths, so they work on stopped
+// containers), or the inside of a running Docker container.
+async function populateFileManagerTargets() {
+    const nodeSel = document.getElementById('file-node-select');
+    const locSel = document.getElementById('file-location-select');
+    if (nodeSel) {
+        const nodes = (allNodes || []).filter(n => n.node_type !== 'proxmox');
+        nodeSel.innerHTML = nodes.map(n =>
+            `<option value="${escapeHtml(n.id)}"${n.id === currentNodeId ? ' selected' : ''}${n.online === false ? ' disabled' : ''}>${escapeHtml(n.hostname || n.address || n.id)}${n.online === false ? ' (offline)' : ''}</option>`
+        ).join('');
+        nodeSel.style.display = nodes.length > 1 ? '' : 'none';
+    }
+    if (!locSel) return;
+    locSel.innerHTML = '<option value="host">Host filesystem</option>';
+    const [volumes, lxc, docker] = await Promise.all([
+        fetch(apiUrl('/api/files/docker/volumes')).then(r => r.ok ? r.json() : []).catch(() => []),
+        fetch(apiUrl('/api/containers/lxc')).then(r => r.ok ? r.json() : []).catch(() => []),
+        fetch(apiUrl('/api/containers/docker')).then(r => r.ok ? r.json() : []).catch(() => []),
+    ]);
+    const group = (label, items) => items.length ? `<optgroup label="${label}">${items.join('')}</optgroup>` : '';
+    locSel.innerHTML += group('Docker volumes', (Array.isArray(volumes) ? volumes : []).map(v =>
+        `<option value="vol:${escapeHtml(v.mountpoint)}">${escapeHtml(v.name)}</option>`));
+    locSel.innerHTML += group('LXC root filesystems', (Array.isArray(lxc) ? lxc : []).map(c =>
+        `<option value="lxcfs:${escapeHtml(c.name)}">${escapeHtml(c.name)}${c.state === 'running' ? '' : ' (stopped)'}</option>`));
+    locSel.innerHTML += group('Inside Docker containers', (Array.isArray(docker) ? docker : [])
+        .filter(c => c.state === 'running')
+        .map(c => `<option value="docker:${escapeHtml(c.name)}">${escapeHtml(c.name)}</option>`));
+    locSel.value = containerFileMode ? `${containerFileMode.type}:${containerFileMode.name}` : 'host';
+    if (!locSel.value) locSel.value = 'host';
+}
+
+function fileManagerSwitchNode(nodeId) {
+    if (!nodeId || nodeId === currentNodeId) return;
+    containerFileMode = null;
+    currentFilePath = '/';
+    selectServerView(nodeId, 'files');
+}
+
+async function fileManagerSwitchLocation(value) {
+    const [kind, ...rest] = value.split(':');
+    const target = rest.join(':');
+    if (kind === 'docker') {
+        containerFileMode = { type: 'docker', name: target };
+        loadFiles('/');
+        return;
+    }
+    containerFileMode = null;
+    if (kind === 'vol') {
+        loadFiles(target);
+    } else if (kind === 'lxcfs') {
+        try {
+            const r = await fetch(apiUrl(`/api/files/lxc/rootfs?container=${encodeURIComponent(target)}`));
+            const data = await r.json();
+            if (!r.ok) throw new Error(data.error || `HTTP ${r.status}`);
+            showToast(`Browsing ${target}'s rootfs from the host — in unprivileged containers, new files show up as nobody:nogroup until chowned`, 'info');
+            loadFiles(data.path);
+        } catch (e) {
+            showToast(`Cannot open rootfs: ${e.message}`, 'error');
+            document.getElementById('file-location-select').value = 'host';
+        }
+    } else {
+        loadFiles('/');
+    }
+}
+
 // Common config-file paths we suggest in the editor datalist. Covers
 // the containers people actually edit from WolfStack often — AdGuard,
 // Nginx, Traefik, Mosquitto, Home Assistant, Unbound, Pi-hole, and
@@ -11008,6 +11458,7 @@ function renderFilteredFileList(entries, isSearch) {
             <td style="font-family:var(--font-mono);font-size:13px;cursor:pointer;color:var(--accent);" onclick="changePermissions('${e.path.replace(/'/g, "\\'")}')" title="Click to change permissions">${escapeHtml(e.permissions)}</td>
             <td style="white-space:nowrap;">
                 ${!e.is_dir ? `<button class="btn btn-sm" style="font-size:12px;padding:3px 8px;background:var(--bg-tertiary);color:var(--text-primary);border:1px solid var(--border);" onclick="downloadFile('${e.path.replace(/'/g, "\\'")}')">⬇️</button>` : ''}
+                ${e.is_dir && !containerFileMode ? `<button class="btn btn-sm" style="font-size:12px;padding:3px 8px;background:var(--bg-tertiary);color:var(--text-primary);border:1px solid var(--border);" onclick="downloadFolder('${e.path.replace(/'/g, "\\'")}')" title="Download as .tar.gz (shift-click for .zip)">⬇️</button>` : ''}
                 ${!e.is_dir && e.size < 2097152 ? `<button class="btn btn-sm" style="font-size:12px;padding:3px 8px;background:var(--bg-tertiary);color:var(--text-primary);border:1px solid var(--border);" onclick="editFile('${e.path.replace(/'/g, "\\'")}', '${e.name.replace(/'/g, "\\'")}')"><span class="ws-icon-clean-wrap" data-icon="edit"></span></button>` : ''}
                 <button class="btn btn-sm" style="font-size:12px;padding:3px 8px;background:var(--bg-tertiary);color:var(--text-primary);border:1px solid var(--border);" onclick="renameFile('${e.path.replace(/'/g, "\\'")}', '${e.name.replace(/'/g, "\\'")}')"><span class="ws-icon-clean-wrap" data-icon="edit"></span></button>
                 <button class="btn btn-sm" style="font-size:12px;padding:3px 8px;background:rgba(239,68,68,0.1);color:#ef4444;border:1px solid rgba(239,68,68,0.3);" onclick="deleteFile('${e.path.replace(/'/g, "\\'")}', '${e.name.replace(/'/g, "\\'")}')"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
@@ -11168,6 +11619,13 @@ function navigateUp() {
     navigateToDir(parent);
 }
 
+// Folders are archived on the fly by the server. tar.gz by default (tar
+// is everywhere); shift-click asks for a zip, which needs `zip` installed.
+function downloadFolder(path) {
+    const format = (window.event && window.event.shiftKey) ? 'zip' : 'tar.gz';
+    window.open(apiUrl(`/api/files/archive?path=${encodeURIComponent(path)}&format=${format}`), '_blank');
+}
+
 function downloadFile(path) {
     if (containerFileMode && containerFileMode.type === 'docker') {
         window.open(apiUrl(`/api/files/docker/download?container=${encodeURIComponent(containerFileMode.name)}&path=${encodeURIComponent(path)}`), '_blank');
@@ -11293,6 +11751,7 @@ function triggerFileUpload() {
 // ─── File Editor ───
 
 var _editFilePath = '';
+var _editFileModified = null;
 
 async function editFile(path, name) {
     const url = containerFileMode
@@ -11303,6 +11762,7 @@ async function editFile(path, name) {
         const data = await res.json();
         if (data.error) { showToast(`Cannot edit: ${data.error}`, 'error'); return; }
         _editFilePath = path;
+        _editFileModified = data.modified ?? null;
         document.getElementById('file-editor-title').textContent = name;
         document.getElementById('file-editor-path').textContent = path;
         document.getElementById('file-editor-content').value = data.content;
@@ -11312,7 +11772,7 @@ async function editFile(path, name) {
     }
 }
 
-async function saveFileEdit() {
+async function saveFileEdit(force, skipCheck) {
     const content = document.getElementById('file-editor-content').value;
     const url = containerFileMode
         ? apiUrl(`/api/files/${containerFileMode.type}/write`)
@@ -11320,6 +11780,8 @@ async function saveFileEdit() {
     const body = containerFileMode
         ? { container: containerFileMode.name, path: _editFilePath, content }
         : { path: _editFilePath, content };
+    if (!containerFileMode && !force && _editFileModified !== null) body.expected_modified = _editFileModified;
+    if (!containerFileMode && skipCheck) body.skip_check = true;
     try {
         const res = await fetch(url, {
             method: 'POST',
@@ -11327,6 +11789,18 @@ async function saveFileEdit() {
             body: JSON.stringify(body),
         });
         const data = await res.json();
+        if (res.status === 409) {
+            if (await showConfirm(`${_editFilePath} was changed on disk after you opened it.\n\nOverwrite it with your version?`)) {
+                return saveFileEdit(true);
+            }
+            return;
+        }
+        if (res.status === 422 && data.check_failed) {
+            if (await showConfirm(`${_editFilePath} failed its syntax check:\n\n${data.error}\n\nSaving it may stop the service from starting. Save anyway?`, 'Syntax check failed')) {
+                return saveFileEdit(force, true);
+            }
+            return;
+        }
         if (data.error) { showToast(`Save failed: ${data.error}`, 'error'); return; }
         showToast('File saved', 'success');
         document.getElementById('file-editor-modal').classList.remove('active');
@@ -11337,6 +11811,14 @@ async function saveFileEdit() {
 
 async function uploadFiles(files) {
     if (!files || files.length === 0) return;
+    // Host uploads go through the resumable chunk endpoint so multi-GB
+    // images survive a dropped connection; container uploads still use
+    // a single multipart request.
+    if (!containerFileMode) {
+        await uploadFilesChunked(Array.from(files));
+        document.getElementById('file-upload-input').value = '';
+        return;
+    }
     const formData = new FormData();
     let totalSize = 0;
     for (const file of files) {
@@ -11398,6 +11880,82 @@ async function uploadFiles(files) {
     document.getElementById('file-upload-input').value = '';
 }
 
+const UPLOAD_CHUNK_BYTES = 8 * 1024 * 1024;
+const UPLOAD_MAX_RETRIES = 5;
+
+async function uploadFilesChunked(files) {
+    const dir = currentFilePath;
+    let done = 0;
+    for (const file of files) {
+        const toastId = 'upload-progress-' + Date.now();
+        showToast(`Uploading ${file.name} (${formatFileSize(file.size)})...`, 'info', 0, toastId);
+        const toastEl = document.getElementById(toastId);
+        let bar;
+        if (toastEl) {
+            const barWrap = document.createElement('div');
+            barWrap.style.cssText = 'width:100%;height:6px;background:rgba(255,255,255,0.15);border-radius:3px;margin-top:6px;overflow:hidden;';
+            bar = document.createElement('div');
+            bar.style.cssText = 'width:0%;height:100%;background:#22c55e;border-radius:3px;transition:width 0.2s;';
+            barWrap.appendChild(bar);
+            toastEl.querySelector('.toast-message, span')?.after(barWrap) || toastEl.appendChild(barWrap);
+        }
+        try {
+            if (await uploadOneChunked(dir, file, pct => { if (bar) bar.style.width = pct + '%'; })) done++;
+        } catch (e) {
+            showToast(`Upload of ${file.name} failed: ${e.message} — re-upload the same file here to resume`, 'error');
+        } finally {
+            if (toastEl) toastEl.remove();
+        }
+    }
+    if (done) showToast(`Uploaded ${done} file(s)`, 'success');
+    loadFiles();
+}
+
+async function uploadOneChunked(dir, file, onProgress) {
+    const q = `path=${encodeURIComponent(dir)}&name=${encodeURIComponent(file.name)}`;
+    // Pick up where a previous attempt stopped.
+    const status = await fetch(apiUrl(`/api/files/upload/chunk?${q}`)).then(r => r.json());
+    if (status.error) throw new Error(status.error);
+    let offset = status.offset || 0;
+    if (offset > file.size) {
+        await fetch(apiUrl(`/api/files/upload/chunk?${q}`), { method: 'DELETE' });
+        offset = 0;
+    }
+    // The server never replaces an existing file unless asked to.
+    let overwrite = '';
+    if (status.exists) {
+        if (!(await showConfirm(`${file.name} already exists in this folder. Replace it?`, 'Replace file'))) return false;
+        overwrite = '&overwrite=1';
+    }
+    let retries = 0;
+    do {
+        const chunk = file.slice(offset, Math.min(offset + UPLOAD_CHUNK_BYTES, file.size));
+        let data;
+        try {
+            const resp = await fetch(apiUrl(`/api/files/upload/chunk?${q}&offset=${offset}&total=${file.size}${overwrite}`), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/octet-stream' },
+                body: chunk,
+            });
+            data = await resp.json();
+            if (resp.status === 409 && typeof data.offset === 'number') {
+                offset = data.offset;   // server is ahead/behind — resync
+                continue;
+            }
+            if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        } catch (e) {
+            if (++retries > UPLOAD_MAX_RETRIES) throw e;
+            await new Promise(r => setTimeout(r, 1000 * retries));
+            continue;
+        }
+        retries = 0;
+        offset = data.offset;
+        onProgress(file.size ? Math.round(offset / file.size * 100) : 100);
+        if (data.complete) return true;
+    } while (offset <= file.size);
+    throw new Error('upload did not complete');
+}
+
 // ─── ZFS Storage ───
 
 async function loadZfsStatus() {
@@ -13076,6 +13634,14 @@ async function showVmCreate() {
     if (netSelect) netSelect.value = 'virtio';
     const busWarning = document.getElementById('vm-bus-warning');
     if (busWarning) busWarning.style.display = 'none';
+    // Guest OS back to "Not specified"; the RDP row only shows for Windows.
+    const osSelect = document.getElementById('new-vm-os-type');
+    if (osSelect) osSelect.innerHTML = vmOsTypeOptions('');
+    const rdpInput = document.getElementById('new-vm-rdp-address');
+    if (rdpInput) rdpInput.value = '';
+    const rdpRow = document.getElementById('new-vm-rdp-row');
+    if (rdpRow) rdpRow.style.display = 'none';
+    _windowsAutoDetected = false;
     // Wire up bus warning
     if (busSelect && !busSelect._listenerAdded) {
         busSelect.addEventListener('change', () => {
@@ -13144,6 +13710,8 @@ function _buildNewVmCurl() {
         bios_type: v('new-vm-bios-type') || 'seabios',
         notes: v('new-vm-notes'),
         extra_qemu_args: v('new-vm-extra-qemu-args'),
+        os_type: v('new-vm-os-type'),
+        rdp_address: v('new-vm-rdp-address').trim(),
         // vSwitch resolves to "bridge" on submit (the bridge is auto-
         // created). The curl preview shows the same wire shape.
         network_mode: mode === 'vswitch' ? 'bridge' : mode,
@@ -13159,22 +13727,174 @@ function _buildNewVmCurl() {
         + "  -d '" + json + "'";
 }
 
-// Auto-detect Windows ISO and set disk bus to IDE + net model to e1000
+// ─── Windows guests: OS presets, virtio-win media, RDP shortcut ───
+
+// Guest OS choices. Values are Proxmox `ostype` names (the backend
+// validates against the same list); '' = not recorded, in which case the
+// backend guesses Windows from the attached media and hardware.
+const VM_OS_TYPES = [
+    ['', 'Not specified'],
+    ['l26', 'Linux'],
+    ['win11', 'Windows 11 / Server 2022+'],
+    ['win10', 'Windows 10 / Server 2016–2019'],
+    ['win8', 'Windows 8.x / Server 2012'],
+    ['win7', 'Windows 7 / Server 2008 R2'],
+    ['other', 'Other'],
+];
+const VIRTIO_WIN_ISO_URL = 'https://fedorapeople.org/groups/virt/virtio-win/direct-downloads/stable-virtio/virtio-win.iso';
+
+function vmOsTypeOptions(selected) {
+    const cur = selected || '';
+    // Keep a value set on the hypervisor (e.g. PVE "w2k8") selectable.
+    const extra = VM_OS_TYPES.some(([v]) => v === cur) ? ''
+        : `<option value="${escapeAttr(cur)}" selected>${escapeHtml(cur)}</option>`;
+    return VM_OS_TYPES.map(([v, label]) =>
+        `<option value="${v}"${v === cur ? ' selected' : ''}>${escapeHtml(label)}</option>`).join('') + extra;
+}
+
+function vmOsIsWindows(osType) {
+    return /^(win|w2k|wxp|wvista)/.test(osType || '');
+}
+
+function vmWindowsBadge(vm) {
+    if (!vm.windows) return '';
+    const tip = vm.rdp_address
+        ? `Windows guest — Remote Desktop: ${vm.rdp_address}`
+        : 'Windows guest — set an RDP address in Settings for a Remote Desktop shortcut';
+    return ` <span class="badge" style="background:rgba(59,130,246,0.15); color:#3b82f6; font-size:10px; vertical-align:middle;" title="${escapeAttr(tip)}">Windows</span>`;
+}
+
+// Remote Desktop shortcut for a Windows VM with an RDP hint. Greyed out
+// while the VM is stopped, like the console buttons.
+function vmRdpButton(vm, style) {
+    if (!vm.windows || !vm.rdp_address) return '';
+    const title = vm.running ? `Remote Desktop (${vm.rdp_address}) — downloads a .rdp file` : 'Start the VM to connect with Remote Desktop';
+    return `<button class="btn btn-sm" style="${style}color:#3b82f6;" ${vm.running ? `onclick="downloadVmRdp('${escapeAttr(vm.name)}', this.dataset.rdp)"` : 'disabled'} data-rdp="${escapeAttr(vm.rdp_address)}" title="${escapeAttr(title)}"><span class="ws-icon-clean-wrap" data-icon="laptop"></span></button>`;
+}
+
+// Build a .rdp file for the VM's RDP hint and hand it to the browser —
+// mstsc, Microsoft Remote Desktop (macOS) and Remmina all open it. The
+// address was validated server-side (host[:port] only), so it can't
+// smuggle extra settings lines into the file.
+function downloadVmRdp(name, address) {
+    if (!address) return;
+    const rdp = [
+        `full address:s:${address}`,
+        'prompt for credentials:i:1',
+        'screen mode id:i:2',
+        'use multimon:i:0',
+        'audiomode:i:0',
+        'redirectclipboard:i:1',
+        'authentication level:i:2',
+        '',
+    ].join('\r\n');
+    const a = document.createElement('a');
+    a.href = URL.createObjectURL(new Blob([rdp], { type: 'application/x-rdp' }));
+    a.download = `${name}.rdp`;
+    document.body.appendChild(a);
+    a.click();
+    a.remove();
+    setTimeout(() => URL.revokeObjectURL(a.href), 1000);
+}
+
+// Path of a virtio-win ISO already in this node's ISO library, or ''.
+async function findVirtioWinIso() {
+    try {
+        const r = await fetch(apiUrl('/api/vms/isos'));
+        if (!r.ok) return '';
+        const data = await r.json();
+        const hit = (data.isos || []).find(i => /virtio-win/i.test(i.name || ''));
+        return hit ? hit.path : '';
+    } catch (_) { return ''; }
+}
+
+// Fetch the stable virtio-win ISO into the ISO library and point the
+// given drivers-ISO field at it. The download runs in the background;
+// progress shows in the ISO library.
+async function downloadVirtioWinIso(targetInputId) {
+    try {
+        const resp = await fetch(apiUrl('/api/vms/isos/download'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ url: VIRTIO_WIN_ISO_URL, file_name: 'virtio-win.iso' }),
+        });
+        const data = await resp.json();
+        const el = document.getElementById(targetInputId);
+        if (!resp.ok) {
+            // Most likely already downloaded — use the library copy.
+            const existing = await findVirtioWinIso();
+            if (existing && el) { el.value = existing; showToast('Using ' + existing.split('/').pop() + ' from the ISO library', 'info'); return; }
+            showToast(data.error || 'Failed to start download', 'error');
+            return;
+        }
+        if (el && data.path) el.value = data.path;
+        showToast('Downloading virtio-win.iso into the ISO library — progress is under 💿 Library. Let it finish before starting the VM.', 'info');
+    } catch (e) {
+        showToast('Error: ' + e.message, 'error');
+    }
+}
+
+// Guest OS preset for the create modal. Picking a Windows type:
+//  • Windows 11 gets UEFI + TPM 2.0 + Secure Boot and at least 2 vCPU /
+//    4 GB / 64 GB — Setup refuses to install without them.
+//  • with a virtio-win ISO in the library it is attached as the drivers
+//    CD and the disk/NIC stay VirtIO (load the driver from that CD in
+//    Setup); without one the OS disk drops to SATA and the NIC to e1000,
+//    which Windows drives out of the box.
+//  • the Remote Desktop address row is shown.
+async function applyVmOsPreset() {
+    const os = (document.getElementById('new-vm-os-type') || {}).value || '';
+    const win = vmOsIsWindows(os);
+    const rdpRow = document.getElementById('new-vm-rdp-row');
+    if (rdpRow) rdpRow.style.display = win ? 'block' : 'none';
+    if (!win) return;
+
+    const el = (id) => document.getElementById(id) || {};
+    const atLeast = (id, min) => { const f = el(id); if ((parseInt(f.value) || 0) < min) f.value = min; };
+    if (os === 'win11') {
+        el('new-vm-bios-type').value = 'ovmf';
+        el('new-vm-tpm').checked = true;
+        el('new-vm-secure-boot').checked = true;
+        atLeast('new-vm-cpus', 2);
+        atLeast('new-vm-memory', 4096);
+        atLeast('new-vm-disk', 64);
+    }
+
+    let drivers = (el('new-vm-drivers-iso').value || '').trim();
+    if (!drivers) {
+        drivers = await findVirtioWinIso();
+        if (drivers) el('new-vm-drivers-iso').value = drivers;
+    }
+    el('new-vm-os-bus').value = drivers ? 'virtio' : 'sata';
+    el('new-vm-net-model').value = drivers ? 'virtio' : 'e1000';
+    const warn = document.getElementById('vm-bus-warning');
+    if (warn) warn.style.display = 'none';
+    if (typeof vmPrereqRefresh === 'function') vmPrereqRefresh();
+    showToast(drivers
+        ? 'Windows preset applied — VirtIO disk and NIC, drivers CD attached'
+        : 'Windows preset applied — SATA disk and e1000 NIC (no virtio-win ISO in the library)', 'info');
+}
+
+// Auto-detect a Windows ISO and apply the Windows preset (guest OS picked
+// from the file name unless the operator already chose one).
 let _windowsAutoDetected = false;
 function autoDetectWindowsIso(isoPath) {
-    const lower = (isoPath || '').toLowerCase();
+    const lower = (isoPath || '').split('/').pop().toLowerCase();
     const isWindows = lower.includes('windows') || lower.includes('win10') || lower.includes('win11')
         || lower.includes('win_') || lower.includes('win-') || /\bwin\d/.test(lower);
     const busSelect = document.getElementById('new-vm-os-bus');
     const netSelect = document.getElementById('new-vm-net-model');
+    const osSelect = document.getElementById('new-vm-os-type');
     if (isWindows && !_windowsAutoDetected) {
-        if (busSelect) busSelect.value = 'ide';
-        if (netSelect) netSelect.value = 'e1000';
         _windowsAutoDetected = true;
-        showToast('Windows detected — disk bus set to IDE, network set to e1000', 'info');
+        if (osSelect && !osSelect.value) {
+            osSelect.value = /win10|windows.?10/.test(lower) ? 'win10' : 'win11';
+        }
+        applyVmOsPreset();
     } else if (!isWindows && _windowsAutoDetected) {
         if (busSelect) busSelect.value = 'virtio';
         if (netSelect) netSelect.value = 'virtio';
+        if (osSelect && vmOsIsWindows(osSelect.value)) { osSelect.value = ''; applyVmOsPreset(); }
         _windowsAutoDetected = false;
     }
 }
@@ -13481,6 +14201,9 @@ async function createVm() {
     const biosType = document.getElementById('new-vm-bios-type').value || 'seabios';
     const notes = document.getElementById('new-vm-notes')?.value ?? '';
     const extraQemuArgs = document.getElementById('new-vm-extra-qemu-args')?.value ?? '';
+    const osType = document.getElementById('new-vm-os-type')?.value || '';
+    // The RDP hint only applies to Windows guests (its row is hidden otherwise).
+    const rdpAddress = vmOsIsWindows(osType) ? (document.getElementById('new-vm-rdp-address')?.value.trim() || '') : '';
 
     if (!name) { showToast('Enter VM name', 'error'); return; }
 
@@ -13559,13 +14282,16 @@ async function createVm() {
                 bridge_ip: netFields.bridge_ip,
                 bridge_gateway: netFields.bridge_gateway,
                 notes,
-                extra_qemu_args: extraQemuArgs
+                extra_qemu_args: extraQemuArgs,
+                os_type: osType,
+                rdp_address: rdpAddress
             })
         });
         const data = await resp.json();
 
         if (resp.ok) {
             showToast('VM created successfully', 'success');
+            if (data.capacity_warning) showToast(data.capacity_warning, 'warning');
             taskLog('Created VM: ' + name);
             closeVmCreate();
             loadVms();
@@ -14621,6 +15347,7 @@ function toggleRawCrontab() {
 function openAddServerModal() {
     document.getElementById('add-server-modal').classList.add('active');
     fetchOwnJoinToken();
+    loadArchivedServers();
 }
 
 function closeModal() {
@@ -14670,7 +15397,7 @@ async function addServer() {
             if (data.override_required) {
                 showToast(data.error + ' To proceed, open the target node\'s dashboard → Settings → Cluster and arm "Allow re-cluster", then try again.', 'error');
             } else {
-                showToast(data.error, 'error');
+                showToast(data.hint ? (data.error + ' — ' + data.hint) : data.error, 'error');
             }
             taskLog('Add server: ' + address, 'failed');
             return;
@@ -14764,6 +15491,404 @@ async function generateOneTimeJoinToken() {
     }
 }
 
+// ─── Bare-metal provisioning ───
+// Profiles hand out a pre-approved enrolment token; a machine netbooted
+// from the profile's boot URL installs its OS, runs setup.sh and joins
+// this cluster on its own.
+var provisionOsList = [];
+var provisionProfiles = [];
+
+async function openProvisioning() {
+    showModal('<div id="prov-body" style="white-space:normal;">Loading…</div>', 'Bare-metal Provisioning');
+    await loadProvisioning();
+}
+
+async function loadProvisioning() {
+    var body = document.getElementById('prov-body');
+    if (!body) return;
+    try {
+        var resp = await fetch('/api/provision/profiles', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        provisionOsList = data.os || [];
+        var profiles = data.profiles || [];
+        var html = '<p style="font-size:12px;color:var(--text-secondary);margin:0 0 10px;">Point your DHCP server\'s iPXE chain at a profile\'s boot URL. The machine installs the OS, runs setup.sh and joins this cluster — no keyboard needed.</p>' +
+            '<button class="btn btn-primary btn-sm" onclick="editProvisionProfile()">+ New profile</button>';
+        if (!profiles.length) {
+            html += '<p style="color:var(--text-muted);text-align:center;padding:16px;">No provisioning profiles yet.</p>';
+        }
+        profiles.forEach(function (p) {
+            var os = provisionOsList.find(function (o) { return o.id === p.os; });
+            var expired = p.expires_at && p.expires_at * 1000 < Date.now();
+            var recent = (p.enrollments || []).slice(-5).reverse();
+            html += '<div style="border:1px solid var(--border);border-radius:8px;padding:10px;margin-top:10px;">' +
+                '<div style="display:flex;justify-content:space-between;align-items:center;gap:8px;">' +
+                '<strong>' + escapeHtml(p.name) + '</strong>' +
+                '<span style="font-size:11px;color:var(--text-muted);">' + escapeHtml(os ? os.label : p.os) + ' · ' +
+                (p.uses || 0) + (p.max_uses ? '/' + p.max_uses : '') + ' enrolled' + (expired ? ' · <span style="color:var(--danger);">expired</span>' : '') + '</span></div>' +
+                '<div style="display:flex;gap:6px;align-items:center;margin:6px 0;">' +
+                '<code style="flex:1;font-size:11px;word-break:break-all;">' + escapeHtml(p.boot_url) + '</code>' +
+                '<button class="btn btn-sm" data-url="' + escapeAttr(p.boot_url) + '" onclick="navigator.clipboard.writeText(this.dataset.url); showToast(\'Boot URL copied\', \'success\')">Copy</button></div>' +
+                '<div style="display:flex;gap:6px;">' +
+                '<button class="btn btn-sm" data-id="' + escapeAttr(p.id) + '" onclick="editProvisionProfile(this.dataset.id)">Edit</button>' +
+                '<button class="btn btn-sm" data-id="' + escapeAttr(p.id) + '" onclick="rotateProvisionToken(this.dataset.id)">Rotate token</button>' +
+                '<button class="btn btn-sm btn-danger" data-id="' + escapeAttr(p.id) + '" onclick="deleteProvisionProfile(this.dataset.id)">Delete</button></div>' +
+                (recent.length ? '<div style="font-size:11px;margin-top:6px;">' + recent.map(function (e) {
+                    return '<div style="color:' + (e.ok ? 'var(--success)' : 'var(--danger)') + ';">' + new Date(e.ts * 1000).toLocaleString() + ' — ' +
+                        escapeHtml(e.hostname || e.address) + ' (' + escapeHtml(e.address) + '): ' + escapeHtml(e.detail) + '</div>';
+                }).join('') + '</div>' : '') +
+                '</div>';
+        });
+        body.innerHTML = html;
+        provisionProfiles = profiles;
+    } catch (e) {
+        body.innerHTML = '<p style="color:var(--danger);">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
+function editProvisionProfile(id) {
+    var p = provisionProfiles.find(function (x) { return x.id === id; }) || {
+        name: '', server_url: 'http://' + location.hostname + ':8554', os: (provisionOsList[0] || {}).id || 'debian-12',
+        hostname_prefix: 'wolfstack', admin_user: 'wolfadmin', cluster_name: '', disk: '', iso_url: '',
+        ssh_keys: [], allowed_macs: [], max_uses: 0, expires_at: 0
+    };
+    var field = function (label, input, hint) {
+        return '<div class="form-group" style="margin-bottom:8px;"><label style="font-size:12px;">' + label + '</label>' + input +
+            (hint ? '<small style="color:var(--text-muted);font-size:11px;">' + hint + '</small>' : '') + '</div>';
+    };
+    var text = function (elId, value, placeholder) {
+        return '<input type="text" class="form-control" id="' + elId + '" value="' + escapeAttr(value || '') + '" placeholder="' + escapeAttr(placeholder || '') + '">';
+    };
+    var expires = p.expires_at ? new Date(p.expires_at * 1000).toISOString().slice(0, 10) : '';
+    var html = '<div style="white-space:normal;">' +
+        '<input type="hidden" id="prov-id" value="' + escapeAttr(p.id || '') + '">' +
+        field('Name', text('prov-name', p.name, 'Rack 4 hypervisors')) +
+        field('Operating system', '<select class="form-control" id="prov-os">' + provisionOsList.map(function (o) {
+            return '<option value="' + escapeAttr(o.id) + '"' + (o.id === p.os ? ' selected' : '') + '>' + escapeHtml(o.label) + '</option>';
+        }).join('') + '</select>') +
+        field('Server URL', text('prov-server-url', p.server_url), 'How the netbooting machine reaches this node — usually plain http on the inter-node port.') +
+        field('Cluster', text('prov-cluster', p.cluster_name, 'this node\'s cluster')) +
+        field('Hostname prefix', text('prov-prefix', p.hostname_prefix), 'Machines are named prefix-<last 6 MAC digits>.') +
+        field('Install disk', text('prov-disk', p.disk, '/dev/sda (blank = installer picks)')) +
+        field('Admin user', text('prov-admin', p.admin_user)) +
+        field('Admin password hash', text('prov-hash', '', p.has_password ? 'unchanged' : '$6$… from openssl passwd -6'), 'Never shown again once saved.') +
+        field('SSH public keys', '<textarea class="form-control" id="prov-keys" rows="2" style="font-family:monospace;font-size:11px;">' + escapeHtml((p.ssh_keys || []).join('\n')) + '</textarea>', 'One per line.') +
+        field('Allowed MAC addresses', text('prov-macs', (p.allowed_macs || []).join(', '), 'any machine'), 'Comma separated. Blank lets any machine with the boot URL enrol. Listed MACs are checked against what this node sees, so the machine must be on the same network segment.') +
+        '<div style="display:flex;gap:8px;">' +
+        field('Max enrolments', '<input type="number" min="0" class="form-control" id="prov-max" value="' + (p.max_uses || 0) + '">', '0 = unlimited') +
+        field('Expires', '<input type="date" class="form-control" id="prov-expires" value="' + expires + '">') + '</div>' +
+        field('Ubuntu ISO URL', text('prov-iso', p.iso_url, 'default for the release'), 'Ubuntu only — override when a newer point release moves the ISO.') +
+        '<div style="text-align:right;margin-top:10px;"><button class="btn btn-primary" onclick="saveProvisionProfile(this)">Save profile</button></div></div>';
+    showModal(html, id ? 'Edit Provisioning Profile' : 'New Provisioning Profile', { noOk: true });
+}
+
+async function saveProvisionProfile(btn) {
+    var val = function (elId) { return document.getElementById(elId).value.trim(); };
+    var expires = val('prov-expires');
+    var profile = {
+        id: val('prov-id'), name: val('prov-name'), os: val('prov-os'), server_url: val('prov-server-url'),
+        cluster_name: val('prov-cluster'), hostname_prefix: val('prov-prefix'), disk: val('prov-disk'),
+        admin_user: val('prov-admin'), password_hash: val('prov-hash'), iso_url: val('prov-iso'),
+        ssh_keys: val('prov-keys').split('\n').map(function (k) { return k.trim(); }).filter(Boolean),
+        allowed_macs: val('prov-macs').split(',').map(function (m) { return m.trim(); }).filter(Boolean),
+        max_uses: parseInt(val('prov-max'), 10) || 0,
+        expires_at: expires ? Math.floor(new Date(expires + 'T23:59:59').getTime() / 1000) : 0
+    };
+    try {
+        var resp = await fetch('/api/provision/profiles', {
+            method: 'POST', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(profile)
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        btn.closest('.modal-overlay').remove();
+        showToast('Provisioning profile saved', 'success');
+        loadProvisioning();
+    } catch (e) {
+        showToast('Failed to save profile: ' + e.message, 'error');
+    }
+}
+
+async function rotateProvisionToken(id) {
+    if (!await showConfirm('Issue a new token for this profile? Boot URLs already configured in DHCP stop working.', 'Rotate provisioning token')) return;
+    try {
+        var resp = await fetch('/api/provision/profiles/' + encodeURIComponent(id) + '/rotate', { method: 'POST', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast('Token rotated — update your DHCP boot URL', 'success');
+        loadProvisioning();
+    } catch (e) {
+        showToast('Failed to rotate token: ' + e.message, 'error');
+    }
+}
+
+async function deleteProvisionProfile(id) {
+    if (!await showConfirm('Delete this provisioning profile? Machines still netbooting from it will fail to enrol.', 'Delete profile')) return;
+    try {
+        var resp = await fetch('/api/provision/profiles/' + encodeURIComponent(id), { method: 'DELETE', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        loadProvisioning();
+    } catch (e) {
+        showToast('Failed to delete profile: ' + e.message, 'error');
+    }
+}
+
+// ─── Reboot windows ───
+// A window reboots its nodes one at a time on a UTC cron: pre-hook, stop
+// guests, reboot, wait for the node to come back, start guests, post-hook.
+// Windows live on the node serving this page.
+var rebootWindows = [];
+
+// ─── Config drift ───
+
+async function openConfigDrift() {
+    showModal('<div id="cd-body" style="white-space:normal;">Loading…</div>', 'Config Drift', { noOk: true });
+    await loadConfigDrift();
+}
+
+function renderConfigDrift(data) {
+    var scan = data.scan || {};
+    var findings = scan.findings || [];
+    var kindLabel = { desired: 'differs from baseline', missing: 'missing', last_known_good: 'changed outside WolfStack' };
+    var html = '<p style="font-size:12px;color:var(--text-secondary);margin:0 0 10px;">' +
+        (scan.at ? 'Last scan ' + new Date(scan.at * 1000).toLocaleString() + ', ' + scan.checked + ' file(s) checked on this node.' : 'Not scanned yet.') +
+        ' Each node reports its own drift on the Issues list.</p>';
+    html += findings.length
+        ? '<table style="width:100%;font-size:12px;border-collapse:collapse;">' + findings.map(function (f) {
+            return '<tr><td style="padding:3px 6px;font-family:monospace;">' + escapeHtml(f.path) + '</td>' +
+                '<td style="padding:3px 6px;color:' + (f.kind === 'last_known_good' ? 'var(--text-muted)' : 'var(--warning)') + ';">' + escapeHtml(kindLabel[f.kind] || f.kind) + '</td></tr>';
+        }).join('') + '</table>'
+        : '<p style="color:var(--success);font-size:12px;">No drift found.</p>';
+    html += '<h4 style="margin:14px 0 6px;">Cluster baselines</h4>';
+    var baselines = data.baselines || [];
+    html += baselines.length
+        ? baselines.map(function (b) {
+            return '<div style="display:flex;align-items:center;gap:8px;font-size:12px;padding:3px 0;">' +
+                '<code style="flex:1;">' + escapeHtml(b.path) + '</code>' +
+                '<span style="color:var(--text-muted);">from ' + escapeHtml(b.source || '?') + '</span>' +
+                '<button class="btn btn-sm" data-path="' + escapeAttr(b.path) + '" onclick="unpinConfigBaseline(this.dataset.path)">Remove</button></div>';
+        }).join('')
+        : '<p style="font-size:12px;color:var(--text-muted);">No baselines — pin a file to hold every node to this node\'s copy.</p>';
+    html += '<div style="display:flex;gap:6px;margin-top:8px;">' +
+        '<input type="text" class="form-control" id="cd-path" placeholder="/etc/wolfdisk/config.toml" style="flex:1;">' +
+        '<button class="btn btn-sm btn-primary" onclick="pinConfigBaseline()" title="Make this node\'s copy the desired state for the cluster">Pin</button></div>' +
+        '<div style="text-align:right;margin-top:12px;"><button class="btn btn-sm" onclick="scanConfigDrift(this)">Scan now</button></div>';
+    return html;
+}
+
+async function loadConfigDrift() {
+    var body = document.getElementById('cd-body');
+    if (!body) return;
+    try {
+        var resp = await fetch('/api/drift', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        body.innerHTML = renderConfigDrift(data);
+    } catch (e) {
+        body.innerHTML = '<p style="color:var(--danger);">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
+async function scanConfigDrift(btn) {
+    btn.disabled = true;
+    try {
+        var resp = await fetch('/api/drift/scan', { method: 'POST', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        await loadConfigDrift();
+    } catch (e) {
+        showToast('Scan failed: ' + e.message, 'error');
+        btn.disabled = false;
+    }
+}
+
+async function changeConfigBaseline(method, path) {
+    try {
+        var resp = await fetch('/api/drift/baselines', {
+            method: method,
+            credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ path: path })
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        await loadConfigDrift();
+    } catch (e) {
+        showToast(e.message, 'error');
+    }
+}
+
+function pinConfigBaseline() {
+    var path = document.getElementById('cd-path').value.trim();
+    if (!path) { showToast('Enter a file path', 'error'); return; }
+    changeConfigBaseline('POST', path);
+}
+
+async function unpinConfigBaseline(path) {
+    if (!await showConfirm('Stop holding ' + path + ' to its baseline on every node?', 'Remove baseline')) return;
+    changeConfigBaseline('DELETE', path);
+}
+
+async function openRebootWindows() {
+    showModal('<div id="rw-body" style="white-space:normal;">Loading…</div>', 'Reboot Windows', { noOk: true });
+    await loadRebootWindows();
+}
+
+function rebootRunBadge(status) {
+    var colour = { succeeded: 'var(--success)', done: 'var(--success)', failed: 'var(--danger)', running: 'var(--warning)', rebooting: 'var(--warning)' }[status] || 'var(--text-muted)';
+    return '<span style="color:' + colour + ';font-weight:600;">' + escapeHtml(status) + '</span>';
+}
+
+async function loadRebootWindows() {
+    var body = document.getElementById('rw-body');
+    if (!body) return;
+    try {
+        var resp = await fetch('/api/maintenance/windows', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        rebootWindows = data.windows || [];
+        var runs = data.runs || [];
+        var html = '<p style="font-size:12px;color:var(--text-secondary);margin:0 0 10px;">Nodes reboot one at a time, this node last. Schedules are cron in UTC.</p>' +
+            '<button class="btn btn-primary btn-sm" onclick="editRebootWindow()">+ New window</button>';
+        if (!rebootWindows.length) {
+            html += '<p style="color:var(--text-muted);text-align:center;padding:16px;">No reboot windows yet.</p>';
+        }
+        rebootWindows.forEach(function (w) {
+            var targets = (w.node_ids || []).map(function (id) {
+                var n = allNodes.find(function (x) { return x.id === id; });
+                return n ? (n.display_name || n.hostname) : id;
+            });
+            if (w.selector) targets.push('label ' + w.selector);
+            html += '<div style="border:1px solid var(--border);border-radius:8px;padding:10px;margin-top:10px;">' +
+                '<div style="display:flex;justify-content:space-between;align-items:center;gap:8px;">' +
+                '<strong>' + escapeHtml(w.name) + (w.enabled ? '' : ' <span style="color:var(--text-muted);font-weight:400;">(disabled)</span>') + '</strong>' +
+                '<code style="font-size:11px;">' + escapeHtml(w.cron || 'manual only') + '</code></div>' +
+                '<div style="font-size:12px;color:var(--text-secondary);margin:4px 0 6px;">' + escapeHtml(targets.join(', ')) + ' · ' +
+                (w.guest_policy === 'leave' ? 'guests left as they are' : 'guests stopped and restarted') + '</div>' +
+                '<div style="display:flex;gap:6px;">' +
+                '<button class="btn btn-sm" data-id="' + escapeAttr(w.id) + '" onclick="editRebootWindow(this.dataset.id)">Edit</button>' +
+                '<button class="btn btn-sm" data-id="' + escapeAttr(w.id) + '" onclick="runRebootWindow(this.dataset.id)">Run now</button>' +
+                '<button class="btn btn-sm btn-danger" data-id="' + escapeAttr(w.id) + '" onclick="deleteRebootWindow(this.dataset.id)">Delete</button></div></div>';
+        });
+        if (runs.length) {
+            html += '<h4 style="margin:16px 0 6px;">Recent runs</h4>';
+            runs.forEach(function (r) {
+                html += '<details style="border:1px solid var(--border);border-radius:8px;padding:8px 10px;margin-top:6px;"' + (r.status === 'running' ? ' open' : '') + '>' +
+                    '<summary style="cursor:pointer;font-size:12px;">' + new Date(r.started * 1000).toLocaleString() + ' — ' + escapeHtml(r.window.name) + ' · ' +
+                    rebootRunBadge(r.status) + ' <span style="color:var(--text-muted);">(' + escapeHtml(r.trigger) + ')</span></summary>' +
+                    (r.nodes || []).map(function (n) {
+                        return '<div style="font-size:12px;margin-top:6px;"><strong>' + escapeHtml(n.hostname) + '</strong> ' + rebootRunBadge(n.status) +
+                            (n.steps || []).map(function (s) {
+                                return '<div style="margin-left:12px;color:' + (s.ok ? 'var(--text-secondary)' : 'var(--danger)') + ';">' +
+                                    new Date(s.ts * 1000).toLocaleTimeString() + ' ' + escapeHtml(s.name) + (s.detail ? ': ' + escapeHtml(s.detail) : '') + '</div>';
+                            }).join('') + '</div>';
+                    }).join('') + '</details>';
+            });
+        }
+        body.innerHTML = html;
+        if (runs.some(function (r) { return r.status === 'running'; })) {
+            setTimeout(loadRebootWindows, 15000);
+        }
+    } catch (e) {
+        body.innerHTML = '<p style="color:var(--danger);">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
+function editRebootWindow(id) {
+    var w = rebootWindows.find(function (x) { return x.id === id; }) || {
+        name: '', enabled: true, cron: '0 3 * * 0', node_ids: [], selector: '', guest_policy: 'stop_restart',
+        pre_hook: '', post_hook: '', hook_timeout_secs: 300, stop_timeout_secs: 180, rejoin_timeout_minutes: 20,
+        continue_on_failure: false
+    };
+    var field = function (label, input, hint) {
+        return '<div class="form-group" style="margin-bottom:8px;"><label style="font-size:12px;">' + label + '</label>' + input +
+            (hint ? '<small style="color:var(--text-muted);font-size:11px;">' + hint + '</small>' : '') + '</div>';
+    };
+    var num = function (elId, value) {
+        return '<input type="number" class="form-control" id="' + elId + '" value="' + escapeAttr(String(value)) + '">';
+    };
+    var nodes = allNodes.filter(function (n) { return n.node_type === 'wolfstack'; }).map(function (n) {
+        return '<label style="display:inline-flex;align-items:center;gap:4px;margin:0 10px 4px 0;font-size:12px;">' +
+            '<input type="checkbox" class="rw-node" value="' + escapeAttr(n.id) + '"' + ((w.node_ids || []).indexOf(n.id) >= 0 ? ' checked' : '') + '>' +
+            escapeHtml(n.display_name || n.hostname) + '</label>';
+    }).join('');
+    var html = '<div style="white-space:normal;">' +
+        '<input type="hidden" id="rw-id" value="' + escapeAttr(w.id || '') + '">' +
+        field('Name', '<input type="text" class="form-control" id="rw-name" value="' + escapeAttr(w.name) + '" placeholder="Sunday patching">') +
+        field('Schedule (cron, UTC)', '<input type="text" class="form-control" id="rw-cron" value="' + escapeAttr(w.cron) + '" placeholder="0 3 * * 0">', 'Blank = only when you press Run now.') +
+        field('Nodes', '<div>' + nodes + '</div>') +
+        field('Label selector', '<input type="text" class="form-control" id="rw-selector" value="' + escapeAttr(w.selector) + '" placeholder="patch-group=a">', 'Nodes matching these labels are included too.') +
+        field('Guests', '<select class="form-control" id="rw-policy">' +
+            '<option value="stop_restart"' + (w.guest_policy !== 'leave' ? ' selected' : '') + '>Stop gracefully, start again after the reboot</option>' +
+            '<option value="leave"' + (w.guest_policy === 'leave' ? ' selected' : '') + '>Leave them — reboot as they are</option></select>') +
+        field('Pre-reboot hook', '<textarea class="form-control" id="rw-pre" rows="2" style="font-family:monospace;font-size:11px;" placeholder="apt-get update && apt-get -y upgrade">' + escapeHtml(w.pre_hook) + '</textarea>', 'Runs as root on each node. If it fails, that node is not rebooted.') +
+        field('Post-reboot hook', '<textarea class="form-control" id="rw-post" rows="2" style="font-family:monospace;font-size:11px;">' + escapeHtml(w.post_hook) + '</textarea>', 'Runs after the guests are back.') +
+        '<div style="display:flex;gap:8px;">' +
+        field('Hook timeout (s)', num('rw-hook-timeout', w.hook_timeout_secs)) +
+        field('VM stop timeout (s)', num('rw-stop-timeout', w.stop_timeout_secs)) +
+        field('Rejoin timeout (min)', num('rw-rejoin', w.rejoin_timeout_minutes)) + '</div>' +
+        '<label style="display:flex;align-items:center;gap:6px;font-size:12px;"><input type="checkbox" id="rw-continue"' + (w.continue_on_failure ? ' checked' : '') + '> Carry on with the remaining nodes if one fails</label>' +
+        '<label style="display:flex;align-items:center;gap:6px;font-size:12px;margin-top:4px;"><input type="checkbox" id="rw-enabled"' + (w.enabled ? ' checked' : '') + '> Enabled</label>' +
+        '<div style="text-align:right;margin-top:10px;"><button class="btn btn-primary" onclick="saveRebootWindow(this)">Save window</button></div></div>';
+    showModal(html, id ? 'Edit Reboot Window' : 'New Reboot Window', { noOk: true });
+}
+
+async function saveRebootWindow(btn) {
+    var val = function (elId) { return document.getElementById(elId).value.trim(); };
+    var rw = {
+        id: val('rw-id'), name: val('rw-name'), cron: val('rw-cron'), selector: val('rw-selector'),
+        node_ids: Array.from(document.querySelectorAll('.rw-node:checked')).map(function (c) { return c.value; }),
+        guest_policy: val('rw-policy'), pre_hook: val('rw-pre'), post_hook: val('rw-post'),
+        hook_timeout_secs: parseInt(val('rw-hook-timeout'), 10) || 300,
+        stop_timeout_secs: parseInt(val('rw-stop-timeout'), 10) || 180,
+        rejoin_timeout_minutes: parseInt(val('rw-rejoin'), 10) || 20,
+        continue_on_failure: document.getElementById('rw-continue').checked,
+        enabled: document.getElementById('rw-enabled').checked
+    };
+    try {
+        var resp = await fetch('/api/maintenance/windows', {
+            method: 'POST', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(rw)
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        btn.closest('.modal-overlay').remove();
+        showToast('Reboot window saved', 'success');
+        loadRebootWindows();
+    } catch (e) {
+        showToast('Failed to save window: ' + e.message, 'error');
+    }
+}
+
+async function runRebootWindow(id) {
+    var w = rebootWindows.find(function (x) { return x.id === id; });
+    if (!await showConfirm('Reboot the nodes in "' + (w ? w.name : id) + '" now? Their guests will be stopped and restarted.', 'Run reboot window')) return;
+    try {
+        var resp = await fetch('/api/maintenance/windows/' + encodeURIComponent(id) + '/run', { method: 'POST', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast('Reboot window started — ' + (data.nodes || []).length + ' node(s)', 'success');
+        loadRebootWindows();
+    } catch (e) {
+        showToast('Failed to start window: ' + e.message, 'error');
+    }
+}
+
+async function deleteRebootWindow(id) {
+    if (!await showConfirm('Delete this reboot window? Its run history is kept.', 'Delete window')) return;
+    try {
+        var resp = await fetch('/api/maintenance/windows/' + encodeURIComponent(id), { method: 'DELETE', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        loadRebootWindows();
+    } catch (e) {
+        showToast('Failed to delete window: ' + e.message, 'error');
+    }
+}
+
 // ─── Proxmox Resource Management ───
 async function loadPveResources(nodeId) {
     try {
@@ -14963,12 +16088,76 @@ async function removeServer(id) {
     }
 }
 
-async function confirmRemoveServer(id, hostname) {
-    if (await showConfirm(`Remove server "${hostname}" from the cluster?`)) {
-        removeServer(id);
+function confirmRemoveServer(id, hostname) {
+    showModal(`<div>
+        <p>Take <strong>${escapeHtml(hostname)}</strong> out of the cluster?</p>
+        <p style="font-size:12px;color:var(--text-muted);"><strong>Archive</strong> keeps its settings (tokens, cluster name, site) so it can be restored in one click from Add Server.
+        <strong>Remove</strong> deletes it for good — adding it back means a fresh join.</p>
+        <div style="display:flex;gap:8px;justify-content:flex-end;margin-top:14px;">
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-primary" onclick="this.closest('.modal-overlay').remove(); archiveServer('${escapeAttr(id)}')">Archive</button>
+            <button class="btn btn-sm btn-danger" onclick="this.closest('.modal-overlay').remove(); removeServer('${escapeAttr(id)}')">Remove permanently</button>
+        </div>
+    </div>`, 'Remove Server', { noOk: true });
+}
+
+async function archiveServer(id) {
+    try {
+        const resp = await fetch(`/api/nodes/${encodeURIComponent(id)}/archive`, { method: 'POST' });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast('Server archived', 'success');
+        taskLog('Archived server: ' + id);
+        fetchNodes();
+    } catch (e) {
+        showToast('Failed to archive server: ' + e.message, 'error');
+        taskLog('Archive server: ' + id, 'failed');
+    }
+}
+
+async function loadArchivedServers() {
+    const box = document.getElementById('archived-servers');
+    if (!box) return;
+    let list = [];
+    try {
+        const resp = await fetch('/api/nodes/archived');
+        if (resp.ok) list = await resp.json();
+    } catch (e) { /* leave the section hidden */ }
+    if (!list.length) { box.style.display = 'none'; box.innerHTML = ''; return; }
+    box.style.display = '';
+    box.innerHTML = `<label>Archived Servers</label>
+        <table class="data-table" style="font-size:12px;"><tbody>${list.map(n => `<tr>
+            <td><strong>${escapeHtml(n.display_name || n.hostname || n.id)}</strong>
+                <div style="color:var(--text-muted);">${escapeHtml(n.address)}:${n.port}${n.cluster_name ? ' · ' + escapeHtml(n.cluster_name) : ''}</div></td>
+            <td style="color:var(--text-muted);">archived ${new Date(n.archived_at * 1000).toLocaleDateString()}</td>
+            <td style="text-align:right;white-space:nowrap;">
+                <button class="btn btn-sm btn-primary" onclick="restoreArchivedServer('${escapeAttr(n.id)}', this)">Restore</button>
+                <button class="btn btn-sm btn-danger" onclick="deleteArchivedServer('${escapeAttr(n.id)}', '${escapeAttr(n.display_name || n.hostname || n.id)}')">Delete</button>
+            </td></tr>`).join('')}</tbody></table>`;
+}
+
+async function restoreArchivedServer(id, btn) {
+    if (btn) btn.disabled = true;
+    try {
+        const resp = await fetch(`/api/nodes/${encodeURIComponent(id)}/restore`, { method: 'POST' });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast(`Restored ${data.hostname || id}`, 'success');
+        taskLog('Restored server: ' + id);
+        loadArchivedServers();
+        fetchNodes();
+    } catch (e) {
+        showToast('Restore failed: ' + e.message, 'error');
+        if (btn) btn.disabled = false;
     }
 }
 
+async function deleteArchivedServer(id, name) {
+    if (!(await showConfirm(`Delete archived server "${name}" permanently?`))) return;
+    await removeServer(id);
+    loadArchivedServers();
+}
+
 async function confirmRemovePveCluster(clusterName, nodeIds) {
     if (!(await showConfirm(`Remove Proxmox cluster "${clusterName}" and all ${nodeIds.length} node(s)?`))) return;
     for (const id of nodeIds) {
@@ -15539,6 +16728,17 @@ function openNodeSettings(nodeId) {
                     <div id="node-upgrade-action" style="display:none;margin-top:12px;"></div>
                 </div>
 
+                ${isPve ? '' : `<div id="node-hardware-section" style="background:var(--bg-secondary,#161622);border:1px solid var(--border,#333);border-radius:8px;padding:14px 16px;margin-bottom:16px;">
+                    <div style="display:flex;align-items:center;justify-content:space-between;margin-bottom:4px;">
+                        <span style="font-weight:600;font-size:13px;color:var(--text,#fff);">Hardware</span>
+                        <span style="display:flex;gap:6px;">
+                            <button type="button" class="btn btn-sm" onclick="copyNodeHardware()" title="Copy the full inventory as JSON — handy for support tickets">Copy JSON</button>
+                            <button type="button" class="btn btn-sm" onclick="loadNodeHardware(document.getElementById('node-settings-modal')._nodeId, true)">Refresh</button>
+                        </span>
+                    </div>
+                    <div id="node-hardware-body" style="font-size:12px;color:var(--text-muted);">Loading…</div>
+                </div>`}
+
                 <hr style="border-color:var(--border);margin:16px 0;">
                 <div class="form-group">
                     <label>Cluster Name</label>
@@ -15550,6 +16750,11 @@ function openNodeSettings(nodeId) {
                     <input type="text" class="form-control" id="node-settings-site" value="${node.site || ''}" placeholder="${autoSiteHint(node.address) || 'unset'}" style="font-family:'JetBrains Mono',monospace;font-size:12px;">
                     <small style="color: var(--text-muted);">Physical location tag (e.g. <code>home</code>, <code>office-vlan10</code>, <code>hetzner-vps</code>). Nodes sharing a site are dialled at their LAN address; different sites go via public IP. Leave blank to auto-derive from address (currently <code>${autoSiteHint(node.address) || 'none'}</code>).</small>
                 </div>
+                <div class="form-group">
+                    <label>Labels</label>
+                    <input type="text" class="form-control" id="node-settings-labels" value="${escapeAttr(formatNodeLabels(node.labels))}" placeholder="env=prod, rack=b4" style="font-family:'JetBrains Mono',monospace;font-size:12px;">
+                    <small style="color: var(--text-muted);">Comma-separated <code>key=value</code> tags. Select nodes by them with <code>/api/nodes?selector=env=prod</code> or run bulk operations on every match.</small>
+                </div>
                 ${isPve ? '' : `
                 <div class="form-group">
                     <label>Tier roles</label>
@@ -15630,6 +16835,70 @@ function openNodeSettings(nodeId) {
     if (node.is_self) loadNodePorts();
     // Populate the tier-role checkboxes from the assignable-roles API.
     loadNodeRoleCheckboxes(node);
+    if (!isPve) loadNodeHardware(nodeId, false);
+}
+
+// Hardware inventory in the node settings modal. The node detail endpoint
+// carries the periodic snapshot; Refresh asks the node itself for a new one.
+let nodeHardware = null;
+
+async function loadNodeHardware(nodeId, refresh) {
+    const box = document.getElementById('node-hardware-body');
+    if (!box) return;
+    const node = allNodes.find(n => n.id === nodeId);
+    box.textContent = refresh ? 'Collecting…' : 'Loading…';
+    try {
+        let hw;
+        if (refresh) {
+            const url = (!node || node.is_self) ? '/api/hardware?refresh=true' : `/api/nodes/${encodeURIComponent(nodeId)}/proxy/hardware?refresh=true`;
+            const resp = await fetch(url);
+            hw = await resp.json();
+            if (!resp.ok) throw new Error(hw.error || ('HTTP ' + resp.status));
+        } else {
+            const resp = await fetch(`/api/nodes/${encodeURIComponent(nodeId)}`);
+            const data = await resp.json();
+            if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+            hw = data.hardware;
+        }
+        nodeHardware = hw || null;
+        if (!hw) {
+            box.textContent = node && !node.online ? 'Node is offline.' : 'No inventory yet — this node may be running an older WolfStack.';
+            return;
+        }
+        const sys = hw.system || {};
+        const row = (label, value) => value ? `<span style="color:var(--text-muted);">${label}</span><span style="color:var(--text-secondary);">${escapeHtml(String(value))}</span>` : '';
+        const cpus = (hw.processors || []).map(c => `${c.model}${c.cores ? ` (${c.cores}C/${c.threads}T)` : ''}`);
+        const dimms = hw.memory || [];
+        let html = `<div style="display:grid;grid-template-columns:auto 1fr;gap:3px 12px;">` +
+            row('System', [sys.vendor, sys.product, sys.version].filter(Boolean).join(' ')) +
+            row('Serial', sys.serial || sys.chassis_serial) +
+            row('Board', [sys.board_vendor, sys.board_product].filter(Boolean).join(' ') + (sys.board_serial ? ` — ${sys.board_serial}` : '')) +
+            row('BIOS', [sys.bios_vendor, sys.bios_version, sys.bios_date].filter(Boolean).join(' ')) +
+            row('CPU', cpus.join(', ')) +
+            row('Memory', dimms.length ? `${dimms.length} module(s): ${dimms.map(d => d.size).join(', ')}` : '') +
+            `</div>`;
+        if ((hw.disks || []).length) {
+            html += `<div style="margin-top:8px;font-weight:600;color:var(--text-secondary);">Disks</div>` + hw.disks.map(d =>
+                `<div>${escapeHtml(d.name)} — ${escapeHtml(d.model || 'unknown model')} ${formatBytes(d.size_bytes || 0)}${d.serial ? ' · S/N ' + escapeHtml(d.serial) : ''}${d.firmware ? ' · FW ' + escapeHtml(d.firmware) : ''}</div>`).join('');
+        }
+        if ((hw.nics || []).length) {
+            html += `<div style="margin-top:8px;font-weight:600;color:var(--text-secondary);">Network</div>` + hw.nics.map(n =>
+                `<div>${escapeHtml(n.name)} — ${escapeHtml(n.mac)} · ${escapeHtml(n.driver || '?')}${n.firmware_version ? ' · FW ' + escapeHtml(n.firmware_version) : ''}${n.speed_mbps ? ' · ' + n.speed_mbps + ' Mb/s' : ''}</div>`).join('');
+        }
+        if ((hw.warnings || []).length) {
+            html += `<div style="margin-top:8px;color:var(--warning);">${hw.warnings.map(escapeHtml).join('<br>')}</div>`;
+        }
+        html += `<div style="margin-top:6px;font-size:11px;">Collected ${new Date(hw.collected_at * 1000).toLocaleString()} · ${(hw.pci || []).length} PCI device(s)</div>`;
+        box.innerHTML = html;
+    } catch (e) {
+        box.innerHTML = `<span style="color:var(--danger);">${escapeHtml(e.message)}</span>`;
+    }
+}
+
+function copyNodeHardware() {
+    if (!nodeHardware) { showToast('No hardware inventory loaded', 'error'); return; }
+    navigator.clipboard.writeText(JSON.stringify(nodeHardware, null, 2));
+    showToast('Hardware inventory copied to clipboard', 'success');
 }
 
 // Render the tier-role checkboxes in the node settings modal, pre-checking
@@ -15834,6 +17103,22 @@ async function upgradeNode(nodeId) {
     return issuesUpgradeAll(targets);
 }
 
+// Node labels as the "k=v, k2=v2" text the settings field edits.
+function formatNodeLabels(labels) {
+    return Object.keys(labels || {}).sort().map(k => k + '=' + labels[k]).join(', ');
+}
+
+// Parse the settings field back into a map; null when a term has no '='.
+function parseNodeLabels(text) {
+    const out = {};
+    for (const term of text.split(',').map(t => t.trim()).filter(Boolean)) {
+        const eq = term.indexOf('=');
+        if (eq <= 0) return null;
+        out[term.slice(0, eq).trim()] = term.slice(eq + 1).trim();
+    }
+    return out;
+}
+
 // Client-side mirror of networking::effective_site for the UI hint —
 // shows the operator what WolfNet would auto-derive when the explicit
 // Site field is left blank. Returns null for public/unparseable
@@ -15917,6 +17202,18 @@ async function saveNodeSettings() {
         if (newSite !== oldSite) updates.site = newSite;
     }
 
+    // Labels — sent as the full map, only when the text changed.
+    const labelsEl = document.getElementById('node-settings-labels');
+    if (labelsEl) {
+        const node = allNodes.find(n => n.id === nodeId);
+        const parsed = parseNodeLabels(labelsEl.value);
+        if (parsed === null) {
+            showToast('Labels must be comma-separated key=value pairs', 'error');
+            return;
+        }
+        if (formatNodeLabels(parsed) !== formatNodeLabels(node && node.labels)) updates.labels = parsed;
+    }
+
     // Tier roles — the checked boxes vs the node's current roles. Send the
     // full desired set (the API treats Some([]) as "clear all roles"), but
     // only when it actually changed so an unrelated edit doesn't touch them.
@@ -15932,6 +17229,41 @@ async function saveNodeSettings() {
         }
     }
 
+    // A WolfStack node's address is changed cluster-wide through the
+    // supervised re-address (checked from every peer, then committed
+    // everywhere) — a plain PATCH here would be gossiped back by peers.
+    const editedNode = allNodes.find(n => n.id === nodeId);
+    if (editedNode && editedNode.node_type === 'wolfstack' && (updates.address || updates.port)) {
+        const address = updates.address || editedNode.address;
+        const port = updates.port || editedNode.port;
+        if (!(await showConfirm(`Move ${editedNode.hostname} to ${address}:${port}? Every node checks the new address can be reached before anything changes.`))) return;
+        showToast('Checking the new address from every node…', 'info');
+        try {
+            const resp = await fetch(`/api/nodes/${encodeURIComponent(nodeId)}/readdress`, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ address, port }),
+            });
+            const data = await resp.json().catch(() => ({}));
+            if (!resp.ok) {
+                const detail = (data.failed || []).map(f => `${f.node}: ${f.error}`).join('\n');
+                showModal((data.error || `HTTP ${resp.status}`) + (detail ? '\n\n' + detail : ''), 'Re-address failed');
+                return;
+            }
+            showToast(`${editedNode.hostname} is now at ${data.address}:${data.port}`, 'success');
+        } catch (e) {
+            showToast('Re-address failed: ' + e.message, 'error');
+            return;
+        }
+        delete updates.address;
+        delete updates.port;
+        if (Object.keys(updates).length === 0) {
+            modal.remove();
+            fetchNodes();
+            return;
+        }
+    }
+
     if (Object.keys(updates).length === 0) {
         showToast('No changes to save', 'info');
         return;
@@ -16798,6 +18130,8 @@ async function refreshComponentDetail(name, opts) {
 
         // Action buttons
         document.getElementById('detail-btn-configure').style.display = hasConfigurator(name) ? '' : 'none';
+        document.getElementById('detail-btn-upgrade').style.display =
+            (d.installed && ['wolfnet', 'wolfproxy', 'wolfserve', 'wolfdisk', 'wolfscale'].includes(name)) ? '' : 'none';
         document.getElementById('detail-btn-start').style.display = d.running ? 'none' : '';
         document.getElementById('detail-btn-restart').style.display = d.running ? '' : 'none';
         document.getElementById('detail-btn-stop').style.display = d.running ? '' : 'none';
@@ -17263,6 +18597,176 @@ async function detailServiceAction(action) {
     }
 }
 
+// ─── Blue/green component upgrades ───
+// The node downloads the new binary, trial-runs it beside the live service
+// (WolfProxy/WolfServe), swaps it in and rolls back if the service isn't
+// healthy afterwards.
+function renderUpgradeReport(r) {
+    const colour = { upgraded: 'var(--success)', aborted: 'var(--text-muted)', rolled_back: 'var(--warning)', rollback_failed: 'var(--danger)' }[r.outcome] || 'var(--text-muted)';
+    return `<div style="border:1px solid var(--border);border-radius:8px;padding:8px 10px;margin-top:6px;font-size:12px;">
+        <div><strong style="color:${colour};">${escapeHtml(r.outcome.replace('_', ' '))}</strong>
+            <span style="color:var(--text-muted);">${new Date(r.ts * 1000).toLocaleString()} · ${escapeHtml(r.from_version || '?')} → ${escapeHtml(r.to_version || '?')}</span></div>
+        ${(r.steps || []).map(s => `<div style="margin-left:10px;color:${s.ok ? 'var(--text-secondary)' : 'var(--danger)'};">${s.ok ? '✓' : '✗'} ${escapeHtml(s.name)}${s.detail ? ': ' + escapeHtml(s.detail) : ''}</div>`).join('')}
+    </div>`;
+}
+
+async function openComponentUpgrade() {
+    if (!currentComponent) return;
+    const name = currentComponent;
+    let info;
+    try {
+        const resp = await fetch(apiUrl(`/api/components/${name}/upgrade`));
+        info = await resp.json();
+        if (!resp.ok) throw new Error(info.error || ('HTTP ' + resp.status));
+    } catch (e) {
+        showToast('Failed to load upgrade info: ' + e.message, 'error');
+        return;
+    }
+    const history = (info.history || []).slice(0, 5);
+    const html = `<div style="white-space:normal;">
+        <p style="font-size:12px;color:var(--text-secondary);margin:0 0 10px;">Installed: <strong>${escapeHtml(info.version || 'unknown')}</strong>.
+            ${info.trial_run ? 'The new binary first runs beside the live service on spare ports.' : 'This component can\'t run twice on one host, so the new binary is only checked with --version before the swap.'}
+            If the service isn't healthy after the swap, the old binary is put back.</p>
+        <div class="form-group" style="margin-bottom:8px;"><label style="font-size:12px;">Binary URL</label>
+            <input type="text" class="form-control" id="cu-url" value="${escapeAttr(info.default_url || '')}" placeholder="https://…/${escapeAttr(name)}-x86_64"></div>
+        <div style="display:flex;gap:8px;">
+            <div class="form-group" style="flex:3;margin-bottom:8px;"><label style="font-size:12px;">SHA-256 (optional)</label>
+                <input type="text" class="form-control" id="cu-sha" style="font-family:monospace;font-size:11px;"></div>
+            <div class="form-group" style="flex:1;margin-bottom:8px;"><label style="font-size:12px;">Soak (s)</label>
+                <input type="number" class="form-control" id="cu-soak" value="20" min="5" max="300"></div>
+        </div>
+        <div id="cu-result"></div>
+        <div style="display:flex;justify-content:space-between;margin-top:10px;">
+            <button class="btn btn-sm" onclick="rollbackComponent(this)" title="Swap back to the binary the last upgrade kept">Roll back to previous</button>
+            <button class="btn btn-primary" onclick="runComponentUpgrade(this)">Upgrade</button>
+        </div>
+        ${history.length ? '<h4 style="margin:14px 0 4px;">Recent upgrades</h4>' + history.map(renderUpgradeReport).join('') : ''}
+    </div>`;
+    showModal(html, 'Upgrade ' + name, { noOk: true });
+}
+
+async function runComponentUpgrade(btn) {
+    const name = currentComponent;
+    const result = document.getElementById('cu-result');
+    btn.disabled = true;
+    result.innerHTML = '<p style="font-size:12px;color:var(--text-muted);">Upgrading — this takes a minute or two…</p>';
+    try {
+        const resp = await fetch(apiUrl(`/api/components/${name}/upgrade`), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                binary_url: document.getElementById('cu-url').value.trim(),
+                sha256: document.getElementById('cu-sha').value.trim(),
+                soak_secs: parseInt(document.getElementById('cu-soak').value, 10) || 20
+            })
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        result.innerHTML = renderUpgradeReport(data);
+        showToast(name + ': ' + data.outcome.replace('_', ' '), data.outcome === 'upgraded' ? 'success' : 'error');
+        taskLog('Upgrade ' + name, data.outcome === 'upgraded' ? undefined : 'failed');
+        refreshComponentDetail(name);
+    } catch (e) {
+        result.innerHTML = '<p style="color:var(--danger);font-size:12px;">' + escapeHtml(e.message) + '</p>';
+    } finally {
+        btn.disabled = false;
+    }
+}
+
+async function rollbackComponent(btn) {
+    const name = currentComponent;
+    if (!await showConfirm(`Swap ${name} back to the binary kept by its last upgrade?`, 'Roll back')) return;
+    btn.disabled = true;
+    try {
+        const resp = await fetch(apiUrl(`/api/components/${name}/rollback`), { method: 'POST' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        document.getElementById('cu-result').innerHTML = renderUpgradeReport(data);
+        refreshComponentDetail(name);
+    } catch (e) {
+        showToast('Rollback failed: ' + e.message, 'error');
+    } finally {
+        btn.disabled = false;
+    }
+}
+
+// ─── Cluster-wide install ───
+
+function openClusterInstall() {
+    if (!currentComponent) return;
+    const name = currentComponent;
+    const nodes = allNodes.filter(n => n.node_type === 'wolfstack')
+        .sort((a, b) => (a.hostname || '').localeCompare(b.hostname || ''));
+    const rows = nodes.map(n => {
+        const comp = (n.components || []).find(c => (c.component || '').toLowerCase() === name);
+        const have = comp && comp.installed ? (comp.version || 'installed') : 'not installed';
+        return `<label style="display:flex;align-items:center;gap:8px;font-size:12px;padding:3px 0;${n.online ? '' : 'opacity:0.5;'}">
+            <input type="checkbox" class="ci-node" value="${escapeAttr(n.id)}" ${n.online ? 'checked' : 'disabled'}>
+            <span style="flex:1;">${escapeHtml(n.display_name || n.hostname)}${n.online ? '' : ' (offline)'}</span>
+            <span style="color:var(--text-muted);">${escapeHtml(have)}</span></label>`;
+    }).join('');
+    const html = `<div style="white-space:normal;">
+        <p style="font-size:12px;color:var(--text-secondary);margin:0 0 10px;">Installs ${escapeHtml(name)} — or updates it where it's already installed — on every ticked node at once.</p>
+        <div id="ci-nodes" style="max-height:220px;overflow-y:auto;margin-bottom:10px;">${rows || '<p style="font-size:12px;">No WolfStack nodes.</p>'}</div>
+        <div id="ci-result"></div>
+        <div style="text-align:right;margin-top:10px;"><button class="btn btn-primary" id="ci-start" onclick="runClusterInstall(this)">Install on selected nodes</button></div>
+    </div>`;
+    showModal(html, 'Install ' + name + ' across the cluster', { noOk: true });
+}
+
+function renderClusterInstall(job) {
+    const colour = { ok: 'var(--success)', failed: 'var(--danger)', running: 'var(--accent-light)', pending: 'var(--text-muted)' };
+    const rows = job.nodes.map(n => `<tr>
+        <td style="padding:3px 6px;">${escapeHtml(n.hostname)}</td>
+        <td style="padding:3px 6px;color:${colour[n.status] || 'inherit'};">${escapeHtml(n.status)}</td>
+        <td style="padding:3px 6px;color:var(--text-muted);word-break:break-word;">${escapeHtml(n.message || '')}</td></tr>`).join('');
+    const summary = job.status === 'running'
+        ? `${job.succeeded + job.failed} of ${job.nodes.length} finished…`
+        : `${job.succeeded} succeeded, ${job.failed} failed.`;
+    return `<p style="font-size:12px;margin:0 0 6px;"><strong>${escapeHtml(summary)}</strong></p>
+        <table style="width:100%;font-size:12px;border-collapse:collapse;">${rows}</table>`;
+}
+
+async function runClusterInstall(btn) {
+    const name = currentComponent;
+    const nodeIds = [...document.querySelectorAll('.ci-node:checked')].map(c => c.value);
+    if (!nodeIds.length) { showToast('Select at least one node', 'error'); return; }
+    const result = document.getElementById('ci-result');
+    btn.disabled = true;
+    let job;
+    try {
+        const resp = await fetch(`/api/components/${name}/install-cluster`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ node_ids: nodeIds })
+        });
+        job = await resp.json();
+        if (!resp.ok) throw new Error(job.error || ('HTTP ' + resp.status));
+    } catch (e) {
+        result.innerHTML = '<p style="color:var(--danger);font-size:12px;">' + escapeHtml(e.message) + '</p>';
+        btn.disabled = false;
+        return;
+    }
+    document.getElementById('ci-nodes').style.display = 'none';
+    btn.style.display = 'none';
+    result.innerHTML = renderClusterInstall(job);
+    while (job.status === 'running') {
+        await new Promise(r => setTimeout(r, 3000));
+        // Stop polling once the modal has been closed.
+        if (!document.body.contains(result)) return;
+        try {
+            const resp = await fetch(`/api/components/install-jobs/${job.id}`);
+            if (!resp.ok) break;
+            job = await resp.json();
+            result.innerHTML = renderClusterInstall(job);
+        } catch (_) { /* transient — try again next tick */ }
+    }
+    if (job.status === 'running') return;
+    showToast(`${name}: ${job.succeeded} of ${job.nodes.length} nodes installed`, job.failed ? 'error' : 'success');
+    taskLog('Install ' + name + ' across cluster', job.failed ? 'failed' : undefined);
+    refreshComponentDetail(name);
+}
+
 async function saveConfig() {
     if (!currentComponent) return;
     const content = document.getElementById('detail-config-editor').value;
@@ -17284,6 +18788,96 @@ async function saveConfig() {
     }
 }
 
+// ─── Config History ───
+
+/// Revisions of one config file on the selected node. With no path, lists
+/// every file with history — `suffix` narrows that list (an LXC config's
+/// directory differs between hosts).
+async function openConfigHistory(path, suffix) {
+    if (!path) {
+        let files = [];
+        try {
+            const resp = await fetch(apiUrl('/api/config-history'));
+            files = await resp.json();
+            if (!resp.ok) throw new Error(files.error || ('HTTP ' + resp.status));
+        } catch (e) {
+            showToast('Failed to load config history: ' + e.message, 'error');
+            return;
+        }
+        if (suffix) files = files.filter(f => f.path.endsWith(suffix));
+        if (files.length === 1) return openConfigHistory(files[0].path);
+        const rows = files.map(f => `<tr style="cursor:pointer;" onclick="openConfigHistory('${escapeAttr(f.path)}')">
+            <td style="padding:3px 6px;font-family:monospace;">${escapeHtml(f.path)}</td>
+            <td style="padding:3px 6px;">${f.revisions}</td>
+            <td style="padding:3px 6px;">${new Date(f.last_ts * 1000).toLocaleString()}</td></tr>`).join('');
+        showModal(`<div style="white-space:normal;">${rows ? '<table style="width:100%;font-size:12px;">' + rows + '</table>'
+            : '<p style="font-size:12px;">No history yet — revisions are kept from the first save through WolfStack.</p>'}</div>`, 'Config history');
+        return;
+    }
+    let data;
+    try {
+        const resp = await fetch(apiUrl('/api/config-history/file?path=' + encodeURIComponent(path)));
+        data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+    } catch (e) {
+        showToast('Failed to load config history: ' + e.message, 'error');
+        return;
+    }
+    document.querySelectorAll('.modal-overlay').forEach(m => m.remove());
+    const rows = (data.revisions || []).map((r, i) => `<tr>
+        <td style="padding:3px 6px;white-space:nowrap;">${new Date(r.ts * 1000).toLocaleString()}</td>
+        <td style="padding:3px 6px;">${escapeHtml(r.user || '—')}</td>
+        <td style="padding:3px 6px;color:var(--text-muted);">${escapeHtml(r.note || '')}${i === 0 ? ' <em>(latest)</em>' : ''}</td>
+        <td style="padding:3px 6px;white-space:nowrap;text-align:right;">
+            <button class="btn btn-sm" onclick="showConfigDiff('${escapeAttr(path)}', ${r.id})">Diff</button>
+            <button class="btn btn-sm" onclick="rollbackConfig('${escapeAttr(path)}', ${r.id})">Restore</button></td></tr>`).join('');
+    const html = `<div style="white-space:normal;">
+        <p style="font-size:12px;color:var(--text-secondary);margin:0 0 8px;font-family:monospace;">${escapeHtml(path)}</p>
+        ${rows ? '<table style="width:100%;font-size:12px;border-collapse:collapse;">' + rows + '</table>'
+            : '<p style="font-size:12px;">No history yet — revisions are kept from the first save through WolfStack.</p>'}
+        <pre id="ch-diff" style="display:none;margin-top:10px;max-height:260px;overflow:auto;font-size:11px;background:var(--bg-primary);padding:8px;border-radius:6px;"></pre>
+    </div>`;
+    showModal(html, 'Config history');
+}
+
+async function showConfigDiff(path, id) {
+    const out = document.getElementById('ch-diff');
+    try {
+        const resp = await fetch(apiUrl(`/api/config-history/diff?path=${encodeURIComponent(path)}&id=${id}`));
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        out.innerHTML = data.diff
+            ? data.diff.split('\n').map(l => {
+                const colour = l.startsWith('@@') ? 'var(--accent-light)' : l.startsWith('+') ? 'var(--success)' : l.startsWith('-') ? 'var(--danger)' : 'inherit';
+                return `<span style="color:${colour};">${escapeHtml(l)}</span>`;
+            }).join('\n')
+            : 'Identical to the current file.';
+    } catch (e) {
+        out.textContent = e.message;
+    }
+    out.style.display = '';
+}
+
+async function rollbackConfig(path, id) {
+    if (!await showConfirm(`Restore ${path} to this revision? The current contents stay in the history.`, 'Restore config')) return;
+    try {
+        const resp = await fetch(apiUrl('/api/config-history/rollback'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ path, id })
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast(data.message, 'success');
+        if (currentComponent && document.getElementById('detail-config-path').textContent === path) {
+            refreshComponentDetail(currentComponent);
+        }
+        openConfigHistory(path);
+    } catch (e) {
+        showToast('Restore failed: ' + e.message, 'error');
+    }
+}
+
 // ─── Component Configurators ───
 
 const CONFIGURATOR_COMPONENTS = ['wolfproxy', 'wolfserve', 'wolfdisk', 'wolfscale'];
@@ -17558,6 +19152,7 @@ async function loadNginxConfigurator() {
     document.getElementById('configurator-title').textContent = 'Nginx / WolfProxy Sites';
     document.getElementById('configurator-header-actions').innerHTML = `
         <button class="btn btn-primary btn-sm" onclick="nginxNewSiteForm()">+ New Site</button>
+        <button class="btn btn-primary btn-sm" onclick="nginxPublishForm()">Publish Container</button>
         <button class="btn btn-sm" onclick="nginxTestConfig()">Test Config</button>
         <button class="btn btn-success btn-sm" onclick="nginxReloadService()">Reload</button>
         <button class="btn btn-sm" onclick="loadCertManager()">SSL Certificates</button>
@@ -17719,6 +19314,104 @@ async function nginxDeleteSite(name) {
     }
 }
 
+// One-shot: domain → container WolfNet IP:port, optional certificate, reload.
+async function nginxPublishForm() {
+    const body = document.getElementById('configurator-body');
+    body.innerHTML = `
+        <div style="margin-bottom:12px; display:flex; align-items:center; gap:12px;">
+            <button class="btn btn-sm" onclick="loadNginxConfigurator()">← Back</button>
+            <h4 style="margin:0;">Publish a Container</h4>
+        </div>
+        <p style="color:var(--text-muted); font-size:12px; margin-bottom:16px;">Creates a proxy site for the domain pointing at the container's WolfNet IP, enables it and reloads the proxy. With HTTPS on, a Let's Encrypt certificate is requested (the domain's DNS must already point at this node) and the site is switched over.</p>
+        <div style="display:grid; grid-template-columns:1fr 1fr; gap:16px; max-width:700px;">
+            <div class="form-group" style="grid-column:span 2;">
+                <label style="font-weight:600; margin-bottom:4px; display:block;">Domain</label>
+                <input class="form-control" id="publish-domain" placeholder="app.example.com">
+            </div>
+            <div class="form-group">
+                <label style="font-weight:600; margin-bottom:4px; display:block;">Container</label>
+                <select class="form-control" id="publish-container" onchange="document.getElementById('publish-ip-group').style.display = this.value ? 'none' : ''">
+                    <option value="">Enter an IP address…</option>
+                </select>
+            </div>
+            <div class="form-group">
+                <label style="font-weight:600; margin-bottom:4px; display:block;">Port</label>
+                <input class="form-control" id="publish-port" type="number" min="1" max="65535" placeholder="8080">
+            </div>
+            <div class="form-group" id="publish-ip-group" style="grid-column:span 2;">
+                <label style="font-weight:600; margin-bottom:4px; display:block;">Upstream IP</label>
+                <input class="form-control" id="publish-ip" placeholder="10.10.10.20">
+            </div>
+            <div class="form-group">
+                <label style="display:flex; align-items:center; gap:8px;"><input type="checkbox" id="publish-ssl" checked> HTTPS (Let's Encrypt)</label>
+            </div>
+            <div class="form-group">
+                <input class="form-control" id="publish-email" placeholder="Contact email (optional if saved)">
+            </div>
+        </div>
+        <div style="display:flex; gap:8px; margin-top:12px;">
+            <button class="btn btn-primary" id="publish-btn" onclick="nginxPublishSite()">Publish</button>
+        </div>
+        <pre id="publish-steps" style="display:none; margin-top:16px; background:var(--bg-input); padding:12px; border-radius:8px; font-size:12px; white-space:pre-wrap;"></pre>`;
+
+    const select = document.getElementById('publish-container');
+    for (const type of ['docker', 'lxc']) {
+        try {
+            const resp = await fetch(apiUrl(`/api/containers/${type}`));
+            const list = resp.ok ? await resp.json() : [];
+            (Array.isArray(list) ? list : []).forEach(c => {
+                const opt = document.createElement('option');
+                opt.value = `${type}:${c.name}`;
+                opt.textContent = `${c.name} (${type === 'docker' ? 'Docker' : 'LXC'})`;
+                select.appendChild(opt);
+            });
+        } catch (e) { /* list stays IP-only */ }
+    }
+}
+
+async function nginxPublishSite() {
+    const container = document.getElementById('publish-container').value;
+    const [containerType, containerName] = container ? [container.split(':')[0], container.slice(container.indexOf(':') + 1)] : ['', ''];
+    const params = {
+        domain: document.getElementById('publish-domain').value.trim(),
+        container_type: containerType,
+        container: containerName,
+        upstream_ip: document.getElementById('publish-ip').value.trim(),
+        upstream_port: parseInt(document.getElementById('publish-port').value, 10) || 0,
+        ssl: document.getElementById('publish-ssl').checked,
+        email: document.getElementById('publish-email').value.trim(),
+    };
+    if (!params.domain) { showToast('Domain is required', 'error'); return; }
+    if (!params.upstream_port) { showToast('Port is required', 'error'); return; }
+
+    const btn = document.getElementById('publish-btn');
+    const out = document.getElementById('publish-steps');
+    btn.disabled = true;
+    btn.textContent = params.ssl ? 'Publishing (requesting certificate)…' : 'Publishing…';
+    out.style.display = 'none';
+    try {
+        const resp = await fetch(configuratorApiUrl('/api/configurator/nginx/publish'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(params)
+        });
+        const data = await resp.json();
+        out.style.display = '';
+        if (resp.ok) {
+            out.textContent = (data.steps || []).map(s => '✓ ' + s).join('\n');
+            showToast(`${params.domain} published`, 'success');
+        } else {
+            out.textContent = data.error || 'Publish failed';
+            showToast('Publish failed', 'error');
+        }
+    } catch (e) {
+        showToast('Failed: ' + e.message, 'error');
+    } finally {
+        btn.disabled = false;
+        btn.textContent = 'Publish';
+    }
+}
+
 function nginxNewSiteForm() {
     const body = document.getElementById('configurator-body');
     body.innerHTML = `
@@ -19314,9 +21007,10 @@ setInterval(() => {
     // On slow tick, skip if we're not due yet
     _currentPollSpeed = needsFast ? 'fast' : 'slow';
 }, POLL_FAST);
-// Slow background poll for when user is on other pages
+// Slow background poll for when user is on other pages — node state
+// changes come over the live event stream while it's connected.
 setInterval(() => {
-    if (_currentPollSpeed === 'slow') fetchNodes();
+    if (_currentPollSpeed === 'slow' && !_eventStreamLive) fetchNodes();
 }, POLL_SLOW);
 // Load k8s cluster data for sidebar badges (non-blocking)
 (async () => {
@@ -19353,9 +21047,73 @@ async function pollAlerts() {
         }
     } catch (e) { /* ignore fetch errors */ }
 }
-// Poll every 60 seconds, first poll after 10s — only when task log is visible
+// Poll every 60 seconds, first poll after 10s — only when task log is visible.
+// While the live event stream is up, alerts arrive on it instead.
 setTimeout(pollAlerts, 10000);
-setInterval(() => { if (_taskLogVisible) pollAlerts(); }, 60000);
+setInterval(() => { if (_taskLogVisible && !_eventStreamLive) pollAlerts(); }, 60000);
+
+// ─── Live event stream (/api/ws/events) ───
+// Node state changes, this node's container lifecycle, alerts and job
+// progress are pushed here, so the slow node poll and the alert poll
+// stand down while it's connected. Every event is also re-dispatched on
+// window as a 'wolfstack-event' CustomEvent for pages that want it.
+let _eventStreamLive = false;
+let _eventStreamRetry = 1000;
+
+function connectEventStream() {
+    const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+    let ws;
+    try { ws = new WebSocket(`${protocol}//${window.location.host}/api/ws/events`); }
+    catch (e) { return; }
+    ws.onopen = () => {
+        _eventStreamLive = true;
+        _eventStreamRetry = 1000;
+        // Catch up on whatever happened while we were disconnected.
+        fetchNodes();
+        pollAlerts();
+    };
+    ws.onmessage = (msg) => {
+        let ev;
+        try { ev = JSON.parse(msg.data); } catch (e) { return; }
+        handleLiveEvent(ev);
+        window.dispatchEvent(new CustomEvent('wolfstack-event', { detail: ev }));
+    };
+    ws.onclose = () => {
+        _eventStreamLive = false;
+        setTimeout(connectEventStream, _eventStreamRetry);
+        _eventStreamRetry = Math.min(_eventStreamRetry * 2, 60000);
+    };
+}
+
+function handleLiveEvent(ev) {
+    const name = ev.event || '';
+    const p = ev.payload || {};
+    if (name === 'lagged') {
+        fetchNodes();
+        pollAlerts();
+    } else if (name === 'node_online' || name === 'node_offline') {
+        fetchNodes();
+    } else if (name.startsWith('container_')) {
+        // Container events are this node's; reload only if that's the node on screen.
+        const node = allNodes.find(n => n.id === currentNodeId);
+        if (!node || node.hostname !== ev.hostname) return;
+        if (currentPage === 'containers' && p.runtime === 'docker') loadDockerContainers();
+        if (currentPage === 'lxc' && p.runtime === 'lxc') loadLxcContainers();
+    } else if (name === 'alert') {
+        if (p.id && p.id <= _lastAlertId) return;
+        addTaskLogEntry({
+            cluster: p.cluster,
+            node: p.hostname,
+            description: p.title + (p.detail ? ' — ' + p.detail : ''),
+            status: p.severity === 'critical' ? 'failed' : 'warning',
+            type: p.severity,
+        });
+        if (p.id > _lastAlertId) _lastAlertId = p.id;
+    }
+    // 'job' events (migration / PBS restore progress) reach pages only
+    // through the 'wolfstack-event' dispatch.
+}
+connectEventStream();
 
 // ─── Container Management ───
 
@@ -20177,6 +21935,24 @@ async function renderAlertsSchedule() {
             style="width:36px;height:36px;border-radius:8px;border:1px solid ${on?'#3b82f6':'var(--border)'};background:${on?'rgba(59,130,246,0.2)':'transparent'};color:${on?'var(--accent-light)':'var(--text-primary)'};cursor:pointer;font-weight:600;font-size:13px;">${label}</button>`;
     }).join('');
     const supprChecked = (cat) => qh.suppress_categories.includes(cat) ? 'checked' : '';
+    // Per-channel windows silence every non-critical category; only the
+    // times, timezone and digest choice are per channel.
+    const channelQh = cfg.channel_quiet_hours || {};
+    const channelRows = [['discord','Discord'],['slack','Slack'],['telegram','Telegram'],['ntfy','ntfy'],['email','Email']].map(([key, label]) => {
+        const c = channelQh[key] || { enabled:false, start_hhmm:'22:00', end_hhmm:'08:00', timezone: qh.timezone || 'UTC', digest:true };
+        return `<tr data-channel="${key}">
+            <td style="padding:4px 6px;"><label style="display:flex; align-items:center; gap:6px; font-size:12px;"><input type="checkbox" class="chq-enabled" ${c.enabled?'checked':''}> ${label}</label></td>
+            <td style="padding:4px 6px;"><input type="time" class="form-control chq-start" value="${escapeAttr(c.start_hhmm)}"></td>
+            <td style="padding:4px 6px;"><input type="time" class="form-control chq-end" value="${escapeAttr(c.end_hhmm)}"></td>
+            <td style="padding:4px 6px;"><input type="text" class="form-control chq-tz" value="${escapeAttr(c.timezone)}" placeholder="UTC"></td>
+            <td style="padding:4px 6px; text-align:center;"><input type="checkbox" class="chq-digest" ${c.digest?'checked':''} title="Send a digest when the window ends"></td>
+        </tr>`;
+    }).join('');
+    const digestMin = cfg.digest_minutes || {};
+    const digestInput = (cat, label) => `<div>
+        <label style="display:block; font-size:11px; color:var(--text-muted);">${label}</label>
+        <input type="number" id="sched-digest-${cat}" class="form-control" min="0" max="1440" value="${digestMin[cat] || 0}" style="max-width:120px;">
+    </div>`;
 
     host.innerHTML = `
         <div style="display:grid; grid-template-columns:1fr 1fr; gap:14px; margin-bottom:14px;">
@@ -20215,6 +21991,10 @@ async function renderAlertsSchedule() {
                     <label style="display:block; font-size:12px; margin-bottom:4px;"><input type="checkbox" id="sched-supp-posture" ${supprChecked('posture')}> Posture (config findings)</label>
                     <label style="display:block; font-size:12px;"><input type="checkbox" id="sched-supp-bruteforce" ${supprChecked('brute_force')}> Brute-force (failed-auth chatter)</label>
                 </div>
+                <label style="display:flex; align-items:center; gap:8px; margin-top:12px; font-size:12px; cursor:pointer;">
+                    <input type="checkbox" id="sched-qh-digest" ${qh.digest?'checked':''}>
+                    <span>Send a digest of what was held when the window ends (instead of dropping it)</span>
+                </label>
             </div></div>
 
             <!-- Alert cooldown card -->
@@ -20254,6 +22034,33 @@ async function renderAlertsSchedule() {
                 </div>
             </div></div>
 
+            <!-- Per-channel quiet hours -->
+            <div class="card"><div class="card-body" style="padding:18px;">
+                <h3 style="margin:0 0 8px 0; font-size:15px;">Per-channel quiet hours</h3>
+                <p style="font-size:12px; color:var(--text-muted); margin:0 0 14px 0;">Silence non-critical alerts on one channel only — e.g. keep the phone quiet at night while Slack still gets everything. Compromise alerts always fire. Applies on top of the quiet hours above.</p>
+                <table style="width:100%; border-collapse:collapse;">
+                    <thead><tr style="font-size:11px; color:var(--text-muted); text-align:left;">
+                        <th style="padding:4px 6px;">Channel</th><th style="padding:4px 6px;">Start</th><th style="padding:4px 6px;">End</th><th style="padding:4px 6px;">Timezone</th><th style="padding:4px 6px;">Digest</th>
+                    </tr></thead>
+                    <tbody id="sched-channel-qh">${channelRows}</tbody>
+                </table>
+            </div></div>
+
+            <!-- Digest intervals -->
+            <div class="card"><div class="card-body" style="padding:18px;">
+                <h3 style="margin:0 0 8px 0; font-size:15px;">Digest intervals</h3>
+                <p style="font-size:12px; color:var(--text-muted); margin:0 0 14px 0;">Batch an alert type into one notification every N minutes instead of one per alert. 0 = send immediately. Compromise alerts are never batched.</p>
+                <div style="display:grid; grid-template-columns:1fr 1fr; gap:10px;">
+                    ${digestInput('threshold', 'Threshold (min)')}
+                    ${digestInput('lifecycle', 'Lifecycle (min)')}
+                    ${digestInput('posture', 'Posture (min)')}
+                    ${digestInput('brute_force', 'Brute-force (min)')}
+                </div>
+                <small style="color:var(--text-muted); font-size:11px; display:block; margin-top:10px;">${cfg.held_alerts ? `${cfg.held_alerts} alert${cfg.held_alerts === 1 ? '' : 's'} currently held for a digest.` : 'Nothing held for a digest right now.'}</small>
+            </div></div>
+        </div>
+
+        <div style="display:grid; grid-template-columns:1fr 1fr; gap:14px; margin-bottom:14px;">
             <!-- Recovery notifications -->
             <div class="card"><div class="card-body" style="padding:18px;">
                 <h3 style="margin:0 0 8px 0; font-size:15px;">Recovery notifications</h3>
@@ -20297,6 +22104,23 @@ async function saveAlertsSchedule() {
     if (document.getElementById('sched-supp-posture').checked) suppressCats.push('posture');
     if (document.getElementById('sched-supp-bruteforce').checked) suppressCats.push('brute_force');
 
+    const channelQuietHours = {};
+    document.querySelectorAll('#sched-channel-qh tr[data-channel]').forEach(row => {
+        channelQuietHours[row.dataset.channel] = {
+            enabled: row.querySelector('.chq-enabled').checked,
+            start_hhmm: row.querySelector('.chq-start').value || '22:00',
+            end_hhmm: row.querySelector('.chq-end').value || '08:00',
+            timezone: row.querySelector('.chq-tz').value.trim() || 'UTC',
+            days_of_week: 0x7F,
+            suppress_categories: ['threshold', 'lifecycle', 'posture', 'brute_force'],
+            digest: row.querySelector('.chq-digest').checked,
+        };
+    });
+    const digestMinutes = {};
+    ['threshold', 'lifecycle', 'posture', 'brute_force'].forEach(cat => {
+        digestMinutes[cat] = parseInt(document.getElementById('sched-digest-' + cat).value, 10) || 0;
+    });
+
     const payload = {
         quiet_hours: {
             enabled,
@@ -20305,7 +22129,10 @@ async function saveAlertsSchedule() {
             timezone: document.getElementById('sched-qh-tz').value.trim() || 'UTC',
             days_of_week: dowMask || 0x7F,
             suppress_categories: suppressCats,
+            digest: document.getElementById('sched-qh-digest').checked,
         },
+        channel_quiet_hours: channelQuietHours,
+        digest_minutes: digestMinutes,
         cooldown_secs: parseInt(document.getElementById('sched-cooldown').value, 10) || 900,
         max_alerts_per_hour: parseInt(document.getElementById('sched-max-per-hour').value, 10) || 0,
         grouping_window_secs: parseInt(document.getElementById('sched-group-secs').value, 10) || 0,
@@ -20470,6 +22297,90 @@ async function installRuntime(runtime) {
 
 let cachedInterfaces = [];
 
+async function loadWolfNetHealth() {
+    const body = document.getElementById('wolfnet-health-body');
+    if (!body) return;
+    const minutes = (document.getElementById('wolfnet-health-window') || {}).value || 60;
+    try {
+        const resp = await fetch(apiUrl(`/api/networking/wolfnet/health?minutes=${minutes}`));
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        renderWolfNetHealth(data, parseInt(minutes));
+    } catch (e) {
+        body.innerHTML = `<p style="color:var(--danger);">Failed to load tunnel telemetry: ${escapeHtml(e.message)}</p>`;
+    }
+}
+
+function renderWolfNetHealth(data, minutes) {
+    const body = document.getElementById('wolfnet-health-body');
+    const muted = 'color:var(--text-muted); font-size:12px;';
+    if (!data.peers.length) {
+        body.innerHTML = `<p style="${muted}">No samples yet — telemetry starts a minute after WolfStack starts, once WolfNet reports peers.</p>`;
+        return;
+    }
+    const staleSecs = (data.stale_minutes || 5) * 60;
+    const ageColor = age => age == null ? 'var(--text-muted)'
+        : age < 180 ? 'var(--success)' : age < staleSecs ? 'var(--warning)' : 'var(--danger)';
+    // One cell per sample, bucketed to at most 60 so the strip stays readable.
+    const strip = samples => {
+        if (!samples.length) return '';
+        const per = Math.max(1, Math.ceil(samples.length / 60));
+        const cells = [];
+        for (let i = 0; i < samples.length; i += per) {
+            const chunk = samples.slice(i, i + per);
+            const worst = Math.max(...chunk.map(s => s.handshake_age_secs == null ? Infinity : s.handshake_age_secs));
+            const down = chunk.some(s => !s.connected);
+            const color = down ? 'var(--danger)' : ageColor(worst === Infinity ? null : worst);
+            const when = new Date(chunk[0].t * 1000).toLocaleTimeString();
+            cells.push(`<span title="${when}${down ? ' — disconnected' : ''}" style="display:inline-block; width:4px; height:14px; margin-right:1px; background:${color};"></span>`);
+        }
+        return `<div style="white-space:nowrap;">${cells.join('')}</div>`;
+    };
+    const rate = (samples, key) => {
+        if (samples.length < 2) return null;
+        const a = samples[samples.length - 2], b = samples[samples.length - 1];
+        const dt = b.t - a.t;
+        return dt > 0 && b[key] >= a[key] ? (b[key] - a[key]) / dt : null;
+    };
+    const rows = data.peers.map(p => {
+        const rx = rate(p.samples, 'rx_bytes'), tx = rate(p.samples, 'tx_bytes');
+        const age = p.handshake_age_secs;
+        const status = p.stale
+            ? '<span class="badge" style="background:rgba(239,68,68,0.15); color:#ef4444;">Silent</span>'
+            : p.connected
+                ? '<span class="badge" style="background:rgba(34,197,94,0.15); color:#22c55e;">Up</span>'
+                : p.relay_via
+                    ? `<span class="badge" style="background:rgba(6,182,212,0.15); color:#06b6d4;">Relay via ${escapeHtml(p.relay_via)}</span>`
+                    : '<span class="badge" style="background:rgba(239,68,68,0.15); color:#ef4444;">Down</span>';
+        return `<tr>
+            <td style="font-weight:600;">${escapeHtml(p.hostname)}<div style="${muted} font-family:var(--font-mono);">${escapeHtml(p.address)}</div></td>
+            <td>${status}</td>
+            <td style="color:${ageColor(age)}; font-size:12px;">${age == null ? 'never' : formatDuration(age) + ' ago'}</td>
+            <td style="font-size:12px;">${rx == null ? '—' : '↓' + formatBytes(rx) + '/s ↑' + formatBytes(tx || 0) + '/s'}
+                <div style="${muted}">↓${formatBytes(p.rx_bytes)} ↑${formatBytes(p.tx_bytes)} total</div></td>
+            <td style="font-family:var(--font-mono); font-size:12px;">${escapeHtml(p.endpoint || '—')}
+                ${p.endpoint_changes ? `<div style="${muted}">${p.endpoint_changes} change${p.endpoint_changes === 1 ? '' : 's'}</div>` : ''}</td>
+            <td>${strip(p.samples)}</td>
+        </tr>`;
+    }).join('');
+    const kindColor = { disconnected: 'var(--danger)', stale: 'var(--danger)', connected: 'var(--success)',
+        recovered: 'var(--success)', endpoint_changed: 'var(--warning)', relay_changed: 'var(--warning)' };
+    const events = data.events.map(e => `<div style="display:flex; gap:10px; font-size:12px; padding:3px 0; border-bottom:1px solid var(--border);">
+        <span style="${muted} white-space:nowrap;">${new Date(e.timestamp).toLocaleString()}</span>
+        <span style="color:${kindColor[e.kind] || 'var(--text-primary)'}; min-width:120px;">${escapeHtml(e.kind.replace('_', ' '))}</span>
+        <span style="font-weight:600;">${escapeHtml(e.hostname)}</span>
+        <span style="${muted}">${escapeHtml(e.detail)}</span>
+    </div>`).join('');
+    body.innerHTML = `
+        <table class="data-table" style="margin-bottom:14px;">
+            <thead><tr><th>Peer</th><th>Status</th><th>Last handshake</th><th>Traffic</th><th>Endpoint</th><th>Last ${minutes >= 60 ? (minutes / 60) + 'h' : minutes + 'm'}</th></tr></thead>
+            <tbody>${rows}</tbody>
+        </table>
+        <div style="${muted} margin-bottom:6px;">${data.stale_minutes ? `Alerts after ${data.stale_minutes} minutes without a handshake (Settings → Alerting).` : 'Silent-peer alerts are off (Settings → Alerting).'}</div>
+        <h4 style="margin:0 0 6px;">Events</h4>
+        <div style="max-height:260px; overflow-y:auto;">${events || `<div style="${muted}">No tunnel events since WolfStack started.</div>`}</div>`;
+}
+
 async function loadWolfNetRoutesTable() {
     try {
         const resp = await fetch(apiUrl('/api/wolfnet/routes'));
@@ -20550,6 +22461,8 @@ async function loadNetworking() {
 
         cachedInterfaces = interfaces;
         renderNetInterfaces(interfaces);
+        const capIfaces = document.getElementById('net-cap-ifaces');
+        if (capIfaces) capIfaces.innerHTML = ['any', ...interfaces.map(i => i.name)].map(n => `<option value="${escapeHtml(n)}">`).join('');
         renderDnsConfig(dns);
         renderWolfNetStatus(wolfnet);
         renderIpMappings(mappings);
@@ -20560,6 +22473,125 @@ async function loadNetworking() {
     }
 }
 
+async function loadConnections() {
+    const body = document.getElementById('net-connections-body');
+    if (!body) return;
+    const container = document.getElementById('net-conn-container')?.value || '';
+    const external = document.getElementById('net-conn-external')?.checked;
+    const params = new URLSearchParams();
+    if (container) params.set('container', container);
+    if (external) params.set('external', 'true');
+    body.innerHTML = '<p style="color:var(--text-muted);">Reading connection table...</p>';
+    try {
+        const resp = await fetch(apiUrl('/api/networking/connections?' + params.toString()));
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        renderConnections(data, container);
+    } catch (e) {
+        body.innerHTML = `<p style="color:var(--danger);">Failed to read connections: ${escapeHtml(e.message)}</p>`;
+    }
+}
+
+function renderConnections(data, selected) {
+    const body = document.getElementById('net-connections-body');
+    const select = document.getElementById('net-conn-container');
+    if (select) {
+        select.innerHTML = '<option value="">All containers</option>' + data.containers.map(c =>
+            `<option value="${escapeHtml(c.name)}" ${c.name === selected ? 'selected' : ''}>${escapeHtml(c.name)} (${c.total})</option>`).join('');
+    }
+    const muted = 'color:var(--text-muted); font-size:12px;';
+    const mono = 'font-family:var(--font-mono); font-size:12px;';
+    const states = Object.entries(data.states).sort((a, b) => b[1] - a[1])
+        .map(([s, n]) => `<span class="badge" style="font-size:11px; margin:0 4px 4px 0;">${escapeHtml(s)} ${n}</span>`).join('');
+    const topList = (title, list) => `<div style="flex:1; min-width:260px;">
+        <h4 style="margin:0 0 6px;">${title}</h4>
+        ${list.length ? list.map(t => `<div style="display:flex; justify-content:space-between; ${mono}">
+            <span>${escapeHtml(t.name)}</span><span style="color:var(--text-muted);">${t.connections} conn${t.bytes ? ' · ' + formatBytes(t.bytes) : ''}</span></div>`).join('')
+            : `<div style="${muted}">None</div>`}
+    </div>`;
+    const containerRows = data.containers.map(c => `<tr>
+        <td>${escapeHtml(c.name)} <span style="${muted}">${escapeHtml(c.runtime)}</span></td>
+        <td>${c.total}</td><td>${c.outbound}</td><td>${c.inbound}</td>
+        <td style="${c.external ? 'color:var(--warning);' : ''}">${c.external}</td>
+        <td>${c.bytes ? formatBytes(c.bytes) : '—'}</td>
+        <td style="${muted}">${Object.entries(c.states).map(([s, n]) => `${escapeHtml(s)} ${n}`).join(', ')}</td>
+    </tr>`).join('');
+    const endpoint = (ip, port) => escapeHtml(ip) + (port != null ? ':' + port : '');
+    const connRows = data.connections.map(c => `<tr>
+        <td>${escapeHtml(c.proto)}</td>
+        <td>${escapeHtml(c.state || '—')}</td>
+        <td style="${mono}">${endpoint(c.src, c.sport)}</td>
+        <td style="${mono}">${endpoint(c.dst, c.dport)}${c.external ? ' <span class="badge" style="font-size:10px; background:var(--warning)20; color:var(--warning);">internet</span>' : ''}</td>
+        <td>${c.container ? escapeHtml(c.container) + ` <span style="${muted}">${escapeHtml(c.direction)}</span>` : `<span style="${muted}">${escapeHtml(c.process || 'host')}</span>`}</td>
+        <td>${c.bytes ? formatBytes(c.bytes) : '—'}</td>
+    </tr>`).join('');
+    body.innerHTML = `
+        ${data.note ? `<div style="${muted} margin-bottom:8px;">${escapeHtml(data.note)}</div>` : ''}
+        <div style="margin-bottom:10px;">
+            <strong>${data.total}</strong> connection${data.total === 1 ? '' : 's'} from ${escapeHtml(data.source)}${data.truncated ? ' (table truncated)' : ''}
+            <div style="margin-top:6px;">${states}</div>
+        </div>
+        <div style="display:flex; gap:24px; flex-wrap:wrap; margin-bottom:14px;">
+            ${topList('Top talkers', data.top_talkers)}
+            ${topList('Top destinations', data.top_destinations)}
+        </div>
+        ${containerRows ? `<h4 style="margin:0 0 6px;">Per container</h4>
+        <table class="data-table" style="margin-bottom:14px;">
+            <thead><tr><th>Container</th><th>Total</th><th>Outbound</th><th>Inbound</th><th>Internet</th><th>Bytes</th><th>States</th></tr></thead>
+            <tbody>${containerRows}</tbody>
+        </table>` : ''}
+        <h4 style="margin:0 0 6px;">Connections (${data.connections.length} shown)</h4>
+        <table class="data-table">
+            <thead><tr><th>Proto</th><th>State</th><th>Source</th><th>Destination</th><th>Owner</th><th>Bytes</th></tr></thead>
+            <tbody>${connRows || `<tr><td colspan="6" style="text-align:center; ${muted} padding:20px;">No matching connections</td></tr>`}</tbody>
+        </table>`;
+}
+
+async function runPacketCapture() {
+    const btn = document.getElementById('net-cap-btn');
+    const status = document.getElementById('net-cap-status');
+    const container = document.getElementById('net-cap-container').value.trim();
+    const body = {
+        interface: document.getElementById('net-cap-iface').value.trim(),
+        container,
+        runtime: container ? document.getElementById('net-cap-runtime').value : '',
+        filter: document.getElementById('net-cap-filter').value.trim(),
+        duration_secs: parseInt(document.getElementById('net-cap-secs').value) || 10,
+        max_packets: parseInt(document.getElementById('net-cap-packets').value) || 1000,
+    };
+    btn.disabled = true;
+    status.style.color = 'var(--text-muted)';
+    status.textContent = `Capturing for up to ${Math.min(body.duration_secs, 120)}s...`;
+    try {
+        const resp = await fetch(apiUrl('/api/networking/capture'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        if (!resp.ok) {
+            const err = await resp.json().catch(() => ({}));
+            throw new Error(err.error || 'HTTP ' + resp.status);
+        }
+        const blob = await resp.blob();
+        const packets = resp.headers.get('X-Capture-Packets');
+        const stoppedBy = resp.headers.get('X-Capture-Stopped-By');
+        const target = (container ? container + '-' : '') + (body.interface || 'any');
+        const a = document.createElement('a');
+        a.href = URL.createObjectURL(blob);
+        a.download = `capture-${target.replace(/[^A-Za-z0-9.-]/g, '_')}-${new Date().toISOString().replace(/[:.]/g, '-')}.pcap`;
+        document.body.appendChild(a);
+        a.click();
+        a.remove();
+        setTimeout(() => URL.revokeObjectURL(a.href), 10000);
+        status.textContent = `Captured ${packets || '?'} packets (${formatBytes(blob.size)})` + (stoppedBy ? ` — stopped by ${stoppedBy} limit` : '');
+    } catch (e) {
+        status.style.color = 'var(--danger)';
+        status.textContent = 'Capture failed: ' + e.message;
+    } finally {
+        btn.disabled = false;
+    }
+}
+
 function renderNetInterfaces(interfaces) {
     const tbody = document.getElementById('net-interfaces-table');
     if (!tbody) return;
@@ -22875,6 +24907,45 @@ async function securitySaveLockoutConfig() {
     }
 }
 
+// Session policy card (Security page). Node-local, like the lockout policy.
+async function securityLoadSessionPolicy() {
+    try {
+        const resp = await fetch('/api/security/session-policy');
+        if (!resp.ok) return;
+        const p = await resp.json();
+        document.getElementById('sec-session-lifetime').value = p.lifetime_hours;
+        document.getElementById('sec-session-idle').value = p.idle_minutes;
+        document.getElementById('sec-session-remember').value = p.remember_days;
+        document.getElementById('sec-session-reauth').value = p.reauth_minutes;
+    } catch (_) {}
+}
+
+async function securitySaveSessionPolicy() {
+    const num = id => parseInt(document.getElementById(id).value, 10) || 0;
+    const body = {
+        lifetime_hours: num('sec-session-lifetime'),
+        idle_minutes: num('sec-session-idle'),
+        remember_days: num('sec-session-remember'),
+        reauth_minutes: num('sec-session-reauth'),
+    };
+    try {
+        const resp = await fetch('/api/security/session-policy', {
+            method: 'POST',
+            headers: {'Content-Type':'application/json'},
+            body: JSON.stringify(body),
+        });
+        const d = await resp.json().catch(() => ({}));
+        if (!resp.ok) {
+            showToast(`Save failed: ${d.error || resp.statusText}`, 'error');
+            return;
+        }
+        showToast('Session policy saved', 'success');
+        securityLoadSessionPolicy();
+    } catch (e) {
+        showToast(`Save failed: ${e.message || e}`, 'error');
+    }
+}
+
 // Manual unblock: operator types an IP that isn't in the lockout list (e.g. one
 // blocked manually, by a scan trip, or propagated from a peer) and lifts it.
 // Delegates to securityUnblockIp, which confirms + unblocks fleet-wide.
@@ -23027,6 +25098,7 @@ async function renderFleetSecurity() {
     };
     await Promise.all([
         wrap(fleetLoadBlockedIps()),
+        wrap(fleetLoadRecentAttacks()),
         wrap(fleetLoadListeningPorts()),
         wrap(fleetPrefillPolicy()),
         wrap(fleetLoadScanDetector()),
@@ -23732,6 +25804,87 @@ async function fleetUnblockIp(ip) {
     }
 }
 
+async function fleetLoadRecentAttacks() {
+    const el = document.getElementById('fleet-recent-attacks');
+    if (!el) return;
+    const hours = (document.getElementById('fleet-attacks-hours') || {}).value || '24';
+    el.innerHTML = '<div style="color:var(--text-muted); font-size:13px;">Loading…</div>';
+    let data;
+    try {
+        const r = await fetch(apiUrl(`/api/security/attacks?hours=${encodeURIComponent(hours)}`));
+        if (!r.ok) {
+            el.innerHTML = '<div style="color:#ef4444; font-size:13px;">Failed to load recent attacks.</div>';
+            return;
+        }
+        data = await r.json();
+    } catch (e) {
+        el.innerHTML = `<div style="color:#ef4444; font-size:13px;">Error: ${vlanEsc(e.message || e)}</div>`;
+        return;
+    }
+    const s = data.summary || {};
+    const attackers = s.attackers || [];
+    const sources = Object.entries(s.by_source || {}).sort((a, b) => b[1] - a[1])
+        .map(([k, v]) => `<span class="badge" style="font-size:11px; margin-right:4px;">${vlanEsc(k)} ${v}</span>`).join('');
+    const unreachable = (data.unreachable || []).length
+        ? ` · <span style="color:#fbbf24;">${data.unreachable.length} node${data.unreachable.length === 1 ? '' : 's'} unreachable (${data.unreachable.map(vlanEsc).join(', ')})</span>` : '';
+    const summary = `<div style="font-size:13px; color:var(--text-secondary); margin-bottom:10px;">
+        ${s.total || 0} failed login${s.total === 1 ? '' : 's'} from ${s.unique_ips || 0} IP${s.unique_ips === 1 ? '' : 's'}${unreachable}
+        ${sources ? `<div style="margin-top:6px;">${sources}</div>` : ''}</div>`;
+    if (attackers.length === 0) {
+        el.innerHTML = summary + '<div style="color:var(--text-muted); font-size:12px;">No failed logins recorded in this window.</div>';
+        return;
+    }
+    const ago = ts => {
+        const s = Math.max(0, ((Date.now() / 1000) - (ts || 0)) | 0);
+        return s < 60 ? `${s}s ago` : s < 3600 ? `${(s / 60) | 0}m ago` : s < 86400 ? `${(s / 3600) | 0}h ago` : `${(s / 86400) | 0}d ago`;
+    };
+    el.innerHTML = summary + `
+        <table style="width:100%; border-collapse:collapse; font-size:13px;">
+            <thead><tr style="text-align:left; border-bottom:2px solid var(--border);">
+                <th style="padding:6px 10px;">IP</th>
+                <th style="padding:6px 10px;">Attempts</th>
+                <th style="padding:6px 10px;">Surfaces</th>
+                <th style="padding:6px 10px;">Usernames tried</th>
+                <th style="padding:6px 10px;">Nodes</th>
+                <th style="padding:6px 10px;">Last seen</th>
+                <th style="padding:6px 10px;"></th>
+            </tr></thead>
+            <tbody>${attackers.slice(0, 100).map(a => `<tr>
+                <td style="padding:6px 10px; font-family:var(--font-mono); font-size:12px;">${vlanEsc(a.ip)}</td>
+                <td style="padding:6px 10px; font-weight:600;">${a.attempts}</td>
+                <td style="padding:6px 10px; font-size:11px;">${(a.sources || []).map(vlanEsc).join(', ')}</td>
+                <td style="padding:6px 10px; font-size:11px; color:var(--text-muted); font-family:var(--font-mono);">${(a.usernames || []).map(vlanEsc).join(', ') || '—'}</td>
+                <td style="padding:6px 10px; font-size:11px;">${(a.nodes || []).map(vlanEsc).join(', ')}</td>
+                <td style="padding:6px 10px; font-size:11px; color:var(--text-muted);" title="First seen ${new Date(a.first_seen * 1000).toLocaleString()}">${ago(a.last_seen)}</td>
+                <td style="padding:6px 10px; white-space:nowrap;">
+                    ${a.blocked
+                        ? '<span style="color:#ef4444; font-size:11px; font-weight:600;">BLOCKED</span>'
+                        : `<button class="btn btn-sm" onclick="fleetBlockAttacker('${vlanEsc(a.ip)}')" style="font-size:11px;">Block everywhere</button>`}
+                    <button class="btn btn-sm" onclick="abuseReportOpen('${vlanEsc(a.ip)}')" style="font-size:11px; margin-left:4px;" title="Compose an abuse report to the IP owner via whois">📮 Report abuse</button>
+                </td>
+            </tr>`).join('')}</tbody>
+        </table>
+        ${attackers.length > 100 ? `<div style="font-size:11px; color:var(--text-muted); margin-top:6px;">Showing the busiest 100 of ${s.unique_ips} IPs.</div>` : ''}`;
+}
+
+async function fleetBlockAttacker(ip) {
+    if (!await wolfConfirm(`Kernel-block ${ip} on EVERY node in the cluster for the lockout duration?`, 'Block attacker')) return;
+    try {
+        const r = await fetch(apiUrl('/api/security/attacks/block'), {
+            method: 'POST',
+            headers: {'Content-Type':'application/json'},
+            body: JSON.stringify({ ip }),
+        });
+        const d = await r.json();
+        if (!r.ok) { showToast(d.error || 'Block failed', 'error'); return; }
+        showToast(`Blocked ${ip} on all nodes`, 'success');
+        fleetLoadRecentAttacks();
+        fleetLoadBlockedIps();
+    } catch (e) {
+        showToast(`Block failed: ${e.message || e}`, 'error');
+    }
+}
+
 async function fleetLoadListeningPorts() {
     const el = document.getElementById('fleet-listening-ports');
     el.innerHTML = '<div style="color:var(--text-muted); font-size:13px;">Loading…</div>';
@@ -25698,6 +27851,153 @@ async function loadWolfNet() {
     } catch (e) {
         console.error('Failed to load WolfNet:', e);
     }
+    loadWolfNetIpam();
+}
+
+// ─── WolfNet IPAM ───
+// Cluster-wide ledger of leased / reserved WolfNet IPs and the per-subnet
+// allocation pool. Every node holds a replicated copy; edits here are pushed
+// to the rest of the cluster by the backend. Overlay subnets are extra /24s
+// assigned to a cluster or tenant.
+
+let wolfnetIpam = null;
+
+async function loadWolfNetIpam() {
+    try {
+        const resp = await fetch(apiUrl('/api/wolfnet/ipam'));
+        if (!resp.ok) return;
+        wolfnetIpam = await resp.json();
+    } catch (e) {
+        console.error('Failed to load WolfNet IPAM:', e);
+        return;
+    }
+    const d = wolfnetIpam;
+    const pool = (d.prefix && d.pools[d.prefix]) || d.default_pool;
+    document.getElementById('wn-ipam-prefix').textContent = d.prefix ? `(${d.prefix}.0/24)` : '(WolfNet not configured)';
+    document.getElementById('wn-ipam-pool-start').value = pool.start;
+    document.getElementById('wn-ipam-pool-end').value = pool.end;
+    document.getElementById('wn-ipam-allocator').textContent = d.allocator ? `Allocator: ${d.allocator}` : '';
+
+    const reservations = Object.entries(d.reservations || {});
+    document.getElementById('wn-ipam-reservations').innerHTML = reservations.length
+        ? reservations.map(([ip, r]) => `<tr>
+            <td style="font-family:var(--font-mono);">${escapeHtml(ip)}</td>
+            <td>${escapeHtml(r.note || '')}</td>
+            <td><button class="btn btn-sm btn-danger" onclick="removeIpamReservation('${escapeHtml(ip)}')">Release</button></td>
+        </tr>`).join('')
+        : '<tr><td colspan="3" style="color:var(--text-muted);">No reservations</td></tr>';
+
+    const subnets = Object.entries(d.subnets || {});
+    document.getElementById('wn-ipam-subnets').innerHTML = subnets.length
+        ? subnets.map(([prefix, s]) => {
+            const assigned = [s.tenant && `tenant ${s.tenant}`, s.cluster && `cluster ${s.cluster}`].filter(Boolean).join(', ');
+            return `<tr>
+                <td style="font-family:var(--font-mono);">${escapeHtml(prefix)}.0/24</td>
+                <td>${assigned ? escapeHtml(assigned) : '<span style="color:var(--text-muted);">unassigned</span>'}</td>
+                <td>${s.isolated ? 'Yes' : 'No'}</td>
+                <td>${escapeHtml(s.note || '')}</td>
+                <td>
+                    <button class="btn btn-sm" onclick="editIpamSubnet('${escapeHtml(prefix)}')">Edit</button>
+                    <button class="btn btn-sm btn-danger" onclick="removeIpamSubnet('${escapeHtml(prefix)}')">Remove</button>
+                </td>
+            </tr>`;
+        }).join('')
+        : `<tr><td colspan="5" style="color:var(--text-muted);">None — every workload uses ${d.prefix ? escapeHtml(d.prefix) + '.0/24' : 'the primary subnet'}</td></tr>`;
+
+    const leases = d.leases || [];
+    document.getElementById('wn-ipam-leases').innerHTML = leases.length
+        ? leases.map(l => `<tr>
+            <td style="font-family:var(--font-mono);">${escapeHtml(l.ip)}</td>
+            <td>${escapeHtml(l.node_name || l.node)}</td>
+            <td style="color:${l.state === 'bound' ? 'var(--success)' : 'var(--warning)'};">${l.state}</td>
+            <td>${new Date(l.granted_at * 1000).toLocaleString()}</td>
+        </tr>`).join('')
+        : '<tr><td colspan="4" style="color:var(--text-muted);">No leases yet</td></tr>';
+}
+
+async function ipamRequest(method, path, body) {
+    try {
+        const resp = await fetch(apiUrl(path), {
+            method,
+            headers: body ? { 'Content-Type': 'application/json' } : {},
+            body: body ? JSON.stringify(body) : undefined,
+        });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok || data.error) {
+            showToast(data.error || `HTTP ${resp.status}`, 'error');
+            return false;
+        }
+        loadWolfNetIpam();
+        return true;
+    } catch (e) {
+        showToast('Request failed: ' + e.message, 'error');
+        return false;
+    }
+}
+
+async function saveIpamPool() {
+    if (!wolfnetIpam?.prefix) return showToast('WolfNet is not configured', 'error');
+    const start = parseInt(document.getElementById('wn-ipam-pool-start').value, 10);
+    const end = parseInt(document.getElementById('wn-ipam-pool-end').value, 10);
+    if (await ipamRequest('PUT', `/api/wolfnet/ipam/pools/${wolfnetIpam.prefix}`, { start, end })) {
+        showToast('Allocation pool saved', 'success');
+    }
+}
+
+async function resetIpamPool() {
+    if (!wolfnetIpam?.prefix) return;
+    if (await ipamRequest('DELETE', `/api/wolfnet/ipam/pools/${wolfnetIpam.prefix}`)) {
+        showToast('Allocation pool reset to the default range', 'success');
+    }
+}
+
+async function addIpamReservation() {
+    const ip = document.getElementById('wn-ipam-reserve-ip').value.trim();
+    const note = document.getElementById('wn-ipam-reserve-note').value.trim();
+    if (!ip) return showToast('Enter an IP to reserve', 'error');
+    if (await ipamRequest('POST', '/api/wolfnet/ipam/reservations', { ip, note })) {
+        document.getElementById('wn-ipam-reserve-ip').value = '';
+        document.getElementById('wn-ipam-reserve-note').value = '';
+        showToast(`${ip} reserved`, 'success');
+    }
+}
+
+async function removeIpamReservation(ip) {
+    if (!(await showConfirm(`Release the reservation on ${ip}? It becomes available for allocation.`, 'Release reservation'))) return;
+    await ipamRequest('DELETE', `/api/wolfnet/ipam/reservations/${encodeURIComponent(ip)}`);
+}
+
+function editIpamSubnet(prefix) {
+    const s = wolfnetIpam?.subnets?.[prefix];
+    if (!s) return;
+    document.getElementById('wn-ipam-subnet-prefix').value = prefix;
+    document.getElementById('wn-ipam-subnet-cluster').value = s.cluster || '';
+    document.getElementById('wn-ipam-subnet-tenant').value = s.tenant || '';
+    document.getElementById('wn-ipam-subnet-isolated').checked = !!s.isolated;
+    document.getElementById('wn-ipam-subnet-note').value = s.note || '';
+}
+
+async function saveIpamSubnet() {
+    const prefix = document.getElementById('wn-ipam-subnet-prefix').value.trim().replace(/\.0\/24$/, '');
+    if (!prefix) return showToast('Enter a subnet prefix like 10.10.20', 'error');
+    const body = {
+        cluster: document.getElementById('wn-ipam-subnet-cluster').value.trim(),
+        tenant: document.getElementById('wn-ipam-subnet-tenant').value.trim(),
+        isolated: document.getElementById('wn-ipam-subnet-isolated').checked,
+        note: document.getElementById('wn-ipam-subnet-note').value.trim(),
+    };
+    if (await ipamRequest('PUT', `/api/wolfnet/ipam/subnets/${encodeURIComponent(prefix)}`, body)) {
+        ['prefix', 'cluster', 'tenant', 'note'].forEach(f => { document.getElementById(`wn-ipam-subnet-${f}`).value = ''; });
+        document.getElementById('wn-ipam-subnet-isolated').checked = false;
+        showToast(`${prefix}.0/24 saved`, 'success');
+    }
+}
+
+async function removeIpamSubnet(prefix) {
+    if (!(await showConfirm(`Remove the overlay subnet ${prefix}.0/24? Its routes are withdrawn from every node.`, 'Remove subnet'))) return;
+    if (await ipamRequest('DELETE', `/api/wolfnet/ipam/subnets/${encodeURIComponent(prefix)}`)) {
+        showToast(`${prefix}.0/24 removed`, 'success');
+    }
 }
 
 let wolfnetLocalInfo = null;
@@ -25855,6 +28155,7 @@ function renderWolfNetPage(wn, config, localInfo, fullStatus) {
 
     // Load WolfNet routes/IPs table below peers
     loadWolfNetRoutesTable();
+    loadWolfNetHealth();
     // Populate structured settings from config
     if (config) {
         const getVal = (key) => {
@@ -26243,7 +28544,11 @@ async function loadDockerContainers() {
         }
         // Load images only on initial page load (not on every poll)
         try {
-            const imagesResp = await fetch(apiUrl('/api/containers/docker/images'));
+            const [imagesResp, cvesResp] = await Promise.all([
+                fetch(apiUrl('/api/containers/docker/images')),
+                fetch(apiUrl('/api/containers/docker/cves?scope=local')).catch(() => null),
+            ]);
+            dockerImageCves = cvesResp && cvesResp.ok ? await cvesResp.json() : null;
             if (gen === _dockerLoadGeneration && currentNodeId === loadNodeId) {
                 renderDockerImages(await imagesResp.json());
             }
@@ -26461,7 +28766,7 @@ function dockerCardHtml(c) {
     return `<div style="background:var(--bg-card);border:1px solid var(--border);border-left:4px solid ${borderColor};border-radius:10px;overflow:hidden;">
         <div class="unit-actions" style="padding:6px 8px;background:var(--bg-secondary);border-bottom:1px solid var(--border);">
             ${isRunning ? `<button class="btn btn-sm" style="${bd}" disabled title="Start"><span class="ws-icon-clean-wrap" data-icon="play"></span></button><button class="btn btn-sm" style="${bs}" onclick="dockerAction('${c.name}','stop',this)" title="Stop"><span class="ws-icon-clean-wrap" data-icon="stop"></span></button><button class="btn btn-sm" style="${bs}" onclick="dockerAction('${c.name}','restart',this)" title="Restart"><span class="ws-icon-clean-wrap" data-icon="restart"></span></button><button class="btn btn-sm" style="${bs}" onclick="dockerAction('${c.name}','pause',this)" title="Pause"><span class="ws-icon-clean-wrap" data-icon="pause"></span></button><button class="btn btn-sm" style="${bs}" onclick="openConsole('docker','${c.name}')" title="Console"><span class="ws-icon-clean-wrap" data-icon="terminal"></span></button>${_containerVncBtnHtml('docker', c.name, c.name, bs, true)}` : isPaused ? `<button class="btn btn-sm" style="${bs}" onclick="dockerAction('${c.name}','unpause',this)" title="Unpause"><span class="ws-icon-clean-wrap" data-icon="play"></span></button>` : `<button class="btn btn-sm" style="${bs}" onclick="dockerAction('${c.name}','start',this)" title="Start"><span class="ws-icon-clean-wrap" data-icon="play"></span></button><button class="btn btn-sm" style="${bd}" disabled></button><button class="btn btn-sm" style="${bd}" disabled></button>`}
-            <button class="btn btn-sm" style="${bs}" onclick="viewContainerLogs('docker','${c.name}')" title="Logs"><span class="ws-icon-clean-wrap" data-icon="logs"></span></button><button class="btn btn-sm" style="${bs}" onclick="viewDockerVolumes('${c.name}')" title="Volumes"><span class="ws-icon-clean-wrap" data-icon="database"></span></button><button class="btn btn-sm" style="${bs}" onclick="browseContainerFiles('docker','${c.name}')" title="Files"><span class="ws-icon-clean-wrap" data-icon="folder-open"></span></button><button class="btn btn-sm" style="${bs}" onclick="openDockerSettings('${c.name}')" title="Settings"><span class="ws-icon-clean-wrap" data-icon="settings"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerConfigurator('docker','${c.name}')" title="Configure"><span class="ws-icon-clean-wrap" data-icon="configure"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerUpdates('docker','${c.name}')" title="Updates"><span class="ws-icon-clean-wrap" data-icon="updates"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerCron('docker','${c.name}')" title="Cron"><span class="ws-icon-clean-wrap" data-icon="calendar"></span></button><button class="btn btn-sm" style="${bs}" onclick="cloneDockerContainer('${c.name}')" title="Clone"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button><button class="btn btn-sm" style="${bs}" onclick="migrateDockerContainer('${c.name}')" title="Migrate"><span class="ws-icon-clean-wrap" data-icon="migrate"></span></button>${!isRunning ? `<button class="btn btn-sm" style="${bs}color:#ef4444;" onclick="dockerAction('${c.name}','remove',this)" title="Remove"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>` : ''}
+            <button class="btn btn-sm" style="${bs}" onclick="viewContainerLogs('docker','${c.name}')" title="Logs"><span class="ws-icon-clean-wrap" data-icon="logs"></span></button><button class="btn btn-sm" style="${bs}" onclick="viewDockerVolumes('${c.name}')" title="Volumes"><span class="ws-icon-clean-wrap" data-icon="database"></span></button><button class="btn btn-sm" style="${bs}" onclick="browseContainerFiles('docker','${c.name}')" title="Files"><span class="ws-icon-clean-wrap" data-icon="folder-open"></span></button><button class="btn btn-sm" style="${bs}" onclick="openDockerSettings('${c.name}')" title="Settings"><span class="ws-icon-clean-wrap" data-icon="settings"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerConfigurator('docker','${c.name}')" title="Configure"><span class="ws-icon-clean-wrap" data-icon="configure"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerUpdates('docker','${c.name}')" title="Updates"><span class="ws-icon-clean-wrap" data-icon="updates"></span></button><button class="btn btn-sm" style="${bs}" onclick="openContainerCron('docker','${c.name}')" title="Cron"><span class="ws-icon-clean-wrap" data-icon="calendar"></span></button><button class="btn btn-sm" style="${bs}" onclick="cloneDockerContainer('${c.name}')" title="Clone"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button><button class="btn btn-sm" style="${bs}" onclick="saveContainerTemplate('docker','${c.name}')" title="Save as Template"><span class="ws-icon-clean-wrap" data-icon="package"></span></button><button class="btn btn-sm" style="${bs}" onclick="migrateDockerContainer('${c.name}')" title="Migrate"><span class="ws-icon-clean-wrap" data-icon="migrate"></span></button>${!isRunning ? `<button class="btn btn-sm" style="${bs}color:#ef4444;" onclick="dockerAction('${c.name}','remove',this)" title="Remove"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>` : ''}
         </div>
         ${pies.length > 0 ? `<div style="display:flex;justify-content:space-evenly;padding:12px 8px;border-bottom:1px solid var(--border);">${pies.join('')}</div>` : ''}
         <div style="padding:10px 12px;">
@@ -26540,6 +28845,7 @@ function lxcCardHtml(c, s) {
                 <button class="btn btn-sm" style="${bs}" onclick="openContainerUpdates('lxc','${c.name}')" title="Updates"><span class="ws-icon-clean-wrap" data-icon="updates"></span></button>
                 <button class="btn btn-sm" style="${bs}" onclick="openContainerCron('lxc','${c.name}')" title="Cron"><span class="ws-icon-clean-wrap" data-icon="calendar"></span></button>
                 <button class="btn btn-sm" style="${bs}" onclick="cloneLxcContainer('${c.name}')" title="Clone"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button>
+                <button class="btn btn-sm" style="${bs}" onclick="saveContainerTemplate('lxc','${c.name}')" title="Save as Template"><span class="ws-icon-clean-wrap" data-icon="package"></span></button>
                 <button class="btn btn-sm" style="${bs}" onclick="migrateLxcContainer('${c.name}')" title="Migrate"><span class="ws-icon-clean-wrap" data-icon="migrate"></span></button>
                 <button class="btn btn-sm" style="${bs}" onclick="openLxcStorage('${escapeHtml(c.name)}')" title="Storage (resize / move)"><span class="ws-icon-clean-wrap" data-icon="hard-drive"></span></button>
                 <button class="btn btn-sm" style="${bs}" onclick="exportLxcContainer('${c.name}')" title="Export"><span class="ws-icon-clean-wrap" data-icon="export"></span></button>
@@ -26605,6 +28911,7 @@ function vmCardHtml(vm) {
                 : `<button class="btn btn-sm" style="${isRunning ? bs : bd}" ${isRunning ? `onclick="vmAction('${vm.name}','pause',this)"` : 'disabled'} title="Pause (suspend to RAM)"><span class="ws-icon-clean-wrap" data-icon="snowflake"></span></button>`}
             ${vncLink ? `<button class="btn btn-sm" style="${bs}" onclick="window.open('${vncLink}')" title="VNC"><span class="ws-icon-clean-wrap" data-icon="monitor"></span></button>` : ''}
             <button class="btn btn-sm" style="${!isRunning ? bd : bs}" ${!isRunning ? 'disabled' : `onclick="openVmConsole('${vm.name}')"`} title="Serial terminal (guest must have serial console enabled)"><span class="ws-icon-clean-wrap" data-icon="terminal"></span></button>
+            ${vmRdpButton(vm, isRunning ? bs : bd)}
             <button class="btn btn-sm" style="${bs}" onclick="showVmSettings('${vm.name}')" title="Settings"><span class="ws-icon-clean-wrap" data-icon="settings"></span></button>
             <button class="btn btn-sm" style="${bs}" onclick="showVmLogs('${vm.name}')" title="Logs"><span class="ws-icon-clean-wrap" data-icon="logs"></span></button>
             <button class="btn btn-sm" style="${!isRunning ? bs : bd}color:#a855f7;" ${!isRunning ? `onclick="backupSingleVm('${vm.name}')"` : 'disabled'} title="${!isRunning ? 'Back up this VM now (portable tar.gz to local storage)' : 'Stop the VM first — backup of a running VM produces an inconsistent disk image'}"><span class="ws-icon-clean-wrap" data-icon="save"></span></button>
@@ -26615,7 +28922,7 @@ function vmCardHtml(vm) {
         </div>
         <div style="padding:10px 12px;">
             <div style="display:flex;justify-content:space-between;align-items:start;">
-                <div><div style="font-weight:700;font-size:14px;">${escapeHtml(vm.name)}</div><div style="font-size:11px;color:var(--text-muted);">${vm.bios_type === 'ovmf' ? 'UEFI' : 'BIOS'} · ${vm.os_disk_bus} · ${vm.net_model || 'virtio'}</div></div>
+                <div><div style="font-weight:700;font-size:14px;">${escapeHtml(vm.name)}${vmWindowsBadge(vm)}</div><div style="font-size:11px;color:var(--text-muted);">${vm.bios_type === 'ovmf' ? 'UEFI' : 'BIOS'} · ${vm.os_disk_bus} · ${vm.net_model || 'virtio'}</div></div>
                 <span style="font-size:10px;padding:2px 8px;border-radius:4px;background:${borderColor}22;color:${borderColor};font-weight:600;">${isPaused ? 'Paused' : (isRunning ? 'Running' : 'Stopped')}</span>
             </div>
             <div style="display:grid;grid-template-columns:1fr 1fr;gap:2px;margin-top:8px;font-size:11px;color:var(--text-muted);">
@@ -27068,7 +29375,7 @@ function renderDockerContainers(containers) {
         const badgeRow = `<div style="display:flex;flex-wrap:wrap;align-items:center;gap:4px;margin-top:3px;">${svcItems}<span data-update-badge="docker:${c.name}"></span><span data-image-update-badge="${escapeAttr(c.name)}"></span>${imageUpdateSettingsChip(c.name, c.image)}</div>`;
 
         return `<tr data-name="${c.name}">
-            <td><strong>${c.name}</strong>${badgeRow}<span style="font-size:11px;color:var(--text-muted)">${c.id.substring(0, 12)}</span></td>
+            <td><strong>${c.name}</strong>${favouriteStarHtml('docker', c.name)}${badgeRow}<span style="font-size:11px;color:var(--text-muted)">${c.id.substring(0, 12)}</span></td>
             <td>${c.image}</td>
             <td><span style="color:${stateColor}">●</span> ${c.status}</td>
             <td style="font-size:12px; font-family:monospace;">${c.ip_address || '-'}${c.gateway ? '<div style="font-size:10px;color:var(--text-muted);">GW: ' + escapeHtml(c.gateway) + '</div>' : ''}${c.mac_address ? '<div style="font-size:10px;color:var(--text-muted);">MAC: ' + escapeHtml(c.mac_address) + '</div>' : ''}</td>
@@ -27103,6 +29410,7 @@ function renderDockerContainers(containers) {
                 <button class="btn btn-sm" style="margin:2px;font-size:20px;line-height:1;padding:4px 6px;" onclick="openContainerUpdates('docker', '${c.name}')" title="Check Updates"><span class="ws-icon-clean-wrap" data-icon="updates"></span></button>
                 <button class="btn btn-sm" style="margin:2px;font-size:20px;line-height:1;padding:4px 6px;" onclick="openContainerCron('docker', '${c.name}')" title="Cron Jobs"><span class="ws-icon-clean-wrap" data-icon="calendar"></span></button>
                 <button class="btn btn-sm" style="margin:2px;font-size:20px;line-height:1;padding:4px 6px;" onclick="cloneDockerContainer('${c.name}')" title="Clone"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button>
+                <button class="btn btn-sm" style="margin:2px;font-size:20px;line-height:1;padding:4px 6px;" onclick="saveContainerTemplate('docker', '${c.name}')" title="Save as Template"><span class="ws-icon-clean-wrap" data-icon="package"></span></button>
                 <button class="btn btn-sm" style="margin:2px;font-size:20px;line-height:1;padding:4px 6px;" onclick="migrateDockerContainer('${c.name}')" title="Migrate"><span class="ws-icon-clean-wrap" data-icon="migrate"></span></button>
             </div></td>
         </tr>${statsSubRow}`;
@@ -27165,10 +29473,64 @@ function renderDockerStats(stats) {
     `;
 }
 
+// Last /api/containers/docker/cves?scope=local report for this node.
+let dockerImageCves = null;
+
+function dockerImageCveCell(id) {
+    const report = dockerImageCves;
+    if (!report) return '<span style="color:var(--text-muted);">—</span>';
+    if (!report.trivy_available) return '<span style="color:var(--text-muted);" title="Install trivy to scan images for CVEs">no trivy</span>';
+    const scan = (report.images || []).find(s => s.image_id === id);
+    if (!scan) return `<span style="color:var(--text-muted);">${report.scanning ? 'scanning…' : 'not scanned'}</span>`;
+    if (scan.error) return `<span style="color:var(--text-muted);" title="${escapeHtml(scan.error)}">scan failed</span>`;
+    const when = `Scanned ${new Date(scan.scanned_at).toLocaleString()}`;
+    if (!scan.critical && !scan.high) return `<span style="color:#10b981;" title="${when}">✓ none</span>`;
+    const color = scan.critical ? '#ef4444' : '#f59e0b';
+    return `<a href="#" onclick="showDockerImageCves('${escapeHtml(id)}'); return false;" style="color:${color};font-weight:600;" title="${when}">${scan.critical} critical · ${scan.high} high</a>`;
+}
+
+function showDockerImageCves(id) {
+    const scan = ((dockerImageCves && dockerImageCves.images) || []).find(s => s.image_id === id);
+    if (!scan) return;
+    const modal = document.getElementById('container-detail-modal');
+    document.getElementById('container-detail-title').textContent = `CVEs — ${scan.image}`;
+    const rows = (scan.cves || []).map(c => `
+        <tr>
+            <td style="font-family:monospace;font-size:12px;white-space:nowrap;"><a href="https://nvd.nist.gov/vuln/detail/${encodeURIComponent(c.id)}" target="_blank" rel="noopener">${escapeHtml(c.id)}</a></td>
+            <td>${escapeHtml(c.package)}</td>
+            <td style="font-family:monospace;font-size:12px;">${escapeHtml(c.installed)}</td>
+            <td style="font-family:monospace;font-size:12px;">${c.fixed ? escapeHtml(c.fixed) : '<span style="color:var(--text-muted);">no fix yet</span>'}</td>
+            <td style="font-size:12px;">${escapeHtml(c.title)}</td>
+        </tr>`).join('');
+    document.getElementById('container-detail-body').innerHTML = `
+        <div style="padding:1rem;">
+            <p style="margin-top:0;font-size:13px;color:var(--text-muted);">
+                ${scan.critical} critical and ${scan.high} high vulnerabilit${scan.critical + scan.high === 1 ? 'y' : 'ies'} found by trivy on ${new Date(scan.scanned_at).toLocaleString()}.
+                Critical findings are listed below; pull a newer tag or rebuild the image to pick up fixes.
+            </p>
+            ${rows ? `<table class="data-table"><thead><tr><th>CVE</th><th>Package</th><th>Installed</th><th>Fixed in</th><th>Title</th></tr></thead><tbody>${rows}</tbody></table>`
+                   : '<p style="color:var(--text-muted);">No critical CVEs — only high-severity findings.</p>'}
+        </div>`;
+    modal.classList.add('active');
+}
+
+async function scanDockerImageCves() {
+    const btn = document.getElementById('docker-cve-scan-btn');
+    try {
+        const resp = await fetch(apiUrl('/api/containers/docker/cves/scan'), { method: 'POST' });
+        const data = await resp.json();
+        if (!resp.ok) { showToast(data.error || 'Failed to start scan', 'error'); return; }
+        showToast('CVE scan started — results appear in the Images table as each image finishes', 'info');
+        if (btn) { btn.disabled = true; setTimeout(() => { btn.disabled = false; }, 30000); }
+    } catch (e) {
+        showToast(`Failed: ${e.message}`, 'error');
+    }
+}
+
 function renderDockerImages(images) {
     const table = document.getElementById('docker-images-table');
     if (!images || images.length === 0) {
-        table.innerHTML = '<tr><td colspan="6" style="text-align:center;color:var(--text-muted);">No images found</td></tr>';
+        table.innerHTML = '<tr><td colspan="7" style="text-align:center;color:var(--text-muted);">No images found</td></tr>';
         return;
     }
     table.innerHTML = images.map(img => {
@@ -27180,14 +29542,130 @@ function renderDockerImages(images) {
             <td style="font-family:monospace;font-size:12px;">${img.id.substring(0, 12)}</td>
             <td>${img.size}</td>
             <td>${img.created}</td>
+            <td>${dockerImageCveCell(img.id)}</td>
             <td>
-                <button class="btn btn-sm btn-primary" style="margin:2px;font-size:11px;" onclick="selectDockerImage('${imageRef.replace(/'/g, "\\'")}')" title="Create container from this image">▶ Use</button>
+                <button class="btn btn-sm btn-primary" style="margin:2px;font-size:11px;" onclick="selectDockerImage('${imageRef.replace(/'/g, "\\'")}', true)" title="Create container from this image">▶ Use</button>
                 <button class="btn btn-sm" style="margin:2px;font-size:11px;color:#ef4444;" onclick="deleteDockerImage('${img.id}', '${imageRef.replace(/'/g, "\\'")}')" title="Delete image">Delete</button>
             </td>
         </tr>`;
     }).join('');
 }
 
+// ─── Docker image builds (Dockerfile from Git or an uploaded context) ───
+
+function showDockerBuild() {
+    const modal = document.getElementById('container-detail-modal');
+    const title = document.getElementById('container-detail-title');
+    const body = document.getElementById('container-detail-body');
+    const input = 'width:100%; padding:8px 12px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary); font-size:13px;';
+    title.textContent = 'Build Docker Image';
+    body.innerHTML = `
+        <div style="padding: 1rem;">
+            <div style="display:grid; grid-template-columns:1fr 1fr; gap:12px; margin-bottom:12px;">
+                <div>
+                    <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Tag</label>
+                    <input id="docker-build-tag" type="text" placeholder="myapp:1.0" style="${input}">
+                </div>
+                <div>
+                    <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Dockerfile</label>
+                    <input id="docker-build-dockerfile" type="text" placeholder="Dockerfile (default)" style="${input}">
+                </div>
+            </div>
+            <div style="margin-bottom:12px;">
+                <label style="margin-right:16px; font-size:13px;"><input type="radio" name="docker-build-source" value="git" checked onchange="dockerBuildSourceChanged()"> Git repository</label>
+                <label style="font-size:13px;"><input type="radio" name="docker-build-source" value="upload" onchange="dockerBuildSourceChanged()"> Upload build context</label>
+            </div>
+            <div id="docker-build-git" style="display:grid; grid-template-columns:2fr 1fr 1fr; gap:12px; margin-bottom:12px;">
+                <input id="docker-build-git-url" type="text" placeholder="https://github.com/org/app.git" style="${input}">
+                <input id="docker-build-git-ref" type="text" placeholder="Branch / tag (optional)" style="${input}">
+                <input id="docker-build-git-dir" type="text" placeholder="Sub-directory (optional)" style="${input}">
+            </div>
+            <div id="docker-build-upload" style="display:none; margin-bottom:12px;">
+                <input id="docker-build-file" type="file" accept=".tar,.tar.gz,.tgz" style="font-size:13px;">
+                <div style="font-size:11px; color:var(--text-muted); margin-top:4px;">A .tar or .tar.gz of the build directory, with the Dockerfile inside.</div>
+            </div>
+            <button class="btn btn-primary" id="docker-build-btn" onclick="startDockerBuild()">Build</button>
+            <pre id="docker-build-log" style="display:none; margin-top:12px; max-height:320px; overflow:auto; background:var(--bg-tertiary); border:1px solid var(--border); border-radius:6px; padding:8px; font-size:11px; white-space:pre-wrap;"></pre>
+        </div>
+    `;
+    modal.classList.add('active');
+}
+
+function dockerBuildSourceChanged() {
+    const git = document.querySelector('input[name="docker-build-source"]:checked')?.value !== 'upload';
+    document.getElementById('docker-build-git').style.display = git ? 'grid' : 'none';
+    document.getElementById('docker-build-upload').style.display = git ? 'none' : 'block';
+}
+
+async function startDockerBuild() {
+    const tag = document.getElementById('docker-build-tag').value.trim();
+    const dockerfile = document.getElementById('docker-build-dockerfile').value.trim();
+    const upload = document.querySelector('input[name="docker-build-source"]:checked')?.value === 'upload';
+    if (!tag) { showToast('Enter a tag for the image', 'error'); return; }
+    let resp;
+    try {
+        if (upload) {
+            const file = document.getElementById('docker-build-file').files[0];
+            if (!file) { showToast('Choose a build context archive', 'error'); return; }
+            const qs = new URLSearchParams({ tag, dockerfile });
+            resp = await fetch(apiUrl('/api/containers/docker/build?' + qs), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/octet-stream' },
+                body: file,
+            });
+        } else {
+            const git_url = document.getElementById('docker-build-git-url').value.trim();
+            if (!git_url) { showToast('Enter the Git repository URL', 'error'); return; }
+            resp = await fetch(apiUrl('/api/containers/docker/build'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({
+                    tag, dockerfile, git_url,
+                    git_ref: document.getElementById('docker-build-git-ref').value.trim(),
+                    git_dir: document.getElementById('docker-build-git-dir').value.trim(),
+                }),
+            });
+        }
+        const job = await resp.json();
+        if (!resp.ok) { showToast(job.error || 'Build failed to start', 'error'); return; }
+        document.getElementById('docker-build-btn').disabled = true;
+        const log = document.getElementById('docker-build-log');
+        log.style.display = 'block';
+        log.textContent = '';
+        pollDockerBuild(job.id, 0);
+    } catch (e) {
+        showToast('Build failed: ' + e.message, 'error');
+    }
+}
+
+async function pollDockerBuild(id, since) {
+    const log = document.getElementById('docker-build-log');
+    if (!log) return; // dialog closed — the build carries on server-side
+    try {
+        const resp = await fetch(apiUrl(`/api/containers/docker/builds/${encodeURIComponent(id)}?since=${since}`));
+        const data = await resp.json();
+        if (!resp.ok) { showToast(data.error || 'Lost track of the build', 'error'); return; }
+        if (data.log.length) {
+            log.textContent += data.log.join('\n') + '\n';
+            log.scrollTop = log.scrollHeight;
+        }
+        if (data.job.status === 'building') {
+            setTimeout(() => pollDockerBuild(id, data.log_total), 1500);
+            return;
+        }
+        const btn = document.getElementById('docker-build-btn');
+        if (btn) btn.disabled = false;
+        if (data.job.status === 'done') {
+            showToast(`Image '${data.job.tag}' built`, 'success');
+            loadDockerContainers();
+        } else {
+            showToast(data.job.error || 'Build failed', 'error');
+        }
+    } catch (e) {
+        setTimeout(() => pollDockerBuild(id, since), 3000);
+    }
+}
+
 async function deleteDockerImage(id, name) {
     if (!(await showConfirm(`Delete Docker image '${name}'?\n\nThis will fail if the image is used by any container.`))) return;
 
@@ -27285,11 +29763,143 @@ async function viewDockerVolumes(container) {
                 </div>
             `;
         }
+        body.insertAdjacentHTML('beforeend', '<div id="mount-deps-docker" style="margin-top:12px;"></div>');
+        loadMountDeps('docker', container, 'mount-deps-docker');
     } catch (e) {
         body.innerHTML = `<p style="color:#ef4444;">Failed to load volumes: ${e.message}</p>`;
     }
 }
 
+// ─── Required storage mounts ───
+// Storage-manager mounts a container needs: started only once they are
+// mounted (boot autostart waits for them, a manual start mounts them).
+
+async function loadMountDeps(runtime, name, elId) {
+    const el = document.getElementById(elId);
+    if (!el) return;
+    try {
+        const resp = await fetch(apiUrl(`/api/containers/${runtime}/${encodeURIComponent(name)}/mount-deps`));
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        if (!data.available.length) { el.innerHTML = ''; return; }
+        const rows = data.available.map(m => {
+            const checked = data.mounts.includes(m.id) ? 'checked' : '';
+            const hint = !checked && data.suggested.includes(m.id)
+                ? '<span class="badge" style="font-size:10px; margin-left:6px;">holds a bind source</span>' : '';
+            return `<label style="display:flex; align-items:center; gap:8px; font-size:12px; padding:4px 0; cursor:pointer;">
+                <input type="checkbox" class="mount-dep-cb" value="${escapeHtml(m.id)}" ${checked}>
+                <strong>${escapeHtml(m.name)}</strong>
+                <code style="color:var(--text-muted); font-size:11px;">${escapeHtml(m.mount_point)}</code>${hint}
+            </label>`;
+        }).join('');
+        el.innerHTML = `<div style="padding:10px; background:var(--bg-primary); border-radius:6px; border:1px solid var(--border);">
+            <div style="font-size:12px; font-weight:600; margin-bottom:4px;">Required storage mounts</div>
+            <div style="font-size:11px; color:var(--text-muted); margin-bottom:6px;">Not started until these are mounted — at boot and on manual start.</div>
+            ${rows}
+            <button class="btn btn-sm" style="font-size:11px; padding:2px 8px; margin-top:6px;" onclick="saveMountDeps('${runtime}', '${escapeHtml(name)}', '${elId}')">Save Requirements</button>
+        </div>`;
+    } catch (e) {
+        el.innerHTML = `<div style="font-size:11px; color:var(--text-muted);">Required mounts unavailable: ${escapeHtml(e.message)}</div>`;
+    }
+}
+
+async function saveMountDeps(runtime, name, elId) {
+    const el = document.getElementById(elId);
+    const mounts = Array.from(el.querySelectorAll('.mount-dep-cb:checked')).map(cb => cb.value);
+    try {
+        const resp = await fetch(apiUrl(`/api/containers/${runtime}/${encodeURIComponent(name)}/mount-deps`), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ mounts }),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        showToast(mounts.length ? `Requires ${mounts.length} mount(s)` : 'Mount requirements cleared', 'success');
+    } catch (e) {
+        showToast('Failed to save mount requirements: ' + e.message, 'error');
+    }
+}
+
+// ─── Startup order ───
+// Boot start order, post-start delay and dependencies (other guests,
+// storage mounts) for this node's LXC containers and VMs.
+
+async function openStartupOrder() {
+    let data;
+    try {
+        const resp = await fetch(apiUrl('/api/autostart'));
+        data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+    } catch (e) {
+        showToast('Failed to load startup order: ' + e.message, 'error');
+        return;
+    }
+    if (data.proxmox) {
+        showModal('<p style="white-space:normal;">Proxmox starts guests at boot itself — set the start order under each guest\'s <strong>Options → Start/Shutdown order</strong> in Proxmox.</p>', 'Startup Order');
+        return;
+    }
+    const options = data.guests.map(g => `${g.kind}:${g.name}`)
+        .concat(data.mounts.map(m => `mount:${m.id}`));
+    const rows = data.guests.map(g => {
+        const key = `${g.kind}:${g.name}`;
+        return `<tr data-kind="${escapeHtml(g.kind)}" data-name="${escapeHtml(g.name)}">
+            <td><span class="badge" style="font-size:10px;">${g.kind === 'vm' ? 'VM' : 'LXC'}</span> <strong>${escapeHtml(g.name)}</strong>
+                ${g.autostart ? '' : '<span style="font-size:10px;color:var(--text-muted);" title="Not set to start at boot">(manual)</span>'}</td>
+            <td><input type="number" class="form-control so-order" value="${g.order}" style="width:70px;font-size:12px;"></td>
+            <td><input type="number" class="form-control so-delay" value="${g.delay_s}" min="0" style="width:70px;font-size:12px;"></td>
+            <td><input type="text" class="form-control so-deps" value="${escapeHtml(g.depends_on.join(', '))}" list="so-dep-options"
+                placeholder="e.g. lxc:db, mount:nfs" style="font-size:12px;font-family:var(--font-mono);"></td>
+            <td><button class="btn btn-sm" onclick="saveStartupOrder(this)" style="font-size:11px;" aria-label="Save ${escapeHtml(key)}">Save</button></td>
+        </tr>`;
+    }).join('');
+    const html = `<div style="white-space:normal;">
+        <p style="font-size:12px;color:var(--text-muted);margin:0 0 10px 0;">At boot, autostart guests start lowest order first, each waiting its delay before the next.
+            A guest listed under <em>Depends on</em> always starts first; if it fails, or a <code>mount:</code> never comes up, the dependent is held back.</p>
+        <div id="so-boot-order" style="font-size:12px;margin-bottom:10px;"></div>
+        <datalist id="so-dep-options">${options.map(o => `<option value="${escapeHtml(o)}">`).join('')}</datalist>
+        <table class="data-table" style="width:100%;">
+            <thead><tr><th>Guest</th><th>Order</th><th>Delay (s)</th><th>Depends on</th><th></th></tr></thead>
+            <tbody>${rows || '<tr><td colspan="5" style="color:var(--text-muted);">No containers or VMs on this node.</td></tr>'}</tbody>
+        </table>
+    </div>`;
+    showModal(html, 'Startup Order', { noOk: true });
+    renderStartupBootOrder(data.boot_order, data.cycle);
+}
+
+function renderStartupBootOrder(order, cycle) {
+    const el = document.getElementById('so-boot-order');
+    if (!el) return;
+    const seq = order.length
+        ? order.map(k => `<code>${escapeHtml(k)}</code>`).join(' → ')
+        : '<span style="color:var(--text-muted);">nothing set to start at boot</span>';
+    el.innerHTML = `<strong>Boot sequence:</strong> ${seq}` + (cycle.length
+        ? `<div style="color:var(--danger);margin-top:4px;">Dependency cycle between ${cycle.map(escapeHtml).join(', ')}</div>` : '');
+}
+
+async function saveStartupOrder(btn) {
+    const row = btn.closest('tr');
+    const kind = row.dataset.kind, name = row.dataset.name;
+    const body = {
+        order: parseInt(row.querySelector('.so-order').value, 10) || 0,
+        delay_s: Math.max(0, parseInt(row.querySelector('.so-delay').value, 10) || 0),
+        depends_on: row.querySelector('.so-deps').value.split(',').map(s => s.trim()).filter(Boolean),
+    };
+    try {
+        const resp = await fetch(apiUrl(`/api/autostart/${kind}/${encodeURIComponent(name)}`), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        row.querySelector('.so-deps').value = data.startup.depends_on.join(', ');
+        renderStartupBootOrder(data.boot_order, data.cycle);
+        showToast(`Startup order saved for ${name}`, 'success');
+    } catch (e) {
+        showToast('Failed to save startup order: ' + e.message, 'error');
+    }
+}
+
 // ─── Docker Settings Editor ───
 
 var _dockerSettingsTab = 1;
@@ -27469,6 +30079,14 @@ async function openDockerSettings(name) {
                         <button class="btn btn-sm btn-primary" onclick="saveDockerEnv('${escapeHtml(name)}')" style="font-size:11px;padding:4px 12px;">Save Env</button>
                     </div>
                 </div>
+
+                <div style="margin-top:12px;padding:12px;background:var(--bg-tertiary);border-radius:8px;border:1px solid var(--border);display:flex;justify-content:space-between;align-items:center;gap:12px;">
+                    <div>
+                        <h4 style="margin:0 0 4px 0;font-size:13px;color:var(--text-primary);">Stored Environment &amp; Secrets</h4>
+                        <div style="font-size:11px;color:var(--text-muted);">Kept encrypted by WolfStack and injected every time this container is created or recreated. Secrets are mounted as files under /run/secrets, not shown by docker inspect.</div>
+                    </div>
+                    <button class="btn btn-sm" onclick="openEnvStore('${escapeHtml(name)}', 'container')" style="font-size:11px;padding:4px 12px;white-space:nowrap;">Manage</button>
+                </div>
             </div>
 
             <!-- ═══ Tab 2: Network ═══ -->
@@ -27770,6 +30388,96 @@ async function saveDockerEnv(name) {
     }
 }
 
+// ─── Stored environment & secrets (per container / compose stack) ───
+
+function envStoreRowHtml(v) {
+    const secret = !!v.secret;
+    return `<div class="env-store-row" style="display:grid;grid-template-columns:180px 1fr auto auto;gap:6px;align-items:center;margin-bottom:4px;">
+        <input type="text" class="form-control env-store-name" value="${escapeHtml(v.name || '')}" placeholder="NAME" style="font-family:var(--font-mono);font-size:12px;">
+        <input type="${secret ? 'password' : 'text'}" class="form-control env-store-value" value="${escapeHtml(v.value || '')}"
+            placeholder="${secret && v.name ? '(unchanged)' : 'value'}" style="font-family:var(--font-mono);font-size:12px;">
+        <label style="display:flex;gap:4px;align-items:center;font-size:12px;white-space:nowrap;" title="Mount as /run/secrets/NAME and set NAME_FILE instead of an environment variable">
+            <input type="checkbox" class="env-store-secret" ${secret ? 'checked' : ''} onchange="this.closest('.env-store-row').querySelector('.env-store-value').type = this.checked ? 'password' : 'text'"> secret</label>
+        <button class="btn btn-sm" onclick="this.closest('.env-store-row').remove()" style="color:var(--danger);" title="Remove">&times;</button>
+    </div>`;
+}
+
+async function openEnvStore(scope, kind) {
+    let vars = [];
+    try {
+        const resp = await fetch(apiUrl(`/api/env-store/${encodeURIComponent(scope)}`));
+        const data = await resp.json();
+        if (!resp.ok) { showToast(data.error || 'Failed to load stored environment', 'error'); return; }
+        vars = data.vars || [];
+    } catch (e) {
+        showToast('Failed to load stored environment: ' + e.message, 'error');
+        return;
+    }
+    const hint = kind === 'stack'
+        ? 'Passed to <code>docker compose</code> for this stack — reference them in the YAML as <code>${NAME}</code>. They take precedence over Secrets Manager entries of the same name.'
+        : 'Injected whenever WolfStack creates or recreates this container, overriding the same name in its own env. Secret values are mounted read-only at <code>/run/secrets/NAME</code> with <code>NAME_FILE</code> pointing at them. Templates and other containers can use a value as <code>${secret:' + escapeHtml(scope) + '/NAME}</code>.';
+    const html = `<div style="white-space:normal;">
+        <p style="font-size:12px;color:var(--text-muted);margin:0 0 10px 0;">${hint}</p>
+        <div id="env-store-rows">${vars.map(envStoreRowHtml).join('')}</div>
+        <button class="btn btn-sm" onclick="document.getElementById('env-store-rows').insertAdjacentHTML('beforeend', envStoreRowHtml({}))" style="margin-top:4px;">+ Add variable</button>
+        <div style="display:flex;justify-content:space-between;gap:8px;margin-top:14px;">
+            <button class="btn btn-sm" onclick="deleteEnvStore('${escapeHtml(scope)}', this)" style="color:var(--danger);" ${vars.length ? '' : 'disabled'}>Remove all</button>
+            <div style="display:flex;gap:8px;">
+                <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+                <button class="btn btn-sm ${kind === 'container' ? '' : 'btn-primary'}" onclick="saveEnvStore('${escapeHtml(scope)}', false, this)">Save</button>
+                ${kind === 'container' ? `<button class="btn btn-sm btn-primary" onclick="saveEnvStore('${escapeHtml(scope)}', true, this)">Save &amp; recreate</button>` : ''}
+            </div>
+        </div>
+    </div>`;
+    showModal(html, `Stored environment — ${escapeHtml(scope)}`, { noOk: true });
+    if (!vars.length) document.getElementById('env-store-rows').insertAdjacentHTML('beforeend', envStoreRowHtml({}));
+}
+
+async function saveEnvStore(scope, apply, btn) {
+    const vars = [];
+    for (const row of document.querySelectorAll('#env-store-rows .env-store-row')) {
+        const name = row.querySelector('.env-store-name').value.trim();
+        if (!name) continue;
+        vars.push({
+            name,
+            value: row.querySelector('.env-store-value').value,
+            secret: row.querySelector('.env-store-secret').checked,
+        });
+    }
+    if (apply && !await showConfirm(`Recreate "${scope}" now so the stored values apply? Volumes, ports and other settings are preserved.`, 'Recreate Container')) return;
+    try {
+        if (apply) showToast(`Saving and recreating "${scope}"...`, 'info');
+        const resp = await fetch(apiUrl(`/api/env-store/${encodeURIComponent(scope)}`), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ vars, apply }),
+        });
+        const data = await resp.json();
+        if (!resp.ok) { showToast(data.error || 'Failed to save', 'error'); return; }
+        showToast(data.message || 'Saved', 'success');
+        btn.closest('.modal-overlay').remove();
+        if (apply) {
+            closeContainerDetail();
+            if (typeof loadDockerContainers === 'function') loadDockerContainers();
+        }
+    } catch (e) {
+        showToast('Failed to save: ' + e.message, 'error');
+    }
+}
+
+async function deleteEnvStore(scope, btn) {
+    if (!await showConfirm(`Remove every stored variable and secret for "${scope}"? Containers already running keep their current values until they are recreated.`, 'Remove Stored Environment')) return;
+    try {
+        const resp = await fetch(apiUrl(`/api/env-store/${encodeURIComponent(scope)}`), { method: 'DELETE' });
+        const data = await resp.json();
+        if (!resp.ok) { showToast(data.error || 'Failed to remove', 'error'); return; }
+        showToast(data.message || 'Removed', 'success');
+        btn.closest('.modal-overlay').remove();
+    } catch (e) {
+        showToast('Failed to remove: ' + e.message, 'error');
+    }
+}
+
 // ─── LXC ───
 
 let lxcPollTimer = null;
@@ -27898,7 +30606,7 @@ function renderLxcContainers(containers, stats) {
         const lxcBadgeRow = `<div style="display:flex;flex-wrap:wrap;align-items:center;gap:4px;margin-top:3px;">${lxcSvcItems}<span data-update-badge="lxc:${c.name}"></span></div>`;
 
         return `<tr data-name="${escapeAttr(c.name)}">
-            <td><strong>${c.hostname || c.name}</strong>${lxcBadgeRow}${c.hostname ? `<div style="font-size:11px;color:var(--text-muted);">CT ${c.name}</div>` : ''}</td>
+            <td><strong>${c.hostname || c.name}</strong>${favouriteStarHtml('lxc', c.name)}${lxcBadgeRow}${c.hostname ? `<div style="font-size:11px;color:var(--text-muted);">CT ${c.name}</div>` : ''}</td>
             <td style="font-size:12px;color:var(--text-secondary);">${c.version || '<span style="color:var(--text-muted)">—</span>'}</td>
             <td><span style="color:${stateColor}">●</span> ${c.state}</td>
             <td style="font-size:12px; font-family:monospace;">${c.ip_address || '-'}${c.gateway ? '<div style="font-size:10px;color:var(--text-muted);">GW: ' + escapeHtml(c.gateway) + '</div>' : ''}${c.mac_address ? '<div style="font-size:10px;color:var(--text-muted);">MAC: ' + escapeHtml(c.mac_address) + '</div>' : ''}</td>
@@ -27921,6 +30629,7 @@ function renderLxcContainers(containers, stats) {
                 <button class="btn btn-sm" style="${btnStyle}" onclick="openContainerUpdates('lxc', '${c.name}')" title="Check Updates"><span class="ws-icon-clean-wrap" data-icon="updates"></span></button>
                 <button class="btn btn-sm" style="${btnStyle}" onclick="openContainerCron('lxc', '${c.name}')" title="Cron Jobs"><span class="ws-icon-clean-wrap" data-icon="calendar"></span></button>
                 <button class="btn btn-sm" style="${btnStyle}" onclick="cloneLxcContainer('${c.name}')" title="Clone"><span class="ws-icon-clean-wrap" data-icon="copy"></span></button>
+                <button class="btn btn-sm" style="${btnStyle}" onclick="saveContainerTemplate('lxc', '${c.name}')" title="Save as Template"><span class="ws-icon-clean-wrap" data-icon="package"></span></button>
                 <button class="btn btn-sm" style="${btnStyle}" onclick="migrateLxcContainer('${c.name}')" title="Migrate"><span class="ws-icon-clean-wrap" data-icon="migrate"></span></button>
                 <button class="btn btn-sm" style="${btnStyle}" onclick="openLxcStorage('${escapeHtml(c.name)}')" title="Storage (resize / move)"><span class="ws-icon-clean-wrap" data-icon="hard-drive"></span></button>
                 <button class="btn btn-sm" style="${btnStyle}" onclick="exportLxcContainer('${c.name}')" title="Export"><span class="ws-icon-clean-wrap" data-icon="export"></span></button>
@@ -28020,7 +30729,11 @@ async function viewContainerLogs(runtime, container) {
         const data = await resp.json();
         const logs = data.logs || [];
 
-        body.innerHTML = `
+        // The modal shows the tail; the full log streams as a download.
+        const download = runtime === 'docker'
+            ? `<div style="text-align:right;margin-bottom:8px;"><button class="btn btn-sm" onclick="window.open(apiUrl('/api/containers/docker/${encodeURIComponent(container)}/logs?download=1&tail=all'), '_blank')">Download full log</button></div>`
+            : '';
+        body.innerHTML = `${download}
             <pre style="background: var(--bg-primary); border: 1px solid var(--border); border-radius: 8px; padding: 12px;
                 font-family: 'JetBrains Mono', monospace; font-size: 12px; max-height: 400px; overflow-y: auto;
                 color: var(--text-primary); white-space: pre-wrap; word-break: break-all;">${logs.length > 0 ? logs.join('\n') : 'No logs available'}</pre>
@@ -28919,7 +31632,8 @@ async function openLxcSettings(name) {
                         <label>Memory Limit (MB)</label>
                         <input type="text" id="lxc-memory" class="form-control" value="${escapeHtml(cfg.memory_limit)}"
                             placeholder="e.g. 2048 (leave blank for unlimited)">
-                        <small style="color:var(--text-muted);margin-top:4px;display:block;">Enter value in MB. Leave blank for unlimited.</small>
+                        <small style="color:var(--text-muted);margin-top:4px;display:block;">Enter value in MB. Leave blank for unlimited.${cfg.proxmox ? '' : ' Applied live if the container is running.'}</small>
+                        ${cfg.live_memory_limit !== undefined ? `<small style="color:var(--text-muted);margin-top:2px;display:block;">Live: <b>${cfg.live_memory_limit ? escapeHtml(cfg.live_memory_limit) + ' MB' : 'unlimited'}</b></small>` : ''}
                     </div>
                     <div class="form-group">
                         <label>Swap Limit (MB)</label>
@@ -28934,7 +31648,8 @@ async function openLxcSettings(name) {
                             ${cfg.proxmox ? '' : `oninput="updateLxcCpuSummary(this.value)"`}>
                         <small style="color:var(--text-muted);margin-top:4px;display:block;">${cfg.proxmox
                             ? 'Number of cores. Leave blank for unlimited. Restart the container to apply.'
-                            : 'Plain number = soft CPU limit (e.g. <b>4</b> caps the container at 4 cores\' worth of CPU time, but the kernel can schedule it on <i>any</i> host CPU). A cpuset list <i>pins</i> the container to specific CPUs: <b>0-3</b> is the range 0,1,2,3 (4 CPUs, pinned); <b>0,3</b> is the list CPU 0 and CPU 3 only (2 CPUs, pinned). Pinning is rarely what you want — prefer a plain number unless you specifically need affinity. Blank = unlimited. Restart the container to apply.'}</small>
+                            : 'Plain number = soft CPU limit (e.g. <b>4</b> caps the container at 4 cores\' worth of CPU time, but the kernel can schedule it on <i>any</i> host CPU). A cpuset list <i>pins</i> the container to specific CPUs: <b>0-3</b> is the range 0,1,2,3 (4 CPUs, pinned); <b>0,3</b> is the list CPU 0 and CPU 3 only (2 CPUs, pinned). Pinning is rarely what you want — prefer a plain number unless you specifically need affinity. Blank = unlimited. Applied live if the container is running.'}</small>
+                        ${cfg.live_cpus !== undefined ? `<small style="color:var(--text-muted);margin-top:2px;display:block;">Live: <b>${cfg.live_cpus ? escapeHtml(cfg.live_cpus) : 'unlimited'}</b></small>` : ''}
                         ${cfg.proxmox ? '' : `<small id="lxc-cpu-summary" style="color:var(--accent-color,#7c3aed);margin-top:4px;display:block;font-weight:500;"></small>`}
                     </div>
                 </div>
@@ -28950,6 +31665,7 @@ async function openLxcSettings(name) {
                         </div>
                     </div>
                     ${mountRows}
+                    <div id="mount-deps-lxc" style="margin-top:12px;"></div>
                 </div>
             </div>
 
@@ -29016,6 +31732,7 @@ async function openLxcSettings(name) {
                     <div style="font-size:11px;color:var(--text-muted);margin-bottom:8px;">
                         Advanced: Edit the raw LXC config. Changes here override the structured settings above.
                         A backup is created automatically before saving.
+                        <a href="#" onclick="openConfigHistory(null, '/${name}/config'); return false;">Revision history</a>
                     </div>
                     <textarea id="lxc-config-editor" style="width:100%;height:250px;background:var(--bg-primary);color:var(--text-primary);
                         border:1px solid var(--border);border-radius:8px;padding:12px;font-family:'JetBrains Mono',monospace;
@@ -29034,6 +31751,7 @@ async function openLxcSettings(name) {
                 <button class="btn btn-sm btn-primary" onclick="saveLxcSettings('${name}')">Save Settings</button>
             </div>
         `;
+        loadMountDeps('lxc', name, 'mount-deps-lxc');
 
         // A vSwitch NIC comes back with a recovered uplink. vswUplinkChanged
         // only fires on user input, so run it once per such NIC to grey out
@@ -29800,6 +32518,196 @@ async function doCloneLxc(name) {
     }
 }
 
+// ─── Container templates (golden images) ───
+
+const TPL_INPUT_STYLE = 'width:100%;padding:8px 12px;background:var(--bg-primary,#111);border:1px solid var(--border,#444);border-radius:6px;color:var(--text,#fff);margin-top:4px;';
+
+function saveContainerTemplate(kind, name) {
+    const modal = document.createElement('div');
+    modal.id = 'tpl-save-modal';
+    modal.style.cssText = 'position:fixed;top:0;left:0;right:0;bottom:0;background:rgba(0,0,0,0.6);display:flex;align-items:center;justify-content:center;z-index:10000;backdrop-filter:blur(4px);';
+    const note = kind === 'lxc'
+        ? 'The container is stopped while its root filesystem is exported, then started again.'
+        : 'Saves the image, ports, environment, volumes and limits. Volume data is not copied.';
+    modal.innerHTML = `
+        <div style="background:var(--card-bg,#1e1e2e);border:1px solid var(--border,#333);border-radius:12px;padding:28px 36px;min-width:400px;max-width:500px;box-shadow:0 20px 60px rgba(0,0,0,0.5);">
+            <h3 style="margin:0 0 16px;color:var(--text,#fff);">Save as Template</h3>
+            <p style="margin:0 0 16px;color:var(--text-muted,#aaa);font-size:0.9em;">Save <strong>${escapeHtml(name)}</strong> to the template library. ${note}</p>
+            <div style="display:flex;flex-direction:column;gap:12px;">
+                <div><label style="font-size:13px;color:var(--text-muted,#aaa);">Template Name</label>
+                    <input id="tpl-save-name" type="text" value="${escapeAttr(name)}" style="${TPL_INPUT_STYLE}"></div>
+                <div><label style="font-size:13px;color:var(--text-muted,#aaa);">Description</label>
+                    <input id="tpl-save-desc" type="text" placeholder="optional" style="${TPL_INPUT_STYLE}"></div>
+                <div style="display:flex;gap:8px;justify-content:flex-end;margin-top:8px;">
+                    <button class="btn" onclick="document.getElementById('tpl-save-modal')?.remove()">Cancel</button>
+                    <button class="btn" id="tpl-save-btn" style="background:var(--primary,#3b82f6);color:#fff;">Save</button>
+                </div>
+            </div>
+        </div>
+    `;
+    document.body.appendChild(modal);
+    document.getElementById('tpl-save-btn').onclick = async () => {
+        const tplName = document.getElementById('tpl-save-name').value.trim();
+        if (!tplName) { showToast('Enter a template name', 'error'); return; }
+        const btn = document.getElementById('tpl-save-btn');
+        btn.disabled = true;
+        btn.textContent = kind === 'lxc' ? 'Exporting…' : 'Saving…';
+        try {
+            const resp = await fetch(apiUrl('/api/templates'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ kind, container: name, name: tplName, description: document.getElementById('tpl-save-desc').value.trim() }),
+            });
+            const data = await resp.json().catch(() => ({}));
+            if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+            modal.remove();
+            showToast(`Template '${tplName}' saved`, 'success');
+        } catch (e) {
+            btn.disabled = false;
+            btn.textContent = 'Save';
+            showToast('Save failed: ' + e.message, 'error');
+        }
+    };
+}
+
+async function openTemplateLibrary(kind) {
+    showModal('<div id="tpl-library">Loading…</div>', kind === 'lxc' ? 'LXC Templates' : 'Docker Templates');
+    await renderTemplateLibrary(kind);
+}
+
+async function renderTemplateLibrary(kind) {
+    const el = document.getElementById('tpl-library');
+    if (!el) return;
+    let data;
+    try {
+        const resp = await fetch(apiUrl('/api/templates'));
+        data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+    } catch (e) {
+        el.innerHTML = `<div style="color:#ef4444;">Failed to load templates: ${escapeHtml(e.message)}</div>`;
+        return;
+    }
+    const list = (data.templates || []).filter(t => t.kind === kind);
+    let h = '<div style="white-space:normal;">';
+    if (!list.length) {
+        h += `<p style="margin:0 0 12px;">No ${kind === 'lxc' ? 'LXC' : 'Docker'} templates yet. Use the <span class="ws-icon-clean-wrap" data-icon="package"></span> button on a container to save one.</p>`;
+    }
+    list.forEach(t => {
+        const detail = t.kind === 'docker'
+            ? escapeHtml(t.docker?.image || '')
+            : escapeHtml([t.lxc?.distribution, t.lxc?.release].filter(Boolean).join(' ')) + (t.size_bytes ? ' · ' + formatBytes(t.size_bytes) : '');
+        h += `<div style="display:flex;align-items:center;gap:8px;padding:8px 0;border-bottom:1px solid var(--border);">
+            <div style="flex:1;min-width:0;">
+                <div style="color:var(--text-primary);font-weight:600;">${escapeHtml(t.name)}</div>
+                <div style="font-size:11px;">${detail} · from ${escapeHtml(t.source_container)} on ${escapeHtml(t.source_node)} · ${new Date(t.created_at * 1000).toLocaleDateString()}</div>
+                ${t.description ? `<div style="font-size:12px;">${escapeHtml(t.description)}</div>` : ''}
+            </div>
+            <button class="btn btn-sm btn-primary" onclick="deployTemplate('${escapeAttr(t.id)}','${escapeAttr(kind)}')">Deploy</button>
+            <button class="btn btn-sm" style="color:#ef4444;" onclick="deleteTemplate('${escapeAttr(t.id)}','${escapeAttr(kind)}')" title="Delete"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
+        </div>`;
+    });
+    h += `<div style="margin-top:14px;font-size:12px;">Library location — point it at a shared storage mount to use the same templates on every node.
+        <div style="display:flex;gap:6px;margin-top:4px;">
+            <input id="tpl-library-path" type="text" value="${escapeAttr(data.library_path || '')}" style="flex:1;padding:6px 10px;background:var(--bg-primary);border:1px solid var(--border);border-radius:6px;color:var(--text-primary);">
+            <button class="btn btn-sm" onclick="saveTemplateLibraryPath('${escapeAttr(kind)}')">Save</button>
+        </div></div></div>`;
+    el.innerHTML = h;
+}
+
+async function saveTemplateLibraryPath(kind) {
+    const path = document.getElementById('tpl-library-path')?.value.trim() || '';
+    try {
+        const resp = await fetch(apiUrl('/api/templates/settings'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ library_path: path }),
+        });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast(data.message || 'Saved', 'success');
+        renderTemplateLibrary(kind);
+    } catch (e) {
+        showToast('Save failed: ' + e.message, 'error');
+    }
+}
+
+async function deleteTemplate(id, kind) {
+    if (!await showConfirm('Delete this template? Containers already deployed from it are not affected.')) return;
+    try {
+        const resp = await fetch(apiUrl(`/api/templates/${encodeURIComponent(id)}`), { method: 'DELETE' });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast('Template deleted', 'success');
+        renderTemplateLibrary(kind);
+    } catch (e) {
+        showToast('Delete failed: ' + e.message, 'error');
+    }
+}
+
+async function deployTemplate(id, kind) {
+    // Node list from the LOCAL server, as the clone dialog does
+    let nodes = [];
+    try {
+        const resp = await fetch('/api/nodes');
+        if (resp.ok) {
+            const data = await resp.json();
+            nodes = Array.isArray(data) ? data : (data.nodes || []);
+        }
+    } catch (e) { }
+    const modal = document.createElement('div');
+    modal.id = 'tpl-deploy-modal';
+    modal.style.cssText = 'position:fixed;top:0;left:0;right:0;bottom:0;background:rgba(0,0,0,0.6);display:flex;align-items:center;justify-content:center;z-index:100001;backdrop-filter:blur(4px);';
+    modal.innerHTML = `
+        <div style="background:var(--card-bg,#1e1e2e);border:1px solid var(--border,#333);border-radius:12px;padding:28px 36px;min-width:400px;max-width:500px;box-shadow:0 20px 60px rgba(0,0,0,0.5);">
+            <h3 style="margin:0 0 16px;color:var(--text,#fff);">Deploy Template</h3>
+            <div style="display:flex;flex-direction:column;gap:12px;">
+                <div><label style="font-size:13px;color:var(--text-muted,#aaa);">Container Name</label>
+                    <input id="tpl-deploy-name" type="text" style="${TPL_INPUT_STYLE}"></div>
+                <div><label style="font-size:13px;color:var(--text-muted,#aaa);">Target Node</label>
+                    <select id="tpl-deploy-node" style="${TPL_INPUT_STYLE}">
+                        <option value="">This node</option>
+                        ${nodes.filter(n => !n.is_self && n.online).sort((a, b) => (a.hostname || a.address).localeCompare(b.hostname || b.address)).map(n => `<option value="${escapeAttr(n.id)}">${escapeHtml(n.hostname)} (${escapeHtml(n.address)})</option>`).join('')}
+                    </select></div>
+                ${kind === 'lxc' ? `<div><label style="font-size:13px;color:var(--text-muted,#aaa);">Storage</label>
+                    <input id="tpl-deploy-storage" type="text" placeholder="Auto (default)" style="${TPL_INPUT_STYLE}"></div>` : ''}
+                <div style="display:flex;gap:8px;justify-content:flex-end;margin-top:8px;">
+                    <button class="btn" onclick="document.getElementById('tpl-deploy-modal')?.remove()">Cancel</button>
+                    <button class="btn" id="tpl-deploy-btn" style="background:var(--primary,#3b82f6);color:#fff;">Deploy</button>
+                </div>
+            </div>
+        </div>
+    `;
+    document.body.appendChild(modal);
+    document.getElementById('tpl-deploy-btn').onclick = async () => {
+        const name = document.getElementById('tpl-deploy-name').value.trim();
+        if (!name) { showToast('Enter a container name', 'error'); return; }
+        const btn = document.getElementById('tpl-deploy-btn');
+        btn.disabled = true;
+        btn.textContent = 'Deploying…';
+        try {
+            const resp = await fetch(apiUrl(`/api/templates/${encodeURIComponent(id)}/deploy`), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({
+                    name,
+                    node_id: document.getElementById('tpl-deploy-node').value,
+                    storage: document.getElementById('tpl-deploy-storage')?.value.trim() || null,
+                }),
+            });
+            const data = await resp.json().catch(() => ({}));
+            if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+            modal.remove();
+            showToast(data.message || `Deployed '${name}'`, 'success');
+            setTimeout(kind === 'lxc' ? loadLxcContainers : loadDockerContainers, 500);
+        } catch (e) {
+            btn.disabled = false;
+            btn.textContent = 'Deploy';
+            showToast('Deploy failed: ' + e.message, 'error');
+        }
+    };
+}
+
+
 async function migrateLxcContainer(name) {
     // Fetch cluster nodes from LOCAL server (not proxied) — remote nodes don't have cluster info
     let nodes = [];
@@ -29865,6 +32773,13 @@ async function migrateLxcContainer(name) {
                         <option value="">Auto (default)</option>
                     </select>
                     <span id="migrate-storage-hint" style="font-size:11px;color:var(--text-muted,#666);margin-top:2px;display:block;">Select a target node to load available storages</span></div>
+                <label id="migrate-live-row" style="display:flex;align-items:flex-start;gap:8px;font-size:13px;color:var(--text,#fff);">
+                    <input type="checkbox" id="migrate-live" style="margin-top:3px;">
+                    <span>Live migrate (CRIU)<br><span style="font-size:11px;color:var(--text-muted,#666);">Checkpoint the running container and restore it on the target, so its processes carry on instead of rebooting. Needs criu on both nodes; falls back to a normal move if either can't.</span></span>
+                </label>
+                <div id="migrate-bwlimit-row"><label style="font-size:13px;color:var(--text-muted,#aaa);">Bandwidth cap (Mbit/s)</label>
+                    <input id="migrate-bwlimit" type="number" min="1" placeholder="Unlimited" style="width:100%;padding:8px 12px;background:var(--bg-primary,#111);border:1px solid var(--border,#444);border-radius:6px;color:var(--text,#fff);margin-top:4px;">
+                    <span style="font-size:11px;color:var(--text-muted,#666);margin-top:2px;display:block;">Only changed data is sent if the target already has an earlier copy; an interrupted transfer picks up where it stopped.</span></div>
                 <div style="display:flex;gap:8px;justify-content:flex-end;margin-top:8px;">
                     <button class="btn" onclick="document.getElementById('lxc-migrate-modal')?.remove()">Cancel</button>
                     <button class="btn" style="background:#ef4444;color:#fff;" onclick="doMigrateLxc('${name}')">Migrate</button>
@@ -29907,6 +32822,8 @@ async function migrateLxcContainer(name) {
     document.getElementById('migrate-target').addEventListener('change', async (e) => {
         const val = e.target.value;
         document.getElementById('migrate-external-fields').style.display = val === '__external__' ? 'block' : 'none';
+        document.getElementById('migrate-live-row').style.display = val === '__external__' ? 'none' : 'flex';
+        document.getElementById('migrate-bwlimit-row').style.display = val === '__external__' ? 'none' : 'block';
 
         const sel = document.getElementById('migrate-storage');
         const hint = document.getElementById('migrate-storage-hint');
@@ -29950,6 +32867,8 @@ async function doMigrateLxc(name) {
     const rawExtUrl = document.getElementById('migrate-ext-url')?.value.trim() || '';
     const extToken = document.getElementById('migrate-ext-token')?.value.trim() || '';
     const migrateStorage = document.getElementById('migrate-storage')?.value || '';
+    const migrateLive = !!document.getElementById('migrate-live')?.checked;
+    const migrateBwlimit = parseInt(document.getElementById('migrate-bwlimit')?.value || '', 10);
     document.getElementById('lxc-migrate-modal')?.remove();
 
     const isExternal = target === '__external__';
@@ -30003,7 +32922,7 @@ async function doMigrateLxc(name) {
         // Intra-cluster: background task with a live stepped progress box.
         const logTaskId = taskLogStart(`Migrate LXC '${name}' → ${lxcTargetLabel}`);
         const steps = [
-            { id: 'export', label: 'Stop & export source', icon: '', stages: ['preflight', 'stop_source', 'export'] },
+            { id: 'export', label: migrateLive ? 'Checkpoint & export source' : 'Stop & export source', icon: '', stages: ['preflight', 'checkpoint', 'stop_source', 'export'] },
             { id: 'upload', label: `Transfer to ${lxcTargetLabel}`, icon: '', stages: ['upload'] },
             { id: 'start',  label: `Import & start on ${lxcTargetLabel}`, icon: '', stages: ['import', 'start'] },
         ];
@@ -30020,6 +32939,8 @@ async function doMigrateLxc(name) {
             const migrateBody = { target_node: target };
             if (targetNode) { migrateBody.target_address = targetNode.address; migrateBody.target_port = targetNode.port || 8553; }
             if (migrateStorage) migrateBody.storage = migrateStorage;
+            if (migrateLive) migrateBody.live = true;
+            if (migrateBwlimit > 0) migrateBody.bwlimit_mbps = migrateBwlimit;
             const resp = await fetch(apiUrl(`/api/containers/lxc/${name}/migrate`), {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
@@ -30421,7 +33342,7 @@ async function doMigrateVm(name) {
 /// doMigrateVmDiskStorage. Inserts the modal into the DOM, wires up
 /// the elapsed timer display, and ensures the spin keyframes style is
 /// present. Steps array shape: { id, label, icon, stages: [string] }.
-function showVmMigrateProgressModal(name, steps, noun = 'VM') {
+function showVmMigrateProgressModal(name, steps, noun = 'VM', heading = null) {
     // Drop any stale modal so the elapsed-timer/progress lookups below bind
     // to this run's elements, not a leftover one from an aborted migration.
     document.getElementById('vm-op-modal')?.remove();
@@ -30430,8 +33351,8 @@ function showVmMigrateProgressModal(name, steps, noun = 'VM') {
     modal.style.cssText = 'position:fixed;top:0;left:0;right:0;bottom:0;background:rgba(0,0,0,0.6);display:flex;align-items:center;justify-content:center;z-index:10000;backdrop-filter:blur(4px);';
     modal.innerHTML = `
         <div style="background:var(--card-bg,#1e1e2e);border:1px solid var(--border,#333);border-radius:12px;padding:28px 36px;min-width:480px;max-width:580px;box-shadow:0 20px 60px rgba(0,0,0,0.5);">
-            <h3 style="margin:0 0 6px;color:var(--text,#fff);">Migrating ${escapeHtml(noun)}</h3>
-            <p style="margin:0 0 16px;color:var(--text-muted,#aaa);font-size:0.85em;">Moving <strong>${escapeHtml(name)}</strong> — progress is polled live from the server.</p>
+            <h3 style="margin:0 0 6px;color:var(--text,#fff);">${escapeHtml(heading || `Migrating ${noun}`)}</h3>
+            <p style="margin:0 0 16px;color:var(--text-muted,#aaa);font-size:0.85em;">${heading ? '' : 'Moving '}<strong>${escapeHtml(name)}</strong> — progress is polled live from the server.</p>
             <div id="vm-migrate-steps" style="display:flex;flex-direction:column;gap:6px;margin-bottom:14px;">
                 ${steps.map(s => `
                     <div id="vmstep-${s.id}" style="display:flex;align-items:center;gap:10px;padding:8px 12px;border-radius:8px;background:var(--bg-secondary,#161622);transition:all 0.3s;">
@@ -30450,6 +33371,12 @@ function showVmMigrateProgressModal(name, steps, noun = 'VM') {
                     <div id="vm-migrate-bar" style="height:100%;width:0%;background:linear-gradient(90deg,#3b82f6,#60a5fa);transition:width 400ms ease-out;"></div>
                 </div>
             </div>
+            <div id="vm-migrate-cap" style="display:none;align-items:center;gap:8px;margin-bottom:8px;font-size:12px;color:var(--text-muted,#888);">
+                <span>Bandwidth cap</span>
+                <input id="vm-migrate-cap-input" type="number" min="1" placeholder="Unlimited" style="width:110px;padding:4px 8px;background:var(--bg-primary,#111);border:1px solid var(--border,#444);border-radius:6px;color:var(--text,#fff);">
+                <span>Mbit/s</span>
+                <button class="btn btn-sm" id="vm-migrate-cap-set">Apply</button>
+            </div>
             <div style="display:flex;align-items:center;gap:10px;margin-bottom:8px;">
                 <div id="vm-migrate-spinner" style="width:20px;height:20px;border:3px solid var(--border,#555);border-top:3px solid #3b82f6;border-radius:50%;animation:spin 1s linear infinite;flex-shrink:0;"></div>
                 <span id="vm-migrate-elapsed" style="font-size:0.82em;color:var(--text-muted,#888);">Elapsed: 0s</span>
@@ -30503,6 +33430,34 @@ function formatMigrateBytes(done, total) {
     return fmt(done);
 }
 
+function formatMigrateRate(rate, eta) {
+    if (!rate) return '';
+    let text = ` · ${formatMigrateBytes(rate)}/s`;
+    if (eta != null) {
+        const h = Math.floor(eta / 3600), m = Math.floor((eta % 3600) / 60), sec = eta % 60;
+        text += ` · ${h ? `${h}h ${m}m` : m ? `${m}m ${sec}s` : `${sec}s`} left`;
+    }
+    return text;
+}
+
+/// Change a running migration's bandwidth cap; empty lifts it.
+async function setMigrationBwlimit(taskId) {
+    const input = document.getElementById('vm-migrate-cap-input');
+    const mbps = parseInt(input?.value || '', 10);
+    try {
+        const r = await fetch(apiUrl(`/api/migration/${taskId}/bwlimit`), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ bwlimit_mbps: mbps > 0 ? mbps : null }),
+        });
+        const data = await r.json().catch(() => ({}));
+        if (!r.ok) throw new Error(data.error || `HTTP ${r.status}`);
+        showToast(data.bwlimit_mbps ? `Transfer capped at ${data.bwlimit_mbps} Mbit/s` : 'Bandwidth cap lifted', 'success');
+    } catch (e) {
+        showToast('Failed to change the bandwidth cap: ' + e.message, 'error');
+    }
+}
+
 /// Poll /api/migration/{id}/status every second and update the modal's
 /// steps + progress bar. Resolves with `{ok, message?, error?, failedStage?}`
 /// once the task reaches `done` or `failed`. Any network hiccup during
@@ -30512,6 +33467,10 @@ async function pollVmMigrationProgress(taskId, steps) {
     const barEl = document.getElementById('vm-migrate-bar');
     const msgEl = document.getElementById('vm-migrate-msg');
     const bytesEl = document.getElementById('vm-migrate-bytes');
+    const capEl = document.getElementById('vm-migrate-cap');
+    const capInput = document.getElementById('vm-migrate-cap-input');
+    const capBtn = document.getElementById('vm-migrate-cap-set');
+    if (capBtn) capBtn.onclick = () => setMigrationBwlimit(taskId);
     const stageToStep = {};
     steps.forEach(s => (s.stages || [s.id]).forEach(stg => { stageToStep[stg] = s.id; }));
     let lastStage = null;
@@ -30548,7 +33507,9 @@ async function pollVmMigrationProgress(taskId, steps) {
         const total = typeof data.bytes_total === 'number' ? data.bytes_total : null;
 
         if (msgEl) msgEl.textContent = msg || '…';
-        if (bytesEl) bytesEl.textContent = formatMigrateBytes(done, total);
+        if (bytesEl) bytesEl.textContent = formatMigrateBytes(done, total) + formatMigrateRate(data.rate_bps, data.eta_secs);
+        if (capEl) capEl.style.display = stage === 'upload' ? 'flex' : 'none';
+        if (capInput && document.activeElement !== capInput) capInput.value = data.bwlimit_mbps || '';
         if (barEl) {
             if (pct != null) {
                 barEl.style.width = Math.max(0, Math.min(100, pct)).toFixed(1) + '%';
@@ -31076,7 +34037,33 @@ async function searchDockerHub() {
     }
 }
 
-function selectDockerImage(imageName) {
+// "Create on" picker for the Docker and LXC create forms: every online
+// node, defaulting to the one being viewed. The create is sent to this
+// server with target_node and routed to the chosen node server-side.
+function createNodeSelectHtml(id) {
+    const self = (allNodes || []).find(n => n.is_self);
+    const current = currentNodeId || self?.id || '';
+    const nodes = (allNodes || []).filter(n => n.online || n.id === current)
+        .sort((a, b) => (a.hostname || a.address).localeCompare(b.hostname || b.address));
+    if (nodes.length < 2) return '';
+    return `<div style="margin-bottom:12px;">
+        <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Create on</label>
+        <select id="${id}" style="width:100%; padding:8px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary);">
+            ${nodes.map(n => `<option value="${escapeAttr(n.id)}" ${n.id === current ? 'selected' : ''}>${escapeHtml(n.hostname || n.address)}${n.is_self ? ' (this server)' : ''}</option>`).join('')}
+        </select>
+        <div style="font-size:11px; color:var(--text-muted); margin-top:2px;">Storage, network and mount choices below are listed from the node being viewed.</div>
+    </div>`;
+}
+
+// URL for a create-form call: straight to this server when a "Create on"
+// node was picked (the server routes it), else the viewed node as usual.
+function createCallUrl(targetNode, path) {
+    return targetNode ? path : apiUrl(path);
+}
+
+// `local` = an image already on the node (e.g. one built here) — the
+// create step skips the registry pull, which would fail for a local tag.
+function selectDockerImage(imageName, local) {
     if (!imageName) { showToast('Please enter an image name', 'error'); return; }
 
     // Move to Step 2: Configuration
@@ -31094,12 +34081,13 @@ function selectDockerImage(imageName) {
                     <span style="font-size:24px;"></span>
                     <div style="flex:1;">
                         <strong style="font-size:15px;">${imageName}</strong>
-                        <div style="font-size:12px; color:var(--text-muted); margin-top:2px;">Docker Hub image</div>
+                        <div style="font-size:12px; color:var(--text-muted); margin-top:2px;">${local ? 'Local image' : 'Docker Hub image'}</div>
                     </div>
                     <button class="btn btn-sm" onclick="showDockerCreate()" style="font-size:11px;">← Change</button>
                 </div>
             </div>
             <input type="hidden" id="docker-create-image" value="${imageName}">
+            <input type="hidden" id="docker-create-local" value="${local ? '1' : ''}">
             <div style="display:grid; grid-template-columns:1fr 1fr; gap:12px; margin-bottom:12px;">
                 <div>
                     <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Container Name</label>
@@ -31112,6 +34100,7 @@ function selectDockerImage(imageName) {
                         style="width:100%; padding:8px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary);">
                 </div>
             </div>
+            ${createNodeSelectHtml('docker-create-node')}
             <div style="margin-bottom:12px;">
                 <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Port Mappings</label>
                 <div id="docker-create-ports-list" style="display:flex; flex-direction:column; gap:6px;"></div>
@@ -31148,6 +34137,18 @@ function selectDockerImage(imageName) {
                         <option value="8">8 cores</option>
                     </select>
                 </div>
+                <div>
+                    <label style="display:block; margin-bottom:4px; font-weight:600; font-size:13px;">Disk Quota</label>
+                    <select id="docker-create-storage" title="Checking storage driver..."
+                        style="width:100%; padding:8px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary); font-size:13px;">
+                        <option value="" selected>Unlimited</option>
+                        <option value="5g">5 GB</option>
+                        <option value="10g">10 GB</option>
+                        <option value="20g">20 GB</option>
+                        <option value="50g">50 GB</option>
+                        <option value="100g">100 GB</option>
+                    </select>
+                </div>
             </div>
             <div style="margin-bottom:12px; padding:12px; background:var(--bg-tertiary); border-radius:8px; border:1px solid var(--border);">
                 <div style="display:flex; align-items:center; justify-content:space-between; margin-bottom:8px;">
@@ -31173,6 +34174,35 @@ function selectDockerImage(imageName) {
                     <span style="font-size:12px; color:var(--text-muted);">Leave empty for no WolfNet</span>
                 </div>
             </div>
+            <div style="margin-bottom:12px; padding:12px; background:var(--bg-tertiary); border-radius:8px; border:1px solid var(--border);">
+                <div style="display:flex; align-items:center; gap:8px; margin-bottom:8px;">
+                    <strong style="font-size:13px;">Register with WolfProxy</strong>
+                    <span style="font-size:12px; color:var(--text-muted);">Add this container to a site's backends; it's removed again when the container is destroyed</span>
+                </div>
+                <div style="display:grid; grid-template-columns:2fr 1fr 1fr 1fr 1fr; gap:8px; align-items:end;">
+                    <div>
+                        <label style="display:block; margin-bottom:4px; font-size:12px;">HTTP proxy</label>
+                        <select id="docker-create-proxy" class="form-control" style="font-size:13px;"><option value="">Don't register</option></select>
+                    </div>
+                    <div>
+                        <label style="display:block; margin-bottom:4px; font-size:12px;">Container port</label>
+                        <input id="docker-create-proxy-port" type="number" min="1" max="65535" class="form-control" placeholder="80" style="font-size:13px;">
+                    </div>
+                    <div>
+                        <label style="display:block; margin-bottom:4px; font-size:12px;">Scheme</label>
+                        <select id="docker-create-proxy-scheme" class="form-control" style="font-size:13px;"><option value="http">http</option><option value="https">https</option></select>
+                    </div>
+                    <div>
+                        <label style="display:block; margin-bottom:4px; font-size:12px;" title="Failed requests before the backend is rested">Max fails</label>
+                        <input id="docker-create-proxy-fails" type="number" min="0" class="form-control" value="3" style="font-size:13px;">
+                    </div>
+                    <div>
+                        <label style="display:block; margin-bottom:4px; font-size:12px;" title="Window for counting failures, and how long a failed backend is rested">Fail timeout (s)</label>
+                        <input id="docker-create-proxy-timeout" type="number" min="0" class="form-control" value="30" style="font-size:13px;">
+                    </div>
+                </div>
+                <div style="font-size:11px; color:var(--text-muted); margin-top:6px;">If the proxy runs on another node, publish the container port above so it can be reached.</div>
+            </div>
             <div style="display:flex; gap:8px;">
                 <button class="btn btn-primary" onclick="createDockerContainer()">Pull & Create</button>
                 <button class="btn" onclick="showDockerCreate()">← Back</button>
@@ -31203,6 +34233,39 @@ function selectDockerImage(imageName) {
         .catch(() => {
             document.getElementById('docker-wolfnet-status').textContent = 'unavailable';
         });
+
+    // Sites this container could join. Internet Exposure sites front a
+    // single workload and don't take extra backends.
+    fetch(apiUrl('/api/router/http-proxies'))
+        .then(r => r.ok ? r.json() : [])
+        .then(list => {
+            const sel = document.getElementById('docker-create-proxy');
+            if (!sel || !Array.isArray(list)) return;
+            list.filter(p => !p.exposure).forEach(p => {
+                const opt = document.createElement('option');
+                opt.value = p.id;
+                opt.textContent = p.id + ((p.server_names || []).length ? ' (' + p.server_names.join(', ') + ')' : '');
+                sel.appendChild(opt);
+            });
+        })
+        .catch(() => {});
+
+    // Disk quotas only work on some storage drivers (overlay2 needs xfs
+    // with pquota) — lock the picker to Unlimited where they'd fail.
+    fetch(apiUrl('/api/containers/docker/storage-quota'))
+        .then(r => r.json())
+        .then(q => {
+            const sel = document.getElementById('docker-create-storage');
+            if (!sel) return;
+            if (q.supported) {
+                sel.title = `Writable-layer limit (${q.driver} storage driver)`;
+            } else {
+                sel.value = '';
+                sel.disabled = true;
+                sel.title = q.reason || q.error || 'Disk quotas are not supported on this host';
+            }
+        })
+        .catch(() => {});
 }
 
 // ─── Port Row Helpers ───
@@ -31277,33 +34340,59 @@ async function createDockerContainer() {
     const ports = collectPortRows('docker-create-ports-list');
     const env = envStr ? envStr.split(',').map(s => s.trim()) : [];
 
+    const proxyId = document.getElementById('docker-create-proxy')?.value || '';
+    let proxy = null;
+    if (proxyId) {
+        const port = parseInt(document.getElementById('docker-create-proxy-port')?.value || '0', 10) || 0;
+        if (!port) {
+            showToast('Enter the container port to register with the proxy', 'error');
+            return;
+        }
+        proxy = {
+            proxy_id: proxyId,
+            port,
+            scheme: document.getElementById('docker-create-proxy-scheme')?.value || 'http',
+            max_fails: parseInt(document.getElementById('docker-create-proxy-fails')?.value || '0', 10) || 0,
+            fail_timeout_s: parseInt(document.getElementById('docker-create-proxy-timeout')?.value || '0', 10) || 0,
+        };
+    }
+
+    const localImage = !!document.getElementById('docker-create-local')?.value;
+    const target_node = document.getElementById('docker-create-node')?.value || '';
+
     closeContainerDetail();
-    showToast(`Pulling image '${image}' and creating container...`, 'info');
+    showToast(localImage ? `Creating container from '${image}'...` : `Pulling image '${image}' and creating container...`, 'info');
 
     const _dockerTaskId = taskLogStart('Creating Docker container: ' + name);
     try {
-        // Pull the image first
-        const pullResp = await fetch(apiUrl('/api/containers/docker/pull'), {
-            method: 'POST',
-            headers: { 'Content-Type': 'application/json' },
-            body: JSON.stringify({ image }),
-        });
-        const pullData = await pullResp.json();
-        if (!pullResp.ok) {
-            showToast(pullData.error || 'Failed to pull image', 'error');
-            return;
+        // Pull the image first (local images are already here)
+        if (!localImage) {
+            const pullResp = await fetch(createCallUrl(target_node, '/api/containers/docker/pull'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ image, target_node: target_node || undefined }),
+            });
+            const pullData = await pullResp.json();
+            if (!pullResp.ok) {
+                showToast(pullData.error || 'Failed to pull image', 'error');
+                return;
+            }
+            showToast(pullData.message || `Image ${image} pulled`, 'success');
         }
-        showToast(pullData.message || `Image ${image} pulled`, 'success');
 
         // Create the container
-        const createResp = await fetch(apiUrl('/api/containers/docker/create'), {
+        const createResp = await fetch(createCallUrl(target_node, '/api/containers/docker/create'), {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
-            body: JSON.stringify({ name, image, ports, env, wolfnet_ip, memory_limit, cpu_cores, storage_limit, volumes }),
+            body: JSON.stringify({ name, image, ports, env, wolfnet_ip, memory_limit, cpu_cores, storage_limit, volumes, proxy, target_node: target_node || undefined }),
         });
         const createData = await createResp.json();
         if (createResp.ok) {
             showToast(createData.message || `Container '${name}' created!`, 'success');
+            if (createData.node) showToast(`'${name}' is on ${createData.node}`, 'info');
+            if (createData.capacity_warning) showToast(createData.capacity_warning, 'warning');
+            if (createData.proxy_warning) showToast(createData.proxy_warning, 'warning');
+            else if (proxy) showToast(`Registered with ${proxy.proxy_id}`, 'success');
             updateTaskLogEntry(_dockerTaskId, { status: 'completed', description: 'Created Docker container: ' + name });
             setTimeout(loadDockerContainers, 500);
         } else {
@@ -31472,6 +34561,7 @@ async function loadLxcTemplates() {
                         <input id="lxc-template-filter" type="text" placeholder="Filter templates (e.g. debian, ubuntu, alpine, default, cloud...)"
                             style="flex:1; padding:8px 12px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary); font-size:14px;"
                             oninput="filterLxcTemplates()">
+                        <button class="btn btn-sm" onclick="openLxcImageSources()" title="Image mirrors, download cache and custom rootfs tarballs" style="font-size:12px;">Image Sources</button>
                     </div>
                     <div id="lxc-template-list" style="max-height:350px; overflow-y:auto; border:1px solid var(--border); border-radius:8px;">
                         <table class="data-table" style="margin:0;">
@@ -31490,10 +34580,139 @@ async function loadLxcTemplates() {
     }
 }
 
+async function openLxcImageSources() {
+    showModal('<div id="lxc-image-sources">Loading…</div>', 'LXC Image Sources');
+    await renderLxcImageSources();
+}
+
+async function renderLxcImageSources() {
+    const el = document.getElementById('lxc-image-sources');
+    if (!el) return;
+    let d;
+    try {
+        const resp = await fetch(apiUrl('/api/containers/lxc/image-sources'));
+        d = await resp.json();
+        if (!resp.ok) throw new Error(d.error || `HTTP ${resp.status}`);
+    } catch (e) {
+        el.innerHTML = `<div style="color:#ef4444;">Failed to load: ${escapeHtml(e.message)}</div>`;
+        return;
+    }
+    const inp = 'width:100%;padding:6px 10px;background:var(--bg-primary);border:1px solid var(--border);border-radius:6px;color:var(--text-primary);font-size:12px;';
+    let h = '<div style="white-space:normal;">';
+    if (d.proxmox) {
+        h += '<p style="margin:0 0 10px;color:#f59e0b;">This is a Proxmox node — templates come from pveam and PVE template storage, so these settings have no effect here.</p>';
+    }
+    h += `<div style="font-weight:600;color:var(--text-primary);margin-bottom:4px;">Mirrors</div>
+        <div style="font-size:11px;margin-bottom:4px;">One image-server base URL per line, tried in order. With a mirror set, WolfStack downloads images itself, verifies them against SHA256SUMS and keeps them in the cache.</div>
+        <textarea id="lxc-img-mirrors" rows="3" style="${inp}font-family:monospace;" placeholder="https://mirror.example.com/lxc-images">${escapeHtml((d.mirrors || []).join('\n'))}</textarea>
+        <label style="display:flex;gap:6px;align-items:center;margin:6px 0;font-size:12px;"><input type="checkbox" id="lxc-img-skip-public" ${d.skip_public ? 'checked' : ''}> Never contact images.linuxcontainers.org</label>
+        <div style="font-size:12px;">Cache directory</div>
+        <input id="lxc-img-cache-dir" type="text" value="${escapeAttr(d.cache_dir || '')}" placeholder="${escapeAttr(d.effective_cache_dir || '')}" style="${inp}">
+        <div style="text-align:right;margin-top:6px;"><button class="btn btn-sm btn-primary" onclick="saveLxcImageSources()">Save</button></div>`;
+
+    const cached = d.cached || [];
+    h += `<div style="font-weight:600;color:var(--text-primary);margin:14px 0 4px;display:flex;justify-content:space-between;align-items:center;">Cached images
+        ${cached.length ? '<button class="btn btn-sm" onclick="clearLxcImageCache()">Clear cache</button>' : ''}</div>`;
+    h += cached.length
+        ? cached.map(c => `<div style="font-size:12px;">${escapeHtml(c.distribution)} ${escapeHtml(c.release)} · ${escapeHtml(c.architecture)} · ${escapeHtml(c.variant)} · build ${escapeHtml(c.build)} · ${formatBytes(c.size_bytes)}</div>`).join('')
+        : '<div style="font-size:12px;">Nothing cached yet.</div>';
+
+    h += '<div style="font-weight:600;color:var(--text-primary);margin:14px 0 4px;">Custom images</div>';
+    (d.custom || []).forEach(c => {
+        h += `<div style="display:flex;align-items:center;gap:8px;padding:4px 0;border-bottom:1px solid var(--border);font-size:12px;">
+            <div style="flex:1;min-width:0;"><strong>${escapeHtml(c.name)}</strong> ${escapeHtml(c.release)} · ${escapeHtml(c.architecture)} · ${formatBytes(c.size_bytes)}
+                <div style="font-family:monospace;font-size:10px;word-break:break-all;">${escapeHtml(c.source)} · sha256 ${escapeHtml(c.sha256.slice(0, 16))}…</div></div>
+            <button class="btn btn-sm" style="color:#ef4444;" onclick="removeLxcCustomImage('${escapeAttr(c.id)}')" title="Remove"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
+        </div>`;
+    });
+    h += `<div style="display:grid;grid-template-columns:1fr 1fr 1fr;gap:6px;margin-top:8px;">
+            <input id="lxc-cimg-name" placeholder="Name (e.g. corp-base)" style="${inp}">
+            <input id="lxc-cimg-release" placeholder="Release (e.g. 2026.10)" style="${inp}">
+            <input id="lxc-cimg-arch" placeholder="Arch (default: this host)" style="${inp}">
+        </div>
+        <input id="lxc-cimg-source" placeholder="/srv/images/rootfs.tar.xz or https://…/rootfs.tar.gz" style="${inp}margin-top:6px;">
+        <input id="lxc-cimg-sha256" placeholder="Expected SHA-256 (optional)" style="${inp}margin-top:6px;font-family:monospace;">
+        <div style="text-align:right;margin-top:6px;"><button class="btn btn-sm btn-primary" id="lxc-cimg-add" onclick="addLxcCustomImage()">Add image</button></div>`;
+    h += '</div>';
+    el.innerHTML = h;
+}
+
+async function lxcImageSourcesRequest(path, method, body) {
+    const opts = { method };
+    if (body) {
+        opts.headers = { 'Content-Type': 'application/json' };
+        opts.body = JSON.stringify(body);
+    }
+    const resp = await fetch(apiUrl(path), opts);
+    const data = await resp.json().catch(() => ({}));
+    if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+    // The template list includes custom images and depends on the mirrors.
+    lxcTemplatesCache = null;
+    return data;
+}
+
+async function saveLxcImageSources() {
+    try {
+        const data = await lxcImageSourcesRequest('/api/containers/lxc/image-sources', 'POST', {
+            mirrors: document.getElementById('lxc-img-mirrors').value.split('\n').map(m => m.trim()).filter(Boolean),
+            skip_public: document.getElementById('lxc-img-skip-public').checked,
+            cache_dir: document.getElementById('lxc-img-cache-dir').value.trim(),
+        });
+        showToast(data.message || 'Saved', 'success');
+        renderLxcImageSources();
+    } catch (e) {
+        showToast('Save failed: ' + e.message, 'error');
+    }
+}
+
+async function clearLxcImageCache() {
+    if (!await showConfirm('Delete every cached distro image? They are downloaded again on the next create.')) return;
+    try {
+        await lxcImageSourcesRequest('/api/containers/lxc/image-sources/cache', 'DELETE');
+        showToast('Image cache cleared', 'success');
+        renderLxcImageSources();
+    } catch (e) {
+        showToast('Clear failed: ' + e.message, 'error');
+    }
+}
+
+async function addLxcCustomImage() {
+    const btn = document.getElementById('lxc-cimg-add');
+    btn.disabled = true;
+    btn.textContent = 'Adding…';
+    try {
+        const img = await lxcImageSourcesRequest('/api/containers/lxc/image-sources/custom', 'POST', {
+            name: document.getElementById('lxc-cimg-name').value.trim(),
+            release: document.getElementById('lxc-cimg-release').value.trim(),
+            architecture: document.getElementById('lxc-cimg-arch').value.trim(),
+            source: document.getElementById('lxc-cimg-source').value.trim(),
+            sha256: document.getElementById('lxc-cimg-sha256').value.trim(),
+        });
+        showToast(`Custom image ${img.name} ${img.release} added`, 'success');
+        renderLxcImageSources();
+    } catch (e) {
+        btn.disabled = false;
+        btn.textContent = 'Add image';
+        showToast('Add failed: ' + e.message, 'error');
+    }
+}
+
+async function removeLxcCustomImage(id) {
+    if (!await showConfirm('Remove this custom image? Containers already created from it are not affected.')) return;
+    try {
+        await lxcImageSourcesRequest(`/api/containers/lxc/image-sources/custom/${encodeURIComponent(id)}`, 'DELETE');
+        showToast('Custom image removed', 'success');
+        renderLxcImageSources();
+    } catch (e) {
+        showToast('Remove failed: ' + e.message, 'error');
+    }
+}
+
 function variantBadge(variant) {
     const v = (variant || 'default').toLowerCase();
     if (v === 'cloud') return '<span style="display:inline-block;padding:2px 8px;border-radius:4px;font-size:11px;font-weight:600;background:#3b82f620;color:#3b82f6;">cloud</span>';
     if (v === 'desktop' || v === 'gui') return '<span style="display:inline-block;padding:2px 8px;border-radius:4px;font-size:11px;font-weight:600;background:#f59e0b20;color:#f59e0b;">desktop</span>';
+    if (v === 'custom') return '<span style="display:inline-block;padding:2px 8px;border-radius:4px;font-size:11px;font-weight:600;background:#8b5cf620;color:#8b5cf6;">custom</span>';
     return '<span style="display:inline-block;padding:2px 8px;border-radius:4px;font-size:11px;font-weight:600;background:#10b98120;color:#10b981;">server</span>';
 }
 
@@ -31501,6 +34720,7 @@ function variantDescription(variant) {
     const v = (variant || 'default').toLowerCase();
     if (v === 'cloud') return 'Cloud-init enabled image for automated provisioning';
     if (v === 'desktop' || v === 'gui') return 'Full desktop environment — heavy, not recommended for containers';
+    if (v === 'custom') return 'Custom rootfs tarball registered under Image Sources';
     return 'Minimal server image — recommended for containers';
 }
 
@@ -31567,6 +34787,7 @@ function selectLxcTemplate(distro, release, arch, variant) {
                         style="width:100%; padding:8px; border-radius:6px; border:1px solid var(--border); background:var(--bg-primary); color:var(--text-primary);">
                 </div>
             </div>
+            ${createNodeSelectHtml('lxc-create-node')}
 
             <!-- ── Resources ─────────────────────────── -->
             <div style="font-size:12px; font-weight:700; color:var(--text-muted); text-transform:uppercase; letter-spacing:0.5px; margin-bottom:8px;">Resources</div>
@@ -31934,6 +35155,7 @@ async function createLxcContainer() {
     const memory_limit = document.getElementById('lxc-create-memory')?.value || '';
     const cpu_cores = document.getElementById('lxc-create-cpus')?.value || '';
     const notes = document.getElementById('lxc-create-notes')?.value ?? '';
+    const target_node = document.getElementById('lxc-create-node')?.value || '';
 
     // Collect bind mounts
     const mountRows = document.querySelectorAll('.lxc-mount-row');
@@ -32022,11 +35244,12 @@ async function createLxcContainer() {
 
     const _lxcTaskId = taskLogStart('Creating LXC container: ' + name);
     try {
-        const resp = await fetch(apiUrl('/api/containers/lxc/create'), {
+        const resp = await fetch(createCallUrl(target_node, '/api/containers/lxc/create'), {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
                 name, distribution, release, architecture, storage_path, template_storage, root_password, memory_limit, cpu_cores, notes,
+                target_node: target_node || undefined,
                 // Network mode from the three-preset picker. Backend uses
                 // this to write the correct LXC net config (veth/bridge
                 // for "bridge", none for "host", WolfNet for "wolfnet").
@@ -32056,7 +35279,8 @@ async function createLxcContainer() {
                 updateStep('step-config', '', `Applying ${mounts.length} mount(s)...`, true);
                 for (const mount of mounts) {
                     try {
-                        await fetch(apiUrl(`/api/containers/lxc/${encodeURIComponent(name)}/mounts`), {
+                        const mountPath = `/api/containers/lxc/${encodeURIComponent(name)}/mounts`;
+                        await fetch(target_node ? nodeApiUrl(target_node, mountPath) : apiUrl(mountPath), {
                             method: 'POST',
                             headers: { 'Content-Type': 'application/json' },
                             body: JSON.stringify(mount),
@@ -32066,8 +35290,9 @@ async function createLxcContainer() {
             }
             updateStep('step-config', '', 'Configuration applied', true);
 
-            const msg = data.message || `Container '${name}' created successfully`;
+            const msg = (data.message || `Container '${name}' created successfully`) + (data.node ? ` (on ${data.node})` : '');
             showResult(true, msg);
+            if (data.capacity_warning) showToast(data.capacity_warning, 'warning');
             updateTaskLogEntry(_lxcTaskId, { status: 'completed', description: 'Created LXC container: ' + name });
             setTimeout(loadLxcContainers, 500);
         } else {
@@ -32961,6 +36186,51 @@ async function showVmSettings(name) {
                     <label>OS Disk Size (GiB) <small style="color:var(--text-muted);">(can only grow)</small></label>
                     <input type="number" class="form-control" id="edit-vm-disk" value="${vm.disk_size_gb}" min="${vm.disk_size_gb}">
                 </div>
+                ${vm.running ? `
+                <div class="form-group" style="padding:10px 12px; border:1px solid var(--border); border-radius:8px; background:var(--bg-secondary);">
+                    <label style="margin-bottom:6px;">Live resize <small style="color:var(--text-muted);">(applies to the running VM now)</small></label>
+                    <div style="display:grid; grid-template-columns:1fr 1fr 1fr; gap:8px;">
+                        <div style="display:flex; gap:4px;">
+                            <input type="number" class="form-control" id="live-vm-cpus" value="${vm.cpus}" min="1" title="vCPUs" style="font-size:13px;">
+                            <button class="btn btn-sm" onclick="liveResizeVm('${escapeAttr(vm.name)}', 'cpus')">vCPUs</button>
+                        </div>
+                        <div style="display:flex; gap:4px;">
+                            <input type="number" class="form-control" id="live-vm-memory" value="${vm.memory_mb}" min="256" title="Memory (MB)" style="font-size:13px;">
+                            <button class="btn btn-sm" onclick="liveResizeVm('${escapeAttr(vm.name)}', 'memory')">MB</button>
+                        </div>
+                        <div style="display:flex; gap:4px;">
+                            <input type="number" class="form-control" id="live-vm-disk" value="${vm.disk_size_gb}" min="${vm.disk_size_gb}" title="OS disk (GiB)" style="font-size:13px;">
+                            <button class="btn btn-sm" onclick="liveResizeVm('${escapeAttr(vm.name)}', 'disk')">GiB</button>
+                        </div>
+                    </div>
+                    <small style="color:var(--text-muted);">Disks can only grow — extend the partition inside the guest afterwards.${vmPlatform === 'native' ? ' Native VMs hot-plug vCPUs up to the ceiling below and resize memory through the balloon (up to boot memory).' : ''}</small>
+                </div>` : ''}
+                ${vmPlatform === 'native' ? `
+                <div class="form-group">
+                    <label>Hot-plug headroom <small style="color:var(--text-muted);">(applies on next start)</small></label>
+                    <div style="display:flex; gap:12px; align-items:center; flex-wrap:wrap;">
+                        <label style="display:flex; gap:6px; align-items:center; margin:0; font-weight:normal;">vCPU ceiling
+                            <input type="number" class="form-control" id="edit-vm-max-cpus" value="${vm.max_cpus || 0}" min="0" max="256" style="width:80px; font-size:13px;">
+                        </label>
+                        <label style="display:flex; gap:6px; align-items:center; margin:0; font-weight:normal;">
+                            <input type="checkbox" id="edit-vm-balloon" ${vm.balloon ? 'checked' : ''}> Memory balloon
+                        </label>
+                        <button class="btn btn-sm" onclick="saveVmHotplugOptions('${escapeAttr(vm.name)}')">Save</button>
+                    </div>
+                    <small style="color:var(--text-muted);">0 = no headroom. The balloon needs the VirtIO drivers in Windows guests.</small>
+                </div>` : ''}
+                <div class="form-group">
+                    <label for="edit-vm-os-type">Guest OS</label>
+                    <select class="form-control" id="edit-vm-os-type" style="font-size:13px;" data-orig="${escapeAttr(vm.os_type || '')}"
+                        onchange="document.getElementById('edit-vm-rdp-row').style.display = (vmOsIsWindows(this.value) || (${vm.windows ? 'true' : 'false'} && !this.value)) ? 'block' : 'none'">${vmOsTypeOptions(vm.os_type)}</select>
+                    <small style="color:var(--text-muted);">Used for the Windows badge and Remote Desktop shortcut${vm.platform === 'proxmox' ? ', and as the Proxmox OS type' : ''}.${!vm.os_type && vm.windows ? ' Not set — detected as Windows from its media/hardware.' : ''}</small>
+                </div>
+                <div class="form-group" id="edit-vm-rdp-row" style="display:${vm.windows ? 'block' : 'none'};">
+                    <label for="edit-vm-rdp-address">Remote Desktop address <small style="color:var(--text-muted);">(optional)</small></label>
+                    <input type="text" class="form-control" id="edit-vm-rdp-address" maxlength="260"
+                        value="${escapeAttr(vm.rdp_address || '')}" data-orig="${escapeAttr(vm.rdp_address || '')}" placeholder="192.168.1.50 or desk.example.com:3390">
+                    <small style="color:var(--text-muted);">Where an RDP client reaches the guest (enable Remote Desktop inside Windows). Shown as a shortcut on the VM list; WolfStack doesn't open any ports for it.</small>
+                </div>
                 <div class="form-group">
                     <label for="edit-vm-notes">Notes / Description <small style="color:var(--text-muted);">(optional)</small></label>
                     <textarea class="form-control" id="edit-vm-notes" rows="3" maxlength="4096"
@@ -33026,6 +36296,41 @@ async function showVmSettings(name) {
                     </div>
                     <button class="btn btn-sm btn-primary" onclick="addVmVolume('${name}')" style="font-size:12px;">Add Volume</button>
                 </div>
+
+                <!-- Import Disk Image -->
+                <div style="padding:10px; margin-top:10px; background:var(--bg-secondary); border:1px solid var(--border); border-radius:8px;">
+                    <div style="font-weight:600; font-size:12px; margin-bottom:4px; color:var(--text-secondary);">Import Disk Image</div>
+                    <div style="font-size:11px; color:var(--text-muted); margin-bottom:8px;">Convert an OVA, VMDK, raw, VDI or VHD image to qcow2 and attach it — e.g. a VM exported from VMware. The VM must be stopped.</div>
+                    <div style="display:flex; gap:8px; margin-bottom:6px;">
+                        <input type="text" class="form-control" id="import-disk-source" placeholder="/mnt/exports/web01.ova" style="font-size:13px; flex:1;">
+                        <button type="button" class="btn btn-sm" style="white-space:nowrap;" onclick="pickFile({title:'Choose a disk image', filter:'.ova,.vmdk,.img,.raw,.qcow2,.vdi,.vhd,.vhdx', onPick:function(p){document.getElementById('import-disk-source').value=p;}})">📁 Browse…</button>
+                    </div>
+                    <div style="display:grid; grid-template-columns:1fr 1fr 1fr; gap:8px; margin-bottom:6px;">
+                        <div>
+                            <label style="display:block; font-size:11px; color:var(--text-muted); margin-bottom:2px;">Attach as</label>
+                            <select class="form-control" id="import-disk-target" style="font-size:13px;">
+                                <option value="extra">Extra volume</option>
+                                <option value="os">OS disk (replaces it)</option>
+                            </select>
+                        </div>
+                        <div>
+                            <label style="display:block; font-size:11px; color:var(--text-muted); margin-bottom:2px;">Storage</label>
+                            <select class="form-control" id="import-disk-storage" style="font-size:13px;">${storageOpts}</select>
+                        </div>
+                        <div>
+                            <label style="display:block; font-size:11px; color:var(--text-muted); margin-bottom:2px;">Bus</label>
+                            <select class="form-control" id="import-disk-bus" style="font-size:13px;">
+                                <option value="">Keep current</option>
+                                <option value="virtio">VirtIO</option>
+                                <option value="scsi">SCSI</option>
+                                <option value="sata">SATA</option>
+                                <option value="ide">IDE</option>
+                            </select>
+                        </div>
+                    </div>
+                    <div style="font-size:11px; color:var(--text-muted); margin-bottom:8px;">VMware guests without VirtIO drivers (most Windows VMs) need SATA or IDE to boot.</div>
+                    <button class="btn btn-sm btn-primary" onclick="importVmDiskImage('${name}')" style="font-size:12px;">Import</button>
+                </div>
             </div>
 
             <!-- ═══ Tab 3: Network & Boot ═══ -->
@@ -33068,14 +36373,18 @@ async function showVmSettings(name) {
                         <div style="display:flex; gap:8px;">
                             <input type="text" class="form-control" id="edit-vm-iso" style="flex:1;" value="${escapeHtml(vm.iso_path || '')}"
                                 placeholder="Leave empty to detach ISO">
+                            <button type="button" class="btn btn-sm" style="white-space:nowrap;" onclick="openIsoLibrary('edit-vm-iso')">💿 Library…</button>
                             <button type="button" class="btn btn-sm" style="white-space:nowrap;" onclick="pickFile({title:'Choose an ISO', filter:'.iso', onPick:function(p){document.getElementById('edit-vm-iso').value=p;}})">📁 Browse…</button>
                         </div>
                         <small style="color:var(--text-muted);">Browse a share/storage or type a host path. Set to empty to boot from disk on next start.</small>
                     </div>
                     <div class="form-group" style="margin-top:12px;">
                         <label>VirtIO Drivers ISO</label>
-                        <input type="text" class="form-control" id="edit-vm-drivers-iso" value="${vm.drivers_iso || ''}"
-                            placeholder="/var/lib/wolfstack/isos/virtio-win.iso">
+                        <div style="display:flex; gap:8px;">
+                            <input type="text" class="form-control" id="edit-vm-drivers-iso" style="flex:1;" value="${vm.drivers_iso || ''}"
+                                placeholder="/var/lib/wolfstack/isos/virtio-win.iso">
+                            <button type="button" class="btn btn-sm" style="white-space:nowrap;" onclick="downloadVirtioWinIso('edit-vm-drivers-iso')" title="Download the stable virtio-win ISO from fedorapeople.org into the ISO library">⬇️ Get virtio-win</button>
+                        </div>
                         <small style="color:var(--text-muted);">Secondary CD-ROM for VirtIO drivers (Windows)</small>
                     </div>
                     <div class="form-group" style="margin-top:12px;">
@@ -33130,6 +36439,7 @@ async function showVmSettings(name) {
             <!-- ═══ Tab 4: Passthrough ═══ -->
             <div class="vms-tab-page" id="vms-tab-4" style="display:none;">
                 <div id="edit-vm-passthrough-preflight"></div>
+                <div id="edit-vm-passthrough-check"></div>
                 <div style="padding:12px; background:var(--bg-tertiary); border:1px solid var(--border); border-radius:8px; margin-bottom:12px;">
                     <div style="display:flex; align-items:center; justify-content:space-between; margin-bottom:8px;">
                         <h4 style="margin:0; font-size:13px;">USB Devices</h4>
@@ -33137,7 +36447,7 @@ async function showVmSettings(name) {
                             data-vm="${escapeAttr(vm.name)}" style="font-size:11px; padding:2px 10px;">↻ Refresh</button>
                     </div>
                     <small style="color:var(--text-muted); display:block; margin-bottom:8px;">
-                        Tick a device to pass it through to this VM. A device in use by another <em>running</em> VM will be disabled.
+                        Tick a device to pass it through to this VM. A device in use by another <em>running</em> VM will be disabled. Tick <em>pin port</em> to bind to the physical USB port instead of the vendor:product ID — needed when several identical licence dongles are attached.
                     </small>
                     <div id="edit-vm-usb-list" style="max-height:200px; overflow-y:auto;">
                         <div style="color:var(--text-muted); font-size:12px; padding:8px;">Loading...</div>
@@ -33235,6 +36545,7 @@ async function loadHostDevicesForEdit(vmName) {
         renderPassthroughPreflight(data.preflight);
         renderUsbList(vmName, data.usb || []);
         renderPciList(vmName, data.pci || []);
+        checkVmPassthrough();
     } catch (e) {
         const list = document.getElementById('edit-vm-usb-list');
         if (list) list.innerHTML = `<div style="color:var(--danger); font-size:12px; padding:8px;">Error: ${escapeHtml(e.message)}</div>`;
@@ -33274,9 +36585,14 @@ function renderUsbList(vmName, usbs) {
     }
     const state = window._editVmPassthrough || { selectedUsb: [] };
     const selected = new Set(state.selectedUsb);
+    const saved = state.savedUsb || [];
     container.innerHTML = usbs.map(u => {
         const key = u.match_key || '';
-        const isSelected = selected.has(key);
+        // A port-pinned entry selects only the device on that port, so two
+        // identical dongles don't both show as ticked.
+        const pinned = saved.some(s => s.host_bus && s.host_bus === u.host_bus);
+        const isSelected = pinned || (selected.has(key) && !saved.some(s => s.host_bus
+            && (s.vendor_id || '').toLowerCase() === u.vendor_id && (s.product_id || '').toLowerCase() === u.product_id));
         const ownerIsOther = u.in_use_by && u.in_use_by !== vmName;
         const blocked = ownerIsOther && u.in_use_running;
         const hint = ownerIsOther
@@ -33287,10 +36603,13 @@ function renderUsbList(vmName, usbs) {
         return `<label style="display:flex; align-items:center; gap:8px; padding:6px 8px; border-bottom:1px solid var(--border); font-size:12px; ${blocked?'opacity:0.5; cursor:not-allowed;':'cursor:pointer;'}">
             <input type="checkbox" class="edit-vm-usb-check" data-key="${escapeAttr(key)}"
                 data-vendor="${escapeAttr(u.vendor_id)}" data-product="${escapeAttr(u.product_id)}"
+                data-bus="${escapeAttr(u.host_bus||'')}" onchange="checkVmPassthrough()"
                 data-label="${escapeAttr(u.description||'')}" ${isSelected?'checked':''} ${blocked?'disabled':''}>
             <span style="font-family:monospace; color:var(--text-muted); min-width:100px;">${escapeHtml(u.vendor_id)}:${escapeHtml(u.product_id)}</span>
             <span style="flex:1;">${escapeHtml(u.description||'USB device')}</span>
             ${hint}
+            ${u.host_bus ? `<span style="font-size:10px; color:var(--text-muted); white-space:nowrap;" title="Pass through whatever is plugged into USB port ${escapeAttr(u.host_bus)}">
+                <input type="checkbox" class="edit-vm-usb-pin" onchange="checkVmPassthrough()" ${pinned?'checked':''} style="width:auto; vertical-align:middle;"> pin port ${escapeHtml(u.host_bus)}</span>` : ''}
         </label>`;
     }).join('');
 }
@@ -33323,6 +36642,8 @@ function renderPciList(vmName, pcis) {
             const ownerIsOther = p.in_use_by && p.in_use_by !== vmName;
             const blocked = ownerIsOther && p.in_use_running;
             const driver = p.driver ? ` · drv: ${escapeHtml(p.driver)}` : '';
+            const peers = (p.iommu_peers || []).length
+                ? `<span style="color:var(--text-muted); font-size:10px;"> · shares group with ${escapeHtml(p.iommu_peers.join(', '))}</span>` : '';
             const hint = ownerIsOther
                 ? (p.in_use_running
                     ? `<span style="color:var(--danger); font-size:10px;"> · in use by running VM '${escapeHtml(p.in_use_by)}'</span>`
@@ -33330,10 +36651,10 @@ function renderPciList(vmName, pcis) {
                 : '';
             return `<label style="display:flex; align-items:center; gap:8px; padding:6px 8px; border-bottom:1px solid var(--border); font-size:12px; ${blocked?'opacity:0.5; cursor:not-allowed;':'cursor:pointer;'}">
                 <input type="checkbox" class="edit-vm-pci-check" data-key="${escapeAttr(key)}"
-                    data-bdf="${escapeAttr(p.bdf)}" data-iommu="${escapeAttr(String(p.iommu_group||''))}"
+                    data-bdf="${escapeAttr(p.bdf)}" data-iommu="${escapeAttr(String(p.iommu_group||''))}" onchange="checkVmPassthrough()"
                     data-label="${escapeAttr(p.description||'')}" ${isSelected?'checked':''} ${blocked?'disabled':''}>
                 <span style="font-family:monospace; color:var(--text-muted); min-width:120px;">${escapeHtml(p.bdf)}</span>
-                <span style="flex:1;">${escapeHtml(p.description||p.class||'PCI device')}${driver}</span>
+                <span style="flex:1;">${escapeHtml(p.description||p.class||'PCI device')}${driver}${peers}</span>
                 ${hint}
             </label>`;
         }).join('');
@@ -33344,6 +36665,40 @@ function renderPciList(vmName, pcis) {
     }).join('');
 }
 
+// Validate the ticked devices server-side (IOMMU group viability, host
+// presence, other VMs' claims) and show the result above the lists.
+// Debounced — every checkbox change calls this.
+let _vmPassthroughCheckTimer = null;
+function checkVmPassthrough() {
+    clearTimeout(_vmPassthroughCheckTimer);
+    _vmPassthroughCheckTimer = setTimeout(async () => {
+        const el = document.getElementById('edit-vm-passthrough-check');
+        const vmName = window._editVmPassthrough?.vmName;
+        if (!el || !vmName) return;
+        const devices = collectPassthroughDevices();
+        if (!devices.usb_devices.length && !devices.pci_devices.length) { el.innerHTML = ''; return; }
+        try {
+            const resp = await fetch(apiUrl(`/api/vms/${encodeURIComponent(vmName)}/passthrough/check`), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify(devices),
+            });
+            if (!resp.ok) { el.innerHTML = ''; return; }
+            const check = await resp.json();
+            const list = (items, color) => items.map(i => `<li style="margin:3px 0; color:${color};">${escapeHtml(i)}</li>`).join('');
+            if (!check.errors.length && !check.warnings.length) {
+                el.innerHTML = `<div style="padding:8px 12px; background:rgba(34,197,94,0.08); border:1px solid rgba(34,197,94,0.3); border-radius:6px; font-size:12px; color:var(--text-secondary); margin-bottom:12px;">Selected devices look good for passthrough.</div>`;
+                return;
+            }
+            const bad = check.errors.length > 0;
+            el.innerHTML = `<div style="padding:10px 12px; background:${bad ? 'rgba(239,68,68,0.08)' : 'rgba(245,158,11,0.08)'}; border:1px solid ${bad ? 'rgba(239,68,68,0.4)' : 'rgba(245,158,11,0.4)'}; border-radius:6px; font-size:12px; margin-bottom:12px;">
+                <div style="font-weight:600; margin-bottom:6px;">${bad ? 'This selection will be refused on save' : 'Passthrough notes'}</div>
+                <ul style="margin:0; padding-left:18px;">${list(check.errors, 'var(--danger)')}${list(check.warnings, 'var(--text-secondary)')}</ul>
+            </div>`;
+        } catch (e) { /* advisory only */ }
+    }, 300);
+}
+
 function escapeAttr(s) {
     // Escape the single-quote too: this declaration hoists over the earlier
     // one (same name), so it's the effective global — it must be safe for
@@ -33369,10 +36724,11 @@ function collectPassthroughDevices() {
     const usbs = [];
     for (const c of usbChecks) {
         if (c.checked) {
+            const pin = c.closest('label')?.querySelector('.edit-vm-usb-pin');
             usbs.push({
                 vendor_id: (c.dataset.vendor || '').toLowerCase(),
                 product_id: (c.dataset.product || '').toLowerCase(),
-                host_bus: null,
+                host_bus: pin?.checked ? (c.dataset.bus || null) : null,
                 label: c.dataset.label || null,
             });
         }
@@ -33419,6 +36775,60 @@ async function addVmVolume(vmName) {
     }
 }
 
+async function importVmDiskImage(vmName) {
+    const source = document.getElementById('import-disk-source').value.trim();
+    const asOs = document.getElementById('import-disk-target').value === 'os';
+    const storage = document.getElementById('import-disk-storage').value || null;
+    const bus = document.getElementById('import-disk-bus').value;
+    if (!source) { showToast('Enter the path of the image to import', 'error'); return; }
+    if (asOs && !(await showConfirm(`Replace ${vmName}'s OS disk with ${source}? The current OS disk is overwritten.`))) return;
+
+    const isOva = source.toLowerCase().endsWith('.ova');
+    const steps = [
+        ...(isOva ? [{ id: 'extract', label: 'Extracting disks from the OVA', icon: '', stages: ['preflight', 'extract'] }] : []),
+        { id: 'convert', label: 'Converting to qcow2', icon: '', stages: isOva ? ['convert'] : ['preflight', 'convert'] },
+        { id: 'register', label: asOs ? 'Replacing the OS disk' : 'Attaching as a volume', icon: '', stages: ['register'] },
+    ];
+    const logTaskId = taskLogStart(`Importing ${source} into VM '${vmName}'`);
+    showVmMigrateProgressModal(vmName, steps, 'VM', 'Importing Disk Image');
+    const startTime = Date.now();
+    const elapsedEl = document.getElementById('vm-migrate-elapsed');
+    const elapsedTimer = setInterval(() => {
+        const secs = Math.floor((Date.now() - startTime) / 1000);
+        const mins = Math.floor(secs / 60);
+        elapsedEl.textContent = mins > 0 ? `Elapsed: ${mins}m ${secs % 60}s` : `Elapsed: ${secs}s`;
+    }, 1000);
+
+    try {
+        const r = await fetch(apiUrl(`/api/vms/${encodeURIComponent(vmName)}/import-disk`), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ source, as_os_disk: asOs, storage_path: storage, bus: bus || null }),
+        });
+        const kickoff = await r.json().catch(() => ({}));
+        if (!r.ok || !kickoff.task_id) {
+            clearInterval(elapsedTimer);
+            vmMigrateProgressFinish(steps, false, kickoff.error || ('HTTP ' + r.status));
+            updateTaskLogEntry(logTaskId, { status: 'failed', description: `VM '${vmName}' disk import: ${kickoff.error || r.status}` });
+            return;
+        }
+        const final = await pollVmMigrationProgress(kickoff.task_id, steps);
+        clearInterval(elapsedTimer);
+        if (final.ok) {
+            vmMigrateProgressFinish(steps, true, final.message || 'Disk import complete');
+            updateTaskLogEntry(logTaskId, { status: 'completed', description: final.message || `Imported ${source} into '${vmName}'` });
+            showVmSettings(vmName);
+        } else {
+            vmMigrateProgressFinish(steps, false, final.error || 'Unknown error', final.failedStage);
+            updateTaskLogEntry(logTaskId, { status: 'failed', description: `VM '${vmName}' disk import: ${final.error || 'Unknown error'}` });
+        }
+    } catch (e) {
+        clearInterval(elapsedTimer);
+        vmMigrateProgressFinish(steps, false, e.message);
+        updateTaskLogEntry(logTaskId, { status: 'failed', description: `VM '${vmName}' disk import: ${e.message}` });
+    }
+}
+
 async function removeVmVolume(vmName, volName) {
     if (!(await showConfirm(`Delete volume '${volName}'? This will permanently delete the disk file.`))) return;
     try {
@@ -33461,6 +36871,50 @@ async function resizeVmVolume(vmName, volName, currentSize) {
     }
 }
 
+// Apply one resource change to a running VM without a restart.
+async function liveResizeVm(vmName, what) {
+    const value = parseInt(document.getElementById(`live-vm-${what}`).value);
+    if (isNaN(value) || value <= 0) {
+        showToast('Enter a positive number', 'error');
+        return;
+    }
+    const body = what === 'cpus' ? { cpus: value }
+        : what === 'memory' ? { memory_mb: value }
+        : { disk: 'os', size_gb: value };
+    try {
+        const resp = await fetch(apiUrl(`/api/vms/${encodeURIComponent(vmName)}/resources/${what}`), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body)
+        });
+        const data = await resp.json();
+        if (resp.ok) {
+            showToast(data.message || 'Applied', 'success');
+            loadVms();
+        } else {
+            showToast(data.error || 'Resize failed', 'error');
+        }
+    } catch (e) {
+        showToast('Error: ' + e.message, 'error');
+    }
+}
+
+async function saveVmHotplugOptions(vmName) {
+    const maxCpus = parseInt(document.getElementById('edit-vm-max-cpus').value) || 0;
+    const balloon = !!document.getElementById('edit-vm-balloon').checked;
+    try {
+        const resp = await fetch(apiUrl(`/api/vms/${encodeURIComponent(vmName)}/hotplug`), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ max_cpus: maxCpus, balloon })
+        });
+        const data = await resp.json();
+        showToast(resp.ok ? data.message : (data.error || 'Failed to save'), resp.ok ? 'success' : 'error');
+    } catch (e) {
+        showToast('Error: ' + e.message, 'error');
+    }
+}
+
 function switchVmSettingsTab(tab) {
     // Hide all tab pages
     document.querySelectorAll('.vms-tab-page').forEach(p => p.style.display = 'none');
@@ -33495,6 +36949,13 @@ async function saveVmSettings(name) {
     const notes = document.getElementById('edit-vm-notes')?.value ?? '';
     // Extra QEMU args: send the raw value (empty string clears it).
     const extraQemuArgs = document.getElementById('edit-vm-extra-qemu-args')?.value ?? '';
+    // Guest OS / RDP hint: only sent when changed, so a routine hardware
+    // save doesn't re-run `qm set --ostype` on Proxmox.
+    const changed = (el, val) => (el && val !== el.dataset.orig) ? val : undefined;
+    const osSel = document.getElementById('edit-vm-os-type');
+    const osType = changed(osSel, osSel?.value);
+    const rdpInput = document.getElementById('edit-vm-rdp-address');
+    const rdpAddress = changed(rdpInput, rdpInput?.value.trim());
 
     // Primary-NIC network mode. vSwitch resolves to a Bridge payload after
     // the auto-create. Validation errors come back as exceptions so we
@@ -33536,6 +36997,8 @@ async function saveVmSettings(name) {
                 bridge_gateway: netFields.bridge_gateway,
                 notes,
                 extra_qemu_args: extraQemuArgs,
+                os_type: osType,
+                rdp_address: rdpAddress,
             })
         });
         const data = await resp.json();
@@ -33775,6 +37238,65 @@ async function toggleVmAutostart(name, enabled) {
 
 // ─── Backup Management ───
 
+// ─── Heavy-job concurrency (backups, restores, migrations) ───
+// Jobs beyond the node's limits wait in a queue instead of all running at
+// once; the modal shows what's running and queued and edits the limits.
+async function openJobLimits() {
+    let data;
+    try {
+        const res = await fetch(apiUrl('/api/jobs'));
+        data = await res.json();
+        if (!res.ok || data.error) throw new Error(data.error || res.status);
+    } catch (e) {
+        showToast('Could not load job limits: ' + e.message, 'error');
+        return;
+    }
+    const l = data.limits || {};
+    const num = (id, label, val, min, hint) =>
+        `<label style="display:block;font-size:12px;">${label}<input type="number" id="${id}" class="form-control" min="${min}" max="64" value="${val}" style="margin-top:3px;"><span style="font-size:11px;color:var(--text-muted);">${hint}</span></label>`;
+    const jobRow = j => `<tr><td>${escapeHtml(j.category)}</td><td>${escapeHtml(j.label)}</td><td style="font-size:11px;color:var(--text-muted);">${escapeHtml(j.priority)}</td>` +
+        `<td style="font-size:11px;color:var(--text-muted);white-space:nowrap;">${new Date(j.since * 1000).toLocaleTimeString()}</td></tr>`;
+    const table = (title, jobs) => `<div style="font-size:12px;font-weight:600;margin:10px 0 4px;">${title} (${jobs.length})</div>` +
+        (jobs.length ? `<table class="data-table" style="width:100%;font-size:12px;"><tbody>${jobs.map(jobRow).join('')}</tbody></table>`
+            : '<div style="font-size:12px;color:var(--text-muted);">None</div>');
+    showModal([
+        '<div style="display:grid;grid-template-columns:1fr 1fr;gap:10px;">',
+        num('job-max-backups', 'Backups at once', l.max_backups, 1, 'Scheduled runs queue behind manual ones'),
+        num('job-max-restores', 'Restores at once', l.max_restores, 1, ''),
+        num('job-max-migrations', 'Migrations at once', l.max_migrations, 1, 'Includes clones and disk moves'),
+        num('job-max-total', 'Heavy jobs at once, overall', l.max_total, 0, '0 = no overall limit'),
+        '</div>',
+        table('Running', data.running || []),
+        table('Queued', data.queued || []),
+        '<div style="margin-top:14px;text-align:right;">',
+        '<button class="btn btn-sm" onclick="this.closest(\'.modal-overlay\').remove()" style="margin-right:6px;">Close</button>',
+        '<button class="btn btn-sm btn-primary" onclick="saveJobLimits(this)">Save limits</button></div>',
+    ].join(''), 'Job limits on this node', { noOk: true });
+}
+
+async function saveJobLimits(btn) {
+    const val = id => parseInt(document.getElementById(id).value, 10) || 0;
+    const body = {
+        max_total: val('job-max-total'),
+        max_backups: val('job-max-backups'),
+        max_restores: val('job-max-restores'),
+        max_migrations: val('job-max-migrations'),
+    };
+    try {
+        const res = await fetch(apiUrl('/api/jobs/limits'), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        const data = await res.json();
+        if (!res.ok || data.error) throw new Error(data.error || res.status);
+        showToast('Job limits saved', 'success');
+        btn.closest('.modal-overlay').remove();
+    } catch (e) {
+        showToast('Save failed: ' + e.message, 'error');
+    }
+}
+
 async function loadBackups() {
     try {
         const [backupsRes, schedulesRes, targetsRes] = await Promise.all([
@@ -34654,6 +38176,7 @@ function renderBackupHistory(backups) {
             <td>${statusBadge}</td>
             <td style="text-align:right; white-space:nowrap;">
                 ${b.status === 'completed' ? `<button class="btn btn-sm btn-primary" onclick="openRestoreDialog('${b.id}', '${b.target?.type || ''}', '${(b.target?.name || '').replace(/[^\w.\- ]/g, '')}')" style="margin-right:4px;">Restore</button>` : ''}
+                ${b.status === 'completed' && /\.(tar|tgz)|^pbsfl-/.test(b.filename || '') ? `<button class="btn btn-sm" onclick="openBackupFiles('${b.id}', '${escapeHtml((b.target?.name || '').replace(/[^\w.\- ]/g, ''))}')" title="Restore individual files" style="margin-right:4px; background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border);">Browse files</button>` : ''}
                 <button class="btn btn-sm" onclick="deleteBackup('${b.id}')" style="background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border);"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
             </td>
         </tr>`;
@@ -34749,6 +38272,27 @@ function setScheduleHookFields(pre, post) {
     if (det) det.open = !!(pre || post);
 }
 
+// Fill / read the shared IO-limit trio (<prefix>-prio, -read, -write) used
+// by the schedule modal and the mount editor. Blank caps mean unlimited.
+function setIoLimitFields(prefix, limits) {
+    const l = limits || {};
+    const set = (suffix, v) => { const el = document.getElementById(`${prefix}-${suffix}`); if (el) el.value = v ?? ''; };
+    set('prio', l.priority || '');
+    set('read', l.read_mbps);
+    set('write', l.write_mbps);
+    const det = document.getElementById(prefix);
+    if (det && det.tagName === 'DETAILS') det.open = !!(l.priority || l.read_mbps || l.write_mbps);
+}
+
+function readIoLimitFields(prefix) {
+    const val = suffix => (document.getElementById(`${prefix}-${suffix}`)?.value || '').trim();
+    const cap = suffix => {
+        const n = parseInt(val(suffix), 10);
+        return n > 0 ? n : null;
+    };
+    return { priority: val('prio'), read_mbps: cap('read'), write_mbps: cap('write') };
+}
+
 // Edit a schedule (Gary 2026-06-25): open the create-schedule modal pre-filled
 // with the schedule's name/frequency/time/retention, and its targets + mount
 // exclusions pre-loaded into the modal's own editable picker so they can be
@@ -34769,6 +38313,7 @@ function editSchedule(id) {
     setVal('schedule-time', s.time);
     setVal('schedule-retention', s.retention);
     setScheduleHookFields(s.pre_command, s.post_command);
+    setIoLimitFields('schedule-io', s.io_limits);
 
     const schedTargets = Array.isArray(s.targets) ? s.targets : [];
     // Pre-load this schedule's per-target mount exclusions + stop-for-backup
@@ -35023,6 +38568,7 @@ async function scheduleSystemFolder() {
     setScheduleModalChrome(false);
     document.getElementById('schedule-name').value = (targets.length === 1 ? targets[0].name : 'Folders') + ' folder';
     setScheduleHookFields('', '');
+    setIoLimitFields('schedule-io', null);
     document.getElementById('create-schedule-modal').classList.add('active');
 }
 
@@ -35468,6 +39014,7 @@ async function showScheduleSelectedModal() {
     setScheduleModalChrome(false);
     document.getElementById('schedule-name').value = '';
     setScheduleHookFields('', '');
+    setIoLimitFields('schedule-io', null);
     document.getElementById('create-schedule-modal').classList.add('active');
 }
 
@@ -35530,6 +39077,7 @@ async function createSchedule() {
         enabled: editing ? !!editing.enabled : true,
         pre_command: (document.getElementById('schedule-pre-command')?.value || '').trim(),
         post_command: (document.getElementById('schedule-post-command')?.value || '').trim(),
+        io_limits: readIoLimitFields('schedule-io'),
     };
     if (editing) body.id = editing.id; // update in place
 
@@ -35629,6 +39177,7 @@ async function loadPbsConfig() {
             showPbsConfigSaved(cfg);
             updatePbsStatusBadge();
             loadPbsSnapshots();
+            loadPbsDatastore();
         } else {
             // No config yet — show the form
             showPbsConfigForm();
@@ -35741,6 +39290,7 @@ async function savePbsConfig() {
             updatePbsStatusBadge();
             populateStorageDropdown();
             loadPbsSnapshots();
+            loadPbsDatastore();
         }
     } catch (e) {
         showToast('PBS save error: ' + e.message, 'error');
@@ -35769,6 +39319,282 @@ async function testPbsConnection() {
     }
 }
 
+// ─── PBS datastore housekeeping (usage, GC / prune schedules, jobs) ───
+
+let _pbsPruneJobs = [];
+
+function pbsKeepSummary(keep) {
+    const parts = [];
+    [['keep_last', 'last'], ['keep_hourly', 'hourly'], ['keep_daily', 'daily'], ['keep_weekly', 'weekly'],
+        ['keep_monthly', 'monthly'], ['keep_yearly', 'yearly']].forEach(([k, label]) => {
+        if (keep && keep[k]) parts.push(`${keep[k]} ${label}`);
+    });
+    return parts.length ? parts.join(', ') : 'keep all';
+}
+
+async function loadPbsDatastore() {
+    const card = document.getElementById('pbs-datastore-card');
+    const body = document.getElementById('pbs-datastore-body');
+    if (!card || !body) return;
+    card.style.display = '';
+    body.innerHTML = '<p style="color:var(--text-muted);">Loading datastore…</p>';
+    let d;
+    try {
+        const res = await fetch(apiUrl('/api/backups/pbs/datastore'));
+        d = await res.json();
+        if (!res.ok || d.error) throw new Error(d.error || `HTTP ${res.status}`);
+    } catch (e) {
+        body.innerHTML = `<p style="color:var(--text-muted);">Can't read the datastore: ${escapeHtml(e.message)}</p>`;
+        return;
+    }
+    _pbsPruneJobs = d.prune_jobs || [];
+    const pct = d.total ? Math.round(d.used / d.total * 100) : 0;
+    const barColor = pct >= 90 ? '#dc3545' : (pct >= 75 ? '#ffc107' : '#28a745');
+    const gc = d.gc_status || {};
+    const gcLine = gc['upid']
+        ? `Last GC: ${formatPbsSize(gc['removed-bytes'])} removed, ${formatPbsSize(gc['pending-bytes'])} pending, dedup on disk ${formatPbsSize(gc['disk-bytes'])}`
+        : 'No garbage collection has run yet';
+    const warnings = (d.warnings || []).map(w => `<div style="color:#ffc107; font-size:12px;">⚠ ${escapeHtml(w)}</div>`).join('');
+
+    let pruneHtml;
+    if (d.prune_jobs) {
+        pruneHtml = `<div style="display:flex; justify-content:space-between; align-items:center; margin:16px 0 6px;">
+                <strong>Prune jobs</strong>
+                <button class="btn btn-sm btn-primary" onclick="openPbsPruneJobModal()">Add prune job</button>
+            </div>` + (d.prune_jobs.length ? `<table class="data-table" style="width:100%; font-size:12px;">
+                <thead><tr><th>ID</th><th>Schedule</th><th>Keep</th><th>Namespace</th><th>State</th><th style="text-align:right;">Actions</th></tr></thead>
+                <tbody>${d.prune_jobs.map(j => `<tr>
+                    <td><strong>${escapeHtml(j.id)}</strong>${j.comment ? `<div style="color:var(--text-muted);">${escapeHtml(j.comment)}</div>` : ''}</td>
+                    <td>${escapeHtml(j.schedule)}</td>
+                    <td>${escapeHtml(pbsKeepSummary(j.keep))}</td>
+                    <td>${escapeHtml(j.ns || '(root)')}</td>
+                    <td>${j.disable ? 'Disabled' : 'Enabled'}</td>
+                    <td style="text-align:right; white-space:nowrap;">
+                        <button class="btn btn-sm btn-primary" onclick="runPbsJob({ action: 'prune_job', id: '${escapeAttr(j.id)}' })">Run now</button>
+                        <button class="btn btn-sm" onclick="openPbsPruneJobModal('${escapeAttr(j.id)}')">Edit</button>
+                        <button class="btn btn-sm btn-danger" onclick="deletePbsPruneJob('${escapeAttr(j.id)}')">Delete</button>
+                    </td></tr>`).join('')}</tbody></table>`
+            : '<p style="color:var(--text-muted); font-size:12px;">No prune jobs — old snapshots are kept until pruned.</p>');
+    } else {
+        pruneHtml = `<p style="font-size:12px; color:var(--text-muted); margin-top:16px;">This PBS predates prune jobs (2.2).
+            Datastore prune schedule: <strong>${escapeHtml(d.legacy_prune_schedule || 'none')}</strong>, keep ${escapeHtml(pbsKeepSummary(d.legacy_keep))}.</p>`;
+    }
+
+    const groups = d.groups || [];
+    const groupsHtml = groups.length ? `<table class="data-table" style="width:100%; font-size:12px;">
+            <thead><tr><th>Group</th><th>Snapshots</th><th>Last backup</th><th>Owner</th></tr></thead>
+            <tbody>${groups.map(g => `<tr>
+                <td>${escapeHtml(g.backup_type)}/${escapeHtml(g.backup_id)}</td>
+                <td>${g.backup_count}</td>
+                <td>${g.last_backup ? new Date(g.last_backup * 1000).toLocaleString() : '—'}</td>
+                <td>${escapeHtml(g.owner)}</td></tr>`).join('')}</tbody></table>`
+        : '<p style="color:var(--text-muted); font-size:12px;">No backup groups.</p>';
+
+    const tasks = d.tasks || [];
+    const tasksHtml = tasks.length ? `<table class="data-table" style="width:100%; font-size:12px;">
+            <thead><tr><th>Task</th><th>Started</th><th>Status</th><th></th></tr></thead>
+            <tbody>${tasks.map(t => `<tr>
+                <td>${escapeHtml(t.worker_type || '')}${t.worker_id ? ' · ' + escapeHtml(t.worker_id) : ''}</td>
+                <td>${t.starttime ? new Date(t.starttime * 1000).toLocaleString() : '—'}</td>
+                <td>${escapeHtml(t.endtime ? (t.status || 'done') : 'running')}</td>
+                <td style="text-align:right;"><button class="btn btn-sm" onclick="watchPbsTask('${escapeAttr(t.upid)}')">Log</button></td></tr>`).join('')}</tbody></table>`
+        : '<p style="color:var(--text-muted); font-size:12px;">No recent tasks.</p>';
+
+    body.innerHTML = `${warnings}
+        <div style="display:flex; justify-content:space-between; font-size:13px; margin-bottom:6px;">
+            <span><strong>${escapeHtml(d.store)}</strong>${d.namespace ? ' · ns ' + escapeHtml(d.namespace) : ''} on ${escapeHtml(d.server)}</span>
+            <span>${formatPbsSize(d.used)} of ${formatPbsSize(d.total)} used · ${formatPbsSize(d.avail)} free</span>
+        </div>
+        <div style="height:8px; background:var(--bg-tertiary); border-radius:4px; overflow:hidden;">
+            <div style="height:100%; width:${pct}%; background:${barColor};"></div>
+        </div>
+        <div style="font-size:12px; color:var(--text-muted); margin-top:6px;">${escapeHtml(gcLine)}</div>
+        <div style="display:flex; gap:8px; align-items:center; margin-top:12px; font-size:13px;">
+            <label for="pbs-gc-schedule" style="white-space:nowrap;">GC schedule</label>
+            <input type="text" id="pbs-gc-schedule" class="form-control" style="max-width:220px;"
+                value="${escapeAttr(d.gc_schedule || '')}" placeholder="e.g. daily, sat 03:00 (empty = off)">
+            <button class="btn btn-sm btn-primary" onclick="savePbsGcSchedule()">Save</button>
+        </div>
+        ${pruneHtml}
+        <div style="margin:16px 0 6px;"><strong>Backup groups</strong></div>
+        ${groupsHtml}
+        <div style="margin:16px 0 6px;"><strong>Recent tasks</strong></div>
+        ${tasksHtml}`;
+}
+
+async function savePbsGcSchedule() {
+    const schedule = (document.getElementById('pbs-gc-schedule') || {}).value || '';
+    try {
+        const res = await fetch(apiUrl('/api/backups/pbs/datastore/gc-schedule'), {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ schedule: schedule.trim() }),
+        });
+        const data = await res.json().catch(() => ({}));
+        if (!res.ok || data.error) throw new Error(data.error || `HTTP ${res.status}`);
+        showToast(schedule.trim() ? 'GC schedule saved' : 'GC schedule cleared', 'success');
+        loadPbsDatastore();
+    } catch (e) {
+        showToast('GC schedule: ' + e.message, 'error');
+    }
+}
+
+function pbsKeepInputs(prefix, keep) {
+    keep = keep || {};
+    return `<div style="display:grid; grid-template-columns:repeat(3, 1fr); gap:8px;">
+        ${['last', 'hourly', 'daily', 'weekly', 'monthly', 'yearly'].map(k => `<div class="form-group">
+            <label style="font-size:12px;">Keep ${k}</label>
+            <input type="number" min="0" id="${prefix}-keep-${k}" class="form-control" value="${keep['keep_' + k] || ''}">
+        </div>`).join('')}
+    </div>`;
+}
+
+function readPbsKeepInputs(prefix) {
+    const keep = {};
+    ['last', 'hourly', 'daily', 'weekly', 'monthly', 'yearly'].forEach(k => {
+        const v = parseInt((document.getElementById(`${prefix}-keep-${k}`) || {}).value, 10);
+        if (v > 0) keep['keep_' + k] = v;
+    });
+    return keep;
+}
+
+function openPbsPruneModal() {
+    showModal(`<div>
+        <p style="margin-top:0;">Prune the whole datastore now. Snapshots outside the keep options are removed;
+            space comes back on the next garbage collection.</p>
+        ${pbsKeepInputs('pbs-prune')}
+        <label style="display:flex; gap:8px; align-items:center; margin-top:8px;">
+            <input type="checkbox" id="pbs-prune-dry-run" checked> Dry run (only list what would be removed)
+        </label>
+        <div style="text-align:right; margin-top:16px;">
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-danger" onclick="submitPbsPrune(this)">Prune</button>
+        </div>
+    </div>`, 'Prune datastore', { noOk: true });
+}
+
+async function submitPbsPrune(btn) {
+    const keep = readPbsKeepInputs('pbs-prune');
+    if (!Object.keys(keep).length) { showToast('Set at least one keep option', 'error'); return; }
+    const dryRun = !!(document.getElementById('pbs-prune-dry-run') || {}).checked;
+    if (!dryRun && !(await showConfirm(`Remove every snapshot outside: ${pbsKeepSummary(keep)}? This can't be undone.`, 'Prune datastore'))) return;
+    btn.closest('.modal-overlay').remove();
+    runPbsJob({ action: 'prune', keep, dry_run: dryRun });
+}
+
+function openPbsPruneJobModal(id) {
+    const job = id ? (_pbsPruneJobs.find(j => j.id === id) || {}) : {};
+    showModal(`<div>
+        <div style="display:grid; grid-template-columns:1fr 1fr; gap:8px;">
+            <div class="form-group"><label style="font-size:12px;">Job ID</label>
+                <input type="text" id="pbs-job-id" class="form-control" value="${escapeAttr(job.id || '')}" ${id ? 'disabled' : ''} placeholder="nightly-prune"></div>
+            <div class="form-group"><label style="font-size:12px;">Schedule</label>
+                <input type="text" id="pbs-job-schedule" class="form-control" value="${escapeAttr(job.schedule || 'daily')}" placeholder="daily, sat 03:00"></div>
+            <div class="form-group"><label style="font-size:12px;">Namespace <span style="color:var(--text-muted);">(empty = root)</span></label>
+                <input type="text" id="pbs-job-ns" class="form-control" value="${escapeAttr(job.ns || '')}"></div>
+            <div class="form-group"><label style="font-size:12px;">Comment</label>
+                <input type="text" id="pbs-job-comment" class="form-control" value="${escapeAttr(job.comment || '')}"></div>
+        </div>
+        ${pbsKeepInputs('pbs-job', job.keep)}
+        <label style="display:flex; gap:8px; align-items:center; margin-top:8px;">
+            <input type="checkbox" id="pbs-job-disable" ${job.disable ? 'checked' : ''}> Disabled
+        </label>
+        <div style="text-align:right; margin-top:16px;">
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-primary" onclick="savePbsPruneJob(this)">Save</button>
+        </div>
+    </div>`, id ? `Edit prune job ${id}` : 'Add prune job', { noOk: true });
+}
+
+async function savePbsPruneJob(btn) {
+    const getVal = id => ((document.getElementById(id) || {}).value || '').trim();
+    const body = {
+        id: getVal('pbs-job-id'),
+        schedule: getVal('pbs-job-schedule'),
+        ns: getVal('pbs-job-ns'),
+        comment: getVal('pbs-job-comment'),
+        disable: !!(document.getElementById('pbs-job-disable') || {}).checked,
+        keep: readPbsKeepInputs('pbs-job'),
+    };
+    btn.disabled = true;
+    try {
+        const res = await fetch(apiUrl('/api/backups/pbs/prune-jobs'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        const data = await res.json().catch(() => ({}));
+        if (!res.ok || data.error) throw new Error(data.error || `HTTP ${res.status}`);
+        btn.closest('.modal-overlay').remove();
+        showToast('Prune job saved', 'success');
+        loadPbsDatastore();
+    } catch (e) {
+        btn.disabled = false;
+        showToast('Prune job: ' + e.message, 'error');
+    }
+}
+
+async function deletePbsPruneJob(id) {
+    if (!(await showConfirm(`Delete prune job ${id}? Snapshots stop being pruned on its schedule.`, 'Delete prune job'))) return;
+    try {
+        const res = await fetch(apiUrl(`/api/backups/pbs/prune-jobs/${encodeURIComponent(id)}`), { method: 'DELETE' });
+        const data = await res.json().catch(() => ({}));
+        if (!res.ok || data.error) throw new Error(data.error || `HTTP ${res.status}`);
+        showToast('Prune job deleted', 'success');
+        loadPbsDatastore();
+    } catch (e) {
+        showToast('Delete failed: ' + e.message, 'error');
+    }
+}
+
+async function runPbsJob(job) {
+    const label = { gc: 'Garbage collection', verify: 'Verify', prune: 'Prune', prune_job: 'Prune job' }[job.action] || job.action;
+    try {
+        const res = await fetch(apiUrl('/api/backups/pbs/jobs'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(job),
+        });
+        const data = await res.json().catch(() => ({}));
+        if (!res.ok || data.error) throw new Error(data.error || `HTTP ${res.status}`);
+        taskLog(`PBS ${label} started`);
+        watchPbsTask(data.upid, label);
+    } catch (e) {
+        showToast(`${label} failed: ${e.message}`, 'error');
+        taskLog(`PBS ${label}`, 'failed');
+    }
+}
+
+/// Show a PBS task's log, refreshing every 2s until the task stops.
+async function watchPbsTask(upid, label) {
+    showModal(`<div><div id="pbs-task-status" style="margin-bottom:8px;">Starting…</div>
+        <pre id="pbs-task-log" style="max-height:320px; overflow:auto; font-size:11px; background:var(--bg-tertiary); padding:8px; border-radius:6px; white-space:pre-wrap;"></pre></div>`,
+        label ? `PBS ${label}` : 'PBS task');
+    const statusEl = document.getElementById('pbs-task-status');
+    const logEl = document.getElementById('pbs-task-log');
+    while (document.body.contains(logEl)) {
+        try {
+            const res = await fetch(apiUrl(`/api/backups/pbs/tasks?upid=${encodeURIComponent(upid)}`));
+            const t = await res.json();
+            if (!res.ok || t.error) throw new Error(t.error || `HTTP ${res.status}`);
+            logEl.textContent = (t.log || []).join('\n');
+            logEl.scrollTop = logEl.scrollHeight;
+            if (t.status === 'stopped') {
+                const ok = t.exitstatus === 'OK';
+                statusEl.innerHTML = ok ? '<span style="color:#28a745;">✓ Finished</span>'
+                    : `<span style="color:#dc3545;">Finished: ${escapeHtml(t.exitstatus || 'unknown')}</span>`;
+                loadPbsDatastore();
+                return;
+            }
+            statusEl.textContent = 'Running…';
+        } catch (e) {
+            statusEl.textContent = 'Could not read task: ' + e.message;
+            return;
+        }
+        await new Promise(r => setTimeout(r, 2000));
+    }
+}
+
 function formatPbsSize(bytes) {
     if (!bytes || bytes === 0) return '\u2014';
     const units = ['B', 'KB', 'MB', 'GB', 'TB'];
@@ -35946,6 +39772,8 @@ async function loadPbsSnapshots() {
                 '<td style="font-size:12px;">' + timeStr + '</td>' +
                 '<td>' + formatPbsSize(size) + '</td>' +
                 '<td style="text-align:right;">' +
+                (btype !== 'vm' ? '<button class="btn btn-sm" onclick="openPbsFiles(\x27' + snapEsc + '\x27)" title="Restore individual files"' +
+                ' style="font-size:11px; padding:3px 10px; margin-right:4px; background:var(--bg-tertiary); color:var(--text-primary); border:1px solid var(--border);">📂 Files</button>' : '') +
                 '<button class="btn btn-sm btn-primary" onclick="restorePbsSnapshot(\x27' + snapEsc + '\x27, \x27' + btypeEsc + '\x27)"' +
                 ' style="font-size:11px; padding:3px 10px;">⬇️ Restore</button>' +
                 '</td>' +
@@ -35957,27 +39785,142 @@ async function loadPbsSnapshots() {
     }
 }
 
-// Restore a PBS snapshot — proper dialog (restore-as name + overwrite +
-// live progress) instead of the old bare confirm. The snapshot id is
-// "type/id/timestamp"; for a container the operator can restore it
-// under a different name.
-async function restorePbsSnapshot(snapshot, backupType) {
-    const parts = String(snapshot).split('/');
-    const snapType = parts[0] || backupType || '';
-    const snapId = parts[1] || '';
-    const isCt = snapType === 'ct';
-    const isVm = snapType === 'vm';
+// ─── File-level restore (backup catalog browsing) ───
+// Browse the files inside a backup — a tarball entry or a PBS file-level
+// snapshot — and restore just the picked ones into a host directory.
+// Picks survive moving between directories.
+let _catalog = null;
+
+function openBackupFiles(id, label) {
+    _openCatalog({ listUrl: `/api/backups/${encodeURIComponent(id)}/files`, restoreUrl: `/api/backups/${encodeURIComponent(id)}/files/restore`, body: {} },
+        'Files in backup' + (label ? ': ' + label : ''));
+}
+
+function openPbsFiles(snapshot) {
+    _openCatalog({ listUrl: '/api/backups/pbs/files', restoreUrl: '/api/backups/pbs/files/restore', body: { snapshot }, snapshot },
+        'Files in ' + snapshot);
+}
 
-    // Proxmox needs a target storage for the restored guest's disks —
-    // `pct restore --storage` (CT) / qm restore (VM). Without one the host
-    // falls back to a default that may not exist, which is the restore
-    // failure operators hit. Offer a picker sourced from the TARGET node's
-    // PVE storages (apiUrl proxies to the node being viewed).
+function _openCatalog(src, title) {
+    _catalog = Object.assign(src, { dir: '', picked: new Set() });
+    showModal([
+        '<div id="catalog-crumbs" style="font-size:12px;margin-bottom:8px;"></div>',
+        '<div id="catalog-list" style="max-height:320px;overflow-y:auto;border:1px solid var(--border);border-radius:6px;">',
+        '<div style="padding:12px;color:var(--text-muted);">Reading backup catalog… large archives can take a minute.</div></div>',
+        '<div style="margin-top:12px;display:flex;gap:8px;align-items:center;">',
+        '<input id="catalog-dest" class="form-control" placeholder="/root/restored" value="/root/restored" style="flex:1;">',
+        '<label style="font-size:12px;white-space:nowrap;"><input type="checkbox" id="catalog-overwrite"> Overwrite</label></div>',
+        '<div style="font-size:11px;color:var(--text-muted);margin-top:4px;">Each picked item is restored as &lt;folder&gt;/&lt;name&gt; on the node being viewed.</div>',
+        '<div style="margin-top:12px;display:flex;justify-content:space-between;align-items:center;">',
+        '<span id="catalog-count" style="font-size:12px;color:var(--text-muted);">Nothing picked</span>',
+        '<span><button class="btn btn-sm" onclick="this.closest(\'.modal-overlay\').remove()" style="margin-right:6px;">Close</button>',
+        '<button class="btn btn-sm btn-primary" id="catalog-restore-btn" onclick="restoreCatalogPicks(this)" disabled>Restore selected</button></span></div>',
+    ].join(''), title, { noOk: true });
+    loadCatalogDir('');
+}
+
+async function loadCatalogDir(dir) {
+    if (!_catalog) return;
+    const list = document.getElementById('catalog-list');
+    if (!list) return;
+    _catalog.dir = dir;
+    const qs = new URLSearchParams({ path: dir });
+    if (_catalog.snapshot) qs.set('snapshot', _catalog.snapshot);
+    try {
+        const res = await fetch(apiUrl(_catalog.listUrl + '?' + qs));
+        const data = await res.json();
+        if (!res.ok || data.error) throw new Error(data.error || res.status);
+        if (_catalog.dir !== dir) return; // navigated away meanwhile
+        _renderCatalogCrumbs(dir);
+        const up = dir ? `<tr><td></td><td colspan="3"><a href="#" onclick="loadCatalogDir(${escapeAttr(JSON.stringify(dir.split('/').slice(0, -1).join('/')))});return false;">⬆ ..</a></td></tr>` : '';
+        const rows = (data.entries || []).map(e => {
+            const name = e.path.split('/').pop();
+            const pathArg = escapeAttr(JSON.stringify(e.path));
+            const label = e.kind === 'dir'
+                ? `<a href="#" onclick="loadCatalogDir(${pathArg});return false;">📁 ${escapeHtml(name)}</a>`
+                : `${e.kind === 'link' ? '🔗' : '📄'} ${escapeHtml(name)}`;
+            return `<tr><td style="width:24px;"><input type="checkbox" ${_catalog.picked.has(e.path) ? 'checked' : ''} onchange="toggleCatalogPick(${pathArg}, this.checked)"></td>` +
+                `<td>${label}</td>` +
+                `<td style="font-size:11px;color:var(--text-muted);text-align:right;">${e.kind === 'file' ? formatBytes(e.size) : ''}</td>` +
+                `<td style="font-size:11px;color:var(--text-muted);white-space:nowrap;">${escapeHtml(e.mtime || '')}</td></tr>`;
+        }).join('');
+        list.innerHTML = `<table class="data-table" style="width:100%;font-size:12px;"><tbody>${up}${rows ||
+            '<tr><td colspan="4" style="color:var(--text-muted);">Empty folder</td></tr>'}</tbody></table>`;
+    } catch (e) {
+        list.innerHTML = `<div style="padding:12px;color:#fca5a5;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+function _renderCatalogCrumbs(dir) {
+    const el = document.getElementById('catalog-crumbs');
+    if (!el) return;
+    const parts = dir ? dir.split('/') : [];
+    const links = ['<a href="#" onclick="loadCatalogDir(\'\');return false;">/</a>'];
+    parts.forEach((p, i) => {
+        const sub = escapeAttr(JSON.stringify(parts.slice(0, i + 1).join('/')));
+        links.push(`<a href="#" onclick="loadCatalogDir(${sub});return false;">${escapeHtml(p)}</a>`);
+    });
+    el.innerHTML = links.join(' / ');
+}
+
+function toggleCatalogPick(path, on) {
+    if (!_catalog) return;
+    if (on) _catalog.picked.add(path); else _catalog.picked.delete(path);
+    const n = _catalog.picked.size;
+    document.getElementById('catalog-count').textContent = n ? `${n} item${n === 1 ? '' : 's'} picked` : 'Nothing picked';
+    document.getElementById('catalog-restore-btn').disabled = n === 0;
+}
+
+async function restoreCatalogPicks(btn) {
+    if (!_catalog || !_catalog.picked.size) return;
+    const dest = document.getElementById('catalog-dest').value.trim();
+    if (!dest.startsWith('/')) { showToast('Enter an absolute destination folder', 'error'); return; }
+    const body = Object.assign({}, _catalog.body, {
+        paths: Array.from(_catalog.picked),
+        dest,
+        overwrite: document.getElementById('catalog-overwrite').checked,
+    });
+    btn.disabled = true;
+    btn.textContent = 'Restoring…';
+    try {
+        const res = await fetch(apiUrl(_catalog.restoreUrl), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        const data = await res.json();
+        if (!res.ok || data.error) {
+            const pkg = parseMissingPackageError(data.error || '');
+            if (pkg) {
+                if (await offerPackageInstall(pkg)) return restoreCatalogPicks(btn);
+                return;
+            }
+            throw new Error(data.error || res.status);
+        }
+        showToast(data.message || 'Restored', 'success');
+        taskLog(data.message || 'File restore', 'completed');
+        btn.closest('.modal-overlay').remove();
+        _catalog = null;
+    } catch (e) {
+        showToast('Restore failed: ' + e.message, 'error');
+    } finally {
+        btn.disabled = false;
+        btn.textContent = 'Restore selected';
+    }
+}
+
+// Proxmox needs a target storage for the restored guest's disks —
+// `pct restore --storage` (CT) / qm restore (VM). Without one the host
+// falls back to a default that may not exist, which is the restore
+// failure operators hit. Offer a picker sourced from the TARGET node's
+// PVE storages — the node picked in the dialog, or the one being viewed.
+async function pbsRestoreStorageRow(targetNode, isCt, isVm) {
     let pveProxmox = false, pveStorages = [];
     try {
         // Short timeout so a slow/unreachable node never leaves the operator
         // staring at a blank dialog before the picker (or its absence) shows.
-        const r = await fetch(apiUrl('/api/storage/list'), { signal: AbortSignal.timeout(3000) });
+        const url = targetNode ? nodeApiUrl(targetNode, '/api/storage/list') : apiUrl('/api/storage/list');
+        const r = await fetch(url, { signal: AbortSignal.timeout(3000) });
         if (r.ok) {
             const d = await r.json();
             pveProxmox = !!d.proxmox;
@@ -35989,7 +39932,7 @@ async function restorePbsSnapshot(snapshot, backupType) {
     const wantContent = isCt ? 'rootdir' : 'images';
     let pveStores = pveStorages.filter(s => Array.isArray(s.content) && s.content.includes(wantContent));
     if (wantStorage && pveStores.length === 0) pveStores = pveStorages;  // content unknown — offer all
-    const storageRow = (wantStorage && pveStores.length > 0) ? `
+    return (wantStorage && pveStores.length > 0) ? `
         <div style="margin-bottom:14px;">
             <label for="pbs-restore-storage" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Target Proxmox storage</label>
             <select id="pbs-restore-storage" class="form-control">
@@ -35999,8 +39942,20 @@ async function restorePbsSnapshot(snapshot, backupType) {
                     return `<option value="${escapeHtml(String(s.id))}">${escapeHtml(String(s.id))}${st}${free}</option>`;
                 }).join('')}
             </select>
-            <div style="font-size:11px;color:var(--text-muted);margin-top:4px;">Where the restored ${isCt ? "container's root filesystem" : "VM's disks"} are placed on this node — passed to <code>${isCt ? 'pct restore --storage' : 'qm importdisk'}</code>.</div>
+            <div style="font-size:11px;color:var(--text-muted);margin-top:4px;">Where the restored ${isCt ? "container's root filesystem" : "VM's disks"} are placed on the target node — passed to <code>${isCt ? 'pct restore --storage' : 'qm importdisk'}</code>.</div>
         </div>` : '';
+}
+
+// Restore a PBS snapshot — proper dialog (restore-as name + overwrite +
+// live progress) instead of the old bare confirm. The snapshot id is
+// "type/id/timestamp"; for a container the operator can restore it
+// under a different name.
+async function restorePbsSnapshot(snapshot, backupType) {
+    const parts = String(snapshot).split('/');
+    const snapType = parts[0] || backupType || '';
+    const snapId = parts[1] || '';
+    const isCt = snapType === 'ct';
+    const isVm = snapType === 'vm';
 
     const stale = document.getElementById('pbs-restore-backdrop');
     if (stale) {
@@ -36008,13 +39963,45 @@ async function restorePbsSnapshot(snapshot, backupType) {
         stale.remove();
     }
 
-    const nameRow = isCt ? `
-        <div style="margin-bottom:14px;">
-            <label for="pbs-restore-name" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Restore as container name</label>
+    const storageRow = await pbsRestoreStorageRow('', isCt, isVm);
+    // Defaults to the node being viewed, which is where the request goes.
+    const viewedNode = allNodes.find(n => n.id === currentNodeId) || allNodes.find(n => n.is_self) || {};
+    const nodeOptions = getAllWolfStackNodes().filter(n => n.online || n.id === viewedNode.id).map(n =>
+        `<option value="${escapeHtml(n.id)}"${n.id === viewedNode.id ? ' selected' : ''}>${escapeHtml(n.hostname || n.id)}${n.is_self ? ' (this node)' : ''}</option>`).join('');
+    const nodeRow = `
+        <div style="margin-bottom:14px;display:grid;grid-template-columns:1fr 1fr;gap:10px;">
+            <div>
+                <label for="pbs-restore-node" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Restore on node</label>
+                <select id="pbs-restore-node" class="form-control">${nodeOptions}</select>
+            </div>
+            <div>
+                <label for="pbs-restore-as" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Restore as</label>
+                <select id="pbs-restore-as" class="form-control">
+                    <option value="auto">Original type</option>
+                    <option value="lxc">New LXC container (file-level snapshot)</option>
+                    <option value="vm_disk">New VM from disk image</option>
+                </select>
+            </div>
+        </div>`;
+    const vmRow = `
+        <div id="pbs-restore-vm-row" style="display:none;margin-bottom:14px;grid-template-columns:1fr 1fr;gap:10px;">
+            <div>
+                <label for="pbs-restore-cpus" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">vCPUs</label>
+                <input type="number" id="pbs-restore-cpus" class="form-control" min="1" value="2">
+            </div>
+            <div>
+                <label for="pbs-restore-mem" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Memory (MB)</label>
+                <input type="number" id="pbs-restore-mem" class="form-control" min="256" step="256" value="2048">
+            </div>
+        </div>`;
+
+    const nameRow = `
+        <div id="pbs-restore-name-row" style="margin-bottom:14px;${isCt ? '' : 'display:none;'}">
+            <label for="pbs-restore-name" id="pbs-restore-name-label" style="display:block;font-size:12px;color:var(--text-muted);margin-bottom:4px;">Restore as container name</label>
             <input type="text" id="pbs-restore-name" class="form-control" value="${escapeHtml(String(snapId))}" placeholder="e.g. web-01">
             <div style="font-size:11px;color:var(--text-muted);margin-top:4px;">Defaults to the snapshot's original name. Change it to restore as a separate copy.</div>
             <div id="pbs-restore-name-warn" role="status" aria-live="polite" style="display:none;font-size:11px;color:var(--warning,#f59e0b);margin-top:4px;"></div>
-        </div>` : '';
+        </div>`;
 
     const backdrop = document.createElement('div');
     backdrop.id = 'pbs-restore-backdrop';
@@ -36024,8 +40011,10 @@ async function restorePbsSnapshot(snapshot, backupType) {
              style="background:var(--bg-primary);border:1px solid var(--border);border-radius:12px;padding:20px;width:480px;max-width:92vw;max-height:85vh;overflow-y:auto;box-shadow:0 20px 60px rgba(0,0,0,0.4);">
             <h3 id="pbs-restore-title" style="margin:0 0 4px;font-size:16px;">Restore PBS snapshot</h3>
             <div style="font-size:12px;color:var(--text-muted);margin-bottom:14px;font-family:monospace;">${escapeHtml(String(snapshot))}</div>
+            ${nodeRow}
             ${nameRow}
-            ${storageRow}
+            ${vmRow}
+            <div id="pbs-restore-storage-wrap">${storageRow}</div>
             <label style="display:flex;align-items:flex-start;gap:8px;font-size:13px;margin-bottom:14px;cursor:pointer;">
                 <input type="checkbox" id="pbs-restore-overwrite" style="margin-top:2px;">
                 <span>Replace the target if it already exists<br><span style="color:var(--text-muted);font-size:11px;">Any existing copy is stopped and replaced.</span></span>
@@ -36042,8 +40031,24 @@ async function restorePbsSnapshot(snapshot, backupType) {
 
     const cancelBtn = backdrop.querySelector('#pbs-restore-cancel');
     const goBtn = backdrop.querySelector('#pbs-restore-go');
+    const nodeSel = backdrop.querySelector('#pbs-restore-node');
+    const asSel = backdrop.querySelector('#pbs-restore-as');
     let running = false;
 
+    // The name row is for containers and for anything restored as a new
+    // guest; the VM sizing row only for a disk-image restore.
+    asSel.onchange = () => {
+        const as = asSel.value;
+        backdrop.querySelector('#pbs-restore-name-row').style.display = (isCt || as !== 'auto') ? '' : 'none';
+        backdrop.querySelector('#pbs-restore-name-label').textContent = as === 'vm_disk' ? 'Restore as VM name' : 'Restore as container name';
+        backdrop.querySelector('#pbs-restore-vm-row').style.display = as === 'vm_disk' ? 'grid' : 'none';
+    };
+    // Storage pickers belong to the node doing the restore.
+    nodeSel.onchange = async () => {
+        const wrap = backdrop.querySelector('#pbs-restore-storage-wrap');
+        wrap.innerHTML = await pbsRestoreStorageRow(nodeSel.value === viewedNode.id ? '' : nodeSel.value, isCt, isVm);
+    };
+
     function closeDialog() {
         if (running) return;  // a restore in flight can't be aborted client-side
         document.removeEventListener('keydown', onKey);
@@ -36069,9 +40074,15 @@ async function restorePbsSnapshot(snapshot, backupType) {
         const overwrite = backdrop.querySelector('#pbs-restore-overwrite').checked;
         const nameEl = backdrop.querySelector('#pbs-restore-name');
         const storageSel = backdrop.querySelector('#pbs-restore-storage');
+        const restoreAs = asSel.value;
+        const vm = restoreAs === 'vm_disk' ? {
+            cpus: parseInt(backdrop.querySelector('#pbs-restore-cpus').value, 10) || 0,
+            memory_mb: parseInt(backdrop.querySelector('#pbs-restore-mem').value, 10) || 0,
+        } : undefined;
         const ok = await _runPbsRestore(snapshot, snapType, overwrite,
-                                        nameEl ? nameEl.value.trim() : '',
-                                        storageSel ? storageSel.value : '', progressEl, resultEl);
+                                        nameEl && nameEl.offsetParent !== null ? nameEl.value.trim() : '',
+                                        storageSel ? storageSel.value : '', progressEl, resultEl,
+                                        { targetNode: nodeSel.value === viewedNode.id ? '' : nodeSel.value, restoreAs, vm });
         running = false;
         cancelBtn.disabled = false;
         if (ok) {
@@ -36091,8 +40102,12 @@ async function restorePbsSnapshot(snapshot, backupType) {
 }
 
 // POST a PBS restore and poll its progress into the modal. Resolves
-// true on success, false on failure.
-async function _runPbsRestore(snapshot, snapType, overwrite, newName, storage, progressEl, resultEl) {
+// true on success, false on failure. `opts.targetNode` restores on another
+// cluster node (the request is routed there server-side and progress is
+// read from that node); `opts.restoreAs` / `opts.vm` pick what it becomes.
+async function _runPbsRestore(snapshot, snapType, overwrite, newName, storage, progressEl, resultEl, opts) {
+    opts = opts || {};
+    const targetNode = opts.targetNode || '';
     let targetDir = '/var/lib/wolfstack/restored';
     if (snapType === 'ct') targetDir = '/var/lib/lxc';
     else if (snapType === 'vm') targetDir = '/var/lib/wolfstack/vms';
@@ -36119,6 +40134,9 @@ async function _runPbsRestore(snapshot, snapType, overwrite, newName, storage, p
                 overwrite: !!overwrite,
                 new_name: newName || '',
                 storage: storage || '',
+                restore_as: opts.restoreAs || 'auto',
+                vm: opts.vm,
+                target_node: targetNode || undefined,
             }),
         });
         const data = await res.json().catch(() => ({}));
@@ -36137,7 +40155,9 @@ async function _runPbsRestore(snapshot, snapType, overwrite, newName, storage, p
                     return;
                 }
                 try {
-                    const pres = await fetch(apiUrl('/api/backups/pbs/restore/progress'));
+                    const pres = await fetch(targetNode
+                        ? nodeApiUrl(targetNode, '/api/backups/pbs/restore/progress')
+                        : apiUrl('/api/backups/pbs/restore/progress'));
                     if (!pres.ok) return;
                     const p = await pres.json();
                     if (p.active) {
@@ -38427,7 +42447,12 @@ async function pollAiChatResult(jobId) {
         if (body.status === 'unknown') {
             throw new Error('AI request expired before it finished');
         }
-        // status === 'running' → keep polling
+        // status === 'running' → keep polling. A streaming local model
+        // reports the reply so far — render it in place of the typing dots.
+        if (body.partial) {
+            var typing = document.getElementById('ai-typing');
+            if (typing) typing.innerHTML = formatAiResponse(body.partial);
+        }
     }
     throw new Error('AI request timed out after 16 minutes');
 }
@@ -38475,6 +42500,17 @@ function renderAiAction(action) {
     // Sanitise action ID for safe embedding in HTML attributes and JS strings
     var safeId = action.id.replace(/[^a-zA-Z0-9\-]/g, '');
 
+    // Structured remediations run server-side on approve — no terminal, no target picker.
+    if (action.remediation) {
+        html += '<div style="display:flex;gap:8px;align-items:center;flex-wrap:wrap;" id="ai-action-btns-' + safeId + '">'
+            + '<button onclick="approveAiRemediation(\'' + safeId + '\')" style="padding:6px 16px;border-radius:6px;border:none;background:#22c55e;color:#fff;font-weight:700;font-size:12px;cursor:pointer;">Approve &amp; Run</button>'
+            + '<button onclick="dismissAiAction(\'' + safeId + '\')" style="padding:6px 16px;border-radius:6px;border:1px solid var(--border);background:transparent;color:var(--text-muted);font-weight:600;font-size:12px;cursor:pointer;">Dismiss</button>'
+            + '</div>';
+        html += '<div id="ai-action-result-' + safeId + '" style="display:none;margin-top:8px;"></div>';
+        card.innerHTML = html;
+        return card;
+    }
+
     // Build target picker — host + any containers/VMs on the current node
     var targetOptions = '<option value="host">Host node</option>';
     if (typeof _aiVisibleInfra !== 'undefined' && _aiVisibleInfra) {
@@ -38555,6 +42591,29 @@ async function approveAiAction(actionId) {
     if (btns) btns.innerHTML = '<span style="color:#22c55e;font-size:12px;font-weight:600;">Opened terminal on ' + escapeHtml(targetLabel) + '</span>';
 }
 
+async function approveAiRemediation(actionId) {
+    var btns = document.getElementById('ai-action-btns-' + actionId);
+    var resultEl = document.getElementById('ai-action-result-' + actionId);
+    if (btns) btns.innerHTML = '<span style="color:var(--text-muted);font-size:12px;">Running…</span>';
+    try {
+        var resp = await fetch(aiNodeUrl('/api/ai/action'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ action_id: actionId, approved: true })
+        });
+        var data = await resp.json();
+        if (data.error) {
+            if (btns) btns.innerHTML = '<span style="color:var(--danger);font-size:12px;font-weight:600;">Failed</span>';
+            if (resultEl) { resultEl.style.display = 'block'; resultEl.innerHTML = '<pre style="background:var(--bg-primary);padding:8px 12px;border-radius:8px;font-size:12px;margin:0;color:var(--danger);white-space:pre-wrap;">' + escapeHtml(data.error) + '</pre>'; }
+            return;
+        }
+        if (btns) btns.innerHTML = '<span style="color:#22c55e;font-size:12px;font-weight:600;">Done</span>';
+        if (resultEl && data.output) { resultEl.style.display = 'block'; resultEl.innerHTML = '<pre style="background:var(--bg-primary);padding:8px 12px;border-radius:8px;font-size:12px;margin:0;color:var(--text);white-space:pre-wrap;">' + escapeHtml(data.output) + '</pre>'; }
+    } catch (e) {
+        if (btns) btns.innerHTML = '<span style="color:var(--danger);font-size:12px;">Failed to approve: ' + escapeHtml(e.message) + '</span>';
+    }
+}
+
 async function dismissAiAction(actionId) {
     var btns = document.getElementById('ai-action-btns-' + actionId);
     if (btns) btns.innerHTML = '<span style="color:var(--text-muted);font-size:12px;">Dismissed</span>';
@@ -38595,6 +42654,8 @@ async function loadAiConfig() {
         if ((el = document.getElementById('ai-cloudflare-key'))) el.value = cfg.has_cloudflare_key ? (cfg.cloudflare_api_key || '') : '';
         if ((el = document.getElementById('ai-local-url'))) el.value = cfg.local_url || '';
         if ((el = document.getElementById('ai-local-key'))) el.value = cfg.has_local_url ? (cfg.local_api_key || '') : '';
+        if ((el = document.getElementById('ai-local-stream'))) el.checked = !!cfg.local_stream;
+        if ((el = document.getElementById('ai-local-no-egress'))) el.checked = !!cfg.local_no_egress;
         // Show/hide provider-specific fields
         const localFields = document.getElementById('ai-local-fields');
         if (localFields) localFields.style.display = (cfg.provider === 'local') ? '' : 'none';
@@ -38620,6 +42681,7 @@ async function loadAiConfig() {
             const _numEl = document.getElementById('ai-agent-max-tool-calls');
             if (_numEl) _numEl.disabled = !_limEl.checked;
         }
+        if ((el = document.getElementById('ai-remediation-enabled'))) el.checked = !!cfg.remediation_actions_enabled;
         // Bind (once) a model-list refresh to each provider's key field so the
         // dropdown loads as soon as a key is entered — no Save required. `change`
         // fires on blur after editing, so we don't hammer the endpoint per
@@ -38818,6 +42880,9 @@ function _buildAiConfigPayload(model) {
         })(),
         agent_max_tool_calls: Math.min(100, Math.max(1, parseInt((document.getElementById('ai-agent-max-tool-calls') || {}).value) || 6)),
         agent_tool_call_limit_enabled: !!(document.getElementById('ai-agent-limit-enabled') || {}).checked,
+        remediation_actions_enabled: !!(document.getElementById('ai-remediation-enabled') || {}).checked,
+        local_stream: !!(document.getElementById('ai-local-stream') || {}).checked,
+        local_no_egress: !!(document.getElementById('ai-local-no-egress') || {}).checked,
     };
 }
 
@@ -39327,6 +43392,61 @@ async function sendTestEmail() {
     }
 }
 
+// SMTP connection check (DNS → TCP → TLS → AUTH), alert outbox and
+// recent send failures for the node whose AI settings are open.
+async function showMailDiagnostics() {
+    showModal('<div id="mail-diag-body"><div class="spinner-sm"></div> Checking SMTP relay...</div>', 'Email Diagnostics');
+    const body = document.getElementById('mail-diag-body');
+    try {
+        const resp = await fetch(aiNodeUrl('/api/mail/diagnostics'));
+        const data = await resp.json();
+        if (data.error) { body.textContent = data.error; return; }
+        const fmt = ts => new Date(ts * 1000).toLocaleString();
+        const steps = (data.steps || []).map(s =>
+            `<div style="display:flex; gap:8px;"><span style="color:${s.ok ? '#22c55e' : '#ef4444'}; width:14px;">${s.ok ? '✓' : '✗'}</span><b style="width:50px;">${escapeHtml(s.step)}</b><span style="flex:1;">${escapeHtml(s.detail)}</span></div>`
+        ).join('');
+        const queued = (data.queued || []);
+        const failures = (data.failures || []).slice(0, 15);
+        body.innerHTML = `${steps}
+            <div style="margin-top:14px; display:flex; align-items:center; gap:8px;">
+                <b>Outbox</b><span style="color:var(--text-muted);">${queued.length} queued — retried every 5 minutes for up to 24h</span>
+                ${queued.length ? '<button class="btn btn-sm" onclick="retryMailOutbox()">Retry now</button>' : ''}
+            </div>
+            ${queued.map(q => `<div style="font-size:12px; border-top:1px solid var(--border); padding:4px 0; display:flex; gap:8px;">
+                <span style="flex:1;">${escapeHtml(q.subject)}<br><span style="color:var(--text-muted);">queued ${fmt(q.queued_at)} · ${q.attempts} attempt(s) · ${escapeHtml(q.last_error)}</span></span>
+                <button class="btn btn-sm" data-id="${escapeHtml(q.id)}" onclick="discardQueuedMail(this.dataset.id)">Discard</button>
+            </div>`).join('')}
+            <div style="margin-top:14px;"><b>Recent failures</b></div>
+            ${failures.length ? failures.map(f => `<div style="font-size:12px; border-top:1px solid var(--border); padding:4px 0;">
+                ${escapeHtml(f.subject)} <span style="color:var(--text-muted);">— ${fmt(f.at)} · ${escapeHtml(f.outcome)}</span><br>
+                <span style="color:#ef4444;">${escapeHtml(f.error)}</span>
+            </div>`).join('') : '<div style="font-size:12px; color:var(--text-muted);">None</div>'}`;
+    } catch (e) {
+        body.textContent = 'Diagnostics failed: ' + e.message;
+    }
+}
+
+async function retryMailOutbox() {
+    try {
+        const resp = await fetch(aiNodeUrl('/api/mail/outbox/retry'), { method: 'POST' });
+        const data = await resp.json();
+        if (data.error) { showToast(data.error, 'error'); return; }
+        showToast(`Sent ${data.sent}, ${data.remaining} still queued`, data.remaining ? 'warning' : 'success');
+    } catch (e) {
+        showToast('Retry failed: ' + e.message, 'error');
+    }
+    document.getElementById('mail-diag-body')?.closest('.modal-overlay')?.remove();
+    showMailDiagnostics();
+}
+
+async function discardQueuedMail(id) {
+    try {
+        await fetch(aiNodeUrl('/api/mail/outbox/' + encodeURIComponent(id)), { method: 'DELETE' });
+    } catch (e) { /* refreshed below */ }
+    document.getElementById('mail-diag-body')?.closest('.modal-overlay')?.remove();
+    showMailDiagnostics();
+}
+
 function onAiProviderChange() {
     var provider = (document.getElementById('ai-provider') || {}).value || 'claude';
     // Show/hide provider-specific fields
@@ -39371,15 +43491,16 @@ window.onAgentLimitToggle = onAgentLimitToggle;
 
 // ─── Config Export / Import ───
 
-async function exportConfig() {
+async function exportConfig(format) {
+    const yaml = format === 'yaml';
     try {
-        const res = await fetch('/api/config/export', { credentials: 'include' });
+        const res = await fetch('/api/config/export' + (yaml ? '?format=yaml' : ''), { credentials: 'include' });
         if (!res.ok) throw new Error('Export failed: ' + res.status);
         const blob = await res.blob();
         const url = URL.createObjectURL(blob);
         const a = document.createElement('a');
         a.href = url;
-        a.download = 'wolfstack-config.json';
+        a.download = yaml ? 'wolfstack-config.yaml' : 'wolfstack-config.json';
         document.body.appendChild(a);
         a.click();
         document.body.removeChild(a);
@@ -39390,12 +43511,47 @@ async function exportConfig() {
     }
 }
 
+// Declarative YAML: dry-run first so the operator sees exactly what the
+// document changes, then apply.
+async function importConfigDocument(text) {
+    const post = (dryRun) => fetch('/api/config/import?format=yaml' + (dryRun ? '&dry_run=true' : ''), {
+        method: 'POST',
+        credentials: 'include',
+        headers: { 'Content-Type': 'application/yaml' },
+        body: text,
+    }).then(async res => ({ ok: res.ok, data: await res.json() }));
+
+    const plan = await post(true);
+    if (!plan.ok) throw new Error(plan.data.error || 'Invalid document');
+    const pending = (plan.data.changes || []).filter(c => c.action !== 'unchanged');
+    if (!pending.length && !(plan.data.errors || []).length) {
+        showToast(plan.data.message || 'Already up to date', 'info');
+        return;
+    }
+    const lines = pending.slice(0, 25).map(c => '  ' + c.action + ' ' + c.section + ': ' + c.id);
+    if (pending.length > 25) lines.push('  … and ' + (pending.length - 25) + ' more');
+    const errs = (plan.data.errors || []).map(e => '  ! ' + e);
+    if (!(await showConfirm('Apply this config document?\n\n' + lines.concat(errs).join('\n')))) return;
+
+    const result = await post(false);
+    if (!result.ok) throw new Error(result.data.error || 'Import failed');
+    const errors = result.data.errors || [];
+    showToast((result.data.message || 'Applied') + (errors.length ? ' — errors: ' + errors.join('; ') : ''),
+        errors.length ? 'warning' : 'success');
+    setTimeout(() => fetchNodes(), 1000);
+}
+
 async function importConfigFile(input) {
     const file = input.files[0];
     if (!file) return;
 
     try {
         const text = await file.text();
+        if (/\.ya?ml$/i.test(file.name)) {
+            await importConfigDocument(text);
+            input.value = '';
+            return;
+        }
         const json = JSON.parse(text);
 
         if (!(await showConfirm('Import config from "' + (json.exported_from || 'unknown') +
@@ -39516,6 +43672,51 @@ async function restoreConfigBackup(name) {
     }
 }
 
+// ─── Full system config (disaster recovery) ───
+
+function downloadSystemConfig() {
+    const a = document.createElement('a');
+    a.href = '/api/config/system-backup';
+    document.body.appendChild(a);
+    a.click();
+    document.body.removeChild(a);
+}
+
+async function restoreSystemConfigFile(input) {
+    const file = input.files[0];
+    if (!file) return;
+    const mode = document.getElementById('system-config-restore-mode')?.value || 'replace';
+    const what = mode === 'replace'
+        ? 'This node will TAKE OVER the backed-up node\'s identity, TLS certificate and cluster membership. ' +
+          'Only do this when the original node is gone for good.'
+        : 'Users, settings and schedules are restored; this node keeps its own identity, TLS and networking.';
+    if (!(await showConfirm('Restore system config from "' + file.name + '"?\n\n' + what +
+        '\n\nExisting config on this server is overwritten and WolfStack restarts.'))) {
+        input.value = '';
+        return;
+    }
+    try {
+        const res = await fetch('/api/config/system-restore?mode=' + encodeURIComponent(mode), {
+            method: 'POST',
+            credentials: 'include',
+            headers: { 'Content-Type': 'application/gzip' },
+            body: file,
+        });
+        const result = await res.json();
+        if (res.ok) {
+            showToast(result.message || 'System config restored — restarting', 'success');
+            // WolfStack restarts itself; reload once it's back (users and the
+            // session secret may have changed, so expect a login page).
+            setTimeout(() => location.reload(), 8000);
+        } else {
+            showToast(result.error || 'Restore failed', 'error');
+        }
+    } catch (e) {
+        showToast('Restore failed: ' + e.message, 'error');
+    }
+    input.value = '';
+}
+
 async function deleteConfigBackup(name) {
     if (!(await showConfirm('Delete this backup permanently?'))) return;
     try {
@@ -39793,6 +43994,14 @@ async function mysqlLoadDatabases() {
 
         // Render tree
         let html = '';
+        if (mysqlDbType === 'mysql') {
+            const btn = 'background:var(--bg-tertiary); border:1px solid var(--border); color:var(--text-secondary); padding:3px 8px; border-radius:5px; cursor:pointer; font-size:11px;';
+            html += `<div style="display:flex; gap:6px; padding:6px 14px 8px; border-bottom:1px solid var(--border); margin-bottom:4px;">
+                <button onclick="mysqlShowOverview()" style="${btn}" title="Sizes and table counts">Overview</button>
+                <button onclick="mysqlShowUsers()" style="${btn}" title="Accounts and grants">Users</button>
+                <button onclick="mysqlShowSlowLog()" style="${btn}" title="Slow query log">Slow log</button>
+            </div>`;
+        }
         for (const db of mysqlDatabases) {
             const isSystem = ['information_schema', 'performance_schema', 'mysql', 'sys'].includes(db);
             html += `<div class="mysql-db-node" data-db="${db}">
@@ -39808,7 +44017,7 @@ async function mysqlLoadDatabases() {
                 <div id="mysql-tables-${db}" style="display:none; padding-left:28px;"></div>
             </div>`;
         }
-        tree.innerHTML = html || '<div style="padding:16px; text-align:center; color:var(--text-muted); font-size:12px;">No databases found</div>';
+        tree.innerHTML = mysqlDatabases.length ? html : html + '<div style="padding:16px; text-align:center; color:var(--text-muted); font-size:12px;">No databases found</div>';
 
     } catch (e) {
         tree.innerHTML = `<div style="padding:16px; text-align:center; color:#e74c3c; font-size:12px;">Error: ${e.message}</div>`;
@@ -41194,6 +45403,180 @@ async function mysqlDoDump(db, includeData, modal) {
     }
 }
 
+// ─── MySQL server administration (overview, users & grants, slow log) ───
+
+async function mysqlAdminPost(path, extra) {
+    const baseUrl = getNodeApiBase(mysqlConnectedNodeId);
+    const resp = await fetch(`${baseUrl}/mysql/${path}`, {
+        method: 'POST',
+        credentials: 'include',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ ...mysqlCreds, ...(extra || {}) }),
+    });
+    let data;
+    try { data = await resp.json(); } catch { throw new Error('HTTP ' + resp.status); }
+    if (!resp.ok || data.error) throw new Error(data.error || 'HTTP ' + resp.status);
+    return data;
+}
+
+async function mysqlShowOverview() {
+    if (!mysqlCreds) return;
+    showModal('<div id="mysql-overview-body"><div class="spinner-sm"></div> Loading...</div>', 'Databases');
+    const body = document.getElementById('mysql-overview-body');
+    try {
+        const data = await mysqlAdminPost('overview');
+        const dbs = data.databases || [];
+        const total = dbs.reduce((s, d) => s + (d.size_bytes || 0), 0);
+        body.innerHTML = `<table style="width:100%; border-collapse:collapse; font-size:12px;">
+            <tr style="color:var(--text-muted); text-align:left;"><th style="padding:4px;">Database</th><th style="padding:4px; text-align:right;">Tables</th><th style="padding:4px; text-align:right;">Size</th><th style="padding:4px;">Collation</th></tr>
+            ${dbs.map(d => `<tr style="border-top:1px solid var(--border);${d.system ? ' opacity:0.6;' : ''}">
+                <td style="padding:4px;">${escapeHtml(d.name)}</td>
+                <td style="padding:4px; text-align:right;">${d.tables}</td>
+                <td style="padding:4px; text-align:right;">${formatBytes(d.size_bytes || 0)}</td>
+                <td style="padding:4px; color:var(--text-muted);">${escapeHtml(d.collation || '')}</td>
+            </tr>`).join('')}
+            <tr style="border-top:1px solid var(--border); font-weight:600;"><td style="padding:4px;">Total</td><td></td><td style="padding:4px; text-align:right;">${formatBytes(total)}</td><td></td></tr>
+        </table>`;
+    } catch (e) {
+        body.innerHTML = `<div style="color:#e74c3c;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+async function mysqlShowUsers() {
+    if (!mysqlCreds) return;
+    if (!document.getElementById('mysql-users-body')) {
+        showModal('<div id="mysql-users-body"><div class="spinner-sm"></div> Loading...</div>', 'MySQL Users');
+    }
+    const body = document.getElementById('mysql-users-body');
+    const input = 'background:var(--bg-input); border:1px solid var(--border); color:var(--text-primary); padding:5px 8px; border-radius:5px; font-size:12px;';
+    try {
+        const data = await mysqlAdminPost('users', { action: 'list' });
+        const users = data.users || [];
+        body.innerHTML = `<div style="display:flex; gap:6px; margin-bottom:10px; flex-wrap:wrap;">
+                <input id="mysql-new-user" placeholder="user" style="${input} width:110px;">
+                <input id="mysql-new-host" placeholder="host (%)" style="${input} width:100px;">
+                <input id="mysql-new-pass" type="password" placeholder="password" style="${input} width:120px;">
+                <button class="btn btn-sm btn-primary" onclick="mysqlCreateUser()">Create</button>
+            </div>
+            <table style="width:100%; border-collapse:collapse; font-size:12px;">
+            ${users.map(u => `<tr style="border-top:1px solid var(--border);" data-user="${escapeHtml(u.user)}" data-host="${escapeHtml(u.host)}">
+                <td style="padding:4px;">${escapeHtml(u.user)}<span style="color:var(--text-muted);">@${escapeHtml(u.host)}</span></td>
+                <td style="padding:4px; text-align:right; white-space:nowrap;">
+                    <button class="btn btn-sm" onclick="mysqlUserGrants(this.closest('tr'))">Grants</button>
+                    <button class="btn btn-sm" onclick="mysqlUserPassword(this.closest('tr'))">Password</button>
+                    <button class="btn btn-sm btn-danger" onclick="mysqlUserDrop(this.closest('tr'))">Drop</button>
+                </td>
+            </tr>`).join('')}
+            </table>
+            <div id="mysql-grants-panel" style="margin-top:12px;"></div>`;
+    } catch (e) {
+        body.innerHTML = `<div style="color:#e74c3c;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+async function mysqlUserAction(action, extra) {
+    try {
+        const data = await mysqlAdminPost('users', { action, ...extra });
+        showToast(data.message || 'Done', 'success');
+        return true;
+    } catch (e) {
+        showToast(e.message, 'error');
+        return false;
+    }
+}
+
+async function mysqlCreateUser() {
+    const account_user = document.getElementById('mysql-new-user').value.trim();
+    const account_host = document.getElementById('mysql-new-host').value.trim() || '%';
+    const account_password = document.getElementById('mysql-new-pass').value;
+    if (!account_user) { showToast('Enter a user name', 'error'); return; }
+    if (await mysqlUserAction('create', { account_user, account_host, account_password })) mysqlShowUsers();
+}
+
+async function mysqlUserPassword(row) {
+    const account_password = prompt(`New password for ${row.dataset.user}@${row.dataset.host}:`);
+    if (account_password === null) return;
+    await mysqlUserAction('password', { account_user: row.dataset.user, account_host: row.dataset.host, account_password });
+}
+
+async function mysqlUserDrop(row) {
+    if (!confirm(`Drop user ${row.dataset.user}@${row.dataset.host}? This cannot be undone.`)) return;
+    if (await mysqlUserAction('drop', { account_user: row.dataset.user, account_host: row.dataset.host })) mysqlShowUsers();
+}
+
+const MYSQL_COMMON_PRIVILEGES = ['ALL PRIVILEGES', 'SELECT', 'INSERT', 'UPDATE', 'DELETE', 'CREATE', 'DROP', 'ALTER', 'INDEX', 'EXECUTE'];
+
+async function mysqlUserGrants(row) {
+    const panel = document.getElementById('mysql-grants-panel');
+    panel.dataset.user = row.dataset.user;
+    panel.dataset.host = row.dataset.host;
+    panel.innerHTML = '<div class="spinner-sm"></div>';
+    try {
+        const data = await mysqlAdminPost('users', { action: 'grants', account_user: row.dataset.user, account_host: row.dataset.host });
+        const dbOptions = ['*', ...mysqlDatabases].map(d => `<option value="${escapeHtml(d)}">${d === '*' ? 'All databases (*.*)' : escapeHtml(d)}</option>`).join('');
+        panel.innerHTML = `<div style="font-weight:600; margin-bottom:6px;">Grants for ${escapeHtml(row.dataset.user)}@${escapeHtml(row.dataset.host)}</div>
+            <pre style="background:var(--bg-input); padding:8px; border-radius:6px; font-size:11px; white-space:pre-wrap; margin:0 0 8px;">${escapeHtml((data.grants || []).join('\n'))}</pre>
+            <div style="display:flex; flex-wrap:wrap; gap:4px 10px; font-size:11px; margin-bottom:8px;">
+                ${MYSQL_COMMON_PRIVILEGES.map(p => `<label><input type="checkbox" class="mysql-priv-cb" value="${p}"> ${p}</label>`).join('')}
+            </div>
+            <div style="display:flex; gap:6px; align-items:center;">
+                <select id="mysql-grant-db" style="font-size:12px;">${dbOptions}</select>
+                <button class="btn btn-sm btn-primary" onclick="mysqlChangeGrants(false)">Grant</button>
+                <button class="btn btn-sm btn-danger" onclick="mysqlChangeGrants(true)">Revoke</button>
+            </div>`;
+    } catch (e) {
+        panel.innerHTML = `<div style="color:#e74c3c;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+async function mysqlChangeGrants(revoke) {
+    const panel = document.getElementById('mysql-grants-panel');
+    const privileges = [...panel.querySelectorAll('.mysql-priv-cb:checked')].map(cb => cb.value);
+    if (!privileges.length) { showToast('Select at least one privilege', 'error'); return; }
+    const ok = await mysqlUserAction(revoke ? 'revoke' : 'grant', {
+        account_user: panel.dataset.user,
+        account_host: panel.dataset.host,
+        privileges,
+        on_database: document.getElementById('mysql-grant-db').value,
+    });
+    if (ok) mysqlUserGrants(panel);
+}
+
+async function mysqlShowSlowLog(set) {
+    if (!mysqlCreds) return;
+    if (!document.getElementById('mysql-slowlog-body')) {
+        showModal('<div id="mysql-slowlog-body"><div class="spinner-sm"></div> Loading...</div>', 'Slow Query Log');
+    }
+    const body = document.getElementById('mysql-slowlog-body');
+    try {
+        const data = await mysqlAdminPost('slow-log', set ? { set } : {});
+        const s = data.settings || {};
+        const entries = data.entries || [];
+        body.innerHTML = `<div style="display:flex; gap:10px; align-items:center; flex-wrap:wrap; font-size:12px; margin-bottom:6px;">
+                <label><input type="checkbox" id="mysql-slow-enabled"${s.enabled ? ' checked' : ''}> Enabled</label>
+                <label>Threshold <input type="number" id="mysql-slow-threshold" min="0" step="0.1" value="${s.long_query_time}" style="width:60px;"> s</label>
+                <label><input type="checkbox" id="mysql-slow-table"${s.to_table ? ' checked' : ''}> Log to table</label>
+                <button class="btn btn-sm btn-primary" onclick="mysqlApplySlowLog()">Apply</button>
+            </div>
+            <div style="font-size:11px; color:var(--text-muted); margin-bottom:10px;">Output: ${escapeHtml(s.log_output || '')}. Changes use SET GLOBAL and are lost when the server restarts — persist them in my.cnf.</div>
+            ${data.entries_error ? `<div style="color:#f59e0b; font-size:12px;">${escapeHtml(data.entries_error)}</div>` : ''}
+            ${entries.length ? entries.map(e => `<div style="border-top:1px solid var(--border); padding:6px 0;">
+                <div style="font-size:11px; color:var(--text-muted);">${escapeHtml(e.time || '')} · ${escapeHtml(e.user_host || '')}${e.db ? ' · ' + escapeHtml(e.db) : ''} · <b>${(e.query_time ?? 0).toFixed(3)}s</b> · ${e.rows_examined ?? '?'} examined / ${e.rows_sent ?? '?'} sent</div>
+                <pre style="margin:4px 0 0; font-size:11px; white-space:pre-wrap;">${escapeHtml(e.sql || '')}</pre>
+            </div>`).join('') : (data.entries_error ? '' : '<div style="color:var(--text-muted); font-size:12px;">No slow queries recorded.</div>')}`;
+    } catch (e) {
+        body.innerHTML = `<div style="color:#e74c3c;">${escapeHtml(e.message)}</div>`;
+    }
+}
+
+function mysqlApplySlowLog() {
+    mysqlShowSlowLog({
+        enabled: document.getElementById('mysql-slow-enabled').checked,
+        long_query_time: parseFloat(document.getElementById('mysql-slow-threshold').value) || 0,
+        to_table: document.getElementById('mysql-slow-table').checked,
+    });
+}
+
 async function mysqlExecuteQuery() {
     if (!mysqlCreds) {
         showToast('Not connected to MySQL', 'error');
@@ -42383,6 +46766,389 @@ function _startUpgradeTracking() {
 // Resume tracking on page load
 setTimeout(function() { _startUpgradeTracking(); }, 3000);
 
+// Per-node check toggles — built-ins plus anything in checks.d. Only the
+// local node's list is shown; remote nodes keep their own settings.
+async function openIssueChecks() {
+    try {
+        var resp = await fetch('/api/issues/checks', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var html = '<div style="white-space:normal;">';
+        (data.checks || []).forEach(function (c) {
+            var disabled = c.skipped ? ' disabled' : '';
+            html += '<label style="display:flex;align-items:center;gap:8px;padding:4px 0;">' +
+                '<input type="checkbox"' + (c.enabled ? ' checked' : '') + disabled +
+                ' data-check-id="' + escapeHtml(c.id) + '" onchange="toggleIssueCheck(this.dataset.checkId, this)">' +
+                '<span>' + escapeHtml(c.name) + '</span>' +
+                (c.source === 'script' ? '<span style="font-size:11px;color:var(--text-muted);">script</span>' : '') +
+                (c.skipped ? '<span style="font-size:11px;color:#eab308;">skipped: ' + escapeHtml(c.skipped) + '</span>' : '') +
+                '</label>';
+        });
+        html += '<p style="font-size:12px;color:var(--text-muted);margin:12px 0 0;">Add custom checks by placing root-owned executables in <code>' +
+            escapeHtml(data.scripts_dir || '/etc/wolfstack/checks.d') + '</code>. Each script runs with a ' +
+            (data.script_timeout_secs || 20) + 's timeout and reports JSON issues or Nagios-style exit codes.</p></div>';
+        showModal(html, 'Issue Checks');
+    } catch (e) {
+        showToast('Failed to load checks: ' + e.message, 'error');
+    }
+}
+
+// ─── Inventory report (export + monthly email) ───
+
+async function openInventoryReport() {
+    var sched = { enabled: false, day_of_month: 1, email_to: '', last_sent: '' };
+    try {
+        var resp = await fetch('/api/reports/inventory/schedule', { credentials: 'include' });
+        if (resp.ok) sched = await resp.json();
+    } catch (e) { /* defaults */ }
+    var html = '<div style="white-space:normal;">' +
+        '<p style="font-size:13px;color:var(--text-secondary);margin:0 0 12px;">Every node, VM and container across the cluster with allocated vCPU, memory and disk, IP addresses and versions.</p>' +
+        '<div style="display:flex;gap:8px;margin-bottom:18px;">' +
+        '<a class="btn btn-primary" href="/api/reports/inventory?format=csv" download>Download CSV</a>' +
+        '<a class="btn" href="/api/reports/inventory?format=json" target="_blank" rel="noopener">View JSON</a></div>' +
+        '<h4 style="margin:0 0 8px;font-size:14px;">Monthly email</h4>' +
+        '<label style="display:flex;align-items:center;gap:8px;padding:4px 0;"><input type="checkbox" id="inv-sched-enabled"' + (sched.enabled ? ' checked' : '') + '> Email the inventory every month</label>' +
+        '<label style="display:block;font-size:12px;color:var(--text-muted);margin-top:8px;">Day of month (1–28)</label>' +
+        '<input type="number" id="inv-sched-day" class="form-control" min="1" max="28" value="' + (sched.day_of_month || 1) + '" style="max-width:120px;">' +
+        '<label style="display:block;font-size:12px;color:var(--text-muted);margin-top:8px;">Recipient (blank = the alert address in the email settings)</label>' +
+        '<input type="email" id="inv-sched-to" class="form-control" value="' + escapeAttr(sched.email_to || '') + '" placeholder="management@example.com">' +
+        (sched.last_sent ? '<p style="font-size:12px;color:var(--text-muted);margin:8px 0 0;">Last sent: ' + escapeHtml(sched.last_sent) + '</p>' : '') +
+        '<div style="display:flex;gap:8px;margin-top:14px;">' +
+        '<button class="btn btn-primary" onclick="saveInventorySchedule()">Save schedule</button>' +
+        '<button class="btn" id="inv-send-now" onclick="sendInventoryReportNow()">Send now</button></div></div>';
+    showModal(html, 'Inventory Report');
+}
+
+async function saveInventorySchedule() {
+    try {
+        var resp = await fetch('/api/reports/inventory/schedule', {
+            method: 'PUT', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                enabled: document.getElementById('inv-sched-enabled').checked,
+                day_of_month: parseInt(document.getElementById('inv-sched-day').value, 10) || 1,
+                email_to: document.getElementById('inv-sched-to').value.trim(),
+            })
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast(data.enabled ? 'Inventory report will be emailed on day ' + data.day_of_month + ' of each month' : 'Monthly inventory email turned off', 'success');
+    } catch (e) {
+        showToast('Failed to save schedule: ' + e.message, 'error');
+    }
+}
+
+async function sendInventoryReportNow() {
+    var btn = document.getElementById('inv-send-now');
+    if (btn) { btn.disabled = true; btn.textContent = 'Sending…'; }
+    try {
+        var resp = await fetch('/api/reports/inventory/send', { method: 'POST', credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast(data.message, 'success');
+    } catch (e) {
+        showToast('Failed to send report: ' + e.message, 'error');
+    } finally {
+        if (btn) { btn.disabled = false; btn.textContent = 'Send now'; }
+    }
+}
+
+// ─── Housekeeping (scheduled disk cleanup, this node) ───
+
+var HOUSEKEEPING_TASKS = {
+    docker_images: 'Dangling Docker images',
+    docker_volumes: 'Unused anonymous Docker volumes',
+    journal: 'Journal vacuum',
+    temp_files: 'Old import/export/build files in /tmp',
+    backups: 'Backups beyond retention',
+};
+
+function renderHousekeepingReport(report) {
+    if (!report) return '<p style="font-size:12px;color:var(--text-muted);margin:0;">No runs yet.</p>';
+    var html = '<p style="font-size:12px;color:var(--text-muted);margin:0 0 6px;">' +
+        (report.dry_run ? 'Dry run' : 'Run') + ' (' + escapeHtml(report.trigger) + ') at ' + escapeHtml(new Date(report.finished_at).toLocaleString()) + ' — ' +
+        (report.dry_run ? 'would free ' : 'freed ') + formatBytes(report.total_bytes) + '</p>' +
+        '<table class="data-table" style="width:100%;font-size:12px;"><thead><tr><th>Task</th><th>Items</th><th>Size</th></tr></thead><tbody>';
+    (report.tasks || []).forEach(function (t) {
+        var detail = t.error ? '<span style="color:var(--danger);">' + escapeHtml(t.error) + '</span>'
+            : t.items.length ? '<details><summary>' + t.items.length + '</summary>' + t.items.map(function (i) {
+                return '<div style="font-family:monospace;font-size:11px;">' + escapeHtml(i.name) + (i.bytes ? ' — ' + formatBytes(i.bytes) : '') + '</div>';
+            }).join('') + '</details>' : '<span style="color:var(--text-muted);">none</span>';
+        html += '<tr><td>' + escapeHtml(HOUSEKEEPING_TASKS[t.task] || t.task) + '</td><td>' + detail + '</td><td>' + formatBytes(t.bytes) + '</td></tr>';
+    });
+    return html + '</tbody></table>';
+}
+
+async function openHousekeeping() {
+    try {
+        var resp = await fetch('/api/housekeeping', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var c = data.config;
+        var html = '<div style="white-space:normal;max-height:75vh;overflow:auto;">' +
+            '<p style="font-size:13px;color:var(--text-secondary);margin:0 0 12px;">Frees disk space on this node. Named Docker volumes are never removed.</p>';
+        Object.keys(HOUSEKEEPING_TASKS).forEach(function (k) {
+            html += '<label style="display:flex;align-items:center;gap:8px;padding:3px 0;"><input type="checkbox" id="hk-' + k + '"' + (c[k] ? ' checked' : '') + '> ' + HOUSEKEEPING_TASKS[k] + '</label>';
+        });
+        html += '<div style="display:flex;gap:12px;margin-top:8px;">' +
+            '<div><label style="display:block;font-size:12px;color:var(--text-muted);">Journal cap (MB)</label><input type="number" id="hk-journal-max" class="form-control" min="50" value="' + c.journal_max_mb + '" style="max-width:120px;"></div>' +
+            '<div><label style="display:block;font-size:12px;color:var(--text-muted);">Temp file age (hours)</label><input type="number" id="hk-temp-age" class="form-control" min="1" value="' + c.temp_max_age_hours + '" style="max-width:120px;"></div></div>' +
+            '<h4 style="margin:16px 0 8px;font-size:14px;">Schedule</h4>' +
+            '<label style="display:flex;align-items:center;gap:8px;"><input type="checkbox" id="hk-enabled"' + (c.enabled ? ' checked' : '') + '> Run daily at ' +
+            '<input type="number" id="hk-hour" class="form-control" min="0" max="23" value="' + c.hour + '" style="max-width:70px;display:inline-block;"> :00 (server time)</label>' +
+            (c.last_run ? '<p style="font-size:12px;color:var(--text-muted);margin:6px 0 0;">Last scheduled run: ' + escapeHtml(c.last_run) + '</p>' : '') +
+            '<div style="display:flex;gap:8px;margin-top:14px;">' +
+            '<button class="btn btn-primary" onclick="saveHousekeeping()">Save</button>' +
+            '<button class="btn" id="hk-dry-run" onclick="runHousekeeping(true)">Dry run</button>' +
+            '<button class="btn btn-danger" id="hk-run" onclick="runHousekeeping(false)">Run now</button></div>' +
+            '<h4 style="margin:16px 0 8px;font-size:14px;">Last report</h4><div id="hk-report">' + renderHousekeepingReport(data.last_report) + '</div></div>';
+        showModal(html, 'Housekeeping');
+    } catch (e) {
+        showToast('Failed to load housekeeping settings: ' + e.message, 'error');
+    }
+}
+
+function housekeepingFormConfig() {
+    var config = {
+        enabled: document.getElementById('hk-enabled').checked,
+        hour: parseInt(document.getElementById('hk-hour').value, 10) || 0,
+        journal_max_mb: parseInt(document.getElementById('hk-journal-max').value, 10) || 500,
+        temp_max_age_hours: parseInt(document.getElementById('hk-temp-age').value, 10) || 24,
+    };
+    Object.keys(HOUSEKEEPING_TASKS).forEach(function (k) { config[k] = document.getElementById('hk-' + k).checked; });
+    return config;
+}
+
+async function saveHousekeeping() {
+    try {
+        var resp = await fetch('/api/housekeeping/config', {
+            method: 'POST', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(housekeepingFormConfig())
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        showToast(data.config.enabled ? 'Housekeeping will run daily at ' + data.config.hour + ':00' : 'Housekeeping settings saved', 'success');
+    } catch (e) {
+        showToast('Failed to save housekeeping settings: ' + e.message, 'error');
+    }
+}
+
+async function runHousekeeping(dryRun) {
+    if (!dryRun && !confirm('Run housekeeping now with the saved settings? Removed files and images cannot be recovered.')) return;
+    var btn = document.getElementById(dryRun ? 'hk-dry-run' : 'hk-run');
+    var label = btn ? btn.textContent : '';
+    if (btn) { btn.disabled = true; btn.textContent = 'Running…'; }
+    try {
+        var resp = await fetch('/api/housekeeping/run', {
+            method: 'POST', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ dry_run: dryRun })
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var el = document.getElementById('hk-report');
+        if (el) el.innerHTML = renderHousekeepingReport(data);
+        showToast((dryRun ? 'Would free ' : 'Freed ') + formatBytes(data.total_bytes), 'success');
+    } catch (e) {
+        showToast('Housekeeping failed: ' + e.message, 'error');
+    } finally {
+        if (btn) { btn.disabled = false; btn.textContent = label; }
+    }
+}
+
+async function openCapacityReport() {
+    try {
+        var resp = await fetch('/api/reports/capacity', { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var target = data.target_pct || 90;
+        var html = '<div style="white-space:normal;max-height:70vh;overflow:auto;">' +
+            '<p style="font-size:13px;color:var(--text-secondary);margin:0 0 12px;">Projected date each resource reaches ' + target +
+            '%, from the trend of its daily peak. With two weeks of history the projection also allows for weekly patterns such as weekend backups. The same outlook is included in the daily report email.</p>' +
+            '<table class="data-table" style="width:100%;font-size:12px;"><thead><tr><th>Node</th><th>Resource</th><th>Now</th><th>Growth / day</th><th>Reaches ' + target + '%</th><th>Method</th></tr></thead><tbody>';
+        var rows = 0;
+        (data.nodes || []).forEach(function (n) {
+            if (n.error) {
+                html += '<tr><td><strong>' + escapeHtml(n.node) + '</strong></td><td colspan="5" style="color:var(--text-muted);">' + escapeHtml(n.error) + '</td></tr>';
+                return;
+            }
+            (n.projections || []).forEach(function (p) {
+                rows++;
+                var eta = p.eta_days == null ? '<span style="color:var(--text-muted);">not within a year</span>'
+                    : p.eta_days <= 0 ? '<span style="color:var(--danger);font-weight:600;">now</span>'
+                    : '<span style="color:' + (p.eta_days < 14 ? 'var(--danger)' : p.eta_days < 60 ? 'var(--warning)' : 'var(--success)') + ';font-weight:600;">' +
+                      Math.round(p.eta_days) + ' day(s)</span> <span style="color:var(--text-muted);">' + escapeHtml(p.eta_date || '') + '</span>';
+                html += '<tr><td><strong>' + escapeHtml(n.node) + '</strong><div style="font-size:10px;color:var(--text-muted);">' + escapeHtml(n.cluster) + '</div></td>' +
+                    '<td>' + escapeHtml(p.label) + '</td><td>' + p.current_pct.toFixed(0) + '%</td>' +
+                    '<td>' + (p.growth_pct_per_day >= 0 ? '+' : '') + p.growth_pct_per_day.toFixed(2) + '%</td><td>' + eta + '</td>' +
+                    '<td style="color:var(--text-muted);">' + escapeHtml(p.method) + ' · ' + p.history_days.toFixed(1) + 'd</td></tr>';
+            });
+        });
+        if (!rows && !(data.nodes || []).some(function (n) { return n.error; })) {
+            html += '<tr><td colspan="6" style="color:var(--text-muted);text-align:center;padding:16px;">No history yet — samples are taken hourly; a first projection appears after about 12 hours.</td></tr>';
+        }
+        html += '</tbody></table></div>';
+        showModal(html, 'Capacity Outlook');
+    } catch (e) {
+        showToast('Failed to load capacity report: ' + e.message, 'error');
+    }
+}
+
+// ─── Availability report (per-guest / per-node uptime for SLAs) ───
+
+function openAvailabilityReport() {
+    var month = new Date().toISOString().slice(0, 7);
+    var html = '<div style="white-space:normal;">' +
+        '<p style="font-size:13px;color:var(--text-secondary);margin:0 0 12px;">Share of the month each guest was running and each node was up. Stops made through WolfStack count as planned; anything else that took a guest down counts as an outage. Click a row for its start/stop history.</p>' +
+        '<div style="display:flex;gap:8px;align-items:center;margin-bottom:12px;">' +
+        '<input type="month" id="avail-month" class="form-control" value="' + month + '" style="max-width:170px;" onchange="loadAvailabilityReport()">' +
+        '<a class="btn btn-primary" id="avail-csv" href="/api/reports/availability?format=csv&month=' + month + '" download>Download CSV</a></div>' +
+        '<div id="avail-report-body" style="max-height:55vh;overflow:auto;">Loading…</div></div>';
+    showModal(html, 'Availability Report');
+    loadAvailabilityReport();
+}
+
+function formatDowntime(secs) {
+    if (secs < 60) return secs + 's';
+    if (secs < 3600) return Math.round(secs / 60) + 'm';
+    return (secs / 3600).toFixed(1) + 'h';
+}
+
+async function loadAvailabilityReport() {
+    var month = document.getElementById('avail-month').value;
+    var body = document.getElementById('avail-report-body');
+    document.getElementById('avail-csv').href = '/api/reports/availability?format=csv&month=' + encodeURIComponent(month);
+    body.textContent = 'Loading…';
+    try {
+        var resp = await fetch('/api/reports/availability?month=' + encodeURIComponent(month), { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var rows = data.rows || [];
+        if (!rows.length) {
+            body.innerHTML = '<p style="color:var(--text-muted);text-align:center;padding:16px;">No history for this month — recording starts when WolfStack starts.</p>';
+            return;
+        }
+        var html = '<table class="data-table" style="width:100%;font-size:12px;"><thead><tr><th>Node</th><th>Kind</th><th>Name</th><th>Availability</th><th>Downtime</th><th>Starts</th><th>Stops</th><th>Outages</th></tr></thead><tbody>';
+        rows.forEach(function (r) {
+            var pct = r.availability_percent;
+            var colour = pct == null ? 'var(--text-muted)' : pct >= 99.9 ? 'var(--success)' : pct >= 99 ? 'var(--warning)' : 'var(--danger)';
+            html += '<tr style="cursor:pointer;" data-node-id="' + escapeAttr(r.node_id) + '" data-kind="' + escapeAttr(r.kind) + '" data-name="' + escapeAttr(r.name) + '"' +
+                ' onclick="showAvailabilityHistory(this.dataset.nodeId, this.dataset.kind, this.dataset.name)">' +
+                '<td>' + escapeHtml(r.node) + '</td><td>' + escapeHtml(r.kind) + '</td><td><strong>' + escapeHtml(r.name) + '</strong></td>' +
+                '<td style="color:' + colour + ';font-weight:600;">' + (pct == null ? 'no data' : pct.toFixed(2) + '%') + '</td>' +
+                '<td>' + formatDowntime(r.downtime_secs || 0) + '</td><td>' + (r.starts || 0) + '</td><td>' + (r.stops || 0) + '</td>' +
+                '<td' + (r.outages ? ' style="color:var(--danger);font-weight:600;"' : '') + '>' + (r.outages || 0) + '</td></tr>';
+        });
+        body.innerHTML = html + '</tbody></table>';
+    } catch (e) {
+        body.innerHTML = '<p style="color:var(--danger);">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
+async function showAvailabilityHistory(nodeId, kind, name) {
+    var month = document.getElementById('avail-month').value;
+    var node = allNodes.find(function (n) { return n.id === nodeId; });
+    var path = 'reports/availability/history?month=' + encodeURIComponent(month) + '&kind=' + encodeURIComponent(kind) + '&name=' + encodeURIComponent(name);
+    var url = (!node || node.is_self) ? '/api/' + path : '/api/nodes/' + encodeURIComponent(nodeId) + '/proxy/' + path;
+    try {
+        var resp = await fetch(url, { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        var colours = { running: 'var(--success)', up: 'var(--success)', stopped: 'var(--text-muted)', crashed: 'var(--danger)', down: 'var(--danger)', gone: 'var(--text-muted)' };
+        var html = '<div style="white-space:normal;"><table class="data-table" style="width:100%;font-size:12px;"><thead><tr><th>When</th><th>State</th></tr></thead><tbody>' +
+            (data.changes || []).map(function (c) {
+                return '<tr><td>' + new Date(c.ts * 1000).toLocaleString() + '</td><td style="color:' + (colours[c.state] || 'inherit') + ';font-weight:600;">' + escapeHtml(c.state) + '</td></tr>';
+            }).join('') +
+            '</tbody></table></div>';
+        showModal(html, name + ' — ' + month);
+    } catch (e) {
+        showToast('Failed to load history: ' + e.message, 'error');
+    }
+}
+
+function openIssueHistory() {
+    var until = new Date().toISOString().slice(0, 10);
+    var since = new Date(Date.now() - 90 * 86400000).toISOString().slice(0, 10);
+    var html = '<div style="white-space:normal;">' +
+        '<p style="font-size:13px;color:var(--text-secondary);margin:0 0 12px;">Every issue the scans have raised, when it was first and last seen and when it cleared. Time to resolution runs from first seen to the first scan that no longer showed it. Nodes scan themselves every 15 minutes.</p>' +
+        '<div style="display:flex;gap:8px;align-items:center;margin-bottom:12px;">' +
+        '<input type="date" id="issue-history-since" class="form-control" value="' + since + '" style="max-width:160px;" onchange="loadIssueHistory()">' +
+        '<span style="color:var(--text-muted);">to</span>' +
+        '<input type="date" id="issue-history-until" class="form-control" value="' + until + '" style="max-width:160px;" onchange="loadIssueHistory()">' +
+        '<a class="btn btn-primary" id="issue-history-csv" href="#" download>Download CSV</a></div>' +
+        '<div id="issue-history-body" style="max-height:55vh;overflow:auto;">Loading…</div></div>';
+    showModal(html, 'Issue History');
+    loadIssueHistory();
+}
+
+function issueHistoryQuery() {
+    var since = document.getElementById('issue-history-since').value;
+    var until = document.getElementById('issue-history-until').value;
+    // The end date is inclusive: query up to the following midnight.
+    var end = until ? new Date(Date.parse(until) + 86400000).toISOString().slice(0, 10) : '';
+    return 'since=' + encodeURIComponent(since) + '&until=' + encodeURIComponent(end);
+}
+
+async function loadIssueHistory() {
+    var body = document.getElementById('issue-history-body');
+    var q = issueHistoryQuery();
+    document.getElementById('issue-history-csv').href = '/api/issues/history?format=csv&' + q;
+    body.textContent = 'Loading…';
+    try {
+        var resp = await fetch('/api/issues/history?limit=500&' + q, { credentials: 'include' });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        if (!data.total) {
+            body.innerHTML = '<p style="color:var(--text-muted);text-align:center;padding:16px;">No issues recorded in this period.</p>';
+            return;
+        }
+        var dur = function (s) { return s == null ? '—' : formatDowntime(s); };
+        var html = ['<table class="data-table" style="width:100%;font-size:12px;margin-bottom:16px;"><thead><tr><th>Category</th><th>Occurrences</th><th>Resolved</th><th>Open</th><th>MTTR</th><th>Median</th><th>Longest</th></tr></thead><tbody>'];
+        (data.stats || []).concat([data.overall]).forEach(function (s) {
+            var all = s.category === 'all';
+            html.push('<tr' + (all ? ' style="font-weight:600;border-top:2px solid var(--border);"' : '') + '>' +
+                '<td>' + (all ? 'All categories' : escapeHtml(s.category)) + '</td><td>' + s.occurrences + '</td><td>' + s.resolved + '</td>' +
+                '<td' + (s.open ? ' style="color:var(--warning);"' : '') + '>' + s.open + '</td>' +
+                '<td><strong>' + dur(s.mttr_secs) + '</strong></td><td>' + dur(s.median_secs) + '</td><td>' + dur(s.longest_secs) + '</td></tr>');
+        });
+        html.push('</tbody></table>');
+        var colours = { critical: 'var(--danger)', warning: 'var(--warning)', info: 'var(--text-muted)' };
+        html.push('<table class="data-table" style="width:100%;font-size:12px;"><thead><tr><th>Node</th><th>Severity</th><th>Issue</th><th>First seen</th><th>Cleared</th><th>Took</th></tr></thead><tbody>');
+        (data.occurrences || []).forEach(function (o) {
+            var took = o.resolved_at ? formatDowntime(o.resolved_at - o.first_seen) : '';
+            html.push('<tr><td>' + escapeHtml(o.node || '') + '</td>' +
+                '<td style="color:' + (colours[o.severity] || 'inherit') + ';font-weight:600;">' + escapeHtml(o.severity) + '</td>' +
+                '<td title="' + escapeAttr(o.detail || '') + '"><strong>' + escapeHtml(o.title) + '</strong> <span style="color:var(--text-muted);">' + escapeHtml(o.category) + '</span></td>' +
+                '<td>' + new Date(o.first_seen * 1000).toLocaleString() + '</td>' +
+                '<td>' + (o.resolved_at ? new Date(o.resolved_at * 1000).toLocaleString() : '<span style="color:var(--warning);">still open</span>') + '</td>' +
+                '<td>' + took + '</td></tr>');
+        });
+        html.push('</tbody></table>');
+        if (data.total > (data.occurrences || []).length) {
+            html.push('<p style="font-size:12px;color:var(--text-muted);margin-top:8px;">Showing the latest ' + data.occurrences.length + ' of ' + data.total + ' — download the CSV for all of them.</p>');
+        }
+        body.innerHTML = html.join('');
+    } catch (e) {
+        body.innerHTML = '<p style="color:var(--danger);">' + escapeHtml(e.message) + '</p>';
+    }
+}
+
+async function toggleIssueCheck(id, el) {
+    try {
+        var resp = await fetch('/api/issues/checks', {
+            method: 'POST', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ id: id, enabled: el.checked })
+        });
+        var data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+    } catch (e) {
+        el.checked = !el.checked;
+        showToast('Failed to update check: ' + e.message, 'error');
+    }
+}
+
 async function cleanSystem() {
     try {
         await showIssuesConfirm('\uD83E\uDDF9', 'Clean All Servers?',
@@ -44133,6 +48899,7 @@ async function loadNodeSecurity() {
         if (typeof securityRefreshLockouts === 'function') {
             securityRefreshLockouts();
         }
+        securityLoadSessionPolicy();
         // Per-node antivirus card — loads in parallel; failure here
         // doesn't take down the rest of the Security page.
         if (typeof loadNodeAntivirus === 'function') {
@@ -45356,6 +50123,8 @@ function switchSettingsTab(tabName) {
         loadDnsProviders();
     } else if (tabName === 'cloudproviders') {
         loadCloudProviders();
+    } else if (tabName === 'webhooks') {
+        loadWebhooks();
     } else if (tabName === 'passkeys') {
         loadPasskeys();
     } else if (tabName === 'paths') {
@@ -45381,6 +50150,9 @@ function switchSettingsTab(tabName) {
         loadApiKeysTab();
     } else if (tabName === 'profile') {
         loadUserProfile();
+    } else if (tabName === 'snmp') {
+        populateSystemCheckNodes('snmp-node');
+        loadSnmpSettings();
     } else if (tabName === 'systemcheck') {
         populateSystemCheckNodes();
         const out = document.getElementById('systemcheck-results');
@@ -45640,6 +50412,34 @@ async function lockScreenLogout() {
     window.location.href = '/login.html';
 }
 
+// Session idle timeout (Security → Sessions). The dashboard's own polling
+// keeps the server-side session fresh, so an open-but-abandoned tab is
+// signed out here, on real inactivity. Not armed for "remember me".
+let _sessionIdleTimer = null;
+let _sessionIdleMs = 0;
+let _sessionIdleReset = 0;
+function startSessionIdleLogout(minutes) {
+    if (!minutes || _sessionIdleMs) return;
+    _sessionIdleMs = minutes * 60 * 1000;
+    const arm = () => {
+        const now = Date.now();
+        if (now - _sessionIdleReset < 1000) return;
+        _sessionIdleReset = now;
+        clearTimeout(_sessionIdleTimer);
+        _sessionIdleTimer = setTimeout(sessionIdleLogout, _sessionIdleMs);
+    };
+    ['mousemove', 'mousedown', 'keydown', 'touchstart', 'scroll', 'wheel'].forEach(ev => {
+        window.addEventListener(ev, arm, { passive: true });
+    });
+    arm();
+}
+
+async function sessionIdleLogout() {
+    // Bare path for the same reason as lockScreenLogout.
+    try { await fetch('/api/auth/logout', { method: 'POST' }); } catch (_) {}
+    window.location.href = '/login.html?reason=idle';
+}
+
 function resetIdleTimer() {
     if (!_lockSettings.enabled || !_lockSettings.pin_set || _ifaceLocked) return;
     // Throttle: activity events fire constantly; only re-arm at most once/sec.
@@ -45718,8 +50518,8 @@ window.addEventListener('storage', (e) => {
 // Populate the target-node dropdown with every known node, grouped by
 // cluster. Called on every open so newly-joined nodes appear without
 // a page reload. The previous selection is preserved when possible.
-function populateSystemCheckNodes() {
-    const sel = document.getElementById('systemcheck-node');
+function populateSystemCheckNodes(selectId = 'systemcheck-node') {
+    const sel = document.getElementById(selectId);
     if (!sel) return;
     const prev = sel.value;
     const nodes = (typeof allNodes !== 'undefined' && allNodes) ? allNodes : [];
@@ -45744,6 +50544,103 @@ function populateSystemCheckNodes() {
     if (prev && nodes.some(n => n.id === prev)) sel.value = prev;
 }
 
+// ─── SNMP agent (Settings → SNMP) ───
+
+function snmpApiUrl(path) {
+    const sel = document.getElementById('snmp-node');
+    const opt = sel?.selectedOptions?.[0];
+    if (!sel || !sel.value || opt?.dataset.isSelf === '1') return '/api/' + path;
+    return `/api/nodes/${encodeURIComponent(sel.value)}/proxy/${path}`;
+}
+
+async function loadSnmpSettings() {
+    const status = document.getElementById('snmp-status');
+    if (status) status.textContent = 'Loading…';
+    try {
+        const resp = await fetch(snmpApiUrl('snmp'), { credentials: 'include' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || ('HTTP ' + resp.status));
+        const c = data.config || {};
+        const set = (id, v) => { const el = document.getElementById(id); if (el) el.value = v ?? ''; };
+        document.getElementById('snmp-enabled').checked = !!c.enabled;
+        document.getElementById('snmp-v2c').checked = !!c.v2c_enabled;
+        document.getElementById('snmp-v3').checked = !!c.v3_enabled;
+        set('snmp-listen', c.listen);
+        set('snmp-base-oid', c.base_oid);
+        set('snmp-location', c.location);
+        set('snmp-contact', c.contact);
+        set('snmp-source', c.community_source);
+        set('snmp-v3-user', c.v3_user);
+        set('snmp-v3-auth-proto', c.v3_auth_protocol);
+        set('snmp-v3-priv-proto', c.v3_priv_protocol);
+        set('snmp-community', '');
+        set('snmp-v3-auth-pass', '');
+        set('snmp-v3-priv-pass', '');
+        document.getElementById('snmp-community').placeholder = data.community_set ? '(saved — leave blank to keep)' : '';
+        document.getElementById('snmp-v3-auth-pass').placeholder = data.v3_auth_pass_set ? '(saved — leave blank to keep)' : '';
+        document.getElementById('snmp-v3-priv-pass').placeholder = data.v3_priv_pass_set ? '(saved — leave blank to keep)' : '';
+        if (status) {
+            status.innerHTML = !data.installed
+                ? '<span style="color:var(--text-muted);">snmpd not installed — WolfStack offers to install it when you enable SNMP</span>'
+                : data.running
+                    ? '<span style="color:var(--success);">● snmpd running</span>'
+                    : '<span style="color:var(--text-muted);">○ snmpd stopped</span>';
+        }
+    } catch (e) {
+        if (status) status.innerHTML = '<span style="color:var(--danger);">' + escapeHtml(e.message) + '</span>';
+    }
+}
+
+async function saveSnmpSettings() {
+    const val = id => document.getElementById(id).value.trim();
+    const body = {
+        enabled: document.getElementById('snmp-enabled').checked,
+        listen: val('snmp-listen'),
+        base_oid: val('snmp-base-oid'),
+        location: val('snmp-location'),
+        contact: val('snmp-contact'),
+        v2c_enabled: document.getElementById('snmp-v2c').checked,
+        community: val('snmp-community'),
+        community_source: val('snmp-source') || 'default',
+        v3_enabled: document.getElementById('snmp-v3').checked,
+        v3_user: val('snmp-v3-user'),
+        v3_auth_protocol: val('snmp-v3-auth-proto'),
+        v3_auth_pass: val('snmp-v3-auth-pass'),
+        v3_priv_protocol: document.getElementById('snmp-v3-priv-proto').value,
+        v3_priv_pass: val('snmp-v3-priv-pass'),
+    };
+    const btn = document.getElementById('snmp-save-btn');
+    if (btn) btn.disabled = true;
+    try {
+        const resp = await fetch(snmpApiUrl('snmp'), {
+            method: 'PUT', credentials: 'include',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body),
+        });
+        const data = await resp.json();
+        if (!resp.ok) {
+            const missing = parseMissingPackageError(data.error);
+            // The install terminal runs on this server, so only offer it here.
+            if (missing && snmpApiUrl('snmp') === '/api/snmp') {
+                if (await offerPackageInstall(missing)) saveSnmpSettings();
+                return;
+            }
+            if (missing) throw new Error('snmpd is not installed on that node — install the ' + missing.debianPkg + ' (or ' + missing.redhatPkg + ') package there first');
+            throw new Error(data.error || ('HTTP ' + resp.status));
+        }
+        showToast(data.message || 'SNMP settings saved', 'success');
+        loadSnmpSettings();
+    } catch (e) {
+        showToast('SNMP: ' + e.message, 'error');
+    } finally {
+        if (btn) btn.disabled = false;
+    }
+}
+
+function downloadSnmpMib() {
+    window.location.href = snmpApiUrl('snmp/mib');
+}
+
 // Probe for a popup blocker. Must run inside the user-gesture that
 // triggered runSystemCheck() — calling window.open() outside a gesture
 // is blocked by every browser and would give a false positive. The
@@ -46084,6 +50981,12 @@ function setSidebarDefaultCollapsed(on) {
 function renderSidebarPrefs() {
     const dc = document.getElementById('sidebar-default-collapsed');
     if (dc) dc.checked = localStorage.getItem('wolfstack_sidebar_default_collapsed') === '1';
+    const dn = document.getElementById('sidebar-default-node');
+    if (dn) {
+        const cur = localStorage.getItem('wolfstack_default_node') || '';
+        dn.innerHTML = '<option value="">Datacenter overview</option>' + (allNodes || []).map(n =>
+            `<option value="${escapeAttr(n.id)}"${n.id === cur ? ' selected' : ''}>${escapeHtml(n.display_name || n.hostname || n.id)}${n.cluster_name ? ' (' + escapeHtml(n.cluster_name) + ')' : ''}</option>`).join('');
+    }
     const grid = document.getElementById('sidebar-prefs-grid');
     if (!grid) return;
     const hidden = new Set(currentHiddenFeatures);
@@ -47287,6 +52190,10 @@ async function loadUsers() {
                                                : 'rgba(59,130,246,0.12);color:#60a5fa;border:1px solid rgba(59,130,246,0.35)';
                 html += ' <span style="padding:2px 6px;border-radius:4px;background:' + tone + ';font-size:10px;font-weight:600;" title="Clusters this user can see">' + escapeHtml(label) + '</span>';
             }
+            // Tenancy only confines non-admins — an admin's tenant is inert.
+            if (user.tenant && user.role !== 'admin') {
+                html += ' <span style="padding:2px 6px;border-radius:4px;background:rgba(168,85,247,0.12);color:#c084fc;border:1px solid rgba(168,85,247,0.35);font-size:10px;font-weight:600;" title="Only sees this tenant\'s containers, VMs, mounts and backups">TENANT ' + escapeHtml(user.tenant) + '</span>';
+            }
             // Email drives the forgot-password flow — surface its absence
             // so operators know reset-by-email won't work for this user.
             if (user.email) html += ' <span style="margin-left:8px;">✉ ' + escapeHtml(user.email) + '</span>';
@@ -47298,6 +52205,7 @@ async function loadUsers() {
             if (perUserClusters) {
                 html += '<button class="btn btn-sm" onclick="editUserClusters(\'' + escapeHtml(user.username) + '\')" style="background:var(--bg-tertiary);border:1px solid var(--border);color:var(--text-primary);font-size:11px;">Clusters</button>';
             }
+            html += '<button class="btn btn-sm" onclick="changeUserTenant(\'' + escapeHtml(user.username) + '\')" style="background:var(--bg-tertiary);border:1px solid var(--border);color:var(--text-primary);font-size:11px;">Tenant</button>';
             html += '<button class="btn btn-sm" onclick="changeUserEmail(\'' + escapeHtml(user.username) + '\')" style="background:var(--bg-tertiary);border:1px solid var(--border);color:var(--text-primary);font-size:11px;">Email</button>';
             html += '<button class="btn btn-sm" onclick="changePassword(\'' + escapeHtml(user.username) + '\')" style="background:var(--bg-tertiary);border:1px solid var(--border);color:var(--text-primary);font-size:11px;">Password</button>';
             html += '<button class="btn btn-sm" onclick="deleteUser(\'' + escapeHtml(user.username) + '\')" style="background:rgba(220,38,38,0.1);border:1px solid rgba(220,38,38,0.3);color:var(--danger, #ef4444);font-size:11px;">Delete</button>';
@@ -47315,11 +52223,12 @@ async function createUser() {
     const display_name = document.getElementById('new-user-display').value.trim();
     const email = (document.getElementById('new-user-email') || { value: '' }).value.trim();
     const role = document.getElementById('new-user-role').value;
+    const tenant = (document.getElementById('new-user-tenant') || { value: '' }).value.trim();
     if (!username || !password) { showToast('Username and password required', 'warning'); return; }
     try {
-        const resp = await fetch('/api/auth/users', { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify({ username, password, display_name, email, role }) });
+        const resp = await fetch('/api/auth/users', { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify({ username, password, display_name, email, role, tenant }) });
         const data = await resp.json();
-        if (data.success) { showToast('User created', 'success'); taskLog('Created user: ' + username); document.getElementById('create-user-form').style.display = 'none'; ['new-user-username','new-user-password','new-user-display','new-user-email'].forEach(id => { const el = document.getElementById(id); if (el) el.value = ''; }); loadUsers(); }
+        if (data.success) { showToast('User created', 'success'); taskLog('Created user: ' + username); document.getElementById('create-user-form').style.display = 'none'; ['new-user-username','new-user-password','new-user-display','new-user-email','new-user-tenant'].forEach(id => { const el = document.getElementById(id); if (el) el.value = ''; }); loadUsers(); }
         else showToast(data.error || 'Failed', 'error');
     } catch (e) { showToast('Error: ' + e.message, 'error'); }
 }
@@ -47406,6 +52315,25 @@ async function changeUserEmail(username) {
 }
 window.changeUserEmail = changeUserEmail;
 
+async function changeUserTenant(username) {
+    const current = ((window._wsUsersByName || {})[username] || {}).tenant || '';
+    const tenant = await showPrompt(
+        'Tenant for "' + username + '" — a non-admin in a tenant only sees and manages the containers, VMs, mounts and backups that tenant owns. Leave empty for no tenant.',
+        'Set tenant', current);
+    if (tenant === null) return;
+    try {
+        const resp = await fetch('/api/auth/users/' + encodeURIComponent(username) + '/tenant', { method: 'PUT', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify({ tenant: tenant.trim() || null }) });
+        const data = await resp.json();
+        if (data.success) {
+            showToast(tenant.trim() ? username + ' is now in tenant ' + tenant.trim() : username + ' is no longer in a tenant', 'success');
+            taskLog('Tenant updated: ' + username);
+            loadUsers();
+        }
+        else showToast(data.error || 'Failed', 'error');
+    } catch (e) { showToast('Error: ' + e.message, 'error'); }
+}
+window.changeUserTenant = changeUserTenant;
+
 async function setupTotp(username) {
     try {
         const resp = await fetch('/api/auth/users/' + encodeURIComponent(username) + '/2fa/setup', { method: 'POST' });
@@ -48104,6 +53032,11 @@ async function loadReverseProxyConfig() {
         const cfg = await resp.json();
         input.value = cfg.public_base_url || '';
         _reverseProxyBase = (cfg.public_base_url || '').replace(/\/+$/, '');
+        const setList = (id, v) => { const el = document.getElementById(id); if (el) el.value = (v || []).join(', '); };
+        setList('reverse-proxy-trusted', cfg.trusted_proxies);
+        setList('reverse-proxy-cors', cfg.cors_origins);
+        const bp = document.getElementById('reverse-proxy-base-path');
+        if (bp) bp.value = cfg.base_path || '';
         updateReverseProxyPreview();
     } catch (e) {
         if (statusEl) {
@@ -48125,16 +53058,24 @@ async function saveReverseProxyConfig() {
         }
         return;
     }
+    const list = (id) => (document.getElementById(id)?.value || '').split(',').map(v => v.trim()).filter(Boolean);
     try {
         const resp = await fetch('/api/reverse-proxy/config', {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
-            body: JSON.stringify({ public_base_url: raw }),
+            body: JSON.stringify({
+                public_base_url: raw,
+                trusted_proxies: list('reverse-proxy-trusted'),
+                base_path: (document.getElementById('reverse-proxy-base-path')?.value || '').trim(),
+                cors_origins: list('reverse-proxy-cors'),
+            }),
         });
         const data = await resp.json();
         if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
         _reverseProxyBase = (data.config?.public_base_url || '').replace(/\/+$/, '');
         input.value = _reverseProxyBase;
+        const bp = document.getElementById('reverse-proxy-base-path');
+        if (bp) bp.value = data.config?.base_path || '';
         updateReverseProxyPreview();
         if (statusEl) {
             statusEl.textContent = 'Saved.';
@@ -49519,6 +54460,165 @@ async function repushClusterSecret() {
 var dnsProvidersCache = [];
 var dnsKnownPlugins = [];
 
+// ─── Webhooks ───
+//
+// Lifecycle events from the server's event bus, POSTed to operator URLs
+// with an HMAC signature. The secret is only shown once — on create or
+// rotate — so it's surfaced in a modal the operator can copy from.
+
+let webhookEventTypes = [];
+
+async function loadWebhooks() {
+    const listEl = document.getElementById('webhooks-list');
+    try {
+        const [hooksResp, typesResp] = await Promise.all([fetch('/api/webhooks'), fetch('/api/events/types')]);
+        if (!hooksResp.ok) throw new Error('HTTP ' + hooksResp.status);
+        const hooks = await hooksResp.json();
+        if (typesResp.ok) webhookEventTypes = await typesResp.json();
+        renderWebhookEventPicker();
+        renderWebhooks(hooks);
+    } catch (e) {
+        if (listEl) listEl.innerHTML = '<div style="color:var(--danger);padding:12px;">Failed to load: ' + escapeHtml(e.message) + '</div>';
+    }
+}
+
+function renderWebhookEventPicker() {
+    const el = document.getElementById('webhook-add-events');
+    if (!el) return;
+    el.innerHTML = '<span style="color:var(--text-muted);">Events (none ticked = all):</span>' +
+        webhookEventTypes.map(t =>
+            '<label style="display:flex;gap:4px;align-items:center;"><input type="checkbox" class="webhook-add-event" value="' +
+            escapeHtml(t.id) + '"> ' + escapeHtml(t.label) + '</label>').join('');
+}
+
+function renderWebhooks(hooks) {
+    const listEl = document.getElementById('webhooks-list');
+    if (!listEl) return;
+    if (!hooks.length) {
+        listEl.innerHTML = '<div style="color:var(--text-muted);padding:12px;">No webhooks yet.</div>';
+        return;
+    }
+    const label = id => (webhookEventTypes.find(t => t.id === id) || { label: id }).label;
+    listEl.innerHTML = '<table class="data-table"><thead><tr><th>Name</th><th>URL</th><th>Events</th><th>Enabled</th><th></th></tr></thead><tbody>' +
+        hooks.map(h => {
+            const id = escapeHtml(h.id);
+            const events = h.events.length ? h.events.map(e => escapeHtml(label(e))).join(', ') : '<em>All events</em>';
+            return '<tr><td>' + escapeHtml(h.name) + '</td>' +
+                '<td style="font-family:monospace;font-size:12px;word-break:break-all;">' + escapeHtml(h.url) + '</td>' +
+                '<td style="font-size:12px;">' + events + '</td>' +
+                '<td><input type="checkbox" ' + (h.enabled ? 'checked' : '') + ' onchange="toggleWebhook(\'' + id + '\', this.checked)"></td>' +
+                '<td style="white-space:nowrap;">' +
+                '<button class="btn btn-sm" onclick="testWebhook(\'' + id + '\')">Test</button> ' +
+                '<button class="btn btn-sm" onclick="showWebhookDeliveries(\'' + id + '\')">Deliveries</button> ' +
+                '<button class="btn btn-sm" onclick="rotateWebhookSecret(\'' + id + '\')">New secret</button> ' +
+                '<button class="btn btn-sm btn-danger" onclick="deleteWebhook(\'' + id + '\')">Delete</button></td></tr>';
+        }).join('') + '</tbody></table>';
+}
+
+function showWebhookSecret(secret) {
+    showModal('<p style="font-size:13px;">Copy this signing secret now — it won\'t be shown again.</p>' +
+        '<input type="text" class="form-control" readonly value="' + escapeHtml(secret) + '" onclick="this.select()" style="font-family:monospace;">',
+        'Webhook secret');
+}
+
+async function addWebhook() {
+    const name = document.getElementById('webhook-add-name').value.trim();
+    const url = document.getElementById('webhook-add-url').value.trim();
+    if (!url) { showToast('Enter a webhook URL', 'error'); return; }
+    const events = Array.from(document.querySelectorAll('.webhook-add-event:checked')).map(cb => cb.value);
+    try {
+        const resp = await fetch('/api/webhooks', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ name, url, events }),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        document.getElementById('webhook-add-name').value = '';
+        document.getElementById('webhook-add-url').value = '';
+        document.querySelectorAll('.webhook-add-event:checked').forEach(cb => { cb.checked = false; });
+        showWebhookSecret(data.secret);
+        loadWebhooks();
+    } catch (e) {
+        showToast('Failed to add webhook: ' + e.message, 'error');
+    }
+}
+
+async function updateWebhook(id, patch) {
+    const resp = await fetch('/api/webhooks/' + encodeURIComponent(id), {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(patch),
+    });
+    const data = await resp.json();
+    if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+    return data;
+}
+
+async function toggleWebhook(id, enabled) {
+    try {
+        await updateWebhook(id, { enabled });
+        showToast(enabled ? 'Webhook enabled' : 'Webhook disabled', 'success');
+    } catch (e) {
+        showToast('Failed: ' + e.message, 'error');
+        loadWebhooks();
+    }
+}
+
+async function rotateWebhookSecret(id) {
+    if (!confirm('Generate a new signing secret? The receiver must be updated before it will accept new deliveries.')) return;
+    try {
+        const data = await updateWebhook(id, { rotate_secret: true });
+        showWebhookSecret(data.secret);
+    } catch (e) {
+        showToast('Failed: ' + e.message, 'error');
+    }
+}
+
+async function deleteWebhook(id) {
+    if (!confirm('Delete this webhook on every node in the cluster?')) return;
+    try {
+        const resp = await fetch('/api/webhooks/' + encodeURIComponent(id), { method: 'DELETE' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'HTTP ' + resp.status);
+        loadWebhooks();
+    } catch (e) {
+        showToast('Failed to delete: ' + e.message, 'error');
+    }
+}
+
+async function testWebhook(id) {
+    showToast('Sending test event…', 'info');
+    try {
+        const resp = await fetch('/api/webhooks/' + encodeURIComponent(id) + '/test', { method: 'POST' });
+        const d = await resp.json();
+        if (!resp.ok) throw new Error(d.error || 'HTTP ' + resp.status);
+        if (d.error) showToast('Test failed after ' + d.attempts + ' attempt(s): ' + d.error, 'error', 8000);
+        else showToast('Receiver answered HTTP ' + d.status + ' in ' + d.duration_ms + ' ms', 'success');
+    } catch (e) {
+        showToast('Test failed: ' + e.message, 'error');
+    }
+}
+
+async function showWebhookDeliveries(id) {
+    try {
+        const resp = await fetch('/api/webhooks/' + encodeURIComponent(id) + '/deliveries');
+        const rows = await resp.json();
+        if (!resp.ok) throw new Error(rows.error || 'HTTP ' + resp.status);
+        const body = rows.length
+            ? '<table class="data-table"><thead><tr><th>Time</th><th>Event</th><th>Result</th><th>Attempts</th></tr></thead><tbody>' +
+                rows.map(d => '<tr><td style="font-size:12px;">' + escapeHtml(new Date(d.timestamp).toLocaleString()) + '</td>' +
+                    '<td>' + escapeHtml(d.event) + '</td>' +
+                    '<td style="color:' + (d.error ? 'var(--danger)' : 'var(--success)') + ';">' +
+                    escapeHtml(d.error || ('HTTP ' + d.status)) + '</td>' +
+                    '<td>' + d.attempts + '</td></tr>').join('') + '</tbody></table>'
+            : '<p style="color:var(--text-muted);">No deliveries from this node yet.</p>';
+        showModal('<p style="font-size:12px;color:var(--text-muted);">Deliveries sent by this node since it last restarted.</p>' + body, 'Webhook deliveries');
+    } catch (e) {
+        showToast('Failed to load deliveries: ' + e.message, 'error');
+    }
+}
+
 async function loadDnsProviders() {
     var listEl = document.getElementById('dns-providers-list');
     try {
@@ -50305,6 +55405,10 @@ async function loadAlertingConfig() {
         document.getElementById('alerting-evt-containers').checked = c.alert_containers !== false;
         const containerMemEl = document.getElementById('alerting-container-mem');
         containerMemEl.value = c.container_memory_threshold || 90; containerMemEl.nextElementSibling.textContent = containerMemEl.value + '%';
+        const wnStaleEl = document.getElementById('alerting-wolfnet-stale');
+        if (wnStaleEl) wnStaleEl.value = c.wolfnet_stale_minutes ?? 5;
+        const attackEl = document.getElementById('alerting-attack-threshold');
+        if (attackEl) attackEl.value = c.attack_alert_threshold ?? 100;
         if (c.check_interval_secs) {
             document.getElementById('alerting-check-interval').value = String(c.check_interval_secs);
         }
@@ -50375,6 +55479,8 @@ async function saveAlertingConfig(skipBtnState) {
         alert_disk: document.getElementById('alerting-evt-disk').checked,
         alert_containers: document.getElementById('alerting-evt-containers').checked,
         container_memory_threshold: parseInt(document.getElementById('alerting-container-mem').value),
+        wolfnet_stale_minutes: Math.max(0, parseInt((document.getElementById('alerting-wolfnet-stale') || {}).value) || 0),
+        attack_alert_threshold: Math.max(0, parseInt((document.getElementById('alerting-attack-threshold') || {}).value) || 0),
         check_interval_secs: parseInt(document.getElementById('alerting-check-interval').value) || 60,
         security_scan_interval_secs: parseInt((document.getElementById('alerting-security-scan-interval') || {}).value) || 14400,
         alert_verbosity: (document.getElementById('alerting-verbosity-verbose') || {}).checked ? 'verbose' : 'simple',
@@ -50531,6 +55637,10 @@ const WOLFFN_EVENTS = [
     { id: 'backup_failed', label: 'Backup failed' },
     { id: 'monitor_down', label: 'Status monitor went down' },
     { id: 'monitor_up', label: 'Status monitor recovered' },
+    { id: 'container_created', label: 'Container created' },
+    { id: 'container_started', label: 'Container started' },
+    { id: 'container_stopped', label: 'Container stopped' },
+    { id: 'container_deleted', label: 'Container deleted' },
     { id: 'container_failover', label: 'WolfRun failover (standby promoted)' },
     { id: 'container_updated', label: 'Container image updated' },
     { id: 'container_update_failed', label: 'Container image update failed' },
@@ -50952,9 +56062,163 @@ function _fpHumanSize(n) {
     return x.toFixed(i ? 1 : 0) + ' ' + u[i];
 }
 
+// ─── ISO library ───
+
+let _isoLibraryTarget = null;
+let _isoLibraryPoll = null;
+
+async function openIsoLibrary(targetInputId) {
+    _isoLibraryTarget = targetInputId;
+    showModal('<div id="iso-library">Loading…</div>', 'ISO Library');
+    await renderIsoLibrary();
+}
+
+async function renderIsoLibrary() {
+    const el = document.getElementById('iso-library');
+    if (!el) return;
+    let d, jobs = [];
+    try {
+        const [r1, r2] = await Promise.all([fetch(apiUrl('/api/vms/isos')), fetch(apiUrl('/api/vms/isos/downloads'))]);
+        d = await r1.json();
+        if (!r1.ok) throw new Error(d.error || `HTTP ${r1.status}`);
+        if (r2.ok) jobs = await r2.json();
+    } catch (e) {
+        el.innerHTML = `<div style="color:#ef4444;">Failed to load the ISO library: ${escapeHtml(e.message)}</div>`;
+        return;
+    }
+    const inp = 'padding:6px 10px;background:var(--bg-primary);border:1px solid var(--border);border-radius:6px;color:var(--text-primary);font-size:12px;';
+    let h = `<div style="white-space:normal;">
+        <input id="iso-library-filter" type="text" placeholder="Filter…" oninput="filterIsoLibrary()" style="${inp}width:100%;margin-bottom:8px;">
+        <div id="iso-library-list" style="max-height:260px;overflow-y:auto;">`;
+    const isos = d.isos || [];
+    if (!isos.length) h += '<div style="font-size:12px;">No images yet — download one below or add a directory.</div>';
+    isos.forEach(iso => {
+        h += `<div class="iso-library-row" data-name="${escapeAttr(iso.name.toLowerCase())}" style="display:flex;align-items:center;gap:8px;padding:5px 0;border-bottom:1px solid var(--border);">
+            <div style="flex:1;min-width:0;">
+                <div style="color:var(--text-primary);font-size:13px;word-break:break-all;">${escapeHtml(iso.name)}</div>
+                <div style="font-size:11px;font-family:monospace;word-break:break-all;">${escapeHtml(iso.path)} · ${formatBytes(iso.size_bytes)} · ${escapeHtml(iso.source)}</div>
+            </div>
+            ${_isoLibraryTarget ? `<button class="btn btn-sm btn-primary" onclick="pickIsoFromLibrary('${escapeAttr(iso.path)}')">Use</button>` : ''}
+            <button class="btn btn-sm" style="color:#ef4444;" onclick="deleteIsoFromLibrary('${escapeAttr(iso.path)}')" title="Delete"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
+        </div>`;
+    });
+    h += '</div>';
+
+    const active = jobs.filter(j => j.status === 'downloading' || j.status === 'verifying');
+    if (jobs.length) {
+        h += '<div style="font-weight:600;color:var(--text-primary);margin:12px 0 4px;">Downloads</div>';
+        jobs.forEach(j => {
+            const name = j.path.split('/').pop();
+            const pct = j.bytes_total ? Math.floor(j.bytes_done * 100 / j.bytes_total) : null;
+            let status;
+            if (j.status === 'failed') status = `<span style="color:#ef4444;">Failed: ${escapeHtml(j.error || '')}</span>`;
+            else if (j.status === 'done') status = '<span style="color:#10b981;">Done' + (j.sha256 ? ` · sha256 ${escapeHtml(j.sha256.slice(0, 16))}…` : '') + '</span>';
+            else if (j.status === 'verifying') status = 'Verifying…';
+            else status = `${formatBytes(j.bytes_done)}${j.bytes_total ? ' of ' + formatBytes(j.bytes_total) : ''}`;
+            h += `<div style="font-size:12px;margin-bottom:6px;"><div style="word-break:break-all;">${escapeHtml(name)} — ${status}</div>
+                ${pct !== null && j.status === 'downloading' ? `<div style="height:4px;background:var(--border);border-radius:2px;margin-top:3px;"><div style="height:4px;width:${pct}%;background:var(--accent,#3b82f6);border-radius:2px;"></div></div>` : ''}</div>`;
+        });
+    }
+
+    const dirOptions = (d.dirs || []).map(dir => `<option value="${escapeAttr(dir.path)}">${escapeHtml(dir.path)} (${escapeHtml(dir.source)})</option>`).join('');
+    h += `<div style="font-weight:600;color:var(--text-primary);margin:12px 0 4px;">Download from URL</div>
+        <input id="iso-dl-url" type="text" placeholder="https://…/image.iso" style="${inp}width:100%;">
+        <div style="display:grid;grid-template-columns:1fr 1fr;gap:6px;margin-top:6px;">
+            <input id="iso-dl-name" type="text" placeholder="Save as (default: from URL)" style="${inp}">
+            <select id="iso-dl-dir" style="${inp}">${dirOptions}</select>
+        </div>
+        <input id="iso-dl-sha256" type="text" placeholder="Expected SHA-256 (recommended)" style="${inp}width:100%;margin-top:6px;font-family:monospace;">
+        <div style="text-align:right;margin-top:6px;"><button class="btn btn-sm btn-primary" onclick="startIsoDownload()">Download</button></div>
+        <div style="font-weight:600;color:var(--text-primary);margin:12px 0 4px;">Extra directories</div>
+        <div style="font-size:11px;margin-bottom:4px;">The default directory, Proxmox ISO storages and an <code>iso</code>/<code>isos</code> folder on each storage mount are included automatically. One path per line.</div>
+        <textarea id="iso-extra-dirs" rows="2" style="${inp}width:100%;font-family:monospace;">${escapeHtml((d.extra_dirs || []).join('\n'))}</textarea>
+        <div style="text-align:right;margin-top:6px;"><button class="btn btn-sm" onclick="saveIsoLibraryDirs()">Save directories</button></div>
+    </div>`;
+    const filter = document.getElementById('iso-library-filter')?.value || '';
+    el.innerHTML = h;
+    if (filter) {
+        document.getElementById('iso-library-filter').value = filter;
+        filterIsoLibrary();
+    }
+
+    clearTimeout(_isoLibraryPoll);
+    if (active.length) _isoLibraryPoll = setTimeout(renderIsoLibrary, 2000);
+}
+
+function filterIsoLibrary() {
+    const q = (document.getElementById('iso-library-filter')?.value || '').toLowerCase();
+    document.querySelectorAll('#iso-library-list .iso-library-row').forEach(row => {
+        row.style.display = row.dataset.name.includes(q) ? '' : 'none';
+    });
+}
+
+function pickIsoFromLibrary(path) {
+    const input = _isoLibraryTarget && document.getElementById(_isoLibraryTarget);
+    if (input) {
+        input.value = path;
+        if (_isoLibraryTarget === 'new-vm-iso' && typeof autoDetectWindowsIso === 'function') autoDetectWindowsIso(path);
+    }
+    clearTimeout(_isoLibraryPoll);
+    document.getElementById('iso-library')?.closest('.modal-overlay')?.remove();
+}
+
+async function startIsoDownload() {
+    const url = document.getElementById('iso-dl-url').value.trim();
+    if (!url) { showToast('Enter a URL', 'error'); return; }
+    try {
+        const resp = await fetch(apiUrl('/api/vms/isos/download'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                url,
+                file_name: document.getElementById('iso-dl-name').value.trim(),
+                dir: document.getElementById('iso-dl-dir').value,
+                sha256: document.getElementById('iso-dl-sha256').value.trim(),
+            }),
+        });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast(`Downloading ${data.path.split('/').pop()}`, 'info');
+        renderIsoLibrary();
+    } catch (e) {
+        showToast('Download failed: ' + e.message, 'error');
+    }
+}
+
+async function deleteIsoFromLibrary(path) {
+    if (!await showConfirm(`Delete ${path}? VMs still using it will lose their install media.`)) return;
+    try {
+        const resp = await fetch(apiUrl('/api/vms/isos?path=' + encodeURIComponent(path)), { method: 'DELETE' });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast('Image deleted', 'success');
+        renderIsoLibrary();
+    } catch (e) {
+        showToast('Delete failed: ' + e.message, 'error');
+    }
+}
+
+async function saveIsoLibraryDirs() {
+    const dirs = document.getElementById('iso-extra-dirs').value.split('\n').map(d => d.trim()).filter(Boolean);
+    try {
+        const resp = await fetch(apiUrl('/api/vms/isos/dirs'), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ dirs }),
+        });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `HTTP ${resp.status}`);
+        showToast(data.message || 'Saved', 'success');
+        renderIsoLibrary();
+    } catch (e) {
+        showToast('Save failed: ' + e.message, 'error');
+    }
+}
+
 function pickFile(opts) {
     opts = opts || {};
-    const filter = (opts.filter || '').toLowerCase();   // extension like '.iso', or '' for all
+    const filter = (opts.filter || '').toLowerCase();   // extension like '.iso' (or '.ova,.vmdk'), or '' for all
+    const filterExts = filter.split(',').map(f => f.trim()).filter(Boolean);
     const title = opts.title || 'Choose a file';
     const onPick = typeof opts.onPick === 'function' ? opts.onPick : function () {};
 
@@ -51059,7 +56323,7 @@ function pickFile(opts) {
                     const el = row('📁', en.name, '');
                     el.onclick = () => browse(en.path);
                     listEl.appendChild(el); shown++;
-                } else if (!filter || en.name.toLowerCase().endsWith(filter)) {
+                } else if (!filterExts.length || filterExts.some(ext => en.name.toLowerCase().endsWith(ext))) {
                     const el = row('📄', en.name, _fpHumanSize(en.size));
                     el.onclick = () => setSelected(en.path, el);
                     el.ondblclick = () => { onPick(en.path); close(); };
@@ -51528,8 +56792,12 @@ function renderWolfRunServices(services) {
             ? `<span style="font-size:9px;padding:1px 6px;border-radius:8px;background:rgba(59, 130, 246,0.15);color:#60a5fa;border:1px solid rgba(59, 130, 246,0.3);margin-left:6px;" title="Failover enabled — ${standbyInstances.length} standby">HA</span>`
             : '';
 
+        const autoscaleBadge = svc.autoscale && svc.autoscale.enabled
+            ? `<span style="font-size:9px;padding:1px 6px;border-radius:8px;background:rgba(16,185,129,0.15);color:#10b981;border:1px solid rgba(16,185,129,0.3);margin-left:6px;" title="Autoscaling between ${minR} and ${maxR} replicas">AUTO</span>`
+            : '';
+
         return `<tr style="cursor:pointer;" onclick="toggleWolfRunInstances('${svc.id}')">
-            <td style="font-weight:600;">${toggleBtn}${svc.name}${failoverBadge}</td>
+            <td style="font-weight:600;">${toggleBtn}${svc.name}${failoverBadge}${autoscaleBadge}</td>
             <td>${runtimeIcon} ${runtimeLabel}</td>
             <td><code style="font-size:12px;">${svc.image || '—'}</code></td>
             <td>${replicaHtml}</td>
@@ -51543,6 +56811,7 @@ function renderWolfRunServices(services) {
                 <button class="btn btn-sm" onclick="wolfrunScale('${svc.id}', ${desired - 1})" ${desired <= minR ? 'disabled' : ''} title="Scale down" style="padding:4px 8px; font-size:12px;"><span class="ws-icon-clean-wrap" data-icon="minus"></span></button>
                 <button class="btn btn-sm" onclick="wolfrunScale('${svc.id}', ${desired + 1})" ${desired >= maxR ? 'disabled' : ''} title="Scale up" style="padding:4px 8px; font-size:12px;"></button>
                 <button class="btn btn-sm" onclick="wolfrunSettings('${svc.id}', '${svc.name}', ${desired}, ${minR}, ${maxR}, '${svc.lb_policy || 'round_robin'}', ${JSON.stringify(svc.allowed_nodes || [])}, '${svc.cluster_name || ''}', ${!!svc.failover})" title="Settings" style="padding:4px 8px; font-size:12px; color:#60a5fa;"><span class="ws-icon-clean-wrap" data-icon="settings"></span></button>
+                <button class="btn btn-sm" onclick="openWolfRunAutoscale('${svc.id}')" title="Autoscaling" style="padding:4px 8px; font-size:12px; color:#10b981;"><span class="ws-icon-clean-wrap" data-icon="lightning"></span></button>
                 <button class="btn btn-sm" onclick="openWolfRunPortForward('${svc.id}', '${svc.name}', '${vip || ''}')" title="Port Forward" style="padding:4px 8px; font-size:12px; color:#60a5fa;" ${!vip ? 'disabled' : ''}><span class="ws-icon-clean-wrap" data-icon="link"></span></button>
                 <button class="btn btn-sm" onclick="wolfrunDelete('${svc.id}', '${svc.name}')" title="Remove" style="padding:4px 8px; font-size:12px; color:#ef4444;"><span class="ws-icon-clean-wrap" data-icon="trash"></span></button>
             </td>
@@ -51794,6 +57063,101 @@ async function saveWolfRunSettings() {
     }
 }
 
+// ─── WolfRun autoscaling ───
+// The policy is stored on the service, so it syncs like every other
+// setting; the leader evaluates it every 15s before reconciling.
+async function openWolfRunAutoscale(serviceId) {
+    let svc, events = [];
+    try {
+        const sr = await fetch(wolfrunApiUrl(`/api/wolfrun/services/${serviceId}`));
+        svc = await sr.json();
+        if (!sr.ok) throw new Error(svc.error || 'Service not found');
+        const er = await fetch(wolfrunApiUrl(`/api/wolfrun/autoscale/events?service_id=${encodeURIComponent(serviceId)}`));
+        if (er.ok) events = await er.json();
+    } catch (e) {
+        showToast(e.message, 'error');
+        return;
+    }
+    const p = svc.autoscale || { enabled: true, cpu_high: 80, cpu_low: 20, mem_high: null, mem_low: null, sustain_secs: 120, cooldown_secs: 300, step: 1 };
+    const num = (v) => v == null ? '' : v;
+    const field = (id, label, value, hint) => `<div class="form-group" style="flex:1; min-width:120px;"><label style="font-size:12px;">${label}</label>
+        <input id="${id}" class="form-control" type="number" min="0" value="${escapeAttr(String(num(value)))}" placeholder="${escapeAttr(hint || 'off')}"></div>`;
+    const eventRows = events.slice().reverse().slice(0, 15).map(e => `<tr>
+        <td style="padding:3px 6px; white-space:nowrap;">${escapeHtml(new Date(e.timestamp * 1000).toLocaleString())}</td>
+        <td style="padding:3px 6px; color:${e.direction === 'out' ? 'var(--success)' : 'var(--warning)'};">${e.direction === 'out' ? 'Scale out' : 'Scale in'} ${e.from_replicas} → ${e.to_replicas}</td>
+        <td style="padding:3px 6px; color:var(--text-muted);">${escapeHtml(e.reason)}</td></tr>`).join('');
+    showModal(`<div style="white-space:normal;">
+        <p style="font-size:12px; color:var(--text-secondary); margin:0 0 10px;">Average CPU/RAM across running replicas. Above a scale-out threshold adds replicas on the least-loaded nodes; below every scale-in threshold removes the newest. Bounded by min ${svc.min_replicas} / max ${svc.max_replicas} replicas (change these in Settings).</p>
+        <label style="display:flex; gap:8px; align-items:center; font-size:13px; margin-bottom:10px;"><input type="checkbox" id="wr-as-enabled" ${p.enabled ? 'checked' : ''}> Enabled</label>
+        <div style="display:flex; gap:8px; flex-wrap:wrap;">
+            ${field('wr-as-cpu-high', 'CPU scale-out above %', p.cpu_high)}
+            ${field('wr-as-cpu-low', 'CPU scale-in below %', p.cpu_low)}
+        </div>
+        <div style="display:flex; gap:8px; flex-wrap:wrap;">
+            ${field('wr-as-mem-high', 'RAM scale-out above %', p.mem_high)}
+            ${field('wr-as-mem-low', 'RAM scale-in below %', p.mem_low)}
+        </div>
+        <div style="display:flex; gap:8px; flex-wrap:wrap;">
+            ${field('wr-as-sustain', 'Sustained for (s)', p.sustain_secs, '120')}
+            ${field('wr-as-cooldown', 'Cooldown (s)', p.cooldown_secs, '300')}
+            ${field('wr-as-step', 'Replicas per scale-out', p.step, '1')}
+        </div>
+        <div style="font-weight:600; font-size:13px; margin:10px 0 4px;">History</div>
+        ${eventRows ? `<div style="max-height:180px; overflow-y:auto;"><table style="width:100%; border-collapse:collapse; font-size:12px;">${eventRows}</table></div>`
+            : '<div style="font-size:12px; color:var(--text-muted);">No scaling actions yet.</div>'}
+        <div style="display:flex; justify-content:flex-end; gap:8px; margin-top:14px;">
+            ${svc.autoscale ? `<button class="btn btn-sm btn-danger" onclick="deleteWolfRunAutoscale('${escapeAttr(serviceId)}', this)">Remove policy</button>` : ''}
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-primary" onclick="saveWolfRunAutoscale('${escapeAttr(serviceId)}', this)">Save</button>
+        </div></div>`, 'Autoscaling — ' + svc.name, { noOk: true });
+}
+
+async function saveWolfRunAutoscale(serviceId, btn) {
+    const val = (id) => {
+        const v = document.getElementById(id).value.trim();
+        return v === '' ? null : Number(v);
+    };
+    const body = {
+        enabled: document.getElementById('wr-as-enabled').checked,
+        cpu_high: val('wr-as-cpu-high'),
+        cpu_low: val('wr-as-cpu-low'),
+        mem_high: val('wr-as-mem-high'),
+        mem_low: val('wr-as-mem-low'),
+        sustain_secs: val('wr-as-sustain') ?? 120,
+        cooldown_secs: val('wr-as-cooldown') ?? 300,
+        step: val('wr-as-step') ?? 1,
+    };
+    btn.disabled = true;
+    try {
+        const resp = await fetch(wolfrunApiUrl(`/api/wolfrun/services/${serviceId}/autoscale`), {
+            method: 'PUT', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Save failed');
+        showToast('Autoscaling policy saved', 'success');
+        btn.closest('.modal-overlay').remove();
+        loadWolfRunServices();
+    } catch (e) {
+        showToast(e.message, 'error');
+        btn.disabled = false;
+    }
+}
+
+async function deleteWolfRunAutoscale(serviceId, btn) {
+    btn.disabled = true;
+    try {
+        const resp = await fetch(wolfrunApiUrl(`/api/wolfrun/services/${serviceId}/autoscale`), { method: 'DELETE' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Remove failed');
+        showToast('Autoscaling removed', 'success');
+        btn.closest('.modal-overlay').remove();
+        loadWolfRunServices();
+    } catch (e) {
+        showToast(e.message, 'error');
+        btn.disabled = false;
+    }
+}
+
 async function loadWolfRunFailoverEvents() {
     try {
         const resp = await fetch(wolfrunApiUrl('/api/wolfrun/failover-events'));
@@ -52743,6 +58107,215 @@ async function loadWolfDiskSyncHealth() {
         </div>`;
 }
 
+// ─── WolfDisk volumes ───
+// Volumes are top-level directories on the WolfDisk mount, so every node sees
+// the same list — read it from the first node that has WolfDisk mounted.
+// Attach/detach go to the node the container or VM lives on.
+let wdVolumeNodeId = null;
+
+async function loadWolfDiskVolumes() {
+    const el = document.getElementById('wd-volumes');
+    if (!el) return;
+    const nodes = (typeof getClusterNodes === 'function' ? getClusterNodes(wdCurrentCluster) : []).filter(n => n.online);
+    let data = null;
+    wdVolumeNodeId = null;
+    for (const node of nodes) {
+        try {
+            const resp = await fetch(nodeApiUrl(node.id, '/api/storage/wolfdisk/volumes'));
+            const d = resp.ok ? await resp.json() : null;
+            if (d && d.health && d.health.mounted) { data = d; wdVolumeNodeId = node.id; break; }
+        } catch (_) {}
+    }
+    if (!data) {
+        el.innerHTML = nodes.length === 0 ? '' : `<div style="background:var(--bg-card); border:1px solid var(--border); border-radius:10px; padding:14px 16px; color:var(--text-muted); font-size:13px;">
+            <div style="font-weight:600; font-size:14px; color:var(--text-primary); margin-bottom:4px;">Volumes</div>
+            WolfDisk isn't mounted on any online node in this cluster, so volumes can't be listed or created.</div>`;
+        return;
+    }
+    const h = data.health;
+    const stateColor = { healthy: '#10b981', degraded: '#f59e0b', offline: '#ef4444' }[h.state] || 'var(--text-muted)';
+    const fmtBytes = (b) => (typeof formatStorageBytes === 'function') ? formatStorageBytes(b) : (b + ' B');
+    const rows = data.volumes.map(v => {
+        const over = v.size_gb > 0 && v.used_bytes > v.size_gb * 1073741824;
+        const attached = (v.attachments || []).map(a =>
+            `<span style="display:inline-flex; gap:4px; align-items:center; border:1px solid var(--border); border-radius:8px; padding:1px 6px; margin:1px; font-size:11px;">
+                ${a.kind === 'vm' ? 'VM' : 'LXC'} ${escapeHtml(a.target)} <span style="color:var(--text-muted);">@ ${escapeHtml(a.node)}</span>
+                <a href="#" title="Detach" onclick="wdDetachVolume('${escapeAttr(v.name)}', '${escapeAttr(a.kind)}', '${escapeAttr(a.target)}', '${escapeAttr(a.node)}'); return false;">✕</a>
+            </span>`).join('') || '<span style="color:var(--text-muted);">—</span>';
+        return `<tr>
+            <td style="padding:6px 10px;"><b>${escapeHtml(v.name)}</b>${v.managed ? '' : ' <span style="color:var(--text-muted); font-size:11px;">(unmanaged)</span>'}
+                ${v.description ? `<div style="color:var(--text-muted); font-size:11px;">${escapeHtml(v.description)}</div>` : ''}</td>
+            <td style="padding:6px 10px;${over ? ' color:#f59e0b;' : ''}">${escapeHtml(fmtBytes(v.used_bytes))}${v.size_gb ? ' / ' + v.size_gb + ' GB' : ''}</td>
+            <td style="padding:6px 10px;">${attached}</td>
+            <td style="padding:6px 10px; text-align:right; white-space:nowrap;">
+                <button class="btn btn-sm" onclick="wdOpenAttachVolume('${escapeAttr(v.name)}')">Attach</button>
+                <button class="btn btn-sm btn-danger" onclick="wdDeleteVolume('${escapeAttr(v.name)}')">Delete</button>
+            </td>
+        </tr>`;
+    }).join('');
+    el.innerHTML = `
+        <div style="background:var(--bg-card); border:1px solid var(--border); border-radius:10px; padding:14px 16px;">
+            <div style="display:flex; align-items:center; justify-content:space-between; margin-bottom:8px; gap:12px; flex-wrap:wrap;">
+                <div style="font-weight:600; font-size:14px;">Volumes</div>
+                <div style="font-size:12px; color:var(--text-muted);">
+                    <span style="color:${stateColor}; font-weight:700;">${escapeHtml(h.state)}</span>
+                    — ${escapeHtml(h.replication_mode || 'unknown')} replication${h.replication_factor ? ' ×' + h.replication_factor : ''},
+                    ${h.peers_up}/${h.peers_total} peers up, mounted at <code>${escapeHtml(h.mount_path)}</code>
+                </div>
+                <button class="btn btn-sm btn-primary" onclick="wdOpenCreateVolume()">+ New Volume</button>
+            </div>
+            ${data.volumes.length === 0
+                ? '<div style="color:var(--text-muted); font-size:13px; padding:6px 0;">No volumes yet. A volume is replicated across the cluster and can be attached to containers or VMs on any node.</div>'
+                : `<table style="width:100%; border-collapse:collapse; font-size:13px;">
+                    <thead><tr style="color:var(--text-muted); font-size:11px; text-align:left;">
+                        <th style="padding:4px 10px;">Name</th><th style="padding:4px 10px;">Used / Size</th>
+                        <th style="padding:4px 10px;">Attached to</th><th></th>
+                    </tr></thead>
+                    <tbody>${rows}</tbody>
+                </table>`}
+        </div>`;
+}
+
+function wdOpenCreateVolume() {
+    showModal(`<div style="white-space:normal;">
+        <div class="form-group"><label>Name</label>
+            <input id="wd-vol-name" class="form-control" placeholder="web-data">
+            <small style="color:var(--text-muted); font-size:11px;">Letters, digits, - and _. Becomes a directory on the WolfDisk mount.</small></div>
+        <div class="form-group"><label>Size (GB)</label>
+            <input id="wd-vol-size" class="form-control" type="number" min="1" value="10">
+            <small style="color:var(--text-muted); font-size:11px;">The disk size when attached to a VM; a soft quota for containers.</small></div>
+        <div class="form-group"><label>Description</label>
+            <input id="wd-vol-desc" class="form-control" placeholder="Optional"></div>
+        <div style="display:flex; justify-content:flex-end; gap:8px;">
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-primary" onclick="wdCreateVolume(this)">Create</button>
+        </div></div>`, 'New WolfDisk Volume', { noOk: true });
+}
+
+async function wdCreateVolume(btn) {
+    const body = {
+        name: document.getElementById('wd-vol-name').value.trim(),
+        size_gb: parseInt(document.getElementById('wd-vol-size').value, 10) || 0,
+        description: document.getElementById('wd-vol-desc').value,
+    };
+    btn.disabled = true;
+    try {
+        const resp = await fetch(nodeApiUrl(wdVolumeNodeId, '/api/storage/wolfdisk/volumes'), {
+            method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Create failed');
+        showToast(`Volume ${body.name} created`, 'success');
+        btn.closest('.modal-overlay').remove();
+        loadWolfDiskVolumes();
+    } catch (e) {
+        showToast(e.message, 'error');
+        btn.disabled = false;
+    }
+}
+
+async function wdDeleteVolume(name) {
+    if (!await showConfirm(`Delete WolfDisk volume "${name}" and all its data on every node?`)) return;
+    try {
+        const resp = await fetch(nodeApiUrl(wdVolumeNodeId, '/api/storage/wolfdisk/volumes/' + encodeURIComponent(name)), { method: 'DELETE' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Delete failed');
+        showToast(`Volume ${name} deleted`, 'success');
+        loadWolfDiskVolumes();
+    } catch (e) {
+        showToast(e.message, 'error');
+    }
+}
+
+function wdOpenAttachVolume(name) {
+    const nodes = (typeof getClusterNodes === 'function' ? getClusterNodes(wdCurrentCluster) : []).filter(n => n.online);
+    const nodeOpts = nodes.map(n => `<option value="${escapeAttr(n.id)}">${escapeHtml(n.hostname || n.name || n.id)}</option>`).join('');
+    showModal(`<div style="white-space:normal;">
+        <div class="form-group"><label>Node</label>
+            <select id="wd-att-node" class="form-control" onchange="wdAttachLoadTargets()">${nodeOpts}</select></div>
+        <div class="form-group"><label>Attach to</label>
+            <select id="wd-att-kind" class="form-control" onchange="wdAttachLoadTargets()">
+                <option value="lxc">LXC container (bind mount)</option>
+                <option value="vm">VM (disk image on the volume)</option>
+            </select></div>
+        <div class="form-group"><label>Container / VM</label>
+            <select id="wd-att-target" class="form-control"><option value="">Loading…</option></select></div>
+        <div class="form-group" id="wd-att-lxc-opts"><label>Mount point</label>
+            <input id="wd-att-path" class="form-control" placeholder="/mnt/${escapeAttr(name)}">
+            <label style="display:flex; gap:8px; align-items:center; font-size:13px; margin-top:6px;"><input type="checkbox" id="wd-att-ro"> Read-only</label></div>
+        <div class="form-group" id="wd-att-vm-opts" style="display:none;"><label>Bus</label>
+            <select id="wd-att-bus" class="form-control"><option value="virtio">virtio</option><option value="scsi">scsi</option><option value="ide">ide</option></select>
+            <small style="color:var(--text-muted); font-size:11px;">The VM must be stopped. Its disk stays in the volume when detached.</small></div>
+        <div style="display:flex; justify-content:flex-end; gap:8px;">
+            <button class="btn btn-sm" onclick="this.closest('.modal-overlay').remove()">Cancel</button>
+            <button class="btn btn-sm btn-primary" onclick="wdAttachVolume('${escapeAttr(name)}', this)">Attach</button>
+        </div></div>`, 'Attach ' + name, { noOk: true });
+    wdAttachLoadTargets();
+}
+
+async function wdAttachLoadTargets() {
+    const nodeId = document.getElementById('wd-att-node').value;
+    const kind = document.getElementById('wd-att-kind').value;
+    const sel = document.getElementById('wd-att-target');
+    document.getElementById('wd-att-lxc-opts').style.display = kind === 'lxc' ? '' : 'none';
+    document.getElementById('wd-att-vm-opts').style.display = kind === 'vm' ? '' : 'none';
+    sel.innerHTML = '<option value="">Loading…</option>';
+    try {
+        const resp = await fetch(nodeApiUrl(nodeId, kind === 'vm' ? '/api/vms' : '/api/containers/lxc'));
+        const list = resp.ok ? await resp.json() : [];
+        const names = (Array.isArray(list) ? list : []).map(g => g.name).filter(Boolean);
+        sel.innerHTML = names.length
+            ? names.map(n => `<option value="${escapeAttr(n)}">${escapeHtml(n)}</option>`).join('')
+            : `<option value="">No ${kind === 'vm' ? 'VMs' : 'containers'} on this node</option>`;
+    } catch (e) {
+        sel.innerHTML = `<option value="">${escapeHtml(e.message)}</option>`;
+    }
+}
+
+async function wdAttachVolume(name, btn) {
+    const nodeId = document.getElementById('wd-att-node').value;
+    const body = {
+        kind: document.getElementById('wd-att-kind').value,
+        target: document.getElementById('wd-att-target').value,
+        path: document.getElementById('wd-att-path').value.trim(),
+        read_only: document.getElementById('wd-att-ro').checked,
+        bus: document.getElementById('wd-att-bus').value,
+    };
+    if (!body.target) { showToast('Pick a container or VM', 'error'); return; }
+    btn.disabled = true;
+    try {
+        const resp = await fetch(nodeApiUrl(nodeId, '/api/storage/wolfdisk/volumes/' + encodeURIComponent(name) + '/attach'), {
+            method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Attach failed');
+        showToast(data.message || 'Attached', 'success');
+        btn.closest('.modal-overlay').remove();
+        loadWolfDiskVolumes();
+    } catch (e) {
+        showToast(e.message, 'error');
+        btn.disabled = false;
+    }
+}
+
+async function wdDetachVolume(name, kind, target, hostname) {
+    const node = (typeof getClusterNodes === 'function' ? getClusterNodes(wdCurrentCluster) : [])
+        .find(n => (n.hostname || n.name) === hostname);
+    if (!node) { showToast(`Node ${hostname} isn't in this cluster`, 'error'); return; }
+    if (!await showConfirm(`Detach ${name} from ${target}?`)) return;
+    try {
+        const resp = await fetch(nodeApiUrl(node.id, '/api/storage/wolfdisk/volumes/' + encodeURIComponent(name) + '/detach'), {
+            method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify({ kind, target }),
+        });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || 'Detach failed');
+        showToast(data.message || 'Detached', 'success');
+        loadWolfDiskVolumes();
+    } catch (e) {
+        showToast(e.message, 'error');
+    }
+}
+
 // ─── Dedicate a disk to WolfDisk (klasSponsor 2026-06) ───
 // Pick an online node + an unused whole disk; the backend wipes it, migrates this
 // node's WolfDisk data onto it, mounts it at the data_dir (+ fstab), and restarts
@@ -52856,6 +58429,7 @@ async function loadWolfDiskCluster() {
     try { await fetchNodes(); } catch (_) {}
     // Live sync-health card (independent of the heavier node scan below).
     loadWolfDiskSyncHealth();
+    loadWolfDiskVolumes();
 
     // Fetch latest WolfDisk version from GitHub (in parallel with node scans)
     if (!wdLatestVersion) {
@@ -53890,6 +59464,16 @@ async function loadSystemLogs() {
     }
 }
 
+// Whole matching journal (not just the lines shown), streamed to disk.
+function downloadSystemLogs() {
+    const params = new URLSearchParams({ download: '1' });
+    const search = document.getElementById('syslog-search').value.trim();
+    const unit = document.getElementById('syslog-unit').value;
+    if (search) params.set('search', search);
+    if (unit) params.set('unit', unit);
+    window.open(apiUrl(`/api/system/logs?${params}`), '_blank');
+}
+
 function copySystemLogs() {
     const viewer = document.getElementById('syslog-viewer');
     const text = viewer.innerText;
@@ -54109,6 +59693,8 @@ function renderStatusPages(pages) {
                     </div>
                     <div style="font-size:12px; color:var(--text-muted); margin-top:6px; margin-left:18px;">
                         <a href="${pageUrl}" target="_blank" style="color:var(--accent-light);">${pageUrl}</a>
+                        &middot; <a href="${pageUrl}/status.json" target="_blank" style="color:var(--text-muted);">JSON</a>
+                        ${p.page.unlisted ? '<span style="font-size:10px; padding:2px 8px; background:rgba(107,114,128,0.2); border-radius:4px; color:#9ca3af; margin-left:6px;">Unlisted</span>' : ''}
                     </div>
                 </div>
                 <div style="text-align:right;">
@@ -54229,7 +59815,8 @@ async function showMonitorDetails(monitorId) {
     let checkDetails = '';
     const c = mon.check || {};
     if (c.type === 'http') {
-        checkDetails = `URL: <code>${escapeHtml(c.url)}</code><br>Expected: ${c.expected_status || 200}`;
+        checkDetails = `URL: <code>${escapeHtml(c.url)}</code><br>Expected: ${c.expected_status || 200}`
+            + (c.keyword ? `<br>Keyword: <code>${escapeHtml(c.keyword)}</code>` : '');
     } else if (c.type === 'tcp') {
         checkDetails = `Host: <code>${escapeHtml(c.host)}:${c.port}</code>`;
     } else if (c.type === 'ping') {
@@ -54253,6 +59840,18 @@ async function showMonitorDetails(monitorId) {
         </div>`;
     }).join('') || '<div style="color:var(--text-muted); padding:12px;">No recent checks</div>';
 
+    const locations = Object.values(m.locations || {}).filter(l => l.location);
+    const locationRows = locations.length > 1 ? `
+                <div style="margin-bottom:20px;">
+                    <div style="font-size:12px; color:var(--text-muted); margin-bottom:8px;">By Location</div>
+                    <div style="background:var(--bg-tertiary); padding:12px; border-radius:8px; font-size:13px;">
+                        ${locations.map(l => `<div style="display:flex; justify-content:space-between; padding:4px 0;">
+                            <span><span style="color:${l.success ? colors.up : colors.down};">●</span> ${escapeHtml(l.location)}</span>
+                            <span style="font-size:11px; color:var(--text-muted);">${l.success ? l.latency_ms + 'ms' : escapeHtml(l.error || 'down')}</span>
+                        </div>`).join('')}
+                    </div>
+                </div>` : '';
+
     // CSS exposes `.modal-overlay` (fixed-position centering layer)
     // and `.modal` (inner card). Earlier this code set `class="modal
     // active"` on the outer div and `class="modal-content"` on the
@@ -54294,8 +59893,14 @@ async function showMonitorDetails(monitorId) {
                     <div style="background:var(--bg-tertiary); padding:12px; border-radius:8px; font-size:13px;">
                         <span style="text-transform:uppercase; font-size:10px; padding:2px 6px; background:var(--primary-color); color:#fff; border-radius:4px; margin-right:8px;">${c.type}</span>
                         ${checkDetails}
+                        ${(mon.nodes || []).length ? `<br>Runs from: ${mon.nodes.map(id => escapeHtml(allNodes.find(n => n.id === id)?.hostname || id)).join(', ')}` : ''}
                     </div>
                 </div>
+                ${locationRows}
+                <div style="margin-bottom:20px;">
+                    <div style="font-size:12px; color:var(--text-muted); margin-bottom:8px;">Response Time</div>
+                    <div id="sp-monitor-latency-chart" style="background:var(--bg-tertiary); padding:12px; border-radius:8px; font-size:12px; color:var(--text-muted);">Loading…</div>
+                </div>
                 <div>
                     <div style="font-size:12px; color:var(--text-muted); margin-bottom:8px;">Recent Checks</div>
                     <div style="background:var(--bg-tertiary); padding:12px; border-radius:8px; max-height:200px; overflow-y:auto;">
@@ -54306,6 +59911,42 @@ async function showMonitorDetails(monitorId) {
         </div>
     `;
     document.body.appendChild(modal);
+    loadMonitorLatencyChart(monitorId);
+}
+
+// Response-time line per location over the retained history (up to
+// 24 h); failed checks are red ticks along the bottom.
+async function loadMonitorLatencyChart(monitorId) {
+    const el = document.getElementById('sp-monitor-latency-chart');
+    let results = [];
+    try {
+        const res = await fetch(spUrl(`statuspage/monitors/${encodeURIComponent(monitorId)}/history`));
+        if (res.ok) results = (await res.json()).results || [];
+    } catch (e) { /* fall through to the empty state */ }
+    if (!el) return;
+    if (results.length < 2) {
+        el.textContent = 'Not enough checks yet';
+        return;
+    }
+    const W = 560, H = 120;
+    const t0 = results[0].timestamp, t1 = results[results.length - 1].timestamp || t0 + 1;
+    const maxMs = Math.max(1, ...results.filter(r => r.success).map(r => r.latency_ms));
+    const x = t => ((t - t0) / Math.max(1, t1 - t0)) * W;
+    const y = ms => H - 4 - (ms / maxMs) * (H - 12);
+    const palette = ['#3b82f6', '#a855f7', '#14b8a6', '#f97316', '#ec4899'];
+    const byLocation = {};
+    results.forEach(r => (byLocation[r.location || ''] ||= []).push(r));
+    const lines = Object.entries(byLocation).map(([loc, rs], i) => {
+        const pts = rs.filter(r => r.success).map(r => `${x(r.timestamp).toFixed(1)},${y(r.latency_ms).toFixed(1)}`).join(' ');
+        return { loc, color: palette[i % palette.length], svg: `<polyline fill="none" stroke="${palette[i % palette.length]}" stroke-width="1.5" points="${pts}"/>` };
+    });
+    const failures = results.filter(r => !r.success)
+        .map(r => `<line x1="${x(r.timestamp).toFixed(1)}" x2="${x(r.timestamp).toFixed(1)}" y1="${H - 6}" y2="${H}" stroke="#ef4444" stroke-width="2"/>`).join('');
+    const legend = lines.length > 1
+        ? `<div style="display:flex; gap:12px; margin-top:6px;">${lines.map(l => `<span><span style="color:${l.color};">■</span> ${escapeHtml(l.loc || 'this node')}</span>`).join('')}</div>`
+        : '';
+    el.innerHTML = `<div style="display:flex; justify-content:space-between; margin-bottom:4px;"><span>${new Date(t0 * 1000).toLocaleString()}</span><span>max ${maxMs}ms</span></div>
+        <svg viewBox="0 0 ${W} ${H}" preserveAspectRatio="none" style="width:100%; height:${H}px; display:block;">${lines.map(l => l.svg).join('')}${failures}</svg>${legend}`;
 }
 
 // ─── Monitor Form ───
@@ -54320,6 +59961,12 @@ function showMonitorForm(existing) {
     document.getElementById('sp-mon-timeout').value = existing?.timeout_secs || 10;
     document.getElementById('sp-mon-enabled').checked = existing?.enabled !== false;
     document.getElementById('sp-mon-type').value = existing?.check?.type || 'http';
+    const pinned = existing?.nodes || [];
+    document.getElementById('sp-mon-nodes').innerHTML = allNodes
+        .filter(n => (n.cluster_name || 'WolfStack') === spCurrentCluster)
+        .map(n => `<label style="display:flex; align-items:center; gap:6px; cursor:pointer;">
+            <input type="checkbox" class="sp-mon-node" value="${escapeHtml(n.id)}"${pinned.includes(n.id) ? ' checked' : ''}> ${escapeHtml(n.hostname)}
+        </label>`).join('') || '<span style="color:var(--text-muted);">No nodes</span>';
     updateMonitorFormFields(existing?.check);
     form.scrollIntoView({ behavior: 'smooth' });
 }
@@ -54338,7 +59985,8 @@ async function updateMonitorFormFields(existingCheck) {
         el.innerHTML = `<div style="display:grid; grid-template-columns:3fr 1fr; gap:16px; margin-bottom:16px;">
             <div class="form-group"><label>URL</label><input type="text" id="sp-mon-url" class="form-control" placeholder="https://example.com" value="${escapeHtml(c.url || '')}"></div>
             <div class="form-group"><label>Expected Status</label><input type="number" id="sp-mon-status" class="form-control" value="${c.expected_status || 200}"></div>
-        </div>`;
+        </div>
+        <div class="form-group" style="margin-bottom:16px;"><label>Keyword (optional)</label><input type="text" id="sp-mon-keyword" class="form-control" placeholder="Text the page must contain" value="${escapeHtml(c.keyword || '')}"></div>`;
     } else if (type === 'tcp') {
         el.innerHTML = `<div style="display:grid; grid-template-columns:3fr 1fr; gap:16px; margin-bottom:16px;">
             <div class="form-group"><label>Host</label><input type="text" id="sp-mon-host" class="form-control" placeholder="192.168.1.10" value="${escapeHtml(c.host || '')}"></div>
@@ -54361,7 +60009,12 @@ async function saveMonitor() {
     const type = document.getElementById('sp-mon-type').value;
     let check;
     if (type === 'http') {
-        check = { type: 'http', url: document.getElementById('sp-mon-url').value, expected_status: parseInt(document.getElementById('sp-mon-status').value) || 200 };
+        check = {
+            type: 'http',
+            url: document.getElementById('sp-mon-url').value,
+            expected_status: parseInt(document.getElementById('sp-mon-status').value) || 200,
+            keyword: document.getElementById('sp-mon-keyword').value.trim(),
+        };
     } else if (type === 'tcp') {
         check = { type: 'tcp', host: document.getElementById('sp-mon-host').value, port: parseInt(document.getElementById('sp-mon-port').value) || 80 };
     } else if (type === 'ping') {
@@ -54397,6 +60050,7 @@ async function saveMonitor() {
         interval_secs: parseInt(document.getElementById('sp-mon-interval').value) || 60,
         timeout_secs: parseInt(document.getElementById('sp-mon-timeout').value) || 10,
         enabled: document.getElementById('sp-mon-enabled').checked,
+        nodes: [...document.querySelectorAll('.sp-mon-node:checked')].map(cb => cb.value),
         cluster: spCurrentCluster,
     };
     try {
@@ -54435,6 +60089,10 @@ function showStatusPageForm(existing) {
     document.getElementById('sp-page-footer').value = existing?.footer_text || '';
     document.getElementById('sp-page-enabled').checked = existing?.enabled !== false;
     document.getElementById('sp-page-theme').value = existing?.theme || 'dark';
+    document.getElementById('sp-page-description').value = existing?.description || '';
+    document.getElementById('sp-page-accent-on').checked = !!existing?.accent_color;
+    document.getElementById('sp-page-accent').value = existing?.accent_color || '#3b82f6';
+    document.getElementById('sp-page-unlisted').checked = !!existing?.unlisted;
 
     const container = document.getElementById('sp-page-services');
     container.innerHTML = '';
@@ -54497,6 +60155,9 @@ async function saveStatusPage() {
         logo_url: document.getElementById('sp-page-logo').value || null,
         footer_text: document.getElementById('sp-page-footer').value || null,
         theme: document.getElementById('sp-page-theme').value || null,
+        description: document.getElementById('sp-page-description').value.trim() || null,
+        accent_color: document.getElementById('sp-page-accent-on').checked ? document.getElementById('sp-page-accent').value : null,
+        unlisted: document.getElementById('sp-page-unlisted').checked,
         enabled: document.getElementById('sp-page-enabled').checked,
         cluster: spCurrentCluster,
         monitor_ids,
@@ -62061,6 +67722,7 @@ async function loadComposeStacks() {
                         ${lifecycle}
                         ${iconBtn(`composeAction('${nm}', 'pull')`, 'updates', 'Pull images', { tip: 'Pull the latest images (reports which were updated). Click Up afterwards to apply them.' })}
                         ${iconBtn(`openComposeEditor('${nm}')`, 'edit', 'Edit')}
+                        ${iconBtn(`openEnvStore('${nm}', 'stack')`, 'lock', 'Stored env & secrets', { tip: 'Encrypted variables for this stack — reference them in the YAML as ${NAME}' })}
                         ${iconBtn(`showComposeLogs('${nm}')`, 'logs', 'Logs')}
                         ${iconBtn(`deleteComposeStack('${nm}')`, 'trash', 'Delete', { danger: true })}
                     </div>
@@ -0,0 +1,48 @@
+// Written by Paul Clevett
+// (C)Copyright Wolf Software Systems Ltd
+// https://wolf.uk.com
+//
+// Echo the session's CSRF token on every state-changing request to this
+// origin. The server issues it at login in the JS-readable wolfstack_csrf
+// cookie and refuses a cookie-authenticated POST/PUT/PATCH/DELETE without
+// it in X-CSRF-Token (src/auth/csrf.rs). Load this before any script
+// that calls fetch() or XMLHttpRequest so every caller is covered.
+(function () {
+    const HEADER = 'X-CSRF-Token';
+    const SAFE = ['GET', 'HEAD', 'OPTIONS', 'TRACE'];
+
+    function token() {
+        const m = document.cookie.match(/(?:^|;\s*)wolfstack_csrf=([^;]*)/);
+        return m ? decodeURIComponent(m[1]) : '';
+    }
+
+    function sameOrigin(url) {
+        try { return new URL(url, location.href).origin === location.origin; }
+        catch (e) { return false; }
+    }
+
+    const origFetch = window.fetch;
+    window.fetch = function (input, init) {
+        const req = input instanceof Request ? input : null;
+        const method = ((init && init.method) || (req && req.method) || 'GET').toUpperCase();
+        const t = token();
+        if (t && !SAFE.includes(method) && sameOrigin(req ? req.url : String(input))) {
+            const headers = new Headers((init && init.headers) || (req && req.headers) || undefined);
+            if (!headers.has(HEADER)) headers.set(HEADER, t);
+            init = Object.assign({}, init, { headers });
+        }
+        return origFetch.call(this, input, init);
+    };
+
+    const origOpen = XMLHttpRequest.prototype.open;
+    const origSend = XMLHttpRequest.prototype.send;
+    XMLHttpRequest.prototype.open = function (method, url) {
+        this._wsCsrf = !SAFE.includes(String(method).toUpperCase()) && sameOrigin(url);
+        return origOpen.apply(this, arguments);
+    };
+    XMLHttpRequest.prototype.send = function () {
+        const t = this._wsCsrf && token();
+        if (t) this.setRequestHeader(HEADER, t);
+        return origSend.apply(this, arguments);
+    };
+})();
@@ -203,6 +203,8 @@
                             <li><kbd>Alt</kbd>+<kbd>-</kbd> Split horizontal</li>
                             <li><kbd>Alt</kbd>+<kbd>W</kbd> Close pane</li>
                             <li><kbd>Alt</kbd>+<kbd>R</kbd> Rename tab</li>
+                            <li><kbd>Alt</kbd>+<kbd>S</kbd> Saved snippets</li>
+                            <li><kbd>Alt</kbd>+<kbd>H</kbd> Command history</li>
                         </ul>
                         <div class="fc-help-foot">Shortcuts fire while the terminal has focus.</div>
                     </div>
@@ -650,9 +652,13 @@
             tools.className = 'fc-tabstrip-tools';
             tools.innerHTML = `
                 <button class="fc-tool-btn" type="button" data-act="split-h" title="Split top / bottom (Alt+-)" aria-label="Split horizontally">Split ▤</button>
-                <button class="fc-tool-btn" type="button" data-act="split-v" title="Split left / right (Alt+\\)" aria-label="Split vertically">Split ▥</button>`;
+                <button class="fc-tool-btn" type="button" data-act="split-v" title="Split left / right (Alt+\\)" aria-label="Split vertically">Split ▥</button>
+                <button class="fc-tool-btn" type="button" data-act="snippets" title="Saved command snippets (Alt+S)" aria-label="Saved command snippets">📋 Snippets</button>
+                <button class="fc-tool-btn" type="button" data-act="history" title="Command history (Alt+H)" aria-label="Command history">🕘 History</button>`;
             tools.querySelector('[data-act="split-h"]').addEventListener('click', () => split('h'));
             tools.querySelector('[data-act="split-v"]').addEventListener('click', () => split('v'));
+            tools.querySelector('[data-act="snippets"]').addEventListener('click', () => openSnippets());
+            tools.querySelector('[data-act="history"]').addEventListener('click', openHistory);
             strip.appendChild(tools);
 
             renderStatusWindows();
@@ -852,11 +858,203 @@
             nodeSel.focus();
         }
 
+        // ── snippets & history ──
+        // Send text into the active pane, or every pane of the active tab —
+        // the latter is how a maintenance command goes to several containers
+        // opened side by side.
+        function sendToPanes(text, allPanes) {
+            const win = activeWindow();
+            if (!win) { toast('Open a terminal first', 'error'); return; }
+            const panes = allPanes ? win.panes : win.panes.filter(p => p.id === win.activePaneId);
+            let sent = 0;
+            panes.forEach(p => {
+                if (p.ws && p.ws.readyState === WebSocket.OPEN) { p.ws.send(text); sent++; }
+            });
+            if (!sent) toast('No connected terminal to send to', 'error');
+            else setActivePane(win.activePaneId);
+        }
+
+        function openSidePanel(title, bodyHtml) {
+            const old = document.querySelector('.wtc-side-overlay');
+            if (old) old.remove();
+            const overlay = document.createElement('div');
+            overlay.className = 'modal-overlay active wtc-side-overlay';
+            overlay.style.cssText = 'display:flex; z-index:10060;';
+            overlay.innerHTML = `
+                <div role="dialog" aria-label="${escAttr(title)}" aria-modal="true"
+                     style="background:var(--bg-card); border:1px solid var(--border); border-radius:14px; padding:20px; max-width:640px; width:94%; max-height:80vh; display:flex; flex-direction:column; box-shadow:0 20px 60px rgba(0,0,0,0.5);">
+                    <div style="display:flex; align-items:center; margin-bottom:12px;">
+                        <h3 style="color:var(--text-primary); font-size:16px; font-weight:700; margin:0; flex:1;">${esc(title)}</h3>
+                        <button class="btn btn-sm" type="button" data-act="close" aria-label="Close">✕</button>
+                    </div>
+                    ${bodyHtml}
+                </div>`;
+            document.body.appendChild(overlay);
+            const close = () => {
+                document.removeEventListener('keydown', escHandler, true);
+                if (overlay.parentElement) overlay.remove();
+            };
+            const escHandler = (e) => { if (e.key === 'Escape') { e.stopPropagation(); close(); } };
+            document.addEventListener('keydown', escHandler, true);
+            overlay.querySelector('[data-act="close"]').addEventListener('click', close);
+            overlay.addEventListener('mousedown', (e) => { if (e.target === overlay) close(); });
+            return { overlay, close };
+        }
+
+        const inputCss = 'width:100%; padding:8px 10px; border-radius:8px; border:1px solid var(--border); background:var(--bg-secondary); color:var(--text-primary);';
+
+        function openSnippets(prefill) {
+            const { overlay, close } = openSidePanel('Saved snippets', `
+                <div style="display:flex; gap:8px; margin-bottom:8px;">
+                    <input type="search" id="wtc-snip-filter" placeholder="Filter snippets…" style="${inputCss}">
+                    <label style="display:flex; gap:4px; align-items:center; white-space:nowrap; font-size:12px; color:var(--text-secondary);">
+                        <input type="checkbox" id="wtc-snip-all"> All panes in tab
+                    </label>
+                </div>
+                <div id="wtc-snip-list" style="overflow:auto; flex:1; min-height:80px; margin-bottom:12px;">Loading…</div>
+                <details id="wtc-snip-form-wrap" ${prefill ? 'open' : ''} style="border-top:1px solid var(--border); padding-top:10px;">
+                    <summary style="cursor:pointer; font-size:13px; color:var(--text-secondary);">➕ New snippet</summary>
+                    <input type="hidden" id="wtc-snip-id">
+                    <input type="text" id="wtc-snip-name" placeholder="Name (e.g. Update packages)" maxlength="100" style="${inputCss} margin:8px 0;">
+                    <textarea id="wtc-snip-cmd" rows="3" placeholder="Command — multiple lines run one after another" style="${inputCss} font-family:monospace; resize:vertical;">${esc(prefill || '')}</textarea>
+                    <div style="display:flex; justify-content:flex-end; margin-top:8px;">
+                        <button class="btn btn-primary btn-sm" type="button" id="wtc-snip-save">Save snippet</button>
+                    </div>
+                </details>`);
+            let snippets = [];
+            const listEl = overlay.querySelector('#wtc-snip-list');
+            const filterEl = overlay.querySelector('#wtc-snip-filter');
+            const render = () => {
+                const f = filterEl.value.toLowerCase();
+                const shown = snippets.filter(s => !f || s.name.toLowerCase().includes(f) || s.command.toLowerCase().includes(f));
+                if (!shown.length) {
+                    listEl.innerHTML = `<div style="color:var(--text-muted); font-size:13px; padding:12px 0;">${snippets.length ? 'No snippets match.' : 'No snippets yet — save a command below or from History.'}</div>`;
+                    return;
+                }
+                listEl.innerHTML = shown.map(s => `
+                    <div style="display:flex; gap:8px; align-items:center; padding:8px 0; border-bottom:1px solid var(--border);">
+                        <div style="flex:1; min-width:0;">
+                            <div style="font-size:13px; font-weight:600; color:var(--text-primary);">${esc(s.name)}</div>
+                            <code style="display:block; font-size:12px; color:var(--text-secondary); white-space:pre-wrap; word-break:break-all;">${esc(s.command)}</code>
+                        </div>
+                        <button class="btn btn-primary btn-sm" type="button" data-run="${escAttr(s.id)}" title="Run in the terminal">▶ Run</button>
+                        <button class="btn btn-sm" type="button" data-edit="${escAttr(s.id)}" title="Edit">✎</button>
+                        <button class="btn btn-sm" type="button" data-del="${escAttr(s.id)}" title="Delete">🗑</button>
+                    </div>`).join('');
+            };
+            const load = () => fetch('/api/console/snippets').then(r => r.ok ? r.json() : [])
+                .then(list => { snippets = Array.isArray(list) ? list : []; render(); })
+                .catch(() => { listEl.textContent = 'Failed to load snippets'; });
+            filterEl.addEventListener('input', render);
+            listEl.addEventListener('click', (e) => {
+                const btn = e.target.closest('button');
+                if (!btn) return;
+                const find = (id) => snippets.find(s => s.id === id);
+                if (btn.dataset.run) {
+                    const s = find(btn.dataset.run);
+                    if (!s) return;
+                    const all = overlay.querySelector('#wtc-snip-all').checked;
+                    close();
+                    sendToPanes(s.command.replace(/\r?\n/g, '\r') + '\r', all);
+                } else if (btn.dataset.edit) {
+                    const s = find(btn.dataset.edit);
+                    if (!s) return;
+                    overlay.querySelector('#wtc-snip-form-wrap').open = true;
+                    overlay.querySelector('#wtc-snip-id').value = s.id;
+                    overlay.querySelector('#wtc-snip-name').value = s.name;
+                    overlay.querySelector('#wtc-snip-cmd').value = s.command;
+                } else if (btn.dataset.del) {
+                    fetch('/api/console/snippets/' + encodeURIComponent(btn.dataset.del), { method: 'DELETE' })
+                        .then(r => r.json().then(d => { if (!r.ok) throw new Error(d.error || 'Delete failed'); }))
+                        .then(load)
+                        .catch(err => toast(err.message, 'error'));
+                }
+            });
+            overlay.querySelector('#wtc-snip-save').addEventListener('click', () => {
+                const body = {
+                    id: overlay.querySelector('#wtc-snip-id').value,
+                    name: overlay.querySelector('#wtc-snip-name').value,
+                    command: overlay.querySelector('#wtc-snip-cmd').value,
+                };
+                fetch('/api/console/snippets', {
+                    method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body),
+                })
+                    .then(r => r.json().then(d => { if (!r.ok) throw new Error(d.error || 'Save failed'); }))
+                    .then(() => {
+                        ['#wtc-snip-id', '#wtc-snip-name', '#wtc-snip-cmd'].forEach(sel => { overlay.querySelector(sel).value = ''; });
+                        toast('Snippet saved', 'success');
+                        load();
+                    })
+                    .catch(err => toast(err.message, 'error'));
+            });
+            load();
+            (prefill ? overlay.querySelector('#wtc-snip-name') : filterEl).focus();
+        }
+
+        function openHistory() {
+            const win = activeWindow();
+            const active = win && (win.panes.find(p => p.id === win.activePaneId) || win.panes[0]);
+            const { overlay, close } = openSidePanel('Command history', `
+                <div style="display:flex; gap:8px; margin-bottom:8px;">
+                    <input type="search" id="wtc-hist-q" placeholder="Search commands…" style="${inputCss}">
+                    <label style="display:flex; gap:4px; align-items:center; white-space:nowrap; font-size:12px; color:var(--text-secondary);">
+                        <input type="checkbox" id="wtc-hist-here" ${active && active.target ? 'checked' : ''}> This target only
+                    </label>
+                </div>
+                <div id="wtc-hist-list" style="overflow:auto; flex:1; min-height:80px;">Loading…</div>
+                <small style="color:var(--text-muted); margin-top:8px;">Click a command to type it into the terminal (without running it). Lines starting with a space are never recorded.</small>`);
+            let entries = [];
+            const listEl = overlay.querySelector('#wtc-hist-list');
+            const qEl = overlay.querySelector('#wtc-hist-q');
+            const hereEl = overlay.querySelector('#wtc-hist-here');
+            let timer = null;
+            const load = () => {
+                const params = new URLSearchParams({ q: qEl.value, limit: '300' });
+                if (hereEl.checked && active) params.set('target', active.target || (active.node && active.node.hostname) || '');
+                fetch('/api/console/history?' + params.toString()).then(r => r.ok ? r.json() : [])
+                    .then(list => {
+                        entries = Array.isArray(list) ? list : [];
+                        if (!entries.length) {
+                            listEl.innerHTML = '<div style="color:var(--text-muted); font-size:13px; padding:12px 0;">No commands found.</div>';
+                            return;
+                        }
+                        listEl.innerHTML = entries.map((h, i) => `
+                            <div style="display:flex; gap:8px; align-items:center; padding:6px 0; border-bottom:1px solid var(--border);">
+                                <div style="flex:1; min-width:0; cursor:pointer;" data-type="${i}" title="Type into the terminal">
+                                    <code style="display:block; font-size:12px; color:var(--text-primary); white-space:pre-wrap; word-break:break-all;">${esc(h.command)}</code>
+                                    <div style="font-size:11px; color:var(--text-muted);">${esc(h.target_type)}:${esc(h.target)} @ ${esc(h.node)} · ${esc(new Date(h.timestamp).toLocaleString())}${h.approximate ? ' · <span title="Edited with cursor keys or tab completion — may differ from what ran">≈ approximate</span>' : ''}</div>
+                                </div>
+                                <button class="btn btn-sm" type="button" data-save="${i}" title="Save as snippet">⭐</button>
+                            </div>`).join('');
+                    })
+                    .catch(() => { listEl.textContent = 'Failed to load history'; });
+            };
+            qEl.addEventListener('input', () => { clearTimeout(timer); timer = setTimeout(load, 250); });
+            hereEl.addEventListener('change', load);
+            listEl.addEventListener('click', (e) => {
+                const typeEl = e.target.closest('[data-type]');
+                const saveBtn = e.target.closest('[data-save]');
+                if (saveBtn) {
+                    const h = entries[parseInt(saveBtn.dataset.save, 10)];
+                    close();
+                    if (h) openSnippets(h.command);
+                } else if (typeEl) {
+                    const h = entries[parseInt(typeEl.dataset.type, 10)];
+                    close();
+                    if (h) sendToPanes(h.command, false);
+                }
+            });
+            load();
+            qEl.focus();
+        }
+
         // ── keyboard ──
         function onKeydown(e) {
             if (!e.altKey || e.ctrlKey || e.metaKey) return;
             const k = e.key;
             if (k === 't' || k === 'T') { e.preventDefault(); openPicker(); return; }
+            if (k === 's' || k === 'S') { e.preventDefault(); openSnippets(); return; }
+            if (k === 'h' || k === 'H') { e.preventDefault(); openHistory(); return; }
             if (k === 'w' || k === 'W') { e.preventDefault(); const w = activeWindow(); if (w) closePane(w.activePaneId); return; }
             if (k === 'r' || k === 'R') { e.preventDefault(); const w = activeWindow(); if (w) renameWindow(w.id); return; }
             if (k === '\\') { e.preventDefault(); split('v'); return; }
@@ -9393,6 +9393,7 @@
                   '<h4 style="margin:20px 0 8px;font-size:13px;text-transform:uppercase;letter-spacing:0.5px;color:var(--text-muted);">Backends</h4>' +
                   '<div id="hp-upstreams">' + hpUpstreamsHtml(d.upstreams || []) + '</div>' +
                   '<button class="btn btn-sm" onclick="hpAddUpstream()" style="margin-top:6px;">+ Backend</button>' +
+                  '<small style="display:block;color:var(--text-muted);margin-top:4px;">URL · weight · max conns · max fails · fail timeout (s). After <em>max fails</em> failed requests within the timeout a backend is rested for that long (0 = proxy default). Containers created with &ldquo;Register with WolfProxy&rdquo; join here automatically and leave when destroyed.</small>' +
                   '<div class="form-group" style="margin-top:10px;"><label>Load balancing</label>' +
                   '<select id="hp-lb" class="form-control" style="max-width:240px;">' +
                     '<option value="round_robin"' + (d.lb_strategy === 'round_robin' ? ' selected' : '') + '>Round robin</option>' +
@@ -9430,10 +9431,19 @@
         return ups.map(hpUpstreamRowHtml).join('');
     }
     function hpUpstreamRowHtml(u) {
-        return '<div data-hp-upstream style="display:grid;grid-template-columns:1fr 80px 100px auto;gap:8px;margin-bottom:6px;align-items:center;">' +
-            '<input data-hp-up-url class="form-control" value="' + escHtml(u.url || '') + '" placeholder="http://10.0.0.5:3000">' +
+        // Auto-registered backends carry their container in `source`; keep
+        // it on the row so saving the form doesn't turn them into manual ones.
+        const src = u.source ? ' data-hp-up-source="' + escHtml(JSON.stringify(u.source)) + '"' : '';
+        const url = u.source && !u.url ? '' : (u.url || '');
+        return '<div data-hp-upstream' + src + ' style="display:grid;grid-template-columns:1fr 70px 80px 80px 90px auto;gap:8px;margin-bottom:6px;align-items:center;">' +
+            '<div style="display:flex;gap:6px;align-items:center;">' +
+              '<input data-hp-up-url class="form-control" value="' + escHtml(url) + '" placeholder="' + (u.source ? 'waiting for container address' : 'http://10.0.0.5:3000') + '"' + (u.source ? ' readonly' : '') + '>' +
+              (u.source ? '<span class="badge" title="Registered automatically — follows the container">' + escHtml(u.source.workload_kind + ':' + u.source.workload_ref) + '</span>' : '') +
+            '</div>' +
             '<input data-hp-up-weight type="number" min="1" class="form-control" value="' + (u.weight || 1) + '" title="Weight">' +
             '<input data-hp-up-maxconns type="number" min="0" class="form-control" value="' + (u.max_conns || 0) + '" title="Max conns">' +
+            '<input data-hp-up-maxfails type="number" min="0" class="form-control" value="' + (u.max_fails || 0) + '" title="Max fails">' +
+            '<input data-hp-up-failtimeout type="number" min="0" class="form-control" value="' + (u.fail_timeout_s || 0) + '" title="Fail timeout (seconds)">' +
             '<button class="btn btn-sm" onclick="this.parentElement.remove()" style="background:var(--danger);color:#fff;border:1px solid var(--danger);">−</button>' +
             '</div>';
     }
@@ -9543,12 +9553,19 @@
                 const u = r.querySelector('[data-hp-up-url]');
                 const w = r.querySelector('[data-hp-up-weight]');
                 const m = r.querySelector('[data-hp-up-maxconns]');
-                if (u && u.value.trim()) {
-                    d.upstreams.push({
+                const f = r.querySelector('[data-hp-up-maxfails]');
+                const t = r.querySelector('[data-hp-up-failtimeout]');
+                const src = r.getAttribute('data-hp-up-source');
+                if (u && (u.value.trim() || src)) {
+                    const up = {
                         url: u.value.trim(),
                         weight: parseInt((w && w.value) || '1', 10) || 1,
                         max_conns: parseInt((m && m.value) || '0', 10) || 0,
-                    });
+                        max_fails: parseInt((f && f.value) || '0', 10) || 0,
+                        fail_timeout_s: parseInt((t && t.value) || '0', 10) || 0,
+                    };
+                    if (src) up.source = JSON.parse(src);
+                    d.upstreams.push(up);
                 }
             });
         }